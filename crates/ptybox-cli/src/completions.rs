@@ -0,0 +1,296 @@
+//! `ptybox completions` and the hidden `ptybox __complete` helper.
+//!
+//! The scripts clap generates complete subcommands, flags, and fixed value
+//! lists. Values that depend on the machine are left to `__complete`, which
+//! the scripts call with a value kind and the word being completed:
+//! - `--scenario` / `--policy`: directories and `.json`/`.yaml`/`.yml` files
+//! - `--artifacts`: directories
+//! - `--normalize` / `--compare`: filter and class names (`--compare` accepts
+//!   comma-separated lists)
+//! - the session ID of `keys`, `type`, `wait`, `screen`, and `close`: live
+//!   sessions
+
+use clap::{Command, Parser, ValueEnum};
+use clap_complete::{generate, Shell};
+use miette::Result;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+/// Arguments of `ptybox __complete`.
+///
+/// Parsed apart from the main CLI so the helper stays out of `--help` and
+/// the generated scripts.
+#[derive(Debug, Parser)]
+#[command(name = "ptybox __complete")]
+struct CompleteArgs {
+    #[arg(value_enum)]
+    kind: CompleteKind,
+    /// Word being completed
+    #[arg(default_value = "", allow_hyphen_values = true)]
+    current: String,
+}
+
+/// Value kinds `ptybox __complete` can list candidates for.
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum CompleteKind {
+    Scenario,
+    Policy,
+    Artifacts,
+    Normalize,
+    Compare,
+    Session,
+}
+
+/// Flags whose values are completed by `__complete` (the value kind matches
+/// the flag name).
+const VALUE_FLAGS: [&str; 5] = ["scenario", "policy", "artifacts", "normalize", "compare"];
+
+/// Flags among [`VALUE_FLAGS`] that take paths.
+const PATH_FLAGS: [&str; 3] = ["scenario", "policy", "artifacts"];
+
+/// Subcommands whose first positional argument is a session ID.
+const SESSION_COMMANDS: [&str; 5] = ["keys", "type", "wait", "screen", "close"];
+
+/// Handle the completions command.
+#[allow(clippy::unnecessary_wraps)] // Consistent with other command handlers
+pub fn cmd_completions(mut cmd: Command, shell: Shell) -> Result<()> {
+    let name = cmd.get_name().to_string();
+    let mut script = Vec::new();
+    generate(shell, &mut cmd, name, &mut script);
+    let script = String::from_utf8_lossy(&script);
+    let script = match shell {
+        Shell::Bash => format!("{script}\n{}", bash_hook()),
+        Shell::Zsh => zsh_with_hook(&script),
+        Shell::Fish => fish_with_hook(&script),
+        _ => script.into_owned(),
+    };
+    print!("{script}");
+    Ok(())
+}
+
+/// Handle `ptybox __complete <kind> [current]` when it is the invoked
+/// command: print candidates for `current`, one per line.
+pub fn run_complete_helper() -> Option<Result<()>> {
+    if std::env::args_os().nth(1)? != "__complete" {
+        return None;
+    }
+    let args = CompleteArgs::parse_from(std::env::args_os().skip(1));
+    for candidate in candidates(args.kind, &args.current) {
+        println!("{candidate}");
+    }
+    Some(Ok(()))
+}
+
+fn candidates(kind: CompleteKind, current: &str) -> Vec<String> {
+    match kind {
+        CompleteKind::Scenario | CompleteKind::Policy => path_candidates(current, false),
+        CompleteKind::Artifacts => path_candidates(current, true),
+        CompleteKind::Normalize => value_candidates::<crate::NormalizeFilterArg>(current),
+        CompleteKind::Compare => list_candidates::<crate::CompareArg>(current),
+        CompleteKind::Session => session_candidates(current),
+    }
+}
+
+/// Directories (with a trailing `/`) and, unless `dirs_only`, scenario or
+/// policy files whose path starts with `current`. Hidden entries are only
+/// offered once `current` names them with a leading `.`.
+fn path_candidates(current: &str, dirs_only: bool) -> Vec<String> {
+    let (dir_part, file_prefix) = match current.rfind('/') {
+        Some(idx) => current.split_at(idx + 1),
+        None => ("", current),
+    };
+    let dir = if dir_part.is_empty() {
+        Path::new(".")
+    } else {
+        Path::new(dir_part)
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut out: Vec<String> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name().into_string().ok()?;
+            if !name.starts_with(file_prefix)
+                || (name.starts_with('.') && !file_prefix.starts_with('.'))
+            {
+                return None;
+            }
+            let path = entry.path();
+            if path.is_dir() {
+                Some(format!("{dir_part}{name}/"))
+            } else if !dirs_only && is_document(&path) {
+                Some(format!("{dir_part}{name}"))
+            } else {
+                None
+            }
+        })
+        .collect();
+    out.sort();
+    out
+}
+
+fn is_document(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| matches!(ext, "json" | "yaml" | "yml"))
+}
+
+fn value_names<T: ValueEnum + 'static>() -> impl Iterator<Item = String> {
+    T::value_variants()
+        .iter()
+        .filter_map(|value| value.to_possible_value())
+        .map(|value| value.get_name().to_string())
+}
+
+fn value_candidates<T: ValueEnum + 'static>(current: &str) -> Vec<String> {
+    value_names::<T>()
+        .filter(|name| name.starts_with(current))
+        .collect()
+}
+
+/// Candidates for the last item of a comma-separated list, skipping values
+/// already listed.
+fn list_candidates<T: ValueEnum + 'static>(current: &str) -> Vec<String> {
+    let (done, last) = match current.rfind(',') {
+        Some(idx) => current.split_at(idx + 1),
+        None => ("", current),
+    };
+    let chosen: Vec<&str> = done.split(',').collect();
+    value_names::<T>()
+        .filter(|name| name.starts_with(last) && !chosen.contains(&name.as_str()))
+        .map(|name| format!("{done}{name}"))
+        .collect()
+}
+
+fn session_candidates(current: &str) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(crate::session_client::socket_dir()) else {
+        return Vec::new();
+    };
+    let mut out: Vec<String> = entries
+        .filter_map(|entry| crate::session_client::session_id_from_path(&entry.ok()?.path()))
+        .filter(|id| id.starts_with(current) && crate::session_client::is_session_alive(id))
+        .collect();
+    out.sort();
+    out
+}
+
+/// Wrap the generated `_ptybox` so flag values and session IDs come from
+/// `__complete`.
+fn bash_hook() -> String {
+    let mut flag_cases = String::new();
+    for flag in VALUE_FLAGS {
+        let _ = writeln!(flag_cases, "        --{flag}) kind={flag} ;;");
+    }
+    let path_kinds = PATH_FLAGS.join("|");
+    let session_commands = SESSION_COMMANDS.join("|");
+    format!(
+        r#"_ptybox_dynamic() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}" kind="" sub="" i
+    case "${{COMP_WORDS[COMP_CWORD-1]}}" in
+{flag_cases}    esac
+    if [[ -z "${{kind}}" && "${{cur}}" != -* ]]; then
+        for ((i = 1; i < COMP_CWORD; i++)); do
+            case "${{COMP_WORDS[i]}}" in
+                --color) ((i++)) ;;
+                -*) ;;
+                *) sub="${{COMP_WORDS[i]}}"; break ;;
+            esac
+        done
+        case "${{sub}}" in
+            {session_commands})
+                kind=session
+                for ((i = i + 1; i < COMP_CWORD; i++)); do
+                    [[ "${{COMP_WORDS[i]}}" != -* ]] && kind=""
+                done
+                ;;
+        esac
+    fi
+    if [[ -z "${{kind}}" ]]; then
+        _ptybox "$@"
+        return
+    fi
+    local IFS=$'\n'
+    COMPREPLY=($(ptybox __complete "${{kind}}" "${{cur}}" 2>/dev/null))
+    case "${{kind}}" in
+        {path_kinds}|compare) compopt -o nospace 2>/dev/null ;;
+    esac
+}}
+
+if [[ "${{BASH_VERSINFO[0]}}" -eq 4 && "${{BASH_VERSINFO[1]}}" -ge 4 || "${{BASH_VERSINFO[0]}}" -gt 4 ]]; then
+    complete -F _ptybox_dynamic -o nosort -o bashdefault -o default ptybox
+else
+    complete -F _ptybox_dynamic -o bashdefault -o default ptybox
+fi
+"#
+    )
+}
+
+const ZSH_HELPER: &str = r#"(( $+functions[_ptybox__dynamic] )) ||
+_ptybox__dynamic() {
+    local -a candidates
+    candidates=(${(f)"$(ptybox __complete "$1" "$PREFIX" 2>/dev/null)"})
+    compadd -U -- ${candidates:#*/}
+    compadd -U -S '' -- ${(M)candidates:#*/}
+}
+
+"#;
+
+/// Point path flags and session IDs in the generated zsh script at
+/// `_ptybox__dynamic`; fixed value lists stay as generated.
+fn zsh_with_hook(script: &str) -> String {
+    let mut out = String::with_capacity(script.len() + ZSH_HELPER.len());
+    for line in script.lines() {
+        if line.starts_with("if [ \"$funcstack[1]\" = \"_ptybox\" ]") {
+            out.push_str(ZSH_HELPER);
+        }
+        let spec = line
+            .trim_start()
+            .trim_start_matches("'*")
+            .trim_start_matches('\'');
+        let path_kind = PATH_FLAGS
+            .iter()
+            .find(|flag| spec.starts_with(&format!("--{flag}=")));
+        let line = match path_kind {
+            Some(kind) => line.replacen(":_files'", &format!(":_ptybox__dynamic {kind}'"), 1),
+            None if spec.starts_with(":session_id -- ") => {
+                line.replacen(":_default'", ":_ptybox__dynamic session'", 1)
+            }
+            None => line.to_string(),
+        };
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+/// Replace file completion for path flags in the generated fish script and
+/// add session ID completion.
+fn fish_with_hook(script: &str) -> String {
+    let mut out = String::with_capacity(script.len());
+    for line in script.lines() {
+        let path_kind = PATH_FLAGS
+            .iter()
+            .find(|flag| line.contains(&format!(" -l {flag} ")) && line.ends_with(" -r -F"));
+        match path_kind {
+            Some(kind) => {
+                let _ = writeln!(
+                    out,
+                    "{} -f -a \"(ptybox __complete {kind} (commandline -ct))\"",
+                    line.trim_end_matches(" -F")
+                );
+            }
+            None => {
+                out.push_str(line);
+                out.push('\n');
+            }
+        }
+    }
+    let _ = writeln!(
+        out,
+        "complete -c ptybox -n \"__fish_ptybox_using_subcommand {}; and test (count (commandline -opc)) -eq 2\" -f -a \"(ptybox __complete session (commandline -ct))\"",
+        SESSION_COMMANDS.join(" ")
+    );
+    out
+}
@@ -0,0 +1,87 @@
+//! `ptybox estimate`: predict how long a directory of scenarios takes to
+//! run, from step timeouts, the run index, and the matrix.
+
+use miette::Result;
+use ptybox::artifacts::estimate_scenarios;
+use ptybox::model::policy::DeterminismPolicy;
+use ptybox::model::{EstimateBasis, EstimateReport, ScenarioEstimate};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// Print a per-scenario table of expected durations and ranges, then the
+/// total. Each scenario runs once per cell of the `locales` × `timezones`
+/// matrix.
+pub fn cmd_estimate(
+    dirs: &[PathBuf],
+    root: Option<&Path>,
+    locales: &[String],
+    timezones: &[String],
+    json: bool,
+) -> Result<()> {
+    let cells = DeterminismPolicy::matrix(locales, timezones).len();
+    let report = match estimate_scenarios(dirs, root, cells as u64) {
+        Ok(report) => report,
+        Err(err) => return crate::emit_result(json, Err(err)),
+    };
+    if json {
+        return crate::emit_json(&report);
+    }
+    print!("{}", format_report(&report));
+    Ok(())
+}
+
+fn format_report(report: &EstimateReport) -> String {
+    let width = report
+        .scenarios
+        .iter()
+        .map(|estimate| estimate.scenario.len())
+        .chain(["SCENARIO".len(), "total".len()])
+        .max()
+        .unwrap_or_default();
+    let mut table = format!(
+        "{:<width$}  {:>4}  {:<11}  {:>9}  {}\n",
+        "SCENARIO", "RUNS", "BASIS", "EXPECTED", "RANGE"
+    );
+    for estimate in &report.scenarios {
+        let _ = writeln!(
+            table,
+            "{:<width$}  {:>4}  {:<11}  {:>9}  {}",
+            estimate.scenario,
+            estimate.runs,
+            basis(estimate),
+            format_ms(estimate.total_ms),
+            range(estimate.total_low_ms, estimate.total_high_ms)
+        );
+    }
+    let _ = writeln!(
+        table,
+        "{:<width$}  {:>4}  {:<11}  {:>9}  {}",
+        "total",
+        report.matrix_cells * report.scenarios.len() as u64,
+        "",
+        format_ms(report.total_ms),
+        range(report.total_low_ms, report.total_high_ms)
+    );
+    table
+}
+
+/// `history(N)` or `timeouts`.
+fn basis(estimate: &ScenarioEstimate) -> String {
+    match estimate.basis {
+        EstimateBasis::History => format!("history({})", estimate.history_runs),
+        EstimateBasis::Timeouts => "timeouts".to_string(),
+    }
+}
+
+fn range(low_ms: u64, high_ms: u64) -> String {
+    format!("{}..{}", format_ms(low_ms), format_ms(high_ms))
+}
+
+/// `950ms`, `12.3s`, or `4m05s`.
+fn format_ms(ms: u64) -> String {
+    match ms {
+        0..=999 => format!("{ms}ms"),
+        1_000..=59_999 => format!("{}.{}s", ms / 1000, ms % 1000 / 100),
+        _ => format!("{}m{:02}s", ms / 60_000, ms % 60_000 / 1000),
+    }
+}
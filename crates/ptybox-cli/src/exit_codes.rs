@@ -0,0 +1,136 @@
+//! Exit-code profiles for CI systems.
+//!
+//! Errors keep their stable `E_*` codes in JSON output; a profile only
+//! changes the process exit code. `gitlab` and `buildkite` group the codes
+//! into test failures (1), usage and policy errors (2), and infrastructure
+//! errors, which get a code the CI system can retry on (125 and 255).
+//! An `--exit-code-map` file overrides single codes on top of the profile.
+
+use clap::ValueEnum;
+use ptybox::runner::{ErrorCode, RunnerError};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Process-wide mapping, set once from the global CLI flags.
+static EXIT_CODES: OnceLock<ExitCodes> = OnceLock::new();
+
+/// Built-in exit-code profile.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum ExitCodeProfile {
+    /// One exit code per error code (2-13, 1 for `E_INTERNAL`)
+    #[default]
+    Default,
+    /// Infrastructure errors exit 125, for `retry: exit_codes`
+    Gitlab,
+    /// Infrastructure errors exit 255, for `retry: automatic` on `exit_status`
+    Buildkite,
+}
+
+impl ExitCodeProfile {
+    fn exit_code(self, code: ErrorCode) -> i32 {
+        let infrastructure = match self {
+            Self::Default => return code.exit_code(),
+            Self::Gitlab => 125,
+            Self::Buildkite => 255,
+        };
+        match code {
+            ErrorCode::AssertionFailed
+            | ErrorCode::ProcessExit
+            | ErrorCode::Timeout
+            | ErrorCode::TerminalParse
+            | ErrorCode::ReplayMismatch
+            | ErrorCode::StaleObservation => 1,
+            ErrorCode::PolicyDenied
+            | ErrorCode::CliInvalidArg
+            | ErrorCode::Protocol
+            | ErrorCode::ProtocolVersionMismatch => 2,
+            ErrorCode::SandboxUnavailable | ErrorCode::Io | ErrorCode::Internal => infrastructure,
+        }
+    }
+}
+
+/// A profile plus per-code overrides.
+#[derive(Debug)]
+struct ExitCodes {
+    profile: ExitCodeProfile,
+    overrides: HashMap<ErrorCode, i32>,
+}
+
+/// Set the process-wide mapping from `--exit-codes` and `--exit-code-map`.
+///
+/// The map file is a JSON object from `E_*` codes to exit codes (1-255).
+///
+/// # Errors
+/// `E_IO` if the map file cannot be read, `E_CLI_INVALID_ARG` if it is not
+/// such an object.
+pub(crate) fn configure(profile: ExitCodeProfile, map: Option<&Path>) -> Result<(), RunnerError> {
+    let overrides = match map {
+        Some(path) => load_overrides(path)?,
+        None => HashMap::new(),
+    };
+    // Only `main` configures, once; a second call keeps the first mapping.
+    let _ = EXIT_CODES.set(ExitCodes { profile, overrides });
+    Ok(())
+}
+
+/// Exit code for an error code under the configured mapping.
+pub(crate) fn exit_code(code: ErrorCode) -> i32 {
+    let Some(codes) = EXIT_CODES.get() else {
+        return code.exit_code();
+    };
+    codes
+        .overrides
+        .get(&code)
+        .copied()
+        .unwrap_or_else(|| codes.profile.exit_code(code))
+}
+
+fn load_overrides(path: &Path) -> Result<HashMap<ErrorCode, i32>, RunnerError> {
+    let data = std::fs::read_to_string(path)
+        .map_err(|err| RunnerError::io("E_IO", "failed to read exit code map", err))?;
+    let entries: HashMap<String, i64> = serde_json::from_str(&data).map_err(|err| {
+        RunnerError::cli_invalid_arg(format!(
+            "exit code map {} must be a JSON object of E_* codes to exit codes: {err}",
+            path.display()
+        ))
+    })?;
+    entries
+        .into_iter()
+        .map(|(name, value)| {
+            let code = ErrorCode::parse(&name).ok_or_else(|| {
+                RunnerError::cli_invalid_arg(format!("unknown error code in exit code map: {name}"))
+            })?;
+            let exit = i32::try_from(value)
+                .ok()
+                .filter(|exit| (1..=255).contains(exit))
+                .ok_or_else(|| {
+                    RunnerError::cli_invalid_arg(format!(
+                        "exit code for {name} must be between 1 and 255, got {value}"
+                    ))
+                })?;
+            Ok((code, exit))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExitCodeProfile;
+    use ptybox::runner::ErrorCode;
+
+    #[test]
+    fn ci_profiles_group_error_codes() {
+        for profile in [ExitCodeProfile::Gitlab, ExitCodeProfile::Buildkite] {
+            assert_eq!(profile.exit_code(ErrorCode::AssertionFailed), 1);
+            assert_eq!(profile.exit_code(ErrorCode::Timeout), 1);
+            assert_eq!(profile.exit_code(ErrorCode::PolicyDenied), 2);
+        }
+        assert_eq!(
+            ExitCodeProfile::Gitlab.exit_code(ErrorCode::SandboxUnavailable),
+            125
+        );
+        assert_eq!(ExitCodeProfile::Buildkite.exit_code(ErrorCode::Io), 255);
+        assert_eq!(ExitCodeProfile::Default.exit_code(ErrorCode::Timeout), 4);
+    }
+}
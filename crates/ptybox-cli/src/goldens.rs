@@ -0,0 +1,99 @@
+//! `ptybox goldens`: list, prune, and update a directory of golden screen
+//! baselines.
+
+use miette::Result;
+use ptybox::artifacts::{list_goldens, prune_goldens, update_goldens};
+use ptybox::model::GoldenEntry;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// List the goldens under `baseline`, one per line; goldens no scenario in
+/// `scenarios` references are marked `unreferenced`.
+pub fn cmd_goldens_list(baseline: &Path, scenarios: &[PathBuf], json: bool) -> Result<()> {
+    let entries = match list_goldens(baseline, scenarios) {
+        Ok(entries) => entries,
+        Err(err) => return crate::emit_result(json, Err(err)),
+    };
+    if json {
+        return crate::emit_json(&entries);
+    }
+    for entry in &entries {
+        println!("{}", format_entry(entry));
+    }
+    Ok(())
+}
+
+fn format_entry(entry: &GoldenEntry) -> String {
+    let mut line = format!(
+        "{}  scenario={}  run={}  snapshots={}  bytes={}  updates={}  lines_changed={}",
+        entry.name,
+        entry.scenario,
+        entry.run_id,
+        entry.snapshots,
+        entry.bytes,
+        entry.updates,
+        entry.lines_changed
+    );
+    if entry.referenced_by.as_ref().is_some_and(Vec::is_empty) {
+        let _ = write!(line, "  unreferenced");
+    }
+    line
+}
+
+/// Remove the goldens no scenario in `scenarios` references.
+pub fn cmd_goldens_prune(
+    baseline: &Path,
+    scenarios: &[PathBuf],
+    dry_run: bool,
+    json: bool,
+) -> Result<()> {
+    let report = match prune_goldens(baseline, scenarios, dry_run) {
+        Ok(report) => report,
+        Err(err) => return crate::emit_result(json, Err(err)),
+    };
+    if json {
+        return crate::emit_json(&report);
+    }
+    let verb = if dry_run { "would remove" } else { "removed" };
+    for name in &report.removed {
+        println!("{verb} {name}");
+    }
+    eprintln!(
+        "{verb} {} golden(s) ({} bytes), kept {}",
+        report.removed.len(),
+        report.bytes_freed,
+        report.kept
+    );
+    Ok(())
+}
+
+/// Update goldens from the newest passing runs indexed under `root`.
+pub fn cmd_goldens_update(
+    baseline: &Path,
+    root: &Path,
+    names: &[String],
+    json: bool,
+) -> Result<()> {
+    let report = match update_goldens(baseline, root, names) {
+        Ok(report) => report,
+        Err(err) => return crate::emit_result(json, Err(err)),
+    };
+    if json {
+        return crate::emit_json(&report);
+    }
+    for update in &report.updated {
+        println!(
+            "updated {} from {}: +{} -{} ~{} snapshots, {} lines changed",
+            update.name,
+            update.run_id,
+            update.snapshots_added,
+            update.snapshots_removed,
+            update.snapshots_changed,
+            update.lines_changed
+        );
+    }
+    for skip in &report.skipped {
+        eprintln!("skipped {}: {}", skip.name, skip.reason);
+    }
+    Ok(())
+}
@@ -9,19 +9,21 @@
 #![allow(missing_docs, deprecated)]
 
 use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
-use clap_complete::{generate, Shell};
+use clap_complete::Shell;
 use miette::{IntoDiagnostic, Result};
 use serde::Serialize;
 
 use ptybox::artifacts::ArtifactsWriterConfig;
-use ptybox::model::policy::Policy;
-use ptybox::policy::explain_policy_for_run_config;
-use ptybox::runner::{
-    load_scenario, run_exec_with_options, run_scenario, RunnerError, RunnerOptions,
+use ptybox::model::policy::{DeterminismPolicy, Policy};
+use ptybox::model::{
+    AckSource, Acknowledgement, ArtifactsQuota, ArtifactsStatus, QuotaRetention, QuotaRunState,
+    RunStatus, UnsafeOption, DEFAULT_QUOTA_ACTIVE_TIMEOUT_MS,
 };
+use ptybox::policy::explain_policy_for_run_config;
+use ptybox::runner::{run_exec_with_options, run_scenario, RunnerError, RunnerOptions};
 use ptybox::scenario::load_policy_file;
-use std::io::{self, Write};
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
@@ -44,6 +46,14 @@ struct Cli {
     #[arg(long, value_enum, default_value = "auto", global = true)]
     color: ColorMode,
 
+    /// Exit-code profile for CI systems (JSON output keeps the E_* codes)
+    #[arg(long, value_enum, default_value = "default", global = true)]
+    exit_codes: exit_codes::ExitCodeProfile,
+
+    /// JSON file mapping E_* codes to exit codes, applied over --exit-codes
+    #[arg(long, global = true)]
+    exit_code_map: Option<PathBuf>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -87,6 +97,30 @@ enum Commands {
             help = "Require explicit write acknowledgement for any write access"
         )]
         strict_write: bool,
+        #[arg(
+            long = "tag",
+            value_name = "TAG",
+            help = "Tag the run (repeatable); recorded in run.json and the run index"
+        )]
+        tags: Vec<String>,
+        #[arg(
+            long,
+            value_name = "SHA",
+            help = "Git commit of the code under test, recorded in run.json provenance"
+        )]
+        git_sha: Option<String>,
+        #[arg(
+            long,
+            requires = "artifacts",
+            help = "Record Seatbelt denials in violations.json (requires the sandbox-audit feature)"
+        )]
+        audit_sandbox: bool,
+        #[arg(
+            long,
+            requires = "artifacts",
+            help = "Compare with a second terminal emulator in emulator-diff.json (requires the emulator-diff feature)"
+        )]
+        emulator_diff: bool,
         #[arg(last = true, required = true)]
         command: Vec<String>,
     },
@@ -99,8 +133,22 @@ enum Commands {
         explain_policy: bool,
         #[arg(long, short = 'v', help = "Show step-by-step progress to stderr")]
         verbose: bool,
+        #[arg(
+            long,
+            value_name = "MODE",
+            conflicts_with = "verbose",
+            help = "Progress on stderr: verbose (one line per step) or compact (one updating line with an ETA)"
+        )]
+        progress: Option<ProgressArg>,
         #[arg(long, help = "Run with interactive TUI showing live terminal output")]
         tui: bool,
+        #[arg(
+            long,
+            value_name = "PATH",
+            requires = "tui",
+            help = "With --tui, offer on exit to save a copy of the scenario with manual input added as steps"
+        )]
+        save_amended: Option<PathBuf>,
         #[arg(
             long,
             help = "Write artifacts to this directory (requires allowlisted write access)"
@@ -129,6 +177,90 @@ enum Commands {
             help = "Require explicit write acknowledgement for any write access"
         )]
         strict_write: bool,
+        #[arg(
+            long = "tag",
+            value_name = "TAG",
+            help = "Tag the run (repeatable); recorded in run.json and the run index"
+        )]
+        tags: Vec<String>,
+        #[arg(
+            long,
+            value_name = "SHA",
+            help = "Git commit of the code under test, recorded in run.json provenance"
+        )]
+        git_sha: Option<String>,
+        #[arg(
+            long,
+            requires = "artifacts",
+            help = "Record Seatbelt denials in violations.json (requires the sandbox-audit feature)"
+        )]
+        audit_sandbox: bool,
+        #[arg(
+            long,
+            requires = "artifacts",
+            help = "Compare with a second terminal emulator in emulator-diff.json (requires the emulator-diff feature)"
+        )]
+        emulator_diff: bool,
+        #[arg(
+            long,
+            value_name = "FORMAT",
+            value_parser = parse_report_arg,
+            help = "Report the result (repeatable): print GitHub annotations (github) or a Checks API payload (github-checks) to stdout, or write JUnit XML to a file (junit=PATH)"
+        )]
+        report: Vec<ReportArg>,
+    },
+    /// Rerun a scenario whenever watched files change
+    ///
+    /// Runs once immediately, then again after each debounced change to the
+    /// watched paths or the scenario file, printing a pass/fail delta versus
+    /// the previous run.
+    Watch {
+        #[arg(long)]
+        json: bool,
+        #[arg(long)]
+        scenario: PathBuf,
+        #[arg(
+            long = "paths",
+            value_name = "PATH",
+            num_args = 1..,
+            help = "Files or directories to watch (repeatable)"
+        )]
+        paths: Vec<PathBuf>,
+        #[arg(
+            long,
+            value_name = "MS",
+            default_value_t = 300,
+            help = "Quiet period after the last change before rerunning"
+        )]
+        debounce_ms: u64,
+        #[arg(
+            long,
+            value_name = "MS",
+            default_value_t = 200,
+            value_parser = clap::value_parser!(u64).range(1..),
+            help = "Interval between scans when polling for changes"
+        )]
+        poll_ms: u64,
+        #[arg(
+            long,
+            help = "Poll for changes instead of using file events (e.g. on network filesystems)"
+        )]
+        poll: bool,
+        #[arg(
+            long,
+            help = "Write each run's artifacts to DIR/run-<n> (requires allowlisted write access)"
+        )]
+        artifacts: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "N",
+            default_value_t = 5,
+            value_parser = clap::value_parser!(u64).range(1..),
+            help = "Number of most recent run artifact directories to keep"
+        )]
+        keep: u64,
+        #[arg(long, value_name = "N", help = "Stop after N runs")]
+        max_runs: Option<u64>,
     },
     Replay {
         #[arg(long)]
@@ -145,6 +277,17 @@ enum Commands {
         require_events: bool,
         #[arg(long)]
         require_checksums: bool,
+        #[arg(long, help = "Age identity file for decrypting encrypted artifacts")]
+        identity: Option<PathBuf>,
+        #[arg(long, help = "age binary used with --identity (default /usr/bin/age)")]
+        age_binary: Option<PathBuf>,
+        #[arg(
+            long,
+            value_enum,
+            value_delimiter = ',',
+            help = "Compare only these artifact classes (repeatable; default: all)"
+        )]
+        compare: Vec<CompareArg>,
     },
     ReplayReport {
         #[arg(long)]
@@ -152,6 +295,66 @@ enum Commands {
         #[arg(long)]
         artifacts: PathBuf,
     },
+    /// Render a recorded run as a report for pull request descriptions
+    Report {
+        #[arg(long, help = "Path to artifacts directory")]
+        artifacts: PathBuf,
+        #[arg(long, value_enum, default_value = "markdown")]
+        format: report::ReportFormat,
+        #[arg(
+            long,
+            short = 'o',
+            help = "Write the report to this file instead of stdout"
+        )]
+        output: Option<PathBuf>,
+    },
+    /// Emit an in-toto style attestation statement for a run's artifacts
+    Attest {
+        #[arg(long, help = "Path to artifacts directory")]
+        artifacts: PathBuf,
+        #[arg(
+            long,
+            short = 'o',
+            help = "Write the statement to this file instead of stdout"
+        )]
+        output: Option<PathBuf>,
+    },
+    /// Rank the recorded differences between two runs that ended differently
+    WhyDiffers {
+        #[arg(long, help = "Artifacts directory of the first run")]
+        a: PathBuf,
+        #[arg(long, help = "Artifacts directory of the second run")]
+        b: PathBuf,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Pack a failed run into one redacted .tar.gz for triage
+    Bundle {
+        #[arg(long, help = "Path to artifacts directory")]
+        artifacts: PathBuf,
+        #[arg(long, help = "Bundle file to write (.tar.gz)")]
+        out: PathBuf,
+        #[arg(
+            long,
+            default_value_t = 64,
+            help = "KiB kept from the end of the transcript"
+        )]
+        transcript_kib: usize,
+        #[arg(
+            long = "redact",
+            value_name = "REGEX",
+            help = "Extra pattern to redact (repeatable)"
+        )]
+        redact: Vec<String>,
+        #[arg(long, help = "Age identity file for encrypted artifacts")]
+        identity: Option<PathBuf>,
+        #[arg(long, help = "age binary used with --identity (default /usr/bin/age)")]
+        age_binary: Option<PathBuf>,
+        #[arg(long, help = "Replace the bundle file if it exists")]
+        overwrite: bool,
+        #[arg(long)]
+        json: bool,
+    },
     Driver {
         #[arg(long)]
         stdio: bool,
@@ -189,6 +392,11 @@ enum Commands {
         strict_write: bool,
         #[arg(long, help = "Acknowledge unsafe write access")]
         ack_unsafe_write: bool,
+        #[arg(
+            long,
+            help = "Capture stderr separately from the PTY (reported as stderr_delta)"
+        )]
+        separate_stderr: bool,
         #[arg(last = true, required = true)]
         command: Vec<String>,
     },
@@ -197,21 +405,272 @@ enum Commands {
         #[arg(long, help = "Output as JSON (default: human-readable)")]
         json: bool,
     },
+    /// Write protocol conformance vectors and schemas for client implementers
+    ProtocolVectors {
+        #[arg(long, help = "Output directory for vectors and schemas")]
+        out: PathBuf,
+        #[arg(long, help = "Print the manifest as JSON")]
+        json: bool,
+    },
+    /// Check that a PTY can be opened here and report the host's PTY setup
+    Doctor {
+        #[arg(long, help = "Output the probe as JSON")]
+        json: bool,
+    },
     /// Generate shell completions for bash, zsh, or fish
+    ///
+    /// Scenario, policy, and artifacts paths, `--normalize`/`--compare`
+    /// values, and session IDs are completed at runtime via `ptybox __complete`.
     Completions {
         #[arg(value_enum, help = "Shell to generate completions for")]
         shell: Shell,
     },
-    /// Generate an interactive HTML trace viewer from run artifacts
+    /// Search transcripts and snapshots of the runs indexed under an artifacts root
+    Grep {
+        #[arg(help = "Regular expression to search for")]
+        pattern: String,
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "Artifacts root holding runs.jsonl (parent of each run's --artifacts dir)"
+        )]
+        root: PathBuf,
+        #[arg(long, short = 'i', help = "Match without regard to case")]
+        ignore_case: bool,
+        #[arg(long, short = 'F', help = "Treat the pattern as literal text")]
+        fixed_strings: bool,
+        #[arg(
+            long,
+            short = 'C',
+            value_name = "N",
+            default_value_t = 0,
+            help = "Lines of context around each match"
+        )]
+        context: usize,
+        #[arg(long, help = "Age identity file for encrypted artifacts")]
+        identity: Option<PathBuf>,
+        #[arg(long, help = "age binary used with --identity (default /usr/bin/age)")]
+        age_binary: Option<PathBuf>,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Query the local run index (`runs.jsonl`) under an artifacts root
+    Runs {
+        #[command(subcommand)]
+        command: RunsCommand,
+    },
+    /// Manage run artifacts directories
+    Artifacts {
+        #[command(subcommand)]
+        command: ArtifactsCommand,
+    },
+    /// Estimate how long a set of scenarios takes to run
+    Estimate {
+        #[arg(
+            long = "dir",
+            value_name = "PATH",
+            num_args = 1..,
+            required = true,
+            help = "Scenario files or directories to estimate"
+        )]
+        dirs: Vec<PathBuf>,
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "Artifacts root holding runs.jsonl, for durations of earlier runs"
+        )]
+        root: Option<PathBuf>,
+        #[arg(
+            long = "locale",
+            value_name = "LOCALE",
+            help = "Run each scenario under this locale (repeatable; matrix axis)"
+        )]
+        locales: Vec<String>,
+        #[arg(
+            long = "timezone",
+            value_name = "TZ",
+            help = "Run each scenario under this timezone (repeatable; matrix axis)"
+        )]
+        timezones: Vec<String>,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Run every scenario in a directory as one suite
+    ///
+    /// Discovers JSON and YAML scenario files, runs them one after another
+    /// (or several at once with --jobs), and prints each scenario's status.
+    /// With --artifacts, each run writes to DIR/<nnn>-<name>/ and the
+    /// summary to DIR/suite.json. With --changed-files, only the scenarios
+    /// the change affects run; --dry-run prints that selection instead.
+    /// With --locale/--timezone, each scenario runs once per matrix cell.
+    /// Also available as `ptybox suite`.
+    #[command(visible_alias = "suite")]
+    RunSuite {
+        #[arg(
+            long = "dir",
+            value_name = "PATH",
+            num_args = 1..,
+            required = true,
+            help = "Scenario files or directories in the suite"
+        )]
+        dirs: Vec<PathBuf>,
+        #[arg(
+            long,
+            value_name = "N",
+            default_value_t = 1,
+            value_parser = clap::value_parser!(u16).range(1..),
+            help = "Scenarios to run at once"
+        )]
+        jobs: u16,
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "Write each scenario's artifacts and suite.json under this directory (requires allowlisted write access)"
+        )]
+        artifacts: Option<PathBuf>,
+        #[arg(
+            long,
+            help = "Overwrite existing scenario artifacts directories and suite.json"
+        )]
+        overwrite: bool,
+        #[arg(
+            long = "tag",
+            value_name = "TAG",
+            help = "Tag every run (repeatable); recorded in run.json and the run index"
+        )]
+        tags: Vec<String>,
+        #[arg(
+            long,
+            value_name = "SHA",
+            help = "Git commit of the code under test, recorded in run.json provenance"
+        )]
+        git_sha: Option<String>,
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Run only the scenarios affected by these changed file paths, one per line (e.g. from git diff --name-only)"
+        )]
+        changed_files: Option<PathBuf>,
+        #[arg(
+            long = "locale",
+            value_name = "LOCALE",
+            help = "Run each scenario under this locale (repeatable; matrix axis)"
+        )]
+        locales: Vec<String>,
+        #[arg(
+            long = "timezone",
+            value_name = "TZ",
+            help = "Run each scenario under this timezone (repeatable; matrix axis)"
+        )]
+        timezones: Vec<String>,
+        #[arg(
+            long,
+            value_name = "FILE",
+            requires = "changed_files",
+            help = "Impact map of watched globs per scenario (JSON or YAML); without it every scenario is selected"
+        )]
+        impact_map: Option<PathBuf>,
+        #[arg(
+            long,
+            help = "Print which scenarios would run and why, without running them"
+        )]
+        dry_run: bool,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Manage a directory of golden screen baselines
+    Goldens {
+        #[command(subcommand)]
+        command: GoldensCommand,
+    },
+    /// Create and inspect policies
+    Policy {
+        #[command(subcommand)]
+        command: PolicyCommand,
+    },
+    /// Generate an interactive HTML trace viewer, or an asciinema recording, from run artifacts
     Trace {
         #[arg(long, help = "Path to artifacts directory")]
         artifacts: PathBuf,
         #[arg(
             long,
             short = 'o',
-            help = "Output HTML file path (default: trace.html)"
+            help = "Output file path (default: trace.html, or session.cast for asciicast)"
         )]
         output: Option<PathBuf>,
+        #[arg(long, value_enum, default_value = "html")]
+        format: trace::TraceFormat,
+        #[arg(long, help = "Age identity file for decrypting encrypted artifacts")]
+        identity: Option<PathBuf>,
+        #[arg(long, help = "age binary used with --identity (default /usr/bin/age)")]
+        age_binary: Option<PathBuf>,
+    },
+    /// Re-render a recorded session into the terminal in real time
+    Play {
+        #[arg(long, help = "Path to artifacts directory")]
+        artifacts: PathBuf,
+        #[arg(
+            long,
+            default_value = "1",
+            value_parser = play::parse_speed,
+            help = "Playback speed multiplier (0.1 to 100)"
+        )]
+        speed: f64,
+        #[arg(
+            long,
+            default_value_t = 0,
+            help = "Start at this recording time in ms, rendering earlier output at once"
+        )]
+        from_ms: u64,
+        #[arg(long, help = "Cap pauses between outputs at this many ms")]
+        max_idle_ms: Option<u64>,
+        #[arg(long, help = "Age identity file for decrypting encrypted artifacts")]
+        identity: Option<PathBuf>,
+        #[arg(long, help = "age binary used with --identity (default /usr/bin/age)")]
+        age_binary: Option<PathBuf>,
+    },
+    /// Send seeded random input to a command and minimize any crash it finds
+    Fuzz {
+        #[arg(long)]
+        json: bool,
+        #[arg(long)]
+        policy: Option<PathBuf>,
+        #[arg(long, help = "Override the policy working directory (absolute path)")]
+        cwd: Option<String>,
+        #[arg(long, help = "Base seed (default: random, printed)")]
+        seed: Option<u64>,
+        #[arg(long, default_value_t = ptybox::fuzz::DEFAULT_ITERATIONS, help = "Random sequences to try")]
+        iterations: u32,
+        #[arg(long, default_value_t = ptybox::fuzz::DEFAULT_STEPS, help = "Actions per sequence")]
+        steps: usize,
+        #[arg(
+            long,
+            default_value_t = 50,
+            help = "Milliseconds to observe the command after each action"
+        )]
+        step_timeout_ms: u64,
+        #[arg(
+            long = "crash-pattern",
+            value_name = "REGEX",
+            help = "Also fail when the screen matches this regex (repeatable)"
+        )]
+        crash_patterns: Vec<String>,
+        #[arg(
+            long,
+            default_value_t = ptybox::fuzz::DEFAULT_MAX_MINIMIZE_RUNS,
+            help = "Most sequences to run while minimizing a failure"
+        )]
+        max_minimize_runs: u32,
+        #[arg(
+            long,
+            short = 'o',
+            help = "Write the reproduction scenario to this file instead of stdout"
+        )]
+        output: Option<PathBuf>,
+        #[arg(long, help = "Replace the output file if it exists")]
+        overwrite: bool,
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
     },
 
     // =========================================================================
@@ -301,39 +760,434 @@ enum Commands {
         #[arg(long)]
         json: bool,
     },
-    /// Internal: run the serve daemon (not user-facing)
-    #[command(hide = true)]
+    /// Run a long-lived scenario queue runner (`--queue`), driver gateway (`--listen`), or warm session pool (`--pool`)
+    ///
+    /// With `--queue`, watches a directory for scenario files, runs them with
+    /// bounded concurrency, and drains gracefully on SIGTERM/SIGINT. With
+    /// `--listen`, serves protocol v2 driver sessions on a Unix socket, each
+    /// bound to the policy of the token it authenticated with. With `--pool`,
+    /// keeps `--pool-size` children of one command spawned under `--policy`
+    /// and leases one to each driver connection on the socket. Without any
+    /// of these this is the internal session daemon spawned by `open`.
     Serve {
+        #[arg(long, value_name = "DIR", help = "Scenario queue directory to watch")]
+        queue: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "SOCKET",
+            conflicts_with = "queue",
+            requires = "auth_config",
+            help = "Serve token-authenticated driver sessions on this Unix socket"
+        )]
+        listen: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "FILE",
+            requires = "listen",
+            help = "Gateway auth config binding tokens to policies (JSON or YAML)"
+        )]
+        auth_config: Option<PathBuf>,
+        #[arg(
+            long,
+            default_value_t = 64,
+            value_parser = clap::value_parser!(u16).range(1..),
+            requires = "listen",
+            help = "Open gateway connections served at once"
+        )]
+        max_connections: u16,
+        #[arg(
+            long,
+            default_value_t = 16,
+            value_parser = clap::value_parser!(u16).range(1..),
+            requires = "listen",
+            help = "Driver sessions each gateway tenant may run at once"
+        )]
+        max_sessions: u16,
+        #[arg(
+            long,
+            value_name = "SOCKET",
+            conflicts_with_all = ["queue", "listen"],
+            requires = "policy",
+            help = "Lease pre-spawned driver sessions of the command on this Unix socket"
+        )]
+        pool: Option<PathBuf>,
+        #[arg(
+            long,
+            default_value_t = 4,
+            value_parser = clap::value_parser!(u16).range(1..=64),
+            requires = "pool",
+            help = "Children kept spawned and ready to lease"
+        )]
+        pool_size: u16,
+        #[arg(
+            long,
+            requires = "pool",
+            help = "Return children whose screen is back to its baseline after an episode"
+        )]
+        recycle: bool,
+        #[arg(
+            long,
+            value_name = "MS",
+            default_value_t = 200,
+            requires = "pool",
+            help = "Milliseconds a child is observed before its screen is taken as the baseline"
+        )]
+        warmup_ms: u64,
+        #[arg(
+            long,
+            default_value_t = 1,
+            value_parser = clap::value_parser!(u16).range(1..),
+            requires = "queue",
+            help = "Maximum scenarios run concurrently"
+        )]
+        jobs: u16,
+        #[arg(
+            long,
+            value_name = "ADDR",
+            requires = "queue",
+            help = "Loopback address for the health/status endpoint (e.g. 127.0.0.1:8787)"
+        )]
+        status_addr: Option<std::net::SocketAddr>,
+        #[arg(
+            long,
+            value_name = "FILE",
+            requires = "status_addr",
+            help = "File holding the hex SHA-256 of the bearer token that POST /jobs and job cancellation require"
+        )]
+        token_sha256_file: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "DIR",
+            conflicts_with_all = ["listen", "session_id"],
+            help = "Write per-job (queue) or per-episode (pool) artifacts under DIR/<id>"
+        )]
+        artifacts: Option<PathBuf>,
+        #[arg(
+            long = "locale",
+            value_name = "LOCALE",
+            requires = "queue",
+            help = "Run each job under this locale (repeatable; matrix axis)"
+        )]
+        locales: Vec<String>,
+        #[arg(
+            long = "timezone",
+            value_name = "TZ",
+            requires = "queue",
+            help = "Run each job under this timezone (repeatable; matrix axis)"
+        )]
+        timezones: Vec<String>,
+        #[arg(
+            long,
+            hide = true,
+            required_unless_present_any = ["queue", "listen", "pool"],
+            conflicts_with_all = ["queue", "listen", "pool"]
+        )]
+        session_id: Option<String>,
+        #[arg(
+            long,
+            value_name = "FILE",
+            conflicts_with_all = ["queue", "listen"],
+            help = "Policy file every pooled child runs under"
+        )]
+        policy: Option<PathBuf>,
+        #[arg(long, hide = true, conflicts_with_all = ["queue", "listen"])]
+        cwd: Option<String>,
+        #[arg(long, hide = true, conflicts_with_all = ["queue", "listen"])]
+        idle_timeout: Option<u64>,
+        #[arg(long, hide = true, conflicts_with_all = ["queue", "listen"])]
+        no_sandbox: bool,
+        #[arg(long, hide = true, conflicts_with_all = ["queue", "listen"])]
+        ack_unsafe_sandbox: bool,
+        #[arg(long, hide = true, conflicts_with_all = ["queue", "listen"])]
+        enable_network: bool,
+        #[arg(long, hide = true, conflicts_with_all = ["queue", "listen"])]
+        ack_unsafe_network: bool,
+        #[arg(long, hide = true, conflicts_with_all = ["queue", "listen"])]
+        ack_unsafe_write: bool,
+        #[arg(long, hide = true, conflicts_with_all = ["queue", "listen"])]
+        strict_write: bool,
+        #[arg(
+            last = true,
+            required_unless_present_any = ["queue", "listen"],
+            conflicts_with_all = ["queue", "listen"]
+        )]
+        command: Vec<String>,
+    },
+    /// Live monitor for a `serve --queue` runner
+    ///
+    /// Polls the runner's status endpoint and shows each in-flight job with
+    /// its current step, elapsed time, and budget use. Select a job with the
+    /// arrow keys and press `c` to cancel it before its next step; `q` quits.
+    /// With `--once`, prints the table (or the status JSON with `--json`)
+    /// and exits.
+    Top {
+        #[arg(
+            long,
+            value_name = "ADDR",
+            help = "Status endpoint of the queue runner (its --status-addr)"
+        )]
+        status_addr: std::net::SocketAddr,
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "File holding the queue runner's bearer token, for cancelling jobs"
+        )]
+        token_file: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "MS",
+            default_value_t = 500,
+            value_parser = clap::value_parser!(u64).range(50..),
+            help = "Interval between status refreshes"
+        )]
+        refresh_ms: u64,
+        #[arg(long, help = "Print the current status once and exit")]
+        once: bool,
+        #[arg(long, requires = "once")]
+        json: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum RunsCommand {
+    /// List indexed runs, newest first
+    List {
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "Artifacts root holding runs.jsonl (parent of each run's --artifacts dir)"
+        )]
+        root: PathBuf,
+        #[arg(
+            long = "tag",
+            value_name = "TAG",
+            help = "Only runs carrying this tag (repeatable; all must match)"
+        )]
+        tags: Vec<String>,
+        #[arg(long, value_enum, help = "Only runs with this status")]
+        status: Option<RunStatusArg>,
+        #[arg(long, help = "Show at most N runs")]
+        limit: Option<usize>,
         #[arg(long)]
-        session_id: String,
+        json: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum GoldensCommand {
+    /// List goldens with their size, references, and update churn
+    List {
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "Directory holding one baseline per subdirectory"
+        )]
+        baseline: PathBuf,
+        #[arg(
+            long,
+            value_name = "PATH",
+            num_args = 1..,
+            help = "Scenario files or directories to check references against"
+        )]
+        scenarios: Vec<PathBuf>,
         #[arg(long)]
-        policy: Option<PathBuf>,
+        json: bool,
+    },
+    /// Remove goldens whose scenario no longer exists
+    Prune {
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "Directory holding one baseline per subdirectory"
+        )]
+        baseline: PathBuf,
+        #[arg(
+            long,
+            value_name = "PATH",
+            num_args = 1..,
+            required = true,
+            help = "Scenario files or directories that reference goldens"
+        )]
+        scenarios: Vec<PathBuf>,
+        #[arg(long, help = "Report what would be removed without deleting it")]
+        dry_run: bool,
         #[arg(long)]
-        cwd: Option<String>,
+        json: bool,
+    },
+    /// Replace goldens with the newest passing run of their scenario
+    Update {
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "Directory holding one baseline per subdirectory"
+        )]
+        baseline: PathBuf,
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "Artifacts root holding runs.jsonl to take passing runs from"
+        )]
+        root: PathBuf,
+        #[arg(
+            long = "name",
+            value_name = "NAME",
+            help = "Golden to update (repeatable; default: all)"
+        )]
+        names: Vec<String>,
         #[arg(long)]
-        idle_timeout: Option<u64>,
+        json: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ArtifactsCommand {
+    /// Copy only the replay-essential artifacts into a new directory
+    Slim {
+        #[arg(long, help = "Path to artifacts directory")]
+        artifacts: PathBuf,
+        #[arg(
+            long,
+            short = 'o',
+            help = "Directory to write the slimmed artifacts to"
+        )]
+        output: PathBuf,
+        #[arg(long, help = "Replace the output directory if it exists")]
+        overwrite: bool,
         #[arg(long)]
-        no_sandbox: bool,
+        json: bool,
+    },
+    /// Rewrite an existing run's JSON artifacts in canonical form
+    Reformat {
+        #[arg(long, help = "Path to artifacts directory")]
+        artifacts: PathBuf,
         #[arg(long)]
-        ack_unsafe_sandbox: bool,
+        json: bool,
+    },
+    /// Rebuild run.json from journal.jsonl for a run whose harness was killed
+    Recover {
+        #[arg(long, help = "Path to artifacts directory")]
+        artifacts: PathBuf,
         #[arg(long)]
-        enable_network: bool,
+        json: bool,
+    },
+    /// Track runs under an artifacts root and set the byte quota they share
+    Quota {
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "Artifacts root (parent of each run's --artifacts dir)"
+        )]
+        root: PathBuf,
+        #[arg(long, help = "Total bytes the runs may use (default: no limit)")]
+        max_bytes: Option<u64>,
+        #[arg(
+            long,
+            value_name = "N",
+            requires = "max_bytes",
+            help = "Never evict the N newest completed runs"
+        )]
+        keep_newest: Option<u64>,
+        #[arg(
+            long,
+            requires = "max_bytes",
+            help = "Never evict runs that did not pass"
+        )]
+        keep_failed: bool,
+        #[arg(
+            long,
+            value_name = "MS",
+            requires = "max_bytes",
+            help = "Treat runs active this long as abandoned (default: 24h)"
+        )]
+        active_timeout_ms: Option<u64>,
         #[arg(long)]
-        ack_unsafe_network: bool,
+        json: bool,
+    },
+    /// Show usage of an artifacts root against its quota
+    Status {
+        #[arg(long, value_name = "DIR", help = "Artifacts root holding quota.json")]
+        root: PathBuf,
         #[arg(long)]
-        ack_unsafe_write: bool,
+        json: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum PolicyCommand {
+    /// Draft a policy by running a command once, unsandboxed, and recording what it touches
+    Init {
         #[arg(long)]
-        strict_write: bool,
+        json: bool,
+        #[arg(
+            long,
+            short = 'o',
+            help = "Write the draft policy to this file instead of stdout"
+        )]
+        output: Option<PathBuf>,
+        #[arg(long, help = "Replace the output file if it exists")]
+        overwrite: bool,
+        #[arg(long, help = "Working directory for the command (absolute path)")]
+        cwd: Option<String>,
+        #[arg(
+            long,
+            default_value_t = 5000,
+            help = "Stop the command after this many milliseconds"
+        )]
+        duration_ms: u64,
+        #[arg(long, help = "Sample /proc instead of running under strace")]
+        no_strace: bool,
+        #[arg(
+            long,
+            help = "Acknowledge that the command runs without a sandbox and with the full environment"
+        )]
+        ack_unsafe_observe: bool,
         #[arg(last = true, required = true)]
         command: Vec<String>,
     },
+    /// Suggest a tighter policy from what a finished run used, written to policy-suggestions.json
+    Suggest {
+        #[arg(long)]
+        json: bool,
+        #[arg(long, help = "Path to the run's artifacts directory")]
+        artifacts: PathBuf,
+    },
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum RunStatusArg {
+    Passed,
+    Failed,
+    Errored,
+    Canceled,
 }
 
+impl From<RunStatusArg> for ptybox::model::RunStatus {
+    fn from(status: RunStatusArg) -> Self {
+        match status {
+            RunStatusArg::Passed => Self::Passed,
+            RunStatusArg::Failed => Self::Failed,
+            RunStatusArg::Errored => Self::Errored,
+            RunStatusArg::Canceled => Self::Canceled,
+        }
+    }
+}
+
+mod completions;
+mod estimate;
+mod exit_codes;
+mod goldens;
+mod play;
 mod progress;
 mod protocol_help;
+mod report;
+mod runs;
 mod session_client;
+mod suite;
+mod top;
 mod trace;
 mod tui_mode;
+mod watch;
+mod why_differs;
 
 /// Configure color output based on CLI flag and environment
 fn configure_colors(mode: ColorMode) {
@@ -394,9 +1248,16 @@ fn install_signal_handler() {
 
 #[allow(clippy::too_many_lines)]
 fn main() -> Result<()> {
+    if let Some(result) = completions::run_complete_helper() {
+        return result;
+    }
     install_signal_handler();
     let cli = Cli::parse();
     configure_colors(cli.color);
+    if let Err(err) = exit_codes::configure(cli.exit_codes, cli.exit_code_map.as_deref()) {
+        eprintln!("error: {err}");
+        std::process::exit(err.exit_code());
+    }
     match cli.command {
         Commands::Exec {
             json,
@@ -411,14 +1272,21 @@ fn main() -> Result<()> {
             ack_unsafe_network,
             ack_unsafe_write,
             strict_write,
+            tags,
+            git_sha,
+            audit_sandbox,
+            emulator_diff,
             command,
         } => cmd_exec(
             json,
             policy,
             explain_policy,
             cwd,
-            artifacts,
-            overwrite,
+            runner_options(artifacts, overwrite, tags, git_sha),
+            RunnerFlags {
+                audit_sandbox,
+                emulator_diff,
+            },
             PolicyOverrides {
                 no_sandbox,
                 ack_unsafe_sandbox,
@@ -434,7 +1302,9 @@ fn main() -> Result<()> {
             scenario,
             explain_policy,
             verbose,
+            progress,
             tui,
+            save_amended,
             artifacts,
             overwrite,
             no_sandbox,
@@ -443,14 +1313,24 @@ fn main() -> Result<()> {
             ack_unsafe_network,
             ack_unsafe_write,
             strict_write,
+            tags,
+            git_sha,
+            audit_sandbox,
+            emulator_diff,
+            report,
         } => cmd_run(
             json,
             scenario,
             explain_policy,
-            verbose,
+            progress.or(verbose.then_some(ProgressArg::Verbose)),
             tui,
-            artifacts,
-            overwrite,
+            save_amended,
+            runner_options(artifacts, overwrite, tags, git_sha),
+            RunnerFlags {
+                audit_sandbox,
+                emulator_diff,
+            },
+            report,
             PolicyOverrides {
                 no_sandbox,
                 ack_unsafe_sandbox,
@@ -460,6 +1340,27 @@ fn main() -> Result<()> {
                 strict_write,
             },
         ),
+        Commands::Watch {
+            json,
+            scenario,
+            paths,
+            debounce_ms,
+            poll_ms,
+            poll,
+            artifacts,
+            keep,
+            max_runs,
+        } => watch::cmd_watch(&watch::WatchOptions {
+            scenario,
+            paths,
+            debounce: std::time::Duration::from_millis(debounce_ms),
+            poll_interval: std::time::Duration::from_millis(poll_ms),
+            poll,
+            artifacts,
+            keep: usize::try_from(keep).unwrap_or(usize::MAX),
+            max_runs,
+            json,
+        }),
         Commands::Driver {
             stdio,
             json,
@@ -473,6 +1374,7 @@ fn main() -> Result<()> {
             ack_unsafe_network,
             strict_write,
             ack_unsafe_write,
+            separate_stderr,
             command,
         } => cmd_driver(
             stdio,
@@ -481,6 +1383,7 @@ fn main() -> Result<()> {
             cwd,
             artifacts,
             overwrite,
+            separate_stderr,
             PolicyOverrides {
                 no_sandbox,
                 ack_unsafe_sandbox,
@@ -492,6 +1395,8 @@ fn main() -> Result<()> {
             command,
         ),
         Commands::ProtocolHelp { json } => cmd_protocol_help(json),
+        Commands::ProtocolVectors { out, json } => cmd_protocol_vectors(&out, json),
+        Commands::Doctor { json } => cmd_doctor(json),
         Commands::Replay {
             json,
             artifacts,
@@ -500,6 +1405,9 @@ fn main() -> Result<()> {
             explain,
             require_events,
             require_checksums,
+            identity,
+            age_binary,
+            compare,
         } => cmd_replay(
             json,
             artifacts,
@@ -508,10 +1416,270 @@ fn main() -> Result<()> {
             explain,
             require_events,
             require_checksums,
+            identity,
+            age_binary,
+            &compare,
         ),
         Commands::ReplayReport { json, artifacts } => cmd_replay_report(json, artifacts),
-        Commands::Completions { shell } => cmd_completions(shell),
-        Commands::Trace { artifacts, output } => cmd_trace(artifacts, output),
+        Commands::Report {
+            artifacts,
+            format,
+            output,
+        } => report::cmd_report(&artifacts, format, output.as_deref()),
+        Commands::Attest { artifacts, output } => cmd_attest(&artifacts, output.as_deref()),
+        Commands::WhyDiffers { a, b, json } => why_differs::cmd_why_differs(&a, &b, json),
+        Commands::Bundle {
+            artifacts,
+            out,
+            transcript_kib,
+            redact,
+            identity,
+            age_binary,
+            overwrite,
+            json,
+        } => cmd_bundle(
+            &artifacts,
+            &out,
+            &ptybox::artifacts::BundleOptions {
+                transcript_tail_bytes: transcript_kib.saturating_mul(1024),
+                redact,
+                overwrite,
+                identity,
+                age_binary,
+            },
+            json,
+        ),
+        Commands::Completions { shell } => completions::cmd_completions(Cli::command(), shell),
+        Commands::Trace {
+            artifacts,
+            output,
+            format,
+            identity,
+            age_binary,
+        } => cmd_trace(artifacts, output, format, identity, age_binary),
+        Commands::Play {
+            artifacts,
+            speed,
+            from_ms,
+            max_idle_ms,
+            identity,
+            age_binary,
+        } => play::cmd_play(
+            &artifacts,
+            identity.as_deref(),
+            age_binary,
+            &play::PlayOptions {
+                speed,
+                from_ms,
+                max_idle_ms,
+            },
+        ),
+        Commands::Grep {
+            pattern,
+            root,
+            ignore_case,
+            fixed_strings,
+            context,
+            identity,
+            age_binary,
+            json,
+        } => runs::cmd_grep(
+            &root,
+            &ptybox::artifacts::GrepOptions {
+                pattern,
+                ignore_case,
+                fixed_strings,
+                context,
+                identity,
+                age_binary,
+            },
+            json,
+        ),
+        Commands::Runs {
+            command:
+                RunsCommand::List {
+                    root,
+                    tags,
+                    status,
+                    limit,
+                    json,
+                },
+        } => runs::cmd_runs_list(&root, &tags, status.map(Into::into), limit, json),
+        Commands::Artifacts {
+            command:
+                ArtifactsCommand::Slim {
+                    artifacts,
+                    output,
+                    overwrite,
+                    json,
+                },
+        } => cmd_artifacts_slim(&artifacts, &output, overwrite, json),
+        Commands::Artifacts {
+            command: ArtifactsCommand::Reformat { artifacts, json },
+        } => cmd_artifacts_reformat(&artifacts, json),
+        Commands::Artifacts {
+            command: ArtifactsCommand::Recover { artifacts, json },
+        } => cmd_artifacts_recover(&artifacts, json),
+        Commands::Artifacts {
+            command:
+                ArtifactsCommand::Quota {
+                    root,
+                    max_bytes,
+                    keep_newest,
+                    keep_failed,
+                    active_timeout_ms,
+                    json,
+                },
+        } => {
+            let quota = max_bytes.map(|max_bytes| ArtifactsQuota {
+                max_bytes,
+                retention: QuotaRetention {
+                    keep_newest: keep_newest.unwrap_or(0),
+                    keep_failed,
+                    active_timeout_ms: active_timeout_ms.unwrap_or(DEFAULT_QUOTA_ACTIVE_TIMEOUT_MS),
+                },
+            });
+            cmd_artifacts_quota(&root, quota, json)
+        }
+        Commands::Artifacts {
+            command: ArtifactsCommand::Status { root, json },
+        } => cmd_artifacts_status(&root, json),
+        Commands::Estimate {
+            dirs,
+            root,
+            locales,
+            timezones,
+            json,
+        } => estimate::cmd_estimate(&dirs, root.as_deref(), &locales, &timezones, json),
+        Commands::RunSuite {
+            dirs,
+            jobs,
+            artifacts,
+            overwrite,
+            tags,
+            git_sha,
+            changed_files,
+            locales,
+            timezones,
+            impact_map,
+            dry_run,
+            json,
+        } => suite::cmd_run_suite(
+            ptybox::runner::SuiteConfig {
+                paths: dirs,
+                jobs: usize::from(jobs),
+                artifacts,
+                overwrite,
+                options: RunnerOptions {
+                    tags,
+                    git_sha,
+                    ..RunnerOptions::default()
+                },
+                matrix: DeterminismPolicy::matrix(&locales, &timezones),
+                ..ptybox::runner::SuiteConfig::default()
+            },
+            changed_files.as_deref(),
+            impact_map.as_deref(),
+            dry_run,
+            json,
+        ),
+        Commands::Goldens {
+            command:
+                GoldensCommand::List {
+                    baseline,
+                    scenarios,
+                    json,
+                },
+        } => goldens::cmd_goldens_list(&baseline, &scenarios, json),
+        Commands::Goldens {
+            command:
+                GoldensCommand::Prune {
+                    baseline,
+                    scenarios,
+                    dry_run,
+                    json,
+                },
+        } => goldens::cmd_goldens_prune(&baseline, &scenarios, dry_run, json),
+        Commands::Goldens {
+            command:
+                GoldensCommand::Update {
+                    baseline,
+                    root,
+                    names,
+                    json,
+                },
+        } => goldens::cmd_goldens_update(&baseline, &root, &names, json),
+        Commands::Policy {
+            command:
+                PolicyCommand::Init {
+                    json,
+                    output,
+                    overwrite,
+                    cwd,
+                    duration_ms,
+                    no_strace,
+                    ack_unsafe_observe,
+                    command,
+                },
+        } => {
+            let (command, args) = match split_command(command) {
+                Ok(parts) => parts,
+                Err(err) => return emit_result(json, Err(err)),
+            };
+            let config = ptybox::policy::infer::InferConfig {
+                cwd,
+                duration: std::time::Duration::from_millis(duration_ms),
+                strace: !no_strace,
+                ack: ack_unsafe_observe,
+                ..ptybox::policy::infer::InferConfig::new(command, args)
+            };
+            cmd_policy_init(&config, output.as_deref(), overwrite, json)
+        }
+        Commands::Policy {
+            command: PolicyCommand::Suggest { json, artifacts },
+        } => cmd_policy_suggest(&artifacts, json),
+        Commands::Fuzz {
+            json,
+            policy,
+            cwd,
+            seed,
+            iterations,
+            steps,
+            step_timeout_ms,
+            crash_patterns,
+            max_minimize_runs,
+            output,
+            overwrite,
+            command,
+        } => {
+            let (command, args) = match split_command(command) {
+                Ok(parts) => parts,
+                Err(err) => return emit_result(json, Err(err)),
+            };
+            validate_cwd(cwd.as_deref(), json)?;
+            let policy = match policy {
+                Some(path) => load_policy_file(&path)?,
+                None => Policy::default(),
+            };
+            let seed = seed.unwrap_or_else(random_seed);
+            let mut config = ptybox::fuzz::FuzzConfig {
+                cwd,
+                iterations,
+                steps,
+                step_timeout: std::time::Duration::from_millis(step_timeout_ms),
+                max_minimize_runs,
+                ..ptybox::fuzz::FuzzConfig::new(command, args, policy, seed)
+            };
+            config
+                .watchers
+                .extend(crash_patterns.iter().enumerate().map(|(index, pattern)| {
+                    ptybox::model::Watcher {
+                        name: format!("crash-pattern-{}", index + 1),
+                        condition: ptybox::model::Condition::screen_matches(pattern),
+                    }
+                }));
+            cmd_fuzz(&config, output.as_deref(), overwrite, json)
+        }
         Commands::Open {
             json,
             policy,
@@ -559,6 +1727,73 @@ fn main() -> Result<()> {
         Commands::Screen { session_id, json } => cmd_screen(session_id, json),
         Commands::Close { session_id, json } => cmd_close(session_id, json),
         Commands::Sessions { json } => cmd_sessions(json),
+        Commands::Serve {
+            queue: Some(queue),
+            jobs,
+            status_addr,
+            token_sha256_file,
+            artifacts,
+            locales,
+            timezones,
+            ..
+        } => cmd_serve_queue(
+            queue,
+            jobs,
+            status_addr,
+            token_sha256_file.as_deref(),
+            artifacts,
+            DeterminismPolicy::matrix(&locales, &timezones),
+        ),
+        Commands::Serve {
+            listen: Some(listen),
+            auth_config: Some(auth_config),
+            max_connections,
+            max_sessions,
+            ..
+        } => cmd_serve_gateway(
+            listen,
+            &auth_config,
+            usize::from(max_connections),
+            usize::from(max_sessions),
+        ),
+        Commands::Serve {
+            pool: Some(pool),
+            policy: Some(policy),
+            pool_size,
+            recycle,
+            warmup_ms,
+            artifacts,
+            cwd,
+            command,
+            ..
+        } => cmd_serve_pool(
+            pool,
+            &policy,
+            ptybox::serve::pool::SessionPoolConfig {
+                command: command.first().cloned().unwrap_or_default(),
+                args: command.iter().skip(1).cloned().collect(),
+                cwd,
+                policy: ptybox::model::policy::Policy::default(),
+                separate_stderr: false,
+                size: usize::from(pool_size),
+                recycle,
+                warmup: std::time::Duration::from_millis(warmup_ms),
+                artifacts_root: artifacts,
+            },
+        ),
+        Commands::Top {
+            status_addr,
+            token_file,
+            refresh_ms,
+            once,
+            json,
+        } => top::cmd_top(
+            status_addr,
+            token_file.as_deref(),
+            std::time::Duration::from_millis(refresh_ms),
+            once,
+            json,
+        ),
         Commands::Serve {
             session_id,
             policy,
@@ -571,8 +1806,9 @@ fn main() -> Result<()> {
             ack_unsafe_write,
             strict_write,
             command,
+            ..
         } => cmd_serve(
-            session_id,
+            session_id.unwrap_or_default(),
             policy,
             cwd,
             idle_timeout,
@@ -589,9 +1825,116 @@ fn main() -> Result<()> {
     }
 }
 
-// =============================================================================
-// Command Handlers
-// =============================================================================
+// =============================================================================
+// Command Handlers
+// =============================================================================
+
+/// Runner options for `exec`/`run`: artifacts, tags, git SHA, and a run index
+/// at the artifacts root (the parent of the artifacts directory).
+fn runner_options(
+    artifacts: Option<PathBuf>,
+    overwrite: bool,
+    tags: Vec<String>,
+    git_sha: Option<String>,
+) -> RunnerOptions {
+    RunnerOptions {
+        run_index: artifacts
+            .as_ref()
+            .and_then(|dir| dir.parent())
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .map(std::path::Path::to_path_buf),
+        artifacts: artifacts.map(|dir| ArtifactsWriterConfig { dir, overwrite }),
+        tags,
+        git_sha,
+        ..RunnerOptions::default()
+    }
+}
+
+/// Apply `--audit-sandbox` to runner options.
+#[cfg(feature = "sandbox-audit")]
+#[allow(clippy::unnecessary_wraps)] // shares its signature with the feature-less build
+fn with_sandbox_audit(
+    options: RunnerOptions,
+    sandbox_audit: bool,
+) -> Result<RunnerOptions, RunnerError> {
+    Ok(RunnerOptions {
+        sandbox_audit,
+        ..options
+    })
+}
+
+/// Reject `--audit-sandbox` when the audit collector is not compiled in.
+#[cfg(not(feature = "sandbox-audit"))]
+fn with_sandbox_audit(
+    options: RunnerOptions,
+    sandbox_audit: bool,
+) -> Result<RunnerOptions, RunnerError> {
+    if sandbox_audit {
+        return Err(RunnerError::cli_invalid_arg(
+            "--audit-sandbox requires ptybox built with the `sandbox-audit` feature",
+        ));
+    }
+    Ok(options)
+}
+
+/// Apply `--emulator-diff` to runner options.
+#[cfg(feature = "emulator-diff")]
+#[allow(clippy::unnecessary_wraps)] // shares its signature with the feature-less build
+fn with_emulator_diff(
+    options: RunnerOptions,
+    emulator_diff: bool,
+) -> Result<RunnerOptions, RunnerError> {
+    Ok(RunnerOptions {
+        emulator_diff,
+        ..options
+    })
+}
+
+/// Reject `--emulator-diff` when the second emulator backend is not compiled in.
+#[cfg(not(feature = "emulator-diff"))]
+fn with_emulator_diff(
+    options: RunnerOptions,
+    emulator_diff: bool,
+) -> Result<RunnerOptions, RunnerError> {
+    if emulator_diff {
+        return Err(RunnerError::cli_invalid_arg(
+            "--emulator-diff requires ptybox built with the `emulator-diff` feature",
+        ));
+    }
+    Ok(options)
+}
+
+/// Configure trace export from the standard `OTEL_EXPORTER_OTLP_*` variables.
+#[cfg(feature = "otel")]
+fn with_otel(options: RunnerOptions) -> Result<RunnerOptions, RunnerError> {
+    Ok(RunnerOptions {
+        otel: ptybox::otel::OtelConfig::from_env()?,
+        ..options
+    })
+}
+
+/// Without the `otel` feature the OTLP environment variables are ignored.
+#[cfg(not(feature = "otel"))]
+#[allow(clippy::unnecessary_wraps)] // shares its signature with the feature build
+fn with_otel(options: RunnerOptions) -> Result<RunnerOptions, RunnerError> {
+    Ok(options)
+}
+
+/// Configure log forwarding from `PTYBOX_LOG_FORWARD` and friends.
+#[cfg(feature = "log-forward")]
+fn with_log_forward(options: RunnerOptions) -> Result<RunnerOptions, RunnerError> {
+    Ok(RunnerOptions {
+        log_forward: ptybox::log_forward::LogForwardConfig::from_env()?,
+        ..options
+    })
+}
+
+/// Without the `log-forward` feature `PTYBOX_LOG_FORWARD` is ignored.
+#[cfg(not(feature = "log-forward"))]
+#[allow(clippy::unnecessary_wraps)] // shares its signature with the feature build
+fn with_log_forward(options: RunnerOptions) -> Result<RunnerOptions, RunnerError> {
+    Ok(options)
+}
 
 /// Handle the exec command.
 #[allow(clippy::too_many_arguments)]
@@ -600,17 +1943,25 @@ fn cmd_exec(
     policy: Option<PathBuf>,
     explain_policy: bool,
     cwd: Option<String>,
-    artifacts: Option<PathBuf>,
-    overwrite: bool,
+    options: RunnerOptions,
+    flags: RunnerFlags,
     overrides: PolicyOverrides,
     command: Vec<String>,
 ) -> Result<()> {
+    let options = match with_sandbox_audit(options, flags.audit_sandbox)
+        .and_then(|options| with_emulator_diff(options, flags.emulator_diff))
+        .and_then(with_otel)
+        .and_then(with_log_forward)
+    {
+        Ok(options) => options,
+        Err(err) => return emit_result(json, Err(err)),
+    };
     let (cmd, args) = split_command(command)?;
     let mut policy = match policy {
         Some(path) => load_policy_file(&path)?,
         None => Policy::default(),
     };
-    apply_cli_policy_overrides(&mut policy, &overrides);
+    let ack_overrides = apply_cli_policy_overrides(&mut policy, &overrides);
     validate_cwd(cwd.as_deref(), json)?;
     if explain_policy {
         let cwd = cwd.clone().or_else(|| policy.fs.working_dir.clone());
@@ -620,14 +1971,20 @@ fn cmd_exec(
             cwd,
             initial_size: ptybox::model::TerminalSize::default(),
             policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy.clone())),
+            separate_stderr: false,
+            output_buffer: ptybox::model::OutputBufferConfig::default(),
+            resize: ptybox::model::ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         };
         let explanation = explain_policy_for_run_config(&policy, &run_config);
         emit_explanation(json, &explanation)?;
         return Ok(());
     }
     let options = RunnerOptions {
-        artifacts: artifacts.map(|dir| ArtifactsWriterConfig { dir, overwrite }),
-        progress: None,
+        ack_overrides,
+        ..options
     };
     let result = run_exec_with_options(cmd, args, cwd, policy, options);
     emit_result(json, result)
@@ -639,30 +1996,74 @@ fn cmd_run(
     json: bool,
     scenario_path: PathBuf,
     explain_policy: bool,
-    verbose: bool,
+    progress: Option<ProgressArg>,
     tui: bool,
-    artifacts: Option<PathBuf>,
-    overwrite: bool,
+    save_amended: Option<PathBuf>,
+    options: RunnerOptions,
+    flags: RunnerFlags,
+    report: Vec<ReportArg>,
     overrides: PolicyOverrides,
 ) -> Result<()> {
+    let mut stdout_reports = Vec::new();
+    let mut reporters = Vec::new();
+    for arg in report {
+        match arg {
+            ReportArg::Stdout(format) => stdout_reports.push(format),
+            ReportArg::Reporter(reporter) => reporters.push(reporter),
+        }
+    }
+    if stdout_reports.len() > 1 {
+        return emit_cli_error(
+            json,
+            "--report prints at most one of github and github-checks",
+        );
+    }
+    if !stdout_reports.is_empty() && (json || tui || explain_policy) {
+        return emit_cli_error(
+            json,
+            "--report github and github-checks cannot be combined with --json, --tui, or --explain-policy",
+        );
+    }
+    let mut options = match with_sandbox_audit(options, flags.audit_sandbox)
+        .and_then(|options| with_emulator_diff(options, flags.emulator_diff))
+        .and_then(with_otel)
+        .and_then(with_log_forward)
+    {
+        Ok(options) => options,
+        Err(err) => return emit_result(json, Err(err)),
+    };
+    options.reporters = reporters;
     let path_str = scenario_path
         .to_str()
         .ok_or_else(|| miette::miette!("scenario path is not valid UTF-8"))?;
-    let mut scenario = load_scenario(path_str)?;
+    let (mut scenario, source) = ptybox::scenario::load_scenario_file_with_source(path_str)?;
     let mut policy = ptybox::scenario::load_policy_ref(&scenario.run.policy)?;
-    apply_cli_policy_overrides(&mut policy, &overrides);
+    options.ack_overrides = apply_cli_policy_overrides(&mut policy, &overrides);
     if let Some(dir) = scenario.run.cwd.as_ref() {
         if !std::path::Path::new(dir).is_absolute() {
             return emit_cli_error(json, "scenario cwd must be an absolute path");
         }
     }
     if explain_policy {
+        // Explain the policy the run would use, with matching grants applied.
+        let mut policy = policy;
+        if let Err(err) =
+            ptybox::policy::grants::apply_policy_grants(&mut policy, &scenario.metadata.name)
+        {
+            return emit_result(json, Err(err));
+        }
         let run_config = ptybox::model::RunConfig {
             command: scenario.run.command.clone(),
             args: scenario.run.args.clone(),
             cwd: scenario.run.cwd.clone(),
             initial_size: scenario.run.initial_size.clone(),
             policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy.clone())),
+            separate_stderr: false,
+            output_buffer: ptybox::model::OutputBufferConfig::default(),
+            resize: ptybox::model::ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         };
         let explanation = explain_policy_for_run_config(&policy, &run_config);
         emit_explanation(json, &explanation)?;
@@ -672,26 +2073,120 @@ fn cmd_run(
 
     // TUI mode runs the scenario in an interactive terminal UI
     if tui {
-        if verbose || json {
-            return emit_cli_error(json, "--tui cannot be combined with --verbose or --json");
+        if progress.is_some() || json {
+            return emit_cli_error(
+                json,
+                "--tui cannot be combined with --verbose, --progress, or --json",
+            );
         }
-        let artifacts_config = artifacts.map(|dir| ArtifactsWriterConfig { dir, overwrite });
-        return tui_mode::run_tui(scenario, artifacts_config);
+        let amend = save_amended.map(|dest| tui_mode::AmendTarget {
+            source: path_str.to_string(),
+            dest,
+        });
+        return tui_mode::run_tui(scenario, options, amend);
     }
 
-    let progress_callback = if verbose {
-        Some(Arc::new(progress::VerboseProgress::new()) as Arc<dyn ptybox::runner::ProgressCallback>)
-    } else {
-        None
-    };
-    let options = RunnerOptions {
-        artifacts: artifacts.map(|dir| ArtifactsWriterConfig { dir, overwrite }),
-        progress: progress_callback,
-    };
+    options.progress = progress.map(|mode| match mode {
+        ProgressArg::Verbose => {
+            Arc::new(progress::VerboseProgress::new()) as Arc<dyn ptybox::runner::ProgressCallback>
+        }
+        ProgressArg::Compact => {
+            let prior = options
+                .run_index
+                .as_deref()
+                .and_then(|root| progress::expected_duration_ms(root, &scenario.metadata.name));
+            Arc::new(progress::CompactProgress::new(prior))
+        }
+    });
     let result = run_scenario(scenario, options);
+    if let Some(format) = stdout_reports.pop() {
+        emit_report(format, &result, source)?;
+    }
     emit_result(json, result)
 }
 
+/// Progress output for `run --progress`.
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum ProgressArg {
+    /// One line per step, with assertion details for failed steps
+    Verbose,
+    /// A single updating line with counts, elapsed time, and an ETA
+    Compact,
+}
+
+/// Reports for `run --report`.
+#[derive(Clone, Debug)]
+enum ReportArg {
+    /// Printed to stdout after the run
+    Stdout(StdoutReport),
+    /// Written to a file by the runner, such as `junit=PATH`
+    Reporter(ptybox::runner::Reporter),
+}
+
+/// Report formats `run --report` prints to stdout.
+#[derive(Copy, Clone, Debug)]
+enum StdoutReport {
+    /// GitHub Actions `::error` workflow commands
+    Github,
+    /// GitHub Checks API check-run JSON payload
+    GithubChecks,
+}
+
+/// Parse `--report`: `github`, `github-checks`, or `junit=PATH`.
+fn parse_report_arg(value: &str) -> std::result::Result<ReportArg, String> {
+    match value.split_once('=') {
+        None if value == "github" => Ok(ReportArg::Stdout(StdoutReport::Github)),
+        None if value == "github-checks" => Ok(ReportArg::Stdout(StdoutReport::GithubChecks)),
+        Some(("junit", path)) if !path.is_empty() => Ok(ReportArg::Reporter(
+            ptybox::runner::Reporter::Junit(PathBuf::from(path)),
+        )),
+        Some(("junit", _)) => Err("junit needs a file: junit=PATH".to_string()),
+        _ => Err(format!(
+            "unknown report '{value}' (expected github, github-checks, or junit=PATH)"
+        )),
+    }
+}
+
+/// Print a run report to stdout, with the scenario path relative to the
+/// current directory so annotations land on the checked-out file.
+fn emit_report(
+    format: StdoutReport,
+    result: &Result<ptybox::model::RunResult, RunnerError>,
+    mut source: ptybox::scenario::ScenarioSource,
+) -> Result<()> {
+    let path = PathBuf::from(&source.path);
+    let relative = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| {
+            path.strip_prefix(cwd)
+                .ok()
+                .map(std::path::Path::to_path_buf)
+        })
+        .unwrap_or(path);
+    let relative = relative.strip_prefix(".").unwrap_or(&relative);
+    source.path = relative.to_string_lossy().into_owned();
+    let error_info;
+    let outcome = match result {
+        Ok(run) => Ok(run),
+        Err(err) => {
+            error_info = err.to_error_info();
+            Err(&error_info)
+        }
+    };
+    match format {
+        StdoutReport::Github => {
+            print!(
+                "{}",
+                ptybox::report::github_workflow_commands(outcome, &source)
+            );
+            std::io::stdout().flush().into_diagnostic()
+        }
+        StdoutReport::GithubChecks => {
+            emit_json(&ptybox::report::github_check_run(outcome, &source))
+        }
+    }
+}
+
 /// Handle the driver command.
 #[allow(clippy::too_many_arguments)]
 fn cmd_driver(
@@ -701,6 +2196,7 @@ fn cmd_driver(
     cwd: Option<String>,
     artifacts: Option<PathBuf>,
     overwrite: bool,
+    separate_stderr: bool,
     overrides: PolicyOverrides,
     command: Vec<String>,
 ) -> Result<()> {
@@ -712,7 +2208,7 @@ fn cmd_driver(
         Some(path) => load_policy_file(&path)?,
         None => Policy::default(),
     };
-    apply_cli_policy_overrides(&mut policy, &overrides);
+    let ack_overrides = apply_cli_policy_overrides(&mut policy, &overrides);
     validate_cwd(cwd.as_deref(), json)?;
 
     let config = ptybox::driver::DriverConfig {
@@ -721,6 +2217,8 @@ fn cmd_driver(
         cwd,
         policy,
         artifacts: artifacts.map(|dir| ArtifactsWriterConfig { dir, overwrite }),
+        separate_stderr,
+        ack_overrides,
     };
 
     match ptybox::driver::run_driver(config) {
@@ -741,8 +2239,74 @@ fn cmd_protocol_help(json: bool) -> Result<()> {
     Ok(())
 }
 
+/// Handle the protocol-vectors command.
+fn cmd_protocol_vectors(out: &Path, json: bool) -> Result<()> {
+    let manifest = ptybox::driver::write_protocol_vectors(out)?;
+    if json {
+        emit_json(&manifest)?;
+    } else {
+        eprintln!(
+            "wrote {} exchanges, {} error vectors, and {} schemas to {}",
+            manifest.exchanges.len(),
+            manifest.errors.len(),
+            manifest.schemas.len(),
+            out.display()
+        );
+    }
+    Ok(())
+}
+
+/// Handle the doctor command: probe PTY creation, exiting with the `E_IO`
+/// code when no PTY could be opened.
+fn cmd_doctor(json: bool) -> Result<()> {
+    let probe = ptybox::session::probe_pty();
+    if json {
+        emit_json(&probe)?;
+    } else {
+        print_pty_probe(&probe);
+    }
+    if !probe.ok {
+        std::process::exit(exit_codes::exit_code(ptybox::runner::ErrorCode::Io));
+    }
+    Ok(())
+}
+
+fn print_pty_probe(probe: &ptybox::model::PtyProbe) {
+    let diagnostics = &probe.diagnostics;
+    match &probe.error {
+        None => println!("pty: ok ({} attempt(s))", probe.attempts),
+        Some(error) => println!("pty: failed after {} attempt(s): {error}", probe.attempts),
+    }
+    if let Some(name) = &diagnostics.errno_name {
+        let kind = if diagnostics.transient {
+            "transient"
+        } else {
+            "not transient"
+        };
+        println!("errno: {name} ({kind})");
+    }
+    let present = if diagnostics.ptmx_present {
+        "present"
+    } else {
+        "missing"
+    };
+    println!("/dev/ptmx: {present}");
+    if let Some(mounted) = diagnostics.devpts_mounted {
+        let state = if mounted { "mounted" } else { "not mounted" };
+        println!("devpts: {state}");
+    }
+    println!(
+        "container: {}",
+        if diagnostics.container { "yes" } else { "no" }
+    );
+    for hint in &diagnostics.hints {
+        println!("hint: {hint}");
+    }
+}
+
 /// Handle the replay command.
 #[allow(clippy::fn_params_excessive_bools)]
+#[allow(clippy::too_many_arguments)]
 fn cmd_replay(
     json: bool,
     artifacts: PathBuf,
@@ -751,6 +2315,9 @@ fn cmd_replay(
     explain: bool,
     require_events: bool,
     require_checksums: bool,
+    identity: Option<PathBuf>,
+    age_binary: Option<PathBuf>,
+    compare: &[CompareArg],
 ) -> Result<()> {
     let has_none = normalize
         .iter()
@@ -785,11 +2352,21 @@ fn cmd_replay(
                 .collect(),
         )
     };
+    let compare = CompareArg::to_replay_compare(compare);
+    if require_events && !compare.events {
+        return emit_cli_error(
+            json,
+            "--require-events cannot be combined with --compare that excludes events",
+        );
+    }
     let options = ptybox::replay::ReplayOptions {
         strict,
         filters,
         require_events,
         require_checksums,
+        identity,
+        age_binary,
+        compare,
     };
     if explain {
         let explanation = ptybox::replay::explain_replay(&artifacts, options)?;
@@ -815,19 +2392,334 @@ fn cmd_replay_report(json: bool, artifacts: PathBuf) -> Result<()> {
     Ok(())
 }
 
-/// Handle the completions command.
-#[allow(clippy::unnecessary_wraps)] // Consistent with other command handlers
-fn cmd_completions(shell: Shell) -> Result<()> {
-    let mut cmd = Cli::command();
-    let name = cmd.get_name().to_string();
-    generate(shell, &mut cmd, name, &mut io::stdout());
+/// Handle the attest command.
+fn cmd_attest(artifacts: &Path, output: Option<&Path>) -> Result<()> {
+    let statement = ptybox::artifacts::attest_artifacts(artifacts)?;
+    match output {
+        Some(path) => {
+            let mut data = serde_json::to_vec_pretty(&statement).into_diagnostic()?;
+            data.push(b'\n');
+            std::fs::write(path, data).into_diagnostic()?;
+            eprintln!(
+                "attested {} artifacts to {}",
+                statement.subject.len(),
+                path.display()
+            );
+        }
+        None => emit_json(&statement)?,
+    }
+    Ok(())
+}
+
+fn cmd_artifacts_slim(artifacts: &Path, output: &Path, overwrite: bool, json: bool) -> Result<()> {
+    let report = match ptybox::artifacts::slim_artifacts(artifacts, output, overwrite) {
+        Ok(report) => report,
+        Err(err) => return emit_result(json, Err(err)),
+    };
+    if json {
+        return emit_json(&report);
+    }
+    eprintln!(
+        "kept {} files ({} bytes), dropped {} files ({} bytes) -> {}",
+        report.kept.len(),
+        report.kept_bytes,
+        report.dropped_files,
+        report.dropped_bytes,
+        report.output
+    );
+    Ok(())
+}
+
+fn cmd_policy_init(
+    config: &ptybox::policy::infer::InferConfig,
+    output: Option<&Path>,
+    overwrite: bool,
+    json: bool,
+) -> Result<()> {
+    if let Some(output) = output {
+        if output.exists() && !overwrite {
+            return emit_cli_error(json, "output file exists (use --overwrite to replace it)");
+        }
+    }
+    let inference = match ptybox::policy::infer::infer_policy(config) {
+        Ok(inference) => inference,
+        Err(err) => return emit_result(json, Err(err)),
+    };
+    let mut policy = serde_json::to_string_pretty(&inference.policy).into_diagnostic()?;
+    policy.push('\n');
+    match output {
+        Some(output) => std::fs::write(output, policy).into_diagnostic()?,
+        None if !json => print!("{policy}"),
+        None => {}
+    }
+    if json {
+        return emit_json(&inference);
+    }
+    let observed = &inference.observed;
+    eprintln!(
+        "observed via {} for {}ms: {} executables, {} read, {} written, {} env vars",
+        inference.source,
+        inference.duration_ms,
+        observed.executables.len(),
+        observed.read.len(),
+        observed.written.len(),
+        observed.env.len()
+    );
+    for note in &inference.notes {
+        eprintln!("note: {note}");
+    }
+    if let Some(output) = output {
+        eprintln!(
+            "draft policy written to {}; review it before use",
+            output.display()
+        );
+    }
+    Ok(())
+}
+
+fn cmd_policy_suggest(artifacts: &Path, json: bool) -> Result<()> {
+    let suggestions = match ptybox::policy::infer::suggest_policy(artifacts) {
+        Ok(suggestions) => suggestions,
+        Err(err) => return emit_result(json, Err(err)),
+    };
+    let output = artifacts.join(ptybox::policy::infer::SUGGESTIONS_FILE);
+    let data = ptybox::artifacts::to_canonical_json(&suggestions).into_diagnostic()?;
+    std::fs::write(&output, data).into_diagnostic()?;
+    if json {
+        return emit_json(&suggestions);
+    }
+    for change in &suggestions.changes {
+        if change.replaced_by.is_empty() {
+            println!("- {} {}: {}", change.field, change.value, change.reason);
+        } else {
+            println!(
+                "~ {} {} -> {}: {}",
+                change.field,
+                change.value,
+                change.replaced_by.join(", "),
+                change.reason
+            );
+        }
+    }
+    for attempt in &suggestions.network_attempts {
+        println!("! network attempt: {attempt}");
+    }
+    for note in &suggestions.notes {
+        eprintln!("note: {note}");
+    }
+    eprintln!(
+        "{} suggested change(s) written to {}; review them before use",
+        suggestions.changes.len(),
+        output.display()
+    );
+    Ok(())
+}
+
+fn cmd_fuzz(
+    config: &ptybox::fuzz::FuzzConfig,
+    output: Option<&Path>,
+    overwrite: bool,
+    json: bool,
+) -> Result<()> {
+    if let Some(output) = output {
+        if output.exists() && !overwrite {
+            return emit_cli_error(json, "output file exists (use --overwrite to replace it)");
+        }
+    }
+    if !json {
+        eprintln!(
+            "fuzzing with seed {} ({} iterations of {} actions)",
+            config.seed, config.iterations, config.steps
+        );
+    }
+    let report = match ptybox::fuzz::fuzz(config) {
+        Ok(report) => report,
+        Err(err) => return emit_result(json, Err(err)),
+    };
+    if let Some(failure) = &report.failure {
+        let mut scenario = serde_json::to_string_pretty(&failure.scenario).into_diagnostic()?;
+        scenario.push('\n');
+        match output {
+            Some(output) => std::fs::write(output, scenario).into_diagnostic()?,
+            None if !json => print!("{scenario}"),
+            None => {}
+        }
+    }
+    if json {
+        emit_json(&report)?;
+    } else {
+        eprintln!(
+            "{} iterations, {} actions, {} clean exits",
+            report.iterations, report.actions, report.clean_exits
+        );
+    }
+    let Some(failure) = &report.failure else {
+        return Ok(());
+    };
+    if !json {
+        eprintln!(
+            "iteration {} (seed {}) failed: {}: {}",
+            failure.iteration, failure.seed, failure.error.code, failure.error.message
+        );
+        eprintln!(
+            "minimized {} actions to {} in {} runs",
+            failure.original_steps, failure.minimized_steps, failure.minimization_runs
+        );
+        if let Some(output) = output {
+            eprintln!("reproduction scenario written to {}", output.display());
+        }
+    }
+    std::process::exit(exit_code_for_error_code(&failure.error.code));
+}
+
+/// Seed from the clock, for runs without `--seed`.
+fn random_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| {
+            elapsed
+                .as_secs()
+                .wrapping_mul(1_000_000_007)
+                .wrapping_add(u64::from(elapsed.subsec_nanos()))
+        })
+}
+
+fn cmd_artifacts_reformat(artifacts: &Path, json: bool) -> Result<()> {
+    let report = match ptybox::artifacts::reformat_artifacts(artifacts) {
+        Ok(report) => report,
+        Err(err) => return emit_result(json, Err(err)),
+    };
+    if json {
+        return emit_json(&report);
+    }
+    eprintln!(
+        "reformatted {} files, {} already canonical",
+        report.reformatted.len(),
+        report.unchanged
+    );
+    Ok(())
+}
+
+fn cmd_artifacts_recover(artifacts: &Path, json: bool) -> Result<()> {
+    let run = match ptybox::artifacts::recover_run(artifacts) {
+        Ok(run) => run,
+        Err(err) => return emit_result(json, Err(err)),
+    };
+    if json {
+        return emit_json(&run);
+    }
+    eprintln!(
+        "recovered run {} as {:?} with {} finished steps -> {}",
+        run.run_id,
+        run.status,
+        run.steps.as_ref().map_or(0, Vec::len),
+        artifacts.join("run.json").display()
+    );
+    Ok(())
+}
+
+fn cmd_artifacts_quota(root: &Path, quota: Option<ArtifactsQuota>, json: bool) -> Result<()> {
+    let status = match ptybox::artifacts::set_artifacts_quota(root, quota) {
+        Ok(status) => status,
+        Err(err) => return emit_result(json, Err(err)),
+    };
+    if json {
+        return emit_json(&status);
+    }
+    eprintln!("{}", format_artifacts_usage(&status));
+    Ok(())
+}
+
+fn cmd_artifacts_status(root: &Path, json: bool) -> Result<()> {
+    let status = match ptybox::artifacts::artifacts_status(root) {
+        Ok(status) => status,
+        Err(err) => return emit_result(json, Err(err)),
+    };
+    if json {
+        return emit_json(&status);
+    }
+    for run in &status.runs {
+        let state = match run.state {
+            QuotaRunState::Active => "active",
+            QuotaRunState::Completed => "completed",
+        };
+        let run_status = match run.status {
+            Some(RunStatus::Passed) => "passed",
+            Some(RunStatus::Failed) => "failed",
+            Some(RunStatus::Errored) => "errored",
+            Some(RunStatus::Canceled) => "canceled",
+            None => "-",
+        };
+        println!(
+            "{}  {state}  {run_status}  {} bytes  {}",
+            run.run_id, run.bytes, run.dir
+        );
+    }
+    eprintln!("{}", format_artifacts_usage(&status));
+    Ok(())
+}
+
+fn format_artifacts_usage(status: &ArtifactsStatus) -> String {
+    let usage = match &status.quota {
+        Some(quota) => format!("{} of {} bytes used", status.used_bytes, quota.max_bytes),
+        None => format!("{} bytes used, no quota", status.used_bytes),
+    };
+    format!(
+        "{usage}: {} active, {} completed ({} evictable); {} runs ({} bytes) evicted so far",
+        status.active_runs,
+        status.completed_runs,
+        status.evictable_runs,
+        status.evicted_runs,
+        status.evicted_bytes
+    )
+}
+
+fn cmd_bundle(
+    artifacts: &Path,
+    out: &Path,
+    options: &ptybox::artifacts::BundleOptions,
+    json: bool,
+) -> Result<()> {
+    let report = match ptybox::artifacts::bundle_artifacts(artifacts, out, options) {
+        Ok(report) => report,
+        Err(err) => return emit_result(json, Err(err)),
+    };
+    if json {
+        return emit_json(&report);
+    }
+    eprintln!(
+        "bundled {} files ({} bytes, {} redactions{}) -> {}",
+        report.files.len(),
+        report.bytes,
+        report.redactions,
+        report
+            .failing_step
+            .as_ref()
+            .map_or_else(String::new, |step| format!(", failing step '{step}'")),
+        report.output
+    );
     Ok(())
 }
 
 /// Handle the trace command.
-fn cmd_trace(artifacts: PathBuf, output: Option<PathBuf>) -> Result<()> {
-    let output_path = output.unwrap_or_else(|| PathBuf::from("trace.html"));
-    trace::generate_trace(&artifacts, &output_path)?;
+fn cmd_trace(
+    artifacts: PathBuf,
+    output: Option<PathBuf>,
+    format: trace::TraceFormat,
+    identity: Option<PathBuf>,
+    age_binary: Option<PathBuf>,
+) -> Result<()> {
+    let output_path = output.unwrap_or_else(|| PathBuf::from(format.default_output()));
+    let decryptor =
+        identity.map(|identity| ptybox::artifacts::ArtifactsDecryptor::new(identity, age_binary));
+    match format {
+        trace::TraceFormat::Html => {
+            trace::generate_trace(&artifacts, &output_path, decryptor.as_ref())?;
+        }
+        trace::TraceFormat::Asciicast => {
+            trace::generate_asciicast(&artifacts, &output_path, decryptor.as_ref())?;
+        }
+    }
     eprintln!("trace written to: {}", output_path.display());
     Ok(())
 }
@@ -839,6 +2731,14 @@ fn emit_result(json: bool, result: Result<ptybox::model::RunResult, RunnerError>
                 emit_json(&run_result)?;
             } else {
                 eprintln!("run completed: {:?}", run_result.status);
+                if let Some(classification) = &run_result.classification {
+                    eprintln!(
+                        "failure: {} ({} confidence): {}",
+                        classification.category.as_str(),
+                        classification.confidence.as_str(),
+                        classification.reason
+                    );
+                }
             }
             match run_result.status {
                 ptybox::model::RunStatus::Passed => Ok(()),
@@ -876,6 +2776,34 @@ fn split_command(mut command: Vec<String>) -> Result<(String, Vec<String>), Runn
     Ok((cmd, command))
 }
 
+#[derive(Copy, Clone, Debug, ValueEnum)]
+#[value(rename_all = "snake_case")]
+enum CompareArg {
+    Snapshots,
+    Transcript,
+    RunResult,
+    Events,
+}
+
+impl CompareArg {
+    /// Build the replay comparison selection; an empty list selects everything.
+    fn to_replay_compare(args: &[Self]) -> ptybox::replay::ReplayCompare {
+        if args.is_empty() {
+            return ptybox::replay::ReplayCompare::default();
+        }
+        let mut compare = ptybox::replay::ReplayCompare::none();
+        for arg in args {
+            match arg {
+                Self::Snapshots => compare.snapshots = true,
+                Self::Transcript => compare.transcript = true,
+                Self::RunResult => compare.run_result = true,
+                Self::Events => compare.events = true,
+            }
+        }
+        compare
+    }
+}
+
 #[derive(Copy, Clone, Debug, ValueEnum)]
 #[value(rename_all = "snake_case")]
 enum NormalizeFilterArg {
@@ -909,6 +2837,12 @@ impl NormalizeFilterArg {
     }
 }
 
+/// CLI flags that enable optional runner features.
+struct RunnerFlags {
+    audit_sandbox: bool,
+    emulator_diff: bool,
+}
+
 /// CLI flags that override policy settings.
 struct PolicyOverrides {
     no_sandbox: bool,
@@ -928,29 +2862,64 @@ fn validate_cwd(cwd: Option<&str>, json: bool) -> Result<()> {
     Ok(())
 }
 
-fn apply_cli_policy_overrides(policy: &mut Policy, overrides: &PolicyOverrides) {
+/// Apply CLI flags (and their `PTYBOX_ACK_UNSAFE_*` environment equivalents)
+/// to `policy`, returning the acknowledgements they supplied for
+/// `acknowledgements.json`.
+fn apply_cli_policy_overrides(
+    policy: &mut Policy,
+    overrides: &PolicyOverrides,
+) -> Vec<Acknowledgement> {
     use ptybox::model::policy::{NetworkPolicy, SandboxMode};
 
+    let sandbox_ack = ack_origin(
+        overrides.ack_unsafe_sandbox,
+        "--ack-unsafe-sandbox",
+        "PTYBOX_ACK_UNSAFE_SANDBOX",
+    );
+    let network_ack = ack_origin(
+        overrides.ack_unsafe_network,
+        "--ack-unsafe-network",
+        "PTYBOX_ACK_UNSAFE_NETWORK",
+    );
+    let write_ack = ack_origin(
+        overrides.ack_unsafe_write,
+        "--ack-unsafe-write",
+        "PTYBOX_ACK_UNSAFE_WRITE",
+    );
+    let mut acknowledgements = Vec::new();
+
     // Handle sandbox mode
     if overrides.no_sandbox {
         // Set disabled with ack if ack_unsafe_sandbox is also set
         policy.sandbox = SandboxMode::Disabled {
-            ack: overrides.ack_unsafe_sandbox,
+            ack: sandbox_ack.is_some(),
         };
-    } else if overrides.ack_unsafe_sandbox {
+    } else if sandbox_ack.is_some() {
         // If already disabled, update the ack
         if let SandboxMode::Disabled { ref mut ack } = policy.sandbox {
             *ack = true;
         }
     }
+    if let Some((source, origin)) = &sandbox_ack {
+        let origin = if overrides.no_sandbox && *source == AckSource::Cli {
+            format!("--no-sandbox {origin}")
+        } else {
+            origin.clone()
+        };
+        acknowledgements.push(acknowledgement(
+            UnsafeOption::SandboxDisabled,
+            *source,
+            origin,
+        ));
+    }
 
     // Handle network policy
     if overrides.enable_network {
         // Set enabled with ack if ack_unsafe_network is also set
         policy.network = NetworkPolicy::Enabled {
-            ack: overrides.ack_unsafe_network,
+            ack: network_ack.is_some(),
         };
-    } else if overrides.ack_unsafe_network {
+    } else if network_ack.is_some() {
         // If already enabled, update the ack
         if let NetworkPolicy::Enabled { ref mut ack } = policy.network {
             *ack = true;
@@ -958,17 +2927,53 @@ fn apply_cli_policy_overrides(policy: &mut Policy, overrides: &PolicyOverrides)
     }
 
     // Handle network enforcement ack (for unenforced network when sandbox disabled)
-    if overrides.ack_unsafe_network {
+    if let Some((source, origin)) = &network_ack {
         policy.network_enforcement.unenforced_ack = true;
+        let enabled_origin = if overrides.enable_network && *source == AckSource::Cli {
+            format!("--enable-network {origin}")
+        } else {
+            origin.clone()
+        };
+        acknowledgements.push(acknowledgement(
+            UnsafeOption::NetworkEnabled,
+            *source,
+            enabled_origin,
+        ));
+        acknowledgements.push(acknowledgement(
+            UnsafeOption::NetworkUnenforced,
+            *source,
+            origin.clone(),
+        ));
     }
 
     // Handle filesystem write acknowledgement
-    if overrides.ack_unsafe_write {
+    if let Some((source, origin)) = write_ack {
         policy.fs.write_ack = true;
+        acknowledgements.push(acknowledgement(UnsafeOption::FsWrite, source, origin));
     }
     if overrides.strict_write {
         policy.fs.strict_write = true;
     }
+    acknowledgements
+}
+
+/// Where an `--ack-unsafe-*` acknowledgement came from: the flag, or else its
+/// environment variable set to `1` or `true`.
+fn ack_origin(flag_set: bool, flag: &str, env_var: &str) -> Option<(AckSource, String)> {
+    if flag_set {
+        return Some((AckSource::Cli, flag.to_string()));
+    }
+    let value = std::env::var(env_var).ok()?;
+    matches!(value.as_str(), "1" | "true").then(|| (AckSource::Env, format!("{env_var}={value}")))
+}
+
+fn acknowledgement(option: UnsafeOption, source: AckSource, origin: String) -> Acknowledgement {
+    Acknowledgement {
+        option,
+        source,
+        origin,
+        recorded_at_ms: ptybox::policy::acknowledgements::now_ms(),
+    }
 }
 
 fn emit_explanation(json: bool, explanation: &ptybox::policy::PolicyExplanation) -> Result<()> {
@@ -986,7 +2991,7 @@ fn emit_explanation(json: bool, explanation: &ptybox::policy::PolicyExplanation)
 }
 
 fn exit_code_for_error_code(code: &str) -> i32 {
-    ptybox::runner::ErrorCode::parse(code).map_or(1, |c| c.exit_code())
+    ptybox::runner::ErrorCode::parse(code).map_or(1, exit_codes::exit_code)
 }
 
 fn emit_json<T: Serialize>(value: &T) -> Result<()> {
@@ -1000,7 +3005,7 @@ fn emit_cli_error(json: bool, message: &str) -> Result<()> {
 }
 
 fn exit_code_for_error(err: &RunnerError) -> i32 {
-    err.exit_code()
+    exit_codes::exit_code(err.code)
 }
 
 // =============================================================================
@@ -1334,6 +3339,110 @@ fn cmd_serve(
     }
 }
 
+/// Handle `serve --queue`: run the scenario queue runner until drained.
+fn cmd_serve_queue(
+    queue_dir: PathBuf,
+    jobs: u16,
+    status_addr: Option<std::net::SocketAddr>,
+    token_sha256_file: Option<&Path>,
+    artifacts: Option<PathBuf>,
+    matrix: Vec<DeterminismPolicy>,
+) -> Result<()> {
+    let token_sha256 = match token_sha256_file.map(std::fs::read_to_string).transpose() {
+        Ok(digest) => digest.map(|digest| digest.trim().to_string()),
+        Err(err) => {
+            eprintln!("error: failed to read token hash file: {err}");
+            std::process::exit(exit_code_for_error_code("E_IO"));
+        }
+    };
+    let shutdown = Arc::new(AtomicBool::new(false));
+    for signal in [signal_hook::consts::SIGTERM, signal_hook::consts::SIGINT] {
+        signal_hook::flag::register(signal, Arc::clone(&shutdown)).into_diagnostic()?;
+    }
+
+    let config = ptybox::serve::queue::QueueConfig {
+        queue_dir,
+        concurrency: usize::from(jobs),
+        status_addr,
+        token_sha256,
+        artifacts_root: artifacts,
+        matrix,
+        poll_interval: std::time::Duration::from_millis(200),
+        shutdown,
+        ready_output: Box::new(std::io::stdout()),
+    };
+
+    match ptybox::serve::queue::run_queue(config) {
+        Ok(()) => Ok(()),
+        Err(err) => {
+            eprintln!("error: {}", err.message);
+            std::process::exit(exit_code_for_error(&err));
+        }
+    }
+}
+
+/// Handle `serve --listen`: run the multi-tenant driver gateway until
+/// SIGTERM/SIGINT.
+fn cmd_serve_gateway(
+    socket_path: PathBuf,
+    auth_config: &Path,
+    max_connections: usize,
+    max_sessions: usize,
+) -> Result<()> {
+    let shutdown = Arc::new(AtomicBool::new(false));
+    for signal in [signal_hook::consts::SIGTERM, signal_hook::consts::SIGINT] {
+        signal_hook::flag::register(signal, Arc::clone(&shutdown)).into_diagnostic()?;
+    }
+
+    let result = ptybox::serve::gateway::load_gateway_auth_config(auth_config).and_then(|auth| {
+        ptybox::serve::gateway::run_gateway(ptybox::serve::gateway::GatewayConfig {
+            socket_path,
+            auth,
+            shutdown,
+            ready_output: Box::new(std::io::stdout()),
+            max_connections,
+            max_sessions,
+        })
+    });
+    match result {
+        Ok(()) => Ok(()),
+        Err(err) => {
+            eprintln!("error: {}", err.message);
+            std::process::exit(exit_code_for_error(&err));
+        }
+    }
+}
+
+/// Handle `serve --pool`: warm the pool and lease its children until
+/// SIGTERM/SIGINT.
+fn cmd_serve_pool(
+    socket_path: PathBuf,
+    policy_path: &Path,
+    mut pool: ptybox::serve::pool::SessionPoolConfig,
+) -> Result<()> {
+    let shutdown = Arc::new(AtomicBool::new(false));
+    for signal in [signal_hook::consts::SIGTERM, signal_hook::consts::SIGINT] {
+        signal_hook::flag::register(signal, Arc::clone(&shutdown)).into_diagnostic()?;
+    }
+
+    let result = ptybox::scenario::load_policy_file(policy_path).and_then(|policy| {
+        pool.policy = policy;
+        ptybox::serve::pool::run_pool(ptybox::serve::pool::PoolConfig {
+            socket_path,
+            pool,
+            shutdown,
+            output: Box::new(std::io::stdout()),
+        })
+    });
+    match result {
+        Ok(()) => Ok(()),
+        Err(err) => {
+            eprintln!("error: {}", err.message);
+            std::process::exit(exit_code_for_error(&err));
+        }
+    }
+}
+
 /// Common handler for session commands that return a screen.
 fn handle_session_response(
     session_id: &str,
@@ -1394,10 +3503,11 @@ fn print_protocol_help_text(help: &protocol_help::ProtocolHelp) {
                     .iter()
                     .map(|(k, v)| format!("{k}: {v}"))
                     .collect();
+                let tag = protocol_help::stability_tag(variant.stability);
                 if payload_desc.is_empty() {
-                    println!("  {name}: {{}}");
+                    println!("  {name}{tag}: {{}}");
                 } else {
-                    println!("  {name}: {{{}}}", payload_desc.join(", "));
+                    println!("  {name}{tag}: {{{}}}", payload_desc.join(", "));
                 }
             }
         }
@@ -1413,10 +3523,11 @@ fn print_protocol_help_text(help: &protocol_help::ProtocolHelp) {
                     .iter()
                     .map(|(k, v)| format!("{k}: {v}"))
                     .collect();
+                let tag = protocol_help::stability_tag(variant.stability);
                 if payload_desc.is_empty() {
-                    println!("  {name}: {{}}");
+                    println!("  {name}{tag}: {{}}");
                 } else {
-                    println!("  {name}: {{{}}}", payload_desc.join(", "));
+                    println!("  {name}{tag}: {{{}}}", payload_desc.join(", "));
                 }
             }
         }
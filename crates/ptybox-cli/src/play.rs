@@ -0,0 +1,302 @@
+//! `ptybox play`: re-render a recorded session into the terminal.
+//!
+//! Output comes from the observation logs (`events.jsonl`, plus the per-step
+//! logs of the `per_step` layout): each observation's `transcript_delta` is
+//! written to stdout at its `timestamp_ms`, scaled by the playback speed.
+//! Timing is as fine as the recording's observations, so output gathered by
+//! one wait is played when the wait ended.
+//!
+//! When stdin and stdout are terminals, keys control playback: space pauses,
+//! `+`/`-` change speed, `←`/`→` seek 5s, and `q` quits. Seeking backwards
+//! clears the screen and re-renders from the start instantly.
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+use miette::{IntoDiagnostic, Result, WrapErr};
+use ptybox::artifacts::{event_log_paths, read_artifact, ArtifactsDecryptor};
+use serde::Deserialize;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Slowest and fastest accepted `--speed`.
+const SPEED_RANGE: (f64, f64) = (0.1, 100.0);
+/// Distance moved by one seek key.
+const SEEK_STEP_MS: u64 = 5_000;
+/// Clear the screen and scrollback, reset attributes, and home the cursor.
+const CLEAR: &str = "\x1b[0m\x1b[2J\x1b[3J\x1b[H";
+/// Reset attributes, show the cursor, and leave the alternate screen, in
+/// case the recording stopped with the app still drawing.
+const RESTORE: &str = "\x1b[0m\x1b[?25h\x1b[?1049l";
+
+/// Playback settings from the command line.
+pub struct PlayOptions {
+    /// Playback speed multiplier.
+    pub speed: f64,
+    /// Start from this recording time, rendering earlier output instantly.
+    pub from_ms: u64,
+    /// Longest pause played between two outputs.
+    pub max_idle_ms: Option<u64>,
+}
+
+/// Parse `--speed`: a multiplier between 0.1 and 100.
+pub fn parse_speed(value: &str) -> std::result::Result<f64, String> {
+    let speed: f64 = value
+        .parse()
+        .map_err(|_| format!("invalid speed '{value}'"))?;
+    if (SPEED_RANGE.0..=SPEED_RANGE.1).contains(&speed) {
+        Ok(speed)
+    } else {
+        Err(format!(
+            "speed must be between {} and {}",
+            SPEED_RANGE.0, SPEED_RANGE.1
+        ))
+    }
+}
+
+/// Play the session recorded in `artifacts_dir` to stdout.
+///
+/// Encrypted event logs are decrypted with the age `identity` file, using
+/// the `age` binary at `age_binary` (default `/usr/bin/age`).
+pub fn cmd_play(
+    artifacts_dir: &Path,
+    identity: Option<&Path>,
+    age_binary: Option<PathBuf>,
+    options: &PlayOptions,
+) -> Result<()> {
+    let decryptor = identity.map(|identity| ArtifactsDecryptor::new(identity, age_binary));
+    let frames = load_frames(artifacts_dir, decryptor.as_ref())?;
+    if frames.is_empty() {
+        miette::bail!(
+            "no recorded output in {}; play needs events.jsonl",
+            artifacts_dir.display()
+        );
+    }
+
+    let stdout = io::stdout();
+    let interactive = io::stdin().is_terminal() && stdout.is_terminal();
+    let mut player = Player::new(&frames, options, stdout.lock());
+    player.skip_to(options.from_ms)?;
+    if !interactive {
+        return player.run(&mut |wait| {
+            std::thread::sleep(wait);
+            Ok(None)
+        });
+    }
+
+    enable_raw_mode().into_diagnostic()?;
+    let result = player.run(&mut read_control);
+    let restore = player
+        .out
+        .write_all(RESTORE.as_bytes())
+        .and_then(|()| player.out.flush())
+        .into_diagnostic();
+    disable_raw_mode().into_diagnostic()?;
+    result.and(restore)
+}
+
+/// Output recorded by one observation.
+struct Frame {
+    at_ms: u64,
+    output: String,
+}
+
+/// The fields of an observation record that playback reads.
+#[derive(Deserialize)]
+struct RecordedOutput {
+    timestamp_ms: u64,
+    #[serde(default)]
+    transcript_delta: Option<String>,
+}
+
+/// Observations with output from every event log, in recording order.
+fn load_frames(artifacts_dir: &Path, decryptor: Option<&ArtifactsDecryptor>) -> Result<Vec<Frame>> {
+    let mut frames = Vec::new();
+    for path in event_log_paths(artifacts_dir)? {
+        let data = read_artifact(&path, decryptor)?;
+        for line in String::from_utf8_lossy(&data).lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record: RecordedOutput = serde_json::from_str(line)
+                .into_diagnostic()
+                .wrap_err_with(|| format!("failed to parse {}", path.display()))?;
+            if let Some(output) = record.transcript_delta.filter(|delta| !delta.is_empty()) {
+                frames.push(Frame {
+                    at_ms: record.timestamp_ms,
+                    output,
+                });
+            }
+        }
+    }
+    frames.sort_by_key(|frame| frame.at_ms);
+    Ok(frames)
+}
+
+/// Key commands during interactive playback.
+enum Control {
+    Quit,
+    TogglePause,
+    Faster,
+    Slower,
+    Back,
+    Forward,
+}
+
+/// Wait up to `wait` for a key; `None` when the wait ran out.
+fn read_control(wait: Duration) -> Result<Option<Control>> {
+    let deadline = Instant::now() + wait;
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if !event::poll(remaining).into_diagnostic()? {
+            return Ok(None);
+        }
+        let Event::Key(key) = event::read().into_diagnostic()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let control = match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => Control::Quit,
+            KeyCode::Char(' ') => Control::TogglePause,
+            KeyCode::Char('+' | '=') | KeyCode::Up => Control::Faster,
+            KeyCode::Char('-') | KeyCode::Down => Control::Slower,
+            KeyCode::Left => Control::Back,
+            KeyCode::Right => Control::Forward,
+            _ => continue,
+        };
+        return Ok(Some(control));
+    }
+}
+
+/// Playback position over a recording.
+struct Player<'a, W: Write> {
+    frames: &'a [Frame],
+    /// Next frame to write.
+    next: usize,
+    /// Recording time already played.
+    position_ms: u64,
+    speed: f64,
+    max_idle_ms: Option<u64>,
+    paused: bool,
+    out: W,
+}
+
+impl<'a, W: Write> Player<'a, W> {
+    fn new(frames: &'a [Frame], options: &PlayOptions, out: W) -> Self {
+        Self {
+            frames,
+            next: 0,
+            position_ms: 0,
+            speed: options.speed,
+            max_idle_ms: options.max_idle_ms,
+            paused: false,
+            out,
+        }
+    }
+
+    /// Play every frame, waiting between them through `wait_for`, which
+    /// returns early with a key command.
+    fn run(&mut self, wait_for: &mut dyn FnMut(Duration) -> Result<Option<Control>>) -> Result<()> {
+        self.flush()?;
+        while let Some(frame) = self.frames.get(self.next) {
+            if self.paused {
+                if let Some(control) = wait_for(Duration::from_secs(3600))? {
+                    if !self.apply(control)? {
+                        return Ok(());
+                    }
+                }
+                continue;
+            }
+            let mut gap_ms = frame.at_ms.saturating_sub(self.position_ms);
+            if let Some(max_idle_ms) = self.max_idle_ms.filter(|max| gap_ms > *max) {
+                self.position_ms = frame.at_ms - max_idle_ms;
+                gap_ms = max_idle_ms;
+            }
+            let started = Instant::now();
+            match wait_for(self.real_time(gap_ms))? {
+                None => {
+                    self.write_frame()?;
+                    self.flush()?;
+                }
+                Some(control) => {
+                    self.advance(started.elapsed(), frame.at_ms);
+                    if !self.apply(control)? {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Handle a key command; `false` stops playback.
+    fn apply(&mut self, control: Control) -> Result<bool> {
+        match control {
+            Control::Quit => return Ok(false),
+            Control::TogglePause => self.paused = !self.paused,
+            Control::Faster => self.speed = (self.speed * 2.0).min(SPEED_RANGE.1),
+            Control::Slower => self.speed = (self.speed / 2.0).max(SPEED_RANGE.0),
+            Control::Back => {
+                let target = self.position_ms.saturating_sub(SEEK_STEP_MS);
+                self.out.write_all(CLEAR.as_bytes()).into_diagnostic()?;
+                self.next = 0;
+                self.position_ms = 0;
+                self.skip_to(target)?;
+            }
+            Control::Forward => self.skip_to(self.position_ms.saturating_add(SEEK_STEP_MS))?,
+        }
+        self.flush()?;
+        Ok(true)
+    }
+
+    /// Render all output up to `target_ms` at once.
+    fn skip_to(&mut self, target_ms: u64) -> Result<()> {
+        while self
+            .frames
+            .get(self.next)
+            .is_some_and(|frame| frame.at_ms <= target_ms)
+        {
+            self.write_frame()?;
+        }
+        self.position_ms = self.position_ms.max(target_ms);
+        Ok(())
+    }
+
+    fn write_frame(&mut self) -> Result<()> {
+        let Some(frame) = self.frames.get(self.next) else {
+            return Ok(());
+        };
+        self.out
+            .write_all(frame.output.as_bytes())
+            .into_diagnostic()?;
+        self.position_ms = self.position_ms.max(frame.at_ms);
+        self.next += 1;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.out.flush().into_diagnostic()
+    }
+
+    /// Move the position forward by `elapsed` real time, short of `limit_ms`.
+    fn advance(&mut self, elapsed: Duration, limit_ms: u64) {
+        // Recording times are far below 2^52 ms, so the f64 round trip is exact
+        #[allow(
+            clippy::cast_precision_loss,
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss
+        )]
+        let played = (elapsed.as_secs_f64() * 1000.0 * self.speed) as u64;
+        self.position_ms = self.position_ms.saturating_add(played).min(limit_ms);
+    }
+
+    /// Real time to wait for `gap_ms` of recording at the current speed.
+    fn real_time(&self, gap_ms: u64) -> Duration {
+        // Gaps are far below 2^52 ms, so the conversion is exact
+        #[allow(clippy::cast_precision_loss)]
+        let seconds = gap_ms as f64 / 1000.0 / self.speed;
+        Duration::from_secs_f64(seconds)
+    }
+}
@@ -1,10 +1,15 @@
-//! Verbose progress output using indicatif.
+//! Progress output on stderr using indicatif: one line per step
+//! ([`VerboseProgress`]) or a single status line ([`CompactProgress`]).
 
-use indicatif::{ProgressBar, ProgressStyle};
+use indicatif::{ProgressBar, ProgressState, ProgressStyle};
 use ptybox::model::StepStatus;
 use ptybox::runner::{ProgressCallback, ProgressEvent};
-use std::io::Write;
-use std::sync::Mutex;
+use std::fmt;
+use std::io::{IsTerminal, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 /// Progress callback that outputs step-by-step progress to stderr.
 pub struct VerboseProgress {
@@ -64,6 +69,7 @@ impl ProgressCallback for VerboseProgress {
                 status,
                 duration_ms,
                 assertions,
+                ..
             } => {
                 if let Ok(mut spinner) = self.spinner.lock() {
                     if let Some(pb) = spinner.take() {
@@ -111,3 +117,209 @@ impl ProgressCallback for VerboseProgress {
         }
     }
 }
+
+/// How often a summary line is printed when stderr is not a terminal.
+const SUMMARY_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Most recent indexed runs of a scenario whose durations feed the ETA.
+const ETA_HISTORY_RUNS: usize = 5;
+
+/// Marker in [`CompactProgress::expected_ms`] for an unknown run duration.
+const UNKNOWN_MS: u64 = u64::MAX;
+
+/// Median duration of the last [`ETA_HISTORY_RUNS`] runs of `scenario`
+/// recorded in the run index at `root`, or `None` without history.
+pub fn expected_duration_ms(root: &Path, scenario: &str) -> Option<u64> {
+    let entries = ptybox::artifacts::read_run_index(root).ok()?;
+    let mut durations: Vec<u64> = entries
+        .iter()
+        .rev()
+        .filter(|entry| entry.scenario.as_deref() == Some(scenario))
+        .filter_map(|entry| entry.duration_ms)
+        .take(ETA_HISTORY_RUNS)
+        .collect();
+    durations.sort_unstable();
+    durations.get(durations.len() / 2).copied()
+}
+
+/// Progress callback that keeps a single updating status line on stderr:
+/// the current step, pass/fail counts, elapsed time, and an ETA.
+///
+/// The ETA comes from prior runs in the run index when available, and from
+/// the average duration of the steps completed so far otherwise. When stderr
+/// is not a terminal, a summary line is printed at most every
+/// [`SUMMARY_INTERVAL`] instead. Failed steps are always printed.
+pub struct CompactProgress {
+    state: Mutex<CompactState>,
+    expected_ms: Arc<AtomicU64>,
+    prior_ms: Option<u64>,
+    bar: Option<ProgressBar>,
+}
+
+#[derive(Default)]
+struct CompactState {
+    total_steps: usize,
+    current: Option<(usize, String)>,
+    passed: usize,
+    failed: usize,
+    completed_ms: u64,
+    started: Option<Instant>,
+    last_summary: Option<Instant>,
+}
+
+impl CompactProgress {
+    /// Create a compact progress callback; `prior_ms` is the expected run
+    /// duration from earlier runs (see [`expected_duration_ms`]).
+    pub fn new(prior_ms: Option<u64>) -> Self {
+        let expected_ms = Arc::new(AtomicU64::new(prior_ms.unwrap_or(UNKNOWN_MS)));
+        let bar = std::io::stderr().is_terminal().then(|| {
+            let expected = Arc::clone(&expected_ms);
+            let style = ProgressStyle::default_spinner()
+                .template("{spinner:.cyan} {msg} {eta_prior}")
+                .unwrap_or_else(|_| ProgressStyle::default_spinner())
+                .with_key(
+                    "eta_prior",
+                    move |state: &ProgressState, out: &mut dyn fmt::Write| {
+                        let elapsed = duration_ms(state.elapsed());
+                        let _ = out.write_str(&timing(elapsed, expected.load(Ordering::Relaxed)));
+                    },
+                );
+            let bar = ProgressBar::new_spinner().with_style(style);
+            bar.enable_steady_tick(Duration::from_millis(100));
+            bar
+        });
+        Self {
+            state: Mutex::new(CompactState::default()),
+            expected_ms,
+            prior_ms,
+            bar,
+        }
+    }
+
+    /// Refresh the status line, or print a summary when the last one is
+    /// older than [`SUMMARY_INTERVAL`] (always when `force`).
+    fn report(&self, state: &mut CompactState, force: bool) {
+        let message = status_message(state);
+        if let Some(bar) = &self.bar {
+            bar.set_message(message);
+            return;
+        }
+        let now = Instant::now();
+        let due = state
+            .last_summary
+            .map_or(true, |last| now.duration_since(last) >= SUMMARY_INTERVAL);
+        if force || due {
+            state.last_summary = Some(now);
+            let elapsed = state
+                .started
+                .map_or(0, |started| duration_ms(started.elapsed()));
+            let timing = timing(elapsed, self.expected_ms.load(Ordering::Relaxed));
+            let _ = writeln!(std::io::stderr(), "progress: {message} {timing}");
+        }
+    }
+
+    fn print_line(&self, line: &str) {
+        match &self.bar {
+            Some(bar) => bar.println(line),
+            None => {
+                let _ = writeln!(std::io::stderr(), "{line}");
+            }
+        }
+    }
+}
+
+impl ProgressCallback for CompactProgress {
+    fn on_progress(&self, event: &ProgressEvent) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        match event {
+            ProgressEvent::RunStarted { total_steps, .. } => {
+                state.total_steps = *total_steps;
+                state.started = Some(Instant::now());
+                self.report(&mut state, true);
+            }
+            ProgressEvent::StepStarted {
+                step_index, name, ..
+            } => {
+                state.current = Some((*step_index, name.clone()));
+                self.report(&mut state, false);
+            }
+            ProgressEvent::StepCompleted {
+                name,
+                status,
+                duration_ms,
+                ..
+            } => {
+                if *status == StepStatus::Passed {
+                    state.passed += 1;
+                } else {
+                    state.failed += 1;
+                    self.print_line(&format!("  ✗ {name} ({duration_ms}ms)"));
+                }
+                state.completed_ms = state.completed_ms.saturating_add(*duration_ms);
+                if self.prior_ms.is_none() {
+                    let done = u64::try_from(state.passed + state.failed).unwrap_or(u64::MAX);
+                    let total = u64::try_from(state.total_steps).unwrap_or(u64::MAX);
+                    let estimate = state.completed_ms / done.max(1) * total.max(done);
+                    self.expected_ms.store(estimate, Ordering::Relaxed);
+                }
+                self.report(&mut state, false);
+            }
+            ProgressEvent::RunCompleted {
+                success,
+                duration_ms,
+                ..
+            } => {
+                if let Some(bar) = &self.bar {
+                    bar.finish_and_clear();
+                }
+                let outcome = if *success { "passed" } else { "failed" };
+                let _ = writeln!(
+                    std::io::stderr(),
+                    "run {outcome}: {} passed, {} failed in {}",
+                    state.passed,
+                    state.failed,
+                    format_duration(*duration_ms)
+                );
+            }
+        }
+    }
+}
+
+/// `[index/total] name  N passed, M failed`.
+fn status_message(state: &CompactState) -> String {
+    let step = state
+        .current
+        .as_ref()
+        .map_or_else(String::new, |(index, name)| {
+            format!("[{index}/{}] {name}  ", state.total_steps)
+        });
+    format!("{step}{} passed, {} failed", state.passed, state.failed)
+}
+
+/// `12s elapsed, ETA 30s`; the ETA is `?` until a duration is expected.
+fn timing(elapsed_ms: u64, expected_ms: u64) -> String {
+    let eta = if expected_ms == UNKNOWN_MS {
+        "?".to_string()
+    } else {
+        format_duration(expected_ms.saturating_sub(elapsed_ms))
+    };
+    format!("{} elapsed, ETA {eta}", format_duration(elapsed_ms))
+}
+
+/// `850ms`, `12s`, or `3m05s`.
+fn format_duration(ms: u64) -> String {
+    let seconds = ms / 1000;
+    if ms < 1000 {
+        format!("{ms}ms")
+    } else if seconds < 60 {
+        format!("{seconds}s")
+    } else {
+        format!("{}m{:02}s", seconds / 60, seconds % 60)
+    }
+}
+
+fn duration_ms(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
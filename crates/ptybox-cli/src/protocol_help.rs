@@ -3,9 +3,16 @@
 //! Generates structured documentation of the ptybox protocol,
 //! including schemas, examples, and error codes.
 
+use ptybox::driver::MAX_DRIVER_HISTORY;
+use ptybox::model::driver::{
+    DriverEvent, DriverRequestV2, DriverResponseV2, DEFAULT_DRIVER_EVENT_POLL_MS,
+    DEFAULT_DRIVER_EVENT_WINDOW, MAX_DRIVER_EVENT_WINDOW, MIN_DRIVER_EVENT_POLL_MS,
+};
 use ptybox::model::{
+    Action, ActionType, Assertion, Condition, Observation, ScreenSnapshot, Stability, Watcher,
     POLICY_VERSION, PROTOCOL_VERSION, RUN_RESULT_VERSION, SCENARIO_VERSION, SNAPSHOT_VERSION,
 };
+use ptybox::predicate::PREDICATES;
 use serde::Serialize;
 use std::collections::BTreeMap;
 
@@ -56,12 +63,20 @@ pub struct SchemaHelp {
     pub fields: Option<BTreeMap<String, String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub types: Option<BTreeMap<String, TypeVariant>>,
+    /// Sourced from the model type's `STABILITY` constant.
+    pub stability: Stability,
 }
 
 /// A variant of a union type.
 #[derive(Debug, Serialize)]
 pub struct TypeVariant {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
     pub payload: BTreeMap<String, String>,
+    /// Sourced from [`ActionType::stability`] or the predicate's `PredicateSpec`.
+    pub stability: Stability,
 }
 
 /// Documentation for an error code.
@@ -159,6 +174,18 @@ fn generate_commands() -> BTreeMap<String, CommandHelp> {
         },
     );
 
+    commands.insert(
+        "protocol-vectors".to_string(),
+        CommandHelp {
+            description:
+                "Write canonical request/response pairs, error payloads, and schemas for client implementers."
+                    .to_string(),
+            usage: "ptybox protocol-vectors --out <dir> [--json]".to_string(),
+            required_flags: Some(vec!["--out".to_string()]),
+            output: "Vector files under <dir>; manifest JSON on stdout with --json".to_string(),
+        },
+    );
+
     commands
 }
 
@@ -185,12 +212,28 @@ fn generate_schemas() -> BTreeMap<String, SchemaHelp> {
         "u64 | null: per-action timeout in ms (default: 200ms, 5000ms for wait actions)"
             .to_string(),
     );
+    driver_input_fields.insert(
+        "expected_screen_hash".to_string(),
+        "string | null: reject the action with E_STALE_OBSERVATION unless the current screen_hash matches"
+            .to_string(),
+    );
+    driver_input_fields.insert(
+        "coalesce".to_string(),
+        "bool (default false): for wait/observe, answer unchanged=true without an observation or a recorded step when the screen_hash equals the previous response's"
+            .to_string(),
+    );
+    driver_input_fields.insert(
+        "include_image".to_string(),
+        "bool (default false): attach a base64 PNG of the screen to a successful response (render feature; E_PROTOCOL without it)"
+            .to_string(),
+    );
     schemas.insert(
         "DriverRequestV2".to_string(),
         SchemaHelp {
             description: "Input message sent to driver via stdin (NDJSON).".to_string(),
             fields: Some(driver_input_fields),
             types: None,
+            stability: DriverRequestV2::STABILITY,
         },
     );
 
@@ -203,7 +246,8 @@ fn generate_schemas() -> BTreeMap<String, SchemaHelp> {
     driver_response_fields.insert("status".to_string(), "ok | error".to_string());
     driver_response_fields.insert(
         "observation".to_string(),
-        "Observation | null: present when status=ok".to_string(),
+        "Observation | null: present when status=ok, unless unchanged or a history or subscribe query"
+            .to_string(),
     );
     driver_response_fields.insert(
         "error".to_string(),
@@ -213,12 +257,81 @@ fn generate_schemas() -> BTreeMap<String, SchemaHelp> {
         "action_metrics".to_string(),
         "object | null: {sequence, duration_ms}".to_string(),
     );
+    driver_response_fields.insert(
+        "screen_hash".to_string(),
+        "string | null: hash of the observed screen, usable as expected_screen_hash".to_string(),
+    );
+    driver_response_fields.insert(
+        "unchanged".to_string(),
+        "bool (omitted when false): coalesced poll; the screen matches the previous screen_hash"
+            .to_string(),
+    );
+    driver_response_fields.insert(
+        "history".to_string(),
+        "object (history queries only): {entries: [{request_id, action, status, sequence?, error_code?, started_at_ms, ended_at_ms, screen_hash?, observation_timestamp_ms?, unchanged?}], total}"
+            .to_string(),
+    );
+    driver_response_fields.insert(
+        "image".to_string(),
+        "object (include_image requests only): {mime_type, width, height, bytes, data_base64?, omitted?}; data is omitted over 1 MiB"
+            .to_string(),
+    );
+    driver_response_fields.insert(
+        "subscription".to_string(),
+        "object (subscribe requests only): {events, max_unacked, poll_interval_ms, last_event_seq}"
+            .to_string(),
+    );
     schemas.insert(
         "DriverResponseV2".to_string(),
         SchemaHelp {
             description: "Output message emitted by driver for each request.".to_string(),
             fields: Some(driver_response_fields),
             types: None,
+            stability: DriverResponseV2::STABILITY,
+        },
+    );
+
+    let mut driver_event_fields = BTreeMap::new();
+    driver_event_fields.insert("type".to_string(), "\"event\"".to_string());
+    driver_event_fields.insert("protocol_version".to_string(), "u32".to_string());
+    driver_event_fields.insert(
+        "event_seq".to_string(),
+        "number: 1-based; acknowledge with {\"type\":\"ack\",\"event_seq\":N} (no response)"
+            .to_string(),
+    );
+    driver_event_fields.insert(
+        "kind".to_string(),
+        "screen_changed | bell | process_exited | overflow".to_string(),
+    );
+    driver_event_fields.insert(
+        "timestamp_ms".to_string(),
+        "number: ms since run start".to_string(),
+    );
+    driver_event_fields.insert(
+        "screen_hash".to_string(),
+        "string (screen_changed, overflow): current screen hash".to_string(),
+    );
+    driver_event_fields.insert(
+        "bells".to_string(),
+        "number (bell): bells rung since the previous bell event or request".to_string(),
+    );
+    driver_event_fields.insert(
+        "exit_status".to_string(),
+        "ExitStatus (process_exited)".to_string(),
+    );
+    driver_event_fields.insert(
+        "dropped".to_string(),
+        "number (overflow): events dropped while max_unacked events were unacknowledged"
+            .to_string(),
+    );
+    schemas.insert(
+        "DriverEvent".to_string(),
+        SchemaHelp {
+            description: "Unsolicited frame a subscribed driver pushes between responses (NDJSON)."
+                .to_string(),
+            fields: Some(driver_event_fields),
+            types: None,
+            stability: DriverEvent::STABILITY,
         },
     );
 
@@ -228,23 +341,19 @@ fn generate_schemas() -> BTreeMap<String, SchemaHelp> {
     let mut key_payload = BTreeMap::new();
     key_payload.insert(
         "key".to_string(),
-        "string: Enter, F1-F12, arrows/navigation keys, Ctrl+<char>, or single character"
+        "string: Enter, F1-F12, arrows/navigation keys, or single character, with optional Ctrl+/Alt+/Shift+ modifiers"
             .to_string(),
     );
     action_types.insert(
         "key".to_string(),
-        TypeVariant {
-            payload: key_payload,
-        },
+        action_variant(&ActionType::Key, None, key_payload),
     );
 
     let mut text_payload = BTreeMap::new();
     text_payload.insert("text".to_string(), "string: text to type".to_string());
     action_types.insert(
         "text".to_string(),
-        TypeVariant {
-            payload: text_payload,
-        },
+        action_variant(&ActionType::Text, None, text_payload),
     );
 
     let mut resize_payload = BTreeMap::new();
@@ -252,34 +361,129 @@ fn generate_schemas() -> BTreeMap<String, SchemaHelp> {
     resize_payload.insert("cols".to_string(), "u16: terminal width".to_string());
     action_types.insert(
         "resize".to_string(),
-        TypeVariant {
-            payload: resize_payload,
-        },
+        action_variant(&ActionType::Resize, None, resize_payload),
     );
 
     let mut wait_payload = BTreeMap::new();
     wait_payload.insert("condition".to_string(), "Condition object".to_string());
     action_types.insert(
         "wait".to_string(),
-        TypeVariant {
-            payload: wait_payload,
-        },
+        action_variant(&ActionType::Wait, None, wait_payload),
     );
 
     let observe_payload = BTreeMap::new();
     action_types.insert(
         "observe".to_string(),
-        TypeVariant {
-            payload: observe_payload,
-        },
+        action_variant(&ActionType::Observe, None, observe_payload),
     );
 
     let terminate_payload = BTreeMap::new();
     action_types.insert(
         "terminate".to_string(),
-        TypeVariant {
-            payload: terminate_payload,
-        },
+        action_variant(&ActionType::Terminate, None, terminate_payload),
+    );
+
+    let mut raw_payload = BTreeMap::new();
+    raw_payload.insert(
+        "hex".to_string(),
+        "string: bytes as hex (one of hex/base64; requires policy input.allow_raw_bytes)"
+            .to_string(),
+    );
+    raw_payload.insert(
+        "base64".to_string(),
+        "string: bytes as base64 (one of hex/base64)".to_string(),
+    );
+    action_types.insert(
+        "raw".to_string(),
+        action_variant(&ActionType::Raw, None, raw_payload),
+    );
+
+    let mut hold_key_payload = BTreeMap::new();
+    hold_key_payload.insert(
+        "key".to_string(),
+        "string: key to hold (same names as the key action)".to_string(),
+    );
+    hold_key_payload.insert(
+        "duration_ms".to_string(),
+        "number: how long to hold the key (1-60000)".to_string(),
+    );
+    hold_key_payload.insert(
+        "repeat_hz".to_string(),
+        "number (optional): presses per second (1-100, default 30)".to_string(),
+    );
+    action_types.insert(
+        "hold_key".to_string(),
+        action_variant(&ActionType::HoldKey, None, hold_key_payload),
+    );
+
+    let mut termios_payload = BTreeMap::new();
+    for (flag, meaning) in [
+        ("echo", "input is echoed back"),
+        ("icanon", "canonical line-buffered input"),
+        ("isig", "^C, ^\\ and ^Z raise signals"),
+    ] {
+        termios_payload.insert(
+            flag.to_string(),
+            format!("bool (optional): {meaning}; omitted flags are unchanged"),
+        );
+    }
+    action_types.insert(
+        "termios".to_string(),
+        action_variant(&ActionType::Termios, None, termios_payload),
+    );
+
+    let mut signal_payload = BTreeMap::new();
+    signal_payload.insert(
+        "signal".to_string(),
+        "string: signal name (SIGINT, SIGHUP, SIGUSR1, ...) listed in policy.process.allowed_signals"
+            .to_string(),
+    );
+    action_types.insert(
+        "signal".to_string(),
+        action_variant(&ActionType::Signal, None, signal_payload),
+    );
+
+    let mut history_payload = BTreeMap::new();
+    history_payload.insert(
+        "limit".to_string(),
+        format!(
+            "number (optional): most recent entries to return (default and max {MAX_DRIVER_HISTORY} kept)"
+        ),
+    );
+    action_types.insert(
+        "history".to_string(),
+        action_variant(
+            &ActionType::History,
+            Some("Driver only: return the session log instead of an observation; no step is recorded"),
+            history_payload,
+        ),
+    );
+
+    let mut subscribe_payload = BTreeMap::new();
+    subscribe_payload.insert(
+        "events".to_string(),
+        "array (optional): screen_changed, bell, process_exited (default all; [] unsubscribes)"
+            .to_string(),
+    );
+    subscribe_payload.insert(
+        "max_unacked".to_string(),
+        format!(
+            "number (optional): events in flight before the driver waits for an ack (default {DEFAULT_DRIVER_EVENT_WINDOW}, max {MAX_DRIVER_EVENT_WINDOW})"
+        ),
+    );
+    subscribe_payload.insert(
+        "poll_interval_ms".to_string(),
+        format!(
+            "number (optional): how often the idle driver checks for events (default {DEFAULT_DRIVER_EVENT_POLL_MS}, min {MIN_DRIVER_EVENT_POLL_MS})"
+        ),
+    );
+    action_types.insert(
+        "subscribe".to_string(),
+        action_variant(
+            &ActionType::Subscribe,
+            Some("Driver only: push DriverEvent frames between responses; answered with subscription, no step is recorded"),
+            subscribe_payload,
+        ),
     );
 
     schemas.insert(
@@ -288,63 +492,60 @@ fn generate_schemas() -> BTreeMap<String, SchemaHelp> {
             description: "Action to perform on the terminal session.".to_string(),
             fields: None,
             types: Some(action_types),
+            stability: Action::STABILITY,
         },
     );
 
-    // Condition schema
-    let mut condition_types = BTreeMap::new();
-
-    let mut screen_contains_payload = BTreeMap::new();
-    screen_contains_payload.insert(
-        "text".to_string(),
-        "string: substring to find on screen".to_string(),
-    );
-    condition_types.insert(
-        "screen_contains".to_string(),
-        TypeVariant {
-            payload: screen_contains_payload,
+    schemas.insert(
+        "Condition".to_string(),
+        SchemaHelp {
+            description:
+                "Wait condition for the wait action: {type, payload} with any predicate type."
+                    .to_string(),
+            fields: None,
+            types: Some(predicate_types()),
+            stability: Condition::STABILITY,
         },
     );
 
-    let mut screen_matches_payload = BTreeMap::new();
-    screen_matches_payload.insert(
-        "pattern".to_string(),
-        "string: Rust regex pattern".to_string(),
-    );
-    condition_types.insert(
-        "screen_matches".to_string(),
-        TypeVariant {
-            payload: screen_matches_payload,
-        },
+    let mut assertion_fields = BTreeMap::new();
+    assertion_fields.insert(
+        "type".to_string(),
+        "string: predicate type (see Condition types)".to_string(),
     );
-
-    let mut cursor_at_payload = BTreeMap::new();
-    cursor_at_payload.insert("row".to_string(), "u16: cursor row (0-based)".to_string());
-    cursor_at_payload.insert(
-        "col".to_string(),
-        "u16: cursor column (0-based)".to_string(),
+    assertion_fields.insert(
+        "payload".to_string(),
+        "object: predicate payload".to_string(),
     );
-    condition_types.insert(
-        "cursor_at".to_string(),
-        TypeVariant {
-            payload: cursor_at_payload,
+    schemas.insert(
+        "Assertion".to_string(),
+        SchemaHelp {
+            description:
+                "Step assertion: the same predicates as wait conditions, checked after the action."
+                    .to_string(),
+            fields: Some(assertion_fields),
+            types: Some(predicate_types()),
+            stability: Assertion::STABILITY,
         },
     );
 
-    let process_exited_payload = BTreeMap::new();
-    condition_types.insert(
-        "process_exited".to_string(),
-        TypeVariant {
-            payload: process_exited_payload,
-        },
+    let mut watcher_fields = BTreeMap::new();
+    watcher_fields.insert(
+        "name".to_string(),
+        "string: reported when the watcher triggers".to_string(),
+    );
+    watcher_fields.insert(
+        "condition".to_string(),
+        "Condition object: any predicate type".to_string(),
     );
-
     schemas.insert(
-        "Condition".to_string(),
+        "Watcher".to_string(),
         SchemaHelp {
-            description: "Wait condition for the wait action.".to_string(),
-            fields: None,
-            types: Some(condition_types),
+            description: "Scenario watcher: fails the run on the first observation where its condition holds."
+                .to_string(),
+            fields: Some(watcher_fields),
+            types: None,
+            stability: Watcher::STABILITY,
         },
     );
 
@@ -363,12 +564,18 @@ fn generate_schemas() -> BTreeMap<String, SchemaHelp> {
         "string | null: new output since last observation".to_string(),
     );
     observation_fields.insert("events".to_string(), "array of Event objects".to_string());
+    observation_fields.insert(
+        "read_truncated".to_string(),
+        "bool (omitted when false): timeout hit while output was still arriving; observe again"
+            .to_string(),
+    );
     schemas.insert(
         "Observation".to_string(),
         SchemaHelp {
             description: "Terminal state observation returned after each action.".to_string(),
             fields: Some(observation_fields),
             types: None,
+            stability: Observation::STABILITY,
         },
     );
 
@@ -394,12 +601,62 @@ fn generate_schemas() -> BTreeMap<String, SchemaHelp> {
             description: "Current terminal screen state.".to_string(),
             fields: Some(screen_fields),
             types: None,
+            stability: ScreenSnapshot::STABILITY,
         },
     );
 
     schemas
 }
 
+/// Predicate types shared by wait conditions, assertions, and watchers,
+/// rendered from [`ptybox::predicate::PREDICATES`].
+fn predicate_types() -> BTreeMap<String, TypeVariant> {
+    PREDICATES
+        .iter()
+        .map(|spec| {
+            (
+                spec.name.to_string(),
+                TypeVariant {
+                    description: Some(spec.description.to_string()),
+                    aliases: spec.aliases.iter().map(ToString::to_string).collect(),
+                    payload: spec
+                        .payload
+                        .iter()
+                        .map(|&(field, meaning)| (field.to_string(), meaning.to_string()))
+                        .collect(),
+                    stability: spec.stability,
+                },
+            )
+        })
+        .collect()
+}
+
+/// Text-mode marker after a type name: empty for stable types.
+#[must_use]
+pub fn stability_tag(stability: Stability) -> String {
+    match stability {
+        Stability::Stable => String::new(),
+        Stability::Experimental => " [experimental]".to_string(),
+        Stability::Deprecated { since, replacement } => {
+            format!(" [deprecated since {since}; use {replacement}]")
+        }
+    }
+}
+
+/// An action type's entry, with the stability declared on [`ActionType`].
+fn action_variant(
+    action_type: &ActionType,
+    description: Option<&str>,
+    payload: BTreeMap<String, String>,
+) -> TypeVariant {
+    TypeVariant {
+        description: description.map(str::to_string),
+        aliases: Vec::new(),
+        payload,
+        stability: action_type.stability(),
+    }
+}
+
 /// Generate error code documentation.
 ///
 /// This function builds static error code definitions - allowed to be long since it's declarative data.
@@ -453,7 +710,7 @@ fn generate_error_codes() -> BTreeMap<String, ErrorCodeHelp> {
             description: "Scenario assertion check failed.".to_string(),
             common_causes: Some(vec![
                 "screen_contains text not found".to_string(),
-                "regex_match pattern didn't match".to_string(),
+                "screen_matches pattern didn't match".to_string(),
                 "cursor_at position incorrect".to_string(),
             ]),
         },
@@ -544,6 +801,19 @@ fn generate_error_codes() -> BTreeMap<String, ErrorCodeHelp> {
         },
     );
 
+    codes.insert(
+        "E_STALE_OBSERVATION".to_string(),
+        ErrorCodeHelp {
+            exit_code: 13,
+            description: "Driver action rejected: screen changed since the expected observation."
+                .to_string(),
+            common_causes: Some(vec![
+                "expected_screen_hash taken from an older response".to_string(),
+                "Application redrew the screen between observations".to_string(),
+            ]),
+        },
+    );
+
     codes.insert(
         "E_INTERNAL".to_string(),
         ErrorCodeHelp {
@@ -0,0 +1,51 @@
+//! `ptybox report`: render a recorded run as a Markdown report, from its
+//! `run.json` and `normalization.json`, without re-running anything.
+
+use clap::ValueEnum;
+use miette::{IntoDiagnostic, Result};
+use ptybox::model::{NormalizationRecord, RunResult};
+use ptybox::runner::{RunnerError, RunnerResult};
+use std::io::Write as _;
+use std::path::Path;
+
+/// Report formats for `ptybox report`.
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub enum ReportFormat {
+    /// Markdown for pull request descriptions
+    Markdown,
+}
+
+/// Print the report for the run in `artifacts`, or write it to `output`.
+pub fn cmd_report(artifacts: &Path, format: ReportFormat, output: Option<&Path>) -> Result<()> {
+    let (run, normalization) = load(artifacts)?;
+    let report = match format {
+        ReportFormat::Markdown => ptybox::report::markdown_report(&run, normalization.as_ref()),
+    };
+    match output {
+        Some(path) => {
+            std::fs::write(path, report).into_diagnostic()?;
+            eprintln!("report written to {}", path.display());
+        }
+        None => {
+            print!("{report}");
+            std::io::stdout().flush().into_diagnostic()?;
+        }
+    }
+    Ok(())
+}
+
+fn load(artifacts: &Path) -> RunnerResult<(RunResult, Option<NormalizationRecord>)> {
+    let data = std::fs::read(artifacts.join("run.json"))
+        .map_err(|err| RunnerError::io("E_IO", "failed to read run.json", err))?;
+    let run = serde_json::from_slice(&data)
+        .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to parse run.json", err))?;
+    let path = artifacts.join("normalization.json");
+    if !path.is_file() {
+        return Ok((run, None));
+    }
+    let data = std::fs::read(&path)
+        .map_err(|err| RunnerError::io("E_IO", "failed to read normalization.json", err))?;
+    let normalization = serde_json::from_slice(&data)
+        .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to parse normalization.json", err))?;
+    Ok((run, Some(normalization)))
+}
@@ -0,0 +1,119 @@
+//! `ptybox runs list` and `ptybox grep`: query the local run index written
+//! at an artifacts root.
+
+use miette::Result;
+use ptybox::artifacts::{grep_runs, read_run_index, GrepOptions};
+use ptybox::model::{GrepMatch, GrepSource, RunIndexEntry, RunStatus};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// List indexed runs under `root`, newest first, filtered by tags and status.
+pub fn cmd_runs_list(
+    root: &Path,
+    tags: &[String],
+    status: Option<RunStatus>,
+    limit: Option<usize>,
+    json: bool,
+) -> Result<()> {
+    let entries = match read_run_index(root) {
+        Ok(entries) => entries,
+        Err(err) => return crate::emit_result(json, Err(err)),
+    };
+    let matches: Vec<RunIndexEntry> = entries
+        .into_iter()
+        .rev()
+        .filter(|entry| tags.iter().all(|tag| entry.tags.contains(tag)))
+        .filter(|entry| {
+            status
+                .as_ref()
+                .map_or(true, |status| &entry.status == status)
+        })
+        .take(limit.unwrap_or(usize::MAX))
+        .collect();
+
+    if json {
+        return crate::emit_json(&matches);
+    }
+    for entry in &matches {
+        println!("{}", format_entry(entry));
+    }
+    Ok(())
+}
+
+fn format_entry(entry: &RunIndexEntry) -> String {
+    let status = match entry.status {
+        RunStatus::Passed => "passed",
+        RunStatus::Failed => "failed",
+        RunStatus::Errored => "errored",
+        RunStatus::Canceled => "canceled",
+    };
+    let mut line = format!("{}  {status:<8}  {}", entry.run_id, entry.artifacts_dir);
+    if let Some(name) = &entry.scenario {
+        let _ = write!(line, "  scenario={name}");
+    }
+    if !entry.tags.is_empty() {
+        let _ = write!(line, "  tags={}", entry.tags.join(","));
+    }
+    if let Some(code) = &entry.error_code {
+        let _ = write!(line, "  error={code}");
+    }
+    line
+}
+
+/// Search the runs indexed under `root`, printing matches grep-style.
+///
+/// Each line is prefixed with the run id and its location
+/// (`transcript` or the snapshot file, plus the step); matching lines use
+/// `:` after the line number and context lines `-`, with `--` between
+/// matches. Skipped runs are reported on stderr.
+pub fn cmd_grep(root: &Path, options: &GrepOptions, json: bool) -> Result<()> {
+    let report = match grep_runs(root, options) {
+        Ok(report) => report,
+        Err(err) => return crate::emit_result(json, Err(err)),
+    };
+    if json {
+        return crate::emit_json(&report);
+    }
+    for (index, found) in report.matches.iter().enumerate() {
+        if options.context > 0 && index > 0 {
+            println!("--");
+        }
+        let first = found.line - found.before.len() as u64;
+        for (offset, line) in found.before.iter().enumerate() {
+            println!("{}-{}- {line}", location(found), first + offset as u64);
+        }
+        println!("{}:{}: {}", location(found), found.line, found.text);
+        for (offset, line) in found.after.iter().enumerate() {
+            println!(
+                "{}-{}- {line}",
+                location(found),
+                found.line + 1 + offset as u64
+            );
+        }
+    }
+    for skip in &report.skipped {
+        eprintln!(
+            "skipped {} ({}): {}",
+            skip.run_id, skip.artifacts_dir, skip.reason
+        );
+    }
+    eprintln!(
+        "{} match(es) in {} run(s)",
+        report.matches.len(),
+        report.runs_searched
+    );
+    Ok(())
+}
+
+fn location(found: &GrepMatch) -> String {
+    let mut location = format!("{}  ", found.run_id);
+    match (found.source, &found.snapshot) {
+        (GrepSource::Snapshot, Some(snapshot)) => location.push_str(snapshot),
+        (GrepSource::Snapshot, None) => location.push_str("snapshot"),
+        (GrepSource::Transcript, _) => location.push_str("transcript"),
+    }
+    if let Some(step) = &found.step {
+        let _ = write!(location, " [{step}]");
+    }
+    location
+}
@@ -0,0 +1,191 @@
+//! `ptybox run-suite`: run every scenario of a suite, or only those a
+//! change affects under an impact map of watched globs, and report each
+//! one's status.
+//!
+//! `ptybox suite` is `run-suite --dry-run`: it prints the selection without
+//! running anything.
+
+use miette::Result;
+use ptybox::artifacts::{load_impact_map, read_changed_files};
+use ptybox::model::{RunStatus, ScenarioSelection, SelectionReason, SuiteResult, SuiteSelection};
+use ptybox::runner::{run_suite, select_suite, RunnerResult, SuiteConfig};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Run the suite, or with `dry_run` print each scenario with whether it
+/// runs and why, then the totals.
+///
+/// A run exits with the first failing scenario's error code unless every
+/// scenario passed.
+pub fn cmd_run_suite(
+    mut config: SuiteConfig,
+    changed_files: Option<&Path>,
+    impact_map: Option<&Path>,
+    dry_run: bool,
+    json: bool,
+) -> Result<()> {
+    if let Err(err) = load_selection_inputs(&mut config, changed_files, impact_map) {
+        return crate::emit_result(json, Err(err));
+    }
+    if dry_run {
+        let selection = match select_suite(&config) {
+            Ok(selection) => selection,
+            Err(err) => return crate::emit_result(json, Err(err)),
+        };
+        if json {
+            return crate::emit_json(&selection);
+        }
+        print!("{}", format_selection(&selection));
+        return Ok(());
+    }
+    let result = match run_suite(&config) {
+        Ok(result) => result,
+        Err(err) => return crate::emit_result(json, Err(err)),
+    };
+    if json {
+        crate::emit_json(&result)?;
+    } else {
+        print!("{}", format_suite_result(&result));
+    }
+    if result.status == RunStatus::Passed {
+        return Ok(());
+    }
+    let error = result
+        .scenarios
+        .iter()
+        .filter(|scenario| scenario.status != RunStatus::Passed)
+        .find_map(|scenario| scenario.error.as_ref());
+    std::process::exit(error.map_or(1, |err| crate::exit_code_for_error_code(&err.code)));
+}
+
+fn load_selection_inputs(
+    config: &mut SuiteConfig,
+    changed_files: Option<&Path>,
+    impact_map: Option<&Path>,
+) -> RunnerResult<()> {
+    config.changed_files = changed_files.map(read_changed_files).transpose()?;
+    config.impact_map = impact_map.map(load_impact_map).transpose()?;
+    Ok(())
+}
+
+fn format_selection(selection: &SuiteSelection) -> String {
+    let width = selection
+        .scenarios
+        .iter()
+        .map(|scenario| scenario.scenario.len())
+        .chain(["SCENARIO".len()])
+        .max()
+        .unwrap_or_default();
+    let mut table = format!("{:<4}  {:<width$}  {}\n", "RUN", "SCENARIO", "REASON");
+    for scenario in &selection.scenarios {
+        let _ = writeln!(
+            table,
+            "{:<4}  {:<width$}  {}",
+            if scenario.selected { "yes" } else { "no" },
+            scenario.scenario,
+            reason(scenario)
+        );
+    }
+    let _ = writeln!(
+        table,
+        "{} selected, {} skipped ({} changed files{})",
+        selection.selected,
+        selection.skipped,
+        selection.changed_files.len(),
+        if selection.full_run { ", full run" } else { "" }
+    );
+    for name in &selection.unknown_mappings {
+        let _ = writeln!(
+            table,
+            "warning: impact map entry '{name}' names no scenario"
+        );
+    }
+    table
+}
+
+/// The reason, with the first matching file and how many others matched.
+fn reason(scenario: &ScenarioSelection) -> String {
+    let label = match scenario.reason {
+        SelectionReason::Matched => "matched",
+        SelectionReason::Always => "always",
+        SelectionReason::NoImpactMap => "no impact map",
+        SelectionReason::Unmapped => "unmapped",
+        SelectionReason::NotAffected => "not affected",
+    };
+    match scenario.matches.split_first() {
+        Some((first, [])) => format!("{label}: {} ({})", first.file, first.glob),
+        Some((first, rest)) => format!(
+            "{label}: {} ({}) and {} more",
+            first.file,
+            first.glob,
+            rest.len()
+        ),
+        None => label.to_string(),
+    }
+}
+
+fn format_suite_result(result: &SuiteResult) -> String {
+    // each run's scenario name, with its matrix cell
+    let names: Vec<String> = result
+        .scenarios
+        .iter()
+        .map(|scenario| match &scenario.determinism {
+            Some(cell) => format!("{} [{}]", scenario.scenario, cell.cell_label()),
+            None => scenario.scenario.clone(),
+        })
+        .collect();
+    let width = names
+        .iter()
+        .map(String::len)
+        .chain(["SCENARIO".len()])
+        .max()
+        .unwrap_or_default();
+    let mut table = format!(
+        "{:<8}  {:<width$}  {:>5}  {:>8}  {}\n",
+        "STATUS", "SCENARIO", "STEPS", "TIME", "ERROR"
+    );
+    for (scenario, name) in result.scenarios.iter().zip(&names) {
+        let _ = writeln!(
+            table,
+            "{:<8}  {:<width$}  {:>5}  {:>8}  {}",
+            status_label(&scenario.status),
+            name,
+            format!("{}/{}", scenario.steps_passed, scenario.steps),
+            format!("{}ms", scenario.duration_ms),
+            scenario
+                .error
+                .as_ref()
+                .map(|err| format!("{}: {}", err.code, err.message))
+                .unwrap_or_default()
+        );
+    }
+    let _ = writeln!(
+        table,
+        "{}: {} passed, {} failed, {} errored, {} canceled in {}ms (jobs: {})",
+        status_label(&result.status),
+        result.passed,
+        result.failed,
+        result.errored,
+        result.canceled,
+        result.duration_ms,
+        result.jobs
+    );
+    if let Some(selection) = &result.selection {
+        let _ = writeln!(
+            table,
+            "{} skipped as not affected by {} changed files",
+            selection.skipped,
+            selection.changed_files.len()
+        );
+    }
+    table
+}
+
+fn status_label(status: &RunStatus) -> &'static str {
+    match status {
+        RunStatus::Passed => "passed",
+        RunStatus::Failed => "failed",
+        RunStatus::Errored => "errored",
+        RunStatus::Canceled => "canceled",
+    }
+}
@@ -0,0 +1,359 @@
+//! `ptybox top`: live monitor for a `serve --queue` runner.
+//!
+//! Polls the runner's status endpoint and renders one row per in-flight job:
+//! current step, elapsed time, and how much of the runtime and output
+//! budgets it has used. `c` cancels the selected job through the endpoint,
+//! authenticated with the runner's bearer token (`--token-file`); the
+//! runner stops it before its next step.
+
+// TUI-specific lint allowances - ratatui layouts have fixed indices
+#![allow(clippy::indexing_slicing)]
+
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use miette::{IntoDiagnostic, Result};
+use ptybox::serve::queue::{
+    cancel_queue_job, fetch_queue_status, QueueJobStatus, QueueState, QueueStatus,
+};
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Row, Table, TableState},
+    Frame, Terminal,
+};
+use std::fmt::Write as _;
+use std::io;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Column headers shared by the live table and `--once` output.
+const HEADERS: [&str; 6] = ["JOB", "STEP", "ELAPSED", "RUNTIME", "OUTPUT", "STATE"];
+
+/// Monitor the queue runner whose status endpoint listens on `addr`.
+pub fn cmd_top(
+    addr: SocketAddr,
+    token_file: Option<&Path>,
+    refresh: Duration,
+    once: bool,
+    json: bool,
+) -> Result<()> {
+    let token = match token_file.map(std::fs::read_to_string).transpose() {
+        Ok(token) => token.map(|token| token.trim().to_string()),
+        Err(err) => {
+            return crate::emit_result(
+                json,
+                Err(ptybox::runner::RunnerError::io(
+                    "E_IO",
+                    "failed to read token file",
+                    err,
+                )),
+            )
+        }
+    };
+    if once {
+        let status = match fetch_queue_status(addr) {
+            Ok(status) => status,
+            Err(err) => return crate::emit_result(json, Err(err)),
+        };
+        if json {
+            return crate::emit_json(&status);
+        }
+        print!("{}", format_status(&status));
+        return Ok(());
+    }
+
+    enable_raw_mode().into_diagnostic()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen).into_diagnostic()?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout)).into_diagnostic()?;
+
+    let result = run_monitor(&mut terminal, addr, token, refresh);
+
+    disable_raw_mode().into_diagnostic()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen).into_diagnostic()?;
+    terminal.show_cursor().into_diagnostic()?;
+    result
+}
+
+/// Monitor state between refreshes.
+#[derive(Default)]
+struct Monitor {
+    status: Option<QueueStatus>,
+    /// Last refresh error; the previous status stays on screen.
+    error: Option<String>,
+    /// Outcome of the last cancel request.
+    message: Option<String>,
+    /// Bearer token for cancel requests.
+    token: Option<String>,
+    table: TableState,
+}
+
+impl Monitor {
+    fn refresh(&mut self, addr: SocketAddr) {
+        match fetch_queue_status(addr) {
+            Ok(status) => {
+                let rows = status.jobs.len();
+                self.status = Some(status);
+                self.error = None;
+                self.table.select(match (self.table.selected(), rows) {
+                    (_, 0) => None,
+                    (Some(index), _) => Some(index.min(rows - 1)),
+                    (None, _) => Some(0),
+                });
+            }
+            Err(err) => self.error = Some(err.message),
+        }
+    }
+
+    fn selected_job(&self) -> Option<&QueueJobStatus> {
+        let index = self.table.selected()?;
+        self.status.as_ref()?.jobs.get(index)
+    }
+
+    fn cancel_selected(&mut self, addr: SocketAddr) {
+        let Some(job) = self.selected_job().map(|job| job.job.clone()) else {
+            return;
+        };
+        self.message = Some(match cancel_queue_job(addr, &job, self.token.as_deref()) {
+            Ok(()) => format!("cancel requested for {job}"),
+            Err(err) => format!("cancel {job}: {}", err.message),
+        });
+    }
+}
+
+fn run_monitor<B: ratatui::backend::Backend>(
+    terminal: &mut Terminal<B>,
+    addr: SocketAddr,
+    token: Option<String>,
+    refresh: Duration,
+) -> Result<()> {
+    let mut monitor = Monitor {
+        token,
+        ..Monitor::default()
+    };
+    let mut next_refresh = Instant::now();
+    loop {
+        if Instant::now() >= next_refresh {
+            monitor.refresh(addr);
+            next_refresh = Instant::now() + refresh;
+        }
+        terminal.draw(|f| ui(f, &mut monitor)).into_diagnostic()?;
+
+        let wait = next_refresh.saturating_duration_since(Instant::now());
+        if !event::poll(wait).into_diagnostic()? {
+            continue;
+        }
+        let Event::Key(key) = event::read().into_diagnostic()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Up | KeyCode::Char('k') => monitor.table.select_previous(),
+            KeyCode::Down | KeyCode::Char('j') => monitor.table.select_next(),
+            KeyCode::Char('c') => {
+                monitor.cancel_selected(addr);
+                next_refresh = Instant::now();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn ui(f: &mut Frame, monitor: &mut Monitor) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(5),
+            Constraint::Length(3),
+        ])
+        .split(f.area());
+    let border = Style::default().fg(Color::Cyan);
+
+    let summary = monitor
+        .status
+        .as_ref()
+        .map_or_else(|| "Connecting...".to_string(), summary_line);
+    f.render_widget(
+        Paragraph::new(summary).block(
+            Block::default()
+                .title(" ptybox top ")
+                .borders(Borders::ALL)
+                .border_style(border),
+        ),
+        chunks[0],
+    );
+
+    render_jobs(f, chunks[1], monitor);
+    render_footer(f, chunks[2], monitor);
+}
+
+fn render_jobs(f: &mut Frame, area: Rect, monitor: &mut Monitor) {
+    let rows: Vec<Row> = monitor
+        .status
+        .as_ref()
+        .map(|status| status.jobs.iter().map(job_row).map(Row::new).collect())
+        .unwrap_or_default();
+    let widths = [
+        Constraint::Percentage(25),
+        Constraint::Percentage(27),
+        Constraint::Length(9),
+        Constraint::Length(14),
+        Constraint::Length(22),
+        Constraint::Length(10),
+    ];
+    let table = Table::new(rows, widths)
+        .header(Row::new(HEADERS).style(Style::default().add_modifier(Modifier::BOLD)))
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .block(
+            Block::default()
+                .title(" Jobs ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan)),
+        );
+    f.render_stateful_widget(table, area, &mut monitor.table);
+}
+
+fn render_footer(f: &mut Frame, area: Rect, monitor: &Monitor) {
+    let hint = Style::default().fg(Color::DarkGray);
+    let mut spans = vec![
+        Span::raw(" "),
+        Span::styled("[q]uit", hint),
+        Span::raw(" "),
+        Span::styled("[↑↓]select", hint),
+        Span::raw(" "),
+        Span::styled("[c]ancel job", hint),
+    ];
+    if let Some(error) = &monitor.error {
+        spans.push(Span::raw(" │ "));
+        spans.push(Span::styled(
+            format!("Error: {error}"),
+            Style::default().fg(Color::Red),
+        ));
+    } else if let Some(message) = &monitor.message {
+        spans.push(Span::raw(" │ "));
+        spans.push(Span::styled(
+            message.as_str(),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    f.render_widget(
+        Paragraph::new(Line::from(spans)).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::DarkGray)),
+        ),
+        area,
+    );
+}
+
+/// Summary line followed by the job table as aligned plain text.
+fn format_status(status: &QueueStatus) -> String {
+    let rows: Vec<[String; 6]> = status.jobs.iter().map(job_row).collect();
+    let mut widths = HEADERS.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut out = summary_line(status);
+    out.push('\n');
+    let header = HEADERS.map(str::to_string);
+    for row in std::iter::once(&header).chain(&rows) {
+        let mut line = String::new();
+        for (cell, width) in row.iter().zip(widths) {
+            let _ = write!(line, "{cell:<width$}  ");
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+fn summary_line(status: &QueueStatus) -> String {
+    let state = match status.state {
+        QueueState::Running => "running",
+        QueueState::Draining => "draining",
+    };
+    format!(
+        "{state}  jobs {}/{}  queued {}  completed {}  failed {}  up {}",
+        status.running,
+        status.concurrency,
+        status.queued,
+        status.completed,
+        status.failed,
+        format_ms(status.uptime_ms)
+    )
+}
+
+fn job_row(job: &QueueJobStatus) -> [String; 6] {
+    let step = match &job.step {
+        Some(name) => format!("{}/{} {name}", job.step_index, job.total_steps),
+        None => format!("-/{}", job.total_steps),
+    };
+    let (runtime, output) = match &job.budgets {
+        Some(budgets) => (
+            format!(
+                "{}% of {}",
+                percent(job.elapsed_ms, budgets.max_runtime_ms),
+                format_ms(budgets.max_runtime_ms)
+            ),
+            format!(
+                "{} / {}",
+                format_bytes(job.output_bytes),
+                format_bytes(budgets.max_output_bytes)
+            ),
+        ),
+        None => ("-".to_string(), format_bytes(job.output_bytes)),
+    };
+    let state = if job.cancel_requested {
+        "canceling"
+    } else {
+        "running"
+    };
+    [
+        job.job.clone(),
+        step,
+        format_ms(job.elapsed_ms),
+        runtime,
+        output,
+        state.to_string(),
+    ]
+}
+
+fn percent(used: u64, budget: u64) -> u64 {
+    if budget == 0 {
+        return 100;
+    }
+    used.saturating_mul(100) / budget
+}
+
+/// `950ms`, `12.3s`, or `4m05s`.
+fn format_ms(ms: u64) -> String {
+    match ms {
+        0..=999 => format!("{ms}ms"),
+        1_000..=59_999 => format!("{}.{}s", ms / 1000, ms % 1000 / 100),
+        _ => format!("{}m{:02}s", ms / 60_000, ms % 60_000 / 1000),
+    }
+}
+
+/// `512B`, `4.0KiB`, or `8.0MiB`.
+fn format_bytes(bytes: u64) -> String {
+    const KIB: u64 = 1024;
+    const MIB: u64 = KIB * 1024;
+    match bytes {
+        0..=1023 => format!("{bytes}B"),
+        KIB..=1_048_575 => format!("{}.{}KiB", bytes / KIB, bytes % KIB * 10 / KIB),
+        _ => format!("{}.{}MiB", bytes / MIB, bytes % MIB * 10 / MIB),
+    }
+}
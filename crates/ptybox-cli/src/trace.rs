@@ -1,17 +1,72 @@
 //! Static HTML trace viewer generator.
 //!
 //! Generates an interactive HTML page from run artifacts that displays:
-//! - Timeline of steps with status
-//! - Terminal snapshots for each step
-//! - Run metadata and assertion results
+//! - Timeline of steps with status and measured response latency
+//! - Terminal snapshots for each step, plus intermediate screens sampled
+//!   within steps that set `sample_interval_ms`
+//! - Run metadata, step metadata, and assertion results
+//!
+//! Steps with a content key can be linked to as `trace.html#step-<key>`;
+//! the link keeps pointing at the same step after other steps are added or
+//! removed.
+//!
+//! `--format asciicast` writes the run's output as an asciinema v2 recording
+//! instead, for standard players and docs.
 
+use clap::ValueEnum;
 use miette::{IntoDiagnostic, Result, WrapErr};
-use ptybox::model::{RunResult, ScreenSnapshot};
+use ptybox::artifacts::{
+    artifact_path, asciicast_from_artifacts, read_artifact, sample_paths, snapshot_paths,
+    ArtifactsDecryptor,
+};
+use ptybox::model::{RunResult, ScreenSample, ScreenSnapshot};
+use serde::de::DeserializeOwned;
 use std::fs;
 use std::path::Path;
 
+/// Output formats for `ptybox trace`.
+#[derive(Copy, Clone, Debug, Default, ValueEnum)]
+pub enum TraceFormat {
+    /// Interactive HTML viewer
+    #[default]
+    Html,
+    /// asciinema v2 recording (`.cast`)
+    Asciicast,
+}
+
+impl TraceFormat {
+    /// Output file used when `--output` is not given.
+    pub fn default_output(self) -> &'static str {
+        match self {
+            Self::Html => "trace.html",
+            Self::Asciicast => "session.cast",
+        }
+    }
+}
+
+/// Convert the run's event logs to an asciinema v2 recording at
+/// `output_path`.
+///
+/// Encrypted event logs are decrypted with `decryptor`.
+pub fn generate_asciicast(
+    artifacts_dir: &Path,
+    output_path: &Path,
+    decryptor: Option<&ArtifactsDecryptor>,
+) -> Result<()> {
+    let cast = asciicast_from_artifacts(artifacts_dir, decryptor)?;
+    fs::write(output_path, cast)
+        .into_diagnostic()
+        .wrap_err_with(|| format!("failed to write {}", output_path.display()))
+}
+
 /// Load artifacts and generate an HTML trace viewer.
-pub fn generate_trace(artifacts_dir: &Path, output_path: &Path) -> Result<()> {
+///
+/// Encrypted artifacts are decrypted with `decryptor`.
+pub fn generate_trace(
+    artifacts_dir: &Path,
+    output_path: &Path,
+    decryptor: Option<&ArtifactsDecryptor>,
+) -> Result<()> {
     // Load run.json
     let run_path = artifacts_dir.join("run.json");
     let run_content = fs::read_to_string(&run_path)
@@ -21,16 +76,29 @@ pub fn generate_trace(artifacts_dir: &Path, output_path: &Path) -> Result<()> {
         .into_diagnostic()
         .wrap_err("failed to parse run.json")?;
 
-    // Load snapshots
-    let snapshots_dir = artifacts_dir.join("snapshots");
-    let snapshots = load_snapshots(&snapshots_dir)?;
+    // Load snapshots and intermediate samples
+    let snapshots: Vec<ScreenSnapshot> = load_json_artifacts(
+        snapshot_paths(artifacts_dir),
+        decryptor,
+        "failed to read snapshots directory",
+    )?;
+    let samples: Vec<ScreenSample> = load_json_artifacts(
+        sample_paths(artifacts_dir),
+        decryptor,
+        "failed to read samples directory",
+    )?;
 
     // Load transcript
-    let transcript_path = artifacts_dir.join("transcript.log");
-    let transcript = fs::read_to_string(&transcript_path).unwrap_or_default();
+    let transcript_path = artifact_path(artifacts_dir, "transcript.log");
+    let transcript = if transcript_path.exists() {
+        let data = read_artifact(&transcript_path, decryptor)?;
+        String::from_utf8_lossy(&data).into_owned()
+    } else {
+        String::new()
+    };
 
     // Generate HTML
-    let html = render_html(&run_result, &snapshots, &transcript)?;
+    let html = render_html(&run_result, &snapshots, &samples, &transcript)?;
 
     // Write output
     fs::write(output_path, html)
@@ -40,38 +108,30 @@ pub fn generate_trace(artifacts_dir: &Path, output_path: &Path) -> Result<()> {
     Ok(())
 }
 
-fn load_snapshots(snapshots_dir: &Path) -> Result<Vec<ScreenSnapshot>> {
-    let mut snapshots = Vec::new();
-
-    if !snapshots_dir.exists() {
-        return Ok(snapshots);
-    }
-
-    let mut entries: Vec<_> = fs::read_dir(snapshots_dir)
-        .into_diagnostic()
-        .wrap_err("failed to read snapshots directory")?
-        .filter_map(|e| e.ok())
-        .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
-        .collect();
+fn load_json_artifacts<T: DeserializeOwned>(
+    paths: ptybox::runner::RunnerResult<Vec<std::path::PathBuf>>,
+    decryptor: Option<&ArtifactsDecryptor>,
+    context: &'static str,
+) -> Result<Vec<T>> {
+    let mut items = Vec::new();
 
-    entries.sort_by_key(|e| e.file_name());
+    let entries = paths.into_diagnostic().wrap_err(context)?;
 
-    for entry in entries {
-        let content = fs::read_to_string(entry.path())
+    for path in entries {
+        let content = read_artifact(&path, decryptor)?;
+        let item: T = serde_json::from_slice(&content)
             .into_diagnostic()
-            .wrap_err_with(|| format!("failed to read {}", entry.path().display()))?;
-        let snapshot: ScreenSnapshot = serde_json::from_str(&content)
-            .into_diagnostic()
-            .wrap_err_with(|| format!("failed to parse {}", entry.path().display()))?;
-        snapshots.push(snapshot);
+            .wrap_err_with(|| format!("failed to parse {}", path.display()))?;
+        items.push(item);
     }
 
-    Ok(snapshots)
+    Ok(items)
 }
 
 fn render_html(
     run_result: &RunResult,
     snapshots: &[ScreenSnapshot],
+    samples: &[ScreenSample],
     transcript: &str,
 ) -> Result<String> {
     let steps_json = serde_json::to_string(&run_result.steps)
@@ -80,6 +140,9 @@ fn render_html(
     let snapshots_json = serde_json::to_string(snapshots)
         .into_diagnostic()
         .wrap_err("failed to serialize snapshots")?;
+    let samples_json = serde_json::to_string(samples)
+        .into_diagnostic()
+        .wrap_err("failed to serialize samples")?;
     let run_json = serde_json::to_string(run_result)
         .into_diagnostic()
         .wrap_err("failed to serialize run result")?;
@@ -144,6 +207,7 @@ fn render_html(
     <script>
 const STEPS = {steps_json};
 const SNAPSHOTS = {snapshots_json};
+const SAMPLES = {samples_json};
 const RUN = {run_json};
 
 {JS}
@@ -160,6 +224,7 @@ const RUN = {run_json};
         transcript_escaped = transcript_escaped,
         steps_json = steps_json,
         snapshots_json = snapshots_json,
+        samples_json = samples_json,
         run_json = run_json,
         CSS = CSS,
         JS = JS,
@@ -381,6 +446,13 @@ main {
 .assertion-item.passed { border-left: 3px solid var(--status-passed); }
 .assertion-item.failed { border-left: 3px solid var(--status-failed); }
 
+.metadata-item {
+    font-family: monospace;
+    font-size: 0.8rem;
+    padding: 0.25rem 0;
+    overflow-wrap: anywhere;
+}
+
 .transcript {
     flex: 1;
     overflow: auto;
@@ -439,6 +511,12 @@ footer {
     text-align: center;
 }
 
+.sample-btn {
+    margin: 0 0.25rem 0.25rem 0;
+    font-family: monospace;
+    font-size: 0.75rem;
+}
+
 /* Cell styling */
 .cell-bold { font-weight: bold; }
 .cell-italic { font-style: italic; }
@@ -456,7 +534,8 @@ function init() {
         renderSnapshot(0);
     }
     if (STEPS && STEPS.length > 0) {
-        selectStep(0);
+        const linked = STEPS.findIndex(step => step.step_key && location.hash === '#step-' + step.step_key);
+        selectStep(Math.max(linked, 0));
     }
 
     document.getElementById('prev-btn').onclick = () => navigate(-1);
@@ -475,19 +554,23 @@ function renderStepsList() {
         const duration = step.ended_at_ms - step.started_at_ms;
         const statusClass = step.status.toLowerCase();
         return `
-            <div class="step-item" data-index="${i}" onclick="selectStep(${i})">
+            <div class="step-item" data-index="${i}"${step.step_key ? ` id="step-${escapeHtml(step.step_key)}"` : ''} onclick="selectStep(${i})">
                 <div class="step-header">
                     <span class="step-name">${escapeHtml(step.name || 'Step ' + (i + 1))}</span>
                     <span class="step-status ${statusClass}">${step.status}</span>
                 </div>
                 <div class="step-meta">
-                    ${step.action ? step.action.type : 'unknown'} &middot; ${duration}ms
+                    ${step.action ? step.action.type : 'unknown'} &middot; ${duration}ms${latencyLabel(step)}
                 </div>
             </div>
         `;
     }).join('');
 }
 
+function latencyLabel(step) {
+    return step.latency_ms != null ? ` &middot; responded in ${step.latency_ms}ms` : '';
+}
+
 function selectStep(index) {
     // Update selection visual
     document.querySelectorAll('.step-item').forEach((el, i) => {
@@ -501,6 +584,9 @@ function selectStep(index) {
     // Update footer
     document.getElementById('current-step').textContent =
         `Step ${index + 1} of ${STEPS.length}: ${step.name || 'Unnamed'}`;
+    if (step.step_key) {
+        history.replaceState(null, '', '#step-' + step.step_key);
+    }
 
     // Find matching snapshot (simplified: use index)
     if (index < SNAPSHOTS.length) {
@@ -532,6 +618,22 @@ function renderDetails(step) {
         `;
     }
 
+    // Caller metadata (ticket IDs, owners, ...)
+    const metadata = Object.entries(step.metadata || {});
+    if (metadata.length > 0) {
+        html += `
+            <div class="detail-section">
+                <h3>Metadata</h3>
+                ${metadata.map(([key, value]) => `
+                    <div class="metadata-item">
+                        <strong>${escapeHtml(key)}</strong>:
+                        ${escapeHtml(typeof value === 'string' ? value : JSON.stringify(value))}
+                    </div>
+                `).join('')}
+            </div>
+        `;
+    }
+
     // Assertions
     if (step.assertions && step.assertions.length > 0) {
         html += `
@@ -547,6 +649,19 @@ function renderDetails(step) {
         `;
     }
 
+    // Intermediate frames sampled within the step
+    const samples = SAMPLES.filter(s => s.step_id === step.step_id);
+    if (samples.length > 0) {
+        html += `
+            <div class="detail-section">
+                <h3>Intermediate frames (${samples.length})</h3>
+                ${samples.map(s => `
+                    <button class="sample-btn" onclick="renderSample(${SAMPLES.indexOf(s)})">${s.timestamp_ms}ms</button>
+                `).join('')}
+            </div>
+        `;
+    }
+
     // Error
     if (step.error) {
         html += `
@@ -570,7 +685,20 @@ function renderSnapshot(index) {
         return;
     }
 
-    const snapshot = SNAPSHOTS[index];
+    renderScreen(SNAPSHOTS[index]);
+    currentSnapshotIndex = index;
+}
+
+function renderSample(index) {
+    const sample = SAMPLES[index];
+    if (sample) {
+        renderScreen(sample.screen);
+        document.getElementById('snapshot-index').textContent = `frame @ ${sample.timestamp_ms}ms`;
+    }
+}
+
+function renderScreen(snapshot) {
+    const container = document.getElementById('terminal');
 
     // Render lines with optional cell styling
     let html = '';
@@ -628,7 +756,6 @@ function renderSnapshot(index) {
     }
 
     container.innerHTML = html;
-    currentSnapshotIndex = index;
 }
 
 function colorToCss(color) {
@@ -0,0 +1,184 @@
+//! Manual intervention while a scenario runs in the TUI.
+//!
+//! Keystrokes typed in input mode are forwarded to the running scenario via
+//! [`ManualInputSource`] and recorded as proposed steps. On exit the steps
+//! can be spliced into a copy of the scenario file at the point where they
+//! were typed, so a manual fix flows back into the scenario.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ptybox::model::{Action, ActionType, Step, StepId};
+use ptybox::runner::{ManualInputSource, RunnerError};
+use ptybox::scenario::{read_scenario_file, write_scenario_file};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Step timeout for recorded manual steps, in milliseconds.
+const MANUAL_STEP_TIMEOUT_MS: u64 = 500;
+
+/// Where to save the amended scenario: a copy of `source` written to `dest`.
+pub struct AmendTarget {
+    /// Scenario file the run was loaded from.
+    pub source: String,
+    /// Output path for the amended copy.
+    pub dest: PathBuf,
+}
+
+/// Queue shared between the TUI thread and the runner.
+#[derive(Default)]
+pub struct ManualInputQueue {
+    pending: Mutex<Vec<Action>>,
+}
+
+impl ManualInputQueue {
+    fn push(&self, action: Action) {
+        self.pending
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .push(action);
+    }
+}
+
+impl ManualInputSource for ManualInputQueue {
+    fn take_pending(&self) -> Vec<Action> {
+        std::mem::take(
+            &mut *self
+                .pending
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner),
+        )
+    }
+}
+
+/// Manual actions recorded during the run, each with the index of the
+/// scenario step it should precede.
+#[derive(Default)]
+pub struct ManualRecorder {
+    steps: Vec<(usize, Action)>,
+}
+
+impl ManualRecorder {
+    /// Forward `action` to the runner and record it before step `insert_at`.
+    /// Consecutive text typed at the same point is merged into one step.
+    pub fn record(&mut self, queue: &ManualInputQueue, insert_at: usize, action: Action) {
+        queue.push(action.clone());
+        if let Some((at, last)) = self.steps.last_mut() {
+            if *at == insert_at
+                && matches!(last.action_type, ActionType::Text)
+                && matches!(action.action_type, ActionType::Text)
+            {
+                let merged = format!(
+                    "{}{}",
+                    payload_str(last, "text"),
+                    payload_str(&action, "text")
+                );
+                last.payload = serde_json::json!({ "text": merged });
+                return;
+            }
+        }
+        self.steps.push((insert_at, action));
+    }
+
+    /// Number of proposed steps.
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Whether nothing was recorded.
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Write a copy of the scenario with the recorded steps spliced in.
+    ///
+    /// The source file is re-read as written, so scenario defaults and policy
+    /// references are kept rather than the resolved run-time values.
+    pub fn save(&self, target: &AmendTarget) -> Result<(), RunnerError> {
+        let mut scenario = read_scenario_file(&target.source)?;
+        let inserts = self
+            .steps
+            .iter()
+            .map(|(at, action)| (*at, manual_step(action)))
+            .collect();
+        scenario.insert_steps(inserts);
+        write_scenario_file(&target.dest.to_string_lossy(), &scenario)
+    }
+}
+
+/// Map a key press in input mode to a `key` or `text` action.
+///
+/// Returns `None` for keys with no scenario equivalent. `Esc` never reaches
+/// here: it leaves input mode.
+pub fn key_event_to_action(key: &KeyEvent) -> Option<Action> {
+    let named = |name: &str| Some(key_action(name));
+    match key.code {
+        KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            let alt = if key.modifiers.contains(KeyModifiers::ALT) {
+                "Alt+"
+            } else {
+                ""
+            };
+            c.is_ascii_alphabetic()
+                .then(|| key_action(&format!("Ctrl+{alt}{}", c.to_ascii_uppercase())))
+        }
+        KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::ALT) => c
+            .is_ascii_graphic()
+            .then(|| key_action(&format!("Alt+{c}"))),
+        KeyCode::Char(c) => Some(Action {
+            action_type: ActionType::Text,
+            payload: serde_json::json!({ "text": c.to_string() }),
+        }),
+        KeyCode::Enter => named("Enter"),
+        KeyCode::Tab => named("Tab"),
+        KeyCode::BackTab => named("Shift+Tab"),
+        KeyCode::Backspace => named("Backspace"),
+        KeyCode::Delete => named("Delete"),
+        KeyCode::Home => named("Home"),
+        KeyCode::End => named("End"),
+        KeyCode::PageUp => named("PageUp"),
+        KeyCode::PageDown => named("PageDown"),
+        KeyCode::Up => named("Up"),
+        KeyCode::Down => named("Down"),
+        KeyCode::Left => named("Left"),
+        KeyCode::Right => named("Right"),
+        KeyCode::F(n @ 1..=12) => Some(key_action(&format!("F{n}"))),
+        _ => None,
+    }
+}
+
+fn key_action(name: &str) -> Action {
+    Action {
+        action_type: ActionType::Key,
+        payload: serde_json::json!({ "key": name }),
+    }
+}
+
+fn payload_str<'a>(action: &'a Action, field: &str) -> &'a str {
+    action
+        .payload
+        .get(field)
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or_default()
+}
+
+fn manual_step(action: &Action) -> Step {
+    let name = match action.action_type {
+        ActionType::Text => format!("manual: type {:?}", payload_str(action, "text")),
+        _ => format!("manual: press {}", payload_str(action, "key")),
+    };
+    Step {
+        id: StepId::new(),
+        name,
+        action: action.clone(),
+        assert: Vec::new(),
+        timeout_ms: MANUAL_STEP_TIMEOUT_MS,
+        retries: 0,
+        skip_default_assertions: false,
+        sample_interval_ms: None,
+        expected_screen: None,
+        capture: None,
+        env_overlay: BTreeMap::new(),
+        metadata: BTreeMap::new(),
+        vars: Vec::new(),
+    }
+}
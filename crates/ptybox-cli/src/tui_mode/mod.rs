@@ -4,19 +4,25 @@
 //! - The emulated terminal screen
 //! - Step list with status
 //! - Run progress and controls
+//!
+//! Press `i` to type into the running application; see [`manual`] for how
+//! that input is recorded and saved with `--save-amended`.
 
 // TUI-specific lint allowances - ratatui layouts have fixed indices
 #![allow(clippy::indexing_slicing)]
 
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use miette::{IntoDiagnostic, Result};
-use ptybox::artifacts::ArtifactsWriterConfig;
 use ptybox::model::{RunResult, Scenario, ScreenSnapshot, StepStatus};
-use ptybox::runner::{run_scenario, ProgressCallback, ProgressEvent, RunnerOptions};
+use ptybox::runner::{
+    run_scenario, ManualInputSource, ProgressCallback, ProgressEvent, RunnerOptions,
+};
 use ratatui::{
     backend::CrosstermBackend,
     layout::{Constraint, Direction, Layout, Rect},
@@ -31,8 +37,20 @@ use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 
+mod manual;
+
+pub use manual::AmendTarget;
+use manual::{key_event_to_action, ManualInputQueue, ManualRecorder};
+
 /// Run a scenario in interactive TUI mode.
-pub fn run_tui(scenario: Scenario, artifacts: Option<ArtifactsWriterConfig>) -> Result<()> {
+///
+/// With `amend`, manual input recorded during the run can be saved on exit
+/// as a copy of the scenario file.
+pub fn run_tui(
+    scenario: Scenario,
+    options: RunnerOptions,
+    amend: Option<AmendTarget>,
+) -> Result<()> {
     // Set up terminal
     enable_raw_mode().into_diagnostic()?;
     let mut stdout = io::stdout();
@@ -44,17 +62,20 @@ pub fn run_tui(scenario: Scenario, artifacts: Option<ArtifactsWriterConfig>) ->
     let (tx, rx) = mpsc::channel::<TuiEvent>();
 
     // Create app state
-    let mut app = App::new(&scenario);
+    let mut app = App::new(&scenario, amend.as_ref());
+    let queue = Arc::new(ManualInputQueue::default());
 
     // Run scenario in background thread
     let progress_tx = tx.clone();
     let finish_tx = tx.clone();
     let scenario_clone = scenario.clone();
+    let manual_input = Arc::clone(&queue);
     thread::spawn(move || {
         let callback = Arc::new(TuiProgressCallback { tx: progress_tx });
         let options = RunnerOptions {
-            artifacts,
             progress: Some(callback as Arc<dyn ProgressCallback>),
+            manual_input: Some(manual_input as Arc<dyn ManualInputSource>),
+            ..options
         };
         let result = run_scenario(scenario_clone, options);
         // Ignore send error if receiver dropped
@@ -62,7 +83,7 @@ pub fn run_tui(scenario: Scenario, artifacts: Option<ArtifactsWriterConfig>) ->
     });
 
     // Main UI loop
-    let result = run_app(&mut terminal, &mut app, rx);
+    let result = run_app(&mut terminal, &mut app, &queue, rx);
 
     // Restore terminal
     disable_raw_mode().into_diagnostic()?;
@@ -74,6 +95,15 @@ pub fn run_tui(scenario: Scenario, artifacts: Option<ArtifactsWriterConfig>) ->
     .into_diagnostic()?;
     terminal.show_cursor().into_diagnostic()?;
 
+    if let (true, Some(target)) = (app.save_requested, amend.as_ref()) {
+        app.recorder.save(target)?;
+        eprintln!(
+            "saved amended scenario with {} manual step(s) to {}",
+            app.recorder.len(),
+            target.dest.display()
+        );
+    }
+
     // Handle result
     if let Some(run_result) = app.run_result {
         eprintln!("run completed: {:?}", run_result.status);
@@ -111,6 +141,16 @@ struct App {
     run_result: Option<RunResult>,
     error_message: Option<String>,
     scroll_offset: u16,
+    /// Keys are forwarded to the application instead of controlling the TUI.
+    input_mode: bool,
+    /// Scenario step index that manual input typed now should precede.
+    insert_at: usize,
+    recorder: ManualRecorder,
+    /// Destination shown when offering to save recorded input.
+    amend_dest: Option<String>,
+    /// Quit was pressed with recorded input; waiting for y/n.
+    confirm_save: bool,
+    save_requested: bool,
 }
 
 struct StepState {
@@ -120,7 +160,7 @@ struct StepState {
 }
 
 impl App {
-    fn new(scenario: &Scenario) -> Self {
+    fn new(scenario: &Scenario, amend: Option<&AmendTarget>) -> Self {
         let steps = scenario
             .steps
             .iter()
@@ -143,9 +183,57 @@ impl App {
             run_result: None,
             error_message: None,
             scroll_offset: 0,
+            input_mode: false,
+            insert_at: 0,
+            recorder: ManualRecorder::default(),
+            amend_dest: amend.map(|target| target.dest.display().to_string()),
+            confirm_save: false,
+            save_requested: false,
         }
     }
 
+    /// Handle a key press. Returns `true` when the TUI should exit.
+    fn handle_key(&mut self, key: &KeyEvent, queue: &ManualInputQueue) -> bool {
+        if self.confirm_save {
+            match key.code {
+                KeyCode::Char('y' | 'Y') => {
+                    self.save_requested = true;
+                    return true;
+                }
+                KeyCode::Char('n' | 'N') => return true,
+                KeyCode::Esc => self.confirm_save = false,
+                _ => {}
+            }
+            return false;
+        }
+        if self.input_mode {
+            if key.code == KeyCode::Esc || !self.running {
+                self.input_mode = false;
+            } else if let Some(action) = key_event_to_action(key) {
+                self.recorder.record(queue, self.insert_at, action);
+            }
+            return false;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                if self.amend_dest.is_some() && !self.recorder.is_empty() {
+                    self.confirm_save = true;
+                    return false;
+                }
+                return true;
+            }
+            KeyCode::Char('i') if self.running => self.input_mode = true,
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.scroll_offset = self.scroll_offset.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.scroll_offset += 1;
+            }
+            _ => {}
+        }
+        false
+    }
+
     fn handle_event(&mut self, event: TuiEvent) {
         match event {
             TuiEvent::Progress(progress) => match progress {
@@ -154,6 +242,7 @@ impl App {
                 } => {
                     if step_index > 0 && step_index <= self.steps.len() {
                         self.current_step = step_index - 1;
+                        self.insert_at = self.current_step;
                         self.steps[self.current_step].name = name;
                     }
                 }
@@ -167,15 +256,18 @@ impl App {
                         self.steps[self.current_step].name = name;
                         self.steps[self.current_step].status = status;
                         self.steps[self.current_step].duration_ms = Some(duration_ms);
+                        self.insert_at = self.current_step + 1;
                     }
                 }
                 ProgressEvent::RunCompleted { .. } => {
                     self.running = false;
+                    self.input_mode = false;
                 }
                 ProgressEvent::RunStarted { .. } => {}
             },
             TuiEvent::RunFinished(result) => {
                 self.running = false;
+                self.input_mode = false;
                 match *result {
                     Ok(run_result) => {
                         self.run_result = Some(run_result);
@@ -192,6 +284,7 @@ impl App {
 fn run_app<B: ratatui::backend::Backend>(
     terminal: &mut Terminal<B>,
     app: &mut App,
+    queue: &ManualInputQueue,
     rx: Receiver<TuiEvent>,
 ) -> Result<()> {
     loop {
@@ -205,21 +298,8 @@ fn run_app<B: ratatui::backend::Backend>(
         // Handle keyboard input
         if event::poll(Duration::from_millis(50)).into_diagnostic()? {
             if let Event::Key(key) = event::read().into_diagnostic()? {
-                if key.kind == KeyEventKind::Press {
-                    match key.code {
-                        KeyCode::Char('q') | KeyCode::Esc => {
-                            return Ok(());
-                        }
-                        KeyCode::Up | KeyCode::Char('k') => {
-                            if app.scroll_offset > 0 {
-                                app.scroll_offset -= 1;
-                            }
-                        }
-                        KeyCode::Down | KeyCode::Char('j') => {
-                            app.scroll_offset += 1;
-                        }
-                        _ => {}
-                    }
+                if key.kind == KeyEventKind::Press && app.handle_key(&key, queue) {
+                    return Ok(());
                 }
             }
         }
@@ -353,17 +433,47 @@ fn render_footer(f: &mut Frame, area: Rect, app: &App) {
     };
 
     let step_info = format!("Step {}/{}", app.current_step + 1, app.steps.len());
+    let hint = Style::default().fg(Color::DarkGray);
 
-    let content = Line::from(vec![
+    let mut spans = vec![
         Span::raw(" "),
         status,
         Span::raw(" │ "),
         Span::raw(step_info),
         Span::raw(" │ "),
-        Span::styled("[q]uit", Style::default().fg(Color::DarkGray)),
-        Span::raw(" "),
-        Span::styled("[↑↓]scroll", Style::default().fg(Color::DarkGray)),
-    ]);
+    ];
+    if !app.recorder.is_empty() {
+        spans.push(Span::styled(
+            format!("{} manual", app.recorder.len()),
+            Style::default().fg(Color::Magenta),
+        ));
+        spans.push(Span::raw(" │ "));
+    }
+    if app.confirm_save {
+        let dest = app.amend_dest.as_deref().unwrap_or_default();
+        spans.push(Span::styled(
+            format!("Save manual steps to {dest}? [y/n]"),
+            Style::default().fg(Color::Yellow),
+        ));
+    } else if app.input_mode {
+        spans.push(Span::styled(
+            "INPUT",
+            Style::default()
+                .fg(Color::Magenta)
+                .add_modifier(Modifier::BOLD),
+        ));
+        spans.push(Span::raw(" "));
+        spans.push(Span::styled("[Esc]stop typing", hint));
+    } else {
+        spans.push(Span::styled("[q]uit", hint));
+        spans.push(Span::raw(" "));
+        spans.push(Span::styled("[↑↓]scroll", hint));
+        if app.running {
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled("[i]nput", hint));
+        }
+    }
+    let content = Line::from(spans);
 
     let block = Block::default()
         .borders(Borders::ALL)
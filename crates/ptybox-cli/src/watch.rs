@@ -0,0 +1,439 @@
+//! `ptybox watch`: rerun a scenario whenever watched files change.
+//!
+//! Changes come from the platform's file events (inotify on Linux, for example)
+//! through `notify`, falling back to scanning every `--poll-ms` where native
+//! events are unavailable or with `--poll` (network filesystems). The
+//! scenario file is always watched. A burst of changes is debounced into a
+//! single rerun, each run is reported as a compact delta against the previous
+//! one, and an optional artifacts root keeps only the most recent runs.
+//! Run directories are numbered past any left by earlier sessions, which
+//! count toward the kept window.
+
+use miette::{IntoDiagnostic, Result, WrapErr};
+use notify::{Event, PollWatcher, RecursiveMode, Watcher};
+use ptybox::artifacts::ArtifactsWriterConfig;
+use ptybox::model::{ErrorInfo, RunResult, RunStatus, StepStatus};
+use ptybox::runner::{load_scenario, run_scenario, RunnerError, RunnerOptions};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+/// How often the wait loop checks for interrupts between file events.
+const INTERRUPT_CHECK: Duration = Duration::from_millis(50);
+
+/// Options for `ptybox watch`.
+pub struct WatchOptions {
+    /// Scenario to rerun.
+    pub scenario: PathBuf,
+    /// Files or directories whose changes trigger a rerun.
+    pub paths: Vec<PathBuf>,
+    /// Quiet period after the last change before rerunning.
+    pub debounce: Duration,
+    /// Interval between scans when polling instead of using file events.
+    pub poll_interval: Duration,
+    /// Poll even where native file events are available.
+    pub poll: bool,
+    /// Root for per-run artifacts (`<root>/run-<n>`).
+    pub artifacts: Option<PathBuf>,
+    /// Number of most recent run artifact directories to keep.
+    pub keep: usize,
+    /// Stop after this many runs.
+    pub max_runs: Option<u64>,
+    /// Emit one JSON report per run instead of text.
+    pub json: bool,
+}
+
+/// Report emitted after every run.
+#[derive(Serialize)]
+struct WatchReport {
+    run: u64,
+    /// Paths whose change triggered the run (empty for the first run).
+    changed: Vec<String>,
+    status: RunStatus,
+    passed_steps: usize,
+    total_steps: usize,
+    duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    previous_status: Option<RunStatus>,
+    /// Steps that passed in the previous run and fail now.
+    newly_failing: Vec<String>,
+    /// Steps that failed in the previous run and pass now.
+    newly_passing: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    artifacts_dir: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<ErrorInfo>,
+}
+
+/// File events for the watched roots.
+struct ChangeWatcher {
+    /// Kept alive for as long as events are wanted.
+    _watcher: Box<dyn Watcher>,
+    events: Receiver<notify::Result<Event>>,
+    roots: Vec<PathBuf>,
+    exclude: Option<PathBuf>,
+}
+
+impl ChangeWatcher {
+    fn start(
+        roots: Vec<PathBuf>,
+        exclude: Option<PathBuf>,
+        options: &WatchOptions,
+    ) -> Result<Self> {
+        let (tx, events) = mpsc::channel();
+        if !options.poll {
+            match watch_roots(notify::recommended_watcher(tx.clone()), &roots) {
+                Ok(watcher) => {
+                    return Ok(Self {
+                        _watcher: watcher,
+                        events,
+                        roots,
+                        exclude,
+                    })
+                }
+                Err(err) => {
+                    tracing::warn!(error = %err, "file events unavailable; polling for changes");
+                }
+            }
+        }
+        // Hash contents too: on filesystems with coarse timestamps a
+        // same-size edit leaves the metadata unchanged.
+        let config = notify::Config::default()
+            .with_poll_interval(options.poll_interval)
+            .with_compare_contents(true);
+        let watcher = watch_roots(PollWatcher::new(tx, config), &roots)
+            .into_diagnostic()
+            .wrap_err("failed to watch paths")?;
+        Ok(Self {
+            _watcher: watcher,
+            events,
+            roots,
+            exclude,
+        })
+    }
+
+    /// Watched paths `event` added, removed, or modified.
+    fn changed_paths(&self, event: &Event) -> Vec<PathBuf> {
+        let kind = event.kind;
+        if !(kind.is_create() || kind.is_modify() || kind.is_remove()) {
+            return Vec::new();
+        }
+        event
+            .paths
+            .iter()
+            .filter(|path| self.is_watched(path))
+            .cloned()
+            .collect()
+    }
+
+    /// Whether `path` is a root or under one, outside the artifacts root and
+    /// hidden entries.
+    fn is_watched(&self, path: &Path) -> bool {
+        if self
+            .exclude
+            .as_deref()
+            .is_some_and(|dir| path.starts_with(dir))
+        {
+            return false;
+        }
+        self.roots.iter().any(|root| {
+            path.strip_prefix(root)
+                .is_ok_and(|rest| !rest.components().any(is_hidden))
+        })
+    }
+}
+
+fn is_hidden(component: Component) -> bool {
+    matches!(component, Component::Normal(name) if name.to_string_lossy().starts_with('.'))
+}
+
+/// Watch directories recursively, and files through their directory so that
+/// editors which save by replacing the file are still seen.
+fn watch_roots<W: Watcher + 'static>(
+    watcher: notify::Result<W>,
+    roots: &[PathBuf],
+) -> notify::Result<Box<dyn Watcher>> {
+    let mut watcher = watcher?;
+    for root in roots {
+        if root.is_dir() {
+            watcher.watch(root, RecursiveMode::Recursive)?;
+        } else {
+            let dir = root.parent().unwrap_or(root);
+            watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        }
+    }
+    Ok(Box::new(watcher))
+}
+
+/// Pass/fail per step name; skipped steps are not recorded.
+type StepOutcomes = BTreeMap<String, bool>;
+
+/// Watch `options.paths` and rerun the scenario on every debounced change.
+///
+/// Runs once immediately, then until interrupted or `max_runs` is reached.
+/// The process exits with the status of the last run, like `ptybox run`.
+pub fn cmd_watch(options: &WatchOptions) -> Result<()> {
+    let exclude = match &options.artifacts {
+        Some(root) => {
+            fs::create_dir_all(root).into_diagnostic()?;
+            Some(fs::canonicalize(root).into_diagnostic()?)
+        }
+        None => None,
+    };
+    let mut roots: Vec<PathBuf> = options.paths.iter().map(|path| absolute(path)).collect();
+    roots.push(absolute(&options.scenario));
+    let watcher = ChangeWatcher::start(roots, exclude, options)?;
+
+    let mut previous: Option<(RunStatus, StepOutcomes)> = None;
+    let mut kept: VecDeque<PathBuf> = options
+        .artifacts
+        .as_deref()
+        .map(existing_runs)
+        .unwrap_or_default();
+    let first_dir = kept.back().and_then(|dir| run_number(dir)).unwrap_or(0) + 1;
+    let mut changed = Vec::new();
+    let mut run = 0;
+    loop {
+        run += 1;
+        let artifacts_dir = options
+            .artifacts
+            .as_ref()
+            .map(|root| root.join(format!("run-{}", first_dir + run - 1)));
+        let (report, outcomes) = run_once(options, run, artifacts_dir, changed, previous.as_ref());
+        if let Some(dir) = &report.artifacts_dir {
+            kept.push_back(PathBuf::from(dir));
+            prune_artifacts(&mut kept, options.keep);
+        }
+        emit_report(options.json, &report)?;
+        let status = report.status.clone();
+        let error = report.error;
+        previous = Some((status.clone(), outcomes));
+
+        let done = options.max_runs.is_some_and(|max| run >= max);
+        let next = if done {
+            None
+        } else {
+            wait_for_change(&watcher, options)
+        };
+        match next {
+            Some(paths) => changed = paths,
+            None => {
+                exit_with(&status, error.as_ref());
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn interrupted() -> bool {
+    crate::INTERRUPTED.load(Ordering::SeqCst)
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Wait until a change is followed by `debounce` of quiet; `None` if
+/// interrupted or the watcher stopped.
+fn wait_for_change(watcher: &ChangeWatcher, options: &WatchOptions) -> Option<Vec<String>> {
+    let mut changed = BTreeSet::new();
+    let mut last_change: Option<Instant> = None;
+    loop {
+        if interrupted() {
+            return None;
+        }
+        match watcher.events.recv_timeout(INTERRUPT_CHECK) {
+            Ok(Ok(event)) => {
+                let paths = watcher.changed_paths(&event);
+                if !paths.is_empty() {
+                    changed.extend(paths);
+                    last_change = Some(Instant::now());
+                }
+            }
+            Ok(Err(err)) => tracing::warn!(error = %err, "file watch error"),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return None,
+        }
+        if last_change.is_some_and(|at| at.elapsed() >= options.debounce) {
+            return Some(
+                changed
+                    .into_iter()
+                    .map(|path: PathBuf| path.display().to_string())
+                    .collect(),
+            );
+        }
+    }
+}
+
+/// `run-<n>` directories already under `root`, oldest first.
+fn existing_runs(root: &Path) -> VecDeque<PathBuf> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return VecDeque::new();
+    };
+    let mut runs: Vec<(u64, PathBuf)> = entries
+        .filter_map(std::result::Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .filter_map(|path| Some((run_number(&path)?, path)))
+        .collect();
+    runs.sort();
+    runs.into_iter().map(|(_, path)| path).collect()
+}
+
+/// `n` of a `run-<n>` directory.
+fn run_number(dir: &Path) -> Option<u64> {
+    dir.file_name()?
+        .to_str()?
+        .strip_prefix("run-")?
+        .parse()
+        .ok()
+}
+
+fn run_once(
+    options: &WatchOptions,
+    run: u64,
+    artifacts_dir: Option<PathBuf>,
+    changed: Vec<String>,
+    previous: Option<&(RunStatus, StepOutcomes)>,
+) -> (WatchReport, StepOutcomes) {
+    let started = Instant::now();
+    let result = options
+        .scenario
+        .to_str()
+        .ok_or_else(|| RunnerError::cli_invalid_arg("scenario path is not valid UTF-8"))
+        .and_then(load_scenario)
+        .and_then(|scenario| {
+            let runner_options = RunnerOptions {
+                artifacts: artifacts_dir.clone().map(|dir| ArtifactsWriterConfig {
+                    dir,
+                    overwrite: false,
+                }),
+                ..RunnerOptions::default()
+            };
+            run_scenario(scenario, runner_options)
+        });
+    let duration_ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+
+    let (status, outcomes, total_steps, error) = summarize(result);
+    let passed_steps = outcomes.values().filter(|passed| **passed).count();
+    let (newly_failing, newly_passing) = previous
+        .map(|(_, before)| step_delta(before, &outcomes))
+        .unwrap_or_default();
+    let report = WatchReport {
+        run,
+        changed,
+        status,
+        passed_steps,
+        total_steps,
+        duration_ms,
+        previous_status: previous.map(|(status, _)| status.clone()),
+        newly_failing,
+        newly_passing,
+        artifacts_dir: artifacts_dir
+            .filter(|dir| dir.exists())
+            .map(|dir| dir.display().to_string()),
+        error,
+    };
+    (report, outcomes)
+}
+
+fn summarize(
+    result: Result<RunResult, RunnerError>,
+) -> (RunStatus, StepOutcomes, usize, Option<ErrorInfo>) {
+    match result {
+        Ok(run_result) => {
+            let steps = run_result.steps.unwrap_or_default();
+            let outcomes = steps
+                .iter()
+                .filter(|step| step.status != StepStatus::Skipped)
+                .map(|step| (step.name.clone(), step.status == StepStatus::Passed))
+                .collect();
+            (run_result.status, outcomes, steps.len(), run_result.error)
+        }
+        Err(err) => (
+            RunStatus::Errored,
+            StepOutcomes::new(),
+            0,
+            Some(err.to_error_info()),
+        ),
+    }
+}
+
+/// Steps whose outcome flipped: (newly failing, newly passing).
+fn step_delta(before: &StepOutcomes, after: &StepOutcomes) -> (Vec<String>, Vec<String>) {
+    let flipped = |from: bool| -> Vec<String> {
+        after
+            .iter()
+            .filter(|(name, passed)| **passed != from && before.get(*name) == Some(&from))
+            .map(|(name, _)| name.clone())
+            .collect()
+    };
+    (flipped(true), flipped(false))
+}
+
+/// Drop the oldest run directories beyond the rolling window.
+fn prune_artifacts(kept: &mut VecDeque<PathBuf>, keep: usize) {
+    while kept.len() > keep.max(1) {
+        if let Some(dir) = kept.pop_front() {
+            if let Err(err) = fs::remove_dir_all(&dir) {
+                tracing::warn!(dir = %dir.display(), error = %err, "failed to prune watch artifacts");
+            }
+        }
+    }
+}
+
+fn emit_report(json: bool, report: &WatchReport) -> Result<()> {
+    if json {
+        return crate::emit_json(report);
+    }
+    println!("{}", format_report(report));
+    Ok(())
+}
+
+fn format_report(report: &WatchReport) -> String {
+    let mut line = format!(
+        "[run {}] {} {}/{} steps ({}ms)",
+        report.run,
+        status_label(&report.status),
+        report.passed_steps,
+        report.total_steps,
+        report.duration_ms
+    );
+    if let Some(previous) = &report.previous_status {
+        if *previous != report.status {
+            let _ = write!(line, ", was {}", status_label(previous));
+        }
+    }
+    if !report.newly_failing.is_empty() {
+        let _ = write!(line, "; newly failing: {}", report.newly_failing.join(", "));
+    }
+    if !report.newly_passing.is_empty() {
+        let _ = write!(line, "; fixed: {}", report.newly_passing.join(", "));
+    }
+    if let Some(error) = &report.error {
+        let _ = write!(line, "; {}: {}", error.code, error.message);
+    }
+    line
+}
+
+fn status_label(status: &RunStatus) -> &'static str {
+    match status {
+        RunStatus::Passed => "passed",
+        RunStatus::Failed => "failed",
+        RunStatus::Errored => "errored",
+        RunStatus::Canceled => "canceled",
+    }
+}
+
+/// Exit with the run's error code unless it passed.
+fn exit_with(status: &RunStatus, error: Option<&ErrorInfo>) {
+    if *status == RunStatus::Passed {
+        return;
+    }
+    std::process::exit(error.map_or(1, |err| crate::exit_code_for_error_code(&err.code)));
+}
@@ -0,0 +1,64 @@
+//! `ptybox why-differs`: rank the recorded differences between two runs.
+
+use miette::Result;
+use ptybox::artifacts::why_differs;
+use ptybox::model::{DivergenceReport, DivergenceRun, RunStatus};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Compare the runs in `a` and `b` and print the likely causes of their
+/// different outcomes, most likely first.
+pub fn cmd_why_differs(a: &Path, b: &Path, json: bool) -> Result<()> {
+    let report = match why_differs(a, b) {
+        Ok(report) => report,
+        Err(err) => return crate::emit_result(json, Err(err)),
+    };
+    if json {
+        return crate::emit_json(&report);
+    }
+    print_report(&report);
+    Ok(())
+}
+
+fn print_report(report: &DivergenceReport) {
+    println!("a: {}", describe(&report.a));
+    println!("b: {}", describe(&report.b));
+    if let Some(step) = &report.first_divergent_step {
+        println!("first divergent step: {step}");
+    }
+    if report.causes.is_empty() {
+        println!("no differences found in the recorded settings");
+    } else {
+        println!("likely causes, most likely first:");
+    }
+    for cause in &report.causes {
+        println!(
+            "{:>3}. [{}] {} {}: {} -> {}",
+            cause.rank,
+            cause.likelihood.as_str(),
+            cause.category.as_str(),
+            cause.field,
+            cause.a,
+            cause.b
+        );
+        println!("     {}", cause.reason);
+    }
+    for name in &report.not_compared {
+        println!("not compared: {name} (missing from at least one run)");
+    }
+}
+
+fn describe(run: &DivergenceRun) -> String {
+    let status = match run.status {
+        RunStatus::Passed => "passed",
+        RunStatus::Failed => "failed",
+        RunStatus::Errored => "errored",
+        RunStatus::Canceled => "canceled",
+    };
+    let mut line = format!("{}  {status}", run.artifacts_dir);
+    if let Some(code) = &run.error_code {
+        let _ = write!(line, "  {code}");
+    }
+    let _ = write!(line, "  {}ms", run.duration_ms);
+    line
+}
@@ -0,0 +1,167 @@
+// Test module - relaxed lint rules
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(missing_docs)]
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ptybox::model::policy::PolicyBuilder;
+use serde_json::{json, Value};
+
+fn temp_dir(prefix: &str) -> PathBuf {
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let dir = std::env::temp_dir().join(format!("ptybox-cli-test-{prefix}-{stamp}"));
+    fs::create_dir_all(&dir).unwrap();
+    dir.canonicalize().unwrap()
+}
+
+fn ptybox(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn step(id: u8, action: Value, expect: &str) -> Value {
+    json!({
+        "id": format!("00000000-0000-0000-0000-{id:012}"),
+        "name": format!("step {id}"),
+        "action": action,
+        "assert": [{ "type": "screen_contains", "payload": { "text": expect } }],
+        "timeout_ms": 2000,
+        "retries": 0,
+    })
+}
+
+/// Run a `/bin/cat` scenario that types, resizes, and exits, with the cast
+/// written when `asciicast` is set. Returns the artifacts directory.
+fn cat_run(prefix: &str, asciicast: bool) -> PathBuf {
+    let dir = temp_dir(prefix);
+    let mut policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_executables(vec!["/bin/cat".to_string()])
+        .allowed_write(vec![dir.display().to_string()]);
+    if asciicast {
+        policy = policy.artifacts_asciicast();
+    }
+    let scenario = json!({
+        "scenario_version": 1,
+        "metadata": { "name": "cast-demo" },
+        "run": {
+            "command": "/bin/cat",
+            "args": [],
+            "initial_size": { "rows": 4, "cols": 20 },
+            "policy": policy.build()
+        },
+        "steps": [
+            step(1, json!({ "type": "text", "payload": { "text": "hello\n" } }), "hello"),
+            step(2, json!({ "type": "resize", "payload": { "rows": 6, "cols": 30 } }), "hello"),
+            step(3, json!({ "type": "key", "payload": { "key": "Ctrl+D" } }), "hello"),
+        ]
+    });
+    let scenario_path = dir.join("scenario.json");
+    fs::write(
+        &scenario_path,
+        serde_json::to_vec_pretty(&scenario).unwrap(),
+    )
+    .unwrap();
+    let artifacts = dir.join("artifacts");
+    let run = ptybox(&[
+        "run",
+        "--json",
+        "--scenario",
+        scenario_path.to_str().unwrap(),
+        "--artifacts",
+        artifacts.to_str().unwrap(),
+    ]);
+    assert!(
+        run.status.success(),
+        "{}",
+        String::from_utf8_lossy(&run.stderr)
+    );
+    artifacts
+}
+
+fn cast_lines(data: &str) -> Vec<Value> {
+    data.lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn asciicast_policy_writes_session_cast() {
+    let artifacts = cat_run("cast-live", true);
+    let cast = fs::read_to_string(artifacts.join("session.cast")).unwrap();
+    let lines = cast_lines(&cast);
+
+    assert_eq!(lines[0]["version"], 2);
+    assert_eq!(lines[0]["width"], 20);
+    assert_eq!(lines[0]["height"], 4);
+    let events = &lines[1..];
+    assert!(events.iter().all(|event| event[0].is_f64()));
+    let output: String = events
+        .iter()
+        .filter(|event| event[1] == "o")
+        .map(|event| event[2].as_str().unwrap())
+        .collect();
+    assert!(output.contains("hello"), "{output}");
+    assert!(events
+        .iter()
+        .any(|event| event[1] == "r" && event[2] == "30x6"));
+
+    let checksums: Value =
+        serde_json::from_str(&fs::read_to_string(artifacts.join("checksums.json")).unwrap())
+            .unwrap();
+    assert!(checksums["session.cast"].is_string());
+}
+
+#[test]
+fn trace_format_asciicast_converts_event_logs() {
+    let artifacts = cat_run("cast-trace", false);
+    assert!(!artifacts.join("session.cast").exists());
+    let output = artifacts.with_file_name("out.cast");
+    let trace = ptybox(&[
+        "trace",
+        "--artifacts",
+        artifacts.to_str().unwrap(),
+        "--format",
+        "asciicast",
+        "-o",
+        output.to_str().unwrap(),
+    ]);
+    assert!(
+        trace.status.success(),
+        "{}",
+        String::from_utf8_lossy(&trace.stderr)
+    );
+
+    let converted = fs::read_to_string(&output).unwrap();
+    let lines = cast_lines(&converted);
+    assert_eq!(lines[0]["version"], 2);
+    assert!(lines
+        .iter()
+        .any(|event| event[1] == "o" && event[2].as_str().unwrap().contains("hello")));
+
+    let live = cat_run("cast-compare", true);
+    let converted = ptybox(&[
+        "trace",
+        "--artifacts",
+        live.to_str().unwrap(),
+        "--format",
+        "asciicast",
+        "-o",
+        live.with_file_name("out.cast").to_str().unwrap(),
+    ]);
+    assert!(converted.status.success());
+    assert_eq!(
+        fs::read_to_string(live.with_file_name("out.cast")).unwrap(),
+        fs::read_to_string(live.join("session.cast")).unwrap()
+    );
+}
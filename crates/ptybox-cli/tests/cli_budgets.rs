@@ -10,8 +10,10 @@
 #![allow(clippy::unwrap_used)]
 #![allow(clippy::expect_used)]
 #![allow(clippy::cast_possible_truncation)]
+#![allow(clippy::too_many_lines)]
 #![allow(missing_docs)]
 
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
@@ -19,12 +21,13 @@ use std::thread;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use ptybox::model::policy::{
-    Budgets, EnvPolicy, ExecPolicy, FsPolicy, NetworkEnforcementAck, NetworkPolicy, Policy,
-    ReplayPolicy, SandboxMode, POLICY_VERSION,
+    Budgets, DeterminismPolicy, EnvPolicy, ExecPolicy, FsPolicy, InputPolicy,
+    NetworkEnforcementAck, NetworkPolicy, Policy, ProcessPolicy, ReplayPolicy, SandboxFallback,
+    SandboxMode, POLICY_VERSION,
 };
 use ptybox::model::{
-    Action, ActionType, Assertion, RunResult, Scenario, ScenarioMetadata, Step, StepId,
-    TerminalSize,
+    Action, ActionType, Assertion, OutputBufferConfig, ResizeConfig, RunResult, Scenario,
+    ScenarioDefaults, ScenarioMetadata, Step, StepId, TerminalSize,
 };
 
 fn temp_dir(prefix: &str) -> PathBuf {
@@ -43,12 +46,24 @@ fn write_policy(path: &Path, policy: &Policy) {
 }
 
 fn process_is_running(pid: u32) -> bool {
-    Command::new("/bin/kill")
+    let alive = Command::new("/bin/kill")
         .arg("-0")
         .arg(pid.to_string())
         .stderr(Stdio::null())
         .status()
         .map(|status| status.success())
+        .unwrap_or(false);
+    // A killed orphan stays a zombie until init reaps it; it is not running.
+    alive && !process_is_zombie(pid)
+}
+
+fn process_is_zombie(pid: u32) -> bool {
+    fs::read_to_string(format!("/proc/{pid}/stat"))
+        .ok()
+        .and_then(|stat| {
+            stat.rsplit_once(')')
+                .map(|(_, rest)| rest.trim_start().starts_with('Z'))
+        })
         .unwrap_or(false)
 }
 
@@ -82,6 +97,7 @@ fn base_policy(work_dir: &Path, allowed_exec: Vec<String>) -> Policy {
     Policy {
         policy_version: POLICY_VERSION,
         sandbox: SandboxMode::Disabled { ack: true },
+        sandbox_fallback: SandboxFallback::Deny,
         network: NetworkPolicy::Disabled,
         network_enforcement: NetworkEnforcementAck {
             unenforced_ack: true,
@@ -92,6 +108,7 @@ fn base_policy(work_dir: &Path, allowed_exec: Vec<String>) -> Policy {
             working_dir: Some(work_dir.display().to_string()),
             write_ack: false,
             strict_write: false,
+            write_quotas: Default::default(),
         },
         exec: ExecPolicy {
             allowed_executables: allowed_exec,
@@ -105,6 +122,15 @@ fn base_policy(work_dir: &Path, allowed_exec: Vec<String>) -> Policy {
         budgets: Budgets::default(),
         artifacts: Default::default(),
         replay: ReplayPolicy::default(),
+        input: InputPolicy::default(),
+        determinism: DeterminismPolicy::default(),
+        process: ProcessPolicy::default(),
+        sockets: Vec::new(),
+        remote: Default::default(),
+        terminal: Default::default(),
+        log_forward: Default::default(),
+        substitution_allowlist: Vec::new(),
+        grants: Vec::new(),
     }
 }
 
@@ -206,6 +232,10 @@ fn scenario_max_steps_is_enforced() {
         metadata: ScenarioMetadata {
             name: "steps".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: ptybox::model::RunConfig {
             command: "/bin/cat".to_string(),
@@ -215,6 +245,12 @@ fn scenario_max_steps_is_enforced() {
             policy: ptybox::model::scenario::PolicyRef::File {
                 path: policy_path.display().to_string(),
             },
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![
             Step {
@@ -227,9 +263,17 @@ fn scenario_max_steps_is_enforced() {
                 assert: vec![Assertion {
                     assertion_type: "screen_contains".to_string(),
                     payload: serde_json::json!({"text": "hello"}),
+                    inherited: false,
                 }],
                 timeout_ms: 100,
                 retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
             Step {
                 id: StepId::new(),
@@ -241,8 +285,20 @@ fn scenario_max_steps_is_enforced() {
                 assert: Vec::new(),
                 timeout_ms: 100,
                 retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
         ],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
     let scenario_path = dir.join("scenario.json");
     fs::write(
@@ -286,6 +342,10 @@ fn scenario_wait_budget_is_enforced() {
         metadata: ScenarioMetadata {
             name: "wait".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: ptybox::model::RunConfig {
             command: "/bin/cat".to_string(),
@@ -295,6 +355,12 @@ fn scenario_wait_budget_is_enforced() {
             policy: ptybox::model::scenario::PolicyRef::File {
                 path: policy_path.display().to_string(),
             },
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![Step {
             id: StepId::new(),
@@ -311,7 +377,19 @@ fn scenario_wait_budget_is_enforced() {
             assert: Vec::new(),
             timeout_ms: 1000,
             retries: 0,
+            skip_default_assertions: false,
+            sample_interval_ms: None,
+            expected_screen: None,
+            capture: None,
+            env_overlay: BTreeMap::new(),
+            metadata: BTreeMap::new(),
+            vars: Vec::new(),
         }],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
     let scenario_path = dir.join("scenario.json");
     fs::write(
@@ -356,6 +434,10 @@ fn scenario_runtime_budget_is_enforced_after_steps_complete() {
         metadata: ScenarioMetadata {
             name: "runtime-after-steps".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: ptybox::model::RunConfig {
             command: "/usr/bin/yes".to_string(),
@@ -365,6 +447,12 @@ fn scenario_runtime_budget_is_enforced_after_steps_complete() {
             policy: ptybox::model::scenario::PolicyRef::File {
                 path: policy_path.display().to_string(),
             },
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![Step {
             id: StepId::new(),
@@ -381,7 +469,19 @@ fn scenario_runtime_budget_is_enforced_after_steps_complete() {
             assert: Vec::new(),
             timeout_ms: 50,
             retries: 0,
+            skip_default_assertions: false,
+            sample_interval_ms: None,
+            expected_screen: None,
+            capture: None,
+            env_overlay: BTreeMap::new(),
+            metadata: BTreeMap::new(),
+            vars: Vec::new(),
         }],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
     let scenario_path = dir.join("scenario.json");
     fs::write(
@@ -480,6 +580,10 @@ fn scenario_max_steps_boundary_just_below_limit() {
         metadata: ScenarioMetadata {
             name: "steps-below".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: ptybox::model::RunConfig {
             command: "/bin/cat".to_string(),
@@ -489,6 +593,12 @@ fn scenario_max_steps_boundary_just_below_limit() {
             policy: ptybox::model::scenario::PolicyRef::File {
                 path: policy_path.display().to_string(),
             },
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![Step {
             id: StepId::new(),
@@ -500,7 +610,19 @@ fn scenario_max_steps_boundary_just_below_limit() {
             assert: Vec::new(),
             timeout_ms: 100,
             retries: 0,
+            skip_default_assertions: false,
+            sample_interval_ms: None,
+            expected_screen: None,
+            capture: None,
+            env_overlay: BTreeMap::new(),
+            metadata: BTreeMap::new(),
+            vars: Vec::new(),
         }],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
     let scenario_path = dir.join("scenario.json");
     fs::write(
@@ -540,6 +662,10 @@ fn scenario_max_steps_boundary_at_limit() {
         metadata: ScenarioMetadata {
             name: "steps-at".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: ptybox::model::RunConfig {
             command: "/bin/cat".to_string(),
@@ -549,6 +675,12 @@ fn scenario_max_steps_boundary_at_limit() {
             policy: ptybox::model::scenario::PolicyRef::File {
                 path: policy_path.display().to_string(),
             },
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![Step {
             id: StepId::new(),
@@ -560,7 +692,19 @@ fn scenario_max_steps_boundary_at_limit() {
             assert: Vec::new(),
             timeout_ms: 100,
             retries: 0,
+            skip_default_assertions: false,
+            sample_interval_ms: None,
+            expected_screen: None,
+            capture: None,
+            env_overlay: BTreeMap::new(),
+            metadata: BTreeMap::new(),
+            vars: Vec::new(),
         }],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
     let scenario_path = dir.join("scenario.json");
     fs::write(
@@ -629,6 +773,10 @@ fn terminal_resize_boundary_at_max() {
         metadata: ScenarioMetadata {
             name: "resize-max".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: ptybox::model::RunConfig {
             command: "/bin/cat".to_string(),
@@ -638,6 +786,12 @@ fn terminal_resize_boundary_at_max() {
             policy: ptybox::model::scenario::PolicyRef::File {
                 path: policy_path.display().to_string(),
             },
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![
             Step {
@@ -650,6 +804,13 @@ fn terminal_resize_boundary_at_max() {
                 assert: Vec::new(),
                 timeout_ms: 100,
                 retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
             Step {
                 id: StepId::new(),
@@ -661,8 +822,20 @@ fn terminal_resize_boundary_at_max() {
                 assert: Vec::new(),
                 timeout_ms: 100,
                 retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
         ],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
     let scenario_path = dir.join("scenario.json");
     fs::write(
@@ -701,6 +874,10 @@ fn terminal_resize_boundary_exceeds_max() {
         metadata: ScenarioMetadata {
             name: "resize-exceed".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: ptybox::model::RunConfig {
             command: "/bin/cat".to_string(),
@@ -710,6 +887,12 @@ fn terminal_resize_boundary_exceeds_max() {
             policy: ptybox::model::scenario::PolicyRef::File {
                 path: policy_path.display().to_string(),
             },
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![Step {
             id: StepId::new(),
@@ -721,7 +904,19 @@ fn terminal_resize_boundary_exceeds_max() {
             assert: Vec::new(),
             timeout_ms: 100,
             retries: 0,
+            skip_default_assertions: false,
+            sample_interval_ms: None,
+            expected_screen: None,
+            capture: None,
+            env_overlay: BTreeMap::new(),
+            metadata: BTreeMap::new(),
+            vars: Vec::new(),
         }],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
     let scenario_path = dir.join("scenario.json");
     fs::write(
@@ -768,6 +963,10 @@ fn terminal_resize_boundary_zero_rejected() {
         metadata: ScenarioMetadata {
             name: "resize-zero".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: ptybox::model::RunConfig {
             command: "/bin/cat".to_string(),
@@ -777,6 +976,12 @@ fn terminal_resize_boundary_zero_rejected() {
             policy: ptybox::model::scenario::PolicyRef::File {
                 path: policy_path.display().to_string(),
             },
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![Step {
             id: StepId::new(),
@@ -788,7 +993,19 @@ fn terminal_resize_boundary_zero_rejected() {
             assert: Vec::new(),
             timeout_ms: 100,
             retries: 0,
+            skip_default_assertions: false,
+            sample_interval_ms: None,
+            expected_screen: None,
+            capture: None,
+            env_overlay: BTreeMap::new(),
+            metadata: BTreeMap::new(),
+            vars: Vec::new(),
         }],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
     let scenario_path = dir.join("scenario.json");
     fs::write(
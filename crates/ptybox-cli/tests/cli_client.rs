@@ -0,0 +1,82 @@
+// Test module - relaxed lint rules
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(missing_docs)]
+
+//! End-to-end tests for `ptybox-client` against the real `ptybox driver`.
+
+use std::fs;
+use std::path::PathBuf;
+
+use ptybox::model::policy::PolicyBuilder;
+use ptybox::model::{Action, Condition};
+use ptybox::runner::ErrorCode;
+use ptybox_client::{block_on, ClientError, DriverClient, SpawnConfig};
+
+fn spawn_config(command: &str) -> (tempfile::TempDir, SpawnConfig) {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let policy_path = dir.path().join("policy.json");
+    let policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_executables(vec![command.to_string()])
+        .build();
+    fs::write(&policy_path, serde_json::to_vec(&policy).unwrap()).unwrap();
+    let config = SpawnConfig {
+        program: Some(PathBuf::from(env!("CARGO_BIN_EXE_ptybox"))),
+        policy: Some(policy_path),
+        command: command.to_string(),
+        ..SpawnConfig::default()
+    };
+    (dir, config)
+}
+
+#[test]
+fn client_drives_cat_through_typed_api() {
+    let (_dir, config) = spawn_config("/bin/cat");
+    block_on(async {
+        let mut client = DriverClient::spawn(config).await.unwrap();
+        assert!(client.handshake().supports_action("wait"));
+
+        client.act(Action::text("typed-hello")).await.unwrap();
+        let observation = client
+            .wait(Condition::screen_contains("typed-hello"), Some(5_000))
+            .await
+            .unwrap();
+        assert!(observation
+            .screen
+            .lines
+            .iter()
+            .any(|line| line.contains("typed-hello")));
+
+        let queried = client.query().await.unwrap();
+        assert!(queried
+            .screen
+            .lines
+            .iter()
+            .any(|line| line.contains("typed-hello")));
+
+        client.terminate().await.unwrap();
+    });
+}
+
+#[test]
+fn client_surfaces_wait_timeout_as_error_code() {
+    let (_dir, config) = spawn_config("/bin/cat");
+    block_on(async {
+        let mut client = DriverClient::spawn(config).await.unwrap();
+        let err = client
+            .wait(Condition::screen_contains("never-appears"), Some(100))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), ErrorCode::Timeout);
+        assert!(matches!(err, ClientError::Driver { .. }));
+    });
+}
+
+#[test]
+fn client_reports_policy_denial_before_handshake() {
+    let (_dir, mut config) = spawn_config("/bin/cat");
+    config.command = "/bin/echo".to_string();
+    let err = block_on(DriverClient::spawn(config)).err().unwrap();
+    assert_eq!(err.code(), ErrorCode::PolicyDenied, "{err}");
+}
@@ -103,3 +103,89 @@ fn completions_rejects_invalid_shell() {
         "completions with invalid shell should fail"
     );
 }
+
+fn complete(args: &[&str]) -> Vec<String> {
+    let output = ptybox_bin()
+        .arg("__complete")
+        .args(args)
+        .output()
+        .expect("failed to execute");
+    assert!(
+        output.status.success(),
+        "__complete should succeed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn completions_hook_dynamic_values_for_each_shell() {
+    for (shell, hook) in [
+        ("bash", "ptybox __complete \"${kind}\""),
+        ("zsh", ":_ptybox__dynamic scenario'"),
+        ("fish", "(ptybox __complete session (commandline -ct))"),
+    ] {
+        let output = ptybox_bin()
+            .arg("completions")
+            .arg(shell)
+            .output()
+            .expect("failed to execute");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            stdout.contains(hook),
+            "{shell} completions should call __complete"
+        );
+    }
+}
+
+#[test]
+fn complete_lists_scenario_files_and_directories() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    for name in ["login.json", "menu.yaml", "notes.txt"] {
+        std::fs::write(dir.path().join(name), "{}").expect("write");
+    }
+    std::fs::create_dir(dir.path().join("nested")).expect("mkdir");
+    let prefix = format!("{}/", dir.path().display());
+
+    let scenarios = complete(&["scenario", &prefix]);
+    assert_eq!(
+        scenarios,
+        vec![
+            format!("{prefix}login.json"),
+            format!("{prefix}menu.yaml"),
+            format!("{prefix}nested/"),
+        ]
+    );
+    assert_eq!(
+        complete(&["artifacts", &prefix]),
+        vec![format!("{prefix}nested/")]
+    );
+}
+
+#[test]
+fn complete_lists_normalize_and_compare_values() {
+    assert_eq!(
+        complete(&["normalize", "run"]),
+        vec!["run_id", "run_timestamps"]
+    );
+    assert_eq!(
+        complete(&["compare", "snapshots,"]),
+        vec![
+            "snapshots,transcript",
+            "snapshots,run_result",
+            "snapshots,events"
+        ]
+    );
+}
+
+#[test]
+fn complete_helper_is_hidden_from_help() {
+    let output = ptybox_bin()
+        .arg("--help")
+        .output()
+        .expect("failed to execute");
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("__complete"));
+}
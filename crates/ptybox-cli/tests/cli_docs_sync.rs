@@ -76,6 +76,7 @@ fn cli_reference_tracks_help_flags() {
         "--scenario",
         "--explain-policy",
         "--verbose",
+        "--progress",
         "--tui",
         "--artifacts",
         "--overwrite",
@@ -0,0 +1,38 @@
+// Test module - relaxed lint rules
+#![allow(clippy::default_trait_access)]
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::unreadable_literal)]
+#![allow(clippy::cast_lossless)]
+#![allow(clippy::inefficient_to_string)]
+#![allow(clippy::panic)]
+#![allow(clippy::manual_assert)]
+#![allow(clippy::uninlined_format_args)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(clippy::cast_possible_truncation)]
+#![allow(missing_docs)]
+
+use std::process::Command;
+
+#[test]
+fn doctor_reports_a_working_pty() {
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args(["doctor", "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "doctor failed: {output:?}");
+    let probe: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(probe["ok"], true);
+    assert_eq!(probe["attempts"], 1);
+    assert!(probe.get("error").is_none());
+    assert_eq!(probe["diagnostics"]["ptmx_present"], true);
+
+    let text = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .arg("doctor")
+        .output()
+        .unwrap();
+    assert!(text.status.success());
+    let stdout = String::from_utf8_lossy(&text.stdout);
+    assert!(stdout.starts_with("pty: ok"), "{stdout}");
+    assert!(stdout.contains("/dev/ptmx: present"), "{stdout}");
+}
@@ -25,7 +25,7 @@ use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use ptybox::model::policy::PolicyBuilder;
-use ptybox::model::{DriverResponseStatus, DriverResponseV2, PROTOCOL_VERSION};
+use ptybox::model::{DriverHistory, DriverResponseStatus, DriverResponseV2, PROTOCOL_VERSION};
 use serde_json::json;
 
 static TEST_DIR_COUNTER: AtomicU64 = AtomicU64::new(0);
@@ -281,6 +281,55 @@ fn driver_accepts_resize_action() {
     let _ = child.wait();
 }
 
+#[test]
+fn driver_rejects_stale_expected_screen_hash() {
+    let mut child = spawn_driver("/bin/cat");
+    consume_handshake(&mut child);
+
+    let first = send_action(
+        &mut child,
+        request("req-first", "resize", json!({"rows": 24, "cols": 80})),
+    );
+    let current_hash = first.screen_hash.expect("screen_hash should be present");
+    assert_eq!(current_hash.len(), 16);
+
+    let mut stale = request("req-stale", "text", json!({"text": "ignored"}));
+    stale["expected_screen_hash"] = json!("0000000000000000");
+    let response = send_action(&mut child, stale);
+    assert_eq!(response.status, DriverResponseStatus::Error);
+    let error = response.error.expect("error should be present");
+    assert_eq!(error.code, "E_STALE_OBSERVATION");
+    assert!(response.action_metrics.is_none());
+    assert!(response.observation.is_some());
+    let fresh_hash = response
+        .screen_hash
+        .expect("fresh screen_hash should be present");
+    assert_eq!(
+        error
+            .context
+            .as_ref()
+            .and_then(|c| c.get("actual_screen_hash")),
+        Some(&json!(fresh_hash))
+    );
+
+    // The session stays usable and a matching precondition is accepted.
+    let mut fresh = request("req-fresh", "text", json!({"text": "hello"}));
+    fresh["expected_screen_hash"] = json!(fresh_hash);
+    let response = send_action(&mut child, fresh);
+    assert_eq!(response.status, DriverResponseStatus::Ok);
+    assert_eq!(
+        response
+            .action_metrics
+            .as_ref()
+            .expect("metrics should be present")
+            .sequence,
+        2
+    );
+
+    let _ = send_action(&mut child, request("req-term", "terminate", json!({})));
+    let _ = child.wait();
+}
+
 #[test]
 fn driver_terminate_exits_cleanly() {
     let mut child = spawn_driver("/bin/cat");
@@ -709,13 +758,9 @@ fn driver_observation_includes_transcript_delta() {
     let _ = child.wait();
 }
 
-#[test]
-fn driver_artifacts_are_replay_compatible() {
-    let dir = temp_dir("driver-artifacts");
-    let artifacts_dir = dir.join("artifacts");
-    let policy_path = write_driver_policy_with_artifacts("/bin/cat", &artifacts_dir);
-
-    let mut child = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+/// Spawn `/bin/cat` under the driver, writing artifacts to `artifacts_dir`.
+fn spawn_driver_with_artifacts(policy_path: &Path, artifacts_dir: &Path) -> Child {
+    Command::new(env!("CARGO_BIN_EXE_ptybox"))
         .args([
             "driver",
             "--stdio",
@@ -732,7 +777,16 @@ fn driver_artifacts_are_replay_compatible() {
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .spawn()
-        .expect("failed to spawn driver");
+        .expect("failed to spawn driver")
+}
+
+#[test]
+fn driver_artifacts_are_replay_compatible() {
+    let dir = temp_dir("driver-artifacts");
+    let artifacts_dir = dir.join("artifacts");
+    let policy_path = write_driver_policy_with_artifacts("/bin/cat", &artifacts_dir);
+
+    let mut child = spawn_driver_with_artifacts(&policy_path, &artifacts_dir);
     consume_handshake(&mut child);
 
     let response = send_action(
@@ -775,3 +829,297 @@ fn driver_artifacts_are_replay_compatible() {
         String::from_utf8_lossy(&replay_output.stderr)
     );
 }
+
+#[test]
+fn driver_coalesces_unchanged_polls() {
+    let dir = temp_dir("driver-coalesce");
+    let artifacts_dir = dir.join("artifacts");
+    let policy_path = write_driver_policy_with_artifacts("/bin/cat", &artifacts_dir);
+
+    let mut child = spawn_driver_with_artifacts(&policy_path, &artifacts_dir);
+    consume_handshake(&mut child);
+
+    let typed = send_action(
+        &mut child,
+        request("req-text", "text", json!({"text": "coalesce"})),
+    );
+    assert_eq!(typed.status, DriverResponseStatus::Ok);
+    assert!(!typed.unchanged);
+
+    // Without the flag, an identical poll is a full step.
+    let full = send_action(&mut child, request("req-full", "observe", json!({})));
+    assert!(!full.unchanged);
+    assert!(full.observation.is_some());
+    assert_eq!(full.action_metrics.expect("metrics").sequence, 2);
+    let hash = full.screen_hash.expect("screen_hash should be present");
+
+    let mut poll = request("req-poll", "observe", json!({}));
+    poll["coalesce"] = json!(true);
+    let coalesced = send_action(&mut child, poll);
+    assert_eq!(coalesced.status, DriverResponseStatus::Ok);
+    assert!(coalesced.unchanged);
+    assert!(coalesced.observation.is_none());
+    assert!(coalesced.action_metrics.is_none());
+    assert_eq!(coalesced.screen_hash.as_deref(), Some(hash.as_str()));
+    assert_eq!(
+        coalesced.budget_status.expect("budget status").steps_used,
+        2
+    );
+
+    // Input is never coalesced.
+    let mut input = request("req-input", "text", json!({"text": "!"}));
+    input["coalesce"] = json!(true);
+    let input = send_action(&mut child, input);
+    assert!(!input.unchanged);
+    assert_eq!(input.action_metrics.expect("metrics").sequence, 3);
+
+    let _ = send_action(&mut child, request("req-term", "terminate", json!({})));
+    let status = child.wait().expect("failed to wait for child");
+    assert!(status.success());
+    assert_steps_recorded(&artifacts_dir, 4);
+}
+
+#[test]
+fn driver_history_summarizes_recent_requests() {
+    let dir = temp_dir("driver-history");
+    let artifacts_dir = dir.join("artifacts");
+    let policy_path = write_driver_policy_with_artifacts("/bin/cat", &artifacts_dir);
+
+    let mut child = spawn_driver_with_artifacts(&policy_path, &artifacts_dir);
+    let handshake = consume_handshake(&mut child);
+    assert!(handshake["supported_actions"]
+        .as_array()
+        .unwrap()
+        .contains(&json!("history")));
+
+    let typed = send_action(
+        &mut child,
+        request("req-text", "text", json!({"text": "history"})),
+    );
+    let mut stale = request("req-stale", "key", json!({"key": "Enter"}));
+    stale["expected_screen_hash"] = json!("0123456789abcdef");
+    let stale = send_action(&mut child, stale);
+    let mut poll = request("req-poll", "observe", json!({}));
+    poll["coalesce"] = json!(true);
+    assert!(send_action(&mut child, poll).unchanged);
+
+    let response = send_action(&mut child, request("req-hist", "history", json!({})));
+    assert_eq!(response.status, DriverResponseStatus::Ok);
+    assert!(response.observation.is_none());
+    assert!(response.action_metrics.is_none());
+    assert_eq!(response.screen_hash, stale.screen_hash);
+    assert_eq!(response.budget_status.expect("budget status").steps_used, 1);
+    let history = response.history.expect("history should be present");
+    assert_history_entries(&history, typed.screen_hash.as_deref());
+
+    let latest = send_action(
+        &mut child,
+        request("req-last", "history", json!({"limit": 1})),
+    );
+    let latest = latest.history.expect("history should be present");
+    assert_eq!(latest.total, 3);
+    assert_eq!(latest.entries.len(), 1);
+    assert_eq!(latest.entries[0].request_id, "req-poll");
+
+    // A bad limit is rejected without ending the session.
+    let invalid = send_action(
+        &mut child,
+        request("req-bad", "history", json!({"limit": 0})),
+    );
+    assert_eq!(invalid.error.expect("error").code, "E_PROTOCOL");
+    let _ = send_action(&mut child, request("req-term", "terminate", json!({})));
+    assert!(child.wait().expect("failed to wait for child").success());
+    let actions = fs::read_to_string(artifacts_dir.join("driver-actions.jsonl")).unwrap();
+    assert!(!actions.contains("req-hist"));
+}
+
+/// Check the log of `req-text`, the stale `req-stale`, and the coalesced
+/// `req-poll`.
+fn assert_history_entries(history: &DriverHistory, text_hash: Option<&str>) {
+    assert_eq!(history.total, 3);
+    let ids: Vec<&str> = history
+        .entries
+        .iter()
+        .map(|entry| entry.request_id.as_str())
+        .collect();
+    assert_eq!(ids, ["req-text", "req-stale", "req-poll"]);
+    let text = &history.entries[0];
+    assert_eq!(text.sequence, Some(1));
+    assert_eq!(text.screen_hash.as_deref(), text_hash);
+    assert!(text.observation_timestamp_ms.is_some());
+    assert!(text.started_at_ms <= text.ended_at_ms);
+    let rejected = &history.entries[1];
+    assert_eq!(rejected.status, DriverResponseStatus::Error);
+    assert_eq!(rejected.error_code.as_deref(), Some("E_STALE_OBSERVATION"));
+    assert_eq!(rejected.sequence, None);
+    assert!(history.entries[2].unchanged);
+}
+
+/// Check that the driver recorded `steps` actions and snapshots, none of
+/// them for the coalesced `req-poll`.
+fn assert_steps_recorded(artifacts_dir: &Path, steps: usize) {
+    let actions = fs::read_to_string(artifacts_dir.join("driver-actions.jsonl")).unwrap();
+    assert!(!actions.contains("req-poll"));
+    assert_eq!(actions.lines().count(), steps);
+    let snapshots = fs::read_dir(artifacts_dir.join("snapshots"))
+        .unwrap()
+        .count();
+    assert_eq!(snapshots, steps);
+}
+
+// =============================================================================
+// Session Budget Tests
+// =============================================================================
+
+fn spawn_driver_with_budgets(
+    artifacts_dir: &Path,
+    idle_ms: Option<u64>,
+    lifetime_ms: Option<u64>,
+) -> Child {
+    let mut builder = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_executables(vec!["/bin/cat".to_string()])
+        .allowed_write(vec![artifacts_dir.display().to_string()]);
+    if let Some(ms) = idle_ms {
+        builder = builder.max_session_idle_ms(ms);
+    }
+    if let Some(ms) = lifetime_ms {
+        builder = builder.max_session_lifetime_ms(ms);
+    }
+    spawn_cat_driver_with_artifacts(builder, artifacts_dir)
+}
+
+fn spawn_cat_driver_with_artifacts(builder: PolicyBuilder, artifacts_dir: &Path) -> Child {
+    let dir = temp_dir("policy-cat-artifacts");
+    let policy_path = dir.join("policy.json");
+    let payload = serde_json::to_vec_pretty(&builder.build()).unwrap();
+    fs::write(&policy_path, payload).unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "driver",
+            "--stdio",
+            "--json",
+            "--policy",
+            policy_path.to_str().unwrap(),
+            "--artifacts",
+            artifacts_dir.to_str().unwrap(),
+            "--overwrite",
+            "--",
+            "/bin/cat",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to spawn driver")
+}
+
+#[test]
+fn driver_terminates_idle_session_and_writes_artifacts() {
+    let artifacts_dir = temp_dir("driver-idle").join("artifacts");
+    let mut child = spawn_driver_with_budgets(&artifacts_dir, Some(300), None);
+    let handshake = consume_handshake(&mut child);
+    assert_eq!(handshake["budgets"]["max_session_idle_ms"], 300);
+
+    // Keep stdin open but send nothing.
+    let line = read_response_line(&mut child);
+    let response: DriverResponseV2 = serde_json::from_str(&line).unwrap();
+    assert_eq!(response.status, DriverResponseStatus::Error);
+    let error = response.error.unwrap();
+    assert_eq!(error.code, "E_TIMEOUT");
+    assert!(error.message.contains("idle"), "got: {}", error.message);
+
+    let status = child.wait().unwrap();
+    assert_eq!(status.code(), Some(4));
+
+    let run: serde_json::Value =
+        serde_json::from_slice(&fs::read(artifacts_dir.join("run.json")).unwrap()).unwrap();
+    assert_eq!(run["status"], "errored");
+    assert_eq!(run["error"]["code"], "E_TIMEOUT");
+}
+
+#[test]
+fn driver_enforces_session_lifetime_while_client_is_silent() {
+    let artifacts_dir = temp_dir("driver-lifetime").join("artifacts");
+    let mut child = spawn_driver_with_budgets(&artifacts_dir, None, Some(600));
+    consume_handshake(&mut child);
+
+    let response = send_action(
+        &mut child,
+        request("req-1", "text", json!({"text": "still here"})),
+    );
+    assert_eq!(response.status, DriverResponseStatus::Ok);
+
+    let line = read_response_line(&mut child);
+    let response: DriverResponseV2 = serde_json::from_str(&line).unwrap();
+    let error = response.error.unwrap();
+    assert_eq!(error.code, "E_TIMEOUT");
+    assert!(error.message.contains("lifetime"), "got: {}", error.message);
+
+    assert_eq!(child.wait().unwrap().code(), Some(4));
+    let actions = fs::read_to_string(artifacts_dir.join("driver-actions.jsonl")).unwrap();
+    assert_eq!(actions.lines().count(), 1);
+}
+
+// =============================================================================
+// Raw Action Tests
+// =============================================================================
+
+#[test]
+fn driver_raw_action_denied_without_policy_flag() {
+    let mut child = spawn_driver("/bin/cat");
+    consume_handshake(&mut child);
+
+    let response = send_action(
+        &mut child,
+        request("req-raw", "raw", json!({"hex": "1b5b3f3130303468"})),
+    );
+    assert_eq!(response.status, DriverResponseStatus::Error);
+    let error = response.error.unwrap();
+    assert_eq!(error.code, "E_POLICY_DENIED");
+    assert!(error.message.contains("raw"), "got: {}", error.message);
+    let _ = child.wait();
+}
+
+#[test]
+fn driver_raw_action_writes_bytes_and_records_artifact() {
+    let artifacts_dir = temp_dir("driver-raw").join("artifacts");
+    let builder = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_executables(vec!["/bin/cat".to_string()])
+        .allowed_write(vec![artifacts_dir.display().to_string()])
+        .allow_raw_bytes()
+        .max_raw_bytes(16);
+    let mut child = spawn_cat_driver_with_artifacts(builder, &artifacts_dir);
+    consume_handshake(&mut child);
+
+    // "raw-ok" as base64
+    let response = send_action(
+        &mut child,
+        request("req-raw", "raw", json!({"base64": "cmF3LW9r"})),
+    );
+    assert_eq!(response.status, DriverResponseStatus::Ok);
+    let response = send_action(
+        &mut child,
+        request(
+            "req-wait",
+            "wait",
+            json!({"condition": {"type": "screen_contains", "payload": {"text": "raw-ok"}}}),
+        ),
+    );
+    assert_eq!(response.status, DriverResponseStatus::Ok);
+
+    let response = send_action(
+        &mut child,
+        request("req-big", "raw", json!({"hex": "41".repeat(17)})),
+    );
+    assert_eq!(response.error.unwrap().code, "E_POLICY_DENIED");
+    let _ = child.wait();
+
+    let records = fs::read_to_string(artifacts_dir.join("raw-input.jsonl")).unwrap();
+    let record: serde_json::Value = serde_json::from_str(records.lines().next().unwrap()).unwrap();
+    assert_eq!(record["step"], "driver-step-1");
+    assert_eq!(record["bytes"], 6);
+    assert_eq!(record["hex"], "7261772d6f6b");
+}
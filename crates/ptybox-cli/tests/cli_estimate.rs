@@ -0,0 +1,188 @@
+// Test module - relaxed lint rules
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(missing_docs)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ptybox::model::policy::PolicyBuilder;
+use ptybox::model::{EstimateBasis, EstimateReport, RunId, RunIndexEntry, RunStatus, StepId};
+use serde_json::json;
+
+fn temp_dir(prefix: &str) -> PathBuf {
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let dir = std::env::temp_dir().join(format!("ptybox-cli-test-{prefix}-{stamp}"));
+    fs::create_dir_all(&dir).unwrap();
+    dir.canonicalize().unwrap()
+}
+
+fn ptybox(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// Write `dir/<name>.json`, a scenario with one step per `(timeout_ms,
+/// retries)` pair.
+fn write_scenario(dir: &Path, name: &str, steps: &[(u64, u32)]) {
+    let steps: Vec<_> = steps
+        .iter()
+        .map(|(timeout_ms, retries)| {
+            json!({
+                "id": StepId::new(),
+                "name": "wait",
+                "action": {
+                    "type": "wait",
+                    "payload": { "condition": { "type": "process_exited", "payload": {} } }
+                },
+                "assert": [],
+                "timeout_ms": timeout_ms,
+                "retries": retries
+            })
+        })
+        .collect();
+    let scenario = json!({
+        "scenario_version": 1,
+        "metadata": { "name": name },
+        "run": {
+            "command": "/bin/true",
+            "args": [],
+            "initial_size": { "rows": 4, "cols": 20 },
+            "policy": PolicyBuilder::new().build()
+        },
+        "steps": steps
+    });
+    fs::write(
+        dir.join(format!("{name}.json")),
+        serde_json::to_vec_pretty(&scenario).unwrap(),
+    )
+    .unwrap();
+}
+
+/// Write `root/runs.jsonl` with one entry per `(scenario, status,
+/// duration_ms)`.
+fn write_index(root: &Path, runs: &[(&str, RunStatus, u64)]) {
+    let lines: Vec<String> = runs
+        .iter()
+        .map(|(scenario, status, duration_ms)| {
+            let entry = RunIndexEntry {
+                run_id: RunId::new(),
+                status: status.clone(),
+                tags: Vec::new(),
+                scenario: Some((*scenario).to_string()),
+                command: "/bin/true".to_string(),
+                artifacts_dir: root.join("run").display().to_string(),
+                recorded_at_ms: 0,
+                error_code: None,
+                duration_ms: Some(*duration_ms),
+            };
+            serde_json::to_string(&entry).unwrap()
+        })
+        .collect();
+    fs::write(root.join("runs.jsonl"), lines.join("\n") + "\n").unwrap();
+}
+
+fn estimate(args: &[&str]) -> EstimateReport {
+    let output = ptybox(&[&["estimate", "--json"], args].concat());
+    assert!(output.status.success(), "{args:?}: {output:?}");
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn estimate_combines_history_timeouts_and_matrix() {
+    let dir = temp_dir("estimate");
+    let scenarios = dir.join("scenarios");
+    fs::create_dir_all(scenarios.join("nested")).unwrap();
+    write_scenario(&scenarios, "login", &[(1000, 0), (2000, 1)]);
+    write_scenario(&scenarios.join("nested"), "search", &[(3000, 0)]);
+    fs::write(scenarios.join("policy.json"), "{}").unwrap();
+    write_index(
+        &dir,
+        &[
+            ("login", RunStatus::Passed, 400),
+            ("login", RunStatus::Failed, 900),
+            ("login", RunStatus::Passed, 500),
+            ("login", RunStatus::Errored, 5),
+            ("other", RunStatus::Passed, 10_000),
+        ],
+    );
+    let scenarios = scenarios.display().to_string();
+    let root = dir.display().to_string();
+
+    let report = estimate(&[
+        "--dir",
+        &scenarios,
+        "--root",
+        &root,
+        "--locale",
+        "C",
+        "--locale",
+        "de_DE.UTF-8",
+        "--timezone",
+        "UTC",
+    ]);
+    assert_eq!(report.matrix_cells, 2);
+    assert_eq!(report.scenarios.len(), 2, "{report:?}");
+
+    // Errored runs are ignored; the median of 400, 500, 900 is expected.
+    let login = &report.scenarios[0];
+    assert_eq!(login.scenario, "login");
+    assert_eq!(login.basis, EstimateBasis::History);
+    assert_eq!(login.history_runs, 3);
+    assert_eq!(login.timeout_ms, 1000 + 2 * 2000);
+    assert_eq!(
+        (login.expected_ms, login.low_ms, login.high_ms),
+        (500, 400, 900)
+    );
+    assert_eq!(
+        (login.total_ms, login.total_low_ms, login.total_high_ms),
+        (1000, 800, 1800)
+    );
+
+    // Never run: bounded by its declared timeouts.
+    let search = &report.scenarios[1];
+    assert_eq!(search.scenario, "search");
+    assert_eq!(search.basis, EstimateBasis::Timeouts);
+    assert_eq!(
+        (search.expected_ms, search.low_ms, search.high_ms),
+        (3000, 0, 3000)
+    );
+
+    assert_eq!(report.total_ms, 1000 + 6000);
+    assert_eq!(report.total_low_ms, 800);
+    assert_eq!(report.total_high_ms, 1800 + 6000);
+}
+
+#[test]
+fn estimate_prints_a_table_with_a_total() {
+    let dir = temp_dir("estimate-table");
+    write_scenario(&dir, "login", &[(1500, 0)]);
+    let output = ptybox(&["estimate", "--dir", &dir.display().to_string()]);
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 3, "{stdout}");
+    assert!(lines[0].starts_with("SCENARIO"), "{stdout}");
+    assert!(lines[1].starts_with("login"), "{stdout}");
+    assert!(lines[1].contains("timeouts"), "{stdout}");
+    assert!(lines[1].contains("0ms..1.5s"), "{stdout}");
+    assert!(lines[2].starts_with("total"), "{stdout}");
+}
+
+#[test]
+fn estimate_rejects_a_missing_directory() {
+    let dir = temp_dir("estimate-missing");
+    let missing = dir.join("missing").display().to_string();
+    let output = ptybox(&["estimate", "--dir", &missing, "--json"]);
+    assert!(!output.status.success());
+    let error: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(error["code"], "E_IO", "{error}");
+}
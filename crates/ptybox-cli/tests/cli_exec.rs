@@ -19,8 +19,9 @@ use std::process::{Command, Stdio};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use ptybox::model::policy::{
-    EnvPolicy, ExecPolicy, FsPolicy, NetworkEnforcementAck, NetworkPolicy, Policy, ReplayPolicy,
-    SandboxMode, POLICY_VERSION,
+    DeterminismPolicy, EnvPolicy, ExecPolicy, FsPolicy, InputPolicy, NetworkEnforcementAck,
+    NetworkPolicy, Policy, ProcessPolicy, ReplayPolicy, SandboxFallback, SandboxMode,
+    POLICY_VERSION,
 };
 use ptybox::model::{DriverResponseStatus, DriverResponseV2, Observation, RunResult, TerminalSize};
 use ptybox::policy::PolicyExplanation;
@@ -74,6 +75,7 @@ fn base_policy(work_dir: &Path, allowed_exec: Vec<String>) -> Policy {
     Policy {
         policy_version: POLICY_VERSION,
         sandbox: SandboxMode::Disabled { ack: true },
+        sandbox_fallback: SandboxFallback::Deny,
         network: NetworkPolicy::Disabled,
         network_enforcement: NetworkEnforcementAck {
             unenforced_ack: true,
@@ -84,6 +86,7 @@ fn base_policy(work_dir: &Path, allowed_exec: Vec<String>) -> Policy {
             working_dir: Some(work_dir.display().to_string()),
             write_ack: false,
             strict_write: false,
+            write_quotas: Default::default(),
         },
         exec: ExecPolicy {
             allowed_executables: allowed_exec,
@@ -97,6 +100,15 @@ fn base_policy(work_dir: &Path, allowed_exec: Vec<String>) -> Policy {
         budgets: Default::default(),
         artifacts: Default::default(),
         replay: ReplayPolicy::default(),
+        input: InputPolicy::default(),
+        determinism: DeterminismPolicy::default(),
+        process: ProcessPolicy::default(),
+        sockets: Vec::new(),
+        remote: Default::default(),
+        terminal: Default::default(),
+        log_forward: Default::default(),
+        substitution_allowlist: Vec::new(),
+        grants: Vec::new(),
     }
 }
 
@@ -304,6 +316,222 @@ fn exec_filters_environment_by_allowlist() {
     assert!(!transcript.contains("HOME="));
 }
 
+#[test]
+fn exec_records_acknowledgement_sources() {
+    let dir = temp_dir("exec-acknowledgements");
+    let policy_path = dir.join("policy.json");
+    let artifacts_dir = dir.join("artifacts");
+
+    let mut policy = base_policy(&dir, vec!["/bin/echo".to_string()]);
+    policy.fs.allowed_write = vec![artifacts_dir.display().to_string()];
+    write_policy(&policy_path, &policy);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .env_remove("PTYBOX_ACK_UNSAFE_SANDBOX")
+        .env_remove("PTYBOX_ACK_UNSAFE_WRITE")
+        .env("PTYBOX_ACK_UNSAFE_NETWORK", "1")
+        .args([
+            "exec",
+            "--json",
+            "--ack-unsafe-write",
+            "--policy",
+            policy_path.to_str().unwrap(),
+            "--artifacts",
+            artifacts_dir.to_str().unwrap(),
+            "--",
+            "/bin/echo",
+            "hi",
+        ])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "stderr={}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let run: RunResult = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        run.risk_summary.as_deref(),
+        Some(
+            "unsafe: sandbox_disabled (policy: sandbox_unsafe_ack), \
+             network_unenforced (env: PTYBOX_ACK_UNSAFE_NETWORK=1), \
+             fs_write (cli: --ack-unsafe-write)"
+        )
+    );
+    let report: serde_json::Value =
+        serde_json::from_slice(&fs::read(artifacts_dir.join("acknowledgements.json")).unwrap())
+            .unwrap();
+    let sources: Vec<(&str, &str)> = report["acknowledgements"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|ack| {
+            (
+                ack["option"].as_str().unwrap(),
+                ack["source"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        sources,
+        vec![
+            ("sandbox_disabled", "policy"),
+            ("network_unenforced", "env"),
+            ("fs_write", "cli"),
+        ]
+    );
+    assert!(
+        report["acknowledgements"][0]["recorded_at_ms"]
+            .as_u64()
+            .unwrap()
+            > 0
+    );
+    assert_eq!(report["summary"], run.risk_summary.unwrap().as_str());
+}
+
+#[test]
+fn exec_persists_substituted_policy_paths_in_artifacts() {
+    let dir = temp_dir("exec-policy-substitution");
+    let policy_path = dir.join("policy.json");
+    let artifacts_dir = dir.join("artifacts");
+
+    let mut policy = base_policy(&dir, vec!["/bin/echo".to_string()]);
+    policy.substitution_allowlist = vec!["cwd".to_string(), "env:PTYBOX_TEST_OUT".to_string()];
+    policy.fs.allowed_read = vec!["${cwd}".to_string()];
+    policy.fs.working_dir = Some("${cwd}".to_string());
+    policy.fs.allowed_write = vec!["${env:PTYBOX_TEST_OUT}".to_string()];
+    policy.fs.write_ack = true;
+    write_policy(&policy_path, &policy);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .current_dir(&dir)
+        .env("PTYBOX_TEST_OUT", &artifacts_dir)
+        .args([
+            "exec",
+            "--json",
+            "--policy",
+            policy_path.to_str().unwrap(),
+            "--artifacts",
+            artifacts_dir.to_str().unwrap(),
+            "--",
+            "/bin/echo",
+            "hi",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "stdout={}\nstderr={}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let persisted: Policy =
+        serde_json::from_slice(&fs::read(artifacts_dir.join("policy.json")).unwrap()).unwrap();
+    assert_eq!(persisted.fs.allowed_read, vec![dir.display().to_string()]);
+    assert_eq!(persisted.fs.working_dir, Some(dir.display().to_string()));
+    assert_eq!(
+        persisted.fs.allowed_write,
+        vec![artifacts_dir.display().to_string()]
+    );
+}
+
+#[test]
+fn exec_denies_policy_placeholder_missing_from_allowlist() {
+    let dir = temp_dir("exec-policy-substitution-denied");
+    let policy_path = dir.join("policy.json");
+
+    let mut policy = base_policy(&dir, vec!["/bin/echo".to_string()]);
+    policy.fs.allowed_read = vec!["${cwd}".to_string()];
+    write_policy(&policy_path, &policy);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .current_dir(&dir)
+        .args([
+            "exec",
+            "--json",
+            "--policy",
+            policy_path.to_str().unwrap(),
+            "--",
+            "/bin/echo",
+            "hi",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("E_POLICY_DENIED"), "stderr={stderr}");
+    assert!(stderr.contains("substitution_allowlist"), "stderr={stderr}");
+}
+
+#[cfg(not(feature = "sandbox-audit"))]
+#[test]
+fn exec_audit_sandbox_requires_feature() {
+    let dir = temp_dir("exec-audit-sandbox");
+    let policy_path = dir.join("policy.json");
+    let artifacts_dir = dir.join("artifacts");
+    write_policy(
+        &policy_path,
+        &base_policy(&dir, vec!["/bin/echo".to_string()]),
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "exec",
+            "--json",
+            "--policy",
+            policy_path.to_str().unwrap(),
+            "--artifacts",
+            artifacts_dir.to_str().unwrap(),
+            "--audit-sandbox",
+            "--",
+            "/bin/echo",
+            "hi",
+        ])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(12));
+    let err: ptybox::model::ErrorInfo = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(err.code, "E_CLI_INVALID_ARG");
+    assert!(err.message.contains("sandbox-audit"));
+}
+
+#[cfg(not(feature = "emulator-diff"))]
+#[test]
+fn exec_emulator_diff_requires_feature() {
+    let dir = temp_dir("exec-emulator-diff");
+    let policy_path = dir.join("policy.json");
+    let artifacts_dir = dir.join("artifacts");
+    write_policy(
+        &policy_path,
+        &base_policy(&dir, vec!["/bin/echo".to_string()]),
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "exec",
+            "--json",
+            "--policy",
+            policy_path.to_str().unwrap(),
+            "--artifacts",
+            artifacts_dir.to_str().unwrap(),
+            "--emulator-diff",
+            "--",
+            "/bin/echo",
+            "hi",
+        ])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(12));
+    let err: ptybox::model::ErrorInfo = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(err.code, "E_CLI_INVALID_ARG");
+    assert!(err.message.contains("emulator-diff"));
+}
+
 #[test]
 fn exec_invalid_utf8_returns_terminal_parse_error_and_writes_artifacts() {
     let dir = temp_dir("exec-invalid-utf8");
@@ -371,6 +599,81 @@ fn exec_rejects_existing_artifacts_without_overwrite() {
     assert_eq!(err.code, "E_POLICY_DENIED");
 }
 
+#[test]
+fn artifacts_status_reports_runs_under_a_quota() {
+    let dir = temp_dir("artifacts-quota");
+    let root = dir.join("runs");
+    let policy_path = dir.join("policy.json");
+    let mut policy = base_policy(&dir, vec!["/bin/echo".to_string()]);
+    policy.fs.allowed_write = vec![root.display().to_string()];
+    policy.fs.write_ack = true;
+    write_policy(&policy_path, &policy);
+    let ptybox = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_ptybox"))
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    let output = ptybox(&[
+        "artifacts",
+        "quota",
+        "--root",
+        root.to_str().unwrap(),
+        "--max-bytes",
+        "1000000",
+        "--keep-newest",
+        "2",
+        "--json",
+    ]);
+    assert!(output.status.success());
+    let status: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(status["quota"]["retention"]["keep_newest"], 2);
+    assert_eq!(status["used_bytes"], 0);
+
+    let artifacts_dir = root.join("echo");
+    let output = ptybox(&[
+        "exec",
+        "--json",
+        "--policy",
+        policy_path.to_str().unwrap(),
+        "--artifacts",
+        artifacts_dir.to_str().unwrap(),
+        "--",
+        "/bin/echo",
+        "hello",
+    ]);
+    assert!(output.status.success());
+    let run: RunResult = serde_json::from_slice(&output.stdout).unwrap();
+
+    let output = ptybox(&[
+        "artifacts",
+        "status",
+        "--root",
+        root.to_str().unwrap(),
+        "--json",
+    ]);
+    assert!(output.status.success());
+    let status: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(status["completed_runs"], 1);
+    assert_eq!(status["evictable_runs"], 0);
+    assert_eq!(status["runs"][0]["run_id"], run.run_id.to_string());
+    assert_eq!(status["runs"][0]["dir"], "echo");
+    assert_eq!(status["runs"][0]["status"], "passed");
+    let used = status["used_bytes"].as_u64().unwrap();
+    assert!(used > 0);
+    assert_eq!(status["available_bytes"], 1000000 - used);
+
+    let output = ptybox(&[
+        "artifacts",
+        "status",
+        "--root",
+        dir.to_str().unwrap(),
+        "--json",
+    ]);
+    assert_eq!(output.status.code(), Some(10));
+}
+
 #[test]
 fn explain_policy_outputs_json() {
     let dir = temp_dir("explain");
@@ -836,6 +1139,10 @@ fn run_explain_policy_uses_scenario_config() {
         metadata: ptybox::model::ScenarioMetadata {
             name: "cli-explain".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: ptybox::model::RunConfig {
             command: "/bin/echo".to_string(),
@@ -843,8 +1150,19 @@ fn run_explain_policy_uses_scenario_config() {
             cwd: Some(dir.display().to_string()),
             initial_size: TerminalSize::default(),
             policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy)),
+            separate_stderr: false,
+            output_buffer: ptybox::model::OutputBufferConfig::default(),
+            resize: ptybox::model::ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: Vec::new(),
+        defaults: ptybox::model::ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
     let scenario_path = dir.join("scenario.json");
     fs::write(
@@ -868,3 +1186,54 @@ fn run_explain_policy_uses_scenario_config() {
     let explanation: PolicyExplanation = serde_json::from_slice(&output.stdout).unwrap();
     assert!(!explanation.allowed);
 }
+
+#[test]
+fn why_differs_compares_two_exec_runs() {
+    let dir = temp_dir("exec-why-differs");
+    let policy_path = dir.join("policy.json");
+    let mut policy = base_policy(&dir, vec!["/bin/echo".to_string()]);
+    policy.fs.allowed_write = vec![dir.display().to_string()];
+    policy.fs.write_ack = true;
+    write_policy(&policy_path, &policy);
+    for (name, word) in [("a", "one"), ("b", "two")] {
+        let status = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+            .args(["exec", "--json", "--policy", policy_path.to_str().unwrap()])
+            .arg("--artifacts")
+            .arg(dir.join(name))
+            .args(["--", "/bin/echo", word])
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success());
+        assert!(dir.join(name).join("host.json").exists());
+        assert!(dir.join(name).join("env.json").exists());
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args(["why-differs", "--json", "--a"])
+        .arg(dir.join("a"))
+        .arg("--b")
+        .arg(dir.join("b"))
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let causes = report["causes"].as_array().unwrap();
+    assert_eq!(causes[0]["rank"], 1);
+    assert_eq!(causes[0]["field"], "args");
+    assert_eq!(causes[0]["likelihood"], "high");
+    assert_eq!(causes[0]["b"], serde_json::json!(["two"]));
+
+    let text = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args(["why-differs", "--a"])
+        .arg(dir.join("a"))
+        .arg("--b")
+        .arg(dir.join("b"))
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&text.stdout);
+    assert!(
+        stdout.contains(r#"1. [high] scenario args: ["one"] -> ["two"]"#),
+        "{stdout}"
+    );
+}
@@ -10,6 +10,7 @@
 #![allow(clippy::unwrap_used)]
 #![allow(clippy::expect_used)]
 #![allow(clippy::cast_possible_truncation)]
+#![allow(clippy::too_many_lines)]
 #![allow(missing_docs)]
 
 //! Integration tests using fixture TUI programs.
@@ -22,6 +23,7 @@
 //! - `ptybox-alt-screen`: Uses alternate screen buffer
 //! - `ptybox-unicode-test`: Prints Unicode/CJK/emoji
 
+use std::collections::BTreeMap;
 use std::fs;
 use std::io::{BufRead, Write};
 use std::path::{Path, PathBuf};
@@ -30,12 +32,14 @@ use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use ptybox::model::policy::{
-    EnvPolicy, ExecPolicy, FsPolicy, NetworkEnforcementAck, NetworkPolicy, Policy, ReplayPolicy,
-    SandboxMode, POLICY_VERSION,
+    DeterminismPolicy, EnvPolicy, ExecPolicy, FsPolicy, InputPolicy, NetworkEnforcementAck,
+    NetworkPolicy, Policy, ProcessPolicy, ReplayPolicy, SandboxFallback, SandboxMode,
+    POLICY_VERSION,
 };
 use ptybox::model::{
-    Action, ActionType, Assertion, DriverResponseStatus, DriverResponseV2, Observation, RunResult,
-    RunStatus, Scenario, ScenarioMetadata, Step, StepId, TerminalSize, PROTOCOL_VERSION,
+    Action, ActionType, Assertion, DriverResponseStatus, DriverResponseV2, Observation,
+    OutputBufferConfig, ResizeConfig, RunResult, RunStatus, Scenario, ScenarioDefaults,
+    ScenarioMetadata, Step, StepId, TerminalSize, PROTOCOL_VERSION,
 };
 
 static DRIVER_REQUEST_SEQUENCE: AtomicU64 = AtomicU64::new(1);
@@ -65,6 +69,7 @@ fn base_policy(work_dir: &Path, allowed_exec: Vec<String>) -> Policy {
     Policy {
         policy_version: POLICY_VERSION,
         sandbox: SandboxMode::Disabled { ack: true },
+        sandbox_fallback: SandboxFallback::Deny,
         network: NetworkPolicy::Disabled,
         network_enforcement: NetworkEnforcementAck {
             unenforced_ack: true,
@@ -75,6 +80,7 @@ fn base_policy(work_dir: &Path, allowed_exec: Vec<String>) -> Policy {
             working_dir: Some(work_dir.display().to_string()),
             write_ack: false,
             strict_write: false,
+            write_quotas: Default::default(),
         },
         exec: ExecPolicy {
             allowed_executables: allowed_exec,
@@ -88,6 +94,15 @@ fn base_policy(work_dir: &Path, allowed_exec: Vec<String>) -> Policy {
         budgets: Default::default(),
         artifacts: Default::default(),
         replay: ReplayPolicy::default(),
+        input: InputPolicy::default(),
+        determinism: DeterminismPolicy::default(),
+        process: ProcessPolicy::default(),
+        sockets: Vec::new(),
+        remote: Default::default(),
+        terminal: Default::default(),
+        log_forward: Default::default(),
+        substitution_allowlist: Vec::new(),
+        grants: Vec::new(),
     }
 }
 
@@ -518,6 +533,10 @@ fn delay_output_fixture_wait_condition() {
         metadata: ScenarioMetadata {
             name: "delay-wait".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: ptybox::model::RunConfig {
             command: fixture.clone(),
@@ -525,6 +544,12 @@ fn delay_output_fixture_wait_condition() {
             cwd: Some(dir.display().to_string()),
             initial_size: TerminalSize::default(),
             policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy)),
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![
             Step {
@@ -537,6 +562,13 @@ fn delay_output_fixture_wait_condition() {
                 assert: Vec::new(),
                 timeout_ms: 5000,
                 retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
             // Wait for process to exit (it exits after printing the delayed message)
             Step {
@@ -549,8 +581,20 @@ fn delay_output_fixture_wait_condition() {
                 assert: Vec::new(),
                 timeout_ms: 5000,
                 retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
         ],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
 
     let scenario_path = dir.join("scenario.json");
@@ -884,6 +928,10 @@ fn exec_timeout_includes_step_context() {
         metadata: ScenarioMetadata {
             name: "timeout".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: ptybox::model::RunConfig {
             command: fixture,
@@ -891,6 +939,12 @@ fn exec_timeout_includes_step_context() {
             cwd: Some(dir.display().to_string()),
             initial_size: TerminalSize::default(),
             policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy)),
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![Step {
             id: StepId::new(),
@@ -902,7 +956,19 @@ fn exec_timeout_includes_step_context() {
             assert: Vec::new(),
             timeout_ms: 50, // Will timeout
             retries: 0,
+            skip_default_assertions: false,
+            sample_interval_ms: None,
+            expected_screen: None,
+            capture: None,
+            env_overlay: BTreeMap::new(),
+            metadata: BTreeMap::new(),
+            vars: Vec::new(),
         }],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
 
     let scenario_path = dir.join("scenario.json");
@@ -944,6 +1010,10 @@ fn scenario_assertion_failure_returns_correct_exit_code() {
         metadata: ScenarioMetadata {
             name: "assert-fail".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: ptybox::model::RunConfig {
             command: fixture,
@@ -951,6 +1021,12 @@ fn scenario_assertion_failure_returns_correct_exit_code() {
             cwd: Some(dir.display().to_string()),
             initial_size: TerminalSize::default(),
             policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy)),
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![Step {
             id: StepId::new(),
@@ -962,10 +1038,23 @@ fn scenario_assertion_failure_returns_correct_exit_code() {
             assert: vec![Assertion {
                 assertion_type: "screen_contains".to_string(),
                 payload: serde_json::json!({"text": "goodbye"}), // Will fail
+                inherited: false,
             }],
             timeout_ms: 100,
             retries: 0,
+            skip_default_assertions: false,
+            sample_interval_ms: None,
+            expected_screen: None,
+            capture: None,
+            env_overlay: BTreeMap::new(),
+            metadata: BTreeMap::new(),
+            vars: Vec::new(),
         }],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
 
     let scenario_path = dir.join("scenario.json");
@@ -1013,6 +1102,10 @@ fn scenario_resize_action() {
         metadata: ScenarioMetadata {
             name: "resize-scenario".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: ptybox::model::RunConfig {
             command: fixture.clone(),
@@ -1020,44 +1113,53 @@ fn scenario_resize_action() {
             cwd: Some(dir.display().to_string()),
             initial_size: TerminalSize::default(),
             policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy)),
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
-        steps: vec![
-            // Resize to 40x120
-            Step {
-                id: StepId::new(),
-                name: "resize".to_string(),
-                action: Action {
-                    action_type: ActionType::Resize,
-                    payload: serde_json::json!({"rows": 40, "cols": 120}),
-                },
-                assert: Vec::new(),
-                timeout_ms: 1000,
-                retries: 0,
-            },
-            // Type some text to verify we can still interact
-            Step {
-                id: StepId::new(),
-                name: "type".to_string(),
-                action: Action {
-                    action_type: ActionType::Text,
-                    payload: serde_json::json!({"text": "resized"}),
-                },
-                assert: Vec::new(),
-                timeout_ms: 1000,
-                retries: 0,
-            },
-            Step {
-                id: StepId::new(),
-                name: "terminate".to_string(),
-                action: Action {
-                    action_type: ActionType::Terminate,
-                    payload: serde_json::json!({}),
-                },
-                assert: Vec::new(),
-                timeout_ms: 1000,
-                retries: 0,
+        // Resize to 40x120, type some text to verify we can still interact,
+        // then terminate.
+        steps: [
+            (
+                "resize",
+                ActionType::Resize,
+                serde_json::json!({"rows": 40, "cols": 120}),
+            ),
+            (
+                "type",
+                ActionType::Text,
+                serde_json::json!({"text": "resized"}),
+            ),
+            ("terminate", ActionType::Terminate, serde_json::json!({})),
+        ]
+        .into_iter()
+        .map(|(name, action_type, payload)| Step {
+            id: StepId::new(),
+            name: name.to_string(),
+            action: Action {
+                action_type,
+                payload,
             },
-        ],
+            assert: Vec::new(),
+            timeout_ms: 1000,
+            retries: 0,
+            skip_default_assertions: false,
+            sample_interval_ms: None,
+            expected_screen: None,
+            capture: None,
+            env_overlay: BTreeMap::new(),
+            metadata: BTreeMap::new(),
+            vars: Vec::new(),
+        })
+        .collect(),
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
 
     let scenario_path = dir.join("scenario.json");
@@ -0,0 +1,91 @@
+//! Tests for `ptybox fuzz`.
+// Test module - relaxed lint rules
+#![allow(clippy::expect_used)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::indexing_slicing)]
+
+use std::fs;
+use std::process::Command;
+use tempfile::tempdir;
+
+use ptybox::model::policy::PolicyBuilder;
+
+#[test]
+fn fuzz_writes_a_reproduction_that_replays_the_crash() {
+    let dir = tempdir().unwrap();
+    let policy_path = dir.path().join("policy.json");
+    let policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allow_shell()
+        .allowed_executables(vec!["/bin/sh".to_string()])
+        .max_runtime_ms(10_000)
+        .build();
+    fs::write(&policy_path, serde_json::to_vec(&policy).unwrap()).unwrap();
+    let scenario_path = dir.path().join("repro.json");
+    let script = "/bin/stty raw -echo; while c=$(/bin/dd bs=1 count=1 2>/dev/null); do \
+                  case \"$c\" in [0-9]) echo 'panicked at fuzz'; /bin/sleep 5;; esac; done";
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "fuzz",
+            "--json",
+            "--policy",
+            policy_path.to_str().unwrap(),
+            "--seed",
+            "42",
+            "--iterations",
+            "3",
+            "--steps",
+            "30",
+            "--step-timeout-ms",
+            "20",
+            "-o",
+            scenario_path.to_str().unwrap(),
+            "--",
+            "/bin/sh",
+            "-c",
+            script,
+        ])
+        .output()
+        .expect("run ptybox fuzz");
+    assert_eq!(output.status.code(), Some(5), "{output:?}");
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["seed"], 42);
+    assert_eq!(report["failure"]["kind"], "watcher");
+    assert_eq!(report["failure"]["watcher"], "crash-output");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "run",
+            "--json",
+            "--scenario",
+            scenario_path.to_str().unwrap(),
+        ])
+        .output()
+        .expect("run reproduction");
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["status"], "failed");
+    assert_eq!(result["error"]["context"]["watcher"], "crash-output");
+}
+
+#[test]
+fn fuzz_refuses_to_replace_output() {
+    let dir = tempdir().unwrap();
+    let scenario_path = dir.path().join("repro.json");
+    fs::write(&scenario_path, "{}").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "fuzz",
+            "--json",
+            "-o",
+            scenario_path.to_str().unwrap(),
+            "--",
+            "/bin/echo",
+        ])
+        .output()
+        .expect("run ptybox fuzz");
+    assert!(!output.status.success());
+    let error: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(error["code"], "E_CLI_INVALID_ARG");
+}
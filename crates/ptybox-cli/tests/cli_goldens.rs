@@ -0,0 +1,277 @@
+// Test module - relaxed lint rules
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(missing_docs)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ptybox::model::policy::PolicyBuilder;
+use ptybox::model::{GoldenEntry, GoldenPruneReport, GoldenUpdateReport, StepId};
+use serde_json::json;
+
+fn temp_dir(prefix: &str) -> PathBuf {
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let dir = std::env::temp_dir().join(format!("ptybox-cli-test-{prefix}-{stamp}"));
+    fs::create_dir_all(&dir).unwrap();
+    dir.canonicalize().unwrap()
+}
+
+fn ptybox(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn json_output<T: serde::de::DeserializeOwned>(args: &[&str]) -> T {
+    let output = ptybox(args);
+    assert!(output.status.success(), "{args:?}: {output:?}");
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+/// Write `dir/scenarios/<name>.json`, a scenario that echoes `text`.
+fn write_scenario(dir: &Path, name: &str, text: &str) -> PathBuf {
+    let policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_executables(vec!["/bin/echo".to_string()])
+        .allowed_read(vec![dir.display().to_string()])
+        .allowed_write(vec![dir.join("runs").display().to_string()])
+        .build();
+    let scenario = json!({
+        "scenario_version": 1,
+        "metadata": { "name": name },
+        "run": {
+            "command": "/bin/echo",
+            "args": [text],
+            "cwd": dir.display().to_string(),
+            "initial_size": { "rows": 4, "cols": 20 },
+            "policy": policy,
+        },
+        "steps": [{
+            "id": StepId::new(),
+            "name": "exit",
+            "action": {
+                "type": "wait",
+                "payload": { "condition": { "type": "process_exited", "payload": {} } }
+            },
+            "assert": [{ "type": "screen_contains", "payload": { "text": text } }],
+            "timeout_ms": 2000,
+            "retries": 0
+        }]
+    });
+    let path = dir.join("scenarios").join(format!("{name}.json"));
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, serde_json::to_vec_pretty(&scenario).unwrap()).unwrap();
+    path
+}
+
+/// Run a scenario with artifacts in `dir/runs/<run>`, indexed in
+/// `dir/runs/runs.jsonl`, and return the artifacts directory.
+fn run(dir: &Path, scenario: &Path, run: &str) -> PathBuf {
+    let artifacts = dir.join("runs").join(run);
+    let output = ptybox(&[
+        "run",
+        "--json",
+        "--scenario",
+        scenario.to_str().unwrap(),
+        "--artifacts",
+        artifacts.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "{output:?}");
+    artifacts
+}
+
+/// Store the run in `artifacts` as golden `name` under `dir/goldens`.
+fn record_golden(dir: &Path, artifacts: &Path, name: &str) {
+    let output = ptybox(&[
+        "artifacts",
+        "slim",
+        "--artifacts",
+        artifacts.to_str().unwrap(),
+        "--output",
+        dir.join("goldens").join(name).to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "{output:?}");
+}
+
+#[test]
+fn goldens_list_and_prune_unreferenced_baselines() {
+    let dir = temp_dir("goldens-prune");
+    let menu = write_scenario(&dir, "menu", "main menu");
+    let about = write_scenario(&dir, "about", "about box");
+    record_golden(&dir, &run(&dir, &menu, "menu-1"), "menu");
+    record_golden(&dir, &run(&dir, &about, "about-1"), "about");
+    fs::remove_file(&about).unwrap();
+
+    let baseline = dir.join("goldens");
+    let scenarios = dir.join("scenarios");
+    let (baseline, scenarios) = (baseline.to_str().unwrap(), scenarios.to_str().unwrap());
+    let entries: Vec<GoldenEntry> = json_output(&[
+        "goldens",
+        "list",
+        "--json",
+        "--baseline",
+        baseline,
+        "--scenarios",
+        scenarios,
+    ]);
+    let names: Vec<&str> = entries.iter().map(|entry| entry.name.as_str()).collect();
+    assert_eq!(names, vec!["about", "menu"]);
+    assert_eq!(entries[0].referenced_by, Some(Vec::new()));
+    assert_eq!(
+        entries[1].referenced_by,
+        Some(vec![menu.display().to_string()])
+    );
+    assert!(entries[1].snapshots > 0 && entries[1].bytes > 0);
+    assert_eq!(entries[1].updates, 0);
+
+    let listed: Vec<GoldenEntry> =
+        json_output(&["goldens", "list", "--json", "--baseline", baseline]);
+    assert!(listed.iter().all(|entry| entry.referenced_by.is_none()));
+
+    let prune = |extra: &[&str]| -> GoldenPruneReport {
+        let mut args = vec![
+            "goldens",
+            "prune",
+            "--json",
+            "--baseline",
+            baseline,
+            "--scenarios",
+            scenarios,
+        ];
+        args.extend_from_slice(extra);
+        json_output(&args)
+    };
+    let dry = prune(&["--dry-run"]);
+    assert_eq!(dry.removed, vec!["about"]);
+    assert_eq!(dry.kept, 1);
+    assert!(dry.dry_run && dry.bytes_freed > 0);
+    assert!(dir.join("goldens/about").is_dir());
+
+    let pruned = prune(&[]);
+    assert_eq!(pruned.removed, vec!["about"]);
+    assert!(!dir.join("goldens/about").exists());
+    assert!(dir.join("goldens/menu").is_dir());
+
+    let output = ptybox(&["goldens", "prune", "--baseline", baseline]);
+    assert!(!output.status.success(), "prune needs --scenarios");
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn goldens_prune_refuses_missing_unparsable_or_empty_scenarios() {
+    let dir = temp_dir("goldens-prune-guard");
+    let menu = write_scenario(&dir, "menu", "main menu");
+    record_golden(&dir, &run(&dir, &menu, "menu-1"), "menu");
+    let baseline = dir.join("goldens");
+    let prune = |scenarios: &Path| {
+        ptybox(&[
+            "goldens",
+            "prune",
+            "--json",
+            "--baseline",
+            baseline.to_str().unwrap(),
+            "--scenarios",
+            scenarios.to_str().unwrap(),
+        ])
+    };
+
+    let output = prune(&dir.join("scenarios-typo"));
+    assert!(!output.status.success(), "missing path: {output:?}");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("E_IO"));
+
+    let broken = dir.join("broken");
+    fs::create_dir_all(&broken).unwrap();
+    fs::write(broken.join("menu.json"), "{\"scenario_version\": 1,").unwrap();
+    let output = prune(&broken);
+    assert!(!output.status.success(), "unparsable scenario: {output:?}");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("E_PROTOCOL"));
+
+    let empty = dir.join("empty");
+    fs::create_dir_all(&empty).unwrap();
+    fs::write(empty.join("notes.json"), "{\"title\": \"not a scenario\"}").unwrap();
+    let output = prune(&empty);
+    assert!(!output.status.success(), "no scenarios: {output:?}");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("no scenario files found"));
+
+    assert!(baseline.join("menu").is_dir(), "golden must survive");
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn goldens_update_from_newest_passing_run_and_report_churn() {
+    let dir = temp_dir("goldens-update");
+    let scenario = write_scenario(&dir, "menu", "version 1");
+    let first = run(&dir, &scenario, "menu-1");
+    record_golden(&dir, &first, "menu");
+    let baseline = dir.join("goldens");
+    let root = dir.join("runs");
+    let update_args = [
+        "goldens",
+        "update",
+        "--json",
+        "--baseline",
+        baseline.to_str().unwrap(),
+        "--root",
+        root.to_str().unwrap(),
+    ];
+
+    let report: GoldenUpdateReport = json_output(&update_args);
+    assert!(report.updated.is_empty());
+    assert_eq!(report.skipped[0].name, "menu");
+
+    write_scenario(&dir, "menu", "version 2");
+    let second = run(&dir, &scenario, "menu-2");
+    let report: GoldenUpdateReport = json_output(&update_args);
+    assert_eq!(report.updated.len(), 1, "{report:?}");
+    let update = &report.updated[0];
+    assert_eq!(update.name, "menu");
+    assert_eq!(update.source, second.display().to_string());
+    assert!(update.snapshots_changed > 0, "{update:?}");
+    assert!(update.lines_changed > 0, "{update:?}");
+    assert_ne!(update.previous_run_id, update.run_id);
+    assert!(!baseline.join(".menu.update").exists());
+
+    let entries: Vec<GoldenEntry> = json_output(&[
+        "goldens",
+        "list",
+        "--json",
+        "--baseline",
+        baseline.to_str().unwrap(),
+    ]);
+    assert_eq!(entries[0].run_id, update.run_id);
+    assert_eq!(entries[0].updates, 1);
+    assert_eq!(entries[0].lines_changed, update.lines_changed);
+    assert_eq!(entries[0].last_updated_at_ms, Some(update.updated_at_ms));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn goldens_update_rejects_unknown_names() {
+    let dir = temp_dir("goldens-unknown");
+    fs::create_dir_all(dir.join("goldens")).unwrap();
+    let output = ptybox(&[
+        "goldens",
+        "update",
+        "--json",
+        "--baseline",
+        dir.join("goldens").to_str().unwrap(),
+        "--root",
+        dir.to_str().unwrap(),
+        "--name",
+        "missing",
+    ]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("no golden with this name"));
+
+    let _ = fs::remove_dir_all(&dir);
+}
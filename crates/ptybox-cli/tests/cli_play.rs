@@ -0,0 +1,105 @@
+//! Tests for the session playback command.
+// Test module - relaxed lint rules
+#![allow(clippy::expect_used)]
+#![allow(clippy::unwrap_used)]
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+use std::time::{Duration, Instant};
+use tempfile::tempdir;
+
+fn play(args: &[&str]) -> (Output, Duration) {
+    let started = Instant::now();
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .arg("play")
+        .args(args)
+        .output()
+        .expect("run ptybox play");
+    (output, started.elapsed())
+}
+
+/// A per-step recording: the step log holds the first two outputs, the
+/// top-level log the final observation two seconds later.
+fn write_recording(dir: &Path) {
+    let step_dir = dir.join("steps").join("001-start");
+    fs::create_dir_all(&step_dir).unwrap();
+    fs::write(
+        step_dir.join("events.jsonl"),
+        concat!(
+            r#"{"timestamp_ms": 10, "transcript_delta": "\u001b[1mone\u001b[0m\r\n"}"#,
+            "\n",
+            r#"{"timestamp_ms": 20, "transcript_delta": null}"#,
+            "\n",
+            r#"{"timestamp_ms": 30, "transcript_delta": "two\r\n"}"#,
+            "\n",
+        ),
+    )
+    .unwrap();
+    fs::write(
+        dir.join("events.jsonl"),
+        concat!(
+            r#"{"timestamp_ms": 2030, "transcript_delta": "three\r\n"}"#,
+            "\n"
+        ),
+    )
+    .unwrap();
+}
+
+#[test]
+fn play_writes_recorded_output_in_order_at_speed() {
+    let dir = tempdir().unwrap();
+    write_recording(dir.path());
+
+    let artifacts = dir.path().to_str().unwrap();
+    let (output, elapsed) = play(&["--artifacts", artifacts, "--speed", "10"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "\u{1b}[1mone\u{1b}[0m\r\ntwo\r\nthree\r\n"
+    );
+    assert!(elapsed >= Duration::from_millis(200), "{elapsed:?}");
+    assert!(elapsed < Duration::from_millis(1500), "{elapsed:?}");
+}
+
+#[test]
+fn play_from_ms_and_max_idle_skip_waiting() {
+    let dir = tempdir().unwrap();
+    write_recording(dir.path());
+    let artifacts = dir.path().to_str().unwrap();
+
+    let (output, elapsed) = play(&["--artifacts", artifacts, "--from-ms", "2030"]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "\u{1b}[1mone\u{1b}[0m\r\ntwo\r\nthree\r\n"
+    );
+    assert!(elapsed < Duration::from_secs(1), "{elapsed:?}");
+
+    let (output, elapsed) = play(&["--artifacts", artifacts, "--max-idle-ms", "50"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).ends_with("three\r\n"));
+    assert!(elapsed < Duration::from_secs(1), "{elapsed:?}");
+}
+
+#[test]
+fn play_rejects_recordings_without_output_and_bad_speeds() {
+    let dir = tempdir().unwrap();
+    let artifacts = dir.path().to_str().unwrap();
+
+    let (output, _) = play(&["--artifacts", artifacts]);
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("no recorded output"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let (output, _) = play(&["--artifacts", artifacts, "--speed", "0"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("speed must be between"));
+}
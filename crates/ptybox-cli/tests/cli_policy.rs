@@ -0,0 +1,221 @@
+//! Tests for `ptybox policy init` and `ptybox policy suggest`.
+// Test module - relaxed lint rules
+#![allow(clippy::expect_used)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::indexing_slicing)]
+
+use std::fs;
+use std::process::Command;
+use tempfile::tempdir;
+
+#[test]
+fn policy_init_requires_ack() {
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args(["policy", "init", "--json", "--", "/bin/echo", "hi"])
+        .output()
+        .expect("run ptybox policy init");
+    assert!(!output.status.success());
+    let error: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(error["code"], "E_POLICY_DENIED");
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn policy_init_drafts_policy_from_observed_run() {
+    let dir = tempdir().unwrap();
+    let dir_path = fs::canonicalize(dir.path()).unwrap();
+    fs::write(dir_path.join("in.txt"), "hello").unwrap();
+    fs::write(
+        dir_path.join("app.sh"),
+        "#!/bin/sh\necho \"$PTYBOX_INFER_TOKEN\"\nexec 3>out.txt 4<in.txt\nsleep 1\n",
+    )
+    .unwrap();
+    let policy_path = dir_path.join("drafts").join("policy.json");
+    fs::create_dir_all(policy_path.parent().unwrap()).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .env("PTYBOX_INFER_TOKEN", "secret")
+        .args([
+            "policy",
+            "init",
+            "--json",
+            "--no-strace",
+            "--ack-unsafe-observe",
+            "--cwd",
+            dir_path.to_str().unwrap(),
+            "--output",
+            policy_path.to_str().unwrap(),
+            "--",
+            "/bin/sh",
+            "app.sh",
+        ])
+        .output()
+        .expect("run ptybox policy init");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let report: ptybox::model::PolicyInference = serde_json::from_slice(&output.stdout).unwrap();
+    let dir_str = dir_path.display().to_string();
+    assert_eq!(report.source, "proc");
+    assert!(report
+        .observed
+        .written
+        .contains(&format!("{dir_str}/out.txt")));
+    assert!(report.observed.read.contains(&format!("{dir_str}/in.txt")));
+    assert!(report
+        .observed
+        .env
+        .contains(&"PTYBOX_INFER_TOKEN".to_string()));
+
+    let policy = ptybox::scenario::load_policy_file(&policy_path).unwrap();
+    assert_eq!(policy.fs.allowed_write, vec![dir_str.clone()]);
+    assert_eq!(policy.fs.allowed_read, vec![dir_str.clone()]);
+    assert_eq!(policy.fs.working_dir.as_deref(), Some(dir_str.as_str()));
+    assert!(policy.fs.write_ack);
+    assert!(policy.exec.allow_shell);
+    assert!(policy
+        .exec
+        .allowed_executables
+        .iter()
+        .any(|exe| exe.ends_with("/sleep")));
+    assert!(policy
+        .env
+        .allowlist
+        .contains(&"PTYBOX_INFER_TOKEN".to_string()));
+    ptybox::policy::validate_fs_policy(&policy.fs).unwrap();
+
+    let again = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "policy",
+            "init",
+            "--ack-unsafe-observe",
+            "--output",
+            policy_path.to_str().unwrap(),
+            "--",
+            "/bin/echo",
+        ])
+        .output()
+        .expect("run ptybox policy init");
+    assert!(!again.status.success());
+}
+
+#[test]
+fn policy_suggest_narrows_policy_to_what_the_run_used() {
+    let dir = tempdir().unwrap();
+    let root = fs::canonicalize(dir.path()).unwrap();
+    let work = root.join("work");
+    let artifacts = work.join("artifacts");
+    fs::create_dir_all(work.join("cache")).unwrap();
+    let policy = ptybox::model::policy::PolicyBuilder::new()
+        .sandbox_disabled()
+        .allow_shell()
+        .allowed_executables(vec!["/bin/sh".to_string(), "/bin/echo".to_string()])
+        .allowed_read(vec![root.display().to_string()])
+        .allowed_write(vec![work.display().to_string()])
+        .env_allowlist(vec!["PATH".to_string(), "PTYBOX_UNUSED_VAR".to_string()])
+        .artifacts_fs_manifest()
+        .build();
+    let policy_path = root.join("policy.json");
+    fs::write(&policy_path, serde_json::to_vec(&policy).unwrap()).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "exec",
+            "--json",
+            "--policy",
+            policy_path.to_str().unwrap(),
+            "--cwd",
+            work.to_str().unwrap(),
+            "--artifacts",
+            artifacts.to_str().unwrap(),
+            "--",
+            "/bin/sh",
+            "-c",
+            "mkdir -p out/logs && echo done > out/logs/run.log",
+        ])
+        .output()
+        .expect("run ptybox exec");
+    assert!(output.status.success(), "{output:?}");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args(["policy", "suggest", "--json", "--artifacts"])
+        .arg(&artifacts)
+        .output()
+        .expect("run ptybox policy suggest");
+    assert!(output.status.success(), "{output:?}");
+    let suggestions: ptybox::model::PolicySuggestions =
+        serde_json::from_slice(&output.stdout).unwrap();
+
+    let changes: Vec<(&str, &str)> = suggestions
+        .changes
+        .iter()
+        .map(|change| (change.field.as_str(), change.value.as_str()))
+        .collect();
+    let work = work.display().to_string();
+    assert_eq!(
+        changes,
+        vec![
+            ("exec.allowed_executables", "/bin/echo"),
+            ("fs.allowed_write", work.as_str()),
+            ("env.allowlist", "PTYBOX_UNUSED_VAR"),
+        ]
+    );
+    let logs = format!("{work}/out/logs");
+    let artifacts = artifacts.display().to_string();
+    assert_eq!(
+        suggestions.changes[1].replaced_by,
+        vec![logs.clone(), artifacts.clone()]
+    );
+    let candidate = &suggestions.policy;
+    assert_eq!(candidate.exec.allowed_executables, vec!["/bin/sh"]);
+    assert!(candidate.exec.allow_shell);
+    assert_eq!(candidate.fs.allowed_write, vec![logs, artifacts.clone()]);
+    assert_eq!(candidate.env.allowlist, vec!["PATH"]);
+    assert!(suggestions
+        .notes
+        .iter()
+        .any(|note| note.contains("fs.allowed_read is left as is")));
+
+    let written: serde_json::Value =
+        serde_json::from_slice(&fs::read(format!("{artifacts}/policy-suggestions.json")).unwrap())
+            .unwrap();
+    assert_eq!(written["changes"].as_array().unwrap().len(), 3);
+
+    let denial = serde_json::json!({
+        "source": "log_stream",
+        "collected": true,
+        "violations": [{"process": "sh", "pid": 7, "operation": "network-outbound", "target": "10.0.0.1:443"}]
+    });
+    fs::write(format!("{artifacts}/violations.json"), denial.to_string()).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args(["policy", "suggest", "--json", "--artifacts", &artifacts])
+        .output()
+        .expect("run ptybox policy suggest");
+    let suggestions: ptybox::model::PolicySuggestions =
+        serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        suggestions.network_attempts,
+        vec!["network-outbound 10.0.0.1:443"]
+    );
+    assert!(suggestions
+        .notes
+        .iter()
+        .any(|note| note.contains("denied 1 network operation")));
+}
+
+#[test]
+fn policy_suggest_requires_run_artifacts() {
+    let dir = tempdir().unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args(["policy", "suggest", "--json", "--artifacts"])
+        .arg(dir.path())
+        .output()
+        .expect("run ptybox policy suggest");
+    assert!(!output.status.success());
+    let error: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(error["code"], "E_IO");
+    assert!(!dir.path().join("policy-suggestions.json").exists());
+}
@@ -16,7 +16,8 @@
 
 use std::process::Command;
 
-use ptybox::model::PROTOCOL_VERSION;
+use ptybox::model::{ActionType, PROTOCOL_VERSION};
+use ptybox::predicate::PREDICATES;
 
 #[test]
 fn protocol_help_json_output_valid() {
@@ -250,3 +251,92 @@ fn protocol_help_wait_conditions_documented() {
         "process_exited not documented"
     );
 }
+
+#[test]
+fn protocol_vectors_writes_manifest_and_files() {
+    let out = std::env::temp_dir().join(format!(
+        "ptybox-cli-vectors-{}-{}",
+        std::process::id(),
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos()
+    ));
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args(["protocol-vectors", "--json", "--out", out.to_str().unwrap()])
+        .output()
+        .expect("failed to run command");
+    assert!(output.status.success());
+
+    let manifest: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(manifest["protocol_version"], PROTOCOL_VERSION);
+    for file in manifest["exchanges"].as_array().unwrap() {
+        assert!(out.join(file.as_str().unwrap()).is_file());
+    }
+    let timeout = manifest["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|error| error["code"] == "E_TIMEOUT")
+        .unwrap();
+    assert_eq!(timeout["exit_code"], 4);
+    assert!(out.join("errors/E_TIMEOUT.json").is_file());
+    assert!(out.join("manifest.json").is_file());
+    let _ = std::fs::remove_dir_all(&out);
+}
+
+#[test]
+fn protocol_help_assertions_share_condition_predicates() {
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args(["protocol-help", "--json"])
+        .output()
+        .expect("failed to run command");
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let schemas = &json["schemas"];
+    assert_eq!(schemas["Assertion"]["types"], schemas["Condition"]["types"]);
+    assert_eq!(
+        schemas["Condition"]["types"]["screen_matches"]["aliases"],
+        serde_json::json!(["regex_match"])
+    );
+    assert!(schemas["Condition"]["types"]["exit_code"].is_object());
+    assert!(schemas["Watcher"]["fields"]["condition"].is_string());
+}
+
+#[test]
+fn protocol_help_reports_stability_from_model_definitions() {
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args(["protocol-help", "--json"])
+        .output()
+        .expect("failed to run command");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let schemas = json["schemas"].as_object().unwrap();
+
+    for (name, schema) in schemas {
+        assert!(
+            schema["stability"]["status"].is_string(),
+            "{name} has no stability"
+        );
+    }
+    for action_type in ActionType::ALL {
+        let name = serde_json::to_value(&action_type).unwrap();
+        let name = name.as_str().unwrap();
+        assert_eq!(
+            json["schemas"]["Action"]["types"][name]["stability"],
+            serde_json::to_value(action_type.stability()).unwrap(),
+            "action {name}"
+        );
+    }
+    for spec in PREDICATES {
+        assert_eq!(
+            json["schemas"]["Condition"]["types"][spec.name]["stability"],
+            serde_json::to_value(spec.stability).unwrap(),
+            "predicate {}",
+            spec.name
+        );
+    }
+    assert_eq!(
+        json["schemas"]["Action"]["types"]["history"]["stability"]["status"],
+        "experimental"
+    );
+}
@@ -0,0 +1,157 @@
+// Test module - relaxed lint rules
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(missing_docs)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ptybox::model::policy::PolicyBuilder;
+use ptybox::model::{RunResult, RunStatus};
+use serde_json::{json, Value};
+
+fn temp_dir(prefix: &str) -> PathBuf {
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let dir = std::env::temp_dir().join(format!("ptybox-cli-test-{prefix}-{stamp}"));
+    fs::create_dir_all(&dir).unwrap();
+    dir.canonicalize().unwrap()
+}
+
+fn ptybox(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn step(id: u8, action: Value, expect: &str) -> Value {
+    json!({
+        "id": format!("00000000-0000-0000-0000-{id:012}"),
+        "name": format!("step {id}"),
+        "action": action,
+        "assert": [{ "type": "screen_contains", "payload": { "text": expect } }],
+        "timeout_ms": 2000,
+        "retries": 0,
+    })
+}
+
+/// Run a passing three-step `/bin/cat` scenario with artifacts, then make
+/// it look killed during step 2: no `run.json`, and a journal that ends
+/// with step 2 started and a torn line.
+fn killed_run(prefix: &str) -> PathBuf {
+    let dir = temp_dir(prefix);
+    let policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_executables(vec!["/bin/cat".to_string()])
+        .allowed_write(vec![dir.display().to_string()])
+        .build();
+    let scenario = json!({
+        "scenario_version": 1,
+        "metadata": { "name": "recover-demo" },
+        "run": {
+            "command": "/bin/cat",
+            "args": [],
+            "initial_size": { "rows": 4, "cols": 20 },
+            "policy": policy
+        },
+        "steps": [
+            step(1, json!({ "type": "text", "payload": { "text": "hello" } }), "hello"),
+            step(2, json!({ "type": "text", "payload": { "text": " world\n" } }), "world"),
+            step(3, json!({ "type": "key", "payload": { "key": "Ctrl+D" } }), "world"),
+        ]
+    });
+    let scenario_path = dir.join("scenario.json");
+    fs::write(
+        &scenario_path,
+        serde_json::to_vec_pretty(&scenario).unwrap(),
+    )
+    .unwrap();
+    let artifacts = dir.join("artifacts");
+    let run = ptybox(&[
+        "run",
+        "--json",
+        "--scenario",
+        scenario_path.to_str().unwrap(),
+        "--artifacts",
+        artifacts.to_str().unwrap(),
+    ]);
+    assert!(run.status.success(), "{run:?}");
+
+    fs::remove_file(artifacts.join("run.json")).unwrap();
+    let journal = fs::read_to_string(artifacts.join("journal.jsonl")).unwrap();
+    let events: Vec<&str> = journal.lines().collect();
+    let kept: Vec<&str> = events
+        .iter()
+        .take_while(|line| !line.contains("\"step_finished\"") || !line.contains("step 2"))
+        .copied()
+        .collect();
+    assert!(kept.len() < events.len(), "{journal}");
+    let mut truncated = kept.join("\n");
+    truncated.push_str("\n{\"at_ms\": 12, \"event\": \"step_fin");
+    fs::write(artifacts.join("journal.jsonl"), truncated).unwrap();
+    artifacts
+}
+
+fn artifacts_arg(artifacts: &Path) -> [&str; 5] {
+    [
+        "artifacts",
+        "recover",
+        "--json",
+        "--artifacts",
+        artifacts.to_str().unwrap(),
+    ]
+}
+
+#[test]
+fn recover_rebuilds_run_json_from_the_journal() {
+    let artifacts = killed_run("recover");
+    let output = ptybox(&artifacts_arg(&artifacts));
+    assert!(output.status.success(), "{output:?}");
+    let run: RunResult = serde_json::from_slice(&output.stdout).unwrap();
+
+    assert_eq!(run.status, RunStatus::Errored);
+    assert_eq!(run.command, "/bin/cat");
+    let steps = run.steps.as_ref().unwrap();
+    assert_eq!(steps.len(), 1);
+    assert_eq!(steps[0].name, "step 1");
+    let error = run.error.as_ref().unwrap();
+    assert_eq!(error.code, "E_INTERNAL");
+    let context = error.context.as_ref().unwrap();
+    assert_eq!(context["recovered"], true);
+    assert_eq!(context["step_in_progress"], "step 2");
+    assert!(run
+        .final_observation
+        .as_ref()
+        .unwrap()
+        .screen
+        .lines
+        .iter()
+        .any(|line| line.contains("hello")));
+
+    let written: RunResult =
+        serde_json::from_slice(&fs::read(artifacts.join("run.json")).unwrap()).unwrap();
+    assert_eq!(written.run_id, run.run_id);
+    let checksums: Value =
+        serde_json::from_slice(&fs::read(artifacts.join("checksums.json")).unwrap()).unwrap();
+    assert!(checksums.get("run.json").is_some(), "{checksums}");
+
+    let report = ptybox(&["report", "--artifacts", artifacts.to_str().unwrap()]);
+    assert!(report.status.success(), "{report:?}");
+}
+
+#[test]
+fn recover_refuses_a_run_that_finished() {
+    let artifacts = killed_run("recover-twice");
+    assert!(ptybox(&artifacts_arg(&artifacts)).status.success());
+
+    let output = ptybox(&artifacts_arg(&artifacts));
+    assert!(!output.status.success());
+    let error: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(error["code"], "E_PROTOCOL");
+}
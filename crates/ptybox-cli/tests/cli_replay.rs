@@ -19,12 +19,14 @@ use std::process::Command;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use ptybox::model::policy::{
-    EnvPolicy, ExecPolicy, FsPolicy, NetworkEnforcementAck, NetworkPolicy, Policy, ReplayPolicy,
-    SandboxMode, POLICY_VERSION,
+    ArtifactsEncryption, ArtifactsLayout, DeterminismPolicy, EnvPolicy, ExecPolicy, FsPolicy,
+    InputPolicy, NetworkEnforcementAck, NetworkPolicy, Policy, ProcessPolicy, ReplayPolicy,
+    SandboxFallback, SandboxMode, POLICY_VERSION,
 };
 use ptybox::model::{
-    Action, ActionType, Assertion, NormalizationRule, NormalizationRuleTarget, Scenario,
-    ScenarioMetadata, Step, StepId, TerminalSize,
+    Action, ActionType, Assertion, NormalizationRule, NormalizationRuleTarget, OutputBufferConfig,
+    ResizeConfig, Scenario, ScenarioDefaults, ScenarioMetadata, ScreenRegion, Step, StepId,
+    TerminalSize,
 };
 
 fn temp_dir(prefix: &str) -> PathBuf {
@@ -86,6 +88,7 @@ fn base_policy(work_dir: &Path, artifacts_dir: &Path) -> Policy {
     Policy {
         policy_version: POLICY_VERSION,
         sandbox: SandboxMode::Disabled { ack: true },
+        sandbox_fallback: SandboxFallback::Deny,
         network: NetworkPolicy::Disabled,
         network_enforcement: NetworkEnforcementAck {
             unenforced_ack: true,
@@ -96,6 +99,7 @@ fn base_policy(work_dir: &Path, artifacts_dir: &Path) -> Policy {
             working_dir: Some(work_dir.display().to_string()),
             write_ack: true,
             strict_write: false,
+            write_quotas: Default::default(),
         },
         exec: ExecPolicy {
             allowed_executables: vec!["/bin/cat".to_string()],
@@ -109,6 +113,15 @@ fn base_policy(work_dir: &Path, artifacts_dir: &Path) -> Policy {
         budgets: Default::default(),
         artifacts: Default::default(),
         replay: ReplayPolicy::default(),
+        input: InputPolicy::default(),
+        determinism: DeterminismPolicy::default(),
+        process: ProcessPolicy::default(),
+        sockets: Vec::new(),
+        remote: Default::default(),
+        terminal: Default::default(),
+        log_forward: Default::default(),
+        substitution_allowlist: Vec::new(),
+        grants: Vec::new(),
     }
 }
 
@@ -118,6 +131,10 @@ fn build_scenario(dir: &Path, policy: Policy) -> Scenario {
         metadata: ScenarioMetadata {
             name: "replay".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: ptybox::model::RunConfig {
             command: "/bin/cat".to_string(),
@@ -125,6 +142,12 @@ fn build_scenario(dir: &Path, policy: Policy) -> Scenario {
             cwd: Some(dir.display().to_string()),
             initial_size: TerminalSize::default(),
             policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy)),
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![
             Step {
@@ -137,9 +160,17 @@ fn build_scenario(dir: &Path, policy: Policy) -> Scenario {
                 assert: vec![Assertion {
                     assertion_type: "screen_contains".to_string(),
                     payload: serde_json::json!({"text": "hello"}),
+                    inherited: false,
                 }],
                 timeout_ms: 1000,
                 retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
             Step {
                 id: StepId::new(),
@@ -151,8 +182,20 @@ fn build_scenario(dir: &Path, policy: Policy) -> Scenario {
                 assert: Vec::new(),
                 timeout_ms: 1000,
                 retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
         ],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     }
 }
 
@@ -191,6 +234,225 @@ fn replay_succeeds_for_matching_snapshots() {
     assert!(replay_output.status.success());
 }
 
+#[test]
+fn replay_succeeds_for_per_step_layout() {
+    let dir = temp_dir("per-step");
+    let artifacts_dir = dir.join("artifacts");
+    let scenario_path = dir.join("scenario.json");
+    let mut policy = base_policy(&dir, &artifacts_dir);
+    policy.artifacts.layout = ArtifactsLayout::PerStep;
+    let scenario = build_scenario(&dir, policy);
+    write_scenario(&scenario_path, &scenario);
+
+    let run_output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "run",
+            "--json",
+            "--scenario",
+            scenario_path.to_str().unwrap(),
+            "--artifacts",
+            artifacts_dir.to_str().unwrap(),
+            "--overwrite",
+        ])
+        .output()
+        .unwrap();
+    assert!(run_output.status.success());
+    let step_dir = artifacts_dir.join("steps/001-type");
+    assert!(step_dir.join("events.jsonl").exists());
+    assert!(fs::read_dir(step_dir.join("snapshots")).unwrap().count() > 0);
+
+    let replay_output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "replay",
+            "--json",
+            "--artifacts",
+            artifacts_dir.to_str().unwrap(),
+            "--require-events",
+        ])
+        .output()
+        .unwrap();
+    assert!(
+        replay_output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&replay_output.stdout)
+    );
+}
+
+/// Stand-in for the `age` binary: "encrypts" with rot13 behind a header line
+/// and refuses to decrypt without an existing identity file.
+fn write_fake_age(dir: &Path) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+    let path = dir.join("fake-age");
+    fs::write(
+        &path,
+        r#"#!/bin/sh
+mode=; out=; id=; in=
+while [ $# -gt 0 ]; do
+  case "$1" in
+    --encrypt) mode=enc ;;
+    --decrypt) mode=dec ;;
+    --recipient) shift ;;
+    --identity) shift; id=$1 ;;
+    --output) shift; out=$1 ;;
+    *) in=$1 ;;
+  esac
+  shift
+done
+if [ "$mode" = enc ]; then
+  { echo "age-encryption.org/v1"; tr 'A-Za-z' 'N-ZA-Mn-za-m'; } > "$out"
+else
+  [ -f "$id" ] || { echo "no identity" >&2; exit 1; }
+  tail -n +2 "$in" | tr 'A-Za-z' 'N-ZA-Mn-za-m'
+fi
+"#,
+    )
+    .unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+fn ptybox(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn replay_and_trace_decrypt_encrypted_artifacts() {
+    let dir = temp_dir("encrypted");
+    let artifacts_dir = dir.join("artifacts");
+    let scenario_path = dir.join("scenario.json");
+    let identity = dir.join("identity.txt");
+    fs::write(&identity, "AGE-SECRET-KEY-1TEST\n").unwrap();
+    let mut policy = base_policy(&dir, &artifacts_dir);
+    policy.artifacts.encryption = Some(ArtifactsEncryption {
+        recipients: vec!["age1qqqqqqqqqqqqqqqqqqqq".to_string()],
+        age_binary: write_fake_age(&dir).display().to_string(),
+    });
+    write_scenario(&scenario_path, &build_scenario(&dir, policy));
+    let artifacts = artifacts_dir.to_str().unwrap();
+    let scenario = scenario_path.to_str().unwrap();
+
+    let run_output = ptybox(&[
+        "run",
+        "--json",
+        "--scenario",
+        scenario,
+        "--artifacts",
+        artifacts,
+    ]);
+    assert!(run_output.status.success());
+    assert!(artifacts_dir.join("transcript.log.age").exists());
+    assert!(!artifacts_dir.join("transcript.log").exists());
+    let run_json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(artifacts_dir.join("run.json")).unwrap()).unwrap();
+    assert!(run_json["final_observation"].is_null());
+
+    let denied = ptybox(&["replay", "--json", "--artifacts", artifacts]);
+    assert!(!denied.status.success());
+    assert!(String::from_utf8_lossy(&denied.stdout).contains("E_POLICY_DENIED"));
+
+    let identity = identity.to_str().unwrap();
+    let age = dir.join("fake-age");
+    let age = age.to_str().unwrap();
+    let replay_output = ptybox(&[
+        "replay",
+        "--json",
+        "--artifacts",
+        artifacts,
+        "--identity",
+        identity,
+        "--age-binary",
+        age,
+    ]);
+    assert!(
+        replay_output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&replay_output.stdout)
+    );
+
+    let trace_path = dir.join("trace.html");
+    let trace_args = ["trace", "--artifacts", artifacts, "--identity", identity];
+    let trace_output = ptybox(
+        &[
+            &trace_args[..],
+            &["--age-binary", age, "-o", trace_path.to_str().unwrap()],
+        ]
+        .concat(),
+    );
+    assert!(trace_output.status.success());
+    assert!(fs::read_to_string(&trace_path).unwrap().contains("hello"));
+}
+
+#[test]
+fn trace_ignores_age_binary_from_artifacts_policy() {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = temp_dir("tampered-age");
+    let artifacts_dir = dir.join("artifacts");
+    let scenario_path = dir.join("scenario.json");
+    let identity = dir.join("identity.txt");
+    fs::write(&identity, "AGE-SECRET-KEY-1TEST\n").unwrap();
+    let mut policy = base_policy(&dir, &artifacts_dir);
+    policy.artifacts.encryption = Some(ArtifactsEncryption {
+        recipients: vec!["age1qqqqqqqqqqqqqqqqqqqq".to_string()],
+        age_binary: write_fake_age(&dir).display().to_string(),
+    });
+    write_scenario(&scenario_path, &build_scenario(&dir, policy));
+    let artifacts = artifacts_dir.to_str().unwrap();
+    let run_output = ptybox(&[
+        "run",
+        "--json",
+        "--scenario",
+        scenario_path.to_str().unwrap(),
+        "--artifacts",
+        artifacts,
+    ]);
+    assert!(run_output.status.success());
+
+    // Point the recorded policy at a binary that leaves a marker when run
+    let marker = dir.join("tampered-ran");
+    let tampered = dir.join("tampered-age");
+    fs::write(
+        &tampered,
+        format!("#!/bin/sh\ntouch '{}'\n", marker.display()),
+    )
+    .unwrap();
+    fs::set_permissions(&tampered, fs::Permissions::from_mode(0o755)).unwrap();
+    let policy_path = artifacts_dir.join("policy.json");
+    let mut recorded: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&policy_path).unwrap()).unwrap();
+    recorded["artifacts"]["encryption"]["age_binary"] = tampered.display().to_string().into();
+    fs::write(&policy_path, serde_json::to_vec_pretty(&recorded).unwrap()).unwrap();
+
+    let trace_path = dir.join("trace.html");
+    let trace_args = [
+        "trace",
+        "--artifacts",
+        artifacts,
+        "--identity",
+        identity.to_str().unwrap(),
+        "-o",
+        trace_path.to_str().unwrap(),
+    ];
+    let _ = ptybox(&trace_args);
+    assert!(
+        !marker.exists(),
+        "trace ran the age binary from policy.json"
+    );
+
+    let age = dir.join("fake-age");
+    let trace_output =
+        ptybox(&[&trace_args[..], &["--age-binary", age.to_str().unwrap()]].concat());
+    assert!(
+        trace_output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&trace_output.stderr)
+    );
+    assert!(!marker.exists());
+    assert!(fs::read_to_string(&trace_path).unwrap().contains("hello"));
+}
+
 #[test]
 fn replay_detects_snapshot_mismatch() {
     let dir = temp_dir("mismatch");
@@ -236,6 +498,272 @@ fn replay_detects_snapshot_mismatch() {
     assert_eq!(err.code, "E_REPLAY_MISMATCH");
 }
 
+#[test]
+fn artifacts_slim_output_still_replays() {
+    let dir = temp_dir("slim");
+    let artifacts_dir = dir.join("artifacts");
+    let slim_dir = dir.join("slim");
+    let scenario_path = dir.join("scenario.json");
+    let policy = base_policy(&dir, &dir);
+    let scenario = build_scenario(&dir, policy);
+    write_scenario(&scenario_path, &scenario);
+
+    let run_output = ptybox(&[
+        "run",
+        "--json",
+        "--scenario",
+        scenario_path.to_str().unwrap(),
+        "--artifacts",
+        artifacts_dir.to_str().unwrap(),
+        "--overwrite",
+    ]);
+    assert!(run_output.status.success());
+
+    let slim_args = [
+        "artifacts",
+        "slim",
+        "--json",
+        "--artifacts",
+        artifacts_dir.to_str().unwrap(),
+        "--output",
+        slim_dir.to_str().unwrap(),
+    ];
+    let slim_output = ptybox(&slim_args);
+    assert!(
+        slim_output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&slim_output.stdout)
+    );
+    let report: ptybox::model::SlimReport = serde_json::from_slice(&slim_output.stdout).unwrap();
+    assert!(report.kept.contains(&"scenario.json".to_string()));
+    assert!(report.kept.contains(&"transcript.log".to_string()));
+    assert!(report
+        .kept
+        .iter()
+        .any(|path| path.starts_with("snapshots/")));
+    assert!(report.dropped_files > 0, "{report:?}");
+    assert!(!slim_dir.join("events.jsonl").exists());
+    let checksums: BTreeMap<String, String> =
+        serde_json::from_str(&fs::read_to_string(slim_dir.join("checksums.json")).unwrap())
+            .unwrap();
+    assert_eq!(checksums.len(), report.kept.len());
+
+    let replay_output = ptybox(&[
+        "replay",
+        "--json",
+        "--artifacts",
+        slim_dir.to_str().unwrap(),
+        "--require-checksums",
+    ]);
+    assert!(
+        replay_output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&replay_output.stdout)
+    );
+
+    let again = ptybox(&slim_args);
+    assert!(!again.status.success());
+    let err: ptybox::model::ErrorInfo = serde_json::from_slice(&again.stdout).unwrap();
+    assert_eq!(err.code, "E_POLICY_DENIED");
+}
+
+#[test]
+fn artifacts_slim_rejects_corrupted_source() {
+    let dir = temp_dir("slim-corrupt");
+    let artifacts_dir = dir.join("artifacts");
+    let scenario_path = dir.join("scenario.json");
+    let policy = base_policy(&dir, &artifacts_dir);
+    let scenario = build_scenario(&dir, policy);
+    write_scenario(&scenario_path, &scenario);
+
+    let run_output = ptybox(&[
+        "run",
+        "--json",
+        "--scenario",
+        scenario_path.to_str().unwrap(),
+        "--artifacts",
+        artifacts_dir.to_str().unwrap(),
+        "--overwrite",
+    ]);
+    assert!(run_output.status.success());
+    fs::write(artifacts_dir.join("transcript.log"), "tampered").unwrap();
+
+    let slim_output = ptybox(&[
+        "artifacts",
+        "slim",
+        "--json",
+        "--artifacts",
+        artifacts_dir.to_str().unwrap(),
+        "--output",
+        dir.join("slim").to_str().unwrap(),
+    ]);
+    assert_eq!(slim_output.status.code(), Some(11));
+    let err: ptybox::model::ErrorInfo = serde_json::from_slice(&slim_output.stdout).unwrap();
+    assert_eq!(err.code, "E_REPLAY_MISMATCH");
+    assert!(!dir.join("slim").exists());
+}
+
+#[test]
+fn artifacts_reformat_output_still_replays() {
+    let dir = temp_dir("reformat");
+    let artifacts_dir = dir.join("artifacts");
+    let scenario_path = dir.join("scenario.json");
+    let mut policy = base_policy(&dir, &dir);
+    policy.artifacts.canonical_json = false;
+    let scenario = build_scenario(&dir, policy);
+    write_scenario(&scenario_path, &scenario);
+
+    let run_output = ptybox(&[
+        "run",
+        "--json",
+        "--scenario",
+        scenario_path.to_str().unwrap(),
+        "--artifacts",
+        artifacts_dir.to_str().unwrap(),
+        "--overwrite",
+    ]);
+    assert!(run_output.status.success());
+
+    let reformat_args = [
+        "artifacts",
+        "reformat",
+        "--json",
+        "--artifacts",
+        artifacts_dir.to_str().unwrap(),
+    ];
+    let reformat_output = ptybox(&reformat_args);
+    assert!(
+        reformat_output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&reformat_output.stdout)
+    );
+    let report: ptybox::model::ReformatReport =
+        serde_json::from_slice(&reformat_output.stdout).unwrap();
+    assert!(report.reformatted.contains(&"run.json".to_string()));
+    assert!(report
+        .reformatted
+        .iter()
+        .any(|path| path.starts_with("snapshots/")));
+    let run_json = fs::read_to_string(artifacts_dir.join("run.json")).unwrap();
+    assert!(run_json.ends_with("}\n"));
+
+    let replay_output = ptybox(&[
+        "replay",
+        "--json",
+        "--artifacts",
+        artifacts_dir.to_str().unwrap(),
+        "--require-checksums",
+    ]);
+    assert!(
+        replay_output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&replay_output.stdout)
+    );
+
+    let again: ptybox::model::ReformatReport =
+        serde_json::from_slice(&ptybox(&reformat_args).stdout).unwrap();
+    assert!(again.reformatted.is_empty(), "{again:?}");
+}
+
+fn extract_bundle(bundle: &Path, dir: &Path, files: &[String]) -> Vec<String> {
+    fs::create_dir_all(dir).unwrap();
+    let tar = Command::new("tar")
+        .arg("-xzf")
+        .arg(bundle)
+        .arg("-C")
+        .arg(dir)
+        .output()
+        .unwrap();
+    assert!(
+        tar.status.success(),
+        "{}",
+        String::from_utf8_lossy(&tar.stderr)
+    );
+    files
+        .iter()
+        .map(|file| fs::read_to_string(dir.join(file)).unwrap())
+        .collect()
+}
+
+fn assert_bundle_readme(readme: &str) {
+    assert!(readme.contains("Step 1 `type`"), "{readme}");
+    assert!(readme.contains("never shown"), "{readme}");
+    assert!(
+        readme.contains("- Likely cause: assertion_regression"),
+        "{readme}"
+    );
+    assert!(readme.contains("[REDACTED:API_TOKEN]"), "{readme}");
+}
+
+#[test]
+fn bundle_packs_failing_step_with_secrets_redacted() {
+    let dir = temp_dir("bundle");
+    let artifacts_dir = dir.join("artifacts");
+    let bundle_path = dir.join("fail.tar.gz");
+    let scenario_path = dir.join("scenario.json");
+    let mut policy = base_policy(&dir, &artifacts_dir);
+    policy.artifacts.layout = ArtifactsLayout::PerStep;
+    policy.env.allowlist.push("API_TOKEN".to_string());
+    policy
+        .env
+        .set
+        .insert("API_TOKEN".to_string(), "sekrit-token-value".to_string());
+    let mut scenario = build_scenario(&dir, policy);
+    scenario.steps[0].action.payload =
+        serde_json::json!({"text": "password=hunter2 key sekrit-token-value"});
+    scenario.steps[0].assert[0].payload = serde_json::json!({"text": "never shown"});
+    write_scenario(&scenario_path, &scenario);
+
+    let run_output = ptybox(&[
+        "run",
+        "--json",
+        "--scenario",
+        scenario_path.to_str().unwrap(),
+        "--artifacts",
+        artifacts_dir.to_str().unwrap(),
+        "--overwrite",
+    ]);
+    assert!(!run_output.status.success());
+
+    let bundle_args = [
+        "bundle",
+        "--json",
+        "--artifacts",
+        artifacts_dir.to_str().unwrap(),
+        "--out",
+        bundle_path.to_str().unwrap(),
+    ];
+    let output = ptybox(&bundle_args);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+    let report: ptybox::model::BundleReport = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report.failing_step.as_deref(), Some("type"));
+    assert!(report.redactions > 0, "{report:?}");
+    assert!(report.files[0].ends_with("/README.md"));
+    assert!(report
+        .files
+        .iter()
+        .any(|file| file.contains("/steps/001-type/snapshots/")));
+
+    let bodies = extract_bundle(&bundle_path, &dir.join("extract"), &report.files);
+    for (file, body) in report.files.iter().zip(&bodies) {
+        assert!(!body.contains("hunter2"), "{file} leaks a password");
+        assert!(
+            !body.contains("sekrit-token-value"),
+            "{file} leaks API_TOKEN"
+        );
+    }
+    assert_bundle_readme(&bodies[0]);
+
+    let again = ptybox(&bundle_args);
+    assert!(!again.status.success());
+    let err: ptybox::model::ErrorInfo = serde_json::from_slice(&again.stdout).unwrap();
+    assert_eq!(err.code, "E_POLICY_DENIED");
+}
+
 #[test]
 fn replay_explain_uses_policy_defaults() {
     let dir = temp_dir("explain");
@@ -319,6 +847,112 @@ fn replay_detects_transcript_mismatch() {
     assert_eq!(err.code, "E_REPLAY_MISMATCH");
 }
 
+#[test]
+fn replay_compare_scopes_to_selected_artifacts() {
+    let dir = temp_dir("compare-scope");
+    let artifacts_dir = dir.join("artifacts");
+    let scenario_path = dir.join("scenario.json");
+    let policy = base_policy(&dir, &artifacts_dir);
+    let scenario = build_scenario(&dir, policy);
+    write_scenario(&scenario_path, &scenario);
+
+    let run_output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "run",
+            "--json",
+            "--scenario",
+            scenario_path.to_str().unwrap(),
+            "--artifacts",
+            artifacts_dir.to_str().unwrap(),
+            "--overwrite",
+        ])
+        .output()
+        .unwrap();
+    assert!(run_output.status.success());
+
+    fs::write(artifacts_dir.join("transcript.log"), "corrupt").unwrap();
+    update_checksum(&artifacts_dir, "transcript.log");
+
+    let replay_output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "replay",
+            "--json",
+            "--compare",
+            "snapshots,run_result",
+            "--artifacts",
+            artifacts_dir.to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert!(
+        replay_output.status.success(),
+        "stdout: {}",
+        String::from_utf8_lossy(&replay_output.stdout)
+    );
+    let summary: serde_json::Value = serde_json::from_slice(
+        &fs::read(latest_replay_dir(&artifacts_dir).join("replay.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(summary["status"], "passed");
+    assert_eq!(
+        summary["compare"],
+        serde_json::json!({
+            "snapshots": true,
+            "transcript": false,
+            "run_result": true,
+            "events": false
+        })
+    );
+
+    let explain_output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "replay",
+            "--json",
+            "--explain",
+            "--compare",
+            "transcript",
+            "--artifacts",
+            artifacts_dir.to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert!(explain_output.status.success());
+    let explanation: serde_json::Value = serde_json::from_slice(&explain_output.stdout).unwrap();
+    assert_eq!(explanation["compare"]["transcript"], true);
+    assert_eq!(explanation["compare"]["snapshots"], false);
+
+    let full_output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "replay",
+            "--json",
+            "--artifacts",
+            artifacts_dir.to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(full_output.status.code(), Some(11));
+}
+
+#[test]
+fn replay_require_events_conflicts_with_compare_without_events() {
+    let dir = temp_dir("compare-require-events");
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "replay",
+            "--json",
+            "--require-events",
+            "--compare",
+            "snapshots",
+            "--artifacts",
+            dir.to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(12));
+    let err: ptybox::model::ErrorInfo = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(err.code, "E_CLI_INVALID_ARG");
+}
+
 #[test]
 fn replay_report_reads_latest_summary() {
     let dir = temp_dir("report");
@@ -368,6 +1002,62 @@ fn replay_report_reads_latest_summary() {
     assert!(report["dir"].as_str().unwrap().contains("replay-"));
 }
 
+#[test]
+fn replay_paces_steps_to_baseline_start_times() {
+    let dir = temp_dir("paced");
+    let artifacts_dir = dir.join("artifacts");
+    let scenario_path = dir.join("scenario.json");
+    let mut policy = base_policy(&dir, &artifacts_dir);
+    policy.determinism.paced_input = true;
+    let scenario = build_scenario(&dir, policy);
+    write_scenario(&scenario_path, &scenario);
+
+    let run_output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "run",
+            "--json",
+            "--scenario",
+            scenario_path.to_str().unwrap(),
+            "--artifacts",
+            artifacts_dir.to_str().unwrap(),
+            "--overwrite",
+        ])
+        .output()
+        .unwrap();
+    assert!(run_output.status.success());
+
+    // Pretend the baseline waited before terminating.
+    let run_path = artifacts_dir.join("run.json");
+    let mut baseline: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&run_path).unwrap()).unwrap();
+    baseline["steps"][1]["started_at_ms"] = serde_json::json!(600);
+    fs::write(&run_path, serde_json::to_vec_pretty(&baseline).unwrap()).unwrap();
+    update_checksum(&artifacts_dir, "run.json");
+
+    let replay_output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "replay",
+            "--json",
+            "--artifacts",
+            artifacts_dir.to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert!(
+        replay_output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&replay_output.stdout)
+    );
+
+    let replay_dir = latest_replay_dir(&artifacts_dir);
+    let replayed: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(replay_dir.join("run.json")).unwrap()).unwrap();
+    assert!(replayed["steps"][1]["started_at_ms"].as_u64().unwrap() >= 600);
+    let summary: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(replay_dir.join("replay.json")).unwrap()).unwrap();
+    assert_eq!(summary["paced_input"], true);
+}
+
 #[test]
 fn replay_detects_run_result_mismatch() {
     let dir = temp_dir("run-mismatch");
@@ -412,6 +1102,67 @@ fn replay_detects_run_result_mismatch() {
     assert_eq!(err.code, "E_REPLAY_MISMATCH");
 }
 
+#[test]
+fn replay_reports_mismatched_step_by_content_key() {
+    let dir = temp_dir("step-key-mismatch");
+    let artifacts_dir = dir.join("artifacts");
+    let scenario_path = dir.join("scenario.json");
+    let policy = base_policy(&dir, &artifacts_dir);
+    let scenario = build_scenario(&dir, policy);
+    write_scenario(&scenario_path, &scenario);
+
+    let run_output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "run",
+            "--json",
+            "--scenario",
+            scenario_path.to_str().unwrap(),
+            "--artifacts",
+            artifacts_dir.to_str().unwrap(),
+            "--overwrite",
+        ])
+        .output()
+        .unwrap();
+    assert!(run_output.status.success());
+
+    let run_path = artifacts_dir.join("run.json");
+    let mut run: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&run_path).unwrap()).unwrap();
+    let step_key = run["steps"][0]["step_key"].clone();
+    assert_eq!(
+        step_key,
+        serde_json::json!(scenario.steps[0].content_key()),
+        "run.json records the step's content key"
+    );
+    run["steps"][0]["attempts"] = serde_json::json!(2);
+    fs::write(run_path, serde_json::to_vec_pretty(&run).unwrap()).unwrap();
+    update_checksum(&artifacts_dir, "run.json");
+
+    let replay_output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "replay",
+            "--json",
+            "--artifacts",
+            artifacts_dir.to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(replay_output.status.code(), Some(11));
+
+    let replay_dir = latest_replay_dir(&artifacts_dir);
+    let summary: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(replay_dir.join("replay.json")).unwrap()).unwrap();
+    assert_eq!(summary["mismatch"]["kind"], "run_result");
+    assert_eq!(summary["mismatch"]["step_key"], step_key);
+    let diff: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(replay_dir.join("diff.json")).unwrap()).unwrap();
+    let step = &diff["context"]["step"];
+    assert_eq!(step["step_key"], step_key);
+    assert_eq!(step["name"], "type");
+    assert_eq!(step["index"], 0);
+    assert_eq!(step["reason"], "differs");
+}
+
 #[test]
 fn replay_strict_mode_detects_snapshot_id_mismatch() {
     let dir = temp_dir("strict");
@@ -744,12 +1495,14 @@ fn replay_policy_rules_allow_nondeterministic_output() {
             pattern: "\\d+".to_string(),
             replace: "<ts>".to_string(),
             terminated_by_harness: false,
+            region: None,
         },
         NormalizationRule {
             target: NormalizationRuleTarget::SnapshotLines,
             pattern: "\\d+".to_string(),
             replace: "<ts>".to_string(),
             terminated_by_harness: false,
+            region: None,
         },
     ]);
     let scenario = Scenario {
@@ -757,6 +1510,10 @@ fn replay_policy_rules_allow_nondeterministic_output() {
         metadata: ScenarioMetadata {
             name: "rules".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: ptybox::model::RunConfig {
             command: fixture,
@@ -764,6 +1521,12 @@ fn replay_policy_rules_allow_nondeterministic_output() {
             cwd: Some(dir.display().to_string()),
             initial_size: TerminalSize::default(),
             policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy)),
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![Step {
             id: StepId::new(),
@@ -780,7 +1543,19 @@ fn replay_policy_rules_allow_nondeterministic_output() {
             assert: Vec::new(),
             timeout_ms: 1000,
             retries: 0,
+            skip_default_assertions: false,
+            sample_interval_ms: None,
+            expected_screen: None,
+            capture: None,
+            env_overlay: BTreeMap::new(),
+            metadata: BTreeMap::new(),
+            vars: Vec::new(),
         }],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
     write_scenario(&scenario_path, &scenario);
 
@@ -810,6 +1585,137 @@ fn replay_policy_rules_allow_nondeterministic_output() {
     assert!(replay_output.status.success());
 }
 
+fn clock_region_rules() -> Vec<NormalizationRule> {
+    vec![
+        NormalizationRule {
+            target: NormalizationRuleTarget::Transcript,
+            pattern: "\\d+".to_string(),
+            replace: "<ts>".to_string(),
+            terminated_by_harness: false,
+            region: None,
+        },
+        NormalizationRule {
+            target: NormalizationRuleTarget::SnapshotRegion,
+            pattern: String::new(),
+            replace: "<clock>".to_string(),
+            terminated_by_harness: false,
+            region: Some(ScreenRegion {
+                row: 0,
+                col: 0,
+                rows: 1,
+                cols: 80,
+            }),
+        },
+    ]
+}
+
+#[test]
+fn replay_snapshot_region_rule_blanks_dynamic_rows() {
+    let dir = temp_dir("region-rule");
+    let artifacts_dir = dir.join("artifacts");
+    let scenario_path = dir.join("scenario.json");
+    let fixture = "/bin/date".to_string();
+    let mut policy = base_policy(&dir, &artifacts_dir);
+    policy.exec.allowed_executables = vec![fixture.clone()];
+    policy.replay.normalization_rules = Some(clock_region_rules());
+    let scenario = Scenario {
+        scenario_version: 1,
+        metadata: ScenarioMetadata {
+            name: "region".to_string(),
+            description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
+        },
+        run: ptybox::model::RunConfig {
+            command: fixture,
+            args: Vec::new(),
+            cwd: Some(dir.display().to_string()),
+            initial_size: TerminalSize::default(),
+            policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy)),
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
+        },
+        steps: vec![Step {
+            id: StepId::new(),
+            name: "wait_exit".to_string(),
+            action: Action {
+                action_type: ActionType::Wait,
+                payload: serde_json::json!({"condition": {"type": "process_exited", "payload": {}}}),
+            },
+            assert: Vec::new(),
+            timeout_ms: 1000,
+            retries: 0,
+            skip_default_assertions: false,
+            sample_interval_ms: None,
+            expected_screen: None,
+            capture: None,
+            env_overlay: BTreeMap::new(),
+            metadata: BTreeMap::new(),
+            vars: Vec::new(),
+        }],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
+    };
+    write_scenario(&scenario_path, &scenario);
+
+    let run_output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "run",
+            "--json",
+            "--scenario",
+            scenario_path.to_str().unwrap(),
+            "--artifacts",
+            artifacts_dir.to_str().unwrap(),
+            "--overwrite",
+        ])
+        .output()
+        .unwrap();
+    assert!(run_output.status.success());
+
+    let replay_output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "replay",
+            "--json",
+            "--artifacts",
+            artifacts_dir.to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert!(
+        replay_output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&replay_output.stdout)
+    );
+
+    let replay_dir = fs::read_dir(&artifacts_dir)
+        .unwrap()
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("replay-"))
+        })
+        .expect("replay directory");
+    let record: serde_json::Value =
+        serde_json::from_slice(&fs::read(replay_dir.join("normalization.json")).unwrap()).unwrap();
+    let region_rule = &record["rules"][1];
+    assert_eq!(region_rule["target"], "snapshot_region");
+    assert_eq!(
+        region_rule["region"],
+        serde_json::json!({"row": 0, "col": 0, "rows": 1, "cols": 80})
+    );
+}
+
 #[test]
 fn replay_detects_checksum_mismatch() {
     let dir = temp_dir("checksum-mismatch");
@@ -0,0 +1,132 @@
+// Test module - relaxed lint rules
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(missing_docs)]
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ptybox::model::policy::PolicyBuilder;
+use serde_json::json;
+
+fn temp_dir(prefix: &str) -> PathBuf {
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let dir = std::env::temp_dir().join(format!("ptybox-cli-test-{prefix}-{stamp}"));
+    fs::create_dir_all(&dir).unwrap();
+    dir.canonicalize().unwrap()
+}
+
+fn ptybox(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn text_step(id: u8, text: &str, expect: &str) -> serde_json::Value {
+    json!({
+        "id": format!("00000000-0000-0000-0000-{id:012}"),
+        "name": format!("type {text}"),
+        "action": { "type": "text", "payload": { "text": text } },
+        "assert": [{ "type": "screen_contains", "payload": { "text": expect } }],
+        "timeout_ms": 300,
+        "retries": 0,
+    })
+}
+
+/// Run a two-step `/bin/cat` scenario whose second step fails, recording
+/// artifacts; returns the temp dir and the artifacts dir.
+fn failed_run(prefix: &str) -> (PathBuf, String) {
+    let dir = temp_dir(prefix);
+    let policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_executables(vec!["/bin/cat".to_string()])
+        .allowed_write(vec![dir.display().to_string()])
+        .build();
+    let scenario = json!({
+        "scenario_version": 1,
+        "metadata": { "name": "report-demo" },
+        "run": {
+            "command": "/bin/cat",
+            "args": [],
+            "initial_size": { "rows": 4, "cols": 20 },
+            "policy": policy
+        },
+        "steps": [text_step(1, "hello", "hello"), text_step(2, "x|y", "goodbye")]
+    });
+    let scenario_path = dir.join("scenario.json");
+    fs::write(
+        &scenario_path,
+        serde_json::to_vec_pretty(&scenario).unwrap(),
+    )
+    .unwrap();
+    let artifacts = dir.join("artifacts").display().to_string();
+    let run = ptybox(&[
+        "run",
+        "--json",
+        "--scenario",
+        scenario_path.to_str().unwrap(),
+        "--artifacts",
+        &artifacts,
+    ]);
+    assert!(!run.status.success(), "{run:?}");
+    (dir, artifacts)
+}
+
+#[test]
+fn report_renders_a_failed_run_as_markdown() {
+    let (_dir, artifacts) = failed_run("report");
+
+    let output = ptybox(&["report", "--artifacts", &artifacts]);
+    assert!(output.status.success(), "{output:?}");
+    let report = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        report.starts_with("# \u{274c} ptybox run failed\n"),
+        "{report}"
+    );
+    for expected in [
+        "| Scenario | `report-demo` |",
+        "| Steps | 1/2 passed, 1 failed |",
+        "| 1 | type hello | \u{2705} passed | 1 |",
+        "| 2 | type x\\|y | \u{274c} failed | 1 |",
+        "### \u{274c} Step 2: type x|y",
+        "- screen_contains: screen did not contain 'goodbye'",
+        "### Final screen (20x4)\n\n```text\nhellox|y\n```\n",
+        "## Budgets",
+        "| Runtime |",
+        "## Normalization",
+    ] {
+        assert!(report.contains(expected), "{expected}\n{report}");
+    }
+}
+
+#[test]
+fn report_writes_to_a_file() {
+    let (dir, artifacts) = failed_run("report-output");
+    let out = dir.join("report.md");
+    let output = ptybox(&[
+        "report",
+        "--artifacts",
+        &artifacts,
+        "--format",
+        "markdown",
+        "-o",
+        out.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "{output:?}");
+    let stdout = ptybox(&["report", "--artifacts", &artifacts]).stdout;
+    assert_eq!(fs::read(&out).unwrap(), stdout);
+
+    let output = ptybox(&[
+        "report",
+        "--artifacts",
+        dir.join("missing").to_str().unwrap(),
+    ]);
+    assert!(!output.status.success());
+}
@@ -10,20 +10,23 @@
 #![allow(clippy::unwrap_used)]
 #![allow(clippy::expect_used)]
 #![allow(clippy::cast_possible_truncation)]
+#![allow(clippy::too_many_lines)]
 #![allow(missing_docs)]
 
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use ptybox::model::policy::{
-    EnvPolicy, ExecPolicy, FsPolicy, NetworkEnforcementAck, NetworkPolicy, Policy, ReplayPolicy,
-    SandboxMode, POLICY_VERSION,
+    DeterminismPolicy, EnvPolicy, ExecPolicy, FsPolicy, InputPolicy, NetworkEnforcementAck,
+    NetworkPolicy, Policy, ProcessPolicy, ReplayPolicy, SandboxFallback, SandboxMode,
+    POLICY_VERSION,
 };
 use ptybox::model::{
-    Action, ActionType, Assertion, RunResult, Scenario, ScenarioMetadata, Step, StepId,
-    TerminalSize,
+    Action, ActionType, Assertion, OutputBufferConfig, ResizeConfig, RunResult, Scenario,
+    ScenarioDefaults, ScenarioMetadata, Step, StepId, TerminalSize,
 };
 
 fn temp_dir(prefix: &str) -> PathBuf {
@@ -51,6 +54,7 @@ fn base_policy(work_dir: &Path, allowed_exec: Vec<String>) -> Policy {
     Policy {
         policy_version: POLICY_VERSION,
         sandbox: SandboxMode::Disabled { ack: true },
+        sandbox_fallback: SandboxFallback::Deny,
         network: NetworkPolicy::Disabled,
         network_enforcement: NetworkEnforcementAck {
             unenforced_ack: true,
@@ -61,6 +65,7 @@ fn base_policy(work_dir: &Path, allowed_exec: Vec<String>) -> Policy {
             working_dir: Some(work_dir.display().to_string()),
             write_ack: false,
             strict_write: false,
+            write_quotas: Default::default(),
         },
         exec: ExecPolicy {
             allowed_executables: allowed_exec,
@@ -74,6 +79,15 @@ fn base_policy(work_dir: &Path, allowed_exec: Vec<String>) -> Policy {
         budgets: Default::default(),
         artifacts: Default::default(),
         replay: ReplayPolicy::default(),
+        input: InputPolicy::default(),
+        determinism: DeterminismPolicy::default(),
+        process: ProcessPolicy::default(),
+        sockets: Vec::new(),
+        remote: Default::default(),
+        terminal: Default::default(),
+        log_forward: Default::default(),
+        substitution_allowlist: Vec::new(),
+        grants: Vec::new(),
     }
 }
 
@@ -88,6 +102,10 @@ fn run_scenario_outputs_passed_run_result() {
         metadata: ScenarioMetadata {
             name: "echo".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: ptybox::model::RunConfig {
             command: fixture,
@@ -95,6 +113,12 @@ fn run_scenario_outputs_passed_run_result() {
             cwd: Some(dir.display().to_string()),
             initial_size: TerminalSize::default(),
             policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy)),
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![
             Step {
@@ -107,9 +131,17 @@ fn run_scenario_outputs_passed_run_result() {
                 assert: vec![Assertion {
                     assertion_type: "screen_contains".to_string(),
                     payload: serde_json::json!({"text": "hello"}),
+                    inherited: false,
                 }],
                 timeout_ms: 1000,
                 retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
             Step {
                 id: StepId::new(),
@@ -121,8 +153,20 @@ fn run_scenario_outputs_passed_run_result() {
                 assert: Vec::new(),
                 timeout_ms: 1000,
                 retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
         ],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
 
     let scenario_path = dir.join("scenario.json");
@@ -167,6 +211,10 @@ fn run_scenario_accepts_yaml() {
         metadata: ScenarioMetadata {
             name: "echo".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: ptybox::model::RunConfig {
             command: fixture,
@@ -174,6 +222,12 @@ fn run_scenario_accepts_yaml() {
             cwd: Some(dir.display().to_string()),
             initial_size: TerminalSize::default(),
             policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy)),
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![
             Step {
@@ -186,9 +240,17 @@ fn run_scenario_accepts_yaml() {
                 assert: vec![Assertion {
                     assertion_type: "screen_contains".to_string(),
                     payload: serde_json::json!({"text": "hello"}),
+                    inherited: false,
                 }],
                 timeout_ms: 1000,
                 retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
             Step {
                 id: StepId::new(),
@@ -200,8 +262,20 @@ fn run_scenario_accepts_yaml() {
                 assert: Vec::new(),
                 timeout_ms: 1000,
                 retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
         ],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
 
     let scenario_path = dir.join("scenario.yaml");
@@ -233,6 +307,10 @@ fn run_scenario_supports_key_action() {
         metadata: ScenarioMetadata {
             name: "key".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: ptybox::model::RunConfig {
             command: fixture,
@@ -240,6 +318,12 @@ fn run_scenario_supports_key_action() {
             cwd: Some(dir.display().to_string()),
             initial_size: TerminalSize::default(),
             policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy)),
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![
             Step {
@@ -252,9 +336,17 @@ fn run_scenario_supports_key_action() {
                 assert: vec![Assertion {
                     assertion_type: "screen_contains".to_string(),
                     payload: serde_json::json!({"text": "a"}),
+                    inherited: false,
                 }],
                 timeout_ms: 1000,
                 retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
             Step {
                 id: StepId::new(),
@@ -266,8 +358,20 @@ fn run_scenario_supports_key_action() {
                 assert: Vec::new(),
                 timeout_ms: 1000,
                 retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
         ],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
 
     let scenario_path = dir.join("scenario.json");
@@ -302,6 +406,10 @@ fn run_scenario_supports_resize_action() {
         metadata: ScenarioMetadata {
             name: "resize".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: ptybox::model::RunConfig {
             command: fixture,
@@ -309,42 +417,65 @@ fn run_scenario_supports_resize_action() {
             cwd: Some(dir.display().to_string()),
             initial_size: TerminalSize::default(),
             policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy)),
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![
             Step {
                 id: StepId::new(),
                 name: "resize".to_string(),
-                action: Action {
-                    action_type: ActionType::Resize,
-                    payload: serde_json::json!({"rows": 40, "cols": 100}),
-                },
+                action: Action::resize(40, 100),
                 assert: Vec::new(),
                 timeout_ms: 1000,
                 retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
             Step {
                 id: StepId::new(),
                 name: "type".to_string(),
-                action: Action {
-                    action_type: ActionType::Text,
-                    payload: serde_json::json!({"text": "x"}),
-                },
+                action: Action::text("x"),
                 assert: Vec::new(),
                 timeout_ms: 1000,
                 retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
             Step {
                 id: StepId::new(),
                 name: "terminate".to_string(),
-                action: Action {
-                    action_type: ActionType::Terminate,
-                    payload: serde_json::json!({}),
-                },
+                action: Action::terminate(),
                 assert: Vec::new(),
                 timeout_ms: 1000,
                 retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
         ],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
 
     let scenario_path = dir.join("scenario.json");
@@ -384,6 +515,10 @@ fn run_scenario_supports_wait_action() {
         metadata: ScenarioMetadata {
             name: "wait".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: ptybox::model::RunConfig {
             command: fixture,
@@ -391,6 +526,12 @@ fn run_scenario_supports_wait_action() {
             cwd: Some(dir.display().to_string()),
             initial_size: TerminalSize::default(),
             policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy)),
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![
             Step {
@@ -403,6 +544,13 @@ fn run_scenario_supports_wait_action() {
                 assert: Vec::new(),
                 timeout_ms: 1000,
                 retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
             Step {
                 id: StepId::new(),
@@ -414,6 +562,13 @@ fn run_scenario_supports_wait_action() {
                 assert: Vec::new(),
                 timeout_ms: 500,
                 retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
             Step {
                 id: StepId::new(),
@@ -425,8 +580,20 @@ fn run_scenario_supports_wait_action() {
                 assert: Vec::new(),
                 timeout_ms: 1000,
                 retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
         ],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
 
     let scenario_path = dir.join("scenario.json");
@@ -458,6 +625,10 @@ fn run_scenario_handles_split_utf8_output_without_terminal_parse() {
         metadata: ScenarioMetadata {
             name: "split-utf8".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: ptybox::model::RunConfig {
             command: "/bin/sh".to_string(),
@@ -468,6 +639,12 @@ fn run_scenario_handles_split_utf8_output_without_terminal_parse() {
             cwd: Some(dir.display().to_string()),
             initial_size: TerminalSize::default(),
             policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy)),
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![Step {
             id: StepId::new(),
@@ -479,10 +656,23 @@ fn run_scenario_handles_split_utf8_output_without_terminal_parse() {
             assert: vec![Assertion {
                 assertion_type: "screen_contains".to_string(),
                 payload: serde_json::json!({"text": "😀"}),
+                inherited: false,
             }],
             timeout_ms: 2000,
             retries: 0,
+            skip_default_assertions: false,
+            sample_interval_ms: None,
+            expected_screen: None,
+            capture: None,
+            env_overlay: BTreeMap::new(),
+            metadata: BTreeMap::new(),
+            vars: Vec::new(),
         }],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
 
     let scenario_path = dir.join("scenario.json");
@@ -519,6 +709,10 @@ fn run_scenario_retries_failed_steps() {
         metadata: ScenarioMetadata {
             name: "retries".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: ptybox::model::RunConfig {
             command: fixture,
@@ -526,6 +720,12 @@ fn run_scenario_retries_failed_steps() {
             cwd: Some(dir.display().to_string()),
             initial_size: TerminalSize::default(),
             policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy)),
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![
             Step {
@@ -538,9 +738,17 @@ fn run_scenario_retries_failed_steps() {
                 assert: vec![Assertion {
                     assertion_type: "screen_contains".to_string(),
                     payload: serde_json::json!({"text": "hihi"}),
+                    inherited: false,
                 }],
                 timeout_ms: 1000,
                 retries: 1,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
             Step {
                 id: StepId::new(),
@@ -552,8 +760,20 @@ fn run_scenario_retries_failed_steps() {
                 assert: Vec::new(),
                 timeout_ms: 1000,
                 retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
         ],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
 
     let scenario_path = dir.join("scenario.json");
@@ -594,6 +814,10 @@ fn run_scenario_is_deterministic_for_same_inputs() {
         metadata: ScenarioMetadata {
             name: "deterministic".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: ptybox::model::RunConfig {
             command: fixture,
@@ -601,64 +825,69 @@ fn run_scenario_is_deterministic_for_same_inputs() {
             cwd: Some(dir.display().to_string()),
             initial_size: TerminalSize::default(),
             policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy)),
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![
             Step {
                 id: StepId::new(),
                 name: "type".to_string(),
-                action: Action {
-                    action_type: ActionType::Text,
-                    payload: serde_json::json!({"text": "hello"}),
-                },
-                assert: vec![Assertion {
-                    assertion_type: "screen_contains".to_string(),
-                    payload: serde_json::json!({"text": "hello"}),
-                }],
+                action: Action::text("hello"),
+                assert: vec![Assertion::screen_contains("hello")],
                 timeout_ms: 1000,
                 retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
             Step {
                 id: StepId::new(),
                 name: "terminate".to_string(),
-                action: Action {
-                    action_type: ActionType::Terminate,
-                    payload: serde_json::json!({}),
-                },
+                action: Action::terminate(),
                 assert: Vec::new(),
                 timeout_ms: 1000,
                 retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
         ],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
 
     let scenario_path = dir.join("scenario.json");
     write_scenario(&scenario_path, &scenario);
 
-    let output_a = Command::new(env!("CARGO_BIN_EXE_ptybox"))
-        .args([
-            "run",
-            "--json",
-            "--artifacts",
-            artifacts_a.to_str().unwrap(),
-            "--scenario",
-            scenario_path.to_str().unwrap(),
-        ])
-        .output()
-        .unwrap();
-    assert!(output_a.status.success());
-
-    let output_b = Command::new(env!("CARGO_BIN_EXE_ptybox"))
-        .args([
-            "run",
-            "--json",
-            "--artifacts",
-            artifacts_b.to_str().unwrap(),
-            "--scenario",
-            scenario_path.to_str().unwrap(),
-        ])
-        .output()
-        .unwrap();
-    assert!(output_b.status.success());
+    for artifacts in [&artifacts_a, &artifacts_b] {
+        let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+            .args([
+                "run",
+                "--json",
+                "--artifacts",
+                artifacts.to_str().unwrap(),
+                "--scenario",
+                scenario_path.to_str().unwrap(),
+            ])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+    }
 
     let snapshot_a: ptybox::model::ScreenSnapshot = serde_json::from_str(
         &fs::read_to_string(artifacts_a.join("snapshots/000001.json")).unwrap(),
@@ -685,6 +914,10 @@ fn run_timeout_includes_step_context() {
         metadata: ScenarioMetadata {
             name: "timeout-context".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: ptybox::model::RunConfig {
             command: fixture,
@@ -692,6 +925,12 @@ fn run_timeout_includes_step_context() {
             cwd: Some(dir.display().to_string()),
             initial_size: TerminalSize::default(),
             policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy)),
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![
             Step {
@@ -704,6 +943,13 @@ fn run_timeout_includes_step_context() {
                 assert: Vec::new(),
                 timeout_ms: 50,
                 retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
             Step {
                 id: StepId::new(),
@@ -715,8 +961,20 @@ fn run_timeout_includes_step_context() {
                 assert: Vec::new(),
                 timeout_ms: 1000,
                 retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
         ],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
 
     let scenario_path = dir.join("scenario.json");
@@ -762,6 +1020,10 @@ fn run_writes_artifacts_on_assertion_failure() {
         metadata: ScenarioMetadata {
             name: "assert-fail".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: ptybox::model::RunConfig {
             command: fixture,
@@ -769,6 +1031,12 @@ fn run_writes_artifacts_on_assertion_failure() {
             cwd: Some(dir.display().to_string()),
             initial_size: TerminalSize::default(),
             policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy)),
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![Step {
             id: StepId::new(),
@@ -780,10 +1048,23 @@ fn run_writes_artifacts_on_assertion_failure() {
             assert: vec![Assertion {
                 assertion_type: "screen_contains".to_string(),
                 payload: serde_json::json!({"text": "nope"}),
+                inherited: false,
             }],
             timeout_ms: 100,
             retries: 0,
+            skip_default_assertions: false,
+            sample_interval_ms: None,
+            expected_screen: None,
+            capture: None,
+            env_overlay: BTreeMap::new(),
+            metadata: BTreeMap::new(),
+            vars: Vec::new(),
         }],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
     let scenario_path = dir.join("scenario.json");
     write_scenario(&scenario_path, &scenario);
@@ -818,6 +1099,10 @@ fn run_rejects_relative_scenario_cwd() {
         metadata: ScenarioMetadata {
             name: "relative-cwd".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: ptybox::model::RunConfig {
             command: fixture,
@@ -825,6 +1110,12 @@ fn run_rejects_relative_scenario_cwd() {
             cwd: Some("relative".to_string()),
             initial_size: TerminalSize::default(),
             policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy)),
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![Step {
             id: StepId::new(),
@@ -836,7 +1127,19 @@ fn run_rejects_relative_scenario_cwd() {
             assert: Vec::new(),
             timeout_ms: 100,
             retries: 0,
+            skip_default_assertions: false,
+            sample_interval_ms: None,
+            expected_screen: None,
+            capture: None,
+            env_overlay: BTreeMap::new(),
+            metadata: BTreeMap::new(),
+            vars: Vec::new(),
         }],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
     let scenario_path = dir.join("scenario.json");
     write_scenario(&scenario_path, &scenario);
@@ -873,6 +1176,10 @@ fn run_scenario_policy_file_writes_effective_policy() {
         metadata: ScenarioMetadata {
             name: "echo".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: ptybox::model::RunConfig {
             command: fixture,
@@ -882,6 +1189,12 @@ fn run_scenario_policy_file_writes_effective_policy() {
             policy: ptybox::model::scenario::PolicyRef::File {
                 path: policy_path.display().to_string(),
             },
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![Step {
             id: StepId::new(),
@@ -893,7 +1206,19 @@ fn run_scenario_policy_file_writes_effective_policy() {
             assert: Vec::new(),
             timeout_ms: 1000,
             retries: 0,
+            skip_default_assertions: false,
+            sample_interval_ms: None,
+            expected_screen: None,
+            capture: None,
+            env_overlay: BTreeMap::new(),
+            metadata: BTreeMap::new(),
+            vars: Vec::new(),
         }],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
 
     let policy_data = serde_json::to_vec_pretty(&policy).unwrap();
@@ -945,6 +1270,10 @@ fn artifacts_layout_is_written() {
         metadata: ScenarioMetadata {
             name: "echo".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: ptybox::model::RunConfig {
             command: fixture,
@@ -952,6 +1281,12 @@ fn artifacts_layout_is_written() {
             cwd: Some(dir.display().to_string()),
             initial_size: TerminalSize::default(),
             policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy)),
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![
             Step {
@@ -964,9 +1299,17 @@ fn artifacts_layout_is_written() {
                 assert: vec![Assertion {
                     assertion_type: "screen_contains".to_string(),
                     payload: serde_json::json!({"text": "hello"}),
+                    inherited: false,
                 }],
                 timeout_ms: 1000,
                 retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
             Step {
                 id: StepId::new(),
@@ -978,8 +1321,20 @@ fn artifacts_layout_is_written() {
                 assert: Vec::new(),
                 timeout_ms: 1000,
                 retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
         ],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
 
     let scenario_path = dir.join("scenario.json");
@@ -998,22 +1353,23 @@ fn artifacts_layout_is_written() {
         .output()
         .unwrap();
 
-    if !output.status.success() {
-        panic!(
-            "run failed: status={:?}\nstdout={}\nstderr={}",
-            output.status.code(),
-            String::from_utf8_lossy(&output.stdout),
-            String::from_utf8_lossy(&output.stderr)
-        );
-    }
+    assert!(
+        output.status.success(),
+        "run failed: stderr={}",
+        String::from_utf8_lossy(&output.stderr)
+    );
     assert!(artifacts_dir.join("run.json").exists());
     assert!(artifacts_dir.join("policy.json").exists());
     assert!(artifacts_dir.join("scenario.json").exists());
     assert!(artifacts_dir.join("transcript.log").exists());
     assert!(artifacts_dir.join("snapshots/000001.json").exists());
     assert!(artifacts_dir.join("events.jsonl").exists());
-    assert!(artifacts_dir.join("normalization.json").exists());
     assert!(artifacts_dir.join("checksums.json").exists());
+    assert_no_normalization(&artifacts_dir);
+}
+
+/// `normalization.json` exists and records that no filters were applied.
+fn assert_no_normalization(artifacts_dir: &Path) {
     let normalization: ptybox::model::NormalizationRecord = serde_json::from_str(
         &fs::read_to_string(artifacts_dir.join("normalization.json")).unwrap(),
     )
@@ -1025,3 +1381,695 @@ fn artifacts_layout_is_written() {
         ptybox::model::NormalizationSource::None
     );
 }
+
+fn tagged_run(dir: &Path, root: &Path, name: &str, expect: &str, tags: &[&str]) -> RunResult {
+    let mut args = vec!["--json"];
+    for tag in tags {
+        args.extend(["--tag", tag]);
+    }
+    let output = indexed_run(dir, root, name, expect, &args);
+    serde_json::from_slice(&output.stdout).unwrap_or_else(|err| panic!("{err}: {output:?}"))
+}
+
+/// Run a two-step `/bin/cat` scenario with artifacts under `root`, so it is
+/// recorded in `root/runs.jsonl`.
+fn indexed_run(
+    dir: &Path,
+    root: &Path,
+    name: &str,
+    expect: &str,
+    extra_args: &[&str],
+) -> std::process::Output {
+    let mut policy = base_policy(dir, vec!["/bin/cat".to_string()]);
+    policy.fs.allowed_write = vec![root.display().to_string()];
+    policy.fs.write_ack = true;
+    let scenario = Scenario {
+        scenario_version: 1,
+        metadata: ScenarioMetadata {
+            name: name.to_string(),
+            description: None,
+            tags: vec!["scenario-tag".to_string()],
+            author: None,
+            source: None,
+            revision: None,
+        },
+        run: ptybox::model::RunConfig {
+            command: "/bin/cat".to_string(),
+            args: Vec::new(),
+            cwd: Some(dir.display().to_string()),
+            initial_size: TerminalSize::default(),
+            policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy)),
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
+        },
+        steps: vec![
+            Step {
+                id: StepId::new(),
+                name: "type".to_string(),
+                action: Action {
+                    action_type: ActionType::Text,
+                    payload: serde_json::json!({"text": format!("hello from {name}")}),
+                },
+                assert: vec![Assertion {
+                    assertion_type: "screen_contains".to_string(),
+                    payload: serde_json::json!({"text": expect}),
+                    inherited: false,
+                }],
+                timeout_ms: 300,
+                retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
+            },
+            Step {
+                id: StepId::new(),
+                name: "terminate".to_string(),
+                action: Action {
+                    action_type: ActionType::Terminate,
+                    payload: serde_json::json!({}),
+                },
+                assert: Vec::new(),
+                timeout_ms: 1000,
+                retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
+            },
+        ],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
+    };
+    let scenario_path = dir.join(format!("{name}.json"));
+    write_scenario(&scenario_path, &scenario);
+
+    Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args(["run", "--scenario"])
+        .arg(&scenario_path)
+        .arg("--artifacts")
+        .arg(root.join(name))
+        .arg("--overwrite")
+        .args(extra_args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn run_tags_are_indexed_and_queryable() {
+    let dir = temp_dir("run-index");
+    let root = dir.join("runs");
+
+    let passed = tagged_run(&dir, &root, "ok", "hello", &["flaky", "nightly"]);
+    assert_eq!(passed.status, ptybox::model::RunStatus::Passed);
+    assert_eq!(passed.tags, vec!["scenario-tag", "flaky", "nightly"]);
+    let failed = tagged_run(&dir, &root, "crash", "never shown", &["flaky"]);
+    assert_eq!(failed.status, ptybox::model::RunStatus::Failed);
+
+    let run_json: RunResult =
+        serde_json::from_slice(&fs::read(root.join("ok/run.json")).unwrap()).unwrap();
+    assert_eq!(run_json.tags, passed.tags);
+
+    let list = |extra: &[&str]| -> Vec<ptybox::model::RunIndexEntry> {
+        let mut args = vec!["runs", "list", "--json", "--root", root.to_str().unwrap()];
+        args.extend_from_slice(extra);
+        let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+            .args(&args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+        serde_json::from_slice(&output.stdout).unwrap()
+    };
+
+    let all = list(&["--tag", "flaky"]);
+    assert_eq!(all.len(), 2);
+    assert_eq!(all[0].run_id, failed.run_id, "newest first");
+
+    let failures = list(&["--tag", "flaky", "--status", "failed"]);
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].scenario.as_deref(), Some("crash"));
+    assert_eq!(
+        failures[0].error_code.as_deref(),
+        Some("E_ASSERTION_FAILED")
+    );
+    assert!(failures[0].artifacts_dir.ends_with("crash"));
+
+    assert!(list(&["--tag", "nightly", "--status", "failed"]).is_empty());
+}
+
+#[test]
+fn run_progress_compact_prints_summaries_with_eta_from_prior_runs() {
+    let dir = temp_dir("progress-compact");
+    let root = dir.join("runs");
+
+    let first = indexed_run(
+        &dir,
+        &root,
+        "eta",
+        "hello",
+        &["--json", "--progress", "compact"],
+    );
+    assert!(first.status.success(), "{first:?}");
+    let stderr = String::from_utf8_lossy(&first.stderr);
+    assert!(
+        stderr.contains("progress: 0 passed, 0 failed 0ms elapsed, ETA ?"),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains("run passed: 2 passed, 0 failed in "),
+        "{stderr}"
+    );
+    assert_eq!(
+        stderr.lines().count(),
+        2,
+        "summaries are rate-limited: {stderr}"
+    );
+
+    let entries = ptybox::artifacts::read_run_index(&root).unwrap();
+    assert!(entries[0].duration_ms.is_some());
+
+    let second = indexed_run(
+        &dir,
+        &root,
+        "eta",
+        "never shown",
+        &["--json", "--progress", "compact"],
+    );
+    let stderr = String::from_utf8_lossy(&second.stderr);
+    let started = stderr.lines().next().unwrap();
+    assert!(!started.ends_with("ETA ?"), "{stderr}");
+    assert!(
+        stderr.contains("  ✗ type ("),
+        "failed steps are listed: {stderr}"
+    );
+    assert!(
+        stderr.contains("run failed: 0 passed, 1 failed in "),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn run_progress_conflicts_with_verbose() {
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "run",
+            "--scenario",
+            "x.json",
+            "--verbose",
+            "--progress",
+            "compact",
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be used with"));
+}
+
+fn grep_json(root: &Path, args: &[&str]) -> ptybox::model::GrepReport {
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args(["grep", "--json", "--root", root.to_str().unwrap()])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn grep_searches_transcripts_and_snapshots_of_indexed_runs() {
+    let dir = temp_dir("grep");
+    let root = dir.join("runs");
+    let passed = tagged_run(&dir, &root, "ok", "hello", &[]);
+    let failed = tagged_run(&dir, &root, "crash", "never shown", &[]);
+
+    let grep = |args: &[&str]| grep_json(&root, args);
+    let report = grep(&["HELLO FROM CRASH", "-i"]);
+    assert_eq!(report.runs_searched, 2);
+    assert!(!report.matches.is_empty());
+    assert!(report.matches.iter().all(|m| m.run_id == failed.run_id));
+    let transcript = &report.matches[0];
+    assert_eq!(transcript.source, ptybox::model::GrepSource::Transcript);
+    assert_eq!(transcript.line, 1);
+    let snapshot = report
+        .matches
+        .iter()
+        .find(|m| m.source == ptybox::model::GrepSource::Snapshot)
+        .expect("snapshot match");
+    assert_eq!(snapshot.step.as_deref(), Some("type"));
+    assert!(snapshot
+        .snapshot
+        .as_ref()
+        .unwrap()
+        .starts_with("snapshots/"));
+    assert_eq!(snapshot.text, "hello from crash");
+
+    let report = grep(&["-F", "hello from ok", "-C", "1"]);
+    assert!(report.matches.iter().all(|m| m.run_id == passed.run_id));
+    assert!(report.matches.iter().any(|m| !m.after.is_empty()));
+
+    assert!(grep(&["from (ok|crash)x"]).matches.is_empty());
+}
+
+#[test]
+fn grep_skips_runs_with_missing_artifacts() {
+    let dir = temp_dir("grep-skip");
+    let root = dir.join("runs");
+    let passed = tagged_run(&dir, &root, "ok", "hello", &[]);
+    let failed = tagged_run(&dir, &root, "crash", "never shown", &[]);
+
+    fs::remove_dir_all(root.join("ok")).unwrap();
+    let report = grep_json(&root, &["hello"]);
+    assert_eq!(report.runs_searched, 1);
+    assert_eq!(report.skipped.len(), 1);
+    assert_eq!(report.skipped[0].run_id, passed.run_id);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args(["grep", "--root", root.to_str().unwrap(), "hello"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&format!(
+        "{}  transcript:1: hello from crash",
+        failed.run_id
+    )));
+    assert!(stdout.contains("[type]"), "{stdout}");
+}
+
+#[test]
+fn run_index_skipped_when_root_not_writable() {
+    let dir = temp_dir("run-index-denied");
+    let root = dir.join("runs");
+    let mut policy = base_policy(&dir, vec!["/bin/cat".to_string()]);
+    let artifacts_dir = root.join("only");
+    policy.fs.allowed_write = vec![artifacts_dir.display().to_string()];
+    policy.fs.write_ack = true;
+    let policy_path = dir.join("policy.json");
+    fs::write(&policy_path, serde_json::to_vec(&policy).unwrap()).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "exec",
+            "--json",
+            "--policy",
+            policy_path.to_str().unwrap(),
+            "--artifacts",
+            artifacts_dir.to_str().unwrap(),
+            "--tag",
+            "smoke",
+            "--",
+            "/bin/cat",
+            "/dev/null",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let run: RunResult = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(run.tags, vec!["smoke"]);
+    assert!(artifacts_dir.join("run.json").exists());
+    assert!(!root.join("runs.jsonl").exists());
+}
+
+/// Run `/bin/cat /dev/null` with artifacts and a git SHA, returning the result.
+fn exec_with_git_sha(dir: &Path, artifacts_dir: &Path, git_sha: &str) -> RunResult {
+    let mut policy = base_policy(dir, vec!["/bin/cat".to_string()]);
+    policy.fs.allowed_write = vec![artifacts_dir.display().to_string()];
+    policy.fs.write_ack = true;
+    let policy_path = dir.join("policy.json");
+    fs::write(&policy_path, serde_json::to_vec(&policy).unwrap()).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "exec",
+            "--json",
+            "--policy",
+            policy_path.to_str().unwrap(),
+            "--artifacts",
+            artifacts_dir.to_str().unwrap(),
+            "--git-sha",
+            git_sha,
+            "--",
+            "/bin/cat",
+            "/dev/null",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn attest_covers_run_artifacts_and_provenance() {
+    let dir = temp_dir("attest");
+    let artifacts_dir = dir.join("artifacts");
+    let run = exec_with_git_sha(&dir, &artifacts_dir, "deadbeef01");
+    let provenance = run.provenance.clone().expect("provenance");
+    assert_eq!(provenance.git_sha.as_deref(), Some("deadbeef01"));
+    assert_eq!(provenance.scenario_hash, None, "exec runs have no scenario");
+
+    fs::create_dir_all(artifacts_dir.join("replay-old")).unwrap();
+    fs::write(artifacts_dir.join("replay-old/replay.json"), "{}").unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args(["attest", "--artifacts", artifacts_dir.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let statement: ptybox::model::AttestationStatement =
+        serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        statement.statement_type,
+        ptybox::model::IN_TOTO_STATEMENT_TYPE
+    );
+    assert_eq!(
+        statement.predicate_type,
+        ptybox::model::RUN_ATTESTATION_PREDICATE_TYPE
+    );
+    assert_eq!(statement.predicate.run_id, run.run_id);
+    assert_eq!(
+        statement.predicate.provenance.and_then(|p| p.git_sha),
+        Some("deadbeef01".to_string())
+    );
+
+    let names: Vec<&str> = statement
+        .subject
+        .iter()
+        .map(|subject| subject.name.as_str())
+        .collect();
+    assert!(names.contains(&"run.json"), "{names:?}");
+    assert!(names.contains(&"snapshots/000001.json"), "{names:?}");
+    assert!(names.iter().all(|name| !name.starts_with("replay-")));
+    let mut sorted = names.clone();
+    sorted.sort_unstable();
+    assert_eq!(names, sorted);
+    let run_json = statement
+        .subject
+        .iter()
+        .find(|subject| subject.name == "run.json")
+        .unwrap();
+    assert_eq!(
+        run_json.digest["sha256"],
+        ptybox::util::sha256_hex(&fs::read(artifacts_dir.join("run.json")).unwrap())
+    );
+}
+
+#[test]
+fn attest_writes_statement_to_output_file() {
+    let dir = temp_dir("attest-output");
+    let artifacts_dir = dir.join("artifacts");
+    exec_with_git_sha(&dir, &artifacts_dir, "0123456789abcdef");
+
+    let out_path = dir.join("attestation.json");
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "attest",
+            "--artifacts",
+            artifacts_dir.to_str().unwrap(),
+            "--output",
+            out_path.to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let written: serde_json::Value = serde_json::from_slice(&fs::read(&out_path).unwrap()).unwrap();
+    assert_eq!(written["_type"], "https://in-toto.io/Statement/v1");
+    assert!(written["predicateType"].is_string());
+}
+
+#[test]
+fn exec_rejects_malformed_git_sha() {
+    let dir = temp_dir("git-sha");
+    let policy = base_policy(&dir, vec!["/bin/cat".to_string()]);
+    let policy_path = dir.join("policy.json");
+    fs::write(&policy_path, serde_json::to_vec(&policy).unwrap()).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "exec",
+            "--json",
+            "--policy",
+            policy_path.to_str().unwrap(),
+            "--git-sha",
+            "main",
+            "--",
+            "/bin/cat",
+            "/dev/null",
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let err: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(err["code"], "E_PROTOCOL");
+}
+
+fn report_step(name: &str, expect: &str) -> Step {
+    Step {
+        id: StepId::new(),
+        name: name.to_string(),
+        action: Action {
+            action_type: ActionType::Text,
+            payload: serde_json::json!({"text": format!("{name}\n")}),
+        },
+        assert: vec![Assertion {
+            assertion_type: "screen_contains".to_string(),
+            payload: serde_json::json!({"text": expect}),
+            inherited: false,
+        }],
+        timeout_ms: 200,
+        retries: 0,
+        skip_default_assertions: false,
+        sample_interval_ms: None,
+        expected_screen: None,
+        capture: None,
+        env_overlay: BTreeMap::new(),
+        metadata: BTreeMap::new(),
+        vars: Vec::new(),
+    }
+}
+
+/// A `/bin/cat` scenario whose second step fails its assertion.
+fn report_scenario(dir: &Path) -> Scenario {
+    Scenario {
+        scenario_version: 1,
+        metadata: ScenarioMetadata {
+            name: "report".to_string(),
+            description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
+        },
+        run: ptybox::model::RunConfig {
+            command: "/bin/cat".to_string(),
+            args: Vec::new(),
+            cwd: Some(dir.display().to_string()),
+            initial_size: TerminalSize::default(),
+            policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(base_policy(
+                dir,
+                vec!["/bin/cat".to_string()],
+            ))),
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
+        },
+        steps: vec![report_step("first", "first"), report_step("second", "nope")],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
+    }
+}
+
+#[test]
+fn run_report_github_annotates_failing_step_line() {
+    let dir = temp_dir("report-github");
+    write_scenario_yaml(&dir.join("scenario.yaml"), &report_scenario(&dir));
+    let yaml = fs::read_to_string(dir.join("scenario.yaml")).unwrap();
+    let step_lines: Vec<usize> = yaml
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| line.starts_with("- id:"))
+        .map(|(index, _)| index + 1)
+        .collect();
+    assert_eq!(step_lines.len(), 2);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .current_dir(&dir)
+        .args(["run", "--scenario", "scenario.yaml", "--report", "github"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(5));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let commands: Vec<&str> = stdout.lines().collect();
+    assert_eq!(commands.len(), 1, "{stdout}");
+    let prefix = format!(
+        "::error file=scenario.yaml,line={0},endLine={0},title=step 'second' failed::",
+        step_lines[1]
+    );
+    assert!(commands[0].starts_with(&prefix), "{stdout}");
+    assert!(commands[0].contains("screen_contains"), "{stdout}");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("run completed: Failed"));
+}
+
+#[test]
+fn run_report_github_checks_emits_check_run_payload() {
+    let dir = temp_dir("report-checks");
+    let scenario_path = dir.join("scenario.json");
+    write_scenario(&scenario_path, &report_scenario(&dir));
+    let (_, source) =
+        ptybox::scenario::load_scenario_file_with_source(scenario_path.to_str().unwrap()).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "run",
+            "--scenario",
+            scenario_path.to_str().unwrap(),
+            "--report",
+            "github-checks",
+            "--git-sha",
+            "deadbeef01",
+        ])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(5));
+    let check: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(check["name"], "ptybox");
+    assert_eq!(check["head_sha"], "deadbeef01");
+    assert_eq!(check["status"], "completed");
+    assert_eq!(check["conclusion"], "failure");
+    assert_eq!(check["output"]["title"], "failed: 1/2 steps passed");
+    let annotations = check["output"]["annotations"].as_array().unwrap();
+    assert_eq!(annotations.len(), 1);
+    assert_eq!(annotations[0]["path"], scenario_path.to_str().unwrap());
+    assert_eq!(annotations[0]["start_line"], source.step_line(1).unwrap());
+    assert_eq!(annotations[0]["annotation_level"], "failure");
+}
+
+#[test]
+fn run_report_junit_writes_test_suite() {
+    let dir = temp_dir("report-junit");
+    let scenario_path = dir.join("scenario.json");
+    write_scenario(&scenario_path, &report_scenario(&dir));
+    let junit = dir.join("reports").join("junit.xml");
+    let junit_arg = format!("junit={}", junit.display());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "run",
+            "--json",
+            "--scenario",
+            scenario_path.to_str().unwrap(),
+            "--report",
+            &junit_arg,
+        ])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(5));
+    let run: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(run["status"], "failed");
+    let xml = fs::read_to_string(&junit).unwrap();
+    assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"ptybox\" tests=\"2\" failures=\"1\" errors=\"0\" skipped=\"0\""), "{xml}");
+    assert!(
+        xml.contains("<testsuite name=\"report\" tests=\"2\""),
+        "{xml}"
+    );
+    assert!(xml.contains(&format!(
+        "<property name=\"run_id\" value=\"{}\"/>",
+        run["run_id"].as_str().unwrap()
+    )));
+    assert!(
+        xml.contains("<testcase name=\"first\" classname=\"report\" time=\""),
+        "{xml}"
+    );
+    assert_eq!(xml.matches("<testcase ").count(), 2, "{xml}");
+    let failure = xml.split("<testcase name=\"second\"").nth(1).unwrap();
+    assert!(
+        failure.contains("<failure message=\"screen_contains"),
+        "{xml}"
+    );
+    assert!(xml.ends_with("</testsuite>\n</testsuites>\n"));
+}
+
+#[test]
+fn run_report_rejects_unknown_and_conflicting_reports() {
+    let dir = temp_dir("report-args");
+    let scenario_path = dir.join("scenario.json");
+    write_scenario(&scenario_path, &report_scenario(&dir));
+    let run = |report: &str, json: bool| {
+        let mut args = vec!["run", "--scenario", scenario_path.to_str().unwrap()];
+        if json {
+            args.push("--json");
+        }
+        args.extend(["--report", report]);
+        Command::new(env!("CARGO_BIN_EXE_ptybox"))
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    let unknown = run("xml", false);
+    assert_eq!(unknown.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&unknown.stderr).contains("junit=PATH"));
+    assert_eq!(run("junit=", false).status.code(), Some(2));
+    let conflicting = run("github", true);
+    assert_eq!(conflicting.status.code(), Some(12));
+    let err: ptybox::model::ErrorInfo = serde_json::from_slice(&conflicting.stdout).unwrap();
+    assert_eq!(err.code, "E_CLI_INVALID_ARG");
+}
+
+#[test]
+fn run_exit_code_profiles_remap_exit_status() {
+    let dir = temp_dir("exit-codes");
+    let scenario_path = dir.join("scenario.json");
+    write_scenario(&scenario_path, &report_scenario(&dir));
+    let map_path = dir.join("exit-codes.json");
+    let run = |extra: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_ptybox"))
+            .args([
+                "run",
+                "--json",
+                "--scenario",
+                scenario_path.to_str().unwrap(),
+            ])
+            .args(extra)
+            .output()
+            .unwrap()
+    };
+
+    let output = run(&["--exit-codes", "gitlab"]);
+    assert_eq!(output.status.code(), Some(1));
+    let result: RunResult = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result.error.unwrap().code, "E_ASSERTION_FAILED");
+
+    fs::write(&map_path, r#"{"E_ASSERTION_FAILED": 42}"#).unwrap();
+    let output = run(&[
+        "--exit-codes",
+        "gitlab",
+        "--exit-code-map",
+        map_path.to_str().unwrap(),
+    ]);
+    assert_eq!(output.status.code(), Some(42));
+
+    fs::write(&map_path, r#"{"E_NOPE": 42}"#).unwrap();
+    let output = run(&["--exit-code-map", map_path.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(12));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("E_NOPE"));
+}
@@ -0,0 +1,263 @@
+// Test module - relaxed lint rules
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::cast_possible_wrap)]
+#![allow(missing_docs)]
+
+//! Tests for the `serve --listen` multi-tenant driver gateway.
+
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ptybox::model::policy::PolicyBuilder;
+use ptybox::model::PROTOCOL_VERSION;
+use ptybox::util::sha256_hex;
+use serde_json::{json, Value};
+
+static TEST_DIR_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn temp_dir(prefix: &str) -> PathBuf {
+    let mut dir = std::env::temp_dir();
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let sequence = TEST_DIR_COUNTER.fetch_add(1, Ordering::Relaxed);
+    dir.push(format!("ptybox-cli-test-{prefix}-{stamp}-{sequence}"));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Write a policy allowing `command` and writes under `artifacts_root`, and
+/// return its tenant binding.
+fn tenant(dir: &Path, name: &str, token: &str, command: &str) -> Value {
+    let artifacts_root = dir.join(format!("{name}-artifacts"));
+    fs::create_dir_all(&artifacts_root).unwrap();
+    let policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_executables(vec![command.to_string()])
+        .allowed_write(vec![artifacts_root.display().to_string()])
+        .build();
+    let policy_path = dir.join(format!("{name}-policy.json"));
+    fs::write(&policy_path, serde_json::to_vec_pretty(&policy).unwrap()).unwrap();
+    json!({
+        "name": name,
+        "token_sha256": sha256_hex(token.as_bytes()),
+        "policy": policy_path,
+        "artifacts_root": artifacts_root,
+    })
+}
+
+fn spawn_gateway(socket: &Path, auth_config: &Path) -> Child {
+    spawn_gateway_with(socket, auth_config, &[])
+}
+
+fn spawn_gateway_with(socket: &Path, auth_config: &Path, extra: &[&str]) -> Child {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args(["serve", "--listen", socket.to_str().unwrap()])
+        .args(["--auth-config", auth_config.to_str().unwrap()])
+        .args(extra)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to spawn gateway");
+    let mut line = String::new();
+    BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut line)
+        .unwrap();
+    let ready: Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(ready["ok"], true, "gateway not ready: {line}");
+    assert_eq!(ready["tenants"], json!(["alpha", "beta"]));
+    child
+}
+
+/// Connect, send a hello, and return the reply with the open connection.
+fn connect(socket: &Path, token: &str, command: &str) -> (Value, BufReader<UnixStream>) {
+    let mut stream = UnixStream::connect(socket).unwrap();
+    let hello = json!({ "token": token, "command": command, "args": [] });
+    writeln!(stream, "{hello}").unwrap();
+    let mut reader = BufReader::new(stream);
+    let reply = read_json(&mut reader);
+    (reply, reader)
+}
+
+fn read_json(reader: &mut BufReader<UnixStream>) -> Value {
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    serde_json::from_str(&line).expect("gateway line should be JSON")
+}
+
+#[test]
+fn serve_gateway_binds_each_token_to_its_policy_and_audits() {
+    let dir = temp_dir("serve-gateway");
+    let audit_log = dir.join("audit.jsonl");
+    let auth_config = dir.join("auth.json");
+    let config = json!({
+        "tenants": [
+            tenant(&dir, "alpha", "alpha-secret", "/bin/cat"),
+            tenant(&dir, "beta", "beta-secret", "/bin/echo"),
+        ],
+        "audit_log": audit_log,
+    });
+    fs::write(&auth_config, config.to_string()).unwrap();
+    let socket = dir.join("gateway.sock");
+    let mut child = spawn_gateway(&socket, &auth_config);
+
+    let (reply, _) = connect(&socket, "wrong-secret", "/bin/cat");
+    assert_eq!(reply["ok"], false);
+    assert_eq!(reply["error"]["code"], "E_POLICY_DENIED");
+
+    let (reply, _) = connect(&socket, "beta-secret", "/bin/cat");
+    assert_eq!(reply["ok"], false, "beta's policy must not allow /bin/cat");
+
+    let (reply, mut reader) = connect(&socket, "alpha-secret", "/bin/cat");
+    assert_eq!(reply["ok"], true, "alpha should be accepted: {reply}");
+    assert_eq!(reply["tenant"], "alpha");
+    let artifacts_dir = PathBuf::from(reply["artifacts_dir"].as_str().unwrap());
+    assert!(artifacts_dir.starts_with(dir.join("alpha-artifacts")));
+    assert_eq!(read_json(&mut reader)["type"], "handshake");
+    let terminate = json!({
+        "protocol_version": PROTOCOL_VERSION,
+        "request_id": "req-term",
+        "action": { "type": "terminate", "payload": {} },
+    });
+    writeln!(reader.get_mut(), "{terminate}").unwrap();
+    assert_eq!(read_json(&mut reader)["status"], "ok");
+
+    nix::sys::signal::kill(
+        nix::unistd::Pid::from_raw(child.id() as i32),
+        nix::sys::signal::Signal::SIGTERM,
+    )
+    .unwrap();
+    assert!(child.wait().unwrap().success());
+    assert!(!socket.exists(), "socket should be removed on shutdown");
+    assert!(artifacts_dir.join("transcript.log").exists());
+
+    let audit = fs::read_to_string(&audit_log).unwrap();
+    assert!(!audit.contains("secret"), "audit log must not hold tokens");
+    let events: Vec<(Value, Value)> = audit
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap())
+        .map(|record| (record["event"].clone(), record["tenant"].clone()))
+        .collect();
+    assert_eq!(
+        events,
+        [
+            (json!("auth_failed"), Value::Null),
+            (json!("denied"), json!("beta")),
+            (json!("session_started"), json!("alpha")),
+            (json!("session_ended"), json!("alpha")),
+        ]
+    );
+}
+
+#[test]
+fn serve_gateway_refuses_connections_and_sessions_over_the_limits() {
+    let dir = temp_dir("serve-gateway-limits");
+    let audit_log = dir.join("audit.jsonl");
+    let auth_config = dir.join("auth.json");
+    let config = json!({
+        "tenants": [
+            tenant(&dir, "alpha", "alpha-secret", "/bin/cat"),
+            tenant(&dir, "beta", "beta-secret", "/bin/cat"),
+        ],
+        "audit_log": audit_log,
+    });
+    fs::write(&auth_config, config.to_string()).unwrap();
+    let socket = dir.join("gateway.sock");
+    let mut child = spawn_gateway_with(
+        &socket,
+        &auth_config,
+        &["--max-connections", "2", "--max-sessions", "1"],
+    );
+
+    let (reply, _alpha) = connect(&socket, "alpha-secret", "/bin/cat");
+    assert_eq!(reply["ok"], true, "first alpha session: {reply}");
+    let (reply, mut refused) = connect(&socket, "alpha-secret", "/bin/cat");
+    assert_eq!(reply["ok"], false, "alpha is at its session limit");
+    assert_eq!(reply["error"]["code"], "E_PROTOCOL");
+    assert_eq!(reply["error"]["context"]["max_sessions"], 1);
+    // Wait for the refused connection to close so it no longer counts.
+    assert_eq!(refused.read_line(&mut String::new()).unwrap(), 0);
+    std::thread::sleep(Duration::from_millis(200));
+
+    let (reply, _beta) = connect(&socket, "beta-secret", "/bin/cat");
+    assert_eq!(reply["ok"], true, "beta has its own session limit: {reply}");
+
+    // Two connections are open, so a third is refused before its hello is read.
+    let mut reader = BufReader::new(UnixStream::connect(&socket).unwrap());
+    let reply = read_json(&mut reader);
+    assert_eq!(reply["ok"], false);
+    assert_eq!(reply["error"]["code"], "E_PROTOCOL");
+    assert_eq!(reply["error"]["context"]["max_connections"], 2);
+
+    nix::sys::signal::kill(
+        nix::unistd::Pid::from_raw(child.id() as i32),
+        nix::sys::signal::Signal::SIGTERM,
+    )
+    .unwrap();
+    assert!(child.wait().unwrap().success());
+
+    let denied = fs::read_to_string(&audit_log)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap())
+        .filter(|record| record["event"] == "denied")
+        .count();
+    assert_eq!(denied, 2);
+}
+
+#[test]
+fn serve_gateway_creates_a_private_socket() {
+    let dir = temp_dir("serve-gateway-mode");
+    let auth_config = dir.join("auth.json");
+    let config = json!({
+        "tenants": [
+            tenant(&dir, "alpha", "alpha-secret", "/bin/cat"),
+            tenant(&dir, "beta", "beta-secret", "/bin/echo"),
+        ],
+    });
+    fs::write(&auth_config, config.to_string()).unwrap();
+    let socket = dir.join("gateway.sock");
+    let mut child = spawn_gateway(&socket, &auth_config);
+
+    let mode = fs::metadata(&socket).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+    let leftovers: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .filter(|name| name.to_string_lossy().ends_with(".bind"))
+        .collect();
+    assert!(
+        leftovers.is_empty(),
+        "staging dir left behind: {leftovers:?}"
+    );
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+#[test]
+fn serve_gateway_rejects_invalid_token_digest() {
+    let dir = temp_dir("serve-gateway-invalid");
+    let mut binding = tenant(&dir, "alpha", "alpha-secret", "/bin/cat");
+    binding["token_sha256"] = json!("not-a-digest");
+    let auth_config = dir.join("auth.json");
+    fs::write(&auth_config, json!({ "tenants": [binding] }).to_string()).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args(["serve", "--listen", dir.join("gw.sock").to_str().unwrap()])
+        .args(["--auth-config", auth_config.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("token_sha256"));
+}
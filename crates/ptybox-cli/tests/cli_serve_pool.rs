@@ -0,0 +1,129 @@
+// Test module - relaxed lint rules
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::cast_possible_wrap)]
+#![allow(missing_docs)]
+
+//! Tests for the `serve --pool` warm session pool.
+
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ptybox::model::policy::PolicyBuilder;
+use ptybox::model::PROTOCOL_VERSION;
+use serde_json::{json, Value};
+
+fn temp_dir(prefix: &str) -> PathBuf {
+    let mut dir = std::env::temp_dir();
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    dir.push(format!("ptybox-cli-test-{prefix}-{stamp}"));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn read_json<R: BufRead>(reader: &mut R) -> Value {
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    serde_json::from_str(&line).expect("pool line should be JSON")
+}
+
+fn request(id: &str, action: &Value) -> String {
+    json!({
+        "protocol_version": PROTOCOL_VERSION,
+        "request_id": id,
+        "action": action,
+    })
+    .to_string()
+}
+
+/// Connect and return the handshake's `run_id` with the open connection.
+fn lease(socket: &Path) -> (String, BufReader<UnixStream>) {
+    let mut reader = BufReader::new(UnixStream::connect(socket).unwrap());
+    let handshake = read_json(&mut reader);
+    assert_eq!(handshake["type"], "handshake");
+    (handshake["run_id"].as_str().unwrap().to_string(), reader)
+}
+
+/// Start a one-child recycling pool of `/bin/cat` and wait until it is ready.
+fn spawn_pool(dir: &Path, artifacts_root: &Path) -> (Child, BufReader<ChildStdout>) {
+    let policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_executables(vec!["/bin/cat".to_string()])
+        .allowed_write(vec![artifacts_root.display().to_string()])
+        .build();
+    let policy_path = dir.join("policy.json");
+    fs::write(&policy_path, serde_json::to_vec(&policy).unwrap()).unwrap();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args(["serve", "--pool", dir.join("pool.sock").to_str().unwrap()])
+        .args(["--policy", policy_path.to_str().unwrap()])
+        .args(["--pool-size", "1", "--recycle", "--warmup-ms", "100"])
+        .args(["--artifacts", artifacts_root.to_str().unwrap()])
+        .args(["--", "/bin/cat"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to spawn pool");
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    let ready = read_json(&mut stdout);
+    assert_eq!(ready["ok"], true, "pool not ready: {ready}");
+    assert_eq!(ready["size"], 1);
+    (child, stdout)
+}
+
+#[test]
+fn serve_pool_recycles_clean_children_and_replaces_used_ones() {
+    let dir = temp_dir("serve-pool");
+    let artifacts_root = dir.join("artifacts");
+    fs::create_dir_all(&artifacts_root).unwrap();
+    let socket = dir.join("pool.sock");
+    let (mut child, mut stdout) = spawn_pool(&dir, &artifacts_root);
+
+    // An episode that leaves the screen untouched returns its child.
+    let (first_run, mut reader) = lease(&socket);
+    let observe = json!({ "type": "observe", "payload": {} });
+    writeln!(reader.get_mut(), "{}", request("req-1", &observe)).unwrap();
+    assert_eq!(read_json(&mut reader)["action_metrics"]["sequence"], 1);
+    drop(reader);
+    let episode = read_json(&mut stdout);
+    assert_eq!(episode["warm"], true);
+    assert_eq!(episode["recycled"], true, "{episode}");
+    let artifacts_dir = PathBuf::from(episode["artifacts_dir"].as_str().unwrap());
+    assert!(artifacts_dir.starts_with(&artifacts_root));
+    assert!(artifacts_dir.join("run.json").exists());
+
+    // The same child serves the next episode, whose steps start over.
+    let (second_run, mut reader) = lease(&socket);
+    assert_eq!(second_run, first_run);
+    let text = json!({ "type": "text", "payload": { "text": "dirty\n" } });
+    writeln!(reader.get_mut(), "{}", request("req-1", &text)).unwrap();
+    assert_eq!(read_json(&mut reader)["action_metrics"]["sequence"], 1);
+    drop(reader);
+    let episode = read_json(&mut stdout);
+    assert_eq!(episode["recycled"], false);
+    assert_eq!(
+        episode["discard_reason"],
+        "screen differs from the baseline"
+    );
+
+    // A replacement was warmed for the next episode.
+    let (third_run, reader) = lease(&socket);
+    assert_ne!(third_run, first_run);
+    drop(reader);
+    assert_eq!(read_json(&mut stdout)["warm"], true);
+
+    nix::sys::signal::kill(
+        nix::unistd::Pid::from_raw(child.id() as i32),
+        nix::sys::signal::Signal::SIGTERM,
+    )
+    .unwrap();
+    assert!(child.wait().unwrap().success());
+    assert!(!socket.exists(), "socket should be removed on shutdown");
+}
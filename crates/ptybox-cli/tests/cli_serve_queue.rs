@@ -0,0 +1,653 @@
+// Test module - relaxed lint rules
+#![allow(clippy::default_trait_access)]
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::unreadable_literal)]
+#![allow(clippy::cast_lossless)]
+#![allow(clippy::inefficient_to_string)]
+#![allow(clippy::panic)]
+#![allow(clippy::manual_assert)]
+#![allow(clippy::uninlined_format_args)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(clippy::cast_possible_truncation)]
+#![allow(clippy::cast_possible_wrap)]
+#![allow(missing_docs)]
+
+//! Tests for the `serve --queue` scenario queue runner.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use ptybox::model::policy::PolicyBuilder;
+use ptybox::model::scenario::PolicyRef;
+use ptybox::model::{
+    Action, ActionType, OutputBufferConfig, ResizeConfig, RunConfig, RunResult, RunStatus,
+    Scenario, ScenarioDefaults, ScenarioMetadata, Step, StepId, StepStatus, TerminalSize,
+};
+use ptybox::util::sha256_hex;
+
+/// Bearer token of the queue runners spawned by [`spawn_queue`].
+const TOKEN: &str = "queue-test-token";
+
+static TEST_DIR_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn temp_dir(prefix: &str) -> PathBuf {
+    let mut dir = std::env::temp_dir();
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let sequence = TEST_DIR_COUNTER.fetch_add(1, Ordering::Relaxed);
+    dir.push(format!("ptybox-cli-test-{prefix}-{stamp}-{sequence}"));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn echo_scenario(name: &str) -> Scenario {
+    let policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_executables(vec!["/bin/echo".to_string()])
+        .build();
+    Scenario {
+        scenario_version: 1,
+        metadata: ScenarioMetadata {
+            name: name.to_string(),
+            description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
+        },
+        run: RunConfig {
+            command: "/bin/echo".to_string(),
+            args: vec!["queued".to_string()],
+            cwd: None,
+            initial_size: TerminalSize::default(),
+            policy: PolicyRef::Inline(Box::new(policy)),
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
+        },
+        steps: Vec::new(),
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
+    }
+}
+
+/// A shell loop printing `tick-<n>-` every 300ms, with one step waiting for
+/// each of the first `steps` ticks.
+fn ticking_scenario(name: &str, steps: usize) -> Scenario {
+    let mut scenario = echo_scenario(name);
+    scenario.run.command = "/bin/sh".to_string();
+    scenario.run.args = vec![
+        "-c".to_string(),
+        "i=0; while [ $i -lt 60 ]; do i=$((i+1)); echo \"tick-$i-\"; sleep 0.3; done".to_string(),
+    ];
+    scenario.run.policy = PolicyRef::Inline(Box::new(
+        PolicyBuilder::new()
+            .sandbox_disabled()
+            .allow_shell()
+            .allowed_executables(vec!["/bin/sh".to_string()])
+            .build(),
+    ));
+    scenario.steps = (1..=steps)
+        .map(|tick| Step {
+            id: StepId::new(),
+            name: format!("tick-{tick}"),
+            action: Action {
+                action_type: ActionType::Wait,
+                payload: serde_json::json!({
+                    "condition": {
+                        "type": "screen_contains",
+                        "payload": {"text": format!("tick-{tick}-")}
+                    }
+                }),
+            },
+            assert: Vec::new(),
+            timeout_ms: 5000,
+            retries: 0,
+            skip_default_assertions: false,
+            sample_interval_ms: None,
+            expected_screen: None,
+            capture: None,
+            env_overlay: BTreeMap::new(),
+            metadata: BTreeMap::new(),
+            vars: Vec::new(),
+        })
+        .collect();
+    scenario
+}
+
+/// Spawn a queue runner on `queue_dir` whose endpoint accepts [`TOKEN`].
+fn spawn_queue(queue_dir: &Path, extra_args: &[&str]) -> (Child, String) {
+    let digest = queue_dir.with_extension("token-sha256");
+    fs::write(&digest, format!("{}\n", sha256_hex(TOKEN.as_bytes()))).unwrap();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "serve",
+            "--queue",
+            queue_dir.to_str().unwrap(),
+            "--jobs",
+            "2",
+            "--status-addr",
+            "127.0.0.1:0",
+            "--token-sha256-file",
+            digest.to_str().unwrap(),
+        ])
+        .args(extra_args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to spawn queue runner");
+    let mut line = String::new();
+    BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut line)
+        .unwrap();
+    let ready: serde_json::Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(ready["ok"], true);
+    assert_eq!(ready["concurrency"], 2);
+    let addr = ready["status_addr"].as_str().unwrap().to_string();
+    (child, addr)
+}
+
+/// Send a request with [`TOKEN`] and a JSON content type.
+fn http(addr: &str, method: &str, path: &str, body: &str) -> (u16, serde_json::Value) {
+    let headers = format!("Authorization: Bearer {TOKEN}\r\nContent-Type: application/json\r\n");
+    http_with_headers(addr, method, path, &headers, body)
+}
+
+fn http_with_headers(
+    addr: &str,
+    method: &str,
+    path: &str,
+    headers: &str,
+    body: &str,
+) -> (u16, serde_json::Value) {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(
+        stream,
+        "{method} {path} HTTP/1.1\r\nHost: localhost\r\n{headers}Content-Length: {}\r\n\r\n{body}",
+        body.len()
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let status: u16 = response.split_whitespace().nth(1).unwrap().parse().unwrap();
+    let payload = response.split("\r\n\r\n").nth(1).unwrap();
+    (status, serde_json::from_str(payload).unwrap())
+}
+
+fn wait_for_file(path: &Path) {
+    let deadline = Instant::now() + Duration::from_secs(15);
+    while !path.exists() {
+        assert!(
+            Instant::now() < deadline,
+            "timed out waiting for {}",
+            path.display()
+        );
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+#[test]
+fn serve_queue_runs_jobs_and_drains_on_sigterm() {
+    let queue_dir = temp_dir("serve-queue");
+    let scenario = echo_scenario("queued-echo");
+    fs::write(
+        queue_dir.join("001-echo.json"),
+        serde_json::to_vec_pretty(&scenario).unwrap(),
+    )
+    .unwrap();
+
+    let (mut child, addr) = spawn_queue(&queue_dir, &[]);
+
+    let result_path = queue_dir.join("done/001-echo.json.result.json");
+    wait_for_file(&result_path);
+    assert!(queue_dir.join("done/001-echo.json").exists());
+    let result: RunResult = serde_json::from_slice(&fs::read(&result_path).unwrap()).unwrap();
+    assert_eq!(result.status, RunStatus::Passed);
+
+    let (status, health) = http(&addr, "GET", "/health", "");
+    assert_eq!(status, 200);
+    assert_eq!(health["state"], "running");
+
+    let (status, submitted) = http(
+        &addr,
+        "POST",
+        "/jobs",
+        &serde_json::to_string(&echo_scenario("posted-echo")).unwrap(),
+    );
+    assert_eq!(status, 202);
+    let job_id = submitted["job_id"].as_str().unwrap();
+    wait_for_file(&queue_dir.join(format!("done/{job_id}.result.json")));
+
+    let (status, report) = http(&addr, "GET", "/status", "");
+    assert_eq!(status, 200);
+    assert_eq!(report["completed"], 2);
+    assert_eq!(report["failed"], 0);
+    assert_eq!(report["concurrency"], 2);
+
+    let (status, _) = http(&addr, "POST", "/jobs", "{not json");
+    assert_eq!(status, 400);
+
+    nix::sys::signal::kill(
+        nix::unistd::Pid::from_raw(child.id() as i32),
+        nix::sys::signal::Signal::SIGTERM,
+    )
+    .unwrap();
+    let exit = child.wait().unwrap();
+    assert!(
+        exit.success(),
+        "queue runner should drain cleanly: {exit:?}"
+    );
+}
+
+#[test]
+fn serve_queue_files_unloadable_jobs_as_failed() {
+    let queue_dir = temp_dir("serve-queue-bad");
+    fs::write(queue_dir.join("bad.json"), "{}").unwrap();
+
+    let (mut child, _addr) = spawn_queue(&queue_dir, &[]);
+    let error_path = queue_dir.join("failed/bad.json.error.json");
+    wait_for_file(&error_path);
+    let error: serde_json::Value = serde_json::from_slice(&fs::read(&error_path).unwrap()).unwrap();
+    assert_eq!(error["code"], "E_IO");
+
+    nix::sys::signal::kill(
+        nix::unistd::Pid::from_raw(child.id() as i32),
+        nix::sys::signal::Signal::SIGTERM,
+    )
+    .unwrap();
+    assert!(child.wait().unwrap().success());
+}
+
+#[test]
+fn serve_queue_files_jobs_with_unloadable_policies_as_failed() {
+    let queue_dir = temp_dir("serve-queue-bad-policy");
+    let missing = queue_dir.join("missing-policy.json");
+    let mut scenario = echo_scenario("bad-policy");
+    scenario.run.policy = PolicyRef::File {
+        path: missing.display().to_string(),
+    };
+    fs::write(
+        queue_dir.join("bad-policy.json"),
+        serde_json::to_string(&scenario).unwrap(),
+    )
+    .unwrap();
+
+    let (mut child, _addr) = spawn_queue(&queue_dir, &[]);
+    let error_path = queue_dir.join("failed/bad-policy.json.error.json");
+    wait_for_file(&error_path);
+    let error: serde_json::Value = serde_json::from_slice(&fs::read(&error_path).unwrap()).unwrap();
+    assert_eq!(error["code"], "E_IO", "{error}");
+    assert!(
+        error["message"].as_str().unwrap().contains("policy"),
+        "{error}"
+    );
+
+    nix::sys::signal::kill(
+        nix::unistd::Pid::from_raw(child.id() as i32),
+        nix::sys::signal::Signal::SIGTERM,
+    )
+    .unwrap();
+    assert!(child.wait().unwrap().success());
+}
+
+#[test]
+fn serve_queue_rejects_non_loopback_status_addr() {
+    let queue_dir = temp_dir("serve-queue-addr");
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "serve",
+            "--queue",
+            queue_dir.to_str().unwrap(),
+            "--status-addr",
+            "0.0.0.0:0",
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("loopback"));
+}
+
+#[test]
+fn serve_queue_runs_each_job_across_the_locale_timezone_matrix() {
+    let queue_dir = temp_dir("serve-queue-matrix");
+    let mut scenario = echo_scenario("matrix-tz");
+    scenario.run.command = "/bin/sh".to_string();
+    scenario.run.args = vec!["-c".to_string(), "echo \"tz=$TZ lang=$LANG\"".to_string()];
+    scenario.run.policy = PolicyRef::Inline(Box::new(
+        PolicyBuilder::new()
+            .sandbox_disabled()
+            .allow_shell()
+            .allowed_executables(vec!["/bin/sh".to_string()])
+            .build(),
+    ));
+    fs::write(
+        queue_dir.join("tz.json"),
+        serde_json::to_vec_pretty(&scenario).unwrap(),
+    )
+    .unwrap();
+
+    let (mut child, _addr) = spawn_queue(
+        &queue_dir,
+        &[
+            "--locale",
+            "C.UTF-8",
+            "--timezone",
+            "UTC",
+            "--timezone",
+            "Asia/Tokyo",
+        ],
+    );
+    let result_path = queue_dir.join("done/tz.json.result.json");
+    wait_for_file(&result_path);
+    let result: serde_json::Value =
+        serde_json::from_slice(&fs::read(&result_path).unwrap()).unwrap();
+    assert_eq!(result["passed"], true);
+    let cells = result["matrix"].as_array().unwrap();
+    assert_eq!(cells.len(), 2);
+    for (cell, (label, timezone)) in cells
+        .iter()
+        .zip([("C.UTF-8+UTC", "UTC"), ("C.UTF-8+Asia-Tokyo", "Asia/Tokyo")])
+    {
+        assert_eq!(cell["label"], label);
+        assert_eq!(cell["determinism"]["timezone"], timezone);
+        let run: RunResult = serde_json::from_value(cell["result"].clone()).unwrap();
+        assert_eq!(run.status, RunStatus::Passed);
+        assert_eq!(run.policy.determinism.timezone.as_deref(), Some(timezone));
+        let pinned = run.determinism.unwrap();
+        assert_eq!(pinned.timezone.as_deref(), Some(timezone));
+        assert_eq!(pinned.locale.as_deref(), Some("C.UTF-8"));
+        let screen = run.final_observation.unwrap().screen.lines.join("\n");
+        assert!(
+            screen.contains(&format!("tz={timezone} lang=C.UTF-8")),
+            "{screen}"
+        );
+    }
+
+    nix::sys::signal::kill(
+        nix::unistd::Pid::from_raw(child.id() as i32),
+        nix::sys::signal::Signal::SIGTERM,
+    )
+    .unwrap();
+    assert!(child.wait().unwrap().success());
+}
+
+#[test]
+fn serve_queue_rejects_invalid_matrix_timezone() {
+    let queue_dir = temp_dir("serve-queue-bad-tz");
+    let output = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args([
+            "serve",
+            "--queue",
+            queue_dir.to_str().unwrap(),
+            "--timezone",
+            "UTC; rm",
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("invalid determinism timezone"));
+}
+
+fn top_once(addr: &str, json: bool) -> std::process::Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_ptybox"));
+    command.args(["top", "--status-addr", addr, "--once"]);
+    if json {
+        command.arg("--json");
+    }
+    command.output().unwrap()
+}
+
+/// Poll `top --once --json` until job `name` has started step `step_index`.
+fn wait_for_job_step(addr: &str, name: &str, step_index: u64) -> serde_json::Value {
+    let deadline = Instant::now() + Duration::from_secs(15);
+    loop {
+        let output = top_once(addr, true);
+        assert!(output.status.success(), "top failed: {output:?}");
+        let status: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        if let Some(job) = status["jobs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|job| job["job"] == name)
+        {
+            if job["step_index"].as_u64().unwrap() >= step_index {
+                return job.clone();
+            }
+        }
+        assert!(Instant::now() < deadline, "timed out waiting for {name}");
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+fn assert_top_table_lists(addr: &str, name: &str) {
+    let output = top_once(addr, false);
+    assert!(output.status.success());
+    let table = String::from_utf8_lossy(&output.stdout);
+    assert!(table.starts_with("running  jobs 1/2"), "{table}");
+    assert!(table.contains("JOB"), "{table}");
+    assert!(
+        table
+            .lines()
+            .any(|line| line.starts_with(&format!("{name} ")) && line.ends_with("running")),
+        "{table}"
+    );
+}
+
+fn assert_canceled(result_path: &Path, total_steps: usize) {
+    wait_for_file(result_path);
+    let result: RunResult = serde_json::from_slice(&fs::read(result_path).unwrap()).unwrap();
+    assert_eq!(result.status, RunStatus::Canceled);
+    assert!(result.error.is_none());
+    let steps = result.steps.unwrap();
+    assert_eq!(steps.len(), total_steps);
+    assert_eq!(steps[0].status, StepStatus::Passed);
+    assert_eq!(steps[total_steps - 1].status, StepStatus::Skipped);
+}
+
+#[test]
+fn top_reports_job_progress_and_cancels_a_running_job() {
+    let queue_dir = temp_dir("serve-queue-top");
+    fs::write(
+        queue_dir.join("slow.json"),
+        serde_json::to_vec_pretty(&ticking_scenario("slow", 20)).unwrap(),
+    )
+    .unwrap();
+    let (mut child, addr) = spawn_queue(&queue_dir, &[]);
+
+    let job = wait_for_job_step(&addr, "slow.json", 2);
+    assert_eq!(job["total_steps"], 20);
+    assert_eq!(job["cancel_requested"], false);
+    assert!(job["step"].as_str().unwrap().starts_with("tick-"));
+    assert!(job["budgets"]["max_runtime_ms"].as_u64().unwrap() > 0);
+
+    assert_top_table_lists(&addr, "slow.json");
+
+    let (status, cancel) = http(&addr, "POST", "/jobs/slow.json/cancel", "");
+    assert_eq!(status, 202);
+    assert_eq!(cancel["job"], "slow.json");
+    let (status, _) = http(&addr, "POST", "/jobs/missing/cancel", "");
+    assert_eq!(status, 404);
+
+    assert_canceled(&queue_dir.join("failed/slow.json.result.json"), 20);
+
+    let (_, report) = http(&addr, "GET", "/status", "");
+    assert_eq!(report["failed"], 1);
+    assert_eq!(report["jobs"], serde_json::json!([]));
+
+    nix::sys::signal::kill(
+        nix::unistd::Pid::from_raw(child.id() as i32),
+        nix::sys::signal::Signal::SIGTERM,
+    )
+    .unwrap();
+    assert!(child.wait().unwrap().success());
+}
+
+#[test]
+fn top_reports_an_unreachable_status_endpoint() {
+    let addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .to_string();
+    let output = top_once(&addr, true);
+    assert_eq!(output.status.code(), Some(10));
+    let error: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(error["code"], "E_IO");
+}
+
+#[test]
+fn serve_queue_endpoint_refuses_unauthenticated_and_browser_posts() {
+    let queue_dir = temp_dir("serve-queue-auth");
+    let (mut child, addr) = spawn_queue(&queue_dir, &[]);
+    let body = serde_json::to_string(&echo_scenario("posted-echo")).unwrap();
+    let json_type = "Content-Type: application/json\r\n";
+
+    let (status, _) = http_with_headers(&addr, "POST", "/jobs", json_type, &body);
+    assert_eq!(status, 401);
+    let wrong = format!("Authorization: Bearer not-the-token\r\n{json_type}");
+    let (status, _) = http_with_headers(&addr, "POST", "/jobs", &wrong, &body);
+    assert_eq!(status, 401);
+    let text = format!("Authorization: Bearer {TOKEN}\r\nContent-Type: text/plain\r\n");
+    let (status, _) = http_with_headers(&addr, "POST", "/jobs", &text, &body);
+    assert_eq!(status, 415);
+    let browser =
+        format!("Authorization: Bearer {TOKEN}\r\n{json_type}Origin: https://example.com\r\n");
+    let (status, _) = http_with_headers(&addr, "POST", "/jobs", &browser, &body);
+    assert_eq!(status, 403);
+    let (status, _) = http_with_headers(&addr, "GET", "/status", &browser, "");
+    assert_eq!(status, 403);
+    assert_eq!(
+        fs::read_dir(&queue_dir).unwrap().count(),
+        3,
+        "only the queue subdirectories"
+    );
+
+    nix::sys::signal::kill(
+        nix::unistd::Pid::from_raw(child.id() as i32),
+        nix::sys::signal::Signal::SIGTERM,
+    )
+    .unwrap();
+    assert!(child.wait().unwrap().success());
+}
+
+/// Send raw bytes and return the response status.
+fn raw_http_status(addr: &str, request: &[u8]) -> u16 {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream.write_all(request).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response.split_whitespace().nth(1).unwrap().parse().unwrap()
+}
+
+#[test]
+fn serve_queue_endpoint_bounds_request_lines_and_headers() {
+    let queue_dir = temp_dir("serve-queue-limits");
+    let (mut child, addr) = spawn_queue(&queue_dir, &[]);
+
+    // A request line without a newline is cut off instead of buffered.
+    let started = Instant::now();
+    assert_eq!(raw_http_status(&addr, &[b'A'; 9000]), 400);
+    assert!(started.elapsed() < Duration::from_secs(4));
+    let long_header = format!("X-Padding: {}\r\n", "a".repeat(9000));
+    let (status, body) = http_with_headers(&addr, "GET", "/status", &long_header, "");
+    assert_eq!(status, 400);
+    assert!(
+        body["error"].as_str().unwrap().contains("too long"),
+        "{body}"
+    );
+    let many_headers = "X-Padding: a\r\n".repeat(100);
+    let (status, body) = http_with_headers(&addr, "GET", "/status", &many_headers, "");
+    assert_eq!(status, 400);
+    assert!(
+        body["error"].as_str().unwrap().contains("too many headers"),
+        "{body}"
+    );
+    let (status, _) = http(&addr, "GET", "/status", "");
+    assert_eq!(status, 200);
+
+    nix::sys::signal::kill(
+        nix::unistd::Pid::from_raw(child.id() as i32),
+        nix::sys::signal::Signal::SIGTERM,
+    )
+    .unwrap();
+    assert!(child.wait().unwrap().success());
+}
+
+#[test]
+fn serve_queue_endpoint_without_a_token_refuses_jobs() {
+    let queue_dir = temp_dir("serve-queue-no-token");
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args(["serve", "--queue", queue_dir.to_str().unwrap()])
+        .args(["--status-addr", "127.0.0.1:0"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut line = String::new();
+    BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut line)
+        .unwrap();
+    let ready: serde_json::Value = serde_json::from_str(&line).unwrap();
+    let addr = ready["status_addr"].as_str().unwrap();
+
+    let body = serde_json::to_string(&echo_scenario("posted-echo")).unwrap();
+    let (status, _) = http(addr, "POST", "/jobs", &body);
+    assert_eq!(status, 403);
+    let (status, _) = http(addr, "GET", "/health", "");
+    assert_eq!(status, 200);
+
+    nix::sys::signal::kill(
+        nix::unistd::Pid::from_raw(child.id() as i32),
+        nix::sys::signal::Signal::SIGTERM,
+    )
+    .unwrap();
+    assert!(child.wait().unwrap().success());
+}
+
+#[test]
+fn serve_queue_keys_jobs_by_file_name() {
+    let queue_dir = temp_dir("serve-queue-names");
+    let scenario = echo_scenario("same-stem");
+    fs::write(
+        queue_dir.join("same.json"),
+        serde_json::to_vec(&scenario).unwrap(),
+    )
+    .unwrap();
+    fs::write(
+        queue_dir.join("same.yaml"),
+        serde_yml::to_string(&scenario).unwrap(),
+    )
+    .unwrap();
+
+    let (mut child, _addr) = spawn_queue(&queue_dir, &[]);
+    for name in ["same.json", "same.yaml"] {
+        let result_path = queue_dir.join(format!("done/{name}.result.json"));
+        wait_for_file(&result_path);
+        let result: RunResult = serde_json::from_slice(&fs::read(&result_path).unwrap()).unwrap();
+        assert_eq!(result.status, RunStatus::Passed);
+        assert!(queue_dir.join("done").join(name).exists());
+    }
+
+    nix::sys::signal::kill(
+        nix::unistd::Pid::from_raw(child.id() as i32),
+        nix::sys::signal::Signal::SIGTERM,
+    )
+    .unwrap();
+    assert!(child.wait().unwrap().success());
+}
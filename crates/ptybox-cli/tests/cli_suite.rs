@@ -0,0 +1,539 @@
+// Test module - relaxed lint rules
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(missing_docs)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ptybox::model::policy::PolicyBuilder;
+use ptybox::model::{RunStatus, SelectionReason, SuiteResult, SuiteSelection};
+use serde_json::{json, Value};
+
+fn temp_dir(prefix: &str) -> PathBuf {
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let dir = std::env::temp_dir().join(format!("ptybox-cli-test-{prefix}-{stamp}"));
+    fs::create_dir_all(&dir).unwrap();
+    dir.canonicalize().unwrap()
+}
+
+fn ptybox(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// Write `dir/<name>.json`, a scenario without steps.
+fn write_scenario(dir: &Path, name: &str) {
+    let scenario = json!({
+        "scenario_version": 1,
+        "metadata": { "name": name },
+        "run": {
+            "command": "/bin/true",
+            "args": [],
+            "initial_size": { "rows": 4, "cols": 20 },
+            "policy": PolicyBuilder::new().build()
+        },
+        "steps": []
+    });
+    fs::write(
+        dir.join(format!("{name}.json")),
+        serde_json::to_vec_pretty(&scenario).unwrap(),
+    )
+    .unwrap();
+}
+
+/// A suite of `login`, `search`, and `settings` scenarios, and a list of
+/// changed files touching the auth code.
+fn suite(prefix: &str) -> (PathBuf, String, String) {
+    let dir = temp_dir(prefix);
+    let scenarios = dir.join("scenarios");
+    fs::create_dir_all(&scenarios).unwrap();
+    for name in ["login", "search", "settings"] {
+        write_scenario(&scenarios, name);
+    }
+    let changed = dir.join("changed.txt");
+    fs::write(&changed, "./src/auth/session.rs\n\nREADME.md\n").unwrap();
+    (
+        dir,
+        scenarios.display().to_string(),
+        changed.display().to_string(),
+    )
+}
+
+fn select(args: &[&str]) -> SuiteSelection {
+    let output = ptybox(&[&["run-suite", "--dry-run", "--json"], args].concat());
+    assert!(output.status.success(), "{args:?}: {output:?}");
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn suite_selects_scenarios_whose_globs_match_changed_files() {
+    let (dir, scenarios, changed) = suite("suite-select");
+    let map = dir.join("impact.yaml");
+    fs::write(
+        &map,
+        "impact_map_version: 1\nscenarios:\n  login: [\"src/auth/*\"]\n  search: [\"src/search/*\"]\n  removed: [\"src/old/*\"]\n",
+    )
+    .unwrap();
+
+    let selection = select(&[
+        "--dir",
+        &scenarios,
+        "--changed-files",
+        &changed,
+        "--impact-map",
+        &map.display().to_string(),
+    ]);
+    assert!(!selection.full_run);
+    assert_eq!(
+        selection.changed_files,
+        ["src/auth/session.rs", "README.md"]
+    );
+    assert_eq!((selection.selected, selection.skipped), (2, 1));
+    assert_eq!(selection.unknown_mappings, ["removed"]);
+    let decisions: Vec<(&str, bool, SelectionReason)> = selection
+        .scenarios
+        .iter()
+        .map(|s| (s.scenario.as_str(), s.selected, s.reason))
+        .collect();
+    assert_eq!(
+        decisions,
+        [
+            ("login", true, SelectionReason::Matched),
+            ("search", false, SelectionReason::NotAffected),
+            ("settings", true, SelectionReason::Unmapped),
+        ]
+    );
+    let matched = &selection.scenarios[0].matches[0];
+    assert_eq!(
+        (matched.file.as_str(), matched.glob.as_str()),
+        ("src/auth/session.rs", "src/auth/*")
+    );
+}
+
+#[test]
+fn suite_globs_keep_single_stars_within_a_directory() {
+    let (dir, scenarios, _) = suite("suite-nested");
+    let changed = dir.join("nested.txt");
+    fs::write(&changed, "src/auth/oauth/token.rs\n").unwrap();
+    let map = dir.join("impact.json");
+    fs::write(
+        &map,
+        r#"{"impact_map_version": 1, "scenarios": {"login": ["src/auth/**"], "search": ["src/*"], "settings": ["src/auth/*"]}}"#,
+    )
+    .unwrap();
+
+    let selection = select(&[
+        "--dir",
+        &scenarios,
+        "--changed-files",
+        &changed.display().to_string(),
+        "--impact-map",
+        &map.display().to_string(),
+    ]);
+    let decisions: Vec<(&str, bool)> = selection
+        .scenarios
+        .iter()
+        .map(|s| (s.scenario.as_str(), s.selected))
+        .collect();
+    assert_eq!(
+        decisions,
+        [("login", true), ("search", false), ("settings", false)]
+    );
+}
+
+#[test]
+fn suite_falls_back_to_a_full_run() {
+    let (dir, scenarios, changed) = suite("suite-full");
+    let selection = select(&["--dir", &scenarios, "--changed-files", &changed]);
+    assert!(selection.full_run);
+    assert_eq!(selection.selected, 3);
+    assert!(selection
+        .scenarios
+        .iter()
+        .all(|s| s.reason == SelectionReason::NoImpactMap));
+
+    let map = dir.join("impact.json");
+    fs::write(
+        &map,
+        r#"{"impact_map_version": 1, "always": ["*.md"], "scenarios": {"search": ["src/search/*"]}}"#,
+    )
+    .unwrap();
+    let map = map.display().to_string();
+    let selection = select(&[
+        "--dir",
+        &scenarios,
+        "--changed-files",
+        &changed,
+        "--impact-map",
+        &map,
+    ]);
+    assert!(selection.full_run);
+    assert_eq!((selection.selected, selection.skipped), (3, 0));
+    assert_eq!(selection.scenarios[1].reason, SelectionReason::Always);
+    assert_eq!(selection.scenarios[1].matches[0].file, "README.md");
+
+    let output = ptybox(&[
+        "run-suite",
+        "--dry-run",
+        "--dir",
+        &scenarios,
+        "--changed-files",
+        &changed,
+        "--impact-map",
+        &map,
+    ]);
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert!(lines[0].starts_with("RUN"), "{stdout}");
+    assert!(lines[2].contains("always: README.md (*.md)"), "{stdout}");
+    assert_eq!(
+        lines[4], "3 selected, 0 skipped (2 changed files, full run)",
+        "{stdout}"
+    );
+}
+
+#[test]
+fn suite_rejects_an_unsupported_impact_map() {
+    let (dir, scenarios, changed) = suite("suite-version");
+    let map = dir.join("impact.json");
+    fs::write(&map, r#"{"impact_map_version": 2}"#).unwrap();
+    let output = ptybox(&[
+        "run-suite",
+        "--dry-run",
+        "--json",
+        "--dir",
+        &scenarios,
+        "--changed-files",
+        &changed,
+        "--impact-map",
+        &map.display().to_string(),
+    ]);
+    assert!(!output.status.success());
+    let error: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(error["code"], "E_PROTOCOL");
+}
+
+/// Write `dir/<name>.json`, a `/bin/cat` scenario that types `hello`,
+/// expects `expect` on screen, and exits. Only `/bin/cat` is allowed, so any other
+/// `command` is denied.
+fn write_cat_scenario(dir: &Path, name: &str, command: &str, expect: &str, root: &Path) {
+    let policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_executables(vec!["/bin/cat".to_string()])
+        .allowed_write(vec![root.display().to_string()])
+        .build();
+    let scenario = json!({
+        "scenario_version": 1,
+        "metadata": { "name": name },
+        "run": {
+            "command": command,
+            "args": [],
+            "initial_size": { "rows": 4, "cols": 20 },
+            "policy": policy
+        },
+        "steps": [{
+            "id": "00000000-0000-0000-0000-000000000001",
+            "name": "type",
+            "action": { "type": "text", "payload": { "text": "hello\n" } },
+            "assert": [{ "type": "screen_contains", "payload": { "text": expect } }],
+            "timeout_ms": 500,
+            "retries": 0
+        }, {
+            "id": "00000000-0000-0000-0000-000000000002",
+            "name": "exit",
+            "action": { "type": "key", "payload": { "key": "Ctrl+D" } },
+            "assert": [],
+            "timeout_ms": 500,
+            "retries": 0
+        }]
+    });
+    fs::write(
+        dir.join(format!("{name}.json")),
+        serde_json::to_vec_pretty(&scenario).unwrap(),
+    )
+    .unwrap();
+}
+
+/// A suite where `alpha` passes, `beta` fails its assertion, and `gamma`
+/// runs a command the policy denies, plus a file that is not a scenario.
+fn run_suite_dir(prefix: &str) -> (PathBuf, PathBuf) {
+    let dir = temp_dir(prefix);
+    let scenarios = dir.join("scenarios");
+    fs::create_dir_all(&scenarios).unwrap();
+    write_cat_scenario(&scenarios, "alpha", "/bin/cat", "hello", &dir);
+    write_cat_scenario(&scenarios, "beta", "/bin/cat", "nope", &dir);
+    write_cat_scenario(&scenarios, "gamma", "/bin/echo", "hello", &dir);
+    fs::write(scenarios.join("notes.json"), "{\"not\": \"a scenario\"}").unwrap();
+    (scenarios, dir.join("artifacts"))
+}
+
+/// Check the per-scenario outcomes of the suite from [`run_suite_dir`].
+fn assert_suite_scenarios(result: &SuiteResult, artifacts: &Path) {
+    let alpha = &result.scenarios[0];
+    assert_eq!(alpha.status, RunStatus::Passed);
+    assert_eq!((alpha.steps_passed, alpha.steps), (2, 2));
+    let alpha_dir = artifacts.join("001-alpha");
+    assert_eq!(alpha.artifacts_dir.as_deref(), alpha_dir.to_str());
+    let run: Value =
+        serde_json::from_str(&fs::read_to_string(alpha_dir.join("run.json")).unwrap()).unwrap();
+    assert_eq!(run["run_id"], json!(alpha.run_id));
+
+    let beta = &result.scenarios[1];
+    assert_eq!(beta.status, RunStatus::Failed);
+    assert_eq!(beta.error.as_ref().unwrap().code, "E_ASSERTION_FAILED");
+    let gamma = &result.scenarios[2];
+    assert_eq!(gamma.status, RunStatus::Errored);
+    assert_eq!(gamma.error.as_ref().unwrap().code, "E_POLICY_DENIED");
+    assert!(gamma.run_id.is_none());
+}
+
+#[test]
+fn run_suite_runs_each_scenario_and_writes_summary() {
+    let (scenarios, artifacts) = run_suite_dir("run-suite");
+    let output = ptybox(&[
+        "run-suite",
+        "--json",
+        "--dir",
+        scenarios.to_str().unwrap(),
+        "--artifacts",
+        artifacts.to_str().unwrap(),
+        "--jobs",
+        "2",
+    ]);
+
+    // beta is the first scenario that did not pass
+    assert_eq!(output.status.code(), Some(5), "{output:?}");
+    let result: SuiteResult = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result.status, RunStatus::Failed);
+    assert_eq!(result.jobs, 2);
+    assert_eq!(
+        (
+            result.passed,
+            result.failed,
+            result.errored,
+            result.canceled
+        ),
+        (1, 1, 1, 0)
+    );
+    let names: Vec<&str> = result
+        .scenarios
+        .iter()
+        .map(|scenario| scenario.scenario.as_str())
+        .collect();
+    assert_eq!(names, ["alpha", "beta", "gamma"]);
+
+    assert_suite_scenarios(&result, &artifacts);
+
+    let summary: Value =
+        serde_json::from_str(&fs::read_to_string(artifacts.join("suite.json")).unwrap()).unwrap();
+    assert_eq!(
+        summary,
+        serde_json::from_slice::<Value>(&output.stdout).unwrap()
+    );
+    assert_eq!(summary["suite_result_version"], 1);
+    let index = fs::read_to_string(artifacts.join("runs.jsonl")).unwrap();
+    assert_eq!(index.lines().count(), 3, "{index}");
+}
+
+#[test]
+fn run_suite_prints_a_table_and_guards_the_summary() {
+    let (scenarios, artifacts) = run_suite_dir("run-suite-table");
+    fs::remove_file(scenarios.join("beta.json")).unwrap();
+    fs::remove_file(scenarios.join("gamma.json")).unwrap();
+    let run = |extra: &[&str]| {
+        ptybox(
+            &[
+                &[
+                    "run-suite",
+                    "--dir",
+                    scenarios.to_str().unwrap(),
+                    "--artifacts",
+                    artifacts.to_str().unwrap(),
+                ],
+                extra,
+            ]
+            .concat(),
+        )
+    };
+
+    let output = run(&[]);
+    assert!(output.status.success(), "{output:?}");
+    let table = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = table.lines().collect();
+    assert!(lines[0].starts_with("STATUS    SCENARIO"), "{table}");
+    assert!(lines[1].starts_with("passed    alpha"), "{table}");
+    assert!(lines[1].contains("2/2"), "{table}");
+    assert!(
+        lines[2].starts_with("passed: 1 passed, 0 failed, 0 errored, 0 canceled in "),
+        "{table}"
+    );
+
+    let again = run(&["--json"]);
+    assert_eq!(again.status.code(), Some(2));
+    let err: ptybox::model::ErrorInfo = serde_json::from_slice(&again.stdout).unwrap();
+    assert_eq!(err.code, "E_POLICY_DENIED");
+    assert!(run(&["--overwrite"]).status.success());
+
+    let empty = temp_dir("run-suite-empty");
+    let output = ptybox(&["run-suite", "--json", "--dir", empty.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(9));
+}
+
+/// The suite from [`run_suite_dir`] plus a passing `delta`, each scenario
+/// mapped to its own source directory, and a change touching only `delta`'s.
+fn run_suite_selection(prefix: &str) -> (PathBuf, PathBuf, Vec<String>) {
+    let (scenarios, artifacts) = run_suite_dir(prefix);
+    let dir = scenarios.parent().unwrap().to_path_buf();
+    write_cat_scenario(&scenarios, "delta", "/bin/cat", "hello", &dir);
+    let map = dir.join("impact.json");
+    let impact = json!({
+        "impact_map_version": 1,
+        "scenarios": {
+            "alpha": ["src/a/*"],
+            "beta": ["src/b/*"],
+            "delta": ["src/d/*"],
+            "gamma": ["src/g/*"]
+        }
+    });
+    fs::write(&map, serde_json::to_vec(&impact).unwrap()).unwrap();
+    let changed = dir.join("changed.txt");
+    fs::write(&changed, "src/d/main.rs\n").unwrap();
+    let args = vec![
+        "--dir".to_string(),
+        scenarios.display().to_string(),
+        "--artifacts".to_string(),
+        artifacts.display().to_string(),
+        "--changed-files".to_string(),
+        changed.display().to_string(),
+        "--impact-map".to_string(),
+        map.display().to_string(),
+    ];
+    (scenarios, artifacts, args)
+}
+
+#[test]
+fn run_suite_runs_only_scenarios_the_change_affects() {
+    let (_, artifacts, args) = run_suite_selection("run-suite-selected");
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let output = ptybox(&[&["run-suite", "--json"], args.as_slice()].concat());
+
+    assert!(output.status.success(), "{output:?}");
+    let result: SuiteResult = serde_json::from_slice(&output.stdout).unwrap();
+    let names: Vec<&str> = result
+        .scenarios
+        .iter()
+        .map(|scenario| scenario.scenario.as_str())
+        .collect();
+    assert_eq!(names, ["delta"]);
+    assert_eq!(result.passed, 1);
+    // delta keeps its number in the whole suite
+    assert!(artifacts.join("003-delta").join("run.json").is_file());
+    assert!(!artifacts.join("001-alpha").exists());
+
+    let selection = result.selection.unwrap();
+    assert_eq!((selection.selected, selection.skipped), (1, 3));
+    assert_eq!(selection.changed_files, ["src/d/main.rs"]);
+    let delta = &selection.scenarios[2];
+    assert_eq!(delta.reason, SelectionReason::Matched);
+    assert_eq!(delta.matches[0].glob, "src/d/*");
+    let summary: SuiteResult =
+        serde_json::from_str(&fs::read_to_string(artifacts.join("suite.json")).unwrap()).unwrap();
+    assert_eq!(summary.selection.unwrap().skipped, 3);
+}
+
+#[test]
+fn run_suite_dry_run_prints_the_selection_without_running() {
+    let (_, artifacts, args) = run_suite_selection("run-suite-dry-run");
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let output = ptybox(&[&["run-suite", "--dry-run", "--json"], args.as_slice()].concat());
+
+    assert!(output.status.success(), "{output:?}");
+    let selection: SuiteSelection = serde_json::from_slice(&output.stdout).unwrap();
+    let selected: Vec<&str> = selection
+        .scenarios
+        .iter()
+        .filter(|scenario| scenario.selected)
+        .map(|scenario| scenario.scenario.as_str())
+        .collect();
+    assert_eq!(selected, ["delta"]);
+    assert!(!artifacts.exists());
+}
+
+#[test]
+fn suite_runs_the_scenarios_the_change_affects() {
+    let (_, artifacts, args) = run_suite_selection("suite-alias");
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let output = ptybox(&[&["suite", "--json"], args.as_slice()].concat());
+
+    assert!(output.status.success(), "{output:?}");
+    let result: SuiteResult = serde_json::from_slice(&output.stdout).unwrap();
+    let names: Vec<&str> = result
+        .scenarios
+        .iter()
+        .map(|scenario| scenario.scenario.as_str())
+        .collect();
+    assert_eq!(names, ["delta"]);
+    assert_eq!(result.selection.unwrap().skipped, 3);
+    assert!(artifacts.join("003-delta").join("run.json").is_file());
+}
+
+#[test]
+fn run_suite_runs_each_scenario_across_the_locale_timezone_matrix() {
+    let (scenarios, artifacts) = run_suite_dir("run-suite-matrix");
+    fs::remove_file(scenarios.join("beta.json")).unwrap();
+    fs::remove_file(scenarios.join("gamma.json")).unwrap();
+    let output = ptybox(&[
+        "run-suite",
+        "--json",
+        "--dir",
+        scenarios.to_str().unwrap(),
+        "--artifacts",
+        artifacts.to_str().unwrap(),
+        "--locale",
+        "C.UTF-8",
+        "--timezone",
+        "UTC",
+        "--timezone",
+        "Asia/Tokyo",
+    ]);
+
+    assert!(output.status.success(), "{output:?}");
+    let result: SuiteResult = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result.matrix.len(), 2);
+    assert_eq!(result.passed, 2);
+    for (entry, (label, timezone)) in result
+        .scenarios
+        .iter()
+        .zip([("C.UTF-8+UTC", "UTC"), ("C.UTF-8+Asia-Tokyo", "Asia/Tokyo")])
+    {
+        assert_eq!(entry.scenario, "alpha");
+        let cell = entry.determinism.as_ref().unwrap();
+        assert_eq!(cell.timezone.as_deref(), Some(timezone));
+        let dir = artifacts.join("001-alpha").join(label);
+        assert_eq!(entry.artifacts_dir.as_deref(), dir.to_str());
+        let run: Value =
+            serde_json::from_str(&fs::read_to_string(dir.join("run.json")).unwrap()).unwrap();
+        assert_eq!(run["determinism"]["timezone"], timezone);
+        assert_eq!(run["determinism"]["locale"], "C.UTF-8");
+    }
+
+    let invalid = ptybox(&[
+        "run-suite",
+        "--json",
+        "--dir",
+        scenarios.to_str().unwrap(),
+        "--timezone",
+        "UTC; rm",
+    ]);
+    assert_eq!(invalid.status.code(), Some(2), "{invalid:?}");
+}
@@ -56,11 +56,14 @@ fn create_mock_artifacts(dir: &std::path::Path) {
         "steps": [
             {
                 "step_id": "00000000-0000-0000-0000-000000000002",
+                "step_key": "3f2a9c04d1e8b7a6",
                 "name": "Send text",
                 "status": "passed",
                 "attempts": 1,
                 "started_at_ms": 1000,
                 "ended_at_ms": 1500,
+                "latency_ms": 42,
+                "metadata": {"owner": "tui-team", "ticket": "PTY-42"},
                 "action": { "type": "text", "payload": {"text": "hello"} },
                 "assertions": [
                     {"type": "screen_contains", "passed": true}
@@ -216,6 +219,30 @@ fn trace_embeds_step_results() {
         html.contains("screen_contains"),
         "should contain assertion type"
     );
+    assert!(
+        html.contains("\"latency_ms\":42"),
+        "should embed step latency"
+    );
+    assert!(
+        html.contains("responded in"),
+        "should label latency in the timeline"
+    );
+    assert!(
+        html.contains("\"ticket\":\"PTY-42\""),
+        "should embed step metadata"
+    );
+    assert!(
+        html.contains("metadata-item"),
+        "should render step metadata"
+    );
+    assert!(
+        html.contains("\"step_key\":\"3f2a9c04d1e8b7a6\""),
+        "should embed the step's content key"
+    );
+    assert!(
+        html.contains("'#step-' + step.step_key"),
+        "should link steps by content key"
+    );
 }
 
 #[test]
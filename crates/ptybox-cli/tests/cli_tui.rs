@@ -48,6 +48,27 @@ fn run_help_shows_tui_flag() {
     );
 }
 
+#[test]
+fn run_save_amended_requires_tui() {
+    let output = ptybox_bin()
+        .args([
+            "run",
+            "--scenario",
+            "scenario.json",
+            "--save-amended",
+            "out.json",
+        ])
+        .output()
+        .expect("failed to execute");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("--tui"),
+        "expected --tui requirement: {stderr}"
+    );
+}
+
 #[test]
 fn run_tui_rejects_json_combination() {
     // Create a minimal scenario file
@@ -0,0 +1,294 @@
+// Test module - relaxed lint rules
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::panic)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(missing_docs)]
+
+//! `ptybox watch` reruns, deltas, and rolling artifacts.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ptybox::model::policy::PolicyBuilder;
+use ptybox::model::scenario::PolicyRef;
+use ptybox::model::{
+    Action, Assertion, OutputBufferConfig, ResizeConfig, RunConfig, Scenario, ScenarioDefaults,
+    ScenarioMetadata, Step, StepId, TerminalSize,
+};
+use serde_json::Value;
+
+const LINE_TIMEOUT: Duration = Duration::from_secs(30);
+
+fn temp_dir(prefix: &str) -> PathBuf {
+    let mut dir = std::env::temp_dir();
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    dir.push(format!("ptybox-cli-test-{prefix}-{stamp}"));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Scenario typing `hello` into `cat` and asserting that `expected` appears.
+fn write_scenario(path: &Path, dir: &Path, expected: &str) {
+    let artifacts = dir.join("artifacts").display().to_string();
+    let policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_read(vec![dir.display().to_string()])
+        .allowed_write(vec![artifacts])
+        .working_dir(dir.display().to_string())
+        .allowed_executables(vec!["/bin/cat".to_string()])
+        .build();
+    let step = Step {
+        id: StepId::new(),
+        name: "greet".to_string(),
+        action: Action::text("hello"),
+        assert: vec![Assertion::screen_contains(expected)],
+        timeout_ms: 500,
+        retries: 0,
+        skip_default_assertions: false,
+        sample_interval_ms: None,
+        expected_screen: None,
+        capture: None,
+        env_overlay: BTreeMap::new(),
+        metadata: BTreeMap::new(),
+        vars: Vec::new(),
+    };
+    let scenario = Scenario {
+        scenario_version: 1,
+        metadata: ScenarioMetadata {
+            name: "watch".to_string(),
+            description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
+        },
+        run: RunConfig {
+            command: "/bin/cat".to_string(),
+            args: Vec::new(),
+            cwd: Some(dir.display().to_string()),
+            initial_size: TerminalSize::default(),
+            policy: PolicyRef::Inline(Box::new(policy)),
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
+        },
+        steps: vec![
+            step,
+            Step {
+                id: StepId::new(),
+                name: "stop".to_string(),
+                action: Action::terminate(),
+                assert: Vec::new(),
+                timeout_ms: 500,
+                retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
+            },
+        ],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
+    };
+    fs::write(path, serde_json::to_vec_pretty(&scenario).unwrap()).unwrap();
+}
+
+fn spawn_watch(dir: &Path, scenario: &Path, extra: &[&str]) -> (Child, Receiver<Value>) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ptybox"))
+        .args(["watch", "--json", "--debounce-ms", "100", "--poll-ms", "20"])
+        .arg("--scenario")
+        .arg(scenario)
+        .arg("--paths")
+        .arg(dir.join("src"))
+        .args(extra)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let stdout = child.stdout.take().unwrap();
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            let Ok(line) = line else { break };
+            if tx.send(serde_json::from_str(&line).unwrap()).is_err() {
+                break;
+            }
+        }
+    });
+    (child, rx)
+}
+
+fn next_report(child: &mut Child, rx: &Receiver<Value>) -> Value {
+    rx.recv_timeout(LINE_TIMEOUT).unwrap_or_else(|err| {
+        let _ = child.kill();
+        panic!("no watch report: {err}");
+    })
+}
+
+#[test]
+fn watch_reruns_on_change_and_reports_delta() {
+    let dir = temp_dir("watch-delta");
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(dir.join("src/app.txt"), "v1").unwrap();
+    let scenario = dir.join("scenario.json");
+    write_scenario(&scenario, &dir, "hello");
+
+    let (mut child, rx) = spawn_watch(&dir, &scenario, &["--max-runs", "3"]);
+
+    let first = next_report(&mut child, &rx);
+    assert_eq!(first["run"], 1);
+    assert_eq!(first["status"], "passed");
+    assert_eq!(first["passed_steps"], 2);
+    assert!(first["changed"].as_array().unwrap().is_empty());
+
+    write_scenario(&scenario, &dir, "never-printed");
+    let second = next_report(&mut child, &rx);
+    assert_eq!(second["run"], 2);
+    assert_eq!(second["status"], "failed");
+    assert_eq!(second["previous_status"], "passed");
+    assert_eq!(second["newly_failing"], serde_json::json!(["greet"]));
+    assert!(second["changed"][0]
+        .as_str()
+        .unwrap()
+        .ends_with("scenario.json"));
+
+    write_scenario(&scenario, &dir, "hello");
+    fs::write(dir.join("src/app.txt"), "v2-longer").unwrap();
+    let third = next_report(&mut child, &rx);
+    assert_eq!(third["status"], "passed");
+    assert_eq!(third["newly_passing"], serde_json::json!(["greet"]));
+
+    let status = child.wait().unwrap();
+    assert!(status.success(), "last run passed, so watch exits 0");
+}
+
+#[test]
+fn watch_keeps_rolling_window_of_artifacts() {
+    let dir = temp_dir("watch-artifacts");
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(dir.join("src/app.txt"), "v1").unwrap();
+    let scenario = dir.join("scenario.json");
+    write_scenario(&scenario, &dir, "hello");
+    let artifacts = dir.join("artifacts");
+
+    let (mut child, rx) = spawn_watch(
+        &dir,
+        &scenario,
+        &[
+            "--artifacts",
+            artifacts.to_str().unwrap(),
+            "--keep",
+            "2",
+            "--max-runs",
+            "3",
+        ],
+    );
+
+    for (run, contents) in [(1, "v2-a"), (2, "v3-ab"), (3, "")] {
+        let report = next_report(&mut child, &rx);
+        assert_eq!(report["run"], run);
+        assert_eq!(report["status"], "passed", "{report}");
+        assert!(report["artifacts_dir"]
+            .as_str()
+            .unwrap()
+            .ends_with(&format!("run-{run}")));
+        if !contents.is_empty() {
+            fs::write(dir.join("src/app.txt"), contents).unwrap();
+        }
+    }
+    assert!(child.wait().unwrap().success());
+
+    assert!(!artifacts.join("run-1").exists());
+    assert!(artifacts.join("run-2").join("run.json").exists());
+    assert!(artifacts.join("run-3").join("run.json").exists());
+}
+
+#[test]
+fn watch_numbers_runs_past_earlier_sessions() {
+    let dir = temp_dir("watch-sessions");
+    fs::create_dir_all(dir.join("src")).unwrap();
+    let scenario = dir.join("scenario.json");
+    write_scenario(&scenario, &dir, "hello");
+    let artifacts = dir.join("artifacts");
+    for earlier in ["run-2", "run-10"] {
+        fs::create_dir_all(artifacts.join(earlier)).unwrap();
+        fs::write(artifacts.join(earlier).join("run.json"), "{}").unwrap();
+    }
+
+    let (mut child, rx) = spawn_watch(
+        &dir,
+        &scenario,
+        &[
+            "--artifacts",
+            artifacts.to_str().unwrap(),
+            "--keep",
+            "2",
+            "--max-runs",
+            "1",
+        ],
+    );
+    let report = next_report(&mut child, &rx);
+    assert_eq!(report["run"], 1);
+    assert!(report["artifacts_dir"]
+        .as_str()
+        .unwrap()
+        .ends_with("run-11"));
+    assert!(child.wait().unwrap().success());
+
+    assert!(!artifacts.join("run-2").exists());
+    assert_eq!(
+        fs::read_to_string(artifacts.join("run-10/run.json")).unwrap(),
+        "{}"
+    );
+    assert!(artifacts.join("run-11/run.json").exists());
+}
+
+#[test]
+fn watch_poll_detects_same_size_edits() {
+    let dir = temp_dir("watch-poll");
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(dir.join("src/app.txt"), "v1").unwrap();
+    let scenario = dir.join("scenario.json");
+    write_scenario(&scenario, &dir, "hello");
+
+    let (mut child, rx) = spawn_watch(&dir, &scenario, &["--poll", "--max-runs", "2"]);
+    assert_eq!(next_report(&mut child, &rx)["run"], 1);
+
+    // Same size, and possibly the same mtime on coarse-timestamp filesystems
+    fs::write(dir.join("src/app.txt"), "v2").unwrap();
+    let second = next_report(&mut child, &rx);
+    assert_eq!(second["run"], 2);
+    assert!(second["changed"][0].as_str().unwrap().ends_with("app.txt"));
+    assert!(child.wait().unwrap().success());
+}
+
+#[test]
+fn watch_exits_with_last_run_status() {
+    let dir = temp_dir("watch-exit");
+    fs::create_dir_all(dir.join("src")).unwrap();
+    let scenario = dir.join("scenario.json");
+    write_scenario(&scenario, &dir, "never-printed");
+
+    let (mut child, rx) = spawn_watch(&dir, &scenario, &["--max-runs", "1"]);
+    let report = next_report(&mut child, &rx);
+    assert_eq!(report["status"], "failed");
+    assert!(!child.wait().unwrap().success());
+}
@@ -0,0 +1,533 @@
+//! Driver process management and typed request methods.
+
+use crate::error::{ClientError, ClientResult};
+use crate::inbox::Inbox;
+use ptybox::model::{
+    Action, Condition, DriverEvent, DriverEventAck, DriverEventKind, DriverHistory,
+    DriverRequestV2, DriverResponseStatus, DriverResponseV2, DriverSubscription, ErrorInfo,
+    Observation, TerminalSize, PROTOCOL_VERSION,
+};
+use ptybox::runner::ErrorCode;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
+
+/// Request id the driver uses when it cannot attribute an error to a request.
+const UNKNOWN_REQUEST_ID: &str = "unknown";
+
+/// How to launch `ptybox driver --stdio --json`.
+///
+/// Mirrors the driver's CLI flags; `command` and `args` are the program the
+/// driver runs under its policy.
+#[derive(Clone, Debug, Default)]
+pub struct SpawnConfig {
+    /// Path to the `ptybox` binary (defaults to `ptybox` on `PATH`).
+    pub program: Option<PathBuf>,
+    /// Policy file passed as `--policy`.
+    pub policy: Option<PathBuf>,
+    /// Working directory override passed as `--cwd`.
+    pub cwd: Option<String>,
+    /// Artifacts directory passed as `--artifacts`.
+    pub artifacts: Option<PathBuf>,
+    /// Pass `--overwrite` for the artifacts directory.
+    pub overwrite: bool,
+    /// Pass `--separate-stderr`.
+    pub separate_stderr: bool,
+    /// Additional driver flags (e.g. `--no-sandbox`), inserted before `--`.
+    pub driver_args: Vec<String>,
+    /// Program the driver runs.
+    pub command: String,
+    /// Arguments for `command`.
+    pub args: Vec<String>,
+}
+
+impl SpawnConfig {
+    fn to_command(&self) -> Command {
+        let program = self
+            .program
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("ptybox"));
+        let mut cmd = Command::new(program);
+        cmd.args(["driver", "--stdio", "--json"]);
+        if let Some(policy) = &self.policy {
+            cmd.arg("--policy").arg(policy);
+        }
+        if let Some(cwd) = &self.cwd {
+            cmd.arg("--cwd").arg(cwd);
+        }
+        if let Some(artifacts) = &self.artifacts {
+            cmd.arg("--artifacts").arg(artifacts);
+        }
+        if self.overwrite {
+            cmd.arg("--overwrite");
+        }
+        if self.separate_stderr {
+            cmd.arg("--separate-stderr");
+        }
+        cmd.args(&self.driver_args)
+            .arg("--")
+            .arg(&self.command)
+            .args(&self.args);
+        cmd
+    }
+}
+
+/// First line emitted by the driver, announcing its capabilities.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Handshake {
+    /// Protocol version the driver speaks.
+    pub protocol_version: u32,
+    /// Run identifier for this driver session.
+    pub run_id: String,
+    /// Initial terminal size.
+    pub terminal_size: TerminalSize,
+    /// Effective policy budgets.
+    pub budgets: HandshakeBudgets,
+    /// Action types the driver accepts.
+    pub supported_actions: Vec<String>,
+    /// Wait condition types the driver accepts.
+    pub supported_conditions: Vec<String>,
+}
+
+impl Handshake {
+    /// Whether the driver accepts actions of type `action` (e.g. `hold_key`).
+    #[must_use]
+    pub fn supports_action(&self, action: &str) -> bool {
+        self.supported_actions.iter().any(|name| name == action)
+    }
+}
+
+/// Budgets announced in the [`Handshake`].
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct HandshakeBudgets {
+    /// Maximum number of actions.
+    #[serde(default)]
+    pub max_steps: u64,
+    /// Maximum run duration in milliseconds.
+    #[serde(default)]
+    pub max_runtime_ms: u64,
+    /// Maximum terminal output in bytes.
+    #[serde(default)]
+    pub max_output_bytes: u64,
+    /// Maximum snapshot size in bytes.
+    #[serde(default)]
+    pub max_snapshot_bytes: u64,
+    /// Maximum single wait duration in milliseconds.
+    #[serde(default)]
+    pub max_wait_ms: u64,
+    /// Idle timeout between requests in milliseconds.
+    #[serde(default)]
+    pub max_session_idle_ms: Option<u64>,
+    /// Maximum session lifetime in milliseconds.
+    #[serde(default)]
+    pub max_session_lifetime_ms: Option<u64>,
+}
+
+/// Optional request fields for [`DriverClient::request`].
+#[derive(Clone, Debug, Default)]
+pub struct RequestOptions {
+    /// Per-action timeout in milliseconds (driver default when `None`).
+    pub timeout_ms: Option<u64>,
+    /// Only run the action if the screen still has this hash.
+    pub expected_screen_hash: Option<String>,
+    /// For `wait` and `observe`: answer `unchanged` instead of repeating an
+    /// identical screen.
+    pub coalesce: bool,
+    /// Attach a base64 PNG of the screen to the response (the driver must
+    /// be built with the `render` feature).
+    pub include_image: bool,
+}
+
+/// A connection to a protocol v2 driver.
+///
+/// Requests are answered in order, so each method takes `&mut self` and
+/// resolves once its response arrives. Writes to the driver are synchronous
+/// (request lines are small); waiting for the response never blocks the
+/// executor. Event frames pushed after [`subscribe`](Self::subscribe) are
+/// kept aside until [`next_event`](Self::next_event) takes them.
+pub struct DriverClient {
+    writer: Option<Box<dyn Write + Send>>,
+    inbox: Arc<Inbox>,
+    handshake: Handshake,
+    events: VecDeque<DriverEvent>,
+    child: Option<Child>,
+    next_request: u64,
+}
+
+impl DriverClient {
+    /// Spawn `ptybox driver --stdio --json` and wait for its handshake.
+    ///
+    /// The driver's stderr is inherited.
+    ///
+    /// # Errors
+    /// [`ClientError::Io`] if the driver cannot be spawned, the driver's own
+    /// error if it exits before the handshake (policy denial, bad arguments),
+    /// or [`ClientError::UnsupportedProtocol`] on a version mismatch.
+    pub async fn spawn(config: SpawnConfig) -> ClientResult<Self> {
+        let mut child = config
+            .to_command()
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|err| ClientError::io("spawn ptybox driver", err))?;
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(ClientError::protocol("driver stdio was not captured"));
+        };
+        let mut client = match Self::connect(stdout, stdin).await {
+            Ok(client) => client,
+            Err(ClientError::Closed) => return Err(exited_before_handshake(&mut child)),
+            Err(err) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(err);
+            }
+        };
+        client.child = Some(child);
+        Ok(client)
+    }
+
+    /// Attach to a driver whose output is `reader` and input is `writer`,
+    /// and wait for its handshake.
+    ///
+    /// Use this for drivers started elsewhere (a remote shell, a container
+    /// exec, a socket).
+    ///
+    /// # Errors
+    /// [`ClientError::Closed`] if the stream ends before the handshake, or
+    /// [`ClientError::UnsupportedProtocol`] on a version mismatch.
+    pub async fn attach<R, W>(reader: R, writer: W) -> ClientResult<Self>
+    where
+        R: Read + Send + 'static,
+        W: Write + Send + 'static,
+    {
+        Self::connect(reader, writer).await
+    }
+
+    async fn connect<R, W>(reader: R, writer: W) -> ClientResult<Self>
+    where
+        R: Read + Send + 'static,
+        W: Write + Send + 'static,
+    {
+        let inbox =
+            Inbox::spawn(reader).map_err(|err| ClientError::io("start reader thread", err))?;
+        let line = match inbox.next_line().await {
+            Some(line) => line.map_err(|err| ClientError::io("read driver handshake", err))?,
+            None => return Err(ClientError::Closed),
+        };
+        let handshake = parse_handshake(&line)?;
+        if handshake.protocol_version != PROTOCOL_VERSION {
+            return Err(ClientError::UnsupportedProtocol {
+                driver: handshake.protocol_version,
+                client: PROTOCOL_VERSION,
+            });
+        }
+        Ok(Self {
+            writer: Some(Box::new(writer)),
+            inbox,
+            handshake,
+            events: VecDeque::new(),
+            child: None,
+            next_request: 0,
+        })
+    }
+
+    /// The driver's handshake.
+    #[must_use]
+    pub fn handshake(&self) -> &Handshake {
+        &self.handshake
+    }
+
+    /// Send `action` and return the full successful response.
+    ///
+    /// # Errors
+    /// [`ClientError::UnsupportedAction`] if the handshake did not list the
+    /// action type, [`ClientError::Driver`] if the driver answered with an
+    /// error, or [`ClientError::Closed`] if it exited first.
+    pub async fn request(
+        &mut self,
+        action: Action,
+        options: RequestOptions,
+    ) -> ClientResult<DriverResponseV2> {
+        let action_type = serde_json::to_value(&action.action_type)
+            .ok()
+            .and_then(|value| value.as_str().map(str::to_string))
+            .unwrap_or_default();
+        if !self.handshake.supports_action(&action_type) {
+            return Err(ClientError::UnsupportedAction {
+                action: action_type,
+            });
+        }
+        self.next_request += 1;
+        let request = DriverRequestV2 {
+            protocol_version: PROTOCOL_VERSION,
+            request_id: format!("req-{}", self.next_request),
+            action,
+            timeout_ms: options.timeout_ms,
+            expected_screen_hash: options.expected_screen_hash,
+            coalesce: options.coalesce,
+            include_image: options.include_image,
+        };
+        self.write_request(&request)?;
+        let response = self.read_response().await?;
+        if response.request_id != request.request_id && response.request_id != UNKNOWN_REQUEST_ID {
+            return Err(ClientError::protocol(format!(
+                "response for `{}` while waiting for `{}`",
+                response.request_id, request.request_id
+            )));
+        }
+        match response.status {
+            DriverResponseStatus::Ok => Ok(response),
+            DriverResponseStatus::Error => Err(response.error.map_or_else(
+                || ClientError::protocol("error response without an error object"),
+                ClientError::from,
+            )),
+        }
+    }
+
+    /// Run an action (key, text, resize, raw, `hold_key`, ...) and return the
+    /// observation that follows it.
+    ///
+    /// # Errors
+    /// See [`DriverClient::request`].
+    pub async fn act(&mut self, action: Action) -> ClientResult<Observation> {
+        let response = self.request(action, RequestOptions::default()).await?;
+        into_observation(response)
+    }
+
+    /// Wait until `condition` holds, up to `timeout_ms` (driver default when
+    /// `None`).
+    ///
+    /// # Errors
+    /// [`ClientError::Driver`] with [`ErrorCode::Timeout`] if the condition
+    /// never held; otherwise see [`DriverClient::request`].
+    pub async fn wait(
+        &mut self,
+        condition: Condition,
+        timeout_ms: Option<u64>,
+    ) -> ClientResult<Observation> {
+        let action = Action {
+            action_type: ptybox::model::ActionType::Wait,
+            payload: serde_json::json!({ "condition": condition }),
+        };
+        let options = RequestOptions {
+            timeout_ms,
+            expected_screen_hash: None,
+            coalesce: false,
+            include_image: false,
+        };
+        into_observation(self.request(action, options).await?)
+    }
+
+    /// Observe the current screen without sending input.
+    ///
+    /// # Errors
+    /// See [`DriverClient::request`].
+    pub async fn query(&mut self) -> ClientResult<Observation> {
+        let action = Action {
+            action_type: ptybox::model::ActionType::Observe,
+            payload: serde_json::json!({}),
+        };
+        self.act(action).await
+    }
+
+    /// Fetch the driver's log of recent requests, at most `limit` entries
+    /// (all kept entries when `None`), to resynchronize after reconnecting.
+    ///
+    /// # Errors
+    /// See [`DriverClient::request`].
+    pub async fn history(&mut self, limit: Option<u64>) -> ClientResult<DriverHistory> {
+        let response = self
+            .request(Action::history(limit), RequestOptions::default())
+            .await?;
+        response
+            .history
+            .ok_or_else(|| ClientError::protocol("history response without a history"))
+    }
+
+    /// Ask the driver to push `events` between responses (an empty slice
+    /// unsubscribes), with at most `max_unacked` unacknowledged (driver
+    /// default when `None`).
+    ///
+    /// # Errors
+    /// See [`DriverClient::request`].
+    pub async fn subscribe(
+        &mut self,
+        events: &[DriverEventKind],
+        max_unacked: Option<u64>,
+    ) -> ClientResult<DriverSubscription> {
+        let response = self
+            .request(
+                Action::subscribe(events, max_unacked, None),
+                RequestOptions::default(),
+            )
+            .await?;
+        response
+            .subscription
+            .ok_or_else(|| ClientError::protocol("subscribe response without a subscription"))
+    }
+
+    /// Wait for the next pushed event. Call [`ack`](Self::ack) as events
+    /// are handled, or the driver stops pushing once its window is full.
+    ///
+    /// # Errors
+    /// [`ClientError::Closed`] if the driver exits first, or a protocol error
+    /// if a response arrives while no request is pending.
+    pub async fn next_event(&mut self) -> ClientResult<DriverEvent> {
+        if let Some(event) = self.events.pop_front() {
+            return Ok(event);
+        }
+        let line = self.read_frame().await?;
+        serde_json::from_str(&line)
+            .map_err(|err| ClientError::protocol(format!("expected an event frame: {err}")))
+    }
+
+    /// Acknowledge every event up to `event_seq`; the driver does not answer.
+    ///
+    /// # Errors
+    /// [`ClientError::Io`] if the acknowledgement cannot be written.
+    pub fn ack(&mut self, event_seq: u64) -> ClientResult<()> {
+        self.write_line(&DriverEventAck { event_seq })
+    }
+
+    /// Terminate the driven process, close the driver, and return the final
+    /// observation.
+    ///
+    /// # Errors
+    /// See [`DriverClient::request`]; a spawned driver that exits non-zero
+    /// afterwards reports its error code as [`ClientError::Driver`].
+    pub async fn terminate(mut self) -> ClientResult<Observation> {
+        let observation = self.act(Action::terminate()).await?;
+        self.finish()?;
+        Ok(observation)
+    }
+
+    /// Close the driver's input without terminating explicitly; the driver
+    /// shuts the process down and exits.
+    ///
+    /// # Errors
+    /// A spawned driver that exits non-zero reports its error code as
+    /// [`ClientError::Driver`].
+    pub fn close(mut self) -> ClientResult<()> {
+        self.finish()
+    }
+
+    fn write_request(&mut self, request: &DriverRequestV2) -> ClientResult<()> {
+        self.write_line(request)
+    }
+
+    fn write_line(&mut self, frame: &impl Serialize) -> ClientResult<()> {
+        let Some(writer) = self.writer.as_mut() else {
+            return Err(ClientError::Closed);
+        };
+        let mut line = serde_json::to_string(frame)
+            .map_err(|err| ClientError::protocol(format!("failed to encode request: {err}")))?;
+        line.push('\n');
+        writer
+            .write_all(line.as_bytes())
+            .and_then(|()| writer.flush())
+            .map_err(|err| ClientError::io("write driver request", err))
+    }
+
+    /// Read the next response, setting aside event frames that precede it.
+    async fn read_response(&mut self) -> ClientResult<DriverResponseV2> {
+        loop {
+            let line = self.read_frame().await?;
+            if let Ok(event) = serde_json::from_str::<DriverEvent>(&line) {
+                self.events.push_back(event);
+                continue;
+            }
+            return serde_json::from_str(&line)
+                .map_err(|err| ClientError::protocol(format!("invalid response: {err}")));
+        }
+    }
+
+    /// Read the next non-empty line from the driver.
+    async fn read_frame(&self) -> ClientResult<String> {
+        loop {
+            let line = match self.inbox.next_line().await {
+                Some(line) => line.map_err(|err| ClientError::io("read driver response", err))?,
+                None => return Err(ClientError::Closed),
+            };
+            if !line.trim().is_empty() {
+                return Ok(line);
+            }
+        }
+    }
+
+    fn finish(&mut self) -> ClientResult<()> {
+        drop(self.writer.take());
+        let Some(mut child) = self.child.take() else {
+            return Ok(());
+        };
+        let status = child
+            .wait()
+            .map_err(|err| ClientError::io("wait for driver", err))?;
+        match status.code() {
+            Some(0) => Ok(()),
+            code => Err(ClientError::Driver {
+                code: error_code_for_exit(code),
+                message: format!("driver exited with {status}"),
+                context: None,
+            }),
+        }
+    }
+}
+
+impl Drop for DriverClient {
+    /// Closing input lets the driver tear the session down before it exits.
+    fn drop(&mut self) {
+        drop(self.writer.take());
+        if let Some(mut child) = self.child.take() {
+            let _ = child.wait();
+        }
+    }
+}
+
+fn into_observation(response: DriverResponseV2) -> ClientResult<Observation> {
+    response
+        .observation
+        .ok_or_else(|| ClientError::protocol("ok response without an observation"))
+}
+
+/// Parse the handshake line; the driver may instead print an `ErrorInfo`
+/// when it fails during startup.
+fn parse_handshake(line: &str) -> ClientResult<Handshake> {
+    let value: serde_json::Value = serde_json::from_str(line)
+        .map_err(|err| ClientError::protocol(format!("invalid handshake: {err}")))?;
+    if value.get("type").and_then(|kind| kind.as_str()) == Some("handshake") {
+        return serde_json::from_value(value)
+            .map_err(|err| ClientError::protocol(format!("invalid handshake: {err}")));
+    }
+    match serde_json::from_value::<ErrorInfo>(value) {
+        Ok(info) => Err(ClientError::from(info)),
+        Err(_) => Err(ClientError::protocol(
+            "first driver line was not a handshake",
+        )),
+    }
+}
+
+fn exited_before_handshake(child: &mut Child) -> ClientError {
+    match child.wait() {
+        Ok(status) => ClientError::Driver {
+            code: error_code_for_exit(status.code()),
+            message: format!("driver exited before handshake with {status}"),
+            context: None,
+        },
+        Err(err) => ClientError::io("wait for driver", err),
+    }
+}
+
+fn error_code_for_exit(code: Option<i32>) -> ErrorCode {
+    code.and_then(|code| {
+        ErrorCode::ALL
+            .into_iter()
+            .find(|candidate| candidate.exit_code() == code)
+    })
+    .unwrap_or(ErrorCode::Internal)
+}
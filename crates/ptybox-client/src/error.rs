@@ -0,0 +1,90 @@
+//! Client error type.
+
+use ptybox::model::ErrorInfo;
+use ptybox::runner::ErrorCode;
+use serde_json::Value;
+use std::io;
+
+/// Result alias for client operations.
+pub type ClientResult<T> = Result<T, ClientError>;
+
+/// Errors reported by [`DriverClient`](crate::DriverClient).
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    /// The driver answered a request with a structured error.
+    #[error("{code}: {message}")]
+    Driver {
+        /// Stable error code reported by the driver.
+        code: ErrorCode,
+        /// Human-readable error message.
+        message: String,
+        /// Structured context from the driver, if any.
+        context: Option<Value>,
+    },
+    /// The driver speaks a different protocol version than this client.
+    #[error("driver speaks protocol version {driver}, client supports {client}")]
+    UnsupportedProtocol {
+        /// Version announced in the driver handshake.
+        driver: u32,
+        /// Version this client implements.
+        client: u32,
+    },
+    /// The driver handshake did not list the requested action type.
+    #[error("driver does not support action `{action}`")]
+    UnsupportedAction {
+        /// Action type that was refused before sending.
+        action: String,
+    },
+    /// The driver sent a line the client could not interpret.
+    #[error("malformed driver output: {message}")]
+    Protocol {
+        /// What was wrong with the line.
+        message: String,
+    },
+    /// The driver closed its output before answering.
+    #[error("driver closed the connection")]
+    Closed,
+    /// Reading from, writing to, or spawning the driver failed.
+    #[error("failed to {operation}: {source}")]
+    Io {
+        /// Operation that failed.
+        operation: &'static str,
+        /// Underlying I/O error.
+        #[source]
+        source: io::Error,
+    },
+}
+
+impl ClientError {
+    /// Stable error code for this error, matching the driver's error codes.
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::Driver { code, .. } => *code,
+            Self::UnsupportedProtocol { .. } => ErrorCode::ProtocolVersionMismatch,
+            Self::UnsupportedAction { .. } | Self::Protocol { .. } => ErrorCode::Protocol,
+            Self::Closed | Self::Io { .. } => ErrorCode::Io,
+        }
+    }
+
+    pub(crate) fn protocol(message: impl Into<String>) -> Self {
+        Self::Protocol {
+            message: message.into(),
+        }
+    }
+
+    pub(crate) fn io(operation: &'static str, source: io::Error) -> Self {
+        Self::Io { operation, source }
+    }
+}
+
+impl From<ErrorInfo> for ClientError {
+    /// Unknown codes from newer drivers map to [`ErrorCode::Internal`].
+    fn from(info: ErrorInfo) -> Self {
+        Self::Driver {
+            code: ErrorCode::parse(&info.code).unwrap_or(ErrorCode::Internal),
+            message: info.message,
+            context: info.context,
+        }
+    }
+}
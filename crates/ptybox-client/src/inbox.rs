@@ -0,0 +1,130 @@
+//! Runtime-agnostic line delivery from the driver's output stream.
+//!
+//! A background thread reads driver lines into an [`Inbox`]; [`NextLine`]
+//! resolves once a line (or end of stream) is available and registers the
+//! task's waker otherwise.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io::{self, BufRead, BufReader, Read};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+/// Lines read from the driver, shared with the reader thread.
+#[derive(Default)]
+pub(crate) struct Inbox {
+    state: Mutex<InboxState>,
+}
+
+#[derive(Default)]
+struct InboxState {
+    lines: VecDeque<io::Result<String>>,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+impl Inbox {
+    /// Start a thread that forwards every line of `reader` into a new inbox.
+    pub(crate) fn spawn<R>(reader: R) -> io::Result<Arc<Self>>
+    where
+        R: Read + Send + 'static,
+    {
+        let inbox = Arc::new(Self::default());
+        let shared = Arc::clone(&inbox);
+        thread::Builder::new()
+            .name("ptybox-client-reader".to_string())
+            .spawn(move || {
+                for line in BufReader::new(reader).lines() {
+                    let failed = line.is_err();
+                    shared.push(line);
+                    if failed {
+                        break;
+                    }
+                }
+                shared.close();
+            })?;
+        Ok(inbox)
+    }
+
+    /// Wait for the next line; resolves to `None` once the stream has ended.
+    pub(crate) fn next_line(&self) -> NextLine<'_> {
+        NextLine { inbox: self }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, InboxState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn push(&self, line: io::Result<String>) {
+        let waker = {
+            let mut state = self.lock();
+            state.lines.push_back(line);
+            state.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    fn close(&self) {
+        let waker = {
+            let mut state = self.lock();
+            state.closed = true;
+            state.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// Future returned by [`Inbox::next_line`].
+pub(crate) struct NextLine<'a> {
+    inbox: &'a Inbox,
+}
+
+impl Future for NextLine<'_> {
+    type Output = Option<io::Result<String>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.inbox.lock();
+        if let Some(line) = state.lines.pop_front() {
+            return Poll::Ready(Some(line));
+        }
+        if state.closed {
+            return Poll::Ready(None);
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+/// Run `future` to completion on the current thread.
+///
+/// A minimal executor for callers without an async runtime; the thread parks
+/// between polls until the driver's reader thread wakes it.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut future = std::pin::pin!(future);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
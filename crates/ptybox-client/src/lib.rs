@@ -0,0 +1,46 @@
+//! Typed Rust client for the `ptybox driver` protocol v2.
+//!
+//! [`DriverClient`] spawns `ptybox driver --stdio --json` (or attaches to an
+//! existing driver's streams), checks the handshake, and exchanges
+//! [`DriverRequestV2`](ptybox::model::DriverRequestV2) /
+//! [`DriverResponseV2`](ptybox::model::DriverResponseV2) lines so callers
+//! never handcraft NDJSON.
+//!
+//! The methods are `async` but depend on no runtime: responses are read on a
+//! background thread that wakes the pending future, so the client works
+//! under any executor. [`block_on`] drives a future on the current thread
+//! for synchronous callers.
+//!
+//! # Example
+//! ```no_run
+//! use ptybox::model::{Action, Condition};
+//! use ptybox_client::{block_on, DriverClient, SpawnConfig};
+//!
+//! # fn example() -> Result<(), ptybox_client::ClientError> {
+//! block_on(async {
+//!     let mut client = DriverClient::spawn(SpawnConfig {
+//!         command: "/bin/cat".to_string(),
+//!         ..SpawnConfig::default()
+//!     })
+//!     .await?;
+//!     client.act(Action::text("hello")).await?;
+//!     let observation = client
+//!         .wait(Condition::screen_contains("hello"), Some(2_000))
+//!         .await?;
+//!     assert!(observation.screen.lines.iter().any(|line| line.contains("hello")));
+//!     client.terminate().await?;
+//!     Ok(())
+//! })
+//! # }
+//! ```
+//!
+//! Driver errors surface as [`ClientError::Driver`] carrying the stable
+//! [`ErrorCode`](ptybox::runner::ErrorCode) the driver reported.
+
+mod client;
+mod error;
+mod inbox;
+
+pub use client::{DriverClient, Handshake, HandshakeBudgets, RequestOptions, SpawnConfig};
+pub use error::{ClientError, ClientResult};
+pub use inbox::block_on;
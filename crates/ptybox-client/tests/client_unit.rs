@@ -0,0 +1,264 @@
+// Test module - relaxed lint rules
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(clippy::panic)]
+#![allow(clippy::indexing_slicing)]
+#![allow(missing_docs)]
+
+//! Client behaviour against scripted driver streams.
+
+use ptybox::model::{Action, DriverEventKind, PROTOCOL_VERSION};
+use ptybox::runner::ErrorCode;
+use ptybox_client::{block_on, ClientError, DriverClient};
+use serde_json::{json, Value};
+use std::io::{Cursor, Write};
+use std::sync::{Arc, Mutex};
+
+/// Writer that keeps everything the client sends for inspection.
+#[derive(Clone, Default)]
+struct Sent(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Sent {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Sent {
+    fn requests(&self) -> Vec<Value> {
+        let bytes = self.0.lock().unwrap().clone();
+        String::from_utf8(bytes)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+}
+
+fn handshake(version: u32, actions: &[&str]) -> Value {
+    json!({
+        "type": "handshake",
+        "protocol_version": version,
+        "run_id": "00000000-0000-0000-0000-000000000000",
+        "terminal_size": {"rows": 24, "cols": 80},
+        "budgets": {"max_steps": 10, "max_runtime_ms": 1000, "max_output_bytes": 1024,
+                    "max_snapshot_bytes": 1024, "max_wait_ms": 500},
+        "supported_actions": actions,
+        "supported_conditions": ["screen_contains"],
+    })
+}
+
+fn script(lines: &[Value]) -> Cursor<Vec<u8>> {
+    let mut out = String::new();
+    for line in lines {
+        out.push_str(&line.to_string());
+        out.push('\n');
+    }
+    Cursor::new(out.into_bytes())
+}
+
+#[test]
+fn attach_reads_handshake() {
+    let reader = script(&[handshake(PROTOCOL_VERSION, &["key", "text"])]);
+    let client = block_on(DriverClient::attach(reader, Sent::default())).unwrap();
+    assert_eq!(client.handshake().terminal_size.cols, 80);
+    assert_eq!(client.handshake().budgets.max_steps, 10);
+    assert!(client.handshake().supports_action("text"));
+    assert!(!client.handshake().supports_action("hold_key"));
+}
+
+#[test]
+fn attach_rejects_protocol_version_mismatch() {
+    let reader = script(&[handshake(PROTOCOL_VERSION + 1, &["key"])]);
+    let err = block_on(DriverClient::attach(reader, Sent::default()))
+        .err()
+        .unwrap();
+    assert!(matches!(
+        err,
+        ClientError::UnsupportedProtocol { driver, client }
+            if driver == PROTOCOL_VERSION + 1 && client == PROTOCOL_VERSION
+    ));
+    assert_eq!(err.code(), ErrorCode::ProtocolVersionMismatch);
+}
+
+#[test]
+fn attach_surfaces_startup_error_info() {
+    let reader = script(&[json!({
+        "code": "E_POLICY_DENIED",
+        "message": "executable not allowlisted",
+        "context": {"command": "/bin/sh"},
+    })]);
+    let err = block_on(DriverClient::attach(reader, Sent::default()))
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), ErrorCode::PolicyDenied);
+    assert!(err.to_string().contains("executable not allowlisted"));
+}
+
+#[test]
+fn attach_reports_closed_stream() {
+    let err = block_on(DriverClient::attach(script(&[]), Sent::default()))
+        .err()
+        .unwrap();
+    assert!(matches!(err, ClientError::Closed));
+}
+
+#[test]
+fn act_sends_versioned_request_and_maps_error_code() {
+    let reader = script(&[
+        handshake(PROTOCOL_VERSION, &["key", "text"]),
+        json!({
+            "protocol_version": PROTOCOL_VERSION,
+            "request_id": "req-1",
+            "status": "error",
+            "error": {"code": "E_TIMEOUT", "message": "wait timed out", "context": null},
+        }),
+    ]);
+    let sent = Sent::default();
+    let mut client = block_on(DriverClient::attach(reader, sent.clone())).unwrap();
+
+    let err = block_on(client.act(Action::text("hi"))).unwrap_err();
+    match &err {
+        ClientError::Driver { code, message, .. } => {
+            assert_eq!(*code, ErrorCode::Timeout);
+            assert_eq!(message, "wait timed out");
+        }
+        other => panic!("expected driver error, got {other:?}"),
+    }
+
+    let requests = sent.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0]["protocol_version"], PROTOCOL_VERSION);
+    assert_eq!(requests[0]["request_id"], "req-1");
+    assert_eq!(requests[0]["action"]["type"], "text");
+}
+
+#[test]
+fn act_refuses_actions_missing_from_handshake() {
+    let reader = script(&[handshake(PROTOCOL_VERSION, &["key"])]);
+    let sent = Sent::default();
+    let mut client = block_on(DriverClient::attach(reader, sent.clone())).unwrap();
+
+    let err = block_on(client.act(Action::hold_key("Down", 100, 30))).unwrap_err();
+    assert!(matches!(&err, ClientError::UnsupportedAction { action } if action == "hold_key"));
+    assert!(sent.requests().is_empty());
+}
+
+#[test]
+fn act_rejects_response_for_another_request() {
+    let reader = script(&[
+        handshake(PROTOCOL_VERSION, &["key"]),
+        json!({
+            "protocol_version": PROTOCOL_VERSION,
+            "request_id": "req-9",
+            "status": "error",
+            "error": {"code": "E_PROTOCOL", "message": "x", "context": null},
+        }),
+    ]);
+    let mut client = block_on(DriverClient::attach(reader, Sent::default())).unwrap();
+    let err = block_on(client.act(Action::key("Enter"))).unwrap_err();
+    assert!(matches!(err, ClientError::Protocol { .. }));
+}
+
+#[test]
+fn act_reports_closed_when_driver_stops_answering() {
+    let reader = script(&[handshake(PROTOCOL_VERSION, &["key"])]);
+    let mut client = block_on(DriverClient::attach(reader, Sent::default())).unwrap();
+    let err = block_on(client.act(Action::key("Enter"))).unwrap_err();
+    assert!(matches!(err, ClientError::Closed));
+    assert_eq!(err.code(), ErrorCode::Io);
+}
+
+#[test]
+fn history_sends_limit_and_returns_entries() {
+    let reader = script(&[
+        handshake(PROTOCOL_VERSION, &["text", "history"]),
+        json!({
+            "protocol_version": PROTOCOL_VERSION,
+            "request_id": "req-1",
+            "status": "ok",
+            "screen_hash": "0123456789abcdef",
+            "history": {
+                "entries": [{
+                    "request_id": "req-0",
+                    "action": {"type": "text", "payload": {"text": "hi"}},
+                    "status": "ok",
+                    "sequence": 1,
+                    "started_at_ms": 3,
+                    "ended_at_ms": 9,
+                    "screen_hash": "0123456789abcdef",
+                }],
+                "total": 4,
+            },
+        }),
+    ]);
+    let sent = Sent::default();
+    let mut client = block_on(DriverClient::attach(reader, sent.clone())).unwrap();
+
+    let history = block_on(client.history(Some(1))).unwrap();
+    assert_eq!(history.total, 4);
+    assert_eq!(history.entries[0].request_id, "req-0");
+    assert_eq!(history.entries[0].sequence, Some(1));
+
+    let requests = sent.requests();
+    assert_eq!(requests[0]["action"]["type"], "history");
+    assert_eq!(requests[0]["action"]["payload"]["limit"], 1);
+}
+
+#[test]
+fn subscribe_sets_pushed_events_aside_and_acks() {
+    let reader = script(&[
+        handshake(PROTOCOL_VERSION, &["text", "subscribe"]),
+        json!({
+            "type": "event",
+            "protocol_version": PROTOCOL_VERSION,
+            "event_seq": 1,
+            "kind": "bell",
+            "timestamp_ms": 12,
+            "bells": 1,
+        }),
+        json!({
+            "protocol_version": PROTOCOL_VERSION,
+            "request_id": "req-1",
+            "status": "ok",
+            "subscription": {
+                "events": ["bell"],
+                "max_unacked": 8,
+                "poll_interval_ms": 50,
+                "last_event_seq": 0,
+            },
+        }),
+        json!({
+            "type": "event",
+            "protocol_version": PROTOCOL_VERSION,
+            "event_seq": 2,
+            "kind": "process_exited",
+            "timestamp_ms": 40,
+            "exit_status": {"success": true, "exit_code": 0, "terminated_by_harness": false},
+        }),
+    ]);
+    let sent = Sent::default();
+    let mut client = block_on(DriverClient::attach(reader, sent.clone())).unwrap();
+
+    let subscription = block_on(client.subscribe(&[DriverEventKind::Bell], Some(8))).unwrap();
+    assert_eq!(subscription.max_unacked, 8);
+    let first = block_on(client.next_event()).unwrap();
+    assert_eq!((first.event_seq, first.kind), (1, DriverEventKind::Bell));
+    let second = block_on(client.next_event()).unwrap();
+    assert_eq!(second.kind, DriverEventKind::ProcessExited);
+    client.ack(second.event_seq).unwrap();
+
+    let requests = sent.requests();
+    assert_eq!(requests[0]["action"]["type"], "subscribe");
+    assert_eq!(requests[0]["action"]["payload"]["events"], json!(["bell"]));
+    assert_eq!(requests[0]["action"]["payload"]["max_unacked"], 8);
+    assert_eq!(requests[1], json!({"type": "ack", "event_seq": 2}));
+}
@@ -24,13 +24,14 @@
 use std::path::Path;
 
 use ptybox::model::policy::{
-    ArtifactsPolicy, Budgets, EnvPolicy, ExecPolicy, FsPolicy, NetworkEnforcementAck,
-    NetworkPolicy, Policy, ReplayPolicy, SandboxMode, POLICY_VERSION,
+    ArtifactsPolicy, Budgets, DeterminismPolicy, EnvPolicy, ExecPolicy, FsPolicy, InputPolicy,
+    LogForwardPolicy, NetworkEnforcementAck, NetworkPolicy, Policy, ProcessPolicy, RemotePolicy,
+    ReplayPolicy, SandboxFallback, SandboxMode, TerminalPolicy, POLICY_VERSION,
 };
 use ptybox::model::scenario::PolicyRef;
 use ptybox::model::{
-    Action, ActionType, Assertion, RunConfig, Scenario, ScenarioMetadata, Step, StepId,
-    TerminalSize,
+    Action, ActionType, Assertion, OutputBufferConfig, ResizeConfig, RunConfig, Scenario,
+    ScenarioDefaults, ScenarioMetadata, Step, StepId, TerminalSize,
 };
 
 // ============================================================================
@@ -139,6 +140,7 @@ impl PolicyBuilder {
         Policy {
             policy_version: POLICY_VERSION,
             sandbox: SandboxMode::Disabled { ack: true },
+            sandbox_fallback: SandboxFallback::Deny,
             network: NetworkPolicy::Disabled,
             network_enforcement: NetworkEnforcementAck {
                 unenforced_ack: true,
@@ -149,6 +151,7 @@ impl PolicyBuilder {
                 working_dir: Some(self.work_dir),
                 write_ack: has_write,
                 strict_write: false,
+                write_quotas: std::collections::BTreeMap::new(),
             },
             exec: ExecPolicy {
                 allowed_executables: self.allowed_exec,
@@ -165,9 +168,22 @@ impl PolicyBuilder {
                 max_output_bytes: self.max_output_bytes,
                 max_snapshot_bytes: self.max_snapshot_bytes,
                 max_wait_ms: self.max_wait_ms,
+                max_session_idle_ms: None,
+                max_session_lifetime_ms: None,
+                max_poll_interval_ms: None,
+                max_step_samples: None,
             },
             artifacts: ArtifactsPolicy::default(),
             replay: ReplayPolicy::default(),
+            input: InputPolicy::default(),
+            determinism: DeterminismPolicy::default(),
+            process: ProcessPolicy::default(),
+            sockets: Vec::new(),
+            remote: RemotePolicy::default(),
+            terminal: TerminalPolicy::default(),
+            log_forward: LogForwardPolicy::default(),
+            substitution_allowlist: Vec::new(),
+            grants: Vec::new(),
         }
     }
 }
@@ -298,6 +314,7 @@ impl StepBuilder {
         self.assertions.push(Assertion {
             assertion_type: "screen_contains".to_string(),
             payload: serde_json::json!({ "text": text }),
+            inherited: false,
         });
         self
     }
@@ -308,6 +325,7 @@ impl StepBuilder {
         self.assertions.push(Assertion {
             assertion_type: "not_contains".to_string(),
             payload: serde_json::json!({ "text": text }),
+            inherited: false,
         });
         self
     }
@@ -339,6 +357,13 @@ impl StepBuilder {
             assert: self.assertions,
             timeout_ms: self.timeout_ms,
             retries: self.retries,
+            skip_default_assertions: false,
+            sample_interval_ms: None,
+            expected_screen: None,
+            capture: None,
+            env_overlay: std::collections::BTreeMap::new(),
+            metadata: std::collections::BTreeMap::new(),
+            vars: Vec::new(),
         }
     }
 }
@@ -415,7 +440,7 @@ impl ScenarioBuilder {
     /// Set the initial terminal size.
     #[must_use]
     pub fn with_size(mut self, rows: u16, cols: u16) -> Self {
-        self.initial_size = TerminalSize { rows, cols };
+        self.initial_size = TerminalSize::new(rows, cols);
         self
     }
 
@@ -484,6 +509,10 @@ impl ScenarioBuilder {
             metadata: ScenarioMetadata {
                 name: self.name,
                 description: self.description,
+                tags: Vec::new(),
+                author: None,
+                source: None,
+                revision: None,
             },
             run: RunConfig {
                 command: self.command,
@@ -491,8 +520,19 @@ impl ScenarioBuilder {
                 cwd: self.cwd,
                 initial_size: self.initial_size,
                 policy: PolicyRef::Inline(Box::new(policy)),
+                separate_stderr: false,
+                output_buffer: OutputBufferConfig::default(),
+                resize: ResizeConfig::default(),
+                remote: None,
+                validate_args: false,
+                capture_attributes: false,
             },
             steps: self.steps,
+            defaults: ScenarioDefaults::default(),
+            watchers: Vec::new(),
+            post_run: Vec::new(),
+            post: Vec::new(),
+            fixtures: Vec::new(),
         }
     }
 }
@@ -0,0 +1,84 @@
+// Benchmark - relaxed lint rules
+#![allow(clippy::print_stdout)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+
+//! Per-step overhead of [`ArtifactsWriter`] with background and inline I/O.
+//!
+//! Each simulated step writes transcript output, an observation line, and a
+//! snapshot between short waits standing in for reading the terminal, then
+//! ends the step. Overhead is the step's wall time minus those waits.
+//!
+//! Run with `cargo bench -p ptybox --bench artifacts_writer`.
+
+use ptybox::artifacts::{ArtifactsWriter, ArtifactsWriterConfig};
+use ptybox::model::{Cursor, RunId, ScreenSnapshot, SnapshotId, SNAPSHOT_VERSION};
+use std::time::{Duration, Instant};
+
+const STEPS: u32 = 200;
+const WRITES_PER_STEP: u32 = 10;
+const WAIT: Duration = Duration::from_micros(200);
+
+fn main() {
+    let snapshot = ScreenSnapshot {
+        snapshot_version: SNAPSHOT_VERSION,
+        snapshot_id: SnapshotId::new(),
+        rows: 24,
+        cols: 80,
+        cursor: Cursor {
+            row: 0,
+            col: 0,
+            visible: true,
+        },
+        alternate_screen: false,
+        lines: vec!["x".repeat(80); 24],
+        cells: None,
+    };
+    println!("{STEPS} steps, {WRITES_PER_STEP} writes per step");
+    for background in [false, true] {
+        let overhead = run(&snapshot, background);
+        println!(
+            "{:<10} per-step overhead {:?}",
+            if background { "background" } else { "inline" },
+            overhead / STEPS
+        );
+    }
+}
+
+/// Total time spent outside the simulated waits.
+fn run(snapshot: &ScreenSnapshot, background: bool) -> Duration {
+    let dir = std::env::temp_dir().join(format!(
+        "ptybox-bench-artifacts-{}-{background}",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    let config = ArtifactsWriterConfig {
+        dir: dir.clone(),
+        overwrite: false,
+    };
+    let mut writer = ArtifactsWriter::new(RunId::new(), config).unwrap();
+    writer.set_background_io(background).unwrap();
+    let output = "y".repeat(1024);
+    let mut overhead = Duration::ZERO;
+    for step in 1..=STEPS {
+        let started = Instant::now();
+        writer.begin_step(step as usize, "step").unwrap();
+        for n in 0..WRITES_PER_STEP {
+            std::thread::sleep(WAIT);
+            writer.write_transcript(&output).unwrap();
+            writer
+                .write_json_line(
+                    "events.bench.jsonl",
+                    &serde_json::json!({ "step": step, "n": n }),
+                )
+                .unwrap();
+            writer.write_snapshot(snapshot).unwrap();
+        }
+        writer.end_step().unwrap();
+        overhead += started.elapsed().saturating_sub(WAIT * WRITES_PER_STEP);
+    }
+    writer.flush_checksums().unwrap();
+    drop(writer);
+    let _ = std::fs::remove_dir_all(&dir);
+    overhead
+}
@@ -1,40 +1,30 @@
-//! Shared action dispatch and wait-condition logic.
+//! Shared action dispatch.
 //!
-//! These helpers are used by both the interactive [`driver`](crate::driver) and
-//! the stateless [`serve`](crate::serve) modules so the action execution
-//! semantics stay consistent across entry points.
+//! These helpers are used by the [`runner`](crate::runner), the interactive
+//! [`driver`](crate::driver), and the stateless [`serve`](crate::serve) modules
+//! so the action execution semantics stay consistent across entry points.
+//! Wait conditions are evaluated by [`Session::wait_until`].
 
 use crate::model::policy::Policy;
-use crate::model::{Action, ActionType, Observation};
-use crate::runner::{compile_safe_regex, RunnerError, RunnerResult};
+use crate::model::{Action, ActionType, Condition, Observation};
+use crate::policy::{validate_raw_input, validate_signal};
+use crate::runner::{RunnerError, RunnerResult};
 use crate::session::Session;
-use crate::util::pause_until;
 use serde::Deserialize;
-use serde_json::Value;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
 /// Deserialized wait-action payload (extracted from `Action::payload`).
 #[derive(Debug, Deserialize)]
-pub(crate) struct WaitPayload {
+struct WaitPayload {
     /// The wait condition to evaluate.
-    pub condition: Condition,
-}
-
-/// A single wait condition with a type tag and type-specific payload.
-#[derive(Debug, Deserialize)]
-pub(crate) struct Condition {
-    /// Condition type name (e.g. `"screen_contains"`, `"screen_matches"`).
-    #[serde(rename = "type")]
-    pub condition_type: String,
-    /// Type-specific fields.
-    #[serde(default)]
-    pub payload: Value,
+    condition: Condition,
 }
 
 /// Dispatch a single action against `session` and return the resulting observation.
 ///
 /// Wait actions are routed to [`wait_for_condition`]; terminate actions send
-/// SIGTERM then observe; all others send the action and observe.
+/// `policy.process.kill_signal` then observe; raw actions are checked against the input policy
+/// and signal actions against `process.allowed_signals`; all others send the action and observe.
 pub(crate) fn perform_action(
     session: &mut Session,
     action: &Action,
@@ -44,8 +34,18 @@ pub(crate) fn perform_action(
     match action.action_type {
         ActionType::Wait => wait_for_condition(session, action, timeout, policy),
         ActionType::Observe => session.observe(timeout),
+        ActionType::Raw => {
+            validate_raw_input(&policy.input, action)?;
+            session.send(action)?;
+            session.observe(timeout)
+        }
+        ActionType::Signal => {
+            validate_signal(&policy.process, action)?;
+            session.send(action)?;
+            session.observe(timeout)
+        }
         ActionType::Terminate => {
-            session.terminate()?;
+            session.signal(policy.process.kill_signal)?;
             session.observe(Duration::from_millis(10))
         }
         _ => {
@@ -55,8 +55,26 @@ pub(crate) fn perform_action(
     }
 }
 
-/// Poll until `condition` inside a wait action is satisfied or `timeout` elapses.
-#[allow(clippy::too_many_lines)]
+/// Response latency in milliseconds for an action that took `duration_ms`.
+///
+/// Wait actions return as soon as their condition matches, so their latency
+/// is the action duration; input actions measure until the first output
+/// after the input. Observe, terminate, and signal actions have no latency.
+pub(crate) fn action_latency_ms(
+    session: &Session,
+    action: &Action,
+    duration_ms: u64,
+) -> RunnerResult<Option<u64>> {
+    match action.action_type {
+        ActionType::Wait => Ok(Some(duration_ms)),
+        ActionType::Observe | ActionType::Terminate | ActionType::Signal => Ok(None),
+        _ => Ok(session
+            .response_latency()?
+            .map(|latency| u64::try_from(latency.as_millis()).unwrap_or(u64::MAX))),
+    }
+}
+
+/// Parse the condition carried by a wait action and hand it to [`Session::wait_until`].
 pub(crate) fn wait_for_condition(
     session: &mut Session,
     action: &Action,
@@ -68,161 +86,25 @@ pub(crate) fn wait_for_condition(
             RunnerError::protocol(
                 "E_PROTOCOL",
                 "invalid wait action payload",
-                Some(serde_json::json!({
+                serde_json::json!({
                     "parse_error": err.to_string(),
                     "received_payload": action.payload,
-                })),
+                    "expected": {
+                        "condition": {
+                            "type": crate::predicate::PREDICATES
+                                .map(|spec| spec.name)
+                                .join(" | "),
+                            "payload": "object (varies by condition type)"
+                        }
+                    },
+                    "examples": {
+                        "screen_contains": {"condition": {"type": "screen_contains", "payload": {"text": "Ready"}}},
+                        "screen_matches": {"condition": {"type": "screen_matches", "payload": {"pattern": "\\$\\s*$"}}},
+                        "cursor_at": {"condition": {"type": "cursor_at", "payload": {"row": 0, "col": 0}}},
+                        "process_exited": {"condition": {"type": "process_exited", "payload": {}}}
+                    }
+                }),
             )
         })?;
-    let max_wait = Duration::from_millis(policy.budgets.max_wait_ms);
-    let wait_timeout = timeout.min(max_wait);
-    let deadline = Instant::now() + wait_timeout;
-
-    // Validate required fields before entering the polling loop
-    if wait_payload.condition.condition_type == "screen_contains"
-        && wait_payload
-            .condition
-            .payload
-            .get("text")
-            .and_then(Value::as_str)
-            .is_none()
-    {
-        return Err(RunnerError::protocol(
-            "E_PROTOCOL",
-            "screen_contains condition requires 'text' field",
-            Some(serde_json::json!({ "received_payload": wait_payload.condition.payload })),
-        ));
-    }
-
-    let compiled_regex = if wait_payload.condition.condition_type == "screen_matches" {
-        let pattern = wait_payload
-            .condition
-            .payload
-            .get("pattern")
-            .and_then(Value::as_str)
-            .ok_or_else(|| {
-                RunnerError::protocol(
-                    "E_PROTOCOL",
-                    "screen_matches condition requires 'pattern' field",
-                    Some(serde_json::json!({ "received_payload": wait_payload.condition.payload })),
-                )
-            })?;
-        Some(compile_safe_regex(pattern)?)
-    } else {
-        None
-    };
-
-    loop {
-        if Instant::now() > deadline {
-            return Err(RunnerError::timeout(
-                "E_TIMEOUT",
-                "wait condition timed out",
-                Some(serde_json::json!({
-                    "condition": wait_payload.condition.condition_type
-                })),
-            ));
-        }
-
-        let observation = session.observe(Duration::from_millis(50))?;
-        if session.wait_for_exit(Duration::from_millis(0))?.is_some() {
-            if wait_payload.condition.condition_type == "process_exited" {
-                return Ok(observation);
-            }
-            return Err(RunnerError::process_exit(
-                "E_PROCESS_EXIT",
-                "process exited during wait",
-            ));
-        }
-
-        if condition_satisfied(
-            &observation,
-            &wait_payload.condition,
-            compiled_regex.as_ref(),
-        )? {
-            return Ok(observation);
-        }
-        pause_until(deadline, Duration::from_millis(10));
-    }
-}
-
-/// Test whether `observation` satisfies `condition`.
-pub(crate) fn condition_satisfied(
-    observation: &Observation,
-    condition: &Condition,
-    compiled_regex: Option<&regex::Regex>,
-) -> RunnerResult<bool> {
-    match condition.condition_type.as_str() {
-        "screen_contains" => {
-            // text field is validated before the polling loop
-            let text = condition
-                .payload
-                .get("text")
-                .and_then(Value::as_str)
-                .unwrap_or("");
-            Ok(observation.screen.lines.join("\n").contains(text))
-        }
-        "screen_matches" => {
-            // pattern field is validated and compiled before the polling loop
-            let screen_text = observation.screen.lines.join("\n");
-            if let Some(re) = compiled_regex {
-                Ok(re.is_match(&screen_text))
-            } else {
-                let pattern = condition
-                    .payload
-                    .get("pattern")
-                    .and_then(Value::as_str)
-                    .unwrap_or("");
-                let re = compile_safe_regex(pattern)?;
-                Ok(re.is_match(&screen_text))
-            }
-        }
-        "cursor_at" => {
-            let row_u64 = condition
-                .payload
-                .get("row")
-                .and_then(Value::as_u64)
-                .ok_or_else(|| {
-                    RunnerError::protocol(
-                        "E_PROTOCOL",
-                        "cursor_at condition requires 'row' field",
-                        Some(serde_json::json!({ "received_payload": condition.payload })),
-                    )
-                })?;
-            let col_u64 = condition
-                .payload
-                .get("col")
-                .and_then(Value::as_u64)
-                .ok_or_else(|| {
-                    RunnerError::protocol(
-                        "E_PROTOCOL",
-                        "cursor_at condition requires 'col' field",
-                        Some(serde_json::json!({ "received_payload": condition.payload })),
-                    )
-                })?;
-            let row = u16::try_from(row_u64).map_err(|_| {
-                RunnerError::protocol(
-                    "E_PROTOCOL",
-                    format!("row value {row_u64} exceeds maximum u16 value {}", u16::MAX),
-                    Some(serde_json::json!({ "received": row_u64, "max": u16::MAX })),
-                )
-            })?;
-            let col = u16::try_from(col_u64).map_err(|_| {
-                RunnerError::protocol(
-                    "E_PROTOCOL",
-                    format!("col value {col_u64} exceeds maximum u16 value {}", u16::MAX),
-                    Some(serde_json::json!({ "received": col_u64, "max": u16::MAX })),
-                )
-            })?;
-            Ok(observation.screen.cursor.row == row && observation.screen.cursor.col == col)
-        }
-        "process_exited" => Ok(false),
-        other => Err(RunnerError::protocol(
-            "E_PROTOCOL",
-            format!("unsupported wait condition '{other}'"),
-            Some(serde_json::json!({
-                "received": other,
-                "supported_conditions": ["screen_contains", "screen_matches", "cursor_at", "process_exited"]
-            })),
-        )),
-    }
+    session.wait_until(&wait_payload.condition, timeout, &policy.budgets)
 }
@@ -0,0 +1,119 @@
+//! asciinema v2 recordings (`.cast`) of a run's terminal output.
+//!
+//! A cast is a JSON header line followed by one JSON array per event:
+//! `[seconds, "o", output]` for each observation's `transcript_delta`, at the
+//! observation's `timestamp_ms`, and `[seconds, "r", "COLSxROWS"]` when the
+//! screen size changes. Timing is as fine as the run's observations, as with
+//! `ptybox play`.
+//!
+//! [`ArtifactsWriter::set_asciicast`](super::ArtifactsWriter::set_asciicast)
+//! writes `session.cast` while the run is observed (policy
+//! `artifacts.asciicast`); [`asciicast_from_artifacts`] converts the event
+//! logs of a finished run (`ptybox trace --format asciicast`).
+
+use super::{event_log_paths, read_artifact, ArtifactsDecryptor};
+use crate::model::Observation;
+use crate::runner::{RunnerError, RunnerResult};
+use std::path::Path;
+
+/// Cast file name, relative to the artifacts directory.
+pub const ASCIICAST_FILE: &str = "session.cast";
+
+/// `TERM` recorded in the cast header, as players use it to pick a palette.
+const ASCIICAST_TERM: &str = "xterm-256color";
+
+/// Encodes observations as cast lines, in recording order.
+#[derive(Debug, Default)]
+pub struct AsciicastEncoder {
+    /// Screen size of the previous observation; `None` before the header.
+    size: Option<(u16, u16)>,
+}
+
+impl AsciicastEncoder {
+    /// Cast lines for `observation`, each ending in a newline: the header
+    /// before the first observation, a resize event when the screen size
+    /// changed, and its output, if any.
+    #[must_use]
+    pub fn encode(&mut self, observation: &Observation) -> String {
+        let screen = &observation.screen;
+        let size = (screen.cols, screen.rows);
+        // Run times are far below 2^52 ms
+        #[allow(clippy::cast_precision_loss)]
+        let seconds = observation.timestamp_ms as f64 / 1000.0;
+        let mut lines = Vec::new();
+        match self.size {
+            None => lines.push(serde_json::json!({
+                "version": 2,
+                "width": size.0,
+                "height": size.1,
+                "env": { "TERM": ASCIICAST_TERM }
+            })),
+            Some(previous) if previous != size => {
+                lines.push(serde_json::json!([
+                    seconds,
+                    "r",
+                    format!("{}x{}", size.0, size.1)
+                ]));
+            }
+            Some(_) => {}
+        }
+        self.size = Some(size);
+        if let Some(output) = observation
+            .transcript_delta
+            .as_deref()
+            .filter(|delta| !delta.is_empty())
+        {
+            lines.push(serde_json::json!([seconds, "o", output]));
+        }
+        lines.iter().fold(String::new(), |mut cast, line| {
+            cast.push_str(&line.to_string());
+            cast.push('\n');
+            cast
+        })
+    }
+}
+
+/// Convert the event logs in `dir` (`events.jsonl`, plus the per-step logs
+/// of the `per_step` layout) to a cast.
+///
+/// Encrypted logs are decrypted with `decryptor`.
+///
+/// # Errors
+/// - `E_IO`: a log cannot be read or decrypted
+/// - `E_PROTOCOL`: a log line is not an observation, or `dir` has no
+///   observations
+pub fn asciicast_from_artifacts(
+    dir: &Path,
+    decryptor: Option<&ArtifactsDecryptor>,
+) -> RunnerResult<String> {
+    let mut observations = Vec::new();
+    for path in event_log_paths(dir)? {
+        let data = read_artifact(&path, decryptor)?;
+        for line in String::from_utf8_lossy(&data).lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let observation: Observation = serde_json::from_str(line).map_err(|err| {
+                RunnerError::protocol(
+                    "E_PROTOCOL",
+                    "event log line is not an observation",
+                    serde_json::json!({ "path": path, "error": err.to_string() }),
+                )
+            })?;
+            observations.push(observation);
+        }
+    }
+    if observations.is_empty() {
+        return Err(RunnerError::protocol(
+            "E_PROTOCOL",
+            "no observations to convert; a cast needs events.jsonl",
+            serde_json::json!({ "dir": dir }),
+        ));
+    }
+    observations.sort_by_key(|observation| observation.timestamp_ms);
+    let mut encoder = AsciicastEncoder::default();
+    Ok(observations
+        .iter()
+        .map(|observation| encoder.encode(observation))
+        .collect())
+}
@@ -0,0 +1,79 @@
+//! In-toto style attestation of a run's artifacts directory.
+//!
+//! Every regular file under the directory becomes a subject with its SHA-256
+//! digest; the predicate is taken from `run.json`. Replay output
+//! (`replay-*` directories) and symlinks are not attested.
+
+use crate::model::{
+    AttestationStatement, AttestationSubject, RunAttestation, RunResult, IN_TOTO_STATEMENT_TYPE,
+    RUN_ATTESTATION_PREDICATE_TYPE,
+};
+use crate::runner::{RunnerError, RunnerResult};
+use crate::util::sha256_hex;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Build an attestation statement for the run in `artifacts_dir`.
+///
+/// # Errors
+/// - `E_IO`: `run.json` or an artifact file cannot be read
+/// - `E_PROTOCOL`: `run.json` is not a valid run result
+pub fn attest_artifacts(artifacts_dir: &Path) -> RunnerResult<AttestationStatement> {
+    let run_json = fs::read(artifacts_dir.join("run.json"))
+        .map_err(|err| RunnerError::io("E_IO", "failed to read run.json", err))?;
+    let run_result: RunResult = serde_json::from_slice(&run_json)
+        .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to parse run.json", err))?;
+
+    let mut subject = Vec::new();
+    collect_subjects(artifacts_dir, "", &mut subject)?;
+    subject.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(AttestationStatement {
+        statement_type: IN_TOTO_STATEMENT_TYPE.to_string(),
+        subject,
+        predicate_type: RUN_ATTESTATION_PREDICATE_TYPE.to_string(),
+        predicate: RunAttestation {
+            run_id: run_result.run_id,
+            status: run_result.status,
+            command: run_result.command,
+            args: run_result.args,
+            provenance: run_result.provenance,
+            scenario: run_result.scenario.map(|scenario| scenario.metadata),
+        },
+    })
+}
+
+fn collect_subjects(
+    dir: &Path,
+    prefix: &str,
+    subjects: &mut Vec<AttestationSubject>,
+) -> RunnerResult<()> {
+    let entries = fs::read_dir(dir)
+        .map_err(|err| RunnerError::io("E_IO", "failed to read artifacts dir", err))?;
+    for entry in entries {
+        let entry =
+            entry.map_err(|err| RunnerError::io("E_IO", "failed to read artifacts dir", err))?;
+        let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        let file_type = entry
+            .file_type()
+            .map_err(|err| RunnerError::io("E_IO", "failed to stat artifact", err))?;
+        let rel = format!("{prefix}{name}");
+        if file_type.is_dir() {
+            if prefix.is_empty() && name.starts_with("replay-") {
+                continue;
+            }
+            collect_subjects(&entry.path(), &format!("{rel}/"), subjects)?;
+        } else if file_type.is_file() {
+            let data = fs::read(entry.path())
+                .map_err(|err| RunnerError::io("E_IO", "failed to read artifact", err))?;
+            subjects.push(AttestationSubject {
+                name: rel,
+                digest: BTreeMap::from([("sha256".to_string(), sha256_hex(&data))]),
+            });
+        }
+    }
+    Ok(())
+}
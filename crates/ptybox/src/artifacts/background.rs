@@ -0,0 +1,262 @@
+//! Background persistence for [`ArtifactsWriter`](super::ArtifactsWriter).
+//!
+//! The writer serializes artifacts and updates checksums on the caller's
+//! thread, then hands the bytes to an [`IoQueue`] as [`IoJob`]s. By default
+//! a dedicated thread performs the file I/O in submission order, fed over a
+//! channel bounded at [`IO_QUEUE_JOBS`] jobs; a full queue blocks the caller.
+//! The thread owns the open streaming sinks.
+//!
+//! [`IoQueue::sync`] waits until every queued job is done, flushes the
+//! sinks, and returns the first error since the previous sync. The writer
+//! syncs at step boundaries and when the run is flushed, so a failed write
+//! surfaces there rather than from the call that queued it.
+
+use super::{atomic_write, StreamSink};
+use crate::runner::{RunnerError, RunnerResult};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::thread::JoinHandle;
+
+/// Jobs the background queue holds before [`IoQueue::submit`] blocks.
+pub(super) const IO_QUEUE_JOBS: usize = 256;
+
+/// Streaming artifact a sink is open for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) enum Stream {
+    Transcript,
+    Stderr,
+    Events,
+    /// Event log of the current step (`per_step` layout).
+    StepEvents,
+}
+
+impl Stream {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Transcript => "transcript",
+            Self::Stderr => "stderr log",
+            Self::Events => "events log",
+            Self::StepEvents => "step events log",
+        }
+    }
+}
+
+/// One unit of artifact I/O.
+pub(super) enum IoJob {
+    /// Start writing `Stream` to the sink, closing any sink it replaces.
+    Open(Stream, StreamSink),
+    /// Append bytes to an open stream.
+    Append(Stream, Vec<u8>),
+    /// Flush and close a stream, completing it if encrypted.
+    Finish(Stream),
+    /// Write a whole file atomically, creating parent directories.
+    WriteFile(PathBuf, Vec<u8>),
+    /// Append to a file, creating it and its parent directories.
+    AppendFile(PathBuf, Vec<u8>),
+}
+
+enum Message {
+    Job(IoJob),
+    Sync(mpsc::Sender<Option<RunnerError>>),
+}
+
+/// Executes [`IoJob`]s; owns the open sinks.
+#[derive(Default)]
+pub(super) struct IoWorker {
+    sinks: BTreeMap<Stream, StreamSink>,
+}
+
+impl IoWorker {
+    fn run(&mut self, job: IoJob) -> RunnerResult<()> {
+        match job {
+            IoJob::Open(stream, sink) => {
+                if let Some(mut previous) = self.sinks.insert(stream, sink) {
+                    flush_sink(stream, &mut previous)?;
+                }
+                Ok(())
+            }
+            IoJob::Append(stream, data) => {
+                let Some(sink) = self.sinks.get_mut(&stream) else {
+                    return Err(RunnerError::io(
+                        "E_IO",
+                        format!("failed to write {}", stream.label()),
+                        "stream is not open",
+                    ));
+                };
+                sink.write_all(&data).map_err(|err| {
+                    RunnerError::io("E_IO", format!("failed to write {}", stream.label()), err)
+                })
+            }
+            IoJob::Finish(stream) => match self.sinks.remove(&stream) {
+                Some(mut sink) => {
+                    flush_sink(stream, &mut sink)?;
+                    sink.finish()
+                }
+                None => Ok(()),
+            },
+            IoJob::WriteFile(path, data) => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).map_err(|err| {
+                        RunnerError::io("E_IO", "failed to create artifacts dir", err)
+                    })?;
+                }
+                atomic_write(&path, &data)
+            }
+            IoJob::AppendFile(path, data) => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).map_err(|err| {
+                        RunnerError::io("E_IO", "failed to create artifacts dir", err)
+                    })?;
+                }
+                fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&path)
+                    .map_err(|err| RunnerError::io("E_IO", "failed to open jsonl artifact", err))?
+                    .write_all(&data)
+                    .map_err(|err| RunnerError::io("E_IO", "failed to write jsonl artifact", err))
+            }
+        }
+    }
+
+    fn flush(&mut self) -> RunnerResult<()> {
+        for (stream, sink) in &mut self.sinks {
+            flush_sink(*stream, sink)?;
+        }
+        Ok(())
+    }
+}
+
+fn flush_sink(stream: Stream, sink: &mut StreamSink) -> RunnerResult<()> {
+    sink.flush()
+        .map_err(|err| RunnerError::io("E_IO", format!("failed to flush {}", stream.label()), err))
+}
+
+/// Where [`IoJob`]s run: on a background thread, or inline on submission.
+pub(super) enum IoQueue {
+    Inline(IoWorker),
+    Background(Background),
+}
+
+/// Handle to the I/O thread.
+pub(super) struct Background {
+    jobs: Option<SyncSender<Message>>,
+    thread: Option<JoinHandle<IoWorker>>,
+}
+
+impl IoQueue {
+    /// Start an I/O thread for `worker`.
+    pub(super) fn spawn(mut worker: IoWorker) -> RunnerResult<Self> {
+        let (jobs, receiver) = mpsc::sync_channel(IO_QUEUE_JOBS);
+        let thread = std::thread::Builder::new()
+            .name("ptybox-artifacts".to_string())
+            .spawn(move || {
+                serve(&mut worker, &receiver);
+                worker
+            })
+            .map_err(|err| RunnerError::io("E_IO", "failed to start artifacts I/O thread", err))?;
+        Ok(Self::Background(Background {
+            jobs: Some(jobs),
+            thread: Some(thread),
+        }))
+    }
+
+    /// Queue `job`. Inline, the job runs now and its error is returned.
+    pub(super) fn submit(&mut self, job: IoJob) -> RunnerResult<()> {
+        match self {
+            Self::Inline(worker) => worker.run(job),
+            Self::Background(background) => background.send(Message::Job(job)),
+        }
+    }
+
+    /// Wait for queued jobs and flush open sinks.
+    ///
+    /// # Errors
+    /// The first error of a job queued since the previous sync, or a flush
+    /// error.
+    pub(super) fn sync(&mut self) -> RunnerResult<()> {
+        match self {
+            Self::Inline(worker) => worker.flush(),
+            Self::Background(background) => {
+                let (reply, done) = mpsc::channel();
+                background.send(Message::Sync(reply))?;
+                match done.recv() {
+                    Ok(None) => Ok(()),
+                    Ok(Some(err)) => Err(err),
+                    Err(err) => Err(stopped(err)),
+                }
+            }
+        }
+    }
+
+    /// Move the open sinks to a background thread (`true`) or back to the
+    /// caller's thread (`false`), after a [`sync`](Self::sync).
+    pub(super) fn set_background(&mut self, enabled: bool) -> RunnerResult<()> {
+        if enabled == matches!(self, Self::Background(_)) {
+            return Ok(());
+        }
+        self.sync()?;
+        let worker = match std::mem::replace(self, Self::Inline(IoWorker::default())) {
+            Self::Inline(worker) => worker,
+            Self::Background(mut background) => background.stop()?,
+        };
+        if enabled {
+            *self = Self::spawn(worker)?;
+        } else {
+            *self = Self::Inline(worker);
+        }
+        Ok(())
+    }
+}
+
+impl Background {
+    fn send(&self, message: Message) -> RunnerResult<()> {
+        self.jobs
+            .as_ref()
+            .ok_or_else(|| stopped("queue closed"))?
+            .send(message)
+            .map_err(stopped)
+    }
+
+    /// Close the queue and take the sinks back from the thread.
+    fn stop(&mut self) -> RunnerResult<IoWorker> {
+        self.jobs.take();
+        self.thread
+            .take()
+            .ok_or_else(|| stopped("already stopped"))?
+            .join()
+            .map_err(|_| stopped("thread panicked"))
+    }
+}
+
+impl Drop for Background {
+    fn drop(&mut self) {
+        let _ = self.stop();
+    }
+}
+
+/// Run jobs until the queue closes, keeping the first error for the next
+/// sync.
+fn serve(worker: &mut IoWorker, receiver: &Receiver<Message>) {
+    let mut error = None;
+    for message in receiver {
+        match message {
+            Message::Job(job) => {
+                if let Err(err) = worker.run(job) {
+                    error.get_or_insert(err);
+                }
+            }
+            Message::Sync(reply) => {
+                let flushed = worker.flush();
+                let _ = reply.send(error.take().or(flushed.err()));
+            }
+        }
+    }
+}
+
+fn stopped(err: impl std::fmt::Display) -> RunnerError {
+    RunnerError::io("E_IO", "artifacts I/O thread stopped", err)
+}
@@ -0,0 +1,318 @@
+//! Failure triage bundles: one redacted `.tar.gz` per failed run.
+//!
+//! A bundle holds a generated `README.md`, `run.json`, `policy.json`,
+//! `host.json`, the tail of the transcript, and the failing step's
+//! snapshots, all under a `ptybox-bundle-<run_id>/` directory. Every entry
+//! passes through [`Redactor`] before it is archived.
+
+use super::redact::Redactor;
+use super::tarball::TarGz;
+use super::{artifact_path, read_artifact, snapshot_paths, step_dirs, ArtifactsDecryptor};
+use crate::model::policy::{ArtifactsLayout, Policy};
+use crate::model::{
+    BundleReport, HostFingerprint, RunResult, ScreenSnapshot, StepResult, StepStatus,
+};
+use crate::runner::{RunnerError, RunnerResult};
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Default number of transcript bytes kept in a bundle.
+pub const DEFAULT_TRANSCRIPT_TAIL_BYTES: usize = 64 * 1024;
+
+/// Options for [`bundle_artifacts`].
+#[derive(Clone, Debug)]
+pub struct BundleOptions {
+    /// Bytes kept from the end of the transcript.
+    pub transcript_tail_bytes: usize,
+    /// Extra regex patterns to redact, on top of the built-in rules.
+    pub redact: Vec<String>,
+    /// Replace `out` if it already exists.
+    pub overwrite: bool,
+    /// Age identity file for decrypting encrypted artifacts.
+    pub identity: Option<PathBuf>,
+    /// `age` binary used with `identity` (default `/usr/bin/age`).
+    pub age_binary: Option<PathBuf>,
+}
+
+impl Default for BundleOptions {
+    fn default() -> Self {
+        Self {
+            transcript_tail_bytes: DEFAULT_TRANSCRIPT_TAIL_BYTES,
+            redact: Vec::new(),
+            overwrite: false,
+            identity: None,
+            age_binary: None,
+        }
+    }
+}
+
+/// Pack the failure-relevant artifacts of the run in `artifacts_dir` into a
+/// `.tar.gz` at `out`.
+///
+/// Snapshots come from the first failed or errored step: its `steps/`
+/// directory under the `per_step` layout, or the last `attempts + 1`
+/// snapshots (its attempts plus the final screen) under the flat layout.
+/// A run without a failing step contributes its last snapshot.
+///
+/// # Errors
+/// - `E_IO`: `run.json` is missing, or an artifact cannot be read or the bundle written
+/// - `E_POLICY_DENIED`: `out` exists and `overwrite` is false, or an artifact
+///   is encrypted and no identity was given
+/// - `E_PROTOCOL`: `run.json` or `policy.json` does not parse, or a redaction
+///   pattern is invalid
+pub fn bundle_artifacts(
+    artifacts_dir: &Path,
+    out: &Path,
+    options: &BundleOptions,
+) -> RunnerResult<BundleReport> {
+    if out.exists() && !options.overwrite {
+        return Err(RunnerError::policy_denied(
+            "E_POLICY_DENIED",
+            "bundle file exists and overwrite is disabled",
+            serde_json::json!({ "path": out }),
+        ));
+    }
+    let run_path = artifacts_dir.join("run.json");
+    let run_json = fs::read(&run_path)
+        .map_err(|err| RunnerError::io("E_IO", "failed to read run.json", err))?;
+    let run: RunResult = serde_json::from_slice(&run_json)
+        .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to parse run.json", err))?;
+    let policy_path = artifacts_dir.join("policy.json");
+    let policy_json = if policy_path.is_file() {
+        fs::read(&policy_path)
+            .map_err(|err| RunnerError::io("E_IO", "failed to read policy.json", err))?
+    } else {
+        serde_json::to_vec_pretty(&run.policy)
+            .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to serialize policy", err))?
+    };
+    let policy: Policy = serde_json::from_slice(&policy_json)
+        .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to parse policy.json", err))?;
+    let decryptor = options
+        .identity
+        .as_ref()
+        .map(|identity| ArtifactsDecryptor::new(identity, options.age_binary.clone()));
+    let mut redactor = Redactor::new(Some(&policy), &options.redact)?;
+
+    let failing = run
+        .steps
+        .iter()
+        .flatten()
+        .enumerate()
+        .find(|(_, step)| matches!(step.status, StepStatus::Failed | StepStatus::Errored));
+    let mut entries: Vec<(String, String)> = vec![
+        ("run.json".to_string(), text(&run_json)),
+        ("policy.json".to_string(), text(&policy_json)),
+        (
+            "host.json".to_string(),
+            to_json(&HostFingerprint::current())?,
+        ),
+    ];
+    let transcript = artifact_path(artifacts_dir, "transcript.log");
+    if transcript.is_file() {
+        let data = read_artifact(&transcript, decryptor.as_ref())?;
+        entries.push((
+            "transcript-tail.log".to_string(),
+            text(tail(&data, options.transcript_tail_bytes)),
+        ));
+    }
+    let mut last_screen = None;
+    for path in bundled_snapshots(artifacts_dir, policy.artifacts.layout, failing)? {
+        let data = read_artifact(&path, decryptor.as_ref())?;
+        last_screen = serde_json::from_slice::<ScreenSnapshot>(&data).ok();
+        entries.push((entry_name(artifacts_dir, &path)?, text(&data)));
+    }
+
+    let mut entries: Vec<(String, String)> = entries
+        .into_iter()
+        .map(|(name, body)| (name, redactor.redact(&body)))
+        .collect();
+    let names: Vec<String> = entries.iter().map(|(name, _)| name.clone()).collect();
+    let mut readme = redactor.redact(&readme(&run, failing, last_screen.as_ref(), &names));
+    let _ = writeln!(
+        readme,
+        "\nRedaction replaced {} value(s).",
+        redactor.redactions()
+    );
+    entries.insert(0, ("README.md".to_string(), readme));
+
+    let root = format!("ptybox-bundle-{}", run.run_id);
+    let mut archive = TarGz::default();
+    let mut files = Vec::new();
+    for (name, body) in &entries {
+        let path = format!("{root}/{name}");
+        archive.add(&path, body.as_bytes())?;
+        files.push(path);
+    }
+    let bytes = archive.finish();
+    fs::write(out, &bytes).map_err(|err| RunnerError::io("E_IO", "failed to write bundle", err))?;
+
+    Ok(BundleReport {
+        output: out.display().to_string(),
+        files,
+        bytes: bytes.len() as u64,
+        redactions: redactor.redactions(),
+        failing_step: failing.map(|(_, step)| step.name.clone()),
+    })
+}
+
+/// Snapshot files to bundle for `failing` (index within the run's steps).
+fn bundled_snapshots(
+    artifacts_dir: &Path,
+    layout: ArtifactsLayout,
+    failing: Option<(usize, &StepResult)>,
+) -> RunnerResult<Vec<PathBuf>> {
+    let all = snapshot_paths(artifacts_dir)?;
+    let Some((index, step)) = failing else {
+        return Ok(all.last().cloned().into_iter().collect());
+    };
+    if layout == ArtifactsLayout::PerStep {
+        let prefix = format!("{:03}-", index + 1);
+        let step_dir = step_dirs(artifacts_dir)?.into_iter().find(|dir| {
+            dir.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(&prefix))
+        });
+        if let Some(step_dir) = step_dir {
+            return Ok(all
+                .into_iter()
+                .filter(|path| path.starts_with(&step_dir))
+                .collect());
+        }
+    }
+    let keep = usize::try_from(step.attempts)
+        .unwrap_or(usize::MAX)
+        .saturating_add(1);
+    let skip = all.len().saturating_sub(keep);
+    Ok(all.into_iter().skip(skip).collect())
+}
+
+/// Bundle entry name for an artifact: its relative path, without `.age`.
+fn entry_name(artifacts_dir: &Path, path: &Path) -> RunnerResult<String> {
+    let relative = path
+        .strip_prefix(artifacts_dir)
+        .ok()
+        .and_then(Path::to_str)
+        .ok_or_else(|| RunnerError::io("E_IO", "invalid artifact path", path.display()))?
+        .replace('\\', "/");
+    Ok(relative
+        .strip_suffix(super::ENCRYPTED_SUFFIX)
+        .map_or_else(|| relative.clone(), str::to_string))
+}
+
+/// The last `limit` bytes of `data`, starting on a UTF-8 character boundary.
+fn tail(data: &[u8], limit: usize) -> &[u8] {
+    let start = data.len().saturating_sub(limit);
+    let tail = data.get(start..).unwrap_or_default();
+    let boundary = tail
+        .iter()
+        .position(|byte| (byte & 0b1100_0000) != 0b1000_0000)
+        .unwrap_or(tail.len());
+    tail.get(boundary..).unwrap_or_default()
+}
+
+fn readme(
+    run: &RunResult,
+    failing: Option<(usize, &StepResult)>,
+    screen: Option<&ScreenSnapshot>,
+    files: &[String],
+) -> String {
+    let mut out = String::from("# ptybox failure bundle\n\n");
+    let _ = writeln!(out, "- Run: `{}`", run.run_id);
+    let _ = writeln!(out, "- Status: {}", label(&run.status));
+    let command = std::iter::once(run.command.as_str())
+        .chain(run.args.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let _ = writeln!(out, "- Command: `{command}`");
+    if let Some(name) = run
+        .scenario
+        .as_ref()
+        .map(|scenario| &scenario.metadata.name)
+    {
+        let _ = writeln!(out, "- Scenario: {name}");
+    }
+    if let Some(error) = &run.error {
+        let _ = writeln!(out, "- Error: `{}` {}", error.code, error.message);
+    }
+    if let Some(classification) = &run.classification {
+        let _ = writeln!(
+            out,
+            "- Likely cause: {} ({} confidence)",
+            classification.category.as_str(),
+            classification.confidence.as_str()
+        );
+    }
+
+    out.push_str("\n## What failed\n\n");
+    match failing {
+        Some((index, step)) => {
+            let _ = writeln!(
+                out,
+                "Step {} `{}` ({}) {} after {} attempt(s).",
+                index + 1,
+                step.name,
+                label(&step.action.action_type),
+                label(&step.status),
+                step.attempts
+            );
+            if let Some(error) = &step.error {
+                let _ = writeln!(out, "\nError `{}`: {}", error.code, error.message);
+            }
+            let failed: Vec<_> = step.assertions.iter().filter(|a| !a.passed).collect();
+            if !failed.is_empty() {
+                out.push_str("\nFailed assertions:\n\n");
+                for assertion in failed {
+                    let _ = writeln!(
+                        out,
+                        "- `{}`: {}",
+                        assertion.assertion_type,
+                        assertion.message.as_deref().unwrap_or("failed")
+                    );
+                }
+            }
+        }
+        None => out.push_str("No step failed; see the run error above.\n"),
+    }
+
+    if let Some(screen) = screen {
+        let _ = writeln!(
+            out,
+            "\n## Last screen ({}x{})\n\n```text",
+            screen.cols, screen.rows
+        );
+        let used = screen
+            .lines
+            .iter()
+            .rposition(|line| !line.trim().is_empty())
+            .map_or(0, |last| last + 1);
+        for line in screen.lines.iter().take(used) {
+            let _ = writeln!(out, "{line}");
+        }
+        out.push_str("```\n");
+    }
+
+    out.push_str("\n## Contents\n\n");
+    for file in files {
+        let _ = writeln!(out, "- `{file}`");
+    }
+    out
+}
+
+/// The serialized name of a unit enum variant (`failed`, `screen_contains`).
+fn label<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn text(data: &[u8]) -> String {
+    String::from_utf8_lossy(data).into_owned()
+}
+
+fn to_json<T: Serialize>(value: &T) -> RunnerResult<String> {
+    serde_json::to_string_pretty(value)
+        .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to serialize bundle entry", err))
+}
@@ -0,0 +1,165 @@
+//! Canonical JSON for artifacts that are checked in and diffed.
+//!
+//! Canonical form is `serde_json`'s pretty printer (two-space indentation)
+//! over a value whose object keys are sorted at every level, followed by a
+//! trailing newline. Numbers keep `serde_json`'s shortest round-trip
+//! formatting, so the same value always prints the same way.
+//! [`reformat_artifacts`] rewrites an existing run's JSON files in this
+//! form and updates `checksums.json` to match.
+
+use super::atomic_write;
+use crate::model::ReformatReport;
+use crate::runner::{ErrorCode, RunnerError, RunnerResult};
+use crate::util::compute_checksum;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Serialize `value` as canonical JSON: sorted keys, two-space indentation,
+/// and a trailing newline.
+///
+/// # Errors
+/// Returns the serializer's error if `value` cannot be represented as JSON.
+pub fn to_canonical_json<T: Serialize + ?Sized>(value: &T) -> serde_json::Result<Vec<u8>> {
+    let mut value = serde_json::to_value(value)?;
+    value.sort_all_objects();
+    let mut data = serde_json::to_vec_pretty(&value)?;
+    data.push(b'\n');
+    Ok(data)
+}
+
+/// Rewrite every JSON artifact in `dir` in canonical form.
+///
+/// Covers `*.json` files at any depth except under top-level `replay-*`
+/// directories; JSONL logs and encrypted files are left as they are. Files
+/// listed in `checksums.json` are verified before they are rewritten, and
+/// their entries are updated afterwards.
+///
+/// # Errors
+/// - `E_IO`: `dir` is not a directory, or a file cannot be read or written
+/// - `E_PROTOCOL`: An artifact or `checksums.json` is not valid JSON
+/// - `E_REPLAY_MISMATCH`: An artifact does not match its recorded checksum
+pub fn reformat_artifacts(dir: &Path) -> RunnerResult<ReformatReport> {
+    if !dir.is_dir() {
+        return Err(RunnerError::with_context(
+            ErrorCode::Io,
+            "artifacts directory not found",
+            serde_json::json!({ "dir": dir }),
+        ));
+    }
+    let mut checksums = read_checksums(dir)?;
+    let mut files = Vec::new();
+    collect_json_files(dir, "", &mut files)?;
+    files.sort();
+    let total = files.len() as u64;
+
+    let mut reformatted = Vec::new();
+    for relative in files {
+        let path = dir.join(&relative);
+        if let Some(checksums) = &checksums {
+            verify_checksum(&path, &relative, checksums)?;
+        }
+        let data = fs::read(&path)
+            .map_err(|err| RunnerError::io("E_IO", "failed to read artifact", err))?;
+        let canonical = canonicalize(&data, &relative)?;
+        if canonical == data {
+            continue;
+        }
+        atomic_write(&path, &canonical)?;
+        if let Some(entry) = checksums.as_mut().and_then(|sums| sums.get_mut(&relative)) {
+            *entry = compute_checksum(&path)?;
+        }
+        reformatted.push(relative);
+    }
+
+    if let Some(checksums) = &checksums {
+        let path = dir.join("checksums.json");
+        let data = to_canonical_json(checksums)
+            .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to serialize checksums", err))?;
+        let current = fs::read(&path)
+            .map_err(|err| RunnerError::io("E_IO", "failed to read checksums.json", err))?;
+        if current != data {
+            atomic_write(&path, &data)?;
+        }
+    }
+
+    Ok(ReformatReport {
+        dir: dir.display().to_string(),
+        unchanged: total.saturating_sub(reformatted.len() as u64),
+        reformatted,
+    })
+}
+
+fn read_checksums(dir: &Path) -> RunnerResult<Option<BTreeMap<String, String>>> {
+    let path = dir.join("checksums.json");
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let data = fs::read(&path)
+        .map_err(|err| RunnerError::io("E_IO", "failed to read checksums.json", err))?;
+    serde_json::from_slice(&data)
+        .map(Some)
+        .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to parse checksums.json", err))
+}
+
+fn verify_checksum(
+    path: &Path,
+    relative: &str,
+    checksums: &BTreeMap<String, String>,
+) -> RunnerResult<()> {
+    let Some(expected) = checksums.get(relative) else {
+        return Ok(());
+    };
+    let actual = compute_checksum(path)?;
+    if actual == *expected {
+        return Ok(());
+    }
+    Err(RunnerError::replay_mismatch(
+        "artifact corrupted",
+        serde_json::json!({
+            "kind": "checksum",
+            "path": relative,
+            "expected": expected,
+            "actual": actual
+        }),
+    ))
+}
+
+fn canonicalize(data: &[u8], relative: &str) -> RunnerResult<Vec<u8>> {
+    let value: serde_json::Value = serde_json::from_slice(data)
+        .map_err(|err| RunnerError::io("E_PROTOCOL", format!("failed to parse {relative}"), err))?;
+    to_canonical_json(&value).map_err(|err| {
+        RunnerError::io("E_PROTOCOL", format!("failed to serialize {relative}"), err)
+    })
+}
+
+/// Relative paths of the JSON artifacts under `dir`, skipping the top-level
+/// `checksums.json` and `replay-*` dirs.
+fn collect_json_files(dir: &Path, prefix: &str, files: &mut Vec<String>) -> RunnerResult<()> {
+    let top = prefix.is_empty();
+    let entries = fs::read_dir(dir)
+        .map_err(|err| RunnerError::io("E_IO", "failed to read artifacts dir", err))?;
+    for entry in entries {
+        let entry =
+            entry.map_err(|err| RunnerError::io("E_IO", "failed to read artifacts dir", err))?;
+        let file_type = entry
+            .file_type()
+            .map_err(|err| RunnerError::io("E_IO", "failed to stat artifact", err))?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let relative = format!("{prefix}{name}");
+        if file_type.is_dir() {
+            if !(top && name.starts_with("replay-")) {
+                collect_json_files(&entry.path(), &format!("{relative}/"), files)?;
+            }
+        } else if file_type.is_file()
+            && Path::new(&name)
+                .extension()
+                .is_some_and(|ext| ext == "json")
+            && !(top && name == "checksums.json")
+        {
+            files.push(relative);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,521 @@
+//! Comparison of two runs' recorded settings (`ptybox why-differs`).
+//!
+//! For a scenario that passes on one machine and fails on another, the two
+//! runs' `run.json`, `policy.json`, `host.json`, and `env.json` are compared
+//! field by field. Every difference becomes a [`DivergenceCause`] with a
+//! likelihood of explaining the different outcome, and the causes are
+//! returned most likely first. Settings that cannot change the app's
+//! behavior (artifact and replay options) are not compared.
+
+use crate::model::{
+    DivergenceCategory, DivergenceCause, DivergenceReport, DivergenceRun, EnvRecord,
+    FailureConfidence, HostFingerprint, Policy, RunResult, StepResult, RECORDED_ENV_VALUES,
+};
+use crate::runner::{RunnerError, RunnerResult};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+/// Environment variables that change how an app draws the screen.
+const TERMINAL_ENV_VARS: [&str; 5] = ["TERM", "COLORTERM", "NO_COLOR", "COLUMNS", "LINES"];
+
+/// A duration counts as different when the longer one is at least this many
+/// times the shorter one...
+const TIMING_RATIO: u64 = 2;
+
+/// ...and at least this many milliseconds longer.
+const TIMING_MIN_DELTA_MS: u64 = 100;
+
+/// Compare the runs whose artifacts are in `a` and `b`.
+///
+/// # Errors
+/// - `E_IO`: `run.json` is missing or unreadable in either directory
+/// - `E_PROTOCOL`: a recorded artifact does not parse
+pub fn why_differs(a: &Path, b: &Path) -> RunnerResult<DivergenceReport> {
+    let a = RunRecord::load(a)?;
+    let b = RunRecord::load(b)?;
+    let mut causes = Causes::default();
+    compare_scenario(&mut causes, &a.run, &b.run);
+    compare_terminal(&mut causes, &a, &b);
+    compare_env(&mut causes, a.env.as_ref(), b.env.as_ref());
+    compare_policies(&mut causes, &a, &b);
+    if let (Some(host_a), Some(host_b)) = (&a.host, &b.host) {
+        compare_hosts(&mut causes, host_a, host_b);
+    }
+    let first_divergent_step = first_divergent_step(&a.run, &b.run);
+    compare_timing(&mut causes, &a.run, &b.run, first_divergent_step.as_deref());
+
+    let mut not_compared = Vec::new();
+    for (name, a_has, b_has) in [
+        ("host.json", a.host.is_some(), b.host.is_some()),
+        ("env.json", a.env.is_some(), b.env.is_some()),
+    ] {
+        if !(a_has && b_has) {
+            not_compared.push(name.to_string());
+        }
+    }
+    Ok(DivergenceReport {
+        a: a.summary(),
+        b: b.summary(),
+        first_divergent_step,
+        causes: causes.ranked(),
+        not_compared,
+    })
+}
+
+/// Artifacts of one run.
+struct RunRecord {
+    dir: String,
+    run: RunResult,
+    policy: Policy,
+    host: Option<HostFingerprint>,
+    env: Option<EnvRecord>,
+}
+
+impl RunRecord {
+    fn load(dir: &Path) -> RunnerResult<Self> {
+        let run: RunResult = load_json(&dir.join("run.json"), "run.json")?.ok_or_else(|| {
+            RunnerError::io(
+                "E_IO",
+                format!("{} has no run.json", dir.display()),
+                std::io::Error::from(std::io::ErrorKind::NotFound),
+            )
+        })?;
+        let policy = load_json(&dir.join("policy.json"), "policy.json")?
+            .unwrap_or_else(|| run.policy.clone());
+        Ok(Self {
+            dir: dir.display().to_string(),
+            policy,
+            host: load_json(&dir.join("host.json"), "host.json")?,
+            env: load_json(&dir.join("env.json"), "env.json")?,
+            run,
+        })
+    }
+
+    fn summary(&self) -> DivergenceRun {
+        DivergenceRun {
+            artifacts_dir: self.dir.clone(),
+            run_id: self.run.run_id,
+            status: self.run.status.clone(),
+            error_code: self.error_code().map(str::to_string),
+            duration_ms: run_duration(&self.run),
+        }
+    }
+
+    fn error_code(&self) -> Option<&str> {
+        self.run.error.as_ref().map(|error| error.code.as_str())
+    }
+}
+
+fn load_json<T: DeserializeOwned>(path: &Path, name: &str) -> RunnerResult<Option<T>> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let data = fs::read(path)
+        .map_err(|err| RunnerError::io("E_IO", format!("failed to read {name}"), err))?;
+    serde_json::from_slice(&data)
+        .map(Some)
+        .map_err(|err| RunnerError::io("E_PROTOCOL", format!("failed to parse {name}"), err))
+}
+
+/// Differences found so far, in discovery order.
+#[derive(Default)]
+struct Causes(Vec<DivergenceCause>);
+
+impl Causes {
+    fn push(
+        &mut self,
+        category: DivergenceCategory,
+        likelihood: FailureConfidence,
+        field: impl Into<String>,
+        (a, b): (Value, Value),
+        reason: impl Into<String>,
+    ) {
+        self.0.push(DivergenceCause {
+            rank: 0,
+            category,
+            likelihood,
+            field: field.into(),
+            a,
+            b,
+            reason: reason.into(),
+        });
+    }
+
+    fn compare<T: Serialize + PartialEq>(
+        &mut self,
+        (category, likelihood): (DivergenceCategory, FailureConfidence),
+        field: &str,
+        (a, b): (&T, &T),
+        reason: &str,
+    ) {
+        if a != b {
+            self.push(
+                category,
+                likelihood,
+                field,
+                (to_value(a), to_value(b)),
+                reason,
+            );
+        }
+    }
+
+    /// Causes sorted most likely first (discovery order within a
+    /// likelihood), numbered from 1.
+    fn ranked(mut self) -> Vec<DivergenceCause> {
+        self.0
+            .sort_by_key(|cause| std::cmp::Reverse(cause.likelihood));
+        for (index, cause) in self.0.iter_mut().enumerate() {
+            cause.rank = u32::try_from(index + 1).unwrap_or(u32::MAX);
+        }
+        self.0
+    }
+}
+
+fn to_value<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or_default()
+}
+
+fn compare_scenario(causes: &mut Causes, a: &RunResult, b: &RunResult) {
+    use DivergenceCategory::{Scenario, Version};
+    use FailureConfidence::{High, Low, Medium};
+
+    let hash = |run: &RunResult| {
+        run.provenance
+            .as_ref()
+            .and_then(|p| p.scenario_hash.clone())
+    };
+    let git_sha = |run: &RunResult| run.provenance.as_ref().and_then(|p| p.git_sha.clone());
+    let version = |run: &RunResult| run.provenance.as_ref().map(|p| p.ptybox_version.clone());
+    causes.compare(
+        (Scenario, High),
+        "provenance.scenario_hash",
+        (&hash(a), &hash(b)),
+        "the runs executed different scenarios; compare their scenario.json",
+    );
+    causes.compare(
+        (Scenario, High),
+        "command",
+        (&a.command, &b.command),
+        "a different program was run",
+    );
+    causes.compare(
+        (Scenario, High),
+        "args",
+        (&a.args, &b.args),
+        "the program was run with different arguments",
+    );
+    causes.compare(
+        (Scenario, Low),
+        "cwd",
+        (&a.cwd, &b.cwd),
+        "relative paths resolve against a different working directory",
+    );
+    causes.compare(
+        (Version, High),
+        "provenance.git_sha",
+        (&git_sha(a), &git_sha(b)),
+        "different revisions of the code under test were run",
+    );
+    causes.compare(
+        (Version, Medium),
+        "provenance.ptybox_version",
+        (&version(a), &version(b)),
+        "ptybox itself differs; terminal emulation or timing may have changed",
+    );
+}
+
+fn compare_terminal(causes: &mut Causes, a: &RunRecord, b: &RunRecord) {
+    use DivergenceCategory::Terminal;
+    use FailureConfidence::{High, Medium};
+
+    let run_config = |record: &RunRecord| record.run.scenario.as_ref().map(|s| s.run.clone());
+    if let (Some(a_run), Some(b_run)) = (run_config(a), run_config(b)) {
+        causes.compare(
+            (Terminal, High),
+            "run.initial_size",
+            (&a_run.initial_size, &b_run.initial_size),
+            "layout, wrapping, and what fits on screen depend on the terminal size",
+        );
+        causes.compare(
+            (Terminal, Medium),
+            "run.separate_stderr",
+            (&a_run.separate_stderr, &b_run.separate_stderr),
+            "stderr reaches the screen in only one of the runs",
+        );
+        causes.compare(
+            (Terminal, Medium),
+            "run.output_buffer",
+            (&a_run.output_buffer, &b_run.output_buffer),
+            "output buffering changes how bursts of output are read",
+        );
+        causes.compare(
+            (Terminal, Medium),
+            "run.resize",
+            (&a_run.resize, &b_run.resize),
+            "resized screens are truncated or rewrapped differently",
+        );
+    }
+    if let (Some(a_env), Some(b_env)) = (&a.env, &b.env) {
+        for name in TERMINAL_ENV_VARS {
+            let (a_value, b_value) = (env_value(a_env, name), env_value(b_env, name));
+            if a_value != b_value {
+                causes.push(
+                    Terminal,
+                    High,
+                    format!("env.{name}"),
+                    (a_value, b_value),
+                    "the app may choose different colors, capabilities, or sizes",
+                );
+            }
+        }
+    }
+}
+
+fn compare_env(causes: &mut Causes, a: Option<&EnvRecord>, b: Option<&EnvRecord>) {
+    use DivergenceCategory::Env;
+    use FailureConfidence::{High, Medium};
+
+    let (Some(a), Some(b)) = (a, b) else {
+        return;
+    };
+    let names: BTreeSet<&str> = a
+        .vars
+        .iter()
+        .chain(&b.vars)
+        .map(|var| var.name.as_str())
+        .filter(|name| !TERMINAL_ENV_VARS.contains(name))
+        .collect();
+    for name in names {
+        let digest = |record: &EnvRecord| {
+            record
+                .vars
+                .iter()
+                .find(|var| var.name == name)
+                .map(|var| var.digest.clone())
+        };
+        if digest(a) == digest(b) {
+            continue;
+        }
+        let (likelihood, reason) = if name == "TZ" || name == "LANG" || name.starts_with("LC_") {
+            (
+                High,
+                "text, sorting, and dates depend on the locale and timezone",
+            )
+        } else {
+            (Medium, "the app sees a different environment")
+        };
+        causes.push(
+            Env,
+            likelihood,
+            format!("env.{name}"),
+            (env_value(a, name), env_value(b, name)),
+            reason,
+        );
+    }
+}
+
+/// Recorded value of `name`, its digest when the value was not recorded,
+/// or `null` when the child did not get the variable.
+fn env_value(record: &EnvRecord, name: &str) -> Value {
+    record
+        .vars
+        .iter()
+        .find(|var| var.name == name)
+        .map_or(Value::Null, |var| match &var.value {
+            Some(value) => Value::String(value.clone()),
+            None if RECORDED_ENV_VALUES.contains(&name) => Value::Null,
+            None => Value::String(var.digest.clone()),
+        })
+}
+
+fn compare_policies(causes: &mut Causes, a: &RunRecord, b: &RunRecord) {
+    let mut a_leaves = BTreeMap::new();
+    let mut b_leaves = BTreeMap::new();
+    flatten("policy", &to_value(&a.policy), &mut a_leaves);
+    flatten("policy", &to_value(&b.policy), &mut b_leaves);
+    let fields: BTreeSet<&String> = a_leaves.keys().chain(b_leaves.keys()).collect();
+    for field in fields {
+        let a_value = a_leaves.get(field).cloned().unwrap_or(Value::Null);
+        let b_value = b_leaves.get(field).cloned().unwrap_or(Value::Null);
+        if a_value == b_value {
+            continue;
+        }
+        let section = field.split('.').nth(1).unwrap_or_default();
+        let (category, likelihood, reason) = match section {
+            "budgets" => budget_difference(a, b, &a_value, &b_value),
+            "determinism" => (
+                DivergenceCategory::Env,
+                FailureConfidence::High,
+                "locale, timezone, or input pacing is pinned differently".to_string(),
+            ),
+            "env" => (
+                DivergenceCategory::Env,
+                FailureConfidence::Medium,
+                "the policy passes a different environment".to_string(),
+            ),
+            "sandbox" | "sandbox_fallback" | "network" | "network_enforcement" => (
+                DivergenceCategory::Policy,
+                FailureConfidence::Medium,
+                "the app runs with different sandbox or network access".to_string(),
+            ),
+            "artifacts" | "replay" | "policy_version" => continue,
+            _ => (
+                DivergenceCategory::Policy,
+                FailureConfidence::Low,
+                "the policy allows different access".to_string(),
+            ),
+        };
+        causes.push(
+            category,
+            likelihood,
+            field.clone(),
+            (a_value, b_value),
+            reason,
+        );
+    }
+}
+
+/// Likelihood of a budget difference: high when the run with the smaller
+/// budget is the one that timed out.
+fn budget_difference(
+    a: &RunRecord,
+    b: &RunRecord,
+    a_value: &Value,
+    b_value: &Value,
+) -> (DivergenceCategory, FailureConfidence, String) {
+    let timed_out_with_less = |record: &RunRecord, own: &Value, other: &Value| {
+        record.error_code() == Some("E_TIMEOUT")
+            && matches!((own.as_u64(), other.as_u64()), (Some(own), Some(other)) if own < other)
+    };
+    if timed_out_with_less(a, a_value, b_value) || timed_out_with_less(b, b_value, a_value) {
+        (
+            DivergenceCategory::Budgets,
+            FailureConfidence::High,
+            "the run with the smaller budget failed with E_TIMEOUT".to_string(),
+        )
+    } else {
+        (
+            DivergenceCategory::Budgets,
+            FailureConfidence::Medium,
+            "a tighter budget can turn a slow run into E_TIMEOUT".to_string(),
+        )
+    }
+}
+
+/// Collect the leaves of `value` under dotted paths; arrays are leaves.
+fn flatten(path: &str, value: &Value, leaves: &mut BTreeMap<String, Value>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                flatten(&format!("{path}.{key}"), child, leaves);
+            }
+        }
+        _ => {
+            leaves.insert(path.to_string(), value.clone());
+        }
+    }
+}
+
+fn compare_hosts(causes: &mut Causes, a: &HostFingerprint, b: &HostFingerprint) {
+    use DivergenceCategory::Host;
+    use FailureConfidence::{Low, Medium};
+
+    causes.compare(
+        (Host, Medium),
+        "host.os",
+        (&a.os, &b.os),
+        "PTY and signal behavior differ between operating systems",
+    );
+    causes.compare(
+        (Host, Medium),
+        "host.arch",
+        (&a.arch, &b.arch),
+        "the app was built for a different architecture",
+    );
+    causes.compare(
+        (Host, Low),
+        "host.cpus",
+        (&a.cpus, &b.cpus),
+        "fewer CPUs make the app slower; see the timing causes",
+    );
+}
+
+/// Name of the first step whose status differs, or the first step only
+/// one run reached.
+fn first_divergent_step(a: &RunResult, b: &RunResult) -> Option<String> {
+    let a_steps = a.steps.as_deref().unwrap_or_default();
+    let b_steps = b.steps.as_deref().unwrap_or_default();
+    a_steps
+        .iter()
+        .zip(b_steps)
+        .find(|(a_step, b_step)| a_step.status != b_step.status)
+        .map(|(a_step, _)| a_step.name.clone())
+        .or_else(|| {
+            let common = a_steps.len().min(b_steps.len());
+            a_steps
+                .get(common)
+                .or_else(|| b_steps.get(common))
+                .map(|step| step.name.clone())
+        })
+}
+
+fn compare_timing(
+    causes: &mut Causes,
+    a: &RunResult,
+    b: &RunResult,
+    first_divergent_step: Option<&str>,
+) {
+    use DivergenceCategory::Timing;
+    use FailureConfidence::{High, Medium};
+
+    let a_steps = a.steps.as_deref().unwrap_or_default();
+    let b_steps = b.steps.as_deref().unwrap_or_default();
+    for (a_step, b_step) in a_steps.iter().zip(b_steps) {
+        if a_step.name != b_step.name {
+            break;
+        }
+        let (a_ms, b_ms) = (step_duration(a_step), step_duration(b_step));
+        if !durations_differ(a_ms, b_ms) {
+            continue;
+        }
+        let diverged = first_divergent_step == Some(a_step.name.as_str());
+        causes.push(
+            Timing,
+            if diverged { High } else { Medium },
+            format!("steps.{}.duration_ms", a_step.name),
+            (Value::from(a_ms), Value::from(b_ms)),
+            if diverged {
+                "the step whose outcome differs also took much longer in one run"
+            } else {
+                "the step took much longer in one run; waits and budgets may be too tight"
+            },
+        );
+    }
+    let (a_ms, b_ms) = (run_duration(a), run_duration(b));
+    if durations_differ(a_ms, b_ms) {
+        causes.push(
+            Timing,
+            Medium,
+            "duration_ms",
+            (Value::from(a_ms), Value::from(b_ms)),
+            "one run was much slower overall",
+        );
+    }
+}
+
+fn durations_differ(a: u64, b: u64) -> bool {
+    let (short, long) = (a.min(b), a.max(b));
+    long - short >= TIMING_MIN_DELTA_MS && long >= short.saturating_mul(TIMING_RATIO)
+}
+
+fn run_duration(run: &RunResult) -> u64 {
+    run.ended_at_ms.saturating_sub(run.started_at_ms)
+}
+
+fn step_duration(step: &StepResult) -> u64 {
+    step.ended_at_ms.saturating_sub(step.started_at_ms)
+}
@@ -0,0 +1,219 @@
+//! Age encryption of sensitive artifacts at rest.
+//!
+//! ptybox does not implement age itself: it pipes plaintext through the
+//! `age` binary named by [`ArtifactsEncryption::age_binary`] and never
+//! handles key material beyond recipient strings and identity file paths.
+//! Decryption runs the `age` binary given to [`ArtifactsDecryptor::new`].
+
+use crate::model::policy::{ArtifactsEncryption, DEFAULT_AGE_BINARY};
+use crate::runner::{RunnerError, RunnerResult};
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+/// File name suffix of encrypted artifacts.
+pub const ENCRYPTED_SUFFIX: &str = ".age";
+
+/// Spawns `age --encrypt` for each encrypted artifact.
+pub(crate) struct AgeEncryptor {
+    binary: PathBuf,
+    recipients: Vec<String>,
+}
+
+impl AgeEncryptor {
+    pub(crate) fn new(config: &ArtifactsEncryption) -> Self {
+        Self {
+            binary: PathBuf::from(&config.age_binary),
+            recipients: config.recipients.clone(),
+        }
+    }
+
+    /// Start an encrypted stream writing ciphertext to `path`.
+    pub(crate) fn open_stream(&self, path: &Path) -> RunnerResult<AgeStream> {
+        let mut command = Command::new(&self.binary);
+        command.arg("--encrypt");
+        for recipient in &self.recipients {
+            command.arg("--recipient").arg(recipient);
+        }
+        let mut child = command
+            .arg("--output")
+            .arg(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|err| RunnerError::io("E_IO", "failed to start age", err))?;
+        let stdin = child.stdin.take().map(BufWriter::new);
+        Ok(AgeStream {
+            child: Some(child),
+            stdin,
+        })
+    }
+
+    /// Encrypt `data` into `path` atomically (temp file + rename).
+    pub(crate) fn write_file(&self, path: &Path, data: &[u8]) -> RunnerResult<()> {
+        let tmp_path = path.with_extension("tmp");
+        let mut stream = self.open_stream(&tmp_path)?;
+        stream
+            .write_all(data)
+            .map_err(|err| RunnerError::io("E_IO", "failed to write encrypted artifact", err))?;
+        stream.finish()?;
+        fs::rename(&tmp_path, path)
+            .map_err(|err| RunnerError::io("E_IO", "failed to rename artifact into place", err))
+    }
+}
+
+/// Plaintext sink feeding a running `age --encrypt` process.
+///
+/// The ciphertext is complete only after [`finish`](Self::finish).
+pub(crate) struct AgeStream {
+    child: Option<Child>,
+    stdin: Option<BufWriter<ChildStdin>>,
+}
+
+impl AgeStream {
+    /// Close the plaintext input and wait for `age` to write the file.
+    ///
+    /// Idempotent; writes after finishing fail with a broken pipe.
+    pub(crate) fn finish(&mut self) -> RunnerResult<()> {
+        if let Some(mut stdin) = self.stdin.take() {
+            stdin.flush().map_err(|err| {
+                RunnerError::io("E_IO", "failed to write encrypted artifact", err)
+            })?;
+        }
+        let Some(child) = self.child.take() else {
+            return Ok(());
+        };
+        let output = child
+            .wait_with_output()
+            .map_err(|err| RunnerError::io("E_IO", "failed to wait for age", err))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(RunnerError::io(
+                "E_IO",
+                "age encryption failed",
+                format!(
+                    "{}: {}",
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+            ))
+        }
+    }
+}
+
+impl Write for AgeStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.stdin.as_mut() {
+            Some(stdin) => stdin.write(buf),
+            None => Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "encrypted artifact already finished",
+            )),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stdin.as_mut().map_or(Ok(()), Write::flush)
+    }
+}
+
+impl Drop for AgeStream {
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
+
+/// Reads artifacts written under `policy.artifacts.encryption`.
+///
+/// The `age` binary is always chosen by the caller, never read from the
+/// artifacts: `policy.json` travels with the artifacts directory, so
+/// trusting its `age_binary` would let whoever supplied the directory pick
+/// the program that `replay` or `trace` runs.
+#[derive(Clone, Debug)]
+pub struct ArtifactsDecryptor {
+    age_binary: PathBuf,
+    identity: PathBuf,
+}
+
+impl ArtifactsDecryptor {
+    /// Decrypt with `identity` using the `age` binary at `age_binary`, or
+    /// [`DEFAULT_AGE_BINARY`] when `None`.
+    #[must_use]
+    pub fn new(identity: impl Into<PathBuf>, age_binary: Option<PathBuf>) -> Self {
+        Self {
+            age_binary: age_binary.unwrap_or_else(|| PathBuf::from(DEFAULT_AGE_BINARY)),
+            identity: identity.into(),
+        }
+    }
+
+    /// Decrypt the age file at `path`.
+    ///
+    /// # Errors
+    /// Returns `E_IO` if `age` cannot be started or fails to decrypt.
+    pub fn decrypt(&self, path: &Path) -> RunnerResult<Vec<u8>> {
+        let output = Command::new(&self.age_binary)
+            .arg("--decrypt")
+            .arg("--identity")
+            .arg(&self.identity)
+            .arg(path)
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|err| RunnerError::io("E_IO", "failed to start age", err))?;
+        if !output.status.success() {
+            return Err(RunnerError::io(
+                "E_IO",
+                format!("failed to decrypt {}", path.display()),
+                format!(
+                    "{}: {}",
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+            ));
+        }
+        Ok(output.stdout)
+    }
+}
+
+/// Read an artifact file, decrypting it when its name ends in `.age`.
+///
+/// # Errors
+/// - `E_POLICY_DENIED` if the file is encrypted and no decryptor is given
+/// - `E_IO` if the file cannot be read or decrypted
+pub fn read_artifact(path: &Path, decryptor: Option<&ArtifactsDecryptor>) -> RunnerResult<Vec<u8>> {
+    if !is_encrypted(path) {
+        return fs::read(path).map_err(|err| {
+            RunnerError::io("E_IO", format!("failed to read {}", path.display()), err)
+        });
+    }
+    decryptor
+        .ok_or_else(|| {
+            RunnerError::policy_denied(
+                "E_POLICY_DENIED",
+                "encrypted artifact requires an identity file",
+                serde_json::json!({
+                    "path": path,
+                    "fix": "Pass --identity with an age identity for one of the policy recipients"
+                }),
+            )
+        })?
+        .decrypt(path)
+}
+
+/// `dir/name`, or `dir/name.age` when only the encrypted form exists.
+#[must_use]
+pub fn artifact_path(dir: &Path, name: &str) -> PathBuf {
+    let plain = dir.join(name);
+    let encrypted = dir.join(format!("{name}{ENCRYPTED_SUFFIX}"));
+    if !plain.exists() && encrypted.exists() {
+        encrypted
+    } else {
+        plain
+    }
+}
+
+fn is_encrypted(path: &Path) -> bool {
+    path.extension().and_then(|ext| ext.to_str()) == Some("age")
+}
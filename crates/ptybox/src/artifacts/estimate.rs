@@ -0,0 +1,129 @@
+//! Time estimates for running a set of scenario files.
+//!
+//! A scenario's expected duration is the median of its last
+//! [`ESTIMATE_HISTORY_RUNS`] completed runs in a run index, and its range
+//! the shortest and longest of those runs. A scenario that has not run yet
+//! falls back to its declared step timeouts, which bound a run from above.
+//! Every figure is multiplied by the matrix cells the scenario runs in.
+
+use super::goldens::scenario_name;
+use super::read_run_index;
+use crate::model::{
+    EstimateBasis, EstimateReport, RunIndexEntry, RunStatus, Scenario, ScenarioEstimate,
+};
+use crate::runner::{ErrorCode, RunnerError, RunnerResult};
+use crate::scenario::load_scenario_file;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Earlier runs of a scenario considered by [`estimate_scenarios`].
+pub const ESTIMATE_HISTORY_RUNS: usize = 20;
+
+/// Estimate how long the scenarios in `paths` (files or directories
+/// searched for `.json`, `.yaml`, and `.yml` scenario files) take to run,
+/// each in `matrix_cells` matrix cells (at least 1).
+///
+/// History comes from the run index at `root`; passed and failed runs
+/// count, while errored and canceled runs are ignored. Files that are not
+/// scenarios are skipped.
+///
+/// # Errors
+/// - `E_IO`: A path does not exist, or a directory or the run index cannot
+///   be read
+/// - `E_PROTOCOL`: A scenario file or the run index does not parse
+pub fn estimate_scenarios(
+    paths: &[PathBuf],
+    root: Option<&Path>,
+    matrix_cells: u64,
+) -> RunnerResult<EstimateReport> {
+    let history = match root {
+        Some(root) => read_run_index(root)?,
+        None => Vec::new(),
+    };
+    let runs = matrix_cells.max(1);
+    let mut report = EstimateReport {
+        matrix_cells: runs,
+        ..EstimateReport::default()
+    };
+    for path in scenario_files(paths)? {
+        let scenario = load_scenario_file(&path.display().to_string())?;
+        let estimate = estimate_scenario(&scenario, &path, &history, runs);
+        report.total_ms = report.total_ms.saturating_add(estimate.total_ms);
+        report.total_low_ms = report.total_low_ms.saturating_add(estimate.total_low_ms);
+        report.total_high_ms = report.total_high_ms.saturating_add(estimate.total_high_ms);
+        report.scenarios.push(estimate);
+    }
+    Ok(report)
+}
+
+fn estimate_scenario(
+    scenario: &Scenario,
+    path: &Path,
+    history: &[RunIndexEntry],
+    runs: u64,
+) -> ScenarioEstimate {
+    let timeout_ms = scenario.steps.iter().fold(0_u64, |total, step| {
+        let attempts = u64::from(step.retries).saturating_add(1);
+        total.saturating_add(step.timeout_ms.saturating_mul(attempts))
+    });
+    let mut durations: Vec<u64> = history
+        .iter()
+        .rev()
+        .filter(|entry| entry.scenario.as_deref() == Some(scenario.metadata.name.as_str()))
+        .filter(|entry| matches!(entry.status, RunStatus::Passed | RunStatus::Failed))
+        .filter_map(|entry| entry.duration_ms)
+        .take(ESTIMATE_HISTORY_RUNS)
+        .collect();
+    durations.sort_unstable();
+    let observed = durations
+        .first()
+        .zip(durations.last())
+        .zip(durations.get(durations.len() / 2));
+    let (basis, expected_ms, low_ms, high_ms) = match observed {
+        Some(((&low, &high), &median)) => (EstimateBasis::History, median, low, high),
+        None => (EstimateBasis::Timeouts, timeout_ms, 0, timeout_ms),
+    };
+    ScenarioEstimate {
+        scenario: scenario.metadata.name.clone(),
+        path: path.display().to_string(),
+        steps: scenario.steps.len() as u64,
+        runs,
+        timeout_ms,
+        basis,
+        history_runs: durations.len() as u64,
+        expected_ms,
+        low_ms,
+        high_ms,
+        total_ms: expected_ms.saturating_mul(runs),
+        total_low_ms: low_ms.saturating_mul(runs),
+        total_high_ms: high_ms.saturating_mul(runs),
+    }
+}
+
+/// Scenario files in `paths`, in path order.
+pub(crate) fn scenario_files(paths: &[PathBuf]) -> RunnerResult<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = Vec::new();
+    for path in paths {
+        if !path.exists() {
+            return Err(RunnerError::with_context(
+                ErrorCode::Io,
+                "scenario path does not exist",
+                serde_json::json!({ "path": path }),
+            ));
+        }
+        pending.push(path.clone());
+    }
+    while let Some(path) = pending.pop() {
+        if path.is_dir() {
+            let entries = fs::read_dir(&path)
+                .map_err(|err| RunnerError::io("E_IO", "failed to read scenarios dir", err))?;
+            pending.extend(entries.filter_map(|entry| entry.ok().map(|entry| entry.path())));
+        } else if scenario_name(&path)?.is_some() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
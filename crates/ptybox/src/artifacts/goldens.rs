@@ -0,0 +1,503 @@
+//! Golden screen baselines kept under a baseline directory.
+//!
+//! Each subdirectory of the baseline directory is one golden: a replay
+//! baseline (see [`slim_artifacts`](super::slim_artifacts)) whose snapshots
+//! are the expected screens of its scenario. Goldens are matched to
+//! scenario files by scenario name, replaced from the newest passing run in
+//! a run index, and every replacement is appended to `goldens.jsonl` so
+//! churn can be reported per golden.
+
+use super::{read_artifact, read_run_index, slim_artifacts, snapshot_paths, ENCRYPTED_SUFFIX};
+use crate::model::{
+    GoldenEntry, GoldenPruneReport, GoldenSkip, GoldenUpdate, GoldenUpdateReport, RunId, RunStatus,
+    SCENARIO_VERSION,
+};
+use crate::runner::{ErrorCode, RunnerError, RunnerResult};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// File name of the update log inside the baseline directory.
+pub const GOLDENS_LOG_FILE: &str = "goldens.jsonl";
+
+/// A golden as read from disk.
+struct Golden {
+    name: String,
+    dir: PathBuf,
+    scenario: String,
+    run_id: RunId,
+}
+
+#[derive(Deserialize)]
+struct ScenarioHeader {
+    scenario_version: u32,
+    metadata: MetadataHeader,
+}
+
+impl ScenarioHeader {
+    /// The scenario name, once `scenario_version` is known to be supported.
+    fn into_name(self, path: &Path) -> RunnerResult<String> {
+        if self.scenario_version != SCENARIO_VERSION {
+            return Err(RunnerError::with_context(
+                ErrorCode::Protocol,
+                "unsupported scenario_version",
+                serde_json::json!({
+                    "path": path,
+                    "scenario_version": self.scenario_version,
+                    "supported": SCENARIO_VERSION,
+                }),
+            ));
+        }
+        Ok(self.metadata.name)
+    }
+}
+
+#[derive(Deserialize)]
+struct MetadataHeader {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct RunHeader {
+    run_id: RunId,
+}
+
+/// List the goldens under `baseline`, in name order, with their churn.
+///
+/// When `scenarios` (files or directories searched for `.json`, `.yaml`,
+/// and `.yml` scenario files) is not empty, each entry lists the scenario
+/// files that reference it.
+///
+/// # Errors
+/// - `E_IO`: `baseline`, a golden, or a path in `scenarios` cannot be read,
+///   or a path in `scenarios` does not exist
+/// - `E_PROTOCOL`: A golden's `scenario.json` or `run.json`, a scenario file,
+///   or the update log does not parse
+pub fn list_goldens(baseline: &Path, scenarios: &[PathBuf]) -> RunnerResult<Vec<GoldenEntry>> {
+    let references = if scenarios.is_empty() {
+        None
+    } else {
+        Some(scenario_names(baseline, scenarios)?)
+    };
+    golden_entries(baseline, references.as_ref())
+}
+
+/// The goldens under `baseline` with their churn and, when `references` is
+/// given, the scenario files naming each.
+fn golden_entries(
+    baseline: &Path,
+    references: Option<&BTreeMap<String, Vec<String>>>,
+) -> RunnerResult<Vec<GoldenEntry>> {
+    let history = read_history(baseline)?;
+    read_goldens(baseline)?
+        .into_iter()
+        .map(|golden| {
+            let updates: Vec<&GoldenUpdate> = history
+                .iter()
+                .filter(|update| update.name == golden.name)
+                .collect();
+            Ok(GoldenEntry {
+                snapshots: snapshot_paths(&golden.dir)?.len() as u64,
+                bytes: dir_size(&golden.dir)?,
+                referenced_by: references
+                    .map(|names| names.get(&golden.scenario).cloned().unwrap_or_default()),
+                updates: updates.len() as u64,
+                lines_changed: updates.iter().map(|update| update.lines_changed).sum(),
+                last_updated_at_ms: updates.iter().map(|update| update.updated_at_ms).max(),
+                name: golden.name,
+                scenario: golden.scenario,
+                run_id: golden.run_id,
+            })
+        })
+        .collect()
+}
+
+/// Remove the goldens whose scenario no file in `scenarios` names.
+///
+/// With `dry_run`, report what would be removed without deleting anything.
+///
+/// # Errors
+/// - `E_PROTOCOL`: `scenarios` is empty or holds no scenario files (every
+///   golden would be removed)
+/// - The errors of [`list_goldens`], or `E_IO` when a golden cannot be removed
+pub fn prune_goldens(
+    baseline: &Path,
+    scenarios: &[PathBuf],
+    dry_run: bool,
+) -> RunnerResult<GoldenPruneReport> {
+    if scenarios.is_empty() {
+        return Err(RunnerError::with_context(
+            ErrorCode::Protocol,
+            "pruning goldens requires the scenarios that reference them",
+            serde_json::json!({
+                "baseline": baseline,
+                "fix": "Pass the scenario files or directories with --scenarios",
+            }),
+        ));
+    }
+    let references = scenario_names(baseline, scenarios)?;
+    if references.is_empty() {
+        return Err(RunnerError::with_context(
+            ErrorCode::Protocol,
+            "no scenario files found; refusing to prune every golden",
+            serde_json::json!({
+                "baseline": baseline,
+                "scenarios": scenarios,
+                "fix": "Check that --scenarios points at the scenario files or directories",
+            }),
+        ));
+    }
+    let mut report = GoldenPruneReport {
+        removed: Vec::new(),
+        kept: 0,
+        bytes_freed: 0,
+        dry_run,
+    };
+    for entry in golden_entries(baseline, Some(&references))? {
+        if entry
+            .referenced_by
+            .as_ref()
+            .is_some_and(|by| !by.is_empty())
+        {
+            report.kept += 1;
+            continue;
+        }
+        if !dry_run {
+            fs::remove_dir_all(baseline.join(&entry.name))
+                .map_err(|err| RunnerError::io("E_IO", "failed to remove golden", err))?;
+        }
+        report.bytes_freed += entry.bytes;
+        report.removed.push(entry.name);
+    }
+    Ok(report)
+}
+
+/// Replace goldens with slimmed copies of the newest passing run of their
+/// scenario recorded in the run index at `root`.
+///
+/// `names` selects goldens by name; when empty, every golden is considered.
+/// Goldens without a newer passing run are skipped. Each update is appended
+/// to `goldens.jsonl`.
+///
+/// # Errors
+/// - `E_PROTOCOL`: A name in `names` is not a golden under `baseline`
+/// - The errors of [`read_run_index`] and
+///   [`slim_artifacts`](super::slim_artifacts), or `E_IO` when a golden
+///   cannot be replaced
+pub fn update_goldens(
+    baseline: &Path,
+    root: &Path,
+    names: &[String],
+) -> RunnerResult<GoldenUpdateReport> {
+    let goldens = read_goldens(baseline)?;
+    if let Some(missing) = names
+        .iter()
+        .find(|name| !goldens.iter().any(|golden| &golden.name == *name))
+    {
+        return Err(RunnerError::with_context(
+            ErrorCode::Protocol,
+            "no golden with this name",
+            serde_json::json!({ "name": missing, "baseline": baseline }),
+        ));
+    }
+    let runs = read_run_index(root)?;
+    let mut report = GoldenUpdateReport::default();
+    for golden in goldens
+        .into_iter()
+        .filter(|golden| names.is_empty() || names.contains(&golden.name))
+    {
+        let newest = runs.iter().rev().find(|run| {
+            run.status == RunStatus::Passed
+                && run.scenario.as_deref() == Some(golden.scenario.as_str())
+                && Path::new(&run.artifacts_dir).join("run.json").is_file()
+        });
+        match newest {
+            Some(run) if run.run_id == golden.run_id => report.skipped.push(GoldenSkip {
+                name: golden.name,
+                reason: "already recorded from the newest passing run".to_string(),
+            }),
+            Some(run) => {
+                let update = replace_golden(baseline, &golden, run.run_id, &run.artifacts_dir)?;
+                append_history(baseline, &update)?;
+                report.updated.push(update);
+            }
+            None => report.skipped.push(GoldenSkip {
+                name: golden.name,
+                reason: format!("no passing run of scenario '{}' indexed", golden.scenario),
+            }),
+        }
+    }
+    Ok(report)
+}
+
+fn replace_golden(
+    baseline: &Path,
+    golden: &Golden,
+    run_id: RunId,
+    source: &str,
+) -> RunnerResult<GoldenUpdate> {
+    let before = snapshot_lines(&golden.dir)?;
+    let staging = baseline.join(format!(".{}.update", golden.name));
+    slim_artifacts(Path::new(source), &staging, true)?;
+    let after = snapshot_lines(&staging)?;
+    fs::remove_dir_all(&golden.dir)
+        .map_err(|err| RunnerError::io("E_IO", "failed to remove old golden", err))?;
+    fs::rename(&staging, &golden.dir)
+        .map_err(|err| RunnerError::io("E_IO", "failed to move updated golden", err))?;
+
+    let mut update = GoldenUpdate {
+        name: golden.name.clone(),
+        scenario: golden.scenario.clone(),
+        previous_run_id: golden.run_id,
+        run_id,
+        source: source.to_string(),
+        snapshots_added: 0,
+        snapshots_removed: 0,
+        snapshots_changed: 0,
+        lines_changed: 0,
+        updated_at_ms: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX))
+            .unwrap_or(0),
+    };
+    for (path, old) in &before {
+        match after.get(path) {
+            Some(new) => {
+                let differing = (0..old.len().max(new.len()))
+                    .filter(|&index| old.get(index) != new.get(index))
+                    .count() as u64;
+                if differing > 0 {
+                    update.snapshots_changed += 1;
+                    update.lines_changed += differing;
+                }
+            }
+            None => {
+                update.snapshots_removed += 1;
+                update.lines_changed += old.len() as u64;
+            }
+        }
+    }
+    for (path, new) in &after {
+        if !before.contains_key(path) {
+            update.snapshots_added += 1;
+            update.lines_changed += new.len() as u64;
+        }
+    }
+    Ok(update)
+}
+
+/// Goldens under `baseline` in name order: subdirectories (not starting
+/// with `.`) holding `scenario.json` and `run.json`.
+fn read_goldens(baseline: &Path) -> RunnerResult<Vec<Golden>> {
+    let entries = fs::read_dir(baseline)
+        .map_err(|err| RunnerError::io("E_IO", "failed to read baseline directory", err))?;
+    let mut goldens = Vec::new();
+    for entry in entries.filter_map(Result::ok) {
+        let dir = entry.path();
+        let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        if name.starts_with('.')
+            || !dir.join("scenario.json").is_file()
+            || !dir.join("run.json").is_file()
+        {
+            continue;
+        }
+        let scenario_path = dir.join("scenario.json");
+        let scenario = read_json::<ScenarioHeader>(&scenario_path)?.into_name(&scenario_path)?;
+        let run: RunHeader = read_json(&dir.join("run.json"))?;
+        goldens.push(Golden {
+            name,
+            dir,
+            scenario,
+            run_id: run.run_id,
+        });
+    }
+    goldens.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(goldens)
+}
+
+/// Scenario names found in `paths`, each with the files that declare it.
+/// Files inside `baseline` and files that are not scenarios are ignored.
+///
+/// # Errors
+/// - `E_IO`: A path does not exist, or a file or directory cannot be read
+/// - `E_PROTOCOL`: A scenario file does not parse
+fn scenario_names(
+    baseline: &Path,
+    paths: &[PathBuf],
+) -> RunnerResult<BTreeMap<String, Vec<String>>> {
+    let baseline = baseline.canonicalize().unwrap_or_else(|_| baseline.into());
+    let mut names: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for path in paths {
+        if !path.exists() {
+            return Err(RunnerError::with_context(
+                ErrorCode::Io,
+                "scenario path does not exist",
+                serde_json::json!({ "path": path }),
+            ));
+        }
+    }
+    let mut pending: Vec<PathBuf> = paths.to_vec();
+    while let Some(path) = pending.pop() {
+        if path
+            .canonicalize()
+            .is_ok_and(|real| real.starts_with(&baseline))
+        {
+            continue;
+        }
+        if path.is_dir() {
+            let entries = fs::read_dir(&path)
+                .map_err(|err| RunnerError::io("E_IO", "failed to read scenarios dir", err))?;
+            pending.extend(entries.filter_map(|entry| entry.ok().map(|entry| entry.path())));
+            continue;
+        }
+        if let Some(name) = scenario_name(&path)? {
+            names
+                .entry(name)
+                .or_default()
+                .push(path.display().to_string());
+        }
+    }
+    for files in names.values_mut() {
+        files.sort();
+    }
+    Ok(names)
+}
+
+/// `metadata.name` of a scenario file, or `None` for anything else: files
+/// without a `.json`, `.yaml`, or `.yml` extension, and documents without a
+/// `scenario_version`.
+///
+/// # Errors
+/// - `E_IO`: The file cannot be read
+/// - `E_PROTOCOL`: The file does not parse, or it declares a
+///   `scenario_version` but no `metadata.name` or an unsupported version
+pub(crate) fn scenario_name(path: &Path) -> RunnerResult<Option<String>> {
+    let Some(extension) = path.extension().and_then(|ext| ext.to_str()) else {
+        return Ok(None);
+    };
+    if !matches!(extension, "json" | "yaml" | "yml") {
+        return Ok(None);
+    }
+    let data = fs::read_to_string(path).map_err(|err| {
+        RunnerError::io("E_IO", format!("failed to read {}", path.display()), err)
+    })?;
+    let parse_error = |err: &dyn std::fmt::Display| {
+        RunnerError::with_context(
+            ErrorCode::Protocol,
+            format!("failed to parse {}", path.display()),
+            serde_json::json!({ "path": path, "error": err.to_string() }),
+        )
+    };
+    let document: serde_json::Value = if extension == "json" {
+        serde_json::from_str(&data).map_err(|err| parse_error(&err))?
+    } else {
+        serde_yml::from_str(&data).map_err(|err| parse_error(&err))?
+    };
+    if document.get("scenario_version").is_none() {
+        return Ok(None);
+    }
+    let header: ScenarioHeader =
+        serde_json::from_value(document).map_err(|err| parse_error(&err))?;
+    header.into_name(path).map(Some)
+}
+
+/// Screen lines of each snapshot under `dir`, keyed by path relative to
+/// `dir` without the encryption suffix.
+fn snapshot_lines(dir: &Path) -> RunnerResult<BTreeMap<String, Vec<String>>> {
+    let mut snapshots = BTreeMap::new();
+    for path in snapshot_paths(dir)? {
+        let data = read_artifact(&path, None)?;
+        let value: serde_json::Value = serde_json::from_slice(&data)
+            .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to parse snapshot", err))?;
+        let lines = value
+            .get("lines")
+            .and_then(serde_json::Value::as_array)
+            .map(|lines| {
+                lines
+                    .iter()
+                    .map(|line| line.as_str().unwrap_or_default().trim_end().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let relative = path.strip_prefix(dir).unwrap_or(&path).to_string_lossy();
+        let key = relative
+            .strip_suffix(ENCRYPTED_SUFFIX)
+            .unwrap_or(&relative)
+            .replace('\\', "/");
+        snapshots.insert(key, lines);
+    }
+    Ok(snapshots)
+}
+
+fn read_history(baseline: &Path) -> RunnerResult<Vec<GoldenUpdate>> {
+    let path = baseline.join(GOLDENS_LOG_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let data = fs::read_to_string(&path)
+        .map_err(|err| RunnerError::io("E_IO", "failed to read goldens log", err))?;
+    data.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(line_no, line)| {
+            serde_json::from_str(line).map_err(|err| {
+                RunnerError::io(
+                    "E_PROTOCOL",
+                    format!("failed to parse goldens log line {}", line_no + 1),
+                    err,
+                )
+            })
+        })
+        .collect()
+}
+
+fn append_history(baseline: &Path, update: &GoldenUpdate) -> RunnerResult<()> {
+    let mut line = serde_json::to_vec(update)
+        .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to serialize golden update", err))?;
+    line.push(b'\n');
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(baseline.join(GOLDENS_LOG_FILE))
+        .map_err(|err| RunnerError::io("E_IO", "failed to open goldens log", err))?;
+    file.write_all(&line)
+        .map_err(|err| RunnerError::io("E_IO", "failed to write goldens log", err))
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> RunnerResult<T> {
+    let data = fs::read(path).map_err(|err| {
+        RunnerError::io("E_IO", format!("failed to read {}", path.display()), err)
+    })?;
+    serde_json::from_slice(&data).map_err(|err| {
+        RunnerError::io(
+            "E_PROTOCOL",
+            format!("failed to parse {}", path.display()),
+            err,
+        )
+    })
+}
+
+fn dir_size(dir: &Path) -> RunnerResult<u64> {
+    let mut bytes = 0;
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = fs::read_dir(&dir)
+            .map_err(|err| RunnerError::io("E_IO", "failed to read golden", err))?;
+        for entry in entries.filter_map(Result::ok) {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else {
+                bytes += metadata.len();
+            }
+        }
+    }
+    Ok(bytes)
+}
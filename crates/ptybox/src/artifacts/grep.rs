@@ -0,0 +1,255 @@
+//! Search across indexed runs (`ptybox grep`).
+//!
+//! Runs are found through the run index at an artifacts root and searched
+//! newest first. Each run contributes matches from `transcript.log` (with
+//! escape sequences removed) and then from its snapshots' screen lines.
+//! Runs whose artifacts are gone or unreadable are reported as skipped
+//! instead of failing the search.
+
+use super::{artifact_path, read_artifact, read_run_index, snapshot_paths, ArtifactsDecryptor};
+use crate::model::{
+    GrepMatch, GrepReport, GrepSkip, GrepSource, RunIndexEntry, RunResult, ScreenSnapshot,
+    StepResult,
+};
+use crate::runner::{compile_safe_regex, RunnerResult};
+use regex::Regex;
+use std::fs;
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::str::Chars;
+
+/// Options for [`grep_runs`].
+#[derive(Clone, Debug, Default)]
+pub struct GrepOptions {
+    /// Regular expression (or literal text with `fixed_strings`).
+    pub pattern: String,
+    /// Match without regard to case.
+    pub ignore_case: bool,
+    /// Treat `pattern` as literal text.
+    pub fixed_strings: bool,
+    /// Lines of context kept before and after each match.
+    pub context: usize,
+    /// Age identity file for runs with encrypted artifacts.
+    pub identity: Option<PathBuf>,
+    /// `age` binary used with `identity` (default `/usr/bin/age`).
+    pub age_binary: Option<PathBuf>,
+}
+
+/// Search the transcripts and snapshots of every run indexed under `root`.
+///
+/// A snapshot's step comes from its `steps/` directory under the `per_step`
+/// layout; under the flat layout each step is assumed to own one snapshot
+/// per attempt, in order, which holds unless watchers tripped mid-run.
+///
+/// # Errors
+/// - `E_PROTOCOL`: `pattern` is not a valid regex, or the index does not parse
+/// - `E_IO`: The run index cannot be read
+pub fn grep_runs(root: &Path, options: &GrepOptions) -> RunnerResult<GrepReport> {
+    let pattern = if options.fixed_strings {
+        regex::escape(&options.pattern)
+    } else {
+        options.pattern.clone()
+    };
+    let pattern = if options.ignore_case {
+        format!("(?i){pattern}")
+    } else {
+        pattern
+    };
+    let regex = compile_safe_regex(&pattern)?;
+
+    let mut report = GrepReport::default();
+    for entry in read_run_index(root)?.into_iter().rev() {
+        let skip = |reason: String| GrepSkip {
+            run_id: entry.run_id,
+            artifacts_dir: entry.artifacts_dir.clone(),
+            reason,
+        };
+        let Some(dir) = run_dir(root, &entry.artifacts_dir) else {
+            report
+                .skipped
+                .push(skip("artifacts directory not found".to_string()));
+            continue;
+        };
+        match grep_run(&entry, &dir, &regex, options) {
+            Ok(matches) => {
+                report.runs_searched += 1;
+                report.matches.extend(matches);
+            }
+            Err(err) => report.skipped.push(skip(err.message)),
+        }
+    }
+    Ok(report)
+}
+
+/// The run's artifacts directory: as recorded, or by name under `root` when
+/// the recorded path was relative to another working directory.
+fn run_dir(root: &Path, recorded: &str) -> Option<PathBuf> {
+    let recorded = PathBuf::from(recorded);
+    if recorded.is_dir() {
+        return Some(recorded);
+    }
+    recorded
+        .file_name()
+        .map(|name| root.join(name))
+        .filter(|dir| dir.is_dir())
+}
+
+fn grep_run(
+    entry: &RunIndexEntry,
+    dir: &Path,
+    regex: &Regex,
+    options: &GrepOptions,
+) -> RunnerResult<Vec<GrepMatch>> {
+    let decryptor = options
+        .identity
+        .as_ref()
+        .map(|identity| ArtifactsDecryptor::new(identity, options.age_binary.clone()));
+    let found = |source, step: Option<&String>, snapshot: Option<&str>, hit: Hit| GrepMatch {
+        run_id: entry.run_id,
+        artifacts_dir: entry.artifacts_dir.clone(),
+        source,
+        step: step.cloned(),
+        snapshot: snapshot.map(str::to_string),
+        line: hit.line,
+        text: hit.text,
+        before: hit.before,
+        after: hit.after,
+    };
+    let mut matches = Vec::new();
+
+    let transcript = artifact_path(dir, "transcript.log");
+    if transcript.is_file() {
+        let data = read_artifact(&transcript, decryptor.as_ref())?;
+        let text = strip_escapes(&String::from_utf8_lossy(&data));
+        let lines: Vec<&str> = text.lines().collect();
+        for hit in search(&lines, regex, options.context) {
+            matches.push(found(GrepSource::Transcript, None, None, hit));
+        }
+    }
+
+    let steps = fs::read(dir.join("run.json"))
+        .ok()
+        .and_then(|data| serde_json::from_slice::<RunResult>(&data).ok())
+        .and_then(|run| run.steps)
+        .unwrap_or_default();
+    let paths = snapshot_paths(dir)?;
+    for (path, step) in paths.iter().zip(snapshot_steps(dir, &paths, &steps)) {
+        let data = read_artifact(path, decryptor.as_ref())?;
+        let Ok(snapshot) = serde_json::from_slice::<ScreenSnapshot>(&data) else {
+            continue;
+        };
+        let name = snapshot_name(dir, path);
+        let lines: Vec<&str> = snapshot.lines.iter().map(|line| line.trim_end()).collect();
+        for hit in search(&lines, regex, options.context) {
+            matches.push(found(GrepSource::Snapshot, step.as_ref(), Some(&name), hit));
+        }
+    }
+    Ok(matches)
+}
+
+/// A matching line with its context.
+struct Hit {
+    line: u64,
+    text: String,
+    before: Vec<String>,
+    after: Vec<String>,
+}
+
+fn search(lines: &[&str], regex: &Regex, context: usize) -> Vec<Hit> {
+    let owned = |range: &[&str]| range.iter().map(|line| (*line).to_string()).collect();
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| regex.is_match(line))
+        .map(|(index, line)| Hit {
+            line: index as u64 + 1,
+            text: (*line).to_string(),
+            before: lines
+                .get(index.saturating_sub(context)..index)
+                .map_or_else(Vec::new, owned),
+            after: lines
+                .get(index + 1..lines.len().min(index + 1 + context))
+                .map_or_else(Vec::new, owned),
+        })
+        .collect()
+}
+
+/// Step name for each snapshot in `paths`.
+fn snapshot_steps(dir: &Path, paths: &[PathBuf], steps: &[StepResult]) -> Vec<Option<String>> {
+    let mut flat = steps.iter().flat_map(|step| {
+        std::iter::repeat(&step.name).take(usize::try_from(step.attempts).unwrap_or(0))
+    });
+    let steps_dir = dir.join("steps");
+    paths
+        .iter()
+        .map(|path| match path.strip_prefix(&steps_dir) {
+            Ok(relative) => relative
+                .iter()
+                .next()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.split('-').next())
+                .and_then(|index| index.parse::<usize>().ok())
+                .and_then(|index| steps.get(index.checked_sub(1)?))
+                .map(|step| step.name.clone()),
+            Err(_) => flat.next().cloned(),
+        })
+        .collect()
+}
+
+/// Snapshot path relative to the artifacts directory, without `.age`.
+fn snapshot_name(dir: &Path, path: &Path) -> String {
+    let relative = path
+        .strip_prefix(dir)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/");
+    relative
+        .strip_suffix(super::ENCRYPTED_SUFFIX)
+        .map_or_else(|| relative.clone(), str::to_string)
+}
+
+/// Remove escape sequences, carriage returns, and other control characters
+/// (except newlines and tabs) from raw terminal output.
+pub(crate) fn strip_escapes(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\x1b' => skip_escape(&mut chars),
+            '\n' | '\t' => out.push(ch),
+            ch if ch.is_control() => {}
+            ch => out.push(ch),
+        }
+    }
+    out
+}
+
+/// Skip the rest of an escape sequence whose `ESC` was just read.
+fn skip_escape(chars: &mut Peekable<Chars<'_>>) {
+    match chars.next() {
+        // CSI: parameters up to a final byte in `@`..=`~`.
+        Some('[') => {
+            for ch in chars.by_ref() {
+                if ('@'..='~').contains(&ch) {
+                    break;
+                }
+            }
+        }
+        // OSC, DCS, and friends: up to BEL or ST (`ESC \`).
+        Some(']' | 'P' | '_' | '^') => {
+            while let Some(ch) = chars.next() {
+                if ch == '\x07' {
+                    break;
+                }
+                if ch == '\x1b' && chars.next_if_eq(&'\\').is_some() {
+                    break;
+                }
+            }
+        }
+        // Character set designation takes one more character.
+        Some('(' | ')' | '*' | '+') => {
+            chars.next();
+        }
+        _ => {}
+    }
+}
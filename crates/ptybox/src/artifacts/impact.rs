@@ -0,0 +1,157 @@
+//! Test impact analysis: the scenarios a set of changed files affects.
+//!
+//! An [`ImpactMap`] lists the globs each scenario watches. A scenario is
+//! selected when a changed file matches one of them, and skipped otherwise.
+//! Globs are path-aware: `*` stays within a directory and `**` crosses
+//! directories.
+//! Missing mappings fall back to running: without a map every scenario runs,
+//! and a scenario the map does not mention runs as well. A changed file
+//! matching an `always` glob selects the whole suite.
+
+use super::estimate::scenario_files;
+use super::goldens::scenario_name;
+use crate::model::{
+    ImpactMap, ImpactMatch, ScenarioSelection, SelectionReason, SuiteSelection, IMPACT_MAP_VERSION,
+};
+use crate::runner::{ErrorCode, RunnerError, RunnerResult};
+use crate::util::path_glob_matches;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Select the scenarios in `paths` (files or directories searched for
+/// `.json`, `.yaml`, and `.yml` scenario files) that `changed_files`
+/// affect under `map`.
+///
+/// Files that are not scenarios are skipped.
+///
+/// # Errors
+/// - `E_IO`: A path does not exist or a file or directory cannot be read
+/// - `E_PROTOCOL`: A scenario file does not parse
+pub fn select_scenarios(
+    paths: &[PathBuf],
+    changed_files: &[String],
+    map: Option<&ImpactMap>,
+) -> RunnerResult<SuiteSelection> {
+    let always = map.map_or_else(Vec::new, |map| matches(&map.always, changed_files));
+    let full_run = map.is_none() || !always.is_empty();
+    let mut selection = SuiteSelection {
+        full_run,
+        changed_files: changed_files.to_vec(),
+        ..SuiteSelection::default()
+    };
+    let mut seen = BTreeSet::new();
+    for path in scenario_files(paths)? {
+        let Some(scenario) = scenario_name(&path)? else {
+            continue;
+        };
+        let watched = map.and_then(|map| map.scenarios.get(&scenario));
+        let (reason, matched) = match (map, watched) {
+            (None, _) => (SelectionReason::NoImpactMap, Vec::new()),
+            (Some(_), _) if !always.is_empty() => (SelectionReason::Always, always.clone()),
+            (Some(_), None) => (SelectionReason::Unmapped, Vec::new()),
+            (Some(_), Some(globs)) => {
+                let matched = matches(globs, changed_files);
+                if matched.is_empty() {
+                    (SelectionReason::NotAffected, matched)
+                } else {
+                    (SelectionReason::Matched, matched)
+                }
+            }
+        };
+        let selected = reason != SelectionReason::NotAffected;
+        if selected {
+            selection.selected += 1;
+        } else {
+            selection.skipped += 1;
+        }
+        seen.insert(scenario.clone());
+        selection.scenarios.push(ScenarioSelection {
+            scenario,
+            path: path.display().to_string(),
+            selected,
+            reason,
+            matches: matched,
+        });
+    }
+    if let Some(map) = map {
+        selection.unknown_mappings = map
+            .scenarios
+            .keys()
+            .filter(|name| !seen.contains(*name))
+            .cloned()
+            .collect();
+    }
+    Ok(selection)
+}
+
+/// Load an impact map from a JSON or YAML (`.yaml`/`.yml`) file.
+///
+/// # Errors
+/// - `E_IO`: The file cannot be read
+/// - `E_PROTOCOL`: The file does not parse or has an unsupported
+///   `impact_map_version`
+pub fn load_impact_map(path: &Path) -> RunnerResult<ImpactMap> {
+    let data = fs::read_to_string(path)
+        .map_err(|err| RunnerError::io("E_IO", "failed to read impact map", err))?;
+    let yaml = path
+        .extension()
+        .is_some_and(|ext| ext == "yaml" || ext == "yml");
+    let parsed = if yaml {
+        serde_yml::from_str::<ImpactMap>(&data).map_err(|err| err.to_string())
+    } else {
+        serde_json::from_str::<ImpactMap>(&data).map_err(|err| err.to_string())
+    };
+    let map = parsed.map_err(|err| {
+        RunnerError::with_context(
+            ErrorCode::Protocol,
+            "failed to parse impact map",
+            serde_json::json!({ "path": path, "error": err }),
+        )
+    })?;
+    if map.impact_map_version != IMPACT_MAP_VERSION {
+        return Err(RunnerError::with_context(
+            ErrorCode::Protocol,
+            "unsupported impact map version",
+            serde_json::json!({
+                "path": path,
+                "impact_map_version": map.impact_map_version,
+                "supported_version": IMPACT_MAP_VERSION
+            }),
+        ));
+    }
+    Ok(map)
+}
+
+/// Read a changed-files list: one path per line, as printed by
+/// `git diff --name-only`. Blank lines are ignored and a leading `./` is
+/// dropped.
+///
+/// # Errors
+/// - `E_IO`: The file cannot be read
+pub fn read_changed_files(path: &Path) -> RunnerResult<Vec<String>> {
+    let data = fs::read_to_string(path)
+        .map_err(|err| RunnerError::io("E_IO", "failed to read changed files list", err))?;
+    Ok(data
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| line.strip_prefix("./").unwrap_or(line).to_string())
+        .collect())
+}
+
+/// Each changed file matching one of `globs`, with the first glob it matched.
+fn matches(globs: &[String], changed_files: &[String]) -> Vec<ImpactMatch> {
+    changed_files
+        .iter()
+        .filter_map(|file| {
+            globs
+                .iter()
+                .find(|glob| path_glob_matches(glob, file))
+                .map(|glob| ImpactMatch {
+                    file: file.clone(),
+                    glob: glob.clone(),
+                })
+        })
+        .collect()
+}
@@ -0,0 +1,91 @@
+//! Local run index (`runs.jsonl`) kept at the artifacts root.
+//!
+//! Each completed run appends one [`RunIndexEntry`] line so runs can be found
+//! by tag and status without opening every `run.json`.
+
+use crate::model::{RunIndexEntry, RunResult};
+use crate::runner::{RunnerError, RunnerResult};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// File name of the run index inside the artifacts root.
+pub const RUN_INDEX_FILE: &str = "runs.jsonl";
+
+/// Build the index entry for `run_result` written to `artifacts_dir`.
+pub(crate) fn index_entry(run_result: &RunResult, artifacts_dir: &Path) -> RunIndexEntry {
+    let recorded_at_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX))
+        .unwrap_or(0);
+    RunIndexEntry {
+        run_id: run_result.run_id,
+        status: run_result.status.clone(),
+        tags: run_result.tags.clone(),
+        scenario: run_result
+            .scenario
+            .as_ref()
+            .map(|scenario| scenario.metadata.name.clone()),
+        command: run_result.command.clone(),
+        artifacts_dir: artifacts_dir.display().to_string(),
+        recorded_at_ms,
+        error_code: run_result.error.as_ref().map(|err| err.code.clone()),
+        duration_ms: Some(
+            run_result
+                .ended_at_ms
+                .saturating_sub(run_result.started_at_ms),
+        ),
+    }
+}
+
+/// Append `entry` to `root/runs.jsonl` as a single line.
+///
+/// The line is written with one `O_APPEND` write so concurrent runs sharing a
+/// root (such as a scenario queue) do not interleave entries.
+///
+/// # Errors
+/// Returns `E_IO` on write failure, `E_PROTOCOL` on serialization failure.
+pub(crate) fn append_run_index(root: &Path, entry: &RunIndexEntry) -> RunnerResult<()> {
+    let mut line = serde_json::to_vec(entry)
+        .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to serialize run index entry", err))?;
+    line.push(b'\n');
+    fs::create_dir_all(root)
+        .map_err(|err| RunnerError::io("E_IO", "failed to create run index dir", err))?;
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(root.join(RUN_INDEX_FILE))
+        .map_err(|err| RunnerError::io("E_IO", "failed to open run index", err))?;
+    file.write_all(&line)
+        .map_err(|err| RunnerError::io("E_IO", "failed to write run index", err))
+}
+
+/// Read every entry from `root/runs.jsonl`, oldest first.
+///
+/// A missing index yields an empty list.
+///
+/// # Errors
+/// - `E_IO`: Index exists but cannot be read
+/// - `E_PROTOCOL`: A line is not a valid [`RunIndexEntry`]
+pub fn read_run_index(root: &Path) -> RunnerResult<Vec<RunIndexEntry>> {
+    let path = root.join(RUN_INDEX_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let data = fs::read_to_string(&path)
+        .map_err(|err| RunnerError::io("E_IO", "failed to read run index", err))?;
+    data.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(line_no, line)| {
+            serde_json::from_str(line).map_err(|err| {
+                RunnerError::io(
+                    "E_PROTOCOL",
+                    format!("failed to parse run index line {}", line_no + 1),
+                    err,
+                )
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,66 @@
+//! Post-run listing of the write-allowlisted directories (`fs-manifest.json`).
+
+use crate::model::{FsManifest, FsManifestEntry, MAX_FS_MANIFEST_FILES};
+use crate::util::sha256_hex;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Files larger than this are listed without a hash.
+const MAX_HASHED_BYTES: u64 = 64 * 1024 * 1024;
+
+/// List the regular files under `roots` with their sizes and SHA-256
+/// hashes, without following symlinks and skipping `excluded` trees.
+///
+/// Roots that do not exist and entries that vanish or cannot be read while
+/// walking are skipped. Files nested under two roots are listed once.
+#[must_use]
+pub fn build_fs_manifest(roots: &[String], excluded: &[PathBuf]) -> FsManifest {
+    let mut files: BTreeMap<String, FsManifestEntry> = BTreeMap::new();
+    let mut truncated = false;
+    let mut pending: Vec<PathBuf> = roots.iter().map(PathBuf::from).collect();
+    while let Some(path) = pending.pop() {
+        if excluded.iter().any(|dir| path.starts_with(dir)) {
+            continue;
+        }
+        let Ok(metadata) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if metadata.is_dir() {
+            if let Ok(entries) = fs::read_dir(&path) {
+                pending.extend(entries.flatten().map(|entry| entry.path()));
+            }
+            continue;
+        }
+        if !metadata.is_file() {
+            continue;
+        }
+        let key = path.display().to_string();
+        if files.contains_key(&key) {
+            continue;
+        }
+        if files.len() >= MAX_FS_MANIFEST_FILES {
+            truncated = true;
+            continue;
+        }
+        files.insert(
+            key.clone(),
+            FsManifestEntry {
+                path: key,
+                size: metadata.len(),
+                sha256: file_hash(&path, metadata.len()),
+            },
+        );
+    }
+    FsManifest {
+        files: files.into_values().collect(),
+        truncated,
+    }
+}
+
+fn file_hash(path: &Path, size: u64) -> Option<String> {
+    if size > MAX_HASHED_BYTES {
+        return None;
+    }
+    fs::read(path).ok().map(|data| sha256_hex(&data))
+}
@@ -12,11 +12,63 @@
 //! | `policy.json` | Effective [`Policy`] used for the run |
 //! | `scenario.json` | Resolved [`Scenario`] (including driver-generated) |
 //! | `transcript.log` | Raw terminal output (cumulative) |
+//! | `stderr.log` | Child stderr (only with `separate_stderr`, created on first write) |
 //! | `events.jsonl` | NDJSON stream of [`Observation`](crate::model::Observation) records |
 //! | `snapshots/*.json` | Sequential [`ScreenSnapshot`] captures |
+//! | `steps/<index>-<name>/` | Per-step `snapshots/` and `events.jsonl` (`per_step` layout) |
+//! | `captures/<name>.txt` | Step output or screen kept by a step's `capture` |
 //! | `normalization.json` | Applied normalization filters for replay |
 //! | `checksums.json` | FNV-1a checksums for integrity verification |
+//! | `journal.jsonl` | [`JournalEntry`] per run lifecycle event, for [`recover_run`] |
+//! | `raw-input.jsonl` | [`RawInputRecord`](crate::model::RawInputRecord) per `raw` action |
+//! | `key-holds.jsonl` | [`KeyHoldRecord`](crate::model::KeyHoldRecord) per `hold_key` action |
+//! | `termios.jsonl` | [`TermiosRecord`](crate::model::TermiosRecord) per `termios` action |
 //! | `sandbox.sb` | Seatbelt profile (when sandbox is enabled) |
+//! | `container.json` | [`ContainerInvocation`] (container sandbox) |
+//! | `enforcement.json` | [`EnforcementReport`] (when the Seatbelt fallback is used) |
+//! | `acknowledgements.json` | [`AcknowledgementReport`] (when an unsafe option is acknowledged) |
+//! | `violations.json` | [`SandboxViolationReport`] (sandbox auditing, feature `sandbox-audit`) |
+//! | `emulator-diff.json` | [`EmulatorDiffReport`] (second emulator backend, feature `emulator-diff`) |
+//! | `resources.json` | [`ResourceUsage`] (when the policy sets `fs.write_quotas`) |
+//! | `fs-manifest.json` | [`FsManifest`] of `fs.allowed_write` after exit (when the policy sets `artifacts.fs_manifest`) |
+//! | `session.cast` | asciinema v2 recording of the output (when the policy sets `artifacts.asciicast`; not written for encrypted artifacts) |
+//! | `policy-suggestions.json` | [`PolicySuggestions`](crate::model::PolicySuggestions), added afterwards by `ptybox policy suggest` |
+//!
+//! When encryption is configured ([`ArtifactsWriter::set_encryption`]),
+//! `transcript.log`, `stderr.log`, `events.jsonl`, snapshots, and `emulator-diff.json`
+//! are written as `*.age`
+//! files and checksummed as ciphertext; read them back with
+//! [`read_artifact`] and an [`ArtifactsDecryptor`]. The plaintext `run.json`
+//! and `journal.jsonl` then omit the final observation, assertion details,
+//! error contexts, and step variables.
+//!
+//! When a run index root is configured ([`ArtifactsWriter::set_run_index`]),
+//! [`ArtifactsWriter::write_run_result`] also appends a
+//! [`RunIndexEntry`](crate::model::RunIndexEntry) to `<root>/runs.jsonl`;
+//! query it with [`read_run_index`], or search the indexed runs' transcripts
+//! and snapshots with [`grep_runs`].
+//!
+//! A root that holds a quota ledger ([`set_artifacts_quota`]) also tracks
+//! the size of every run written under it and evicts the oldest completed
+//! runs to stay under a byte quota shared by concurrent runs; see
+//! [`artifacts_status`].
+//!
+//! [`attest_artifacts`] builds an in-toto style statement over every file in
+//! a finished run's directory, for supply-chain tooling.
+//! [`recover_run`] rebuilds `run.json` from `journal.jsonl` for a run whose
+//! harness was killed before writing it.
+//! [`slim_artifacts`] copies just the files replay reads into a new
+//! directory, with fresh checksums, for cheaper uploads.
+//! [`list_goldens`], [`prune_goldens`], and [`update_goldens`] manage a
+//! directory of such slimmed baselines kept as golden screens.
+//! [`estimate_scenarios`] predicts how long a set of scenarios takes from
+//! their step timeouts and the durations recorded in a run index, and
+//! [`select_scenarios`] which of them a set of changed files affects.
+//!
+//! JSON artifacts and `checksums.json` are written in canonical form
+//! ([`to_canonical_json`]: sorted keys, trailing newline) so checked-in
+//! baselines diff cleanly; [`ArtifactsWriter::set_canonical_json`] turns
+//! this off, and [`reformat_artifacts`] converts an existing run.
 //!
 //! # Key Types
 //!
@@ -27,15 +79,77 @@
 //!
 //! JSON artifacts are written atomically via write-to-temp + rename to
 //! prevent partial writes from leaving corrupt files on interruption.
+//!
+//! # Background I/O
+//!
+//! File I/O runs on a background thread fed by a bounded queue, in the order
+//! the writes were made, so a step does not wait on the disk between
+//! observations. The queue is drained and streaming artifacts (transcript
+//! and event logs) are flushed at step boundaries
+//! ([`ArtifactsWriter::begin_step`], [`ArtifactsWriter::end_step`]) and by
+//! [`ArtifactsWriter::flush_checksums`]; a failed write is returned from the
+//! next of these. [`ArtifactsWriter::set_background_io`] switches to writing
+//! inline.
+
+mod asciicast;
+mod attest;
+mod background;
+mod bundle;
+mod canonical;
+mod differs;
+mod encrypt;
+mod estimate;
+mod goldens;
+pub(crate) mod grep;
+mod impact;
+mod index;
+mod manifest;
+pub(crate) mod quota;
+mod recover;
+mod redact;
+mod slim;
+mod tarball;
+mod truncate;
 
-use crate::model::{NormalizationRecord, Policy, RunId, RunResult, Scenario, ScreenSnapshot};
+pub use asciicast::{asciicast_from_artifacts, AsciicastEncoder, ASCIICAST_FILE};
+pub use attest::attest_artifacts;
+pub use bundle::{bundle_artifacts, BundleOptions, DEFAULT_TRANSCRIPT_TAIL_BYTES};
+pub use canonical::{reformat_artifacts, to_canonical_json};
+pub use differs::why_differs;
+pub use encrypt::{artifact_path, read_artifact, ArtifactsDecryptor, ENCRYPTED_SUFFIX};
+pub use estimate::{estimate_scenarios, ESTIMATE_HISTORY_RUNS};
+pub use goldens::{list_goldens, prune_goldens, update_goldens, GOLDENS_LOG_FILE};
+pub use grep::{grep_runs, GrepOptions};
+pub use impact::{load_impact_map, read_changed_files, select_scenarios};
+pub use index::{read_run_index, RUN_INDEX_FILE};
+pub use manifest::build_fs_manifest;
+pub use quota::{artifacts_status, set_artifacts_quota, QUOTA_LEDGER_FILE, QUOTA_LOCK_FILE};
+pub use recover::{recover_run, JOURNAL_FILE};
+pub use slim::slim_artifacts;
+
+use crate::model::policy::{
+    ArtifactsEncryption, ArtifactsLayout, TranscriptPolicy, TranscriptTruncate,
+};
+use crate::model::{
+    AcknowledgementReport, Action, ArgsProbeRecord, AssertionResult, ContainerInvocation,
+    EmulatorDiffReport, EnforcementReport, EnvRecord, ErrorInfo, FsManifest, HostFingerprint,
+    JournalEntry, JournalEvent, NormalizationRecord, Policy, RemoteHost, ResourceUsage, RunId,
+    RunResult, RunStatus, SandboxViolationReport, Scenario, ScreenSample, ScreenSnapshot, StepId,
+    StepResult,
+};
+use crate::policy::container::CONTAINER_INVOCATION_FILE;
 use crate::runner::{RunnerError, RunnerResult};
-use crate::util::{compute_checksum, fnv1a_hash_incremental, FnvHashState};
+use crate::util::{
+    compute_checksum, fnv1a_hash, fnv1a_hash_incremental, sha256_hex, FnvHashState, Sha256,
+};
+use background::{IoJob, IoQueue, IoWorker, Stream};
+use encrypt::{AgeEncryptor, AgeStream};
 use serde::Serialize;
 use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::io::Write;
+use std::io::{self, BufWriter, Write};
 use std::path::{Path, PathBuf};
+use truncate::HeadTailBuffer;
 
 /// Configuration for the artifacts writer.
 ///
@@ -54,32 +168,127 @@ pub struct ArtifactsWriterConfig {
 ///
 /// Maintains open file handles for streaming artifacts (transcript and events)
 /// and tracks checksums for integrity verification. Checksums are flushed
-/// lazily to reduce I/O overhead.
+/// lazily to reduce I/O overhead. Writes are persisted by a background thread
+/// (see [Background I/O](self#background-io)).
 ///
 /// The writer implements [`Drop`] to flush pending checksums and file handles
 /// on cleanup, ensuring artifact integrity even on early exit.
 pub struct ArtifactsWriter {
     dir: PathBuf,
-    transcript: fs::File,
-    events: fs::File,
+    /// Persists writes; owns the open streaming sinks.
+    io: IoQueue,
+    /// Head/tail window when transcript truncation is enabled.
+    transcript_window: Option<HeadTailBuffer>,
+    /// Whether `stderr.log` is open (it is created on the first write).
+    stderr_open: bool,
+    layout: ArtifactsLayout,
+    /// Serialize JSON artifacts in canonical form.
+    canonical_json: bool,
+    /// Encryptor for sensitive artifacts, when encryption is enabled.
+    encryption: Option<AgeEncryptor>,
+    /// Directory holding `runs.jsonl`, when run indexing is enabled.
+    run_index: Option<PathBuf>,
+    /// Quota ledger the run was admitted into, completed on drop.
+    quota: Option<QuotaTracking>,
+    /// Current step directory relative to the artifacts root (`per_step`
+    /// layout only); its event log is [`Stream::StepEvents`].
+    step: Option<String>,
     snapshot_count: usize,
+    sample_count: usize,
+    /// Transcript output collected for a step capture, while one is open.
+    capture: Option<String>,
+    /// SHA-256 of the step captures written and, when tracked, the
+    /// transcript.
+    capture_digests: CaptureDigests,
+    /// Running SHA-256 of the transcript output, when tracked.
+    transcript_sha256: Option<Sha256>,
     checksums: BTreeMap<String, String>,
     /// Track whether checksums need to be written (dirty flag for batching)
     checksums_dirty: bool,
     /// Incremental hash state for streaming files (transcript, events)
     incremental_hashes: HashMap<String, FnvHashState>,
+    /// Encoder for `session.cast`, when the cast is written.
+    asciicast: Option<AsciicastEncoder>,
+    /// Step actions as written in the scenario, before variables are
+    /// substituted; restored in plaintext step results of encrypted runs.
+    scenario_actions: HashMap<StepId, Action>,
+}
+
+/// SHA-256 digests of a run's captured output, checked by
+/// `capture_checksum_equals` assertions.
+///
+/// Digests cover the plaintext, so they match `sha256sum` of an unencrypted
+/// capture file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CaptureDigests {
+    /// Lowercase hex digest of each step capture written, by capture name.
+    pub captures: BTreeMap<String, String>,
+    /// Lowercase hex digest of the run's transcript output, when tracked
+    /// ([`ArtifactsWriter::track_transcript_sha256`]).
+    pub transcript: Option<String>,
+}
+
+/// A run admitted into the quota ledger at `root`.
+struct QuotaTracking {
+    root: PathBuf,
+    run_id: RunId,
+    /// Status from the last `run.json` written.
+    status: Option<RunStatus>,
+}
+
+/// Append-only artifact file, written directly or piped through `age`.
+enum StreamSink {
+    Plain(BufWriter<fs::File>),
+    Encrypted(AgeStream),
+}
+
+impl StreamSink {
+    /// Complete an encrypted stream; plaintext streams are left open.
+    fn finish(&mut self) -> RunnerResult<()> {
+        match self {
+            Self::Plain(_) => Ok(()),
+            Self::Encrypted(stream) => stream.finish(),
+        }
+    }
+}
+
+impl Write for StreamSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(file) => file.write(buf),
+            Self::Encrypted(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Plain(file) => file.flush(),
+            Self::Encrypted(stream) => stream.flush(),
+        }
+    }
 }
 
 impl Drop for ArtifactsWriter {
     fn drop(&mut self) {
-        // Best-effort flush of file handles before close
-        let _ = self.transcript.flush();
-        let _ = self.events.flush();
+        // Best-effort flush of file handles before close; dropping `io`
+        // afterwards stops the I/O thread.
+        let _ = self.finish_transcript();
+        let _ = self.finish_encrypted_streams();
 
         // Write final checksums if dirty (batched writes optimization)
         if self.checksums_dirty {
             let _ = self.write_checksums_internal();
         }
+
+        if let Some(quota) = self.quota.take() {
+            // Measure the run only once its writes are on disk.
+            let _ = self.io.sync();
+            if let Err(err) =
+                quota::complete_run(&quota.root, quota.run_id, &self.dir, quota.status)
+            {
+                tracing::warn!(root = %quota.root.display(), error = %err, "failed to record run in quota ledger");
+            }
+        }
     }
 }
 
@@ -87,12 +296,13 @@ impl ArtifactsWriter {
     /// Create a new artifacts writer for the given run.
     ///
     /// Creates the output directory (and `snapshots/` subdirectory) if needed.
-    /// Opens file handles for `transcript.log` and `events.jsonl`.
+    /// Opens file handles for `transcript.log` and `events.jsonl` and starts
+    /// the background I/O thread.
     ///
     /// # Errors
     ///
     /// - `E_POLICY_DENIED` if the directory exists and `overwrite` is false
-    /// - `E_IO` if directory creation or file open fails
+    /// - `E_IO` if directory creation, file open, or starting the thread fails
     pub fn new(_run_id: RunId, config: ArtifactsWriterConfig) -> RunnerResult<Self> {
         if config.dir.exists() {
             if !config.overwrite {
@@ -112,17 +322,192 @@ impl ArtifactsWriter {
         let events_path = config.dir.join("events.jsonl");
         let events = fs::File::create(&events_path)
             .map_err(|err| RunnerError::io("E_IO", "failed to create events log", err))?;
+        let mut io = IoQueue::spawn(IoWorker::default())?;
+        io.submit(IoJob::Open(
+            Stream::Transcript,
+            StreamSink::Plain(BufWriter::new(transcript)),
+        ))?;
+        io.submit(IoJob::Open(
+            Stream::Events,
+            StreamSink::Plain(BufWriter::new(events)),
+        ))?;
         Ok(Self {
             dir: config.dir,
-            transcript,
-            events,
+            io,
+            transcript_window: None,
+            stderr_open: false,
+            layout: ArtifactsLayout::Flat,
+            canonical_json: true,
+            encryption: None,
+            run_index: None,
+            quota: None,
+            step: None,
             snapshot_count: 0,
+            sample_count: 0,
+            capture: None,
+            capture_digests: CaptureDigests::default(),
+            transcript_sha256: None,
             checksums: BTreeMap::new(),
             checksums_dirty: false,
             incremental_hashes: HashMap::new(),
+            asciicast: None,
+            scenario_actions: HashMap::new(),
         })
     }
 
+    /// Select the on-disk layout for snapshots and observations.
+    ///
+    /// Takes effect from the next [`begin_step`](Self::begin_step).
+    pub fn set_layout(&mut self, layout: ArtifactsLayout) {
+        self.layout = layout;
+    }
+
+    /// Choose between canonical JSON (the default) and `serde_json`'s plain
+    /// pretty printing, which keeps fields in declaration order.
+    ///
+    /// Applies to JSON files and `checksums.json` written afterwards;
+    /// JSONL logs are unaffected.
+    pub fn set_canonical_json(&mut self, canonical_json: bool) {
+        self.canonical_json = canonical_json;
+    }
+
+    /// Encrypt the transcript, event logs, and snapshots for `config`'s
+    /// recipients.
+    ///
+    /// Call before anything is written to the transcript or event log: the
+    /// plaintext `transcript.log` and `events.jsonl` are replaced with
+    /// `transcript.log.age` and `events.jsonl.age`.
+    ///
+    /// # Errors
+    /// Returns `E_IO` if the plaintext files cannot be removed or `age`
+    /// cannot be started.
+    pub fn set_encryption(&mut self, config: &ArtifactsEncryption) -> RunnerResult<()> {
+        let encryptor = AgeEncryptor::new(config);
+        for name in ["transcript.log", "events.jsonl"] {
+            fs::remove_file(self.dir.join(name))
+                .map_err(|err| RunnerError::io("E_IO", "failed to remove plaintext log", err))?;
+            self.checksums.remove(name);
+            self.incremental_hashes.remove(name);
+        }
+        self.io.submit(IoJob::Open(
+            Stream::Transcript,
+            StreamSink::Encrypted(
+                encryptor.open_stream(&self.dir.join(encrypted_name("transcript.log")))?,
+            ),
+        ))?;
+        self.io.submit(IoJob::Open(
+            Stream::Events,
+            StreamSink::Encrypted(
+                encryptor.open_stream(&self.dir.join(encrypted_name("events.jsonl")))?,
+            ),
+        ))?;
+        self.encryption = Some(encryptor);
+        Ok(())
+    }
+
+    /// Apply the transcript retention policy.
+    ///
+    /// With `head_tail`, only the first `head_kib` and last `tail_kib` of
+    /// output reach `transcript.log`; the tail and an elision marker are
+    /// written when the writer is flushed or dropped, so a run killed by a
+    /// budget still keeps the output leading up to the kill.
+    pub fn set_transcript(&mut self, policy: &TranscriptPolicy) {
+        self.transcript_window = match policy.truncate {
+            TranscriptTruncate::None => None,
+            TranscriptTruncate::HeadTail => Some(HeadTailBuffer::new(policy)),
+        };
+    }
+
+    /// Also write each observation's output to `session.cast`, an asciinema
+    /// v2 recording (see [`AsciicastEncoder`]).
+    ///
+    /// The cast holds the output in plaintext, so it is not written once
+    /// [`set_encryption`](Self::set_encryption) is in effect; convert the
+    /// encrypted logs with [`asciicast_from_artifacts`] instead.
+    pub fn set_asciicast(&mut self, enabled: bool) {
+        self.asciicast = enabled.then(AsciicastEncoder::default);
+    }
+
+    /// Record each run result written by this writer in `root/runs.jsonl`.
+    pub fn set_run_index(&mut self, root: PathBuf) {
+        self.run_index = Some(root);
+    }
+
+    /// Record this run's final size and status in the quota ledger at
+    /// `root` when the writer is dropped.
+    ///
+    /// Used for runs admitted with the crate's quota admission, which must
+    /// happen before the writer creates its directory.
+    pub(crate) fn set_quota(&mut self, root: PathBuf, run_id: RunId) {
+        self.quota = Some(QuotaTracking {
+            root,
+            run_id,
+            status: None,
+        });
+    }
+
+    /// Persist writes on the background I/O thread (the default) or inline,
+    /// before each write call returns.
+    ///
+    /// Pending writes are completed first.
+    ///
+    /// # Errors
+    /// Returns the first error of a pending write, or `E_IO` if the thread
+    /// cannot be started or stopped.
+    pub fn set_background_io(&mut self, enabled: bool) -> RunnerResult<()> {
+        self.io.set_background(enabled)
+    }
+
+    /// Mark the start of step `index` (1-based).
+    ///
+    /// Completes and flushes writes made by the previous step. With the
+    /// `per_step` layout, subsequent snapshots and observations go to
+    /// `steps/<index>-<name>/` until [`end_step`](Self::end_step).
+    ///
+    /// # Errors
+    /// Returns the first error of a pending write, or `E_IO` if flushing or
+    /// creating the step directory fails.
+    pub fn begin_step(&mut self, index: usize, name: &str) -> RunnerResult<()> {
+        self.end_step()?;
+        if self.layout != ArtifactsLayout::PerStep {
+            return Ok(());
+        }
+        let dir = format!("steps/{index:03}-{}", step_dir_name(name));
+        fs::create_dir_all(self.dir.join(&dir))
+            .map_err(|err| RunnerError::io("E_IO", "failed to create step artifacts dir", err))?;
+        let events = match &self.encryption {
+            Some(encryptor) => StreamSink::Encrypted(
+                encryptor.open_stream(&self.dir.join(&dir).join(encrypted_name("events.jsonl")))?,
+            ),
+            None => StreamSink::Plain(BufWriter::new(
+                fs::File::create(self.dir.join(&dir).join("events.jsonl")).map_err(|err| {
+                    RunnerError::io("E_IO", "failed to create step events log", err)
+                })?,
+            )),
+        };
+        self.io.submit(IoJob::Open(Stream::StepEvents, events))?;
+        self.step = Some(dir);
+        Ok(())
+    }
+
+    /// Mark the end of the current step, waiting for its writes to complete
+    /// and flushing buffered streams.
+    ///
+    /// # Errors
+    /// Returns the first error of a pending write, or `E_IO` on flush
+    /// failure.
+    pub fn end_step(&mut self) -> RunnerResult<()> {
+        let step = self.step.take();
+        if step.is_some() {
+            self.io.submit(IoJob::Finish(Stream::StepEvents))?;
+        }
+        self.io.sync()?;
+        if let Some(dir) = step.filter(|_| self.encryption.is_some()) {
+            self.record_checksum(&encrypted_name(&format!("{dir}/events.jsonl")))?;
+        }
+        Ok(())
+    }
+
     /// Write the effective policy as `policy.json`.
     ///
     /// # Errors
@@ -131,20 +516,147 @@ impl ArtifactsWriter {
         self.write_json("policy.json", policy)
     }
 
+    /// Write the host the run executed on as `host.json`.
+    ///
+    /// # Errors
+    /// Returns `E_IO` on write failure, `E_PROTOCOL` on serialization failure.
+    pub fn write_host(&mut self, host: &HostFingerprint) -> RunnerResult<()> {
+        self.write_json("host.json", host)
+    }
+
+    /// Write the remote host of a remote session as `remote.json`.
+    ///
+    /// # Errors
+    /// Returns `E_IO` on write failure, `E_PROTOCOL` on serialization failure.
+    pub fn write_remote(&mut self, remote: &RemoteHost) -> RunnerResult<()> {
+        self.write_json("remote.json", remote)
+    }
+
+    /// Write the `run.validate_args` probe output as `args-probe.json`.
+    ///
+    /// # Errors
+    /// Returns `E_IO` on write failure, `E_PROTOCOL` on serialization failure.
+    pub fn write_args_probe(&mut self, record: &ArgsProbeRecord) -> RunnerResult<()> {
+        self.write_json("args-probe.json", record)
+    }
+
+    /// Write the child's environment record as `env.json`.
+    ///
+    /// # Errors
+    /// Returns `E_IO` on write failure, `E_PROTOCOL` on serialization failure.
+    pub fn write_env(&mut self, record: &EnvRecord) -> RunnerResult<()> {
+        self.write_json("env.json", record)
+    }
+
+    /// Write the Seatbelt fallback report as `enforcement.json`.
+    ///
+    /// # Errors
+    /// Returns `E_IO` on write failure, `E_PROTOCOL` on serialization failure.
+    pub fn write_enforcement(&mut self, report: &EnforcementReport) -> RunnerResult<()> {
+        self.write_json("enforcement.json", report)
+    }
+
+    /// Write the unsafe options acknowledged for the run as
+    /// `acknowledgements.json`.
+    ///
+    /// # Errors
+    /// Returns `E_IO` on write failure, `E_PROTOCOL` on serialization failure.
+    pub fn write_acknowledgements(&mut self, report: &AcknowledgementReport) -> RunnerResult<()> {
+        self.write_json("acknowledgements.json", report)
+    }
+
+    /// Write collected sandbox denials as `violations.json`.
+    ///
+    /// # Errors
+    /// Returns `E_IO` on write failure, `E_PROTOCOL` on serialization failure.
+    pub fn write_violations(&mut self, report: &SandboxViolationReport) -> RunnerResult<()> {
+        self.write_json("violations.json", report)
+    }
+
+    /// Write the container runtime invocation as `container.json`.
+    ///
+    /// # Errors
+    /// Returns `E_IO` on write failure, `E_PROTOCOL` on serialization failure.
+    pub fn write_container_invocation(
+        &mut self,
+        invocation: &ContainerInvocation,
+    ) -> RunnerResult<()> {
+        self.write_json(CONTAINER_INVOCATION_FILE, invocation)
+    }
+
+    /// Write a comparison with a second emulator backend as
+    /// `emulator-diff.json`.
+    ///
+    /// The report quotes output bytes and screen rows, so it is written as
+    /// `emulator-diff.json.age` when encryption is enabled.
+    ///
+    /// # Errors
+    /// Returns `E_IO` on write failure, `E_PROTOCOL` on serialization failure.
+    pub fn write_emulator_diff(&mut self, report: &EmulatorDiffReport) -> RunnerResult<()> {
+        if self.encryption.is_some() {
+            self.write_encrypted_json(&encrypted_name("emulator-diff.json"), report)
+        } else {
+            self.write_json("emulator-diff.json", report)
+        }
+    }
+
+    /// Write sampled resource usage as `resources.json`.
+    ///
+    /// # Errors
+    /// Returns `E_IO` on write failure, `E_PROTOCOL` on serialization failure.
+    pub fn write_resources(&mut self, usage: &ResourceUsage) -> RunnerResult<()> {
+        self.write_json("resources.json", usage)
+    }
+
+    /// Write the post-run file listing as `fs-manifest.json`.
+    ///
+    /// # Errors
+    /// Returns `E_IO` on write failure, `E_PROTOCOL` on serialization failure.
+    pub fn write_fs_manifest(&mut self, manifest: &FsManifest) -> RunnerResult<()> {
+        self.write_json("fs-manifest.json", manifest)
+    }
+
     /// Write the resolved scenario as `scenario.json`.
     ///
     /// # Errors
     /// Returns `E_IO` on write failure, `E_PROTOCOL` on serialization failure.
     pub fn write_scenario(&mut self, scenario: &Scenario) -> RunnerResult<()> {
+        self.scenario_actions = scenario
+            .steps
+            .iter()
+            .map(|step| (step.id, step.action.clone()))
+            .collect();
         self.write_json("scenario.json", scenario)
     }
 
     /// Write the run result summary as `run.json`.
     ///
+    /// With encryption enabled `final_observation` and assertion details are
+    /// omitted, since they carry screen contents and `run.json` stays
+    /// plaintext.
+    ///
     /// # Errors
     /// Returns `E_IO` on write failure, `E_PROTOCOL` on serialization failure.
     pub fn write_run_result(&mut self, run_result: &RunResult) -> RunnerResult<()> {
-        self.write_json("run.json", run_result)
+        if self.encryption.is_some() {
+            let metadata = plaintext_run_result(run_result, &self.scenario_actions);
+            self.write_json("run.json", &metadata)?;
+        } else {
+            self.write_json("run.json", run_result)?;
+        }
+        self.journal(
+            run_result.ended_at_ms,
+            JournalEvent::RunFinished {
+                status: run_result.status.clone(),
+            },
+        )?;
+        if let Some(root) = &self.run_index {
+            index::append_run_index(root, &index::index_entry(run_result, &self.dir))?;
+        }
+        if let Some(quota) = self.quota.as_mut() {
+            quota.status = Some(run_result.status.clone());
+        }
+        Ok(())
     }
 
     /// Write the normalization record as `normalization.json`.
@@ -160,37 +672,187 @@ impl ArtifactsWriter {
 
     /// Write a screen snapshot as `snapshots/NNNNNN.json`.
     ///
-    /// Snapshots are numbered sequentially starting from 1.
+    /// Snapshots are numbered sequentially starting from 1 across the whole
+    /// run; with the `per_step` layout they live under the current step
+    /// directory. Encrypted snapshots are named `NNNNNN.json.age`.
     ///
     /// # Errors
     /// Returns `E_IO` on write failure, `E_PROTOCOL` on serialization failure.
     pub fn write_snapshot(&mut self, snapshot: &ScreenSnapshot) -> RunnerResult<()> {
         self.snapshot_count += 1;
-        let name = format!("snapshots/{:06}.json", self.snapshot_count);
-        self.write_json(&name, snapshot)
+        let name = match &self.step {
+            Some(dir) => format!("{dir}/snapshots/{:06}.json", self.snapshot_count),
+            None => format!("snapshots/{:06}.json", self.snapshot_count),
+        };
+        if self.encryption.is_some() {
+            self.write_encrypted_json(&encrypted_name(&name), snapshot)
+        } else {
+            self.write_json(&name, snapshot)
+        }
+    }
+
+    /// Write an intermediate screen as `samples/NNNNNN.json`.
+    ///
+    /// Numbered like snapshots but in their own sequence, and placed beside
+    /// `snapshots/` (under the step directory with the `per_step` layout), so
+    /// replay's snapshot comparison never sees them.
+    ///
+    /// # Errors
+    /// Returns `E_IO` on write failure, `E_PROTOCOL` on serialization failure.
+    pub fn write_sample(&mut self, sample: &ScreenSample) -> RunnerResult<()> {
+        self.sample_count += 1;
+        let name = match &self.step {
+            Some(dir) => format!("{dir}/samples/{:06}.json", self.sample_count),
+            None => format!("samples/{:06}.json", self.sample_count),
+        };
+        if self.encryption.is_some() {
+            self.write_encrypted_json(&encrypted_name(&name), sample)
+        } else {
+            self.write_json(&name, sample)
+        }
+    }
+
+    /// Start collecting transcript output for a step capture.
+    ///
+    /// Everything passed to [`write_transcript`](Self::write_transcript)
+    /// until [`take_capture`](Self::take_capture) is kept, before any
+    /// transcript truncation.
+    pub fn begin_capture(&mut self) {
+        self.capture = Some(String::new());
+    }
+
+    /// Stop collecting and return the transcript output since
+    /// [`begin_capture`](Self::begin_capture).
+    pub fn take_capture(&mut self) -> String {
+        self.capture.take().unwrap_or_default()
+    }
+
+    /// Keep a SHA-256 of the transcript output written from now on, before
+    /// any transcript truncation, for [`capture_digests`](Self::capture_digests).
+    pub fn track_transcript_sha256(&mut self) {
+        self.transcript_sha256.get_or_insert_with(Sha256::new);
+    }
+
+    /// SHA-256 digests of the step captures written so far and of the
+    /// tracked transcript output.
+    #[must_use]
+    pub fn capture_digests(&self) -> CaptureDigests {
+        CaptureDigests {
+            transcript: self.transcript_sha256.as_ref().map(Sha256::finish_hex),
+            ..self.capture_digests.clone()
+        }
+    }
+
+    /// Write a step capture as `captures/<name>.txt` (`.txt.age` when
+    /// encrypted) and return `captures/<name>.txt`.
+    ///
+    /// # Errors
+    /// Returns `E_IO` on write failure.
+    pub fn write_capture(&mut self, name: &str, text: &str) -> RunnerResult<String> {
+        self.capture_digests
+            .captures
+            .insert(name.to_string(), sha256_hex(text.as_bytes()));
+        let relative = format!("captures/{name}.txt");
+        let name = match &self.encryption {
+            Some(_) => encrypted_name(&relative),
+            None => relative.clone(),
+        };
+        let path = self.dir.join(&name);
+        match &self.encryption {
+            Some(encryptor) => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).map_err(|err| {
+                        RunnerError::io("E_IO", "failed to create captures dir", err)
+                    })?;
+                }
+                encryptor.write_file(&path, text.as_bytes())?;
+                self.record_checksum(&name)?;
+            }
+            None => {
+                self.record_checksum_data(&name, text.as_bytes());
+                self.io
+                    .submit(IoJob::WriteFile(path, text.as_bytes().to_vec()))?;
+            }
+        }
+        Ok(relative)
     }
 
     /// Append raw terminal output to `transcript.log`.
     ///
-    /// Each call appends the delta and flushes immediately.
+    /// Writes are flushed at the next step boundary. With `head_tail`
+    /// truncation, output past the head budget is held back until
+    /// [`flush_checksums`](Self::flush_checksums).
     ///
     /// # Errors
-    /// Returns `E_IO` on write or flush failure.
+    /// Returns `E_IO` on write failure.
     pub fn write_transcript(&mut self, delta: &str) -> RunnerResult<()> {
+        if let Some(capture) = self.capture.as_mut() {
+            capture.push_str(delta);
+        }
+        if let Some(hasher) = self.transcript_sha256.as_mut() {
+            hasher.update(delta.as_bytes());
+        }
+        let head = match self.transcript_window.as_mut() {
+            Some(window) => window.push(delta),
+            None => delta,
+        };
+        self.append_transcript(head)
+    }
+
+    fn append_transcript(&mut self, text: &str) -> RunnerResult<()> {
+        if text.is_empty() {
+            return Ok(());
+        }
+        let bytes = text.as_bytes();
+        if self.encryption.is_none() {
+            self.record_checksum_incremental("transcript.log", bytes);
+        }
+        self.io
+            .submit(IoJob::Append(Stream::Transcript, bytes.to_vec()))
+    }
+
+    /// Append separately captured stderr to `stderr.log`.
+    ///
+    /// The file is created on the first non-empty write, so runs without
+    /// `separate_stderr` leave no `stderr.log`. Writes are flushed at the
+    /// next step boundary.
+    ///
+    /// # Errors
+    /// Returns `E_IO` on create or write failure.
+    pub fn write_stderr(&mut self, delta: &str) -> RunnerResult<()> {
+        if delta.is_empty() {
+            return Ok(());
+        }
+        if !self.stderr_open {
+            let sink = self.open_stderr()?;
+            self.io.submit(IoJob::Open(Stream::Stderr, sink))?;
+            self.stderr_open = true;
+        }
         let bytes = delta.as_bytes();
-        self.transcript
-            .write_all(bytes)
-            .map_err(|err| RunnerError::io("E_IO", "failed to write transcript", err))?;
-        self.transcript
-            .flush()
-            .map_err(|err| RunnerError::io("E_IO", "failed to flush transcript", err))?;
-        self.record_checksum_incremental("transcript.log", bytes);
-        Ok(())
+        if self.encryption.is_none() {
+            self.record_checksum_incremental("stderr.log", bytes);
+        }
+        self.io
+            .submit(IoJob::Append(Stream::Stderr, bytes.to_vec()))
+    }
+
+    fn open_stderr(&self) -> RunnerResult<StreamSink> {
+        if let Some(encryptor) = &self.encryption {
+            return Ok(StreamSink::Encrypted(
+                encryptor.open_stream(&self.dir.join(encrypted_name("stderr.log")))?,
+            ));
+        }
+        let file = fs::File::create(self.dir.join("stderr.log"))
+            .map_err(|err| RunnerError::io("E_IO", "failed to create stderr log", err))?;
+        Ok(StreamSink::Plain(BufWriter::new(file)))
     }
 
     /// Append an observation record to `events.jsonl` as NDJSON.
     ///
-    /// Each observation is serialized as a single JSON line and flushed.
+    /// With the `per_step` layout the record goes to the current step's
+    /// `events.jsonl`. With [`set_asciicast`](Self::set_asciicast), its output
+    /// is appended to `session.cast` too. Writes are flushed at the next step
+    /// boundary.
     ///
     /// # Errors
     /// Returns `E_IO` on write failure, `E_PROTOCOL` on serialization failure.
@@ -198,48 +860,106 @@ impl ArtifactsWriter {
         &mut self,
         observation: &crate::model::Observation,
     ) -> RunnerResult<()> {
-        let data = serde_json::to_vec(observation)
+        let mut data = serde_json::to_vec(observation)
             .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to serialize observation", err))?;
-        self.events
-            .write_all(&data)
-            .map_err(|err| RunnerError::io("E_IO", "failed to write events log", err))?;
-        self.events
-            .write_all(b"\n")
-            .map_err(|err| RunnerError::io("E_IO", "failed to write events log", err))?;
-        self.events
-            .flush()
-            .map_err(|err| RunnerError::io("E_IO", "failed to flush events log", err))?;
-        self.record_checksum_incremental("events.jsonl", &data);
-        self.record_checksum_incremental("events.jsonl", b"\n");
-        Ok(())
+        data.push(b'\n');
+        let (name, stream) = match &self.step {
+            Some(dir) => (format!("{dir}/events.jsonl"), Stream::StepEvents),
+            None => ("events.jsonl".to_string(), Stream::Events),
+        };
+        // Encrypted logs are checksummed as ciphertext once finished.
+        if self.encryption.is_none() {
+            self.record_checksum_incremental(&name, &data);
+        }
+        self.io.submit(IoJob::Append(stream, data))?;
+        match (self.asciicast.as_mut(), &self.encryption) {
+            (Some(encoder), None) => {
+                let cast = encoder.encode(observation).into_bytes();
+                self.record_checksum_incremental(ASCIICAST_FILE, &cast);
+                self.io
+                    .submit(IoJob::AppendFile(self.dir.join(ASCIICAST_FILE), cast))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Append the bytes written by a `raw` action to `raw-input.jsonl`.
+    ///
+    /// # Errors
+    /// Returns `E_IO` on write failure, `E_PROTOCOL` on an undecodable payload.
+    pub fn write_raw_input(
+        &mut self,
+        step: &str,
+        action: &crate::model::Action,
+    ) -> RunnerResult<()> {
+        let bytes = crate::session::decode_raw_payload(&action.payload)?;
+        let record = crate::model::RawInputRecord {
+            step: step.to_string(),
+            bytes: bytes.len() as u64,
+            hex: crate::util::encode_hex(&bytes),
+        };
+        self.write_json_line("raw-input.jsonl", &record)
+    }
+
+    /// Append the burst written by a `hold_key` action to `key-holds.jsonl`.
+    ///
+    /// # Errors
+    /// Returns `E_IO` on write failure, `E_PROTOCOL` on serialization failure.
+    pub fn write_key_hold(&mut self, step: &str, hold: crate::model::KeyHold) -> RunnerResult<()> {
+        let record = crate::model::KeyHoldRecord {
+            step: step.to_string(),
+            hold,
+        };
+        self.write_json_line("key-holds.jsonl", &record)
+    }
+
+    /// Append the flags applied by a `termios` action to `termios.jsonl`.
+    ///
+    /// # Errors
+    /// Returns `E_IO` on write failure, `E_PROTOCOL` on serialization failure.
+    pub fn write_termios(
+        &mut self,
+        step: &str,
+        settings: crate::model::TermiosSettings,
+    ) -> RunnerResult<()> {
+        let record = crate::model::TermiosRecord {
+            step: step.to_string(),
+            settings,
+        };
+        self.write_json_line("termios.jsonl", &record)
+    }
+
+    /// Append a run lifecycle event to `journal.jsonl`, `at_ms` after the
+    /// run started. [`write_run_result`](Self::write_run_result) records
+    /// `run_finished` itself.
+    ///
+    /// With encryption enabled, step results lose their assertion details as
+    /// in `run.json`.
+    ///
+    /// # Errors
+    /// Returns `E_IO` on write failure, `E_PROTOCOL` on serialization failure.
+    pub fn journal(&mut self, at_ms: u64, event: JournalEvent) -> RunnerResult<()> {
+        let event = match event {
+            JournalEvent::StepFinished { step_index, result } if self.encryption.is_some() => {
+                JournalEvent::StepFinished {
+                    step_index,
+                    result: Box::new(plaintext_step_result(&result, &self.scenario_actions)),
+                }
+            }
+            event => event,
+        };
+        self.write_json_line(JOURNAL_FILE, &JournalEntry { at_ms, event })
     }
 
     /// Write a single JSON line to a named artifact file.
     ///
     /// The file is created if it does not exist and appended to when it does.
     pub fn write_json_line<T: Serialize>(&mut self, name: &str, value: &T) -> RunnerResult<()> {
-        let path = self.dir.join(name);
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)
-                .map_err(|err| RunnerError::io("E_IO", "failed to create artifacts dir", err))?;
-        }
-
-        let mut file = fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&path)
-            .map_err(|err| RunnerError::io("E_IO", "failed to open jsonl artifact", err))?;
-        let data = serde_json::to_vec(value)
+        let mut data = serde_json::to_vec(value)
             .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to serialize jsonl line", err))?;
-        file.write_all(&data)
-            .map_err(|err| RunnerError::io("E_IO", "failed to write jsonl artifact", err))?;
-        file.write_all(b"\n")
-            .map_err(|err| RunnerError::io("E_IO", "failed to write jsonl newline", err))?;
-        file.flush()
-            .map_err(|err| RunnerError::io("E_IO", "failed to flush jsonl artifact", err))?;
+        data.push(b'\n');
         self.record_checksum_incremental(name, &data);
-        self.record_checksum_incremental(name, b"\n");
-        Ok(())
+        self.io.submit(IoJob::AppendFile(self.dir.join(name), data))
     }
 
     /// Artifacts root directory for this writer.
@@ -249,20 +969,38 @@ impl ArtifactsWriter {
     }
 
     fn write_json<T: Serialize>(&mut self, name: &str, value: &T) -> RunnerResult<()> {
+        let data = self
+            .serialize_json(value)
+            .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to serialize", err))?;
+        self.record_checksum_data(name, &data);
+        self.io.submit(IoJob::WriteFile(self.dir.join(name), data))
+    }
+
+    fn write_encrypted_json<T: Serialize>(&mut self, name: &str, value: &T) -> RunnerResult<()> {
         let path = self.dir.join(name);
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent)
                 .map_err(|err| RunnerError::io("E_IO", "failed to create artifacts dir", err))?;
         }
-        let data = serde_json::to_vec_pretty(value)
+        let data = self
+            .serialize_json(value)
             .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to serialize", err))?;
-        atomic_write(&path, &data)?;
-        self.record_checksum(name)?;
-        Ok(())
+        if let Some(encryptor) = &self.encryption {
+            encryptor.write_file(&path, &data)?;
+        }
+        self.record_checksum(name)
+    }
+
+    fn serialize_json<T: Serialize + ?Sized>(&self, value: &T) -> serde_json::Result<Vec<u8>> {
+        if self.canonical_json {
+            to_canonical_json(value)
+        } else {
+            serde_json::to_vec_pretty(value)
+        }
     }
 
     /// Record a checksum for an artifact by re-reading the file from disk.
-    /// Used for non-streaming artifacts (JSON files written atomically).
+    /// Used for encrypted artifacts, which are written inline.
     /// The checksum file is written lazily to reduce I/O overhead (batched writes).
     fn record_checksum(&mut self, name: &str) -> RunnerResult<()> {
         if name == "checksums.json" {
@@ -275,6 +1013,17 @@ impl ArtifactsWriter {
         Ok(())
     }
 
+    /// Record the checksum of an artifact from the bytes queued for it.
+    /// Used for non-streaming artifacts written through the I/O queue.
+    fn record_checksum_data(&mut self, name: &str, data: &[u8]) {
+        if name == "checksums.json" {
+            return;
+        }
+        self.checksums
+            .insert(name.to_string(), format!("{:016x}", fnv1a_hash(data)));
+        self.checksums_dirty = true;
+    }
+
     /// Incrementally update the checksum for a streaming artifact without
     /// re-reading the entire file. Used for transcript.log, events.jsonl,
     /// and other append-only files.
@@ -289,9 +1038,19 @@ impl ArtifactsWriter {
         self.checksums_dirty = true;
     }
 
-    /// Flush all pending checksums to disk. Call this after all artifacts
-    /// have been written to ensure checksums.json is complete.
+    /// Complete pending writes, then flush buffered streams and all pending
+    /// checksums to disk. Call this after all artifacts have been written to
+    /// ensure checksums.json is complete.
+    ///
+    /// Encrypted streams are finished here; later transcript or event
+    /// writes fail.
+    ///
+    /// # Errors
+    /// Returns the first error of a pending write, or `E_IO` on flush or
+    /// write failure.
     pub fn flush_checksums(&mut self) -> RunnerResult<()> {
+        self.finish_transcript()?;
+        self.finish_encrypted_streams()?;
         if self.checksums_dirty {
             self.write_checksums_internal()?;
             self.checksums_dirty = false;
@@ -299,9 +1058,42 @@ impl ArtifactsWriter {
         Ok(())
     }
 
+    /// Write the held transcript tail; later output is not truncated.
+    fn finish_transcript(&mut self) -> RunnerResult<()> {
+        match self.transcript_window.take() {
+            Some(mut window) => self.append_transcript(&window.finish()),
+            None => Ok(()),
+        }
+    }
+
+    /// Complete pending writes; finish encrypted streams and checksum their
+    /// ciphertext.
+    fn finish_encrypted_streams(&mut self) -> RunnerResult<()> {
+        self.end_step()?;
+        if self.encryption.is_none() {
+            return Ok(());
+        }
+        let mut streams = vec![
+            (Stream::Transcript, "transcript.log"),
+            (Stream::Events, "events.jsonl"),
+        ];
+        if self.stderr_open {
+            streams.push((Stream::Stderr, "stderr.log"));
+        }
+        for (stream, _) in &streams {
+            self.io.submit(IoJob::Finish(*stream))?;
+        }
+        self.io.sync()?;
+        for (_, name) in streams {
+            self.record_checksum(&encrypted_name(name))?;
+        }
+        Ok(())
+    }
+
     /// Internal method to write checksums (used by flush and Drop)
     fn write_checksums_internal(&self) -> RunnerResult<()> {
-        let data = serde_json::to_vec_pretty(&self.checksums)
+        let data = self
+            .serialize_json(&self.checksums)
             .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to serialize checksums", err))?;
         let path = self.dir.join("checksums.json");
         atomic_write(&path, &data)?;
@@ -309,11 +1101,189 @@ impl ArtifactsWriter {
     }
 }
 
+/// Run result as written to the plaintext `run.json` of an encrypted run:
+/// no final observation, and no assertion details or error context (see
+/// [`plaintext_step_result`]).
+fn plaintext_run_result(
+    run_result: &RunResult,
+    scenario_actions: &HashMap<StepId, Action>,
+) -> RunResult {
+    let mut result = run_result.clone();
+    result.final_observation = None;
+    if let Some(steps) = result.steps.as_mut() {
+        *steps = steps
+            .iter()
+            .map(|step| plaintext_step_result(step, scenario_actions))
+            .collect();
+    }
+    strip_details(&mut result.post_run);
+    strip_context(&mut result.error);
+    result
+}
+
+/// Step result as written to plaintext artifacts of an encrypted run.
+///
+/// Assertion details and error contexts quote the screen, stderr, and file
+/// contents (`screen_not_contains` keeps the whole offending snapshot), and
+/// `vars` and `resolved_vars` hold text captured from the transcript, so
+/// they are dropped; the encrypted artifacts hold the same data. An action
+/// that had variables substituted into it is replaced by the action as
+/// written in the scenario.
+fn plaintext_step_result(
+    result: &StepResult,
+    scenario_actions: &HashMap<StepId, Action>,
+) -> StepResult {
+    let mut result = result.clone();
+    strip_details(&mut result.assertions);
+    strip_context(&mut result.error);
+    if !result.resolved_vars.is_empty() {
+        if let Some(action) = scenario_actions.get(&result.step_id) {
+            result.action = action.clone();
+        }
+    }
+    result.vars.clear();
+    result.resolved_vars.clear();
+    result
+}
+
+fn strip_details(assertions: &mut [AssertionResult]) {
+    for assertion in assertions {
+        assertion.details = None;
+    }
+}
+
+fn strip_context(error: &mut Option<ErrorInfo>) {
+    if let Some(error) = error.as_mut() {
+        error.context = None;
+    }
+}
+
+fn encrypted_name(name: &str) -> String {
+    format!("{name}{ENCRYPTED_SUFFIX}")
+}
+
+/// Directory-safe form of a step name: ASCII alphanumerics, `-` and `_` kept,
+/// everything else mapped to `-`, capped at 48 characters.
+pub(crate) fn step_dir_name(name: &str) -> String {
+    let slug: String = name
+        .chars()
+        .take(48)
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
+                ch
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    if slug.is_empty() {
+        "step".to_string()
+    } else {
+        slug
+    }
+}
+
+/// Snapshot files under `artifacts_dir` in capture order, including
+/// encrypted `*.json.age` snapshots.
+///
+/// Covers both the flat `snapshots/` directory and `steps/*/snapshots/` from
+/// the `per_step` layout. Snapshot numbers are global, so ordering by file
+/// name preserves capture order.
+///
+/// # Errors
+/// Returns `E_IO` if a snapshot directory cannot be read.
+pub fn snapshot_paths(artifacts_dir: &Path) -> RunnerResult<Vec<PathBuf>> {
+    json_paths(artifacts_dir, "snapshots")
+}
+
+/// Intermediate screen sample files (see [`ArtifactsWriter::write_sample`])
+/// under `artifacts_dir` in capture order, including encrypted ones.
+///
+/// # Errors
+/// Returns `E_IO` if a samples directory cannot be read.
+pub fn sample_paths(artifacts_dir: &Path) -> RunnerResult<Vec<PathBuf>> {
+    json_paths(artifacts_dir, "samples")
+}
+
+/// `*.json` and `*.json.age` files in `artifacts_dir/<subdir>` and
+/// `steps/*/<subdir>`, sorted by file name.
+fn json_paths(artifacts_dir: &Path, subdir: &str) -> RunnerResult<Vec<PathBuf>> {
+    let mut dirs = vec![artifacts_dir.join(subdir)];
+    dirs.extend(
+        step_dirs(artifacts_dir)?
+            .into_iter()
+            .map(|dir| dir.join(subdir)),
+    );
+    let mut paths = Vec::new();
+    for dir in dirs.into_iter().filter(|dir| dir.is_dir()) {
+        let entries = fs::read_dir(&dir)
+            .map_err(|err| RunnerError::io("E_IO", "failed to read artifacts subdir", err))?;
+        paths.extend(
+            entries
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| {
+                    path.file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| {
+                            name.ends_with(".json")
+                                || name.ends_with(&format!(".json{ENCRYPTED_SUFFIX}"))
+                        })
+                }),
+        );
+    }
+    paths.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
+    Ok(paths)
+}
+
+/// Observation logs under `artifacts_dir`: per-step `events.jsonl` files in
+/// step order, followed by the top-level `events.jsonl`. Only existing files
+/// are returned, preferring `events.jsonl.age` where only it exists.
+///
+/// # Errors
+/// Returns `E_IO` if the `steps/` directory cannot be read.
+pub fn event_log_paths(artifacts_dir: &Path) -> RunnerResult<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = step_dirs(artifacts_dir)?
+        .iter()
+        .map(|dir| artifact_path(dir, "events.jsonl"))
+        .collect();
+    paths.push(artifact_path(artifacts_dir, "events.jsonl"));
+    paths.retain(|path| path.is_file());
+    Ok(paths)
+}
+
+/// Step directories under `steps/`, in step order.
+///
+/// Sorted by the numeric index prefix rather than by name, as the index is
+/// only padded to three digits: `1000-x` follows `999-y`.
+fn step_dirs(artifacts_dir: &Path) -> RunnerResult<Vec<PathBuf>> {
+    let steps = artifacts_dir.join("steps");
+    if !steps.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut dirs: Vec<PathBuf> = fs::read_dir(&steps)
+        .map_err(|err| RunnerError::io("E_IO", "failed to read steps dir", err))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_dir())
+        .collect();
+    dirs.sort_by_cached_key(|dir| {
+        let name = dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let index = name
+            .split_once('-')
+            .and_then(|(index, _)| index.parse::<u64>().ok())
+            .unwrap_or(u64::MAX);
+        (index, name)
+    });
+    Ok(dirs)
+}
+
 /// Write data to a file atomically via write-to-temp + rename.
 ///
 /// Prevents partial writes from leaving corrupt artifacts when the
 /// process is interrupted mid-write (e.g., SIGKILL, power loss).
-fn atomic_write(path: &Path, data: &[u8]) -> RunnerResult<()> {
+pub(crate) fn atomic_write(path: &Path, data: &[u8]) -> RunnerResult<()> {
     let tmp_path = path.with_extension("tmp");
     fs::write(&tmp_path, data)
         .map_err(|err| RunnerError::io("E_IO", "failed to write temp artifact", err))?;
@@ -0,0 +1,422 @@
+//! Global byte quota for the runs under an artifacts root.
+//!
+//! A root opts in by holding a usage ledger, `quota.json`
+//! ([`QuotaLedger`]), created by [`set_artifacts_quota`]. Every run whose
+//! artifacts directory is inside such a root is admitted into the ledger
+//! before its directory is created and marked completed, with its final
+//! size, when its artifacts are closed. To stay under the quota, admission
+//! and completion evict the oldest completed runs the retention policy
+//! allows; a run that cannot be admitted fails with `E_IO`.
+//!
+//! Processes sharing the root serialize on an exclusive `flock` of
+//! `quota.lock` while they read and rewrite the ledger, so hundreds of
+//! concurrent runs see a consistent total. Sizes are apparent sizes of the
+//! regular files in each run's directory; symlinks are not followed.
+
+use crate::model::{
+    ArtifactsQuota, ArtifactsStatus, QuotaLedger, QuotaRun, QuotaRunState, RunId, RunStatus,
+    QUOTA_LEDGER_VERSION,
+};
+use crate::policy::acknowledgements::now_ms;
+use crate::runner::{ErrorCode, RunnerError, RunnerResult};
+use nix::fcntl::{Flock, FlockArg};
+use std::fs;
+use std::path::{Component, Path};
+
+/// File name of the usage ledger inside the artifacts root.
+pub const QUOTA_LEDGER_FILE: &str = "quota.json";
+
+/// File name of the lock file inside the artifacts root.
+pub const QUOTA_LOCK_FILE: &str = "quota.lock";
+
+/// Set (or, with `None`, lift) the byte quota of the runs under `root`,
+/// creating the ledger if needed, and evict runs until usage fits.
+///
+/// Without a quota the ledger still tracks usage for
+/// [`artifacts_status`].
+///
+/// # Errors
+/// - `E_IO`: The root, lock, or ledger cannot be written, or an evicted
+///   run cannot be removed
+/// - `E_PROTOCOL`: The existing ledger does not parse or names a run
+///   directory outside `root`
+pub fn set_artifacts_quota(
+    root: &Path,
+    quota: Option<ArtifactsQuota>,
+) -> RunnerResult<ArtifactsStatus> {
+    fs::create_dir_all(root)
+        .map_err(|err| RunnerError::io("E_IO", "failed to create artifacts root", err))?;
+    let _lock = lock(root)?;
+    let mut ledger = read_ledger(root)?.unwrap_or(QuotaLedger {
+        quota_version: QUOTA_LEDGER_VERSION,
+        quota: None,
+        runs: Vec::new(),
+        evicted_runs: 0,
+        evicted_bytes: 0,
+    });
+    ledger.quota = quota;
+    let now = now_ms();
+    refresh(root, &mut ledger, now);
+    if let Some(max_bytes) = ledger.quota.as_ref().map(|quota| quota.max_bytes) {
+        evict(root, &mut ledger, max_bytes, now, None)?;
+    }
+    write_ledger(root, &ledger)?;
+    Ok(status(root, ledger, now))
+}
+
+/// Report the usage of the runs under `root` against its quota.
+///
+/// # Errors
+/// - `E_IO`: `root` has no ledger, or it cannot be read
+/// - `E_PROTOCOL`: The ledger does not parse or names a run directory
+///   outside `root`
+pub fn artifacts_status(root: &Path) -> RunnerResult<ArtifactsStatus> {
+    if !root.join(QUOTA_LEDGER_FILE).exists() {
+        return Err(no_ledger(root));
+    }
+    let _lock = lock(root)?;
+    let mut ledger = read_ledger(root)?.ok_or_else(|| no_ledger(root))?;
+    let now = now_ms();
+    refresh(root, &mut ledger, now);
+    Ok(status(root, ledger, now))
+}
+
+fn no_ledger(root: &Path) -> RunnerError {
+    RunnerError::with_context(
+        ErrorCode::Io,
+        "artifacts root has no quota ledger",
+        serde_json::json!({
+            "root": root,
+            "fix": "Start tracking the root with `ptybox artifacts quota --root <DIR>`"
+        }),
+    )
+}
+
+/// Admit a run writing to `dir` into the ledger at `root`, evicting
+/// completed runs to make room. Returns whether the run is tracked: runs
+/// are not when `root` has no ledger or `dir` is not inside it.
+///
+/// Call before `dir` is created.
+///
+/// # Errors
+/// - `E_IO`: The quota is used up by runs that cannot be evicted, or the
+///   ledger cannot be read or written
+/// - `E_PROTOCOL`: The ledger does not parse or names a run directory
+///   outside `root`
+pub(crate) fn admit_run(root: &Path, run_id: RunId, dir: &Path) -> RunnerResult<bool> {
+    let Some(relative) = relative_dir(root, dir) else {
+        return Ok(false);
+    };
+    if !root.join(QUOTA_LEDGER_FILE).exists() {
+        return Ok(false);
+    }
+    let _lock = lock(root)?;
+    let Some(mut ledger) = read_ledger(root)? else {
+        return Ok(false);
+    };
+    let now = now_ms();
+    // An overwritten directory no longer holds the earlier run.
+    ledger
+        .runs
+        .retain(|run| run.dir != relative || run.state == QuotaRunState::Active);
+    refresh(root, &mut ledger, now);
+    if let Some(max_bytes) = ledger.quota.as_ref().map(|quota| quota.max_bytes) {
+        // Leave at least one byte for the new run.
+        evict(root, &mut ledger, max_bytes.saturating_sub(1), now, None)?;
+        let used_bytes = used_bytes(&ledger);
+        if used_bytes >= max_bytes {
+            write_ledger(root, &ledger)?;
+            return Err(RunnerError::with_context(
+                ErrorCode::Io,
+                "artifacts quota exhausted",
+                serde_json::json!({
+                    "root": root,
+                    "max_bytes": max_bytes,
+                    "used_bytes": used_bytes,
+                    "active_runs": count(&ledger, QuotaRunState::Active),
+                    "fix": "Raise the quota with `ptybox artifacts quota --max-bytes`, relax its retention, or wait for active runs to finish"
+                }),
+            ));
+        }
+    }
+    ledger.runs.push(QuotaRun {
+        run_id,
+        dir: relative,
+        state: QuotaRunState::Active,
+        bytes: 0,
+        started_at_ms: now,
+        completed_at_ms: None,
+        status: None,
+    });
+    write_ledger(root, &ledger)?;
+    Ok(true)
+}
+
+/// Record the final size and status of a run admitted with [`admit_run`],
+/// then evict other runs until usage fits the quota.
+///
+/// # Errors
+/// - `E_IO`: The ledger cannot be read or written, or an evicted run
+///   cannot be removed
+/// - `E_PROTOCOL`: The ledger does not parse or names a run directory
+///   outside `root`
+pub(crate) fn complete_run(
+    root: &Path,
+    run_id: RunId,
+    dir: &Path,
+    status: Option<RunStatus>,
+) -> RunnerResult<()> {
+    let Some(relative) = relative_dir(root, dir) else {
+        return Ok(());
+    };
+    let _lock = lock(root)?;
+    let Some(mut ledger) = read_ledger(root)? else {
+        return Ok(());
+    };
+    let now = now_ms();
+    let completed = QuotaRun {
+        run_id,
+        dir: relative,
+        state: QuotaRunState::Completed,
+        bytes: dir_size(dir),
+        started_at_ms: now,
+        completed_at_ms: Some(now),
+        status,
+    };
+    match ledger.runs.iter_mut().find(|run| run.run_id == run_id) {
+        Some(run) => {
+            *run = QuotaRun {
+                started_at_ms: run.started_at_ms,
+                ..completed
+            };
+        }
+        // Dropped as abandoned while it ran.
+        None => ledger.runs.push(completed),
+    }
+    refresh(root, &mut ledger, now);
+    if let Some(max_bytes) = ledger.quota.as_ref().map(|quota| quota.max_bytes) {
+        evict(root, &mut ledger, max_bytes, now, Some(run_id))?;
+    }
+    write_ledger(root, &ledger)
+}
+
+/// Remove a run admitted with [`admit_run`] whose artifacts were never
+/// opened.
+///
+/// # Errors
+/// - `E_IO`: The ledger cannot be read or written
+/// - `E_PROTOCOL`: The ledger does not parse or names a run directory
+///   outside `root`
+pub(crate) fn release_run(root: &Path, run_id: RunId) -> RunnerResult<()> {
+    let _lock = lock(root)?;
+    let Some(mut ledger) = read_ledger(root)? else {
+        return Ok(());
+    };
+    ledger.runs.retain(|run| run.run_id != run_id);
+    write_ledger(root, &ledger)
+}
+
+/// `dir` relative to `root`, when it is strictly inside it.
+fn relative_dir(root: &Path, dir: &Path) -> Option<String> {
+    let relative = dir.strip_prefix(root).ok()?;
+    is_plain_relative(relative).then(|| relative.display().to_string())
+}
+
+/// Whether `path` is non-empty and made only of normal components, so
+/// joining it onto the root cannot leave the root.
+fn is_plain_relative(path: &Path) -> bool {
+    path.components().next().is_some()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+fn lock(root: &Path) -> RunnerResult<Flock<fs::File>> {
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(root.join(QUOTA_LOCK_FILE))
+        .map_err(|err| RunnerError::io("E_IO", "failed to open quota lock", err))?;
+    Flock::lock(file, FlockArg::LockExclusive)
+        .map_err(|(_, errno)| RunnerError::io("E_IO", "failed to lock quota ledger", errno))
+}
+
+fn read_ledger(root: &Path) -> RunnerResult<Option<QuotaLedger>> {
+    let path = root.join(QUOTA_LEDGER_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let data = fs::read(&path)
+        .map_err(|err| RunnerError::io("E_IO", "failed to read quota ledger", err))?;
+    let ledger: QuotaLedger = serde_json::from_slice(&data)
+        .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to parse quota ledger", err))?;
+    // Eviction deletes these directories, so they must stay inside the root.
+    if let Some(run) = ledger
+        .runs
+        .iter()
+        .find(|run| !is_plain_relative(Path::new(&run.dir)))
+    {
+        return Err(RunnerError::protocol(
+            "E_PROTOCOL",
+            "quota ledger names a run directory outside the artifacts root",
+            serde_json::json!({
+                "root": root,
+                "run_id": run.run_id,
+                "dir": run.dir,
+                "fix": "Remove the entry from quota.json"
+            }),
+        ));
+    }
+    Ok(Some(ledger))
+}
+
+fn write_ledger(root: &Path, ledger: &QuotaLedger) -> RunnerResult<()> {
+    let mut data = serde_json::to_vec_pretty(ledger)
+        .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to serialize quota ledger", err))?;
+    data.push(b'\n');
+    super::atomic_write(&root.join(QUOTA_LEDGER_FILE), &data)
+}
+
+/// Re-measure active runs and drop runs whose directory is gone, unless
+/// they are active and not yet abandoned (their directory may not exist
+/// yet).
+fn refresh(root: &Path, ledger: &mut QuotaLedger, now: u64) {
+    let timeout = active_timeout(ledger);
+    ledger.runs.retain_mut(|run| {
+        let dir = root.join(&run.dir);
+        if !dir.exists() {
+            return run.state == QuotaRunState::Active && !abandoned(run, timeout, now);
+        }
+        if run.state == QuotaRunState::Active {
+            run.bytes = dir_size(&dir);
+        }
+        true
+    });
+}
+
+/// Evict the oldest evictable runs until usage is at most `target_bytes`.
+fn evict(
+    root: &Path,
+    ledger: &mut QuotaLedger,
+    target_bytes: u64,
+    now: u64,
+    protected: Option<RunId>,
+) -> RunnerResult<()> {
+    let mut candidates = evictable(ledger, now);
+    candidates.retain(|run_id| Some(*run_id) != protected);
+    for run_id in candidates {
+        if used_bytes(ledger) <= target_bytes {
+            break;
+        }
+        let Some(index) = ledger.runs.iter().position(|run| run.run_id == run_id) else {
+            continue;
+        };
+        let run = ledger.runs.remove(index);
+        let dir = root.join(&run.dir);
+        if dir.exists() {
+            fs::remove_dir_all(&dir)
+                .map_err(|err| RunnerError::io("E_IO", "failed to evict run artifacts", err))?;
+        }
+        tracing::debug!(run_id = %run.run_id, dir = %dir.display(), bytes = run.bytes, "evicted run artifacts");
+        ledger.evicted_runs += 1;
+        ledger.evicted_bytes = ledger.evicted_bytes.saturating_add(run.bytes);
+    }
+    Ok(())
+}
+
+/// Runs the retention policy allows evicting, oldest first.
+fn evictable(ledger: &QuotaLedger, now: u64) -> Vec<RunId> {
+    let retention = ledger
+        .quota
+        .as_ref()
+        .map(|quota| quota.retention.clone())
+        .unwrap_or_default();
+    let mut finished: Vec<&QuotaRun> = ledger
+        .runs
+        .iter()
+        .filter(|run| {
+            run.state == QuotaRunState::Completed
+                || abandoned(run, retention.active_timeout_ms, now)
+        })
+        .collect();
+    finished.sort_by_key(|run| {
+        (
+            run.completed_at_ms.unwrap_or(run.started_at_ms),
+            run.started_at_ms,
+        )
+    });
+    let keep = usize::try_from(retention.keep_newest).unwrap_or(usize::MAX);
+    let evictable_len = finished.len().saturating_sub(keep);
+    finished
+        .into_iter()
+        .take(evictable_len)
+        .filter(|run| !retention.keep_failed || run.status == Some(RunStatus::Passed))
+        .map(|run| run.run_id)
+        .collect()
+}
+
+fn active_timeout(ledger: &QuotaLedger) -> u64 {
+    ledger
+        .quota
+        .as_ref()
+        .map_or(crate::model::DEFAULT_QUOTA_ACTIVE_TIMEOUT_MS, |quota| {
+            quota.retention.active_timeout_ms
+        })
+}
+
+fn abandoned(run: &QuotaRun, timeout_ms: u64, now: u64) -> bool {
+    run.state == QuotaRunState::Active && now.saturating_sub(run.started_at_ms) > timeout_ms
+}
+
+fn used_bytes(ledger: &QuotaLedger) -> u64 {
+    ledger
+        .runs
+        .iter()
+        .fold(0, |total, run| total.saturating_add(run.bytes))
+}
+
+fn count(ledger: &QuotaLedger, state: QuotaRunState) -> u64 {
+    ledger.runs.iter().filter(|run| run.state == state).count() as u64
+}
+
+fn status(root: &Path, ledger: QuotaLedger, now: u64) -> ArtifactsStatus {
+    let used_bytes = used_bytes(&ledger);
+    ArtifactsStatus {
+        root: root.display().to_string(),
+        available_bytes: ledger
+            .quota
+            .as_ref()
+            .map(|quota| quota.max_bytes.saturating_sub(used_bytes)),
+        used_bytes,
+        active_runs: count(&ledger, QuotaRunState::Active),
+        completed_runs: count(&ledger, QuotaRunState::Completed),
+        evictable_runs: evictable(&ledger, now).len() as u64,
+        evicted_runs: ledger.evicted_runs,
+        evicted_bytes: ledger.evicted_bytes,
+        quota: ledger.quota,
+        runs: ledger.runs,
+    }
+}
+
+/// Apparent size of the regular files under `dir`; 0 when it is missing.
+fn dir_size(dir: &Path) -> u64 {
+    let mut bytes: u64 = 0;
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.filter_map(Result::ok) {
+            let Ok(metadata) = entry.path().symlink_metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else if metadata.is_file() {
+                bytes = bytes.saturating_add(metadata.len());
+            }
+        }
+    }
+    bytes
+}
@@ -0,0 +1,214 @@
+//! Rebuild `run.json` for a run whose harness was killed mid-run.
+//!
+//! [`ArtifactsWriter`](super::ArtifactsWriter) appends a
+//! [`JournalEntry`] to `journal.jsonl` as the run starts, as each step
+//! starts and finishes, and when `run.json` is written. [`recover_run`]
+//! replays that journal into a best-effort [`RunResult`]: `errored` (or
+//! `canceled` when the journal records a cancel), the steps that finished,
+//! and the last observation in `events.jsonl` as the final observation, so
+//! the partial run can still be reported on and replayed.
+
+use super::{atomic_write, step_dirs, to_canonical_json};
+use crate::model::{
+    JournalEntry, JournalEvent, Observation, Policy, RunResult, RunStatus, Scenario, StepResult,
+    PROTOCOL_VERSION,
+};
+use crate::runner::{classify_failure, ErrorCode, RunnerError, RunnerResult};
+use crate::util::compute_checksum;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Run journal file name, relative to the artifacts directory.
+pub const JOURNAL_FILE: &str = "journal.jsonl";
+
+/// Rebuild `run.json` in `dir` from `journal.jsonl` and write it.
+///
+/// The policy and scenario come from `policy.json` and `scenario.json` when
+/// present. A torn last journal line is ignored. When `checksums.json`
+/// exists, its entries are recomputed for the files as they are now and
+/// `run.json` is added. The error names the step that was running, if any,
+/// with `recovered: true` in its context.
+///
+/// # Errors
+/// - `E_IO`: `dir` is not a directory, or a file cannot be read or written
+/// - `E_PROTOCOL`: `run.json` already exists, or the journal is missing or
+///   does not start with `run_started`
+pub fn recover_run(dir: &Path) -> RunnerResult<RunResult> {
+    if !dir.is_dir() {
+        return Err(RunnerError::with_context(
+            ErrorCode::Io,
+            "artifacts directory not found",
+            serde_json::json!({ "dir": dir }),
+        ));
+    }
+    if dir.join("run.json").exists() {
+        return Err(RunnerError::with_context(
+            ErrorCode::Protocol,
+            "run.json already exists; the run has nothing to recover",
+            serde_json::json!({ "dir": dir }),
+        ));
+    }
+    let journal = read_journal(dir)?;
+    let Some(JournalEvent::RunStarted {
+        run_id,
+        command,
+        args,
+        cwd,
+        tags,
+    }) = journal.first().map(|entry| entry.event.clone())
+    else {
+        return Err(RunnerError::with_context(
+            ErrorCode::Protocol,
+            "journal.jsonl does not start with run_started",
+            serde_json::json!({ "dir": dir, "entries": journal.len() }),
+        ));
+    };
+
+    let mut steps: BTreeMap<usize, StepResult> = BTreeMap::new();
+    let mut in_progress = None;
+    let mut status = RunStatus::Errored;
+    for entry in &journal {
+        match &entry.event {
+            JournalEvent::StepStarted { name, .. } => in_progress = Some(name.clone()),
+            JournalEvent::StepFinished { step_index, result } => {
+                in_progress = None;
+                steps.insert(*step_index, (**result).clone());
+            }
+            JournalEvent::RunCanceled => status = RunStatus::Canceled,
+            JournalEvent::RunFinished { status: finished } => status = finished.clone(),
+            JournalEvent::RunStarted { .. } => {}
+        }
+    }
+    let scenario: Option<Scenario> = read_optional_json(dir, "scenario.json")?;
+    let policy: Policy = read_optional_json(dir, "policy.json")?.unwrap_or_default();
+    let error = RunnerError::with_context(
+        ErrorCode::Internal,
+        "run was interrupted before run.json was written; recovered from journal.jsonl",
+        serde_json::json!({
+            "recovered": true,
+            "journal_entries": journal.len(),
+            "step_in_progress": in_progress
+        }),
+    );
+    let mut run_result = RunResult {
+        run_result_version: 1,
+        protocol_version: PROTOCOL_VERSION,
+        run_id,
+        status,
+        started_at_ms: 0,
+        ended_at_ms: journal.last().map_or(0, |entry| entry.at_ms),
+        command,
+        args,
+        cwd,
+        determinism: policy.determinism.pinned(),
+        policy,
+        steps: scenario.as_ref().map(|_| steps.into_values().collect()),
+        scenario,
+        final_observation: last_observation(dir)?,
+        exit_status: None,
+        error: Some(error.to_error_info()),
+        tags,
+        enforcement: None,
+        provenance: None,
+        classification: None,
+        post_run: Vec::new(),
+        risk_summary: None,
+        fixtures: Vec::new(),
+        policy_grants: Vec::new(),
+        keymap: None,
+    };
+    run_result.classification = classify_failure(&run_result);
+
+    let canonical = run_result.policy.artifacts.canonical_json;
+    let data = serialize(&run_result, canonical)
+        .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to serialize run.json", err))?;
+    atomic_write(&dir.join("run.json"), &data)?;
+    refresh_checksums(dir, canonical)?;
+    Ok(run_result)
+}
+
+/// Every complete line of the journal, stopping at the first that does not
+/// parse (a write cut short by the kill).
+fn read_journal(dir: &Path) -> RunnerResult<Vec<JournalEntry>> {
+    let path = dir.join(JOURNAL_FILE);
+    if !path.is_file() {
+        return Err(RunnerError::with_context(
+            ErrorCode::Protocol,
+            "journal.jsonl not found; the run was not recorded with a journal",
+            serde_json::json!({ "dir": dir }),
+        ));
+    }
+    let data = fs::read_to_string(&path)
+        .map_err(|err| RunnerError::io("E_IO", "failed to read journal.jsonl", err))?;
+    Ok(data
+        .lines()
+        .map_while(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+fn read_optional_json<T: DeserializeOwned>(dir: &Path, name: &str) -> RunnerResult<Option<T>> {
+    let path = dir.join(name);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let data =
+        fs::read(&path).map_err(|err| RunnerError::io("E_IO", "failed to read artifact", err))?;
+    Ok(serde_json::from_slice(&data).ok())
+}
+
+/// The last observation that parses in the run's plaintext event log (the
+/// last step's log with the `per_step` layout).
+fn last_observation(dir: &Path) -> RunnerResult<Option<Observation>> {
+    let mut logs = vec![dir.join("events.jsonl")];
+    logs.extend(
+        step_dirs(dir)?
+            .into_iter()
+            .rev()
+            .map(|step| step.join("events.jsonl")),
+    );
+    for log in logs.iter().filter(|log| log.is_file()) {
+        let data = fs::read_to_string(log)
+            .map_err(|err| RunnerError::io("E_IO", "failed to read events.jsonl", err))?;
+        if let Some(observation) = data
+            .lines()
+            .rev()
+            .find_map(|line| serde_json::from_str(line).ok())
+        {
+            return Ok(Some(observation));
+        }
+    }
+    Ok(None)
+}
+
+/// Recompute `checksums.json` for the files it lists that still exist, plus
+/// `run.json`.
+fn refresh_checksums(dir: &Path, canonical: bool) -> RunnerResult<()> {
+    let path = dir.join("checksums.json");
+    let Some(listed) = read_optional_json::<BTreeMap<String, String>>(dir, "checksums.json")?
+    else {
+        return Ok(());
+    };
+    let mut checksums = BTreeMap::new();
+    for name in listed.keys().map(String::as_str).chain(["run.json"]) {
+        let file = dir.join(name);
+        if file.is_file() {
+            checksums.insert(name.to_string(), compute_checksum(&file)?);
+        }
+    }
+    let data = serialize(&checksums, canonical)
+        .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to serialize checksums", err))?;
+    atomic_write(&path, &data)
+}
+
+/// JSON in the form the run was written in: canonical when the policy sets
+/// `artifacts.canonical_json`.
+fn serialize<T: Serialize>(value: &T, canonical: bool) -> serde_json::Result<Vec<u8>> {
+    if canonical {
+        to_canonical_json(value)
+    } else {
+        serde_json::to_vec_pretty(value)
+    }
+}
@@ -0,0 +1,120 @@
+//! Secret redaction for text leaving the artifacts directory.
+//!
+//! Built-in rules cover `key=value` style credentials, well-known token
+//! formats, and PEM private keys. Values the policy sets for secret-looking
+//! environment variables are redacted wherever they appear, and callers can
+//! add their own patterns.
+
+use crate::model::policy::Policy;
+use crate::runner::{compile_safe_regex, RunnerResult};
+use regex::Regex;
+use std::borrow::Cow;
+
+/// Built-in patterns and their replacements.
+const BUILTIN_RULES: [(&str, &str); 6] = [
+    (
+        r#"(?i)\b(password|passwd|pwd|secret|token|api[_-]?key|access[_-]?key|auth)\b(["']?\s*[:=]\s*["']?)([^\s"',\[{][^\s"',]*)"#,
+        "${1}${2}[REDACTED]",
+    ),
+    (
+        r"(?i)\b(bearer)\s+[A-Za-z0-9\-._~+/]{8,}=*",
+        "${1} [REDACTED]",
+    ),
+    (r"\bAKIA[0-9A-Z]{16}\b", "[REDACTED]"),
+    (r"\bgh[pousr]_[A-Za-z0-9]{20,}\b", "[REDACTED]"),
+    (r"\bxox[abprs]-[A-Za-z0-9-]{10,}", "[REDACTED]"),
+    (
+        r"(?s)-----BEGIN [A-Z ]*PRIVATE KEY-----.*?-----END [A-Z ]*PRIVATE KEY-----",
+        "[REDACTED PRIVATE KEY]",
+    ),
+];
+
+/// Environment variable name fragments that mark a value as secret.
+const SECRET_ENV_MARKERS: [&str; 7] = [
+    "TOKEN",
+    "SECRET",
+    "PASSWORD",
+    "PASSWD",
+    "KEY",
+    "CREDENTIAL",
+    "AUTH",
+];
+
+/// Policy env values shorter than this are too generic to redact safely.
+const MIN_SECRET_VALUE_LEN: usize = 4;
+
+/// Applies redaction rules to text and counts what it replaced.
+pub(crate) struct Redactor {
+    rules: Vec<(Regex, String)>,
+    literals: Vec<(String, String)>,
+    redactions: u64,
+}
+
+impl Redactor {
+    /// Build a redactor from the built-in rules, the secret-looking
+    /// `env.set` values of `policy`, and `extra` user patterns.
+    ///
+    /// # Errors
+    /// Returns `E_PROTOCOL` if an `extra` pattern is invalid or too long.
+    pub(crate) fn new(policy: Option<&Policy>, extra: &[String]) -> RunnerResult<Self> {
+        let mut rules = Vec::new();
+        for (pattern, replacement) in BUILTIN_RULES {
+            rules.push((compile_safe_regex(pattern)?, replacement.to_string()));
+        }
+        for pattern in extra {
+            rules.push((compile_safe_regex(pattern)?, "[REDACTED]".to_string()));
+        }
+        let mut literals: Vec<(String, String)> = policy
+            .map(|policy| {
+                policy
+                    .env
+                    .set
+                    .iter()
+                    .filter(|(name, value)| {
+                        let upper = name.to_ascii_uppercase();
+                        value.len() >= MIN_SECRET_VALUE_LEN
+                            && SECRET_ENV_MARKERS
+                                .iter()
+                                .any(|marker| upper.contains(marker))
+                    })
+                    .map(|(name, value)| (value.clone(), format!("[REDACTED:{name}]")))
+                    .collect()
+            })
+            .unwrap_or_default();
+        // Longest first, so a value containing another is replaced whole.
+        literals.sort_by_key(|(value, _)| std::cmp::Reverse(value.len()));
+        Ok(Self {
+            rules,
+            literals,
+            redactions: 0,
+        })
+    }
+
+    /// Redact `text`, literal env values first and then pattern rules.
+    pub(crate) fn redact(&mut self, text: &str) -> String {
+        let mut text = text.to_string();
+        for (value, replacement) in &self.literals {
+            let count = text.matches(value.as_str()).count();
+            if count > 0 {
+                self.redactions += count as u64;
+                text = text.replace(value.as_str(), replacement);
+            }
+        }
+        for (regex, replacement) in &self.rules {
+            let count = regex.find_iter(&text).count();
+            if count == 0 {
+                continue;
+            }
+            self.redactions += count as u64;
+            if let Cow::Owned(replaced) = regex.replace_all(&text, replacement.as_str()) {
+                text = replaced;
+            }
+        }
+        text
+    }
+
+    /// Number of values replaced so far.
+    pub(crate) fn redactions(&self) -> u64 {
+        self.redactions
+    }
+}
@@ -0,0 +1,202 @@
+//! Slim copies of run artifacts that keep only what replay reads.
+//!
+//! A slimmed directory holds `scenario.json`, `policy.json`, `run.json`, the
+//! transcript, and every snapshot (flat or per-step), plus a freshly computed
+//! `checksums.json`. Event logs, audit logs, and replay output are left
+//! behind.
+
+use super::{artifact_path, snapshot_paths};
+use crate::model::policy::Policy;
+use crate::model::{RunResult, SlimReport};
+use crate::runner::{ErrorCode, RunnerError, RunnerResult};
+use crate::util::compute_checksum;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Artifacts replay cannot run without.
+const REQUIRED: [&str; 3] = ["scenario.json", "policy.json", "run.json"];
+
+/// Copy the replay-essential artifacts of `source` into `output`.
+///
+/// Files listed in the source `checksums.json` are verified before they are
+/// copied, and the result is checked to load the way replay loads it.
+/// With `overwrite`, an existing `output` directory is replaced.
+///
+/// # Errors
+/// - `E_IO`: A required artifact is missing, or a file cannot be read or written
+/// - `E_POLICY_DENIED`: `output` exists and `overwrite` is false
+/// - `E_PROTOCOL`: `output` would replace `source`, or a kept artifact does not parse
+/// - `E_REPLAY_MISMATCH`: A kept artifact does not match the source checksums
+pub fn slim_artifacts(source: &Path, output: &Path, overwrite: bool) -> RunnerResult<SlimReport> {
+    for name in REQUIRED {
+        if !source.join(name).is_file() {
+            return Err(RunnerError::with_context(
+                ErrorCode::Io,
+                format!("artifacts missing {name}"),
+                serde_json::json!({ "dir": source, "missing": name }),
+            ));
+        }
+    }
+    let kept = kept_files(source)?;
+    verify_source_checksums(source, &kept)?;
+    let (total_files, total_bytes) = tree_size(source, true)?;
+    prepare_output(source, output, overwrite)?;
+
+    let mut checksums = BTreeMap::new();
+    let mut kept_bytes = 0;
+    for relative in &kept {
+        let target = output.join(relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|err| RunnerError::io("E_IO", "failed to create slim dir", err))?;
+        }
+        kept_bytes += fs::copy(source.join(relative), &target)
+            .map_err(|err| RunnerError::io("E_IO", "failed to copy artifact", err))?;
+        checksums.insert(relative.clone(), compute_checksum(&target)?);
+    }
+    let data = serde_json::to_vec_pretty(&checksums)
+        .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to serialize checksums", err))?;
+    fs::write(output.join("checksums.json"), data)
+        .map_err(|err| RunnerError::io("E_IO", "failed to write checksums", err))?;
+    validate_replayable(output)?;
+
+    Ok(SlimReport {
+        source: source.display().to_string(),
+        output: output.display().to_string(),
+        dropped_files: total_files.saturating_sub(kept.len() as u64),
+        dropped_bytes: total_bytes.saturating_sub(kept_bytes),
+        kept,
+        kept_bytes,
+    })
+}
+
+/// Relative paths of the files to keep, in a stable order.
+fn kept_files(source: &Path) -> RunnerResult<Vec<String>> {
+    let mut paths: Vec<PathBuf> = REQUIRED.iter().map(|name| source.join(name)).collect();
+    let transcript = artifact_path(source, "transcript.log");
+    if transcript.is_file() {
+        paths.push(transcript);
+    }
+    paths.extend(snapshot_paths(source)?);
+    paths
+        .iter()
+        .map(|path| {
+            path.strip_prefix(source)
+                .ok()
+                .and_then(Path::to_str)
+                .map(|relative| relative.replace('\\', "/"))
+                .ok_or_else(|| RunnerError::io("E_IO", "invalid artifact path", path.display()))
+        })
+        .collect()
+}
+
+fn verify_source_checksums(source: &Path, kept: &[String]) -> RunnerResult<()> {
+    let path = source.join("checksums.json");
+    if !path.is_file() {
+        return Ok(());
+    }
+    let data = fs::read(&path)
+        .map_err(|err| RunnerError::io("E_IO", "failed to read checksums.json", err))?;
+    let checksums: BTreeMap<String, String> = serde_json::from_slice(&data)
+        .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to parse checksums.json", err))?;
+    for relative in kept {
+        let Some(expected) = checksums.get(relative) else {
+            continue;
+        };
+        let actual = compute_checksum(&source.join(relative))?;
+        if actual != *expected {
+            return Err(RunnerError::replay_mismatch(
+                "source artifact corrupted",
+                serde_json::json!({
+                    "kind": "checksum",
+                    "path": relative,
+                    "expected": expected,
+                    "actual": actual
+                }),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn prepare_output(source: &Path, output: &Path, overwrite: bool) -> RunnerResult<()> {
+    if output.exists() {
+        if !overwrite {
+            return Err(RunnerError::policy_denied(
+                "E_POLICY_DENIED",
+                "artifacts directory exists and overwrite is disabled",
+                serde_json::json!({ "dir": output }),
+            ));
+        }
+        let source = source
+            .canonicalize()
+            .map_err(|err| RunnerError::io("E_IO", "failed to resolve artifacts dir", err))?;
+        let existing = output
+            .canonicalize()
+            .map_err(|err| RunnerError::io("E_IO", "failed to resolve output dir", err))?;
+        if source.starts_with(&existing) {
+            return Err(RunnerError::with_context(
+                ErrorCode::Protocol,
+                "slim output must not contain the source artifacts",
+                serde_json::json!({ "source": source, "output": existing }),
+            ));
+        }
+        fs::remove_dir_all(output)
+            .map_err(|err| RunnerError::io("E_IO", "failed to clear output dir", err))?;
+    }
+    fs::create_dir_all(output)
+        .map_err(|err| RunnerError::io("E_IO", "failed to create output dir", err))
+}
+
+/// Load the slimmed artifacts the way replay does.
+fn validate_replayable(dir: &Path) -> RunnerResult<()> {
+    let scenario = dir.join("scenario.json");
+    crate::scenario::load_scenario_file(
+        scenario
+            .to_str()
+            .ok_or_else(|| RunnerError::io("E_IO", "invalid scenario path", "path"))?,
+    )?;
+    parse_json::<Policy>(&dir.join("policy.json"), "policy.json")?;
+    parse_json::<RunResult>(&dir.join("run.json"), "run.json")?;
+    Ok(())
+}
+
+fn parse_json<T: serde::de::DeserializeOwned>(path: &Path, name: &str) -> RunnerResult<T> {
+    let data = fs::read(path)
+        .map_err(|err| RunnerError::io("E_IO", format!("failed to read {name}"), err))?;
+    serde_json::from_slice(&data)
+        .map_err(|err| RunnerError::io("E_PROTOCOL", format!("failed to parse {name}"), err))
+}
+
+/// File count and byte total under `dir`, skipping the top-level
+/// `checksums.json` and `replay-*` dirs.
+fn tree_size(dir: &Path, top: bool) -> RunnerResult<(u64, u64)> {
+    let mut totals = (0, 0);
+    let entries = fs::read_dir(dir)
+        .map_err(|err| RunnerError::io("E_IO", "failed to read artifacts dir", err))?;
+    for entry in entries {
+        let entry =
+            entry.map_err(|err| RunnerError::io("E_IO", "failed to read artifacts dir", err))?;
+        let file_type = entry
+            .file_type()
+            .map_err(|err| RunnerError::io("E_IO", "failed to stat artifact", err))?;
+        if file_type.is_dir() {
+            let is_replay = entry.file_name().to_string_lossy().starts_with("replay-");
+            if top && is_replay {
+                continue;
+            }
+            let (files, bytes) = tree_size(&entry.path(), false)?;
+            totals.0 += files;
+            totals.1 += bytes;
+        } else if file_type.is_file() && !(top && entry.file_name() == "checksums.json") {
+            let len = entry
+                .metadata()
+                .map_err(|err| RunnerError::io("E_IO", "failed to stat artifact", err))?
+                .len();
+            totals.0 += 1;
+            totals.1 += len;
+        }
+    }
+    Ok(totals)
+}
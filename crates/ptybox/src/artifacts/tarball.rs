@@ -0,0 +1,96 @@
+//! Minimal `.tar.gz` writer for failure bundles.
+//!
+//! Entries are regular files in ustar format with fixed ownership and a zero
+//! mtime, so the same inputs produce the same archive.
+
+use crate::runner::{ErrorCode, RunnerError, RunnerResult};
+
+const BLOCK: usize = 512;
+const NAME_LEN: usize = 100;
+const PREFIX_LEN: usize = 155;
+
+/// In-memory tar archive, gzip-compressed on [`finish`](Self::finish).
+#[derive(Default)]
+pub(crate) struct TarGz {
+    tar: Vec<u8>,
+}
+
+impl TarGz {
+    /// Append a regular file at `path` (`/`-separated).
+    pub(crate) fn add(&mut self, path: &str, data: &[u8]) -> RunnerResult<()> {
+        let (prefix, name) = split_path(path)?;
+        let mut header = [0u8; BLOCK];
+        put(&mut header, 0, name.as_bytes());
+        put(&mut header, 100, b"0000644\0");
+        put(&mut header, 108, b"0000000\0");
+        put(&mut header, 116, b"0000000\0");
+        put(
+            &mut header,
+            124,
+            format!("{:011o}\0", data.len()).as_bytes(),
+        );
+        put(&mut header, 136, b"00000000000\0");
+        put(&mut header, 148, b"        ");
+        header[156] = b'0';
+        put(&mut header, 257, b"ustar\0");
+        put(&mut header, 263, b"00");
+        put(&mut header, 345, prefix.as_bytes());
+        let sum: u32 = header.iter().map(|byte| u32::from(*byte)).sum();
+        put(&mut header, 148, format!("{sum:06o}\0 ").as_bytes());
+
+        self.tar.extend_from_slice(&header);
+        self.tar.extend_from_slice(data);
+        let padding = (BLOCK - data.len() % BLOCK) % BLOCK;
+        self.tar.resize(self.tar.len() + padding, 0);
+        Ok(())
+    }
+
+    /// Close the archive and return it gzip-compressed.
+    pub(crate) fn finish(mut self) -> Vec<u8> {
+        self.tar.resize(self.tar.len() + 2 * BLOCK, 0);
+        let mut out = vec![0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff];
+        out.extend(miniz_oxide::deflate::compress_to_vec(&self.tar, 6));
+        out.extend(crc32(&self.tar).to_le_bytes());
+        // ISIZE is the input length modulo 2^32.
+        #[allow(clippy::cast_possible_truncation)]
+        out.extend((self.tar.len() as u32).to_le_bytes());
+        out
+    }
+}
+
+/// Split `path` into ustar `prefix` and `name` fields.
+fn split_path(path: &str) -> RunnerResult<(&str, &str)> {
+    if path.len() <= NAME_LEN {
+        return Ok(("", path));
+    }
+    path.char_indices()
+        .filter(|(_, ch)| *ch == '/')
+        .map(|(index, _)| (&path[..index], &path[index + 1..]))
+        .find(|(prefix, name)| prefix.len() <= PREFIX_LEN && name.len() <= NAME_LEN)
+        .ok_or_else(|| {
+            RunnerError::with_context(
+                ErrorCode::Protocol,
+                "bundle entry path is too long for tar",
+                serde_json::json!({ "path": path }),
+            )
+        })
+}
+
+fn put(header: &mut [u8; BLOCK], offset: usize, bytes: &[u8]) {
+    if let Some(slot) = header.get_mut(offset..offset + bytes.len()) {
+        slot.copy_from_slice(bytes);
+    }
+}
+
+/// CRC-32 (IEEE) as used by gzip.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+    !crc
+}
@@ -0,0 +1,88 @@
+//! Head/tail retention for `transcript.log`.
+//!
+//! Output up to the head budget is written through immediately; everything
+//! after it is held in a sliding tail window and written, behind an elision
+//! marker, when the transcript is finished. Cuts always fall on UTF-8
+//! character boundaries, so either end may keep slightly less than its budget.
+
+use crate::model::policy::TranscriptPolicy;
+
+/// Sliding head/tail window over transcript output.
+pub(crate) struct HeadTailBuffer {
+    head_limit: usize,
+    tail_limit: usize,
+    head_written: usize,
+    /// Set once output has gone past the head budget.
+    head_closed: bool,
+    tail: String,
+    total: u64,
+    elided: u64,
+}
+
+impl HeadTailBuffer {
+    pub(crate) fn new(policy: &TranscriptPolicy) -> Self {
+        Self {
+            head_limit: kib_to_bytes(policy.head_kib),
+            tail_limit: kib_to_bytes(policy.tail_kib),
+            head_written: 0,
+            head_closed: false,
+            tail: String::new(),
+            total: 0,
+            elided: 0,
+        }
+    }
+
+    /// Account for `delta` and return the prefix to write through now.
+    pub(crate) fn push<'a>(&mut self, delta: &'a str) -> &'a str {
+        self.total += delta.len() as u64;
+        let mut cut = 0;
+        if !self.head_closed {
+            cut = floor_char_boundary(delta, self.head_limit.saturating_sub(self.head_written));
+            self.head_written += cut;
+            self.head_closed = cut < delta.len();
+        }
+        let (head, rest) = delta.split_at(cut);
+        self.tail.push_str(rest);
+        if self.tail.len() > self.tail_limit {
+            let drop_to = ceil_char_boundary(&self.tail, self.tail.len() - self.tail_limit);
+            self.elided += drop_to as u64;
+            self.tail.drain(..drop_to);
+        }
+        head
+    }
+
+    /// Remaining transcript text: the held tail, preceded by an elision
+    /// marker when output was dropped.
+    pub(crate) fn finish(&mut self) -> String {
+        let tail = std::mem::take(&mut self.tail);
+        if self.elided == 0 {
+            return tail;
+        }
+        format!(
+            "\n[ptybox: transcript truncated: {} of {} bytes elided]\n{tail}",
+            self.elided, self.total
+        )
+    }
+}
+
+fn kib_to_bytes(kib: u64) -> usize {
+    usize::try_from(kib.saturating_mul(1024)).unwrap_or(usize::MAX)
+}
+
+/// Largest char boundary in `text` at or below `index`.
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    if index >= text.len() {
+        return text.len();
+    }
+    (0..=index)
+        .rev()
+        .find(|&i| text.is_char_boundary(i))
+        .unwrap_or(0)
+}
+
+/// Smallest char boundary in `text` at or above `index`.
+fn ceil_char_boundary(text: &str, index: usize) -> usize {
+    (index..text.len())
+        .find(|&i| text.is_char_boundary(i))
+        .unwrap_or(text.len())
+}
@@ -0,0 +1,119 @@
+//! Checksum assertions on captured output
+//! ([`Scenario::post`](crate::model::Scenario::post)).
+//!
+//! - `capture_checksum_equals` (`{capture?, sha256}`): the SHA-256 of the
+//!   step capture named `capture` equals `sha256`; without `capture`, the
+//!   digest of the run's whole transcript output is compared
+//!
+//! Digests come from the artifacts writer ([`CaptureDigests`]), so the
+//! assertion needs an artifacts directory. Results hold only the names and
+//! digests, which replay compares like any other run result.
+
+use crate::artifacts::CaptureDigests;
+use crate::model::scenario::Assertion;
+use crate::runner::{ErrorCode, RunnerError};
+use serde_json::Value;
+
+/// Assertion type comparing a capture's SHA-256.
+pub const CAPTURE_CHECKSUM_ASSERTION: &str = "capture_checksum_equals";
+
+/// A parsed `capture_checksum_equals` assertion.
+struct CaptureChecksum {
+    /// Step capture name; `None` for the transcript.
+    capture: Option<String>,
+    /// Expected lowercase hex digest.
+    sha256: String,
+}
+
+impl CaptureChecksum {
+    fn parse(assertion: &Assertion) -> Result<Self, RunnerError> {
+        let payload = &assertion.payload;
+        let capture = match payload.get("capture") {
+            None | Some(Value::Null) => None,
+            Some(Value::String(name)) => Some(name.clone()),
+            Some(_) => return Err(invalid_payload(payload, "'capture' must be a string")),
+        };
+        let sha256 = payload
+            .get("sha256")
+            .and_then(Value::as_str)
+            .ok_or_else(|| invalid_payload(payload, "missing required 'sha256' field"))?;
+        if sha256.len() != 64 || !sha256.chars().all(|ch| ch.is_ascii_hexdigit()) {
+            return Err(invalid_payload(
+                payload,
+                "'sha256' must be 64 hexadecimal characters",
+            ));
+        }
+        Ok(Self {
+            capture,
+            sha256: sha256.to_ascii_lowercase(),
+        })
+    }
+}
+
+fn invalid_payload(payload: &Value, reason: &str) -> RunnerError {
+    RunnerError::with_context(
+        ErrorCode::Protocol,
+        format!("{reason} in {CAPTURE_CHECKSUM_ASSERTION} payload"),
+        serde_json::json!({ "received_payload": payload }),
+    )
+}
+
+pub(super) fn is_checksum_assertion(assertion: &Assertion) -> bool {
+    assertion.assertion_type == CAPTURE_CHECKSUM_ASSERTION
+}
+
+/// Check the payload, and that a named capture is one of `capture_names`.
+pub(super) fn validate_capture_checksum(
+    assertion: &Assertion,
+    capture_names: &[&str],
+) -> Result<(), RunnerError> {
+    let parsed = CaptureChecksum::parse(assertion)?;
+    match parsed.capture {
+        Some(name) if !capture_names.contains(&name.as_str()) => Err(RunnerError::with_context(
+            ErrorCode::Protocol,
+            format!("{CAPTURE_CHECKSUM_ASSERTION} names a capture no step defines"),
+            serde_json::json!({
+                "capture": name,
+                "captures": capture_names,
+                "fix": "Use the name of a step's capture, or omit 'capture' to check the transcript"
+            }),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Compare the expected digest with the one recorded in `digests`.
+pub(super) fn evaluate_capture_checksum(
+    assertion: &Assertion,
+    digests: &CaptureDigests,
+) -> (bool, Option<String>, Option<Value>) {
+    let parsed = match CaptureChecksum::parse(assertion) {
+        Ok(parsed) => parsed,
+        Err(err) => return (false, Some(err.message), err.context),
+    };
+    let (target, actual) = match &parsed.capture {
+        Some(name) => (
+            format!("capture '{name}'"),
+            digests.captures.get(name).cloned(),
+        ),
+        None => ("transcript".to_string(), digests.transcript.clone()),
+    };
+    let details = serde_json::json!({
+        "capture": parsed.capture,
+        "expected": parsed.sha256,
+        "actual": actual,
+    });
+    match actual {
+        Some(actual) if actual == parsed.sha256 => (true, None, Some(details)),
+        Some(_) => (
+            false,
+            Some(format!("{target} SHA-256 does not match")),
+            Some(details),
+        ),
+        None => (
+            false,
+            Some(format!("{target} was not recorded")),
+            Some(details),
+        ),
+    }
+}
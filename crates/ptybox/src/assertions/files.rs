@@ -0,0 +1,197 @@
+//! Filesystem assertions checked after the process exits, as entries of
+//! [`Scenario::post_run`](crate::model::Scenario::post_run).
+//!
+//! - `file_exists` (`{path}`): a regular file exists at `path`
+//! - `file_contains` (`{path, text}`): the file contains `text` (read as
+//!   lossy UTF-8, at most [`MAX_FILE_ASSERTION_BYTES`])
+//!
+//! Paths must be absolute and inside `fs.allowed_read` or `fs.allowed_write`.
+//! Symlinks are resolved when the assertion is evaluated, and a file that
+//! resolves outside the allowlists fails the assertion without being read.
+
+use crate::model::policy::FsPolicy;
+use crate::model::scenario::Assertion;
+use crate::model::AssertionResult;
+use crate::policy::path_allowed;
+use crate::runner::{ErrorCode, RunnerError};
+use serde_json::Value;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+/// Filesystem assertion types, allowed only in `post_run`.
+pub const FILE_ASSERTIONS: &[&str] = &["file_exists", "file_contains"];
+
+/// Most bytes of a file `file_contains` reads.
+pub const MAX_FILE_ASSERTION_BYTES: u64 = 16 * 1024 * 1024;
+
+/// A parsed filesystem assertion.
+enum FileAssertion {
+    Exists { path: String },
+    Contains { path: String, text: String },
+}
+
+impl FileAssertion {
+    fn parse(assertion: &Assertion) -> Result<Self, RunnerError> {
+        let field = |name: &str| {
+            assertion
+                .payload
+                .get(name)
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .ok_or_else(|| {
+                    RunnerError::with_context(
+                        ErrorCode::Protocol,
+                        format!(
+                            "missing required '{name}' field in {} payload",
+                            assertion.assertion_type
+                        ),
+                        serde_json::json!({ "received_payload": assertion.payload }),
+                    )
+                })
+        };
+        match assertion.assertion_type.as_str() {
+            "file_exists" => Ok(Self::Exists {
+                path: field("path")?,
+            }),
+            "file_contains" => Ok(Self::Contains {
+                path: field("path")?,
+                text: field("text")?,
+            }),
+            other => Err(RunnerError::with_context(
+                ErrorCode::Protocol,
+                format!("unsupported file assertion type '{other}'"),
+                serde_json::json!({
+                    "received": other,
+                    "supported_types": FILE_ASSERTIONS,
+                }),
+            )),
+        }
+    }
+
+    fn path(&self) -> &str {
+        match self {
+            Self::Exists { path } | Self::Contains { path, .. } => path,
+        }
+    }
+}
+
+/// Check that every assertion is a file assertion on an absolute path inside
+/// the fs allowlists.
+///
+/// # Errors
+/// - `E_PROTOCOL` for another assertion type or a missing payload field
+/// - `E_POLICY_DENIED` for a relative path or one outside the allowlists
+pub(super) fn validate_file_assertions(
+    assertions: &[Assertion],
+    fs: &FsPolicy,
+) -> Result<(), RunnerError> {
+    for assertion in assertions {
+        let parsed = FileAssertion::parse(assertion)?;
+        let path = parsed.path();
+        if !Path::new(path).is_absolute()
+            || !path_allowed(path, &fs.allowed_read, &fs.allowed_write)
+        {
+            return Err(RunnerError::with_context(
+                ErrorCode::PolicyDenied,
+                "file assertion path is not within allowlisted paths",
+                serde_json::json!({
+                    "type": assertion.assertion_type,
+                    "path": path,
+                    "allowed_read": fs.allowed_read,
+                    "allowed_write": fs.allowed_write,
+                    "fix": "Use an absolute path inside policy.fs.allowed_read or allowed_write"
+                }),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Evaluate file assertions against the filesystem now.
+///
+/// Assertions are expected to have passed [`validate_file_assertions`]; one
+/// that does not parse fails with the parse error.
+pub(super) fn evaluate_file_assertions(
+    assertions: &[Assertion],
+    fs: &FsPolicy,
+) -> Vec<AssertionResult> {
+    assertions
+        .iter()
+        .map(|assertion| {
+            let (passed, message, details) = match FileAssertion::parse(assertion) {
+                Ok(parsed) => evaluate_file(&parsed, fs),
+                Err(err) => (false, Some(err.message), err.context),
+            };
+            AssertionResult {
+                assertion_type: assertion.assertion_type.clone(),
+                passed,
+                message,
+                details,
+                inherited: false,
+            }
+        })
+        .collect()
+}
+
+fn evaluate_file(
+    assertion: &FileAssertion,
+    fs: &FsPolicy,
+) -> (bool, Option<String>, Option<Value>) {
+    let path = assertion.path();
+    let resolved = match fs::canonicalize(path) {
+        Ok(resolved) if resolved.is_file() => resolved,
+        _ => {
+            return (
+                false,
+                Some(format!("file '{path}' does not exist")),
+                Some(serde_json::json!({ "path": path })),
+            )
+        }
+    };
+    let resolved_str = resolved.display().to_string();
+    if !path_allowed(
+        &resolved_str,
+        &resolve_all(&fs.allowed_read),
+        &resolve_all(&fs.allowed_write),
+    ) {
+        return (
+            false,
+            Some(format!("file '{path}' resolves outside the fs allowlists")),
+            Some(serde_json::json!({ "path": path, "resolved": resolved_str })),
+        );
+    }
+    let FileAssertion::Contains { text, .. } = assertion else {
+        return (true, None, None);
+    };
+    let mut data = Vec::new();
+    let read = fs::File::open(&resolved)
+        .and_then(|file| file.take(MAX_FILE_ASSERTION_BYTES).read_to_end(&mut data));
+    if let Err(err) = read {
+        return (
+            false,
+            Some(format!("failed to read file '{path}': {err}")),
+            Some(serde_json::json!({ "path": path })),
+        );
+    }
+    if String::from_utf8_lossy(&data).contains(text.as_str()) {
+        (true, None, None)
+    } else {
+        (
+            false,
+            Some(format!("file '{path}' does not contain '{text}'")),
+            Some(serde_json::json!({ "path": path, "text": text, "bytes_read": data.len() })),
+        )
+    }
+}
+
+/// Allowlist entries with symlinks resolved (as given when they do not
+/// exist), to compare with a resolved path.
+fn resolve_all(paths: &[String]) -> Vec<String> {
+    paths
+        .iter()
+        .map(|path| {
+            fs::canonicalize(path).map_or_else(|_| path.clone(), |path| path.display().to_string())
+        })
+        .collect()
+}
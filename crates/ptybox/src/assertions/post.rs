@@ -0,0 +1,96 @@
+//! Run-level post-conditions
+//! ([`Scenario::post_run`](crate::model::Scenario::post_run)).
+//!
+//! `post_run` assertions are checked once, after the steps have run and the
+//! process has exited or been terminated, whatever the steps' outcome. Each
+//! entry is a file assertion (`file_exists`, `file_contains`), a
+//! `capture_checksum_equals` check on captured output, or any predicate,
+//! evaluated against the observation taken after exit with the exit status
+//! and captured stderr. The deprecated `post` list is folded into `post_run`
+//! by [`Scenario::apply_defaults`](crate::model::Scenario::apply_defaults).
+
+use super::checksum::{
+    evaluate_capture_checksum, is_checksum_assertion, validate_capture_checksum,
+};
+use super::files::{evaluate_file_assertions, validate_file_assertions, FILE_ASSERTIONS};
+use super::{evaluate_in_context, AssertionContext};
+use crate::artifacts::CaptureDigests;
+use crate::model::policy::FsPolicy;
+use crate::model::scenario::Assertion;
+use crate::model::{AssertionResult, Observation};
+use crate::predicate::Predicate;
+use crate::runner::RunnerError;
+use std::slice;
+
+/// Check that every `post_run` assertion parses, that file assertions name
+/// paths inside the fs allowlists, and that checksum assertions name one of
+/// the scenario's `capture_names`.
+///
+/// # Errors
+/// - `E_PROTOCOL` for an unknown type, an invalid payload, or an unknown
+///   capture
+/// - `E_POLICY_DENIED` for a file assertion path outside the allowlists
+pub fn validate_post_run(
+    assertions: &[Assertion],
+    capture_names: &[&str],
+    fs: &FsPolicy,
+) -> Result<(), RunnerError> {
+    for assertion in assertions {
+        if is_checksum_assertion(assertion) {
+            validate_capture_checksum(assertion, capture_names)?;
+        } else if is_file_assertion(assertion) {
+            validate_file_assertions(slice::from_ref(assertion), fs)?;
+        } else {
+            Predicate::parse(&assertion.assertion_type, &assertion.payload)?;
+        }
+    }
+    Ok(())
+}
+
+/// Evaluate `post_run` assertions.
+///
+/// File assertions read the filesystem now and checksum assertions compare
+/// against `captures`; the others are evaluated against `observation` in
+/// `context`, and fail when there is no observation.
+#[must_use]
+pub fn evaluate_post_run(
+    assertions: &[Assertion],
+    observation: Option<&Observation>,
+    context: &AssertionContext<'_>,
+    captures: &CaptureDigests,
+    fs: &FsPolicy,
+) -> Vec<AssertionResult> {
+    assertions
+        .iter()
+        .map(|assertion| {
+            if is_file_assertion(assertion) {
+                if let Some(result) = evaluate_file_assertions(slice::from_ref(assertion), fs).pop()
+                {
+                    return result;
+                }
+            }
+            let (passed, message, details) = match observation {
+                _ if is_checksum_assertion(assertion) => {
+                    evaluate_capture_checksum(assertion, captures)
+                }
+                Some(observation) => evaluate_in_context(observation, assertion, context),
+                None => (
+                    false,
+                    Some("no observation was captured after the run".to_string()),
+                    None,
+                ),
+            };
+            AssertionResult {
+                assertion_type: assertion.assertion_type.clone(),
+                passed,
+                message,
+                details,
+                inherited: false,
+            }
+        })
+        .collect()
+}
+
+fn is_file_assertion(assertion: &Assertion) -> bool {
+    FILE_ASSERTIONS.contains(&assertion.assertion_type.as_str())
+}
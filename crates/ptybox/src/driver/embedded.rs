@@ -0,0 +1,286 @@
+//! In-process driver handle for embedders that cannot use stdin/stdout.
+//!
+//! [`EmbeddedDriver`] runs the same protocol v2 loop as [`run_driver`](super::run_driver)
+//! on a background thread and exchanges request/response lines over channels.
+//! It is the engine behind the optional C ABI in `ptybox::ffi`.
+
+use super::{run_driver_with_io, DriverConfig};
+use crate::model::driver::{DriverEvent, DriverEventAck};
+use crate::runner::{RunnerError, RunnerResult};
+use std::collections::VecDeque;
+use std::io::{self, BufReader, Read, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// A protocol v2 driver session running in-process.
+///
+/// Requests and responses are the same JSON objects exchanged by
+/// `ptybox driver --stdio`: [`DriverRequestV2`](crate::model::driver::DriverRequestV2)
+/// in, [`DriverResponseV2`](crate::model::driver::DriverResponseV2) out.
+/// After a `subscribe` request, [`DriverEvent`] frames are set aside for
+/// [`next_event`](Self::next_event) instead of being returned by `send`.
+///
+/// # Example
+/// ```no_run
+/// # use ptybox::driver::{DriverConfig, EmbeddedDriver};
+/// # use ptybox::model::Policy;
+/// # fn example() -> Result<(), ptybox::runner::RunnerError> {
+/// let mut driver = EmbeddedDriver::start(DriverConfig {
+///     command: "/bin/cat".to_string(),
+///     args: Vec::new(),
+///     cwd: None,
+///     policy: Policy::default(),
+///     artifacts: None,
+///     separate_stderr: false,
+///     ack_overrides: Vec::new(),
+/// })?;
+/// let response = driver.send(
+///     r#"{"protocol_version":2,"request_id":"r1","action":{"type":"text","payload":{"text":"hi"}}}"#,
+/// )?;
+/// driver.close()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct EmbeddedDriver {
+    input: Option<Sender<Vec<u8>>>,
+    output: Receiver<String>,
+    handshake: String,
+    /// Event frames received and not yet taken.
+    events: VecDeque<String>,
+    /// A response that arrived while waiting for an event.
+    response: Option<String>,
+    thread: Option<JoinHandle<RunnerResult<()>>>,
+}
+
+impl EmbeddedDriver {
+    /// Validate the policy, spawn the child, and wait for the driver handshake.
+    ///
+    /// # Errors
+    /// Any error [`run_driver`](super::run_driver) reports before its
+    /// handshake (policy denial, spawn failure, artifacts setup).
+    pub fn start(config: DriverConfig) -> RunnerResult<Self> {
+        let (input_tx, input_rx) = mpsc::channel();
+        let (output_tx, output_rx) = mpsc::channel();
+        let thread = std::thread::Builder::new()
+            .name("ptybox-embedded-driver".to_string())
+            .spawn(move || {
+                run_driver_with_io(
+                    config,
+                    BufReader::new(ChannelReader::new(input_rx)),
+                    LineWriter::new(output_tx),
+                )
+            })
+            .map_err(|err| RunnerError::io("E_IO", "failed to start driver thread", err))?;
+
+        let mut driver = Self {
+            input: Some(input_tx),
+            output: output_rx,
+            handshake: String::new(),
+            events: VecDeque::new(),
+            response: None,
+            thread: Some(thread),
+        };
+        match driver.output.recv() {
+            Ok(handshake) => {
+                driver.handshake = handshake;
+                Ok(driver)
+            }
+            Err(_) => Err(driver.finish()),
+        }
+    }
+
+    /// The handshake JSON line emitted when the session started.
+    pub fn handshake(&self) -> &str {
+        &self.handshake
+    }
+
+    /// Send one request JSON object and return the response JSON line.
+    ///
+    /// # Errors
+    /// - `E_PROTOCOL`: Request is empty or spans multiple lines
+    /// - The driver's terminal error if the session has already ended
+    pub fn send(&mut self, request: &str) -> RunnerResult<String> {
+        if self.write_line(request)? {
+            if let Some(response) = self.response.take() {
+                return Ok(response);
+            }
+            while let Ok(line) = self.output.recv() {
+                if !is_event(&line) {
+                    return Ok(line);
+                }
+                self.events.push_back(line);
+            }
+        }
+        Err(self.finish())
+    }
+
+    /// Acknowledge every event up to `event_seq`. The driver sends no
+    /// response to an acknowledgement.
+    ///
+    /// # Errors
+    /// The driver's terminal error if the session has already ended.
+    pub fn ack(&mut self, event_seq: u64) -> RunnerResult<()> {
+        let ack = serde_json::to_string(&DriverEventAck { event_seq })
+            .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to serialize ack", err))?;
+        if self.write_line(&ack)? {
+            Ok(())
+        } else {
+            Err(self.finish())
+        }
+    }
+
+    /// Take the oldest unread event JSON line, waiting up to `timeout` for
+    /// one; `None` when none arrived.
+    ///
+    /// A response arriving meanwhile (an idle-budget error) is kept for the
+    /// next [`send`](Self::send).
+    pub fn next_event(&mut self, timeout: Duration) -> Option<String> {
+        if let Some(event) = self.events.pop_front() {
+            return Some(event);
+        }
+        if self.response.is_some() {
+            return None;
+        }
+        match self.output.recv_timeout(timeout) {
+            Ok(line) if is_event(&line) => Some(line),
+            Ok(line) => {
+                self.response = Some(line);
+                None
+            }
+            Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => None,
+        }
+    }
+
+    /// Send one line to the driver; `false` once its input is gone.
+    fn write_line(&mut self, line: &str) -> RunnerResult<bool> {
+        let line = line.trim();
+        if line.is_empty() || line.contains('\n') {
+            return Err(RunnerError::protocol(
+                "E_PROTOCOL",
+                "request must be a single non-empty JSON line",
+                None,
+            ));
+        }
+        let mut bytes = line.as_bytes().to_vec();
+        bytes.push(b'\n');
+        Ok(self
+            .input
+            .as_ref()
+            .is_some_and(|input| input.send(bytes).is_ok()))
+    }
+
+    /// End the session: close the request stream, wait for the driver to
+    /// terminate the child and write final artifacts.
+    ///
+    /// # Errors
+    /// The driver's terminal error, if the session ended with one.
+    pub fn close(mut self) -> RunnerResult<()> {
+        self.input = None;
+        match self.thread.take().map(JoinHandle::join) {
+            Some(Ok(result)) => result,
+            Some(Err(_)) => Err(driver_panicked()),
+            None => Ok(()),
+        }
+    }
+
+    /// Join a driver thread that stopped on its own and report why.
+    fn finish(&mut self) -> RunnerError {
+        self.input = None;
+        match self.thread.take().map(JoinHandle::join) {
+            Some(Ok(Err(err))) => err,
+            Some(Err(_)) => driver_panicked(),
+            Some(Ok(Ok(()))) | None => {
+                RunnerError::protocol("E_PROTOCOL", "driver session has ended", None)
+            }
+        }
+    }
+}
+
+impl Drop for EmbeddedDriver {
+    fn drop(&mut self) {
+        self.input = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn is_event(line: &str) -> bool {
+    serde_json::from_str::<DriverEvent>(line).is_ok()
+}
+
+fn driver_panicked() -> RunnerError {
+    RunnerError::internal("E_INTERNAL", "driver thread panicked")
+}
+
+/// [`Read`] adapter over a channel of byte chunks; EOF when all senders drop.
+struct ChannelReader {
+    chunks: Receiver<Vec<u8>>,
+    pending: Vec<u8>,
+    offset: usize,
+}
+
+impl ChannelReader {
+    fn new(chunks: Receiver<Vec<u8>>) -> Self {
+        Self {
+            chunks,
+            pending: Vec::new(),
+            offset: 0,
+        }
+    }
+}
+
+impl Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.offset >= self.pending.len() {
+            match self.chunks.recv() {
+                Ok(chunk) => {
+                    self.pending = chunk;
+                    self.offset = 0;
+                }
+                Err(_) => return Ok(0),
+            }
+        }
+        let available = self.pending.get(self.offset..).unwrap_or_default();
+        let count = available.len().min(buf.len());
+        if let (Some(dst), Some(src)) = (buf.get_mut(..count), available.get(..count)) {
+            dst.copy_from_slice(src);
+        }
+        self.offset += count;
+        Ok(count)
+    }
+}
+
+/// [`Write`] adapter that forwards each complete line to a channel.
+struct LineWriter {
+    lines: Sender<String>,
+    buffer: Vec<u8>,
+}
+
+impl LineWriter {
+    fn new(lines: Sender<String>) -> Self {
+        Self {
+            lines,
+            buffer: Vec::new(),
+        }
+    }
+}
+
+impl Write for LineWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        while let Some(pos) = self.buffer.iter().position(|byte| *byte == b'\n') {
+            let rest = self.buffer.split_off(pos + 1);
+            let mut line = std::mem::replace(&mut self.buffer, rest);
+            line.pop();
+            // A dropped receiver means the embedder is gone; keep draining.
+            let _ = self.lines.send(String::from_utf8_lossy(&line).into_owned());
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+//! In-memory session log answered by `history` queries.
+//!
+//! Every answered action request is summarized (request id, action, status,
+//! timestamps, `screen_hash`) so a client that reconnects mid-session can
+//! resynchronize without reading artifacts from disk. `history` queries
+//! themselves are not logged.
+
+use crate::model::driver::{DriverHistory, DriverHistoryEntry, DriverRequestV2, DriverResponseV2};
+use crate::model::{Action, Observation};
+use crate::runner::{ErrorCode, RunnerError, RunnerResult};
+use std::collections::VecDeque;
+
+/// Entries kept in memory; older entries are dropped first.
+pub const MAX_DRIVER_HISTORY: usize = 256;
+
+/// Bounded log of the session's answered requests.
+#[derive(Debug, Default)]
+pub(crate) struct HistoryLog {
+    entries: VecDeque<DriverHistoryEntry>,
+    total: u64,
+}
+
+impl HistoryLog {
+    /// Log the `response` to `request`, which observed `observation`.
+    pub(crate) fn record(
+        &mut self,
+        request: &DriverRequestV2,
+        response: &DriverResponseV2,
+        observation: &Observation,
+        started_at_ms: u64,
+        ended_at_ms: u64,
+    ) {
+        if self.entries.len() >= MAX_DRIVER_HISTORY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(DriverHistoryEntry {
+            request_id: request.request_id.clone(),
+            action: request.action.clone(),
+            status: response.status.clone(),
+            sequence: response
+                .action_metrics
+                .as_ref()
+                .map(|metrics| metrics.sequence),
+            error_code: response.error.as_ref().map(|error| error.code.clone()),
+            started_at_ms,
+            ended_at_ms,
+            screen_hash: response.screen_hash.clone(),
+            observation_timestamp_ms: Some(observation.timestamp_ms),
+            unchanged: response.unchanged,
+        });
+        self.total += 1;
+    }
+
+    /// Answer a `history` query (payload `{limit?}`) with the most recent
+    /// entries, oldest first.
+    ///
+    /// # Errors
+    /// `E_PROTOCOL` if `limit` is present but not a positive integer.
+    pub(crate) fn query(&self, action: &Action) -> RunnerResult<DriverHistory> {
+        let limit = match action.payload.get("limit") {
+            None | Some(serde_json::Value::Null) => MAX_DRIVER_HISTORY,
+            Some(value) => value
+                .as_u64()
+                .filter(|limit| *limit > 0)
+                .map(|limit| usize::try_from(limit).unwrap_or(usize::MAX))
+                .ok_or_else(|| {
+                    RunnerError::with_context(
+                        ErrorCode::Protocol,
+                        "history limit must be a positive integer",
+                        serde_json::json!({ "received": value }),
+                    )
+                })?,
+        };
+        let skip = self.entries.len().saturating_sub(limit);
+        Ok(DriverHistory {
+            entries: self.entries.iter().skip(skip).cloned().collect(),
+            total: self.total,
+        })
+    }
+}
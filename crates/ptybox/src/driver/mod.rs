@@ -9,10 +9,13 @@
 //! # Key Types
 //!
 //! - [`DriverConfig`] — Runtime configuration (command, policy, artifacts)
+//! - [`EmbeddedDriver`] — In-process session exchanging request/response lines
+//! - [`ProtocolVectors`] — Canonical request/response examples for client implementers
 //!
 //! # Key Functions
 //!
 //! - [`run_driver`] — Start the stdin/stdout driver loop
+//! - [`write_protocol_vectors`] — Write conformance vectors and schemas to a directory
 //!
 //! # Protocol Flow
 //!
@@ -21,36 +24,68 @@
 //! 3. Driver writes a JSON line response with the observation or error
 //! 4. Loop ends when client sends `terminate` or an error occurs
 //!
+//! After a `subscribe` request the driver also pushes unsolicited `event`
+//! frames between responses (see [`DriverEvent`](crate::model::DriverEvent)).
+//!
+//! Input is read on a background thread so the loop can enforce
+//! `budgets.max_session_idle_ms` and `budgets.max_session_lifetime_ms` while
+//! the client is silent; when either expires the child is terminated and
+//! final artifacts are written.
+//!
 //! # Artifacts
 //!
 //! When artifacts are enabled, the driver writes:
 //! - `driver-actions.jsonl` — log of all actions with timing
+//! - `driver-events.jsonl` — event frames pushed to a subscribed client
 //! - `scenario.json` — generated scenario from the action sequence
 //! - Standard artifacts (snapshots, transcript, events, run.json, checksums)
 
-use crate::actions::perform_action;
+mod embedded;
+mod history;
+mod screenshot;
+mod subscription;
+mod vectors;
+
+pub use embedded::EmbeddedDriver;
+pub use history::MAX_DRIVER_HISTORY;
+pub use vectors::{
+    protocol_vectors, write_protocol_vectors, ManifestError, ProtocolVector, ProtocolVectors,
+    VectorsManifest, PROTOCOL_SCHEMAS,
+};
+
+use crate::actions::{action_latency_ms, perform_action};
 use crate::artifacts::{ArtifactsWriter, ArtifactsWriterConfig};
-use crate::model::policy::Policy;
+use crate::model::policy::{Budgets, Keymap, Policy};
 use crate::model::{
     driver::{
-        BudgetStatus, DriverActionMetrics, DriverActionRecord, DriverRequestV2,
-        DriverResponseStatus, DriverResponseV2,
+        BudgetStatus, DriverActionMetrics, DriverActionRecord, DriverEventAck, DriverHistory,
+        DriverRequestV2, DriverResponseStatus, DriverResponseV2, DriverSubscription,
     },
-    ActionType, ErrorInfo, NormalizationRecord, RunConfig, RunId, RunResult, RunStatus, Scenario,
+    Acknowledgement, AcknowledgementReport, Action, ActionType, EnforcementReport, ErrorInfo,
+    ExitStatus, HostFingerprint, NormalizationRecord, Observation, OutputBufferConfig,
+    ResizeConfig, RunConfig, RunId, RunResult, RunStatus, Scenario, ScenarioDefaults,
     ScenarioMetadata, Step, StepId, StepResult, StepStatus, TerminalSize, NORMALIZATION_VERSION,
     PROTOCOL_VERSION, RUN_RESULT_VERSION, SCENARIO_VERSION,
 };
+use crate::policy::acknowledgements::acknowledgement_report;
+use crate::policy::keymap::resolve_keymap;
 use crate::policy::{
-    validate_artifacts_dir, validate_artifacts_policy, validate_policy, validate_write_access,
-    EffectivePolicy,
+    fallback_spawn_policy, validate_artifacts_dir, validate_artifacts_policy, validate_policy,
+    validate_write_access, EffectivePolicy,
+};
+use crate::runner::{
+    arm_write_quotas, catch_panic, classify_failure, finish_write_quotas, run_provenance,
+    write_fs_manifest, ErrorCode, RunnerError, RunnerResult,
 };
-use crate::runner::{RunnerError, RunnerResult};
-use crate::session::{Session, SessionConfig};
+use crate::session::{Session, SessionConfig, Termination};
 use crate::util::{
-    build_spawn_command, convert_exit_status, elapsed_ms, resolve_artifacts_config, snapshot_bytes,
-    SandboxCleanupGuard,
+    bind_policy_sockets, build_spawn_command, convert_exit_status, elapsed_ms,
+    resolve_artifacts_config, snapshot_bytes, SandboxCleanupGuard,
 };
-use std::io::{self, BufRead, Write};
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
 use std::time::{Duration, Instant};
 
 /// Driver runtime configuration.
@@ -66,6 +101,13 @@ pub struct DriverConfig {
     pub policy: Policy,
     /// Optional artifacts configuration.
     pub artifacts: Option<ArtifactsWriterConfig>,
+    /// Route the child's stderr to a separate pipe (see
+    /// [`RunConfig::separate_stderr`](crate::model::RunConfig::separate_stderr)).
+    pub separate_stderr: bool,
+    /// Acknowledgements given outside the policy, recorded in
+    /// `acknowledgements.json` (see
+    /// [`RunnerOptions::ack_overrides`](crate::runner::RunnerOptions::ack_overrides)).
+    pub ack_overrides: Vec<Acknowledgement>,
 }
 
 /// Run the protocol v2 driver loop against stdin/stdout.
@@ -75,6 +117,7 @@ pub struct DriverConfig {
 /// - The client sends a `terminate` action
 /// - A protocol error occurs (invalid JSON, version mismatch)
 /// - A budget is exceeded (runtime, steps, output, snapshot)
+/// - The client is silent past the session idle or lifetime budget
 /// - The child process exits unexpectedly
 ///
 /// # Errors
@@ -83,79 +126,281 @@ pub struct DriverConfig {
 /// - `E_POLICY_DENIED` — Policy validation failed before spawning
 /// - `E_PROTOCOL` — Invalid request JSON or payload
 /// - `E_PROTOCOL_VERSION_MISMATCH` — Unsupported protocol version
-/// - `E_TIMEOUT` — Budget exceeded (runtime, steps, output, snapshot, wait)
+/// - `E_TIMEOUT` — Budget exceeded (runtime, steps, output, snapshot, wait,
+///   session idle, session lifetime)
 /// - `E_PROCESS_EXIT` — Child process exited during a wait condition
 /// - `E_IO` — I/O failure on stdin/stdout or artifact writes
 pub fn run_driver(config: DriverConfig) -> RunnerResult<()> {
-    let stdin = io::stdin();
     let stdout = io::stdout();
-    run_driver_with_io(config, stdin.lock(), stdout.lock())
+    run_driver_with_io(config, BufReader::new(io::stdin()), stdout.lock())
 }
 
-#[allow(clippy::too_many_lines, clippy::cognitive_complexity)]
-fn run_driver_with_io<R, W>(config: DriverConfig, input: R, mut output: W) -> RunnerResult<()>
+pub(crate) fn run_driver_with_io<R, W>(
+    config: DriverConfig,
+    input: R,
+    output: W,
+) -> RunnerResult<()>
 where
-    R: BufRead,
+    R: BufRead + Send + 'static,
     W: Write,
 {
-    const MAX_CONSECUTIVE_PARSE_ERRORS: u32 = 5;
-
-    let DriverConfig {
-        command,
-        args,
-        cwd,
-        policy,
-        artifacts,
-    } = config;
-
-    validate_policy(&policy)?;
-    validate_artifacts_policy(&policy)?;
-    let effective_policy = EffectivePolicy::new(policy.clone());
-    let run_config = RunConfig {
-        command: command.clone(),
-        args: args.clone(),
-        cwd: cwd.clone(),
-        initial_size: TerminalSize::default(),
-        policy: crate::model::scenario::PolicyRef::Inline(Box::new(policy.clone())),
-    };
-    effective_policy.validate_run_config(&run_config)?;
-
-    let artifacts_config = resolve_artifacts_config(&policy, artifacts);
+    let enforcement = validate_driver_config(&config)?;
+    let artifacts_config = resolve_artifacts_config(&config.policy, config.artifacts.clone());
     let artifacts_dir = artifacts_config.as_ref().map(|cfg| cfg.dir.clone());
-    validate_write_access(&policy, artifacts_dir.as_deref())?;
+    validate_write_access(&config.policy, artifacts_dir.as_deref())?;
     if let Some(cfg) = artifacts_config.as_ref() {
-        validate_artifacts_dir(&cfg.dir, &policy.fs)?;
+        validate_artifacts_dir(&cfg.dir, &config.policy.fs)?;
     }
 
     let run_id = RunId::new();
-    let run_started = Instant::now();
-    let mut writer = if let Some(cfg) = artifacts_config {
-        Some(ArtifactsWriter::new(run_id, cfg)?)
-    } else {
-        None
+    let acknowledgements = acknowledgement_report(&config.policy, &config.ack_overrides);
+    let mut writer = open_driver_artifacts(
+        &config.policy,
+        enforcement.as_ref(),
+        acknowledgements.as_ref(),
+        run_id,
+        artifacts_config,
+    )?;
+    let mut process = DriverProcess::spawn(
+        &config,
+        enforcement,
+        acknowledgements,
+        run_id,
+        artifacts_dir.as_ref(),
+        writer.as_mut(),
+    )?;
+    let episode = drive_episode(&mut process, writer, artifacts_dir, input, output)?;
+    finish_episode(&mut process, episode, true)
+}
+
+/// Check everything about `config` that does not depend on the artifacts
+/// directory: the policy itself and the command it runs.
+pub(crate) fn validate_driver_config(
+    config: &DriverConfig,
+) -> RunnerResult<Option<EnforcementReport>> {
+    let enforcement = validate_policy(&config.policy)?;
+    validate_artifacts_policy(&config.policy)?;
+    EffectivePolicy::new(config.policy.clone()).validate_run_config(&RunConfig {
+        command: config.command.clone(),
+        args: config.args.clone(),
+        cwd: config.cwd.clone(),
+        initial_size: TerminalSize::default(),
+        policy: crate::model::scenario::PolicyRef::Inline(Box::new(config.policy.clone())),
+        separate_stderr: config.separate_stderr,
+        output_buffer: OutputBufferConfig::default(),
+        resize: ResizeConfig::default(),
+        remote: None,
+        validate_args: false,
+        capture_attributes: false,
+    })?;
+    Ok(enforcement)
+}
+
+/// Create the artifacts writer of one driver session and write the records
+/// known before the first request.
+pub(crate) fn open_driver_artifacts(
+    policy: &Policy,
+    enforcement: Option<&EnforcementReport>,
+    acknowledgements: Option<&AcknowledgementReport>,
+    run_id: RunId,
+    config: Option<ArtifactsWriterConfig>,
+) -> RunnerResult<Option<ArtifactsWriter>> {
+    let Some(config) = config else {
+        return Ok(None);
     };
-    if let Some(writer) = writer.as_mut() {
-        writer.write_policy(&policy)?;
-        writer.write_normalization(&NormalizationRecord {
-            normalization_version: NORMALIZATION_VERSION,
-            filters: Vec::new(),
-            strict: false,
-            source: crate::model::NormalizationSource::None,
-            rules: Vec::new(),
+    let mut writer = ArtifactsWriter::new(run_id, config)?;
+    writer.set_layout(policy.artifacts.layout);
+    writer.set_transcript(&policy.artifacts.transcript);
+    writer.set_canonical_json(policy.artifacts.canonical_json);
+    writer.set_asciicast(policy.artifacts.asciicast);
+    if let Some(encryption) = &policy.artifacts.encryption {
+        writer.set_encryption(encryption)?;
+    }
+    writer.write_policy(policy)?;
+    writer.write_host(&HostFingerprint::current())?;
+    writer.write_env(&crate::policy::env_record(&policy.effective_env()))?;
+    if let Some(report) = enforcement {
+        writer.write_enforcement(report)?;
+    }
+    if let Some(report) = acknowledgements {
+        writer.write_acknowledgements(report)?;
+    }
+    writer.write_normalization(&NormalizationRecord {
+        normalization_version: NORMALIZATION_VERSION,
+        filters: Vec::new(),
+        strict: false,
+        source: crate::model::NormalizationSource::None,
+        rules: Vec::new(),
+    })?;
+    Ok(Some(writer))
+}
+
+/// A child spawned under a validated driver policy, not yet or no longer
+/// attached to a client. Dropping it kills the child.
+pub(crate) struct DriverProcess {
+    command: String,
+    args: Vec<String>,
+    cwd: Option<String>,
+    policy: Policy,
+    enforcement: Option<EnforcementReport>,
+    acknowledgements: Option<AcknowledgementReport>,
+    keymap: Option<Keymap>,
+    separate_stderr: bool,
+    run_id: RunId,
+    pub(crate) session: Session,
+    /// Declared after `session` so the child is gone before its sandbox
+    /// profile and sockets are removed.
+    _cleanup_guard: SandboxCleanupGuard,
+}
+
+impl DriverProcess {
+    /// Spawn the child of `config`, which passed [`validate_driver_config`].
+    ///
+    /// `artifacts_dir` receives the sandbox profile and is left out of
+    /// write quotas, and `writer` records a container invocation; the
+    /// artifacts in `config` are not used.
+    pub(crate) fn spawn(
+        config: &DriverConfig,
+        enforcement: Option<EnforcementReport>,
+        acknowledgements: Option<AcknowledgementReport>,
+        run_id: RunId,
+        artifacts_dir: Option<&PathBuf>,
+        writer: Option<&mut ArtifactsWriter>,
+    ) -> RunnerResult<Self> {
+        let keymap = resolve_keymap(&config.policy.input)?;
+        let mut spawn = build_spawn_command(
+            &fallback_spawn_policy(&config.policy, enforcement.as_ref()),
+            &config.command,
+            &config.args,
+            config.cwd.as_deref(),
+            artifacts_dir,
+            run_id,
+        )?;
+        let mut cleanup_guard = SandboxCleanupGuard::new(spawn.cleanup_path.clone());
+        cleanup_guard.container = spawn.container.take();
+        if let (Some(writer), Some(invocation)) = (writer, &spawn.invocation) {
+            writer.write_container_invocation(invocation)?;
+        }
+        let env = bind_policy_sockets(&config.policy, run_id, None, &mut cleanup_guard)?;
+
+        let mut session = Session::spawn(SessionConfig {
+            command: spawn.command,
+            args: spawn.args,
+            cwd: config.cwd.clone(),
+            size: TerminalSize::default(),
+            run_id,
+            env,
+            separate_stderr: config.separate_stderr,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            encoding: config.policy.terminal.encoding,
         })?;
+        if let Some(container) = &cleanup_guard.container {
+            session.set_container(container.clone());
+        }
+        session.set_keymap(keymap.clone());
+        arm_write_quotas(&mut session, &config.policy, artifacts_dir);
+        Ok(Self {
+            command: config.command.clone(),
+            args: config.args.clone(),
+            cwd: config.cwd.clone(),
+            policy: config.policy.clone(),
+            enforcement,
+            acknowledgements,
+            keymap,
+            separate_stderr: config.separate_stderr,
+            run_id,
+            session,
+            _cleanup_guard: cleanup_guard,
+        })
     }
 
-    let spawn = build_spawn_command(&policy, &command, &args, artifacts_dir.as_ref(), run_id)?;
-    let cleanup_guard = SandboxCleanupGuard::new(spawn.cleanup_path.clone());
+    /// Run identifier of the child, reported in every handshake.
+    pub(crate) const fn run_id(&self) -> RunId {
+        self.run_id
+    }
 
-    let mut session = Session::spawn(SessionConfig {
-        command: spawn.command,
-        args: spawn.args,
-        cwd: cwd.clone(),
-        size: TerminalSize::default(),
-        run_id,
-        env: policy.env.clone(),
-    })?;
+    /// Terminate the child per `process` policy and report how it exited.
+    /// `at_deadline` sends `process.deadline_signal` first (see
+    /// [`Session::terminate_at_deadline`]).
+    fn stop(&mut self, at_deadline: bool) -> Option<ExitStatus> {
+        match self.session.wait_for_exit(Duration::from_millis(50)) {
+            Ok(Some(status)) => Some(convert_exit_status(status, false)),
+            Ok(None) | Err(_) if at_deadline => self
+                .session
+                .terminate_at_deadline(&self.policy.process)
+                .ok()
+                .and_then(Termination::exit_status),
+            Ok(None) | Err(_) => self
+                .session
+                .terminate_with(&self.policy.process)
+                .ok()
+                .and_then(Termination::exit_status),
+        }
+    }
+}
+
+/// What one client connection did, kept for its run result.
+pub(crate) struct DriverEpisode {
+    writer: Option<ArtifactsWriter>,
+    artifacts_dir: Option<PathBuf>,
+    started: Instant,
+    steps: Vec<Step>,
+    step_results: Vec<StepResult>,
+    final_observation: Option<Observation>,
+    pub(crate) error: Option<RunnerError>,
+    /// The client closed its input without a `terminate`.
+    pub(crate) detached: bool,
+}
+
+/// Send the handshake and answer requests until the client terminates,
+/// disconnects, or fails a budget. Budgets and step numbering start with
+/// the episode, not with the child.
+///
+/// A panic while performing an action is answered like any failed action
+/// and ends the episode with its `E_INTERNAL` error. A panic anywhere else
+/// is answered with a final `E_INTERNAL` error response (request id
+/// `unknown`) and returned.
+pub(crate) fn drive_episode<R, W>(
+    process: &mut DriverProcess,
+    writer: Option<ArtifactsWriter>,
+    artifacts_dir: Option<PathBuf>,
+    input: R,
+    mut output: W,
+) -> RunnerResult<DriverEpisode>
+where
+    R: BufRead + Send + 'static,
+    W: Write,
+{
+    catch_panic("driver episode", || {
+        answer_requests(process, writer, artifacts_dir, input, &mut output)
+    })
+    .unwrap_or_else(|panic| {
+        let response = error_response("unknown", panic.to_error_info(), None, None);
+        let _ = emit_driver_response(&mut output, &response);
+        Err(panic)
+    })
+}
+
+#[allow(clippy::too_many_lines, clippy::cognitive_complexity)]
+fn answer_requests<R, W>(
+    process: &mut DriverProcess,
+    mut writer: Option<ArtifactsWriter>,
+    artifacts_dir: Option<PathBuf>,
+    input: R,
+    mut output: W,
+) -> RunnerResult<DriverEpisode>
+where
+    R: BufRead + Send + 'static,
+    W: Write,
+{
+    const MAX_CONSECUTIVE_PARSE_ERRORS: u32 = 5;
+
+    let run_id = process.run_id;
+    let policy = &process.policy;
+    let session = &mut process.session;
+    let run_started = Instant::now();
 
     // Emit handshake so agents know protocol capabilities upfront
     let handshake = serde_json::json!({
@@ -172,9 +417,11 @@ where
             "max_output_bytes": policy.budgets.max_output_bytes,
             "max_snapshot_bytes": policy.budgets.max_snapshot_bytes,
             "max_wait_ms": policy.budgets.max_wait_ms,
+            "max_session_idle_ms": policy.budgets.max_session_idle_ms,
+            "max_session_lifetime_ms": policy.budgets.max_session_lifetime_ms,
         },
-        "supported_actions": ["key", "text", "resize", "wait", "observe", "terminate"],
-        "supported_conditions": ["screen_contains", "screen_matches", "cursor_at", "process_exited"],
+        "supported_actions": ["key", "text", "resize", "wait", "observe", "terminate", "raw", "hold_key", "termios", "history", "subscribe"],
+        "supported_conditions": crate::predicate::PREDICATES.map(|spec| spec.name),
     });
     let handshake_str = serde_json::to_string(&handshake)
         .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to serialize handshake", err))?;
@@ -186,15 +433,77 @@ where
 
     let mut output_bytes: u64 = 0;
     let mut sequence: u64 = 0;
+    // `screen_hash` of the last response that carried an observation.
+    let mut last_screen_hash: Option<String> = None;
+    let mut history = history::HistoryLog::default();
+    let mut events = subscription::EventStream::default();
     let mut scenario_steps: Vec<Step> = Vec::new();
     let mut step_results: Vec<StepResult> = Vec::new();
     let mut final_observation = None;
     let mut final_error: Option<RunnerError> = None;
+    let mut detached = false;
     let mut consecutive_parse_errors: u32 = 0;
+    let input_lines = spawn_input_reader(input);
+    let mut last_activity = Instant::now();
 
-    for line in input.lines() {
-        let line =
-            line.map_err(|err| RunnerError::io("E_IO", "failed to read driver input", err))?;
+    loop {
+        events.resync(session);
+        let input = loop {
+            let input = next_driver_input(
+                &input_lines,
+                &policy.budgets,
+                &run_started,
+                &last_activity,
+                events.poll_interval(),
+            )?;
+            match input {
+                DriverInput::Tick => {}
+                DriverInput::Line(line) => match serde_json::from_str::<DriverEventAck>(&line) {
+                    Ok(ack) => {
+                        last_activity = Instant::now();
+                        events.ack(ack.event_seq);
+                    }
+                    Err(_) => break DriverInput::Line(line),
+                },
+                input => break input,
+            }
+            if events.poll_interval().is_some() {
+                push_events(
+                    &mut events,
+                    session,
+                    &mut writer,
+                    &mut output_bytes,
+                    &mut last_screen_hash,
+                    &mut output,
+                )?;
+            }
+        };
+        let line = match input {
+            DriverInput::Line(line) => line,
+            DriverInput::Tick => continue,
+            DriverInput::Closed => {
+                detached = true;
+                break;
+            }
+            DriverInput::Expired(err) => {
+                let response = error_response(
+                    "unknown",
+                    err.to_error_info(),
+                    Some(make_budget_status(
+                        sequence,
+                        policy,
+                        &run_started,
+                        output_bytes,
+                        session.wait_stats().wait_ms,
+                    )),
+                    None,
+                );
+                emit_driver_response(&mut output, &response)?;
+                final_error = Some(err);
+                break;
+            }
+        };
+        last_activity = Instant::now();
         if line.trim().is_empty() {
             continue;
         }
@@ -258,6 +567,12 @@ where
             break;
         }
 
+        if let Some(error) = screenshot::image_unsupported(&request) {
+            let response = error_response(&request.request_id, error, None, None);
+            emit_driver_response(&mut output, &response)?;
+            continue;
+        }
+
         if sequence >= policy.budgets.max_steps {
             let response = error_response(
                 &request.request_id,
@@ -268,9 +583,10 @@ where
                 },
                 Some(make_budget_status(
                     sequence,
-                    &policy,
+                    policy,
                     &run_started,
                     output_bytes,
+                    session.wait_stats().wait_ms,
                 )),
                 None,
             );
@@ -295,9 +611,10 @@ where
                 },
                 Some(make_budget_status(
                     sequence,
-                    &policy,
+                    policy,
                     &run_started,
                     output_bytes,
+                    session.wait_stats().wait_ms,
                 )),
                 None,
             );
@@ -310,6 +627,79 @@ where
             break;
         }
 
+        if matches!(request.action.action_type, ActionType::History) {
+            let response = history_response(
+                &request.request_id,
+                history.query(&request.action),
+                last_screen_hash.clone(),
+                make_budget_status(
+                    sequence,
+                    policy,
+                    &run_started,
+                    output_bytes,
+                    session.wait_stats().wait_ms,
+                ),
+            );
+            emit_driver_response(&mut output, &response)?;
+            continue;
+        }
+
+        if matches!(request.action.action_type, ActionType::Subscribe) {
+            let response = subscribe_response(
+                &request.request_id,
+                events.subscribe(&request.action),
+                last_screen_hash.clone(),
+                make_budget_status(
+                    sequence,
+                    policy,
+                    &run_started,
+                    output_bytes,
+                    session.wait_stats().wait_ms,
+                ),
+            );
+            emit_driver_response(&mut output, &response)?;
+            continue;
+        }
+
+        let started_at_ms = elapsed_ms(&run_started);
+        if let Some(expected) = request.expected_screen_hash.as_deref() {
+            if let Some((observation, actual)) =
+                stale_screen(session, expected, &mut writer, &mut output_bytes)?
+            {
+                let mut response = error_response(
+                    &request.request_id,
+                    ErrorInfo {
+                        code: ErrorCode::StaleObservation.as_str().to_string(),
+                        message: "screen changed since the expected observation".to_string(),
+                        context: Some(serde_json::json!({
+                            "expected_screen_hash": expected,
+                            "actual_screen_hash": actual
+                        })),
+                    },
+                    Some(make_budget_status(
+                        sequence,
+                        policy,
+                        &run_started,
+                        output_bytes,
+                        session.wait_stats().wait_ms,
+                    )),
+                    None,
+                );
+                last_screen_hash = Some(actual.clone());
+                response.screen_hash = Some(actual);
+                history.record(
+                    &request,
+                    &response,
+                    &observation,
+                    started_at_ms,
+                    elapsed_ms(&run_started),
+                );
+                response.observation = Some(observation);
+                emit_driver_response(&mut output, &response)?;
+                continue;
+            }
+        }
+
         let action = request.action.clone();
         let default_timeout_ms = if matches!(action.action_type, ActionType::Wait) {
             5000
@@ -317,14 +707,13 @@ where
             200
         };
         let timeout_ms = request.timeout_ms.unwrap_or(default_timeout_ms);
-        let started_at_ms = elapsed_ms(&run_started);
         let action_started = Instant::now();
-        let observation = match perform_action(
-            &mut session,
-            &action,
-            Duration::from_millis(timeout_ms),
-            &policy,
-        ) {
+        let wait_before = session.wait_stats();
+        let observation = match catch_panic("driver action", || {
+            perform_action(session, &action, Duration::from_millis(timeout_ms), policy)
+        })
+        .and_then(|result| result)
+        {
             Ok(obs) => obs,
             Err(err) => {
                 let response = error_response(
@@ -332,9 +721,10 @@ where
                     err.to_error_info(),
                     Some(make_budget_status(
                         sequence,
-                        &policy,
+                        policy,
                         &run_started,
                         output_bytes,
+                        session.wait_stats().wait_ms,
                     )),
                     Some(DriverActionMetrics {
                         sequence: sequence + 1,
@@ -347,10 +737,7 @@ where
             }
         };
 
-        output_bytes += observation
-            .transcript_delta
-            .as_ref()
-            .map_or(0, |delta| delta.len() as u64);
+        output_bytes += observation.output_bytes();
         if output_bytes > policy.budgets.max_output_bytes {
             let err = RunnerError::timeout(
                 "E_TIMEOUT",
@@ -364,9 +751,10 @@ where
                 err.to_error_info(),
                 Some(make_budget_status(
                     sequence,
-                    &policy,
+                    policy,
                     &run_started,
                     output_bytes,
+                    session.wait_stats().wait_ms,
                 )),
                 Some(DriverActionMetrics {
                     sequence: sequence + 1,
@@ -390,9 +778,10 @@ where
                 err.to_error_info(),
                 Some(make_budget_status(
                     sequence,
-                    &policy,
+                    policy,
                     &run_started,
                     output_bytes,
+                    session.wait_stats().wait_ms,
                 )),
                 Some(DriverActionMetrics {
                     sequence: sequence + 1,
@@ -404,21 +793,79 @@ where
             break;
         }
 
+        let screen_hash = observation.screen.screen_hash();
+        if request.coalesce
+            && is_unchanged(
+                &action,
+                &observation,
+                &screen_hash,
+                last_screen_hash.as_deref(),
+            )
+        {
+            if let Some(writer) = writer.as_mut() {
+                if let Some(delta) = &observation.transcript_delta {
+                    writer.write_transcript(delta)?;
+                }
+                if let Some(delta) = &observation.stderr_delta {
+                    writer.write_stderr(delta)?;
+                }
+            }
+            let response = DriverResponseV2 {
+                protocol_version: PROTOCOL_VERSION,
+                request_id: request.request_id.clone(),
+                status: DriverResponseStatus::Ok,
+                observation: None,
+                error: None,
+                action_metrics: None,
+                budget_status: Some(make_budget_status(
+                    sequence,
+                    policy,
+                    &run_started,
+                    output_bytes,
+                    session.wait_stats().wait_ms,
+                )),
+                screen_hash: Some(screen_hash),
+                unchanged: true,
+                history: None,
+                image: None,
+                subscription: None,
+            };
+            history.record(
+                &request,
+                &response,
+                &observation,
+                started_at_ms,
+                elapsed_ms(&run_started),
+            );
+            emit_driver_response(&mut output, &response)?;
+            continue;
+        }
+
         sequence += 1;
         let ended_at_ms = elapsed_ms(&run_started);
         let duration_ms = elapsed_ms(&action_started);
 
         let step_id = StepId::new();
-        scenario_steps.push(Step {
+        let step = Step {
             id: step_id,
             name: format!("driver-step-{sequence}"),
             action: action.clone(),
             assert: Vec::new(),
             timeout_ms,
             retries: 0,
-        });
+            skip_default_assertions: false,
+            sample_interval_ms: None,
+            expected_screen: None,
+            capture: None,
+            env_overlay: BTreeMap::new(),
+            metadata: BTreeMap::new(),
+            vars: Vec::new(),
+        };
+        let step_key = Some(step.content_key());
+        scenario_steps.push(step);
         step_results.push(StepResult {
             step_id,
+            step_key,
             name: format!("driver-step-{sequence}"),
             status: StepStatus::Passed,
             attempts: 1,
@@ -427,13 +874,35 @@ where
             action: action.clone(),
             assertions: Vec::new(),
             error: None,
+            wait: session.wait_stats().since(wait_before),
+            latency_ms: action_latency_ms(session, &action, duration_ms)?,
+            samples: None,
+            capture: None,
+            env_overlay: BTreeMap::new(),
+            metadata: BTreeMap::new(),
+            vars: BTreeMap::new(),
+            resolved_vars: BTreeMap::new(),
         });
 
         if let Some(writer) = writer.as_mut() {
+            let step_name = format!("driver-step-{sequence}");
+            writer.begin_step(usize::try_from(sequence).unwrap_or(usize::MAX), &step_name)?;
+            if matches!(action.action_type, ActionType::Raw) {
+                writer.write_raw_input(&step_name, &action)?;
+            }
+            if let Some(hold) = session.take_key_hold() {
+                writer.write_key_hold(&step_name, hold)?;
+            }
+            if let Some(settings) = session.take_termios() {
+                writer.write_termios(&step_name, settings)?;
+            }
             writer.write_snapshot(&observation.screen)?;
             if let Some(delta) = &observation.transcript_delta {
                 writer.write_transcript(delta)?;
             }
+            if let Some(delta) = &observation.stderr_delta {
+                writer.write_stderr(delta)?;
+            }
             writer.write_observation(&observation)?;
             writer.write_json_line(
                 "driver-actions.jsonl",
@@ -446,6 +915,7 @@ where
                     ended_at_ms,
                 },
             )?;
+            writer.end_step()?;
         }
 
         let response = DriverResponseV2 {
@@ -460,12 +930,26 @@ where
             }),
             budget_status: Some(make_budget_status(
                 sequence,
-                &policy,
+                policy,
                 &run_started,
                 output_bytes,
+                session.wait_stats().wait_ms,
             )),
+            screen_hash: Some(screen_hash.clone()),
+            unchanged: false,
+            history: None,
+            image: screenshot::attach_image(session, &request),
+            subscription: None,
         };
+        history.record(
+            &request,
+            &response,
+            &observation,
+            started_at_ms,
+            ended_at_ms,
+        );
         emit_driver_response(&mut output, &response)?;
+        last_screen_hash = Some(screen_hash);
         final_observation = Some(observation);
 
         if matches!(action.action_type, ActionType::Terminate) {
@@ -473,34 +957,77 @@ where
         }
     }
 
+    Ok(DriverEpisode {
+        writer,
+        artifacts_dir,
+        started: run_started,
+        steps: scenario_steps,
+        step_results,
+        final_observation,
+        error: final_error,
+        detached,
+    })
+}
+
+/// Write the run result of `episode`, stopping the child first when `stop`
+/// is set (otherwise the run has no exit status and the child keeps
+/// running).
+///
+/// # Errors
+/// The episode's own error, if any, after its artifacts are written.
+pub(crate) fn finish_episode(
+    process: &mut DriverProcess,
+    episode: DriverEpisode,
+    stop: bool,
+) -> RunnerResult<()> {
+    let DriverEpisode {
+        mut writer,
+        artifacts_dir,
+        started,
+        steps,
+        step_results,
+        mut final_observation,
+        error: mut final_error,
+        detached: _,
+    } = episode;
     if final_observation.is_none() {
-        final_observation = session.observe(Duration::from_millis(10)).ok();
+        final_observation = process.session.observe(Duration::from_millis(10)).ok();
     }
 
-    let exit_status = match session.wait_for_exit(Duration::from_millis(50)) {
-        Ok(Some(status)) => Some(convert_exit_status(status, false)),
-        Ok(None) | Err(_) => session
-            .terminate_process_group(Duration::from_millis(200))
-            .ok()
-            .flatten()
-            .map(|status| convert_exit_status(status, true)),
+    let at_deadline = elapsed_ms(&started) > process.policy.budgets.max_runtime_ms;
+    let exit_status = if stop {
+        process.stop(at_deadline)
+    } else {
+        None
     };
+    if stop && at_deadline && process.policy.process.deadline_signal.is_some() {
+        // What the child drew while shutting down after the warning.
+        if let Ok(observation) = process.session.observe(Duration::from_millis(10)) {
+            final_observation = Some(observation);
+        }
+    }
+    if let Some(err) = finish_write_quotas(&mut process.session, &mut writer)? {
+        final_error.get_or_insert(err);
+    }
+    let policy = &process.policy;
+    write_fs_manifest(policy, artifacts_dir.as_ref(), &mut writer)?;
 
     let status = if final_error.is_none() {
         RunStatus::Passed
     } else {
         RunStatus::Errored
     };
-    let run_result = RunResult {
+    let mut run_result = RunResult {
         run_result_version: RUN_RESULT_VERSION,
         protocol_version: PROTOCOL_VERSION,
-        run_id,
+        run_id: process.run_id,
         status,
         started_at_ms: 0,
-        ended_at_ms: elapsed_ms(&run_started),
-        command: command.clone(),
-        args: args.clone(),
-        cwd: cwd
+        ended_at_ms: elapsed_ms(&started),
+        command: process.command.clone(),
+        args: process.args.clone(),
+        cwd: process
+            .cwd
             .clone()
             .or_else(|| policy.fs.working_dir.clone())
             .unwrap_or_else(|| {
@@ -509,26 +1036,27 @@ where
                     .unwrap_or_else(|_| "<unknown>".to_string())
             }),
         policy: policy.clone(),
-        scenario: Some(Scenario {
-            scenario_version: SCENARIO_VERSION,
-            metadata: ScenarioMetadata {
-                name: "driver-session".to_string(),
-                description: Some("generated from driver-actions.jsonl".to_string()),
-            },
-            run: RunConfig {
-                command,
-                args,
-                cwd,
-                initial_size: TerminalSize::default(),
-                policy: crate::model::scenario::PolicyRef::Inline(Box::new(policy.clone())),
-            },
-            steps: scenario_steps,
-        }),
+        scenario: Some(driver_scenario(process, steps)),
         steps: Some(step_results),
         final_observation,
         exit_status,
         error: final_error.as_ref().map(RunnerError::to_error_info),
+        tags: Vec::new(),
+        enforcement: process.enforcement.clone(),
+        provenance: None,
+        classification: None,
+        post_run: Vec::new(),
+        risk_summary: process
+            .acknowledgements
+            .as_ref()
+            .map(|report| report.summary.clone()),
+        fixtures: Vec::new(),
+        policy_grants: Vec::new(),
+        keymap: process.keymap.clone(),
+        determinism: policy.determinism.pinned(),
     };
+    run_result.provenance = Some(run_provenance(run_result.scenario.as_ref(), None));
+    run_result.classification = classify_failure(&run_result);
 
     if let Some(writer) = writer.as_mut() {
         if let Some(observation) = run_result.final_observation.as_ref() {
@@ -543,14 +1071,181 @@ where
         writer.flush_checksums()?;
     }
 
-    drop(cleanup_guard);
     if let Some(err) = final_error {
         return Err(err);
     }
     Ok(())
 }
 
-fn error_response(
+/// Scenario recorded for a driver session: its command and the actions sent.
+fn driver_scenario(process: &DriverProcess, steps: Vec<Step>) -> Scenario {
+    Scenario {
+        scenario_version: SCENARIO_VERSION,
+        metadata: ScenarioMetadata {
+            name: "driver-session".to_string(),
+            description: Some("generated from driver-actions.jsonl".to_string()),
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
+        },
+        run: RunConfig {
+            command: process.command.clone(),
+            args: process.args.clone(),
+            cwd: process.cwd.clone(),
+            initial_size: TerminalSize::default(),
+            policy: crate::model::scenario::PolicyRef::Inline(Box::new(process.policy.clone())),
+            separate_stderr: process.separate_stderr,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
+        },
+        steps,
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
+    }
+}
+
+/// Outcome of waiting for the next driver input line.
+enum DriverInput {
+    /// A raw request line.
+    Line(String),
+    /// The event poll interval passed without input.
+    Tick,
+    /// Input reached EOF.
+    Closed,
+    /// The session idle or lifetime budget expired first.
+    Expired(RunnerError),
+}
+
+/// Forward input lines from a background thread so reads can time out.
+///
+/// The thread exits at EOF or once the receiver is dropped and the next line
+/// arrives.
+fn spawn_input_reader<R>(input: R) -> Receiver<io::Result<String>>
+where
+    R: BufRead + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in input.lines() {
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+/// Wait for the next input line, bounded by the session idle and lifetime
+/// budgets, and by `poll` when events are subscribed.
+fn next_driver_input(
+    lines: &Receiver<io::Result<String>>,
+    budgets: &Budgets,
+    run_started: &Instant,
+    last_activity: &Instant,
+    poll: Option<Duration>,
+) -> RunnerResult<DriverInput> {
+    let idle_deadline = budgets
+        .max_session_idle_ms
+        .and_then(|ms| last_activity.checked_add(Duration::from_millis(ms)));
+    let lifetime_deadline = budgets
+        .max_session_lifetime_ms
+        .and_then(|ms| run_started.checked_add(Duration::from_millis(ms)));
+    let deadline = match (idle_deadline, lifetime_deadline) {
+        (Some(idle), Some(lifetime)) => Some(idle.min(lifetime)),
+        (idle, lifetime) => idle.or(lifetime),
+    };
+
+    let tick = poll.and_then(|interval| Instant::now().checked_add(interval));
+    let wake = match (deadline, tick) {
+        (Some(deadline), Some(tick)) => Some(deadline.min(tick)),
+        (deadline, tick) => deadline.or(tick),
+    };
+
+    let received = match wake {
+        Some(wake) => lines.recv_timeout(wake.saturating_duration_since(Instant::now())),
+        None => lines.recv().map_err(|_| RecvTimeoutError::Disconnected),
+    };
+    match received {
+        Ok(Ok(line)) => Ok(DriverInput::Line(line)),
+        Ok(Err(err)) => Err(RunnerError::io("E_IO", "failed to read driver input", err)),
+        Err(RecvTimeoutError::Disconnected) => Ok(DriverInput::Closed),
+        Err(RecvTimeoutError::Timeout)
+            if tick.is_some() && deadline.map_or(true, |end| Instant::now() < end) =>
+        {
+            Ok(DriverInput::Tick)
+        }
+        Err(RecvTimeoutError::Timeout) => {
+            let lifetime_expired = lifetime_deadline.is_some_and(|end| Instant::now() >= end);
+            let err = if lifetime_expired {
+                RunnerError::timeout(
+                    "E_TIMEOUT",
+                    "driver session exceeded max lifetime budget",
+                    Some(serde_json::json!({
+                        "max_session_lifetime_ms": budgets.max_session_lifetime_ms
+                    })),
+                )
+            } else {
+                RunnerError::timeout(
+                    "E_TIMEOUT",
+                    "driver session idle budget exceeded",
+                    Some(serde_json::json!({
+                        "max_session_idle_ms": budgets.max_session_idle_ms
+                    })),
+                )
+            };
+            Ok(DriverInput::Expired(err))
+        }
+    }
+}
+
+/// Check an `expected_screen_hash` precondition against the live screen.
+///
+/// Returns the fresh observation and its hash when the screen differs. Output
+/// read while checking is recorded in the artifacts either way.
+fn stale_screen(
+    session: &mut Session,
+    expected: &str,
+    writer: &mut Option<ArtifactsWriter>,
+    output_bytes: &mut u64,
+) -> RunnerResult<Option<(Observation, String)>> {
+    let observation = session.observe(Duration::ZERO)?;
+    *output_bytes += observation.output_bytes();
+    if let Some(writer) = writer.as_mut() {
+        if let Some(delta) = &observation.transcript_delta {
+            writer.write_transcript(delta)?;
+        }
+        if let Some(delta) = &observation.stderr_delta {
+            writer.write_stderr(delta)?;
+        }
+        writer.write_observation(&observation)?;
+    }
+    let actual = observation.screen.screen_hash();
+    Ok((actual != expected).then_some((observation, actual)))
+}
+
+/// Whether a coalescing request can be answered with `unchanged`: a `wait`
+/// or `observe` whose screen still has the previous response's hash and
+/// that raised no events. Output that left the screen as it was is still
+/// appended to the transcript by the caller.
+fn is_unchanged(
+    action: &Action,
+    observation: &Observation,
+    screen_hash: &str,
+    last_screen_hash: Option<&str>,
+) -> bool {
+    matches!(action.action_type, ActionType::Wait | ActionType::Observe)
+        && observation.events.is_empty()
+        && last_screen_hash == Some(screen_hash)
+}
+
+pub(crate) fn error_response(
     request_id: &str,
     error: ErrorInfo,
     budget_status: Option<BudgetStatus>,
@@ -564,7 +1259,100 @@ fn error_response(
         error: Some(error),
         action_metrics,
         budget_status,
+        screen_hash: None,
+        unchanged: false,
+        history: None,
+        image: None,
+        subscription: None,
+    }
+}
+
+/// Answer to a `history` query: the log on success, with the latest
+/// `screen_hash` so the client can resume `expected_screen_hash` and
+/// `coalesce` requests.
+fn history_response(
+    request_id: &str,
+    history: RunnerResult<DriverHistory>,
+    screen_hash: Option<String>,
+    budget_status: BudgetStatus,
+) -> DriverResponseV2 {
+    match history {
+        Ok(history) => DriverResponseV2 {
+            protocol_version: PROTOCOL_VERSION,
+            request_id: request_id.to_string(),
+            status: DriverResponseStatus::Ok,
+            observation: None,
+            error: None,
+            action_metrics: None,
+            budget_status: Some(budget_status),
+            screen_hash,
+            unchanged: false,
+            history: Some(history),
+            image: None,
+            subscription: None,
+        },
+        Err(err) => error_response(request_id, err.to_error_info(), Some(budget_status), None),
+    }
+}
+
+/// Answer to a `subscribe` request: the subscription now in effect, with
+/// the latest `screen_hash` that `screen_changed` events are compared with.
+fn subscribe_response(
+    request_id: &str,
+    subscription: RunnerResult<DriverSubscription>,
+    screen_hash: Option<String>,
+    budget_status: BudgetStatus,
+) -> DriverResponseV2 {
+    match subscription {
+        Ok(subscription) => DriverResponseV2 {
+            protocol_version: PROTOCOL_VERSION,
+            request_id: request_id.to_string(),
+            status: DriverResponseStatus::Ok,
+            observation: None,
+            error: None,
+            action_metrics: None,
+            budget_status: Some(budget_status),
+            screen_hash,
+            unchanged: false,
+            history: None,
+            image: None,
+            subscription: Some(subscription),
+        },
+        Err(err) => error_response(request_id, err.to_error_info(), Some(budget_status), None),
+    }
+}
+
+/// Poll a subscribed session and push the events it raised. Output read on
+/// the way counts against the output budget and goes to the artifacts; each
+/// pushed event is also appended to `driver-events.jsonl`.
+fn push_events(
+    events: &mut subscription::EventStream,
+    session: &mut Session,
+    writer: &mut Option<ArtifactsWriter>,
+    output_bytes: &mut u64,
+    last_screen_hash: &mut Option<String>,
+    output: &mut impl Write,
+) -> RunnerResult<()> {
+    let (observation, pushed) = events.poll(session, last_screen_hash)?;
+    *output_bytes += observation.output_bytes();
+    if let Some(writer) = writer.as_mut() {
+        if let Some(delta) = &observation.transcript_delta {
+            writer.write_transcript(delta)?;
+        }
+        if let Some(delta) = &observation.stderr_delta {
+            writer.write_stderr(delta)?;
+        }
+        if !pushed.is_empty() {
+            writer.write_observation(&observation)?;
+        }
+        for event in &pushed {
+            writer.write_json_line("driver-events.jsonl", event)?;
+        }
+    }
+    for event in &pushed {
+        emit_frame(output, event, "driver event")?;
     }
+    Ok(())
 }
 
 fn make_budget_status(
@@ -572,6 +1360,7 @@ fn make_budget_status(
     policy: &Policy,
     run_started: &Instant,
     output_bytes: u64,
+    wait_ms: u64,
 ) -> BudgetStatus {
     BudgetStatus {
         steps_used: sequence,
@@ -580,16 +1369,26 @@ fn make_budget_status(
         runtime_max_ms: policy.budgets.max_runtime_ms,
         output_bytes_used: output_bytes,
         output_bytes_max: policy.budgets.max_output_bytes,
+        wait_ms_used: wait_ms,
     }
 }
 
 fn emit_driver_response(output: &mut impl Write, response: &DriverResponseV2) -> RunnerResult<()> {
-    let payload = serde_json::to_string(response)
-        .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to serialize driver response", err))?;
+    emit_frame(output, response, "driver response")
+}
+
+/// Write one NDJSON frame and flush it; `what` names it in errors.
+fn emit_frame(
+    output: &mut impl Write,
+    frame: &impl serde::Serialize,
+    what: &str,
+) -> RunnerResult<()> {
+    let payload = serde_json::to_string(frame)
+        .map_err(|err| RunnerError::io("E_PROTOCOL", format!("failed to serialize {what}"), err))?;
     writeln!(output, "{payload}")
-        .map_err(|err| RunnerError::io("E_IO", "failed to write driver response", err))?;
+        .map_err(|err| RunnerError::io("E_IO", format!("failed to write {what}"), err))?;
     output
         .flush()
-        .map_err(|err| RunnerError::io("E_IO", "failed to flush driver response", err))?;
+        .map_err(|err| RunnerError::io("E_IO", format!("failed to flush {what}"), err))?;
     Ok(())
 }
@@ -0,0 +1,71 @@
+//! Inline screenshots for `include_image` requests.
+//!
+//! With the `render` feature, a successful response to a request with
+//! `include_image` carries a base64 PNG of the screen, rendered by
+//! `terminal::image`. Images larger than
+//! [`MAX_DRIVER_IMAGE_BYTES`](crate::model::MAX_DRIVER_IMAGE_BYTES) are
+//! described but their data is omitted. Without the feature, such requests
+//! are rejected before the action runs.
+
+use crate::model::driver::{DriverImage, DriverRequestV2};
+use crate::model::ErrorInfo;
+use crate::runner::ErrorCode;
+use crate::session::Session;
+
+/// Error for an `include_image` request when rendering is not compiled in.
+pub(crate) fn image_unsupported(request: &DriverRequestV2) -> Option<ErrorInfo> {
+    (request.include_image && !cfg!(feature = "render")).then(|| ErrorInfo {
+        code: ErrorCode::Protocol.as_str().to_string(),
+        message: "include_image requires the render feature".to_string(),
+        context: Some(serde_json::json!({
+            "fix": "Build ptybox with `--features render` or drop include_image"
+        })),
+    })
+}
+
+/// Screenshot of the session's current screen, if `request` asked for one.
+#[cfg(feature = "render")]
+pub(crate) fn attach_image(session: &Session, request: &DriverRequestV2) -> Option<DriverImage> {
+    request.include_image.then(|| render(session))
+}
+
+/// Screenshot of the session's current screen, if `request` asked for one.
+#[cfg(not(feature = "render"))]
+pub(crate) fn attach_image(_session: &Session, _request: &DriverRequestV2) -> Option<DriverImage> {
+    None
+}
+
+#[cfg(feature = "render")]
+fn render(session: &Session) -> DriverImage {
+    use crate::model::driver::MAX_DRIVER_IMAGE_BYTES;
+    use crate::terminal::image::{render_png, PNG_MIME_TYPE};
+
+    let rendered = session
+        .styled_snapshot()
+        .and_then(|snapshot| render_png(&snapshot));
+    let image = match rendered {
+        Ok(image) => image,
+        Err(err) => {
+            return DriverImage {
+                mime_type: PNG_MIME_TYPE.to_string(),
+                width: 0,
+                height: 0,
+                bytes: 0,
+                data_base64: None,
+                omitted: Some(err.message),
+            }
+        }
+    };
+    let bytes = u64::try_from(image.png.len()).unwrap_or(u64::MAX);
+    let fits = bytes <= MAX_DRIVER_IMAGE_BYTES;
+    DriverImage {
+        mime_type: PNG_MIME_TYPE.to_string(),
+        width: image.width,
+        height: image.height,
+        bytes,
+        data_base64: fits.then(|| crate::util::encode_base64(&image.png)),
+        omitted: (!fits).then(|| {
+            format!("image is {bytes} bytes, over the {MAX_DRIVER_IMAGE_BYTES}-byte inline limit")
+        }),
+    }
+}
@@ -0,0 +1,249 @@
+//! Unsolicited `event` frames for clients that `subscribe`.
+//!
+//! While no request is pending the driver polls the session every
+//! `poll_interval_ms` and pushes an event when the screen hash differs from
+//! the last one the client was sent, when the bell rings, or when the child
+//! exits. Clients acknowledge with `{"type":"ack","event_seq":N}` (covering
+//! every event up to `N`); at most `max_unacked` events are in flight.
+//! While the window is full, `screen_changed` and `bell` events are dropped
+//! and counted, and reported by a single `overflow` event (with the current
+//! screen hash) once the client catches up; `process_exited` is held back
+//! instead of dropped. Nothing is queued, so memory stays bounded however
+//! slow the client is.
+
+use crate::model::driver::{
+    DriverEvent, DriverEventKind, DriverSubscription, DEFAULT_DRIVER_EVENT_POLL_MS,
+    DEFAULT_DRIVER_EVENT_WINDOW, MAX_DRIVER_EVENT_WINDOW, MIN_DRIVER_EVENT_POLL_MS,
+};
+use crate::model::{Action, Observation, PROTOCOL_VERSION};
+use crate::runner::{ErrorCode, RunnerError, RunnerResult};
+use crate::session::Session;
+use crate::util::convert_exit_status;
+use serde_json::Value;
+use std::time::Duration;
+
+/// Subscription state of one driver episode.
+#[derive(Debug, Default)]
+pub(crate) struct EventStream {
+    events: Vec<DriverEventKind>,
+    max_unacked: u64,
+    poll_interval_ms: u64,
+    /// `event_seq` of the last event sent.
+    sent: u64,
+    /// Highest `event_seq` acknowledged.
+    acked: u64,
+    /// Events dropped since the last `overflow` event.
+    dropped: u64,
+    /// Screen hash of the last dropped `screen_changed`, so a screen that
+    /// stays put is counted once.
+    dropped_hash: Option<String>,
+    /// Bells already reported since the session's last input.
+    bells: u64,
+    /// Exit seen but not yet sent because the window was full.
+    held_exit: Option<DriverEvent>,
+    exited: bool,
+}
+
+impl EventStream {
+    /// Apply a `subscribe` request (payload
+    /// `{events?, max_unacked?, poll_interval_ms?}`).
+    ///
+    /// # Errors
+    /// `E_PROTOCOL` for an unknown event kind or an out-of-range setting.
+    pub(crate) fn subscribe(&mut self, action: &Action) -> RunnerResult<DriverSubscription> {
+        let payload = &action.payload;
+        let events = match payload.get("events") {
+            None | Some(Value::Null) => DriverEventKind::SUBSCRIBABLE.to_vec(),
+            Some(value) => {
+                let mut events: Vec<DriverEventKind> = serde_json::from_value(value.clone())
+                    .ok()
+                    .filter(|events: &Vec<DriverEventKind>| {
+                        !events.contains(&DriverEventKind::Overflow)
+                    })
+                    .ok_or_else(|| {
+                        invalid_setting(
+                            "events must be a list of screen_changed, bell, process_exited",
+                            value,
+                        )
+                    })?;
+                events.sort_unstable();
+                events.dedup();
+                events
+            }
+        };
+        let max_unacked = setting(
+            payload,
+            "max_unacked",
+            DEFAULT_DRIVER_EVENT_WINDOW,
+            1..=MAX_DRIVER_EVENT_WINDOW,
+        )?;
+        let poll_interval_ms = setting(
+            payload,
+            "poll_interval_ms",
+            DEFAULT_DRIVER_EVENT_POLL_MS,
+            MIN_DRIVER_EVENT_POLL_MS..=u64::from(u32::MAX),
+        )?;
+        self.events = events;
+        self.max_unacked = max_unacked;
+        self.poll_interval_ms = poll_interval_ms;
+        Ok(self.subscription())
+    }
+
+    /// The subscription in effect.
+    pub(crate) fn subscription(&self) -> DriverSubscription {
+        DriverSubscription {
+            events: self.events.clone(),
+            max_unacked: self.max_unacked,
+            poll_interval_ms: self.poll_interval_ms,
+            last_event_seq: self.sent,
+        }
+    }
+
+    /// How long the idle driver waits between polls; `None` when there is
+    /// nothing left to watch.
+    pub(crate) fn poll_interval(&self) -> Option<Duration> {
+        let finished = self.exited && self.held_exit.is_none();
+        (!self.events.is_empty() && !finished).then(|| Duration::from_millis(self.poll_interval_ms))
+    }
+
+    /// Acknowledge every event up to `event_seq` (later ones are clamped to
+    /// the last event sent).
+    pub(crate) fn ack(&mut self, event_seq: u64) {
+        self.acked = self.acked.max(event_seq.min(self.sent));
+    }
+
+    /// Forget bells the client saw through a response: a request resets the
+    /// session's bell count, and only later bells are reported.
+    pub(crate) fn resync(&mut self, session: &Session) {
+        self.bells = session.raw_output().bells;
+    }
+
+    /// Read pending output and return the events to push, in order.
+    ///
+    /// `screen_hash` is the last hash sent to the client; it is updated when
+    /// an event carries a new one. The observation is returned so its output
+    /// can be recorded.
+    pub(crate) fn poll(
+        &mut self,
+        session: &mut Session,
+        screen_hash: &mut Option<String>,
+    ) -> RunnerResult<(Observation, Vec<DriverEvent>)> {
+        let observation = session.observe(Duration::ZERO)?;
+        let timestamp_ms = observation.timestamp_ms;
+        let mut pushed = Vec::new();
+        let current_hash = observation.screen.screen_hash();
+
+        if self.dropped > 0 && self.has_room() {
+            let mut event = event(DriverEventKind::Overflow, timestamp_ms);
+            event.dropped = Some(std::mem::take(&mut self.dropped));
+            event.screen_hash = Some(current_hash.clone());
+            *screen_hash = Some(current_hash.clone());
+            self.dropped_hash = None;
+            self.push(event, &mut pushed);
+        }
+        if self.wants(DriverEventKind::ScreenChanged)
+            && screen_hash.as_deref() != Some(current_hash.as_str())
+            && self.dropped_hash.as_deref() != Some(current_hash.as_str())
+        {
+            let mut event = event(DriverEventKind::ScreenChanged, timestamp_ms);
+            event.screen_hash = Some(current_hash.clone());
+            if self.push(event, &mut pushed) {
+                *screen_hash = Some(current_hash);
+            } else {
+                self.dropped_hash = Some(current_hash);
+            }
+        }
+        let bells = session.raw_output().bells;
+        if bells > self.bells && self.wants(DriverEventKind::Bell) {
+            let mut event = event(DriverEventKind::Bell, timestamp_ms);
+            event.bells = Some(bells - self.bells);
+            self.push(event, &mut pushed);
+        }
+        self.bells = bells;
+
+        if !self.exited {
+            if let Some(status) = session.wait_for_exit(Duration::ZERO)? {
+                self.exited = true;
+                if self.wants(DriverEventKind::ProcessExited) {
+                    let mut event = event(DriverEventKind::ProcessExited, timestamp_ms);
+                    event.exit_status = Some(convert_exit_status(status, false));
+                    self.held_exit = Some(event);
+                }
+            }
+        }
+        if self.has_room() {
+            if let Some(event) = self.held_exit.take() {
+                self.push(event, &mut pushed);
+            }
+        }
+        Ok((observation, pushed))
+    }
+
+    fn wants(&self, kind: DriverEventKind) -> bool {
+        self.events.contains(&kind)
+    }
+
+    fn has_room(&self) -> bool {
+        self.sent - self.acked < self.max_unacked
+    }
+
+    /// Number and queue `event` for sending, or count it as dropped when
+    /// the window is full. Returns whether it will be sent.
+    fn push(&mut self, mut event: DriverEvent, pushed: &mut Vec<DriverEvent>) -> bool {
+        if !self.has_room() {
+            self.dropped += 1;
+            return false;
+        }
+        self.sent += 1;
+        event.event_seq = self.sent;
+        pushed.push(event);
+        true
+    }
+}
+
+/// An unnumbered event of `kind`.
+fn event(kind: DriverEventKind, timestamp_ms: u64) -> DriverEvent {
+    DriverEvent {
+        protocol_version: PROTOCOL_VERSION,
+        event_seq: 0,
+        kind,
+        timestamp_ms,
+        screen_hash: None,
+        bells: None,
+        exit_status: None,
+        dropped: None,
+    }
+}
+
+/// Read the optional integer `key` of `payload`, which must lie in `range`.
+fn setting(
+    payload: &Value,
+    key: &str,
+    default: u64,
+    range: std::ops::RangeInclusive<u64>,
+) -> RunnerResult<u64> {
+    match payload.get(key) {
+        None | Some(Value::Null) => Ok(default),
+        Some(value) => value
+            .as_u64()
+            .filter(|number| range.contains(number))
+            .ok_or_else(|| {
+                invalid_setting(
+                    &format!(
+                        "{key} must be an integer from {} to {}",
+                        range.start(),
+                        range.end()
+                    ),
+                    value,
+                )
+            }),
+    }
+}
+
+fn invalid_setting(message: &str, received: &Value) -> RunnerError {
+    RunnerError::with_context(
+        ErrorCode::Protocol,
+        format!("invalid subscribe payload: {message}"),
+        serde_json::json!({ "received": received }),
+    )
+}
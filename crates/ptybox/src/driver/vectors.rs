@@ -0,0 +1,506 @@
+//! Protocol v2 conformance vectors for client implementers.
+//!
+//! Vectors are built from the model types and serialized with serde, so they
+//! always match what the driver accepts and emits. [`write_protocol_vectors`]
+//! lays them out on disk:
+//!
+//! | Path | Contents |
+//! |------|----------|
+//! | `manifest.json` | [`VectorsManifest`] listing every file |
+//! | `exchanges/<name>.json` | `{description, request, response}` per action type |
+//! | `errors/<CODE>.json` | `{description, request, response}` per error code |
+//! | `schemas/*.schema.json` | JSON Schemas from `spec/schemas/` |
+//!
+//! IDs and timestamps are fixed so regenerated vectors are byte-identical.
+
+use crate::model::driver::{
+    BudgetStatus, DriverActionMetrics, DriverEventKind, DriverHistory, DriverHistoryEntry,
+    DriverRequestV2, DriverResponseStatus, DriverResponseV2, DriverSubscription,
+    DEFAULT_DRIVER_EVENT_POLL_MS, DEFAULT_DRIVER_EVENT_WINDOW,
+};
+use crate::model::policy::Budgets;
+use crate::model::{
+    Action, ActionType, Cursor, ErrorInfo, Observation, RunId, ScreenSnapshot, SessionId,
+    SnapshotId, PROTOCOL_VERSION, SNAPSHOT_VERSION,
+};
+use crate::runner::{ErrorCode, RunnerError, RunnerResult};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+/// JSON Schemas shipped with the vectors, as `(file name, contents)`.
+pub const PROTOCOL_SCHEMAS: [(&str, &str); 8] = [
+    (
+        "driver-request-v2.schema.json",
+        include_str!("../../../../spec/schemas/driver-request-v2.schema.json"),
+    ),
+    (
+        "driver-response-v2.schema.json",
+        include_str!("../../../../spec/schemas/driver-response-v2.schema.json"),
+    ),
+    (
+        "observation.schema.json",
+        include_str!("../../../../spec/schemas/observation.schema.json"),
+    ),
+    (
+        "policy.schema.json",
+        include_str!("../../../../spec/schemas/policy.schema.json"),
+    ),
+    (
+        "scenario.schema.json",
+        include_str!("../../../../spec/schemas/scenario.schema.json"),
+    ),
+    (
+        "run-result.schema.json",
+        include_str!("../../../../spec/schemas/run-result.schema.json"),
+    ),
+    (
+        "normalization.schema.json",
+        include_str!("../../../../spec/schemas/normalization.schema.json"),
+    ),
+    (
+        "replay.schema.json",
+        include_str!("../../../../spec/schemas/replay.schema.json"),
+    ),
+];
+
+/// One canonical request/response exchange.
+#[derive(Clone, Debug, Serialize)]
+pub struct ProtocolVector {
+    /// File stem (`text`, `E_TIMEOUT`, ...).
+    #[serde(skip)]
+    pub name: String,
+    /// What the exchange demonstrates.
+    pub description: String,
+    /// Request sent by the client.
+    pub request: DriverRequestV2,
+    /// Response emitted by the driver.
+    pub response: DriverResponseV2,
+}
+
+/// Canonical vectors for protocol v2.
+#[derive(Clone, Debug)]
+pub struct ProtocolVectors {
+    /// Successful exchanges, one per action type.
+    pub exchanges: Vec<ProtocolVector>,
+    /// Error exchanges, one per [`ErrorCode`].
+    pub errors: Vec<ProtocolVector>,
+}
+
+/// Index of the files written by [`write_protocol_vectors`].
+#[derive(Clone, Debug, Serialize)]
+pub struct VectorsManifest {
+    /// Protocol version the vectors target.
+    pub protocol_version: u32,
+    /// Exchange files relative to the output directory.
+    pub exchanges: Vec<String>,
+    /// Error files relative to the output directory.
+    pub errors: Vec<ManifestError>,
+    /// Schema files relative to the output directory.
+    pub schemas: Vec<String>,
+}
+
+/// Manifest entry for an error vector.
+#[derive(Clone, Debug, Serialize)]
+pub struct ManifestError {
+    /// Stable error code.
+    pub code: String,
+    /// CLI exit code for this error.
+    pub exit_code: i32,
+    /// Vector file relative to the output directory.
+    pub file: String,
+}
+
+/// Build the canonical vectors.
+#[must_use]
+pub fn protocol_vectors() -> ProtocolVectors {
+    let mut exchanges: Vec<ProtocolVector> = example_actions()
+        .into_iter()
+        .zip(1u64..)
+        .map(|((name, description, action), sequence)| {
+            let request = request(name, action);
+            let observation = example_observation(sequence);
+            let response = DriverResponseV2 {
+                protocol_version: PROTOCOL_VERSION,
+                request_id: request.request_id.clone(),
+                status: DriverResponseStatus::Ok,
+                screen_hash: Some(observation.screen.screen_hash()),
+                observation: Some(observation),
+                error: None,
+                action_metrics: Some(DriverActionMetrics {
+                    sequence,
+                    duration_ms: 5,
+                }),
+                budget_status: Some(example_budget_status(sequence)),
+                unchanged: false,
+                history: None,
+                image: None,
+                subscription: None,
+            };
+            ProtocolVector {
+                name: name.to_string(),
+                description: description.to_string(),
+                request,
+                response,
+            }
+        })
+        .collect();
+    exchanges.push(history_vector());
+    exchanges.push(subscribe_vector());
+    let errors = ErrorCode::ALL
+        .into_iter()
+        .map(|code| {
+            let (description, message, context) = example_error(code);
+            let mut request = request(code.as_str(), error_action(code));
+            // Requests rejected before execution carry no metrics.
+            let executed = !matches!(
+                code,
+                ErrorCode::ProtocolVersionMismatch
+                    | ErrorCode::Protocol
+                    | ErrorCode::StaleObservation
+            );
+            if code == ErrorCode::ProtocolVersionMismatch {
+                request.protocol_version = 1;
+            }
+            let mut response = DriverResponseV2 {
+                protocol_version: PROTOCOL_VERSION,
+                request_id: request.request_id.clone(),
+                status: DriverResponseStatus::Error,
+                observation: None,
+                error: Some(ErrorInfo {
+                    code: code.as_str().to_string(),
+                    message: message.to_string(),
+                    context,
+                }),
+                action_metrics: executed.then_some(DriverActionMetrics {
+                    sequence: 1,
+                    duration_ms: 5,
+                }),
+                budget_status: executed.then(|| example_budget_status(1)),
+                screen_hash: None,
+                unchanged: false,
+                history: None,
+                image: None,
+                subscription: None,
+            };
+            if code == ErrorCode::StaleObservation {
+                // The rejected action is not run; the fresh screen comes back.
+                let observation = example_observation(1);
+                request.expected_screen_hash = Some(STALE_SCREEN_HASH.to_string());
+                response.budget_status = Some(example_budget_status(0));
+                response.screen_hash = Some(observation.screen.screen_hash());
+                response.observation = Some(observation);
+            }
+            ProtocolVector {
+                name: code.as_str().to_string(),
+                description: description.to_string(),
+                request,
+                response,
+            }
+        })
+        .collect();
+    ProtocolVectors { exchanges, errors }
+}
+
+/// Write the vectors and schemas under `dir` (created if missing; existing
+/// vector files are replaced).
+///
+/// # Errors
+/// Returns `E_IO` if a directory or file cannot be written, `E_PROTOCOL` if
+/// a vector cannot be serialized.
+pub fn write_protocol_vectors(dir: &Path) -> RunnerResult<VectorsManifest> {
+    let vectors = protocol_vectors();
+    let mut manifest = VectorsManifest {
+        protocol_version: PROTOCOL_VERSION,
+        exchanges: Vec::new(),
+        errors: Vec::new(),
+        schemas: Vec::new(),
+    };
+    for vector in &vectors.exchanges {
+        let file = format!("exchanges/{}.json", vector.name);
+        write_json(dir, &file, vector)?;
+        manifest.exchanges.push(file);
+    }
+    for (vector, code) in vectors.errors.iter().zip(ErrorCode::ALL) {
+        let file = format!("errors/{}.json", vector.name);
+        write_json(dir, &file, vector)?;
+        manifest.errors.push(ManifestError {
+            code: code.as_str().to_string(),
+            exit_code: code.exit_code(),
+            file,
+        });
+    }
+    for (name, contents) in PROTOCOL_SCHEMAS {
+        let file = format!("schemas/{name}");
+        write_file(dir, &file, contents.as_bytes())?;
+        manifest.schemas.push(file);
+    }
+    write_json(dir, "manifest.json", &manifest)?;
+    Ok(manifest)
+}
+
+/// `expected_screen_hash` sent by the stale-observation vector.
+const STALE_SCREEN_HASH: &str = "0123456789abcdef";
+
+fn request(name: &str, action: Action) -> DriverRequestV2 {
+    DriverRequestV2 {
+        protocol_version: PROTOCOL_VERSION,
+        request_id: format!("req-{}", name.to_ascii_lowercase().replace('_', "-")),
+        action,
+        timeout_ms: Some(1000),
+        expected_screen_hash: None,
+        coalesce: false,
+        include_image: false,
+    }
+}
+
+fn example_actions() -> Vec<(&'static str, &'static str, Action)> {
+    vec![
+        ("key", "Press a named key", Action::key("Enter")),
+        ("text", "Type text", Action::text("hello")),
+        ("resize", "Resize the terminal", Action::resize(30, 100)),
+        (
+            "wait",
+            "Wait until the screen contains text",
+            Action::wait_for_text("hello"),
+        ),
+        (
+            "observe",
+            "Observe the screen without side effects",
+            Action {
+                action_type: ActionType::Observe,
+                payload: serde_json::json!({}),
+            },
+        ),
+        (
+            "raw",
+            "Write raw bytes (requires input.allow_raw_bytes)",
+            Action {
+                action_type: ActionType::Raw,
+                payload: serde_json::json!({"hex": "1b5b41"}),
+            },
+        ),
+        (
+            "hold_key",
+            "Hold a key with auto-repeat for a duration",
+            Action::hold_key("Down", 100, 30),
+        ),
+        (
+            "termios",
+            "Turn PTY echo off, leaving canonical mode and signals unchanged",
+            Action::termios(Some(false), None, None),
+        ),
+        (
+            "terminate",
+            "Terminate the process and end the session",
+            Action {
+                action_type: ActionType::Terminate,
+                payload: serde_json::json!({}),
+            },
+        ),
+    ]
+}
+
+/// A `history` query answered with the `text` exchange's log entry.
+fn history_vector() -> ProtocolVector {
+    let request = request("history", Action::history(Some(20)));
+    let observation = example_observation(1);
+    let screen_hash = observation.screen.screen_hash();
+    let entry = DriverHistoryEntry {
+        request_id: "req-text".to_string(),
+        action: Action::text("hello"),
+        status: DriverResponseStatus::Ok,
+        sequence: Some(1),
+        error_code: None,
+        started_at_ms: 0,
+        ended_at_ms: 5,
+        screen_hash: Some(screen_hash.clone()),
+        observation_timestamp_ms: Some(observation.timestamp_ms),
+        unchanged: false,
+    };
+    let response = DriverResponseV2 {
+        protocol_version: PROTOCOL_VERSION,
+        request_id: request.request_id.clone(),
+        status: DriverResponseStatus::Ok,
+        observation: None,
+        error: None,
+        action_metrics: None,
+        budget_status: Some(example_budget_status(1)),
+        screen_hash: Some(screen_hash),
+        unchanged: false,
+        history: Some(DriverHistory {
+            entries: vec![entry],
+            total: 1,
+        }),
+        image: None,
+        subscription: None,
+    };
+    ProtocolVector {
+        name: "history".to_string(),
+        description: "Query the session log to resynchronize after reconnecting".to_string(),
+        request,
+        response,
+    }
+}
+
+/// A `subscribe` request for screen changes and bells.
+fn subscribe_vector() -> ProtocolVector {
+    let events = [DriverEventKind::ScreenChanged, DriverEventKind::Bell];
+    let request = request("subscribe", Action::subscribe(&events, None, None));
+    let response = DriverResponseV2 {
+        protocol_version: PROTOCOL_VERSION,
+        request_id: request.request_id.clone(),
+        status: DriverResponseStatus::Ok,
+        observation: None,
+        error: None,
+        action_metrics: None,
+        budget_status: Some(example_budget_status(1)),
+        screen_hash: Some(example_observation(1).screen.screen_hash()),
+        unchanged: false,
+        history: None,
+        image: None,
+        subscription: Some(DriverSubscription {
+            events: events.to_vec(),
+            max_unacked: DEFAULT_DRIVER_EVENT_WINDOW,
+            poll_interval_ms: DEFAULT_DRIVER_EVENT_POLL_MS,
+            last_event_seq: 0,
+        }),
+    };
+    ProtocolVector {
+        name: "subscribe".to_string(),
+        description: "Ask for pushed event frames between responses".to_string(),
+        request,
+        response,
+    }
+}
+
+/// Action whose failure typically produces `code`.
+fn error_action(code: ErrorCode) -> Action {
+    match code {
+        ErrorCode::Timeout => Action::wait_for_text("ready"),
+        ErrorCode::PolicyDenied => Action {
+            action_type: ActionType::Raw,
+            payload: serde_json::json!({"hex": "1b5b41"}),
+        },
+        _ => Action::text("hello"),
+    }
+}
+
+/// Representative description, message, and context for `code`.
+fn example_error(code: ErrorCode) -> (&'static str, &'static str, Option<serde_json::Value>) {
+    match code {
+        ErrorCode::Internal => ("Unexpected internal failure", "internal error", None),
+        ErrorCode::PolicyDenied => (
+            "Action rejected by policy",
+            "raw input is disabled by policy",
+            Some(serde_json::json!({"fix": "Set policy.input.allow_raw_bytes to true"})),
+        ),
+        ErrorCode::SandboxUnavailable => (
+            "Sandbox requested but unavailable",
+            "sandbox-exec not available",
+            None,
+        ),
+        ErrorCode::Timeout => (
+            "Wait or budget timeout",
+            "wait condition timed out",
+            Some(serde_json::json!({"timeout_ms": 1000})),
+        ),
+        ErrorCode::AssertionFailed => (
+            "Assertion did not hold",
+            "assertion failed",
+            Some(serde_json::json!({"type": "screen_contains", "text": "hello"})),
+        ),
+        ErrorCode::ProcessExit => (
+            "Target process exited unexpectedly",
+            "process exited",
+            Some(serde_json::json!({"exit_code": 1})),
+        ),
+        ErrorCode::TerminalParse => (
+            "Terminal output could not be parsed",
+            "invalid utf-8 in terminal output",
+            Some(serde_json::json!({"valid_up_to": 0})),
+        ),
+        ErrorCode::ProtocolVersionMismatch => (
+            "Request used an unsupported protocol version",
+            "unsupported protocol version",
+            Some(serde_json::json!({"provided_version": 1, "supported_version": PROTOCOL_VERSION})),
+        ),
+        ErrorCode::Protocol => ("Malformed request", "invalid driver request json", None),
+        ErrorCode::Io => ("I/O failure", "failed to write to pty", None),
+        ErrorCode::ReplayMismatch => (
+            "Replay differs from baseline",
+            "snapshot mismatch",
+            Some(serde_json::json!({"kind": "snapshot", "index": 0})),
+        ),
+        ErrorCode::CliInvalidArg => ("Invalid CLI argument", "invalid argument", None),
+        ErrorCode::StaleObservation => (
+            "Action precondition failed on a changed screen",
+            "screen changed since the expected observation",
+            Some(serde_json::json!({
+                "expected_screen_hash": STALE_SCREEN_HASH,
+                "actual_screen_hash": example_observation(1).screen.screen_hash()
+            })),
+        ),
+    }
+}
+
+fn example_observation(sequence: u64) -> Observation {
+    Observation {
+        protocol_version: PROTOCOL_VERSION,
+        run_id: RunId::from_u128(1),
+        session_id: SessionId::from_u128(2),
+        timestamp_ms: sequence * 10,
+        screen: ScreenSnapshot {
+            snapshot_version: SNAPSHOT_VERSION,
+            snapshot_id: SnapshotId::from_u128(u128::from(sequence) + 100),
+            rows: 2,
+            cols: 10,
+            cursor: Cursor {
+                row: 0,
+                col: 5,
+                visible: true,
+            },
+            alternate_screen: false,
+            lines: vec!["hello".to_string(), String::new()],
+            cells: None,
+        },
+        transcript_delta: Some("hello".to_string()),
+        events: Vec::new(),
+        stderr_delta: None,
+        output_flow: None,
+        read_truncated: false,
+        read_bytes: 0,
+        read_dropped_bytes: 0,
+        widgets: Vec::new(),
+    }
+}
+
+fn example_budget_status(sequence: u64) -> BudgetStatus {
+    let budgets = Budgets::default();
+    BudgetStatus {
+        steps_used: sequence,
+        steps_max: budgets.max_steps,
+        runtime_ms: sequence * 10,
+        runtime_max_ms: budgets.max_runtime_ms,
+        output_bytes_used: 5,
+        output_bytes_max: budgets.max_output_bytes,
+        wait_ms_used: 0,
+    }
+}
+
+fn write_json<T: Serialize>(dir: &Path, file: &str, value: &T) -> RunnerResult<()> {
+    let mut data = serde_json::to_vec_pretty(value)
+        .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to serialize protocol vector", err))?;
+    data.push(b'\n');
+    write_file(dir, file, &data)
+}
+
+fn write_file(dir: &Path, file: &str, data: &[u8]) -> RunnerResult<()> {
+    let path = dir.join(file);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|err| RunnerError::io("E_IO", "failed to create vectors dir", err))?;
+    }
+    fs::write(&path, data)
+        .map_err(|err| RunnerError::io("E_IO", "failed to write protocol vector", err))
+}
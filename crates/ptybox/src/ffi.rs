@@ -0,0 +1,236 @@
+//! C ABI over the protocol v2 driver for language bindings.
+//!
+//! Enabled with the `ffi` feature. Build a shared library with:
+//!
+//! ```text
+//! cargo rustc -p ptybox --release --features ffi --crate-type cdylib
+//! ```
+//!
+//! All data crosses the boundary as UTF-8 JSON C strings:
+//!
+//! | Function | Input | Output |
+//! |----------|-------|--------|
+//! | [`ptybox_driver_new`] | [`FfiDriverConfig`] JSON | driver handle, or null + error JSON |
+//! | [`ptybox_driver_handshake`] | handle | handshake JSON |
+//! | [`ptybox_driver_send`] | handle, `DriverRequestV2` JSON | `DriverResponseV2` JSON |
+//! | [`ptybox_driver_close`] | handle | null, or error JSON |
+//! | [`ptybox_string_free`] | string returned by this API | — |
+//!
+//! Every returned string is owned by the caller and must be released with
+//! [`ptybox_string_free`]. Errors are serialized [`ErrorInfo`](crate::model::ErrorInfo) objects. Panics
+//! never unwind into the caller; they are reported as `E_INTERNAL`.
+
+#![allow(unsafe_code)]
+
+use crate::artifacts::ArtifactsWriterConfig;
+use crate::driver::{DriverConfig, EmbeddedDriver};
+use crate::model::driver::{DriverResponseStatus, DriverResponseV2};
+use crate::model::policy::Policy;
+use crate::model::PROTOCOL_VERSION;
+use crate::runner::{catch_panic, RunnerError};
+use serde::Deserialize;
+use std::ffi::{c_char, CStr, CString};
+use std::path::PathBuf;
+
+/// Opaque driver handle returned by [`ptybox_driver_new`].
+pub struct PtyboxDriver {
+    inner: EmbeddedDriver,
+}
+
+/// JSON configuration accepted by [`ptybox_driver_new`].
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FfiDriverConfig {
+    /// Command to execute (absolute path, allowlisted by the policy).
+    pub command: String,
+    /// Command arguments.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Optional working directory.
+    #[serde(default)]
+    pub cwd: Option<String>,
+    /// Policy object, same shape as a policy file.
+    pub policy: Policy,
+    /// Optional artifacts directory (overrides `policy.artifacts.dir`).
+    #[serde(default)]
+    pub artifacts_dir: Option<String>,
+    /// Overwrite an existing artifacts directory.
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+impl From<FfiDriverConfig> for DriverConfig {
+    fn from(config: FfiDriverConfig) -> Self {
+        Self {
+            command: config.command,
+            args: config.args,
+            cwd: config.cwd,
+            policy: config.policy,
+            artifacts: config.artifacts_dir.map(|dir| ArtifactsWriterConfig {
+                dir: PathBuf::from(dir),
+                overwrite: config.overwrite,
+            }),
+            separate_stderr: false,
+            ack_overrides: Vec::new(),
+        }
+    }
+}
+
+/// Start a driver session.
+///
+/// Returns null on failure and, when `error_out` is non-null, stores an
+/// error JSON string in `*error_out` (free with [`ptybox_string_free`]).
+///
+/// # Safety
+/// `config_json` must be null or a valid NUL-terminated string. `error_out`
+/// must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ptybox_driver_new(
+    config_json: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut PtyboxDriver {
+    // SAFETY: forwarded caller contract.
+    let config = unsafe { read_str(config_json) };
+    let result = guard(|| {
+        let config: FfiDriverConfig = serde_json::from_str(config?)
+            .map_err(|err| RunnerError::io("E_PROTOCOL", "invalid ffi driver config json", err))?;
+        EmbeddedDriver::start(config.into())
+    });
+    match result {
+        Ok(inner) => Box::into_raw(Box::new(PtyboxDriver { inner })),
+        Err(err) => {
+            if !error_out.is_null() {
+                // SAFETY: non-null and valid for writes per the caller contract.
+                unsafe { *error_out = error_json(&err) };
+            }
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Return the handshake JSON emitted when the session started, or null if
+/// `driver` is null.
+///
+/// # Safety
+/// `driver` must be null or a live handle from [`ptybox_driver_new`].
+#[no_mangle]
+pub unsafe extern "C" fn ptybox_driver_handshake(driver: *const PtyboxDriver) -> *mut c_char {
+    // SAFETY: forwarded caller contract.
+    match unsafe { driver.as_ref() } {
+        Some(driver) => into_c_string(driver.inner.handshake().to_string()),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Send one `DriverRequestV2` JSON object and return the `DriverResponseV2`
+/// JSON. Failures outside the protocol (null handle, invalid string, ended
+/// session) are returned as an error response with `request_id` `"unknown"`.
+///
+/// # Safety
+/// `driver` must be null or a live handle from [`ptybox_driver_new`] not used
+/// concurrently from another thread. `request_json` must be null or a valid
+/// NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ptybox_driver_send(
+    driver: *mut PtyboxDriver,
+    request_json: *const c_char,
+) -> *mut c_char {
+    // SAFETY: forwarded caller contract.
+    let driver = unsafe { driver.as_mut() };
+    // SAFETY: forwarded caller contract.
+    let request = unsafe { read_str(request_json) };
+    let result = guard(|| {
+        let driver = driver.ok_or_else(null_handle)?;
+        driver.inner.send(request?)
+    });
+    into_c_string(result.unwrap_or_else(|err| error_response_json(&err)))
+}
+
+/// Close the session and free the handle. Returns null on success or an
+/// error JSON string when the session ended with an error.
+///
+/// # Safety
+/// `driver` must be null or a live handle from [`ptybox_driver_new`]; it is
+/// invalid after this call.
+#[no_mangle]
+pub unsafe extern "C" fn ptybox_driver_close(driver: *mut PtyboxDriver) -> *mut c_char {
+    if driver.is_null() {
+        return std::ptr::null_mut();
+    }
+    // SAFETY: non-null handle allocated by `ptybox_driver_new` per the caller contract.
+    let driver = unsafe { Box::from_raw(driver) };
+    match guard(move || driver.inner.close()) {
+        Ok(()) => std::ptr::null_mut(),
+        Err(err) => error_json(&err),
+    }
+}
+
+/// Free a string returned by this API. Null is ignored.
+///
+/// # Safety
+/// `value` must be null or a string returned by this API, freed at most once.
+#[no_mangle]
+pub unsafe extern "C" fn ptybox_string_free(value: *mut c_char) {
+    if !value.is_null() {
+        // SAFETY: allocated by `CString::into_raw` in `into_c_string`.
+        drop(unsafe { CString::from_raw(value) });
+    }
+}
+
+/// Run `body`, converting a panic into `E_INTERNAL`.
+fn guard<T>(body: impl FnOnce() -> Result<T, RunnerError>) -> Result<T, RunnerError> {
+    catch_panic("ffi call", body).and_then(|result| result)
+}
+
+/// # Safety
+/// `value` must be null or a valid NUL-terminated string that outlives `'a`.
+unsafe fn read_str<'a>(value: *const c_char) -> Result<&'a str, RunnerError> {
+    if value.is_null() {
+        return Err(RunnerError::protocol(
+            "E_PROTOCOL",
+            "null string passed to ptybox ffi",
+            None,
+        ));
+    }
+    // SAFETY: non-null and NUL-terminated per the caller contract.
+    unsafe { CStr::from_ptr(value) }
+        .to_str()
+        .map_err(|err| RunnerError::io("E_PROTOCOL", "ffi string is not valid utf-8", err))
+}
+
+fn null_handle() -> RunnerError {
+    RunnerError::protocol("E_PROTOCOL", "null driver handle", None)
+}
+
+fn error_json(err: &RunnerError) -> *mut c_char {
+    into_c_string(to_json(&err.to_error_info()))
+}
+
+fn error_response_json(err: &RunnerError) -> String {
+    to_json(&DriverResponseV2 {
+        protocol_version: PROTOCOL_VERSION,
+        request_id: "unknown".to_string(),
+        status: DriverResponseStatus::Error,
+        observation: None,
+        error: Some(err.to_error_info()),
+        action_metrics: None,
+        budget_status: None,
+        screen_hash: None,
+        unchanged: false,
+        history: None,
+        image: None,
+        subscription: None,
+    })
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| {
+        r#"{"code":"E_INTERNAL","message":"failed to serialize ffi response"}"#.to_string()
+    })
+}
+
+fn into_c_string(value: String) -> *mut c_char {
+    // Interior NULs cannot occur in serde_json output; strip defensively.
+    let value = value.replace('\0', "");
+    CString::new(value).map_or(std::ptr::null_mut(), CString::into_raw)
+}
@@ -0,0 +1,117 @@
+//! Seeded random action sequences.
+
+use crate::model::Action;
+
+/// Named keys sent as-is.
+const KEYS: &[&str] = &[
+    "Enter",
+    "Tab",
+    "Escape",
+    "Backspace",
+    "Delete",
+    "Up",
+    "Down",
+    "Left",
+    "Right",
+    "Home",
+    "End",
+    "PageUp",
+    "PageDown",
+    "F1",
+    "F2",
+    "F5",
+    "F10",
+];
+
+/// Editing and navigation chords. Signal and flow-control chords (`Ctrl+C`,
+/// `Ctrl+Z`, `Ctrl+\`, `Ctrl+S`, `Ctrl+Q`) and EOF (`Ctrl+D`) are left out:
+/// they stop or freeze the command rather than exercise it.
+const CTRL_KEYS: &[&str] = &[
+    "Ctrl+A", "Ctrl+E", "Ctrl+K", "Ctrl+L", "Ctrl+N", "Ctrl+P", "Ctrl+R", "Ctrl+U", "Ctrl+W",
+];
+
+/// Non-ASCII text mixed in to exercise wide and multi-byte handling.
+const WIDE_TEXT: &[&str] = &["é", "ß", "漢字", "🦀", "👍🏽", "\u{301}"];
+
+/// `splitmix64`: small, fast, and stable across platforms and releases, so a
+/// seed always reproduces the same sequence. Fixture generators
+/// (`runner::fixtures`) use it too.
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..bound` (`bound` > 0).
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound.max(1)
+    }
+
+    /// Uniform in `low..=high`.
+    fn between(&mut self, low: u16, high: u16) -> u16 {
+        let span = u64::from(high.saturating_sub(low)) + 1;
+        low.saturating_add(u16::try_from(self.below(span)).unwrap_or(0))
+    }
+
+    /// ASCII character in `low..=high`.
+    fn char_between(&mut self, low: u8, high: u8) -> char {
+        let byte = self.between(u16::from(low), u16::from(high));
+        char::from(u8::try_from(byte).unwrap_or(low))
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        let bound = u64::try_from(items.len()).unwrap_or(u64::MAX);
+        usize::try_from(self.below(bound))
+            .ok()
+            .and_then(|index| items.get(index))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// One action: mostly text, then keys, occasionally a resize.
+    pub(super) fn action(&mut self) -> Action {
+        match self.below(10) {
+            0..=5 => Action::text(&self.text()),
+            6..=8 => match self.below(4) {
+                0 => Action::key(self.pick(CTRL_KEYS)),
+                1 => Action::key(&self.char_between(b'a', b'z').to_string()),
+                _ => Action::key(self.pick(KEYS)),
+            },
+            _ => Action::resize(self.between(4, 60), self.between(10, 200)),
+        }
+    }
+
+    /// One to eight characters of printable ASCII, sometimes with wide or
+    /// combining text.
+    fn text(&mut self) -> String {
+        let len = self.below(8) + 1;
+        let mut text = String::new();
+        for _ in 0..len {
+            if self.below(16) == 0 {
+                text.push_str(self.pick(WIDE_TEXT));
+            } else {
+                text.push(self.char_between(b' ', b'~'));
+            }
+        }
+        text
+    }
+}
+
+/// The random action sequence `seed` produces.
+///
+/// Sequences are stable: the same seed and count give the same actions on
+/// every platform.
+#[must_use]
+pub fn generate_actions(seed: u64, count: usize) -> Vec<Action> {
+    let mut rng = Rng::new(seed);
+    (0..count).map(|_| rng.action()).collect()
+}
@@ -0,0 +1,54 @@
+//! Delta debugging over action sequences.
+
+use crate::model::Action;
+use crate::runner::RunnerResult;
+
+/// Shrink `actions` while `fails` keeps returning true, removing chunks of
+/// halving size (ddmin over complements).
+///
+/// Stops after `max_runs` calls to `fails`. Returns the smallest failing
+/// sequence found and the number of calls made.
+///
+/// # Errors
+/// Propagates the first error from `fails`.
+pub(super) fn minimize<F>(
+    actions: Vec<Action>,
+    max_runs: u32,
+    mut fails: F,
+) -> RunnerResult<(Vec<Action>, u32)>
+where
+    F: FnMut(&[Action]) -> RunnerResult<bool>,
+{
+    let mut current = actions;
+    let mut granularity = 2;
+    let mut runs = 0;
+    while current.len() >= 2 {
+        let chunk = current.len().div_ceil(granularity);
+        let mut reduced = false;
+        for start in (0..current.len()).step_by(chunk) {
+            if runs >= max_runs {
+                return Ok((current, runs));
+            }
+            let candidate: Vec<Action> = current
+                .iter()
+                .take(start)
+                .chain(current.iter().skip(start + chunk))
+                .cloned()
+                .collect();
+            runs += 1;
+            if fails(&candidate)? {
+                current = candidate;
+                granularity = granularity.saturating_sub(1).max(2);
+                reduced = true;
+                break;
+            }
+        }
+        if !reduced {
+            if granularity >= current.len() {
+                break;
+            }
+            granularity = (granularity * 2).min(current.len());
+        }
+    }
+    Ok((current, runs))
+}
@@ -0,0 +1,441 @@
+//! Robustness fuzzing with seeded random input (`ptybox fuzz`).
+//!
+//! [`fuzz`] starts the command under its policy once per iteration and
+//! sends it a random sequence of text, keys, and resizes generated from the
+//! iteration's seed (see [`generate_actions`]). An iteration fails when a
+//! watcher triggers (by default [`crash_watchers`], which look for panic and
+//! crash text on screen) or when the command exits unsuccessfully before
+//! its sequence ends. A clean exit, such as a random `q` quitting the
+//! command, ends the iteration without failing it.
+//!
+//! The first failure stops fuzzing. Its sequence is cut at the failing
+//! action and shrunk by delta debugging, re-running candidate sequences
+//! and keeping those that fail the same way (same watcher, or same exit
+//! code). The minimized actions become a scenario that replays them.
+//!
+//! Each sequence stops early once it exceeds `budgets.max_runtime_ms` or
+//! `budgets.max_output_bytes`, and is at most `budgets.max_steps - 1`
+//! actions long so the reproduction scenario fits the same policy.
+
+mod generate;
+mod minimize;
+
+pub use generate::generate_actions;
+pub(crate) use generate::Rng;
+
+use crate::actions::perform_action;
+use crate::model::policy::Policy;
+use crate::model::{
+    Action, ActionType, Assertion, Condition, ExitStatus, FuzzFailure, FuzzFailureKind, FuzzReport,
+    OutputBufferConfig, PolicyRef, ResizeConfig, RunConfig, RunId, Scenario, ScenarioDefaults,
+    ScenarioMetadata, Step, StepId, TerminalSize, Watcher, SCENARIO_VERSION,
+};
+use crate::policy::{fallback_spawn_policy, validate_policy, EffectivePolicy};
+use crate::runner::{spawn_scenario_session, ErrorCode, RunnerError, RunnerResult};
+use crate::session::Session;
+use crate::util::{convert_exit_status, elapsed_ms, SandboxCleanupGuard};
+use minimize::minimize;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+/// Default number of iterations.
+pub const DEFAULT_ITERATIONS: u32 = 20;
+
+/// Default actions per iteration.
+pub const DEFAULT_STEPS: usize = 50;
+
+/// Default time to observe the command after each action.
+pub const DEFAULT_STEP_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// Default limit on sequences run while minimizing.
+pub const DEFAULT_MAX_MINIMIZE_RUNS: u32 = 200;
+
+/// Name of the watcher [`crash_watchers`] returns.
+pub const CRASH_WATCHER: &str = "crash-output";
+
+/// Screen text that marks a crash: Rust panics, segfault and core dump
+/// messages, Python tracebacks, sanitizer reports, and common fatal errors.
+const CRASH_PATTERN: &str = r"(?i)(panicked at|segmentation fault|core dumped|traceback \(most recent call last\)|addresssanitizer|fatal error|unhandled exception)";
+
+/// Time given to late output and exits after the last action.
+const SETTLE_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// Longest wait for the exit in an exit reproduction's final step.
+const MAX_EXIT_WAIT_MS: u64 = 5_000;
+
+/// What to fuzz and how much.
+#[derive(Clone, Debug)]
+pub struct FuzzConfig {
+    /// Command to run.
+    pub command: String,
+    /// Command arguments.
+    pub args: Vec<String>,
+    /// Working directory; `policy.fs.working_dir` when `None`.
+    pub cwd: Option<String>,
+    /// Policy every iteration runs under.
+    pub policy: Policy,
+    /// Base seed; iteration `i` uses `seed + i`.
+    pub seed: u64,
+    /// Iterations to run unless one fails first.
+    pub iterations: u32,
+    /// Actions per iteration (capped at `budgets.max_steps - 1`).
+    pub steps: usize,
+    /// Time to observe the command after each action.
+    pub step_timeout: Duration,
+    /// Watchers marking a failure.
+    pub watchers: Vec<Watcher>,
+    /// Most sequences to run while minimizing a failure.
+    pub max_minimize_runs: u32,
+}
+
+impl FuzzConfig {
+    /// Fuzz `command` with the defaults above and [`crash_watchers`].
+    #[must_use]
+    pub fn new(command: impl Into<String>, args: Vec<String>, policy: Policy, seed: u64) -> Self {
+        Self {
+            command: command.into(),
+            args,
+            cwd: None,
+            policy,
+            seed,
+            iterations: DEFAULT_ITERATIONS,
+            steps: DEFAULT_STEPS,
+            step_timeout: DEFAULT_STEP_TIMEOUT,
+            watchers: crash_watchers(),
+            max_minimize_runs: DEFAULT_MAX_MINIMIZE_RUNS,
+        }
+    }
+}
+
+/// The default watcher: crash and panic text anywhere on screen.
+#[must_use]
+pub fn crash_watchers() -> Vec<Watcher> {
+    vec![Watcher {
+        name: CRASH_WATCHER.to_string(),
+        condition: Condition::screen_matches(CRASH_PATTERN),
+    }]
+}
+
+/// Fuzz `config.command` until an iteration fails or all have run. See the
+/// module docs.
+///
+/// # Errors
+/// - `E_POLICY_DENIED` if the policy or command is not allowed
+/// - `E_PROTOCOL` if a watcher is invalid
+/// - `E_IO` or `E_SANDBOX_UNAVAILABLE` if the command cannot be spawned
+pub fn fuzz(config: &FuzzConfig) -> RunnerResult<FuzzReport> {
+    let target = Target::new(config)?;
+    let max_steps =
+        usize::try_from(config.policy.budgets.max_steps.saturating_sub(1)).unwrap_or(usize::MAX);
+    let steps = config.steps.min(max_steps).max(1);
+    let mut report = FuzzReport {
+        seed: config.seed,
+        iterations: 0,
+        actions: 0,
+        clean_exits: 0,
+        failure: None,
+    };
+    for iteration in 0..config.iterations {
+        let seed = config.seed.wrapping_add(u64::from(iteration));
+        let mut actions = generate_actions(seed, steps);
+        report.iterations += 1;
+        let failure = match target.run(&actions)? {
+            Outcome::Completed { sent } => {
+                report.actions += sent as u64;
+                continue;
+            }
+            Outcome::CleanExit { sent } => {
+                report.actions += sent as u64;
+                report.clean_exits += 1;
+                continue;
+            }
+            Outcome::Failed(failure) => failure,
+        };
+        actions.truncate(failure.at + 1);
+        report.actions += actions.len() as u64;
+        let original_steps = actions.len();
+        let (minimized, minimization_runs) =
+            minimize(actions, config.max_minimize_runs, |candidate| {
+                Ok(matches!(
+                    target.run(candidate)?,
+                    Outcome::Failed(other) if other.signature == failure.signature
+                ))
+            })?;
+        report.failure = Some(FuzzFailure {
+            iteration,
+            seed,
+            kind: failure.signature.kind(),
+            watcher: failure.signature.watcher(),
+            error: failure.error.to_error_info(),
+            exit_status: failure.exit_status.clone(),
+            original_steps,
+            minimized_steps: minimized.len(),
+            minimization_runs,
+            scenario: target.reproduction(seed, &minimized, &failure),
+        });
+        break;
+    }
+    Ok(report)
+}
+
+/// How one sequence ended.
+enum Outcome {
+    /// Every action was sent (or a budget ran out) without a failure.
+    Completed { sent: usize },
+    /// The command exited successfully before the sequence ended.
+    CleanExit { sent: usize },
+    /// A watcher triggered or the command exited unsuccessfully.
+    Failed(Box<Failure>),
+}
+
+struct Failure {
+    /// Index of the action after which the failure was seen.
+    at: usize,
+    signature: Signature,
+    error: RunnerError,
+    exit_status: Option<ExitStatus>,
+}
+
+/// What minimization must preserve.
+#[derive(Clone, PartialEq, Eq)]
+enum Signature {
+    Watcher(String),
+    Exit(Option<i32>),
+}
+
+impl Signature {
+    fn kind(&self) -> FuzzFailureKind {
+        match self {
+            Self::Watcher(_) => FuzzFailureKind::Watcher,
+            Self::Exit(_) => FuzzFailureKind::Exit,
+        }
+    }
+
+    fn watcher(&self) -> Option<String> {
+        match self {
+            Self::Watcher(name) => Some(name.clone()),
+            Self::Exit(_) => None,
+        }
+    }
+}
+
+/// The validated command and policy every sequence runs against.
+struct Target<'a> {
+    config: &'a FuzzConfig,
+    spawn_policy: Policy,
+    /// Scenario without steps, used to spawn and as the reproduction base.
+    scenario: Scenario,
+}
+
+impl<'a> Target<'a> {
+    fn new(config: &'a FuzzConfig) -> RunnerResult<Self> {
+        let enforcement = validate_policy(&config.policy)?;
+        let scenario = Scenario {
+            scenario_version: SCENARIO_VERSION,
+            metadata: ScenarioMetadata {
+                name: format!("fuzz-{}", config.seed),
+                description: None,
+                tags: vec!["fuzz".to_string()],
+                author: None,
+                source: None,
+                revision: None,
+            },
+            run: RunConfig {
+                command: config.command.clone(),
+                args: config.args.clone(),
+                cwd: config.cwd.clone(),
+                initial_size: TerminalSize::default(),
+                policy: PolicyRef::Inline(Box::new(config.policy.clone())),
+                separate_stderr: false,
+                output_buffer: OutputBufferConfig::default(),
+                resize: ResizeConfig::default(),
+                remote: None,
+                validate_args: false,
+                capture_attributes: false,
+            },
+            steps: Vec::new(),
+            defaults: ScenarioDefaults::default(),
+            watchers: config.watchers.clone(),
+            post_run: Vec::new(),
+            post: Vec::new(),
+            fixtures: Vec::new(),
+        };
+        EffectivePolicy::new(config.policy.clone()).validate_run_config(&scenario.run)?;
+        crate::session::validate_watchers(&config.watchers)?;
+        Ok(Self {
+            config,
+            spawn_policy: fallback_spawn_policy(&config.policy, enforcement.as_ref()),
+            scenario,
+        })
+    }
+
+    /// Run `actions` against a fresh process.
+    fn run(&self, actions: &[Action]) -> RunnerResult<Outcome> {
+        let mut cleanup_guard = SandboxCleanupGuard::new(None);
+        let mut session = spawn_scenario_session(
+            &self.scenario,
+            &self.spawn_policy,
+            &None,
+            &mut None,
+            RunId::new(),
+            None,
+            &mut cleanup_guard,
+        )?;
+        session.set_watchers(&self.config.watchers)?;
+        let outcome = self.drive(&mut session, actions);
+        let _ = session.terminate_with(&self.config.policy.process);
+        outcome
+    }
+
+    fn drive(&self, session: &mut Session, actions: &[Action]) -> RunnerResult<Outcome> {
+        let policy = &self.config.policy;
+        let started = Instant::now();
+        let mut output_bytes = 0;
+        let mut sent = 0;
+        for (index, action) in actions.iter().enumerate() {
+            if elapsed_ms(&started) >= policy.budgets.max_runtime_ms
+                || output_bytes >= policy.budgets.max_output_bytes
+            {
+                return Ok(Outcome::Completed { sent });
+            }
+            sent += 1;
+            let result = perform_action(session, action, self.config.step_timeout, policy);
+            if let Some(outcome) = check(session, result, index, &mut output_bytes)? {
+                return Ok(outcome);
+            }
+        }
+        let settled = session.observe(SETTLE_TIMEOUT);
+        let last = sent.saturating_sub(1);
+        Ok(
+            check(session, settled, last, &mut output_bytes)?
+                .unwrap_or(Outcome::Completed { sent }),
+        )
+    }
+
+    /// A scenario replaying `actions` under the fuzzed policy and watchers.
+    ///
+    /// A watcher failure ends with `terminate`; an exit failure ends by
+    /// waiting for the exit and asserting its code.
+    fn reproduction(&self, seed: u64, actions: &[Action], failure: &Failure) -> Scenario {
+        let budgets = &self.config.policy.budgets;
+        let timeout_ms = u64::try_from(self.config.step_timeout.as_millis())
+            .unwrap_or(u64::MAX)
+            .min(budgets.max_wait_ms);
+        let mut steps: Vec<Step> = actions
+            .iter()
+            .enumerate()
+            .map(|(index, action)| step(index + 1, action.clone(), timeout_ms, Vec::new()))
+            .collect();
+        let last = match &failure.signature {
+            Signature::Watcher(_) => {
+                step(steps.len() + 1, Action::terminate(), timeout_ms, Vec::new())
+            }
+            Signature::Exit(code) => step(
+                steps.len() + 1,
+                Action {
+                    action_type: ActionType::Wait,
+                    payload: serde_json::json!({ "condition": Condition::process_exited() }),
+                },
+                budgets.max_wait_ms.min(MAX_EXIT_WAIT_MS),
+                code.iter().copied().map(Assertion::exit_code).collect(),
+            ),
+        };
+        steps.push(last);
+        let mut scenario = self.scenario.clone();
+        scenario.metadata.name = format!("fuzz-{seed}");
+        scenario.metadata.description = Some(format!(
+            "ptybox fuzz seed {seed}, minimized to {} action(s): {}",
+            actions.len(),
+            failure.error.message
+        ));
+        scenario.steps = steps;
+        scenario
+    }
+}
+
+fn step(number: usize, action: Action, timeout_ms: u64, assert: Vec<Assertion>) -> Step {
+    Step {
+        id: StepId::new(),
+        name: format!("fuzz-{number}"),
+        action,
+        assert,
+        timeout_ms,
+        retries: 0,
+        skip_default_assertions: false,
+        sample_interval_ms: None,
+        expected_screen: None,
+        capture: None,
+        env_overlay: BTreeMap::new(),
+        metadata: BTreeMap::new(),
+        vars: Vec::new(),
+    }
+}
+
+/// Classify the observation after action `index`: a watcher trip or an
+/// exit ends the sequence; any other error is returned.
+fn check(
+    session: &mut Session,
+    result: RunnerResult<crate::model::Observation>,
+    index: usize,
+    output_bytes: &mut u64,
+) -> RunnerResult<Option<Outcome>> {
+    let error = match result {
+        Ok(observation) => {
+            *output_bytes += observation.output_bytes();
+            None
+        }
+        Err(err) => match watcher_failure(session, err, index) {
+            Ok(outcome) => return Ok(Some(outcome)),
+            Err(err) => Some(err),
+        },
+    };
+    let Some(status) = session.wait_for_exit(Duration::ZERO)? else {
+        return error.map_or(Ok(None), Err);
+    };
+    // Output written just before exiting can still trip a watcher; let it,
+    // so the same crash is classified the same way on every run.
+    if let Err(err) = session.observe(SETTLE_TIMEOUT) {
+        if let Ok(outcome) = watcher_failure(session, err, index) {
+            return Ok(Some(outcome));
+        }
+    }
+    let status = convert_exit_status(status, false);
+    if status.success {
+        return Ok(Some(Outcome::CleanExit { sent: index + 1 }));
+    }
+    let error = RunnerError::with_context(
+        ErrorCode::ProcessExit,
+        format!("command exited unsuccessfully after action {}", index + 1),
+        serde_json::json!({ "exit_status": status, "action_index": index }),
+    );
+    Ok(Some(Outcome::Failed(Box::new(Failure {
+        at: index,
+        signature: Signature::Exit(status.exit_code),
+        error,
+        exit_status: Some(status),
+    }))))
+}
+
+/// The failure for `err` if a watcher raised it; otherwise `err` back.
+fn watcher_failure(
+    session: &mut Session,
+    err: RunnerError,
+    index: usize,
+) -> Result<Outcome, RunnerError> {
+    if session.take_watcher_trip().is_none() {
+        return Err(err);
+    }
+    let name = err
+        .context
+        .as_ref()
+        .and_then(|context| context.get("watcher"))
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    Ok(Outcome::Failed(Box::new(Failure {
+        at: index,
+        signature: Signature::Watcher(name),
+        error: err,
+        exit_status: None,
+    })))
+}
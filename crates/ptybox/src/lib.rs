@@ -17,11 +17,15 @@
 //! | [`policy`] | Deny-by-default policy validation, sandbox profile generation |
 //! | [`runner`] | Step execution engine, wait conditions, budget enforcement |
 //! | [`driver`] | Interactive NDJSON protocol v2 for agent loops |
+//! | `ffi` | C ABI over the driver for language bindings (`ffi` feature) |
 //! | [`serve`] | Stateless session daemon for agent-friendly CLI |
 //! | [`artifacts`] | Transcript, snapshots, checksums, run summary to disk |
+//! | [`fuzz`] | Seeded random input with minimized failure reproductions |
 //! | [`replay`] | Replay comparison with normalization filters |
+//! | [`report`] | Run results as GitHub annotations and check runs |
 //! | [`scenario`] | Scenario/policy file parsing (JSON/YAML) |
 //! | [`assertions`] | Assertion engine for screen/transcript verification |
+//! | [`predicate`] | Predicates shared by waits, assertions, and watchers |
 //! | [`model`] | All domain types: `Policy`, `Scenario`, `RunResult`, `Observation` |
 //!
 //! # Getting Started
@@ -62,7 +66,7 @@
 //! - Resource budgets prevent denial-of-service
 //! - Regex patterns are bounded to prevent `ReDoS`
 
-#![forbid(unsafe_code)]
+#![cfg_attr(not(feature = "ffi"), forbid(unsafe_code))]
 #![warn(missing_docs)]
 
 // Legacy error constructors are deprecated but still widely used across modules.
@@ -74,11 +78,21 @@ pub mod artifacts;
 pub mod assertions;
 #[allow(deprecated)]
 pub mod driver;
+#[cfg(feature = "ffi")]
+#[allow(deprecated)]
+pub mod ffi;
+pub mod fuzz;
+#[cfg(feature = "log-forward")]
+pub mod log_forward;
 pub mod model;
+#[cfg(feature = "otel")]
+pub mod otel;
 #[allow(deprecated)]
 pub mod policy;
+pub mod predicate;
 #[allow(deprecated)]
 pub mod replay;
+pub mod report;
 #[allow(deprecated)]
 pub mod runner;
 #[allow(deprecated)]
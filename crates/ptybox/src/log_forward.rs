@@ -0,0 +1,683 @@
+//! Forward a run's transcript and key events to syslog, journald, or a UDP
+//! collector (feature `log-forward`).
+//!
+//! When [`RunnerOptions::log_forward`](crate::runner::RunnerOptions::log_forward)
+//! is set, each `run_scenario` / `run_exec` call sends records while it runs:
+//!
+//! | Kind | Sent for | Text |
+//! |------|----------|------|
+//! | `run` | run start and end | command, then status and error code |
+//! | `step` | step start and end | step name, then status and duration |
+//! | `transcript` | each line of PTY output | the line, escape sequences removed |
+//! | `stderr` | each line of separated stderr | the line, escape sequences removed |
+//! | `event` | observation events other than `pty_output` | event type and message |
+//!
+//! Every record carries the run id and, inside a step, the step id and
+//! name: as RFC 5424 structured data (`[ptybox@32473 ...]`) over UDP, as a
+//! bracketed `key=value` prefix on the local syslog socket, and as
+//! `PTYBOX_*` fields for journald. No hostname is sent.
+//!
+//! Before a run starts, the config is checked against the run's policy (see
+//! [`LogForwardConfig::check_policy`]): a UDP collector must be listed in
+//! `log_forward.allowed_udp`, which is empty by default, and nothing is
+//! forwarded when the policy encrypts artifacts, since records carry the
+//! transcript in plaintext.
+//!
+//! Records are sent from a background thread through a bounded queue; when
+//! the collector cannot keep up, records are dropped and the count is
+//! reported when the run ends. Forwarding never writes to the artifacts, so
+//! they stay identical with and without the feature. Failures are logged
+//! with `tracing` and do not change the run's result.
+
+use crate::artifacts::grep::strip_escapes;
+use crate::model::policy::Policy;
+use crate::model::{Observation, RunId, RunResult, RunStatus, StepId, StepStatus};
+use crate::runner::{
+    ErrorCode, ObservationTap, ProgressCallback, ProgressEvent, RunnerError, RunnerOptions,
+    RunnerResult,
+};
+use serde_json::json;
+use std::fmt::Write as _;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::os::unix::net::UnixDatagram;
+use std::path::PathBuf;
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Local syslog socket used by [`LogForwardConfig::syslog`].
+pub const DEFAULT_SYSLOG_SOCKET: &str = "/dev/log";
+/// journald socket used by [`LogForwardConfig::journald`].
+pub const DEFAULT_JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+/// Environment variable selecting the target for
+/// [`LogForwardConfig::from_env`]: `syslog`, `journald`, or
+/// `udp://host:port`.
+pub const LOG_FORWARD_ENV: &str = "PTYBOX_LOG_FORWARD";
+/// Environment variable overriding the syslog identifier.
+pub const LOG_FORWARD_IDENT_ENV: &str = "PTYBOX_LOG_FORWARD_IDENT";
+/// Longest record text in bytes; longer lines are cut.
+pub const MAX_RECORD_TEXT_BYTES: usize = 1024;
+/// Records waiting to be sent before new ones are dropped.
+const QUEUE_RECORDS: usize = 1024;
+/// IANA private enterprise number reserved for documentation, used as the
+/// RFC 5424 structured data ID suffix.
+const SD_ID: &str = "ptybox@32473";
+
+const SEVERITY_ERR: u8 = 3;
+const SEVERITY_WARNING: u8 = 4;
+const SEVERITY_INFO: u8 = 6;
+/// Syslog facility `user`.
+const FACILITY_USER: u8 = 1;
+
+/// Where records are sent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LogTarget {
+    /// `<PRI>ident[pid]: ...` lines on a local syslog datagram socket.
+    Syslog(PathBuf),
+    /// Native journal protocol on journald's datagram socket.
+    Journald(PathBuf),
+    /// RFC 5424 messages over UDP to a `host:port` collector, resolved when
+    /// forwarding starts.
+    Udp(String),
+}
+
+/// Where and how to forward a run's logs.
+#[derive(Clone, Debug)]
+pub struct LogForwardConfig {
+    /// Destination socket.
+    pub target: LogTarget,
+    /// Syslog identifier (`APP-NAME`, `SYSLOG_IDENTIFIER`).
+    pub ident: String,
+}
+
+impl LogForwardConfig {
+    /// Forward to `target` as `ptybox`.
+    #[must_use]
+    pub fn new(target: LogTarget) -> Self {
+        Self {
+            target,
+            ident: "ptybox".to_string(),
+        }
+    }
+
+    /// Forward to the local syslog socket ([`DEFAULT_SYSLOG_SOCKET`]).
+    #[must_use]
+    pub fn syslog() -> Self {
+        Self::new(LogTarget::Syslog(DEFAULT_SYSLOG_SOCKET.into()))
+    }
+
+    /// Forward to journald ([`DEFAULT_JOURNALD_SOCKET`]).
+    #[must_use]
+    pub fn journald() -> Self {
+        Self::new(LogTarget::Journald(DEFAULT_JOURNALD_SOCKET.into()))
+    }
+
+    /// Forward to the UDP collector at `endpoint` (`udp://host:port` or
+    /// `host:port`). Each run checks the endpoint against its policy's
+    /// `log_forward.allowed_udp` before anything is sent, and the host is
+    /// only resolved once it is allowed.
+    ///
+    /// # Errors
+    /// `E_PROTOCOL` if `endpoint` is not `host:port` with a numeric port.
+    pub fn udp(endpoint: &str) -> RunnerResult<Self> {
+        let authority = endpoint.strip_prefix("udp://").unwrap_or(endpoint);
+        if crate::policy::udp_authority(authority).is_none() {
+            return Err(RunnerError::with_context(
+                ErrorCode::Protocol,
+                "invalid UDP log endpoint",
+                json!({ "endpoint": endpoint, "example": "udp://127.0.0.1:514" }),
+            ));
+        }
+        Ok(Self::new(LogTarget::Udp(authority.to_string())))
+    }
+
+    /// Check that the run's `policy` allows forwarding to this target.
+    ///
+    /// # Errors
+    /// `E_POLICY_DENIED` when the policy encrypts artifacts (records carry
+    /// the transcript in plaintext), or the target is a UDP collector not
+    /// listed in `log_forward.allowed_udp`.
+    pub fn check_policy(&self, policy: &Policy) -> RunnerResult<()> {
+        if policy.artifacts.encryption.is_some() {
+            return Err(RunnerError::with_context(
+                ErrorCode::PolicyDenied,
+                "log forwarding would send the transcript in plaintext while artifacts are encrypted",
+                json!({
+                    "target": self.target_label(),
+                    "fix": format!("Unset {LOG_FORWARD_ENV} for runs whose policy sets artifacts.encryption")
+                }),
+            ));
+        }
+        let LogTarget::Udp(authority) = &self.target else {
+            return Ok(());
+        };
+        let allowlist = &policy.log_forward.allowed_udp;
+        if allowlist
+            .iter()
+            .any(|entry| entry.trim().eq_ignore_ascii_case(authority))
+        {
+            return Ok(());
+        }
+        Err(RunnerError::with_context(
+            ErrorCode::PolicyDenied,
+            "UDP log endpoint is not allowlisted",
+            json!({
+                "endpoint": authority,
+                "allowlist": allowlist,
+                "fix": format!("Add {authority} to policy.log_forward.allowed_udp")
+            }),
+        ))
+    }
+
+    fn target_label(&self) -> String {
+        match &self.target {
+            LogTarget::Syslog(path) | LogTarget::Journald(path) => path.display().to_string(),
+            LogTarget::Udp(authority) => format!("udp://{authority}"),
+        }
+    }
+
+    /// Build a config from [`LOG_FORWARD_ENV`] and [`LOG_FORWARD_IDENT_ENV`].
+    /// Returns `None` when no target is set.
+    ///
+    /// # Errors
+    /// `E_PROTOCOL` for an unknown target, and the errors of
+    /// [`udp`](Self::udp) for a UDP endpoint.
+    pub fn from_env() -> RunnerResult<Option<Self>> {
+        let target = match std::env::var(LOG_FORWARD_ENV) {
+            Ok(target) if !target.is_empty() => target,
+            _ => return Ok(None),
+        };
+        let mut config = match target.as_str() {
+            "syslog" => Self::syslog(),
+            "journald" => Self::journald(),
+            endpoint if endpoint.starts_with("udp://") => Self::udp(endpoint)?,
+            _ => {
+                return Err(RunnerError::with_context(
+                    ErrorCode::Protocol,
+                    format!("unsupported {LOG_FORWARD_ENV} target"),
+                    json!({
+                        "received": target,
+                        "expected": ["syslog", "journald", "udp://host:port"]
+                    }),
+                ))
+            }
+        };
+        if let Ok(ident) = std::env::var(LOG_FORWARD_IDENT_ENV) {
+            if !ident.is_empty() {
+                config.ident = ident;
+            }
+        }
+        Ok(Some(config))
+    }
+}
+
+/// One record waiting to be sent.
+struct Record {
+    kind: &'static str,
+    severity: u8,
+    step: Option<(StepId, String)>,
+    text: String,
+    timestamp_ms: u64,
+}
+
+/// Output stream whose partial last line is held back.
+#[derive(Clone, Copy)]
+enum Stream {
+    Transcript,
+    Stderr,
+}
+
+impl Stream {
+    fn kind(self) -> &'static str {
+        match self {
+            Self::Transcript => "transcript",
+            Self::Stderr => "stderr",
+        }
+    }
+}
+
+#[derive(Default)]
+struct ForwardState {
+    sender: Option<SyncSender<Record>>,
+    step: Option<(StepId, String)>,
+    transcript: String,
+    stderr: String,
+    dropped: u64,
+}
+
+impl ForwardState {
+    /// Queue a record, counting it as dropped when the queue is full.
+    fn push(&mut self, kind: &'static str, severity: u8, text: String) {
+        let Some(sender) = &self.sender else {
+            return;
+        };
+        match sender.try_send(self.record(kind, severity, text)) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => self.dropped += 1,
+            Err(TrySendError::Disconnected(_)) => self.sender = None,
+        }
+    }
+
+    fn record(&self, kind: &'static str, severity: u8, text: String) -> Record {
+        Record {
+            kind,
+            severity,
+            step: self.step.clone(),
+            text: truncate(text),
+            timestamp_ms: now_ms(),
+        }
+    }
+
+    fn buffer(&mut self, stream: Stream) -> &mut String {
+        match stream {
+            Stream::Transcript => &mut self.transcript,
+            Stream::Stderr => &mut self.stderr,
+        }
+    }
+
+    /// Append output and send every complete line; a partial line is held
+    /// until it ends or grows past [`MAX_RECORD_TEXT_BYTES`].
+    fn append(&mut self, stream: Stream, delta: &str) {
+        let mut pending = std::mem::take(self.buffer(stream));
+        pending.push_str(delta);
+        while let Some(end) = pending.find('\n') {
+            let rest = pending.split_off(end + 1);
+            self.push_line(stream, &pending);
+            pending = rest;
+        }
+        if pending.len() > MAX_RECORD_TEXT_BYTES {
+            self.push_line(stream, &pending);
+            pending.clear();
+        }
+        *self.buffer(stream) = pending;
+    }
+
+    /// Send the held partial lines.
+    fn flush_output(&mut self) {
+        for stream in [Stream::Transcript, Stream::Stderr] {
+            let pending = std::mem::take(self.buffer(stream));
+            self.push_line(stream, &pending);
+        }
+    }
+
+    fn push_line(&mut self, stream: Stream, line: &str) {
+        let text = strip_escapes(line);
+        let text = text.trim_end();
+        if !text.is_empty() {
+            self.push(stream.kind(), SEVERITY_INFO, text.to_string());
+        }
+    }
+}
+
+/// The forwarder, if any, with the options and progress callback to run
+/// with.
+type Attached = (
+    Option<Arc<LogForwarder>>,
+    RunnerOptions,
+    Option<Arc<dyn ProgressCallback>>,
+);
+
+/// Per-run forwarder: sees progress events and observations, forwarding
+/// both to the caller's callback and tap.
+pub(crate) struct LogForwarder {
+    state: Mutex<ForwardState>,
+    worker: Mutex<Option<JoinHandle<()>>>,
+    progress: Option<Arc<dyn ProgressCallback>>,
+    tap: Option<Arc<dyn ObservationTap>>,
+}
+
+impl LogForwarder {
+    /// Start forwarding when `options.log_forward` is set, and route
+    /// `progress` and the observation tap of `options` through the
+    /// forwarder.
+    pub(crate) fn attach(
+        options: RunnerOptions,
+        progress: Option<Arc<dyn ProgressCallback>>,
+        run_id: RunId,
+        command: &str,
+    ) -> Attached {
+        let Some(config) = options.log_forward.clone() else {
+            return (None, options, progress);
+        };
+        let forwarder = Self::start(
+            config,
+            run_id,
+            command,
+            progress,
+            options.observation_tap.clone(),
+        );
+        let options = RunnerOptions {
+            observation_tap: Some(Arc::clone(&forwarder) as Arc<dyn ObservationTap>),
+            ..options
+        };
+        let progress = Some(Arc::clone(&forwarder) as Arc<dyn ProgressCallback>);
+        (Some(forwarder), options, progress)
+    }
+
+    fn start(
+        config: LogForwardConfig,
+        run_id: RunId,
+        command: &str,
+        progress: Option<Arc<dyn ProgressCallback>>,
+        tap: Option<Arc<dyn ObservationTap>>,
+    ) -> Arc<Self> {
+        let (sender, receiver) = sync_channel(QUEUE_RECORDS);
+        let worker = std::thread::Builder::new()
+            .name("ptybox-log-forward".to_string())
+            .spawn(move || send_records(&config, run_id, &receiver));
+        let mut state = ForwardState::default();
+        let worker = match worker {
+            Ok(worker) => {
+                state.sender = Some(sender);
+                Some(worker)
+            }
+            Err(err) => {
+                tracing::warn!(error = %err, "failed to start log forwarding");
+                None
+            }
+        };
+        state.push("run", SEVERITY_INFO, format!("run started: {command}"));
+        Arc::new(Self {
+            state: Mutex::new(state),
+            worker: Mutex::new(worker),
+            progress,
+            tap,
+        })
+    }
+
+    /// Send the held output and the run's outcome, then wait for the queue
+    /// to drain.
+    pub(crate) fn finish(&self, result: &RunnerResult<RunResult>) {
+        let (severity, text) = match result {
+            Ok(run) => {
+                let severity = match run.status {
+                    RunStatus::Passed => SEVERITY_INFO,
+                    RunStatus::Failed | RunStatus::Canceled => SEVERITY_WARNING,
+                    RunStatus::Errored => SEVERITY_ERR,
+                };
+                let mut text = format!("run {}: {}", label(&run.status), run.command);
+                if let Some(error) = &run.error {
+                    let _ = write!(text, " ({})", error.code.as_str());
+                }
+                (severity, text)
+            }
+            Err(err) => (
+                SEVERITY_ERR,
+                format!("run errored: {} {}", err.code.as_str(), err.message),
+            ),
+        };
+        let sender = match self.state.lock() {
+            Ok(mut state) => {
+                state.flush_output();
+                state.step = None;
+                let mut records = vec![state.record("run", severity, text)];
+                if state.dropped > 0 {
+                    records.push(state.record(
+                        "run",
+                        SEVERITY_WARNING,
+                        format!("{} log records dropped", state.dropped),
+                    ));
+                }
+                let sender = state.sender.take();
+                if let Some(sender) = &sender {
+                    for record in records {
+                        let _ = sender.send(record);
+                    }
+                }
+                sender
+            }
+            Err(_) => None,
+        };
+        drop(sender);
+        let worker = self.worker.lock().ok().and_then(|mut worker| worker.take());
+        if let Some(worker) = worker {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl ProgressCallback for LogForwarder {
+    fn on_progress(&self, event: &ProgressEvent) {
+        if let Ok(mut state) = self.state.lock() {
+            match event {
+                ProgressEvent::StepStarted {
+                    step_id,
+                    step_index,
+                    name,
+                } => {
+                    state.flush_output();
+                    state.step = Some((*step_id, name.clone()));
+                    state.push(
+                        "step",
+                        SEVERITY_INFO,
+                        format!("step {step_index} started: {name}"),
+                    );
+                }
+                ProgressEvent::StepCompleted {
+                    name,
+                    status,
+                    duration_ms,
+                    ..
+                } => {
+                    state.flush_output();
+                    let severity = match status {
+                        StepStatus::Passed | StepStatus::Skipped => SEVERITY_INFO,
+                        _ => SEVERITY_WARNING,
+                    };
+                    state.push(
+                        "step",
+                        severity,
+                        format!("step {} in {duration_ms}ms: {name}", label(status)),
+                    );
+                    state.step = None;
+                }
+                ProgressEvent::RunStarted { .. } | ProgressEvent::RunCompleted { .. } => {}
+            }
+        }
+        if let Some(progress) = &self.progress {
+            progress.on_progress(event);
+        }
+    }
+}
+
+impl ObservationTap for LogForwarder {
+    fn on_observation(&self, observation: &Observation) {
+        if let Ok(mut state) = self.state.lock() {
+            if let Some(delta) = &observation.transcript_delta {
+                state.append(Stream::Transcript, delta);
+            }
+            if let Some(delta) = &observation.stderr_delta {
+                state.append(Stream::Stderr, delta);
+            }
+            for event in &observation.events {
+                if event.event_type == "pty_output" {
+                    continue;
+                }
+                let text = match &event.message {
+                    Some(message) => format!("{}: {message}", event.event_type),
+                    None => event.event_type.clone(),
+                };
+                state.push("event", SEVERITY_INFO, text);
+            }
+        }
+        if let Some(tap) = &self.tap {
+            tap.on_observation(observation);
+        }
+    }
+}
+
+/// Open socket to a [`LogTarget`].
+enum Sink {
+    Unix(UnixDatagram, PathBuf),
+    Udp(UdpSocket, SocketAddr),
+}
+
+impl Sink {
+    /// Non-blocking, so a stalled collector drops records instead of
+    /// holding up the end of the run.
+    fn open(target: &LogTarget) -> std::io::Result<Self> {
+        match target {
+            LogTarget::Syslog(path) | LogTarget::Journald(path) => {
+                let socket = UnixDatagram::unbound()?;
+                socket.set_nonblocking(true)?;
+                Ok(Self::Unix(socket, path.clone()))
+            }
+            LogTarget::Udp(authority) => {
+                let addr = authority
+                    .to_socket_addrs()?
+                    .next()
+                    .ok_or_else(|| std::io::Error::other("UDP log endpoint did not resolve"))?;
+                let local = if addr.is_ipv4() {
+                    "0.0.0.0:0"
+                } else {
+                    "[::]:0"
+                };
+                let socket = UdpSocket::bind(local)?;
+                socket.set_nonblocking(true)?;
+                Ok(Self::Udp(socket, addr))
+            }
+        }
+    }
+
+    fn send(&self, datagram: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Self::Unix(socket, path) => socket.send_to(datagram, path),
+            Self::Udp(socket, addr) => socket.send_to(datagram, addr),
+        }
+    }
+}
+
+/// Worker loop: encode and send records until the forwarder finishes.
+fn send_records(config: &LogForwardConfig, run_id: RunId, receiver: &Receiver<Record>) {
+    let sink = match Sink::open(&config.target) {
+        Ok(sink) => sink,
+        Err(err) => {
+            tracing::warn!(error = %err, "failed to open log forwarding socket");
+            return;
+        }
+    };
+    let mut failed = 0_u64;
+    for record in receiver {
+        let datagram = encode(config, run_id, &record);
+        if let Err(err) = sink.send(&datagram) {
+            if failed == 0 {
+                tracing::warn!(error = %err, target = ?config.target, "failed to forward log record");
+            }
+            failed += 1;
+        }
+    }
+    if failed > 1 {
+        tracing::warn!(failed, "log records could not be forwarded");
+    }
+}
+
+fn encode(config: &LogForwardConfig, run_id: RunId, record: &Record) -> Vec<u8> {
+    let pri = FACILITY_USER * 8 + record.severity;
+    let ident = app_name(&config.ident);
+    let pid = std::process::id();
+    match config.target {
+        LogTarget::Syslog(_) => {
+            let mut line = format!("<{pri}>{ident}[{pid}]: [run_id={run_id}");
+            if let Some((step_id, step)) = &record.step {
+                let _ = write!(line, " step_id={step_id} step={step:?}");
+            }
+            let _ = write!(line, " kind={}] {}", record.kind, record.text);
+            line.into_bytes()
+        }
+        LogTarget::Udp(_) => {
+            let mut data = format!(
+                "run_id=\"{run_id}\" kind=\"{}\" ts_ms=\"{}\"",
+                record.kind, record.timestamp_ms
+            );
+            if let Some((step_id, step)) = &record.step {
+                let _ = write!(data, " step_id=\"{step_id}\" step=\"{}\"", sd_escape(step));
+            }
+            format!(
+                "<{pri}>1 - - {ident} {pid} {} [{SD_ID} {data}] {}",
+                record.kind, record.text
+            )
+            .into_bytes()
+        }
+        LogTarget::Journald(_) => {
+            let mut fields = vec![
+                ("MESSAGE", record.text.clone()),
+                ("PRIORITY", record.severity.to_string()),
+                ("SYSLOG_IDENTIFIER", config.ident.clone()),
+                ("PTYBOX_RUN_ID", run_id.to_string()),
+                ("PTYBOX_KIND", record.kind.to_string()),
+            ];
+            if let Some((step_id, step)) = &record.step {
+                fields.push(("PTYBOX_STEP_ID", step_id.to_string()));
+                fields.push(("PTYBOX_STEP", step.clone()));
+            }
+            journal_fields(&fields)
+        }
+    }
+}
+
+/// Native journal protocol: `KEY=value` lines, with values containing a
+/// newline sent as `KEY`, a little-endian 64-bit length, and the bytes.
+fn journal_fields(fields: &[(&str, String)]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for (key, value) in fields {
+        out.extend_from_slice(key.as_bytes());
+        if value.contains('\n') {
+            out.push(b'\n');
+            out.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        } else {
+            out.push(b'=');
+        }
+        out.extend_from_slice(value.as_bytes());
+        out.push(b'\n');
+    }
+    out
+}
+
+/// RFC 5424 `APP-NAME`: printable ASCII without spaces.
+fn app_name(ident: &str) -> String {
+    ident
+        .chars()
+        .map(|ch| if ch.is_ascii_graphic() { ch } else { '_' })
+        .collect()
+}
+
+/// Escape `"`, `\`, and `]` in an RFC 5424 parameter value.
+fn sd_escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for ch in value.chars() {
+        if matches!(ch, '"' | '\\' | ']') {
+            out.push('\\');
+        }
+        out.push(ch);
+    }
+    out
+}
+
+/// Cut `text` to [`MAX_RECORD_TEXT_BYTES`] on a character boundary.
+fn truncate(mut text: String) -> String {
+    if text.len() > MAX_RECORD_TEXT_BYTES {
+        let mut end = MAX_RECORD_TEXT_BYTES;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+    }
+    text
+}
+
+/// Serialized name of a `snake_case` enum value.
+fn label(value: &impl serde::Serialize) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| {
+            u64::try_from(since.as_millis()).unwrap_or(u64::MAX)
+        })
+}
@@ -1,4 +1,4 @@
-use crate::model::{Action, ErrorInfo, Observation};
+use crate::model::{Action, ErrorInfo, ExitStatus, Observation, Stability};
 use serde::{Deserialize, Serialize};
 
 /// Driver request envelope for protocol v2.
@@ -13,6 +13,19 @@ pub struct DriverRequestV2 {
     /// Optional per-action timeout in milliseconds.
     #[serde(default)]
     pub timeout_ms: Option<u64>,
+    /// Precondition: only run the action if the current screen has this
+    /// [`screen_hash`](crate::model::ScreenSnapshot::screen_hash).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_screen_hash: Option<String>,
+    /// For `wait` and `observe`: when the screen still has the hash of the
+    /// previous response, answer with `unchanged: true` and no observation
+    /// instead of recording a step.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub coalesce: bool,
+    /// Attach a PNG screenshot of the screen to a successful response
+    /// (requires the `render` feature).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub include_image: bool,
 }
 
 /// Driver response status.
@@ -49,6 +62,14 @@ pub struct BudgetStatus {
     pub output_bytes_used: u64,
     /// Maximum allowed output bytes.
     pub output_bytes_max: u64,
+    /// Runtime spent polling wait conditions in milliseconds (part of `runtime_ms`).
+    #[serde(default)]
+    pub wait_ms_used: u64,
+}
+
+impl DriverRequestV2 {
+    /// Stability of the request envelope, as reported by `protocol-help`.
+    pub const STABILITY: Stability = Stability::Stable;
 }
 
 /// Driver response envelope for protocol v2.
@@ -72,6 +93,178 @@ pub struct DriverResponseV2 {
     /// Current budget consumption for proactive monitoring.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub budget_status: Option<BudgetStatus>,
+    /// Hash of `observation.screen`, for use as the next request's
+    /// `expected_screen_hash`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub screen_hash: Option<String>,
+    /// The screen matched the previous response's `screen_hash`, so the
+    /// observation was omitted and no step or snapshot was recorded (only for
+    /// requests with `coalesce`).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub unchanged: bool,
+    /// Session log entries (only for `history` queries).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history: Option<DriverHistory>,
+    /// Screenshot of the observed screen (only for requests with
+    /// `include_image`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<DriverImage>,
+    /// Event subscription now in effect (only for `subscribe` requests).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscription: Option<DriverSubscription>,
+}
+
+impl DriverResponseV2 {
+    /// Stability of the response envelope, as reported by `protocol-help`.
+    pub const STABILITY: Stability = Stability::Stable;
+}
+
+/// Largest inline screenshot, in encoded bytes, a response carries.
+pub const MAX_DRIVER_IMAGE_BYTES: u64 = 1024 * 1024;
+
+/// Screenshot attached to a driver response for `include_image` requests.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DriverImage {
+    /// MIME type of the encoded image (`image/png`).
+    pub mime_type: String,
+    /// Image width in pixels.
+    pub width: u32,
+    /// Image height in pixels.
+    pub height: u32,
+    /// Size of the encoded image in bytes (before base64).
+    pub bytes: u64,
+    /// Base64-encoded image; absent when `omitted` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_base64: Option<String>,
+    /// Why the image data was left out (it exceeded
+    /// [`MAX_DRIVER_IMAGE_BYTES`] or could not be rendered).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub omitted: Option<String>,
+}
+
+/// Answer to a `history` query: the most recent requests of the session.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DriverHistory {
+    /// Entries, oldest first.
+    pub entries: Vec<DriverHistoryEntry>,
+    /// Entries logged since the session started, including ones no longer
+    /// kept in memory.
+    pub total: u64,
+}
+
+/// Summary of one answered request in the driver's in-memory session log.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DriverHistoryEntry {
+    /// Request identifier supplied by client.
+    pub request_id: String,
+    /// Action as requested.
+    pub action: Action,
+    /// Response status.
+    pub status: DriverResponseStatus,
+    /// Step sequence number, for actions recorded as a step.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<u64>,
+    /// Error code, for error responses.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
+    /// Request start timestamp (ms since run start).
+    pub started_at_ms: u64,
+    /// Request end timestamp (ms since run start).
+    pub ended_at_ms: u64,
+    /// `screen_hash` sent in the response.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub screen_hash: Option<String>,
+    /// `timestamp_ms` of the observation behind `screen_hash`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub observation_timestamp_ms: Option<u64>,
+    /// The response was a coalesced `unchanged` answer.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub unchanged: bool,
+}
+
+/// Default [`DriverSubscription::max_unacked`].
+pub const DEFAULT_DRIVER_EVENT_WINDOW: u64 = 64;
+
+/// Largest accepted [`DriverSubscription::max_unacked`].
+pub const MAX_DRIVER_EVENT_WINDOW: u64 = 1024;
+
+/// Default [`DriverSubscription::poll_interval_ms`].
+pub const DEFAULT_DRIVER_EVENT_POLL_MS: u64 = 50;
+
+/// Smallest accepted [`DriverSubscription::poll_interval_ms`].
+pub const MIN_DRIVER_EVENT_POLL_MS: u64 = 10;
+
+/// Kind of an unsolicited [`DriverEvent`].
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum DriverEventKind {
+    /// The screen hash differs from the last one sent to the client.
+    ScreenChanged,
+    /// The terminal rang its bell.
+    Bell,
+    /// The child process exited.
+    ProcessExited,
+    /// Events were dropped while the acknowledgement window was full
+    /// (always delivered; not subscribable).
+    Overflow,
+}
+
+impl DriverEventKind {
+    /// Kinds a client can subscribe to, in documentation order.
+    pub const SUBSCRIBABLE: [Self; 3] = [Self::ScreenChanged, Self::Bell, Self::ProcessExited];
+}
+
+/// Event subscription in effect, echoed in the response to `subscribe`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DriverSubscription {
+    /// Subscribed kinds; empty once unsubscribed.
+    pub events: Vec<DriverEventKind>,
+    /// Events the driver sends before it waits for an acknowledgement.
+    pub max_unacked: u64,
+    /// How often the idle driver polls the session for events.
+    pub poll_interval_ms: u64,
+    /// `event_seq` of the last event sent (0 before the first).
+    pub last_event_seq: u64,
+}
+
+/// Unsolicited frame pushed by a subscribed driver between responses.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename = "event")]
+pub struct DriverEvent {
+    /// Protocol version for request/response compatibility.
+    pub protocol_version: u32,
+    /// Monotonic event sequence number (1-based) for acknowledgements.
+    pub event_seq: u64,
+    /// What happened.
+    pub kind: DriverEventKind,
+    /// When it was seen (ms since run start).
+    pub timestamp_ms: u64,
+    /// Current screen hash (`screen_changed` and `overflow`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub screen_hash: Option<String>,
+    /// Bells rung since the previous `bell` event or request (`bell`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bells: Option<u64>,
+    /// How the child exited (`process_exited`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_status: Option<ExitStatus>,
+    /// Events dropped while the window was full (`overflow`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dropped: Option<u64>,
+}
+
+impl DriverEvent {
+    /// Stability of the event frame, as reported by `protocol-help`.
+    pub const STABILITY: Stability = Stability::Experimental;
+}
+
+/// Client frame acknowledging every event up to `event_seq`; it gets no
+/// response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename = "ack")]
+pub struct DriverEventAck {
+    /// Highest `event_seq` the client has processed.
+    pub event_seq: u64,
 }
 
 /// Artifact record for driver actions.
@@ -1,7 +1,7 @@
 //! Typed UUID identifiers for run, session, step, and snapshot entities.
 //!
 //! Each ID type is a newtype around [`Uuid`](uuid::Uuid) with [`Display`](std::fmt::Display),
-//! `Serialize`/`Deserialize` (transparent), and `new()` constructor.
+//! `Serialize`/`Deserialize` (transparent), and `new()`/`from_u128()` constructors.
 
 use serde::{Deserialize, Serialize};
 use std::fmt;
@@ -20,6 +20,12 @@ macro_rules! define_id {
             pub fn new() -> Self {
                 Self(Uuid::new_v4())
             }
+
+            /// Create an ID with a fixed value (canonical examples, fixtures).
+            #[must_use]
+            pub const fn from_u128(value: u128) -> Self {
+                Self(Uuid::from_u128(value))
+            }
         }
 
         impl Default for $name {
@@ -13,6 +13,7 @@
 //! - [`ids`] — Typed UUID identifiers (`RunId`, `SessionId`, `StepId`, `SnapshotId`)
 //! - [`driver`] — Driver protocol v2 types (`DriverRequestV2`, `DriverResponseV2`)
 //! - [`normalization`] — Normalization filter and rule types for replay
+//! - [`stability`] — Stability annotations reported by `protocol-help`
 
 /// Driver protocol v2 request/response types.
 pub mod driver;
@@ -26,6 +27,8 @@ pub mod policy;
 pub mod run;
 /// Scenario, step, action, and assertion definition types.
 pub mod scenario;
+/// Stability annotations for actions, predicates, and message types.
+pub mod stability;
 /// Terminal display types: snapshots, cursors, cells, and styles.
 pub mod terminal;
 
@@ -35,6 +38,7 @@ pub use normalization::*;
 pub use policy::*;
 pub use run::*;
 pub use scenario::*;
+pub use stability::Stability;
 pub use terminal::*;
 
 /// Maximum length for user-supplied regex patterns to prevent `ReDoS` attacks.
@@ -22,7 +22,8 @@ pub enum NormalizationFilter {
     ObservationTimestamp,
     /// Ignore session IDs.
     SessionId,
-    /// Ignore observation `events` arrays.
+    /// Ignore observation `events` arrays and per-read counters
+    /// (`read_truncated`, `read_bytes`, `read_dropped_bytes`).
     Events,
 }
 
@@ -34,6 +35,25 @@ pub enum NormalizationRuleTarget {
     Transcript,
     /// Apply to screen snapshot lines.
     SnapshotLines,
+    /// Blank or replace a rectangular region of screen snapshots.
+    ///
+    /// Requires [`NormalizationRule::region`]. The region text is replaced
+    /// with `replace` (padded or truncated to the region width) on every row
+    /// where it matches `pattern`; an empty pattern always matches.
+    SnapshotRegion,
+}
+
+/// Rectangular screen region used by `snapshot_region` rules (0-based).
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ScreenRegion {
+    /// First row of the region.
+    pub row: u16,
+    /// First column of the region.
+    pub col: u16,
+    /// Number of rows covered.
+    pub rows: u16,
+    /// Number of columns covered.
+    pub cols: u16,
 }
 
 /// Regex-based normalization rule for variable output.
@@ -50,6 +70,9 @@ pub struct NormalizationRule {
     /// Whether this rule relates to harness termination.
     #[serde(default)]
     pub terminated_by_harness: bool,
+    /// Rectangle for `snapshot_region` rules; ignored by other targets.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<ScreenRegion>,
 }
 
 /// Source of normalization configuration.
@@ -17,6 +17,7 @@ pub const POLICY_VERSION: u32 = 4;
 ///
 /// - `Seatbelt`: Default on macOS, uses `sandbox-exec` for process isolation
 /// - `Disabled { ack }`: No sandboxing, requires `ack: true` to proceed
+/// - `Container { runtime, image }`: Runs the child in a rootless container
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub enum SandboxMode {
     /// macOS Seatbelt sandbox (default).
@@ -29,6 +30,73 @@ pub enum SandboxMode {
         /// cannot be enforced by the OS.
         ack: bool,
     },
+    /// Run the child inside a container, with the fs, network, and env
+    /// policy translated into mounts and runtime flags.
+    Container {
+        /// Container runtime.
+        runtime: ContainerRuntime,
+        /// Image the command runs in.
+        image: String,
+        /// Absolute path of the runtime executable; must be listed in
+        /// `exec.allowed_executables`.
+        program: String,
+        /// Maximum number of processes in the container (`--pids-limit`).
+        pids_limit: u32,
+        /// Memory limit of the container in bytes (`--memory`).
+        memory_bytes: u64,
+    },
+}
+
+/// Default `--pids-limit` of a container sandbox.
+pub const DEFAULT_CONTAINER_PIDS_LIMIT: u32 = 256;
+
+/// Default `--memory` of a container sandbox, in bytes.
+pub const DEFAULT_CONTAINER_MEMORY_BYTES: u64 = 1 << 30;
+
+/// Container runtime for [`SandboxMode::Container`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContainerRuntime {
+    /// `podman` (default).
+    #[default]
+    Podman,
+    /// `docker`.
+    Docker,
+}
+
+impl ContainerRuntime {
+    /// Executable name of the runtime.
+    #[must_use]
+    pub const fn program(self) -> &'static str {
+        match self {
+            Self::Podman => "podman",
+            Self::Docker => "docker",
+        }
+    }
+
+    /// Default absolute path of the runtime executable (`/usr/bin/<name>`).
+    #[must_use]
+    pub fn default_path(self) -> String {
+        format!("/usr/bin/{}", self.program())
+    }
+}
+
+/// Behavior when the Seatbelt sandbox is unavailable on this host.
+///
+/// - `Deny`: Fail with `E_SANDBOX_UNAVAILABLE` (default)
+/// - `Warn { ack }`: Run without the sandbox and record an enforcement
+///   report, requires `ack: true` to proceed
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum SandboxFallback {
+    /// Refuse to run without the sandbox (default).
+    #[default]
+    Deny,
+    /// Degrade to best-effort restrictions (requires acknowledgement).
+    Warn {
+        /// Explicit acknowledgement that filesystem, network, and exec
+        /// restrictions may go unenforced when Seatbelt is unavailable.
+        ack: bool,
+    },
 }
 
 /// Network access policy with embedded acknowledgement.
@@ -77,6 +145,9 @@ pub struct FsPolicy {
     pub write_ack: bool,
     /// Require write acknowledgement for any write access including artifacts.
     pub strict_write: bool,
+    /// Largest growth in bytes allowed per write path, keyed by absolute
+    /// path inside `allowed_write`. Enforced best-effort by sampling.
+    pub write_quotas: BTreeMap<String, u64>,
 }
 
 /// Deny-by-default security policy for TUI execution (v4).
@@ -96,6 +167,8 @@ pub struct Policy {
     pub policy_version: u32,
     /// Sandbox isolation mode (with embedded ack for disabled state).
     pub sandbox: SandboxMode,
+    /// Fallback when Seatbelt is unavailable (with embedded ack for warn).
+    pub sandbox_fallback: SandboxFallback,
     /// Network access policy (with embedded ack for enabled state).
     pub network: NetworkPolicy,
     /// Acknowledgement for unenforced network policy (when sandbox disabled).
@@ -112,6 +185,25 @@ pub struct Policy {
     pub artifacts: ArtifactsPolicy,
     /// Replay comparison policy.
     pub replay: ReplayPolicy,
+    /// Input capabilities beyond keys and text.
+    pub input: InputPolicy,
+    /// Locale and timezone pinned for the child.
+    pub determinism: DeterminismPolicy,
+    /// How ptybox stops the child (signal, grace period, pre-kill hook).
+    pub process: ProcessPolicy,
+    /// Unix sockets created for the child at spawn and served by the embedder.
+    pub sockets: Vec<SocketGrant>,
+    /// SSH hosts and keys remote sessions may use.
+    pub remote: RemotePolicy,
+    /// How terminal output is decoded.
+    pub terminal: TerminalPolicy,
+    /// Collectors the `log-forward` feature may send a run's logs to.
+    pub log_forward: LogForwardPolicy,
+    /// Placeholders (`cwd`, `env:NAME`) that may be substituted into path
+    /// fields when this policy is loaded from a file.
+    pub substitution_allowlist: Vec<String>,
+    /// Exceptions for scenarios whose name matches a pattern.
+    pub grants: Vec<PolicyGrant>,
 }
 
 impl Default for Policy {
@@ -119,6 +211,7 @@ impl Default for Policy {
         Self {
             policy_version: POLICY_VERSION,
             sandbox: SandboxMode::Seatbelt,
+            sandbox_fallback: SandboxFallback::Deny,
             network: NetworkPolicy::Disabled,
             network_enforcement: NetworkEnforcementAck::default(),
             fs: FsPolicy::default(),
@@ -127,6 +220,15 @@ impl Default for Policy {
             budgets: Budgets::default(),
             artifacts: ArtifactsPolicy::default(),
             replay: ReplayPolicy::default(),
+            input: InputPolicy::default(),
+            determinism: DeterminismPolicy::default(),
+            process: ProcessPolicy::default(),
+            sockets: Vec::new(),
+            remote: RemotePolicy::default(),
+            terminal: TerminalPolicy::default(),
+            log_forward: LogForwardPolicy::default(),
+            substitution_allowlist: Vec::new(),
+            grants: Vec::new(),
         }
     }
 }
@@ -145,6 +247,12 @@ struct LegacyPolicy {
     sandbox: LegacySandboxMode,
     #[serde(default)]
     sandbox_unsafe_ack: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    container: Option<LegacyContainer>,
+    #[serde(default, skip_serializing_if = "LegacySandboxFallback::is_deny")]
+    sandbox_fallback: LegacySandboxFallback,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    sandbox_fallback_ack: bool,
     network: LegacyNetworkPolicy,
     #[serde(default)]
     network_unsafe_ack: bool,
@@ -159,6 +267,24 @@ struct LegacyPolicy {
     artifacts: ArtifactsPolicy,
     #[serde(default)]
     replay: ReplayPolicy,
+    #[serde(default)]
+    input: InputPolicy,
+    #[serde(default, skip_serializing_if = "DeterminismPolicy::is_unset")]
+    determinism: DeterminismPolicy,
+    #[serde(default, skip_serializing_if = "ProcessPolicy::is_default")]
+    process: ProcessPolicy,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    sockets: Vec<SocketGrant>,
+    #[serde(default, skip_serializing_if = "RemotePolicy::is_default")]
+    remote: RemotePolicy,
+    #[serde(default, skip_serializing_if = "TerminalPolicy::is_default")]
+    terminal: TerminalPolicy,
+    #[serde(default, skip_serializing_if = "LogForwardPolicy::is_default")]
+    log_forward: LogForwardPolicy,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    substitution_allowlist: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    grants: Vec<PolicyGrant>,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -166,6 +292,44 @@ struct LegacyPolicy {
 enum LegacySandboxMode {
     Seatbelt,
     None,
+    Container,
+}
+
+/// `container` section of a policy with `sandbox: container`.
+#[derive(Deserialize, Serialize, Default)]
+struct LegacyContainer {
+    #[serde(default)]
+    runtime: ContainerRuntime,
+    #[serde(default)]
+    image: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    program: Option<String>,
+    #[serde(default = "default_container_pids_limit")]
+    pids_limit: u32,
+    #[serde(default = "default_container_memory_bytes")]
+    memory_bytes: u64,
+}
+
+fn default_container_pids_limit() -> u32 {
+    DEFAULT_CONTAINER_PIDS_LIMIT
+}
+
+fn default_container_memory_bytes() -> u64 {
+    DEFAULT_CONTAINER_MEMORY_BYTES
+}
+
+#[derive(Deserialize, Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum LegacySandboxFallback {
+    #[default]
+    Deny,
+    Warn,
+}
+
+impl LegacySandboxFallback {
+    fn is_deny(&self) -> bool {
+        *self == Self::Deny
+    }
 }
 
 #[derive(Deserialize, Serialize)]
@@ -183,6 +347,8 @@ struct LegacyFsPolicy {
     allowed_write: Vec<String>,
     #[serde(default)]
     working_dir: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    write_quotas: BTreeMap<String, u64>,
 }
 
 impl From<LegacyPolicy> for Policy {
@@ -192,6 +358,29 @@ impl From<LegacyPolicy> for Policy {
             LegacySandboxMode::None => SandboxMode::Disabled {
                 ack: legacy.sandbox_unsafe_ack,
             },
+            LegacySandboxMode::Container => {
+                let container = legacy.container.unwrap_or_else(|| LegacyContainer {
+                    pids_limit: DEFAULT_CONTAINER_PIDS_LIMIT,
+                    memory_bytes: DEFAULT_CONTAINER_MEMORY_BYTES,
+                    ..LegacyContainer::default()
+                });
+                SandboxMode::Container {
+                    runtime: container.runtime,
+                    image: container.image,
+                    program: container
+                        .program
+                        .unwrap_or_else(|| container.runtime.default_path()),
+                    pids_limit: container.pids_limit,
+                    memory_bytes: container.memory_bytes,
+                }
+            }
+        };
+
+        let sandbox_fallback = match legacy.sandbox_fallback {
+            LegacySandboxFallback::Deny => SandboxFallback::Deny,
+            LegacySandboxFallback::Warn => SandboxFallback::Warn {
+                ack: legacy.sandbox_fallback_ack,
+            },
         };
 
         let network = match legacy.network {
@@ -214,11 +403,13 @@ impl From<LegacyPolicy> for Policy {
             working_dir: legacy.fs.working_dir,
             write_ack: legacy.fs_write_unsafe_ack,
             strict_write: legacy.fs_strict_write,
+            write_quotas: legacy.fs.write_quotas,
         };
 
         Policy {
             policy_version: legacy.policy_version,
             sandbox,
+            sandbox_fallback,
             network,
             network_enforcement,
             fs,
@@ -227,15 +418,46 @@ impl From<LegacyPolicy> for Policy {
             budgets: legacy.budgets,
             artifacts: legacy.artifacts,
             replay: legacy.replay,
+            input: legacy.input,
+            determinism: legacy.determinism,
+            process: legacy.process,
+            sockets: legacy.sockets,
+            remote: legacy.remote,
+            terminal: legacy.terminal,
+            log_forward: legacy.log_forward,
+            substitution_allowlist: legacy.substitution_allowlist,
+            grants: legacy.grants,
         }
     }
 }
 
 impl From<Policy> for LegacyPolicy {
     fn from(policy: Policy) -> Self {
-        let (sandbox, sandbox_unsafe_ack) = match policy.sandbox {
-            SandboxMode::Seatbelt => (LegacySandboxMode::Seatbelt, false),
-            SandboxMode::Disabled { ack } => (LegacySandboxMode::None, ack),
+        let (sandbox, sandbox_unsafe_ack, container) = match policy.sandbox {
+            SandboxMode::Seatbelt => (LegacySandboxMode::Seatbelt, false, None),
+            SandboxMode::Disabled { ack } => (LegacySandboxMode::None, ack, None),
+            SandboxMode::Container {
+                runtime,
+                image,
+                program,
+                pids_limit,
+                memory_bytes,
+            } => (
+                LegacySandboxMode::Container,
+                false,
+                Some(LegacyContainer {
+                    runtime,
+                    image,
+                    program: Some(program),
+                    pids_limit,
+                    memory_bytes,
+                }),
+            ),
+        };
+
+        let (sandbox_fallback, sandbox_fallback_ack) = match policy.sandbox_fallback {
+            SandboxFallback::Deny => (LegacySandboxFallback::Deny, false),
+            SandboxFallback::Warn { ack } => (LegacySandboxFallback::Warn, ack),
         };
 
         let (network, network_enabled_ack) = match policy.network {
@@ -250,12 +472,16 @@ impl From<Policy> for LegacyPolicy {
             policy_version: policy.policy_version,
             sandbox,
             sandbox_unsafe_ack,
+            container,
+            sandbox_fallback,
+            sandbox_fallback_ack,
             network,
             network_unsafe_ack,
             fs: LegacyFsPolicy {
                 allowed_read: policy.fs.allowed_read,
                 allowed_write: policy.fs.allowed_write,
                 working_dir: policy.fs.working_dir,
+                write_quotas: policy.fs.write_quotas,
             },
             fs_write_unsafe_ack: policy.fs.write_ack,
             fs_strict_write: policy.fs.strict_write,
@@ -264,6 +490,15 @@ impl From<Policy> for LegacyPolicy {
             budgets: policy.budgets,
             artifacts: policy.artifacts,
             replay: policy.replay,
+            input: policy.input,
+            determinism: policy.determinism,
+            process: policy.process,
+            sockets: policy.sockets,
+            remote: policy.remote,
+            terminal: policy.terminal,
+            log_forward: policy.log_forward,
+            substitution_allowlist: policy.substitution_allowlist,
+            grants: policy.grants,
         }
     }
 }
@@ -303,14 +538,26 @@ impl SandboxMode {
         matches!(self, Self::Disabled { ack: true })
     }
 
-    /// Get the acknowledgement status (false for Seatbelt mode).
+    /// Get the acknowledgement status (false for Seatbelt and container
+    /// modes).
     #[must_use]
     pub fn ack(&self) -> bool {
         match self {
-            Self::Seatbelt => false,
+            Self::Seatbelt | Self::Container { .. } => false,
             Self::Disabled { ack } => *ack,
         }
     }
+
+    /// Name used in policy JSON and reports (`seatbelt`, `none`,
+    /// `container`).
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Seatbelt => "seatbelt",
+            Self::Disabled { .. } => "none",
+            Self::Container { .. } => "container",
+        }
+    }
 }
 
 impl NetworkPolicy {
@@ -430,6 +677,42 @@ pub struct Budgets {
     pub max_snapshot_bytes: u64,
     /// Maximum wait time per wait action in milliseconds.
     pub max_wait_ms: u64,
+    /// Maximum time a driver session may wait for the next request before the
+    /// child is terminated (unlimited when unset).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_session_idle_ms: Option<u64>,
+    /// Maximum wall-clock lifetime of a driver session, enforced even while
+    /// waiting for input (unlimited when unset).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_session_lifetime_ms: Option<u64>,
+    /// Cap on the adaptive poll interval used while waiting for a condition
+    /// (default [`DEFAULT_MAX_POLL_INTERVAL_MS`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_poll_interval_ms: Option<u64>,
+    /// Maximum intermediate screens kept per step by `sample_interval_ms`
+    /// (default [`DEFAULT_MAX_STEP_SAMPLES`]); further samples are dropped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_step_samples: Option<u64>,
+}
+
+/// Default cap on the wait poll interval in milliseconds.
+pub const DEFAULT_MAX_POLL_INTERVAL_MS: u64 = 50;
+
+/// Default cap on intermediate screen samples per step.
+pub const DEFAULT_MAX_STEP_SAMPLES: u64 = 100;
+
+impl Budgets {
+    /// Effective poll interval cap, never below 1ms.
+    pub fn poll_interval_cap_ms(&self) -> u64 {
+        self.max_poll_interval_ms
+            .unwrap_or(DEFAULT_MAX_POLL_INTERVAL_MS)
+            .max(1)
+    }
+
+    /// Effective per-step sample cap.
+    pub fn step_samples_cap(&self) -> u64 {
+        self.max_step_samples.unwrap_or(DEFAULT_MAX_STEP_SAMPLES)
+    }
 }
 
 impl Default for Budgets {
@@ -440,6 +723,10 @@ impl Default for Budgets {
             max_output_bytes: 8 * 1024 * 1024,
             max_snapshot_bytes: 2 * 1024 * 1024,
             max_wait_ms: 10_000,
+            max_session_idle_ms: None,
+            max_session_lifetime_ms: None,
+            max_poll_interval_ms: None,
+            max_step_samples: None,
         }
     }
 }
@@ -447,7 +734,7 @@ impl Default for Budgets {
 /// Artifact collection configuration.
 ///
 /// When enabled, snapshots, transcripts, and run results are written to disk.
-#[derive(Clone, Debug, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ArtifactsPolicy {
     /// Enable artifact collection.
     #[serde(default)]
@@ -458,6 +745,138 @@ pub struct ArtifactsPolicy {
     /// Overwrite existing artifacts directory.
     #[serde(default)]
     pub overwrite: bool,
+    /// On-disk layout for snapshots and observations.
+    #[serde(default)]
+    pub layout: ArtifactsLayout,
+    /// Encrypt transcripts, snapshots, and event logs at rest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<ArtifactsEncryption>,
+    /// Retention of terminal output in `transcript.log`.
+    #[serde(default, skip_serializing_if = "TranscriptPolicy::is_default")]
+    pub transcript: TranscriptPolicy,
+    /// Write JSON artifacts in canonical form: sorted keys, two-space
+    /// indentation, and a trailing newline (default `true`).
+    #[serde(
+        default = "default_canonical_json",
+        skip_serializing_if = "is_default_canonical_json"
+    )]
+    pub canonical_json: bool,
+    /// After the process exits, list the files under `fs.allowed_write`
+    /// with their sizes and hashes in `fs-manifest.json`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub fs_manifest: bool,
+    /// Write the output as an asciinema v2 recording, `session.cast`, while
+    /// the run is observed. Not written for encrypted artifacts.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub asciicast: bool,
+}
+
+impl Default for ArtifactsPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dir: None,
+            overwrite: false,
+            layout: ArtifactsLayout::default(),
+            encryption: None,
+            transcript: TranscriptPolicy::default(),
+            canonical_json: true,
+            fs_manifest: false,
+            asciicast: false,
+        }
+    }
+}
+
+fn default_canonical_json() -> bool {
+    true
+}
+
+// serde's skip_serializing_if passes a reference
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_default_canonical_json(canonical_json: &bool) -> bool {
+    *canonical_json
+}
+
+/// Default kibibytes kept from each end of a truncated transcript.
+pub const DEFAULT_TRANSCRIPT_KEEP_KIB: u64 = 64;
+
+/// Retention of terminal output in `transcript.log`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TranscriptPolicy {
+    /// How much output to keep.
+    #[serde(default)]
+    pub truncate: TranscriptTruncate,
+    /// Kibibytes kept from the start of the output (`head_tail` only).
+    #[serde(default = "default_transcript_keep_kib")]
+    pub head_kib: u64,
+    /// Kibibytes kept from the end of the output (`head_tail` only).
+    #[serde(default = "default_transcript_keep_kib")]
+    pub tail_kib: u64,
+}
+
+impl Default for TranscriptPolicy {
+    fn default() -> Self {
+        Self {
+            truncate: TranscriptTruncate::None,
+            head_kib: DEFAULT_TRANSCRIPT_KEEP_KIB,
+            tail_kib: DEFAULT_TRANSCRIPT_KEEP_KIB,
+        }
+    }
+}
+
+impl TranscriptPolicy {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+fn default_transcript_keep_kib() -> u64 {
+    DEFAULT_TRANSCRIPT_KEEP_KIB
+}
+
+/// Transcript truncation mode.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptTruncate {
+    /// Keep all output (default).
+    #[default]
+    None,
+    /// Keep the first `head_kib` and last `tail_kib` of output, replacing
+    /// the middle with an elision marker.
+    HeadTail,
+}
+
+/// Default path of the `age` binary used for artifact encryption.
+pub const DEFAULT_AGE_BINARY: &str = "/usr/bin/age";
+
+/// Age (X25519) encryption of sensitive artifacts.
+///
+/// `transcript.log`, snapshots, and `events.jsonl` are written as `*.age`
+/// files readable only with an identity for one of `recipients`. Metadata
+/// (`run.json`, `policy.json`, `scenario.json`, `checksums.json`) stays
+/// plaintext; `run.json` omits `final_observation`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ArtifactsEncryption {
+    /// Age X25519 recipients (`age1...`); any matching identity decrypts.
+    pub recipients: Vec<String>,
+    /// Absolute path to the `age` binary used to encrypt and decrypt.
+    #[serde(default = "default_age_binary")]
+    pub age_binary: String,
+}
+
+fn default_age_binary() -> String {
+    DEFAULT_AGE_BINARY.to_string()
+}
+
+/// On-disk layout for per-step artifacts.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactsLayout {
+    /// All snapshots in `snapshots/` and all observations in `events.jsonl`.
+    #[default]
+    Flat,
+    /// Snapshots and observations under `steps/<index>-<name>/`.
+    PerStep,
 }
 
 /// Replay comparison policy.
@@ -476,6 +895,613 @@ pub struct ReplayPolicy {
     pub normalization_rules: Option<Vec<crate::model::NormalizationRule>>,
 }
 
+/// Default cap on the decoded size of a single `raw` action.
+pub const DEFAULT_MAX_RAW_BYTES: u64 = 4096;
+
+/// Input capability policy.
+///
+/// Raw byte input (the `raw` action) bypasses key translation and is denied
+/// unless `allow_raw_bytes` is set.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct InputPolicy {
+    /// Allow `raw` actions that write decoded bytes verbatim to the PTY.
+    #[serde(default)]
+    pub allow_raw_bytes: bool,
+    /// Maximum decoded bytes per `raw` action.
+    #[serde(default = "default_max_raw_bytes")]
+    pub max_raw_bytes: u64,
+    /// Keyboard layout applied to `key` and `hold_key` actions: the name of
+    /// a built-in keymap (`us`, `de`, `fr`) or the path of a [`Keymap`]
+    /// file (JSON or YAML).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keymap: Option<String>,
+}
+
+impl Default for InputPolicy {
+    fn default() -> Self {
+        Self {
+            allow_raw_bytes: false,
+            max_raw_bytes: DEFAULT_MAX_RAW_BYTES,
+            keymap: None,
+        }
+    }
+}
+
+fn default_max_raw_bytes() -> u64 {
+    DEFAULT_MAX_RAW_BYTES
+}
+
+/// Current version of the keymap format.
+pub const KEYMAP_VERSION: u32 = 1;
+
+/// Translation of logical keys to the bytes a keyboard layout produces.
+///
+/// Logical keys are key names as written in `key` actions, taken as
+/// positions on a US QWERTY keyboard. A key listed in `keys` sends its
+/// value (as UTF-8) instead of the default translation; other keys are
+/// translated as usual. Recorded in `run.json` so replay uses the same
+/// translation.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Keymap {
+    /// Keymap format version ([`KEYMAP_VERSION`]).
+    pub keymap_version: u32,
+    /// Layout name (the built-in name, or the file's own name).
+    pub name: String,
+    /// Text sent for each translated logical key.
+    #[serde(default)]
+    pub keys: BTreeMap<String, String>,
+}
+
+/// Locale and timezone pinned for the child process, and replay pacing.
+///
+/// Rendering of dates, numbers, and sorted lists depends on `LANG`/`TZ`, so
+/// pinning them keeps snapshots replayable across machines. Pinned variables
+/// are set for the child even when they are not in `env.allowlist`, and
+/// take precedence over `env.set`.
+#[derive(Clone, Debug, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct DeterminismPolicy {
+    /// Locale exported as `LANG` and `LC_ALL` (e.g. `C.UTF-8`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// Timezone exported as `TZ` (e.g. `UTC`, `Asia/Tokyo`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    /// Experimental: when replaying, start each step no earlier than it
+    /// started in the baseline run, so the app sees the recorded pacing
+    /// instead of back-to-back input. Has no effect outside replay.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub paced_input: bool,
+}
+
+impl DeterminismPolicy {
+    /// Whether nothing is pinned and paced input is off.
+    #[must_use]
+    pub fn is_unset(&self) -> bool {
+        self.locale.is_none() && self.timezone.is_none() && !self.paced_input
+    }
+
+    /// The locale and timezone this policy pins, without `paced_input`, or
+    /// `None` when neither is pinned. Recorded as `run.json` `determinism`.
+    #[must_use]
+    pub fn pinned(&self) -> Option<Self> {
+        (self.locale.is_some() || self.timezone.is_some()).then(|| Self {
+            locale: self.locale.clone(),
+            timezone: self.timezone.clone(),
+            paced_input: false,
+        })
+    }
+
+    /// Environment variables pinned by this policy, in a stable order.
+    #[must_use]
+    pub fn env_vars(&self) -> Vec<(&'static str, &str)> {
+        let mut vars = Vec::new();
+        if let Some(locale) = &self.locale {
+            vars.push(("LANG", locale.as_str()));
+            vars.push(("LC_ALL", locale.as_str()));
+        }
+        if let Some(timezone) = &self.timezone {
+            vars.push(("TZ", timezone.as_str()));
+        }
+        vars
+    }
+
+    /// Cartesian product of `locales` and `timezones` as matrix cells.
+    ///
+    /// An empty axis leaves that field unpinned in every cell, so two empty
+    /// axes yield no cells.
+    #[must_use]
+    pub fn matrix(locales: &[String], timezones: &[String]) -> Vec<Self> {
+        if locales.is_empty() && timezones.is_empty() {
+            return Vec::new();
+        }
+        let locales: Vec<Option<&String>> = if locales.is_empty() {
+            vec![None]
+        } else {
+            locales.iter().map(Some).collect()
+        };
+        let timezones: Vec<Option<&String>> = if timezones.is_empty() {
+            vec![None]
+        } else {
+            timezones.iter().map(Some).collect()
+        };
+        locales
+            .iter()
+            .flat_map(|locale| {
+                timezones.iter().map(move |timezone| Self {
+                    locale: locale.cloned(),
+                    timezone: timezone.cloned(),
+                    paced_input: false,
+                })
+            })
+            .collect()
+    }
+
+    /// Directory-safe `<locale>+<timezone>` name for a matrix cell, with
+    /// `default` for an unpinned field.
+    #[must_use]
+    pub fn cell_label(&self) -> String {
+        let locale = self.locale.as_deref().unwrap_or("default");
+        let timezone = self.timezone.as_deref().unwrap_or("default");
+        format!("{locale}+{timezone}")
+            .chars()
+            .map(|ch| {
+                if ch.is_ascii_alphanumeric() || "._-+".contains(ch) {
+                    ch
+                } else {
+                    '-'
+                }
+            })
+            .collect()
+    }
+
+    /// Override the pinned fields with those set in `cell`.
+    pub fn apply(&mut self, cell: &Self) {
+        if cell.locale.is_some() {
+            self.locale.clone_from(&cell.locale);
+        }
+        if cell.timezone.is_some() {
+            self.timezone.clone_from(&cell.timezone);
+        }
+    }
+}
+
+/// Default grace period between the termination signal and `SIGKILL`.
+pub const DEFAULT_TERM_GRACE_MS: u64 = 200;
+
+/// Default time a pre-kill hook may run before it is killed.
+pub const DEFAULT_PRE_KILL_HOOK_TIMEOUT_MS: u64 = 1000;
+
+/// Default wait after `process.deadline_signal` before termination starts.
+pub const DEFAULT_DEADLINE_GRACE_MS: u64 = 1000;
+
+/// Child environment variable holding the run's `budgets.max_runtime_ms`.
+pub const DEADLINE_ENV: &str = "PTYBOX_DEADLINE_MS";
+
+/// Child environment variable holding the time between the first signal at
+/// the deadline and `SIGKILL` ([`ProcessPolicy::deadline_grace_total_ms`]).
+pub const GRACE_ENV: &str = "PTYBOX_GRACE_MS";
+
+/// Signal ptybox sends first when it stops the child.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub enum KillSignal {
+    /// `SIGTERM` (default).
+    #[default]
+    #[serde(rename = "SIGTERM")]
+    Term,
+    /// `SIGINT`, as if Ctrl+C reached the whole process group.
+    #[serde(rename = "SIGINT")]
+    Int,
+    /// `SIGHUP`, as if the terminal hung up.
+    #[serde(rename = "SIGHUP")]
+    Hup,
+    /// `SIGQUIT`.
+    #[serde(rename = "SIGQUIT")]
+    Quit,
+    /// `SIGKILL` right away, skipping the grace period and pre-kill hook.
+    #[serde(rename = "SIGKILL")]
+    Kill,
+}
+
+/// Signal a `signal` action can deliver to the child's process group.
+///
+/// Only signals listed in `process.allowed_signals` may be sent.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ChildSignal {
+    /// `SIGINT`, as delivered by the terminal on Ctrl+C.
+    #[serde(rename = "SIGINT")]
+    Int,
+    /// `SIGHUP`, as if the terminal hung up.
+    #[serde(rename = "SIGHUP")]
+    Hup,
+    /// `SIGTERM`.
+    #[serde(rename = "SIGTERM")]
+    Term,
+    /// `SIGQUIT`.
+    #[serde(rename = "SIGQUIT")]
+    Quit,
+    /// `SIGTSTP`, as delivered by the terminal on Ctrl+Z.
+    #[serde(rename = "SIGTSTP")]
+    Tstp,
+    /// `SIGCONT`.
+    #[serde(rename = "SIGCONT")]
+    Cont,
+    /// `SIGUSR1`.
+    #[serde(rename = "SIGUSR1")]
+    Usr1,
+    /// `SIGUSR2`.
+    #[serde(rename = "SIGUSR2")]
+    Usr2,
+    /// `SIGWINCH` without a size change.
+    #[serde(rename = "SIGWINCH")]
+    Winch,
+}
+
+impl ChildSignal {
+    /// Every signal a `signal` action supports.
+    pub const ALL: [Self; 9] = [
+        Self::Int,
+        Self::Hup,
+        Self::Term,
+        Self::Quit,
+        Self::Tstp,
+        Self::Cont,
+        Self::Usr1,
+        Self::Usr2,
+        Self::Winch,
+    ];
+
+    /// Signal name as written in payloads and policies (`SIGINT`).
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Int => "SIGINT",
+            Self::Hup => "SIGHUP",
+            Self::Term => "SIGTERM",
+            Self::Quit => "SIGQUIT",
+            Self::Tstp => "SIGTSTP",
+            Self::Cont => "SIGCONT",
+            Self::Usr1 => "SIGUSR1",
+            Self::Usr2 => "SIGUSR2",
+            Self::Winch => "SIGWINCH",
+        }
+    }
+}
+
+/// Termination behavior shared by the runner, the driver, and
+/// `Session::close`.
+///
+/// ptybox sends `kill_signal` to the child's process group and waits up to
+/// `term_grace_ms` for it to exit. If it is still running, `pre_kill_hook`
+/// (when set) runs, then the group receives `SIGKILL`.
+///
+/// When the run outlives `budgets.max_runtime_ms`, `deadline_signal` (when
+/// set) is sent first and the child gets `deadline_grace_ms` to exit on its
+/// own before that sequence starts.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProcessPolicy {
+    /// Milliseconds to wait after `kill_signal` before escalating to `SIGKILL`.
+    #[serde(default = "default_term_grace_ms")]
+    pub term_grace_ms: u64,
+    /// Signal sent first. Default `SIGTERM`.
+    #[serde(default)]
+    pub kill_signal: KillSignal,
+    /// Command run before escalating to `SIGKILL` (e.g. to capture a core
+    /// dump). It must be listed in `exec.allowed_executables`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_kill_hook: Option<PreKillHook>,
+    /// Signals `signal` actions may send to the process group. Empty (the
+    /// default) denies every `signal` action.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_signals: Vec<ChildSignal>,
+    /// Warning signal sent when the runtime budget runs out, before
+    /// `kill_signal` (e.g. `SIGUSR2`, so the app can save state).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline_signal: Option<ChildSignal>,
+    /// Milliseconds to wait after `deadline_signal` before sending
+    /// `kill_signal`. Unused without `deadline_signal`.
+    #[serde(
+        default = "default_deadline_grace_ms",
+        skip_serializing_if = "is_default_deadline_grace_ms"
+    )]
+    pub deadline_grace_ms: u64,
+}
+
+impl Default for ProcessPolicy {
+    fn default() -> Self {
+        Self {
+            term_grace_ms: DEFAULT_TERM_GRACE_MS,
+            kill_signal: KillSignal::Term,
+            pre_kill_hook: None,
+            allowed_signals: Vec::new(),
+            deadline_signal: None,
+            deadline_grace_ms: DEFAULT_DEADLINE_GRACE_MS,
+        }
+    }
+}
+
+impl ProcessPolicy {
+    /// Whether every field has its default value.
+    #[must_use]
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Milliseconds between the first signal the child receives at the
+    /// deadline and `SIGKILL`: `deadline_grace_ms` when `deadline_signal`
+    /// is set, plus `term_grace_ms` unless `kill_signal` is `SIGKILL`.
+    #[must_use]
+    pub fn deadline_grace_total_ms(&self) -> u64 {
+        let warning = self.deadline_signal.map_or(0, |_| self.deadline_grace_ms);
+        let term = if self.kill_signal == KillSignal::Kill {
+            0
+        } else {
+            self.term_grace_ms
+        };
+        warning.saturating_add(term)
+    }
+}
+
+fn default_deadline_grace_ms() -> u64 {
+    DEFAULT_DEADLINE_GRACE_MS
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_default_deadline_grace_ms(deadline_grace_ms: &u64) -> bool {
+    *deadline_grace_ms == DEFAULT_DEADLINE_GRACE_MS
+}
+
+fn default_term_grace_ms() -> u64 {
+    DEFAULT_TERM_GRACE_MS
+}
+
+/// Command run when the child outlives its grace period, before `SIGKILL`.
+///
+/// The hook runs outside the sandbox with an empty environment except
+/// `PTYBOX_CHILD_PID`; its output is discarded.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PreKillHook {
+    /// Absolute path of the executable.
+    pub command: String,
+    /// Arguments passed to the executable.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Milliseconds the hook may run before it is killed.
+    #[serde(default = "default_pre_kill_hook_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_pre_kill_hook_timeout_ms() -> u64 {
+    DEFAULT_PRE_KILL_HOOK_TIMEOUT_MS
+}
+
+/// Maximum length of a [`SocketGrant`] name, so socket paths stay within
+/// the platform's `sun_path` limit.
+pub const MAX_SOCKET_NAME_LEN: usize = 32;
+
+/// A Unix socket ptybox creates for the child before spawning it.
+///
+/// The socket listens at `<tmp>/ptybox-<run>/<name>.sock` in a directory
+/// only the invoking user can enter, and its path is passed to the child in
+/// an environment variable. The listening end is handed to the embedder
+/// (`RunnerOptions::socket_handler`), which serves connections in-process,
+/// so a TUI can talk to a mock service while network access stays disabled.
+/// Under Seatbelt the profile allows connecting to these sockets only.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SocketGrant {
+    /// Socket name: letters, digits, `_`, and `-`, at most
+    /// [`MAX_SOCKET_NAME_LEN`] characters.
+    pub name: String,
+    /// Environment variable carrying the socket path. Defaults to
+    /// `PTYBOX_SOCKET_<NAME>`, upper-cased with `-` replaced by `_`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<String>,
+}
+
+impl SocketGrant {
+    /// Environment variable the child reads the socket path from.
+    #[must_use]
+    pub fn env_var(&self) -> String {
+        self.env.clone().unwrap_or_else(|| {
+            format!(
+                "PTYBOX_SOCKET_{}",
+                self.name.to_ascii_uppercase().replace('-', "_")
+            )
+        })
+    }
+}
+
+/// Default [`RemotePolicy::connect_timeout_ms`].
+pub const DEFAULT_REMOTE_CONNECT_TIMEOUT_MS: u64 = 10_000;
+
+/// SSH targets remote sessions
+/// ([`RemoteTarget`](crate::model::RemoteTarget)) may connect to.
+///
+/// Empty by default, which denies every remote session. The host key is
+/// always checked against `known_hosts`; unknown or changed keys are denied.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RemotePolicy {
+    /// Allowed hosts as `[user@]host[:port]`. Without a port the entry
+    /// allows port 22 only; without a user it allows any user.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_hosts: Vec<String>,
+    /// Private key files (absolute paths) remote sessions may authenticate
+    /// with.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_keys: Vec<String>,
+    /// `OpenSSH` `known_hosts` file (absolute path) holding the host keys.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub known_hosts: Option<String>,
+    /// Milliseconds allowed for connecting and the SSH handshake.
+    #[serde(default = "default_remote_connect_timeout_ms")]
+    pub connect_timeout_ms: u64,
+}
+
+impl Default for RemotePolicy {
+    fn default() -> Self {
+        Self {
+            allowed_hosts: Vec::new(),
+            allowed_keys: Vec::new(),
+            known_hosts: None,
+            connect_timeout_ms: DEFAULT_REMOTE_CONNECT_TIMEOUT_MS,
+        }
+    }
+}
+
+impl RemotePolicy {
+    /// Whether every field has its default value.
+    #[must_use]
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Network collectors the `log-forward` feature may send a run's transcript
+/// and events to.
+///
+/// Empty by default, which denies every UDP collector. The local syslog and
+/// journald sockets need no entry.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LogForwardPolicy {
+    /// UDP collectors as `host:port`, matched exactly (the host without
+    /// regard to case).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_udp: Vec<String>,
+}
+
+impl LogForwardPolicy {
+    /// Whether every field has its default value.
+    #[must_use]
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+fn default_remote_connect_timeout_ms() -> u64 {
+    DEFAULT_REMOTE_CONNECT_TIMEOUT_MS
+}
+
+/// Decoding of terminal output.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TerminalPolicy {
+    /// How PTY output bytes are decoded into transcript text and the screen.
+    #[serde(default)]
+    pub encoding: TerminalEncoding,
+}
+
+impl TerminalPolicy {
+    /// Whether every field has its default value.
+    #[must_use]
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Character encoding of a child's terminal output.
+///
+/// In every mode a multibyte sequence split across reads is held back until
+/// the rest arrives.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TerminalEncoding {
+    /// UTF-8; invalid output fails the observation with `E_TERMINAL_PARSE`
+    /// (default).
+    #[default]
+    Utf8Strict,
+    /// UTF-8; invalid bytes become U+FFFD and are reported as a
+    /// `decode_error` event on the observation.
+    Utf8Lossy,
+    /// ISO-8859-1: every byte is the character of the same code point.
+    Latin1,
+}
+
+/// A scoped exception to a shared policy, applied when a scenario's
+/// `metadata.name` matches `scenario`.
+///
+/// Grants are applied once, when a scenario run loads its policy: path
+/// lists are appended (without duplicates) and each set budget replaces the
+/// policy's. The grant is then dropped from the run's policy, which goes
+/// through the normal validation, and recorded in `RunResult.policy_grants`.
+/// Grants cannot change the sandbox, network, or acknowledgements.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PolicyGrant {
+    /// Glob over the scenario name: `*` matches any run of characters and
+    /// `?` any one character.
+    pub scenario: String,
+    /// Label for audit (ticket or approval reference).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Paths added to `fs.allowed_read`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_read: Vec<String>,
+    /// Paths added to `fs.allowed_write`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_write: Vec<String>,
+    /// Executables added to `exec.allowed_executables`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_executables: Vec<String>,
+    /// Budgets replacing the policy's.
+    #[serde(default, skip_serializing_if = "BudgetOverrides::is_empty")]
+    pub budgets: BudgetOverrides,
+}
+
+/// Budgets a [`PolicyGrant`] sets; unset fields keep the policy's value.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BudgetOverrides {
+    /// Replaces [`Budgets::max_runtime_ms`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_runtime_ms: Option<u64>,
+    /// Replaces [`Budgets::max_steps`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_steps: Option<u64>,
+    /// Replaces [`Budgets::max_output_bytes`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_bytes: Option<u64>,
+    /// Replaces [`Budgets::max_snapshot_bytes`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_snapshot_bytes: Option<u64>,
+    /// Replaces [`Budgets::max_wait_ms`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_wait_ms: Option<u64>,
+}
+
+impl BudgetOverrides {
+    /// Whether no budget is set.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Policy {
+    /// Environment policy for the child: `env` plus the variables pinned by
+    /// `determinism` and the deadline variables ([`DEADLINE_ENV`],
+    /// [`GRACE_ENV`]), which are allowlisted and override `env.set`.
+    #[must_use]
+    pub fn effective_env(&self) -> EnvPolicy {
+        let mut env = self.env.clone();
+        let deadline = [
+            (DEADLINE_ENV, self.budgets.max_runtime_ms.to_string()),
+            (
+                GRACE_ENV,
+                self.process.deadline_grace_total_ms().to_string(),
+            ),
+        ];
+        let pinned = self
+            .determinism
+            .env_vars()
+            .into_iter()
+            .map(|(key, value)| (key, value.to_string()))
+            .chain(deadline);
+        for (key, value) in pinned {
+            if !env.allowlist.iter().any(|allowed| allowed == key) {
+                env.allowlist.push(key.to_string());
+            }
+            env.set.insert(key.to_string(), value);
+        }
+        env
+    }
+}
+
 // =============================================================================
 // PolicyBuilder
 // =============================================================================
@@ -535,6 +1561,36 @@ impl PolicyBuilder {
         self
     }
 
+    /// Run the child in a container of `image` with `runtime` at
+    /// `program`, using the default process and memory limits.
+    ///
+    /// `program` must also be added to the executable allowlist.
+    #[must_use]
+    pub fn sandbox_container(
+        mut self,
+        runtime: ContainerRuntime,
+        program: &str,
+        image: &str,
+    ) -> Self {
+        self.policy.sandbox = SandboxMode::Container {
+            runtime,
+            image: image.to_string(),
+            program: program.to_string(),
+            pids_limit: DEFAULT_CONTAINER_PIDS_LIMIT,
+            memory_bytes: DEFAULT_CONTAINER_MEMORY_BYTES,
+        };
+        self
+    }
+
+    /// Run without Seatbelt when it is unavailable, with automatic acknowledgement.
+    ///
+    /// Unenforced policy aspects are recorded in the run's enforcement report.
+    #[must_use]
+    pub fn sandbox_fallback_warn(mut self) -> Self {
+        self.policy.sandbox_fallback = SandboxFallback::Warn { ack: true };
+        self
+    }
+
     /// Disable sandbox with automatic acknowledgement.
     ///
     /// This also sets `network_enforcement.unenforced_ack` since network
@@ -611,6 +1667,13 @@ impl PolicyBuilder {
         self
     }
 
+    /// Cap how many bytes `path` may grow during the run.
+    #[must_use]
+    pub fn write_quota(mut self, path: String, max_bytes: u64) -> Self {
+        self.policy.fs.write_quotas.insert(path, max_bytes);
+        self
+    }
+
     /// Enable strict write mode (artifacts path must be in write allowlist).
     #[must_use]
     pub fn strict_write(mut self) -> Self {
@@ -644,6 +1707,99 @@ impl PolicyBuilder {
         self
     }
 
+    // =========================================================================
+    // Input Configuration
+    // =========================================================================
+
+    /// Allow `raw` actions that write bytes verbatim to the PTY.
+    #[must_use]
+    pub fn allow_raw_bytes(mut self) -> Self {
+        self.policy.input.allow_raw_bytes = true;
+        self
+    }
+
+    /// Set the maximum decoded size of a single `raw` action.
+    #[must_use]
+    pub fn max_raw_bytes(mut self, bytes: u64) -> Self {
+        self.policy.input.max_raw_bytes = bytes;
+        self
+    }
+
+    // =========================================================================
+    // Determinism Configuration
+    // =========================================================================
+
+    /// Pin the child's locale (`LANG` and `LC_ALL`).
+    #[must_use]
+    pub fn locale(mut self, locale: impl Into<String>) -> Self {
+        self.policy.determinism.locale = Some(locale.into());
+        self
+    }
+
+    /// Pin the child's timezone (`TZ`).
+    #[must_use]
+    pub fn timezone(mut self, timezone: impl Into<String>) -> Self {
+        self.policy.determinism.timezone = Some(timezone.into());
+        self
+    }
+
+    /// Replay with the baseline's step pacing (`determinism.paced_input`).
+    #[must_use]
+    pub fn paced_input(mut self) -> Self {
+        self.policy.determinism.paced_input = true;
+        self
+    }
+
+    /// Set how the child is stopped (signal, grace period, pre-kill hook).
+    #[must_use]
+    pub fn process(mut self, process: ProcessPolicy) -> Self {
+        self.policy.process = process;
+        self
+    }
+
+    /// Set the Unix sockets created for the child at spawn.
+    #[must_use]
+    pub fn sockets(mut self, sockets: Vec<SocketGrant>) -> Self {
+        self.policy.sockets = sockets;
+        self
+    }
+
+    /// Set the SSH hosts and keys remote sessions may use.
+    #[must_use]
+    pub fn remote(mut self, remote: RemotePolicy) -> Self {
+        self.policy.remote = remote;
+        self
+    }
+
+    /// Set the UDP collectors (`host:port`) logs may be forwarded to.
+    #[must_use]
+    pub fn log_forward_udp(mut self, endpoints: Vec<String>) -> Self {
+        self.policy.log_forward.allowed_udp = endpoints;
+        self
+    }
+
+    /// Set how terminal output is decoded.
+    #[must_use]
+    pub fn terminal_encoding(mut self, encoding: TerminalEncoding) -> Self {
+        self.policy.terminal.encoding = encoding;
+        self
+    }
+
+    /// Set the placeholders (`cwd`, `env:NAME`) this policy's file may use
+    /// in path fields.
+    #[must_use]
+    pub fn substitution_allowlist(mut self, placeholders: Vec<String>) -> Self {
+        self.policy.substitution_allowlist = placeholders;
+        self
+    }
+
+    /// Add a grant applied to scenarios whose name matches its pattern.
+    #[must_use]
+    pub fn grant(mut self, grant: PolicyGrant) -> Self {
+        self.policy.grants.push(grant);
+        self
+    }
+
     // =========================================================================
     // Environment Configuration
     // =========================================================================
@@ -715,6 +1871,27 @@ impl PolicyBuilder {
         self
     }
 
+    /// Set the driver session idle budget in milliseconds.
+    #[must_use]
+    pub fn max_session_idle_ms(mut self, ms: u64) -> Self {
+        self.policy.budgets.max_session_idle_ms = Some(ms);
+        self
+    }
+
+    /// Set the driver session lifetime budget in milliseconds.
+    #[must_use]
+    pub fn max_session_lifetime_ms(mut self, ms: u64) -> Self {
+        self.policy.budgets.max_session_lifetime_ms = Some(ms);
+        self
+    }
+
+    /// Set the cap on the adaptive wait poll interval in milliseconds.
+    #[must_use]
+    pub fn max_poll_interval_ms(mut self, ms: u64) -> Self {
+        self.policy.budgets.max_poll_interval_ms = Some(ms);
+        self
+    }
+
     // =========================================================================
     // Artifacts Configuration
     // =========================================================================
@@ -734,6 +1911,27 @@ impl PolicyBuilder {
         self
     }
 
+    /// Set the artifacts layout (flat or per-step directories).
+    #[must_use]
+    pub fn artifacts_layout(mut self, layout: ArtifactsLayout) -> Self {
+        self.policy.artifacts.layout = layout;
+        self
+    }
+
+    /// Write `fs-manifest.json` listing `fs.allowed_write` after exit.
+    #[must_use]
+    pub fn artifacts_fs_manifest(mut self) -> Self {
+        self.policy.artifacts.fs_manifest = true;
+        self
+    }
+
+    /// Write the output to `session.cast` as an asciinema v2 recording.
+    #[must_use]
+    pub fn artifacts_asciicast(mut self) -> Self {
+        self.policy.artifacts.asciicast = true;
+        self
+    }
+
     // =========================================================================
     // Build
     // =========================================================================
@@ -1,7 +1,8 @@
-use crate::model::policy::Policy;
-use crate::model::scenario::Action;
+use crate::model::policy::{ChildSignal, ContainerRuntime, DeterminismPolicy, KillSignal, Policy};
+use crate::model::scenario::{Action, Scenario, ScenarioMetadata};
 use crate::model::{Observation, RunId};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Summary of a completed run, suitable for JSON output.
 ///
@@ -40,6 +41,353 @@ pub struct RunResult {
     pub exit_status: Option<ExitStatus>,
     /// Error information (present when status is not Passed).
     pub error: Option<ErrorInfo>,
+    /// Free-form labels from the scenario metadata and runner options.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Policy aspects left unenforced because the run fell back from Seatbelt.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enforcement: Option<EnforcementReport>,
+    /// Where this run came from: ptybox version, source revision, and scenario hash.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    /// Likely cause of the failure (absent when the run passed or was canceled).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub classification: Option<FailureClassification>,
+    /// Results of the scenario's `post_run` assertions (including any moved
+    /// from the deprecated `post`), in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_run: Vec<AssertionResult>,
+    /// One-line summary of the unsafe options acknowledged for the run
+    /// (see [`AcknowledgementReport::summary`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub risk_summary: Option<String>,
+    /// Fixtures generated before the command was spawned, in scenario order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fixtures: Vec<FixtureRecord>,
+    /// Policy grants whose pattern matched the scenario name, as applied to
+    /// `policy` (see [`PolicyGrant`](crate::model::policy::PolicyGrant)).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub policy_grants: Vec<crate::model::policy::PolicyGrant>,
+    /// Keymap applied to key actions, as resolved from
+    /// `policy.input.keymap` (see [`Keymap`](crate::model::policy::Keymap)).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keymap: Option<crate::model::policy::Keymap>,
+    /// Locale and timezone pinned for the run by `policy.determinism`, when
+    /// either is (see [`DeterminismPolicy::pinned`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub determinism: Option<DeterminismPolicy>,
+}
+
+/// A fixture the runner generated, with enough detail to regenerate and
+/// verify it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixtureRecord {
+    /// The generator as resolved (sizes in bytes).
+    #[serde(flatten)]
+    pub generator: crate::model::FixtureGenerator,
+    /// Files written.
+    pub files: u64,
+    /// Directories created, including a tree's root.
+    pub dirs: u64,
+    /// Total bytes written.
+    pub bytes: u64,
+    /// FNV-1a digest (16 hex digits) of each file's path relative to `path`
+    /// and content, in creation order.
+    pub checksum: String,
+}
+
+/// Origin of a run, recorded in `run.json` and carried into attestations.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provenance {
+    /// Version of ptybox that produced the run.
+    pub ptybox_version: String,
+    /// Git commit of the code under test, when supplied by the caller.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_sha: Option<String>,
+    /// `sha256:<hex>` digest of the resolved scenario (scenario mode only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scenario_hash: Option<String>,
+}
+
+/// Record of a run that proceeded without its requested sandbox.
+///
+/// Produced when Seatbelt is unavailable and the policy sets
+/// `sandbox_fallback: warn` with acknowledgement. Written to
+/// `enforcement.json` and embedded in [`RunResult::enforcement`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EnforcementReport {
+    /// Sandbox mode the policy requested (`seatbelt`).
+    pub requested_sandbox: String,
+    /// Sandbox mode actually applied (`none`).
+    pub applied_sandbox: String,
+    /// Why the requested sandbox could not be used.
+    pub reason: ErrorInfo,
+    /// Policy aspects that were not enforced by the OS.
+    pub unenforced: Vec<UnenforcedAspect>,
+}
+
+/// How a container-sandboxed run started its container
+/// ([`SandboxMode::Container`](crate::model::policy::SandboxMode::Container)).
+/// Written to `container.json`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ContainerInvocation {
+    /// Container runtime.
+    pub runtime: ContainerRuntime,
+    /// Image the command ran in.
+    pub image: String,
+    /// Runtime executable spawned under the PTY.
+    pub program: String,
+    /// Arguments passed to `program`, in order.
+    pub args: Vec<String>,
+}
+
+/// A single policy aspect that went unenforced.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UnenforcedAspect {
+    /// Policy field path, e.g. `fs.allowed_read`.
+    pub aspect: String,
+    /// What the process could do that the policy would otherwise deny.
+    pub detail: String,
+}
+
+/// Unsafe policy option that takes an explicit acknowledgement.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnsafeOption {
+    /// Sandbox disabled (`sandbox: none`).
+    SandboxDisabled,
+    /// Best-effort restrictions when Seatbelt is unavailable
+    /// (`sandbox_fallback: warn`).
+    SandboxFallback,
+    /// Network access enabled.
+    NetworkEnabled,
+    /// Network restrictions left unenforced because the sandbox is disabled.
+    NetworkUnenforced,
+    /// Write access to `fs.allowed_write`.
+    FsWrite,
+}
+
+impl UnsafeOption {
+    /// Every option, in report order.
+    pub const ALL: [Self; 5] = [
+        Self::SandboxDisabled,
+        Self::SandboxFallback,
+        Self::NetworkEnabled,
+        Self::NetworkUnenforced,
+        Self::FsWrite,
+    ];
+
+    /// Stable snake-case name, as serialized.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::SandboxDisabled => "sandbox_disabled",
+            Self::SandboxFallback => "sandbox_fallback",
+            Self::NetworkEnabled => "network_enabled",
+            Self::NetworkUnenforced => "network_unenforced",
+            Self::FsWrite => "fs_write",
+        }
+    }
+}
+
+/// Where an acknowledgement was given.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AckSource {
+    /// An acknowledgement field in the policy.
+    Policy,
+    /// A command-line flag such as `--ack-unsafe-sandbox`.
+    Cli,
+    /// An environment variable such as `PTYBOX_ACK_UNSAFE_SANDBOX`.
+    Env,
+}
+
+/// Acknowledgement of one unsafe option active for a run.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Acknowledgement {
+    /// The acknowledged option.
+    pub option: UnsafeOption,
+    /// Where the acknowledgement was given.
+    pub source: AckSource,
+    /// Policy field, flags, or environment variable that gave it.
+    pub origin: String,
+    /// When the acknowledgement was recorded (milliseconds since the Unix
+    /// epoch): when the CLI parsed it, or when the run started for policy
+    /// acknowledgements.
+    pub recorded_at_ms: u64,
+}
+
+/// Unsafe options acknowledged for a run.
+///
+/// Written to `acknowledgements.json` when at least one unsafe option is
+/// active; the summary is also set as [`RunResult::risk_summary`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AcknowledgementReport {
+    /// One entry per active unsafe option, in [`UnsafeOption::ALL`] order.
+    pub acknowledgements: Vec<Acknowledgement>,
+    /// One line for reviewers, e.g.
+    /// `unsafe: sandbox_disabled (cli: --ack-unsafe-sandbox), fs_write (policy: fs_write_unsafe_ack)`.
+    pub summary: String,
+}
+
+/// Sandbox denials collected during a run with sandbox auditing enabled.
+///
+/// Written to `violations.json` (feature `sandbox-audit`).
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SandboxViolationReport {
+    /// Where violations were read from (`log_stream`).
+    pub source: String,
+    /// Whether a collector ran for the whole run.
+    pub collected: bool,
+    /// Why nothing was collected, when `collected` is false.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Denials attributed to the child or its allowlisted executables.
+    pub violations: Vec<SandboxViolation>,
+}
+
+/// A single operation the sandbox denied.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SandboxViolation {
+    /// Process name as reported by the sandbox.
+    pub process: String,
+    /// Process id.
+    pub pid: u32,
+    /// Denied Seatbelt operation, e.g. `file-read-data`.
+    pub operation: String,
+    /// Path or address the operation targeted, when reported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Log timestamp, when reported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+}
+
+/// Draft policy inferred by watching one unsandboxed run of a command.
+///
+/// Produced by `ptybox policy init`; the policy is a starting point for
+/// review, not a verified allowlist.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PolicyInference {
+    /// Draft policy built from `observed`.
+    pub policy: Policy,
+    /// How accesses were observed: `strace`, `proc` (sampling `/proc`), or
+    /// `none` when neither is available.
+    pub source: String,
+    /// What the command's process tree was seen to use.
+    pub observed: ObservedAccess,
+    /// How the observed run ended.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_status: Option<ExitStatus>,
+    /// How long the command was observed, in milliseconds.
+    pub duration_ms: u64,
+    /// Accesses left out of the draft and other caveats, for the reviewer.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+}
+
+/// Accesses seen during a [`PolicyInference`] run, sorted and deduplicated.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ObservedAccess {
+    /// Executables run by the process tree, the command first.
+    pub executables: Vec<String>,
+    /// Files and directories opened for reading.
+    pub read: Vec<String>,
+    /// Files and directories opened for writing, created, or removed.
+    pub written: Vec<String>,
+    /// Inherited environment variables the executables refer to by name.
+    pub env: Vec<String>,
+    /// Whether the process tree opened an internet socket.
+    pub network: bool,
+}
+
+/// Tighter policy suggested by comparing a finished run's policy with what
+/// the run was seen to use.
+///
+/// Produced by `ptybox policy suggest` and written to
+/// `policy-suggestions.json` in the run's artifacts. Like
+/// [`PolicyInference`], the candidate is a starting point for review.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PolicySuggestions {
+    /// The run's policy with unused allowances removed or narrowed.
+    pub policy: Policy,
+    /// Allowances the candidate drops or narrows, in policy field order.
+    pub changes: Vec<PolicyChange>,
+    /// Network operations the sandbox denied (from `violations.json`),
+    /// as `operation target`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub network_attempts: Vec<String>,
+    /// What could not be checked and other caveats, for the reviewer.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+}
+
+/// One allowance a [`PolicySuggestions`] candidate removes or narrows.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PolicyChange {
+    /// Policy field, e.g. `exec.allowed_executables`.
+    pub field: String,
+    /// Entry removed from the field.
+    pub value: String,
+    /// Entries added in its place; empty when it is dropped outright.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub replaced_by: Vec<String>,
+    /// Why the entry is not needed.
+    pub reason: String,
+}
+
+/// Outcome of fuzzing a command with random input sequences (`ptybox fuzz`).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FuzzReport {
+    /// Base seed; iteration `i` is generated from `seed + i`.
+    pub seed: u64,
+    /// Iterations run (each a fresh process fed one random sequence).
+    pub iterations: u32,
+    /// Actions sent across all iterations, minimization runs excluded.
+    pub actions: u64,
+    /// Iterations where the command exited successfully before its
+    /// sequence ended (e.g. a random `q` quit it).
+    pub clean_exits: u32,
+    /// First failure found; fuzzing stops there.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure: Option<FuzzFailure>,
+}
+
+/// A failing fuzz sequence and its minimized reproduction.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FuzzFailure {
+    /// Iteration that failed (0-based).
+    pub iteration: u32,
+    /// Seed of that iteration; `--seed <seed> --iterations 1` replays it.
+    pub seed: u64,
+    /// What failed.
+    pub kind: FuzzFailureKind,
+    /// Watcher that triggered, for [`FuzzFailureKind::Watcher`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watcher: Option<String>,
+    /// `E_ASSERTION_FAILED` for a watcher, `E_PROCESS_EXIT` for an exit.
+    pub error: ErrorInfo,
+    /// How the command exited, for [`FuzzFailureKind::Exit`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_status: Option<ExitStatus>,
+    /// Actions sent up to and including the one that failed.
+    pub original_steps: usize,
+    /// Actions left after minimization.
+    pub minimized_steps: usize,
+    /// Sequences run while minimizing.
+    pub minimization_runs: u32,
+    /// Scenario replaying the minimized actions with the same watchers.
+    pub scenario: Scenario,
+}
+
+/// Kind of [`FuzzFailure`].
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FuzzFailureKind {
+    /// A watcher condition held, such as panic text on screen.
+    Watcher,
+    /// The command exited unsuccessfully before its sequence ended.
+    Exit,
 }
 
 /// Overall run status.
@@ -61,6 +409,12 @@ pub enum RunStatus {
 pub struct StepResult {
     /// Step identifier from scenario.
     pub step_id: crate::model::StepId,
+    /// Content-derived step identity ([`Step::content_key`]), stable across
+    /// runs and scenario edits (absent in results from older versions).
+    ///
+    /// [`Step::content_key`]: crate::model::Step::content_key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step_key: Option<String>,
     /// Human-readable step name.
     pub name: String,
     /// Step outcome.
@@ -77,6 +431,82 @@ pub struct StepResult {
     pub assertions: Vec<AssertionResult>,
     /// Error information if step failed.
     pub error: Option<ErrorInfo>,
+    /// Condition polling done by this step (absent when the step did not wait).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wait: Option<WaitStats>,
+    /// Measured response latency in milliseconds: from input to the first
+    /// output after it, or from the start of a wait to its condition match.
+    /// Absent when nothing was measured (no output, observe/terminate steps).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    /// Intermediate screens captured by `sample_interval_ms` (absent when
+    /// the step did not sample).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub samples: Option<SampleStats>,
+    /// Artifact written by the step's `capture` (`captures/<name>.txt`),
+    /// relative to the artifacts directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capture: Option<String>,
+    /// The step's `env_overlay`, as applied to processes it spawned.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env_overlay: BTreeMap<String, String>,
+    /// The step's `metadata`, unchanged.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, serde_json::Value>,
+    /// Variables the step captured (its `vars`), as the text matched.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub vars: BTreeMap<String, String>,
+    /// Variables substituted into the step's action, as the typed values
+    /// used; `action` is the action after substitution.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub resolved_vars: BTreeMap<String, serde_json::Value>,
+}
+
+/// Counters for a step's intermediate screen samples.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SampleStats {
+    /// Samples written to the artifacts.
+    pub captured: u64,
+    /// Samples discarded by the `max_step_samples` or `max_snapshot_bytes` budget.
+    pub dropped: u64,
+}
+
+/// An intermediate screen captured while a step was running.
+///
+/// Written to `samples/NNNNNN.json` (under the step directory with the
+/// `per_step` layout). Samples are only taken when the screen changed.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ScreenSample {
+    /// Step that was running.
+    pub step_id: crate::model::StepId,
+    /// Milliseconds since session start, on the same clock as
+    /// [`Observation::timestamp_ms`](crate::model::Observation::timestamp_ms).
+    pub timestamp_ms: u64,
+    /// Screen at that moment.
+    pub screen: crate::model::ScreenSnapshot,
+}
+
+/// Counters for condition polling, used to tune wait budgets.
+///
+/// Wait time is attributed separately from action execution so slow waits
+/// are visible in step results and driver budget status.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WaitStats {
+    /// Number of observations taken while waiting.
+    pub polls: u64,
+    /// Wall-clock time spent waiting in milliseconds.
+    pub wait_ms: u64,
+}
+
+impl WaitStats {
+    /// Counters accumulated since `earlier`, or `None` if nothing was polled.
+    pub fn since(self, earlier: Self) -> Option<Self> {
+        let delta = Self {
+            polls: self.polls.saturating_sub(earlier.polls),
+            wait_ms: self.wait_ms.saturating_sub(earlier.wait_ms),
+        };
+        (delta.polls > 0).then_some(delta)
+    }
 }
 
 /// Individual step status.
@@ -105,6 +535,9 @@ pub struct AssertionResult {
     pub message: Option<String>,
     /// Structured diagnostic details.
     pub details: Option<serde_json::Value>,
+    /// True when the assertion came from `defaults.assert_each_step`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub inherited: bool,
 }
 
 /// Process exit status.
@@ -121,6 +554,1104 @@ pub struct ExitStatus {
     pub signal: Option<i32>,
     /// True when ptybox killed the process (timeout, error recovery).
     pub terminated_by_harness: bool,
+    /// How ptybox stopped the process, when it did.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub termination: Option<TerminationRecord>,
+}
+
+/// Termination sequence ptybox applied to a child, per `policy.process`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TerminationRecord {
+    /// Signal sent first.
+    pub signal: KillSignal,
+    /// Grace period allowed before escalating to `SIGKILL`.
+    pub grace_ms: u64,
+    /// True when the child outlived the grace period and received `SIGKILL`.
+    pub escalated: bool,
+    /// Outcome of `process.pre_kill_hook`, when it ran.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_kill_hook: Option<PreKillHookResult>,
+    /// `process.deadline_signal` sent when the runtime budget ran out, when
+    /// set. If the child exited within its grace period, `signal` was never
+    /// sent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline_warning: Option<DeadlineWarning>,
+}
+
+/// Warning signal sent to a child at the run's deadline.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DeadlineWarning {
+    /// Signal sent (`process.deadline_signal`).
+    pub signal: ChildSignal,
+    /// Wait allowed before termination started (`process.deadline_grace_ms`).
+    pub grace_ms: u64,
+    /// True when the child exited within `grace_ms`.
+    pub exited: bool,
+}
+
+/// Outcome of a pre-kill hook.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PreKillHookResult {
+    /// Hook executable.
+    pub command: String,
+    /// Exit code, when the hook exited on its own.
+    pub exit_code: Option<i32>,
+    /// True when the hook was killed after its `timeout_ms`.
+    pub timed_out: bool,
+    /// Why the hook could not be run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Audit record for a `raw` action, appended to `raw-input.jsonl`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RawInputRecord {
+    /// Step name (scenario runs) or `driver-step-N` (driver sessions).
+    pub step: String,
+    /// Number of bytes written to the PTY.
+    pub bytes: u64,
+    /// Bytes written, hex encoded regardless of the payload encoding.
+    pub hex: String,
+}
+
+/// Default auto-repeat rate for `hold_key` actions, in presses per second.
+pub const DEFAULT_KEY_REPEAT_HZ: u32 = 30;
+/// Longest accepted `hold_key` duration in milliseconds.
+pub const MAX_KEY_HOLD_MS: u64 = 60_000;
+/// Highest accepted `hold_key` repeat rate in presses per second.
+pub const MAX_KEY_REPEAT_HZ: u32 = 100;
+
+/// Input written by one `hold_key` action.
+///
+/// Presses are scheduled every `1 / repeat_hz` seconds from the start of the
+/// hold, so `presses` is deterministic; `offsets_ms` records when each press
+/// was actually written.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct KeyHold {
+    /// Key that was held.
+    pub key: String,
+    /// Requested hold duration in milliseconds.
+    pub duration_ms: u64,
+    /// Requested auto-repeat rate in presses per second.
+    pub repeat_hz: u32,
+    /// Number of key presses written.
+    pub presses: u64,
+    /// Bytes written per press, hex encoded.
+    pub hex: String,
+    /// Time of each press since the hold started, in milliseconds.
+    pub offsets_ms: Vec<u64>,
+}
+
+/// Audit record for a `hold_key` action, appended to `key-holds.jsonl`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct KeyHoldRecord {
+    /// Step name (scenario runs) or `driver-step-N` (driver sessions).
+    pub step: String,
+    /// The burst written by the action.
+    #[serde(flatten)]
+    pub hold: KeyHold,
+}
+
+/// Line-discipline flags of the PTY, read back after a `termios` action.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TermiosSettings {
+    /// `ECHO`: input is echoed back to the terminal.
+    pub echo: bool,
+    /// `ICANON`: canonical (line-buffered) input with line editing.
+    pub icanon: bool,
+    /// `ISIG`: `^C`, `^\` and `^Z` generate signals.
+    pub isig: bool,
+}
+
+/// Audit record for a `termios` action, appended to `termios.jsonl`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TermiosRecord {
+    /// Step name (scenario runs) or `driver-step-N` (driver sessions).
+    pub step: String,
+    /// Flags in effect after the action.
+    #[serde(flatten)]
+    pub settings: TermiosSettings,
+}
+
+/// One line of the run index (`runs.jsonl`) at the artifacts root.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RunIndexEntry {
+    /// Run identifier (matches `run.json`).
+    pub run_id: RunId,
+    /// Overall run status.
+    pub status: RunStatus,
+    /// Tags recorded on the run.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Scenario name (absent for `exec` runs).
+    #[serde(default)]
+    pub scenario: Option<String>,
+    /// Command that was executed.
+    pub command: String,
+    /// Artifacts directory holding the full run.
+    pub artifacts_dir: String,
+    /// Wall-clock time the entry was recorded (ms since Unix epoch).
+    pub recorded_at_ms: u64,
+    /// Stable error code when the run did not pass.
+    #[serde(default)]
+    pub error_code: Option<String>,
+    /// Run duration in milliseconds (absent in entries from older versions).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+}
+
+/// `_type` of an attestation statement (in-toto Statement v1).
+pub const IN_TOTO_STATEMENT_TYPE: &str = "https://in-toto.io/Statement/v1";
+
+/// `predicateType` of a ptybox run attestation.
+pub const RUN_ATTESTATION_PREDICATE_TYPE: &str =
+    "https://github.com/lelanhus/ptybox/attestation/run/v1";
+
+/// Result of slimming a run's artifacts down to what replay needs.
+///
+/// Produced by `ptybox artifacts slim`; `checksums.json` is recomputed in the
+/// output and is not counted in either total.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SlimReport {
+    /// Source artifacts directory.
+    pub source: String,
+    /// Slimmed output directory.
+    pub output: String,
+    /// Files copied, relative to the output directory.
+    pub kept: Vec<String>,
+    /// Bytes copied.
+    pub kept_bytes: u64,
+    /// Source files left behind (`replay-*` directories excluded).
+    pub dropped_files: u64,
+    /// Bytes left behind.
+    pub dropped_bytes: u64,
+}
+
+/// Result of rewriting a run's JSON artifacts in canonical form.
+///
+/// Produced by `ptybox artifacts reformat`; `checksums.json` is rewritten
+/// whenever an entry changes and is not listed in either field.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ReformatReport {
+    /// Artifacts directory.
+    pub dir: String,
+    /// Files rewritten, relative to the directory.
+    pub reformatted: Vec<String>,
+    /// JSON files already in canonical form.
+    pub unchanged: u64,
+}
+
+/// Result of packing a failed run into a triage bundle.
+///
+/// Produced by `ptybox bundle`. Every text entry in the bundle has secret
+/// redaction applied.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BundleReport {
+    /// Bundle file written (`.tar.gz`).
+    pub output: String,
+    /// Entry paths inside the bundle, in archive order.
+    pub files: Vec<String>,
+    /// Size of the bundle file in bytes.
+    pub bytes: u64,
+    /// Number of values replaced by redaction.
+    pub redactions: u64,
+    /// Name of the first failed or errored step, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failing_step: Option<String>,
+}
+
+/// Artifact a [`GrepMatch`] was found in.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GrepSource {
+    /// `transcript.log`, with terminal escape sequences removed.
+    Transcript,
+    /// A screen snapshot's `lines`.
+    Snapshot,
+}
+
+/// One matching line found by `ptybox grep`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GrepMatch {
+    /// Run the line belongs to.
+    pub run_id: RunId,
+    /// Artifacts directory of the run.
+    pub artifacts_dir: String,
+    /// Artifact the line was found in.
+    pub source: GrepSource,
+    /// Step the snapshot was captured in, when it can be told.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<String>,
+    /// Snapshot file relative to the artifacts directory (without `.age`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<String>,
+    /// 1-based line number within the transcript or snapshot.
+    pub line: u64,
+    /// The matching line.
+    pub text: String,
+    /// Context lines before the match, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub before: Vec<String>,
+    /// Context lines after the match.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub after: Vec<String>,
+}
+
+/// An indexed run `ptybox grep` could not search.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GrepSkip {
+    /// Run that was skipped.
+    pub run_id: RunId,
+    /// Artifacts directory recorded in the index.
+    pub artifacts_dir: String,
+    /// Why the run was skipped.
+    pub reason: String,
+}
+
+/// Result of searching runs under an artifacts root.
+///
+/// Produced by `ptybox grep`; runs are searched newest first.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GrepReport {
+    /// Runs whose artifacts were searched.
+    pub runs_searched: u64,
+    /// Matching lines, grouped by run.
+    pub matches: Vec<GrepMatch>,
+    /// Indexed runs that could not be searched (for example, artifacts
+    /// that were deleted or are encrypted without an identity).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<GrepSkip>,
+}
+
+/// A golden screen baseline stored under a baseline directory.
+///
+/// Produced by `ptybox goldens list`. Each golden is a replay baseline (a
+/// slimmed artifacts directory) named after its subdirectory.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GoldenEntry {
+    /// Subdirectory of the baseline directory.
+    pub name: String,
+    /// Scenario name from the golden's `scenario.json`.
+    pub scenario: String,
+    /// Run the golden was recorded from.
+    pub run_id: RunId,
+    /// Snapshot files in the golden.
+    pub snapshots: u64,
+    /// Total size of the golden's files.
+    pub bytes: u64,
+    /// Scenario files naming the golden's scenario; `None` when no
+    /// scenarios were given to check against.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub referenced_by: Option<Vec<String>>,
+    /// Times the golden was replaced by `ptybox goldens update`.
+    pub updates: u64,
+    /// Snapshot lines changed across those updates.
+    pub lines_changed: u64,
+    /// When the golden was last updated (ms since Unix epoch).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_updated_at_ms: Option<u64>,
+}
+
+/// Result of removing goldens no scenario references.
+///
+/// Produced by `ptybox goldens prune`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GoldenPruneReport {
+    /// Goldens removed (or, with `dry_run`, that would be removed).
+    pub removed: Vec<String>,
+    /// Goldens still referenced.
+    pub kept: u64,
+    /// Bytes freed by the removal.
+    pub bytes_freed: u64,
+    /// Whether nothing was deleted.
+    pub dry_run: bool,
+}
+
+/// One golden replaced from a passing run, with its churn.
+///
+/// Appended to `goldens.jsonl` in the baseline directory.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GoldenUpdate {
+    /// Golden that was replaced.
+    pub name: String,
+    /// Scenario name.
+    pub scenario: String,
+    /// Run the golden was recorded from before the update.
+    pub previous_run_id: RunId,
+    /// Passing run the golden now comes from.
+    pub run_id: RunId,
+    /// Artifacts directory of that run.
+    pub source: String,
+    /// Snapshots present only in the new golden.
+    pub snapshots_added: u64,
+    /// Snapshots present only in the old golden.
+    pub snapshots_removed: u64,
+    /// Snapshots present in both whose screen lines differ.
+    pub snapshots_changed: u64,
+    /// Screen lines that differ, counting every line of added and removed
+    /// snapshots.
+    pub lines_changed: u64,
+    /// When the update was made (ms since Unix epoch).
+    pub updated_at_ms: u64,
+}
+
+/// A golden `ptybox goldens update` left unchanged.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GoldenSkip {
+    /// Golden that was not updated.
+    pub name: String,
+    /// Why it was skipped.
+    pub reason: String,
+}
+
+/// Result of updating goldens from recent passing runs.
+///
+/// Produced by `ptybox goldens update`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GoldenUpdateReport {
+    /// Goldens replaced, in name order.
+    pub updated: Vec<GoldenUpdate>,
+    /// Goldens left unchanged.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<GoldenSkip>,
+}
+
+/// Where a scenario's time estimate comes from.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EstimateBasis {
+    /// Durations of earlier runs in the run index.
+    History,
+    /// Declared step timeouts only (no earlier runs).
+    Timeouts,
+}
+
+/// Expected duration of one scenario file.
+///
+/// Per-run figures cover one run; totals multiply them by `runs` (the
+/// matrix cells).
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ScenarioEstimate {
+    /// Scenario name (`metadata.name`).
+    pub scenario: String,
+    /// Scenario file.
+    pub path: String,
+    /// Steps in the scenario.
+    pub steps: u64,
+    /// Runs the scenario expands to.
+    pub runs: u64,
+    /// Worst case for one run: every step's `timeout_ms`, once per attempt.
+    pub timeout_ms: u64,
+    /// Where `expected_ms`, `low_ms`, and `high_ms` come from.
+    pub basis: EstimateBasis,
+    /// Earlier runs the estimate is based on.
+    pub history_runs: u64,
+    /// Expected duration of one run: the median of earlier runs, or
+    /// `timeout_ms` without history.
+    pub expected_ms: u64,
+    /// Shortest earlier run, or 0 without history.
+    pub low_ms: u64,
+    /// Longest earlier run, or `timeout_ms` without history.
+    pub high_ms: u64,
+    /// `expected_ms` times `runs`.
+    pub total_ms: u64,
+    /// `low_ms` times `runs`.
+    pub total_low_ms: u64,
+    /// `high_ms` times `runs`.
+    pub total_high_ms: u64,
+}
+
+/// Time estimate for a set of scenario files.
+///
+/// Produced by `ptybox estimate`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EstimateReport {
+    /// One entry per scenario file, in path order.
+    pub scenarios: Vec<ScenarioEstimate>,
+    /// Matrix cells each scenario runs in (1 without a matrix).
+    pub matrix_cells: u64,
+    /// Sum of the scenarios' `total_ms`.
+    pub total_ms: u64,
+    /// Sum of the scenarios' `total_low_ms`.
+    pub total_low_ms: u64,
+    /// Sum of the scenarios' `total_high_ms`.
+    pub total_high_ms: u64,
+}
+
+/// Current version of the impact map format.
+pub const IMPACT_MAP_VERSION: u32 = 1;
+
+/// Files each scenario exercises, for selecting the scenarios a change
+/// affects.
+///
+/// Globs are matched against changed file paths as listed (typically
+/// relative to the repository root); `*` matches any run of characters,
+/// including `/`, and `?` exactly one.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ImpactMap {
+    /// Impact map format version ([`IMPACT_MAP_VERSION`]).
+    pub impact_map_version: u32,
+    /// Globs whose change selects every scenario (lockfiles, build
+    /// configuration, shared fixtures).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub always: Vec<String>,
+    /// Watched globs by scenario name (`metadata.name`).
+    #[serde(default)]
+    pub scenarios: BTreeMap<String, Vec<String>>,
+}
+
+/// Why a scenario was or was not selected.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectionReason {
+    /// A changed file matches one of the scenario's watched globs.
+    Matched,
+    /// A changed file matches an `always` glob, so every scenario runs.
+    Always,
+    /// No impact map was given, so every scenario runs.
+    NoImpactMap,
+    /// The impact map has no entry for the scenario, so it runs.
+    Unmapped,
+    /// No changed file matches the scenario's watched globs.
+    NotAffected,
+}
+
+/// A changed file and the glob it matched.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ImpactMatch {
+    /// Changed file path, as listed.
+    pub file: String,
+    /// Watched (or `always`) glob it matched.
+    pub glob: String,
+}
+
+/// Selection decision for one scenario file.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ScenarioSelection {
+    /// Scenario name (`metadata.name`).
+    pub scenario: String,
+    /// Scenario file.
+    pub path: String,
+    /// Whether the scenario runs.
+    pub selected: bool,
+    /// Why it was or was not selected.
+    pub reason: SelectionReason,
+    /// Changed files behind a `matched` or `always` selection.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub matches: Vec<ImpactMatch>,
+}
+
+/// Scenarios of a suite selected by the files a change touched.
+///
+/// Printed by `ptybox run-suite --dry-run` (and `ptybox suite`), and
+/// recorded in [`SuiteResult::selection`] when a suite runs with
+/// `--changed-files`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SuiteSelection {
+    /// Every scenario was selected, because there was no impact map or an
+    /// `always` glob matched.
+    pub full_run: bool,
+    /// Changed files the selection was made from.
+    pub changed_files: Vec<String>,
+    /// Scenarios selected.
+    pub selected: u64,
+    /// Scenarios skipped as not affected.
+    pub skipped: u64,
+    /// Impact map entries naming no scenario in the suite.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unknown_mappings: Vec<String>,
+    /// One entry per scenario file, in path order.
+    pub scenarios: Vec<ScenarioSelection>,
+}
+
+/// Current version of the suite summary (`suite.json`).
+pub const SUITE_RESULT_VERSION: u32 = 1;
+
+/// Outcome of one scenario file in a suite.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SuiteScenarioResult {
+    /// Scenario name (`metadata.name`).
+    pub scenario: String,
+    /// Scenario file.
+    pub path: String,
+    /// Run status; `errored` when the file could not be loaded or the run
+    /// errored before producing a result.
+    pub status: RunStatus,
+    /// Run id, when the run produced a result.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<RunId>,
+    /// Wall-clock duration of the scenario, including loading it.
+    pub duration_ms: u64,
+    /// Steps in the run's result.
+    pub steps: u64,
+    /// Steps that passed.
+    pub steps_passed: u64,
+    /// Artifacts directory of the run, when the suite has an artifacts root
+    /// and the run created it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifacts_dir: Option<String>,
+    /// Effective locale and timezone of the matrix cell the scenario ran
+    /// under; omitted without a matrix.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub determinism: Option<DeterminismPolicy>,
+    /// Error the run ended with, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorInfo>,
+}
+
+/// Outcome of running a suite of scenario files.
+///
+/// Produced by `ptybox run-suite` and written to `suite.json` under the
+/// suite's artifacts root.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SuiteResult {
+    /// Summary format version ([`SUITE_RESULT_VERSION`]).
+    pub suite_result_version: u32,
+    /// `passed` when every scenario passed, otherwise `failed`.
+    pub status: RunStatus,
+    /// Scenarios run at once.
+    pub jobs: u64,
+    /// Wall-clock duration of the whole suite.
+    pub duration_ms: u64,
+    /// Scenarios that passed.
+    pub passed: u64,
+    /// Scenarios that failed.
+    pub failed: u64,
+    /// Scenarios that errored or could not be loaded.
+    pub errored: u64,
+    /// Scenarios canceled before finishing.
+    pub canceled: u64,
+    /// One entry per scenario run, in path order (and matrix cell order for
+    /// each scenario).
+    pub scenarios: Vec<SuiteScenarioResult>,
+    /// Which scenarios the changed files selected and why, when the suite
+    /// ran with a changed-files list. Unselected scenarios are not run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selection: Option<SuiteSelection>,
+    /// Locale/timezone cells each scenario ran under; omitted without a
+    /// matrix.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub matrix: Vec<DeterminismPolicy>,
+}
+
+/// Current version of the artifacts quota ledger (`quota.json`).
+pub const QUOTA_LEDGER_VERSION: u32 = 1;
+
+/// Default time after which a run still marked active in the quota ledger
+/// is taken to be abandoned (24 hours).
+pub const DEFAULT_QUOTA_ACTIVE_TIMEOUT_MS: u64 = 86_400_000;
+
+/// Byte limit shared by every run under an artifacts root.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ArtifactsQuota {
+    /// Largest total size of the tracked runs' artifacts directories.
+    pub max_bytes: u64,
+    /// Which completed runs may be evicted to stay under `max_bytes`.
+    #[serde(default)]
+    pub retention: QuotaRetention,
+}
+
+/// Which completed runs a quota may evict. Eviction always takes the
+/// oldest evictable run first.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct QuotaRetention {
+    /// Newest completed runs that are never evicted.
+    #[serde(default)]
+    pub keep_newest: u64,
+    /// Never evict runs that did not pass.
+    #[serde(default)]
+    pub keep_failed: bool,
+    /// Runs still active after this long are treated as abandoned: they
+    /// become evictable, and are dropped from the ledger once their
+    /// directory is gone.
+    #[serde(default = "default_quota_active_timeout_ms")]
+    pub active_timeout_ms: u64,
+}
+
+impl Default for QuotaRetention {
+    fn default() -> Self {
+        Self {
+            keep_newest: 0,
+            keep_failed: false,
+            active_timeout_ms: DEFAULT_QUOTA_ACTIVE_TIMEOUT_MS,
+        }
+    }
+}
+
+fn default_quota_active_timeout_ms() -> u64 {
+    DEFAULT_QUOTA_ACTIVE_TIMEOUT_MS
+}
+
+/// Usage ledger kept as `quota.json` at an artifacts root.
+///
+/// Runs under a root holding a ledger are recorded in it, and evicted per
+/// the ledger's quota, by every process sharing the root.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct QuotaLedger {
+    /// Ledger format version ([`QUOTA_LEDGER_VERSION`]).
+    pub quota_version: u32,
+    /// Byte limit; `None` tracks usage without enforcing a limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quota: Option<ArtifactsQuota>,
+    /// Tracked runs, in admission order.
+    #[serde(default)]
+    pub runs: Vec<QuotaRun>,
+    /// Runs evicted since the ledger was created.
+    #[serde(default)]
+    pub evicted_runs: u64,
+    /// Bytes freed by those evictions.
+    #[serde(default)]
+    pub evicted_bytes: u64,
+}
+
+/// Whether a tracked run is still writing artifacts.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaRunState {
+    /// Admitted and not yet finished.
+    Active,
+    /// Finished; its size is final.
+    Completed,
+}
+
+/// One run tracked by an artifacts quota ledger.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct QuotaRun {
+    /// Run identifier.
+    pub run_id: RunId,
+    /// Artifacts directory, relative to the artifacts root.
+    pub dir: String,
+    /// Whether the run is still writing.
+    pub state: QuotaRunState,
+    /// Size of the artifacts directory: final once completed, last measured
+    /// while active.
+    pub bytes: u64,
+    /// When the run was admitted (ms since Unix epoch).
+    pub started_at_ms: u64,
+    /// When the run finished (ms since Unix epoch).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at_ms: Option<u64>,
+    /// Final run status, when `run.json` was written.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<RunStatus>,
+}
+
+/// Usage of an artifacts root under its quota.
+///
+/// Produced by `ptybox artifacts status` and `ptybox artifacts quota`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ArtifactsStatus {
+    /// Artifacts root.
+    pub root: String,
+    /// Byte limit, when one is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quota: Option<ArtifactsQuota>,
+    /// Total size of the tracked runs.
+    pub used_bytes: u64,
+    /// Bytes left under the limit, when one is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub available_bytes: Option<u64>,
+    /// Runs still writing artifacts.
+    pub active_runs: u64,
+    /// Finished runs.
+    pub completed_runs: u64,
+    /// Runs the retention policy allows evicting.
+    pub evictable_runs: u64,
+    /// Runs evicted since the ledger was created.
+    pub evicted_runs: u64,
+    /// Bytes freed by those evictions.
+    pub evicted_bytes: u64,
+    /// Tracked runs, oldest first, with active runs' sizes re-measured.
+    pub runs: Vec<QuotaRun>,
+}
+
+/// Host a run or bundle was created on, written to `host.json` in run
+/// artifacts and in bundles.
+///
+/// Deliberately coarse: no hostname, user, or paths.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HostFingerprint {
+    /// Operating system (`linux`, `macos`, ...).
+    pub os: String,
+    /// OS family (`unix`, `windows`).
+    pub family: String,
+    /// CPU architecture (`x86_64`, `aarch64`, ...).
+    pub arch: String,
+    /// Available parallelism, when the OS reports it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpus: Option<u64>,
+    /// Version of ptybox that wrote the file.
+    pub ptybox_version: String,
+}
+
+impl HostFingerprint {
+    /// Fingerprint of the current host.
+    #[must_use]
+    pub fn current() -> Self {
+        Self {
+            os: std::env::consts::OS.to_string(),
+            family: std::env::consts::FAMILY.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            cpus: std::thread::available_parallelism()
+                .ok()
+                .map(|count| count.get() as u64),
+            ptybox_version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+/// Remote host a session ran its command on, written to `remote.json`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RemoteHost {
+    /// Host name or address as given in the target.
+    pub host: String,
+    /// SSH port.
+    pub port: u16,
+    /// Remote user.
+    pub user: String,
+    /// Private key used to authenticate.
+    pub key_path: String,
+    /// Host key algorithm (`ssh-ed25519`, `rsa-sha2-512`, ...).
+    pub host_key_type: String,
+    /// Host key fingerprint as `OpenSSH` prints it (`SHA256:<base64>`).
+    pub host_key_fingerprint: String,
+    /// Command line executed on the remote host, without the `env`
+    /// assignments.
+    pub command_line: String,
+    /// Names of the variables set for the command (values are not recorded).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env: Vec<String>,
+}
+
+/// One line of `journal.jsonl`: a run lifecycle event, appended as it
+/// happens so a run whose harness was killed can be rebuilt with
+/// [`recover_run`](crate::artifacts::recover_run).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Milliseconds since the run started.
+    pub at_ms: u64,
+    /// What happened.
+    #[serde(flatten)]
+    pub event: JournalEvent,
+}
+
+/// Run lifecycle event recorded in a [`JournalEntry`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum JournalEvent {
+    /// Artifacts are being written and the child is about to be spawned.
+    RunStarted {
+        /// Run identifier.
+        run_id: RunId,
+        /// Command being run.
+        command: String,
+        /// Command arguments.
+        args: Vec<String>,
+        /// Working directory.
+        cwd: String,
+        /// Run tags.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        tags: Vec<String>,
+    },
+    /// A scenario step started.
+    StepStarted {
+        /// 1-based position of the step in the scenario.
+        step_index: usize,
+        /// Step identifier.
+        step_id: crate::model::StepId,
+        /// Step name.
+        name: String,
+    },
+    /// A scenario step finished, with its result.
+    StepFinished {
+        /// 1-based position of the step in the scenario.
+        step_index: usize,
+        /// The step's result as it appears in `run.json`.
+        result: Box<StepResult>,
+    },
+    /// The run was canceled; the remaining steps are skipped.
+    RunCanceled,
+    /// `run.json` was written with this status.
+    RunFinished {
+        /// Status recorded in `run.json`.
+        status: RunStatus,
+    },
+}
+
+/// Output of the `run.validate_args` probes, written to `args-probe.json`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ArgsProbeRecord {
+    /// One probe per flag, in the order they ran.
+    pub probes: Vec<ArgsProbe>,
+    /// Flags in `run.args` that the `--help` output does not mention.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub missing_flags: Vec<String>,
+}
+
+/// One run of the command with a single probe flag.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ArgsProbe {
+    /// The flag the command ran with (`--help` or `--version`).
+    pub flag: String,
+    /// Exit code, when the command exited on its own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// The command was still running at the probe timeout and was stopped.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
+    /// Terminal output of the probe.
+    pub output: String,
+}
+
+/// Environment variables the child was started with, written to `env.json`.
+///
+/// Built by [`env_record`](crate::policy::env_record) from the effective
+/// env policy. Values are kept for variables set by the policy and for
+/// [`RECORDED_ENV_VALUES`]; every other inherited value is recorded only as
+/// a digest.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EnvRecord {
+    /// Variables passed to the child, sorted by name.
+    pub vars: Vec<EnvRecordVar>,
+}
+
+/// Inherited variables whose values are recorded in [`EnvRecord`]: the
+/// terminal and locale settings that commonly differ between hosts.
+pub const RECORDED_ENV_VALUES: [&str; 10] = [
+    "TERM",
+    "COLORTERM",
+    "NO_COLOR",
+    "LANG",
+    "LC_ALL",
+    "LC_CTYPE",
+    "LC_MESSAGES",
+    "TZ",
+    "COLUMNS",
+    "LINES",
+];
+
+/// One variable in an [`EnvRecord`].
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EnvRecordVar {
+    /// Variable name.
+    pub name: String,
+    /// Where the value came from.
+    pub source: EnvSource,
+    /// The value, for variables set by the policy or listed in
+    /// [`RECORDED_ENV_VALUES`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// `sha256:<hex>` digest of the value.
+    pub digest: String,
+}
+
+/// Origin of an [`EnvRecordVar`].
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EnvSource {
+    /// From `env.set` or a `determinism` setting.
+    Set,
+    /// Inherited from ptybox's environment through `env.allowlist`.
+    Inherited,
+}
+
+/// Comparison of two runs' recorded settings, produced by `ptybox why-differs`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DivergenceReport {
+    /// The first run (`--a`).
+    pub a: DivergenceRun,
+    /// The second run (`--b`).
+    pub b: DivergenceRun,
+    /// Name of the first step whose status differs between the runs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_divergent_step: Option<String>,
+    /// Differences that may explain the divergence, most likely first.
+    pub causes: Vec<DivergenceCause>,
+    /// Artifacts that one or both runs lack, so their settings could not
+    /// be compared (e.g. `host.json` from runs made before it was written).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub not_compared: Vec<String>,
+}
+
+/// One side of a [`DivergenceReport`].
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DivergenceRun {
+    /// Artifacts directory of the run.
+    pub artifacts_dir: String,
+    /// Run identifier.
+    pub run_id: RunId,
+    /// Run status.
+    pub status: RunStatus,
+    /// Error code, when the run did not pass.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
+    /// Wall time of the run in milliseconds.
+    pub duration_ms: u64,
+}
+
+/// A recorded setting or timing that differs between two runs.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DivergenceCause {
+    /// 1-based position in the ranking.
+    pub rank: u32,
+    /// What kind of setting differs.
+    pub category: DivergenceCategory,
+    /// How likely the difference is to change the outcome.
+    pub likelihood: FailureConfidence,
+    /// Dotted path of the setting, e.g. `policy.budgets.max_wait_ms`.
+    pub field: String,
+    /// Value in the first run (`null` when absent).
+    pub a: serde_json::Value,
+    /// Value in the second run (`null` when absent).
+    pub b: serde_json::Value,
+    /// Why the difference matters.
+    pub reason: String,
+}
+
+/// Kind of a [`DivergenceCause`].
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DivergenceCategory {
+    /// Scenario content, command, arguments, or working directory.
+    Scenario,
+    /// ptybox version or the code revision under test.
+    Version,
+    /// Terminal size, output buffering, stderr routing, or terminal
+    /// environment variables.
+    Terminal,
+    /// Environment variables and locale or timezone pinning.
+    Env,
+    /// Policy budgets.
+    Budgets,
+    /// Sandbox, network, filesystem, exec, input, or process policy.
+    Policy,
+    /// Operating system, architecture, or CPU count.
+    Host,
+    /// Run or step durations.
+    Timing,
+}
+
+impl DivergenceCategory {
+    /// Category name as serialized (`terminal`).
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Scenario => "scenario",
+            Self::Version => "version",
+            Self::Terminal => "terminal",
+            Self::Env => "env",
+            Self::Budgets => "budgets",
+            Self::Policy => "policy",
+            Self::Host => "host",
+            Self::Timing => "timing",
+        }
+    }
+}
+
+/// In-toto style statement binding a run's artifact files to its provenance.
+///
+/// Produced by `ptybox attest` for supply-chain tooling; sign it with any
+/// in-toto or DSSE signer.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AttestationStatement {
+    /// Always [`IN_TOTO_STATEMENT_TYPE`].
+    #[serde(rename = "_type")]
+    pub statement_type: String,
+    /// Every artifact file, sorted by path.
+    pub subject: Vec<AttestationSubject>,
+    /// Always [`RUN_ATTESTATION_PREDICATE_TYPE`].
+    #[serde(rename = "predicateType")]
+    pub predicate_type: String,
+    /// What the run was and where it came from.
+    pub predicate: RunAttestation,
+}
+
+/// One attested artifact file.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AttestationSubject {
+    /// Path relative to the artifacts directory, `/`-separated.
+    pub name: String,
+    /// Digests by algorithm; currently only `sha256` (lowercase hex).
+    pub digest: std::collections::BTreeMap<String, String>,
+}
+
+/// Predicate of a [`AttestationStatement`], taken from `run.json`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RunAttestation {
+    /// Run identifier.
+    pub run_id: RunId,
+    /// Overall run status.
+    pub status: RunStatus,
+    /// Command that was executed.
+    pub command: String,
+    /// Arguments passed to the command.
+    pub args: Vec<String>,
+    /// Run provenance (absent for runs recorded before provenance existed).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    /// Scenario metadata, including license/provenance fields (scenario mode).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scenario: Option<ScenarioMetadata>,
+}
+
+/// Resource usage recorded in `resources.json`.
+///
+/// Written when the policy sets `fs.write_quotas`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ResourceUsage {
+    /// Usage of each write quota, sorted by path.
+    pub write_quotas: Vec<WriteQuotaUsage>,
+}
+
+/// Sampled growth of one path under `fs.write_quotas`.
+///
+/// Sizes are apparent file sizes summed over the tree, without following
+/// symlinks; the artifacts directory is not counted.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WriteQuotaUsage {
+    /// Quota path as written in the policy.
+    pub path: String,
+    /// Largest growth allowed over the baseline.
+    pub max_bytes: u64,
+    /// Size of the tree when the child was spawned.
+    pub baseline_bytes: u64,
+    /// Largest growth over the baseline seen by any sample.
+    pub peak_bytes: u64,
+    /// Number of samples taken, including the baseline and the final one.
+    pub samples: u64,
+    /// A sample saw growth above `max_bytes`.
+    pub exceeded: bool,
+}
+
+/// Files under the write-allowlisted directories after a run, recorded in
+/// `fs-manifest.json`.
+///
+/// Written when the policy sets `artifacts.fs_manifest`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FsManifest {
+    /// Regular files, sorted by path. Symlinks and the artifacts directory
+    /// are left out.
+    pub files: Vec<FsManifestEntry>,
+    /// More than [`MAX_FS_MANIFEST_FILES`] files were found; the rest are
+    /// not listed.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+/// Most files listed in an [`FsManifest`].
+pub const MAX_FS_MANIFEST_FILES: usize = 10_000;
+
+/// One file in an [`FsManifest`].
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FsManifestEntry {
+    /// Absolute path.
+    pub path: String,
+    /// Size in bytes.
+    pub size: u64,
+    /// Hex SHA-256 of the contents (absent for files larger than 64 `MiB`
+    /// or that could not be read).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
 }
 
 /// Error information with stable code for automation.
@@ -137,6 +1668,76 @@ pub struct ErrorInfo {
     pub context: Option<serde_json::Value>,
 }
 
+/// Likely cause of a failed run, from
+/// [`classify_failure`](crate::runner::classify_failure).
+///
+/// Tells an agent whether to retry, fix the scenario or policy, or report a
+/// bug in the application under test.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FailureClassification {
+    /// Failure category.
+    pub category: FailureCategory,
+    /// How strongly the evidence points at `category`.
+    pub confidence: FailureConfidence,
+    /// The evidence the category was chosen from.
+    pub reason: String,
+}
+
+/// Category of a run failure.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureCategory {
+    /// Harness or environment trouble (I/O, terminal parsing, internal
+    /// errors); retrying may pass.
+    InfraFlake,
+    /// A runtime, output, snapshot, or step budget ran out.
+    Budget,
+    /// The application exited unexpectedly or died from a crash signal.
+    AppCrash,
+    /// An assertion, wait condition, or replay comparison did not hold.
+    AssertionRegression,
+    /// The policy or scenario is invalid, or the sandbox is unavailable.
+    PolicyMisconfig,
+}
+
+impl FailureCategory {
+    /// Category name as serialized (`infra_flake`).
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::InfraFlake => "infra_flake",
+            Self::Budget => "budget",
+            Self::AppCrash => "app_crash",
+            Self::AssertionRegression => "assertion_regression",
+            Self::PolicyMisconfig => "policy_misconfig",
+        }
+    }
+}
+
+/// Confidence of a [`FailureClassification`].
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureConfidence {
+    /// Weak evidence; treat the category as a hint.
+    Low,
+    /// The error code fits the category but has other plausible causes.
+    Medium,
+    /// The error code or exit signal identifies the category.
+    High,
+}
+
+impl FailureConfidence {
+    /// Confidence level as serialized (`high`).
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        }
+    }
+}
+
 /// Protocol version for JSON/NDJSON output format.
 pub const PROTOCOL_VERSION: u32 = 2;
 /// Run result format version.
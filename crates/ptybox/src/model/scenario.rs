@@ -1,7 +1,8 @@
-use crate::model::policy::Policy;
+use crate::model::policy::{ChildSignal, Policy};
 use crate::model::terminal::TerminalSize;
-use crate::model::{RunId, StepId};
+use crate::model::{RunId, Stability, StepId};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Scenario format version.
 pub const SCENARIO_VERSION: u32 = 1;
@@ -26,6 +27,95 @@ pub struct Scenario {
     pub run: RunConfig,
     /// Ordered list of steps to execute.
     pub steps: Vec<Step>,
+    /// Scenario-wide defaults applied to every step at load time.
+    #[serde(default, skip_serializing_if = "ScenarioDefaults::is_empty")]
+    pub defaults: ScenarioDefaults,
+    /// Conditions checked on every observation for the whole run; the first
+    /// one to hold fails the run.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub watchers: Vec<Watcher>,
+    /// Run-level assertions checked once after the steps and exit handling,
+    /// whatever the steps' outcome; any failure fails the run. Accepts the
+    /// file assertions (`file_exists`, `file_contains`),
+    /// `capture_checksum_equals`, and every predicate type.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_run: Vec<Assertion>,
+    /// Deprecated alias of `post_run`; [`Scenario::apply_defaults`] appends
+    /// these entries to `post_run`. Write `post_run` instead.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post: Vec<Assertion>,
+    /// Files and directory trees generated before the command is spawned.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fixtures: Vec<FixtureGenerator>,
+}
+
+impl Scenario {
+    /// Append `defaults.assert_each_step` to every step that has not opted
+    /// out via `skip_default_assertions`, marking the copies `inherited`,
+    /// compile each step's `expected_screen` into an `expected_screen`
+    /// assertion, and move the deprecated `post` entries to the end of
+    /// `post_run`.
+    ///
+    /// The defaults, inline screens, and `post` entries are consumed, so
+    /// applying twice is a no-op. Loaders call this; callers building scenarios in code may call
+    /// it directly.
+    pub fn apply_defaults(&mut self) {
+        let post = std::mem::take(&mut self.post);
+        self.post_run.extend(post);
+        for step in &mut self.steps {
+            if let Some(expected) = step.expected_screen.take() {
+                step.assert.push(Assertion::expected_screen(
+                    &expected.lines,
+                    expected.start_line,
+                ));
+            }
+        }
+        let defaults = std::mem::take(&mut self.defaults.assert_each_step);
+        if defaults.is_empty() {
+            return;
+        }
+        for step in self
+            .steps
+            .iter_mut()
+            .filter(|step| !step.skip_default_assertions)
+        {
+            step.assert
+                .extend(defaults.iter().cloned().map(|mut assertion| {
+                    assertion.inherited = true;
+                    assertion
+                }));
+        }
+    }
+
+    /// Insert each `(index, step)` before the step currently at `index`, or at
+    /// the end when `index` is past the last step. Indices refer to the steps
+    /// as they were before the call; inserts sharing an index keep their order.
+    pub fn insert_steps(&mut self, mut inserts: Vec<(usize, Step)>) {
+        inserts.sort_by_key(|(index, _)| *index);
+        let mut pending = inserts.into_iter().peekable();
+        let original = std::mem::take(&mut self.steps);
+        for (index, step) in original.into_iter().enumerate() {
+            while let Some((_, inserted)) = pending.next_if(|(at, _)| *at <= index) {
+                self.steps.push(inserted);
+            }
+            self.steps.push(step);
+        }
+        self.steps.extend(pending.map(|(_, inserted)| inserted));
+    }
+}
+
+/// Scenario-level defaults shared by all steps.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ScenarioDefaults {
+    /// Assertions appended to every step (e.g. "no panic text on screen").
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assert_each_step: Vec<Assertion>,
+}
+
+impl ScenarioDefaults {
+    fn is_empty(&self) -> bool {
+        self.assert_each_step.is_empty()
+    }
 }
 
 /// Scenario metadata.
@@ -35,6 +125,18 @@ pub struct ScenarioMetadata {
     pub name: String,
     /// Optional description.
     pub description: Option<String>,
+    /// Free-form labels copied into `run.json` and the run index.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Who wrote or maintains the scenario.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Where the scenario came from (repository URL, ticket, upstream suite).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Revision of the scenario at its source (commit, tag, version).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<String>,
 }
 
 /// Command execution configuration.
@@ -50,6 +152,278 @@ pub struct RunConfig {
     pub initial_size: TerminalSize,
     /// Policy configuration (inline or file reference).
     pub policy: PolicyRef,
+    /// Route the child's stderr to a separate pipe instead of the PTY. It is
+    /// reported as `stderr_delta` on observations and written to `stderr.log`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub separate_stderr: bool,
+    /// Buffering of PTY output between observations.
+    #[serde(default, skip_serializing_if = "OutputBufferConfig::is_default")]
+    pub output_buffer: OutputBufferConfig,
+    /// How existing output is laid out when the terminal is resized.
+    #[serde(default, skip_serializing_if = "ResizeConfig::is_default")]
+    pub resize: ResizeConfig,
+    /// Run the command on this host over SSH instead of locally (`ssh`
+    /// feature). It must be allowed by `policy.remote`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote: Option<RemoteTarget>,
+    /// Run the command with `--help` and `--version` before the session
+    /// starts, under the same policy, and record the output in
+    /// `args-probe.json`. Local runs only.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub validate_args: bool,
+    /// Include per-cell colors and attributes (`cells`) in every observed
+    /// screen, so assertions and artifacts can see highlights and colors.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub capture_attributes: bool,
+}
+
+/// Default SSH port of a [`RemoteTarget`].
+pub const DEFAULT_SSH_PORT: u16 = 22;
+
+/// SSH host a remote session runs its command on.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteTarget {
+    /// Host name or address.
+    pub host: String,
+    /// SSH port.
+    #[serde(default = "default_ssh_port")]
+    pub port: u16,
+    /// Remote user to log in as.
+    pub user: String,
+    /// Private key file to authenticate with (absolute path).
+    pub key_path: String,
+}
+
+const fn default_ssh_port() -> u16 {
+    DEFAULT_SSH_PORT
+}
+
+/// Default capacity of the PTY output buffer, in bytes.
+pub const DEFAULT_OUTPUT_BUFFER_BYTES: u64 = 1024 * 1024;
+
+/// Bounded buffer between the session's PTY reader thread and `observe`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputBufferConfig {
+    /// Maximum bytes of unobserved output held (must be positive).
+    #[serde(default = "default_output_buffer_bytes")]
+    pub capacity_bytes: u64,
+    /// What the reader does when the buffer is full.
+    #[serde(default)]
+    pub on_full: OutputOverflow,
+}
+
+impl OutputBufferConfig {
+    /// Whether this is the default configuration (omitted from JSON).
+    #[must_use]
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Default for OutputBufferConfig {
+    fn default() -> Self {
+        Self {
+            capacity_bytes: DEFAULT_OUTPUT_BUFFER_BYTES,
+            on_full: OutputOverflow::default(),
+        }
+    }
+}
+
+const fn default_output_buffer_bytes() -> u64 {
+    DEFAULT_OUTPUT_BUFFER_BYTES
+}
+
+/// Reader behavior when the PTY output buffer is full.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputOverflow {
+    /// Stop reading until the buffer drains; the kernel PTY buffer then
+    /// blocks the child's writes. No output is lost.
+    #[default]
+    Backpressure,
+    /// Keep reading and discard output that does not fit.
+    Drop,
+}
+
+/// Cumulative PTY output buffer counters for a session.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputFlowStats {
+    /// Times the buffer filled up before being observed.
+    pub overflows: u64,
+    /// Bytes discarded because the buffer was full (`drop` mode only).
+    pub dropped_bytes: u64,
+}
+
+/// Terminal behavior on resize.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResizeConfig {
+    /// What happens to lines on the primary screen.
+    #[serde(default)]
+    pub reflow: ResizeReflow,
+    /// Lines kept above the screen. Lines scrolled off by a resize or by
+    /// output go here and come back when the screen grows; `rewrap` rewraps
+    /// them too. `0` keeps none.
+    #[serde(default)]
+    pub scrollback_lines: u32,
+}
+
+impl ResizeConfig {
+    /// Whether this is the default configuration (omitted from JSON).
+    #[must_use]
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// How lines on the primary screen are laid out after a resize.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResizeReflow {
+    /// xterm: rows keep their cells and are cut at the new width. A shorter
+    /// screen scrolls lines off the top so the cursor row stays visible.
+    #[default]
+    Truncate,
+    /// VTE, kitty, and other reflowing terminals: soft-wrapped lines are
+    /// joined and wrapped again at the new width, and the cursor moves with
+    /// its character.
+    Rewrap,
+}
+
+/// Test data written by the runner before spawning the command.
+///
+/// Generated content depends only on the generator, so the same scenario
+/// always produces the same bytes. Existing files at generated paths are
+/// overwritten.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixtureGenerator {
+    /// Absolute path of the file or tree root; must be inside
+    /// `policy.fs.allowed_write`.
+    pub path: String,
+    /// What to generate.
+    #[serde(flatten)]
+    pub kind: FixtureKind,
+}
+
+/// Kind of generated fixture.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FixtureKind {
+    /// One file of a given size.
+    File(FileFixture),
+    /// A directory tree of uniform depth and fanout.
+    Tree(TreeFixture),
+}
+
+/// A generated file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileFixture {
+    /// Size in bytes; also accepts `"512B"`, `"64KB"`, `"10MB"`, `"1GB"`
+    /// (powers of 1000) or `"64KiB"`, `"10MiB"`, `"1GiB"` (powers of 1024).
+    #[serde(deserialize_with = "deserialize_byte_size")]
+    pub size: u64,
+    /// File content.
+    #[serde(default)]
+    pub fill: FixtureFill,
+}
+
+/// A generated directory tree.
+///
+/// Every directory holds `fanout` files named `file-<n>`; directories above
+/// `depth` also hold `fanout` subdirectories named `dir-<n>`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TreeFixture {
+    /// Levels of subdirectories below the root.
+    pub depth: u32,
+    /// Files and subdirectories per directory.
+    pub fanout: u32,
+    /// Size of each file, in bytes or with a unit as for
+    /// [`FileFixture::size`].
+    #[serde(default, deserialize_with = "deserialize_byte_size")]
+    pub file_size: u64,
+    /// Content of each file; a random fill continues one stream across
+    /// the files in creation order.
+    #[serde(default)]
+    pub fill: FixtureFill,
+}
+
+/// Content of generated files.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum FixtureFill {
+    /// Zero bytes (`zeros`).
+    #[default]
+    Zeros,
+    /// Pseudo-random bytes from a seed (`random(<seed>)`).
+    Random(u64),
+}
+
+impl TryFrom<String> for FixtureFill {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        if value == "zeros" {
+            return Ok(Self::Zeros);
+        }
+        value
+            .strip_prefix("random(")
+            .and_then(|rest| rest.strip_suffix(')'))
+            .and_then(|seed| seed.trim().parse().ok())
+            .map(Self::Random)
+            .ok_or_else(|| format!("invalid fill '{value}': expected zeros or random(<seed>)"))
+    }
+}
+
+impl From<FixtureFill> for String {
+    fn from(fill: FixtureFill) -> Self {
+        match fill {
+            FixtureFill::Zeros => "zeros".to_string(),
+            FixtureFill::Random(seed) => format!("random({seed})"),
+        }
+    }
+}
+
+/// Parse a byte count: an integer, or a string with a `B`, `KB`, `MB`, `GB`,
+/// `KiB`, `MiB`, or `GiB` suffix.
+///
+/// # Errors
+/// Returns a message for an unknown unit or a count that overflows `u64`.
+pub fn parse_byte_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let split = value
+        .find(|ch: char| !ch.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let multiplier: u64 = match unit.trim() {
+        "" | "B" => 1,
+        "KB" => 1_000,
+        "MB" => 1_000_000,
+        "GB" => 1_000_000_000,
+        "KiB" => 1 << 10,
+        "MiB" => 1 << 20,
+        "GiB" => 1 << 30,
+        other => return Err(format!("unknown size unit '{other}' in '{value}'")),
+    };
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|number| number.checked_mul(multiplier))
+        .ok_or_else(|| format!("invalid size '{value}'"))
+}
+
+fn deserialize_byte_size<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ByteSize {
+        Bytes(u64),
+        Text(String),
+    }
+    match ByteSize::deserialize(deserializer)? {
+        ByteSize::Bytes(bytes) => Ok(bytes),
+        ByteSize::Text(text) => parse_byte_size(&text).map_err(serde::de::Error::custom),
+    }
 }
 
 /// Policy reference - either inline or file path.
@@ -81,8 +455,146 @@ pub struct Step {
     pub timeout_ms: u64,
     /// Number of retries for flaky assertions.
     pub retries: u32,
+    /// Opt out of `defaults.assert_each_step` for this step.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub skip_default_assertions: bool,
+    /// Capture an intermediate screen at most this often (ms) while the step
+    /// reads output or waits, so changes between observations are kept.
+    /// At least [`MIN_SAMPLE_INTERVAL_MS`]; unset disables sampling.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample_interval_ms: Option<u64>,
+    /// Inline golden screen, compiled into an `expected_screen` assertion
+    /// when the scenario is loaded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_screen: Option<ExpectedScreen>,
+    /// Keep the step's output or final screen as a named artifact.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capture: Option<StepCapture>,
+    /// Environment variables added for processes the step spawns besides
+    /// the child (currently `process.pre_kill_hook`). Each name must be in
+    /// `policy.env.allowlist`; the child's own environment is unchanged.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env_overlay: BTreeMap<String, String>,
+    /// Caller data such as ticket IDs or owners, copied verbatim into the
+    /// step result. At most [`MAX_STEP_METADATA_BYTES`] as JSON.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, serde_json::Value>,
+    /// Variables captured from the step's output once it passes, for later
+    /// steps to use as `${var:NAME}` in their action payloads.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vars: Vec<VarCapture>,
+}
+
+impl Step {
+    /// Content-derived identity of the step: the first 16 hex digits of the
+    /// SHA-256 of its `name`, `action`, and `assert` as compact JSON with
+    /// sorted keys.
+    ///
+    /// Unlike the `id`, the key is the same in every run of an unchanged
+    /// step and does not move when other steps are added or removed, so it
+    /// tracks a step across runs, replays, and scenario edits. Identical
+    /// steps share a key; consumers pair repeats by occurrence.
+    #[must_use]
+    pub fn content_key(&self) -> String {
+        let mut value = serde_json::json!({
+            "name": self.name,
+            "action": self.action,
+            "assert": self.assert,
+        });
+        value.sort_all_objects();
+        let bytes = serde_json::to_vec(&value).unwrap_or_default();
+        let mut digest = crate::util::sha256_hex(&bytes);
+        digest.truncate(16);
+        digest
+    }
+}
+
+/// Step data written to `captures/<name>.txt` in the artifacts.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StepCapture {
+    /// File name without `.txt`: letters, digits, `.`, `_`, and `-`, not
+    /// starting with `.`, unique within the scenario.
+    pub name: String,
+    /// What to capture.
+    #[serde(default)]
+    pub source: CaptureSource,
+}
+
+/// Data kept by a [`StepCapture`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureSource {
+    /// Raw output read during the step (every attempt), as in `transcript.log`.
+    #[default]
+    Transcript,
+    /// Screen text after the step's last completed attempt.
+    Screen,
 }
 
+/// A variable captured from the output a step produced.
+///
+/// `transcript_matches` is searched in the output read since the step's
+/// last input, with escape sequences removed. The value is the first
+/// capture group when the pattern has one, otherwise the whole match. A
+/// step whose output does not match fails.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct VarCapture {
+    /// Variable name: letters, digits, and `_`.
+    pub name: String,
+    /// Rust regex pattern the output must match.
+    pub transcript_matches: String,
+    /// Type the value is converted to where it is used.
+    #[serde(default, rename = "as")]
+    pub var_type: VarType,
+}
+
+/// Type of a captured variable.
+///
+/// Values are captured as text and converted where a step uses them; a
+/// value that does not convert fails that step with `E_PROTOCOL`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VarType {
+    /// Text, unchanged.
+    #[default]
+    String,
+    /// A signed 64-bit integer; surrounding whitespace is ignored.
+    Int,
+    /// `true`/`false`, `yes`/`no`, or `1`/`0` (case-insensitive).
+    Bool,
+}
+
+impl VarType {
+    /// Type name as serialized (`int`).
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Int => "int",
+            Self::Bool => "bool",
+        }
+    }
+}
+
+/// Expected screen lines embedded in a step.
+///
+/// Each line is a pattern where `?` matches any one cell and `*` any run of
+/// cells; see [`crate::predicate`] for the full matching rules.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExpectedScreen {
+    /// Line patterns, top to bottom.
+    pub lines: Vec<String>,
+    /// Screen line (0-based) the first pattern applies to.
+    #[serde(default)]
+    pub start_line: u64,
+}
+
+/// Smallest accepted [`Step::sample_interval_ms`].
+pub const MIN_SAMPLE_INTERVAL_MS: u64 = 10;
+
+/// Largest accepted [`Step::metadata`], in bytes of compact JSON.
+pub const MAX_STEP_METADATA_BYTES: u64 = 4 * 1024;
+
 /// Action to send to the terminal session.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Action {
@@ -101,7 +613,7 @@ pub enum ActionType {
     Key,
     /// Type text (payload: `{text: "hello"}`).
     Text,
-    /// Resize terminal (payload: `{rows: 24, cols: 80}`).
+    /// Resize terminal (payload: `{rows: 24, cols: 80}`, optional `pixel_width`/`pixel_height`).
     Resize,
     /// Wait for condition (payload: `{condition: {type: "screen_contains", payload: {...}}}`).
     Wait,
@@ -109,16 +621,108 @@ pub enum ActionType {
     Observe,
     /// Terminate process.
     Terminate,
+    /// Write bytes verbatim (payload: `{hex: "1b5b3f3130303468"}` or `{base64: "..."}`).
+    /// Requires `input.allow_raw_bytes` in the policy.
+    Raw,
+    /// Hold a key with auto-repeat (payload: `{key: "Down", duration_ms: 500, repeat_hz: 30}`).
+    /// The whole burst is one step; each burst is recorded in `key-holds.jsonl`.
+    HoldKey,
+    /// Set or clear PTY line-discipline flags (payload: `{echo: false, icanon: true, isig: true}`,
+    /// any subset). The resulting flags are recorded in `termios.jsonl`.
+    Termios,
+    /// Send a signal to the child's process group (payload: `{signal: "SIGINT"}`).
+    /// Requires the signal in `process.allowed_signals`; recorded as a `signal_sent` event.
+    Signal,
+    /// Driver-only query for the session log (payload: `{limit: 20}`, optional). Answered
+    /// with `history` instead of an observation; not recorded as a step.
+    History,
+    /// Driver-only: push unsolicited `event` frames between responses (payload:
+    /// `{events: ["screen_changed", "bell", "process_exited"], max_unacked: 64,
+    /// poll_interval_ms: 50}`, all optional; `events: []` unsubscribes). Not
+    /// recorded as a step.
+    Subscribe,
+}
+
+impl ActionType {
+    /// Every action type, in documentation order.
+    pub const ALL: [Self; 12] = [
+        Self::Key,
+        Self::Text,
+        Self::Resize,
+        Self::Wait,
+        Self::Observe,
+        Self::Terminate,
+        Self::Raw,
+        Self::HoldKey,
+        Self::Termios,
+        Self::Signal,
+        Self::History,
+        Self::Subscribe,
+    ];
+
+    /// Stability of this action type, as reported by `protocol-help`.
+    #[must_use]
+    pub const fn stability(&self) -> Stability {
+        match self {
+            Self::Key
+            | Self::Text
+            | Self::Resize
+            | Self::Wait
+            | Self::Observe
+            | Self::Terminate
+            | Self::Raw
+            | Self::HoldKey
+            | Self::Termios
+            | Self::Signal => Stability::Stable,
+            Self::History | Self::Subscribe => Stability::Experimental,
+        }
+    }
 }
 
 /// Assertion to verify terminal state.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Assertion {
-    /// Assertion type (e.g., `screen_contains`, `cursor_at`).
+    /// Predicate type (e.g., `screen_contains`, `cursor_at`); see [`crate::predicate`].
     #[serde(rename = "type")]
     pub assertion_type: String,
     /// Type-specific payload.
     pub payload: serde_json::Value,
+    /// True when copied from `defaults.assert_each_step`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub inherited: bool,
+}
+
+/// Wait condition evaluated by [`Session::wait_until`](crate::session::Session::wait_until).
+///
+/// Serialized as `{type, payload}`; this is the `condition` object carried by
+/// wait actions and watchers. `type` is any [predicate](crate::predicate)
+/// type, the same vocabulary as [`Assertion`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Condition {
+    /// Predicate type (e.g., `screen_contains`, `screen_matches`, `process_exited`).
+    #[serde(rename = "type")]
+    pub condition_type: String,
+    /// Type-specific payload.
+    #[serde(default)]
+    pub payload: serde_json::Value,
+}
+
+/// Background condition that fails the run as soon as it holds.
+///
+/// Watchers are evaluated against every observation, including the
+/// intermediate polls of waits, so transient screens between steps are
+/// caught. Any [predicate](crate::predicate) type may be used.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Watcher {
+    /// Name reported when the watcher triggers.
+    pub name: String,
+    /// Condition that fails the run when it holds.
+    pub condition: Condition,
+}
+
+impl Watcher {
+    /// Stability of the watcher shape, as reported by `protocol-help`.
+    pub const STABILITY: Stability = Stability::Stable;
 }
 
 /// Terminal state observation returned by the session.
@@ -139,10 +743,53 @@ pub struct Observation {
     pub screen: crate::model::ScreenSnapshot,
     /// Incremental terminal output since last observation.
     pub transcript_delta: Option<String>,
+    /// Incremental stderr output since last observation (separate-stderr
+    /// sessions only; invalid UTF-8 is replaced).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stderr_delta: Option<String>,
+    /// Output buffer counters since the session started; present once the
+    /// buffer has overflowed at least once.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_flow: Option<OutputFlowStats>,
+    /// The observe timeout expired while output was still arriving; observe
+    /// again before acting on this screen.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub read_truncated: bool,
+    /// PTY bytes read by this observation.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub read_bytes: u64,
+    /// PTY bytes discarded by a full `drop`-mode buffer since the previous
+    /// observation.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub read_dropped_bytes: u64,
+    /// UI widgets detected on the screen (see [`crate::terminal::widgets`]).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub widgets: Vec<crate::model::Widget>,
     /// Events captured during observation.
     pub events: Vec<Event>,
 }
 
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+impl Observation {
+    /// Stability of the observation shape, as reported by `protocol-help`.
+    pub const STABILITY: Stability = Stability::Stable;
+
+    /// Bytes of new output carried by this observation (PTY plus stderr),
+    /// as counted against `max_output_bytes`.
+    #[must_use]
+    pub fn output_bytes(&self) -> u64 {
+        [&self.transcript_delta, &self.stderr_delta]
+            .into_iter()
+            .flatten()
+            .map(|delta| delta.len() as u64)
+            .sum()
+    }
+}
+
 /// Event emitted during observation.
 ///
 /// Events capture notable occurrences like title changes or unsupported sequences.
@@ -162,6 +809,9 @@ pub struct Event {
 // =============================================================================
 
 impl Action {
+    /// Stability of the action envelope, as reported by `protocol-help`.
+    pub const STABILITY: Stability = Stability::Stable;
+
     /// Create a key press action.
     ///
     /// # Examples
@@ -263,6 +913,72 @@ impl Action {
         }
     }
 
+    /// Create a raw byte action (payload encoded as hex).
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let action = Action::raw(b"\x1b[?1004h");
+    /// ```
+    #[must_use]
+    pub fn raw(bytes: &[u8]) -> Self {
+        Self {
+            action_type: ActionType::Raw,
+            payload: serde_json::json!({ "hex": crate::util::encode_hex(bytes) }),
+        }
+    }
+
+    /// Create a held-key action that repeats `key` at `repeat_hz` for `duration_ms`.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let action = Action::hold_key("Down", 500, 30);
+    /// ```
+    #[must_use]
+    pub fn hold_key(key: &str, duration_ms: u64, repeat_hz: u32) -> Self {
+        Self {
+            action_type: ActionType::HoldKey,
+            payload: serde_json::json!({
+                "key": key,
+                "duration_ms": duration_ms,
+                "repeat_hz": repeat_hz,
+            }),
+        }
+    }
+
+    /// Create a termios action; `None` leaves a flag unchanged.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let action = Action::termios(Some(false), None, None); // echo off
+    /// ```
+    #[must_use]
+    pub fn termios(echo: Option<bool>, icanon: Option<bool>, isig: Option<bool>) -> Self {
+        let mut payload = serde_json::Map::new();
+        for (flag, value) in [("echo", echo), ("icanon", icanon), ("isig", isig)] {
+            if let Some(value) = value {
+                payload.insert(flag.to_string(), serde_json::Value::Bool(value));
+            }
+        }
+        Self {
+            action_type: ActionType::Termios,
+            payload: serde_json::Value::Object(payload),
+        }
+    }
+
+    /// Create a signal action.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let action = Action::signal(ChildSignal::Int);
+    /// ```
+    #[must_use]
+    pub fn signal(signal: ChildSignal) -> Self {
+        Self {
+            action_type: ActionType::Signal,
+            payload: serde_json::json!({ "signal": signal }),
+        }
+    }
+
     /// Create a process termination action.
     #[must_use]
     pub fn terminate() -> Self {
@@ -271,6 +987,88 @@ impl Action {
             payload: serde_json::json!({}),
         }
     }
+
+    /// Create a driver `history` query for at most `limit` entries (all kept
+    /// entries when `None`).
+    #[must_use]
+    pub fn history(limit: Option<u64>) -> Self {
+        let payload = limit.map_or_else(
+            || serde_json::json!({}),
+            |limit| serde_json::json!({ "limit": limit }),
+        );
+        Self {
+            action_type: ActionType::History,
+            payload,
+        }
+    }
+
+    /// Create a driver `subscribe` request for `events` (an empty slice
+    /// unsubscribes), with the driver's default window and poll interval
+    /// when `None`.
+    #[must_use]
+    pub fn subscribe(
+        events: &[crate::model::DriverEventKind],
+        max_unacked: Option<u64>,
+        poll_interval_ms: Option<u64>,
+    ) -> Self {
+        let mut payload = serde_json::Map::new();
+        payload.insert("events".to_string(), serde_json::json!(events));
+        if let Some(max_unacked) = max_unacked {
+            payload.insert("max_unacked".to_string(), max_unacked.into());
+        }
+        if let Some(poll_interval_ms) = poll_interval_ms {
+            payload.insert("poll_interval_ms".to_string(), poll_interval_ms.into());
+        }
+        Self {
+            action_type: ActionType::Subscribe,
+            payload: serde_json::Value::Object(payload),
+        }
+    }
+}
+
+// =============================================================================
+// Condition Constructors
+// =============================================================================
+
+impl Condition {
+    /// Stability of the condition envelope, as reported by `protocol-help`.
+    pub const STABILITY: Stability = Stability::Stable;
+
+    /// Wait until the screen contains `text`.
+    #[must_use]
+    pub fn screen_contains(text: &str) -> Self {
+        Self {
+            condition_type: "screen_contains".to_string(),
+            payload: serde_json::json!({ "text": text }),
+        }
+    }
+
+    /// Wait until the screen matches the regex `pattern`.
+    #[must_use]
+    pub fn screen_matches(pattern: &str) -> Self {
+        Self {
+            condition_type: "screen_matches".to_string(),
+            payload: serde_json::json!({ "pattern": pattern }),
+        }
+    }
+
+    /// Wait until the cursor is at (`row`, `col`).
+    #[must_use]
+    pub fn cursor_at(row: u16, col: u16) -> Self {
+        Self {
+            condition_type: "cursor_at".to_string(),
+            payload: serde_json::json!({ "row": row, "col": col }),
+        }
+    }
+
+    /// Wait until the child process exits.
+    #[must_use]
+    pub fn process_exited() -> Self {
+        Self {
+            condition_type: "process_exited".to_string(),
+            payload: serde_json::json!({}),
+        }
+    }
 }
 
 // =============================================================================
@@ -278,6 +1076,9 @@ impl Action {
 // =============================================================================
 
 impl Assertion {
+    /// Stability of the assertion envelope, as reported by `protocol-help`.
+    pub const STABILITY: Stability = Stability::Stable;
+
     /// Assert that the screen contains the given text.
     ///
     /// # Examples
@@ -289,6 +1090,7 @@ impl Assertion {
         Self {
             assertion_type: "screen_contains".to_string(),
             payload: serde_json::json!({"text": text}),
+            inherited: false,
         }
     }
 
@@ -303,6 +1105,29 @@ impl Assertion {
         Self {
             assertion_type: "not_contains".to_string(),
             payload: serde_json::json!({"text": text}),
+            inherited: false,
+        }
+    }
+
+    /// Assert that `text` never appears on screen.
+    ///
+    /// With `for_ms`, the runner keeps observing for that window after the
+    /// action and fails if any sampled snapshot contains the text.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let assertion = Assertion::screen_not_contains("panic", Some(500));
+    /// ```
+    #[must_use]
+    pub fn screen_not_contains(text: &str, for_ms: Option<u64>) -> Self {
+        let payload = match for_ms {
+            Some(for_ms) => serde_json::json!({"text": text, "for_ms": for_ms}),
+            None => serde_json::json!({"text": text}),
+        };
+        Self {
+            assertion_type: "screen_not_contains".to_string(),
+            payload,
+            inherited: false,
         }
     }
 
@@ -317,6 +1142,7 @@ impl Assertion {
         Self {
             assertion_type: "regex_match".to_string(),
             payload: serde_json::json!({"pattern": pattern}),
+            inherited: false,
         }
     }
 
@@ -331,6 +1157,7 @@ impl Assertion {
         Self {
             assertion_type: "cursor_at".to_string(),
             payload: serde_json::json!({"row": row, "col": col}),
+            inherited: false,
         }
     }
 
@@ -345,6 +1172,7 @@ impl Assertion {
         Self {
             assertion_type: "line_equals".to_string(),
             payload: serde_json::json!({"line": line, "text": text}),
+            inherited: false,
         }
     }
 
@@ -359,6 +1187,7 @@ impl Assertion {
         Self {
             assertion_type: "line_contains".to_string(),
             payload: serde_json::json!({"line": line, "text": text}),
+            inherited: false,
         }
     }
 
@@ -373,6 +1202,7 @@ impl Assertion {
         Self {
             assertion_type: "line_matches".to_string(),
             payload: serde_json::json!({"line": line, "pattern": pattern}),
+            inherited: false,
         }
     }
 
@@ -382,6 +1212,7 @@ impl Assertion {
         Self {
             assertion_type: "screen_empty".to_string(),
             payload: serde_json::json!({}),
+            inherited: false,
         }
     }
 
@@ -391,6 +1222,7 @@ impl Assertion {
         Self {
             assertion_type: "cursor_visible".to_string(),
             payload: serde_json::json!({}),
+            inherited: false,
         }
     }
 
@@ -400,6 +1232,7 @@ impl Assertion {
         Self {
             assertion_type: "cursor_hidden".to_string(),
             payload: serde_json::json!({}),
+            inherited: false,
         }
     }
 
@@ -418,6 +1251,129 @@ impl Assertion {
         Self {
             assertion_type: "exit_code".to_string(),
             payload: serde_json::json!({"code": code}),
+            inherited: false,
+        }
+    }
+
+    /// Assert, after the process exits, that a regular file exists at
+    /// `path` (absolute, inside the fs allowlists). `post_run` only.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let assertion = Assertion::file_exists("/tmp/work/out.txt");
+    /// ```
+    #[must_use]
+    pub fn file_exists(path: &str) -> Self {
+        Self {
+            assertion_type: "file_exists".to_string(),
+            payload: serde_json::json!({"path": path}),
+            inherited: false,
+        }
+    }
+
+    /// Assert, after the process exits, that the file at `path` contains
+    /// `text`. `post_run` only.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let assertion = Assertion::file_contains("/tmp/work/out.txt", "done");
+    /// ```
+    #[must_use]
+    pub fn file_contains(path: &str, text: &str) -> Self {
+        Self {
+            assertion_type: "file_contains".to_string(),
+            payload: serde_json::json!({"path": path, "text": text}),
+            inherited: false,
+        }
+    }
+
+    /// Assert, after the run, that the SHA-256 of the step capture named
+    /// `capture` (or of the whole transcript when `None`) is `sha256`.
+    /// `post` only; needs an artifacts directory.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let assertion = Assertion::capture_checksum_equals(Some("report"), &digest);
+    /// ```
+    #[must_use]
+    pub fn capture_checksum_equals(capture: Option<&str>, sha256: &str) -> Self {
+        let payload = match capture {
+            Some(capture) => serde_json::json!({ "capture": capture, "sha256": sha256 }),
+            None => serde_json::json!({ "sha256": sha256 }),
+        };
+        Self {
+            assertion_type: "capture_checksum_equals".to_string(),
+            payload,
+            inherited: false,
+        }
+    }
+
+    /// Assert that the process has written `text` to stderr.
+    ///
+    /// Checks everything captured on the separate stderr pipe so far, so the
+    /// run must set `separate_stderr`.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let assertion = Assertion::stderr_contains("warning:");
+    /// ```
+    #[must_use]
+    pub fn stderr_contains(text: &str) -> Self {
+        Self {
+            assertion_type: "stderr_contains".to_string(),
+            payload: serde_json::json!({"text": text}),
+            inherited: false,
+        }
+    }
+
+    /// Assert that the process has written nothing to stderr.
+    ///
+    /// Like [`Assertion::stderr_contains`], the run must set `separate_stderr`.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let assertion = Assertion::stderr_empty();
+    /// ```
+    #[must_use]
+    pub fn stderr_empty() -> Self {
+        Self {
+            assertion_type: "stderr_empty".to_string(),
+            payload: serde_json::json!({}),
+            inherited: false,
+        }
+    }
+
+    /// Assert that the step responded within `ms` milliseconds.
+    ///
+    /// For input actions the latency runs from sending the input to the
+    /// first output after it; for wait actions, until the condition matched.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let assertion = Assertion::responded_within_ms(200);
+    /// ```
+    #[must_use]
+    pub fn responded_within_ms(ms: u64) -> Self {
+        Self {
+            assertion_type: "responded_within_ms".to_string(),
+            payload: serde_json::json!({"ms": ms}),
+            inherited: false,
+        }
+    }
+
+    /// Assert that screen lines from `start_line` down match `lines`, where
+    /// `?` matches any one cell and `*` any run of cells.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let assertion = Assertion::expected_screen(&["Welcome, *".to_string()], 0);
+    /// ```
+    #[must_use]
+    pub fn expected_screen(lines: &[String], start_line: u64) -> Self {
+        Self {
+            assertion_type: "expected_screen".to_string(),
+            payload: serde_json::json!({"lines": lines, "start_line": start_line}),
+            inherited: false,
         }
     }
 }
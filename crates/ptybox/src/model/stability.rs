@@ -0,0 +1,28 @@
+//! Stability annotations for protocol items.
+//!
+//! Each action type, predicate, and driver message type carries a
+//! [`Stability`] next to its definition ([`ActionType::stability`],
+//! [`PredicateSpec::stability`], and the `STABILITY` constants on the message
+//! types); `ptybox protocol-help` reports them as-is.
+//!
+//! [`ActionType::stability`]: crate::model::ActionType::stability
+//! [`PredicateSpec::stability`]: crate::predicate::PredicateSpec::stability
+
+use serde::Serialize;
+
+/// How settled a protocol item is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Stability {
+    /// Covered by the protocol version; changes need a version bump.
+    Stable,
+    /// May change or be removed without a protocol version bump.
+    Experimental,
+    /// Still accepted, but scheduled for removal.
+    Deprecated {
+        /// Release that deprecated the item.
+        since: &'static str,
+        /// What to use instead.
+        replacement: &'static str,
+    },
+}
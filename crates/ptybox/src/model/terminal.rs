@@ -1,26 +1,64 @@
-use crate::model::SnapshotId;
+use crate::model::{SnapshotId, Stability};
 use serde::{Deserialize, Serialize};
 
 /// Version of the screen snapshot format.
 pub const SNAPSHOT_VERSION: u32 = 1;
 
-/// Terminal dimensions in rows and columns.
+/// Terminal dimensions in rows and columns, with optional pixel size.
 ///
-/// Default is 24 rows by 80 columns (standard VT100 size).
+/// Default is 24 rows by 80 columns (standard VT100 size) with no pixel
+/// size. Pixel dimensions are reported through `TIOCGWINSZ` and XTWINOPS
+/// queries for apps that render images; `0` means unknown.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TerminalSize {
     /// Number of rows (height).
     pub rows: u16,
     /// Number of columns (width).
     pub cols: u16,
+    /// Text area width in pixels (0 = unknown).
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub pixel_width: u16,
+    /// Text area height in pixels (0 = unknown).
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub pixel_height: u16,
+}
+
+impl TerminalSize {
+    /// Create a size with the given rows and columns and no pixel size.
+    pub const fn new(rows: u16, cols: u16) -> Self {
+        Self {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        }
+    }
+
+    /// Set the text area size in pixels.
+    #[must_use]
+    pub const fn with_pixels(mut self, pixel_width: u16, pixel_height: u16) -> Self {
+        self.pixel_width = pixel_width;
+        self.pixel_height = pixel_height;
+        self
+    }
+
+    /// Whether a pixel size is known.
+    pub const fn has_pixels(&self) -> bool {
+        self.pixel_width > 0 && self.pixel_height > 0
+    }
 }
 
 impl Default for TerminalSize {
     fn default() -> Self {
-        Self { rows: 24, cols: 80 }
+        Self::new(24, 80)
     }
 }
 
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_zero(value: &u16) -> bool {
+    *value == 0
+}
+
 /// Cursor position and visibility state.
 ///
 /// Coordinates are 0-based (row 0 is top, col 0 is left).
@@ -58,6 +96,32 @@ pub struct ScreenSnapshot {
     pub cells: Option<Vec<Vec<Cell>>>,
 }
 
+impl ScreenSnapshot {
+    /// Stability of the snapshot shape, as reported by `protocol-help`.
+    pub const STABILITY: Stability = Stability::Stable;
+
+    /// Stable hash of the visible screen state (16 lowercase hex digits).
+    ///
+    /// Covers size, cursor, alternate-screen mode, and text lines. The
+    /// snapshot id and cell styles are excluded, so two captures of the
+    /// same screen hash equally. Driver clients echo it back as
+    /// `expected_screen_hash` to reject actions against a stale screen.
+    #[must_use]
+    pub fn screen_hash(&self) -> String {
+        let mut data = format!(
+            "{}x{};cursor={},{},{};alt={}\n",
+            self.rows,
+            self.cols,
+            self.cursor.row,
+            self.cursor.col,
+            self.cursor.visible,
+            self.alternate_screen
+        );
+        data.push_str(&self.lines.join("\n"));
+        format!("{:016x}", crate::util::fnv1a_hash(data.as_bytes()))
+    }
+}
+
 /// Single terminal cell with character and styling.
 ///
 /// Used when detailed style information is needed beyond plain text.
@@ -108,3 +172,167 @@ pub enum Color {
         b: u8,
     },
 }
+
+/// Kind of a UI widget recognized on screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WidgetKind {
+    /// Region enclosed by box-drawing or ASCII borders (dialogs, menus,
+    /// panels). The title is the text set into the top border, if any.
+    Box,
+    /// Run of inverse-video cells, typically the selected row of a list or
+    /// menu. Only detected when cell styles are available.
+    Selection,
+    /// Line asking a yes/no question (`[y/N]`, `(y/n)`, `(yes/no)`).
+    Prompt,
+}
+
+/// UI widget detected on screen by [`crate::terminal::widgets`].
+///
+/// Positions are 0-based screen cells; `rows` and `cols` include any border.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Widget {
+    /// What was recognized.
+    pub kind: WidgetKind,
+    /// Top row of the region.
+    pub row: u16,
+    /// Left column of the region.
+    pub col: u16,
+    /// Height of the region.
+    pub rows: u16,
+    /// Width of the region.
+    pub cols: u16,
+    /// Title set into a box's top border.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Text inside the region (box interior lines joined by `\n`, the
+    /// selected text, or the prompt line), trimmed.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub text: String,
+}
+
+/// Host PTY setup, gathered when opening a PTY fails and by `ptybox doctor`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PtyDiagnostics {
+    /// OS error number of the last failed `openpty`, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub errno: Option<i32>,
+    /// Symbolic name of `errno` (e.g. `ENOSPC`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub errno_name: Option<String>,
+    /// Whether `errno` is one that is retried as transient.
+    pub transient: bool,
+    /// Whether `/dev/ptmx` exists.
+    pub ptmx_present: bool,
+    /// Whether `/dev/pts` holds a devpts instance. Only checked on Linux.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub devpts_mounted: Option<bool>,
+    /// Whether the process appears to run in a container.
+    pub container: bool,
+    /// Suggested fixes for the conditions found.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hints: Vec<String>,
+}
+
+/// Outcome of a PTY open probe ([`crate::session::probe_pty`]).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PtyProbe {
+    /// Whether a PTY could be opened.
+    pub ok: bool,
+    /// `openpty` calls made, including retries.
+    pub attempts: u32,
+    /// Error of the last failed attempt, when the probe failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Host PTY setup.
+    pub diagnostics: PtyDiagnostics,
+}
+
+/// Version of the emulator diff format (`emulator-diff.json`).
+pub const EMULATOR_DIFF_VERSION: u32 = 1;
+
+/// Comparison of the primary terminal emulator with a second backend fed
+/// the same PTY output (`crate::terminal::emulator_diff`).
+///
+/// Written to `emulator-diff.json` (feature `emulator-diff`).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmulatorDiffReport {
+    /// Format version ([`EMULATOR_DIFF_VERSION`]).
+    pub emulator_diff_version: u32,
+    /// Backend behind snapshots and assertions, e.g. `vt100 0.15`.
+    pub primary: String,
+    /// Backend it is compared with, e.g. `vt100 0.16`.
+    pub secondary: String,
+    /// PTY output bytes fed to both backends.
+    pub bytes_processed: u64,
+    /// Screen comparisons made (one per output chunk and per resize).
+    pub comparisons: u64,
+    /// Times the screens went from agreeing to differing.
+    pub divergences: u64,
+    /// The first divergences, in order (at most 16).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub points: Vec<EmulatorDivergence>,
+    /// First observation whose snapshot was taken while the screens
+    /// differed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_differing_snapshot: Option<EmulatorDiffSnapshot>,
+    /// Whether the screens differed when the run ended.
+    pub diverged_at_end: bool,
+}
+
+/// What the backends had just processed when their screens were compared.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmulatorDiffTrigger {
+    /// A chunk of PTY output.
+    Output,
+    /// A terminal resize.
+    Resize,
+}
+
+/// Point where the backends' screens started to differ.
+///
+/// Chunks are compared whole, so the bytes that caused the divergence lie
+/// in `chunk_start..byte_offset`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmulatorDivergence {
+    /// Whether output or a resize preceded the comparison.
+    pub trigger: EmulatorDiffTrigger,
+    /// Offset in the PTY output of the chunk's first byte.
+    pub chunk_start: u64,
+    /// Offset just past the chunk (equal to `chunk_start` for a resize).
+    pub byte_offset: u64,
+    /// The chunk, escaped (`\x1b[1E`), at most 256 bytes of it.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub chunk: String,
+    /// Rows whose text differs (at most 8).
+    pub rows: Vec<EmulatorRowDiff>,
+    /// Cursor of the primary backend.
+    pub primary_cursor: Cursor,
+    /// Cursor of the secondary backend.
+    pub secondary_cursor: Cursor,
+    /// Whether the primary backend showed the alternate screen.
+    pub primary_alternate_screen: bool,
+    /// Whether the secondary backend showed the alternate screen.
+    pub secondary_alternate_screen: bool,
+}
+
+/// A screen row as each backend rendered it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmulatorRowDiff {
+    /// Row (0-based).
+    pub row: u16,
+    /// Text on the primary backend.
+    pub primary: String,
+    /// Text on the secondary backend.
+    pub secondary: String,
+}
+
+/// Observation snapshot taken while the backends' screens differed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmulatorDiffSnapshot {
+    /// `snapshot_id` of the observation's screen.
+    pub snapshot_id: SnapshotId,
+    /// PTY output bytes processed when it was taken.
+    pub byte_offset: u64,
+}
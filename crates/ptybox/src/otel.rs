@@ -0,0 +1,451 @@
+//! OpenTelemetry trace export (feature `otel`).
+//!
+//! When [`RunnerOptions::otel`](crate::runner::RunnerOptions::otel) is set,
+//! each `run_scenario` / `run_exec` call sends one trace to an OTLP/HTTP
+//! collector after the run finishes:
+//!
+//! | Span | Parent | Attributes |
+//! |------|--------|------------|
+//! | `ptybox.run` | — | `ptybox.run.id`, `ptybox.run.status`, `ptybox.error.code`, `ptybox.exit_code`, `ptybox.steps`, `ptybox.output_bytes`, `process.command` |
+//! | `ptybox.step` | run | `ptybox.step.name`, `ptybox.step.key`, `ptybox.step.status`, `ptybox.step.attempts`, `ptybox.error.code`, `ptybox.output_bytes`, `ptybox.latency_ms` |
+//! | `ptybox.action` | step | `ptybox.action.type`, `ptybox.input_bytes` |
+//!
+//! The trace id is the run id. Span times are wall-clock times reconstructed
+//! from the run's monotonic step offsets; an action span covers its step's
+//! attempts. Spans are encoded as OTLP JSON and posted to
+//! `<endpoint>` over plain HTTP, so extra headers such as auth tokens are
+//! only sent to a loopback collector.
+//!
+//! Export happens after artifacts are written and never writes to them, so
+//! artifacts stay identical with and without the feature. Export failures are
+//! logged with `tracing` and do not change the run's result.
+
+use crate::model::{ActionType, RunResult, StepId, StepResult};
+use crate::runner::{ErrorCode, ProgressCallback, ProgressEvent, RunnerError, RunnerResult};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Default collector timeout for connecting and sending.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Where and how to export traces.
+#[derive(Clone, Debug)]
+pub struct OtelConfig {
+    /// Collector host and port.
+    host: String,
+    port: u16,
+    /// Request path (for example `/v1/traces`).
+    path: String,
+    /// `service.name` resource attribute.
+    pub service_name: String,
+    /// Extra HTTP headers (for example an auth token); only sent to a
+    /// loopback collector, see [`OtelConfig::with_headers`].
+    headers: Vec<(String, String)>,
+    /// Connect, write, and read timeout.
+    pub timeout: Duration,
+}
+
+impl OtelConfig {
+    /// Export to a full OTLP/HTTP traces URL such as
+    /// `http://localhost:4318/v1/traces`.
+    ///
+    /// # Errors
+    /// Returns `E_PROTOCOL` if `endpoint` is not an `http://` URL with a
+    /// host and valid port.
+    pub fn new(endpoint: &str) -> RunnerResult<Self> {
+        let invalid = |reason: &str| {
+            RunnerError::with_context(
+                ErrorCode::Protocol,
+                format!("invalid OTLP endpoint: {reason}"),
+                json!({ "endpoint": endpoint, "example": "http://localhost:4318/v1/traces" }),
+            )
+        };
+        let rest = endpoint
+            .strip_prefix("http://")
+            .ok_or_else(|| invalid("only http:// endpoints are supported"))?;
+        let (authority, path) = match rest.find('/') {
+            Some(idx) => rest.split_at(idx),
+            None => (rest, "/v1/traces"),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                port.parse::<u16>()
+                    .map_err(|_| invalid("port is not a number"))?,
+            ),
+            None => (authority, 80),
+        };
+        if host.is_empty() {
+            return Err(invalid("missing host"));
+        }
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+            service_name: "ptybox".to_string(),
+            headers: Vec::new(),
+            timeout: DEFAULT_TIMEOUT,
+        })
+    }
+
+    /// Send extra HTTP headers (for example an auth token) with each export.
+    ///
+    /// Export uses plain HTTP, so headers are only accepted when the
+    /// collector host is loopback (`localhost`, `127.0.0.0/8`, or `::1`);
+    /// otherwise they would cross the network in cleartext.
+    ///
+    /// # Errors
+    /// Returns `E_PROTOCOL` if `headers` is non-empty and the collector host
+    /// is not loopback.
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> RunnerResult<Self> {
+        if !headers.is_empty() && !is_loopback_host(&self.host) {
+            return Err(RunnerError::with_context(
+                ErrorCode::Protocol,
+                "OTLP headers would be sent in cleartext to a non-loopback collector",
+                json!({
+                    "host": self.host,
+                    "headers": headers.iter().map(|(key, _)| key).collect::<Vec<_>>(),
+                    "fix": "export to a collector on localhost (for example a local agent that forwards over TLS) or unset OTEL_EXPORTER_OTLP_HEADERS",
+                }),
+            ));
+        }
+        self.headers = headers;
+        Ok(self)
+    }
+
+    /// Build a config from the standard OpenTelemetry environment variables.
+    ///
+    /// Reads `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT` (used as is) or
+    /// `OTEL_EXPORTER_OTLP_ENDPOINT` (with `/v1/traces` appended),
+    /// `OTEL_SERVICE_NAME`, and `OTEL_EXPORTER_OTLP_HEADERS`
+    /// (`key=value,key=value`). Returns `None` when no endpoint is set.
+    ///
+    /// # Errors
+    /// Returns `E_PROTOCOL` if the endpoint is invalid, or if headers are
+    /// set for a collector that is not loopback.
+    pub fn from_env() -> RunnerResult<Option<Self>> {
+        let endpoint = match std::env::var("OTEL_EXPORTER_OTLP_TRACES_ENDPOINT") {
+            Ok(endpoint) if !endpoint.is_empty() => endpoint,
+            _ => match std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT") {
+                Ok(base) if !base.is_empty() => {
+                    format!("{}/v1/traces", base.trim_end_matches('/'))
+                }
+                _ => return Ok(None),
+            },
+        };
+        let mut config = Self::new(&endpoint)?;
+        if let Ok(name) = std::env::var("OTEL_SERVICE_NAME") {
+            if !name.is_empty() {
+                config.service_name = name;
+            }
+        }
+        if let Ok(headers) = std::env::var("OTEL_EXPORTER_OTLP_HEADERS") {
+            let headers = headers
+                .split(',')
+                .filter_map(|pair| pair.split_once('='))
+                .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+                .filter(|(key, _)| !key.is_empty())
+                .collect();
+            config = config.with_headers(headers)?;
+        }
+        Ok(Some(config))
+    }
+}
+
+/// Whether `host` names the local machine, so plain HTTP to it never leaves
+/// the host.
+fn is_loopback_host(host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Per-run recorder: forwards progress events to the caller's callback and
+/// keeps the per-step output byte counts that [`RunResult`] does not carry.
+pub(crate) struct TraceRecorder {
+    config: OtelConfig,
+    started: SystemTime,
+    inner: Option<Arc<dyn ProgressCallback>>,
+    step_output: Mutex<HashMap<StepId, u64>>,
+}
+
+impl ProgressCallback for TraceRecorder {
+    fn on_progress(&self, event: &ProgressEvent) {
+        if let ProgressEvent::StepCompleted {
+            step_id,
+            output_bytes,
+            ..
+        } = event
+        {
+            if let Ok(mut steps) = self.step_output.lock() {
+                steps.insert(*step_id, *output_bytes);
+            }
+        }
+        if let Some(inner) = &self.inner {
+            inner.on_progress(event);
+        }
+    }
+}
+
+impl TraceRecorder {
+    /// Start recording a run, chaining the existing progress callback.
+    pub(crate) fn start(
+        config: OtelConfig,
+        progress: Option<Arc<dyn ProgressCallback>>,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            config,
+            started: SystemTime::now(),
+            inner: progress,
+            step_output: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Export the finished run. Failures are logged, never returned.
+    pub(crate) fn export(&self, result: &RunnerResult<RunResult>, command: &str) {
+        let body = self.encode(result, command);
+        if let Err(err) = post(&self.config, &body) {
+            tracing::warn!(error = %err, "failed to export OpenTelemetry trace");
+        }
+    }
+
+    fn encode(&self, result: &RunnerResult<RunResult>, command: &str) -> Value {
+        let elapsed = self.started.elapsed().unwrap_or_default();
+        let at = |offset_ms: u64| unix_nanos(self.started + Duration::from_millis(offset_ms));
+        let mut spans = Vec::new();
+        let run_span_id = span_id();
+        let (trace_id, mut run) = match result {
+            Ok(run) => {
+                let trace_id = run.run_id.to_string().replace('-', "");
+                let steps = run.steps.as_deref().unwrap_or_default();
+                let step_output = self
+                    .step_output
+                    .lock()
+                    .map(|s| s.clone())
+                    .unwrap_or_default();
+                for step in steps {
+                    let output = step_output.get(&step.step_id).copied();
+                    spans.extend(step_spans(step, output, &trace_id, &run_span_id, at));
+                }
+                let mut attributes = vec![
+                    attr("ptybox.run.id", json!(run.run_id.to_string())),
+                    attr("ptybox.run.status", json!(label(&run.status))),
+                    attr("process.command", json!(run.command)),
+                    int_attr("ptybox.steps", steps.len() as u64),
+                ];
+                if let Some(code) = run.exit_status.as_ref().and_then(|status| status.exit_code) {
+                    attributes.push(attr(
+                        "ptybox.exit_code",
+                        json!({ "intValue": code.to_string() }),
+                    ));
+                }
+                if !step_output.is_empty() {
+                    attributes.push(int_attr("ptybox.output_bytes", step_output.values().sum()));
+                }
+                let error = run.error.as_ref().map(|error| error.code.as_str());
+                (trace_id, span("ptybox.run", attributes, error))
+            }
+            Err(err) => {
+                let attributes = vec![
+                    attr("ptybox.run.status", json!("errored")),
+                    attr("process.command", json!(command)),
+                ];
+                (
+                    uuid::Uuid::new_v4().simple().to_string(),
+                    span("ptybox.run", attributes, Some(err.code.as_str())),
+                )
+            }
+        };
+        set(&mut run, "traceId", json!(trace_id));
+        set(&mut run, "spanId", json!(run_span_id));
+        set(
+            &mut run,
+            "startTimeUnixNano",
+            json!(unix_nanos(self.started)),
+        );
+        set(
+            &mut run,
+            "endTimeUnixNano",
+            json!(unix_nanos(self.started + elapsed)),
+        );
+        spans.insert(0, run);
+        json!({
+            "resourceSpans": [{
+                "resource": {
+                    "attributes": [attr("service.name", json!(self.config.service_name))]
+                },
+                "scopeSpans": [{
+                    "scope": { "name": "ptybox", "version": env!("CARGO_PKG_VERSION") },
+                    "spans": spans
+                }]
+            }]
+        })
+    }
+}
+
+/// The step span and its action span.
+fn step_spans(
+    step: &StepResult,
+    output_bytes: Option<u64>,
+    trace_id: &str,
+    parent: &str,
+    at: impl Fn(u64) -> String,
+) -> [Value; 2] {
+    let step_span_id = span_id();
+    let mut attributes = vec![
+        attr("ptybox.step.name", json!(step.name)),
+        attr("ptybox.step.status", json!(label(&step.status))),
+        int_attr("ptybox.step.attempts", u64::from(step.attempts)),
+    ];
+    if let Some(key) = &step.step_key {
+        attributes.push(attr("ptybox.step.key", json!(key)));
+    }
+    if let Some(bytes) = output_bytes {
+        attributes.push(int_attr("ptybox.output_bytes", bytes));
+    }
+    if let Some(latency) = step.latency_ms {
+        attributes.push(int_attr("ptybox.latency_ms", latency));
+    }
+    let error = step.error.as_ref().map(|error| error.code.as_str());
+    let mut step_span = span("ptybox.step", attributes, error);
+    let mut action_span = span(
+        "ptybox.action",
+        vec![
+            attr("ptybox.action.type", json!(label(&step.action.action_type))),
+            int_attr(
+                "ptybox.input_bytes",
+                input_bytes(&step.action.action_type, &step.action.payload),
+            ),
+        ],
+        None,
+    );
+    for (span, id, parent) in [
+        (&mut step_span, step_span_id.clone(), parent.to_string()),
+        (&mut action_span, span_id(), step_span_id),
+    ] {
+        set(span, "traceId", json!(trace_id));
+        set(span, "spanId", json!(id));
+        set(span, "parentSpanId", json!(parent));
+        set(span, "startTimeUnixNano", json!(at(step.started_at_ms)));
+        set(span, "endTimeUnixNano", json!(at(step.ended_at_ms)));
+    }
+    [step_span, action_span]
+}
+
+/// Bytes a key (by the default translation), text, or raw action writes to
+/// the PTY.
+fn input_bytes(action_type: &ActionType, payload: &Value) -> u64 {
+    let field = |name: &str| {
+        payload
+            .get(name)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+    };
+    let bytes = match action_type {
+        ActionType::Key => crate::session::key_to_bytes(field("key"), None).map_or(0, |b| b.len()),
+        ActionType::Text => field("text").len(),
+        ActionType::Raw => crate::session::decode_raw_payload(payload).map_or(0, |b| b.len()),
+        _ => 0,
+    };
+    bytes as u64
+}
+
+fn span(name: &str, attributes: Vec<Value>, error_code: Option<&str>) -> Value {
+    let status = match error_code {
+        Some(code) => json!({ "code": 2, "message": code }),
+        None => json!({ "code": 1 }),
+    };
+    let mut attributes = attributes;
+    if let Some(code) = error_code {
+        attributes.push(attr("ptybox.error.code", json!(code)));
+    }
+    json!({ "name": name, "kind": 1, "attributes": attributes, "status": status })
+}
+
+/// Set a field on a span object.
+fn set(span: &mut Value, key: &str, value: Value) {
+    if let Some(fields) = span.as_object_mut() {
+        fields.insert(key.to_string(), value);
+    }
+}
+
+fn attr(key: &str, value: Value) -> Value {
+    let value = match value {
+        Value::String(text) => json!({ "stringValue": text }),
+        other => other,
+    };
+    json!({ "key": key, "value": value })
+}
+
+/// OTLP JSON encodes 64-bit integers as strings.
+fn int_attr(key: &str, value: u64) -> Value {
+    attr(key, json!({ "intValue": value.to_string() }))
+}
+
+/// Serialized name of a `snake_case` enum value.
+fn label(value: &impl serde::Serialize) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn span_id() -> String {
+    let mut id = uuid::Uuid::new_v4().simple().to_string();
+    id.truncate(16);
+    id
+}
+
+fn unix_nanos(time: SystemTime) -> String {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_nanos())
+        .to_string()
+}
+
+/// POST `body` to the collector and check for a 2xx status.
+fn post(config: &OtelConfig, body: &Value) -> std::io::Result<()> {
+    let payload = body.to_string();
+    let addr = (config.host.as_str(), config.port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| std::io::Error::other("collector address did not resolve"))?;
+    let mut stream = TcpStream::connect_timeout(&addr, config.timeout)?;
+    stream.set_read_timeout(Some(config.timeout))?;
+    stream.set_write_timeout(Some(config.timeout))?;
+    let headers = config
+        .headers
+        .iter()
+        .fold(String::new(), |mut out, (key, value)| {
+            let _ = write!(out, "{key}: {value}\r\n");
+            out
+        });
+    let request = format!(
+        "POST {} HTTP/1.1\r\nHost: {}:{}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n{headers}\r\n",
+        config.path,
+        config.host,
+        config.port,
+        payload.len()
+    );
+    stream.write_all(request.as_bytes())?;
+    stream.write_all(payload.as_bytes())?;
+    stream.flush()?;
+
+    let mut response = Vec::new();
+    stream.take(4096).read_to_end(&mut response)?;
+    let response = String::from_utf8_lossy(&response);
+    let status = response.split_whitespace().nth(1).unwrap_or_default();
+    if status.starts_with('2') {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!(
+            "collector responded with status {status:?}"
+        )))
+    }
+}
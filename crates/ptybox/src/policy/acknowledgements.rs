@@ -0,0 +1,95 @@
+//! Audit trail of the unsafe options acknowledged for a run
+//! (`acknowledgements.json`).
+//!
+//! Each active unsafe option is attributed to the policy field that
+//! acknowledged it, unless the caller reports that a CLI flag or environment
+//! variable supplied the acknowledgement instead.
+
+use crate::model::policy::{Policy, SandboxFallback};
+use crate::model::{AckSource, Acknowledgement, AcknowledgementReport, UnsafeOption};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Whether `option` is in effect, with its acknowledgement, under `policy`.
+#[must_use]
+pub fn is_active(option: UnsafeOption, policy: &Policy) -> bool {
+    match option {
+        UnsafeOption::SandboxDisabled => policy.sandbox.is_disabled_with_ack(),
+        UnsafeOption::SandboxFallback => {
+            !policy.sandbox.is_disabled()
+                && matches!(policy.sandbox_fallback, SandboxFallback::Warn { ack: true })
+        }
+        UnsafeOption::NetworkEnabled => policy.network.is_enabled_with_ack(),
+        UnsafeOption::NetworkUnenforced => {
+            policy.sandbox.is_disabled() && policy.network_enforcement.unenforced_ack
+        }
+        UnsafeOption::FsWrite => policy.fs.has_write_access_with_ack(),
+    }
+}
+
+/// Policy file field that acknowledges `option`.
+const fn policy_field(option: UnsafeOption) -> &'static str {
+    match option {
+        UnsafeOption::SandboxDisabled => "sandbox_unsafe_ack",
+        UnsafeOption::SandboxFallback => "sandbox_fallback_ack",
+        UnsafeOption::NetworkEnabled | UnsafeOption::NetworkUnenforced => "network_unsafe_ack",
+        UnsafeOption::FsWrite => "fs_write_unsafe_ack",
+    }
+}
+
+/// Milliseconds since the Unix epoch.
+#[must_use]
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX))
+        .unwrap_or(0)
+}
+
+/// Report the unsafe options active under `policy`, or `None` when there
+/// are none.
+///
+/// `overrides` are acknowledgements given outside the policy (CLI flags or
+/// environment variables); the first one for an active option is used as is.
+/// Options acknowledged only by the policy are attributed to its field and
+/// stamped now. Overrides for inactive options are ignored.
+#[must_use]
+pub fn acknowledgement_report(
+    policy: &Policy,
+    overrides: &[Acknowledgement],
+) -> Option<AcknowledgementReport> {
+    let recorded_at_ms = now_ms();
+    let acknowledgements: Vec<Acknowledgement> = UnsafeOption::ALL
+        .into_iter()
+        .filter(|option| is_active(*option, policy))
+        .map(|option| {
+            overrides
+                .iter()
+                .find(|ack| ack.option == option)
+                .cloned()
+                .unwrap_or_else(|| Acknowledgement {
+                    option,
+                    source: AckSource::Policy,
+                    origin: policy_field(option).to_string(),
+                    recorded_at_ms,
+                })
+        })
+        .collect();
+    if acknowledgements.is_empty() {
+        return None;
+    }
+    let entries: Vec<String> = acknowledgements
+        .iter()
+        .map(|ack| {
+            let source = match ack.source {
+                AckSource::Policy => "policy",
+                AckSource::Cli => "cli",
+                AckSource::Env => "env",
+            };
+            format!("{} ({source}: {})", ack.option.as_str(), ack.origin)
+        })
+        .collect();
+    Some(AcknowledgementReport {
+        summary: format!("unsafe: {}", entries.join(", ")),
+        acknowledgements,
+    })
+}
@@ -0,0 +1,215 @@
+//! Seatbelt violation auditing (feature `sandbox-audit`).
+//!
+//! Seatbelt denies operations without telling the caller what was attempted.
+//! While a sandboxed run is in flight, [`SandboxAudit`] follows the unified
+//! log (`log stream`) for the kernel's `Sandbox:` deny messages and keeps
+//! those from the child's pid or from one of the policy's allowlisted
+//! executables (the only programs the child tree may exec). The result is
+//! written as `violations.json`.
+//!
+//! Collection is best effort: if the run is not sandboxed or `log stream`
+//! cannot be started, the report says so instead of failing the run.
+//! Endpoint Security clients are not used; they require an entitlement.
+
+use crate::artifacts::ArtifactsWriter;
+use crate::model::policy::{Policy, SandboxMode};
+use crate::model::{SandboxViolation, SandboxViolationReport};
+use crate::runner::RunnerResult;
+use regex::Regex;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Source recorded in [`SandboxViolationReport::source`].
+pub const AUDIT_SOURCE: &str = "log_stream";
+
+/// Unified log reader.
+const LOG_BINARY: &str = "/usr/bin/log";
+/// Only sandbox deny messages are streamed.
+const LOG_PREDICATE: &str = r#"sender == "Sandbox" AND eventMessage CONTAINS "deny""#;
+/// How long to wait for `log stream` to start emitting before the run begins.
+const STARTUP_WAIT: Duration = Duration::from_secs(1);
+/// Delay before stopping the stream, so late log deliveries are not lost.
+const DRAIN_WAIT: Duration = Duration::from_millis(500);
+/// Upper bound on buffered deny lines.
+const MAX_LINES: usize = 10_000;
+
+/// Collects sandbox denials for one run; see the module docs.
+pub struct SandboxAudit {
+    state: AuditState,
+}
+
+enum AuditState {
+    Disabled,
+    Unavailable(String),
+    Collecting(Collector),
+}
+
+struct Collector {
+    child: Child,
+    lines: Arc<Mutex<Vec<String>>>,
+    reader: JoinHandle<()>,
+    pid: Option<u32>,
+    executables: BTreeSet<String>,
+}
+
+impl SandboxAudit {
+    /// Start collecting if `enabled`, the run is sandboxed, and artifacts are
+    /// being written. `pid` is the sandboxed child's process id.
+    pub fn start(enabled: bool, policy: &Policy, pid: Option<u32>, has_artifacts: bool) -> Self {
+        let state = if !enabled || !has_artifacts {
+            AuditState::Disabled
+        } else if !matches!(policy.sandbox, SandboxMode::Seatbelt) {
+            AuditState::Unavailable("run was not sandboxed by Seatbelt".to_string())
+        } else {
+            match Collector::spawn(pid, executable_names(policy)) {
+                Ok(collector) => AuditState::Collecting(collector),
+                Err(reason) => AuditState::Unavailable(reason),
+            }
+        };
+        Self { state }
+    }
+
+    /// Stop collecting and write `violations.json` to `writer`.
+    ///
+    /// # Errors
+    /// Returns `E_IO` if the report cannot be written.
+    pub fn finish(self, writer: Option<&mut ArtifactsWriter>) -> RunnerResult<()> {
+        let report = match self.state {
+            AuditState::Disabled => return Ok(()),
+            AuditState::Unavailable(reason) => SandboxViolationReport {
+                source: AUDIT_SOURCE.to_string(),
+                collected: false,
+                reason: Some(reason),
+                violations: Vec::new(),
+            },
+            AuditState::Collecting(collector) => collector.stop(),
+        };
+        match writer {
+            Some(writer) => writer.write_violations(&report),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Collector {
+    fn spawn(pid: Option<u32>, executables: BTreeSet<String>) -> Result<Self, String> {
+        let mut child = Command::new(LOG_BINARY)
+            .args(["stream", "--style", "ndjson", "--predicate", LOG_PREDICATE])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|err| format!("failed to start log stream: {err}"))?;
+        let Some(stdout) = child.stdout.take() else {
+            let _ = child.kill();
+            return Err("log stream has no stdout".to_string());
+        };
+
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let shared = Arc::clone(&lines);
+        let (ready_tx, ready_rx) = mpsc::channel();
+        let reader = thread::Builder::new()
+            .name("ptybox-sandbox-audit".to_string())
+            .spawn(move || {
+                let mut ready = Some(ready_tx);
+                for line in BufReader::new(stdout).lines() {
+                    let Ok(line) = line else { break };
+                    if let Some(tx) = ready.take() {
+                        let _ = tx.send(());
+                    }
+                    let mut lines = shared.lock().unwrap_or_else(PoisonError::into_inner);
+                    if lines.len() < MAX_LINES {
+                        lines.push(line);
+                    }
+                }
+            })
+            .map_err(|err| format!("failed to start audit reader: {err}"))?;
+        // `log stream` prints a banner once it is attached.
+        let _ = ready_rx.recv_timeout(STARTUP_WAIT);
+
+        Ok(Self {
+            child,
+            lines,
+            reader,
+            pid,
+            executables,
+        })
+    }
+
+    fn stop(mut self) -> SandboxViolationReport {
+        thread::sleep(DRAIN_WAIT);
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = self.reader.join();
+        let lines = self.lines.lock().unwrap_or_else(PoisonError::into_inner);
+        let violations = lines
+            .iter()
+            .filter_map(|line| parse_violation(line))
+            .filter(|violation| {
+                Some(violation.pid) == self.pid || self.executables.contains(&violation.process)
+            })
+            .collect();
+        SandboxViolationReport {
+            source: AUDIT_SOURCE.to_string(),
+            collected: true,
+            reason: None,
+            violations,
+        }
+    }
+}
+
+/// File names of the policy's allowlisted executables.
+fn executable_names(policy: &Policy) -> BTreeSet<String> {
+    policy
+        .exec
+        .allowed_executables
+        .iter()
+        .filter_map(|exe| Path::new(exe).file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+        .collect()
+}
+
+fn deny_pattern() -> Option<&'static Regex> {
+    static PATTERN: OnceLock<Option<Regex>> = OnceLock::new();
+    PATTERN
+        .get_or_init(|| {
+            Regex::new(r"Sandbox: (?P<process>.+?)\((?P<pid>\d+)\) deny\(\d+\) (?P<operation>\S+)(?: (?P<target>.+))?$").ok()
+        })
+        .as_ref()
+}
+
+/// Parse one `log stream --style ndjson` line into a violation.
+///
+/// Accepts either the NDJSON object (reading `eventMessage` and `timestamp`)
+/// or a bare message such as
+/// `Sandbox: cat(4242) deny(1) file-read-data /private/etc/hosts`.
+/// Returns `None` for anything that is not a sandbox deny message.
+pub fn parse_violation(line: &str) -> Option<SandboxViolation> {
+    let (message, timestamp) = match serde_json::from_str::<Value>(line) {
+        Ok(value) => (
+            value.get("eventMessage")?.as_str()?.to_string(),
+            value
+                .get("timestamp")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+        ),
+        Err(_) => (line.to_string(), None),
+    };
+    let captures = deny_pattern()?.captures(message.trim_end())?;
+    Some(SandboxViolation {
+        process: captures.name("process")?.as_str().to_string(),
+        pid: captures.name("pid")?.as_str().parse().ok()?,
+        operation: captures.name("operation")?.as_str().to_string(),
+        target: captures
+            .name("target")
+            .map(|target| target.as_str().to_string()),
+        timestamp,
+    })
+}
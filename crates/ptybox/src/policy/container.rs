@@ -0,0 +1,322 @@
+//! Container sandbox backend ([`SandboxMode::Container`]).
+//!
+//! The child runs as `<runtime> run --interactive --tty ... <image>` under
+//! ptybox's PTY, with the policy translated into runtime flags:
+//!
+//! | Policy | Flag |
+//! |--------|------|
+//! | `fs.allowed_read` | `--volume <path>:<path>:ro` |
+//! | `fs.allowed_write` | `--volume <path>:<path>:rw` |
+//! | `network: disabled` | `--network none` |
+//! | `container.pids_limit` | `--pids-limit <n>` |
+//! | `container.memory_bytes` | `--memory <n>b` |
+//! | `env.allowlist`, `env.set`, `determinism` | `--env <NAME>` |
+//! | `cwd` / `fs.working_dir` | `--workdir <path>` |
+//! | `sockets` | `--volume <socket dir>:<socket dir>:rw` |
+//!
+//! Paths are mounted at the same location, so `fs` paths mean the same
+//! thing inside and outside the container. `--env NAME` copies the value
+//! from the runtime's environment, which the session has already reduced to
+//! the env policy, so values never appear on the command line. The command
+//! replaces the image's entrypoint. Every container also drops all
+//! capabilities, sets `no-new-privileges`, and runs as the invoking user
+//! (`--userns keep-id` for podman, `--user <uid>:<gid>` for docker).
+//!
+//! The runtime is run from the absolute `container.program` path, which
+//! must be in `exec.allowed_executables`; `PATH` is never searched.
+//!
+//! The exact invocation is written to `container.json`
+//! ([`ContainerInvocation`](crate::model::ContainerInvocation), via
+//! [`ArtifactsWriter::write_container_invocation`](crate::artifacts::ArtifactsWriter::write_container_invocation))
+//! and the container is removed when the run ends.
+//! `signal` actions are delivered with `<runtime> kill --signal` rather than
+//! to the runtime CLI running under the PTY.
+
+use crate::model::policy::{ChildSignal, ContainerRuntime, Policy, SandboxMode};
+use crate::model::RunId;
+use crate::runner::{ErrorCode, RunnerError, RunnerResult};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Artifact file recording the container invocation.
+pub const CONTAINER_INVOCATION_FILE: &str = "container.json";
+
+/// Check the image reference, the runtime path and limits, and that every
+/// mounted path can be expressed as a `--volume` flag.
+///
+/// # Errors
+/// `E_POLICY_DENIED` for an empty or malformed image, a runtime `program`
+/// that is not an absolute path in `exec.allowed_executables`, a zero
+/// `pids_limit` or `memory_bytes`, or an `fs` path containing `:` or `,`.
+pub fn validate_container_policy(policy: &Policy) -> Result<(), RunnerError> {
+    let SandboxMode::Container {
+        image,
+        program,
+        pids_limit,
+        memory_bytes,
+        ..
+    } = &policy.sandbox
+    else {
+        return Ok(());
+    };
+    validate_runtime_program(policy, program)?;
+    if *pids_limit == 0 || *memory_bytes == 0 {
+        return Err(RunnerError::with_context(
+            ErrorCode::PolicyDenied,
+            "container limits must be positive",
+            serde_json::json!({
+                "pids_limit": pids_limit,
+                "memory_bytes": memory_bytes,
+                "fix": "Set container.pids_limit and container.memory_bytes above zero, or omit them for the defaults"
+            }),
+        ));
+    }
+    if image.is_empty()
+        || image.starts_with('-')
+        || image
+            .chars()
+            .any(|ch| ch.is_whitespace() || ch.is_control())
+    {
+        return Err(RunnerError::with_context(
+            ErrorCode::PolicyDenied,
+            "container image must be a non-empty image reference",
+            serde_json::json!({
+                "image": image,
+                "fix": "Set container.image to an image such as docker.io/library/alpine:3.20"
+            }),
+        ));
+    }
+    let mounts = policy
+        .fs
+        .allowed_read
+        .iter()
+        .chain(&policy.fs.allowed_write);
+    for path in mounts {
+        if path.contains([':', ',']) {
+            return Err(RunnerError::with_context(
+                ErrorCode::PolicyDenied,
+                "container mount path contains ':' or ','",
+                serde_json::json!({
+                    "path": path,
+                    "fix": "Allowlist a parent directory without ':' or ',' in its path"
+                }),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// The runtime must be an absolute path the exec policy allows, so the
+/// sandbox never depends on what `PATH` resolves.
+fn validate_runtime_program(policy: &Policy, program: &str) -> Result<(), RunnerError> {
+    if !Path::new(program).is_absolute() {
+        return Err(RunnerError::with_context(
+            ErrorCode::PolicyDenied,
+            "container runtime program must be an absolute path",
+            serde_json::json!({
+                "program": program,
+                "fix": "Set container.program to the runtime's absolute path, such as /usr/bin/podman"
+            }),
+        ));
+    }
+    if !policy
+        .exec
+        .allowed_executables
+        .iter()
+        .any(|allowed| allowed == program)
+    {
+        return Err(RunnerError::with_context(
+            ErrorCode::PolicyDenied,
+            "container runtime program is not in the executable allowlist",
+            serde_json::json!({
+                "program": program,
+                "allowed_executables": policy.exec.allowed_executables,
+                "fix": format!("Add {program} to exec.allowed_executables")
+            }),
+        ));
+    }
+    Ok(())
+}
+
+/// Check that the runtime at `program` exists and can reach its engine.
+///
+/// # Errors
+/// `E_SANDBOX_UNAVAILABLE` when `program` is not a file or `<runtime> info`
+/// fails (for example when the docker daemon is not running).
+pub fn ensure_runtime_available(runtime: ContainerRuntime, program: &Path) -> RunnerResult<()> {
+    if !program.is_file() {
+        return Err(RunnerError::with_context(
+            ErrorCode::SandboxUnavailable,
+            format!("container runtime {} not found", runtime.program()),
+            serde_json::json!({
+                "runtime": runtime,
+                "program": program,
+                "fix": format!("Install {} at {} or point container.program at it", runtime.program(), program.display())
+            }),
+        ));
+    }
+    let usable = Command::new(program)
+        .arg("info")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success());
+    if usable {
+        Ok(())
+    } else {
+        Err(RunnerError::with_context(
+            ErrorCode::SandboxUnavailable,
+            format!("{} is installed but not usable", runtime.program()),
+            serde_json::json!({
+                "runtime": runtime,
+                "program": program,
+                "fix": format!("Check that `{} info` succeeds for this user", runtime.program())
+            }),
+        ))
+    }
+}
+
+/// Name of the container started for `run_id`.
+#[must_use]
+pub fn container_name(run_id: RunId) -> String {
+    format!("ptybox-{run_id}")
+}
+
+/// Arguments to `<runtime>` that run `command` with `args` in `image` under
+/// `policy`. `socket_dir` is mounted when the policy grants sockets.
+#[must_use]
+#[allow(clippy::too_many_arguments)]
+pub fn container_args(
+    policy: &Policy,
+    runtime: ContainerRuntime,
+    image: &str,
+    command: &str,
+    args: &[String],
+    cwd: Option<&str>,
+    run_id: RunId,
+    socket_dir: Option<&Path>,
+) -> Vec<String> {
+    let mut flags: Vec<String> = [
+        "run",
+        "--rm",
+        "--interactive",
+        "--tty",
+        "--cap-drop",
+        "ALL",
+        "--security-opt",
+        "no-new-privileges",
+    ]
+    .map(str::to_string)
+    .to_vec();
+    flags.extend(["--name".to_string(), container_name(run_id)]);
+    match runtime {
+        ContainerRuntime::Podman => flags.extend(["--userns".to_string(), "keep-id".to_string()]),
+        ContainerRuntime::Docker => flags.extend([
+            "--user".to_string(),
+            format!("{}:{}", nix::unistd::getuid(), nix::unistd::getgid()),
+        ]),
+    }
+    if !policy.network.is_enabled() {
+        flags.extend(["--network".to_string(), "none".to_string()]);
+    }
+    if let SandboxMode::Container {
+        pids_limit,
+        memory_bytes,
+        ..
+    } = &policy.sandbox
+    {
+        flags.extend([
+            "--pids-limit".to_string(),
+            pids_limit.to_string(),
+            "--memory".to_string(),
+            format!("{memory_bytes}b"),
+        ]);
+    }
+    let writable: BTreeSet<&str> = policy.fs.allowed_write.iter().map(String::as_str).collect();
+    for path in policy
+        .fs
+        .allowed_read
+        .iter()
+        .filter(|path| !writable.contains(path.as_str()))
+    {
+        flags.extend(["--volume".to_string(), format!("{path}:{path}:ro")]);
+    }
+    for path in &writable {
+        flags.extend(["--volume".to_string(), format!("{path}:{path}:rw")]);
+    }
+    if let Some(dir) = socket_dir {
+        let dir = dir.display();
+        flags.extend(["--volume".to_string(), format!("{dir}:{dir}:rw")]);
+    }
+    let env = policy.effective_env();
+    let names: BTreeSet<&String> = env.allowlist.iter().chain(env.set.keys()).collect();
+    for name in names {
+        flags.extend(["--env".to_string(), name.clone()]);
+    }
+    if let Some(cwd) = cwd {
+        flags.extend(["--workdir".to_string(), cwd.to_string()]);
+    }
+    flags.extend([
+        "--entrypoint".to_string(),
+        command.to_string(),
+        image.to_string(),
+    ]);
+    flags.extend(args.iter().cloned());
+    flags
+}
+
+/// A started container, force-removed when the run ends in case the
+/// runtime exited without cleaning it up (for example when killed).
+#[derive(Clone, Debug)]
+pub struct ContainerCleanup {
+    program: PathBuf,
+    name: String,
+}
+
+impl ContainerCleanup {
+    /// Cleanup for the container `name` started with `program`.
+    #[must_use]
+    pub fn new(program: PathBuf, name: String) -> Self {
+        Self { program, name }
+    }
+
+    /// Send `signal` to the container's main process with
+    /// `<runtime> kill --signal`.
+    ///
+    /// # Errors
+    /// `E_IO` when the runtime cannot be started or fails to signal the
+    /// container (for example because it is no longer running).
+    pub fn signal(&self, signal: ChildSignal) -> RunnerResult<()> {
+        let output = Command::new(&self.program)
+            .args(["kill", "--signal", signal.as_str(), self.name.as_str()])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .output()
+            .map_err(|err| RunnerError::io("E_IO", "failed to start container runtime", err))?;
+        if output.status.success() {
+            return Ok(());
+        }
+        Err(RunnerError::with_context(
+            ErrorCode::Io,
+            "container runtime failed to signal the container",
+            serde_json::json!({
+                "container": self.name,
+                "signal": signal,
+                "stderr": String::from_utf8_lossy(&output.stderr).trim(),
+            }),
+        ))
+    }
+
+    /// Remove the container if it still exists; errors are ignored.
+    pub fn remove(&self) {
+        let _ = Command::new(&self.program)
+            .args(["rm", "--force", self.name.as_str()])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    }
+}
@@ -0,0 +1,73 @@
+//! Scenario-scoped policy grants (`grants` in a policy).
+//!
+//! A shared policy can carry narrowly scoped exceptions: each
+//! [`PolicyGrant`] names a glob over scenario names and the extra read,
+//! write, or executable paths and budgets it allows. [`apply_policy_grants`]
+//! folds the grants matching a scenario into the policy before validation,
+//! so granted paths go through the same checks as the policy's own.
+
+use crate::model::policy::{Budgets, Policy, PolicyGrant};
+use crate::runner::{ErrorCode, RunnerError, RunnerResult};
+pub use crate::util::glob_matches;
+
+/// Apply the grants of `policy` whose pattern matches `scenario_name`, in
+/// order, and remove all grants from `policy`. Returns the applied grants.
+///
+/// # Errors
+/// `E_POLICY_DENIED` if a grant's `scenario` pattern is empty.
+pub fn apply_policy_grants(
+    policy: &mut Policy,
+    scenario_name: &str,
+) -> RunnerResult<Vec<PolicyGrant>> {
+    let grants = std::mem::take(&mut policy.grants);
+    if let Some(index) = grants.iter().position(|grant| grant.scenario.is_empty()) {
+        return Err(RunnerError::with_context(
+            ErrorCode::PolicyDenied,
+            "policy grant has an empty scenario pattern",
+            serde_json::json!({
+                "grant": index,
+                "fix": "Set grants[].scenario to a scenario name or glob such as \"payments-*\""
+            }),
+        ));
+    }
+    let applied: Vec<PolicyGrant> = grants
+        .into_iter()
+        .filter(|grant| glob_matches(&grant.scenario, scenario_name))
+        .collect();
+    for grant in &applied {
+        extend_unique(&mut policy.fs.allowed_read, &grant.allowed_read);
+        extend_unique(&mut policy.fs.allowed_write, &grant.allowed_write);
+        extend_unique(
+            &mut policy.exec.allowed_executables,
+            &grant.allowed_executables,
+        );
+        apply_budgets(&mut policy.budgets, grant);
+    }
+    Ok(applied)
+}
+
+fn extend_unique(list: &mut Vec<String>, extra: &[String]) {
+    for item in extra {
+        if !list.contains(item) {
+            list.push(item.clone());
+        }
+    }
+}
+
+fn apply_budgets(budgets: &mut Budgets, grant: &PolicyGrant) {
+    let overrides = &grant.budgets;
+    for (budget, value) in [
+        (&mut budgets.max_runtime_ms, overrides.max_runtime_ms),
+        (&mut budgets.max_steps, overrides.max_steps),
+        (&mut budgets.max_output_bytes, overrides.max_output_bytes),
+        (
+            &mut budgets.max_snapshot_bytes,
+            overrides.max_snapshot_bytes,
+        ),
+        (&mut budgets.max_wait_ms, overrides.max_wait_ms),
+    ] {
+        if let Some(value) = value {
+            *budget = value;
+        }
+    }
+}
@@ -0,0 +1,470 @@
+//! Draft policy inference (`ptybox policy init`).
+//!
+//! [`infer_policy`] runs a command once without a sandbox, with the full
+//! environment (minus blocked variables), and watches what its process tree
+//! touches:
+//!
+//! - with `strace` on `PATH`, the command runs under `strace -f` and every
+//!   successful open, exec, file modification, and `connect` is recorded
+//! - otherwise, on Linux, `/proc` is sampled every 20ms for executables,
+//!   open files, and mapped files; short-lived opens can be missed
+//! - elsewhere only the command itself is recorded
+//!
+//! The draft allowlists the observed executables, the directories holding
+//! the files read and written (outside system locations, which the platform
+//! sandbox profile covers), the working directory, and the inherited
+//! environment variables that the executables and scripts read mention by
+//! name. Network stays disabled. Everything left out is listed in
+//! [`PolicyInference::notes`].
+//!
+//! [`suggest_policy`] works the other way round: it starts from the policy
+//! of a finished run and drops what the run's artifacts show was not used.
+
+mod procfs;
+mod strace;
+mod suggest;
+
+pub use suggest::{suggest_policy, SUGGESTIONS_FILE};
+
+use crate::model::policy::{EnvPolicy, ExecPolicy, FsPolicy, Policy, ProcessPolicy};
+use crate::model::{ObservedAccess, PolicyInference, RunId, TerminalSize};
+use crate::runner::{ErrorCode, RunnerError, RunnerResult};
+use crate::session::{Session, SessionConfig};
+use crate::util::{convert_exit_status, elapsed_ms, pause_until};
+use procfs::ProcSampler;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Default time to observe the command before stopping it.
+pub const DEFAULT_OBSERVE_DURATION: Duration = Duration::from_secs(5);
+
+/// Time between two `/proc` samples.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Bytes of each file scanned for environment variable names.
+const MAX_SCAN_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Locations left out of the draft allowlists.
+const SYSTEM_PREFIXES: &[&str] = &[
+    "/bin", "/dev", "/etc", "/lib", "/lib32", "/lib64", "/libx32", "/proc", "/run", "/sbin",
+    "/sys", "/usr", "/System", "/Library", "/private", "/Volumes",
+];
+
+/// Variables allowlisted whenever they are set, since the C library and
+/// terminal handling read them.
+const BASE_ENV: &[&str] = &["HOME", "LANG", "LC_ALL", "LC_CTYPE", "PATH", "TERM"];
+
+/// What to observe.
+#[derive(Clone, Debug)]
+pub struct InferConfig {
+    /// Command to run: an absolute path, a path relative to `cwd`, or a name
+    /// looked up on `PATH`.
+    pub command: String,
+    /// Command arguments.
+    pub args: Vec<String>,
+    /// Working directory (absolute); the current directory when `None`.
+    pub cwd: Option<String>,
+    /// Terminal size.
+    pub size: TerminalSize,
+    /// How long to let the command run before stopping it.
+    pub duration: Duration,
+    /// Run under `strace` when it is on `PATH`.
+    pub strace: bool,
+    /// Acknowledge that the command runs unsandboxed with the full environment.
+    pub ack: bool,
+}
+
+impl InferConfig {
+    /// Observe `command` for [`DEFAULT_OBSERVE_DURATION`] in the current
+    /// directory, using `strace` when available. `ack` starts `false`.
+    #[must_use]
+    pub fn new(command: impl Into<String>, args: Vec<String>) -> Self {
+        Self {
+            command: command.into(),
+            args,
+            cwd: None,
+            size: TerminalSize::default(),
+            duration: DEFAULT_OBSERVE_DURATION,
+            strace: true,
+            ack: false,
+        }
+    }
+}
+
+/// Files, executables, and sockets seen so far.
+#[derive(Default)]
+struct Accesses {
+    executables: BTreeSet<String>,
+    read: BTreeSet<String>,
+    written: BTreeSet<String>,
+    network: bool,
+}
+
+/// Run `config.command` once, observe it, and draft a policy from what it
+/// touched. See the module docs.
+///
+/// # Errors
+/// - `E_POLICY_DENIED` if `config.ack` is false
+/// - `E_CLI_INVALID_ARG` if `cwd` is not absolute or the command is not found
+/// - `E_IO` if the command cannot be spawned
+pub fn infer_policy(config: &InferConfig) -> RunnerResult<PolicyInference> {
+    if !config.ack {
+        return Err(RunnerError::policy_denied(
+            "E_POLICY_DENIED",
+            "policy inference runs the command without a sandbox",
+            serde_json::json!({
+                "command": config.command,
+                "fix": "Acknowledge with --ack-unsafe-observe (InferConfig::ack)",
+                "note": "The command gets full filesystem, network, and environment access"
+            }),
+        ));
+    }
+    let cwd = resolve_cwd(config.cwd.as_deref())?;
+    let command = resolve_command(&config.command, &cwd)?;
+    let strace_bin = if config.strace {
+        find_on_path("strace")
+    } else {
+        None
+    };
+    let run_id = RunId::new();
+    let trace_log = std::env::temp_dir().join(format!("ptybox-infer-{run_id}.strace"));
+    let (spawn_command, spawn_args) = match &strace_bin {
+        Some(bin) => (
+            bin.display().to_string(),
+            strace::strace_args(&trace_log, &command, &config.args),
+        ),
+        None => (command.clone(), config.args.clone()),
+    };
+
+    let started = Instant::now();
+    let mut session = Session::spawn(SessionConfig {
+        command: spawn_command,
+        args: spawn_args,
+        cwd: Some(cwd.display().to_string()),
+        size: config.size.clone(),
+        run_id,
+        env: observed_env(),
+        separate_stderr: false,
+        output_buffer: crate::model::OutputBufferConfig::default(),
+        resize: crate::model::ResizeConfig::default(),
+        remote: None,
+        encoding: crate::model::policy::TerminalEncoding::default(),
+    })?;
+    let sampler = if strace_bin.is_some() {
+        None
+    } else {
+        ProcSampler::new(session.process_id())
+    };
+    let source = match (&strace_bin, &sampler) {
+        (Some(_), _) => "strace",
+        (None, Some(_)) => "proc",
+        (None, None) => "none",
+    };
+    let mut accesses = Accesses::default();
+    let exit_status = observe(
+        &mut session,
+        config.duration,
+        sampler.as_ref(),
+        &mut accesses,
+    )?;
+    let duration_ms = elapsed_ms(&started);
+    if strace_bin.is_some() {
+        let log = std::fs::read_to_string(&trace_log).unwrap_or_default();
+        let _ = std::fs::remove_file(&trace_log);
+        strace::parse_log(&log, &cwd, &mut accesses);
+    }
+
+    let observed = observed_access(accesses, &command);
+    let (policy, notes) = draft_policy(&observed, &cwd);
+    Ok(PolicyInference {
+        policy,
+        source: source.to_string(),
+        observed,
+        exit_status,
+        duration_ms,
+        notes,
+    })
+}
+
+/// Accesses recorded in an `strace -f` log (as written by `policy init`),
+/// with relative paths resolved against `cwd`. `env` is left empty.
+#[must_use]
+pub fn parse_strace_log(log: &str, cwd: &Path) -> ObservedAccess {
+    let mut accesses = Accesses::default();
+    strace::parse_log(log, cwd, &mut accesses);
+    ObservedAccess {
+        executables: accesses.executables.into_iter().collect(),
+        read: accesses.read.into_iter().collect(),
+        written: accesses.written.into_iter().collect(),
+        env: Vec::new(),
+        network: accesses.network,
+    }
+}
+
+/// Let the command run until it exits or `duration` passes, sampling
+/// `/proc` and draining output; then stop it.
+fn observe(
+    session: &mut Session,
+    duration: Duration,
+    sampler: Option<&ProcSampler>,
+    accesses: &mut Accesses,
+) -> RunnerResult<Option<crate::model::ExitStatus>> {
+    let deadline = Instant::now() + duration;
+    loop {
+        if let Some(sampler) = sampler {
+            sampler.sample(accesses);
+        }
+        if let Some(status) = session.wait_for_exit(Duration::ZERO)? {
+            return Ok(Some(convert_exit_status(status, false)));
+        }
+        if Instant::now() >= deadline {
+            break;
+        }
+        let tick = Instant::now() + SAMPLE_INTERVAL;
+        // Output is only drained so the child does not block on a full PTY.
+        let _ = session.observe(SAMPLE_INTERVAL);
+        pause_until(tick, SAMPLE_INTERVAL);
+    }
+    Ok(session
+        .terminate_with(&ProcessPolicy::default())?
+        .exit_status())
+}
+
+fn resolve_cwd(cwd: Option<&str>) -> RunnerResult<PathBuf> {
+    let cwd = match cwd {
+        Some(cwd) => PathBuf::from(cwd),
+        None => std::env::current_dir()
+            .map_err(|err| RunnerError::io("E_IO", "failed to read current directory", err))?,
+    };
+    if !cwd.is_absolute() {
+        return Err(RunnerError::with_context(
+            ErrorCode::CliInvalidArg,
+            "cwd must be an absolute path",
+            serde_json::json!({"cwd": cwd}),
+        ));
+    }
+    Ok(std::fs::canonicalize(&cwd).unwrap_or(cwd))
+}
+
+/// Absolute, symlink-free path of the command.
+fn resolve_command(command: &str, cwd: &Path) -> RunnerResult<String> {
+    let path = if command.contains('/') {
+        Some(cwd.join(command))
+    } else {
+        find_on_path(command)
+    };
+    path.and_then(|path| std::fs::canonicalize(path).ok())
+        .filter(|path| path.is_file())
+        .map(|path| path.display().to_string())
+        .ok_or_else(|| {
+            RunnerError::with_context(
+                ErrorCode::CliInvalidArg,
+                "command not found",
+                serde_json::json!({
+                    "command": command,
+                    "fix": "Use an absolute path or a command on PATH"
+                }),
+            )
+        })
+}
+
+fn find_on_path(name: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// The parent environment, minus variables that are always blocked.
+fn observed_env() -> EnvPolicy {
+    EnvPolicy {
+        allowlist: inherited_env_names().into_iter().collect(),
+        set: BTreeMap::new(),
+        inherit: true,
+    }
+}
+
+fn inherited_env_names() -> BTreeSet<String> {
+    std::env::vars_os()
+        .filter_map(|(name, _)| name.into_string().ok())
+        .filter(|name| !super::is_dangerous_env_var(name))
+        .collect()
+}
+
+/// Names specific enough to count as a reference when seen in a file (not
+/// `_` and other one-character names).
+fn is_scannable_env_name(name: &str) -> bool {
+    name.len() > 1 && name.bytes().any(|byte| byte.is_ascii_alphabetic())
+}
+
+fn observed_access(accesses: Accesses, command: &str) -> ObservedAccess {
+    let mut executables = vec![command.to_string()];
+    executables.extend(
+        accesses
+            .executables
+            .into_iter()
+            .filter(|exe| exe != command),
+    );
+    let read: Vec<String> = accesses.read.into_iter().collect();
+    let env = referenced_env(
+        executables
+            .iter()
+            .chain(read.iter().filter(|path| !is_system_path(path))),
+    );
+    ObservedAccess {
+        executables,
+        read,
+        written: accesses.written.into_iter().collect(),
+        env,
+        network: accesses.network,
+    }
+}
+
+/// Inherited variables named in any of `files`, plus [`BASE_ENV`].
+fn referenced_env<'a>(files: impl Iterator<Item = &'a String>) -> Vec<String> {
+    let inherited = inherited_env_names();
+    let wanted: HashSet<&str> = inherited
+        .iter()
+        .map(String::as_str)
+        .filter(|name| is_scannable_env_name(name))
+        .collect();
+    let mut referenced = mentioned_names(files, &wanted);
+    referenced.extend(
+        BASE_ENV
+            .iter()
+            .filter(|name| inherited.contains(**name))
+            .map(|name| (*name).to_string()),
+    );
+    referenced.into_iter().collect()
+}
+
+/// The `wanted` names that appear as whole words in the first
+/// [`MAX_SCAN_BYTES`] of any of `files`; unreadable files are skipped.
+fn mentioned_names<'a>(
+    files: impl Iterator<Item = &'a String>,
+    wanted: &HashSet<&str>,
+) -> BTreeSet<String> {
+    let mut mentioned = BTreeSet::new();
+    for file in files {
+        let mut data = Vec::new();
+        let Ok(handle) = std::fs::File::open(file) else {
+            continue;
+        };
+        if handle.take(MAX_SCAN_BYTES).read_to_end(&mut data).is_err() {
+            continue;
+        }
+        mentioned.extend(
+            data.split(|byte| !(byte.is_ascii_alphanumeric() || *byte == b'_'))
+                .filter_map(|token| std::str::from_utf8(token).ok())
+                .filter(|token| wanted.contains(token))
+                .map(str::to_string),
+        );
+    }
+    mentioned
+}
+
+fn is_system_path(path: &str) -> bool {
+    SYSTEM_PREFIXES
+        .iter()
+        .any(|prefix| Path::new(path).starts_with(prefix))
+}
+
+fn draft_policy(observed: &ObservedAccess, cwd: &Path) -> (Policy, Vec<String>) {
+    let mut notes = Vec::new();
+    let cwd = cwd.display().to_string();
+    let allowed_write = allowlist_dirs(observed.written.iter(), "write", &mut notes);
+    let allowed_read = allowlist_dirs(
+        observed.read.iter().chain(std::iter::once(&cwd)),
+        "read",
+        &mut notes,
+    );
+    let working_dir = if super::path_allowed(&cwd, &allowed_read, &allowed_write) {
+        Some(cwd)
+    } else {
+        notes.push(format!(
+            "working directory {cwd} cannot be allowlisted; run with a cwd under an allowlisted path"
+        ));
+        None
+    };
+    if observed.network {
+        notes.push(
+            "the command opened an internet socket; network stays disabled in the draft"
+                .to_string(),
+        );
+    }
+    let allow_shell = observed
+        .executables
+        .iter()
+        .any(|exe| super::is_shell_command(exe, &[]));
+    let policy = Policy {
+        fs: FsPolicy {
+            write_ack: !allowed_write.is_empty(),
+            allowed_read,
+            allowed_write,
+            working_dir,
+            strict_write: false,
+            write_quotas: BTreeMap::new(),
+        },
+        exec: ExecPolicy {
+            allowed_executables: observed.executables.clone(),
+            allow_shell,
+        },
+        env: EnvPolicy {
+            allowlist: observed.env.clone(),
+            set: BTreeMap::new(),
+            inherit: true,
+        },
+        ..Policy::default()
+    };
+    (policy, notes)
+}
+
+/// The directories holding `paths` (a directory stands for itself), without
+/// system locations, paths policy validation rejects, or directories inside
+/// another listed one.
+fn allowlist_dirs<'a>(
+    paths: impl Iterator<Item = &'a String>,
+    access: &str,
+    notes: &mut Vec<String>,
+) -> Vec<String> {
+    let mut system = 0usize;
+    let mut dirs = BTreeSet::new();
+    for path in paths {
+        if is_system_path(path) {
+            system += 1;
+            continue;
+        }
+        let path = Path::new(path);
+        let dir = if path.is_dir() {
+            Some(path)
+        } else {
+            path.parent()
+        };
+        dirs.extend(dir.map(|dir| dir.display().to_string()));
+    }
+    if system > 0 {
+        notes.push(format!(
+            "{system} {access} path(s) under system locations are left to the platform sandbox profile"
+        ));
+    }
+    let mut allowed: Vec<String> = Vec::new();
+    for dir in dirs {
+        let trial = FsPolicy {
+            allowed_read: vec![dir.clone()],
+            ..FsPolicy::default()
+        };
+        if let Err(err) = super::validate_fs_policy(&trial) {
+            notes.push(format!(
+                "{access} access to {dir} left out: {}",
+                err.message
+            ));
+            continue;
+        }
+        if !allowed.iter().any(|kept| Path::new(&dir).starts_with(kept)) {
+            allowed.push(dir);
+        }
+    }
+    allowed
+}
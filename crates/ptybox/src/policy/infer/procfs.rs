@@ -0,0 +1,140 @@
+//! `/proc` sampling of a running process tree (Linux).
+//!
+//! Each sample records the executable, open files (with their access mode
+//! from `fdinfo`), and mapped files of every process descended from the
+//! root. Files opened and closed between two samples are missed.
+
+use super::Accesses;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// `O_ACCMODE` bits of `fdinfo` flags.
+const ACCESS_MODE_MASK: u32 = 0o3;
+/// `O_WRONLY`.
+const WRITE_ONLY: u32 = 0o1;
+/// `O_RDWR`.
+const READ_WRITE: u32 = 0o2;
+
+/// Samples the process tree under one pid.
+pub(super) struct ProcSampler {
+    root: u32,
+}
+
+impl ProcSampler {
+    /// A sampler for the tree under `root`, when `/proc` is available.
+    pub(super) fn new(root: Option<u32>) -> Option<Self> {
+        let root = root?;
+        Path::new("/proc/self/fd").is_dir().then_some(Self { root })
+    }
+
+    /// Record what every process in the tree has open now.
+    pub(super) fn sample(&self, accesses: &mut Accesses) {
+        for pid in self.tree() {
+            sample_process(pid, accesses);
+        }
+    }
+
+    /// The root and all its descendants, by parent pid.
+    fn tree(&self) -> Vec<u32> {
+        let mut children: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+        if let Ok(entries) = fs::read_dir("/proc") {
+            for entry in entries.flatten() {
+                let Some(pid) = entry
+                    .file_name()
+                    .to_str()
+                    .and_then(|name| name.parse().ok())
+                else {
+                    continue;
+                };
+                if let Some(parent) = parent_pid(pid) {
+                    children.entry(parent).or_default().push(pid);
+                }
+            }
+        }
+        let mut tree = vec![self.root];
+        let mut index = 0;
+        while let Some(pid) = tree.get(index).copied() {
+            tree.extend(children.remove(&pid).unwrap_or_default());
+            index += 1;
+        }
+        tree
+    }
+}
+
+/// Parent pid from `/proc/<pid>/stat` (the field after the state, which
+/// follows the parenthesized command name).
+fn parent_pid(pid: u32) -> Option<u32> {
+    let stat = fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    let (_, fields) = stat.rsplit_once(") ")?;
+    fields.split_whitespace().nth(1)?.parse().ok()
+}
+
+fn sample_process(pid: u32, accesses: &mut Accesses) {
+    let base = PathBuf::from(format!("/proc/{pid}"));
+    if let Ok(exe) = fs::read_link(base.join("exe")) {
+        accesses.executables.insert(exe.display().to_string());
+    }
+    let mut sockets = BTreeSet::new();
+    if let Ok(entries) = fs::read_dir(base.join("fd")) {
+        for entry in entries.flatten() {
+            let Ok(target) = fs::read_link(entry.path()) else {
+                continue;
+            };
+            let target = target.display().to_string();
+            if let Some(inode) = target
+                .strip_prefix("socket:[")
+                .and_then(|rest| rest.strip_suffix(']'))
+            {
+                sockets.insert(inode.to_string());
+                continue;
+            }
+            if !target.starts_with('/') {
+                continue;
+            }
+            let path = target.trim_end_matches(" (deleted)").to_string();
+            let mode = access_mode(&base, &entry.file_name().to_string_lossy());
+            if matches!(mode, Some(WRITE_ONLY | READ_WRITE)) {
+                accesses.written.insert(path.clone());
+            }
+            if mode != Some(WRITE_ONLY) {
+                accesses.read.insert(path);
+            }
+        }
+    }
+    if let Ok(maps) = fs::read_to_string(base.join("maps")) {
+        accesses.read.extend(
+            maps.lines()
+                .filter_map(|line| line.split_whitespace().nth(5))
+                .filter(|path| path.starts_with('/'))
+                .map(str::to_string),
+        );
+    }
+    if !sockets.is_empty() && !accesses.network {
+        accesses.network = has_inet_socket(&base, &sockets);
+    }
+}
+
+/// `O_ACCMODE` of an open descriptor, from `/proc/<pid>/fdinfo/<fd>`.
+fn access_mode(base: &Path, fd: &str) -> Option<u32> {
+    let info = fs::read_to_string(base.join("fdinfo").join(fd)).ok()?;
+    let flags = info
+        .lines()
+        .find_map(|line| line.strip_prefix("flags:"))?
+        .trim();
+    u32::from_str_radix(flags, 8)
+        .ok()
+        .map(|flags| flags & ACCESS_MODE_MASK)
+}
+
+/// Whether any of the socket inodes is a TCP or UDP socket.
+fn has_inet_socket(base: &Path, inodes: &BTreeSet<String>) -> bool {
+    ["tcp", "tcp6", "udp", "udp6"].iter().any(|table| {
+        fs::read_to_string(base.join("net").join(table)).is_ok_and(|data| {
+            data.lines()
+                .skip(1)
+                .filter_map(|line| line.split_whitespace().nth(9))
+                .any(|inode| inodes.contains(inode))
+        })
+    })
+}
@@ -0,0 +1,155 @@
+//! `strace -f` logs as observed accesses.
+
+use super::Accesses;
+use crate::policy::canonicalize_for_policy;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Syscalls whose path arguments are all modified.
+const WRITE_CALLS: &[&str] = &[
+    "creat",
+    "mkdir",
+    "mkdirat",
+    "rmdir",
+    "unlink",
+    "unlinkat",
+    "rename",
+    "renameat",
+    "renameat2",
+    "link",
+    "linkat",
+    "symlink",
+    "symlinkat",
+    "truncate",
+    "mknod",
+    "mknodat",
+];
+
+/// `open` flags that make an open a write.
+const WRITE_FLAGS: &[&str] = &["O_WRONLY", "O_RDWR", "O_CREAT", "O_TRUNC", "O_APPEND"];
+
+/// Arguments that run `command` under `strace`, logging to `log`.
+pub(super) fn strace_args(log: &Path, command: &str, args: &[String]) -> Vec<String> {
+    let mut strace_args = vec![
+        "-f".to_string(),
+        "-qq".to_string(),
+        "-s".to_string(),
+        "4096".to_string(),
+        "-e".to_string(),
+        "trace=%file,%process,connect".to_string(),
+        "-o".to_string(),
+        log.display().to_string(),
+        "--".to_string(),
+        command.to_string(),
+    ];
+    strace_args.extend(args.iter().cloned());
+    strace_args
+}
+
+/// Record the successful file, exec, and connect calls in an `strace -f`
+/// log. Relative paths are resolved against `cwd`.
+pub(super) fn parse_log(log: &str, cwd: &Path, accesses: &mut Accesses) {
+    let mut pending: HashMap<&str, String> = HashMap::new();
+    for line in log.lines() {
+        let (pid, rest) = match line.split_once(' ') {
+            Some((pid, rest)) if pid.bytes().all(|byte| byte.is_ascii_digit()) => {
+                (pid, rest.trim_start())
+            }
+            _ => ("", line),
+        };
+        if let Some(head) = rest.strip_suffix(" <unfinished ...>") {
+            pending.insert(pid, head.to_string());
+            continue;
+        }
+        if let Some(resumed) = rest.strip_prefix("<... ") {
+            let head = pending.remove(pid);
+            if let (Some(head), Some((_, tail))) = (head, resumed.split_once(" resumed>")) {
+                record_call(&format!("{head}{tail}"), cwd, accesses);
+            }
+            continue;
+        }
+        record_call(rest, cwd, accesses);
+    }
+}
+
+fn record_call(call: &str, cwd: &Path, accesses: &mut Accesses) {
+    let Some((name, rest)) = call.split_once('(') else {
+        return;
+    };
+    let Some((args, result)) = rest.rsplit_once(") = ") else {
+        return;
+    };
+    if result.trim_start().starts_with('-') {
+        return;
+    }
+    let relative_ok =
+        !name.ends_with("at") && !name.ends_with("at2") || args.starts_with("AT_FDCWD");
+    let resolve = |path: String| -> Option<String> {
+        let path = PathBuf::from(path);
+        if path.is_absolute() {
+            Some(canonicalize_for_policy(&path).display().to_string())
+        } else if relative_ok {
+            Some(
+                canonicalize_for_policy(&cwd.join(path))
+                    .display()
+                    .to_string(),
+            )
+        } else {
+            None
+        }
+    };
+    match name {
+        "execve" | "execveat" => {
+            if let Some(path) = next_quoted(args).and_then(|(path, _)| resolve(path)) {
+                accesses.executables.insert(path);
+            }
+        }
+        "open" | "openat" | "openat2" => {
+            let Some((path, flags)) = next_quoted(args) else {
+                return;
+            };
+            let Some(path) = resolve(path) else {
+                return;
+            };
+            if WRITE_FLAGS.iter().any(|flag| flags.contains(flag)) {
+                accesses.written.insert(path.clone());
+            }
+            if !flags.contains("O_WRONLY") {
+                accesses.read.insert(path);
+            }
+        }
+        "connect" => accesses.network |= args.contains("AF_INET"),
+        _ if WRITE_CALLS.contains(&name) => {
+            let mut rest = args;
+            while let Some((path, tail)) = next_quoted(rest) {
+                accesses.written.extend(resolve(path));
+                rest = tail;
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The next double-quoted string in `text`, unescaped, and the text after it.
+fn next_quoted(text: &str) -> Option<(String, &str)> {
+    let (_, rest) = text.split_once('"')?;
+    let mut value = String::new();
+    let mut chars = rest.char_indices();
+    while let Some((index, ch)) = chars.next() {
+        match ch {
+            '"' => return Some((value, rest.get(index + 1..).unwrap_or_default())),
+            '\\' => match chars.next() {
+                Some((_, escaped @ ('"' | '\\'))) => value.push(escaped),
+                Some((_, 'n')) => value.push('\n'),
+                Some((_, 't')) => value.push('\t'),
+                Some((_, other)) => {
+                    value.push('\\');
+                    value.push(other);
+                }
+                None => return None,
+            },
+            _ => value.push(ch),
+        }
+    }
+    None
+}
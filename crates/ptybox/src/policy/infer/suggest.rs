@@ -0,0 +1,313 @@
+//! Policy suggestions after a run (`ptybox policy suggest`).
+//!
+//! [`suggest_policy`] compares the policy in a finished run's artifacts
+//! with what the run left evidence of using:
+//!
+//! - executables: the run's command, plus allowlisted executables that
+//!   appear as the process of a sandbox denial in `violations.json`
+//! - writes: the files `fs-manifest.json` lists under each
+//!   `fs.allowed_write` entry (policy `artifacts.fs_manifest`); an entry is
+//!   narrowed to the directories holding them, plus the artifacts directory
+//! - environment: allowlisted variables the kept executables mention by
+//!   name, found as in [`infer_policy`](super::infer_policy)
+//! - network: `network*` denials in `violations.json`
+//!
+//! Runs do not record reads, so `fs.allowed_read` is left as is. What could
+//! not be checked is listed in [`PolicySuggestions::notes`].
+
+use super::{allowlist_dirs, is_scannable_env_name, mentioned_names, BASE_ENV};
+use crate::model::policy::{NetworkPolicy, Policy};
+use crate::model::{
+    FsManifest, PolicyChange, PolicySuggestions, RunResult, SandboxViolation,
+    SandboxViolationReport,
+};
+use crate::policy::{canonicalize_for_policy, is_shell_command, path_allowed};
+use crate::runner::{ErrorCode, RunnerError, RunnerResult};
+use serde::de::DeserializeOwned;
+use std::collections::{BTreeSet, HashSet};
+use std::path::Path;
+
+/// File the suggestions are written to in the artifacts directory.
+pub const SUGGESTIONS_FILE: &str = "policy-suggestions.json";
+
+/// Suggest a tighter policy for the run in `artifacts_dir`. See the module
+/// docs.
+///
+/// # Errors
+/// - `E_IO` if `run.json` is missing or an artifact cannot be read
+/// - `E_PROTOCOL` if `run.json`, `policy.json`, `fs-manifest.json`, or
+///   `violations.json` does not parse
+pub fn suggest_policy(artifacts_dir: &Path) -> RunnerResult<PolicySuggestions> {
+    let run: RunResult = load_json(artifacts_dir, "run.json")?.ok_or_else(|| {
+        RunnerError::with_context(
+            ErrorCode::Io,
+            "artifacts directory has no run.json",
+            serde_json::json!({ "artifacts_dir": artifacts_dir.display().to_string() }),
+        )
+    })?;
+    let policy: Policy =
+        load_json(artifacts_dir, "policy.json")?.unwrap_or_else(|| run.policy.clone());
+    let manifest: Option<FsManifest> = load_json(artifacts_dir, "fs-manifest.json")?;
+    let report: Option<SandboxViolationReport> = load_json(artifacts_dir, "violations.json")?;
+
+    let mut draft = Draft {
+        policy,
+        changes: Vec::new(),
+        notes: Vec::new(),
+    };
+    let denied = match report {
+        Some(report) if report.collected => report.violations,
+        Some(report) => {
+            draft.notes.push(format!(
+                "sandbox denials were not collected ({}); executables started by the command and network attempts are not known",
+                report.reason.as_deref().unwrap_or("no reason given")
+            ));
+            Vec::new()
+        }
+        None => {
+            draft.notes.push(
+                "no violations.json (sandbox auditing, feature sandbox-audit); executables started by the command and network attempts are not known"
+                    .to_string(),
+            );
+            Vec::new()
+        }
+    };
+    draft.executables(&run.command, &denied);
+    let artifacts_dir = artifacts_dir
+        .canonicalize()
+        .unwrap_or_else(|_| artifacts_dir.to_path_buf());
+    draft.writes(&run.cwd, manifest.as_ref(), &artifacts_dir);
+    draft.env();
+    let network_attempts = network_attempts(&denied);
+    draft.network(&network_attempts);
+    draft.notes.push(
+        "fs.allowed_read is left as is: runs do not record reads (ptybox policy init observes them)"
+            .to_string(),
+    );
+    Ok(PolicySuggestions {
+        policy: draft.policy,
+        changes: draft.changes,
+        network_attempts,
+        notes: draft.notes,
+    })
+}
+
+/// Candidate policy being tightened.
+struct Draft {
+    policy: Policy,
+    changes: Vec<PolicyChange>,
+    notes: Vec<String>,
+}
+
+impl Draft {
+    fn change(&mut self, field: &str, value: &str, replaced_by: Vec<String>, reason: &str) {
+        self.changes.push(PolicyChange {
+            field: field.to_string(),
+            value: value.to_string(),
+            replaced_by,
+            reason: reason.to_string(),
+        });
+    }
+
+    fn executables(&mut self, command: &str, denied: &[SandboxViolation]) {
+        let command_path = canonicalize_for_policy(Path::new(command));
+        let processes: HashSet<&str> = denied
+            .iter()
+            .map(|violation| violation.process.as_str())
+            .collect();
+        let mut unused = Vec::new();
+        self.policy.exec.allowed_executables.retain(|exe| {
+            let path = Path::new(exe);
+            let used = canonicalize_for_policy(path) == command_path
+                || path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| processes.contains(name));
+            if !used {
+                unused.push(exe.clone());
+            }
+            used
+        });
+        for exe in &unused {
+            self.change(
+                "exec.allowed_executables",
+                exe,
+                Vec::new(),
+                "not the run's command and not seen in sandbox denials",
+            );
+        }
+        let drop_shell = self.policy.exec.allow_shell && !is_shell_command(command, &[]);
+        if drop_shell {
+            self.policy.exec.allow_shell = false;
+            self.change(
+                "exec.allow_shell",
+                "true",
+                Vec::new(),
+                "the command is not a shell",
+            );
+        }
+        if !unused.is_empty() || drop_shell {
+            self.notes.push(
+                "keep any executable the command starts itself; only the command and sandbox denials are observed"
+                    .to_string(),
+            );
+        }
+    }
+
+    fn writes(&mut self, cwd: &str, manifest: Option<&FsManifest>, artifacts_dir: &Path) {
+        let Some(manifest) = manifest else {
+            self.notes.push(
+                "no fs-manifest.json; fs.allowed_write is left as is (set artifacts.fs_manifest to list the files a run leaves)"
+                    .to_string(),
+            );
+            return;
+        };
+        if manifest.truncated {
+            self.notes
+                .push("fs-manifest.json is truncated; fs.allowed_write is left as is".to_string());
+            return;
+        }
+        let artifacts = artifacts_dir.display().to_string();
+        let mut narrowed = Vec::new();
+        let mut changes = Vec::new();
+        for entry in &self.policy.fs.allowed_write {
+            let files = manifest
+                .files
+                .iter()
+                .map(|file| &file.path)
+                .filter(|path| Path::new(path).starts_with(entry));
+            let mut dirs = allowlist_dirs(files, "write", &mut self.notes);
+            if artifacts_dir.starts_with(entry)
+                && !dirs.iter().any(|dir| artifacts_dir.starts_with(dir))
+            {
+                dirs.push(artifacts.clone());
+            }
+            if dirs.len() != 1 || dirs.first() != Some(entry) {
+                let reason = if dirs.is_empty() {
+                    "no files under it after the run"
+                } else {
+                    "files after the run are only under the replacements"
+                };
+                changes.push((entry.clone(), dirs.clone(), reason));
+            }
+            for dir in dirs {
+                if !narrowed.contains(&dir) {
+                    narrowed.push(dir);
+                }
+            }
+        }
+        let fs = &self.policy.fs;
+        let cwds = std::iter::once(cwd).chain(fs.working_dir.as_deref());
+        if let Some(lost) = cwds
+            .filter(|dir| !path_allowed(dir, &fs.allowed_read, &narrowed))
+            .find(|dir| path_allowed(dir, &fs.allowed_read, &fs.allowed_write))
+        {
+            self.notes.push(format!(
+                "fs.allowed_write is left as is: narrowing it would leave the working directory {lost} outside the allowlists"
+            ));
+            return;
+        }
+        for (entry, dirs, reason) in changes {
+            self.change("fs.allowed_write", &entry, dirs, reason);
+        }
+        let quotas = std::mem::take(&mut self.policy.fs.write_quotas);
+        for (path, max_bytes) in quotas {
+            if path_allowed(&path, &[], &narrowed) {
+                self.policy.fs.write_quotas.insert(path, max_bytes);
+            } else {
+                self.change(
+                    "fs.write_quotas",
+                    &path,
+                    Vec::new(),
+                    "outside the narrowed fs.allowed_write",
+                );
+            }
+        }
+        self.policy.fs.write_ack &= !narrowed.is_empty();
+        self.policy.fs.allowed_write = narrowed;
+    }
+
+    fn env(&mut self) {
+        let env = &self.policy.env;
+        let scanned: HashSet<&str> = env
+            .allowlist
+            .iter()
+            .map(String::as_str)
+            .filter(|name| is_scannable_env_name(name))
+            .collect();
+        let mentioned = mentioned_names(self.policy.exec.allowed_executables.iter(), &scanned);
+        let (kept, unused): (Vec<String>, Vec<String>) =
+            env.allowlist.iter().cloned().partition(|name| {
+                BASE_ENV.contains(&name.as_str())
+                    || env.set.contains_key(name)
+                    || mentioned.contains(name)
+            });
+        if unused.is_empty() {
+            return;
+        }
+        self.policy.env.allowlist = kept;
+        for name in &unused {
+            self.change(
+                "env.allowlist",
+                name,
+                Vec::new(),
+                "not named in any allowed executable",
+            );
+        }
+        self.notes.push(
+            "environment reads are found by scanning executables for variable names; variables read by scripts or under computed names are missed"
+                .to_string(),
+        );
+    }
+
+    fn network(&mut self, attempts: &[String]) {
+        match self.policy.network {
+            NetworkPolicy::Disabled if !attempts.is_empty() => self.notes.push(format!(
+                "the sandbox denied {} network operation(s); network stays disabled",
+                attempts.len()
+            )),
+            NetworkPolicy::Disabled => {}
+            NetworkPolicy::Enabled { .. } => self.notes.push(
+                "network stays enabled: allowed connections are not logged; rerun with network disabled and sandbox auditing to see whether the command needs it"
+                    .to_string(),
+            ),
+        }
+    }
+}
+
+/// Distinct denied network operations, as `operation target`.
+fn network_attempts(denied: &[SandboxViolation]) -> Vec<String> {
+    denied
+        .iter()
+        .filter(|violation| violation.operation.starts_with("network"))
+        .map(|violation| match &violation.target {
+            Some(target) => format!("{} {target}", violation.operation),
+            None => violation.operation.clone(),
+        })
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+fn load_json<T: DeserializeOwned>(dir: &Path, name: &str) -> RunnerResult<Option<T>> {
+    let path = dir.join(name);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let context =
+        |err: String| serde_json::json!({ "path": path.display().to_string(), "source": err });
+    let data = std::fs::read(&path).map_err(|err| {
+        RunnerError::with_context(
+            ErrorCode::Io,
+            format!("failed to read {name}"),
+            context(err.to_string()),
+        )
+    })?;
+    serde_json::from_slice(&data).map(Some).map_err(|err| {
+        RunnerError::with_context(
+            ErrorCode::Protocol,
+            format!("failed to parse {name}"),
+            context(err.to_string()),
+        )
+    })
+}
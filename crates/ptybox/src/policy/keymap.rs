@@ -0,0 +1,195 @@
+//! Built-in and file-based keymaps for `input.keymap`.
+//!
+//! A keymap translates `key` actions written for a US QWERTY keyboard into
+//! the characters another layout produces at the same positions, so a
+//! scenario that types `y` on a German layout sends `z`. Keys a keymap does
+//! not list (named keys, `Ctrl-*`, unmapped characters) keep the default
+//! translation.
+//!
+//! `input.keymap` names a built-in keymap ([`BUILTIN_KEYMAPS`]) or, when it
+//! contains a `/` or ends in `.json`/`.yaml`/`.yml`, an absolute path to a
+//! [`Keymap`] file.
+
+use crate::model::policy::{InputPolicy, Keymap, KEYMAP_VERSION};
+use crate::runner::{RunnerError, RunnerResult};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Names of the built-in keymaps.
+pub const BUILTIN_KEYMAPS: [&str; 3] = ["us", "de", "fr"];
+
+/// German QWERTZ, as (US key, text sent) pairs.
+const DE_KEYS: &[(&str, &str)] = &[
+    ("y", "z"),
+    ("z", "y"),
+    ("Y", "Z"),
+    ("Z", "Y"),
+    ("-", "ß"),
+    ("[", "ü"),
+    ("]", "+"),
+    ("\\", "#"),
+    (";", "ö"),
+    ("'", "ä"),
+    ("/", "-"),
+    ("@", "\""),
+    ("#", "§"),
+    ("^", "&"),
+    ("&", "/"),
+    ("*", "("),
+    ("(", ")"),
+    (")", "="),
+    ("_", "?"),
+    ("{", "Ü"),
+    ("}", "*"),
+    ("|", "'"),
+    (":", "Ö"),
+    ("\"", "Ä"),
+    ("<", ";"),
+    (">", ":"),
+    ("?", "_"),
+];
+
+/// French AZERTY, as (US key, text sent) pairs.
+const FR_KEYS: &[(&str, &str)] = &[
+    ("a", "q"),
+    ("q", "a"),
+    ("z", "w"),
+    ("w", "z"),
+    ("A", "Q"),
+    ("Q", "A"),
+    ("Z", "W"),
+    ("W", "Z"),
+    (";", "m"),
+    (":", "M"),
+    ("m", ","),
+    ("M", "?"),
+    (",", ";"),
+    ("<", "."),
+    (".", ":"),
+    (">", "/"),
+    ("/", "!"),
+    ("?", "§"),
+    ("1", "&"),
+    ("2", "é"),
+    ("3", "\""),
+    ("4", "'"),
+    ("5", "("),
+    ("6", "-"),
+    ("7", "è"),
+    ("8", "_"),
+    ("9", "ç"),
+    ("0", "à"),
+    ("-", ")"),
+    ("!", "1"),
+    ("@", "2"),
+    ("#", "3"),
+    ("$", "4"),
+    ("%", "5"),
+    ("^", "6"),
+    ("&", "7"),
+    ("*", "8"),
+    ("(", "9"),
+    (")", "0"),
+    ("_", "°"),
+    ("]", "$"),
+    ("}", "£"),
+    ("'", "ù"),
+    ("\"", "%"),
+    ("\\", "*"),
+    ("|", "µ"),
+];
+
+/// The built-in keymap called `name`, if there is one.
+///
+/// `us` is the identity keymap.
+pub fn builtin_keymap(name: &str) -> Option<Keymap> {
+    let keys: &[(&str, &str)] = match name {
+        "us" => &[],
+        "de" => DE_KEYS,
+        "fr" => FR_KEYS,
+        _ => return None,
+    };
+    Some(Keymap {
+        keymap_version: KEYMAP_VERSION,
+        name: name.to_string(),
+        keys: keys
+            .iter()
+            .map(|(key, text)| ((*key).to_string(), (*text).to_string()))
+            .collect::<BTreeMap<_, _>>(),
+    })
+}
+
+/// Load the keymap `spec` names: a built-in keymap or a keymap file.
+///
+/// # Errors
+/// - `E_POLICY_DENIED` if `spec` is neither a built-in name nor an
+///   absolute file path
+/// - `E_IO` if the file cannot be read
+/// - `E_PROTOCOL` if the file cannot be parsed or has an unsupported
+///   `keymap_version`
+pub fn load_keymap(spec: &str) -> RunnerResult<Keymap> {
+    if !is_keymap_path(spec) {
+        return builtin_keymap(spec).ok_or_else(|| {
+            RunnerError::policy_denied(
+                "E_POLICY_DENIED",
+                format!("unknown keymap '{spec}'"),
+                serde_json::json!({
+                    "keymap": spec,
+                    "builtin": BUILTIN_KEYMAPS,
+                    "fix": "Use a built-in keymap name or an absolute path to a keymap file"
+                }),
+            )
+        });
+    }
+    if !Path::new(spec).is_absolute() {
+        return Err(RunnerError::policy_denied(
+            "E_POLICY_DENIED",
+            "keymap file path must be absolute",
+            serde_json::json!({
+                "keymap": spec,
+                "fix": "Use an absolute path (or ${cwd}/...) for input.keymap"
+            }),
+        ));
+    }
+    let data = std::fs::read_to_string(spec)
+        .map_err(|err| RunnerError::io("E_IO", "failed to read keymap file", err))?;
+    let parsed = if spec.ends_with(".yaml") || spec.ends_with(".yml") {
+        serde_yml::from_str::<Keymap>(&data).map_err(|err| err.to_string())
+    } else {
+        serde_json::from_str::<Keymap>(&data).map_err(|err| err.to_string())
+    };
+    let keymap = parsed.map_err(|err| {
+        RunnerError::protocol(
+            "E_PROTOCOL",
+            "failed to parse keymap file",
+            serde_json::json!({ "keymap": spec, "error": err }),
+        )
+    })?;
+    if keymap.keymap_version != KEYMAP_VERSION {
+        return Err(RunnerError::protocol(
+            "E_PROTOCOL",
+            "unsupported keymap version",
+            serde_json::json!({
+                "keymap": spec,
+                "keymap_version": keymap.keymap_version,
+                "supported_version": KEYMAP_VERSION
+            }),
+        ));
+    }
+    Ok(keymap)
+}
+
+/// Load the keymap an input policy selects, if any.
+///
+/// # Errors
+/// As [`load_keymap`].
+pub fn resolve_keymap(input: &InputPolicy) -> RunnerResult<Option<Keymap>> {
+    input.keymap.as_deref().map(load_keymap).transpose()
+}
+
+fn is_keymap_path(spec: &str) -> bool {
+    spec.contains('/')
+        || Path::new(spec)
+            .extension()
+            .is_some_and(|ext| ext == "json" || ext == "yaml" || ext == "yml")
+}
@@ -15,11 +15,25 @@
 //! - [`validate_fs_policy`] — Filesystem path validation (absolute, no roots, no symlinks)
 //! - [`validate_network_policy`] — Network access and enforcement checks
 //! - [`validate_sandbox_mode`] — Sandbox availability and acknowledgement
+//! - [`resolve_sandbox_enforcement`] — Seatbelt fallback and enforcement report
+//! - [`container::validate_container_policy`] — Container image and mount paths
 //! - [`validate_env_policy`] — Environment variable allowlist consistency
+//! - [`validate_env_overlay`] — Step environment overlays are allowlisted
+//! - [`validate_determinism_policy`] — Pinned locale and timezone values
+//! - [`validate_process_policy`] — Pre-kill hook is allowlisted
+//! - [`validate_signal`] — `signal` actions name an allowed signal
+//! - [`validate_socket_policy`] — Socket names and environment variables
+//! - [`remote::validate_remote_target`] — SSH targets of remote sessions are allowlisted
 //! - [`validate_artifacts_policy`] — Artifacts directory within write allowlist
 //! - [`validate_write_access`] — Write acknowledgement for strict-write mode
 //! - [`explain_policy_for_run_config`] — Dry-run all checks without executing
 //! - [`apply_env_policy`] — Apply environment policy to a command builder
+//! - [`substitution::substitute_policy_paths`] — Resolve `${cwd}`/`${env:NAME}` in policy file paths
+//! - [`grants::apply_policy_grants`] — Fold the grants matching a scenario name into its policy
+//! - [`keymap::resolve_keymap`] — Load the built-in or file keymap `input.keymap` names
+//! - [`infer::infer_policy`] — Draft a policy by observing one unsandboxed run
+//! - [`infer::suggest_policy`] — Tighten a finished run's policy to what it used
+//! - [`acknowledgements::acknowledgement_report`] — Which unsafe options a run acknowledged, and where
 //!
 //! # Security Controls
 //!
@@ -31,13 +45,29 @@
 //! - Shell execution is detected and blocked unless explicitly allowed
 //! - Path traversal via `..` is normalized before validation
 
+pub mod acknowledgements;
+#[cfg(feature = "sandbox-audit")]
+pub mod audit;
+pub mod container;
+pub mod grants;
+pub mod infer;
+pub mod keymap;
+pub mod remote;
 pub mod sandbox;
+pub mod substitution;
 
 use crate::model::policy::{
-    EnvPolicy, ExecPolicy, FsPolicy, NetworkPolicy, Policy, SandboxMode, POLICY_VERSION,
+    ArtifactsEncryption, DeterminismPolicy, EnvPolicy, ExecPolicy, FsPolicy, InputPolicy,
+    LogForwardPolicy, NetworkPolicy, Policy, ProcessPolicy, SandboxFallback, SandboxMode,
+    TranscriptTruncate, MAX_SOCKET_NAME_LEN, POLICY_VERSION,
+};
+use crate::model::{
+    Action, ActionType, EnforcementReport, EnvRecord, EnvRecordVar, EnvSource, RunConfig,
+    UnenforcedAspect, RECORDED_ENV_VALUES,
 };
-use crate::model::{Action, ActionType, RunConfig};
 use crate::runner::RunnerError;
+use crate::util::sha256_hex;
+use std::collections::BTreeMap;
 use std::path::{Component, Path, PathBuf};
 
 /// Environment variables that could enable sandbox escape or library injection.
@@ -170,7 +200,9 @@ impl EffectivePolicy {
     ///
     /// Checks that the command is in the executable allowlist, uses an
     /// absolute path, is not a shell, and that the working directory
-    /// is within allowed paths.
+    /// is within allowed paths. A remote run must target a host allowed by
+    /// `policy.remote`; its working directory is not checked against the fs
+    /// allowlists.
     ///
     /// # Errors
     /// Returns `E_POLICY_DENIED` with structured context describing the fix.
@@ -238,6 +270,10 @@ impl EffectivePolicy {
             ));
         }
 
+        if let Some(target) = &run.remote {
+            remote::validate_remote_target(&self.policy.remote, target)?;
+        }
+
         let fs = &self.policy.fs;
         if let Some(cwd) = &run.cwd {
             if !Path::new(cwd).is_absolute() {
@@ -247,7 +283,8 @@ impl EffectivePolicy {
                     serde_json::json!({"cwd": cwd}),
                 ));
             }
-            if !path_allowed(cwd, &fs.allowed_read, &fs.allowed_write) {
+            // A remote cwd is on the remote host, outside the fs allowlists.
+            if run.remote.is_none() && !path_allowed(cwd, &fs.allowed_read, &fs.allowed_write) {
                 return Err(RunnerError::policy_denied(
                     "E_POLICY_DENIED",
                     "working directory is not within allowlisted paths",
@@ -271,19 +308,17 @@ impl EffectivePolicy {
 
     /// Validate that an action is allowed by the policy.
     ///
-    /// Currently permits all action types; reserved for future per-action
-    /// restrictions.
+    /// `raw` actions are checked against `input.allow_raw_bytes` and
+    /// `input.max_raw_bytes`; all other action types are permitted.
     ///
     /// # Errors
     /// Returns `E_POLICY_DENIED` if the action is disallowed.
     pub fn validate_action(&self, action: &Action) -> Result<(), RunnerError> {
-        if matches!(action.action_type, ActionType::Terminate) {
-            return Ok(());
+        match action.action_type {
+            ActionType::Raw => validate_raw_input(&self.policy.input, action),
+            ActionType::Signal => validate_signal(&self.policy.process, action),
+            _ => Ok(()),
         }
-        if matches!(action.action_type, ActionType::Wait) {
-            return Ok(());
-        }
-        Ok(())
     }
 
     /// Apply environment variable policy to a command builder.
@@ -297,7 +332,7 @@ impl EffectivePolicy {
         &self,
         cmd: &mut portable_pty::CommandBuilder,
     ) -> Result<(), RunnerError> {
-        apply_env_policy(&self.policy.env, cmd)
+        apply_env_policy(&self.policy.effective_env(), cmd)
     }
 }
 
@@ -310,7 +345,7 @@ pub fn explain_policy_for_run_config(policy: &Policy, run: &RunConfig) -> Policy
     if let Err(err) = validate_policy_version(policy) {
         errors.push(err.to_error_info());
     }
-    if let Err(err) = validate_sandbox_mode(&policy.sandbox) {
+    if let Err(err) = resolve_sandbox_enforcement(policy) {
         errors.push(err.to_error_info());
     }
     if let Err(err) = validate_network_policy(policy) {
@@ -319,6 +354,15 @@ pub fn explain_policy_for_run_config(policy: &Policy, run: &RunConfig) -> Policy
     if let Err(err) = validate_env_policy(&policy.env) {
         errors.push(err.to_error_info());
     }
+    if let Err(err) = validate_determinism_policy(&policy.determinism) {
+        errors.push(err.to_error_info());
+    }
+    if let Err(err) = validate_process_policy(policy) {
+        errors.push(err.to_error_info());
+    }
+    if let Err(err) = validate_socket_policy(policy) {
+        errors.push(err.to_error_info());
+    }
     if let Err(err) = validate_fs_policy(&policy.fs) {
         errors.push(err.to_error_info());
     }
@@ -362,7 +406,7 @@ pub fn validate_policy_version(policy: &Policy) -> Result<(), RunnerError> {
     Ok(())
 }
 
-fn path_allowed(path: &str, allowed_read: &[String], allowed_write: &[String]) -> bool {
+pub(crate) fn path_allowed(path: &str, allowed_read: &[String], allowed_write: &[String]) -> bool {
     let path = canonicalize_for_policy(Path::new(path));
     allowed_read
         .iter()
@@ -389,6 +433,83 @@ pub fn validate_shell_policy(exec: &ExecPolicy) -> Result<(), RunnerError> {
     }
 }
 
+/// Check that every `log_forward.allowed_udp` entry is `host:port`.
+///
+/// # Errors
+/// Returns `E_POLICY_DENIED` for an entry without a host or a numeric port.
+pub fn validate_log_forward_policy(log_forward: &LogForwardPolicy) -> Result<(), RunnerError> {
+    for (index, entry) in log_forward.allowed_udp.iter().enumerate() {
+        if udp_authority(entry).is_none() {
+            return Err(RunnerError::policy_denied(
+                "E_POLICY_DENIED",
+                "invalid log forwarding UDP entry",
+                serde_json::json!({
+                    "field": format!("log_forward.allowed_udp[{index}]"),
+                    "entry": entry,
+                    "fix": "Use host:port, e.g. logs.example.internal:514",
+                }),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Split a `host:port` UDP authority, or `None` when the host is empty or
+/// the port is not a number.
+pub(crate) fn udp_authority(authority: &str) -> Option<(&str, u16)> {
+    let (host, port) = authority.rsplit_once(':')?;
+    let port = port.parse().ok()?;
+    (!host.is_empty() && !host.contains(char::is_whitespace)).then_some((host, port))
+}
+
+/// Validate a `raw` action against the input policy.
+///
+/// # Errors
+/// - `E_POLICY_DENIED`: Raw bytes are not allowed, or the decoded payload
+///   exceeds `max_raw_bytes`
+/// - `E_PROTOCOL`: The payload is not valid hex or base64
+pub fn validate_raw_input(input: &InputPolicy, action: &Action) -> Result<(), RunnerError> {
+    if !input.allow_raw_bytes {
+        return Err(RunnerError::policy_denied(
+            "E_POLICY_DENIED",
+            "raw byte input is disabled by policy",
+            serde_json::json!({
+                "fix": "Set policy.input.allow_raw_bytes to true",
+            }),
+        ));
+    }
+    let len = crate::session::decode_raw_payload(&action.payload)?.len() as u64;
+    if len > input.max_raw_bytes {
+        return Err(RunnerError::policy_denied(
+            "E_POLICY_DENIED",
+            "raw action exceeds max_raw_bytes",
+            serde_json::json!({ "bytes": len, "max_raw_bytes": input.max_raw_bytes }),
+        ));
+    }
+    Ok(())
+}
+
+/// Validate a `signal` action against `process.allowed_signals`.
+///
+/// # Errors
+/// - `E_POLICY_DENIED`: The signal is not in `process.allowed_signals`
+/// - `E_PROTOCOL`: The payload does not name a supported signal
+pub fn validate_signal(process: &ProcessPolicy, action: &Action) -> Result<(), RunnerError> {
+    let signal = crate::session::decode_signal_payload(&action.payload)?;
+    if !process.allowed_signals.contains(&signal) {
+        return Err(RunnerError::policy_denied(
+            "E_POLICY_DENIED",
+            format!("{} is not in process.allowed_signals", signal.as_str()),
+            serde_json::json!({
+                "signal": signal,
+                "allowed_signals": process.allowed_signals,
+                "fix": format!("Add '{}' to policy.process.allowed_signals", signal.as_str()),
+            }),
+        ));
+    }
+    Ok(())
+}
+
 /// Validate network access policy and enforcement capability.
 ///
 /// Checks that network access has proper acknowledgement and that
@@ -447,9 +568,80 @@ pub fn validate_artifacts_policy(policy: &Policy) -> Result<(), RunnerError> {
         })?;
         validate_artifacts_dir(Path::new(dir), &policy.fs)?;
     }
+    if let Some(encryption) = &policy.artifacts.encryption {
+        validate_artifacts_encryption(encryption)?;
+    }
+    let transcript = &policy.artifacts.transcript;
+    if transcript.truncate == TranscriptTruncate::HeadTail
+        && transcript.head_kib == 0
+        && transcript.tail_kib == 0
+    {
+        return Err(RunnerError::policy_denied(
+            "E_POLICY_DENIED",
+            "head_tail transcript truncation keeps no output",
+            serde_json::json!({
+                "head_kib": 0,
+                "tail_kib": 0,
+                "fix": "Set policy.artifacts.transcript.head_kib or tail_kib above zero"
+            }),
+        ));
+    }
     Ok(())
 }
 
+/// Validate artifact encryption settings.
+///
+/// # Errors
+/// Returns `E_POLICY_DENIED` if no recipients are given, a recipient is not
+/// an age X25519 public key, or the `age` binary path is not absolute.
+pub fn validate_artifacts_encryption(encryption: &ArtifactsEncryption) -> Result<(), RunnerError> {
+    if encryption.recipients.is_empty() {
+        return Err(RunnerError::policy_denied(
+            "E_POLICY_DENIED",
+            "artifacts encryption requires at least one recipient",
+            serde_json::json!({
+                "fix": "Add an age public key to policy.artifacts.encryption.recipients",
+                "example": {"encryption": {"recipients": ["age1..."]}}
+            }),
+        ));
+    }
+    if let Some(recipient) = encryption
+        .recipients
+        .iter()
+        .find(|recipient| !is_age_recipient(recipient))
+    {
+        return Err(RunnerError::policy_denied(
+            "E_POLICY_DENIED",
+            "artifacts encryption recipient is not an age X25519 public key",
+            serde_json::json!({
+                "recipient": recipient,
+                "fix": "Use the age1... public key printed by age-keygen"
+            }),
+        ));
+    }
+    if !Path::new(&encryption.age_binary).is_absolute() {
+        return Err(RunnerError::policy_denied(
+            "E_POLICY_DENIED",
+            "artifacts encryption age_binary must be an absolute path",
+            serde_json::json!({
+                "age_binary": encryption.age_binary,
+                "fix": "Set policy.artifacts.encryption.age_binary to an absolute path"
+            }),
+        ));
+    }
+    Ok(())
+}
+
+/// Bech32 shape check for `age1...` recipients (lowercase, data charset only).
+fn is_age_recipient(recipient: &str) -> bool {
+    recipient.strip_prefix("age1").is_some_and(|data| {
+        !data.is_empty()
+            && data
+                .chars()
+                .all(|ch| "qpzry9x8gf2tvdw0s3jn54khce6mua7l".contains(ch))
+    })
+}
+
 /// Validate write access acknowledgement in strict-write mode.
 ///
 /// When `fs.strict_write` is true, any operation requiring write access
@@ -487,10 +679,12 @@ pub fn validate_write_access(
     ))
 }
 
-/// Validate sandbox mode: check availability (Seatbelt) or acknowledgement (Disabled).
+/// Validate sandbox mode: check availability (Seatbelt, container runtime)
+/// or acknowledgement (Disabled).
 ///
 /// # Errors
-/// - `E_SANDBOX_UNAVAILABLE` if Seatbelt is not available on this platform
+/// - `E_SANDBOX_UNAVAILABLE` if Seatbelt is not available on this platform,
+///   or the container runtime is missing or not usable
 /// - `E_POLICY_DENIED` if sandbox is disabled without acknowledgement
 pub fn validate_sandbox_mode(mode: &SandboxMode) -> Result<(), RunnerError> {
     match mode {
@@ -498,6 +692,9 @@ pub fn validate_sandbox_mode(mode: &SandboxMode) -> Result<(), RunnerError> {
             crate::policy::sandbox::ensure_sandbox_available()?;
             Ok(())
         }
+        SandboxMode::Container {
+            runtime, program, ..
+        } => container::ensure_runtime_available(*runtime, Path::new(program)),
         SandboxMode::Disabled { ack } => {
             if *ack {
                 Ok(())
@@ -517,6 +714,88 @@ pub fn validate_sandbox_mode(mode: &SandboxMode) -> Result<(), RunnerError> {
     }
 }
 
+/// Validate the sandbox, falling back to no sandbox when the policy allows it.
+///
+/// Returns `None` when the policy is enforced as written, or an
+/// [`EnforcementReport`] when Seatbelt or the container runtime is
+/// unavailable and `sandbox_fallback` is `warn` with acknowledgement. Callers must then
+/// spawn with [`fallback_spawn_policy`] and surface the report.
+///
+/// # Errors
+/// - `E_POLICY_DENIED` if the `warn` fallback is not acknowledged, or the
+///   container image or mount paths are invalid
+/// - Any error from [`validate_sandbox_mode`] when no fallback applies
+pub fn resolve_sandbox_enforcement(
+    policy: &Policy,
+) -> Result<Option<EnforcementReport>, RunnerError> {
+    container::validate_container_policy(policy)?;
+    let fallback = match policy.sandbox_fallback {
+        SandboxFallback::Deny => false,
+        SandboxFallback::Warn { ack: true } => true,
+        SandboxFallback::Warn { ack: false } => {
+            return Err(RunnerError::policy_denied(
+                "E_POLICY_DENIED",
+                "sandbox fallback requires explicit acknowledgement",
+                serde_json::json!({
+                    "sandbox_fallback": "warn",
+                    "fix": "Set sandbox_fallback_ack to true to run without Seatbelt when it is unavailable",
+                    "alternative": "Use sandbox_fallback: 'deny' to fail when Seatbelt is unavailable"
+                }),
+            ));
+        }
+    };
+    match validate_sandbox_mode(&policy.sandbox) {
+        Ok(()) => Ok(None),
+        Err(err) if fallback && !policy.sandbox.is_disabled() => Ok(Some(EnforcementReport {
+            requested_sandbox: policy.sandbox.name().to_string(),
+            applied_sandbox: "none".to_string(),
+            reason: err.to_error_info(),
+            unenforced: unenforced_aspects(policy),
+        })),
+        Err(err) => Err(err),
+    }
+}
+
+/// The policy to spawn with once `enforcement` is known.
+///
+/// Drops the Seatbelt or container wrapper when a fallback report is
+/// present, so the command is not routed through a `sandbox-exec` or
+/// runtime that cannot run.
+#[must_use]
+pub fn fallback_spawn_policy(policy: &Policy, enforcement: Option<&EnforcementReport>) -> Policy {
+    let mut spawn_policy = policy.clone();
+    if enforcement.is_some() {
+        spawn_policy.sandbox = SandboxMode::Disabled { ack: true };
+    }
+    spawn_policy
+}
+
+/// Policy aspects that only the Seatbelt profile enforces.
+fn unenforced_aspects(policy: &Policy) -> Vec<UnenforcedAspect> {
+    let mut aspects = vec![
+        UnenforcedAspect {
+            aspect: "fs.allowed_read".to_string(),
+            detail: "process may read any path readable by the invoking user".to_string(),
+        },
+        UnenforcedAspect {
+            aspect: "fs.allowed_write".to_string(),
+            detail: "process may write any path writable by the invoking user".to_string(),
+        },
+    ];
+    if !policy.network.is_enabled() {
+        aspects.push(UnenforcedAspect {
+            aspect: "network".to_string(),
+            detail: "outbound network access is not blocked".to_string(),
+        });
+    }
+    aspects.push(UnenforcedAspect {
+        aspect: "exec.allowed_executables".to_string(),
+        detail: "only the initial command is checked; child processes may exec any binary"
+            .to_string(),
+    });
+    aspects
+}
+
 /// Validate environment variable policy consistency.
 ///
 /// Ensures every key in `env.set` also appears in the `env.allowlist`.
@@ -540,22 +819,226 @@ pub fn validate_env_policy(env: &EnvPolicy) -> Result<(), RunnerError> {
     Ok(())
 }
 
+/// Validate a step's `env_overlay` against the environment policy.
+///
+/// Overlay variables follow the same rules as `env.set`: each must be in
+/// `env.allowlist` and none may be a dangerous variable.
+///
+/// # Errors
+/// Returns `E_POLICY_DENIED` if a variable is dangerous or not allowlisted.
+pub fn validate_env_overlay(
+    overlay: &BTreeMap<String, String>,
+    env: &EnvPolicy,
+) -> Result<(), RunnerError> {
+    for key in overlay.keys() {
+        if is_dangerous_env_var(key) {
+            return Err(RunnerError::policy_denied(
+                "E_POLICY_DENIED",
+                "dangerous environment variable blocked",
+                serde_json::json!({
+                    "var": key,
+                    "reason": "This variable could enable sandbox escape or library injection",
+                    "blocked_vars": DANGEROUS_ENV_VARS,
+                    "fix": format!("Remove '{}' from the step's env_overlay", key)
+                }),
+            ));
+        }
+        if !env.allowlist.iter().any(|allowed| allowed == key) {
+            return Err(RunnerError::policy_denied(
+                "E_POLICY_DENIED",
+                "env_overlay var without allowlist entry",
+                serde_json::json!({
+                    "var": key,
+                    "current_allowlist": env.allowlist,
+                    "fix": format!("Add '{}' to policy.env.allowlist", key)
+                }),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Validate pinned locale and timezone values.
+///
+/// Values must be non-empty ASCII made of letters, digits, and `._-+/@:`,
+/// so they cannot smuggle whitespace or control characters into the child
+/// environment.
+///
+/// # Errors
+/// Returns `E_POLICY_DENIED` if a pinned value is empty or malformed.
+pub fn validate_determinism_policy(determinism: &DeterminismPolicy) -> Result<(), RunnerError> {
+    let fields = [
+        ("locale", &determinism.locale, "C.UTF-8"),
+        ("timezone", &determinism.timezone, "UTC"),
+    ];
+    for (field, value, example) in fields {
+        let Some(value) = value else {
+            continue;
+        };
+        let valid = !value.is_empty()
+            && value
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || "._-+/@:".contains(ch));
+        if !valid {
+            return Err(RunnerError::policy_denied(
+                "E_POLICY_DENIED",
+                format!("invalid determinism {field}"),
+                serde_json::json!({
+                    "field": format!("determinism.{field}"),
+                    "value": value,
+                    "fix": format!("Set policy.determinism.{field} to a value like '{example}'"),
+                }),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Validate the termination policy.
+///
+/// A pre-kill hook runs outside the sandbox, so its command must be an
+/// absolute path listed in `exec.allowed_executables`.
+///
+/// # Errors
+/// Returns `E_POLICY_DENIED` if the hook command is relative or not
+/// allowlisted, or its `timeout_ms` is zero.
+pub fn validate_process_policy(policy: &Policy) -> Result<(), RunnerError> {
+    let Some(hook) = &policy.process.pre_kill_hook else {
+        return Ok(());
+    };
+    if !Path::new(&hook.command).is_absolute() {
+        return Err(RunnerError::policy_denied(
+            "E_POLICY_DENIED",
+            "pre-kill hook command must be an absolute path",
+            serde_json::json!({
+                "field": "process.pre_kill_hook.command",
+                "requested": hook.command,
+                "fix": "Provide the full path to the executable",
+            }),
+        ));
+    }
+    if !policy
+        .exec
+        .allowed_executables
+        .iter()
+        .any(|allowed| allowed == &hook.command)
+    {
+        return Err(RunnerError::policy_denied(
+            "E_POLICY_DENIED",
+            "pre-kill hook executable is not allowlisted",
+            serde_json::json!({
+                "field": "process.pre_kill_hook.command",
+                "requested": hook.command,
+                "allowed_executables": policy.exec.allowed_executables,
+                "fix": "Add the hook path to policy.exec.allowed_executables",
+            }),
+        ));
+    }
+    if hook.timeout_ms == 0 {
+        return Err(RunnerError::policy_denied(
+            "E_POLICY_DENIED",
+            "pre-kill hook timeout must be positive",
+            serde_json::json!({
+                "field": "process.pre_kill_hook.timeout_ms",
+                "fix": "Set process.pre_kill_hook.timeout_ms to at least 1",
+            }),
+        ));
+    }
+    Ok(())
+}
+
+/// Validate the sockets granted to the child.
+///
+/// Names become file names in the socket directory, so they are limited to
+/// letters, digits, `_`, and `-`. Each socket needs its own name and its own
+/// environment variable, which must be a plain identifier and not one of
+/// the blocked loader variables.
+///
+/// # Errors
+/// Returns `E_POLICY_DENIED` if a name or environment variable is invalid
+/// or repeated.
+pub fn validate_socket_policy(policy: &Policy) -> Result<(), RunnerError> {
+    let mut names = std::collections::BTreeSet::new();
+    let mut vars = std::collections::BTreeSet::new();
+    for (index, grant) in policy.sockets.iter().enumerate() {
+        let var = grant.env_var();
+        let problem = if grant.name.is_empty()
+            || grant.name.len() > MAX_SOCKET_NAME_LEN
+            || !grant
+                .name
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '-')
+        {
+            Some((
+                "name",
+                "invalid socket name",
+                format!("Use 1 to {MAX_SOCKET_NAME_LEN} letters, digits, '_', or '-'"),
+            ))
+        } else if !names.insert(grant.name.as_str()) {
+            Some((
+                "name",
+                "duplicate socket name",
+                "Give each socket its own name".to_string(),
+            ))
+        } else if var.is_empty()
+            || var.starts_with(|ch: char| ch.is_ascii_digit())
+            || !var
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+            || is_dangerous_env_var(&var)
+        {
+            Some((
+                "env",
+                "invalid socket environment variable",
+                "Use a name made of letters, digits, and '_'".to_string(),
+            ))
+        } else if !vars.insert(var.clone()) {
+            Some((
+                "env",
+                "duplicate socket environment variable",
+                "Give each socket its own env variable".to_string(),
+            ))
+        } else {
+            None
+        };
+        if let Some((field, message, fix)) = problem {
+            return Err(RunnerError::policy_denied(
+                "E_POLICY_DENIED",
+                message,
+                serde_json::json!({
+                    "field": format!("sockets[{index}].{field}"),
+                    "name": grant.name,
+                    "env": var,
+                    "fix": fix,
+                }),
+            ));
+        }
+    }
+    Ok(())
+}
+
 /// Run all policy validations in order.
 ///
 /// Equivalent to calling each `validate_*` function. Returns the first
-/// error encountered.
+/// error encountered, or the Seatbelt fallback report from
+/// [`resolve_sandbox_enforcement`] when the sandbox cannot be enforced.
 ///
 /// # Errors
 /// Returns the first validation error (see individual validators).
-pub fn validate_policy(policy: &Policy) -> Result<(), RunnerError> {
+pub fn validate_policy(policy: &Policy) -> Result<Option<EnforcementReport>, RunnerError> {
     validate_policy_version(policy)?;
-    validate_sandbox_mode(&policy.sandbox)?;
+    let enforcement = resolve_sandbox_enforcement(policy)?;
     validate_network_policy(policy)?;
     validate_env_policy(&policy.env)?;
+    validate_determinism_policy(&policy.determinism)?;
+    validate_process_policy(policy)?;
+    validate_socket_policy(policy)?;
+    remote::validate_remote_policy(&policy.remote)?;
+    validate_log_forward_policy(&policy.log_forward)?;
     validate_fs_policy(&policy.fs)?;
     validate_artifacts_policy(policy)?;
     validate_write_access(policy, None)?;
-    Ok(())
+    Ok(enforcement)
 }
 
 /// Apply the environment policy to a command builder.
@@ -618,6 +1101,36 @@ pub fn apply_env_policy(
     Ok(())
 }
 
+/// Record of the variables [`apply_env_policy`] passes to the child.
+///
+/// Values are kept for set variables and [`RECORDED_ENV_VALUES`]; other
+/// inherited variables carry only their digest. Inherited variables missing
+/// from ptybox's environment are left out.
+#[must_use]
+pub fn env_record(env_policy: &EnvPolicy) -> EnvRecord {
+    let mut vars: Vec<EnvRecordVar> = env_policy
+        .allowlist
+        .iter()
+        .filter_map(|name| {
+            let (source, value) = match env_policy.set.get(name) {
+                Some(value) => (EnvSource::Set, value.clone()),
+                None if env_policy.inherit => (EnvSource::Inherited, std::env::var(name).ok()?),
+                None => return None,
+            };
+            let recorded = source == EnvSource::Set || RECORDED_ENV_VALUES.contains(&name.as_str());
+            Some(EnvRecordVar {
+                name: name.clone(),
+                source,
+                digest: format!("sha256:{}", sha256_hex(value.as_bytes())),
+                value: recorded.then_some(value),
+            })
+        })
+        .collect();
+    vars.sort_by(|a, b| a.name.cmp(&b.name));
+    vars.dedup_by(|a, b| a.name == b.name);
+    EnvRecord { vars }
+}
+
 fn is_shell_command(command: &str, args: &[String]) -> bool {
     // Suppress unused warning - args kept for API compatibility and potential future use
     let _ = args;
@@ -654,7 +1167,9 @@ const BLOCKED_FS_ROOTS: &[&str] = &["/", "/System", "/Library", "/Users", "/priv
 ///
 /// Checks every path in `allowed_read`, `allowed_write`, and `working_dir`.
 /// Rejects the root directory, home directory, and system paths
-/// (`/System`, `/Library`, `/Users`, `/private`, `/Volumes`).
+/// (`/System`, `/Library`, `/Users`, `/private`, `/Volumes`). Each
+/// `write_quotas` entry must be a non-zero limit on a path inside
+/// `allowed_write`.
 ///
 /// # Errors
 /// Returns `E_POLICY_DENIED` with structured context identifying the offending path.
@@ -730,6 +1245,36 @@ pub fn validate_fs_policy(fs: &FsPolicy) -> Result<(), RunnerError> {
             ));
         }
     }
+    validate_write_quotas(fs)
+}
+
+/// Check that every `fs.write_quotas` entry is a positive limit on an
+/// absolute path inside `allowed_write`.
+fn validate_write_quotas(fs: &FsPolicy) -> Result<(), RunnerError> {
+    for (path, max_bytes) in &fs.write_quotas {
+        if *max_bytes == 0 {
+            return Err(RunnerError::policy_denied(
+                "E_POLICY_DENIED",
+                "write quota must be greater than zero",
+                serde_json::json!({
+                    "path": path,
+                    "fix": "Remove the path from allowed_write to forbid writes instead"
+                }),
+            ));
+        }
+        let quota_path = Path::new(path);
+        if !quota_path.is_absolute() || !path_allowed_write(quota_path, &fs.allowed_write) {
+            return Err(RunnerError::policy_denied(
+                "E_POLICY_DENIED",
+                "write quota path is not within allowlisted write paths",
+                serde_json::json!({
+                    "path": path,
+                    "allowed_write": fs.allowed_write,
+                    "fix": "Use an absolute path inside policy.fs.allowed_write"
+                }),
+            ));
+        }
+    }
     Ok(())
 }
 
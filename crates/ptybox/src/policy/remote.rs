@@ -0,0 +1,187 @@
+//! Remote session allowlist (`policy.remote`).
+//!
+//! A remote session connects to a [`RemoteTarget`] over SSH. The host (and
+//! optionally the user and port) must match an `allowed_hosts` entry, the key
+//! must be listed in `allowed_keys`, and a `known_hosts` file must be set so
+//! the host key can be verified. Nothing runs locally, so the sandbox, fs
+//! allowlists, and inherited environment do not apply to the remote command;
+//! the executable allowlist and `env.set` still do.
+
+use super::is_dangerous_env_var;
+use crate::model::policy::{EnvPolicy, RemotePolicy};
+use crate::model::{RemoteTarget, DEFAULT_SSH_PORT};
+use crate::runner::RunnerError;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// A parsed `allowed_hosts` entry.
+#[derive(Debug, PartialEq, Eq)]
+struct HostEntry<'a> {
+    user: Option<&'a str>,
+    host: &'a str,
+    port: u16,
+}
+
+/// Check that `policy.remote` entries parse and name absolute paths.
+///
+/// # Errors
+/// Returns `E_POLICY_DENIED` for an entry that is not `[user@]host[:port]`
+/// or a key or `known_hosts` path that is not absolute.
+pub fn validate_remote_policy(remote: &RemotePolicy) -> Result<(), RunnerError> {
+    for (index, entry) in remote.allowed_hosts.iter().enumerate() {
+        if parse_host_entry(entry).is_none() {
+            return Err(RunnerError::policy_denied(
+                "E_POLICY_DENIED",
+                "invalid remote host entry",
+                serde_json::json!({
+                    "field": format!("remote.allowed_hosts[{index}]"),
+                    "entry": entry,
+                    "fix": "Use [user@]host[:port], e.g. qa@appliance.lan:2222",
+                }),
+            ));
+        }
+    }
+    let paths = remote
+        .allowed_keys
+        .iter()
+        .enumerate()
+        .map(|(index, path)| (format!("remote.allowed_keys[{index}]"), path))
+        .chain(
+            remote
+                .known_hosts
+                .iter()
+                .map(|path| ("remote.known_hosts".to_string(), path)),
+        );
+    for (field, path) in paths {
+        if !Path::new(path).is_absolute() {
+            return Err(RunnerError::policy_denied(
+                "E_POLICY_DENIED",
+                "remote key and known_hosts paths must be absolute",
+                serde_json::json!({ "field": field, "path": path }),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Check that `policy.remote` allows a session on `target`.
+///
+/// # Errors
+/// Returns `E_POLICY_DENIED` when the host, user, or port matches no
+/// `allowed_hosts` entry, the key is not in `allowed_keys`, or
+/// `known_hosts` is not set.
+pub fn validate_remote_target(
+    remote: &RemotePolicy,
+    target: &RemoteTarget,
+) -> Result<(), RunnerError> {
+    let allowed = remote
+        .allowed_hosts
+        .iter()
+        .filter_map(|entry| parse_host_entry(entry))
+        .any(|entry| {
+            entry.host.eq_ignore_ascii_case(&target.host)
+                && entry.port == target.port
+                && entry.user.map_or(true, |user| user == target.user)
+        });
+    if !allowed {
+        return Err(RunnerError::policy_denied(
+            "E_POLICY_DENIED",
+            "remote host is not allowlisted",
+            serde_json::json!({
+                "host": target.host,
+                "port": target.port,
+                "user": target.user,
+                "allowed_hosts": remote.allowed_hosts,
+                "fix": format!(
+                    "Add \"{}@{}:{}\" to policy.remote.allowed_hosts",
+                    target.user, target.host, target.port
+                ),
+            }),
+        ));
+    }
+    if !Path::new(&target.key_path).is_absolute()
+        || !remote
+            .allowed_keys
+            .iter()
+            .any(|key| key == &target.key_path)
+    {
+        return Err(RunnerError::policy_denied(
+            "E_POLICY_DENIED",
+            "ssh key is not allowlisted",
+            serde_json::json!({
+                "key_path": target.key_path,
+                "allowed_keys": remote.allowed_keys,
+                "fix": "Add the key's absolute path to policy.remote.allowed_keys",
+            }),
+        ));
+    }
+    if remote.known_hosts.is_none() {
+        return Err(known_hosts_required());
+    }
+    Ok(())
+}
+
+/// `E_POLICY_DENIED` for a remote session without `remote.known_hosts`.
+pub(crate) fn known_hosts_required() -> RunnerError {
+    RunnerError::policy_denied(
+        "E_POLICY_DENIED",
+        "remote sessions require policy.remote.known_hosts",
+        serde_json::json!({
+            "fix": "Set policy.remote.known_hosts to an OpenSSH known_hosts file listing the host",
+            "example": "ssh-keyscan -p 22 appliance.lan >> /etc/ptybox/known_hosts",
+        }),
+    )
+}
+
+/// Variables set for a remote command: the `env.set` entries that are also
+/// allowlisted, as a local child would get them. Nothing is inherited from
+/// ptybox's environment.
+///
+/// # Errors
+/// Returns `E_POLICY_DENIED` for a blocked loader variable.
+pub(crate) fn remote_env(env: &EnvPolicy) -> Result<BTreeMap<String, String>, RunnerError> {
+    let mut vars = BTreeMap::new();
+    for (key, value) in &env.set {
+        if is_dangerous_env_var(key) {
+            return Err(RunnerError::policy_denied(
+                "E_POLICY_DENIED",
+                "dangerous environment variable blocked",
+                serde_json::json!({
+                    "var": key,
+                    "fix": format!("Remove '{key}' from policy.env.set"),
+                }),
+            ));
+        }
+        if env.allowlist.iter().any(|allowed| allowed == key) {
+            vars.insert(key.clone(), value.clone());
+        }
+    }
+    Ok(vars)
+}
+
+/// Parse `[user@]host[:port]`; `IPv6` addresses with a port are bracketed.
+fn parse_host_entry(entry: &str) -> Option<HostEntry<'_>> {
+    let (user, rest) = match entry.split_once('@') {
+        Some((user, rest)) if !user.is_empty() => (Some(user), rest),
+        Some(_) => return None,
+        None => (None, entry),
+    };
+    let (host, port) = if let Some(bracketed) = rest.strip_prefix('[') {
+        let (host, after) = bracketed.split_once(']')?;
+        match after.strip_prefix(':') {
+            Some(port) => (host, port.parse().ok()?),
+            None if after.is_empty() => (host, DEFAULT_SSH_PORT),
+            None => return None,
+        }
+    } else {
+        match rest.rsplit_once(':') {
+            Some((host, port)) if !host.contains(':') => (host, port.parse().ok()?),
+            _ => (rest, DEFAULT_SSH_PORT),
+        }
+    };
+    let valid = !host.is_empty()
+        && host
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '.' | '-' | '_' | ':'));
+    valid.then_some(HostEntry { user, host, port })
+}
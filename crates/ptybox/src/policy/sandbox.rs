@@ -94,7 +94,20 @@ pub fn ensure_sandbox_available() -> RunnerResult<()> {
 /// - `E_POLICY_DENIED` if any path contains unsafe characters
 /// - `E_IO` if the file cannot be created or written
 pub fn write_profile(path: &Path, policy: &crate::model::policy::Policy) -> RunnerResult<()> {
-    let content = build_profile(policy)?;
+    write_profile_with_sockets(path, policy, None)
+}
+
+/// Generate and write a Seatbelt sandbox profile that also lets the child
+/// connect to the Unix sockets under `socket_dir` (see `policy.sockets`).
+///
+/// # Errors
+/// Same as [`write_profile`]; `socket_dir` is validated like policy paths.
+pub fn write_profile_with_sockets(
+    path: &Path,
+    policy: &crate::model::policy::Policy,
+    socket_dir: Option<&Path>,
+) -> RunnerResult<()> {
+    let content = build_profile(policy, socket_dir)?;
 
     // Write with restrictive permissions (0600) to prevent other users from reading sandbox rules
     #[cfg(unix)]
@@ -117,7 +130,10 @@ pub fn write_profile(path: &Path, policy: &crate::model::policy::Policy) -> Runn
     Ok(())
 }
 
-fn build_profile(policy: &crate::model::policy::Policy) -> RunnerResult<String> {
+fn build_profile(
+    policy: &crate::model::policy::Policy,
+    socket_dir: Option<&Path>,
+) -> RunnerResult<String> {
     let mut profile = String::new();
     profile.push_str("(version 1)\n");
     profile.push_str("(deny default)\n");
@@ -127,6 +143,14 @@ fn build_profile(policy: &crate::model::policy::Policy) -> RunnerResult<String>
     if policy.network.is_enabled() {
         profile.push_str("(allow network-outbound (remote ip))\n");
     }
+    if let Some(dir) = socket_dir {
+        let dir = dir.display().to_string();
+        validate_seatbelt_path(&dir)?;
+        let _ = writeln!(
+            profile,
+            "(allow network-outbound (remote unix-socket (subpath \"{dir}\")))"
+        );
+    }
 
     for path in &policy.fs.allowed_read {
         validate_seatbelt_path(path)?;
@@ -0,0 +1,190 @@
+//! `${env:VAR}` and `${cwd}` substitution in policy file path fields.
+//!
+//! Policy files may use placeholders instead of machine-specific paths, but
+//! only those the file itself declares in `substitution_allowlist`
+//! (`"cwd"`, `"env:NAME"`). Substitution runs on the raw JSON before the
+//! policy is parsed, so the resolved paths go through the normal
+//! [`validate_fs_policy`](super::validate_fs_policy) checks and are what the
+//! run persists to `policy.json`. The path lists of `grants` entries accept
+//! the same placeholders.
+
+use crate::runner::RunnerError;
+use serde_json::Value;
+use std::path::Path;
+
+/// Path fields that accept placeholders, as (section, field) pairs.
+const PATH_FIELDS: &[(&str, &str)] = &[
+    ("fs", "allowed_read"),
+    ("fs", "allowed_write"),
+    ("fs", "working_dir"),
+    ("exec", "allowed_executables"),
+    ("artifacts", "dir"),
+    ("input", "keymap"),
+];
+
+/// Path fields of each `grants` entry that accept placeholders.
+const GRANT_PATH_FIELDS: &[&str] = &["allowed_read", "allowed_write", "allowed_executables"];
+
+/// Placeholder for the current working directory.
+const CWD_PLACEHOLDER: &str = "cwd";
+
+/// Prefix of environment variable placeholders.
+const ENV_PREFIX: &str = "env:";
+
+/// Resolve `${...}` placeholders in the path fields of a raw policy document.
+///
+/// Placeholders are replaced once (substituted text is not rescanned);
+/// `${` outside path fields is left untouched. `cwd` is the directory
+/// `${cwd}` resolves to.
+///
+/// # Errors
+/// `E_POLICY_DENIED` if `substitution_allowlist` is malformed, a placeholder
+/// is unterminated or not allowlisted, or an environment variable is unset,
+/// empty, or not valid UTF-8.
+pub fn substitute_policy_paths(policy: &mut Value, cwd: &Path) -> Result<(), RunnerError> {
+    let allowlist = read_allowlist(policy)?;
+    for (section, field) in PATH_FIELDS {
+        if let Some(value) = policy
+            .get_mut(*section)
+            .and_then(|section| section.get_mut(*field))
+        {
+            substitute_field(value, &format!("{section}.{field}"), &allowlist, cwd)?;
+        }
+    }
+    let Some(grants) = policy.get_mut("grants").and_then(Value::as_array_mut) else {
+        return Ok(());
+    };
+    for (index, grant) in grants.iter_mut().enumerate() {
+        for field in GRANT_PATH_FIELDS {
+            if let Some(value) = grant.get_mut(*field) {
+                substitute_field(value, &format!("grants[{index}].{field}"), &allowlist, cwd)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Substitute a path field holding a string or an array of strings.
+fn substitute_field(
+    value: &mut Value,
+    name: &str,
+    allowlist: &[String],
+    cwd: &Path,
+) -> Result<(), RunnerError> {
+    match value {
+        Value::String(path) => *path = substitute(path, name, allowlist, cwd)?,
+        Value::Array(paths) => {
+            for path in paths.iter_mut() {
+                if let Value::String(path) = path {
+                    *path = substitute(path, name, allowlist, cwd)?;
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn read_allowlist(policy: &Value) -> Result<Vec<String>, RunnerError> {
+    let Some(value) = policy.get("substitution_allowlist") else {
+        return Ok(Vec::new());
+    };
+    let entries = value
+        .as_array()
+        .map(|entries| {
+            entries
+                .iter()
+                .map(|entry| entry.as_str().map(str::to_string))
+                .collect::<Option<Vec<_>>>()
+        })
+        .unwrap_or_default();
+    let Some(entries) = entries else {
+        return Err(denied(
+            "substitution_allowlist must be an array of strings",
+            serde_json::json!({ "received": value }),
+        ));
+    };
+    if let Some(bad) = entries.iter().find(|entry| !is_valid_placeholder(entry)) {
+        return Err(denied(
+            "invalid substitution_allowlist entry",
+            serde_json::json!({
+                "entry": bad,
+                "expected": "\"cwd\" or \"env:NAME\" (NAME: letters, digits, underscore)",
+            }),
+        ));
+    }
+    Ok(entries)
+}
+
+fn is_valid_placeholder(entry: &str) -> bool {
+    if entry == CWD_PLACEHOLDER {
+        return true;
+    }
+    let Some(name) = entry.strip_prefix(ENV_PREFIX) else {
+        return false;
+    };
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn substitute(
+    path: &str,
+    field: &str,
+    allowlist: &[String],
+    cwd: &Path,
+) -> Result<String, RunnerError> {
+    let mut resolved = String::with_capacity(path.len());
+    let mut rest = path;
+    while let Some(start) = rest.find("${") {
+        resolved.push_str(rest.get(..start).unwrap_or_default());
+        let after = rest.get(start + 2..).unwrap_or_default();
+        let Some(end) = after.find('}') else {
+            return Err(denied(
+                "unterminated placeholder in policy path",
+                serde_json::json!({ "field": field, "value": path }),
+            ));
+        };
+        let placeholder = after.get(..end).unwrap_or_default();
+        resolved.push_str(&resolve(placeholder, field, allowlist, cwd)?);
+        rest = after.get(end + 1..).unwrap_or_default();
+    }
+    resolved.push_str(rest);
+    Ok(resolved)
+}
+
+fn resolve(
+    placeholder: &str,
+    field: &str,
+    allowlist: &[String],
+    cwd: &Path,
+) -> Result<String, RunnerError> {
+    if !allowlist.iter().any(|entry| entry == placeholder) {
+        return Err(denied(
+            "policy placeholder is not in substitution_allowlist",
+            serde_json::json!({
+                "field": field,
+                "placeholder": format!("${{{placeholder}}}"),
+                "substitution_allowlist": allowlist,
+                "fix": format!("Add \"{placeholder}\" to substitution_allowlist"),
+            }),
+        ));
+    }
+    if placeholder == CWD_PLACEHOLDER {
+        return Ok(cwd.display().to_string());
+    }
+    let name = placeholder.strip_prefix(ENV_PREFIX).unwrap_or(placeholder);
+    match std::env::var(name) {
+        Ok(value) if !value.is_empty() => Ok(value),
+        _ => Err(denied(
+            "policy placeholder variable is unset or empty",
+            serde_json::json!({ "field": field, "variable": name }),
+        )),
+    }
+}
+
+fn denied(message: &str, context: Value) -> RunnerError {
+    RunnerError::policy_denied("E_POLICY_DENIED", message, context)
+}
@@ -0,0 +1,927 @@
+//! Predicates shared by wait conditions, step assertions, and watchers.
+//!
+//! A predicate is a `{type, payload}` pair. [`Predicate::parse`] validates it
+//! once and [`Predicate::evaluate`] checks it against an observation, so every
+//! type below works as a wait `condition`, a step `assert` entry, and a
+//! scenario watcher. [`PREDICATES`] is the single description of the
+//! vocabulary; `ptybox protocol-help` renders it.
+//!
+//! | Type | Holds when | Payload Fields |
+//! |------|------------|----------------|
+//! | `screen_contains` | Screen contains substring | `text` |
+//! | `screen_matches` (alias `regex_match`) | Screen matches regex pattern | `pattern` |
+//! | `not_contains` | Screen does not contain text | `text` |
+//! | `screen_not_contains` | Screen does not contain text, optionally for a window | `text`, `for_ms?` |
+//! | `cursor_at` | Cursor at specific position | `row`, `col` |
+//! | `line_equals` | Specific line equals text | `line`, `text` |
+//! | `line_contains` | Specific line contains text | `line`, `text` |
+//! | `line_matches` | Specific line matches regex | `line`, `pattern` |
+//! | `screen_empty` | All screen lines are whitespace | (none) |
+//! | `cursor_visible` | Cursor is visible | (none) |
+//! | `cursor_hidden` | Cursor is hidden | (none) |
+//! | `process_exited` | Process has exited | (none) |
+//! | `exit_code` | Process exited with code | `code?` (default 0) |
+//! | `stderr_contains` | Captured stderr contains substring (`separate_stderr` runs) | `text` |
+//! | `stderr_empty` | Nothing was written to stderr (`separate_stderr` runs) | (none) |
+//! | `responded_within_ms` | Measured response latency is within budget | `ms` |
+//! | `expected_screen` | Screen lines match wildcard patterns | `lines`, `start_line?` |
+//! | `dialog_present` | A bordered box is on screen, optionally with a title | `title?` |
+//! | `bell` | A BEL was received since the last input | (none) |
+//! | `control_sequence` | Raw output since the last input matches a byte regex | `pattern` |
+//!
+//! `expected_screen` patterns match cell by cell: `?` matches any one cell,
+//! `*` any run of cells within the line, and `\` escapes the next character.
+//! Trailing whitespace is ignored on both sides, and screen lines outside
+//! the listed range are not checked. A step's inline `expected_screen` block
+//! compiles into this predicate (see
+//! [`Scenario::apply_defaults`](crate::model::Scenario::apply_defaults)).
+//!
+//! `dialog_present` checks the observation's `widgets` (see
+//! [`crate::terminal::widgets`]); `title` matches as a substring of the
+//! text set into the box's top border.
+//!
+//! `bell` and `control_sequence` read the raw PTY output rather than the
+//! screen (see [`Terminal::output_since_mark`](crate::terminal::Terminal::output_since_mark)),
+//! for TUIs that signal readiness with a bell or an escape sequence such as
+//! the end of a synchronized update (`\x1b\[\?2026l`). Both look at output
+//! since the session last sent input, so a bell that arrived while a `key`
+//! action was being observed still satisfies a following `wait`.
+//!
+//! `for_ms` only applies to step assertions, where the runner keeps sampling
+//! for the window (see [`crate::assertions::stability_window_ms`]).
+//!
+//! Regex patterns are limited to
+//! [`MAX_REGEX_PATTERN_LEN`](crate::model::MAX_REGEX_PATTERN_LEN) characters.
+
+use crate::model::{ExitStatus, Observation, ScreenSnapshot, Stability, Widget, WidgetKind};
+use crate::runner::{compile_safe_bytes_regex, compile_safe_regex, ErrorCode, RunnerError};
+use serde_json::Value;
+
+/// Characters of unexpected stderr quoted in `stderr_empty` failure details.
+const STDERR_HEAD_CHARS: usize = 512;
+
+/// Outcome of evaluating a predicate: (holds, failure message, details).
+pub type PredicateOutcome = (bool, Option<String>, Option<Value>);
+
+/// Description of one predicate type, rendered by `ptybox protocol-help`.
+#[derive(Clone, Copy, Debug)]
+pub struct PredicateSpec {
+    /// Canonical type name.
+    pub name: &'static str,
+    /// Other accepted type names.
+    pub aliases: &'static [&'static str],
+    /// When the predicate holds.
+    pub description: &'static str,
+    /// Stability reported by `protocol-help`.
+    pub stability: Stability,
+    /// Payload fields and their meaning.
+    pub payload: &'static [(&'static str, &'static str)],
+}
+
+/// Every predicate type, in documentation order.
+pub const PREDICATES: [PredicateSpec; 20] = [
+    PredicateSpec {
+        name: "screen_contains",
+        aliases: &[],
+        description: "screen contains the substring",
+        stability: Stability::Stable,
+        payload: &[("text", "string: substring to find on screen")],
+    },
+    PredicateSpec {
+        name: "screen_matches",
+        aliases: &["regex_match"],
+        description: "screen matches the regex",
+        stability: Stability::Stable,
+        payload: &[("pattern", "string: Rust regex pattern")],
+    },
+    PredicateSpec {
+        name: "not_contains",
+        aliases: &[],
+        description: "screen does not contain the substring",
+        stability: Stability::Stable,
+        payload: &[("text", "string: substring that must be absent")],
+    },
+    PredicateSpec {
+        name: "screen_not_contains",
+        aliases: &[],
+        description:
+            "screen does not contain the substring; as a step assertion, for the whole window",
+        stability: Stability::Stable,
+        payload: &[
+            ("text", "string: substring that must be absent"),
+            (
+                "for_ms",
+                "u64 (optional): stability window, step assertions only",
+            ),
+        ],
+    },
+    PredicateSpec {
+        name: "cursor_at",
+        aliases: &[],
+        description: "cursor is at the position",
+        stability: Stability::Stable,
+        payload: &[
+            ("row", "u16: cursor row (0-based)"),
+            ("col", "u16: cursor column (0-based)"),
+        ],
+    },
+    PredicateSpec {
+        name: "line_equals",
+        aliases: &[],
+        description: "screen line equals the text",
+        stability: Stability::Stable,
+        payload: &[
+            ("line", "u64: line index (0-based)"),
+            ("text", "string: expected line content"),
+        ],
+    },
+    PredicateSpec {
+        name: "line_contains",
+        aliases: &[],
+        description: "screen line contains the substring",
+        stability: Stability::Stable,
+        payload: &[
+            ("line", "u64: line index (0-based)"),
+            ("text", "string: substring to find on the line"),
+        ],
+    },
+    PredicateSpec {
+        name: "line_matches",
+        aliases: &[],
+        description: "screen line matches the regex",
+        stability: Stability::Stable,
+        payload: &[
+            ("line", "u64: line index (0-based)"),
+            ("pattern", "string: Rust regex pattern"),
+        ],
+    },
+    PredicateSpec {
+        name: "screen_empty",
+        aliases: &[],
+        description: "every screen line is whitespace",
+        stability: Stability::Stable,
+        payload: &[],
+    },
+    PredicateSpec {
+        name: "cursor_visible",
+        aliases: &[],
+        description: "cursor is visible",
+        stability: Stability::Stable,
+        payload: &[],
+    },
+    PredicateSpec {
+        name: "cursor_hidden",
+        aliases: &[],
+        description: "cursor is hidden",
+        stability: Stability::Stable,
+        payload: &[],
+    },
+    PredicateSpec {
+        name: "process_exited",
+        aliases: &[],
+        description: "the process has exited",
+        stability: Stability::Stable,
+        payload: &[],
+    },
+    PredicateSpec {
+        name: "exit_code",
+        aliases: &[],
+        description: "the process has exited with the code",
+        stability: Stability::Stable,
+        payload: &[("code", "i32 (optional, default 0): expected exit code")],
+    },
+    PredicateSpec {
+        name: "stderr_contains",
+        aliases: &[],
+        description: "captured stderr contains the substring (requires run.separate_stderr)",
+        stability: Stability::Stable,
+        payload: &[("text", "string: substring to find in stderr")],
+    },
+    PredicateSpec {
+        name: "stderr_empty",
+        aliases: &[],
+        description: "nothing was written to stderr (requires run.separate_stderr)",
+        stability: Stability::Stable,
+        payload: &[],
+    },
+    PredicateSpec {
+        name: "responded_within_ms",
+        aliases: &[],
+        description: "response latency since the last input is within the budget",
+        stability: Stability::Stable,
+        payload: &[("ms", "u64: latency budget in milliseconds")],
+    },
+    PredicateSpec {
+        name: "expected_screen",
+        aliases: &[],
+        description: "screen lines match the patterns (? = any cell, * = any run of cells)",
+        stability: Stability::Stable,
+        payload: &[
+            ("lines", "[string]: expected lines, top to bottom"),
+            (
+                "start_line",
+                "u64 (optional, default 0): screen line of the first pattern",
+            ),
+        ],
+    },
+    PredicateSpec {
+        name: "dialog_present",
+        aliases: &[],
+        description: "a bordered box (dialog, menu, panel) is detected on screen",
+        stability: Stability::Experimental,
+        payload: &[(
+            "title",
+            "string (optional): substring of the title in the box's top border",
+        )],
+    },
+    PredicateSpec {
+        name: "bell",
+        aliases: &[],
+        description: "a BEL was received since the last input",
+        stability: Stability::Experimental,
+        payload: &[],
+    },
+    PredicateSpec {
+        name: "control_sequence",
+        aliases: &[],
+        description: "raw output since the last input matches the regex",
+        stability: Stability::Experimental,
+        payload: &[(
+            "pattern",
+            "string: Rust regex matched against raw bytes (e.g. \\x1b\\[\\?2026l)",
+        )],
+    },
+];
+
+/// Session state beyond the observation that some predicates need.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PredicateContext<'a> {
+    /// Exit status, once the process has exited (`process_exited`, `exit_code`).
+    pub exit_status: Option<&'a ExitStatus>,
+    /// All stderr captured so far, when the session separates stderr
+    /// (`stderr_contains`).
+    pub stderr: Option<&'a str>,
+    /// Measured response latency (`responded_within_ms`).
+    pub latency_ms: Option<u64>,
+    /// Raw terminal output since the last input (`bell`, `control_sequence`).
+    pub raw_output: Option<RawOutput<'a>>,
+}
+
+/// Raw terminal output since the session last sent input.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RawOutput<'a> {
+    /// Audible bells received.
+    pub bells: u64,
+    /// Bytes received, at most
+    /// [`MAX_RAW_OUTPUT_WINDOW`](crate::terminal::MAX_RAW_OUTPUT_WINDOW) of the latest.
+    pub bytes: &'a [u8],
+}
+
+/// A parsed, validated predicate.
+#[derive(Clone, Debug)]
+pub enum Predicate {
+    /// `screen_contains`.
+    ScreenContains {
+        /// Substring to find.
+        text: String,
+    },
+    /// `screen_matches` / `regex_match`.
+    ScreenMatches {
+        /// Source pattern.
+        pattern: String,
+        /// Compiled pattern.
+        regex: regex::Regex,
+    },
+    /// `not_contains`.
+    NotContains {
+        /// Substring that must be absent.
+        text: String,
+    },
+    /// `screen_not_contains`.
+    ScreenNotContains {
+        /// Substring that must be absent.
+        text: String,
+    },
+    /// `cursor_at`.
+    CursorAt {
+        /// Expected row.
+        row: u16,
+        /// Expected column.
+        col: u16,
+    },
+    /// `line_equals`.
+    LineEquals {
+        /// Line index.
+        line: u64,
+        /// Expected content.
+        text: String,
+    },
+    /// `line_contains`.
+    LineContains {
+        /// Line index.
+        line: u64,
+        /// Substring to find.
+        text: String,
+    },
+    /// `line_matches`.
+    LineMatches {
+        /// Line index.
+        line: u64,
+        /// Source pattern.
+        pattern: String,
+        /// Compiled pattern.
+        regex: regex::Regex,
+    },
+    /// `screen_empty`.
+    ScreenEmpty,
+    /// `cursor_visible`.
+    CursorVisible,
+    /// `cursor_hidden`.
+    CursorHidden,
+    /// `process_exited`.
+    ProcessExited,
+    /// `exit_code`.
+    ExitCode {
+        /// Expected exit code.
+        code: i32,
+    },
+    /// `stderr_contains`.
+    StderrContains {
+        /// Substring to find.
+        text: String,
+    },
+    /// `stderr_empty`.
+    StderrEmpty,
+    /// `responded_within_ms`.
+    RespondedWithinMs {
+        /// Latency budget.
+        ms: u64,
+    },
+    /// `expected_screen`.
+    ExpectedScreen {
+        /// Line patterns, top to bottom.
+        lines: Vec<String>,
+        /// Screen line the first pattern applies to.
+        start_line: u64,
+    },
+    /// `dialog_present`.
+    DialogPresent {
+        /// Substring the box title must contain.
+        title: Option<String>,
+    },
+    /// `bell`.
+    Bell,
+    /// `control_sequence`.
+    ControlSequence {
+        /// Source pattern.
+        pattern: String,
+        /// Compiled pattern.
+        regex: regex::bytes::Regex,
+    },
+}
+
+impl Predicate {
+    /// Parse and validate a `{type, payload}` predicate.
+    ///
+    /// # Errors
+    /// Returns `E_PROTOCOL` for an unknown type, a missing or invalid payload
+    /// field, or an invalid regex pattern.
+    pub fn parse(predicate_type: &str, payload: &Value) -> Result<Self, RunnerError> {
+        let fields = Fields {
+            predicate_type,
+            payload,
+        };
+        Ok(match predicate_type {
+            "screen_contains" => Self::ScreenContains {
+                text: fields.string("text")?,
+            },
+            "screen_matches" | "regex_match" => {
+                let pattern = fields.string("pattern")?;
+                Self::ScreenMatches {
+                    regex: compile_safe_regex(&pattern)?,
+                    pattern,
+                }
+            }
+            "not_contains" => Self::NotContains {
+                text: fields.string("text")?,
+            },
+            "screen_not_contains" => Self::ScreenNotContains {
+                text: fields.string("text")?,
+            },
+            "cursor_at" => Self::CursorAt {
+                row: fields.coordinate("row")?,
+                col: fields.coordinate("col")?,
+            },
+            "line_equals" => Self::LineEquals {
+                line: fields.unsigned("line")?,
+                text: fields.string("text")?,
+            },
+            "line_contains" => Self::LineContains {
+                line: fields.unsigned("line")?,
+                text: fields.string("text")?,
+            },
+            "line_matches" => {
+                let pattern = fields.string("pattern")?;
+                Self::LineMatches {
+                    line: fields.unsigned("line")?,
+                    regex: compile_safe_regex(&pattern)?,
+                    pattern,
+                }
+            }
+            "screen_empty" => Self::ScreenEmpty,
+            "cursor_visible" => Self::CursorVisible,
+            "cursor_hidden" => Self::CursorHidden,
+            "process_exited" => Self::ProcessExited,
+            "exit_code" => Self::ExitCode {
+                code: fields.exit_code()?,
+            },
+            "stderr_contains" => Self::StderrContains {
+                text: fields.string("text")?,
+            },
+            "stderr_empty" => Self::StderrEmpty,
+            "responded_within_ms" => Self::RespondedWithinMs {
+                ms: fields.unsigned("ms")?,
+            },
+            "expected_screen" => Self::ExpectedScreen {
+                lines: fields.strings("lines")?,
+                start_line: fields.optional_unsigned("start_line")?.unwrap_or(0),
+            },
+            "dialog_present" => Self::DialogPresent {
+                title: fields.optional_string("title")?,
+            },
+            "bell" => Self::Bell,
+            "control_sequence" => {
+                let pattern = fields.string("pattern")?;
+                Self::ControlSequence {
+                    regex: compile_safe_bytes_regex(&pattern)?,
+                    pattern,
+                }
+            }
+            other => {
+                return Err(RunnerError::with_context(
+                    ErrorCode::Protocol,
+                    format!("unsupported predicate type '{other}'"),
+                    serde_json::json!({
+                        "received": other,
+                        "supported_types": PREDICATES.map(|spec| spec.name),
+                    }),
+                ))
+            }
+        })
+    }
+
+    /// Whether evaluating this predicate needs the process exit status.
+    #[must_use]
+    pub fn needs_exit_status(&self) -> bool {
+        matches!(self, Self::ProcessExited | Self::ExitCode { .. })
+    }
+
+    /// Evaluate the predicate against `observation` and session `context`.
+    ///
+    /// Predicates that read `context` fail when their field is `None`.
+    #[must_use]
+    pub fn evaluate(
+        &self,
+        observation: &Observation,
+        context: &PredicateContext<'_>,
+    ) -> PredicateOutcome {
+        let screen = &observation.screen;
+        let screen_text = || screen.lines.join("\n");
+        match self {
+            Self::ScreenContains { text } => outcome(screen_text().contains(text.as_str()), || {
+                format!("screen did not contain '{text}'")
+            }),
+            Self::ScreenMatches { pattern, regex } => {
+                outcome(regex.is_match(&screen_text()), || {
+                    format!("screen did not match '{pattern}'")
+                })
+            }
+            Self::NotContains { text } => outcome(!screen_text().contains(text.as_str()), || {
+                format!("screen unexpectedly contained '{text}'")
+            }),
+            Self::ScreenNotContains { text } => screen_not_contains(observation, text),
+            Self::CursorAt { row, col } => {
+                let cursor = &screen.cursor;
+                outcome(cursor.row == *row && cursor.col == *col, || {
+                    format!("cursor at ({}, {})", cursor.row, cursor.col)
+                })
+            }
+            Self::LineEquals { line, text } => with_line(screen, *line, |actual| {
+                outcome(actual == text, || {
+                    format!("line {line} was '{actual}', expected '{text}'")
+                })
+            }),
+            Self::LineContains { line, text } => with_line(screen, *line, |actual| {
+                outcome(actual.contains(text.as_str()), || {
+                    format!("line {line} did not contain '{text}'")
+                })
+            }),
+            Self::LineMatches {
+                line,
+                pattern,
+                regex,
+            } => with_line(screen, *line, |actual| {
+                outcome(regex.is_match(actual), || {
+                    format!("line {line} did not match '{pattern}'")
+                })
+            }),
+            Self::ScreenEmpty => outcome(
+                screen.lines.iter().all(|line| line.trim().is_empty()),
+                || "screen is not empty".to_string(),
+            ),
+            Self::CursorVisible => outcome(screen.cursor.visible, || {
+                "cursor is not visible".to_string()
+            }),
+            Self::CursorHidden => outcome(!screen.cursor.visible, || {
+                "cursor is not hidden".to_string()
+            }),
+            Self::ProcessExited => outcome(context.exit_status.is_some(), || {
+                "process has not exited".to_string()
+            }),
+            Self::ExitCode { code } => exit_code(*code, context.exit_status),
+            Self::StderrContains { text } => stderr_contains(text, context.stderr),
+            Self::StderrEmpty => stderr_empty(context.stderr),
+            Self::RespondedWithinMs { ms } => responded_within_ms(*ms, context.latency_ms),
+            Self::ExpectedScreen { lines, start_line } => {
+                expected_screen(screen, lines, *start_line)
+            }
+            Self::DialogPresent { title } => dialog_present(&observation.widgets, title.as_deref()),
+            Self::Bell => bell(context.raw_output.as_ref()),
+            Self::ControlSequence { pattern, regex } => {
+                control_sequence(pattern, regex, context.raw_output.as_ref())
+            }
+        }
+    }
+}
+
+fn outcome(holds: bool, message: impl FnOnce() -> String) -> PredicateOutcome {
+    (holds, (!holds).then(message), None)
+}
+
+fn screen_not_contains(observation: &Observation, text: &str) -> PredicateOutcome {
+    if !observation.screen.lines.join("\n").contains(text) {
+        return (true, None, None);
+    }
+    (
+        false,
+        Some(format!("screen unexpectedly contained '{text}'")),
+        Some(serde_json::json!({
+            "text": text,
+            "timestamp_ms": observation.timestamp_ms,
+            "snapshot": observation.screen,
+        })),
+    )
+}
+
+/// Evaluate `check` against screen line `line`, failing when it is out of bounds.
+fn with_line(
+    screen: &ScreenSnapshot,
+    line: u64,
+    check: impl FnOnce(&str) -> PredicateOutcome,
+) -> PredicateOutcome {
+    let Ok(index) = usize::try_from(line) else {
+        return (
+            false,
+            Some(format!(
+                "line value {line} exceeds maximum usize value {}",
+                usize::MAX
+            )),
+            None,
+        );
+    };
+    match screen.lines.get(index) {
+        Some(actual) => check(actual),
+        None => (
+            false,
+            Some(format!(
+                "line {index} out of bounds (screen has {} lines)",
+                screen.lines.len()
+            )),
+            None,
+        ),
+    }
+}
+
+fn exit_code(expected: i32, exit_status: Option<&ExitStatus>) -> PredicateOutcome {
+    let Some(status) = exit_status else {
+        return (false, Some("process has not exited".to_string()), None);
+    };
+    let Some(actual) = status.exit_code else {
+        return (
+            false,
+            Some("process was killed by signal, no exit code".to_string()),
+            status.signal.map(|sig| serde_json::json!({"signal": sig})),
+        );
+    };
+    if actual == expected {
+        return (true, None, None);
+    }
+    (
+        false,
+        Some(format!("exit code was {actual}, expected {expected}")),
+        Some(serde_json::json!({"actual": actual, "expected": expected})),
+    )
+}
+
+fn stderr_contains(text: &str, stderr: Option<&str>) -> PredicateOutcome {
+    let Some(stderr) = stderr else {
+        return (
+            false,
+            Some("stderr is not captured; set run.separate_stderr".to_string()),
+            None,
+        );
+    };
+    outcome(stderr.contains(text), || {
+        format!("stderr did not contain '{text}'")
+    })
+}
+
+fn stderr_empty(stderr: Option<&str>) -> PredicateOutcome {
+    let Some(stderr) = stderr else {
+        return (
+            false,
+            Some("stderr is not captured; set run.separate_stderr".to_string()),
+            None,
+        );
+    };
+    if stderr.is_empty() {
+        return (true, None, None);
+    }
+    (
+        false,
+        Some("stderr was not empty".to_string()),
+        Some(serde_json::json!({
+            "bytes": stderr.len(),
+            "head": stderr.chars().take(STDERR_HEAD_CHARS).collect::<String>(),
+        })),
+    )
+}
+
+fn responded_within_ms(budget_ms: u64, latency_ms: Option<u64>) -> PredicateOutcome {
+    let Some(latency_ms) = latency_ms else {
+        return (
+            false,
+            Some("no response measured for this step".to_string()),
+            Some(serde_json::json!({"budget_ms": budget_ms})),
+        );
+    };
+    let (holds, message, _) = outcome(latency_ms <= budget_ms, || {
+        format!("responded in {latency_ms}ms, budget {budget_ms}ms")
+    });
+    (
+        holds,
+        message,
+        Some(serde_json::json!({"latency_ms": latency_ms, "budget_ms": budget_ms})),
+    )
+}
+
+fn bell(raw_output: Option<&RawOutput<'_>>) -> PredicateOutcome {
+    let Some(raw_output) = raw_output else {
+        return (false, Some(raw_output_unavailable()), None);
+    };
+    outcome(raw_output.bells > 0, || {
+        "no bell since the last input".to_string()
+    })
+}
+
+fn control_sequence(
+    pattern: &str,
+    regex: &regex::bytes::Regex,
+    raw_output: Option<&RawOutput<'_>>,
+) -> PredicateOutcome {
+    let Some(raw_output) = raw_output else {
+        return (false, Some(raw_output_unavailable()), None);
+    };
+    if regex.is_match(raw_output.bytes) {
+        return (true, None, None);
+    }
+    (
+        false,
+        Some(format!(
+            "output since the last input did not match '{pattern}'"
+        )),
+        Some(serde_json::json!({ "bytes": raw_output.bytes.len() })),
+    )
+}
+
+fn raw_output_unavailable() -> String {
+    "raw output is only available in waits and step assertions".to_string()
+}
+
+fn expected_screen(
+    screen: &ScreenSnapshot,
+    patterns: &[String],
+    start_line: u64,
+) -> PredicateOutcome {
+    let start = usize::try_from(start_line).unwrap_or(usize::MAX);
+    let mismatches: Vec<Value> = patterns
+        .iter()
+        .enumerate()
+        .filter_map(|(offset, pattern)| {
+            let line = start.saturating_add(offset);
+            let actual = screen.lines.get(line);
+            let matched = actual.is_some_and(|actual| cells_match(pattern, actual));
+            (!matched)
+                .then(|| serde_json::json!({ "line": line, "expected": pattern, "actual": actual }))
+        })
+        .collect();
+    if mismatches.is_empty() {
+        return (true, None, None);
+    }
+    let first = mismatches
+        .first()
+        .and_then(|mismatch| mismatch.get("line"))
+        .cloned()
+        .unwrap_or(Value::Null);
+    (
+        false,
+        Some(format!(
+            "{} of {} expected lines differ (first at line {first})",
+            mismatches.len(),
+            patterns.len()
+        )),
+        Some(serde_json::json!({ "start_line": start_line, "mismatches": mismatches })),
+    )
+}
+
+fn dialog_present(widgets: &[Widget], title: Option<&str>) -> PredicateOutcome {
+    let boxes: Vec<&Widget> = widgets
+        .iter()
+        .filter(|widget| widget.kind == WidgetKind::Box)
+        .collect();
+    let found = boxes.iter().any(|widget| match title {
+        Some(title) => widget
+            .title
+            .as_deref()
+            .is_some_and(|actual| actual.contains(title)),
+        None => true,
+    });
+    if found {
+        return (true, None, None);
+    }
+    let message = match title {
+        Some(title) => format!("no dialog titled '{title}' on screen"),
+        None => "no dialog on screen".to_string(),
+    };
+    let titles: Vec<Option<&str>> = boxes.iter().map(|widget| widget.title.as_deref()).collect();
+    (
+        false,
+        Some(message),
+        Some(serde_json::json!({ "boxes": boxes.len(), "titles": titles })),
+    )
+}
+
+/// One cell of an `expected_screen` pattern.
+#[derive(Clone, Copy, PartialEq)]
+enum Cell {
+    /// A literal character.
+    Char(char),
+    /// `?`: any one cell.
+    Any,
+    /// `*`: any run of cells, possibly empty.
+    Run,
+}
+
+fn pattern_cells(pattern: &str) -> Vec<Cell> {
+    let mut cells = Vec::with_capacity(pattern.len());
+    let mut chars = pattern.trim_end().chars();
+    while let Some(ch) = chars.next() {
+        cells.push(match ch {
+            '\\' => Cell::Char(chars.next().unwrap_or('\\')),
+            '?' => Cell::Any,
+            '*' => Cell::Run,
+            other => Cell::Char(other),
+        });
+    }
+    cells
+}
+
+/// Whether `actual` (trailing whitespace ignored) matches `pattern` cell by cell.
+fn cells_match(pattern: &str, actual: &str) -> bool {
+    let cells = pattern_cells(pattern);
+    let text: Vec<char> = actual.trim_end().chars().collect();
+    let (mut cell, mut pos) = (0, 0);
+    // Position after the last `*` and the text position it is matched up to
+    let mut backtrack: Option<(usize, usize)> = None;
+    while let Some(&ch) = text.get(pos) {
+        match cells.get(cell) {
+            Some(Cell::Run) => {
+                backtrack = Some((cell + 1, pos));
+                cell += 1;
+            }
+            Some(Cell::Any) => {
+                cell += 1;
+                pos += 1;
+            }
+            Some(Cell::Char(expected)) if *expected == ch => {
+                cell += 1;
+                pos += 1;
+            }
+            _ => {
+                let Some((after_run, run_end)) = backtrack else {
+                    return false;
+                };
+                backtrack = Some((after_run, run_end + 1));
+                cell = after_run;
+                pos = run_end + 1;
+            }
+        }
+    }
+    cells
+        .get(cell..)
+        .is_some_and(|rest| rest.iter().all(|cell| *cell == Cell::Run))
+}
+
+/// Typed access to payload fields with `E_PROTOCOL` errors naming the predicate.
+struct Fields<'a> {
+    predicate_type: &'a str,
+    payload: &'a Value,
+}
+
+impl Fields<'_> {
+    fn missing(&self, field: &str) -> RunnerError {
+        RunnerError::with_context(
+            ErrorCode::Protocol,
+            format!(
+                "missing required '{field}' field in {} payload",
+                self.predicate_type
+            ),
+            serde_json::json!({ "received_payload": self.payload }),
+        )
+    }
+
+    fn string(&self, field: &str) -> Result<String, RunnerError> {
+        self.payload
+            .get(field)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| self.missing(field))
+    }
+
+    fn optional_string(&self, field: &str) -> Result<Option<String>, RunnerError> {
+        match self.payload.get(field) {
+            None | Some(Value::Null) => Ok(None),
+            Some(value) => value
+                .as_str()
+                .map(|text| Some(text.to_string()))
+                .ok_or_else(|| self.missing(field)),
+        }
+    }
+
+    fn unsigned(&self, field: &str) -> Result<u64, RunnerError> {
+        self.payload
+            .get(field)
+            .and_then(Value::as_u64)
+            .ok_or_else(|| self.missing(field))
+    }
+
+    fn optional_unsigned(&self, field: &str) -> Result<Option<u64>, RunnerError> {
+        match self.payload.get(field) {
+            None | Some(Value::Null) => Ok(None),
+            Some(value) => value.as_u64().map(Some).ok_or_else(|| self.missing(field)),
+        }
+    }
+
+    fn strings(&self, field: &str) -> Result<Vec<String>, RunnerError> {
+        self.payload
+            .get(field)
+            .and_then(Value::as_array)
+            .and_then(|items| {
+                items
+                    .iter()
+                    .map(|item| item.as_str().map(str::to_string))
+                    .collect::<Option<Vec<_>>>()
+            })
+            .ok_or_else(|| self.missing(field))
+    }
+
+    fn coordinate(&self, field: &str) -> Result<u16, RunnerError> {
+        let value = self.unsigned(field)?;
+        u16::try_from(value).map_err(|_| {
+            RunnerError::with_context(
+                ErrorCode::Protocol,
+                format!(
+                    "{field} value {value} exceeds maximum u16 value {}",
+                    u16::MAX
+                ),
+                serde_json::json!({ "received": value, "max": u16::MAX }),
+            )
+        })
+    }
+
+    fn exit_code(&self) -> Result<i32, RunnerError> {
+        let Some(value) = self.payload.get("code") else {
+            return Ok(0);
+        };
+        value
+            .as_i64()
+            .and_then(|code| i32::try_from(code).ok())
+            .ok_or_else(|| {
+                RunnerError::with_context(
+                    ErrorCode::Protocol,
+                    "exit_code 'code' must be a 32-bit integer",
+                    serde_json::json!({ "received_payload": self.payload }),
+                )
+            })
+    }
+}
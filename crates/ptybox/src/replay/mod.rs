@@ -7,6 +7,7 @@
 //! # Key Types
 //!
 //! - [`ReplayOptions`] - Configuration for replay comparison behavior
+//! - [`ReplayCompare`] - Which artifact classes a replay compares
 //! - [`ReplaySummary`] - Summary of replay comparison results
 //! - [`ReplayMismatch`] - Details about what differed between runs
 //! - [`ReplayDiff`] - Detailed diff information for failed comparisons
@@ -31,6 +32,22 @@
 //! Custom regex-based normalization rules can also be applied to
 //! transcript content and snapshot lines.
 //!
+//! # Paced Replay
+//!
+//! With `determinism.paced_input` in the baseline policy, each replayed step
+//! waits until the time it started in the baseline `run.json`, so timing-
+//! sensitive output lines up snapshot by snapshot.
+//!
+//! Keys are translated with the keymap recorded in the baseline `run.json`
+//! rather than the one the policy names now, so an edited keymap file does
+//! not change what the replay types.
+//!
+//! # Incremental Replay
+//!
+//! [`ReplayOptions::compare`] scopes the comparison to selected artifact
+//! classes (snapshots, transcript, run result, events). Checksum validation
+//! always runs. The selection is recorded in `replay.json`.
+//!
 //! # Example
 //!
 //! ```no_run
@@ -63,11 +80,17 @@
 //! - `run.json` - Run result summary
 //! - `events.jsonl` - Event stream (optional)
 //! - `checksums.json` - File integrity checksums (optional)
+//!
+//! Encrypted artifacts (`*.age`) are decrypted with
+//! [`ReplayOptions::identity`].
 
-use crate::artifacts::ArtifactsWriterConfig;
+use crate::artifacts::{
+    artifact_path, event_log_paths, read_artifact, snapshot_paths, ArtifactsDecryptor,
+    ArtifactsWriterConfig,
+};
 use crate::model::{
     NormalizationFilter, NormalizationRecord, NormalizationRule, NormalizationRuleTarget,
-    NormalizationSource, RunId, RunResult, ScreenSnapshot, NORMALIZATION_VERSION,
+    NormalizationSource, RunId, RunResult, ScreenRegion, ScreenSnapshot, NORMALIZATION_VERSION,
 };
 use crate::runner::{compile_safe_regex, run_scenario, RunnerError, RunnerOptions, RunnerResult};
 use crate::scenario::load_scenario_file;
@@ -75,6 +98,7 @@ use crate::util::compute_checksum;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use serde_json::Value;
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -90,6 +114,16 @@ fn load_json_file<T: DeserializeOwned>(path: &Path, file_type: &str) -> RunnerRe
         .map_err(|err| RunnerError::io("E_PROTOCOL", format!("failed to parse {file_type}"), err))
 }
 
+/// Read a text artifact, decrypting `*.age` files with `decryptor`.
+fn read_text_artifact(
+    path: &Path,
+    decryptor: Option<&ArtifactsDecryptor>,
+    file_type: &str,
+) -> RunnerResult<String> {
+    String::from_utf8(read_artifact(path, decryptor)?)
+        .map_err(|err| RunnerError::io("E_IO", format!("{file_type} is not valid utf-8"), err))
+}
+
 /// Load and parse a JSON file if it exists, returning None if missing.
 fn load_json_file_optional<T: DeserializeOwned>(
     path: &Path,
@@ -116,6 +150,58 @@ pub struct ReplayOptions {
     pub require_events: bool,
     /// Require `checksums.json` to exist for integrity validation.
     pub require_checksums: bool,
+    /// Age identity file for decrypting encrypted artifacts.
+    pub identity: Option<PathBuf>,
+    /// `age` binary used with `identity` (default `/usr/bin/age`).
+    pub age_binary: Option<PathBuf>,
+    /// Artifact classes to compare (default: all).
+    pub compare: ReplayCompare,
+}
+
+/// Artifact classes compared during replay.
+///
+/// Defaults to comparing everything. At least one class must be selected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct ReplayCompare {
+    /// Compare screen snapshots.
+    pub snapshots: bool,
+    /// Compare `transcript.log`.
+    pub transcript: bool,
+    /// Compare `run.json` (status, exit status, steps).
+    pub run_result: bool,
+    /// Compare `events.jsonl`.
+    pub events: bool,
+}
+
+impl ReplayCompare {
+    /// Select no artifact classes; enable the ones you want on the result.
+    #[must_use]
+    pub const fn none() -> Self {
+        Self {
+            snapshots: false,
+            transcript: false,
+            run_result: false,
+            events: false,
+        }
+    }
+
+    /// Whether no artifact class is selected.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        !(self.snapshots || self.transcript || self.run_result || self.events)
+    }
+}
+
+impl Default for ReplayCompare {
+    fn default() -> Self {
+        Self {
+            snapshots: true,
+            transcript: true,
+            run_result: true,
+            events: true,
+        }
+    }
 }
 
 /// Explanation of resolved normalization settings for `--explain` mode.
@@ -131,6 +217,8 @@ pub struct ReplayExplanation {
     pub rules: Vec<NormalizationRule>,
     /// Where the settings came from (CLI, policy, or defaults).
     pub source: NormalizationSource,
+    /// Artifact classes that would be compared.
+    pub compare: ReplayCompare,
 }
 
 /// Summary report from a previous replay, loaded from artifact files.
@@ -169,6 +257,12 @@ pub struct ReplaySummary {
     pub filters: Vec<NormalizationFilter>,
     /// Applied regex normalization rules.
     pub rules: Vec<NormalizationRule>,
+    /// Artifact classes that were compared.
+    pub compare: ReplayCompare,
+    /// Whether steps were paced to their baseline start times
+    /// (`determinism.paced_input`).
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub paced_input: bool,
     /// Mismatch details (present when `status` is `"failed"`).
     pub mismatch: Option<ReplayMismatch>,
 }
@@ -180,6 +274,10 @@ pub struct ReplayMismatch {
     pub kind: String,
     /// Index of the first differing element (for sequential artifacts like snapshots).
     pub index: Option<usize>,
+    /// Content key of the first differing step, for `run_result` mismatches
+    /// in a step.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step_key: Option<String>,
 }
 
 /// Detailed diff information written to `diff.json` on replay failure.
@@ -203,11 +301,13 @@ pub struct ReplayDiff {
 /// and returns them for display (used by `--explain`).
 ///
 /// # Errors
-/// Returns `E_IO` if the baseline `policy.json` cannot be read.
+/// - `E_IO` if the baseline `policy.json` cannot be read
+/// - `E_PROTOCOL` if [`ReplayOptions::compare`] selects nothing
 pub fn explain_replay(
     artifacts_dir: &Path,
     options: ReplayOptions,
 ) -> RunnerResult<ReplayExplanation> {
+    validate_compare(options.compare)?;
     let policy = load_policy_from_artifacts(artifacts_dir)?;
     let settings = resolve_replay_settings(&policy.replay, &options);
     Ok(ReplayExplanation {
@@ -215,6 +315,7 @@ pub fn explain_replay(
         filters: settings.filters,
         rules: settings.rules,
         source: settings.source,
+        compare: options.compare,
     })
 }
 
@@ -244,16 +345,20 @@ pub fn read_replay_report(artifacts_dir: &Path) -> RunnerResult<ReplayReport> {
 /// 1. Validates baseline integrity (checksums, if present)
 /// 2. Loads the scenario and policy from baseline artifacts
 /// 3. Re-runs the scenario with a fresh artifacts directory
-/// 4. Compares snapshots, transcript, run results, and events
+/// 4. Compares the artifact classes selected by [`ReplayOptions::compare`]
+///    (snapshots, transcript, run results, and events by default)
 /// 5. Writes `replay.json` summary and `diff.json` on mismatch
 ///
 /// # Errors
 /// - `E_REPLAY_MISMATCH` if any comparison fails
 /// - `E_IO` if baseline artifacts are missing or unreadable
+/// - `E_PROTOCOL` if [`ReplayOptions::compare`] selects nothing
 /// - Any error from [`run_scenario`] during re-run
 pub fn replay_artifacts(artifacts_dir: &Path, options: ReplayOptions) -> RunnerResult<RunResult> {
+    validate_compare(options.compare)?;
     let policy = load_policy_from_artifacts(artifacts_dir)?;
     let policy_replay = policy.replay.clone();
+    let paced_input = policy.determinism.paced_input;
     let mut scenario = load_scenario_from_artifacts(artifacts_dir)?;
     scenario.run.policy = crate::model::scenario::PolicyRef::Inline(Box::new(policy));
 
@@ -263,29 +368,27 @@ pub fn replay_artifacts(artifacts_dir: &Path, options: ReplayOptions) -> RunnerR
     validate_baseline_integrity(artifacts_dir, &options)?;
 
     let replay_dir = artifacts_dir.join(format!("replay-{}", RunId::new()));
-    let runner_options = RunnerOptions {
-        artifacts: Some(ArtifactsWriterConfig {
-            dir: replay_dir.clone(),
-            overwrite: true,
-        }),
-        progress: None,
-    };
+    let runner_options = replay_runner_options(artifacts_dir, &replay_dir, paced_input)?;
     let run_result = run_scenario(scenario, runner_options)?;
 
     let settings = resolve_replay_settings(&policy_replay, &options);
     validate_normalization_rules(&settings.rules)?;
     write_normalization_record(&replay_dir, &settings)?;
 
-    let original_snapshots = load_snapshots(
-        &artifacts_dir.join("snapshots"),
-        &settings.filters,
-        &settings.rules,
-    )?;
-    let replay_snapshots = load_snapshots(
-        &replay_dir.join("snapshots"),
-        &settings.filters,
-        &settings.rules,
-    )?;
+    let decryptor = options
+        .identity
+        .as_ref()
+        .map(|identity| ArtifactsDecryptor::new(identity, options.age_binary.clone()));
+    let decryptor = decryptor.as_ref();
+    let compare = options.compare;
+    let snapshots = if compare.snapshots {
+        Some((
+            load_snapshots(artifacts_dir, &settings.filters, &settings.rules, decryptor)?,
+            load_snapshots(&replay_dir, &settings.filters, &settings.rules, decryptor)?,
+        ))
+    } else {
+        None
+    };
 
     let mut summary = ReplaySummary {
         replay_version: 1,
@@ -294,13 +397,15 @@ pub fn replay_artifacts(artifacts_dir: &Path, options: ReplayOptions) -> RunnerR
         strict: settings.strict,
         filters: settings.filters.clone(),
         rules: settings.rules.clone(),
+        compare,
+        paced_input,
         mismatch: None,
     };
 
     let compare_result = (|| {
-        if options.require_events {
-            let original_events = artifacts_dir.join("events.jsonl");
-            let replay_events = replay_dir.join("events.jsonl");
+        if compare.events && options.require_events {
+            let original_events = artifact_path(artifacts_dir, "events.jsonl");
+            let replay_events = artifact_path(&replay_dir, "events.jsonl");
             if !original_events.exists() || !replay_events.exists() {
                 return Err(RunnerError::replay_mismatch(
                     "event stream missing",
@@ -310,25 +415,35 @@ pub fn replay_artifacts(artifacts_dir: &Path, options: ReplayOptions) -> RunnerR
         }
         validate_checksums(artifacts_dir, options.require_checksums)?;
         validate_checksums(&replay_dir, options.require_checksums)?;
-        compare_snapshots(&original_snapshots, &replay_snapshots)?;
-        compare_transcript(
-            &artifacts_dir.join("transcript.log"),
-            &replay_dir.join("transcript.log"),
-            &settings.rules,
-        )?;
-        compare_run_results(
-            &artifacts_dir.join("run.json"),
-            &replay_dir.join("run.json"),
-            &settings.filters,
-            &settings.rules,
-        )?;
-        compare_events(
-            &artifacts_dir.join("events.jsonl"),
-            &replay_dir.join("events.jsonl"),
-            &settings.filters,
-            &settings.rules,
-            options.require_events,
-        )?;
+        if let Some((original_snapshots, replay_snapshots)) = &snapshots {
+            compare_snapshots(original_snapshots, replay_snapshots)?;
+        }
+        if compare.transcript {
+            compare_transcript(
+                &artifact_path(artifacts_dir, "transcript.log"),
+                &artifact_path(&replay_dir, "transcript.log"),
+                &settings.rules,
+                decryptor,
+            )?;
+        }
+        if compare.run_result {
+            compare_run_results(
+                &artifacts_dir.join("run.json"),
+                &replay_dir.join("run.json"),
+                &settings.filters,
+                &settings.rules,
+            )?;
+        }
+        if compare.events {
+            compare_events(
+                artifacts_dir,
+                &replay_dir,
+                &settings.filters,
+                &settings.rules,
+                options.require_events,
+                decryptor,
+            )?;
+        }
         Ok::<(), RunnerError>(())
     })();
     match compare_result {
@@ -346,6 +461,54 @@ pub fn replay_artifacts(artifacts_dir: &Path, options: ReplayOptions) -> RunnerR
     }
 }
 
+/// Runner options writing to `replay_dir`, translating keys with the
+/// keymap the baseline's `run.json` recorded; with `paced_input`, each step
+/// starts no earlier than it did in the baseline.
+fn replay_runner_options(
+    artifacts_dir: &Path,
+    replay_dir: &Path,
+    paced_input: bool,
+) -> RunnerResult<RunnerOptions> {
+    let run_json = artifacts_dir.join("run.json");
+    let baseline: Option<RunResult> = if paced_input || run_json.exists() {
+        Some(load_json_file(&run_json, "run.json")?)
+    } else {
+        None
+    };
+    let step_offsets_ms = match &baseline {
+        Some(baseline) if paced_input => baseline
+            .steps
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+            .map(|step| step.started_at_ms)
+            .collect(),
+        _ => Vec::new(),
+    };
+    Ok(RunnerOptions {
+        artifacts: Some(ArtifactsWriterConfig {
+            dir: replay_dir.to_path_buf(),
+            overwrite: true,
+        }),
+        step_offsets_ms,
+        keymap: baseline.and_then(|baseline| baseline.keymap),
+        ..RunnerOptions::default()
+    })
+}
+
+fn validate_compare(compare: ReplayCompare) -> RunnerResult<()> {
+    if compare.is_empty() {
+        return Err(RunnerError::protocol(
+            "E_PROTOCOL",
+            "replay compare selection is empty",
+            Some(serde_json::json!({
+                "fix": "select at least one of snapshots, transcript, run_result, events"
+            })),
+        ));
+    }
+    Ok(())
+}
+
 fn load_scenario_from_artifacts(artifacts_dir: &Path) -> RunnerResult<crate::model::Scenario> {
     let scenario_path = artifacts_dir.join("scenario.json");
     if !scenario_path.exists() {
@@ -498,20 +661,17 @@ fn write_replay_diff(dir: &Path, err: &RunnerError) -> RunnerResult<()> {
 }
 
 fn load_snapshots(
-    dir: &Path,
+    artifacts_dir: &Path,
     filters: &[NormalizationFilter],
     rules: &[NormalizationRule],
+    decryptor: Option<&ArtifactsDecryptor>,
 ) -> RunnerResult<Vec<Value>> {
-    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
-        .map_err(|err| RunnerError::io("E_IO", "failed to read snapshots dir", err))?
-        .filter_map(|entry| entry.ok().map(|e| e.path()))
-        .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("json"))
-        .collect();
-    entries.sort();
+    let entries = snapshot_paths(artifacts_dir)?;
 
     let mut snapshots = Vec::with_capacity(entries.len());
     for path in entries {
-        let snapshot: ScreenSnapshot = load_json_file(&path, "snapshot")?;
+        let snapshot: ScreenSnapshot = serde_json::from_slice(&read_artifact(&path, decryptor)?)
+            .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to parse snapshot", err))?;
         let value = serde_json::to_value(snapshot)
             .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to serialize snapshot", err))?;
         let value = if has_filter(filters, NormalizationFilter::SnapshotId) {
@@ -551,6 +711,16 @@ fn apply_rules_to_text(
 /// Returns an error for the first invalid pattern found.
 fn validate_normalization_rules(rules: &[NormalizationRule]) -> RunnerResult<()> {
     for rule in rules {
+        if rule.target == NormalizationRuleTarget::SnapshotRegion && rule.region.is_none() {
+            return Err(RunnerError::protocol(
+                "E_PROTOCOL",
+                "snapshot_region normalization rule requires a region",
+                Some(serde_json::json!({
+                    "pattern": rule.pattern,
+                    "replace": rule.replace
+                })),
+            ));
+        }
         compile_safe_regex(&rule.pattern).map_err(|err| {
             RunnerError::protocol(
                 "E_PROTOCOL",
@@ -593,6 +763,84 @@ fn apply_rules_to_snapshot_object(
             *line = Value::String(normalized);
         }
     }
+    for rule in rules {
+        if rule.target != NormalizationRuleTarget::SnapshotRegion {
+            continue;
+        }
+        if let Some(region) = rule.region {
+            apply_region_rule(obj, rule, region);
+        }
+    }
+}
+
+/// Replace the text (and cells, if captured) inside `region` on every row
+/// whose region text matches the rule pattern.
+fn apply_region_rule(
+    obj: &mut serde_json::Map<String, Value>,
+    rule: &NormalizationRule,
+    region: ScreenRegion,
+) {
+    let Ok(guard) = compile_safe_regex(&rule.pattern) else {
+        return;
+    };
+    let start = usize::from(region.col);
+    let width = usize::from(region.cols);
+    let end = start.saturating_add(width);
+    let fill: Vec<char> = rule
+        .replace
+        .chars()
+        .chain(std::iter::repeat(' '))
+        .take(width)
+        .collect();
+
+    let mut matched_rows = Vec::new();
+    if let Some(lines) = obj.get_mut("lines").and_then(|val| val.as_array_mut()) {
+        let rows = lines
+            .iter_mut()
+            .enumerate()
+            .skip(usize::from(region.row))
+            .take(usize::from(region.rows));
+        for (index, line) in rows {
+            let Some(text) = line.as_str() else {
+                continue;
+            };
+            let mut chars: Vec<char> = text.chars().collect();
+            let current: String = chars.iter().skip(start).take(width).collect();
+            if !guard.is_match(&current) {
+                continue;
+            }
+            if chars.len() < end {
+                chars.resize(end, ' ');
+            }
+            chars.splice(start..end, fill.iter().copied());
+            let replaced: String = chars.into_iter().collect();
+            *line = Value::String(replaced.trim_end().to_string());
+            matched_rows.push(index);
+        }
+    }
+
+    let Some(cells) = obj.get_mut("cells").and_then(|val| val.as_array_mut()) else {
+        return;
+    };
+    for index in matched_rows {
+        let Some(row) = cells.get_mut(index).and_then(|val| val.as_array_mut()) else {
+            continue;
+        };
+        for (cell, ch) in row.iter_mut().skip(start).take(width).zip(&fill) {
+            *cell = serde_json::json!({
+                "ch": ch.to_string(),
+                "width": 1,
+                "style": {
+                    "fg": "default",
+                    "bg": "default",
+                    "bold": false,
+                    "italic": false,
+                    "underline": false,
+                    "inverse": false
+                }
+            });
+        }
+    }
 }
 
 fn compare_snapshots(original: &[Value], replay: &[Value]) -> RunnerResult<()> {
@@ -609,11 +857,10 @@ fn compare_transcript(
     original: &Path,
     replay: &Path,
     rules: &[NormalizationRule],
+    decryptor: Option<&ArtifactsDecryptor>,
 ) -> RunnerResult<()> {
-    let original_text = fs::read_to_string(original)
-        .map_err(|err| RunnerError::io("E_IO", "failed to read transcript", err))?;
-    let replay_text = fs::read_to_string(replay)
-        .map_err(|err| RunnerError::io("E_IO", "failed to read replay transcript", err))?;
+    let original_text = read_text_artifact(original, decryptor, "transcript")?;
+    let replay_text = read_text_artifact(replay, decryptor, "replay transcript")?;
     let original_text =
         apply_rules_to_text(original_text, rules, NormalizationRuleTarget::Transcript);
     let replay_text = apply_rules_to_text(replay_text, rules, NormalizationRuleTarget::Transcript);
@@ -675,21 +922,101 @@ fn compare_run_results(
             }
             _ => None,
         };
-        return Err(RunnerError::replay_mismatch(
-            "run result mismatch",
-            serde_json::json!({
-                "kind": "run_result",
-                "first_diff_field": first_diff_field,
-            }),
-        ));
+        let mut context = serde_json::json!({
+            "kind": "run_result",
+            "first_diff_field": first_diff_field,
+        });
+        if first_diff_field.as_deref() == Some("steps") {
+            if let (Some(step), Some(obj)) = (
+                first_step_mismatch(&original_value, &replay_value),
+                context.as_object_mut(),
+            ) {
+                obj.insert("step".to_string(), step);
+            }
+        }
+        return Err(RunnerError::replay_mismatch("run result mismatch", context));
     }
     Ok(())
 }
 
+/// Locate the first baseline step that differs from its replay counterpart.
+///
+/// Steps are paired by `step_key` (the nth step with a key pairs with the
+/// nth replay step with that key), falling back to position for results
+/// without keys. Returns `{step_key, name, index, reason}`, where `reason`
+/// is `differs`, `missing` (no replay counterpart), or `unexpected` (a
+/// replay step with no baseline counterpart).
+fn first_step_mismatch(original: &Value, replay: &Value) -> Option<Value> {
+    let steps = |value: &Value| {
+        value
+            .get("steps")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default()
+    };
+    let (original, replay) = (steps(original), steps(replay));
+    let keyed = |steps: &[Value]| -> Vec<String> {
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        steps
+            .iter()
+            .enumerate()
+            .map(
+                |(index, step)| match step.get("step_key").and_then(Value::as_str) {
+                    Some(key) => {
+                        let occurrence = seen.entry(key.to_string()).or_insert(0);
+                        *occurrence += 1;
+                        format!("{key}#{occurrence}")
+                    }
+                    None => format!("@{index}"),
+                },
+            )
+            .collect()
+    };
+    let (original_keys, replay_keys) = (keyed(&original), keyed(&replay));
+    let describe = |step: &Value, index: usize, reason: &str| {
+        serde_json::json!({
+            "step_key": step.get("step_key"),
+            "name": step.get("name"),
+            "index": index,
+            "reason": reason,
+        })
+    };
+    for (index, (step, key)) in original.iter().zip(&original_keys).enumerate() {
+        match replay_keys.iter().position(|other| other == key) {
+            None => return Some(describe(step, index, "missing")),
+            Some(at) if replay.get(at) != Some(step) => {
+                return Some(describe(step, index, "differs"))
+            }
+            Some(_) => {}
+        }
+    }
+    replay
+        .iter()
+        .zip(&replay_keys)
+        .enumerate()
+        .find(|(_, (_, key))| !original_keys.contains(key))
+        .map(|(index, (step, _))| describe(step, index, "unexpected"))
+}
+
 fn load_run_value(path: &Path) -> RunnerResult<Value> {
     load_json_file(path, "run.json")
 }
 
+/// Drop the measured latency from `responded_within_ms` assertion details.
+fn remove_assertion_latencies(step: &mut serde_json::Map<String, Value>) {
+    let Some(assertions) = step.get_mut("assertions").and_then(Value::as_array_mut) else {
+        return;
+    };
+    for assertion in assertions {
+        if assertion.get("type").and_then(Value::as_str) != Some("responded_within_ms") {
+            continue;
+        }
+        if let Some(details) = assertion.get_mut("details").and_then(Value::as_object_mut) {
+            details.remove("latency_ms");
+        }
+    }
+}
+
 fn normalize_run_value(
     value: &mut Value,
     filters: &[NormalizationFilter],
@@ -699,6 +1026,15 @@ fn normalize_run_value(
         return;
     };
 
+    // Provenance describes where a run came from, not what it did; replays
+    // of older baselines or under a newer ptybox must still compare equal.
+    // The failure classification is derived from the compared fields.
+    // Grants are folded into the baseline's policy.json, so the replay
+    // runs under the same policy without applying (or recording) them.
+    obj.remove("provenance");
+    obj.remove("classification");
+    obj.remove("policy_grants");
+
     // Top-level run fields
     remove_if_filtered(obj, filters, NormalizationFilter::RunId, &["run_id"]);
     remove_if_filtered(
@@ -708,13 +1044,18 @@ fn normalize_run_value(
         &["started_at_ms", "ended_at_ms"],
     );
 
-    // Step timestamps
+    // Step timestamps (wait poll counters, response latencies, and sample
+    // counts are timing-dependent too)
     if has_filter(filters, NormalizationFilter::StepTimestamps) {
         if let Some(steps) = obj.get_mut("steps").and_then(|val| val.as_array_mut()) {
             for step in steps {
                 if let Some(step_obj) = step.as_object_mut() {
                     step_obj.remove("started_at_ms");
                     step_obj.remove("ended_at_ms");
+                    step_obj.remove("wait");
+                    step_obj.remove("latency_ms");
+                    step_obj.remove("samples");
+                    remove_assertion_latencies(step_obj);
                 }
             }
         }
@@ -821,11 +1162,15 @@ fn compare_events(
     filters: &[NormalizationFilter],
     rules: &[NormalizationRule],
     require: bool,
+    decryptor: Option<&ArtifactsDecryptor>,
 ) -> RunnerResult<()> {
-    let original_events = load_events_if_present(original, filters, rules)?;
-    let replay_events = load_events_if_present(replay, filters, rules)?;
+    let original_events =
+        load_events_if_present(&event_log_paths(original)?, filters, rules, decryptor)?;
+    let replay_events =
+        load_events_if_present(&event_log_paths(replay)?, filters, rules, decryptor)?;
     match (original_events, replay_events) {
-        (None, None) => {
+        // A baseline without event logs (e.g. slimmed) has nothing to compare.
+        (None, _) => {
             if require {
                 return Err(RunnerError::replay_mismatch(
                     "event stream missing",
@@ -834,7 +1179,7 @@ fn compare_events(
             }
             Ok(())
         }
-        (Some(_), None) | (None, Some(_)) => Err(RunnerError::replay_mismatch(
+        (Some(_), None) => Err(RunnerError::replay_mismatch(
             "event stream presence mismatch",
             serde_json::json!({ "kind": "events" }),
         )),
@@ -848,30 +1193,34 @@ fn compare_events(
     }
 }
 
+/// Load and normalize observations from `paths` in order (flat layout has a
+/// single log; `per_step` layout has one per step plus the top-level log).
 fn load_events_if_present(
-    path: &Path,
+    paths: &[PathBuf],
     filters: &[NormalizationFilter],
     rules: &[NormalizationRule],
+    decryptor: Option<&ArtifactsDecryptor>,
 ) -> RunnerResult<Option<Vec<Value>>> {
-    if !path.exists() {
+    if paths.is_empty() {
         return Ok(None);
     }
-    let data = fs::read_to_string(path)
-        .map_err(|err| RunnerError::io("E_IO", "failed to read events log", err))?;
     let mut events = Vec::new();
-    for (line_no, line) in data.lines().enumerate() {
-        if line.trim().is_empty() {
-            continue;
+    for path in paths {
+        let data = read_text_artifact(path, decryptor, "events log")?;
+        for (line_no, line) in data.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let mut value: Value = serde_json::from_str(line).map_err(|err| {
+                RunnerError::io(
+                    "E_PROTOCOL",
+                    format!("failed to parse event line {}", line_no + 1),
+                    err,
+                )
+            })?;
+            normalize_observation_value(&mut value, filters, rules);
+            events.push(value);
         }
-        let mut value: Value = serde_json::from_str(line).map_err(|err| {
-            RunnerError::io(
-                "E_PROTOCOL",
-                format!("failed to parse event line {}", line_no + 1),
-                err,
-            )
-        })?;
-        normalize_observation_value(&mut value, filters, rules);
-        events.push(value);
     }
     Ok(Some(events))
 }
@@ -1006,7 +1355,17 @@ fn normalize_observation_value(
         NormalizationFilter::ObservationTimestamp,
         &["timestamp_ms"],
     );
-    remove_if_filtered(obj, filters, NormalizationFilter::Events, &["events"]);
+    remove_if_filtered(
+        obj,
+        filters,
+        NormalizationFilter::Events,
+        &[
+            "events",
+            "read_truncated",
+            "read_bytes",
+            "read_dropped_bytes",
+        ],
+    );
 
     // Screen normalization
     if let Some(screen) = obj.get_mut("screen").and_then(|val| val.as_object_mut()) {
@@ -1032,5 +1391,13 @@ fn mismatch_from_error(err: &RunnerError) -> Option<ReplayMismatch> {
         .get("index")
         .and_then(Value::as_u64)
         .map(|val| val as usize);
-    Some(ReplayMismatch { kind, index })
+    let step_key = context
+        .pointer("/step/step_key")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    Some(ReplayMismatch {
+        kind,
+        index,
+        step_key,
+    })
 }
@@ -0,0 +1,856 @@
+//! Run results as GitHub annotations, Markdown, and JUnit XML reports.
+//!
+//! Two formats carry the same annotations: one per failed or errored step,
+//! placed on the line where that step starts in the scenario file (see
+//! [`ScenarioSource`]), plus one on line 1 when the run failed without a
+//! failing step.
+//!
+//! - [`github_workflow_commands`] — `::error` workflow commands, printed
+//!   from a GitHub Actions step to annotate the diff inline
+//! - [`github_check_run`] — a Checks API `POST /repos/{owner}/{repo}/check-runs`
+//!   payload, for posting the result as its own check
+//!
+//! [`markdown_report`] renders a recorded run as a Markdown document for
+//! pasting into a pull request description (`ptybox report`).
+//!
+//! [`junit_xml`] renders a run as a JUnit XML test suite for CI test result
+//! views, written when a run finishes by a
+//! [`Reporter`](crate::runner::Reporter) (`ptybox run --report junit=PATH`).
+
+use crate::model::{ErrorInfo, NormalizationRecord, RunResult, RunStatus, StepResult, StepStatus};
+use crate::scenario::ScenarioSource;
+use serde::Serialize;
+use std::fmt::Write as _;
+
+/// Check run name used in [`github_check_run`].
+pub const CHECK_NAME: &str = "ptybox";
+
+/// Most annotations the Checks API accepts in one request.
+pub const MAX_CHECK_ANNOTATIONS: usize = 50;
+
+/// A Checks API check run.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+pub struct GithubCheckRun {
+    /// Check name shown in the pull request.
+    pub name: String,
+    /// Commit the check is attached to (from the run's provenance `git_sha`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub head_sha: Option<String>,
+    /// Always `completed`.
+    pub status: String,
+    /// `success`, `failure`, or `cancelled`.
+    pub conclusion: String,
+    /// Title, summary, and annotations.
+    pub output: GithubCheckOutput,
+}
+
+/// Output section of a [`GithubCheckRun`].
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+pub struct GithubCheckOutput {
+    /// One-line outcome.
+    pub title: String,
+    /// Markdown summary of the run.
+    pub summary: String,
+    /// At most [`MAX_CHECK_ANNOTATIONS`] annotations.
+    pub annotations: Vec<GithubAnnotation>,
+}
+
+/// One annotation on a scenario file line.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+pub struct GithubAnnotation {
+    /// Scenario file path.
+    pub path: String,
+    /// Line the annotation starts on.
+    pub start_line: u32,
+    /// Line the annotation ends on.
+    pub end_line: u32,
+    /// Always `failure`.
+    pub annotation_level: String,
+    /// Short heading.
+    pub title: String,
+    /// Error code, failed assertions, and likely cause.
+    pub message: String,
+}
+
+/// Annotations for a run: one per failed or errored step, or one for the
+/// whole run when it failed without a failing step.
+///
+/// Steps whose line is unknown are placed on line 1.
+#[must_use]
+pub fn github_annotations(
+    result: Result<&RunResult, &ErrorInfo>,
+    source: &ScenarioSource,
+) -> Vec<GithubAnnotation> {
+    let run = match result {
+        Ok(run) => run,
+        Err(error) => {
+            return vec![annotation(
+                source,
+                1,
+                "ptybox run errored".to_string(),
+                error_line(error),
+            )]
+        }
+    };
+    let mut annotations: Vec<GithubAnnotation> = run
+        .steps
+        .iter()
+        .flatten()
+        .enumerate()
+        .filter(|(_, step)| matches!(step.status, StepStatus::Failed | StepStatus::Errored))
+        .map(|(index, step)| {
+            let line = source.step_line(index).unwrap_or(1);
+            annotation(source, line, step_title(step), step_message(run, step))
+        })
+        .collect();
+    if annotations.is_empty() && run.status != RunStatus::Passed {
+        let mut lines: Vec<String> = run.error.iter().map(error_line).collect();
+        lines.extend(classification_line(run));
+        annotations.push(annotation(
+            source,
+            1,
+            format!("ptybox run {}", status_word(&run.status)),
+            lines.join("\n"),
+        ));
+    }
+    annotations
+}
+
+/// GitHub Actions workflow commands annotating the scenario file: one
+/// `::error` line per annotation, or a single `::notice` when the run passed.
+#[must_use]
+pub fn github_workflow_commands(
+    result: Result<&RunResult, &ErrorInfo>,
+    source: &ScenarioSource,
+) -> String {
+    let annotations = github_annotations(result, source);
+    if annotations.is_empty() {
+        return format!(
+            "::notice file={},line=1,title={}::{}\n",
+            escape_property(&source.path),
+            escape_property("ptybox run passed"),
+            escape_data(&summary_title(result)),
+        );
+    }
+    let mut commands = String::new();
+    for annotation in &annotations {
+        let _ = writeln!(
+            commands,
+            "::error file={},line={},endLine={},title={}::{}",
+            escape_property(&annotation.path),
+            annotation.start_line,
+            annotation.end_line,
+            escape_property(&annotation.title),
+            escape_data(&annotation.message),
+        );
+    }
+    commands
+}
+
+/// A completed check run for the run, carrying its annotations.
+///
+/// Annotations past [`MAX_CHECK_ANNOTATIONS`] are dropped and counted in the
+/// summary.
+#[must_use]
+pub fn github_check_run(
+    result: Result<&RunResult, &ErrorInfo>,
+    source: &ScenarioSource,
+) -> GithubCheckRun {
+    let mut annotations = github_annotations(result, source);
+    let dropped = annotations.len().saturating_sub(MAX_CHECK_ANNOTATIONS);
+    annotations.truncate(MAX_CHECK_ANNOTATIONS);
+    let conclusion = match result.map(|run| &run.status) {
+        Ok(RunStatus::Passed) => "success",
+        Ok(RunStatus::Canceled) => "cancelled",
+        Ok(RunStatus::Failed | RunStatus::Errored) | Err(_) => "failure",
+    };
+    let mut summary = summary_body(result, source);
+    if dropped > 0 {
+        let _ = write!(
+            summary,
+            "\n{dropped} more annotation(s) not shown (limit {MAX_CHECK_ANNOTATIONS}).\n"
+        );
+    }
+    GithubCheckRun {
+        name: CHECK_NAME.to_string(),
+        head_sha: result
+            .ok()
+            .and_then(|run| run.provenance.as_ref())
+            .and_then(|provenance| provenance.git_sha.clone()),
+        status: "completed".to_string(),
+        conclusion: conclusion.to_string(),
+        output: GithubCheckOutput {
+            title: summary_title(result),
+            summary,
+            annotations,
+        },
+    }
+}
+
+fn annotation(
+    source: &ScenarioSource,
+    line: u32,
+    title: String,
+    message: String,
+) -> GithubAnnotation {
+    GithubAnnotation {
+        path: source.path.clone(),
+        start_line: line,
+        end_line: line,
+        annotation_level: "failure".to_string(),
+        title,
+        message,
+    }
+}
+
+fn step_title(step: &StepResult) -> String {
+    let outcome = match step.status {
+        StepStatus::Errored => "errored",
+        StepStatus::Passed | StepStatus::Failed | StepStatus::Skipped => "failed",
+    };
+    format!("step '{}' {outcome}", step.name)
+}
+
+fn step_message(run: &RunResult, step: &StepResult) -> String {
+    let mut lines: Vec<String> = step.error.iter().map(error_line).collect();
+    lines.extend(failed_assertions(step));
+    if step.attempts > 1 {
+        lines.push(format!("after {} attempts", step.attempts));
+    }
+    lines.extend(classification_line(run));
+    lines.join("\n")
+}
+
+fn error_line(error: &ErrorInfo) -> String {
+    format!("{}: {}", error.code, error.message)
+}
+
+fn classification_line(run: &RunResult) -> Option<String> {
+    run.classification.as_ref().map(|classification| {
+        format!(
+            "likely cause: {} ({} confidence): {}",
+            classification.category.as_str(),
+            classification.confidence.as_str(),
+            classification.reason
+        )
+    })
+}
+
+fn status_word(status: &RunStatus) -> &'static str {
+    match status {
+        RunStatus::Passed => "passed",
+        RunStatus::Failed => "failed",
+        RunStatus::Errored => "errored",
+        RunStatus::Canceled => "canceled",
+    }
+}
+
+fn summary_title(result: Result<&RunResult, &ErrorInfo>) -> String {
+    match result {
+        Ok(run) => {
+            let steps = run.steps.as_deref().unwrap_or_default();
+            let passed = steps
+                .iter()
+                .filter(|step| step.status == StepStatus::Passed)
+                .count();
+            format!(
+                "{}: {passed}/{} steps passed",
+                status_word(&run.status),
+                steps.len()
+            )
+        }
+        Err(error) => format!("errored: {}", error.code),
+    }
+}
+
+fn summary_body(result: Result<&RunResult, &ErrorInfo>, source: &ScenarioSource) -> String {
+    let mut lines = vec![format!("Scenario `{}`", source.path)];
+    match result {
+        Ok(run) => {
+            lines.push(format!("Run `{}`: {}", run.run_id, summary_title(result)));
+            lines.extend(run.error.iter().map(error_line));
+            lines.extend(classification_line(run));
+        }
+        Err(error) => lines.push(error_line(error)),
+    }
+    let mut summary = lines.join("\n\n");
+    summary.push('\n');
+    summary
+}
+
+/// Escape a workflow command message.
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a workflow command property value.
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// A Markdown report of a recorded run: status, a table of steps, failure
+/// details with the final screen, budget usage, and the normalization the
+/// run's artifacts were recorded under.
+///
+/// `normalization` is the run's `normalization.json`, when recorded.
+#[must_use]
+pub fn markdown_report(run: &RunResult, normalization: Option<&NormalizationRecord>) -> String {
+    let steps = run.steps.as_deref().unwrap_or_default();
+    let mut report = format!(
+        "# {} ptybox run {}\n\n",
+        run_badge(&run.status),
+        status_word(&run.status)
+    );
+    report.push_str("| | |\n|---|---|\n");
+    if let Some(scenario) = &run.scenario {
+        let _ = writeln!(
+            report,
+            "| Scenario | {} |",
+            cell(&code(&scenario.metadata.name))
+        );
+    }
+    let _ = writeln!(report, "| Run | {} |", code(&run.run_id.to_string()));
+    let command = std::iter::once(run.command.as_str())
+        .chain(run.args.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let _ = writeln!(report, "| Command | {} |", cell(&code(&command)));
+    let _ = writeln!(
+        report,
+        "| Duration | {} ms |",
+        run.ended_at_ms.saturating_sub(run.started_at_ms)
+    );
+    if run.steps.is_some() {
+        let _ = writeln!(report, "| Steps | {} |", step_counts(steps));
+    }
+    if let Some(exit) = &run.exit_status {
+        let _ = writeln!(report, "| Exit | {} |", exit_description(exit));
+    }
+    if let Some(error) = &run.error {
+        let _ = writeln!(report, "| Error | {} |", cell(&error_line(error)));
+    }
+    if let Some(classification) = &run.classification {
+        let _ = writeln!(
+            report,
+            "| Likely cause | {} ({} confidence): {} |",
+            classification.category.as_str(),
+            classification.confidence.as_str(),
+            cell(&classification.reason)
+        );
+    }
+
+    if !steps.is_empty() {
+        report.push_str("\n## Steps\n\n| # | Step | Status | Attempts | Duration | Details |\n");
+        report.push_str("|---|---|---|---|---|---|\n");
+        for (index, step) in steps.iter().enumerate() {
+            let _ = writeln!(
+                report,
+                "| {} | {} | {} {} | {} | {} ms | {} |",
+                index + 1,
+                cell(&step.name),
+                step_badge(&step.status),
+                step_status_word(&step.status),
+                step.attempts,
+                step.ended_at_ms.saturating_sub(step.started_at_ms),
+                cell(&step_details(step))
+            );
+        }
+    }
+
+    if run.status != RunStatus::Passed {
+        report.push_str(&failures_section(run, steps));
+    }
+    report.push_str(&budgets_section(run, steps));
+    report.push_str(&normalization_section(run, normalization));
+    report
+}
+
+fn failures_section(run: &RunResult, steps: &[StepResult]) -> String {
+    let mut section = String::from("\n## Failures\n");
+    let failed = steps
+        .iter()
+        .enumerate()
+        .filter(|(_, step)| matches!(step.status, StepStatus::Failed | StepStatus::Errored));
+    let mut any = false;
+    for (index, step) in failed {
+        any = true;
+        let _ = write!(
+            section,
+            "\n### {} Step {}: {}\n\n",
+            step_badge(&step.status),
+            index + 1,
+            step.name
+        );
+        let mut lines: Vec<String> = step.error.iter().map(error_line).collect();
+        lines.extend(failed_assertions(step));
+        if step.attempts > 1 {
+            lines.push(format!("after {} attempts", step.attempts));
+        }
+        for line in lines {
+            let _ = writeln!(section, "- {line}");
+        }
+    }
+    if !any {
+        section.push('\n');
+        let mut lines: Vec<String> = run.error.iter().map(error_line).collect();
+        if lines.is_empty() {
+            lines.push(format!("run {}", status_word(&run.status)));
+        }
+        for line in lines {
+            let _ = writeln!(section, "- {line}");
+        }
+    }
+    if let Some(observation) = &run.final_observation {
+        let screen = &observation.screen;
+        let _ = write!(
+            section,
+            "\n### Final screen ({}x{})\n\n{}",
+            screen.cols,
+            screen.rows,
+            fenced(screen.lines.join("\n").trim_end_matches(['\n', ' ']))
+        );
+    }
+    section
+}
+
+fn budgets_section(run: &RunResult, steps: &[StepResult]) -> String {
+    let budgets = &run.policy.budgets;
+    let mut section = String::from("\n## Budgets\n\n| Budget | Used | Limit |\n|---|---|---|\n");
+    let runtime = run.ended_at_ms.saturating_sub(run.started_at_ms);
+    let _ = writeln!(
+        section,
+        "| Runtime | {runtime} ms ({}) | {} ms |",
+        percent(runtime, budgets.max_runtime_ms),
+        budgets.max_runtime_ms
+    );
+    if run.steps.is_some() {
+        let used = u64::try_from(steps.len()).unwrap_or(u64::MAX);
+        let _ = writeln!(
+            section,
+            "| Steps | {used} ({}) | {} |",
+            percent(used, budgets.max_steps),
+            budgets.max_steps
+        );
+    }
+    if let Some(longest) = steps
+        .iter()
+        .filter_map(|step| step.wait.map(|wait| wait.wait_ms))
+        .max()
+    {
+        let _ = writeln!(
+            section,
+            "| Longest wait | {longest} ms ({}) | {} ms |",
+            percent(longest, budgets.max_wait_ms),
+            budgets.max_wait_ms
+        );
+    }
+    section
+}
+
+fn normalization_section(run: &RunResult, normalization: Option<&NormalizationRecord>) -> String {
+    let mut section = String::from("\n## Normalization\n\n");
+    let Some(record) = normalization else {
+        let replay = &run.policy.replay;
+        let _ = writeln!(
+            section,
+            "No `normalization.json` was recorded; replay uses the policy's settings (strict: {}).",
+            if replay.strict { "yes" } else { "no" }
+        );
+        return section;
+    };
+    let source = serde_json::to_value(&record.source)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default();
+    let _ = writeln!(section, "- Source: {source}");
+    if record.strict {
+        section.push_str("- Strict: snapshots and transcripts are compared exactly\n");
+        return section;
+    }
+    let filters: Vec<String> = record
+        .filters
+        .iter()
+        .filter_map(|filter| serde_json::to_value(filter).ok())
+        .filter_map(|value| value.as_str().map(code))
+        .collect();
+    if filters.is_empty() {
+        section.push_str("- Filters: none\n");
+    } else {
+        let _ = writeln!(section, "- Filters: {}", filters.join(", "));
+    }
+    for rule in &record.rules {
+        let target = serde_json::to_value(&rule.target)
+            .ok()
+            .and_then(|value| value.as_str().map(str::to_string))
+            .unwrap_or_default();
+        let _ = writeln!(
+            section,
+            "- Rule ({target}): {} \u{2192} {}",
+            code(&rule.pattern),
+            code(&rule.replace)
+        );
+    }
+    section
+}
+
+fn run_badge(status: &RunStatus) -> &'static str {
+    match status {
+        RunStatus::Passed => "\u{2705}",
+        RunStatus::Failed => "\u{274c}",
+        RunStatus::Errored => "\u{1f4a5}",
+        RunStatus::Canceled => "\u{1f6ab}",
+    }
+}
+
+fn step_badge(status: &StepStatus) -> &'static str {
+    match status {
+        StepStatus::Passed => "\u{2705}",
+        StepStatus::Failed => "\u{274c}",
+        StepStatus::Errored => "\u{1f4a5}",
+        StepStatus::Skipped => "\u{23ed}\u{fe0f}",
+    }
+}
+
+fn step_status_word(status: &StepStatus) -> &'static str {
+    match status {
+        StepStatus::Passed => "passed",
+        StepStatus::Failed => "failed",
+        StepStatus::Errored => "errored",
+        StepStatus::Skipped => "skipped",
+    }
+}
+
+fn step_counts(steps: &[StepResult]) -> String {
+    let count = |status: StepStatus| steps.iter().filter(|step| step.status == status).count();
+    let mut counts = format!("{}/{} passed", count(StepStatus::Passed), steps.len());
+    for (status, word) in [
+        (StepStatus::Failed, "failed"),
+        (StepStatus::Errored, "errored"),
+        (StepStatus::Skipped, "skipped"),
+    ] {
+        let n = count(status);
+        if n > 0 {
+            let _ = write!(counts, ", {n} {word}");
+        }
+    }
+    counts
+}
+
+/// First failed assertion of a step, or its error, for the steps table.
+fn step_details(step: &StepResult) -> String {
+    failed_assertions(step)
+        .next()
+        .or_else(|| step.error.as_ref().map(error_line))
+        .unwrap_or_default()
+}
+
+fn failed_assertions(step: &StepResult) -> impl Iterator<Item = String> + '_ {
+    step.assertions
+        .iter()
+        .filter(|assertion| !assertion.passed)
+        .map(|assertion| match &assertion.message {
+            Some(message) => format!("{}: {message}", assertion.assertion_type),
+            None => format!("{} failed", assertion.assertion_type),
+        })
+}
+
+fn exit_description(exit: &crate::model::ExitStatus) -> String {
+    let mut description = match (exit.exit_code, exit.signal) {
+        (Some(code), _) => format!("code {code}"),
+        (None, Some(signal)) => format!("signal {signal}"),
+        (None, None) => "unknown".to_string(),
+    };
+    if exit.terminated_by_harness {
+        description.push_str(" (stopped by ptybox)");
+    }
+    description
+}
+
+fn percent(used: u64, limit: u64) -> String {
+    if limit == 0 {
+        return "-".to_string();
+    }
+    format!("{}%", used.saturating_mul(100) / limit)
+}
+
+/// Text for a table cell: pipes escaped and line breaks kept.
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', "<br>")
+}
+
+/// An inline code span, delimited by more backticks than `text` contains
+/// in a row.
+fn code(text: &str) -> String {
+    let ticks = "`".repeat(longest_backtick_run(text) + 1);
+    let pad = if text.starts_with('`') || text.ends_with('`') {
+        " "
+    } else {
+        ""
+    };
+    format!("{ticks}{pad}{text}{pad}{ticks}")
+}
+
+/// A fenced code block whose fence no line of `text` can close.
+fn fenced(text: &str) -> String {
+    let fence = "`".repeat((longest_backtick_run(text) + 1).max(3));
+    format!("{fence}text\n{text}\n{fence}\n")
+}
+
+fn longest_backtick_run(text: &str) -> usize {
+    text.split(|c| c != '`').map(str::len).max().unwrap_or(0)
+}
+
+/// Test case outcome in a JUnit report.
+enum JunitOutcome {
+    Passed,
+    Failure {
+        kind: String,
+        message: String,
+        body: String,
+    },
+    Error {
+        kind: String,
+        message: String,
+        body: String,
+    },
+    Skipped {
+        message: String,
+    },
+}
+
+struct JunitCase {
+    name: String,
+    time_ms: u64,
+    outcome: JunitOutcome,
+    /// The step's `metadata`, values as compact JSON.
+    properties: Vec<(String, String)>,
+}
+
+/// A JUnit XML report of a run, for CI test result views.
+///
+/// The run is one `<testsuite>` named `suite` (the scenario name), with one
+/// `<testcase>` per step: a failed step carries a `<failure>` listing its
+/// failed assertions, an errored step an `<error>`, and a skipped step
+/// `<skipped/>`. A step's `metadata` becomes the test case's `<properties>`,
+/// each value as compact JSON. A run that did not pass without a failing
+/// step, a run without steps (`exec`), and a run that errored before
+/// producing a result get a `run` test case instead.
+#[must_use]
+pub fn junit_xml(result: Result<&RunResult, &ErrorInfo>, suite: &str) -> String {
+    let (cases, properties, time_ms) = match result {
+        Ok(run) => (
+            junit_cases(run),
+            junit_properties(run),
+            run.ended_at_ms.saturating_sub(run.started_at_ms),
+        ),
+        Err(error) => (
+            vec![JunitCase {
+                name: "run".to_string(),
+                time_ms: 0,
+                outcome: JunitOutcome::Error {
+                    kind: error.code.clone(),
+                    message: error.message.clone(),
+                    body: error_line(error),
+                },
+                properties: Vec::new(),
+            }],
+            Vec::new(),
+            0,
+        ),
+    };
+    let count = |matches: fn(&JunitOutcome) -> bool| {
+        cases.iter().filter(|case| matches(&case.outcome)).count()
+    };
+    let counts = format!(
+        "tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{}\"",
+        cases.len(),
+        count(|outcome| matches!(outcome, JunitOutcome::Failure { .. })),
+        count(|outcome| matches!(outcome, JunitOutcome::Error { .. })),
+        count(|outcome| matches!(outcome, JunitOutcome::Skipped { .. })),
+        seconds(time_ms),
+    );
+    let suite = escape_xml(suite);
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(xml, "<testsuites name=\"{CHECK_NAME}\" {counts}>");
+    let _ = writeln!(xml, "  <testsuite name=\"{suite}\" {counts}>");
+    write_junit_properties(&mut xml, "    ", &properties);
+    for case in &cases {
+        let _ = write!(
+            xml,
+            "    <testcase name=\"{}\" classname=\"{suite}\" time=\"{}\"",
+            escape_xml(&case.name),
+            seconds(case.time_ms)
+        );
+        if case.properties.is_empty() && matches!(case.outcome, JunitOutcome::Passed) {
+            xml.push_str("/>\n");
+            continue;
+        }
+        xml.push_str(">\n");
+        write_junit_properties(&mut xml, "      ", &case.properties);
+        match &case.outcome {
+            JunitOutcome::Passed => {}
+            JunitOutcome::Failure {
+                kind,
+                message,
+                body,
+            } => junit_detail(&mut xml, "failure", kind, message, body),
+            JunitOutcome::Error {
+                kind,
+                message,
+                body,
+            } => junit_detail(&mut xml, "error", kind, message, body),
+            JunitOutcome::Skipped { message } => {
+                let _ = writeln!(xml, "      <skipped message=\"{}\"/>", escape_xml(message));
+            }
+        }
+        xml.push_str("    </testcase>\n");
+    }
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+fn junit_cases(run: &RunResult) -> Vec<JunitCase> {
+    let mut cases: Vec<JunitCase> = run
+        .steps
+        .iter()
+        .flatten()
+        .map(|step| JunitCase {
+            name: step.name.clone(),
+            time_ms: step.ended_at_ms.saturating_sub(step.started_at_ms),
+            outcome: step_outcome(run, step),
+            properties: step
+                .metadata
+                .iter()
+                .map(|(key, value)| (key.clone(), value.to_string()))
+                .collect(),
+        })
+        .collect();
+    let step_failed = cases.iter().any(|case| {
+        matches!(
+            case.outcome,
+            JunitOutcome::Failure { .. } | JunitOutcome::Error { .. }
+        )
+    });
+    if run.steps.is_none() || (run.status != RunStatus::Passed && !step_failed) {
+        let mut lines: Vec<String> = run.error.iter().map(error_line).collect();
+        lines.extend(classification_line(run));
+        let kind = run.error.as_ref().map_or_else(
+            || status_word(&run.status).to_string(),
+            |error| error.code.clone(),
+        );
+        let message = run.error.as_ref().map_or_else(
+            || format!("run {}", status_word(&run.status)),
+            |error| error.message.clone(),
+        );
+        let body = lines.join("\n");
+        cases.push(JunitCase {
+            name: "run".to_string(),
+            time_ms: run.ended_at_ms.saturating_sub(run.started_at_ms),
+            outcome: match run.status {
+                RunStatus::Passed => JunitOutcome::Passed,
+                RunStatus::Failed => JunitOutcome::Failure {
+                    kind,
+                    message,
+                    body,
+                },
+                RunStatus::Errored => JunitOutcome::Error {
+                    kind,
+                    message,
+                    body,
+                },
+                RunStatus::Canceled => JunitOutcome::Skipped { message },
+            },
+            properties: Vec::new(),
+        });
+    }
+    cases
+}
+
+fn step_outcome(run: &RunResult, step: &StepResult) -> JunitOutcome {
+    let kind = step
+        .error
+        .as_ref()
+        .map_or_else(|| "assertion".to_string(), |error| error.code.clone());
+    let message = step_details(step);
+    match step.status {
+        StepStatus::Passed => JunitOutcome::Passed,
+        StepStatus::Failed => JunitOutcome::Failure {
+            kind,
+            message,
+            body: step_message(run, step),
+        },
+        StepStatus::Errored => JunitOutcome::Error {
+            kind,
+            message,
+            body: step_message(run, step),
+        },
+        StepStatus::Skipped => JunitOutcome::Skipped {
+            message: format!("run {} before this step", status_word(&run.status)),
+        },
+    }
+}
+
+fn junit_properties(run: &RunResult) -> Vec<(String, String)> {
+    let mut properties = vec![
+        ("run_id".to_string(), run.run_id.to_string()),
+        ("status".to_string(), status_word(&run.status).to_string()),
+        ("command".to_string(), run.command.clone()),
+    ];
+    properties.extend(run.tags.iter().map(|tag| ("tag".to_string(), tag.clone())));
+    properties
+}
+
+/// Write a `<properties>` element at `indent`, if there are any.
+fn write_junit_properties(xml: &mut String, indent: &str, properties: &[(String, String)]) {
+    if properties.is_empty() {
+        return;
+    }
+    let _ = writeln!(xml, "{indent}<properties>");
+    for (name, value) in properties {
+        let _ = writeln!(
+            xml,
+            "{indent}  <property name=\"{}\" value=\"{}\"/>",
+            escape_xml(name),
+            escape_xml(value)
+        );
+    }
+    let _ = writeln!(xml, "{indent}</properties>");
+}
+
+/// Write a `<failure>` or `<error>` element inside a test case.
+fn junit_detail(xml: &mut String, element: &str, kind: &str, message: &str, body: &str) {
+    let _ = writeln!(
+        xml,
+        "      <{element} message=\"{}\" type=\"{}\">{}</{element}>",
+        escape_xml(message),
+        escape_xml(kind),
+        escape_xml(body)
+    );
+}
+
+/// Milliseconds as seconds with three decimals.
+fn seconds(ms: u64) -> String {
+    format!("{}.{:03}", ms / 1000, ms % 1000)
+}
+
+/// Escape text for an XML attribute or element, replacing characters XML
+/// 1.0 cannot carry (control characters from terminal output).
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => escaped.push(char::REPLACEMENT_CHARACTER),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
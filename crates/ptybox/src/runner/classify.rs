@@ -0,0 +1,174 @@
+//! Failure classification for finished runs.
+//!
+//! [`classify_failure`] maps a run's error code, exit status, and failed
+//! assertions to a [`FailureCategory`], so an agent can decide whether to
+//! retry, fix the scenario or policy, or report a bug in the application.
+
+use super::ErrorCode;
+use crate::model::{
+    ErrorInfo, ExitStatus, FailureCategory, FailureClassification, FailureConfidence, RunResult,
+    RunStatus, StepResult, StepStatus,
+};
+use serde_json::Value;
+
+/// Context keys naming the budget an `E_TIMEOUT` error ran out of.
+const BUDGET_KEYS: [&str; 5] = [
+    "max_runtime_ms",
+    "max_output_bytes",
+    "max_snapshot_bytes",
+    "max_steps",
+    "write_quota",
+];
+
+/// Classify why `run` failed.
+///
+/// Returns `None` for passed and canceled runs. A crash signal from the
+/// child outranks the error code, since a crashed application usually
+/// surfaces first as a timed-out wait or a failed assertion.
+#[must_use]
+pub fn classify_failure(run: &RunResult) -> Option<FailureClassification> {
+    if matches!(run.status, RunStatus::Passed | RunStatus::Canceled) {
+        return None;
+    }
+    if let Some(signal) = run.exit_status.as_ref().and_then(crash_signal) {
+        return Some(classification(
+            FailureCategory::AppCrash,
+            FailureConfidence::High,
+            format!("child died from signal {signal}"),
+        ));
+    }
+    let error = run
+        .error
+        .as_ref()
+        .or_else(|| failed_steps(run).find_map(|step| step.error.as_ref()));
+    Some(match error {
+        Some(error) => classify_error(error, run),
+        None => classify_without_error(run),
+    })
+}
+
+fn classify_error(error: &ErrorInfo, run: &RunResult) -> FailureClassification {
+    use FailureCategory::{AppCrash, AssertionRegression, Budget, InfraFlake, PolicyMisconfig};
+    use FailureConfidence::{High, Low, Medium};
+
+    let reason = format!("{}: {}", error.code, error.message);
+    let Some(code) = ErrorCode::parse(&error.code) else {
+        return classification(InfraFlake, Low, reason);
+    };
+    match code {
+        ErrorCode::PolicyDenied | ErrorCode::SandboxUnavailable | ErrorCode::CliInvalidArg => {
+            classification(PolicyMisconfig, High, reason)
+        }
+        ErrorCode::Protocol | ErrorCode::ProtocolVersionMismatch => {
+            classification(PolicyMisconfig, Medium, reason)
+        }
+        ErrorCode::Timeout => match error.context.as_ref().and_then(budget_key) {
+            Some(key) => classification(Budget, High, format!("{reason} ({key})")),
+            None => classification(AssertionRegression, Medium, reason),
+        },
+        ErrorCode::AssertionFailed => classification(AssertionRegression, High, reason),
+        ErrorCode::ReplayMismatch => classification(AssertionRegression, Medium, reason),
+        ErrorCode::ProcessExit => {
+            let confidence = if run.exit_status.as_ref().is_some_and(unexpected_exit) {
+                High
+            } else {
+                Medium
+            };
+            classification(AppCrash, confidence, reason)
+        }
+        ErrorCode::Io | ErrorCode::StaleObservation => classification(InfraFlake, Medium, reason),
+        ErrorCode::TerminalParse | ErrorCode::Internal => classification(InfraFlake, Low, reason),
+    }
+}
+
+/// Classify a failed run that recorded no error.
+fn classify_without_error(run: &RunResult) -> FailureClassification {
+    if let Some(step) = failed_steps(run).find(|step| step.assertions.iter().any(|a| !a.passed)) {
+        let failed = step.assertions.iter().filter(|a| !a.passed).count();
+        return classification(
+            FailureCategory::AssertionRegression,
+            FailureConfidence::High,
+            format!("{failed} assertion(s) failed in step '{}'", step.name),
+        );
+    }
+    if let Some(status) = run
+        .exit_status
+        .as_ref()
+        .filter(|status| unexpected_exit(status))
+    {
+        let code = status
+            .exit_code
+            .map_or_else(|| "unknown".to_string(), |code| code.to_string());
+        return classification(
+            FailureCategory::AppCrash,
+            FailureConfidence::Medium,
+            format!("child exited with code {code}"),
+        );
+    }
+    classification(
+        FailureCategory::InfraFlake,
+        FailureConfidence::Low,
+        "run failed without an error or failed assertion".to_string(),
+    )
+}
+
+fn classification(
+    category: FailureCategory,
+    confidence: FailureConfidence,
+    reason: String,
+) -> FailureClassification {
+    FailureClassification {
+        category,
+        confidence,
+        reason,
+    }
+}
+
+fn failed_steps(run: &RunResult) -> impl Iterator<Item = &StepResult> {
+    run.steps
+        .iter()
+        .flatten()
+        .filter(|step| matches!(step.status, StepStatus::Failed | StepStatus::Errored))
+}
+
+/// The child exited on its own with a failure status.
+fn unexpected_exit(status: &ExitStatus) -> bool {
+    !status.success && !status.terminated_by_harness
+}
+
+/// Signal the child died from, when ptybox did not send it and it marks a crash.
+fn crash_signal(status: &ExitStatus) -> Option<i32> {
+    if status.terminated_by_harness {
+        return None;
+    }
+    status.signal.filter(|signal| is_crash_signal(*signal))
+}
+
+#[cfg(unix)]
+fn is_crash_signal(signal: i32) -> bool {
+    use nix::sys::signal::Signal;
+    [
+        Signal::SIGSEGV,
+        Signal::SIGABRT,
+        Signal::SIGBUS,
+        Signal::SIGILL,
+        Signal::SIGFPE,
+        Signal::SIGSYS,
+    ]
+    .iter()
+    .any(|crash| *crash as i32 == signal)
+}
+
+#[cfg(not(unix))]
+fn is_crash_signal(_signal: i32) -> bool {
+    false
+}
+
+/// First budget key in an error context, searching nested `details`.
+fn budget_key(context: &Value) -> Option<&'static str> {
+    let object = context.as_object()?;
+    BUDGET_KEYS
+        .into_iter()
+        .find(|key| object.contains_key(*key))
+        .or_else(|| object.get("details").and_then(budget_key))
+}
@@ -0,0 +1,238 @@
+//! Generated test data ([`Scenario::fixtures`](crate::model::Scenario::fixtures)).
+//!
+//! Before spawning the command, the runner writes each generator's file or
+//! directory tree. Content depends only on the generator (zeros, or a
+//! `splitmix64` stream from its seed), so a scenario always produces the same
+//! bytes; the [`FixtureRecord`]s in `run.json` carry a checksum to verify it.
+//!
+//! Paths must be absolute and inside `policy.fs.allowed_write`. One run may
+//! write at most [`MAX_FIXTURE_BYTES`] and create at most
+//! [`MAX_FIXTURE_ENTRIES`] files and directories.
+
+use crate::fuzz::Rng;
+use crate::model::policy::FsPolicy;
+use crate::model::{FixtureFill, FixtureGenerator, FixtureKind, FixtureRecord, TreeFixture};
+use crate::policy::path_allowed;
+use crate::runner::{ErrorCode, RunnerError, RunnerResult};
+use crate::util::{fnv1a_hash_incremental, FnvHashState};
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Most bytes the fixtures of one run may write.
+pub const MAX_FIXTURE_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Most files and directories the fixtures of one run may create.
+pub const MAX_FIXTURE_ENTRIES: u64 = 100_000;
+
+/// Size of the buffer generated content is written through.
+const CHUNK_BYTES: u64 = 64 * 1024;
+
+/// Check that every fixture path is writable under `fs` and the fixtures fit
+/// the run limits.
+///
+/// # Errors
+/// - `E_POLICY_DENIED` for a relative path or one outside `fs.allowed_write`
+/// - `E_PROTOCOL` when the fixtures exceed [`MAX_FIXTURE_BYTES`] or
+///   [`MAX_FIXTURE_ENTRIES`]
+pub fn validate_fixtures(fixtures: &[FixtureGenerator], fs: &FsPolicy) -> RunnerResult<()> {
+    let mut bytes = 0u64;
+    let mut entries = 0u64;
+    for (index, fixture) in fixtures.iter().enumerate() {
+        if !Path::new(&fixture.path).is_absolute()
+            || !path_allowed(&fixture.path, &[], &fs.allowed_write)
+        {
+            return Err(RunnerError::with_context(
+                ErrorCode::PolicyDenied,
+                "fixture path is not within allowed_write",
+                serde_json::json!({
+                    "field": format!("fixtures[{index}].path"),
+                    "path": fixture.path,
+                    "allowed_write": fs.allowed_write,
+                    "fix": "Use an absolute path inside policy.fs.allowed_write",
+                }),
+            ));
+        }
+        let (fixture_entries, fixture_bytes) = footprint(&fixture.kind);
+        entries = entries.saturating_add(fixture_entries);
+        bytes = bytes.saturating_add(fixture_bytes);
+    }
+    if bytes > MAX_FIXTURE_BYTES || entries > MAX_FIXTURE_ENTRIES {
+        return Err(RunnerError::with_context(
+            ErrorCode::Protocol,
+            "fixtures exceed the generation limits",
+            serde_json::json!({
+                "bytes": bytes,
+                "entries": entries,
+                "max_bytes": MAX_FIXTURE_BYTES,
+                "max_entries": MAX_FIXTURE_ENTRIES,
+            }),
+        ));
+    }
+    Ok(())
+}
+
+/// Validate and write `fixtures` in order.
+///
+/// # Errors
+/// The errors of [`validate_fixtures`], or `E_IO` when a file or directory
+/// cannot be written.
+pub fn materialize_fixtures(
+    fixtures: &[FixtureGenerator],
+    fs: &FsPolicy,
+) -> RunnerResult<Vec<FixtureRecord>> {
+    validate_fixtures(fixtures, fs)?;
+    fixtures.iter().map(materialize).collect()
+}
+
+/// Files and directories a fixture creates, and the bytes it writes; both
+/// saturate once past the limits.
+fn footprint(kind: &FixtureKind) -> (u64, u64) {
+    match kind {
+        FixtureKind::File(file) => (1, file.size),
+        FixtureKind::Tree(tree) => {
+            let dirs = tree_dirs(tree);
+            let files = dirs.saturating_mul(u64::from(tree.fanout));
+            (
+                dirs.saturating_add(files),
+                files.saturating_mul(tree.file_size),
+            )
+        }
+    }
+}
+
+fn tree_dirs(tree: &TreeFixture) -> u64 {
+    let mut dirs = 0u64;
+    let mut level = 1u64;
+    for _ in 0..=tree.depth {
+        dirs = dirs.saturating_add(level);
+        level = level.saturating_mul(u64::from(tree.fanout));
+        if level == 0 || dirs > MAX_FIXTURE_ENTRIES {
+            break;
+        }
+    }
+    dirs
+}
+
+fn materialize(fixture: &FixtureGenerator) -> RunnerResult<FixtureRecord> {
+    let root = Path::new(&fixture.path);
+    let mut writer = FixtureWriter {
+        root,
+        files: 0,
+        dirs: 0,
+        bytes: 0,
+        checksum: FnvHashState::new(),
+        buffer: Vec::new(),
+    };
+    match &fixture.kind {
+        FixtureKind::File(file) => {
+            if let Some(parent) = root.parent() {
+                fs::create_dir_all(parent).map_err(|err| io_error("create", parent, err))?;
+            }
+            writer.write_file(root, file.size, &mut Fill::new(file.fill))?;
+        }
+        FixtureKind::Tree(tree) => writer.write_tree(tree)?,
+    }
+    Ok(FixtureRecord {
+        generator: fixture.clone(),
+        files: writer.files,
+        dirs: writer.dirs,
+        bytes: writer.bytes,
+        checksum: format!("{:016x}", writer.checksum.hash),
+    })
+}
+
+struct FixtureWriter<'a> {
+    root: &'a Path,
+    files: u64,
+    dirs: u64,
+    bytes: u64,
+    checksum: FnvHashState,
+    buffer: Vec<u8>,
+}
+
+impl FixtureWriter<'_> {
+    /// Create the tree depth-first: each directory's files, then its
+    /// subdirectories in order.
+    fn write_tree(&mut self, tree: &TreeFixture) -> RunnerResult<()> {
+        let mut fill = Fill::new(tree.fill);
+        let mut pending = vec![(self.root.to_path_buf(), 0u32)];
+        while let Some((dir, level)) = pending.pop() {
+            fs::create_dir_all(&dir).map_err(|err| io_error("create", &dir, err))?;
+            self.dirs += 1;
+            for index in 0..tree.fanout {
+                self.write_file(
+                    &dir.join(format!("file-{index}")),
+                    tree.file_size,
+                    &mut fill,
+                )?;
+            }
+            if level < tree.depth {
+                pending.extend(
+                    (0..tree.fanout)
+                        .rev()
+                        .map(|index| (dir.join(format!("dir-{index}")), level + 1)),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn write_file(&mut self, path: &Path, size: u64, fill: &mut Fill) -> RunnerResult<()> {
+        let relative = path.strip_prefix(self.root).unwrap_or(path);
+        fnv1a_hash_incremental(&mut self.checksum, relative.to_string_lossy().as_bytes());
+        let file = fs::File::create(path).map_err(|err| io_error("create", path, err))?;
+        let mut out = BufWriter::new(file);
+        let mut remaining = size;
+        while remaining > 0 {
+            let len = remaining.min(CHUNK_BYTES);
+            self.buffer
+                .resize(usize::try_from(len).unwrap_or(usize::MAX), 0);
+            fill.fill(&mut self.buffer);
+            fnv1a_hash_incremental(&mut self.checksum, &self.buffer);
+            out.write_all(&self.buffer)
+                .map_err(|err| io_error("write", path, err))?;
+            remaining -= len;
+        }
+        out.flush().map_err(|err| io_error("write", path, err))?;
+        self.files += 1;
+        self.bytes += size;
+        Ok(())
+    }
+}
+
+/// Source of generated bytes.
+enum Fill {
+    Zeros,
+    Random(Rng),
+}
+
+impl Fill {
+    fn new(fill: FixtureFill) -> Self {
+        match fill {
+            FixtureFill::Zeros => Self::Zeros,
+            FixtureFill::Random(seed) => Self::Random(Rng::new(seed)),
+        }
+    }
+
+    fn fill(&mut self, buffer: &mut [u8]) {
+        match self {
+            Self::Zeros => buffer.fill(0),
+            Self::Random(rng) => {
+                for chunk in buffer.chunks_mut(8) {
+                    for (byte, value) in chunk.iter_mut().zip(rng.next_u64().to_le_bytes()) {
+                        *byte = value;
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn io_error(action: &str, path: &Path, err: std::io::Error) -> RunnerError {
+    RunnerError::with_context(
+        ErrorCode::Io,
+        format!("failed to {action} fixture"),
+        serde_json::json!({ "path": path.display().to_string(), "source": err.to_string() }),
+    )
+}
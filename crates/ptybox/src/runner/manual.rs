@@ -0,0 +1,14 @@
+//! Manual input injected into a running scenario.
+//!
+//! The TUI uses this to forward keystrokes typed while a scenario runs, so a
+//! user can push a flaky step along by hand. Pending input is delivered to
+//! the PTY at the start of every [`Session::observe`](crate::session::Session::observe),
+//! which covers wait polling as well as ordinary steps.
+
+use crate::model::Action;
+
+/// Source of manual `key` and `text` actions for a running scenario.
+pub trait ManualInputSource: Send + Sync {
+    /// Take the actions queued since the previous call, oldest first.
+    fn take_pending(&self) -> Vec<Action>;
+}
@@ -16,7 +16,10 @@
 //!
 //! - [`run_scenario`] — Execute a complete scenario (steps, assertions, artifacts)
 //! - [`run_exec_with_options`] — Run a single command under policy
+//! - [`run_suite`] — Run a directory of scenario files and aggregate their outcomes
 //! - [`compile_safe_regex`] — Compile a regex with `ReDoS` protection
+//! - [`compile_safe_bytes_regex`] — The same for patterns matched against raw bytes
+//! - [`classify_failure`] — Categorize why a run failed
 //!
 //! # Example
 //!
@@ -32,38 +35,67 @@
 //! # }
 //! ```
 
+mod classify;
+pub mod fixtures;
+pub mod manual;
+mod probe;
 pub mod progress;
-
-use crate::artifacts::{ArtifactsWriter, ArtifactsWriterConfig};
-use crate::model::policy::Policy;
+mod reporters;
+pub mod sockets;
+mod suite;
+pub mod tap;
+mod unwind;
+mod vars;
+mod watchdog;
+
+use crate::actions::{action_latency_ms, perform_action};
+use crate::artifacts::{quota, ArtifactsWriter, ArtifactsWriterConfig};
+use crate::model::policy::{Keymap, Policy, PolicyGrant};
 use crate::model::{
-    Action, ActionType, AssertionResult, ExitStatus, NormalizationRecord, RunConfig, RunId,
-    RunResult, RunStatus, Scenario, StepResult, StepStatus, TerminalSize, MAX_REGEX_PATTERN_LEN,
-    NORMALIZATION_VERSION, PROTOCOL_VERSION,
+    Acknowledgement, AcknowledgementReport, ActionType, AssertionResult, CaptureSource,
+    EnforcementReport, ExitStatus, FixtureRecord, HostFingerprint, JournalEvent, KeyHold,
+    NormalizationRecord, Observation, OutputBufferConfig, Provenance, RemoteTarget, ResizeConfig,
+    ResourceUsage, RunConfig, RunId, RunResult, RunStatus, SampleStats, Scenario, ScreenSnapshot,
+    StepResult, StepStatus, TerminalSize, TermiosSettings, MAX_REGEX_PATTERN_LEN,
+    MAX_STEP_METADATA_BYTES, MIN_SAMPLE_INTERVAL_MS, NORMALIZATION_VERSION, PROTOCOL_VERSION,
 };
+use crate::policy::acknowledgements::acknowledgement_report;
+use crate::policy::grants::apply_policy_grants;
+use crate::policy::keymap::resolve_keymap;
 use crate::policy::{
-    validate_artifacts_dir, validate_artifacts_policy, validate_env_policy, validate_fs_policy,
-    validate_network_policy, validate_policy_version, validate_sandbox_mode, validate_write_access,
-    EffectivePolicy,
+    fallback_spawn_policy, validate_artifacts_dir, validate_artifacts_policy, validate_env_overlay,
+    validate_fs_policy, validate_policy, validate_write_access, EffectivePolicy,
 };
 use crate::scenario::load_policy_ref;
-use crate::session::{Session, SessionConfig};
+use crate::session::{RemoteConfig, Session, SessionConfig, Termination};
 use crate::util::{
-    build_spawn_command, convert_exit_status, elapsed_ms, pause_until, resolve_artifacts_config,
-    snapshot_bytes, SandboxCleanupGuard,
+    bind_policy_sockets, build_spawn_command, convert_exit_status, elapsed_ms,
+    resolve_artifacts_config, sha256_hex, snapshot_bytes, SandboxCleanupGuard,
 };
+pub use classify::classify_failure;
+pub use manual::ManualInputSource;
 use miette::Diagnostic;
 pub use progress::{NoopProgress, ProgressCallback, ProgressEvent};
-use serde::Deserialize;
+pub use reporters::Reporter;
 use serde_json::Value;
+pub use sockets::SocketHandler;
+use std::collections::BTreeMap;
 use std::fmt;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
+pub use suite::{run_suite, select_suite, SuiteConfig, SUITE_RESULT_FILE};
+pub use tap::ObservationTap;
+pub(crate) use unwind::catch_panic;
+use watchdog::Watchdog;
 
 /// Maximum compiled regex size (1 MB) to prevent catastrophic backtracking.
 const MAX_REGEX_SIZE: usize = 1_000_000;
 
+/// Longest a paced step waits for its start offset.
+pub const MAX_PACED_DELAY_MS: u64 = 10_000;
+
 /// Compile a regex pattern with length and DFA size limits to prevent `ReDoS` attacks.
 ///
 /// Both the source pattern length and the compiled automaton size are bounded.
@@ -88,6 +120,35 @@ pub fn compile_safe_regex(pattern: &str) -> Result<regex::Regex, RunnerError> {
         .map_err(|err| RunnerError::protocol("E_PROTOCOL", format!("invalid regex: {err}"), None))
 }
 
+/// Compile a regex pattern for matching raw bytes, with the same limits as
+/// [`compile_safe_regex`].
+///
+/// # Errors
+/// Returns `E_PROTOCOL` if pattern exceeds `MAX_REGEX_PATTERN_LEN`, the compiled
+/// automaton exceeds `MAX_REGEX_SIZE`, or the pattern is invalid.
+pub fn compile_safe_bytes_regex(pattern: &str) -> Result<regex::bytes::Regex, RunnerError> {
+    if pattern.len() > MAX_REGEX_PATTERN_LEN {
+        return Err(RunnerError::with_context(
+            ErrorCode::Protocol,
+            format!("regex pattern exceeds maximum length of {MAX_REGEX_PATTERN_LEN} characters"),
+            serde_json::json!({
+                "pattern_length": pattern.len(),
+                "max_length": MAX_REGEX_PATTERN_LEN
+            }),
+        ));
+    }
+    regex::bytes::RegexBuilder::new(pattern)
+        .size_limit(MAX_REGEX_SIZE)
+        .build()
+        .map_err(|err| {
+            RunnerError::with_context(
+                ErrorCode::Protocol,
+                format!("invalid regex: {err}"),
+                serde_json::json!({ "pattern": pattern }),
+            )
+        })
+}
+
 /// Result type alias for runner operations.
 pub type RunnerResult<T> = Result<T, RunnerError>;
 
@@ -118,11 +179,30 @@ pub enum ErrorCode {
     ReplayMismatch,
     /// Invalid CLI argument (exit 12).
     CliInvalidArg,
+    /// Driver action precondition failed on a changed screen (exit 13).
+    StaleObservation,
     /// Internal error (exit 1).
     Internal,
 }
 
 impl ErrorCode {
+    /// Every error code, in exit-code order.
+    pub const ALL: [Self; 13] = [
+        Self::Internal,
+        Self::PolicyDenied,
+        Self::SandboxUnavailable,
+        Self::Timeout,
+        Self::AssertionFailed,
+        Self::ProcessExit,
+        Self::TerminalParse,
+        Self::ProtocolVersionMismatch,
+        Self::Protocol,
+        Self::Io,
+        Self::ReplayMismatch,
+        Self::CliInvalidArg,
+        Self::StaleObservation,
+    ];
+
     /// Get the string representation of the error code.
     #[must_use]
     pub fn as_str(&self) -> &'static str {
@@ -138,6 +218,7 @@ impl ErrorCode {
             Self::Io => "E_IO",
             Self::ReplayMismatch => "E_REPLAY_MISMATCH",
             Self::CliInvalidArg => "E_CLI_INVALID_ARG",
+            Self::StaleObservation => "E_STALE_OBSERVATION",
             Self::Internal => "E_INTERNAL",
         }
     }
@@ -157,6 +238,7 @@ impl ErrorCode {
             Self::Io => 10,
             Self::ReplayMismatch => 11,
             Self::CliInvalidArg => 12,
+            Self::StaleObservation => 13,
             Self::Internal => 1,
         }
     }
@@ -176,6 +258,7 @@ impl ErrorCode {
             "E_IO" => Some(Self::Io),
             "E_REPLAY_MISMATCH" => Some(Self::ReplayMismatch),
             "E_CLI_INVALID_ARG" => Some(Self::CliInvalidArg),
+            "E_STALE_OBSERVATION" => Some(Self::StaleObservation),
             "E_INTERNAL" => Some(Self::Internal),
             _ => None,
         }
@@ -467,14 +550,93 @@ pub struct RunnerOptions {
     /// step completed, run finished). Used by the CLI for verbose output
     /// and TUI mode visualization.
     pub progress: Option<Arc<dyn ProgressCallback>>,
+    /// Tags recorded on the run, in addition to the scenario's `metadata.tags`.
+    pub tags: Vec<String>,
+    /// Directory holding the run index (`runs.jsonl`). Each run with artifacts
+    /// appends an entry there when the policy allows writing to it. When the
+    /// directory also holds a quota ledger (`quota.json`), the run is
+    /// admitted into its global byte quota (see [`crate::artifacts::set_artifacts_quota`]).
+    pub run_index: Option<PathBuf>,
+    /// Manual key and text input forwarded to the scenario's PTY while it
+    /// runs (TUI intervention). Not used by exec runs.
+    pub manual_input: Option<Arc<dyn ManualInputSource>>,
+    /// Sees every observation of the run's session (see [`ObservationTap`]).
+    pub observation_tap: Option<Arc<dyn ObservationTap>>,
+    /// Receives the listening ends of the sockets granted by
+    /// `policy.sockets`. Required when the policy grants any.
+    pub socket_handler: Option<Arc<dyn SocketHandler>>,
+    /// Earliest start of each step, in milliseconds from run start. A step
+    /// reached sooner waits, at most [`MAX_PACED_DELAY_MS`] and never past
+    /// the runtime budget. Set by replay under `determinism.paced_input`.
+    pub step_offsets_ms: Vec<u64>,
+    /// Cancels the run when set to `true`. Checked before each step: the
+    /// remaining steps are skipped, the child is stopped as on any failure,
+    /// and the run ends `canceled`. The step in
+    /// flight when the flag is set runs to completion.
+    pub cancel: Option<Arc<AtomicBool>>,
+    /// Git commit of the code under test, recorded in the run's provenance.
+    /// Must be 7 to 64 hex characters.
+    pub git_sha: Option<String>,
+    /// Acknowledgements given outside the policy (CLI flags or environment
+    /// variables), recorded in `acknowledgements.json` in place of the
+    /// policy field (see [`acknowledgement_report`]).
+    pub ack_overrides: Vec<Acknowledgement>,
+    /// Keymap used instead of the one `policy.input.keymap` selects. Replay
+    /// sets it from the baseline's `run.json`, so keys are translated as
+    /// they were when the baseline was recorded.
+    pub keymap: Option<Keymap>,
+    /// Reports written when the run finishes, passed or not (see
+    /// [`Reporter`]).
+    pub reporters: Vec<Reporter>,
+    /// Collect Seatbelt denials into `violations.json` (requires artifacts).
+    #[cfg(feature = "sandbox-audit")]
+    pub sandbox_audit: bool,
+    /// Export an OpenTelemetry trace of the run after it finishes.
+    #[cfg(feature = "otel")]
+    pub otel: Option<crate::otel::OtelConfig>,
+    /// Forward the run's transcript and events to syslog, journald, or a
+    /// UDP collector while it runs.
+    #[cfg(feature = "log-forward")]
+    pub log_forward: Option<crate::log_forward::LogForwardConfig>,
+    /// Feed the run's PTY output through a second emulator backend and
+    /// write where the screens diverge to `emulator-diff.json` (requires
+    /// artifacts).
+    #[cfg(feature = "emulator-diff")]
+    pub emulator_diff: bool,
 }
 
 impl std::fmt::Debug for RunnerOptions {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("RunnerOptions")
+        let mut debug = f.debug_struct("RunnerOptions");
+        debug
             .field("artifacts", &self.artifacts)
             .field("progress", &self.progress.as_ref().map(|_| "..."))
-            .finish()
+            .field("tags", &self.tags)
+            .field("run_index", &self.run_index)
+            .field("manual_input", &self.manual_input.as_ref().map(|_| "..."))
+            .field(
+                "observation_tap",
+                &self.observation_tap.as_ref().map(|_| "..."),
+            )
+            .field(
+                "socket_handler",
+                &self.socket_handler.as_ref().map(|_| "..."),
+            )
+            .field("step_offsets_ms", &self.step_offsets_ms)
+            .field("cancel", &self.cancel)
+            .field("git_sha", &self.git_sha)
+            .field("ack_overrides", &self.ack_overrides)
+            .field("keymap", &self.keymap)
+            .field("reporters", &self.reporters);
+        #[cfg(feature = "sandbox-audit")]
+        debug.field("sandbox_audit", &self.sandbox_audit);
+        #[cfg(feature = "otel")]
+        debug.field("otel", &self.otel);
+        #[cfg(feature = "log-forward")]
+        debug.field("log_forward", &self.log_forward);
+        #[cfg(feature = "emulator-diff")]
+        debug.field("emulator_diff", &self.emulator_diff);
+        debug.finish()
     }
 }
 
@@ -502,6 +664,7 @@ fn create_skipped_step(
 ) -> StepResult {
     StepResult {
         step_id: step.id,
+        step_key: Some(step.content_key()),
         name: step.name.clone(),
         status: StepStatus::Skipped,
         attempts: 0,
@@ -510,6 +673,33 @@ fn create_skipped_step(
         action: step.action.clone(),
         assertions: Vec::new(),
         error: error.map(|e| e.to_error_info()),
+        wait: None,
+        latency_ms: None,
+        samples: None,
+        capture: None,
+        env_overlay: BTreeMap::new(),
+        metadata: BTreeMap::new(),
+        vars: BTreeMap::new(),
+        resolved_vars: BTreeMap::new(),
+    }
+}
+
+/// The result of a step that errored without completing (its execution
+/// panicked, or a variable it uses could not be substituted); the error
+/// fails the run.
+fn errored_step(
+    step: &crate::model::Step,
+    started_at_ms: u64,
+    ended_at_ms: u64,
+    error: RunnerError,
+) -> StepExecutionResult {
+    let mut step_result = create_skipped_step(step, started_at_ms, Some(&error));
+    step_result.status = StepStatus::Errored;
+    step_result.attempts = 1;
+    step_result.ended_at_ms = ended_at_ms;
+    StepExecutionResult {
+        step_result,
+        run_error: Some(error),
     }
 }
 
@@ -538,66 +728,71 @@ fn execute_step(
     let mut last_error: Option<RunnerError> = None;
     let mut status = StepStatus::Failed;
     let mut assertion_results = Vec::with_capacity(step.assert.len());
+    let wait_before = session.wait_stats();
+    let mut latency_ms = None;
+    let mut samples = start_step_sampling(session, step, policy)?;
+    let mut captured_screen = None;
+    begin_step_capture(artifacts, step);
 
     for _ in 0..=step.retries {
         attempts += 1;
         effective_policy.validate_action(&step.action)?;
 
-        let observation = match perform_action(
+        latency_ms = None;
+        let action_started = Instant::now();
+        let action_result = perform_action(
             session,
             &step.action,
             Duration::from_millis(step.timeout_ms),
             policy,
-        ) {
-            Ok(obs) => obs,
-            Err(err) => {
-                last_error = Some(if err.code == ErrorCode::Timeout {
-                    with_step_timeout_context(err, step)
-                } else {
-                    err
-                });
-                status = StepStatus::Errored;
-                continue;
+        );
+        let final_screen = action_result.as_ref().ok().map(|obs| &obs.screen);
+        write_step_samples(session, artifacts, final_screen, policy, samples.as_mut())?;
+        let outcome = match action_result {
+            Ok(observation) => {
+                latency_ms = action_latency_ms(session, &step.action, elapsed_ms(&action_started))?;
+                *output_bytes += observation.output_bytes();
+                captured_screen = step.capture.as_ref().map(|_| observation.screen.clone());
+                check_attempt(
+                    session,
+                    step,
+                    policy,
+                    artifacts,
+                    output_bytes,
+                    &observation,
+                    latency_ms,
+                    &mut assertion_results,
+                )?
             }
+            Err(err) => failed_attempt(session, artifacts, output_bytes, err, step)?,
         };
-
-        // Check budgets
-        *output_bytes += observation
-            .transcript_delta
-            .as_ref()
-            .map(|s| s.len() as u64)
-            .unwrap_or(0);
-
-        if let Some(budget_error) = check_step_budgets(&observation, *output_bytes, policy, step)? {
-            last_error = Some(budget_error);
-            status = StepStatus::Errored;
-            break;
-        }
-
-        // Write artifacts
-        if let Some(writer) = artifacts.as_mut() {
-            writer.write_snapshot(&observation.screen)?;
-            if let Some(delta) = &observation.transcript_delta {
-                writer.write_transcript(delta)?;
+        match outcome {
+            AttemptOutcome::Passed => {
+                status = StepStatus::Passed;
+                last_error = None;
+                break;
+            }
+            AttemptOutcome::AssertionsFailed => {
+                last_error = Some(RunnerError::assertion_failed(
+                    "one or more assertions failed",
+                    None,
+                ));
+            }
+            AttemptOutcome::Errored(err) => {
+                last_error = Some(err);
+                status = StepStatus::Errored;
+            }
+            AttemptOutcome::Stopped(err, stop_status) => {
+                last_error = Some(err);
+                status = stop_status;
+                break;
             }
-            writer.write_observation(&observation)?;
-        }
-
-        // Evaluate assertions (with exit status probing for exit_code assertions)
-        let assertions_passed =
-            evaluate_step_assertions(session, &observation, &step.assert, &mut assertion_results)?;
-
-        if assertions_passed {
-            status = StepStatus::Passed;
-            last_error = None;
-            break;
         }
-        last_error = Some(RunnerError::assertion_failed(
-            "one or more assertions failed",
-            None,
-        ));
     }
 
+    finish_step_sampling(session, artifacts, policy, samples.as_mut())?;
+    let capture = write_step_capture(artifacts, step, captured_screen.as_ref())?;
+
     let step_ended_ms = elapsed_ms(run_started);
     let error_info = last_error.as_ref().map(|e| e.to_error_info());
     let run_error = if status == StepStatus::Passed {
@@ -609,6 +804,7 @@ fn execute_step(
     Ok(StepExecutionResult {
         step_result: StepResult {
             step_id: step.id,
+            step_key: Some(step.content_key()),
             name: step.name.clone(),
             status,
             attempts,
@@ -617,19 +813,300 @@ fn execute_step(
             action: step.action.clone(),
             assertions: assertion_results,
             error: error_info,
+            wait: session.wait_stats().since(wait_before),
+            latency_ms,
+            samples,
+            capture,
+            env_overlay: step.env_overlay.clone(),
+            metadata: step.metadata.clone(),
+            vars: BTreeMap::new(),
+            resolved_vars: BTreeMap::new(),
         },
         run_error,
     })
 }
 
+/// How one attempt of a step ended.
+enum AttemptOutcome {
+    /// Every assertion passed, including held stability windows.
+    Passed,
+    /// An assertion failed; the step may be retried.
+    AssertionsFailed,
+    /// The action failed with this error; the step may be retried.
+    Errored(RunnerError),
+    /// A budget or watcher stopped the step with this error and status.
+    Stopped(RunnerError, StepStatus),
+}
+
+/// Check budgets, write the attempt's artifacts, and evaluate the step's
+/// assertions against `observation`, holding any stability windows.
+#[allow(clippy::too_many_arguments)]
+fn check_attempt(
+    session: &mut Session,
+    step: &crate::model::Step,
+    policy: &Policy,
+    artifacts: &mut Option<ArtifactsWriter>,
+    output_bytes: &mut u64,
+    observation: &crate::model::Observation,
+    latency_ms: Option<u64>,
+    assertion_results: &mut Vec<AssertionResult>,
+) -> RunnerResult<AttemptOutcome> {
+    if let Some(budget_error) = check_step_budgets(observation, *output_bytes, policy, step)? {
+        return Ok(AttemptOutcome::Stopped(budget_error, StepStatus::Errored));
+    }
+
+    if let Some(writer) = artifacts.as_mut() {
+        write_step_artifacts(
+            writer,
+            step,
+            observation,
+            session.take_key_hold(),
+            session.take_termios(),
+        )?;
+    }
+
+    // Evaluate assertions (with exit status probing for exit_code assertions)
+    let assertions_passed = evaluate_step_assertions(
+        session,
+        observation,
+        &step.assert,
+        latency_ms,
+        assertion_results,
+    )?;
+    if !assertions_passed {
+        return Ok(AttemptOutcome::AssertionsFailed);
+    }
+    let held = hold_stability_windows(
+        session,
+        step,
+        policy,
+        artifacts,
+        output_bytes,
+        assertion_results,
+    )?;
+    Ok(match held {
+        Ok(true) => AttemptOutcome::Passed,
+        Ok(false) => AttemptOutcome::AssertionsFailed,
+        Err((err, status)) => AttemptOutcome::Stopped(err, status),
+    })
+}
+
+/// Classify an attempt whose action failed with `err`.
+fn failed_attempt(
+    session: &mut Session,
+    artifacts: &mut Option<ArtifactsWriter>,
+    output_bytes: &mut u64,
+    err: RunnerError,
+    step: &crate::model::Step,
+) -> RunnerResult<AttemptOutcome> {
+    Ok(
+        match attempt_failure(session, artifacts, output_bytes, err, step)? {
+            AttemptFailure::Stop(err, status) => AttemptOutcome::Stopped(err, status),
+            AttemptFailure::Other(err) if err.code == ErrorCode::Timeout => {
+                AttemptOutcome::Errored(with_step_timeout_context(err, step))
+            }
+            AttemptFailure::Other(err) => AttemptOutcome::Errored(err),
+        },
+    )
+}
+
+/// Start collecting transcript output when the step has a `capture`.
+#[allow(clippy::ref_option)]
+fn begin_step_capture(artifacts: &mut Option<ArtifactsWriter>, step: &crate::model::Step) {
+    if let (Some(_), Some(writer)) = (&step.capture, artifacts.as_mut()) {
+        writer.begin_capture();
+    }
+}
+
+/// Write the step's `capture`, if any, and return its artifact path.
+///
+/// A transcript capture holds the output read across all attempts; a screen
+/// capture holds the last observed screen, one line per row.
+fn write_step_capture(
+    artifacts: &mut Option<ArtifactsWriter>,
+    step: &crate::model::Step,
+    screen: Option<&ScreenSnapshot>,
+) -> RunnerResult<Option<String>> {
+    let (Some(capture), Some(writer)) = (&step.capture, artifacts.as_mut()) else {
+        return Ok(None);
+    };
+    let transcript = writer.take_capture();
+    let text = match capture.source {
+        CaptureSource::Transcript => transcript,
+        CaptureSource::Screen => screen
+            .map(|screen| {
+                screen
+                    .lines
+                    .iter()
+                    .map(|line| line.trim_end())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+                    .trim_end()
+                    .to_string()
+            })
+            .unwrap_or_default(),
+    };
+    writer.write_capture(&capture.name, &text).map(Some)
+}
+
+/// Write the per-attempt artifacts for a step: input audit, snapshot, transcript, events.
+fn write_step_artifacts(
+    writer: &mut ArtifactsWriter,
+    step: &crate::model::Step,
+    observation: &crate::model::Observation,
+    key_hold: Option<KeyHold>,
+    termios: Option<TermiosSettings>,
+) -> RunnerResult<()> {
+    if matches!(step.action.action_type, ActionType::Raw) {
+        writer.write_raw_input(&step.name, &step.action)?;
+    }
+    if let Some(hold) = key_hold {
+        writer.write_key_hold(&step.name, hold)?;
+    }
+    if let Some(settings) = termios {
+        writer.write_termios(&step.name, settings)?;
+    }
+    writer.write_snapshot(&observation.screen)?;
+    if let Some(delta) = &observation.transcript_delta {
+        writer.write_transcript(delta)?;
+    }
+    if let Some(delta) = &observation.stderr_delta {
+        writer.write_stderr(delta)?;
+    }
+    writer.write_observation(observation)
+}
+
+/// Arm screen sampling for a step that sets `sample_interval_ms`.
+fn start_step_sampling(
+    session: &mut Session,
+    step: &crate::model::Step,
+    policy: &Policy,
+) -> RunnerResult<Option<SampleStats>> {
+    let Some(interval_ms) = step.sample_interval_ms else {
+        return Ok(None);
+    };
+    session.start_sampling(
+        step.id,
+        Duration::from_millis(interval_ms),
+        policy.budgets.step_samples_cap(),
+    )?;
+    Ok(Some(SampleStats::default()))
+}
+
+/// Write the remaining samples of a step and stop sampling.
+#[allow(clippy::ref_option)]
+fn finish_step_sampling(
+    session: &mut Session,
+    artifacts: &mut Option<ArtifactsWriter>,
+    policy: &Policy,
+    stats: Option<&mut SampleStats>,
+) -> RunnerResult<()> {
+    let Some(stats) = stats else {
+        return Ok(());
+    };
+    write_step_samples(session, artifacts, None, policy, Some(stats))?;
+    stats.dropped += session.stop_sampling();
+    Ok(())
+}
+
+/// Write the screens sampled since the last call as step samples (no-op when
+/// the step does not sample).
+///
+/// A trailing sample identical to `final_screen` is skipped, since that screen
+/// is written as the step snapshot. Samples over `max_snapshot_bytes` are
+/// dropped.
+#[allow(clippy::ref_option)]
+fn write_step_samples(
+    session: &mut Session,
+    artifacts: &mut Option<ArtifactsWriter>,
+    final_screen: Option<&ScreenSnapshot>,
+    policy: &Policy,
+    stats: Option<&mut SampleStats>,
+) -> RunnerResult<()> {
+    let Some(stats) = stats else {
+        return Ok(());
+    };
+    let mut samples = session.take_samples();
+    if let (Some(last), Some(final_screen)) = (samples.last(), final_screen) {
+        if last.screen.screen_hash() == final_screen.screen_hash() {
+            samples.pop();
+        }
+    }
+    for sample in samples {
+        if snapshot_bytes(&sample.screen)? > policy.budgets.max_snapshot_bytes {
+            stats.dropped += 1;
+            continue;
+        }
+        if let Some(writer) = artifacts.as_mut() {
+            writer.write_sample(&sample)?;
+        }
+        stats.captured += 1;
+    }
+    Ok(())
+}
+
+/// How an error from a step attempt ends the step.
+enum AttemptFailure {
+    /// A watcher tripped or a write quota was exceeded: stop without retrying.
+    Stop(RunnerError, StepStatus),
+    /// Any other error.
+    Other(RunnerError),
+}
+
+#[allow(clippy::ref_option)]
+fn attempt_failure(
+    session: &mut Session,
+    artifacts: &mut Option<ArtifactsWriter>,
+    output_bytes: &mut u64,
+    err: RunnerError,
+    step: &crate::model::Step,
+) -> RunnerResult<AttemptFailure> {
+    if record_watcher_trip(session, artifacts, output_bytes)? {
+        return Ok(AttemptFailure::Stop(err, StepStatus::Failed));
+    }
+    if session.write_quota_exceeded() {
+        let err = with_step_timeout_context(err, step);
+        return Ok(AttemptFailure::Stop(err, StepStatus::Errored));
+    }
+    Ok(AttemptFailure::Other(err))
+}
+
+/// If the last observation tripped a scenario watcher, record the triggering
+/// observation in the artifacts and return `true`.
+#[allow(clippy::ref_option)]
+fn record_watcher_trip(
+    session: &mut Session,
+    artifacts: &mut Option<ArtifactsWriter>,
+    output_bytes: &mut u64,
+) -> RunnerResult<bool> {
+    let Some(observation) = session.take_watcher_trip() else {
+        return Ok(false);
+    };
+    *output_bytes += observation.output_bytes();
+    if let Some(writer) = artifacts.as_mut() {
+        writer.write_snapshot(&observation.screen)?;
+        if let Some(delta) = &observation.transcript_delta {
+            writer.write_transcript(delta)?;
+        }
+        if let Some(delta) = &observation.stderr_delta {
+            writer.write_stderr(delta)?;
+        }
+        writer.write_observation(&observation)?;
+    }
+    Ok(true)
+}
+
 /// Evaluate step assertions, probing process exit status when needed.
 fn evaluate_step_assertions(
     session: &mut Session,
     observation: &crate::model::Observation,
     assertions: &[crate::model::scenario::Assertion],
+    latency_ms: Option<u64>,
     results: &mut Vec<AssertionResult>,
 ) -> RunnerResult<bool> {
-    let has_exit_code_assertion = assertions.iter().any(|a| a.assertion_type == "exit_code");
+    let has_exit_code_assertion = assertions
+        .iter()
+        .any(|a| matches!(a.assertion_type.as_str(), "exit_code" | "process_exited"));
     let exit_status = if has_exit_code_assertion {
         session
             .wait_for_exit(Duration::from_millis(0))?
@@ -641,10 +1118,15 @@ fn evaluate_step_assertions(
     results.clear();
     let mut all_passed = true;
     for assertion in assertions {
-        let (passed, message, details) = crate::assertions::evaluate_with_exit_status(
+        let (passed, message, details) = crate::assertions::evaluate_in_context(
             observation,
             assertion,
-            exit_status.as_ref(),
+            &crate::assertions::AssertionContext {
+                exit_status: exit_status.as_ref(),
+                stderr: session.stderr(),
+                latency_ms,
+                raw_output: Some(session.raw_output()),
+            },
         );
         if !passed {
             all_passed = false;
@@ -654,11 +1136,142 @@ fn evaluate_step_assertions(
             passed,
             message,
             details,
+            inherited: assertion.inherited,
         });
     }
     Ok(all_passed)
 }
 
+/// Watch the step's stability windows, turning an error while watching
+/// (an exceeded budget, a watcher trip) into the error and status the step
+/// ends with.
+fn hold_stability_windows(
+    session: &mut Session,
+    step: &crate::model::Step,
+    policy: &Policy,
+    artifacts: &mut Option<ArtifactsWriter>,
+    output_bytes: &mut u64,
+    results: &mut [AssertionResult],
+) -> RunnerResult<Result<bool, (RunnerError, StepStatus)>> {
+    match watch_stability_windows(session, step, policy, artifacts, output_bytes, results) {
+        Ok(passed) => Ok(Ok(passed)),
+        Err(err) => Ok(Err(
+            match attempt_failure(session, artifacts, output_bytes, err, step)? {
+                AttemptFailure::Stop(err, stop_status) => (err, stop_status),
+                AttemptFailure::Other(err) => (err, StepStatus::Errored),
+            },
+        )),
+    }
+}
+
+/// Keep observing for assertions with a stability window (`screen_not_contains` + `for_ms`).
+///
+/// Every sampled snapshot is re-checked against the windowed assertions still
+/// inside their window. The first offending snapshot fails its assertion, is
+/// recorded in the assertion details, and is written as the step's last
+/// snapshot. Windows are capped at `max_wait_ms`.
+///
+/// Step budgets are checked on every sample, and each sample whose screen
+/// changed is written to `samples/` (not `snapshots/`, whose count replay
+/// compares and which would vary with timing).
+///
+/// # Errors
+/// Returns the budget error (`E_TIMEOUT`) when a sample exceeds the output or
+/// snapshot budget, and any observation or artifact error.
+fn watch_stability_windows(
+    session: &mut Session,
+    step: &crate::model::Step,
+    policy: &Policy,
+    artifacts: &mut Option<ArtifactsWriter>,
+    output_bytes: &mut u64,
+    results: &mut [AssertionResult],
+) -> RunnerResult<bool> {
+    let assertions = step.assert.as_slice();
+    let windows: Vec<(usize, Duration)> = assertions
+        .iter()
+        .enumerate()
+        .filter_map(|(index, assertion)| {
+            crate::assertions::stability_window_ms(assertion).map(|ms| {
+                (
+                    index,
+                    Duration::from_millis(ms.min(policy.budgets.max_wait_ms)),
+                )
+            })
+        })
+        .collect();
+    let Some(longest) = windows.iter().map(|(_, window)| *window).max() else {
+        return Ok(true);
+    };
+
+    let started = Instant::now();
+    let deadline = started + longest;
+    let mut last_hash = None;
+    while Instant::now() < deadline {
+        let sample = deadline
+            .saturating_duration_since(Instant::now())
+            .min(Duration::from_millis(50));
+        let observation = session.observe(sample)?;
+        *output_bytes += observation.output_bytes();
+        if let Some(budget_error) = check_step_budgets(&observation, *output_bytes, policy, step)? {
+            return Err(budget_error);
+        }
+        let hash = observation.screen.screen_hash();
+        if let Some(writer) = artifacts.as_mut() {
+            if let Some(delta) = &observation.transcript_delta {
+                writer.write_transcript(delta)?;
+            }
+            if let Some(delta) = &observation.stderr_delta {
+                writer.write_stderr(delta)?;
+            }
+            if last_hash.as_ref() != Some(&hash) {
+                writer.write_sample(&crate::model::ScreenSample {
+                    step_id: step.id,
+                    timestamp_ms: observation.timestamp_ms,
+                    screen: observation.screen.clone(),
+                })?;
+            }
+        }
+        last_hash = Some(hash);
+
+        let elapsed = started.elapsed();
+        for (index, window) in &windows {
+            if elapsed > *window {
+                continue;
+            }
+            let (Some(assertion), Some(result)) = (assertions.get(*index), results.get_mut(*index))
+            else {
+                continue;
+            };
+            let (passed, message, details) = crate::assertions::evaluate(&observation, assertion);
+            if !passed {
+                if let Some(writer) = artifacts.as_mut() {
+                    writer.write_snapshot(&observation.screen)?;
+                }
+                result.passed = false;
+                result.message = message;
+                result.details = details.map(|mut details| {
+                    if let Some(map) = details.as_object_mut() {
+                        map.insert(
+                            "window_elapsed_ms".to_string(),
+                            Value::from(elapsed_ms(&started)),
+                        );
+                    }
+                    details
+                });
+                return Ok(false);
+            }
+        }
+
+        // Once the child is gone and drained, the screen can no longer change.
+        if observation.transcript_delta.is_none()
+            && session.wait_for_exit(Duration::ZERO)?.is_some()
+        {
+            break;
+        }
+    }
+    Ok(true)
+}
+
 /// Check step budgets and return an error if exceeded.
 fn check_step_budgets(
     observation: &crate::model::Observation,
@@ -701,76 +1314,125 @@ fn await_scenario_exit(
     policy: &Policy,
     run_started: &Instant,
     has_error: bool,
+    artifacts: &mut Option<ArtifactsWriter>,
 ) -> RunnerResult<Option<ExitStatus>> {
-    if has_error {
-        return Ok(session
-            .terminate_process_group(Duration::from_millis(200))
-            .ok()
-            .flatten()
-            .map(|status| convert_exit_status(status, true)));
-    }
-
     let max_runtime = Duration::from_millis(policy.budgets.max_runtime_ms);
     let elapsed = run_started.elapsed();
 
+    if has_error {
+        let termination = if elapsed >= max_runtime {
+            terminate_at_deadline(session, policy, artifacts)
+        } else {
+            session.terminate_with(&policy.process)
+        };
+        return Ok(termination.ok().and_then(Termination::exit_status));
+    }
+
     if elapsed >= max_runtime {
-        return Err(create_timeout_error(session, policy));
+        return Err(create_timeout_error(session, policy, artifacts));
     }
 
     let remaining = max_runtime.saturating_sub(elapsed);
     match session.wait_for_exit(remaining)? {
         Some(status) => Ok(Some(convert_exit_status(status, false))),
-        None => Err(create_timeout_error(session, policy)),
+        None => Err(create_timeout_error(session, policy, artifacts)),
     }
 }
 
-/// Create a timeout error, terminating the process if needed.
-fn create_timeout_error(session: &mut Session, policy: &Policy) -> RunnerError {
-    let termination = session.terminate_process_group(Duration::from_millis(200));
-    let context = match termination {
-        Ok(_) => serde_json::json!({"max_runtime_ms": policy.budgets.max_runtime_ms}),
-        Err(err) => serde_json::json!({
-            "max_runtime_ms": policy.budgets.max_runtime_ms,
-            "termination_error": err.to_string()
-        }),
-    };
+/// Create the runtime budget error, terminating the process at its
+/// deadline.
+fn create_timeout_error(
+    session: &mut Session,
+    policy: &Policy,
+    artifacts: &mut Option<ArtifactsWriter>,
+) -> RunnerError {
+    let termination = terminate_at_deadline(session, policy, artifacts);
+    let context = with_termination(
+        serde_json::json!({"max_runtime_ms": policy.budgets.max_runtime_ms}),
+        termination,
+    );
     RunnerError::timeout("E_TIMEOUT", "run exceeded max runtime budget", context)
 }
 
+/// Terminate the process because the runtime budget ran out. After a
+/// `process.deadline_signal`, what the child drew while shutting down is
+/// recorded as one more observation.
+fn terminate_at_deadline(
+    session: &mut Session,
+    policy: &Policy,
+    artifacts: &mut Option<ArtifactsWriter>,
+) -> RunnerResult<Termination> {
+    let termination = session.terminate_at_deadline(&policy.process);
+    if policy.process.deadline_signal.is_some() && artifacts.is_some() {
+        let recorded = session
+            .observe(Duration::from_millis(10))
+            .and_then(|observation| record_exec_observation(artifacts, &observation));
+        if let Err(err) = recorded {
+            tracing::warn!(error = %err, "failed to record the observation after the deadline signal");
+        }
+    }
+    termination
+}
+
+/// Terminate the process per `policy.process` after a budget was exceeded,
+/// adding the `termination` record (or `termination_error`) to `context`.
+fn terminate_for_budget(
+    session: &mut Session,
+    policy: &Policy,
+    context: serde_json::Value,
+) -> serde_json::Value {
+    let termination = session.terminate_with(&policy.process);
+    with_termination(context, termination)
+}
+
+/// Add the `termination` record (or `termination_error`) to `context`.
+fn with_termination(
+    mut context: serde_json::Value,
+    termination: RunnerResult<Termination>,
+) -> serde_json::Value {
+    let (key, value) = match termination {
+        Ok(termination) => (
+            "termination",
+            serde_json::to_value(termination.record).unwrap_or_default(),
+        ),
+        Err(err) => ("termination_error", serde_json::json!(err.to_string())),
+    };
+    if let Some(object) = context.as_object_mut() {
+        object.insert(key.to_string(), value);
+    }
+    context
+}
+
 /// Poll for process exit in exec mode, returning final observation and exit status.
 fn poll_exec_until_exit(
     session: &mut Session,
     policy: &Policy,
     artifacts: &mut Option<ArtifactsWriter>,
     deadline: Instant,
+    watchdog: &Watchdog,
 ) -> RunnerResult<(crate::model::Observation, ExitStatus)> {
     let mut output_bytes: u64 = 0;
-    let mut final_observation = session.observe(Duration::from_millis(50))?;
+    let mut final_observation = observe_exec(session, policy, Duration::from_millis(50))?;
+    record_exec_observation(artifacts, &final_observation)?;
     enforce_exec_budgets(session, &final_observation, &mut output_bytes, policy)?;
 
-    if let Some(writer) = artifacts.as_mut() {
-        writer.write_observation(&final_observation)?;
-    }
-
     loop {
+        watchdog.progress();
         if let Some(status) = session.wait_for_exit(Duration::from_millis(0))? {
             // Capture final observation after exit
-            let observation = session.observe(Duration::from_millis(10))?;
-            if let Some(writer) = artifacts.as_mut() {
-                writer.write_observation(&observation)?;
-            }
+            let observation = observe_exec(session, policy, Duration::from_millis(10))?;
+            record_exec_observation(artifacts, &observation)?;
             return Ok((observation, convert_exit_status(status, false)));
         }
 
         if Instant::now() > deadline {
-            return Err(create_timeout_error(session, policy));
+            return Err(create_timeout_error(session, policy, artifacts));
         }
 
-        let observation = session.observe(Duration::from_millis(50))?;
+        let observation = observe_exec(session, policy, Duration::from_millis(50))?;
+        // Record before enforcing budgets so a budget kill keeps its output.
+        record_exec_observation(artifacts, &observation)?;
         enforce_exec_budgets(session, &observation, &mut output_bytes, policy)?;
-        if let Some(writer) = artifacts.as_mut() {
-            writer.write_observation(&observation)?;
-        }
         #[allow(unused_assignments)]
         {
             final_observation = observation;
@@ -778,14 +1440,47 @@ fn poll_exec_until_exit(
     }
 }
 
-/// Run a scenario with the given options.
-///
-/// This is the primary entry point for scenario-based execution.
-/// For simple command execution without scenarios, use [`run_exec`].
-///
-/// # Arguments
-/// * `scenario` - Scenario to execute (from file or constructed)
-/// * `options` - Runner configuration (artifacts, progress callback)
+/// Observe in exec mode, terminating the child once a write quota is exceeded.
+fn observe_exec(
+    session: &mut Session,
+    policy: &Policy,
+    timeout: Duration,
+) -> RunnerResult<crate::model::Observation> {
+    match session.observe(timeout) {
+        Err(err) if session.write_quota_exceeded() => {
+            let context = err.context.unwrap_or_else(|| serde_json::json!({}));
+            let context = terminate_for_budget(session, policy, context);
+            Err(RunnerError::timeout("E_TIMEOUT", err.message, context))
+        }
+        result => result,
+    }
+}
+
+/// Append an observation and its output to the artifacts.
+fn record_exec_observation(
+    artifacts: &mut Option<ArtifactsWriter>,
+    observation: &crate::model::Observation,
+) -> RunnerResult<()> {
+    let Some(writer) = artifacts.as_mut() else {
+        return Ok(());
+    };
+    if let Some(delta) = &observation.transcript_delta {
+        writer.write_transcript(delta)?;
+    }
+    if let Some(delta) = &observation.stderr_delta {
+        writer.write_stderr(delta)?;
+    }
+    writer.write_observation(observation)
+}
+
+/// Run a scenario with the given options.
+///
+/// This is the primary entry point for scenario-based execution.
+/// For simple command execution without scenarios, use [`run_exec`].
+///
+/// # Arguments
+/// * `scenario` - Scenario to execute (from file or constructed)
+/// * `options` - Runner configuration (artifacts, progress callback)
 ///
 /// # Errors
 /// Returns `RunnerError` for policy violations, timeouts, I/O failures, etc.
@@ -810,11 +1505,32 @@ fn poll_exec_until_exit(
 /// - `E_PROCESS_EXIT` — Child process exited unexpectedly during a step
 /// - `E_TERMINAL_PARSE` — Invalid UTF-8 in terminal output
 /// - `E_IO` — Artifact write or session I/O failure
-pub fn run_scenario(scenario: Scenario, options: RunnerOptions) -> RunnerResult<RunResult> {
+pub fn run_scenario(mut scenario: Scenario, options: RunnerOptions) -> RunnerResult<RunResult> {
+    scenario.apply_defaults();
+    scenario.metadata.tags = merge_tags(&scenario.metadata.tags, &options.tags);
     let run_id = RunId::new();
     let run_started = Instant::now();
     let scenario_clone = scenario.clone();
+    #[cfg(feature = "otel")]
+    let recorder = options
+        .otel
+        .clone()
+        .map(|config| crate::otel::TraceRecorder::start(config, options.progress.clone()));
+    #[cfg(feature = "otel")]
+    let progress = match &recorder {
+        Some(recorder) => Some(Arc::clone(recorder) as Arc<dyn ProgressCallback>),
+        None => options.progress.clone(),
+    };
+    #[cfg(not(feature = "otel"))]
     let progress = options.progress.clone();
+    #[cfg(feature = "log-forward")]
+    if let Some(config) = &options.log_forward {
+        config.check_policy(&load_policy_ref(&scenario.run.policy)?)?;
+    }
+    #[cfg(feature = "log-forward")]
+    let (forwarder, options, progress) =
+        crate::log_forward::LogForwarder::attach(options, progress, run_id, &scenario.run.command);
+    let provenance = run_provenance(Some(&scenario), options.git_sha.as_deref());
 
     emit_progress(
         progress.as_ref(),
@@ -825,23 +1541,31 @@ pub fn run_scenario(scenario: Scenario, options: RunnerOptions) -> RunnerResult<
     );
 
     let mut artifacts: Option<ArtifactsWriter> = None;
-    let mut policy_for_error: Option<Policy> = None;
+    let mut policy_for_error: Option<(Policy, Vec<PolicyGrant>)> = None;
     let mut cleanup_guard = SandboxCleanupGuard::new(None);
+    let mut watchdog = Watchdog::default();
 
-    let result = run_scenario_inner(
-        &scenario,
-        &options,
-        run_id,
-        &run_started,
-        &progress,
-        &mut artifacts,
-        &mut policy_for_error,
-        &mut cleanup_guard,
-    );
+    let result = catch_panic("run", || {
+        run_scenario_inner(
+            &scenario,
+            &options,
+            &provenance,
+            run_id,
+            &run_started,
+            progress.as_ref(),
+            &mut artifacts,
+            &mut policy_for_error,
+            &mut cleanup_guard,
+            &mut watchdog,
+        )
+    })
+    .and_then(|result| result);
+    let result = apply_watchdog(result, &mut watchdog, &mut artifacts);
 
     handle_scenario_result(
         &result,
         &scenario_clone,
+        &provenance,
         run_id,
         &run_started,
         &progress,
@@ -850,63 +1574,246 @@ pub fn run_scenario(scenario: Scenario, options: RunnerOptions) -> RunnerResult<
     );
 
     drop(cleanup_guard);
+    reporters::write_reports(&options.reporters, &result, &scenario_clone.metadata.name);
+    #[cfg(feature = "otel")]
+    if let Some(recorder) = recorder {
+        recorder.export(&result, &scenario_clone.run.command);
+    }
+    #[cfg(feature = "log-forward")]
+    if let Some(forwarder) = forwarder {
+        forwarder.finish(&result);
+    }
     result
 }
 
 /// Inner implementation of `run_scenario` to reduce main function complexity.
-#[allow(clippy::too_many_arguments, clippy::ref_option)]
+#[allow(clippy::too_many_arguments)]
 fn run_scenario_inner(
     scenario: &Scenario,
     options: &RunnerOptions,
+    provenance: &Provenance,
     run_id: RunId,
     run_started: &Instant,
-    progress: &Option<Arc<dyn ProgressCallback>>,
+    progress: Option<&Arc<dyn ProgressCallback>>,
     artifacts: &mut Option<ArtifactsWriter>,
-    policy_for_error: &mut Option<Policy>,
+    policy_for_error: &mut Option<(Policy, Vec<PolicyGrant>)>,
     cleanup_guard: &mut SandboxCleanupGuard,
+    watchdog: &mut Watchdog,
 ) -> RunnerResult<RunResult> {
-    let policy = load_policy_ref(&scenario.run.policy)?;
-    *policy_for_error = Some(policy.clone());
-
-    let artifacts_dir = setup_scenario_artifacts(scenario, &policy, options, run_id, artifacts)?;
-    validate_policy(&policy)?;
-    validate_scenario_steps(scenario, &policy)?;
-
-    let effective_policy = EffectivePolicy::new(policy.clone());
-    effective_policy.validate_run_config(&scenario.run)?;
-
-    if let Some(writer) = artifacts.as_mut() {
-        writer.write_policy(&policy)?;
+    let setup = prepare_scenario_run(
+        scenario,
+        options,
+        run_id,
+        run_started,
+        artifacts,
+        policy_for_error,
+    )?;
+    let ScenarioSetup {
+        policy,
+        effective_policy,
+        artifacts_dir,
+        ..
+    } = &setup;
+
+    let spawn_policy = fallback_spawn_policy(policy, setup.enforcement.as_ref());
+    if scenario.run.validate_args && scenario.run.remote.is_none() {
+        probe::probe_args(scenario, &spawn_policy, run_id, artifacts)?;
     }
-
-    let mut session =
-        spawn_scenario_session(scenario, &policy, &artifacts_dir, run_id, cleanup_guard)?;
-    let (step_results, run_error) = execute_scenario_steps(
+    let mut session = spawn_scenario_session(
+        scenario,
+        &spawn_policy,
+        artifacts_dir,
+        artifacts,
+        run_id,
+        options.socket_handler.as_ref(),
+        cleanup_guard,
+    )?;
+    watchdog.arm(session.process_id(), *run_started, policy);
+    configure_scenario_session(&mut session, scenario, options, &setup, artifacts)?;
+    #[cfg(feature = "sandbox-audit")]
+    let audit = crate::policy::audit::SandboxAudit::start(
+        options.sandbox_audit,
+        &spawn_policy,
+        session.process_id(),
+        artifacts.is_some(),
+    );
+    let executed = execute_scenario_steps(
         &mut session,
         scenario,
-        &policy,
-        &effective_policy,
+        policy,
+        effective_policy,
         artifacts,
         run_started,
         progress,
+        options,
+        watchdog,
     )?;
+    let mut run_error = executed.run_error;
 
-    let final_observation = session.observe(Duration::from_millis(10)).ok();
-    if let (Some(writer), Some(obs)) = (artifacts.as_mut(), final_observation.as_ref()) {
-        writer.write_observation(obs)?;
-    }
+    let final_observation = final_scenario_observation(&mut session, artifacts, &mut run_error)?;
 
-    let exit_status = await_scenario_exit(&mut session, &policy, run_started, run_error.is_some())?;
-    let run_result = build_scenario_result(
+    let exit_status = await_scenario_exit(
+        &mut session,
+        policy,
+        run_started,
+        run_error.is_some() || executed.canceled,
+        artifacts,
+    )?;
+    watchdog.disarm();
+    finish_scenario_files(
+        &mut session,
+        policy,
+        artifacts_dir.as_ref(),
+        artifacts,
+        &mut run_error,
+    )?;
+    let post_run = evaluate_scenario_post_run(
+        &mut session,
         scenario,
-        &policy,
+        policy,
+        artifacts.as_ref(),
+        final_observation.as_ref(),
+        exit_status.as_ref(),
+        &mut run_error,
+    );
+    #[cfg(feature = "sandbox-audit")]
+    audit.finish(artifacts.as_mut())?;
+    #[cfg(feature = "emulator-diff")]
+    write_emulator_diff(&session, artifacts)?;
+    let mut run_result = build_scenario_result(
+        scenario,
+        policy,
         run_id,
         run_started,
-        step_results,
+        executed.results,
         final_observation,
         exit_status,
         run_error,
     );
+    if executed.canceled {
+        run_result.status = RunStatus::Canceled;
+    }
+    run_result.post_run = post_run;
+    finish_scenario_result(run_result, setup, provenance, progress, artifacts)
+}
+
+/// Everything a scenario run resolves before its child is spawned.
+struct ScenarioSetup {
+    policy: Policy,
+    policy_grants: Vec<PolicyGrant>,
+    effective_policy: EffectivePolicy,
+    enforcement: Option<EnforcementReport>,
+    acknowledgements: Option<AcknowledgementReport>,
+    keymap: Option<Keymap>,
+    artifacts_dir: Option<PathBuf>,
+    fixtures: Vec<FixtureRecord>,
+}
+
+/// Resolve and validate the scenario's policy, open its artifacts, write
+/// the policy artifacts, and materialize fixtures.
+fn prepare_scenario_run(
+    scenario: &Scenario,
+    options: &RunnerOptions,
+    run_id: RunId,
+    run_started: &Instant,
+    artifacts: &mut Option<ArtifactsWriter>,
+    policy_for_error: &mut Option<(Policy, Vec<PolicyGrant>)>,
+) -> RunnerResult<ScenarioSetup> {
+    validate_git_sha(options.git_sha.as_deref())?;
+    let mut policy = load_policy_ref(&scenario.run.policy)?;
+    let policy_grants = apply_policy_grants(&mut policy, &scenario.metadata.name)?;
+    *policy_for_error = Some((policy.clone(), policy_grants.clone()));
+
+    let artifacts_dir = setup_scenario_artifacts(scenario, &policy, options, run_id, artifacts)?;
+    if let Some(writer) = artifacts.as_mut() {
+        writer.journal(
+            elapsed_ms(run_started),
+            JournalEvent::RunStarted {
+                run_id,
+                command: scenario.run.command.clone(),
+                args: scenario.run.args.clone(),
+                cwd: get_cwd_string(scenario.run.cwd.clone(), policy.fs.working_dir.as_ref()),
+                tags: scenario.metadata.tags.clone(),
+            },
+        )?;
+    }
+    let enforcement = validate_policy(&policy)?;
+    validate_scenario_steps(scenario, &policy)?;
+    crate::session::validate_watchers(&scenario.watchers)?;
+    let keymap = match &options.keymap {
+        Some(keymap) => Some(keymap.clone()),
+        None => resolve_keymap(&policy.input)?,
+    };
+
+    let effective_policy = EffectivePolicy::new(policy.clone());
+    effective_policy.validate_run_config(&scenario.run)?;
+
+    let acknowledgements = acknowledgement_report(&policy, &options.ack_overrides);
+    write_policy_artifacts(
+        artifacts,
+        &policy,
+        enforcement.as_ref(),
+        acknowledgements.as_ref(),
+    )?;
+
+    let fixtures = fixtures::materialize_fixtures(&scenario.fixtures, &policy.fs)?;
+    Ok(ScenarioSetup {
+        policy,
+        policy_grants,
+        effective_policy,
+        enforcement,
+        acknowledgements,
+        keymap,
+        artifacts_dir,
+        fixtures,
+    })
+}
+
+/// Apply the runner options and scenario settings to a freshly spawned
+/// session.
+fn configure_scenario_session(
+    session: &mut Session,
+    scenario: &Scenario,
+    options: &RunnerOptions,
+    setup: &ScenarioSetup,
+    artifacts: &mut Option<ArtifactsWriter>,
+) -> RunnerResult<()> {
+    if let (Some(writer), Some(remote)) = (artifacts.as_mut(), session.remote_host()) {
+        writer.write_remote(remote)?;
+    }
+    if let Some(source) = &options.manual_input {
+        session.set_manual_input(Arc::clone(source));
+    }
+    if let Some(tap) = &options.observation_tap {
+        session.set_observation_tap(Arc::clone(tap));
+    }
+    #[cfg(feature = "emulator-diff")]
+    if options.emulator_diff {
+        session.enable_emulator_diff();
+    }
+    session.set_capture_attributes(scenario.run.capture_attributes);
+    session.set_keymap(setup.keymap.clone());
+    session.set_watchers(&scenario.watchers)?;
+    arm_write_quotas(session, &setup.policy, setup.artifacts_dir.as_ref());
+    Ok(())
+}
+
+/// Attach the setup records to the run result, write `run.json` and the
+/// checksums, and report the run as completed.
+fn finish_scenario_result(
+    mut run_result: RunResult,
+    setup: ScenarioSetup,
+    provenance: &Provenance,
+    progress: Option<&Arc<dyn ProgressCallback>>,
+    artifacts: &mut Option<ArtifactsWriter>,
+) -> RunnerResult<RunResult> {
+    run_result.fixtures = setup.fixtures;
+    run_result.policy_grants = setup.policy_grants;
+    run_result.keymap = setup.keymap;
+    run_result.enforcement = setup.enforcement;
+    run_result.risk_summary = setup.acknowledgements.map(|report| report.summary);
+    run_result.provenance = Some(provenance.clone());
+    run_result.classification = classify_failure(&run_result);
 
     if let Some(writer) = artifacts.as_mut() {
         writer.write_run_result(&run_result)?;
@@ -914,9 +1821,9 @@ fn run_scenario_inner(
     }
 
     emit_progress(
-        progress.as_ref(),
+        progress,
         ProgressEvent::RunCompleted {
-            run_id,
+            run_id: run_result.run_id,
             success: run_result.status == RunStatus::Passed,
             duration_ms: run_result.ended_at_ms,
         },
@@ -925,6 +1832,43 @@ fn run_scenario_inner(
     Ok(run_result)
 }
 
+/// Write `emulator-diff.json` when the session compared emulator backends.
+#[cfg(feature = "emulator-diff")]
+#[allow(clippy::ref_option)]
+fn write_emulator_diff(
+    session: &Session,
+    artifacts: &mut Option<ArtifactsWriter>,
+) -> RunnerResult<()> {
+    match (session.emulator_diff(), artifacts.as_mut()) {
+        (Some(report), Some(writer)) => writer.write_emulator_diff(&report),
+        _ => Ok(()),
+    }
+}
+
+/// Write `policy.json`, `host.json`, `env.json`, and, when present,
+/// `enforcement.json` and `acknowledgements.json`.
+#[allow(clippy::ref_option)]
+fn write_policy_artifacts(
+    artifacts: &mut Option<ArtifactsWriter>,
+    policy: &Policy,
+    enforcement: Option<&EnforcementReport>,
+    acknowledgements: Option<&AcknowledgementReport>,
+) -> RunnerResult<()> {
+    let Some(writer) = artifacts.as_mut() else {
+        return Ok(());
+    };
+    writer.write_policy(policy)?;
+    writer.write_host(&HostFingerprint::current())?;
+    writer.write_env(&crate::policy::env_record(&policy.effective_env()))?;
+    if let Some(report) = enforcement {
+        writer.write_enforcement(report)?;
+    }
+    if let Some(report) = acknowledgements {
+        writer.write_acknowledgements(report)?;
+    }
+    Ok(())
+}
+
 /// Setup artifacts for scenario execution.
 fn setup_scenario_artifacts(
     scenario: &Scenario,
@@ -940,9 +1884,41 @@ fn setup_scenario_artifacts(
     let artifacts_dir = artifacts_config.as_ref().map(|config| config.dir.clone());
     validate_write_access(policy, artifacts_dir.as_deref())?;
 
+    let checksums: Vec<&Value> = scenario
+        .post_run
+        .iter()
+        .filter(|assertion| {
+            assertion.assertion_type == crate::assertions::CAPTURE_CHECKSUM_ASSERTION
+        })
+        .map(|assertion| &assertion.payload)
+        .collect();
+    if artifacts_config.is_none() && !checksums.is_empty() {
+        return Err(RunnerError::with_context(
+            ErrorCode::Protocol,
+            "capture_checksum_equals needs an artifacts directory",
+            serde_json::json!({
+                "fix": "Enable policy.artifacts or pass --artifacts"
+            }),
+        ));
+    }
+
     if let Some(config) = artifacts_config {
         validate_artifacts_dir(&config.dir, &policy.fs)?;
-        let mut writer = ArtifactsWriter::new(run_id, config)?;
+        let mut writer = open_artifacts_writer(policy, options, run_id, config)?;
+        writer.set_layout(policy.artifacts.layout);
+        writer.set_transcript(&policy.artifacts.transcript);
+        writer.set_canonical_json(policy.artifacts.canonical_json);
+        writer.set_asciicast(policy.artifacts.asciicast);
+        if let Some(encryption) = &policy.artifacts.encryption {
+            writer.set_encryption(encryption)?;
+        }
+        if checksums
+            .iter()
+            .any(|payload| payload.get("capture").map_or(true, Value::is_null))
+        {
+            writer.track_transcript_sha256();
+        }
+        configure_run_index(&mut writer, policy, options);
         writer.write_normalization(&NormalizationRecord {
             normalization_version: NORMALIZATION_VERSION,
             filters: Vec::new(),
@@ -966,16 +1942,105 @@ fn validate_scenario_steps(scenario: &Scenario, policy: &Policy) -> RunnerResult
             serde_json::json!({"max_steps": policy.budgets.max_steps}),
         ));
     }
+    if let Some(step) = scenario.steps.iter().find(|step| {
+        step.sample_interval_ms
+            .is_some_and(|ms| ms < MIN_SAMPLE_INTERVAL_MS)
+    }) {
+        return Err(RunnerError::with_context(
+            ErrorCode::Protocol,
+            format!("sample_interval_ms must be at least {MIN_SAMPLE_INTERVAL_MS}"),
+            step_context(
+                step,
+                Some(serde_json::json!({
+                    "sample_interval_ms": step.sample_interval_ms,
+                    "min_sample_interval_ms": MIN_SAMPLE_INTERVAL_MS
+                })),
+            ),
+        ));
+    }
+    validate_step_captures(scenario)?;
+    vars::validate_step_vars(scenario)?;
+    validate_step_metadata(scenario)?;
+    let capture_names: Vec<&str> = scenario
+        .steps
+        .iter()
+        .filter_map(|step| step.capture.as_ref().map(|capture| capture.name.as_str()))
+        .collect();
+    crate::assertions::validate_post_run(&scenario.post_run, &capture_names, &policy.fs)?;
+    validate_step_env_overlays(scenario, policy)
+}
+
+/// Check each step's `env_overlay` against `policy.env`.
+fn validate_step_env_overlays(scenario: &Scenario, policy: &Policy) -> RunnerResult<()> {
+    for step in &scenario.steps {
+        validate_env_overlay(&step.env_overlay, &policy.env).map_err(|err| {
+            RunnerError::with_context(err.code, err.message, step_context(step, err.context))
+        })?;
+    }
     Ok(())
 }
 
-/// Spawn a session for scenario execution.
+/// Capture names become file names under `captures/`, so they must be
+/// plain, unique names.
+fn validate_step_captures(scenario: &Scenario) -> RunnerResult<()> {
+    let mut names = std::collections::BTreeSet::new();
+    for step in &scenario.steps {
+        let Some(capture) = &step.capture else {
+            continue;
+        };
+        let name = capture.name.as_str();
+        let reason = if name.is_empty()
+            || name.starts_with('.')
+            || !name
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '.' | '_' | '-'))
+        {
+            "capture name must be letters, digits, '.', '_', or '-' and not start with '.'"
+        } else if !names.insert(name) {
+            "capture name is used by more than one step"
+        } else {
+            continue;
+        };
+        return Err(RunnerError::with_context(
+            ErrorCode::Protocol,
+            reason,
+            step_context(step, Some(serde_json::json!({ "capture": name }))),
+        ));
+    }
+    Ok(())
+}
+
+/// Step metadata is copied into every result, so its size is capped.
+fn validate_step_metadata(scenario: &Scenario) -> RunnerResult<()> {
+    for step in &scenario.steps {
+        let bytes = serde_json::to_vec(&step.metadata).map_or(u64::MAX, |json| json.len() as u64);
+        if bytes > MAX_STEP_METADATA_BYTES {
+            return Err(RunnerError::with_context(
+                ErrorCode::Protocol,
+                format!("step metadata exceeds {MAX_STEP_METADATA_BYTES} bytes"),
+                step_context(
+                    step,
+                    Some(serde_json::json!({
+                        "metadata_bytes": bytes,
+                        "max_metadata_bytes": MAX_STEP_METADATA_BYTES
+                    })),
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Spawn a session for scenario execution, recording a container
+/// invocation in `artifacts`.
 #[allow(clippy::ref_option)]
-fn spawn_scenario_session(
+pub(crate) fn spawn_scenario_session(
     scenario: &Scenario,
     policy: &Policy,
     artifacts_dir: &Option<PathBuf>,
+    artifacts: &mut Option<ArtifactsWriter>,
     run_id: RunId,
+    socket_handler: Option<&Arc<dyn SocketHandler>>,
     cleanup_guard: &mut SandboxCleanupGuard,
 ) -> RunnerResult<Session> {
     let cwd = scenario
@@ -983,27 +2048,84 @@ fn spawn_scenario_session(
         .cwd
         .clone()
         .or_else(|| policy.fs.working_dir.clone());
-    let spawn = build_spawn_command(
+    if let Some(target) = &scenario.run.remote {
+        return spawn_remote_session(scenario, policy, target, cwd, run_id);
+    }
+    let mut spawn = build_spawn_command(
         policy,
         &scenario.run.command,
         &scenario.run.args,
+        cwd.as_deref(),
         artifacts_dir.as_ref(),
         run_id,
     )?;
     cleanup_guard.path = spawn.cleanup_path.clone();
+    cleanup_guard.container = spawn.container.take();
+    if let (Some(writer), Some(invocation)) = (artifacts.as_mut(), &spawn.invocation) {
+        writer.write_container_invocation(invocation)?;
+    }
+    let env = bind_policy_sockets(policy, run_id, socket_handler, cleanup_guard)?;
 
-    Session::spawn(SessionConfig {
+    let mut session = Session::spawn(SessionConfig {
         command: spawn.command,
         args: spawn.args,
         cwd,
         size: scenario.run.initial_size.clone(),
         run_id,
-        env: policy.env.clone(),
+        env,
+        separate_stderr: scenario.run.separate_stderr,
+        output_buffer: scenario.run.output_buffer,
+        resize: scenario.run.resize,
+        remote: None,
+        encoding: policy.terminal.encoding,
+    })?;
+    if let Some(container) = &cleanup_guard.container {
+        session.set_container(container.clone());
+    }
+    Ok(session)
+}
+
+/// Spawn a scenario session on `target` over SSH. No local sandbox or
+/// sockets are set up; the command runs as given on the remote host.
+fn spawn_remote_session(
+    scenario: &Scenario,
+    policy: &Policy,
+    target: &RemoteTarget,
+    cwd: Option<String>,
+    run_id: RunId,
+) -> RunnerResult<Session> {
+    if !policy.sockets.is_empty() {
+        return Err(RunnerError::with_context(
+            ErrorCode::Protocol,
+            "policy sockets are not available to remote sessions",
+            serde_json::json!({ "host": target.host }),
+        ));
+    }
+    Session::spawn(SessionConfig {
+        command: scenario.run.command.clone(),
+        args: scenario.run.args.clone(),
+        cwd,
+        size: scenario.run.initial_size.clone(),
+        run_id,
+        env: policy.effective_env(),
+        separate_stderr: scenario.run.separate_stderr,
+        output_buffer: scenario.run.output_buffer,
+        resize: scenario.run.resize,
+        remote: Some(RemoteConfig::from_policy(target.clone(), &policy.remote)?),
+        encoding: policy.terminal.encoding,
     })
 }
 
+/// Outcome of [`execute_scenario_steps`].
+struct ExecutedSteps {
+    results: Vec<StepResult>,
+    run_error: Option<RunnerError>,
+    /// The cancel flag stopped the run before a step.
+    canceled: bool,
+}
+
 /// Execute all steps in a scenario.
-#[allow(clippy::ref_option)]
+#[allow(clippy::too_many_arguments)]
 fn execute_scenario_steps(
     session: &mut Session,
     scenario: &Scenario,
@@ -1011,18 +2133,29 @@ fn execute_scenario_steps(
     effective_policy: &EffectivePolicy,
     artifacts: &mut Option<ArtifactsWriter>,
     run_started: &Instant,
-    progress: &Option<Arc<dyn ProgressCallback>>,
-) -> RunnerResult<(Vec<StepResult>, Option<RunnerError>)> {
+    progress: Option<&Arc<dyn ProgressCallback>>,
+    options: &RunnerOptions,
+    watchdog: &Watchdog,
+) -> RunnerResult<ExecutedSteps> {
     let mut step_results = Vec::with_capacity(scenario.steps.len());
     let mut run_error: Option<RunnerError> = None;
+    let mut canceled = false;
     let mut output_bytes: u64 = 0;
+    let mut vars = vars::Vars::default();
 
     for (step_index, step) in scenario.steps.iter().enumerate() {
-        if run_error.is_some() {
+        watchdog.progress();
+        if run_error.is_none() && !canceled {
+            canceled = check_canceled(options, artifacts, run_started)?;
+        }
+        if run_error.is_some() || canceled {
             step_results.push(create_skipped_step(step, elapsed_ms(run_started), None));
             continue;
         }
 
+        if let Some(offset_ms) = options.step_offsets_ms.get(step_index) {
+            pace_step(*offset_ms, run_started, policy);
+        }
         if elapsed_ms(run_started) > policy.budgets.max_runtime_ms {
             run_error = Some(RunnerError::timeout(
                 "E_TIMEOUT",
@@ -1038,7 +2171,7 @@ fn execute_scenario_steps(
         }
 
         emit_progress(
-            progress.as_ref(),
+            progress,
             ProgressEvent::StepStarted {
                 step_id: step.id,
                 step_index: step_index + 1,
@@ -1047,9 +2180,15 @@ fn execute_scenario_steps(
         );
 
         let step_started_ms = elapsed_ms(run_started);
-        let exec_result = execute_step(
+        if let Some(writer) = artifacts.as_mut() {
+            begin_journaled_step(writer, step_index + 1, step, step_started_ms)?;
+        }
+        let output_before = output_bytes;
+        session.set_hook_env(step.env_overlay.clone());
+        let exec_result = execute_step_with_vars(
             session,
             step,
+            &mut vars,
             policy,
             effective_policy,
             artifacts,
@@ -1058,15 +2197,28 @@ fn execute_scenario_steps(
             run_started,
         )?;
 
+        if let Some(writer) = artifacts.as_mut() {
+            end_journaled_step(
+                writer,
+                step_index + 1,
+                &exec_result.step_result,
+                elapsed_ms(run_started),
+            )?;
+        }
+        // A failed step's overlay stays in place for the termination that follows.
+        if exec_result.step_result.status == StepStatus::Passed {
+            session.set_hook_env(BTreeMap::new());
+        }
         let step_ended_ms = elapsed_ms(run_started);
         emit_progress(
-            progress.as_ref(),
+            progress,
             ProgressEvent::StepCompleted {
                 step_id: step.id,
                 name: step.name.clone(),
                 status: exec_result.step_result.status.clone(),
                 duration_ms: step_ended_ms - step_started_ms,
                 assertions: exec_result.step_result.assertions.clone(),
+                output_bytes: output_bytes - output_before,
             },
         );
 
@@ -1076,7 +2228,144 @@ fn execute_scenario_steps(
         step_results.push(exec_result.step_result);
     }
 
-    Ok((step_results, run_error))
+    Ok(ExecutedSteps {
+        results: step_results,
+        run_error,
+        canceled,
+    })
+}
+
+/// Whether the run has been canceled, journaling `run_canceled` when it has.
+#[allow(clippy::ref_option)]
+fn check_canceled(
+    options: &RunnerOptions,
+    artifacts: &mut Option<ArtifactsWriter>,
+    run_started: &Instant,
+) -> RunnerResult<bool> {
+    let canceled = options
+        .cancel
+        .as_ref()
+        .is_some_and(|flag| flag.load(Ordering::SeqCst));
+    if let (true, Some(writer)) = (canceled, artifacts.as_mut()) {
+        writer.journal(elapsed_ms(run_started), JournalEvent::RunCanceled)?;
+    }
+    Ok(canceled)
+}
+
+/// Journal `step_started` and open the step's artifacts.
+fn begin_journaled_step(
+    writer: &mut ArtifactsWriter,
+    step_index: usize,
+    step: &crate::model::Step,
+    at_ms: u64,
+) -> RunnerResult<()> {
+    writer.journal(
+        at_ms,
+        JournalEvent::StepStarted {
+            step_index,
+            step_id: step.id,
+            name: step.name.clone(),
+        },
+    )?;
+    writer.begin_step(step_index, &step.name)
+}
+
+/// Close the step's artifacts and journal `step_finished` with its result.
+fn end_journaled_step(
+    writer: &mut ArtifactsWriter,
+    step_index: usize,
+    result: &StepResult,
+    at_ms: u64,
+) -> RunnerResult<()> {
+    writer.end_step()?;
+    writer.journal(
+        at_ms,
+        JournalEvent::StepFinished {
+            step_index,
+            result: Box::new(result.clone()),
+        },
+    )
+}
+
+/// Execute a step with captured variables substituted into its action,
+/// then capture its own `vars` if it passed.
+#[allow(clippy::too_many_arguments)]
+fn execute_step_with_vars(
+    session: &mut Session,
+    step: &crate::model::Step,
+    vars: &mut vars::Vars,
+    policy: &Policy,
+    effective_policy: &EffectivePolicy,
+    artifacts: &mut Option<ArtifactsWriter>,
+    output_bytes: &mut u64,
+    step_started_ms: u64,
+    run_started: &Instant,
+) -> RunnerResult<StepExecutionResult> {
+    let (resolved, resolved_vars) = match vars.substitute(step) {
+        Ok(resolved) => resolved,
+        Err(err) => {
+            return Ok(errored_step(
+                step,
+                step_started_ms,
+                elapsed_ms(run_started),
+                err,
+            ))
+        }
+    };
+    let exec_result = catch_panic("step", || {
+        execute_step(
+            session,
+            &resolved,
+            policy,
+            effective_policy,
+            artifacts,
+            output_bytes,
+            step_started_ms,
+            run_started,
+        )
+    });
+    let mut exec_result = match exec_result {
+        Ok(result) => result?,
+        Err(panic) => errored_step(step, step_started_ms, elapsed_ms(run_started), panic),
+    };
+    exec_result.step_result.step_key = Some(step.content_key());
+    exec_result.step_result.resolved_vars = resolved_vars;
+    if exec_result.step_result.status == StepStatus::Passed && !step.vars.is_empty() {
+        capture_step_vars(session, step, vars, &mut exec_result);
+    }
+    Ok(exec_result)
+}
+
+/// Capture a passed step's `vars` from the output read since its last input;
+/// a pattern that does not match fails the step.
+fn capture_step_vars(
+    session: &Session,
+    step: &crate::model::Step,
+    vars: &mut vars::Vars,
+    exec_result: &mut StepExecutionResult,
+) {
+    let output = String::from_utf8_lossy(session.raw_output().bytes);
+    let output = crate::artifacts::grep::strip_escapes(&output);
+    match vars.capture(step, &output) {
+        Ok(captured) => exec_result.step_result.vars = captured,
+        Err(err) => {
+            exec_result.step_result.status = StepStatus::Failed;
+            exec_result.step_result.error = Some(err.to_error_info());
+            exec_result.run_error = Some(err);
+        }
+    }
+}
+
+/// Wait until `offset_ms` after run start, bounded by [`MAX_PACED_DELAY_MS`]
+/// and the runtime budget.
+fn pace_step(offset_ms: u64, run_started: &Instant, policy: &Policy) {
+    let target = offset_ms.min(policy.budgets.max_runtime_ms);
+    let delay = target
+        .saturating_sub(elapsed_ms(run_started))
+        .min(MAX_PACED_DELAY_MS);
+    if delay > 0 {
+        std::thread::sleep(Duration::from_millis(delay));
+    }
 }
 
 /// Build the final run result for a scenario.
@@ -1091,9 +2380,10 @@ fn build_scenario_result(
     exit_status: Option<ExitStatus>,
     run_error: Option<RunnerError>,
 ) -> RunResult {
-    let status = if step_results
-        .iter()
-        .all(|s| matches!(s.status, StepStatus::Passed))
+    let status = if run_error.is_none()
+        && step_results
+            .iter()
+            .all(|s| matches!(s.status, StepStatus::Passed))
     {
         RunStatus::Passed
     } else {
@@ -1116,19 +2406,310 @@ fn build_scenario_result(
         final_observation,
         exit_status,
         error: run_error.map(|err| err.to_error_info()),
+        tags: scenario.metadata.tags.clone(),
+        enforcement: None,
+        provenance: None,
+        classification: None,
+        post_run: Vec::new(),
+        risk_summary: None,
+        fixtures: Vec::new(),
+        policy_grants: Vec::new(),
+        keymap: None,
+        determinism: policy.determinism.pinned(),
     }
 }
 
-/// Handle scenario result (emit events and write error artifacts if needed).
+/// Arm `policy.fs.write_quotas` on `session`, leaving the artifacts
+/// directory out of the sampled trees.
+pub(crate) fn arm_write_quotas(
+    session: &mut Session,
+    policy: &Policy,
+    artifacts_dir: Option<&PathBuf>,
+) {
+    let excluded: Vec<PathBuf> = artifacts_dir.into_iter().cloned().collect();
+    session.set_write_quotas(&policy.fs.write_quotas, &excluded);
+}
+
+/// Take the final write quota sample, record it in `resources.json`, and
+/// return the error for an exceeded quota.
+#[allow(clippy::ref_option)]
+pub(crate) fn finish_write_quotas(
+    session: &mut Session,
+    artifacts: &mut Option<ArtifactsWriter>,
+) -> RunnerResult<Option<RunnerError>> {
+    let write_quotas = session.write_quota_usage();
+    if write_quotas.is_empty() {
+        return Ok(None);
+    }
+    if let Some(writer) = artifacts.as_mut() {
+        writer.write_resources(&ResourceUsage { write_quotas })?;
+    }
+    Ok(session.write_quota_error())
+}
+
+/// List the files under `fs.allowed_write` in `fs-manifest.json` when the
+/// policy sets `artifacts.fs_manifest`, leaving the artifacts directory out.
 #[allow(clippy::ref_option)]
+pub(crate) fn write_fs_manifest(
+    policy: &Policy,
+    artifacts_dir: Option<&PathBuf>,
+    artifacts: &mut Option<ArtifactsWriter>,
+) -> RunnerResult<()> {
+    if !policy.artifacts.fs_manifest {
+        return Ok(());
+    }
+    let Some(writer) = artifacts.as_mut() else {
+        return Ok(());
+    };
+    let excluded: Vec<PathBuf> = artifacts_dir.into_iter().cloned().collect();
+    writer.write_fs_manifest(&crate::artifacts::build_fs_manifest(
+        &policy.fs.allowed_write,
+        &excluded,
+    ))
+}
+
+/// Observe the screen once more after the last step and record it. A
+/// watcher that trips on this observation fails the run.
+fn final_scenario_observation(
+    session: &mut Session,
+    artifacts: &mut Option<ArtifactsWriter>,
+    run_error: &mut Option<RunnerError>,
+) -> RunnerResult<Option<Observation>> {
+    let final_observation = match session.observe(Duration::from_millis(10)) {
+        Ok(observation) => Some(observation),
+        Err(err) => {
+            let trip = session.take_watcher_trip();
+            if trip.is_some() {
+                run_error.get_or_insert(err);
+            }
+            trip
+        }
+    };
+    if let (Some(writer), Some(obs)) = (artifacts.as_mut(), final_observation.as_ref()) {
+        writer.write_observation(obs)?;
+    }
+    Ok(final_observation)
+}
+
+/// After the scenario's process exits: take the final write quota sample
+/// and write `fs-manifest.json`.
+#[allow(clippy::ref_option)]
+fn finish_scenario_files(
+    session: &mut Session,
+    policy: &Policy,
+    artifacts_dir: Option<&PathBuf>,
+    artifacts: &mut Option<ArtifactsWriter>,
+    run_error: &mut Option<RunnerError>,
+) -> RunnerResult<()> {
+    if let Some(err) = finish_write_quotas(session, artifacts)? {
+        run_error.get_or_insert(err);
+    }
+    write_fs_manifest(policy, artifacts_dir, artifacts)
+}
+
+/// Evaluate the scenario's `post_run` assertions once the process is gone.
+///
+/// A last observation drains output and stderr written before exit; when it
+/// fails, the final observation from before exit is used instead.
+fn evaluate_scenario_post_run(
+    session: &mut Session,
+    scenario: &Scenario,
+    policy: &Policy,
+    artifacts: Option<&ArtifactsWriter>,
+    final_observation: Option<&Observation>,
+    exit_status: Option<&ExitStatus>,
+    run_error: &mut Option<RunnerError>,
+) -> Vec<AssertionResult> {
+    if scenario.post_run.is_empty() {
+        return Vec::new();
+    }
+    let drained = session.observe(Duration::from_millis(10)).ok();
+    let latency_ms = session
+        .response_latency()
+        .ok()
+        .flatten()
+        .map(|latency| u64::try_from(latency.as_millis()).unwrap_or(u64::MAX));
+    let context = crate::assertions::AssertionContext {
+        exit_status,
+        stderr: session.stderr(),
+        latency_ms,
+        raw_output: Some(session.raw_output()),
+    };
+    let captures = artifacts
+        .map(ArtifactsWriter::capture_digests)
+        .unwrap_or_default();
+    let post_run = crate::assertions::evaluate_post_run(
+        &scenario.post_run,
+        drained.as_ref().or(final_observation),
+        &context,
+        &captures,
+        &policy.fs,
+    );
+    if let Some(err) = post_run_error(&post_run) {
+        run_error.get_or_insert(err);
+    }
+    post_run
+}
+
+/// The run error for failed `post_run` assertions, if any failed.
+///
+/// The context refers to each failed assertion by its index in `post_run`
+/// and its type only, since messages can quote the screen, stderr, or file
+/// paths; the full results are in `RunResult::post_run`.
+fn post_run_error(results: &[AssertionResult]) -> Option<RunnerError> {
+    let failed: Vec<Value> = results
+        .iter()
+        .enumerate()
+        .filter(|(_, result)| !result.passed)
+        .map(|(index, result)| {
+            serde_json::json!({
+                "index": index,
+                "type": result.assertion_type,
+            })
+        })
+        .collect();
+    if failed.is_empty() {
+        return None;
+    }
+    Some(RunnerError::assertion_failed(
+        "post_run assertion failed",
+        serde_json::json!({ "post_run": failed }),
+    ))
+}
+
+/// Describe where a run came from for `run.json`.
+///
+/// The scenario hash covers the compact JSON of the resolved scenario, so two
+/// runs with the same hash executed the same steps under the same policy.
+pub(crate) fn run_provenance(scenario: Option<&Scenario>, git_sha: Option<&str>) -> Provenance {
+    Provenance {
+        ptybox_version: env!("CARGO_PKG_VERSION").to_string(),
+        git_sha: git_sha.map(str::to_string),
+        scenario_hash: scenario
+            .and_then(|scenario| serde_json::to_vec(scenario).ok())
+            .map(|bytes| format!("sha256:{}", sha256_hex(&bytes))),
+    }
+}
+
+/// Reject a git SHA that is not 7 to 64 hex characters.
+fn validate_git_sha(git_sha: Option<&str>) -> RunnerResult<()> {
+    let Some(sha) = git_sha else {
+        return Ok(());
+    };
+    if (7..=64).contains(&sha.len()) && sha.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return Ok(());
+    }
+    Err(RunnerError::protocol(
+        "E_PROTOCOL",
+        "git sha must be 7 to 64 hex characters",
+        Some(serde_json::json!({ "git_sha": sha })),
+    ))
+}
+
+/// Combine scenario and option tags, keeping first occurrence order.
+fn merge_tags(scenario_tags: &[String], option_tags: &[String]) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::with_capacity(scenario_tags.len() + option_tags.len());
+    for tag in scenario_tags.iter().chain(option_tags) {
+        if !tags.contains(tag) {
+            tags.push(tag.clone());
+        }
+    }
+    tags
+}
+
+/// Create the artifacts writer, first admitting the run into the artifacts
+/// quota when the run index root holds a quota ledger and the policy allows
+/// writes to it.
+fn open_artifacts_writer(
+    policy: &Policy,
+    options: &RunnerOptions,
+    run_id: RunId,
+    config: ArtifactsWriterConfig,
+) -> RunnerResult<ArtifactsWriter> {
+    // A directory that may not be overwritten fails below, untracked.
+    let admissible = config.overwrite || !config.dir.exists();
+    let Some(root) = options
+        .run_index
+        .as_ref()
+        .filter(|root| admissible && validate_artifacts_dir(root, &policy.fs).is_ok())
+    else {
+        return ArtifactsWriter::new(run_id, config);
+    };
+    if !quota::admit_run(root, run_id, &config.dir)? {
+        return ArtifactsWriter::new(run_id, config);
+    }
+    match ArtifactsWriter::new(run_id, config) {
+        Ok(mut writer) => {
+            writer.set_quota(root.clone(), run_id);
+            Ok(writer)
+        }
+        Err(err) => {
+            let _ = quota::release_run(root, run_id);
+            Err(err)
+        }
+    }
+}
+
+/// Enable the run index on `writer` when the policy allows writes to its root.
+fn configure_run_index(writer: &mut ArtifactsWriter, policy: &Policy, options: &RunnerOptions) {
+    let Some(root) = options.run_index.as_ref() else {
+        return;
+    };
+    if validate_artifacts_dir(root, &policy.fs).is_ok() {
+        writer.set_run_index(root.clone());
+    } else {
+        tracing::debug!(root = %root.display(), "run index root not writable under policy; skipping");
+    }
+}
+
+/// Record a run whose child the watchdog killed as `E_TIMEOUT`.
+///
+/// A run that still finished keeps its steps and is rewritten as failed; a
+/// run that errored has its error replaced, with the original kept as
+/// `cause`, and is written by the error handler that follows.
+fn apply_watchdog(
+    result: RunnerResult<RunResult>,
+    watchdog: &mut Watchdog,
+    artifacts: &mut Option<ArtifactsWriter>,
+) -> RunnerResult<RunResult> {
+    watchdog.disarm();
+    let Some(mut error) = watchdog.error() else {
+        return result;
+    };
+    match result {
+        Ok(mut run_result) => {
+            run_result.status = RunStatus::Failed;
+            run_result.error = Some(error.to_error_info());
+            run_result.classification = classify_failure(&run_result);
+            if let Some(writer) = artifacts.as_mut() {
+                writer.write_run_result(&run_result)?;
+                writer.flush_checksums()?;
+            }
+            Ok(run_result)
+        }
+        Err(cause) => {
+            if let Some(Value::Object(context)) = error.context.as_mut() {
+                context.insert(
+                    "cause".to_string(),
+                    serde_json::to_value(cause.to_error_info()).unwrap_or_default(),
+                );
+            }
+            Err(error)
+        }
+    }
+}
+
+/// Handle scenario result (emit events and write error artifacts if needed).
+#[allow(clippy::too_many_arguments, clippy::ref_option)]
 fn handle_scenario_result(
     result: &RunnerResult<RunResult>,
     scenario: &Scenario,
+    provenance: &Provenance,
     run_id: RunId,
     run_started: &Instant,
     progress: &Option<Arc<dyn ProgressCallback>>,
     artifacts: &mut Option<ArtifactsWriter>,
-    policy_for_error: &Option<Policy>,
+    policy_for_error: &Option<(Policy, Vec<PolicyGrant>)>,
 ) {
     if let Err(err) = result {
         emit_progress(
@@ -1141,9 +2722,9 @@ fn handle_scenario_result(
         );
 
         if let Some(writer) = artifacts.as_mut() {
-            let policy = policy_for_error.clone().unwrap_or_default();
+            let (policy, policy_grants) = policy_for_error.clone().unwrap_or_default();
             let _ = writer.write_policy(&policy);
-            let run_result = RunResult {
+            let mut run_result = RunResult {
                 run_result_version: 1,
                 protocol_version: PROTOCOL_VERSION,
                 run_id,
@@ -1153,13 +2734,24 @@ fn handle_scenario_result(
                 command: scenario.run.command.clone(),
                 args: scenario.run.args.clone(),
                 cwd: get_cwd_string(scenario.run.cwd.clone(), policy.fs.working_dir.as_ref()),
+                determinism: policy.determinism.pinned(),
                 policy,
                 scenario: Some(scenario.clone()),
                 steps: None,
                 final_observation: None,
                 exit_status: None,
                 error: Some(err.to_error_info()),
+                tags: scenario.metadata.tags.clone(),
+                enforcement: None,
+                provenance: Some(provenance.clone()),
+                classification: None,
+                post_run: Vec::new(),
+                risk_summary: None,
+                fixtures: Vec::new(),
+                policy_grants,
+                keymap: None,
             };
+            run_result.classification = classify_failure(&run_result);
             let _ = writer.write_run_result(&run_result);
         }
     }
@@ -1214,18 +2806,39 @@ pub fn run_exec_with_options(
     let run_started = Instant::now();
     let mut artifacts: Option<ArtifactsWriter> = None;
     let mut cleanup_guard = SandboxCleanupGuard::new(None);
-
-    let result = run_exec_inner(
-        &command,
-        &args,
-        &cwd,
-        &policy,
-        &options,
-        run_id,
-        &run_started,
-        &mut artifacts,
-        &mut cleanup_guard,
-    );
+    let provenance = run_provenance(None, options.git_sha.as_deref());
+    #[cfg(feature = "otel")]
+    let recorder = options
+        .otel
+        .clone()
+        .map(|config| crate::otel::TraceRecorder::start(config, None));
+    #[cfg(feature = "log-forward")]
+    if let Some(config) = &options.log_forward {
+        config.check_policy(&policy)?;
+    }
+    #[cfg(feature = "log-forward")]
+    let (forwarder, options, _) =
+        crate::log_forward::LogForwarder::attach(options, None, run_id, &command);
+
+    let mut watchdog = Watchdog::default();
+
+    let result = catch_panic("run", || {
+        run_exec_inner(
+            &command,
+            &args,
+            &cwd,
+            &policy,
+            &options,
+            &provenance,
+            run_id,
+            &run_started,
+            &mut artifacts,
+            &mut cleanup_guard,
+            &mut watchdog,
+        )
+    })
+    .and_then(|result| result);
+    let result = apply_watchdog(result, &mut watchdog, &mut artifacts);
 
     handle_exec_error(
         &result,
@@ -1235,9 +2848,20 @@ pub fn run_exec_with_options(
         &policy,
         run_id,
         &run_started,
+        &options.tags,
+        &provenance,
         &mut artifacts,
     );
     drop(cleanup_guard);
+    reporters::write_reports(&options.reporters, &result, &command);
+    #[cfg(feature = "otel")]
+    if let Some(recorder) = recorder {
+        recorder.export(&result, &command);
+    }
+    #[cfg(feature = "log-forward")]
+    if let Some(forwarder) = forwarder {
+        forwarder.finish(&result);
+    }
     result
 }
 
@@ -1249,35 +2873,83 @@ fn run_exec_inner(
     cwd: &Option<String>,
     policy: &Policy,
     options: &RunnerOptions,
+    provenance: &Provenance,
     run_id: RunId,
     run_started: &Instant,
     artifacts: &mut Option<ArtifactsWriter>,
     cleanup_guard: &mut SandboxCleanupGuard,
+    watchdog: &mut Watchdog,
 ) -> RunnerResult<RunResult> {
+    validate_git_sha(options.git_sha.as_deref())?;
     let artifacts_dir = setup_exec_artifacts(policy, options, run_id, artifacts)?;
-    validate_policy(policy)?;
+    if let Some(writer) = artifacts.as_mut() {
+        writer.journal(
+            elapsed_ms(run_started),
+            JournalEvent::RunStarted {
+                run_id,
+                command: command.to_string(),
+                args: args.to_vec(),
+                cwd: get_cwd_string(cwd.clone(), policy.fs.working_dir.as_ref()),
+                tags: merge_tags(&[], &options.tags),
+            },
+        )?;
+    }
+    let enforcement = validate_policy(policy)?;
 
     let effective_cwd = cwd.clone().or_else(|| policy.fs.working_dir.clone());
     validate_exec_config(command, args, &effective_cwd, policy)?;
 
-    if let Some(writer) = artifacts.as_mut() {
-        writer.write_policy(policy)?;
-    }
+    let acknowledgements = acknowledgement_report(policy, &options.ack_overrides);
+    write_policy_artifacts(
+        artifacts,
+        policy,
+        enforcement.as_ref(),
+        acknowledgements.as_ref(),
+    )?;
 
+    let spawn_policy = fallback_spawn_policy(policy, enforcement.as_ref());
     let mut session = spawn_exec_session(
         command,
         args,
         &effective_cwd,
-        policy,
+        &spawn_policy,
         &artifacts_dir,
+        artifacts,
         run_id,
+        options.socket_handler.as_ref(),
         cleanup_guard,
     )?;
+    watchdog.arm(session.process_id(), *run_started, policy);
+    if let Some(tap) = &options.observation_tap {
+        session.set_observation_tap(Arc::clone(tap));
+    }
+    #[cfg(feature = "emulator-diff")]
+    if options.emulator_diff {
+        session.enable_emulator_diff();
+    }
+    #[cfg(feature = "sandbox-audit")]
+    let audit = crate::policy::audit::SandboxAudit::start(
+        options.sandbox_audit,
+        &spawn_policy,
+        session.process_id(),
+        artifacts.is_some(),
+    );
+    arm_write_quotas(&mut session, policy, artifacts_dir.as_ref());
     let deadline = Instant::now() + Duration::from_millis(policy.budgets.max_runtime_ms);
-    let (final_observation, exit_status) =
-        poll_exec_until_exit(&mut session, policy, artifacts, deadline)?;
+    let polled = poll_exec_until_exit(&mut session, policy, artifacts, deadline, watchdog);
+    watchdog.disarm();
+    let quota_error = finish_write_quotas(&mut session, artifacts)?;
+    let (final_observation, exit_status) = polled?;
+    if let Some(err) = quota_error {
+        return Err(err);
+    }
+    write_fs_manifest(policy, artifacts_dir.as_ref(), artifacts)?;
+    #[cfg(feature = "sandbox-audit")]
+    audit.finish(artifacts.as_mut())?;
+    #[cfg(feature = "emulator-diff")]
+    write_emulator_diff(&session, artifacts)?;
 
-    let run_result = build_exec_result(
+    let mut run_result = build_exec_result(
         command,
         args,
         &effective_cwd,
@@ -1287,13 +2959,15 @@ fn run_exec_inner(
         final_observation,
         exit_status,
     );
+    run_result.tags = merge_tags(&[], &options.tags);
+    run_result.enforcement = enforcement;
+    run_result.risk_summary = acknowledgements.map(|report| report.summary);
+    run_result.provenance = Some(provenance.clone());
+    run_result.classification = classify_failure(&run_result);
 
     if let Some(writer) = artifacts.as_mut() {
         if let Some(obs) = &run_result.final_observation {
             writer.write_snapshot(&obs.screen)?;
-            if let Some(delta) = &obs.transcript_delta {
-                writer.write_transcript(delta)?;
-            }
         }
         writer.write_run_result(&run_result)?;
         writer.flush_checksums()?;
@@ -1318,7 +2992,14 @@ fn setup_exec_artifacts(
 
     if let Some(config) = artifacts_config {
         validate_artifacts_dir(&config.dir, &policy.fs)?;
-        let mut writer = ArtifactsWriter::new(run_id, config)?;
+        let mut writer = open_artifacts_writer(policy, options, run_id, config)?;
+        writer.set_transcript(&policy.artifacts.transcript);
+        writer.set_canonical_json(policy.artifacts.canonical_json);
+        writer.set_asciicast(policy.artifacts.asciicast);
+        if let Some(encryption) = &policy.artifacts.encryption {
+            writer.set_encryption(encryption)?;
+        }
+        configure_run_index(&mut writer, policy, options);
         writer.write_normalization(&NormalizationRecord {
             normalization_version: NORMALIZATION_VERSION,
             filters: Vec::new(),
@@ -1347,32 +3028,62 @@ fn validate_exec_config(
         cwd: cwd.clone(),
         initial_size: TerminalSize::default(),
         policy: crate::model::scenario::PolicyRef::Inline(Box::new(policy.clone())),
+        separate_stderr: false,
+        output_buffer: OutputBufferConfig::default(),
+        resize: ResizeConfig::default(),
+        remote: None,
+        validate_args: false,
+        capture_attributes: false,
     };
     effective_policy.validate_run_config(&run_config)
 }
 
-/// Spawn a session for exec command.
-#[allow(clippy::ref_option)]
+/// Spawn a session for exec command, recording a container invocation in
+/// `artifacts`.
+#[allow(clippy::too_many_arguments, clippy::ref_option)]
 fn spawn_exec_session(
     command: &str,
     args: &[String],
     cwd: &Option<String>,
     policy: &Policy,
     artifacts_dir: &Option<PathBuf>,
+    artifacts: &mut Option<ArtifactsWriter>,
     run_id: RunId,
+    socket_handler: Option<&Arc<dyn SocketHandler>>,
     cleanup_guard: &mut SandboxCleanupGuard,
 ) -> RunnerResult<Session> {
-    let spawn = build_spawn_command(policy, command, args, artifacts_dir.as_ref(), run_id)?;
+    let mut spawn = build_spawn_command(
+        policy,
+        command,
+        args,
+        cwd.as_deref(),
+        artifacts_dir.as_ref(),
+        run_id,
+    )?;
     cleanup_guard.path = spawn.cleanup_path.clone();
+    cleanup_guard.container = spawn.container.take();
+    if let (Some(writer), Some(invocation)) = (artifacts.as_mut(), &spawn.invocation) {
+        writer.write_container_invocation(invocation)?;
+    }
+    let env = bind_policy_sockets(policy, run_id, socket_handler, cleanup_guard)?;
 
-    Session::spawn(SessionConfig {
+    let mut session = Session::spawn(SessionConfig {
         command: spawn.command,
         args: spawn.args,
         cwd: cwd.clone(),
         size: TerminalSize::default(),
         run_id,
-        env: policy.env.clone(),
-    })
+        env,
+        separate_stderr: false,
+        output_buffer: OutputBufferConfig::default(),
+        resize: ResizeConfig::default(),
+        remote: None,
+        encoding: policy.terminal.encoding,
+    })?;
+    if let Some(container) = &cleanup_guard.container {
+        session.set_container(container.clone());
+    }
+    Ok(session)
 }
 
 /// Build the run result for exec command.
@@ -1418,6 +3129,16 @@ fn build_exec_result(
         final_observation: Some(final_observation),
         exit_status: Some(exit_status),
         error,
+        tags: Vec::new(),
+        enforcement: None,
+        provenance: None,
+        classification: None,
+        post_run: Vec::new(),
+        risk_summary: None,
+        fixtures: Vec::new(),
+        policy_grants: Vec::new(),
+        keymap: None,
+        determinism: policy.determinism.pinned(),
     }
 }
 
@@ -1431,12 +3152,14 @@ fn handle_exec_error(
     policy: &Policy,
     run_id: RunId,
     run_started: &Instant,
+    tags: &[String],
+    provenance: &Provenance,
     artifacts: &mut Option<ArtifactsWriter>,
 ) {
     if let Err(err) = result {
         if let Some(writer) = artifacts.as_mut() {
             let _ = writer.write_policy(policy);
-            let run_result = RunResult {
+            let mut run_result = RunResult {
                 run_result_version: 1,
                 protocol_version: PROTOCOL_VERSION,
                 run_id,
@@ -1452,23 +3175,23 @@ fn handle_exec_error(
                 final_observation: None,
                 exit_status: None,
                 error: Some(err.to_error_info()),
+                tags: tags.to_vec(),
+                enforcement: None,
+                provenance: Some(provenance.clone()),
+                classification: None,
+                post_run: Vec::new(),
+                risk_summary: None,
+                fixtures: Vec::new(),
+                policy_grants: Vec::new(),
+                keymap: None,
+                determinism: policy.determinism.pinned(),
             };
+            run_result.classification = classify_failure(&run_result);
             let _ = writer.write_run_result(&run_result);
         }
     }
 }
 
-fn validate_policy(policy: &Policy) -> RunnerResult<()> {
-    validate_policy_version(policy)?;
-    validate_sandbox_mode(&policy.sandbox)?;
-    validate_network_policy(policy)?;
-    validate_env_policy(&policy.env)?;
-    validate_fs_policy(&policy.fs)?;
-    validate_artifacts_policy(policy)?;
-    validate_write_access(policy, None)?;
-    Ok(())
-}
-
 /// Load a scenario from a file.
 ///
 /// Supports both JSON and YAML formats (detected by extension).
@@ -1482,125 +3205,6 @@ pub fn load_scenario(path: &str) -> RunnerResult<Scenario> {
     crate::scenario::load_scenario_file(path)
 }
 
-fn perform_action(
-    session: &mut Session,
-    action: &Action,
-    timeout: Duration,
-    policy: &Policy,
-) -> RunnerResult<crate::model::Observation> {
-    match action.action_type {
-        ActionType::Wait => wait_for_condition(session, action, timeout, policy),
-        ActionType::Terminate => {
-            session.terminate()?;
-            session.observe(Duration::from_millis(10))
-        }
-        _ => {
-            session.send(action)?;
-            session.observe(timeout)
-        }
-    }
-}
-
-#[derive(Debug, Deserialize)]
-struct WaitPayload {
-    condition: Condition,
-}
-
-#[derive(Debug, Deserialize)]
-struct Condition {
-    #[serde(rename = "type")]
-    condition_type: String,
-    #[serde(default)]
-    payload: Value,
-}
-
-fn wait_for_condition(
-    session: &mut Session,
-    action: &Action,
-    timeout: Duration,
-    policy: &Policy,
-) -> RunnerResult<crate::model::Observation> {
-    let wait_payload: WaitPayload = serde_json::from_value(action.payload.clone())
-        .map_err(|e| {
-            RunnerError::protocol(
-                "E_PROTOCOL",
-                "invalid wait action payload",
-                serde_json::json!({
-                    "parse_error": e.to_string(),
-                    "received_payload": action.payload,
-                    "expected": {
-                        "condition": {
-                            "type": "screen_contains | screen_matches | cursor_at | process_exited",
-                            "payload": "object (varies by condition type)"
-                        }
-                    },
-                    "examples": {
-                        "screen_contains": {"condition": {"type": "screen_contains", "payload": {"text": "Ready"}}},
-                        "screen_matches": {"condition": {"type": "screen_matches", "payload": {"pattern": "\\$\\s*$"}}},
-                        "cursor_at": {"condition": {"type": "cursor_at", "payload": {"row": 0, "col": 0}}},
-                        "process_exited": {"condition": {"type": "process_exited", "payload": {}}}
-                    }
-                }),
-            )
-        })?;
-    let max_wait = Duration::from_millis(policy.budgets.max_wait_ms);
-    let wait_timeout = if timeout > max_wait {
-        max_wait
-    } else {
-        timeout
-    };
-    let deadline = Instant::now() + wait_timeout;
-
-    // Pre-compile regex before the loop to avoid recompilation on each iteration
-    let compiled_regex = if wait_payload.condition.condition_type == "screen_matches" {
-        let pattern = wait_payload
-            .condition
-            .payload
-            .get("pattern")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
-        Some(compile_safe_regex(pattern)?)
-    } else {
-        None
-    };
-
-    loop {
-        if Instant::now() > deadline {
-            return Err(RunnerError::timeout(
-                "E_TIMEOUT",
-                "wait condition timed out",
-                serde_json::json!({"condition": wait_payload.condition.condition_type}),
-            ));
-        }
-
-        let observation = session.observe(Duration::from_millis(50))?;
-
-        // Check for unexpected process exit for all condition types
-        if session.wait_for_exit(Duration::from_millis(0))?.is_some() {
-            if wait_payload.condition.condition_type == "process_exited" {
-                return Ok(observation);
-            }
-            // Process exited unexpectedly while waiting for another condition
-            return Err(RunnerError::process_exit(
-                "E_PROCESS_EXIT",
-                "process exited during wait",
-            ));
-        }
-
-        let screen_text = observation.screen.lines.join("\n");
-        if condition_satisfied(
-            &observation,
-            &screen_text,
-            &wait_payload.condition,
-            compiled_regex.as_ref(),
-        )? {
-            return Ok(observation);
-        }
-
-        pause_until(deadline, Duration::from_millis(10));
-    }
-}
-
 fn action_type_label(action_type: &ActionType) -> &'static str {
     match action_type {
         ActionType::Key => "key",
@@ -1609,6 +3213,12 @@ fn action_type_label(action_type: &ActionType) -> &'static str {
         ActionType::Wait => "wait",
         ActionType::Observe => "observe",
         ActionType::Terminate => "terminate",
+        ActionType::Raw => "raw",
+        ActionType::HoldKey => "hold_key",
+        ActionType::Termios => "termios",
+        ActionType::Signal => "signal",
+        ActionType::History => "history",
+        ActionType::Subscribe => "subscribe",
     }
 }
 
@@ -1635,117 +3245,19 @@ fn with_step_timeout_context(err: RunnerError, step: &crate::model::Step) -> Run
     RunnerError::with_context(err.code, err.message, step_context(step, details))
 }
 
-fn condition_satisfied(
-    observation: &crate::model::Observation,
-    screen_text: &str,
-    condition: &Condition,
-    compiled_regex: Option<&regex::Regex>,
-) -> RunnerResult<bool> {
-    match condition.condition_type.as_str() {
-        "screen_contains" => {
-            let text = condition
-                .payload
-                .get("text")
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| {
-                    RunnerError::protocol(
-                        "E_PROTOCOL",
-                        "screen_contains condition requires 'text' field",
-                        Some(serde_json::json!({ "received_payload": condition.payload })),
-                    )
-                })?;
-            Ok(screen_text.contains(text))
-        }
-        "screen_matches" => {
-            if let Some(re) = compiled_regex {
-                Ok(re.is_match(screen_text))
-            } else {
-                let pattern = condition
-                    .payload
-                    .get("pattern")
-                    .and_then(|v| v.as_str())
-                    .ok_or_else(|| {
-                        RunnerError::protocol(
-                            "E_PROTOCOL",
-                            "screen_matches condition requires 'pattern' field",
-                            Some(serde_json::json!({ "received_payload": condition.payload })),
-                        )
-                    })?;
-                let re = compile_safe_regex(pattern)?;
-                Ok(re.is_match(screen_text))
-            }
-        }
-        "cursor_at" => {
-            let row_u64 = condition
-                .payload
-                .get("row")
-                .and_then(|v| v.as_u64())
-                .ok_or_else(|| {
-                    RunnerError::protocol(
-                        "E_PROTOCOL",
-                        "cursor_at condition requires 'row' field",
-                        Some(serde_json::json!({ "received_payload": condition.payload })),
-                    )
-                })?;
-            let col_u64 = condition
-                .payload
-                .get("col")
-                .and_then(|v| v.as_u64())
-                .ok_or_else(|| {
-                    RunnerError::protocol(
-                        "E_PROTOCOL",
-                        "cursor_at condition requires 'col' field",
-                        Some(serde_json::json!({ "received_payload": condition.payload })),
-                    )
-                })?;
-            let row = u16::try_from(row_u64).map_err(|_| {
-                RunnerError::protocol(
-                    "E_PROTOCOL",
-                    format!("row value {row_u64} exceeds maximum u16 value {}", u16::MAX),
-                    Some(serde_json::json!({ "received": row_u64, "max": u16::MAX })),
-                )
-            })?;
-            let col = u16::try_from(col_u64).map_err(|_| {
-                RunnerError::protocol(
-                    "E_PROTOCOL",
-                    format!("col value {col_u64} exceeds maximum u16 value {}", u16::MAX),
-                    Some(serde_json::json!({ "received": col_u64, "max": u16::MAX })),
-                )
-            })?;
-            Ok(observation.screen.cursor.row == row && observation.screen.cursor.col == col)
-        }
-        "process_exited" => Ok(false),
-        other => Err(RunnerError::protocol(
-            "E_PROTOCOL",
-            format!("unsupported wait condition '{other}'"),
-            Some(serde_json::json!({
-                "received": other,
-                "supported_conditions": ["screen_contains", "screen_matches", "cursor_at", "process_exited"]
-            })),
-        )),
-    }
-}
-
 fn enforce_exec_budgets(
     session: &mut Session,
     observation: &crate::model::Observation,
     output_bytes: &mut u64,
     policy: &Policy,
 ) -> RunnerResult<()> {
-    *output_bytes += observation
-        .transcript_delta
-        .as_ref()
-        .map(|s| s.len() as u64)
-        .unwrap_or(0);
+    *output_bytes += observation.output_bytes();
     if *output_bytes > policy.budgets.max_output_bytes {
-        let termination = session.terminate_process_group(Duration::from_millis(200));
-        let context = match termination {
-            Ok(_) => serde_json::json!({"max_output_bytes": policy.budgets.max_output_bytes}),
-            Err(err) => serde_json::json!({
-                "max_output_bytes": policy.budgets.max_output_bytes,
-                "termination_error": err.to_string()
-            }),
-        };
+        let context = terminate_for_budget(
+            session,
+            policy,
+            serde_json::json!({"max_output_bytes": policy.budgets.max_output_bytes}),
+        );
         return Err(RunnerError::timeout(
             "E_TIMEOUT",
             "output budget exceeded",
@@ -1754,14 +3266,11 @@ fn enforce_exec_budgets(
     }
 
     if snapshot_bytes(&observation.screen)? > policy.budgets.max_snapshot_bytes {
-        let termination = session.terminate_process_group(Duration::from_millis(200));
-        let context = match termination {
-            Ok(_) => serde_json::json!({"max_snapshot_bytes": policy.budgets.max_snapshot_bytes}),
-            Err(err) => serde_json::json!({
-                "max_snapshot_bytes": policy.budgets.max_snapshot_bytes,
-                "termination_error": err.to_string()
-            }),
-        };
+        let context = terminate_for_budget(
+            session,
+            policy,
+            serde_json::json!({"max_snapshot_bytes": policy.budgets.max_snapshot_bytes}),
+        );
         return Err(RunnerError::timeout(
             "E_TIMEOUT",
             "snapshot budget exceeded",
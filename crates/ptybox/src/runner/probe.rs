@@ -0,0 +1,161 @@
+//! Argument probe run before a scenario's session (`run.validate_args`).
+//!
+//! The command runs once with `--help` and once with `--version`, spawned
+//! the way the run will be (sandbox, env, working directory, terminal size).
+//! Each probe's output and exit code go to `args-probe.json`, along with the
+//! `run.args` flags that `--help` does not mention, so a flag the app no
+//! longer accepts shows up before the session starts. A command that exits
+//! non-zero for a probe is only recorded; a probe that cannot execute fails
+//! the run with `E_PROCESS_EXIT`.
+
+use super::{ErrorCode, RunnerError, RunnerResult};
+use crate::artifacts::ArtifactsWriter;
+use crate::model::policy::Policy;
+use crate::model::{ArgsProbe, ArgsProbeRecord, RunId, Scenario};
+use crate::session::{Session, SessionConfig};
+use crate::util::{build_spawn_command, convert_exit_status, SandboxCleanupGuard};
+use std::time::{Duration, Instant};
+
+/// Flags each probe passes, in order.
+const PROBE_FLAGS: [&str; 2] = ["--help", "--version"];
+
+/// Milliseconds a probe may run before it is stopped, capped by the
+/// runtime budget.
+const ARGS_PROBE_TIMEOUT_MS: u64 = 5_000;
+
+/// Exit codes shells and container runtimes use for a command that could
+/// not be executed or was not found.
+const NOT_EXECUTABLE_EXIT_CODES: [i32; 2] = [126, 127];
+
+/// Run the probes for `scenario` under `policy` and write `args-probe.json`.
+///
+/// # Errors
+/// - `E_PROCESS_EXIT`: A probe could not be spawned or its command could
+///   not be executed
+/// - `E_IO`: The record cannot be written
+pub(super) fn probe_args(
+    scenario: &Scenario,
+    policy: &Policy,
+    run_id: RunId,
+    artifacts: &mut Option<ArtifactsWriter>,
+) -> RunnerResult<ArgsProbeRecord> {
+    let timeout = Duration::from_millis(ARGS_PROBE_TIMEOUT_MS.min(policy.budgets.max_runtime_ms));
+    let mut record = ArgsProbeRecord::default();
+    for flag in PROBE_FLAGS {
+        record
+            .probes
+            .push(run_probe(scenario, policy, run_id, flag, timeout)?);
+    }
+    if let Some(help) = record
+        .probes
+        .iter()
+        .find(|probe| probe.flag == "--help" && !probe.timed_out)
+    {
+        record.missing_flags = missing_flags(&scenario.run.args, &help.output);
+    }
+    if let Some(writer) = artifacts.as_mut() {
+        writer.write_args_probe(&record)?;
+    }
+    Ok(record)
+}
+
+fn run_probe(
+    scenario: &Scenario,
+    policy: &Policy,
+    run_id: RunId,
+    flag: &str,
+    timeout: Duration,
+) -> RunnerResult<ArgsProbe> {
+    let command = &scenario.run.command;
+    let cwd = scenario
+        .run
+        .cwd
+        .clone()
+        .or_else(|| policy.fs.working_dir.clone());
+    let mut guard = SandboxCleanupGuard::new(None);
+    let mut spawn = build_spawn_command(
+        policy,
+        command,
+        &[flag.to_string()],
+        cwd.as_deref(),
+        None,
+        run_id,
+    )?;
+    guard.path = spawn.cleanup_path.clone();
+    guard.container = spawn.container.take();
+    let mut session = Session::spawn(SessionConfig {
+        command: spawn.command,
+        args: spawn.args,
+        cwd,
+        size: scenario.run.initial_size.clone(),
+        run_id,
+        env: policy.effective_env(),
+        separate_stderr: false,
+        output_buffer: scenario.run.output_buffer,
+        resize: scenario.run.resize,
+        remote: None,
+        encoding: policy.terminal.encoding,
+    })
+    .map_err(|err| not_executable(command, flag, serde_json::json!({ "error": err.message })))?;
+
+    let deadline = Instant::now() + timeout;
+    let mut output = String::new();
+    let mut status = None;
+    while status.is_none() && Instant::now() < deadline {
+        let observation = session.observe(Duration::from_millis(50))?;
+        output.push_str(observation.transcript_delta.as_deref().unwrap_or_default());
+        status = session.wait_for_exit(Duration::from_millis(0))?;
+    }
+    if status.is_some() {
+        // Output written just before exit may still be in the reader.
+        let observation = session.observe(Duration::from_millis(10))?;
+        output.push_str(observation.transcript_delta.as_deref().unwrap_or_default());
+    }
+    let timed_out = status.is_none();
+    let exit_code = match status {
+        Some(status) => convert_exit_status(status, false).exit_code,
+        None => {
+            let _ = session.close(&policy.process);
+            None
+        }
+    };
+    if let Some(code) = exit_code.filter(|code| NOT_EXECUTABLE_EXIT_CODES.contains(code)) {
+        return Err(not_executable(
+            command,
+            flag,
+            serde_json::json!({ "exit_code": code, "output": output }),
+        ));
+    }
+    Ok(ArgsProbe {
+        flag: flag.to_string(),
+        exit_code,
+        timed_out,
+        output,
+    })
+}
+
+/// Flags in `args` (the part before any `=`) that `help` never mentions.
+fn missing_flags(args: &[String], help: &str) -> Vec<String> {
+    args.iter()
+        .filter(|arg| arg.starts_with('-') && *arg != "-" && *arg != "--")
+        .map(|arg| arg.split('=').next().unwrap_or(arg).to_string())
+        .filter(|flag| !help.contains(flag.as_str()))
+        .collect()
+}
+
+fn not_executable(command: &str, flag: &str, details: serde_json::Value) -> RunnerError {
+    let mut context = serde_json::json!({
+        "command": command,
+        "flag": flag,
+        "fix": "Check run.command and that the policy lets it run, or set run.validate_args to false"
+    });
+    if let (Some(context), serde_json::Value::Object(details)) = (context.as_object_mut(), details)
+    {
+        context.extend(details);
+    }
+    RunnerError::with_context(
+        ErrorCode::ProcessExit,
+        format!("argument probe could not execute '{command} {flag}'"),
+        context,
+    )
+}
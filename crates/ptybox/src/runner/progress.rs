@@ -35,6 +35,8 @@ pub enum ProgressEvent {
         duration_ms: u64,
         /// Assertion results if any.
         assertions: Vec<AssertionResult>,
+        /// Output bytes (PTY and stderr) read during the step.
+        output_bytes: u64,
     },
     /// Run has completed.
     RunCompleted {
@@ -50,7 +52,8 @@ pub enum ProgressEvent {
 /// Trait for receiving progress events during execution.
 ///
 /// Implementors can use this to display progress, log events, or collect metrics.
-pub trait ProgressCallback: Send {
+/// Callbacks are shared behind an `Arc`, so they must be `Sync` as well.
+pub trait ProgressCallback: Send + Sync {
     /// Called for each progress event.
     fn on_progress(&self, event: &ProgressEvent);
 }
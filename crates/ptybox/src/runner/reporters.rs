@@ -0,0 +1,62 @@
+//! Reports written when a run finishes (`RunnerOptions::reporters`).
+//!
+//! Each [`Reporter`] renders the run's result, or the error it ended with,
+//! to its own file once the run's artifacts are written. Reports are
+//! harness output rather than artifacts: they are written outside the
+//! policy's write allowlist, and a report that cannot be written is logged
+//! without changing the run's outcome.
+
+use crate::model::{ErrorInfo, RunResult};
+use crate::report::junit_xml;
+use crate::runner::RunnerResult;
+use std::path::{Path, PathBuf};
+
+/// A report written when a run finishes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Reporter {
+    /// JUnit XML (see [`junit_xml`]) written to this path: the run is a
+    /// test suite named after the scenario, and each step a test case.
+    Junit(PathBuf),
+}
+
+impl Reporter {
+    /// File the report is written to.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Junit(path) => path,
+        }
+    }
+
+    fn render(&self, result: Result<&RunResult, &ErrorInfo>, suite: &str) -> String {
+        match self {
+            Self::Junit(_) => junit_xml(result, suite),
+        }
+    }
+}
+
+/// Write every report for `result`, naming the suite `suite`.
+pub(super) fn write_reports(reporters: &[Reporter], result: &RunnerResult<RunResult>, suite: &str) {
+    if reporters.is_empty() {
+        return;
+    }
+    let error_info;
+    let outcome = match result {
+        Ok(run) => Ok(run),
+        Err(err) => {
+            error_info = err.to_error_info();
+            Err(&error_info)
+        }
+    };
+    for reporter in reporters {
+        let path = reporter.path();
+        let written = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| std::fs::write(path, reporter.render(outcome, suite)));
+        if let Err(err) = written {
+            tracing::warn!(path = %path.display(), error = %err, "failed to write run report");
+        }
+    }
+}
@@ -0,0 +1,20 @@
+//! Serving the Unix sockets a policy creates for the child.
+//!
+//! `policy.sockets` lets a TUI reach a local mock service while network
+//! access stays disabled. ptybox binds the sockets before spawning and
+//! passes the listening ends to a [`SocketHandler`] supplied by the
+//! embedding program, which accepts and answers connections itself.
+
+use std::os::unix::net::UnixListener;
+
+/// Embedder-side peer for the sockets granted by `policy.sockets`.
+pub trait SocketHandler: Send + Sync {
+    /// Take the listening end of socket `name`.
+    ///
+    /// Called once per socket, before the child is spawned, on the thread
+    /// running the scenario. Implementations should hand the listener to
+    /// their own thread rather than accept on the caller's. The socket file
+    /// is removed when the run ends; accepted connections stay open until
+    /// the handler closes them.
+    fn serve(&self, name: &str, listener: UnixListener);
+}
@@ -0,0 +1,290 @@
+//! Run a directory of scenarios as one suite (`ptybox run-suite`).
+//!
+//! [`run_suite`] discovers the scenario files under [`SuiteConfig::paths`],
+//! runs each with [`run_scenario`], and aggregates their outcomes into a
+//! [`SuiteResult`]. Scenarios run in path order, or on up to
+//! [`SuiteConfig::jobs`] threads at once; the result lists them in path
+//! order either way. A scenario that cannot be loaded or errors is recorded
+//! as `errored` and does not stop the others.
+//!
+//! With [`SuiteConfig::changed_files`], only the scenarios
+//! [`select_scenarios`] picks for the change run; the selection, with each
+//! scenario's reason, is recorded in [`SuiteResult::selection`].
+//!
+//! With a locale/timezone matrix ([`SuiteConfig::matrix`]) each scenario runs
+//! once per cell with that cell's [`DeterminismPolicy`] pinned over its
+//! policy, as `ptybox serve --queue` runs jobs.
+//!
+//! With an artifacts root, scenario `n` (1-based, in path order) writes its
+//! artifacts to `<root>/<nnn>-<name>/` (`<root>/<nnn>-<name>/<cell>/` with a
+//! matrix), runs are indexed in the root's `runs.jsonl`, and the suite
+//! summary is written to `<root>/suite.json`.
+
+use super::{elapsed_ms, run_scenario, ErrorCode, RunnerError, RunnerOptions, RunnerResult};
+use crate::artifacts::{atomic_write, select_scenarios, step_dir_name, ArtifactsWriterConfig};
+use crate::model::policy::DeterminismPolicy;
+use crate::model::scenario::PolicyRef;
+use crate::model::Scenario;
+use crate::model::{
+    ImpactMap, RunStatus, ScenarioSelection, StepStatus, SuiteResult, SuiteScenarioResult,
+    SuiteSelection, SUITE_RESULT_VERSION,
+};
+use crate::policy::validate_determinism_policy;
+use crate::scenario::{load_policy_ref, load_scenario_file};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::Instant;
+
+/// Suite summary file name, relative to the suite's artifacts root.
+pub const SUITE_RESULT_FILE: &str = "suite.json";
+
+/// Scenario files to run as a suite, and how (see [`run_suite`]).
+#[derive(Clone, Debug, Default)]
+pub struct SuiteConfig {
+    /// Scenario files, or directories searched for `.json`, `.yaml`, and
+    /// `.yml` scenario files.
+    pub paths: Vec<PathBuf>,
+    /// Scenarios run at once; `0` is treated as `1`.
+    pub jobs: usize,
+    /// Root holding each scenario's artifacts directory, the run index, and
+    /// `suite.json`.
+    pub artifacts: Option<PathBuf>,
+    /// Overwrite existing scenario artifacts directories and `suite.json`.
+    pub overwrite: bool,
+    /// Files the change under test touched. When set, only the scenarios
+    /// they affect under `impact_map` run (see [`select_suite`]).
+    pub changed_files: Option<Vec<String>>,
+    /// Watched globs per scenario used with `changed_files`; without one
+    /// every scenario is selected.
+    pub impact_map: Option<ImpactMap>,
+    /// Options each scenario runs with. `artifacts` and `run_index` are set
+    /// per scenario from the artifacts root; `reporters` are not used, as
+    /// every scenario would write the same files.
+    pub options: RunnerOptions,
+    /// Locale/timezone cells each scenario runs under (see
+    /// [`DeterminismPolicy::matrix`]). Empty runs each scenario once as
+    /// written.
+    pub matrix: Vec<DeterminismPolicy>,
+}
+
+/// The scenarios in `config.paths` and whether each runs.
+///
+/// Without `changed_files` every scenario is selected, with reason
+/// `no_impact_map`. This is what `ptybox run-suite --dry-run` prints.
+///
+/// # Errors
+/// - `E_IO`: A path does not exist or a directory cannot be read
+pub fn select_suite(config: &SuiteConfig) -> RunnerResult<SuiteSelection> {
+    select_scenarios(
+        &config.paths,
+        config.changed_files.as_deref().unwrap_or_default(),
+        config.impact_map.as_ref(),
+    )
+}
+
+/// Run the scenario files in `config.paths` selected by [`select_suite`]
+/// and write the suite summary.
+///
+/// Files that are not scenarios are skipped, as in
+/// [`estimate_scenarios`](crate::artifacts::estimate_scenarios). Scenarios
+/// keep their number in the whole suite when others are not selected, so
+/// their artifacts directories do not move between runs.
+///
+/// # Errors
+/// - `E_IO`: A path does not exist, a directory cannot be read, or
+///   `suite.json` cannot be written
+/// - `E_PROTOCOL`: No scenario files were found
+/// - `E_POLICY_DENIED`: A matrix cell pins an unsupported locale or
+///   timezone
+/// - `E_POLICY_DENIED`: `suite.json` exists and `overwrite` is false
+pub fn run_suite(config: &SuiteConfig) -> RunnerResult<SuiteResult> {
+    let suite_started = Instant::now();
+    for cell in &config.matrix {
+        validate_determinism_policy(cell)?;
+    }
+    let selection = select_suite(config)?;
+    if selection.scenarios.is_empty() {
+        return Err(RunnerError::with_context(
+            ErrorCode::Protocol,
+            "no scenario files found",
+            serde_json::json!({ "paths": config.paths }),
+        ));
+    }
+    let summary = config
+        .artifacts
+        .as_ref()
+        .map(|root| root.join(SUITE_RESULT_FILE));
+    if let Some(summary) = summary.as_ref().filter(|path| path.exists()) {
+        if !config.overwrite {
+            return Err(RunnerError::with_context(
+                ErrorCode::PolicyDenied,
+                "suite summary exists and overwrite is disabled",
+                serde_json::json!({ "path": summary }),
+            ));
+        }
+    }
+    if let Some(root) = &config.artifacts {
+        fs::create_dir_all(root)
+            .map_err(|err| RunnerError::io("E_IO", "failed to create suite artifacts root", err))?;
+    }
+
+    let runs = suite_runs(&selection, &config.matrix);
+    let jobs = config.jobs.clamp(1, runs.len().max(1));
+    let next = AtomicUsize::new(0);
+    let slots: Mutex<Vec<Option<SuiteScenarioResult>>> = Mutex::new(vec![None; runs.len()]);
+    std::thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                let Some(&(number, scenario, cell)) = runs.get(index) else {
+                    break;
+                };
+                let result = run_suite_scenario(config, number, scenario, cell);
+                if let Some(slot) = slots
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .get_mut(index)
+                {
+                    *slot = Some(result);
+                }
+            });
+        }
+    });
+    let scenarios: Vec<SuiteScenarioResult> = slots
+        .into_inner()
+        .unwrap_or_else(PoisonError::into_inner)
+        .into_iter()
+        .flatten()
+        .collect();
+
+    let count = |status: RunStatus| {
+        scenarios
+            .iter()
+            .filter(|scenario| scenario.status == status)
+            .count() as u64
+    };
+    let passed = count(RunStatus::Passed);
+    let result = SuiteResult {
+        suite_result_version: SUITE_RESULT_VERSION,
+        status: if passed == scenarios.len() as u64 {
+            RunStatus::Passed
+        } else {
+            RunStatus::Failed
+        },
+        jobs: jobs as u64,
+        duration_ms: elapsed_ms(&suite_started),
+        passed,
+        failed: count(RunStatus::Failed),
+        errored: count(RunStatus::Errored),
+        canceled: count(RunStatus::Canceled),
+        scenarios,
+        selection: config.changed_files.is_some().then_some(selection),
+        matrix: config.matrix.clone(),
+    };
+    if let Some(summary) = &summary {
+        let data = serde_json::to_vec_pretty(&result)
+            .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to serialize suite.json", err))?;
+        atomic_write(summary, &data)?;
+    }
+    Ok(result)
+}
+
+/// Each selected scenario with its number in the whole suite, once per
+/// matrix cell (or once without a matrix), in path order.
+fn suite_runs<'a>(
+    selection: &'a SuiteSelection,
+    matrix: &'a [DeterminismPolicy],
+) -> Vec<(usize, &'a ScenarioSelection, Option<&'a DeterminismPolicy>)> {
+    let cells: Vec<Option<&DeterminismPolicy>> = if matrix.is_empty() {
+        vec![None]
+    } else {
+        matrix.iter().map(Some).collect()
+    };
+    selection
+        .scenarios
+        .iter()
+        .enumerate()
+        .filter(|(_, scenario)| scenario.selected)
+        .flat_map(|(index, scenario)| cells.iter().map(move |cell| (index + 1, scenario, *cell)))
+        .collect()
+}
+
+/// Pin `cell`'s locale and timezone over the scenario policy, returning the
+/// effective determinism.
+fn apply_matrix_cell(
+    mut scenario: Scenario,
+    cell: &DeterminismPolicy,
+) -> RunnerResult<(Scenario, DeterminismPolicy)> {
+    let mut policy = load_policy_ref(&scenario.run.policy)?;
+    policy.determinism.apply(cell);
+    let determinism = policy.determinism.clone();
+    scenario.run.policy = PolicyRef::Inline(Box::new(policy));
+    Ok((scenario, determinism))
+}
+
+/// Load and run scenario `number` of the suite, under matrix `cell` if any.
+fn run_suite_scenario(
+    config: &SuiteConfig,
+    number: usize,
+    scenario: &ScenarioSelection,
+    cell: Option<&DeterminismPolicy>,
+) -> SuiteScenarioResult {
+    let started = Instant::now();
+    let mut determinism = cell.cloned();
+    let loaded = load_scenario_file(&scenario.path).and_then(|loaded| match cell {
+        Some(cell) => apply_matrix_cell(loaded, cell).map(|(loaded, effective)| {
+            determinism = Some(effective);
+            loaded
+        }),
+        None => Ok(loaded),
+    });
+    let artifacts_dir = config.artifacts.as_ref().map(|root| {
+        let dir = root.join(format!("{number:03}-{}", step_dir_name(&scenario.scenario)));
+        match &determinism {
+            Some(cell) => dir.join(cell.cell_label()),
+            None => dir,
+        }
+    });
+    let mut entry = SuiteScenarioResult {
+        scenario: scenario.scenario.clone(),
+        path: scenario.path.clone(),
+        status: RunStatus::Errored,
+        run_id: None,
+        duration_ms: 0,
+        steps: 0,
+        steps_passed: 0,
+        artifacts_dir: None,
+        determinism,
+        error: None,
+    };
+    let options = RunnerOptions {
+        artifacts: artifacts_dir.clone().map(|dir| ArtifactsWriterConfig {
+            dir,
+            overwrite: config.overwrite,
+        }),
+        run_index: config.artifacts.clone(),
+        reporters: Vec::new(),
+        ..config.options.clone()
+    };
+    match loaded.and_then(|scenario| run_scenario(scenario, options)) {
+        Ok(run) => {
+            let steps = run.steps.as_deref().unwrap_or_default();
+            entry.steps = steps.len() as u64;
+            entry.steps_passed = steps
+                .iter()
+                .filter(|step| step.status == StepStatus::Passed)
+                .count() as u64;
+            entry.status = run.status;
+            entry.run_id = Some(run.run_id);
+            entry.error = run.error;
+        }
+        Err(err) => entry.error = Some(err.to_error_info()),
+    }
+    entry.duration_ms = elapsed_ms(&started);
+    entry.artifacts_dir = artifacts_dir
+        .filter(|dir| dir.is_dir())
+        .map(|dir| dir.display().to_string());
+    entry
+}
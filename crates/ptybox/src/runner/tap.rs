@@ -0,0 +1,13 @@
+//! Observers of a running scenario's output.
+//!
+//! A tap sees every observation the session makes, including wait polling
+//! and stability windows, before watchers are checked. It cannot change
+//! what the runner records, so artifacts are the same with and without one.
+
+use crate::model::Observation;
+
+/// Receives each observation of a running scenario or exec run.
+pub trait ObservationTap: Send + Sync {
+    /// Called after every [`Session::observe`](crate::session::Session::observe).
+    fn on_observation(&self, observation: &Observation);
+}
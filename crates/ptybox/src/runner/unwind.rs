@@ -0,0 +1,98 @@
+//! Panic boundaries of the runner, the driver, and the FFI.
+//!
+//! [`catch_panic`] runs a body under `catch_unwind` and turns a panic into
+//! an `E_INTERNAL` [`RunnerError`] whose context carries the panic message,
+//! where it was raised, and a backtrace. A bug in ptybox then ends the step
+//! or run like any other error, with its artifacts written, instead of
+//! aborting the process.
+//!
+//! The location and backtrace come from a panic hook installed on first use
+//! and chained to the hook that was in place. Inside a boundary it records
+//! them for the boundary and prints nothing; everywhere else it defers to
+//! the previous hook.
+
+use super::{ErrorCode, RunnerError, RunnerResult};
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::cell::{Cell, RefCell};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Once;
+
+/// Backtrace lines kept in an error's context.
+const MAX_BACKTRACE_LINES: usize = 64;
+
+static HOOK: Once = Once::new();
+
+thread_local! {
+    /// Number of boundaries the current thread is inside.
+    static DEPTH: Cell<u32> = const { Cell::new(0) };
+    /// Recorded by the hook for the last panic inside a boundary.
+    static CAPTURED: RefCell<Option<CapturedPanic>> = const { RefCell::new(None) };
+}
+
+struct CapturedPanic {
+    location: Option<String>,
+    backtrace: Vec<String>,
+}
+
+/// Run `body`, converting a panic into an `E_INTERNAL` error.
+///
+/// `boundary` names what was running (`"step"`, `"run"`, `"driver
+/// action"`, ...) and is reported in the error's message and context.
+///
+/// # Errors
+/// `E_INTERNAL` if `body` panicked.
+pub(crate) fn catch_panic<T>(boundary: &str, body: impl FnOnce() -> T) -> RunnerResult<T> {
+    install_hook();
+    DEPTH.with(|depth| depth.set(depth.get().saturating_add(1)));
+    let result = catch_unwind(AssertUnwindSafe(body));
+    DEPTH.with(|depth| depth.set(depth.get().saturating_sub(1)));
+    result.map_err(|payload| panic_error(boundary, payload.as_ref()))
+}
+
+fn install_hook() {
+    HOOK.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if DEPTH.with(Cell::get) == 0 {
+                previous(info);
+                return;
+            }
+            let backtrace = Backtrace::force_capture().to_string();
+            let captured = CapturedPanic {
+                location: info.location().map(ToString::to_string),
+                backtrace: backtrace
+                    .lines()
+                    .map(str::trim)
+                    .take(MAX_BACKTRACE_LINES)
+                    .map(str::to_string)
+                    .collect(),
+            };
+            CAPTURED.with(|slot| *slot.borrow_mut() = Some(captured));
+        }));
+    });
+}
+
+fn panic_error(boundary: &str, payload: &(dyn Any + Send)) -> RunnerError {
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|message| (*message).to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "panic with a non-string payload".to_string());
+    let (location, backtrace) = CAPTURED
+        .with(|slot| slot.borrow_mut().take())
+        .map_or((None, Vec::new()), |captured| {
+            (captured.location, captured.backtrace)
+        });
+    RunnerError::with_context(
+        ErrorCode::Internal,
+        format!("internal panic during {boundary}: {message}"),
+        serde_json::json!({
+            "boundary": boundary,
+            "panic": message,
+            "location": location,
+            "backtrace": backtrace,
+            "hint": "This is a ptybox bug; please report it with the run's artifacts"
+        }),
+    )
+}
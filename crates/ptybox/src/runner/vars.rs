@@ -0,0 +1,249 @@
+//! Variables captured from step output and substituted into later steps.
+//!
+//! A step's `vars` ([`VarCapture`]) are captured once the step passes, from
+//! the output read since its last input. Later steps refer to them as
+//! `${var:NAME}` in string values of their action payload. A string that is
+//! exactly one placeholder becomes the typed value (a JSON number for `int`,
+//! a boolean for `bool`), so `{"rows": "${var:rows}"}` resizes to a number;
+//! placeholders inside longer strings are replaced by the value's text.
+//!
+//! Values are converted when they are used: an unknown variable, or a value
+//! that is not a valid `int` or `bool`, errors the step that uses it with
+//! `E_PROTOCOL`, naming the step and the variable.
+
+use super::{compile_safe_regex, step_context, ErrorCode, RunnerError, RunnerResult};
+use crate::model::{Scenario, Step, VarCapture, VarType};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Placeholder prefix for captured variables.
+const VAR_PREFIX: &str = "${var:";
+
+/// Check that variable names are plain identifiers and patterns compile.
+pub(super) fn validate_step_vars(scenario: &Scenario) -> RunnerResult<()> {
+    for step in &scenario.steps {
+        for var in &step.vars {
+            let name = var.name.as_str();
+            if name.is_empty()
+                || !name
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+            {
+                return Err(RunnerError::with_context(
+                    ErrorCode::Protocol,
+                    "variable name must be letters, digits, or '_'",
+                    step_context(step, Some(serde_json::json!({ "variable": name }))),
+                ));
+            }
+            compile_safe_regex(&var.transcript_matches).map_err(|err| {
+                RunnerError::with_context(
+                    err.code,
+                    format!("variable '{name}': {}", err.message),
+                    step_context(
+                        step,
+                        Some(serde_json::json!({
+                            "variable": name,
+                            "transcript_matches": var.transcript_matches
+                        })),
+                    ),
+                )
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Variables captured so far in a run.
+#[derive(Default)]
+pub(super) struct Vars {
+    values: BTreeMap<String, (String, VarType)>,
+}
+
+impl Vars {
+    /// `step` with its action payload's placeholders replaced, and the typed
+    /// value of each variable used.
+    ///
+    /// # Errors
+    /// `E_PROTOCOL` if a variable has not been captured or its value does
+    /// not convert to its type.
+    pub(super) fn substitute(&self, step: &Step) -> RunnerResult<(Step, BTreeMap<String, Value>)> {
+        let mut resolved = BTreeMap::new();
+        let mut step = step.clone();
+        if !payload_has_placeholder(&step.action.payload) {
+            return Ok((step, resolved));
+        }
+        let payload = std::mem::take(&mut step.action.payload);
+        step.action.payload = self.substitute_value(&step, payload, &mut resolved)?;
+        Ok((step, resolved))
+    }
+
+    /// Capture `step`'s variables from `output`, returning the text each
+    /// one matched.
+    ///
+    /// # Errors
+    /// `E_ASSERTION_FAILED` if a pattern does not match the output.
+    pub(super) fn capture(
+        &mut self,
+        step: &Step,
+        output: &str,
+    ) -> RunnerResult<BTreeMap<String, String>> {
+        let mut captured = BTreeMap::new();
+        for var in &step.vars {
+            let text = capture_text(var, output)?.ok_or_else(|| {
+                RunnerError::with_context(
+                    ErrorCode::AssertionFailed,
+                    format!("variable '{}' did not match the step's output", var.name),
+                    step_context(
+                        step,
+                        Some(serde_json::json!({
+                            "variable": var.name,
+                            "transcript_matches": var.transcript_matches
+                        })),
+                    ),
+                )
+            })?;
+            self.values
+                .insert(var.name.clone(), (text.clone(), var.var_type));
+            captured.insert(var.name.clone(), text);
+        }
+        Ok(captured)
+    }
+
+    fn substitute_value(
+        &self,
+        step: &Step,
+        value: Value,
+        resolved: &mut BTreeMap<String, Value>,
+    ) -> RunnerResult<Value> {
+        match value {
+            Value::String(text) => self.substitute_string(step, &text, resolved),
+            Value::Array(items) => items
+                .into_iter()
+                .map(|item| self.substitute_value(step, item, resolved))
+                .collect::<RunnerResult<Vec<_>>>()
+                .map(Value::Array),
+            Value::Object(map) => map
+                .into_iter()
+                .map(|(key, item)| Ok((key, self.substitute_value(step, item, resolved)?)))
+                .collect::<RunnerResult<serde_json::Map<_, _>>>()
+                .map(Value::Object),
+            other => Ok(other),
+        }
+    }
+
+    fn substitute_string(
+        &self,
+        step: &Step,
+        text: &str,
+        resolved: &mut BTreeMap<String, Value>,
+    ) -> RunnerResult<Value> {
+        if let Some(name) = text
+            .strip_prefix(VAR_PREFIX)
+            .and_then(|rest| rest.strip_suffix('}'))
+            .filter(|name| !name.contains('}'))
+        {
+            return self.resolve(step, name, resolved);
+        }
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find(VAR_PREFIX) {
+            let (before, placeholder) = rest.split_at(start);
+            out.push_str(before);
+            let body = placeholder.get(VAR_PREFIX.len()..).unwrap_or_default();
+            let Some(end) = body.find('}') else {
+                out.push_str(placeholder);
+                rest = "";
+                break;
+            };
+            let name = body.get(..end).unwrap_or_default();
+            match self.resolve(step, name, resolved)? {
+                Value::String(value) => out.push_str(&value),
+                value => out.push_str(&value.to_string()),
+            }
+            rest = body.get(end + 1..).unwrap_or_default();
+        }
+        out.push_str(rest);
+        Ok(Value::String(out))
+    }
+
+    /// The typed value of variable `name`, recorded in `resolved`.
+    fn resolve(
+        &self,
+        step: &Step,
+        name: &str,
+        resolved: &mut BTreeMap<String, Value>,
+    ) -> RunnerResult<Value> {
+        let Some((text, var_type)) = self.values.get(name) else {
+            return Err(RunnerError::with_context(
+                ErrorCode::Protocol,
+                format!(
+                    "step '{}' uses variable '{name}', which has not been captured",
+                    step.name
+                ),
+                step_context(
+                    step,
+                    Some(serde_json::json!({
+                        "variable": name,
+                        "captured": self.values.keys().collect::<Vec<_>>(),
+                        "fix": "Capture the variable with `vars` in an earlier step"
+                    })),
+                ),
+            ));
+        };
+        let value = convert(text, *var_type).ok_or_else(|| {
+            RunnerError::with_context(
+                ErrorCode::Protocol,
+                format!(
+                    "step '{}' uses variable '{name}', whose value '{text}' is not a valid {}",
+                    step.name,
+                    var_type.as_str()
+                ),
+                step_context(
+                    step,
+                    Some(serde_json::json!({
+                        "variable": name,
+                        "value": text,
+                        "as": var_type.as_str()
+                    })),
+                ),
+            )
+        })?;
+        resolved.insert(name.to_string(), value.clone());
+        Ok(value)
+    }
+}
+
+/// The text `var` captures from `output`: its first group when the pattern
+/// has one, otherwise the whole match.
+fn capture_text(var: &VarCapture, output: &str) -> RunnerResult<Option<String>> {
+    let regex = compile_safe_regex(&var.transcript_matches)?;
+    Ok(regex.captures(output).map(|captures| {
+        let group = if captures.len() > 1 {
+            captures.get(1)
+        } else {
+            captures.get(0)
+        };
+        group.map_or_else(String::new, |group| group.as_str().to_string())
+    }))
+}
+
+fn convert(text: &str, var_type: VarType) -> Option<Value> {
+    match var_type {
+        VarType::String => Some(Value::String(text.to_string())),
+        VarType::Int => text.trim().parse::<i64>().ok().map(Value::from),
+        VarType::Bool => match text.trim().to_ascii_lowercase().as_str() {
+            "true" | "yes" | "1" => Some(Value::Bool(true)),
+            "false" | "no" | "0" => Some(Value::Bool(false)),
+            _ => None,
+        },
+    }
+}
+
+fn payload_has_placeholder(value: &Value) -> bool {
+    match value {
+        Value::String(text) => text.contains(VAR_PREFIX),
+        Value::Array(items) => items.iter().any(payload_has_placeholder),
+        Value::Object(map) => map.values().any(payload_has_placeholder),
+        _ => false,
+    }
+}
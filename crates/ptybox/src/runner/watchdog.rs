@@ -0,0 +1,228 @@
+//! Watchdog for runs whose loop stops making progress.
+//!
+//! Runtime budgets are enforced by the runner's own loop, so a loop blocked
+//! in a read that never returns never reaches them. Once the child is
+//! spawned, the watchdog thread is armed with the run deadline. If the run
+//! is still going `margin` past the deadline and has reported no progress
+//! for `margin`, the watchdog sends `SIGKILL` to the child's process group.
+//! That closes the terminal and unblocks the loop; the runner then records
+//! the run as `E_TIMEOUT` with `watchdog: true` in the error context.
+
+use super::RunnerError;
+use crate::model::policy::Policy;
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Milliseconds past the deadline, on top of the termination grace periods,
+/// before the watchdog steps in.
+pub(super) const WATCHDOG_MARGIN_MS: u64 = 5_000;
+
+/// A watchdog thread guarding one run. Dropping it disarms it.
+#[derive(Default)]
+pub(super) struct Watchdog {
+    shared: Arc<(Mutex<State>, Condvar)>,
+    thread: Option<JoinHandle<()>>,
+    armed: Option<Armed>,
+}
+
+#[derive(Clone, Copy)]
+struct Armed {
+    pid: u32,
+    deadline: Instant,
+    max_runtime_ms: u64,
+    margin_ms: u64,
+}
+
+#[derive(Default)]
+struct State {
+    last_progress: Option<Instant>,
+    fired: bool,
+    done: bool,
+}
+
+impl Watchdog {
+    /// Start watching the child `pid` of a run started at `run_started`
+    /// under `policy`.
+    ///
+    /// The margin is [`WATCHDOG_MARGIN_MS`] plus the time the normal
+    /// termination sequence may take. Without a process ID, or if the
+    /// thread cannot be spawned, the run goes unwatched.
+    pub(super) fn arm(&mut self, pid: Option<u32>, run_started: Instant, policy: &Policy) {
+        let Some(pid) = pid else {
+            return;
+        };
+        if self.thread.is_some() {
+            return;
+        }
+        let process = &policy.process;
+        let hook_ms = process
+            .pre_kill_hook
+            .as_ref()
+            .map_or(0, |hook| hook.timeout_ms);
+        let armed = Armed {
+            pid,
+            deadline: run_started + Duration::from_millis(policy.budgets.max_runtime_ms),
+            max_runtime_ms: policy.budgets.max_runtime_ms,
+            margin_ms: WATCHDOG_MARGIN_MS
+                .saturating_add(process.deadline_grace_total_ms())
+                .saturating_add(hook_ms),
+        };
+        let shared = Arc::clone(&self.shared);
+        self.thread = std::thread::Builder::new()
+            .name("ptybox-watchdog".to_string())
+            .spawn(move || watch(&shared, armed))
+            .ok();
+        if self.thread.is_some() {
+            self.armed = Some(armed);
+        }
+    }
+
+    /// Record that the run's loop is still making progress.
+    pub(super) fn progress(&self) {
+        let (lock, _) = &*self.shared;
+        lock.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .last_progress = Some(Instant::now());
+    }
+
+    /// Stop watching. Called once the child has exited, so a recycled
+    /// process ID is never signaled.
+    pub(super) fn disarm(&mut self) {
+        let Some(thread) = self.thread.take() else {
+            return;
+        };
+        let (lock, condvar) = &*self.shared;
+        lock.lock().unwrap_or_else(PoisonError::into_inner).done = true;
+        condvar.notify_all();
+        let _ = thread.join();
+    }
+
+    /// The `E_TIMEOUT` error for the run, if the watchdog killed its child.
+    pub(super) fn error(&self) -> Option<RunnerError> {
+        let armed = self.armed?;
+        let (lock, _) = &*self.shared;
+        if !lock.lock().unwrap_or_else(PoisonError::into_inner).fired {
+            return None;
+        }
+        Some(RunnerError::timeout(
+            "E_TIMEOUT",
+            "run made no progress past its max runtime budget; the watchdog killed it",
+            serde_json::json!({
+                "watchdog": true,
+                "max_runtime_ms": armed.max_runtime_ms,
+                "margin_ms": armed.margin_ms
+            }),
+        ))
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        self.disarm();
+    }
+}
+
+/// Wait until the deadline plus the margin has passed with no progress in
+/// the last margin, then kill the process group.
+fn watch(shared: &(Mutex<State>, Condvar), armed: Armed) {
+    let (lock, condvar) = shared;
+    let margin = Duration::from_millis(armed.margin_ms);
+    let mut state = lock.lock().unwrap_or_else(PoisonError::into_inner);
+    loop {
+        if state.done {
+            return;
+        }
+        let last = state
+            .last_progress
+            .map_or(armed.deadline, |last| last.max(armed.deadline));
+        let fire_at = last + margin;
+        let now = Instant::now();
+        if now >= fire_at {
+            kill_process_group(armed.pid);
+            state.fired = true;
+            return;
+        }
+        state = condvar
+            .wait_timeout(state, fire_at - now)
+            .unwrap_or_else(PoisonError::into_inner)
+            .0;
+    }
+}
+
+#[cfg(unix)]
+fn kill_process_group(pid: u32) {
+    use nix::sys::signal::{kill, killpg, Signal};
+    use nix::unistd::Pid;
+
+    // Process IDs are always positive and fit in i32
+    #[allow(clippy::cast_possible_wrap)]
+    let pid = Pid::from_raw(pid as i32);
+    if killpg(pid, Signal::SIGKILL).is_err() {
+        let _ = kill(pid, Signal::SIGKILL);
+    }
+}
+
+#[cfg(not(unix))]
+fn kill_process_group(_pid: u32) {}
+
+#[cfg(all(test, unix))]
+#[allow(clippy::unwrap_used, clippy::indexing_slicing)]
+mod tests {
+    use super::*;
+    use crate::model::policy::KillSignal;
+    use crate::runner::ErrorCode;
+    use std::os::unix::process::CommandExt;
+    use std::process::{Child, Command};
+
+    fn sleeper() -> Child {
+        Command::new("/bin/sleep")
+            .arg("30")
+            .process_group(0)
+            .spawn()
+            .unwrap()
+    }
+
+    fn policy(max_runtime_ms: u64) -> Policy {
+        let mut policy = Policy::default();
+        policy.budgets.max_runtime_ms = max_runtime_ms;
+        policy.process.kill_signal = KillSignal::Kill;
+        policy
+    }
+
+    #[test]
+    fn kills_a_child_past_the_deadline_and_margin() {
+        let mut child = sleeper();
+        let mut watchdog = Watchdog::default();
+        let started = Instant::now()
+            .checked_sub(Duration::from_millis(WATCHDOG_MARGIN_MS))
+            .unwrap();
+        watchdog.arm(Some(child.id()), started, &policy(0));
+
+        assert!(!child.wait().unwrap().success());
+        watchdog.disarm();
+        let error = watchdog.error().unwrap();
+        assert_eq!(error.code, ErrorCode::Timeout);
+        let context = error.context.unwrap();
+        assert_eq!(context["watchdog"], true);
+        assert_eq!(context["margin_ms"], WATCHDOG_MARGIN_MS);
+    }
+
+    #[test]
+    fn stays_quiet_while_the_run_makes_progress() {
+        let mut child = sleeper();
+        let mut watchdog = Watchdog::default();
+        let started = Instant::now()
+            .checked_sub(Duration::from_millis(WATCHDOG_MARGIN_MS - 200))
+            .unwrap();
+        watchdog.arm(Some(child.id()), started, &policy(0));
+        watchdog.progress();
+        std::thread::sleep(Duration::from_millis(400));
+        watchdog.disarm();
+
+        assert!(watchdog.error().is_none());
+        assert!(child.try_wait().unwrap().is_none());
+        child.kill().unwrap();
+        let _ = child.wait();
+    }
+}
@@ -0,0 +1,105 @@
+//! Source line numbers of scenario steps.
+//!
+//! Neither parser reports positions, so the top-level `steps` list is
+//! located with a small scan of the text: a JSON tokenizer that tracks
+//! strings and nesting, and for YAML the block-style `- ` items under a
+//! top-level `steps:` key. Flow-style YAML lists are not located.
+
+/// 1-based line where each element of the top-level `steps` array starts.
+pub(super) fn json_step_lines(data: &str) -> Vec<u32> {
+    let mut lines = Vec::new();
+    let mut line: u32 = 1;
+    let mut depth: usize = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut string = String::new();
+    let mut last_key = String::new();
+    let mut in_steps = false;
+    let mut expect_element = false;
+
+    for ch in data.chars() {
+        if ch == '\n' {
+            line += 1;
+        }
+        if in_string {
+            if escaped {
+                escaped = false;
+                string.push(ch);
+            } else if ch == '\\' {
+                escaped = true;
+            } else if ch == '"' {
+                in_string = false;
+                if depth == 1 {
+                    last_key = std::mem::take(&mut string);
+                }
+            } else {
+                string.push(ch);
+            }
+            continue;
+        }
+        if in_steps && depth == 2 && expect_element && !ch.is_whitespace() && ch != ']' {
+            lines.push(line);
+            expect_element = false;
+        }
+        match ch {
+            '"' => {
+                in_string = true;
+                string.clear();
+            }
+            '{' | '[' => {
+                if ch == '[' && depth == 1 && last_key == "steps" {
+                    in_steps = true;
+                    expect_element = true;
+                }
+                depth += 1;
+            }
+            '}' | ']' => {
+                depth = depth.saturating_sub(1);
+                if in_steps && depth == 1 {
+                    return lines;
+                }
+            }
+            ',' if in_steps && depth == 2 => expect_element = true,
+            _ => {}
+        }
+    }
+    lines
+}
+
+/// 1-based line of each block-style item in the top-level `steps:` list.
+pub(super) fn yaml_step_lines(data: &str) -> Vec<u32> {
+    let mut lines = Vec::new();
+    let mut in_steps = false;
+    let mut item_indent = None;
+
+    for (number, raw) in (1u32..).zip(data.lines()) {
+        let content = raw.trim_start();
+        let indent = raw.len() - content.len();
+        if content.is_empty() || content.starts_with('#') {
+            continue;
+        }
+        let is_item = content == "-" || content.starts_with("- ");
+        if !in_steps {
+            in_steps = indent == 0 && is_steps_key(content);
+            continue;
+        }
+        if indent == 0 && !is_item {
+            break;
+        }
+        if is_item && *item_indent.get_or_insert(indent) == indent {
+            lines.push(number);
+        }
+    }
+    lines
+}
+
+/// `steps:` with nothing after it but an optional comment.
+fn is_steps_key(content: &str) -> bool {
+    ["steps:", "\"steps\":", "'steps':"]
+        .iter()
+        .find_map(|key| content.strip_prefix(key))
+        .is_some_and(|rest| {
+            let rest = rest.trim_start();
+            rest.is_empty() || rest.starts_with('#')
+        })
+}
@@ -7,54 +7,148 @@
 //! # Key Functions
 //!
 //! - [`load_scenario_file`] — Load a scenario from a JSON or YAML file
+//! - [`load_scenario_file_with_source`] — Load a scenario along with its steps' line numbers
+//! - [`read_scenario_file`] / [`write_scenario_file`] — Round-trip a scenario file as written
 //! - [`load_policy_file`] — Load a policy from a JSON file by path
 //! - [`load_policy_ref`] — Resolve a [`PolicyRef`] (inline or file reference)
 //! - [`to_json_value`] — Serialize any type to a [`serde_json::Value`]
 
 use crate::model::policy::Policy;
 use crate::model::scenario::{PolicyRef, Scenario};
+use crate::policy::substitution::substitute_policy_paths;
 use crate::runner::{RunnerError, RunnerResult};
 use serde_json::Value;
 use std::fs;
 use std::path::Path;
 
+mod lines;
+
+/// Where a scenario's steps appear in its file, so reports can point back
+/// at the source.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScenarioSource {
+    /// Scenario file path as given.
+    pub path: String,
+    /// 1-based line where each step starts, in step order. Empty when the
+    /// steps could not all be located.
+    pub step_lines: Vec<u32>,
+}
+
+impl ScenarioSource {
+    /// Line of the step at `index`, if known.
+    #[must_use]
+    pub fn step_line(&self, index: usize) -> Option<u32> {
+        self.step_lines.get(index).copied()
+    }
+}
+
 /// Load and parse a scenario from a JSON or YAML file.
 ///
 /// File format is determined by extension: `.yaml` or `.yml` for YAML,
-/// anything else is treated as JSON.
+/// anything else is treated as JSON. `defaults.assert_each_step` is expanded
+/// into the steps (see [`Scenario::apply_defaults`]) and `watchers` are
+/// validated.
 ///
 /// # Errors
 /// - `E_IO` if the file cannot be read
-/// - `E_PROTOCOL` if the file cannot be parsed
+/// - `E_PROTOCOL` if the file cannot be parsed or a watcher is invalid
 pub fn load_scenario_file(path: &str) -> RunnerResult<Scenario> {
-    let data = fs::read_to_string(path)
-        .map_err(|err| RunnerError::io("E_IO", "failed to read scenario file", err))?;
-    if path.ends_with(".yaml") || path.ends_with(".yml") {
-        serde_yml::from_str(&data)
+    load_scenario_file_with_source(path).map(|(scenario, _)| scenario)
+}
+
+/// Load a scenario like [`load_scenario_file`], also locating the line
+/// each step starts on.
+///
+/// Lines are found by scanning the text for the top-level `steps` list
+/// (block-style lists only, for YAML). When the scan does not find one line
+/// per step, [`ScenarioSource::step_lines`] is left empty.
+///
+/// # Errors
+/// - `E_IO` if the file cannot be read
+/// - `E_PROTOCOL` if the file cannot be parsed or a watcher is invalid
+pub fn load_scenario_file_with_source(path: &str) -> RunnerResult<(Scenario, ScenarioSource)> {
+    let data = read_file(path)?;
+    let mut scenario = parse_scenario(&data, path)?;
+    scenario.apply_defaults();
+    crate::session::validate_watchers(&scenario.watchers)?;
+    let mut step_lines = if is_yaml_path(path) {
+        lines::yaml_step_lines(&data)
+    } else {
+        lines::json_step_lines(&data)
+    };
+    if step_lines.len() != scenario.steps.len() {
+        step_lines.clear();
+    }
+    let source = ScenarioSource {
+        path: path.to_string(),
+        step_lines,
+    };
+    Ok((scenario, source))
+}
+
+/// Parse a scenario file as written, without expanding `defaults`.
+///
+/// Use this (with [`write_scenario_file`]) to rewrite a scenario; use
+/// [`load_scenario_file`] to run one.
+///
+/// # Errors
+/// - `E_IO` if the file cannot be read
+/// - `E_PROTOCOL` if the file cannot be parsed
+pub fn read_scenario_file(path: &str) -> RunnerResult<Scenario> {
+    parse_scenario(&read_file(path)?, path)
+}
+
+fn read_file(path: &str) -> RunnerResult<String> {
+    fs::read_to_string(path)
+        .map_err(|err| RunnerError::io("E_IO", "failed to read scenario file", err))
+}
+
+fn parse_scenario(data: &str, path: &str) -> RunnerResult<Scenario> {
+    if is_yaml_path(path) {
+        serde_yml::from_str(data)
             .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to parse yaml", err))
     } else {
-        serde_json::from_str(&data)
+        serde_json::from_str(data)
             .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to parse json", err))
     }
 }
 
+/// Write a scenario to `path`, as YAML for `.yaml`/`.yml` and pretty JSON otherwise.
+///
+/// # Errors
+/// - `E_PROTOCOL` if the scenario cannot be serialized
+/// - `E_IO` if the file cannot be written
+pub fn write_scenario_file(path: &str, scenario: &Scenario) -> RunnerResult<()> {
+    let data = if is_yaml_path(path) {
+        serde_yml::to_string(scenario)
+            .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to serialize yaml", err))?
+    } else {
+        let mut json = serde_json::to_string_pretty(scenario)
+            .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to serialize json", err))?;
+        json.push('\n');
+        json
+    };
+    fs::write(path, data)
+        .map_err(|err| RunnerError::io("E_IO", "failed to write scenario file", err))
+}
+
+fn is_yaml_path(path: &str) -> bool {
+    path.ends_with(".yaml") || path.ends_with(".yml")
+}
+
 /// Resolve a policy reference to a [`Policy`].
 ///
 /// If the reference is [`PolicyRef::Inline`], returns the policy directly.
-/// If it is [`PolicyRef::File`], loads and parses the JSON file.
+/// If it is [`PolicyRef::File`], loads it with [`load_policy_file`].
 ///
 /// # Errors
 /// - `E_IO` if the file cannot be read
 /// - `E_PROTOCOL` if the JSON cannot be parsed
+/// - `E_POLICY_DENIED` if a path placeholder cannot be substituted
 pub fn load_policy_ref(policy_ref: &PolicyRef) -> RunnerResult<Policy> {
     match policy_ref {
         PolicyRef::Inline(policy) => Ok(policy.as_ref().clone()),
-        PolicyRef::File { path } => {
-            let data = fs::read_to_string(path)
-                .map_err(|err| RunnerError::io("E_IO", "failed to read policy file", err))?;
-            serde_json::from_str(&data)
-                .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to parse policy", err))
-        }
+        PolicyRef::File { path } => load_policy_file(Path::new(path)),
     }
 }
 
@@ -63,10 +157,20 @@ pub fn load_policy_ref(policy_ref: &PolicyRef) -> RunnerResult<Policy> {
 /// # Errors
 /// - `E_IO` if the file cannot be read
 /// - `E_PROTOCOL` if the JSON cannot be parsed
+/// - `E_POLICY_DENIED` if a path placeholder cannot be substituted
+///
+/// `${cwd}` and `${env:NAME}` placeholders in path fields are resolved
+/// against the file's `substitution_allowlist`; see
+/// [`crate::policy::substitution`].
 pub fn load_policy_file(path: &Path) -> RunnerResult<Policy> {
     let data = fs::read_to_string(path)
         .map_err(|err| RunnerError::io("E_IO", "failed to read policy file", err))?;
-    serde_json::from_str(&data)
+    let mut value: Value = serde_json::from_str(&data)
+        .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to parse policy", err))?;
+    let cwd = std::env::current_dir()
+        .map_err(|err| RunnerError::io("E_IO", "failed to read current directory", err))?;
+    substitute_policy_paths(&mut value, &cwd)?;
+    serde_json::from_value(value)
         .map_err(|err| RunnerError::io("E_PROTOCOL", "failed to parse policy", err))
 }
 
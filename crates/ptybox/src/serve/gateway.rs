@@ -0,0 +1,680 @@
+//! Multi-tenant driver gateway for `ptybox serve --listen`.
+//!
+//! The gateway exposes the protocol v2 driver on a Unix domain socket to
+//! several agents at once. Each connection authenticates with a static token
+//! listed in a [`GatewayAuthConfig`]; the token binds the connection to its
+//! tenant's policy file and artifacts root, so an agent can only spawn what
+//! its own policy allows and only writes artifacts under its own root.
+//!
+//! Connection flow:
+//! 1. The client sends one [`GatewayHello`] line (token, command, args, cwd).
+//! 2. The gateway replies with one [`GatewayReply`] line. On failure `ok` is
+//!    false and the connection is closed.
+//! 3. The connection then carries the driver NDJSON loop (handshake,
+//!    requests, responses) exactly as `ptybox driver --stdio --json` does.
+//!
+//! Open connections are capped by [`GatewayConfig::max_connections`] and
+//! running sessions per tenant by [`GatewayConfig::max_sessions`]; a client
+//! over either limit is refused with `E_PROTOCOL`.
+//!
+//! Every authentication failure, refusal, session start, and session end is
+//! appended to the optional audit log as a [`GatewayAuditRecord`] naming the
+//! tenant, never the token.
+
+use crate::artifacts::ArtifactsWriterConfig;
+use crate::driver::{run_driver_with_io, DriverConfig};
+use crate::model::policy::Policy;
+use crate::model::scenario::PolicyRef;
+use crate::model::{
+    ErrorInfo, OutputBufferConfig, ResizeConfig, RunConfig, SessionId, TerminalSize,
+};
+use crate::policy::acknowledgements::now_ms;
+use crate::policy::{validate_artifacts_dir, validate_policy, EffectivePolicy};
+use crate::runner::{RunnerError, RunnerResult};
+use crate::scenario::load_policy_file;
+use crate::util::sha256_hex;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::Shutdown;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Time a client has to send its hello line after connecting.
+const HELLO_TIMEOUT: Duration = Duration::from_secs(10);
+/// Maximum size of the hello line in bytes.
+const MAX_HELLO_BYTES: u64 = 64 * 1024;
+/// Sleep between non-blocking accept attempts.
+pub(super) const ACCEPT_POLL: Duration = Duration::from_millis(50);
+
+/// Tokens, the tenants they authenticate, and the audit log location.
+///
+/// Loaded from JSON or YAML by [`load_gateway_auth_config`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GatewayAuthConfig {
+    /// One entry per agent token.
+    pub tenants: Vec<TenantBinding>,
+    /// Append-only JSONL audit log (absolute path).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audit_log: Option<PathBuf>,
+}
+
+/// A static token bound to a policy file and an artifacts root.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TenantBinding {
+    /// Tenant name recorded in the audit log.
+    pub name: String,
+    /// Lowercase hex SHA-256 of the token; the token itself is never stored.
+    pub token_sha256: String,
+    /// Policy file every session of this tenant runs under (absolute path).
+    pub policy: PathBuf,
+    /// Directory receiving one artifacts directory per session (absolute
+    /// path, inside the policy's `fs.allowed_write`).
+    pub artifacts_root: PathBuf,
+}
+
+/// First line a client sends after connecting.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GatewayHello {
+    /// Bearer token of the tenant.
+    pub token: String,
+    /// Command to spawn (absolute path, allowlisted by the tenant policy).
+    pub command: String,
+    /// Command arguments.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Working directory for the child.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    /// Route the child's stderr to a separate pipe.
+    #[serde(default)]
+    pub separate_stderr: bool,
+}
+
+/// Gateway answer to a [`GatewayHello`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GatewayReply {
+    /// Whether the connection was accepted.
+    pub ok: bool,
+    /// Authenticated tenant name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    /// Identifier of the session, also the artifacts directory name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Artifacts directory of the session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifacts_dir: Option<String>,
+    /// Why the connection was refused.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorInfo>,
+}
+
+/// Kind of audit log entry.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GatewayAuditEvent {
+    /// The hello line was malformed or its token matched no tenant.
+    AuthFailed,
+    /// A session request was refused by the tenant policy or a gateway
+    /// limit.
+    Denied,
+    /// A driver session started.
+    SessionStarted,
+    /// A driver session ended.
+    SessionEnded,
+}
+
+/// One line of the gateway audit log.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GatewayAuditRecord {
+    /// Milliseconds since the Unix epoch.
+    pub timestamp_ms: u64,
+    /// What happened.
+    pub event: GatewayAuditEvent,
+    /// Authenticated tenant, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    /// Session identifier, once assigned.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Requested command.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// Requested arguments.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Artifacts directory of the session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifacts_dir: Option<String>,
+    /// Session duration (`session_ended` only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Failure, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorInfo>,
+}
+
+/// Configuration for [`run_gateway`].
+pub struct GatewayConfig {
+    /// Unix domain socket to listen on.
+    pub socket_path: PathBuf,
+    /// Token bindings and audit log.
+    pub auth: GatewayAuthConfig,
+    /// Set to `true` to stop accepting connections and close open sessions.
+    pub shutdown: Arc<AtomicBool>,
+    /// Writer for the ready message (typically stdout).
+    pub ready_output: Box<dyn Write + Send>,
+    /// Open connections, authenticated or not, served at once; further
+    /// connections are refused.
+    pub max_connections: usize,
+    /// Driver sessions a single tenant may run at once; further hellos from
+    /// that tenant are refused.
+    pub max_sessions: usize,
+}
+
+/// Ready message written once the socket is bound.
+#[derive(Serialize)]
+struct ReadyMessage {
+    ok: bool,
+    socket: String,
+    tenants: Vec<String>,
+}
+
+/// A tenant with its policy loaded and validated.
+struct Tenant {
+    name: String,
+    token_sha256: String,
+    policy: Policy,
+    artifacts_root: PathBuf,
+    /// Driver sessions currently running for this tenant.
+    sessions: AtomicUsize,
+}
+
+/// State shared by connection threads.
+struct GatewayShared {
+    tenants: Vec<Tenant>,
+    audit: Option<Mutex<File>>,
+    max_sessions: usize,
+}
+
+/// A claimed slot in a tenant's session count, released on drop.
+struct SessionSlot<'a> {
+    sessions: &'a AtomicUsize,
+}
+
+impl Drop for SessionSlot<'_> {
+    fn drop(&mut self) {
+        self.sessions.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Load a gateway auth config from a JSON or YAML (`.yaml`/`.yml`) file.
+///
+/// # Errors
+/// - `E_IO` if the file cannot be read
+/// - `E_PROTOCOL` if it cannot be parsed
+pub fn load_gateway_auth_config(path: &Path) -> RunnerResult<GatewayAuthConfig> {
+    let data = fs::read_to_string(path)
+        .map_err(|err| RunnerError::io("E_IO", "failed to read gateway auth config", err))?;
+    let yaml = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| matches!(ext, "yaml" | "yml"));
+    if yaml {
+        serde_yml::from_str(&data).map_err(|err| {
+            RunnerError::io("E_PROTOCOL", "failed to parse gateway auth config", err)
+        })
+    } else {
+        serde_json::from_str(&data).map_err(|err| {
+            RunnerError::io("E_PROTOCOL", "failed to parse gateway auth config", err)
+        })
+    }
+}
+
+/// Run the gateway until `shutdown` is set.
+///
+/// 1. Validates the auth config and loads and validates every tenant policy.
+/// 2. Binds the socket (mode `0600`) and writes a ready message.
+/// 3. Serves each connection on its own thread, refusing connections beyond
+///    `max_connections` and sessions beyond a tenant's `max_sessions`.
+/// 4. On shutdown, closes open connections (terminating their children and
+///    writing their artifacts) and removes the socket.
+///
+/// # Errors
+///
+/// Returns [`RunnerError`] if the auth config or a limit is invalid
+/// (`E_PROTOCOL`), a
+/// tenant policy cannot be loaded or is denied (`E_POLICY_DENIED`), or the
+/// socket or audit log cannot be opened (`E_IO`).
+pub fn run_gateway(mut config: GatewayConfig) -> RunnerResult<()> {
+    if config.max_connections == 0 || config.max_sessions == 0 {
+        return Err(config_error(
+            "max_connections and max_sessions must be at least 1",
+            None,
+        ));
+    }
+    let tenants = load_tenants(&config.auth)?;
+    let audit = match &config.auth.audit_log {
+        Some(path) => Some(Mutex::new(open_audit_log(path)?)),
+        None => None,
+    };
+    let shared = Arc::new(GatewayShared {
+        tenants,
+        audit,
+        max_sessions: config.max_sessions,
+    });
+
+    let listener = bind_socket(&config.socket_path)?;
+    let ready = ReadyMessage {
+        ok: true,
+        socket: config.socket_path.display().to_string(),
+        tenants: shared.tenants.iter().map(|t| t.name.clone()).collect(),
+    };
+    let ready_json = serde_json::to_string(&ready)
+        .map_err(|e| RunnerError::io_err("failed to serialize ready message", e))?;
+    writeln!(config.ready_output, "{ready_json}")
+        .map_err(|e| RunnerError::io_err("failed to write ready message", e))?;
+    config
+        .ready_output
+        .flush()
+        .map_err(|e| RunnerError::io_err("failed to flush ready message", e))?;
+
+    let mut connections: Vec<(JoinHandle<()>, UnixStream)> = Vec::new();
+    while !config.shutdown.load(Ordering::SeqCst) {
+        connections.retain(|(handle, _)| !handle.is_finished());
+        let stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {
+                thread::sleep(ACCEPT_POLL);
+                continue;
+            }
+            Err(err) => return Err(RunnerError::io_err("failed to accept connection", err)),
+        };
+        if connections.len() >= config.max_connections {
+            refuse_connection(&stream, &shared, config.max_connections);
+            continue;
+        }
+        let Ok(control) = stream.try_clone() else {
+            continue;
+        };
+        let shared = Arc::clone(&shared);
+        let handle = thread::Builder::new()
+            .name("ptybox-gateway-conn".to_string())
+            .spawn(move || serve_connection(stream, &shared))
+            .map_err(|err| RunnerError::io("E_IO", "failed to start connection thread", err))?;
+        connections.push((handle, control));
+    }
+
+    for (_, control) in &connections {
+        let _ = control.shutdown(Shutdown::Both);
+    }
+    for (handle, _) in connections {
+        let _ = handle.join();
+    }
+    let _ = fs::remove_file(&config.socket_path);
+    Ok(())
+}
+
+/// Validate bindings and load each tenant's policy.
+fn load_tenants(auth: &GatewayAuthConfig) -> RunnerResult<Vec<Tenant>> {
+    if auth.tenants.is_empty() {
+        return Err(config_error("gateway auth config has no tenants", None));
+    }
+    let mut tenants: Vec<Tenant> = Vec::with_capacity(auth.tenants.len());
+    for binding in &auth.tenants {
+        let token_sha256 = binding.token_sha256.to_ascii_lowercase();
+        if binding.name.is_empty() {
+            return Err(config_error("tenant name must not be empty", None));
+        }
+        if token_sha256.len() != 64 || !token_sha256.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(config_error(
+                "token_sha256 must be 64 hex characters",
+                Some(&binding.name),
+            ));
+        }
+        if tenants
+            .iter()
+            .any(|t| t.name == binding.name || t.token_sha256 == token_sha256)
+        {
+            return Err(config_error(
+                "tenant names and tokens must be unique",
+                Some(&binding.name),
+            ));
+        }
+        if !binding.policy.is_absolute() || !binding.artifacts_root.is_absolute() {
+            return Err(config_error(
+                "policy and artifacts_root must be absolute paths",
+                Some(&binding.name),
+            ));
+        }
+        let policy = load_policy_file(&binding.policy)?;
+        validate_policy(&policy)?;
+        validate_artifacts_dir(&binding.artifacts_root, &policy.fs)?;
+        tenants.push(Tenant {
+            name: binding.name.clone(),
+            token_sha256,
+            policy,
+            artifacts_root: binding.artifacts_root.clone(),
+            sessions: AtomicUsize::new(0),
+        });
+    }
+    Ok(tenants)
+}
+
+fn config_error(message: &str, tenant: Option<&str>) -> RunnerError {
+    RunnerError::protocol(
+        "E_PROTOCOL",
+        message,
+        tenant.map(|name| serde_json::json!({ "tenant": name })),
+    )
+}
+
+fn open_audit_log(path: &Path) -> RunnerResult<File> {
+    if !path.is_absolute() {
+        return Err(config_error("audit_log must be an absolute path", None));
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|err| RunnerError::io("E_IO", "failed to open gateway audit log", err))
+}
+
+/// Bind the socket with `0600` permissions, replacing a stale socket file.
+///
+/// The socket is bound inside a fresh `0700` directory next to
+/// `socket_path`, restricted to `0600`, and only then renamed into place, so
+/// it is never reachable under the process umask.
+pub(super) fn bind_socket(socket_path: &Path) -> RunnerResult<UnixListener> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+    let dir = socket_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    if !dir.exists() {
+        fs::create_dir_all(dir)
+            .map_err(|e| RunnerError::io_err("failed to create socket directory", e))?;
+    }
+    let file_name = socket_path.file_name().ok_or_else(|| {
+        RunnerError::with_context(
+            crate::runner::ErrorCode::Io,
+            "socket path has no file name",
+            serde_json::json!({ "socket": socket_path.display().to_string() }),
+        )
+    })?;
+    let staging = dir.join(format!(
+        ".{}.{}.bind",
+        file_name.to_string_lossy(),
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&staging);
+    fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&staging)
+        .map_err(|e| RunnerError::io_err("failed to create socket staging directory", e))?;
+    let staged = staging.join("sock");
+    let bound = UnixListener::bind(&staged)
+        .map_err(|e| RunnerError::io_err("failed to bind UDS", e))
+        .and_then(|listener| {
+            fs::set_permissions(&staged, fs::Permissions::from_mode(0o600))
+                .map_err(|e| RunnerError::io_err("failed to restrict UDS permissions", e))?;
+            if socket_path.exists() {
+                let _ = fs::remove_file(socket_path);
+            }
+            fs::rename(&staged, socket_path)
+                .map_err(|e| RunnerError::io_err("failed to move UDS into place", e))?;
+            Ok(listener)
+        });
+    let _ = fs::remove_dir_all(&staging);
+    let listener = bound?;
+    listener
+        .set_nonblocking(true)
+        .map_err(|e| RunnerError::io_err("failed to set UDS non-blocking", e))?;
+    Ok(listener)
+}
+
+/// Authenticate one connection and run a driver session on it.
+fn serve_connection(stream: UnixStream, shared: &GatewayShared) {
+    if stream.set_nonblocking(false).is_err()
+        || stream.set_read_timeout(Some(HELLO_TIMEOUT)).is_err()
+    {
+        return;
+    }
+    let Ok(read_half) = stream.try_clone() else {
+        return;
+    };
+    let mut reader = BufReader::new(read_half);
+    let hello = match read_hello(&mut reader) {
+        Ok(hello) => hello,
+        Err(err) => {
+            reject(&stream, shared, GatewayAuditEvent::AuthFailed, None, &err);
+            return;
+        }
+    };
+    let digest = sha256_hex(hello.token.as_bytes());
+    let Some(tenant) = shared.tenants.iter().find(|t| t.token_sha256 == digest) else {
+        let err = RunnerError::policy_denied("E_POLICY_DENIED", "authentication failed", None);
+        reject(
+            &stream,
+            shared,
+            GatewayAuditEvent::AuthFailed,
+            Some(&hello),
+            &err,
+        );
+        return;
+    };
+    if let Err(err) = validate_hello(tenant, &hello) {
+        deny(&stream, shared, tenant, &hello, &err);
+        return;
+    }
+    let Some(_slot) = claim_session(tenant, shared.max_sessions) else {
+        let err = RunnerError::protocol(
+            "E_PROTOCOL",
+            "tenant session limit reached",
+            Some(serde_json::json!({
+                "tenant": tenant.name,
+                "max_sessions": shared.max_sessions,
+                "fix": "Close one of this tenant's sessions or raise --max-sessions.",
+            })),
+        );
+        deny(&stream, shared, tenant, &hello, &err);
+        return;
+    };
+    run_tenant_session(stream, reader, shared, tenant, hello);
+}
+
+/// Count a new session against `tenant`, unless it already runs `max`.
+fn claim_session(tenant: &Tenant, max: usize) -> Option<SessionSlot<'_>> {
+    tenant
+        .sessions
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |running| {
+            (running < max).then_some(running + 1)
+        })
+        .ok()
+        .map(|_| SessionSlot {
+            sessions: &tenant.sessions,
+        })
+}
+
+/// Refuse a connection accepted while `max_connections` are already open.
+fn refuse_connection(stream: &UnixStream, shared: &GatewayShared, max_connections: usize) {
+    let err = RunnerError::protocol(
+        "E_PROTOCOL",
+        "gateway connection limit reached",
+        Some(serde_json::json!({
+            "max_connections": max_connections,
+            "fix": "Retry after another connection closes or raise --max-connections.",
+        })),
+    );
+    if stream.set_nonblocking(false).is_err()
+        || stream.set_write_timeout(Some(HELLO_TIMEOUT)).is_err()
+    {
+        return;
+    }
+    reject(stream, shared, GatewayAuditEvent::Denied, None, &err);
+}
+
+/// Read and parse the hello line, bounded by [`MAX_HELLO_BYTES`].
+fn read_hello(reader: &mut BufReader<UnixStream>) -> RunnerResult<GatewayHello> {
+    let mut line = String::new();
+    reader
+        .by_ref()
+        .take(MAX_HELLO_BYTES)
+        .read_line(&mut line)
+        .map_err(|err| RunnerError::io("E_IO", "failed to read hello", err))?;
+    serde_json::from_str(line.trim())
+        .map_err(|err| RunnerError::io("E_PROTOCOL", "invalid gateway hello", err))
+}
+
+/// Check the requested command against the tenant policy before replying.
+fn validate_hello(tenant: &Tenant, hello: &GatewayHello) -> RunnerResult<()> {
+    EffectivePolicy::new(tenant.policy.clone()).validate_run_config(&RunConfig {
+        command: hello.command.clone(),
+        args: hello.args.clone(),
+        cwd: hello.cwd.clone(),
+        initial_size: TerminalSize::default(),
+        policy: PolicyRef::Inline(Box::new(tenant.policy.clone())),
+        separate_stderr: hello.separate_stderr,
+        output_buffer: OutputBufferConfig::default(),
+        resize: ResizeConfig::default(),
+        remote: None,
+        validate_args: false,
+        capture_attributes: false,
+    })
+}
+
+/// Reply `ok`, then run the driver loop on the connection under the tenant's
+/// policy with artifacts in `<artifacts_root>/<session_id>`.
+fn run_tenant_session(
+    stream: UnixStream,
+    reader: BufReader<UnixStream>,
+    shared: &GatewayShared,
+    tenant: &Tenant,
+    hello: GatewayHello,
+) {
+    let session_id = SessionId::new().to_string();
+    let artifacts_dir = tenant.artifacts_root.join(&session_id);
+    let reply = GatewayReply {
+        ok: true,
+        tenant: Some(tenant.name.clone()),
+        session_id: Some(session_id.clone()),
+        artifacts_dir: Some(artifacts_dir.display().to_string()),
+        error: None,
+    };
+    if write_reply(&stream, &reply).is_err() || stream.set_read_timeout(None).is_err() {
+        return;
+    }
+    let mut record = audit_record(GatewayAuditEvent::SessionStarted, Some(&hello));
+    record.tenant = Some(tenant.name.clone());
+    record.session_id = Some(session_id);
+    record.artifacts_dir = Some(artifacts_dir.display().to_string());
+    shared.audit(&record);
+
+    let started = Instant::now();
+    let config = DriverConfig {
+        command: hello.command,
+        args: hello.args,
+        cwd: hello.cwd,
+        policy: tenant.policy.clone(),
+        artifacts: Some(ArtifactsWriterConfig {
+            dir: artifacts_dir,
+            overwrite: false,
+        }),
+        separate_stderr: hello.separate_stderr,
+        ack_overrides: Vec::new(),
+    };
+    let result = run_driver_with_io(config, reader, stream);
+
+    record.event = GatewayAuditEvent::SessionEnded;
+    record.timestamp_ms = now_ms();
+    record.duration_ms = Some(u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX));
+    record.error = result.err().map(|err| err.to_error_info());
+    shared.audit(&record);
+}
+
+fn reject(
+    stream: &UnixStream,
+    shared: &GatewayShared,
+    event: GatewayAuditEvent,
+    hello: Option<&GatewayHello>,
+    err: &RunnerError,
+) {
+    let mut record = audit_record(event, hello);
+    record.error = Some(err.to_error_info());
+    shared.audit(&record);
+    let _ = write_reply(stream, &refusal(err));
+}
+
+/// Audit and refuse an authenticated tenant's session request.
+fn deny(
+    stream: &UnixStream,
+    shared: &GatewayShared,
+    tenant: &Tenant,
+    hello: &GatewayHello,
+    err: &RunnerError,
+) {
+    let mut record = audit_record(GatewayAuditEvent::Denied, Some(hello));
+    record.tenant = Some(tenant.name.clone());
+    record.error = Some(err.to_error_info());
+    shared.audit(&record);
+    let _ = write_reply(stream, &refusal(err));
+}
+
+fn refusal(err: &RunnerError) -> GatewayReply {
+    GatewayReply {
+        ok: false,
+        tenant: None,
+        session_id: None,
+        artifacts_dir: None,
+        error: Some(err.to_error_info()),
+    }
+}
+
+fn audit_record(event: GatewayAuditEvent, hello: Option<&GatewayHello>) -> GatewayAuditRecord {
+    GatewayAuditRecord {
+        timestamp_ms: now_ms(),
+        event,
+        tenant: None,
+        session_id: None,
+        command: hello.map(|h| h.command.clone()),
+        args: hello.map(|h| h.args.clone()).unwrap_or_default(),
+        artifacts_dir: None,
+        duration_ms: None,
+        error: None,
+    }
+}
+
+impl GatewayShared {
+    /// Append `record` to the audit log, if one is configured.
+    ///
+    /// A record that cannot be serialized or written is logged as a warning
+    /// rather than failing the session it describes.
+    fn audit(&self, record: &GatewayAuditRecord) {
+        let Some(audit) = &self.audit else {
+            return;
+        };
+        let line = match serde_json::to_string(record) {
+            Ok(line) => line,
+            Err(err) => {
+                tracing::warn!(error = %err, "failed to serialize gateway audit record");
+                return;
+            }
+        };
+        let mut file = audit.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Err(err) = writeln!(file, "{line}") {
+            tracing::warn!(error = %err, "failed to write gateway audit record");
+        }
+    }
+}
+
+fn write_reply(mut stream: &UnixStream, reply: &GatewayReply) -> std::io::Result<()> {
+    let json = serde_json::to_string(reply).map_err(std::io::Error::other)?;
+    writeln!(stream, "{json}")?;
+    stream.flush()
+}
@@ -6,19 +6,33 @@
 //!
 //! The daemon exits when it receives a `Close` command, when the idle timeout
 //! fires, or when the child process exits.
-
+//!
+//! The [`queue`](crate::serve::queue) submodule provides the long-running
+//! scenario queue runner (`ptybox serve --queue`),
+//! [`gateway`](crate::serve::gateway) the token-authenticated, multi-tenant
+//! driver gateway (`ptybox serve --listen`), and
+//! [`pool`](crate::serve::pool) the warm session pool leasing pre-spawned
+//! children to driver episodes (`ptybox serve --pool`).
+
+pub mod gateway;
+pub mod pool;
 pub mod protocol;
+pub mod queue;
 
 use crate::actions::perform_action;
 use crate::model::policy::Policy;
-use crate::model::{Action, ActionType, RunConfig, RunId, TerminalSize};
+use crate::model::{
+    Action, ActionType, OutputBufferConfig, ResizeConfig, RunConfig, RunId, TerminalSize,
+};
 use crate::policy::{
-    validate_artifacts_dir, validate_artifacts_policy, validate_policy, validate_write_access,
-    EffectivePolicy,
+    fallback_spawn_policy, validate_artifacts_dir, validate_artifacts_policy, validate_policy,
+    validate_write_access, EffectivePolicy,
 };
 use crate::runner::{RunnerError, RunnerResult};
 use crate::session::{Session, SessionConfig};
-use crate::util::{build_spawn_command, resolve_artifacts_config, SandboxCleanupGuard};
+use crate::util::{
+    bind_policy_sockets, build_spawn_command, resolve_artifacts_config, SandboxCleanupGuard,
+};
 use protocol::{ScreenOutput, ServeCommand, ServeRequest, ServeResponse};
 use std::io::{BufRead, BufReader, Write};
 use std::os::unix::net::UnixListener;
@@ -73,7 +87,7 @@ struct ReadyMessage {
 #[allow(clippy::too_many_lines)]
 pub fn run_serve(mut config: ServeConfig) -> RunnerResult<()> {
     // --- Policy validation (reuses the driver/runner validation chain) ---
-    validate_policy(&config.policy)?;
+    let enforcement = validate_policy(&config.policy)?;
     validate_artifacts_policy(&config.policy)?;
     let effective_policy = EffectivePolicy::new(config.policy.clone());
     let run_config = RunConfig {
@@ -82,6 +96,12 @@ pub fn run_serve(mut config: ServeConfig) -> RunnerResult<()> {
         cwd: config.cwd.clone(),
         initial_size: TerminalSize::default(),
         policy: crate::model::scenario::PolicyRef::Inline(Box::new(config.policy.clone())),
+        separate_stderr: false,
+        output_buffer: OutputBufferConfig::default(),
+        resize: ResizeConfig::default(),
+        remote: None,
+        validate_args: false,
+        capture_attributes: false,
     };
     effective_policy.validate_run_config(&run_config)?;
 
@@ -93,21 +113,27 @@ pub fn run_serve(mut config: ServeConfig) -> RunnerResult<()> {
     }
 
     let run_id = RunId::new();
-    let mut _writer = if let Some(cfg) = artifacts_config {
+    let mut writer = if let Some(cfg) = artifacts_config {
         Some(ArtifactsWriter::new(run_id, cfg)?)
     } else {
         None
     };
 
     // --- Spawn session ---
-    let spawn = build_spawn_command(
-        &config.policy,
+    let mut spawn = build_spawn_command(
+        &fallback_spawn_policy(&config.policy, enforcement.as_ref()),
         &config.command,
         &config.args,
+        config.cwd.as_deref(),
         artifacts_dir.as_ref(),
         run_id,
     )?;
-    let _cleanup_guard = SandboxCleanupGuard::new(spawn.cleanup_path.clone());
+    let mut cleanup_guard = SandboxCleanupGuard::new(spawn.cleanup_path.clone());
+    cleanup_guard.container = spawn.container.take();
+    if let (Some(writer), Some(invocation)) = (writer.as_mut(), &spawn.invocation) {
+        writer.write_container_invocation(invocation)?;
+    }
+    let env = bind_policy_sockets(&config.policy, run_id, None, &mut cleanup_guard)?;
 
     let mut session = Session::spawn(SessionConfig {
         command: spawn.command,
@@ -115,8 +141,16 @@ pub fn run_serve(mut config: ServeConfig) -> RunnerResult<()> {
         cwd: config.cwd.clone(),
         size: TerminalSize::default(),
         run_id,
-        env: config.policy.env.clone(),
+        env,
+        separate_stderr: false,
+        output_buffer: OutputBufferConfig::default(),
+        resize: ResizeConfig::default(),
+        remote: None,
+        encoding: config.policy.terminal.encoding,
     })?;
+    if let Some(container) = &cleanup_guard.container {
+        session.set_container(container.clone());
+    }
 
     // --- Initial observation ---
     let initial_obs = session.observe(Duration::from_millis(500))?;
@@ -229,8 +263,7 @@ pub fn run_serve(mut config: ServeConfig) -> RunnerResult<()> {
 
     // --- Cleanup ---
     let _ = std::fs::remove_file(&config.socket_path);
-    let _ = session.terminate();
-    let _ = session.terminate_process_group(Duration::from_millis(500));
+    let _ = session.terminate_with(&config.policy.process);
 
     Ok(())
 }
@@ -0,0 +1,470 @@
+//! Warm session pool for `ptybox serve --pool`.
+//!
+//! Spawning a PTY and sandbox for every episode adds latency that dominates
+//! short agent episodes at scale. A [`SessionPool`] keeps `size` children of
+//! one command and policy spawned ahead of time and leases one to each
+//! episode: one protocol v2 driver conversation (handshake, requests,
+//! responses), exactly as `ptybox driver --stdio --json` runs it. Budgets
+//! and step numbering start when the episode does, not when the child was
+//! spawned. [`run_pool`] serves episodes on a Unix domain socket, one per
+//! connection.
+//!
+//! A new child is observed for [`SessionPoolConfig::warmup`] and the
+//! resulting `screen_hash` becomes its baseline. After an episode the child
+//! is stopped and replaced, unless [`SessionPoolConfig::recycle`] is set and
+//! the cleanliness check passes:
+//! - the client closed its connection without `terminate` and no error
+//!   (budget, protocol, process exit) ended the episode
+//! - the child is still running and no write quota was exceeded
+//! - after another `warmup` of observation, the screen is back to the
+//!   baseline
+//!
+//! The check only sees what is on screen. Recycle commands that reset
+//! themselves between episodes (a REPL that clears on disconnect, a menu
+//! that returns home); state the screen does not show survives recycling.
+//!
+//! Each episode writes its own artifacts under
+//! `<artifacts_root>/<episode_id>`; output the child printed while idle in
+//! the pool is not recorded. `policy.artifacts.dir` is not used.
+
+use crate::artifacts::ArtifactsWriterConfig;
+use crate::driver::{
+    drive_episode, error_response, finish_episode, open_driver_artifacts, validate_driver_config,
+    DriverConfig, DriverProcess,
+};
+use crate::model::policy::Policy;
+use crate::model::{AcknowledgementReport, EnforcementReport, ErrorInfo, RunId, SessionId};
+use crate::policy::acknowledgements::acknowledgement_report;
+use crate::policy::validate_artifacts_dir;
+use crate::runner::{ErrorCode, RunnerError, RunnerResult};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::net::Shutdown;
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use super::gateway::{bind_socket, ACCEPT_POLL};
+
+/// Most children a pool keeps ready.
+pub const MAX_POOL_SIZE: usize = 64;
+
+/// Command, policy, and sizing of a [`SessionPool`].
+#[derive(Clone, Debug)]
+pub struct SessionPoolConfig {
+    /// Command every child runs.
+    pub command: String,
+    /// Command arguments.
+    pub args: Vec<String>,
+    /// Optional working directory.
+    pub cwd: Option<String>,
+    /// Security policy for every child.
+    pub policy: Policy,
+    /// Route each child's stderr to a separate pipe.
+    pub separate_stderr: bool,
+    /// Children kept ready (1 to [`MAX_POOL_SIZE`]).
+    pub size: usize,
+    /// Return children that pass the cleanliness check to the pool instead
+    /// of replacing them.
+    pub recycle: bool,
+    /// How long a child is observed before its screen is compared with (or
+    /// becomes) the baseline.
+    pub warmup: Duration,
+    /// Directory receiving one artifacts directory per episode (absolute
+    /// path, inside the policy's `fs.allowed_write`).
+    pub artifacts_root: Option<PathBuf>,
+}
+
+/// What happened in one episode, as reported by [`SessionPool::run_episode`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PoolEpisode {
+    /// Episode identifier, also the artifacts directory name.
+    pub episode_id: String,
+    /// Run identifier of the leased child (shared by the episodes of a
+    /// recycled child).
+    pub run_id: String,
+    /// The child came from the pool rather than being spawned on demand.
+    pub warm: bool,
+    /// The child passed the cleanliness check and went back to the pool.
+    pub recycled: bool,
+    /// Why the child was not recycled (absent when recycling is off).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discard_reason: Option<String>,
+    /// Artifacts directory of the episode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifacts_dir: Option<String>,
+    /// Error that ended the episode, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorInfo>,
+}
+
+/// Counters of a [`SessionPool`] since it was created.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Children ready to lease now.
+    pub idle: usize,
+    /// Children spawned, including replacements and on-demand spawns.
+    pub spawned: u64,
+    /// Episodes served by a child from the pool.
+    pub warm_leases: u64,
+    /// Episodes that had to spawn their child because the pool was empty.
+    pub cold_leases: u64,
+    /// Children returned to the pool after an episode.
+    pub recycled: u64,
+    /// Children stopped after an episode.
+    pub discarded: u64,
+}
+
+/// A spawned child and the screen it showed once warmed up.
+struct WarmProcess {
+    process: DriverProcess,
+    baseline: String,
+}
+
+/// Children of one command and policy, spawned ahead of the episodes that
+/// lease them. Dropping the pool kills the idle children.
+pub struct SessionPool {
+    driver: DriverConfig,
+    enforcement: Option<EnforcementReport>,
+    acknowledgements: Option<AcknowledgementReport>,
+    size: usize,
+    recycle: bool,
+    warmup: Duration,
+    artifacts_root: Option<PathBuf>,
+    idle: Mutex<Vec<WarmProcess>>,
+    stats: Mutex<PoolStats>,
+}
+
+impl SessionPool {
+    /// Validate the policy and command once, then spawn and warm `size`
+    /// children.
+    ///
+    /// # Errors
+    /// - `E_PROTOCOL` if `size` is 0 or above [`MAX_POOL_SIZE`]
+    /// - `E_POLICY_DENIED` if the policy, command, or artifacts root is denied
+    /// - Any spawn error of the first children
+    pub fn new(config: SessionPoolConfig) -> RunnerResult<Self> {
+        if config.size == 0 || config.size > MAX_POOL_SIZE {
+            return Err(RunnerError::with_context(
+                ErrorCode::Protocol,
+                "pool size must be between 1 and the maximum",
+                serde_json::json!({ "size": config.size, "max": MAX_POOL_SIZE }),
+            ));
+        }
+        let driver = DriverConfig {
+            command: config.command,
+            args: config.args,
+            cwd: config.cwd,
+            policy: config.policy,
+            artifacts: None,
+            separate_stderr: config.separate_stderr,
+            ack_overrides: Vec::new(),
+        };
+        let enforcement = validate_driver_config(&driver)?;
+        let acknowledgements = acknowledgement_report(&driver.policy, &driver.ack_overrides);
+        if let Some(root) = &config.artifacts_root {
+            validate_artifacts_dir(root, &driver.policy.fs)?;
+        }
+        let pool = Self {
+            driver,
+            enforcement,
+            acknowledgements,
+            size: config.size,
+            recycle: config.recycle,
+            warmup: config.warmup,
+            artifacts_root: config.artifacts_root,
+            idle: Mutex::new(Vec::with_capacity(config.size)),
+            stats: Mutex::new(PoolStats::default()),
+        };
+        for _ in 0..pool.size {
+            let warm = pool.spawn_warm()?;
+            pool.idle_list().push(warm);
+        }
+        Ok(pool)
+    }
+
+    /// Current counters.
+    #[must_use]
+    pub fn stats(&self) -> PoolStats {
+        let mut stats = *self.stats.lock().unwrap_or_else(PoisonError::into_inner);
+        stats.idle = self.idle_list().len();
+        stats
+    }
+
+    /// Lease a child, run one driver episode on `input`/`output`, then
+    /// recycle or replace the child.
+    ///
+    /// # Errors
+    /// Only when the episode cannot start: no child could be spawned or its
+    /// artifacts directory could not be created. Errors during the episode
+    /// are reported to the client and in [`PoolEpisode::error`].
+    pub fn run_episode<R, W>(&self, input: R, output: W) -> RunnerResult<PoolEpisode>
+    where
+        R: BufRead + Send + 'static,
+        W: Write,
+    {
+        let pooled = self.idle_list().pop();
+        let warm = pooled.is_some();
+        let WarmProcess {
+            mut process,
+            baseline,
+        } = match pooled {
+            Some(pooled) => pooled,
+            None => self.spawn_warm()?,
+        };
+        let episode_id = SessionId::new().to_string();
+        let artifacts_dir = self
+            .artifacts_root
+            .as_ref()
+            .map(|root| root.join(&episode_id));
+        let writer = match open_driver_artifacts(
+            &self.driver.policy,
+            self.enforcement.as_ref(),
+            self.acknowledgements.as_ref(),
+            process.run_id(),
+            artifacts_dir.clone().map(|dir| ArtifactsWriterConfig {
+                dir,
+                overwrite: false,
+            }),
+        ) {
+            Ok(writer) => writer,
+            Err(err) => {
+                self.release(WarmProcess { process, baseline });
+                return Err(err);
+            }
+        };
+        self.count(|stats| {
+            if warm {
+                stats.warm_leases += 1;
+            } else {
+                stats.cold_leases += 1;
+            }
+        });
+
+        let mut report = PoolEpisode {
+            episode_id,
+            run_id: process.run_id().to_string(),
+            warm,
+            recycled: false,
+            discard_reason: None,
+            artifacts_dir: artifacts_dir.as_ref().map(|dir| dir.display().to_string()),
+            error: None,
+        };
+        let episode = match drive_episode(&mut process, writer, artifacts_dir, input, output) {
+            Ok(episode) => episode,
+            Err(err) => {
+                report.error = Some(err.to_error_info());
+                report.discard_reason = self.recycle.then(|| err.message.clone());
+                self.discard(process);
+                return Ok(report);
+            }
+        };
+        let dirty = if !self.recycle {
+            None
+        } else if let Some(err) = &episode.error {
+            Some(format!("episode ended with {}", err.code))
+        } else if !episode.detached {
+            Some("client terminated the session".to_string())
+        } else {
+            self.check_clean(&mut process, &baseline).err()
+        };
+        let recycle = self.recycle && dirty.is_none();
+        report.discard_reason = dirty;
+        if let Err(err) = finish_episode(&mut process, episode, !recycle) {
+            report.error = Some(err.to_error_info());
+            if recycle {
+                report.discard_reason = Some(format!("episode ended with {}", err.code));
+                self.discard(process);
+                return Ok(report);
+            }
+        }
+        if recycle {
+            report.recycled = true;
+            self.count(|stats| stats.recycled += 1);
+            self.release(WarmProcess { process, baseline });
+        } else {
+            self.discard(process);
+        }
+        Ok(report)
+    }
+
+    fn idle_list(&self) -> std::sync::MutexGuard<'_, Vec<WarmProcess>> {
+        self.idle.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn count(&self, update: impl FnOnce(&mut PoolStats)) {
+        update(&mut self.stats.lock().unwrap_or_else(PoisonError::into_inner));
+    }
+
+    /// Spawn a child and capture its baseline screen.
+    fn spawn_warm(&self) -> RunnerResult<WarmProcess> {
+        let mut process = DriverProcess::spawn(
+            &self.driver,
+            self.enforcement.clone(),
+            self.acknowledgements.clone(),
+            RunId::new(),
+            None,
+            None,
+        )?;
+        self.count(|stats| stats.spawned += 1);
+        let baseline = process.session.observe(self.warmup)?.screen.screen_hash();
+        Ok(WarmProcess { process, baseline })
+    }
+
+    /// Why `process` cannot be recycled, if anything.
+    fn check_clean(&self, process: &mut DriverProcess, baseline: &str) -> Result<(), String> {
+        if !matches!(process.session.wait_for_exit(Duration::ZERO), Ok(None)) {
+            return Err("child exited".to_string());
+        }
+        if process.session.write_quota_error().is_some() {
+            return Err("write quota exceeded".to_string());
+        }
+        let observation = process
+            .session
+            .observe(self.warmup)
+            .map_err(|err| format!("observation failed: {}", err.code))?;
+        if observation.screen.screen_hash() != baseline {
+            return Err("screen differs from the baseline".to_string());
+        }
+        Ok(())
+    }
+
+    /// Put a child back unless the pool is already full.
+    fn release(&self, warm: WarmProcess) {
+        let mut idle = self.idle_list();
+        if idle.len() < self.size {
+            idle.push(warm);
+        }
+    }
+
+    /// Drop a used child and spawn its replacement if the pool is short.
+    fn discard(&self, process: DriverProcess) {
+        drop(process);
+        self.count(|stats| stats.discarded += 1);
+        if self.idle_list().len() < self.size {
+            if let Ok(warm) = self.spawn_warm() {
+                self.release(warm);
+            }
+        }
+    }
+}
+
+/// Configuration for [`run_pool`].
+pub struct PoolConfig {
+    /// Unix domain socket to listen on.
+    pub socket_path: PathBuf,
+    /// The pool serving each connection.
+    pub pool: SessionPoolConfig,
+    /// Set to `true` to stop accepting connections and close open episodes.
+    pub shutdown: Arc<AtomicBool>,
+    /// Writer for the ready message and one [`PoolEpisode`] line per
+    /// episode (typically stdout).
+    pub output: Box<dyn Write + Send>,
+}
+
+/// Ready message written once the pool is warm and the socket is bound.
+#[derive(Serialize)]
+struct ReadyMessage {
+    ok: bool,
+    socket: String,
+    size: usize,
+    recycle: bool,
+}
+
+/// Warm the pool and serve one episode per connection until `shutdown` is
+/// set.
+///
+/// 1. Creates the [`SessionPool`] (validating the policy and spawning its
+///    children).
+/// 2. Binds the socket (mode `0600`) and writes a ready message.
+/// 3. Runs each connection's episode on its own thread and writes its
+///    [`PoolEpisode`] line when it ends.
+/// 4. On shutdown, closes open connections, kills idle children, and
+///    removes the socket.
+///
+/// # Errors
+///
+/// Returns [`RunnerError`] if the pool cannot be created (see
+/// [`SessionPool::new`]) or the socket cannot be bound (`E_IO`).
+pub fn run_pool(config: PoolConfig) -> RunnerResult<()> {
+    let pool = Arc::new(SessionPool::new(config.pool)?);
+    let listener = bind_socket(&config.socket_path)?;
+    let output = Arc::new(Mutex::new(config.output));
+    write_line(
+        &output,
+        &ReadyMessage {
+            ok: true,
+            socket: config.socket_path.display().to_string(),
+            size: pool.size,
+            recycle: pool.recycle,
+        },
+    )?;
+
+    let mut connections: Vec<(JoinHandle<()>, UnixStream)> = Vec::new();
+    while !config.shutdown.load(Ordering::SeqCst) {
+        connections.retain(|(handle, _)| !handle.is_finished());
+        let stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {
+                thread::sleep(ACCEPT_POLL);
+                continue;
+            }
+            Err(err) => return Err(RunnerError::io_err("failed to accept connection", err)),
+        };
+        let Ok(control) = stream.try_clone() else {
+            continue;
+        };
+        let pool = Arc::clone(&pool);
+        let output = Arc::clone(&output);
+        let handle = thread::Builder::new()
+            .name("ptybox-pool-episode".to_string())
+            .spawn(move || serve_episode(stream, &pool, &output))
+            .map_err(|err| RunnerError::io("E_IO", "failed to start episode thread", err))?;
+        connections.push((handle, control));
+    }
+
+    for (_, control) in &connections {
+        let _ = control.shutdown(Shutdown::Both);
+    }
+    for (handle, _) in connections {
+        let _ = handle.join();
+    }
+    let _ = std::fs::remove_file(&config.socket_path);
+    Ok(())
+}
+
+/// Run one episode on a connection and log it. An episode that cannot
+/// start is answered with a single error response.
+fn serve_episode(stream: UnixStream, pool: &SessionPool, output: &Mutex<Box<dyn Write + Send>>) {
+    if stream.set_nonblocking(false).is_err() {
+        return;
+    }
+    let Ok(read_half) = stream.try_clone() else {
+        return;
+    };
+    match pool.run_episode(BufReader::new(read_half), &stream) {
+        Ok(episode) => {
+            let _ = write_line(output, &episode);
+        }
+        Err(err) => {
+            let response = error_response("unknown", err.to_error_info(), None, None);
+            if let Ok(json) = serde_json::to_string(&response) {
+                let _ = writeln!(&stream, "{json}");
+            }
+        }
+    }
+}
+
+fn write_line(output: &Mutex<Box<dyn Write + Send>>, value: &impl Serialize) -> RunnerResult<()> {
+    let json = serde_json::to_string(value)
+        .map_err(|e| RunnerError::io_err("failed to serialize pool output", e))?;
+    let mut output = output.lock().unwrap_or_else(PoisonError::into_inner);
+    writeln!(output, "{json}")
+        .map_err(|e| RunnerError::io_err("failed to write pool output", e))?;
+    output
+        .flush()
+        .map_err(|e| RunnerError::io_err("failed to flush pool output", e))
+}
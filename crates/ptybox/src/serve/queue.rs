@@ -0,0 +1,1030 @@
+//! Scenario queue runner for long-lived `ptybox serve --queue` deployments.
+//!
+//! The queue runner watches a directory for scenario files (JSON or YAML),
+//! claims each one by moving it into `running/`, executes it with bounded
+//! concurrency, and files the job under `done/` (passed) or `failed/`
+//! (failed, errored, or unloadable) next to its result.
+//!
+//! An optional loopback HTTP endpoint exposes:
+//! - `GET /health` — `200` while accepting work, `503` while draining
+//! - `GET /status` — a [`QueueStatus`] document, with live progress for
+//!   each in-flight job
+//! - `POST /jobs` — enqueue a JSON scenario body, returns the job id
+//! - `POST /jobs/<job>/cancel` — cancel an in-flight job before its next step
+//!
+//! A posted scenario runs a command on the host, and a web page can reach a
+//! loopback port, so `POST` routes need `Authorization: Bearer <token>`
+//! matching [`QueueConfig::token_sha256`] and are disabled without one.
+//! `POST /jobs` also needs `Content-Type: application/json`, and requests
+//! carrying an `Origin` header (sent by browsers) are rejected outright.
+//! Connections are served one at a time, so each request is bounded by a
+//! read timeout, a per-line length, a header count, and a body size.
+//!
+//! Jobs are named by their file name (`001-echo.json`), so `a.json` and
+//! `a.yaml` are separate jobs.
+//!
+//! [`fetch_queue_status`] and [`cancel_queue_job`] are the client side of the
+//! endpoint, used by `ptybox top`.
+//!
+//! With a locale/timezone matrix ([`QueueConfig::matrix`]) each job runs once
+//! per cell with that cell's [`DeterminismPolicy`] pinned, and passes only if
+//! every cell passes.
+//!
+//! Setting the shutdown flag starts a graceful drain: no new jobs are claimed,
+//! in-flight jobs run to completion, and [`run_queue`] returns once idle.
+
+use crate::artifacts::ArtifactsWriterConfig;
+use crate::model::policy::{Budgets, DeterminismPolicy};
+use crate::model::scenario::PolicyRef;
+use crate::model::{ErrorInfo, RunResult, RunStatus, Scenario};
+use crate::policy::validate_determinism_policy;
+use crate::runner::{
+    run_scenario, ProgressCallback, ProgressEvent, RunnerError, RunnerOptions, RunnerResult,
+};
+use crate::scenario::{load_policy_ref, load_scenario_file};
+use crate::util::sha256_hex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Subdirectory holding claimed, in-flight jobs.
+pub const QUEUE_RUNNING_DIR: &str = "running";
+/// Subdirectory holding jobs whose run passed.
+pub const QUEUE_DONE_DIR: &str = "done";
+/// Subdirectory holding jobs that failed, errored, or could not be loaded.
+pub const QUEUE_FAILED_DIR: &str = "failed";
+
+/// Maximum accepted `POST /jobs` body size in bytes.
+const MAX_JOB_BODY_BYTES: usize = 1024 * 1024;
+/// Maximum number of HTTP header lines read per request.
+const MAX_HTTP_HEADERS: usize = 64;
+/// Maximum length in bytes of the request line and of each header line.
+const MAX_HTTP_LINE_BYTES: u64 = 8 * 1024;
+/// Read/write timeout for status endpoint connections.
+const HTTP_IO_TIMEOUT: Duration = Duration::from_secs(5);
+/// Sleep between non-blocking accept attempts on the status endpoint.
+const HTTP_ACCEPT_POLL: Duration = Duration::from_millis(50);
+
+/// Configuration for the scenario queue runner.
+pub struct QueueConfig {
+    /// Directory watched for scenario job files.
+    pub queue_dir: PathBuf,
+    /// Maximum number of scenarios run concurrently (minimum 1).
+    pub concurrency: usize,
+    /// Optional loopback address for the health/status HTTP endpoint.
+    /// Port `0` binds an ephemeral port, reported in the ready message.
+    pub status_addr: Option<SocketAddr>,
+    /// Lowercase hex SHA-256 of the bearer token the endpoint's `POST`
+    /// routes require; the token itself is never stored. Without one, jobs
+    /// cannot be submitted or canceled over HTTP.
+    pub token_sha256: Option<String>,
+    /// Optional root for per-job artifacts (`<root>/<job>/`, or
+    /// `<root>/<job>/<cell>/` with a matrix).
+    pub artifacts_root: Option<PathBuf>,
+    /// Locale/timezone cells each job runs under (see
+    /// [`DeterminismPolicy::matrix`]). Empty runs each job once as written.
+    pub matrix: Vec<DeterminismPolicy>,
+    /// Interval between queue directory scans.
+    pub poll_interval: Duration,
+    /// Set to `true` to begin a graceful drain.
+    pub shutdown: Arc<AtomicBool>,
+    /// Writer for the ready message (typically stdout).
+    pub ready_output: Box<dyn Write + Send>,
+}
+
+/// Lifecycle state reported by the status endpoint.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueState {
+    /// Claiming and running jobs.
+    Running,
+    /// Shutdown requested; finishing in-flight jobs only.
+    Draining,
+}
+
+/// Snapshot of queue runner counters returned by `GET /status`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct QueueStatus {
+    /// Current lifecycle state.
+    pub state: QueueState,
+    /// Configured concurrency limit.
+    pub concurrency: usize,
+    /// Job files waiting in the queue directory.
+    pub queued: usize,
+    /// Jobs currently executing.
+    pub running: usize,
+    /// Jobs filed under `done/`.
+    pub completed: u64,
+    /// Jobs filed under `failed/`.
+    pub failed: u64,
+    /// Milliseconds since the runner started.
+    pub uptime_ms: u64,
+    /// Progress of each in-flight job, by job name.
+    #[serde(default)]
+    pub jobs: Vec<QueueJobStatus>,
+}
+
+/// Live progress of one in-flight job, reported in [`QueueStatus::jobs`].
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct QueueJobStatus {
+    /// Job name (the job file's name).
+    pub job: String,
+    /// Milliseconds since the job was claimed.
+    pub elapsed_ms: u64,
+    /// 1-based index of the step running or last run; `0` before the first.
+    pub step_index: usize,
+    /// Steps in the scenario.
+    pub total_steps: usize,
+    /// Name of the step running or last run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step: Option<String>,
+    /// Output bytes read by the job's completed steps.
+    pub output_bytes: u64,
+    /// The scenario policy's budgets, once the job has loaded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budgets: Option<Budgets>,
+    /// A cancel was requested; the job stops before its next step.
+    pub cancel_requested: bool,
+}
+
+/// Ready message written once the runner is accepting work.
+#[derive(Serialize)]
+struct ReadyMessage {
+    ok: bool,
+    queue_dir: String,
+    concurrency: usize,
+    status_addr: Option<String>,
+}
+
+/// Counters shared between the scheduler, workers, and status endpoint.
+struct QueueShared {
+    queue_dir: PathBuf,
+    concurrency: usize,
+    started_at: Instant,
+    draining: AtomicBool,
+    running: AtomicUsize,
+    completed: AtomicU64,
+    failed: AtomicU64,
+    jobs: Mutex<BTreeMap<String, JobProgress>>,
+    token_sha256: Option<String>,
+}
+
+impl QueueShared {
+    fn status(&self) -> QueueStatus {
+        QueueStatus {
+            state: if self.draining.load(Ordering::SeqCst) {
+                QueueState::Draining
+            } else {
+                QueueState::Running
+            },
+            concurrency: self.concurrency,
+            queued: pending_jobs(&self.queue_dir).map_or(0, |jobs| jobs.len()),
+            running: self.running.load(Ordering::SeqCst),
+            completed: self.completed.load(Ordering::SeqCst),
+            failed: self.failed.load(Ordering::SeqCst),
+            uptime_ms: crate::util::elapsed_ms(&self.started_at),
+            jobs: self.jobs.lock().map_or_else(
+                |_| Vec::new(),
+                |jobs| {
+                    jobs.iter()
+                        .map(|(name, progress)| progress.status(name))
+                        .collect()
+                },
+            ),
+        }
+    }
+
+    /// Apply `update` to the progress entry of job `name`, if it is running.
+    fn update_job(&self, name: &str, update: impl FnOnce(&mut JobProgress)) {
+        if let Some(progress) = self
+            .jobs
+            .lock()
+            .ok()
+            .as_mut()
+            .and_then(|jobs| jobs.get_mut(name))
+        {
+            update(progress);
+        }
+    }
+
+    /// Request cancellation of job `name`. Returns `false` if it is not running.
+    fn cancel_job(&self, name: &str) -> bool {
+        let mut found = false;
+        self.update_job(name, |progress| {
+            progress.cancel.store(true, Ordering::SeqCst);
+            found = true;
+        });
+        found
+    }
+}
+
+/// Live progress of an in-flight job.
+struct JobProgress {
+    claimed_at: Instant,
+    step_index: usize,
+    total_steps: usize,
+    step: Option<String>,
+    output_bytes: u64,
+    budgets: Option<Budgets>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl JobProgress {
+    fn status(&self, name: &str) -> QueueJobStatus {
+        QueueJobStatus {
+            job: name.to_string(),
+            elapsed_ms: crate::util::elapsed_ms(&self.claimed_at),
+            step_index: self.step_index,
+            total_steps: self.total_steps,
+            step: self.step.clone(),
+            output_bytes: self.output_bytes,
+            budgets: self.budgets.clone(),
+            cancel_requested: self.cancel.load(Ordering::SeqCst),
+        }
+    }
+}
+
+/// Records a job's runner progress into the shared job table.
+struct JobTracker {
+    shared: Arc<QueueShared>,
+    name: String,
+    cancel: Arc<AtomicBool>,
+}
+
+impl ProgressCallback for JobTracker {
+    fn on_progress(&self, event: &ProgressEvent) {
+        self.shared.update_job(&self.name, |progress| match event {
+            ProgressEvent::RunStarted { total_steps, .. } => {
+                progress.total_steps = *total_steps;
+                progress.step_index = 0;
+                progress.step = None;
+            }
+            ProgressEvent::StepStarted {
+                step_index, name, ..
+            } => {
+                progress.step_index = *step_index;
+                progress.step = Some(name.clone());
+            }
+            ProgressEvent::StepCompleted { output_bytes, .. } => {
+                progress.output_bytes = progress.output_bytes.saturating_add(*output_bytes);
+            }
+            ProgressEvent::RunCompleted { .. } => {}
+        });
+    }
+}
+
+/// Decrements the running counter and drops the job's progress entry even if
+/// a worker unwinds.
+struct RunningGuard(Arc<QueueShared>, String);
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        if let Ok(mut jobs) = self.0.jobs.lock() {
+            jobs.remove(&self.1);
+        }
+        self.0.running.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// A job claimed into `running/`.
+struct QueueJob {
+    name: String,
+    path: PathBuf,
+}
+
+/// `result.json` document for a job run under a matrix.
+#[derive(Serialize)]
+struct MatrixJobResult {
+    /// Whether every cell passed.
+    passed: bool,
+    matrix: Vec<MatrixCellResult>,
+}
+
+/// Outcome of one matrix cell.
+#[derive(Serialize)]
+struct MatrixCellResult {
+    /// Directory-safe cell name, also used for the cell's artifacts.
+    label: String,
+    /// Effective locale and timezone for the cell.
+    determinism: DeterminismPolicy,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<RunResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<ErrorInfo>,
+}
+
+/// Run the scenario queue runner until a graceful drain completes.
+///
+/// 1. Creates `running/`, `done/`, and `failed/` under the queue directory.
+/// 2. Binds the optional status endpoint (loopback only).
+/// 3. Writes a ready message to `ready_output`, then drops it.
+/// 4. Claims and runs jobs until `shutdown` is set, then waits for
+///    in-flight jobs to finish.
+///
+/// # Errors
+///
+/// Returns [`RunnerError`] if the queue directory is unusable, a matrix cell
+/// has an invalid locale or timezone, the token hash is not 64 hex
+/// characters, the status address is not loopback, or the endpoint cannot
+/// be bound.
+pub fn run_queue(mut config: QueueConfig) -> RunnerResult<()> {
+    for cell in &config.matrix {
+        validate_determinism_policy(cell)?;
+    }
+    let token_sha256 = config.token_sha256.as_deref().map(str::to_ascii_lowercase);
+    if let Some(digest) = &token_sha256 {
+        if digest.len() != 64 || !digest.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(RunnerError::policy_denied(
+                "E_POLICY_DENIED",
+                "queue token_sha256 must be 64 hex characters",
+                json!({ "fix": "Pass the lowercase hex SHA-256 of the bearer token" }),
+            ));
+        }
+    }
+    prepare_queue_dirs(&config.queue_dir)?;
+    let concurrency = config.concurrency.max(1);
+    let shared = Arc::new(QueueShared {
+        queue_dir: config.queue_dir.clone(),
+        concurrency,
+        started_at: Instant::now(),
+        draining: AtomicBool::new(false),
+        running: AtomicUsize::new(0),
+        completed: AtomicU64::new(0),
+        failed: AtomicU64::new(0),
+        jobs: Mutex::new(BTreeMap::new()),
+        token_sha256,
+    });
+
+    let stop_http = Arc::new(AtomicBool::new(false));
+    let (http_thread, bound_addr) = match config.status_addr {
+        Some(addr) => {
+            let (handle, bound) = spawn_status_endpoint(addr, &shared, &stop_http)?;
+            (Some(handle), Some(bound))
+        }
+        None => (None, None),
+    };
+
+    let ready = ReadyMessage {
+        ok: true,
+        queue_dir: config.queue_dir.display().to_string(),
+        concurrency,
+        status_addr: bound_addr.map(|addr| addr.to_string()),
+    };
+    let ready_json = serde_json::to_string(&ready)
+        .map_err(|e| RunnerError::io_err("failed to serialize ready message", e))?;
+    writeln!(config.ready_output, "{ready_json}")
+        .map_err(|e| RunnerError::io_err("failed to write ready message", e))?;
+    config
+        .ready_output
+        .flush()
+        .map_err(|e| RunnerError::io_err("failed to flush ready message", e))?;
+
+    let result = schedule_jobs(&config, &shared, concurrency);
+
+    stop_http.store(true, Ordering::SeqCst);
+    if let Some(handle) = http_thread {
+        let _ = handle.join();
+    }
+    result
+}
+
+/// Claim and dispatch jobs until shutdown is requested and all workers finish.
+fn schedule_jobs(
+    config: &QueueConfig,
+    shared: &Arc<QueueShared>,
+    concurrency: usize,
+) -> RunnerResult<()> {
+    let mut workers: Vec<JoinHandle<()>> = Vec::new();
+    loop {
+        workers.retain(|handle| !handle.is_finished());
+        if config.shutdown.load(Ordering::SeqCst) {
+            shared.draining.store(true, Ordering::SeqCst);
+            if workers.is_empty() {
+                return Ok(());
+            }
+        } else {
+            while workers.len() < concurrency {
+                let Some(job) = claim_next_job(&shared.queue_dir)? else {
+                    break;
+                };
+                workers.push(spawn_worker(
+                    job,
+                    Arc::clone(shared),
+                    config.artifacts_root.clone(),
+                    config.matrix.clone(),
+                ));
+            }
+        }
+        thread::sleep(config.poll_interval);
+    }
+}
+
+fn prepare_queue_dirs(queue_dir: &Path) -> RunnerResult<()> {
+    if !queue_dir.is_dir() {
+        return Err(RunnerError::io_err(
+            "queue directory does not exist",
+            std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                queue_dir.display().to_string(),
+            ),
+        ));
+    }
+    for sub in [QUEUE_RUNNING_DIR, QUEUE_DONE_DIR, QUEUE_FAILED_DIR] {
+        fs::create_dir_all(queue_dir.join(sub))
+            .map_err(|e| RunnerError::io_err("failed to create queue subdirectory", e))?;
+    }
+    Ok(())
+}
+
+/// List job files waiting in the queue directory, oldest name first.
+fn pending_jobs(queue_dir: &Path) -> RunnerResult<Vec<PathBuf>> {
+    let entries = fs::read_dir(queue_dir)
+        .map_err(|e| RunnerError::io_err("failed to read queue directory", e))?;
+    let mut jobs: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && is_job_file(path))
+        .collect();
+    jobs.sort();
+    Ok(jobs)
+}
+
+fn is_job_file(path: &Path) -> bool {
+    let hidden = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .map_or(true, |name| name.starts_with('.'));
+    let extension_ok = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| matches!(ext, "json" | "yaml" | "yml"));
+    !hidden && extension_ok
+}
+
+/// Claim the next pending job by renaming it into `running/`.
+///
+/// A rename that loses a race with another runner is skipped. The job is
+/// named by its full file name, so `a.json` and `a.yaml` do not share
+/// progress, results, or artifacts.
+fn claim_next_job(queue_dir: &Path) -> RunnerResult<Option<QueueJob>> {
+    for source in pending_jobs(queue_dir)? {
+        let Some(file_name) = source.file_name() else {
+            continue;
+        };
+        let claimed = queue_dir.join(QUEUE_RUNNING_DIR).join(file_name);
+        match fs::rename(&source, &claimed) {
+            Ok(()) => {
+                return Ok(Some(QueueJob {
+                    name: file_name.to_string_lossy().into_owned(),
+                    path: claimed,
+                }))
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(RunnerError::io_err("failed to claim queue job", err)),
+        }
+    }
+    Ok(None)
+}
+
+fn spawn_worker(
+    job: QueueJob,
+    shared: Arc<QueueShared>,
+    artifacts_root: Option<PathBuf>,
+    matrix: Vec<DeterminismPolicy>,
+) -> JoinHandle<()> {
+    shared.running.fetch_add(1, Ordering::SeqCst);
+    let cancel = Arc::new(AtomicBool::new(false));
+    if let Ok(mut jobs) = shared.jobs.lock() {
+        jobs.insert(
+            job.name.clone(),
+            JobProgress {
+                claimed_at: Instant::now(),
+                step_index: 0,
+                total_steps: 0,
+                step: None,
+                output_bytes: 0,
+                budgets: None,
+                cancel: Arc::clone(&cancel),
+            },
+        );
+    }
+    thread::spawn(move || {
+        let guard = RunningGuard(Arc::clone(&shared), job.name.clone());
+        let tracker = Arc::new(JobTracker {
+            shared: Arc::clone(&shared),
+            name: job.name.clone(),
+            cancel,
+        });
+        let passed = run_job(&job, artifacts_root.as_deref(), &matrix, &tracker);
+        let counter = if passed {
+            &shared.completed
+        } else {
+            &shared.failed
+        };
+        counter.fetch_add(1, Ordering::SeqCst);
+        drop(guard);
+    })
+}
+
+/// Run a claimed job and file it with its result. Returns `true` if it passed.
+fn run_job(
+    job: &QueueJob,
+    artifacts_root: Option<&Path>,
+    matrix: &[DeterminismPolicy],
+    tracker: &Arc<JobTracker>,
+) -> bool {
+    let job_dir = artifacts_root.map(|root| root.join(&job.name));
+    let outcome = load_scenario_file(&job.path.to_string_lossy()).and_then(|scenario| {
+        let budgets = load_policy_ref(&scenario.run.policy)?.budgets;
+        tracker
+            .shared
+            .update_job(&job.name, |progress| progress.budgets = Some(budgets));
+        if matrix.is_empty() {
+            let options = job_options(job_dir, artifacts_root, tracker);
+            let result = run_scenario(scenario, options)?;
+            Ok((
+                result.status == RunStatus::Passed,
+                serde_json::to_value(&result),
+            ))
+        } else {
+            run_matrix(
+                &scenario,
+                job_dir.as_deref(),
+                artifacts_root,
+                matrix,
+                tracker,
+            )
+        }
+    });
+
+    let (passed, suffix, document) = match outcome {
+        Ok((passed, document)) => (passed, "result.json", document),
+        Err(err) => (
+            false,
+            "error.json",
+            serde_json::to_value(err.to_error_info()),
+        ),
+    };
+
+    let Some(queue_dir) = job.path.parent().and_then(Path::parent) else {
+        return false;
+    };
+    let dest_dir = queue_dir.join(if passed {
+        QUEUE_DONE_DIR
+    } else {
+        QUEUE_FAILED_DIR
+    });
+    if let Err(err) = file_job(job, &dest_dir, suffix, document) {
+        tracing::warn!(job = %job.name, error = %err, "failed to file queue job");
+        return false;
+    }
+    passed
+}
+
+fn job_options(
+    artifacts_dir: Option<PathBuf>,
+    artifacts_root: Option<&Path>,
+    tracker: &Arc<JobTracker>,
+) -> RunnerOptions {
+    RunnerOptions {
+        artifacts: artifacts_dir.map(|dir| ArtifactsWriterConfig {
+            dir,
+            overwrite: false,
+        }),
+        progress: Some(Arc::clone(tracker) as Arc<dyn ProgressCallback>),
+        run_index: artifacts_root.map(Path::to_path_buf),
+        cancel: Some(Arc::clone(&tracker.cancel)),
+        ..RunnerOptions::default()
+    }
+}
+
+/// Run `scenario` once per matrix cell with the cell's locale and timezone
+/// pinned over the scenario policy. A cell that errors is recorded and does
+/// not stop the remaining cells.
+fn run_matrix(
+    scenario: &Scenario,
+    job_dir: Option<&Path>,
+    artifacts_root: Option<&Path>,
+    matrix: &[DeterminismPolicy],
+    tracker: &Arc<JobTracker>,
+) -> RunnerResult<(bool, serde_json::Result<Value>)> {
+    let base_policy = load_policy_ref(&scenario.run.policy)?;
+    let mut passed = true;
+    let mut cells = Vec::with_capacity(matrix.len());
+    for cell in matrix {
+        let mut policy = base_policy.clone();
+        policy.determinism.apply(cell);
+        let label = policy.determinism.cell_label();
+        let mut cell_scenario = scenario.clone();
+        cell_scenario.run.policy = PolicyRef::Inline(Box::new(policy.clone()));
+        let options = job_options(job_dir.map(|dir| dir.join(&label)), artifacts_root, tracker);
+        let (result, error) = match run_scenario(cell_scenario, options) {
+            Ok(result) => (Some(result), None),
+            Err(err) => (None, Some(err.to_error_info())),
+        };
+        passed &= result
+            .as_ref()
+            .is_some_and(|result| result.status == RunStatus::Passed);
+        cells.push(MatrixCellResult {
+            label,
+            determinism: policy.determinism,
+            result,
+            error,
+        });
+    }
+    Ok((
+        passed,
+        serde_json::to_value(MatrixJobResult {
+            passed,
+            matrix: cells,
+        }),
+    ))
+}
+
+fn file_job(
+    job: &QueueJob,
+    dest_dir: &Path,
+    suffix: &str,
+    document: serde_json::Result<Value>,
+) -> RunnerResult<()> {
+    let document =
+        document.map_err(|e| RunnerError::io_err("failed to serialize job result", e))?;
+    let data = serde_json::to_vec_pretty(&document)
+        .map_err(|e| RunnerError::io_err("failed to serialize job result", e))?;
+    fs::write(dest_dir.join(format!("{}.{suffix}", job.name)), data)
+        .map_err(|e| RunnerError::io_err("failed to write job result", e))?;
+    if let Some(file_name) = job.path.file_name() {
+        fs::rename(&job.path, dest_dir.join(file_name))
+            .map_err(|e| RunnerError::io_err("failed to move finished job", e))?;
+    }
+    Ok(())
+}
+
+// =============================================================================
+// Status endpoint
+// =============================================================================
+
+fn spawn_status_endpoint(
+    addr: SocketAddr,
+    shared: &Arc<QueueShared>,
+    stop: &Arc<AtomicBool>,
+) -> RunnerResult<(JoinHandle<()>, SocketAddr)> {
+    if !addr.ip().is_loopback() {
+        return Err(RunnerError::policy_denied(
+            "E_POLICY_DENIED",
+            "queue status endpoint must bind a loopback address",
+            json!({ "status_addr": addr.to_string() }),
+        ));
+    }
+    let listener = TcpListener::bind(addr)
+        .map_err(|e| RunnerError::io_err("failed to bind status endpoint", e))?;
+    listener
+        .set_nonblocking(true)
+        .map_err(|e| RunnerError::io_err("failed to set status endpoint non-blocking", e))?;
+    let bound = listener
+        .local_addr()
+        .map_err(|e| RunnerError::io_err("failed to read status endpoint address", e))?;
+
+    let shared = Arc::clone(shared);
+    let stop = Arc::clone(stop);
+    let handle = thread::spawn(move || {
+        while !stop.load(Ordering::SeqCst) {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    let _ = handle_http(stream, &shared);
+                }
+                Err(_) => thread::sleep(HTTP_ACCEPT_POLL),
+            }
+        }
+    });
+    Ok((handle, bound))
+}
+
+/// A parsed HTTP/1.1 request.
+struct HttpRequest {
+    method: String,
+    path: String,
+    /// Token of an `Authorization: Bearer` header.
+    bearer: Option<String>,
+    content_type: Option<String>,
+    /// An `Origin` header was sent, so the request came from a browser.
+    has_origin: bool,
+    body: Vec<u8>,
+    body_too_large: bool,
+}
+
+fn handle_http(stream: TcpStream, shared: &QueueShared) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(HTTP_IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(HTTP_IO_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let (status, body) = match read_http_request(&mut reader) {
+        Ok(request) => route_http(&request, shared),
+        Err(err) => (400, error_body(&format!("malformed request: {err}"))),
+    };
+    write_http_response(stream, status, &body)
+}
+
+/// Read one CRLF-terminated line of at most [`MAX_HTTP_LINE_BYTES`], so a
+/// client streaming a line without a newline cannot grow it without bound.
+fn read_http_line(reader: &mut impl BufRead, line: &mut String) -> std::io::Result<usize> {
+    let read = reader
+        .by_ref()
+        .take(MAX_HTTP_LINE_BYTES + 1)
+        .read_line(line)?;
+    if read as u64 > MAX_HTTP_LINE_BYTES {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "request line or header too long",
+        ));
+    }
+    Ok(read)
+}
+
+fn read_http_request(reader: &mut impl BufRead) -> std::io::Result<HttpRequest> {
+    let mut request_line = String::new();
+    read_http_line(reader, &mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut content_length = 0usize;
+    let mut bearer = None;
+    let mut content_type = None;
+    let mut has_origin = false;
+    let mut headers = 0;
+    loop {
+        let mut line = String::new();
+        if read_http_line(reader, &mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        headers += 1;
+        if headers > MAX_HTTP_HEADERS {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "too many headers",
+            ));
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (name, value) = (name.trim().to_ascii_lowercase(), value.trim());
+        match name.as_str() {
+            "content-length" => {
+                content_length = value.parse().map_err(|_| {
+                    std::io::Error::new(std::io::ErrorKind::InvalidData, "bad content-length")
+                })?;
+            }
+            "authorization" => {
+                bearer = value
+                    .strip_prefix("Bearer ")
+                    .map(|token| token.trim().to_string());
+            }
+            "content-type" => content_type = Some(value.to_ascii_lowercase()),
+            "origin" => has_origin = true,
+            _ => {}
+        }
+    }
+
+    let body_too_large = content_length > MAX_JOB_BODY_BYTES;
+    let mut body = Vec::new();
+    if !body_too_large {
+        reader.take(content_length as u64).read_to_end(&mut body)?;
+    }
+    Ok(HttpRequest {
+        method,
+        path,
+        bearer,
+        content_type,
+        has_origin,
+        body,
+        body_too_large,
+    })
+}
+
+fn route_http(request: &HttpRequest, shared: &QueueShared) -> (u16, Value) {
+    if request.has_origin {
+        return (403, error_body("cross-origin requests are not accepted"));
+    }
+    if request.method == "POST" {
+        if let Err(denied) = authorize(request, shared) {
+            return denied;
+        }
+    }
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/health") => {
+            let status = shared.status();
+            let ok = status.state == QueueState::Running;
+            (
+                if ok { 200 } else { 503 },
+                json!({ "ok": ok, "state": status.state }),
+            )
+        }
+        ("GET", "/status") => match serde_json::to_value(shared.status()) {
+            Ok(value) => (200, value),
+            Err(err) => (500, error_body(&err.to_string())),
+        },
+        ("POST", "/jobs") => submit_job(request, shared),
+        (_, "/health" | "/status" | "/jobs") => (405, error_body("method not allowed")),
+        (method, path) => match cancel_route(path) {
+            Some(job) if method == "POST" => {
+                if shared.cancel_job(job) {
+                    (202, json!({ "ok": true, "job": job }))
+                } else {
+                    (404, error_body("job is not running"))
+                }
+            }
+            Some(_) => (405, error_body("method not allowed")),
+            None => (404, error_body("not found")),
+        },
+    }
+}
+
+/// Check the request's bearer token against the configured token hash.
+fn authorize(request: &HttpRequest, shared: &QueueShared) -> Result<(), (u16, Value)> {
+    let Some(expected) = &shared.token_sha256 else {
+        return Err((
+            403,
+            error_body("job submission and cancellation need a configured token"),
+        ));
+    };
+    match &request.bearer {
+        Some(token) if sha256_hex(token.as_bytes()) == *expected => Ok(()),
+        _ => Err((401, error_body("missing or invalid bearer token"))),
+    }
+}
+
+/// Job name addressed by a `/jobs/<job>/cancel` path.
+fn cancel_route(path: &str) -> Option<&str> {
+    path.strip_prefix("/jobs/")?
+        .strip_suffix("/cancel")
+        .filter(|job| !job.is_empty() && !job.contains('/'))
+}
+
+/// Validate a posted scenario and write it atomically into the queue.
+fn submit_job(request: &HttpRequest, shared: &QueueShared) -> (u16, Value) {
+    if shared.draining.load(Ordering::SeqCst) {
+        return (503, error_body("queue is draining"));
+    }
+    if request.body_too_large {
+        return (413, error_body("job body exceeds 1 MiB"));
+    }
+    let json_body = request.content_type.as_deref().is_some_and(|content_type| {
+        content_type.split(';').next().map(str::trim) == Some("application/json")
+    });
+    if !json_body {
+        return (415, error_body("job body must be application/json"));
+    }
+    if let Err(err) = serde_json::from_slice::<Scenario>(&request.body) {
+        return (400, error_body(&format!("invalid scenario: {err}")));
+    }
+
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis());
+    let suffix = uuid::Uuid::new_v4().simple().to_string();
+    let job_id = format!("{millis:013}-{}", suffix.get(..8).unwrap_or(&suffix));
+    let staging = shared.queue_dir.join(format!(".{job_id}.tmp"));
+    let job = format!("{job_id}.json");
+    let target = shared.queue_dir.join(&job);
+    let written = fs::write(&staging, &request.body).and_then(|()| fs::rename(&staging, &target));
+    match written {
+        Ok(()) => (202, json!({ "ok": true, "job_id": job })),
+        Err(err) => {
+            let _ = fs::remove_file(&staging);
+            (500, error_body(&format!("failed to enqueue job: {err}")))
+        }
+    }
+}
+
+fn error_body(message: &str) -> Value {
+    json!({ "ok": false, "error": message })
+}
+
+fn write_http_response(mut stream: TcpStream, status: u16, body: &Value) -> std::io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        415 => "Unsupported Media Type",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    };
+    let payload = body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{payload}",
+        payload.len()
+    )?;
+    stream.flush()
+}
+
+// =============================================================================
+// Status client
+// =============================================================================
+
+/// Fetch the [`QueueStatus`] of the queue runner whose status endpoint
+/// listens on `addr`.
+///
+/// # Errors
+/// Returns `E_IO` if the endpoint cannot be reached and `E_PROTOCOL` if the
+/// response is not a status document.
+pub fn fetch_queue_status(addr: SocketAddr) -> RunnerResult<QueueStatus> {
+    let (status, body) = http_exchange(addr, "GET", "/status", None)?;
+    if status != 200 {
+        return Err(endpoint_error(status, &body));
+    }
+    serde_json::from_value(body.clone()).map_err(|err| {
+        RunnerError::protocol(
+            "E_PROTOCOL",
+            "invalid queue status document",
+            json!({ "parse_error": err.to_string(), "body": body }),
+        )
+    })
+}
+
+/// Ask the queue runner on `addr` to cancel in-flight job `job`,
+/// authenticating with bearer `token`.
+///
+/// # Errors
+/// Returns `E_IO` if the endpoint cannot be reached and `E_PROTOCOL` if the
+/// token is refused or the job is not running.
+pub fn cancel_queue_job(addr: SocketAddr, job: &str, token: Option<&str>) -> RunnerResult<()> {
+    let (status, body) = http_exchange(addr, "POST", &format!("/jobs/{job}/cancel"), token)?;
+    if status == 202 {
+        Ok(())
+    } else {
+        Err(endpoint_error(status, &body))
+    }
+}
+
+fn endpoint_error(status: u16, body: &Value) -> RunnerError {
+    let message = body
+        .get("error")
+        .and_then(Value::as_str)
+        .unwrap_or("unexpected status endpoint response");
+    RunnerError::protocol(
+        "E_PROTOCOL",
+        message,
+        json!({ "http_status": status, "body": body }),
+    )
+}
+
+/// Send one bodiless request and read the JSON response.
+fn http_exchange(
+    addr: SocketAddr,
+    method: &str,
+    path: &str,
+    token: Option<&str>,
+) -> RunnerResult<(u16, Value)> {
+    let io_err = |err| RunnerError::io("E_IO", "status endpoint request failed", err);
+    let mut stream = TcpStream::connect_timeout(&addr, HTTP_IO_TIMEOUT).map_err(io_err)?;
+    stream
+        .set_read_timeout(Some(HTTP_IO_TIMEOUT))
+        .map_err(io_err)?;
+    stream
+        .set_write_timeout(Some(HTTP_IO_TIMEOUT))
+        .map_err(io_err)?;
+    let authorization = token
+        .map(|token| format!("Authorization: Bearer {token}\r\n"))
+        .unwrap_or_default();
+    write!(
+        stream,
+        "{method} {path} HTTP/1.1\r\nHost: {addr}\r\n{authorization}Content-Length: 0\r\nConnection: close\r\n\r\n"
+    )
+    .and_then(|()| stream.flush())
+    .map_err(io_err)?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).map_err(io_err)?;
+
+    let malformed = || {
+        RunnerError::protocol(
+            "E_PROTOCOL",
+            "malformed status endpoint response",
+            json!({ "response": response }),
+        )
+    };
+    let status = response
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(malformed)?;
+    let body = response
+        .split_once("\r\n\r\n")
+        .and_then(|(_, body)| serde_json::from_str(body).ok())
+        .ok_or_else(malformed)?;
+    Ok((status, body))
+}
@@ -0,0 +1,157 @@
+//! Decoding of PTY output per [`TerminalEncoding`] (policy
+//! `terminal.encoding`).
+//!
+//! Output arrives in arbitrary chunks, so a UTF-8 sequence split across
+//! reads is held back until the rest arrives. At EOF an incomplete sequence
+//! is invalid. In `utf8_lossy` mode invalid bytes become U+FFFD and are
+//! reported in a `decode_error` event instead of failing the observation.
+
+use crate::model::policy::TerminalEncoding;
+use crate::model::Event;
+use crate::runner::{ErrorCode, RunnerError};
+use std::borrow::Cow;
+
+/// Longest UTF-8 sequence; a longer pending tail cannot be incomplete.
+const MAX_UTF8_SEQUENCE: usize = 4;
+
+/// Stateful decoder for one session's output.
+pub(super) struct OutputDecoder {
+    encoding: TerminalEncoding,
+    /// Start of a UTF-8 sequence whose remaining bytes have not been read.
+    pending: Vec<u8>,
+}
+
+/// Text decoded from one read, with the `decode_error` event when invalid
+/// bytes were replaced.
+pub(super) struct Decoded {
+    pub(super) text: Option<String>,
+    pub(super) error_event: Option<Event>,
+}
+
+impl OutputDecoder {
+    pub(super) fn new(encoding: TerminalEncoding) -> Self {
+        Self {
+            encoding,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Bytes to feed the terminal emulator, which parses UTF-8: Latin-1
+    /// output is transcoded, UTF-8 is passed through.
+    pub(super) fn for_terminal<'a>(&self, bytes: &'a [u8]) -> Cow<'a, [u8]> {
+        match self.encoding {
+            TerminalEncoding::Latin1 if !bytes.is_ascii() => {
+                Cow::Owned(latin1_to_string(bytes).into_bytes())
+            }
+            _ => Cow::Borrowed(bytes),
+        }
+    }
+
+    /// Decode `bytes` read since the previous call; `eof` means no more
+    /// output follows.
+    ///
+    /// # Errors
+    /// `E_TERMINAL_PARSE` in `utf8_strict` mode when the output is not
+    /// valid UTF-8.
+    pub(super) fn decode(&mut self, bytes: &[u8], eof: bool) -> Result<Decoded, RunnerError> {
+        if self.encoding == TerminalEncoding::Latin1 {
+            return Ok(Decoded {
+                text: (!bytes.is_empty()).then(|| latin1_to_string(bytes)),
+                error_event: None,
+            });
+        }
+        if bytes.is_empty() && self.pending.is_empty() {
+            return Ok(Decoded {
+                text: None,
+                error_event: None,
+            });
+        }
+        let mut combined = std::mem::take(&mut self.pending);
+        combined.extend_from_slice(bytes);
+        let mut text = String::with_capacity(combined.len());
+        let mut errors = InvalidBytes::default();
+        let mut rest = combined.as_slice();
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(valid) => {
+                    text.push_str(valid);
+                    break;
+                }
+                Err(err) => {
+                    let valid = rest.get(..err.valid_up_to()).unwrap_or_default();
+                    let after = rest.get(err.valid_up_to()..).unwrap_or_default();
+                    text.push_str(std::str::from_utf8(valid).unwrap_or_default());
+                    let offset = combined.len() - rest.len() + err.valid_up_to();
+                    let invalid_len = match err.error_len() {
+                        // An incomplete sequence at the end: wait for the rest.
+                        None if !eof && after.len() < MAX_UTF8_SEQUENCE => {
+                            self.pending = after.to_vec();
+                            break;
+                        }
+                        None => after.len(),
+                        Some(len) => len,
+                    };
+                    if self.encoding == TerminalEncoding::Utf8Strict {
+                        return Err(invalid_utf8(&err, offset, eof));
+                    }
+                    errors.record(offset, invalid_len);
+                    text.push(char::REPLACEMENT_CHARACTER);
+                    rest = after.get(invalid_len..).unwrap_or_default();
+                }
+            }
+        }
+        Ok(Decoded {
+            text: (!text.is_empty()).then_some(text),
+            error_event: errors.event(),
+        })
+    }
+}
+
+/// Invalid sequences replaced during one decode.
+#[derive(Default)]
+struct InvalidBytes {
+    sequences: u64,
+    bytes: u64,
+    first_offset: Option<usize>,
+}
+
+impl InvalidBytes {
+    fn record(&mut self, offset: usize, len: usize) {
+        self.sequences += 1;
+        self.bytes += len as u64;
+        self.first_offset.get_or_insert(offset);
+    }
+
+    fn event(&self) -> Option<Event> {
+        (self.sequences > 0).then(|| Event {
+            event_type: "decode_error".to_string(),
+            message: Some(format!(
+                "terminal output was not valid UTF-8; {} byte(s) replaced with U+FFFD",
+                self.bytes
+            )),
+            details: Some(serde_json::json!({
+                "encoding": "utf8_lossy",
+                "sequences": self.sequences,
+                "bytes": self.bytes,
+                "first_offset": self.first_offset,
+            })),
+        })
+    }
+}
+
+fn latin1_to_string(bytes: &[u8]) -> String {
+    bytes.iter().copied().map(char::from).collect()
+}
+
+fn invalid_utf8(err: &std::str::Utf8Error, offset: usize, eof: bool) -> RunnerError {
+    RunnerError::with_context(
+        ErrorCode::TerminalParse,
+        "terminal output was not valid UTF-8",
+        serde_json::json!({
+            "source": err.to_string(),
+            "valid_up_to": offset,
+            "eof": eof,
+            "fix": "Set policy.terminal.encoding to utf8_lossy or latin1 for apps that emit other encodings",
+        }),
+    )
+}
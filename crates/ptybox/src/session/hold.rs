@@ -0,0 +1,104 @@
+//! Held-key simulation: a key written repeatedly on a fixed schedule, the way
+//! terminal auto-repeat delivers a held arrow key.
+
+use super::{key_to_bytes, PayloadExt, Session};
+use crate::model::{KeyHold, DEFAULT_KEY_REPEAT_HZ, MAX_KEY_HOLD_MS, MAX_KEY_REPEAT_HZ};
+use crate::runner::RunnerError;
+use crate::util::{elapsed_ms, encode_hex, pause_until};
+use serde_json::Value;
+use std::time::{Duration, Instant};
+
+/// Longest single sleep between presses, so the schedule stays accurate.
+const MAX_PAUSE: Duration = Duration::from_millis(20);
+
+impl Session {
+    /// Hold `key` for `duration_ms`, writing it `repeat_hz` times per second.
+    ///
+    /// Presses are scheduled at `k / repeat_hz` seconds from the start for
+    /// every `k` that falls inside the hold, and the call returns once the
+    /// full duration has elapsed. Output produced meanwhile stays in the
+    /// output buffer for the next [`observe`](Self::observe). The burst is
+    /// also kept for [`take_key_hold`](Self::take_key_hold).
+    ///
+    /// # Errors
+    /// - `E_PROTOCOL`: Unknown key, or `duration_ms`/`repeat_hz` out of range
+    /// - `E_IO`: Failed to write to the PTY
+    pub fn hold_key(
+        &mut self,
+        key: &str,
+        duration_ms: u64,
+        repeat_hz: u32,
+    ) -> Result<KeyHold, RunnerError> {
+        validate_hold(duration_ms, repeat_hz)?;
+        let bytes = key_to_bytes(key, self.keymap.as_ref())?;
+        let presses = (duration_ms * u64::from(repeat_hz)).div_ceil(1000);
+        let interval_us = 1_000_000 / u64::from(repeat_hz);
+
+        let started = Instant::now();
+        let mut offsets_ms = Vec::new();
+        for press in 0..presses {
+            wait_until(started + Duration::from_micros(press * interval_us));
+            offsets_ms.push(elapsed_ms(&started));
+            self.write_input(
+                &bytes,
+                "failed to write held key",
+                "failed to flush held key",
+            )?;
+        }
+        wait_until(started + Duration::from_millis(duration_ms));
+
+        let hold = KeyHold {
+            key: key.to_string(),
+            duration_ms,
+            repeat_hz,
+            presses,
+            hex: encode_hex(&bytes),
+            offsets_ms,
+        };
+        self.last_key_hold = Some(hold.clone());
+        Ok(hold)
+    }
+
+    /// The burst written by the most recent `hold_key`, cleared on read.
+    pub fn take_key_hold(&mut self) -> Option<KeyHold> {
+        self.last_key_hold.take()
+    }
+
+    /// Run a `hold_key` action payload (`{key, duration_ms, repeat_hz?}`).
+    pub(super) fn hold_key_action(&mut self, payload: &Value) -> Result<(), RunnerError> {
+        let key = payload.extract_str("key", "hold_key action")?;
+        let duration_ms = payload.extract_u64("duration_ms", "hold_key action")?;
+        let repeat_hz = match payload.get("repeat_hz") {
+            None => DEFAULT_KEY_REPEAT_HZ,
+            Some(_) => {
+                let value = payload.extract_u64("repeat_hz", "hold_key action")?;
+                u32::try_from(value).unwrap_or(u32::MAX)
+            }
+        };
+        self.hold_key(key, duration_ms, repeat_hz).map(|_| ())
+    }
+}
+
+fn validate_hold(duration_ms: u64, repeat_hz: u32) -> Result<(), RunnerError> {
+    if !(1..=MAX_KEY_HOLD_MS).contains(&duration_ms) {
+        return Err(RunnerError::protocol(
+            "E_PROTOCOL",
+            format!("hold_key duration_ms must be between 1 and {MAX_KEY_HOLD_MS}"),
+            serde_json::json!({ "received": duration_ms, "max": MAX_KEY_HOLD_MS }),
+        ));
+    }
+    if !(1..=MAX_KEY_REPEAT_HZ).contains(&repeat_hz) {
+        return Err(RunnerError::protocol(
+            "E_PROTOCOL",
+            format!("hold_key repeat_hz must be between 1 and {MAX_KEY_REPEAT_HZ}"),
+            serde_json::json!({ "received": repeat_hz, "max": MAX_KEY_REPEAT_HZ }),
+        ));
+    }
+    Ok(())
+}
+
+fn wait_until(deadline: Instant) {
+    while Instant::now() < deadline {
+        pause_until(deadline, MAX_PAUSE);
+    }
+}
@@ -0,0 +1,169 @@
+//! Modifier chords for `key` actions.
+//!
+//! A key name may carry `Ctrl+`, `Alt+`, and `Shift+` prefixes, in any order
+//! and case, on a single character or a named key:
+//!
+//! - `Ctrl+<char>` sends the control byte: `Ctrl+C` is `0x03`, and `@`, `[`,
+//!   `\`, `]`, `^`, `_`, and space are accepted as well as letters
+//! - `Shift+<letter>` sends the uppercase letter, and `Shift+Tab` sends
+//!   back-tab (`CSI Z`)
+//! - `Alt+` sends `ESC` before the rest, as xterm does with
+//!   `metaSendsEscape`: `Alt+Enter` is `ESC CR`, `Ctrl+Alt+X` is `ESC 0x18`
+//! - on cursor, navigation, and function keys the modifiers are sent as
+//!   xterm's modifier parameter instead: `Ctrl+Up` is `CSI 1;5A`, `Shift+F5`
+//!   is `CSI 15;2~`
+//!
+//! Chords a terminal cannot tell apart from the plain key (`Shift+Enter`,
+//! `Ctrl+Escape`) are rejected rather than sent as the plain key.
+
+/// Modifiers held for a chord.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(super) struct Modifiers {
+    ctrl: bool,
+    alt: bool,
+    shift: bool,
+}
+
+impl Modifiers {
+    /// xterm's modifier parameter: 1, plus 1 for Shift, 2 for Alt, and 4
+    /// for Ctrl.
+    fn param(self) -> u8 {
+        1 + u8::from(self.shift) + 2 * u8::from(self.alt) + 4 * u8::from(self.ctrl)
+    }
+}
+
+/// Keys sent as `CSI <code>;<modifiers><final>` when modified.
+const PARAMETER_KEYS: &[(&str, &str, char)] = &[
+    ("Up", "1", 'A'),
+    ("Down", "1", 'B'),
+    ("Right", "1", 'C'),
+    ("Left", "1", 'D'),
+    ("Home", "1", 'H'),
+    ("End", "1", 'F'),
+    ("F1", "1", 'P'),
+    ("F2", "1", 'Q'),
+    ("F3", "1", 'R'),
+    ("F4", "1", 'S'),
+    ("Delete", "3", '~'),
+    ("PageUp", "5", '~'),
+    ("PageDown", "6", '~'),
+    ("F5", "15", '~'),
+    ("F6", "17", '~'),
+    ("F7", "18", '~'),
+    ("F8", "19", '~'),
+    ("F9", "20", '~'),
+    ("F10", "21", '~'),
+    ("F11", "23", '~'),
+    ("F12", "24", '~'),
+];
+
+/// Split `key` into its modifier prefixes and the key they apply to, or
+/// `None` when it has no modifier prefix.
+pub(super) fn split_modifiers(key: &str) -> Option<(Modifiers, &str)> {
+    let mut modifiers = Modifiers::default();
+    let mut rest = key;
+    while let Some((prefix, tail)) = rest.split_once('+') {
+        if tail.is_empty() {
+            break;
+        }
+        match prefix.to_ascii_lowercase().as_str() {
+            "ctrl" => modifiers.ctrl = true,
+            "alt" => modifiers.alt = true,
+            "shift" => modifiers.shift = true,
+            _ => break,
+        }
+        rest = tail;
+    }
+    (rest.len() < key.len()).then_some((modifiers, rest))
+}
+
+/// Bytes for `base` pressed with `modifiers`, or `None` when the chord is
+/// not supported.
+pub(super) fn chord_bytes(modifiers: Modifiers, base: &str) -> Option<Vec<u8>> {
+    if let Some((_, code, final_char)) = PARAMETER_KEYS.iter().find(|(name, ..)| *name == base) {
+        return Some(format!("\x1b[{code};{}{final_char}", modifiers.param()).into_bytes());
+    }
+    let mut bytes = match (base.as_bytes(), modifiers.ctrl, modifiers.shift) {
+        ([byte], ..) => char_bytes(modifiers, *byte)?,
+        (b"Tab", false, true) => b"\x1b[Z".to_vec(),
+        (b"Tab", false, false) => vec![b'\t'],
+        (b"Enter", false, false) => vec![b'\r'],
+        (b"Escape", false, false) => vec![0x1b],
+        (b"Backspace", false, false) => vec![0x7f],
+        _ => return None,
+    };
+    if modifiers.alt {
+        bytes.insert(0, 0x1b);
+    }
+    Some(bytes)
+}
+
+/// The byte for a single character with Ctrl and Shift applied.
+fn char_bytes(modifiers: Modifiers, byte: u8) -> Option<Vec<u8>> {
+    let byte = match (modifiers.shift, byte.is_ascii_alphabetic()) {
+        (true, true) => byte.to_ascii_uppercase(),
+        (true, false) => return None,
+        (false, _) => byte,
+    };
+    if !modifiers.ctrl {
+        return Some(vec![byte]);
+    }
+    match byte.to_ascii_uppercase() {
+        b' ' => Some(vec![0]),
+        upper @ b'@'..=b'_' => Some(vec![upper - b'@']),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn chord(key: &str) -> Option<Vec<u8>> {
+        let (modifiers, base) = split_modifiers(key)?;
+        chord_bytes(modifiers, base)
+    }
+
+    #[test]
+    fn control_and_shift_chords_send_single_bytes() {
+        assert_eq!(chord("Ctrl+C").unwrap(), [0x03]);
+        assert_eq!(chord("ctrl+c").unwrap(), [0x03]);
+        assert_eq!(chord("Ctrl+\\").unwrap(), [0x1c]);
+        assert_eq!(chord("Ctrl+ ").unwrap(), [0x00]);
+        assert_eq!(chord("Ctrl+Shift+a").unwrap(), [0x01]);
+        assert_eq!(chord("Shift+a").unwrap(), b"A");
+        assert_eq!(chord("Shift+Tab").unwrap(), b"\x1b[Z");
+    }
+
+    #[test]
+    fn alt_prefixes_escape() {
+        assert_eq!(chord("Alt+Enter").unwrap(), b"\x1b\r");
+        assert_eq!(chord("Alt+x").unwrap(), b"\x1bx");
+        assert_eq!(chord("Ctrl+Alt+X").unwrap(), b"\x1b\x18");
+        assert_eq!(chord("Alt+Ctrl+x").unwrap(), b"\x1b\x18");
+        assert_eq!(chord("Alt+Shift+Tab").unwrap(), b"\x1b\x1b[Z");
+        assert_eq!(chord("Alt++").unwrap(), b"\x1b+");
+    }
+
+    #[test]
+    fn navigation_and_function_keys_use_the_modifier_parameter() {
+        assert_eq!(chord("Ctrl+Up").unwrap(), b"\x1b[1;5A");
+        assert_eq!(chord("Shift+Left").unwrap(), b"\x1b[1;2D");
+        assert_eq!(chord("Alt+F1").unwrap(), b"\x1b[1;3P");
+        assert_eq!(chord("Shift+F5").unwrap(), b"\x1b[15;2~");
+        assert_eq!(chord("Ctrl+Alt+Shift+Delete").unwrap(), b"\x1b[3;8~");
+    }
+
+    #[test]
+    fn unsupported_chords_and_plain_keys() {
+        assert!(split_modifiers("Enter").is_none());
+        assert!(split_modifiers("+").is_none());
+        assert!(split_modifiers("Hyper+x").is_none());
+        assert!(chord("Ctrl+1").is_none());
+        assert!(chord("Shift+1").is_none());
+        assert!(chord("Shift+Enter").is_none());
+        assert!(chord("Ctrl+Tab").is_none());
+        assert!(chord("Ctrl+Nope").is_none());
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! This module provides [`Session`] for spawning and interacting with
 //! terminal applications via a pseudo-terminal (PTY). It handles the
-//! low-level details of PTY creation, non-blocking I/O, terminal emulation,
+//! low-level details of PTY creation, buffered background reads, terminal emulation,
 //! and process lifecycle management.
 //!
 //! # Key Types
@@ -14,16 +14,21 @@
 //!
 //! - [`Session::spawn`] - Create a new PTY session with the given configuration
 //! - [`Session::send`] - Send actions (keys, text, resize, terminate) to the session
+//! - [`Session::hold_key`] - Hold a key with timed auto-repeat
+//! - [`Session::set_termios`] - Toggle PTY echo, canonical mode, and signal keys
+//! - [`Session::send_signal`] - Send a signal to the child's process group
 //! - [`Session::observe`] - Read terminal output and capture a screen snapshot
+//! - [`Session::wait_until`] - Poll until a [`Condition`](crate::model::Condition) holds
 //! - [`Session::terminate`] - Send SIGTERM to gracefully stop the process
 //! - [`Session::terminate_process_group`] - Graceful termination with SIGKILL fallback
+//! - [`Session::terminate_with`] - Termination per a [`ProcessPolicy`]
 //! - [`Session::close`] - Explicit cleanup with full error handling
 //!
 //! # Example
 //!
 //! ```no_run
 //! use ptybox::session::{Session, SessionConfig};
-//! use ptybox::model::{Action, ActionType, RunId, TerminalSize};
+//! use ptybox::model::{Action, ActionType, ProcessPolicy, RunId, TerminalSize};
 //! use std::time::Duration;
 //!
 //! # fn example() -> Result<(), ptybox::runner::RunnerError> {
@@ -35,6 +40,11 @@
 //!     size: TerminalSize::default(),
 //!     run_id: RunId::new(),
 //!     env: Default::default(),
+//!     separate_stderr: false,
+//!     output_buffer: Default::default(),
+//!     resize: Default::default(),
+//!     remote: None,
+//!     encoding: Default::default(),
 //! };
 //! let mut session = Session::spawn(config)?;
 //!
@@ -50,7 +60,7 @@
 //! println!("Screen: {:?}", observation.screen.lines);
 //!
 //! // Clean shutdown
-//! session.close(Duration::from_millis(500))?;
+//! session.close(&ProcessPolicy::default())?;
 //! # Ok(())
 //! # }
 //! ```
@@ -61,10 +71,16 @@
 //! proper error handling use [`Session::close`] or [`Session::terminate_process_group`]
 //! before the session goes out of scope.
 
+use crate::model::policy::{Keymap, KillSignal, ProcessPolicy, TerminalEncoding};
 use crate::model::PROTOCOL_VERSION;
-use crate::model::{Action, ActionType, Event, Observation, RunId, SessionId, TerminalSize};
+use crate::model::{
+    Action, ActionType, Event, KeyHold, Observation, OutputBufferConfig, ResizeConfig, RunId,
+    ScreenSnapshot, SessionId, TerminalSize, TermiosSettings, WaitStats,
+};
 use crate::policy::apply_env_policy;
-use crate::runner::RunnerError;
+use crate::policy::container::ContainerCleanup;
+use crate::predicate::RawOutput;
+use crate::runner::{ErrorCode, ManualInputSource, ObservationTap, RunnerError};
 use crate::terminal::Terminal;
 use crate::util::pause_until;
 #[cfg(unix)]
@@ -73,10 +89,56 @@ use nix::fcntl::{fcntl, FcntlArg, OFlag};
 use nix::sys::signal::{kill, killpg, Signal};
 #[cfg(unix)]
 use nix::unistd::Pid;
-use portable_pty::{native_pty_system, CommandBuilder, PtySize};
-use std::io::{Read, Write};
+use portable_pty::{CommandBuilder, PtySize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+mod decode;
+mod hold;
+mod keys;
+mod pty;
+mod quotas;
+mod raw;
+mod reader;
+mod remote;
+mod sampling;
+mod signals;
+mod sockets;
+#[cfg(feature = "ssh")]
+mod ssh;
+#[cfg(unix)]
+mod stderr;
+mod termination;
+mod termios;
+mod wait;
+mod watchers;
+
+pub use pty::{probe_pty, pty_diagnostics, PTY_OPEN_ATTEMPTS};
+pub(crate) use raw::decode_raw_payload;
+pub use remote::RemoteConfig;
+pub(crate) use signals::decode_signal_payload;
+pub(crate) use sockets::socket_dir;
+pub use sockets::SocketDir;
+pub use termination::Termination;
+pub use watchers::validate_watchers;
+
+/// `pty_open_retried` event for a PTY that opened after failed attempts.
+fn pty_retry_events(retried: &[String]) -> Vec<Event> {
+    if retried.is_empty() {
+        return Vec::new();
+    }
+    vec![Event {
+        event_type: "pty_open_retried".to_string(),
+        message: Some(format!(
+            "opened the pty after {} failed attempt(s)",
+            retried.len()
+        )),
+        details: Some(serde_json::json!({ "errors": retried })),
+    }]
+}
+
 /// Minimum terminal rows for resize validation.
 const MIN_TERMINAL_ROWS: u16 = 1;
 /// Maximum terminal rows for resize validation.
@@ -147,6 +209,11 @@ impl PayloadExt for serde_json::Value {
 ///         size: TerminalSize::default(),
 ///         run_id: RunId::new(),
 ///         env: Default::default(),
+///         separate_stderr: false,
+///         output_buffer: Default::default(),
+///         resize: Default::default(),
+///         remote: None,
+///         encoding: Default::default(),
 ///     };
 ///     let mut session = Session::spawn(config)?;
 ///     let observation = session.observe(Duration::from_millis(50))?;
@@ -161,11 +228,26 @@ pub struct Session {
     terminal: Terminal,
     master: Box<dyn portable_pty::MasterPty + Send>,
     writer: Box<dyn Write + Send>,
-    reader: Box<dyn Read + Send>,
+    reader: reader::PtyReader,
     child: Box<dyn portable_pty::Child + Send + Sync>,
     started_at: Instant,
-    pending_utf8_tail: Vec<u8>,
-    read_buffer: Vec<u8>,
+    decoder: decode::OutputDecoder,
+    wait_stats: WaitStats,
+    manual_input: Option<Arc<dyn ManualInputSource>>,
+    observation_tap: Option<Arc<dyn ObservationTap>>,
+    last_key_hold: Option<KeyHold>,
+    last_termios: Option<TermiosSettings>,
+    watchers: Vec<watchers::ArmedWatcher>,
+    watcher_trip: Option<Observation>,
+    sampler: Option<sampling::Sampler>,
+    quotas: Option<quotas::QuotaMonitor>,
+    hook_env: BTreeMap<String, String>,
+    keymap: Option<Keymap>,
+    container: Option<ContainerCleanup>,
+    pending_events: Vec<Event>,
+    #[cfg(unix)]
+    stderr: Option<stderr::StderrPipe>,
+    remote: Option<crate::model::RemoteHost>,
 }
 
 /// Configuration for spawning a session.
@@ -183,31 +265,88 @@ pub struct SessionConfig {
     pub run_id: RunId,
     /// Environment variable policy.
     pub env: crate::model::policy::EnvPolicy,
+    /// Route the child's stderr to a separate pipe instead of the PTY.
+    /// Captured output is reported as `stderr_delta` on observations.
+    pub separate_stderr: bool,
+    /// Buffering of PTY output between observations.
+    pub output_buffer: OutputBufferConfig,
+    /// How the screen is laid out when the session is resized.
+    pub resize: ResizeConfig,
+    /// Run the command on a remote host over SSH instead of a local PTY
+    /// (`ssh` feature). `cwd` and `command` are then remote paths.
+    pub remote: Option<RemoteConfig>,
+    /// How PTY output is decoded (policy `terminal.encoding`).
+    pub encoding: TerminalEncoding,
+}
+
+/// PTY handles a session is assembled from, local or remote.
+struct SpawnedPty {
+    master: Box<dyn portable_pty::MasterPty + Send>,
+    child: Box<dyn portable_pty::Child + Send + Sync>,
+    reader: Box<dyn std::io::Read + Send>,
+    writer: Box<dyn Write + Send>,
 }
 
 impl Session {
     /// Spawn a new PTY session with the given configuration.
     ///
+    /// PTY output is read on a background thread into a buffer bounded by
+    /// [`SessionConfig::output_buffer`].
+    ///
+    /// Transient PTY open failures (`EAGAIN`, `ENOSPC`, `EMFILE`, ...) are
+    /// retried with backoff; when a retry succeeds, the first observation
+    /// carries a `pty_open_retried` event.
+    ///
+    /// With [`SessionConfig::remote`] set, the command runs on the remote
+    /// host instead (see [`RemoteConfig`]).
+    ///
     /// # Errors
-    /// Returns `RunnerError` with code `E_IO` if PTY creation or command spawn fails.
+    /// - `E_IO`: PTY creation, command spawn, or reader thread start failed.
+    ///   A PTY failure's context carries `attempts` and `diagnostics`
+    ///   ([`PtyDiagnostics`](crate::model::PtyDiagnostics)). For a remote
+    ///   session: connecting, the handshake, or authentication failed.
+    /// - `E_POLICY_DENIED`: the remote host key is not in `known_hosts`
+    /// - `E_PROTOCOL`: `output_buffer.capacity_bytes` is zero, or a remote
+    ///   session was requested with `separate_stderr` or without the `ssh`
+    ///   feature
     pub fn spawn(config: SessionConfig) -> Result<Self, RunnerError> {
         debug_assert!(config.size.rows > 0, "terminal rows must be positive");
         debug_assert!(config.size.cols > 0, "terminal cols must be positive");
         debug_assert!(!config.command.is_empty(), "command must not be empty");
 
-        let system = native_pty_system();
-        let pty_size = PtySize {
-            rows: config.size.rows,
-            cols: config.size.cols,
-            pixel_width: 0,
-            pixel_height: 0,
+        if let Some(remote) = &config.remote {
+            return Self::spawn_remote(&config, remote);
+        }
+
+        let opened = pty::open_pty(pty_size(&config.size))?;
+        let pair = opened.pair;
+
+        let session_id = SessionId::new();
+        #[cfg(unix)]
+        let stderr = if config.separate_stderr {
+            Some(stderr::StderrPipe::create(session_id)?)
+        } else {
+            None
         };
-        let pair = system
-            .openpty(pty_size)
-            .map_err(|err| RunnerError::io("E_IO", "failed to open pty", err))?;
+        #[cfg(not(unix))]
+        if config.separate_stderr {
+            return Err(RunnerError::io(
+                "E_IO",
+                "separate_stderr is only supported on unix",
+                std::io::Error::from(std::io::ErrorKind::Unsupported),
+            ));
+        }
 
-        let mut cmd = CommandBuilder::new(&config.command);
-        cmd.args(&config.args);
+        #[cfg(unix)]
+        let (command, args) = match &stderr {
+            Some(pipe) => pipe.wrap(&config.command, &config.args),
+            None => (config.command.clone(), config.args.clone()),
+        };
+        #[cfg(not(unix))]
+        let (command, args) = (config.command.clone(), config.args.clone());
+
+        let mut cmd = CommandBuilder::new(&command);
+        cmd.args(&args);
         if let Some(cwd) = &config.cwd {
             cmd.cwd(cwd);
         }
@@ -241,51 +380,87 @@ impl Session {
             }
         }
 
-        let terminal = Terminal::new(config.size);
+        Self::assemble(
+            &config,
+            session_id,
+            SpawnedPty {
+                master: pair.master,
+                child,
+                reader,
+                writer,
+            },
+            pty_retry_events(&opened.retried),
+            #[cfg(unix)]
+            stderr,
+        )
+    }
 
+    /// Start the reader thread and build the session around `pty`.
+    fn assemble(
+        config: &SessionConfig,
+        session_id: SessionId,
+        pty: SpawnedPty,
+        pending_events: Vec<Event>,
+        #[cfg(unix)] stderr: Option<stderr::StderrPipe>,
+    ) -> Result<Self, RunnerError> {
+        let reader = reader::PtyReader::spawn(pty.reader, config.output_buffer)?;
         Ok(Self {
             run_id: config.run_id,
-            session_id: SessionId::new(),
-            terminal,
-            master: pair.master,
-            writer,
+            session_id,
+            terminal: Terminal::new(config.size.clone()).with_resize(config.resize),
+            master: pty.master,
+            writer: pty.writer,
             reader,
-            child,
+            child: pty.child,
             started_at: Instant::now(),
-            pending_utf8_tail: Vec::new(),
-            read_buffer: vec![0u8; 4096],
+            decoder: decode::OutputDecoder::new(config.encoding),
+            wait_stats: WaitStats::default(),
+            manual_input: None,
+            observation_tap: None,
+            last_key_hold: None,
+            last_termios: None,
+            watchers: Vec::new(),
+            watcher_trip: None,
+            sampler: None,
+            quotas: None,
+            hook_env: BTreeMap::new(),
+            keymap: None,
+            container: None,
+            pending_events,
+            #[cfg(unix)]
+            stderr,
+            remote: None,
         })
     }
 
     /// Send an action to the terminal session.
     ///
-    /// Handles key presses, text input, resize, wait (no-op), and terminate.
+    /// Handles key presses, held keys, text input, resize, raw bytes, termios
+    /// flags, signals, wait (no-op), and terminate. Raw bytes and signals are sent without policy
+    /// checks; the runner and driver gate them on `input.allow_raw_bytes` and
+    /// `process.allowed_signals` first.
     ///
     /// # Errors
     /// - `E_IO`: Failed to write to PTY
     /// - `E_PROTOCOL`: Invalid action payload
     pub fn send(&mut self, action: &Action) -> Result<(), RunnerError> {
+        if action_writes_input(&action.action_type) {
+            self.reader.mark_input(Instant::now())?;
+            self.terminal.mark_output();
+        }
         match action.action_type {
             ActionType::Key => {
                 let key = action.payload.extract_str("key", "key action")?;
-                let bytes = key_to_bytes(key)?;
-                self.writer
-                    .write_all(&bytes)
-                    .map_err(|err| RunnerError::io("E_IO", "failed to write key", err))?;
-                self.writer
-                    .flush()
-                    .map_err(|err| RunnerError::io("E_IO", "failed to flush key", err))?;
-                Ok(())
+                let bytes = key_to_bytes(key, self.keymap.as_ref())?;
+                self.write_input(&bytes, "failed to write key", "failed to flush key")
             }
             ActionType::Text => {
                 let text = action.payload.extract_str("text", "text action")?;
-                self.writer
-                    .write_all(text.as_bytes())
-                    .map_err(|err| RunnerError::io("E_IO", "failed to write text", err))?;
-                self.writer
-                    .flush()
-                    .map_err(|err| RunnerError::io("E_IO", "failed to flush text", err))?;
-                Ok(())
+                self.write_input(
+                    text.as_bytes(),
+                    "failed to write text",
+                    "failed to flush text",
+                )
             }
             ActionType::Resize => {
                 let rows_u64 = action.payload.extract_u64("rows", "resize action")?;
@@ -349,62 +524,86 @@ impl Session {
                     ));
                 }
 
+                let size = TerminalSize::new(rows, cols).with_pixels(
+                    pixel_field(action, "pixel_width")?,
+                    pixel_field(action, "pixel_height")?,
+                );
                 self.master
-                    .resize(PtySize {
-                        rows,
-                        cols,
-                        pixel_width: 0,
-                        pixel_height: 0,
-                    })
+                    .resize(pty_size(&size))
                     .map_err(|err| RunnerError::io("E_IO", "failed to resize pty", err))?;
-                self.terminal.resize(TerminalSize { rows, cols });
+                self.terminal.resize(size);
                 Ok(())
             }
+            ActionType::Raw => {
+                let bytes = decode_raw_payload(&action.payload)?;
+                self.write_input(
+                    &bytes,
+                    "failed to write raw bytes",
+                    "failed to flush raw bytes",
+                )
+            }
+            ActionType::HoldKey => self.hold_key_action(&action.payload),
+            ActionType::Termios => self.termios_action(&action.payload),
+            ActionType::Signal => self.signal_action(&action.payload),
             ActionType::Wait | ActionType::Observe => Ok(()),
             ActionType::Terminate => self.terminate(),
+            ActionType::History | ActionType::Subscribe => Err(driver_query_not_sendable(action)),
         }
     }
 
+    fn write_input(
+        &mut self,
+        bytes: &[u8],
+        write_message: &'static str,
+        flush_message: &'static str,
+    ) -> Result<(), RunnerError> {
+        self.writer
+            .write_all(bytes)
+            .map_err(|err| RunnerError::io("E_IO", write_message, err))?;
+        self.writer
+            .flush()
+            .map_err(|err| RunnerError::io("E_IO", flush_message, err))
+    }
+
     /// Read terminal output and capture a screen snapshot.
     ///
-    /// Reads available PTY output up to `timeout`, processes it through the
-    /// terminal emulator, and returns an observation with the current screen state.
+    /// Collects PTY output buffered by the reader thread up to `timeout`,
+    /// processes it through the terminal emulator, and returns an observation
+    /// with the current screen state. Once the output buffer has overflowed,
+    /// its counters are reported in `output_flow`.
+    /// Pending manual input (see [`set_manual_input`](Self::set_manual_input))
+    /// is written first. With `separate_stderr`, stderr written since the
+    /// last observation is returned in `stderr_delta`. Armed watchers (see
+    /// [`set_watchers`](Self::set_watchers)) are checked against the result,
+    /// after any observation tap (see
+    /// [`set_observation_tap`](Self::set_observation_tap)) has seen it.
+    /// While sampling (see [`start_sampling`](Self::start_sampling)), the
+    /// screen is also captured between interval-sized reads. Armed write
+    /// quotas (see [`set_write_quotas`](Self::set_write_quotas)) are
+    /// sampled while reading, which ends early once one is exceeded.
     ///
     /// # Errors
-    /// - `E_IO`: Failed to read from PTY
-    /// - `E_TERMINAL_PARSE`: Output was not valid UTF-8
+    /// - `E_IO`: Failed to read from PTY or the stderr pipe
+    /// - `E_TERMINAL_PARSE`: Output was not valid UTF-8 (policy
+    ///   `terminal.encoding: utf8_strict`; the lossy mode reports a
+    ///   `decode_error` event instead)
+    /// - `E_ASSERTION_FAILED`: A watcher condition held for this observation
+    /// - `E_TIMEOUT`: A write quota was exceeded (reported once)
+    /// - `E_PROTOCOL`: Manual input was not a valid key or text action
     pub fn observe(&mut self, timeout: Duration) -> Result<Observation, RunnerError> {
-        let mut total = Vec::new();
-        let mut saw_eof = false;
-        let deadline = Instant::now() + timeout;
-        loop {
-            match self.reader.read(&mut self.read_buffer) {
-                Ok(0) => {
-                    saw_eof = true;
-                    break;
-                }
-                Ok(count) => {
-                    // read() guarantees count <= self.read_buffer.len()
-                    if let Some(slice) = self.read_buffer.get(..count) {
-                        total.extend_from_slice(slice);
-                    }
-                }
-                Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {
-                    if Instant::now() >= deadline {
-                        break;
-                    }
-                    pause_until(deadline, Duration::from_millis(5));
-                }
-                Err(err) => return Err(RunnerError::io("E_IO", "failed to read pty", err)),
-            }
-            if Instant::now() >= deadline {
-                break;
-            }
-        }
+        self.forward_manual_input()?;
+        let burst = self.read_output(Instant::now() + timeout)?;
+        let (total, saw_eof) = (burst.bytes, burst.eof);
 
-        let transcript_delta = self.decode_transcript_delta(&total, saw_eof)?;
+        let decoded = self.decoder.decode(&total, saw_eof)?;
+        #[cfg(unix)]
+        let stderr_delta = match self.stderr.as_mut() {
+            Some(pipe) => pipe.read_delta()?,
+            None => None,
+        };
+        #[cfg(not(unix))]
+        let stderr_delta = None;
 
-        self.terminal.process_bytes(&total);
         let snapshot = self.terminal.snapshot()?;
         debug_assert!(
             snapshot.rows > 0,
@@ -415,7 +614,7 @@ impl Session {
             "snapshot cols must be positive after observe"
         );
 
-        let mut events = Vec::new();
+        let mut events = std::mem::take(&mut self.pending_events);
         if !total.is_empty() {
             events.push(Event {
                 event_type: "pty_output".to_string(),
@@ -423,6 +622,7 @@ impl Session {
                 details: Some(serde_json::json!({ "bytes": total.len() })),
             });
         }
+        events.extend(decoded.error_event);
         if saw_eof {
             events.push(Event {
                 event_type: "pty_eof".to_string(),
@@ -430,7 +630,7 @@ impl Session {
                 details: None,
             });
         }
-        Ok(Observation {
+        let observation = Observation {
             protocol_version: PROTOCOL_VERSION,
             run_id: self.run_id,
             session_id: self.session_id,
@@ -438,86 +638,35 @@ impl Session {
             #[allow(clippy::cast_possible_truncation)]
             timestamp_ms: { self.started_at.elapsed().as_millis() as u64 },
             screen: snapshot,
-            transcript_delta,
+            transcript_delta: decoded.text,
             events,
-        })
-    }
-
-    fn decode_transcript_delta(
-        &mut self,
-        total: &[u8],
-        saw_eof: bool,
-    ) -> Result<Option<String>, RunnerError> {
-        if total.is_empty() && self.pending_utf8_tail.is_empty() {
-            return Ok(None);
+            stderr_delta,
+            output_flow: self.reader.stats()?,
+            read_truncated: burst.truncated,
+            read_bytes: total.len() as u64,
+            read_dropped_bytes: burst.dropped_bytes,
+            widgets: self.terminal.widgets(),
+        };
+        if let Some(tap) = &self.observation_tap {
+            tap.on_observation(&observation);
         }
+        let observation = self.check_watchers(observation)?;
+        self.check_write_quotas(observation)
+    }
 
-        let mut combined = Vec::with_capacity(self.pending_utf8_tail.len() + total.len());
-        combined.extend_from_slice(&self.pending_utf8_tail);
-        combined.extend_from_slice(total);
-
-        match std::str::from_utf8(&combined) {
-            Ok(value) => {
-                self.pending_utf8_tail.clear();
-                if value.is_empty() {
-                    Ok(None)
-                } else {
-                    Ok(Some(value.to_string()))
-                }
-            }
-            Err(err) => {
-                if err.error_len().is_none() {
-                    let valid_up_to = err.valid_up_to();
-                    if saw_eof {
-                        return Err(RunnerError::terminal_parse(
-                            "E_TERMINAL_PARSE",
-                            "terminal output was not valid UTF-8",
-                            err,
-                            Some(valid_up_to),
-                        ));
-                    }
-
-                    let prefix = if valid_up_to == 0 {
-                        None
-                    } else {
-                        let valid_prefix = combined.get(..valid_up_to).ok_or_else(|| {
-                            RunnerError::internal("E_INTERNAL", "utf-8 prefix index out of bounds")
-                        })?;
-                        let value = std::str::from_utf8(valid_prefix).map_err(|_| {
-                            RunnerError::internal(
-                                "E_INTERNAL",
-                                "utf-8 prefix validation failed unexpectedly",
-                            )
-                        })?;
-                        Some(value.to_string())
-                    };
-
-                    let pending_tail = combined.get(valid_up_to..).ok_or_else(|| {
-                        RunnerError::internal("E_INTERNAL", "utf-8 tail index out of bounds")
-                    })?;
-                    self.pending_utf8_tail.clear();
-                    // Max valid UTF-8 sequence is 4 bytes; if we have more pending
-                    // bytes, something is wrong — discard excess as invalid
-                    if pending_tail.len() > 4 {
-                        return Err(RunnerError::terminal_parse(
-                            "E_TERMINAL_PARSE",
-                            "pending UTF-8 tail exceeds maximum sequence length (4 bytes)",
-                            format!("pending_tail_len={}", pending_tail.len()),
-                            Some(valid_up_to),
-                        ));
-                    }
-                    self.pending_utf8_tail.extend_from_slice(pending_tail);
-                    Ok(prefix)
-                } else {
-                    Err(RunnerError::terminal_parse(
-                        "E_TERMINAL_PARSE",
-                        "terminal output was not valid UTF-8",
-                        err,
-                        Some(err.valid_up_to()),
-                    ))
-                }
-            }
+    /// Process PTY output and answer any terminal queries it contained.
+    fn feed_terminal(&mut self, bytes: &[u8]) -> Result<(), RunnerError> {
+        self.terminal
+            .process_bytes(&self.decoder.for_terminal(bytes));
+        let replies = self.terminal.take_replies();
+        if !replies.is_empty() {
+            self.write_input(
+                &replies,
+                "failed to write terminal query reply",
+                "failed to flush terminal query reply",
+            )?;
         }
+        Ok(())
     }
 
     /// Wait for the child process to exit.
@@ -551,30 +700,18 @@ impl Session {
     /// Send SIGTERM to the process group.
     ///
     /// This is a best-effort termination. For graceful shutdown with
-    /// fallback to SIGKILL, use [`terminate_process_group`](Self::terminate_process_group).
+    /// fallback to SIGKILL, use [`terminate_with`](Self::terminate_with).
     ///
     /// # Errors
     /// - `E_IO`: Failed to signal process
     pub fn terminate(&mut self) -> Result<(), RunnerError> {
-        #[cfg(unix)]
-        if let Some(pid) = self.child.process_id() {
-            // Process IDs are always positive and fit in i32
-            #[allow(clippy::cast_possible_wrap)]
-            let pgid = Pid::from_raw(pid as i32);
-            return signal_process_group(pgid, Signal::SIGTERM);
-        }
-
-        self.child
-            .kill()
-            .map_err(|err| RunnerError::io("E_IO", "failed to terminate child", err))
+        self.signal(KillSignal::Term)
     }
 
     /// Gracefully terminate the process group with SIGTERM, falling back to SIGKILL.
     ///
-    /// Sends SIGTERM, waits up to `grace` duration for exit, then sends SIGKILL
-    /// if still alive. Returns the exit status if the process terminates.
-    ///
-    /// This is the recommended way to terminate a session when you need the exit status.
+    /// Shorthand for [`terminate_with`](Self::terminate_with) using the
+    /// default [`ProcessPolicy`] with `grace` as the grace period.
     ///
     /// # Errors
     /// - `E_IO`: Failed to signal or wait for process
@@ -582,27 +719,132 @@ impl Session {
         &mut self,
         grace: Duration,
     ) -> Result<Option<portable_pty::ExitStatus>, RunnerError> {
-        #[cfg(unix)]
-        if let Some(pid) = self.child.process_id() {
-            // Process IDs are always positive and fit in i32
-            #[allow(clippy::cast_possible_wrap)]
-            let pgid = Pid::from_raw(pid as i32);
-            signal_process_group(pgid, Signal::SIGTERM)?;
-            if let Some(status) = self.wait_for_exit(grace)? {
-                return Ok(Some(status));
-            }
-            signal_process_group(pgid, Signal::SIGKILL)?;
-            return self.wait_for_exit(Duration::from_millis(200));
-        }
+        let process = ProcessPolicy {
+            term_grace_ms: u64::try_from(grace.as_millis()).unwrap_or(u64::MAX),
+            ..ProcessPolicy::default()
+        };
+        Ok(self.terminate_with(&process)?.status)
+    }
 
-        self.terminate()?;
-        self.wait_for_exit(grace)
+    /// Snapshot of the current screen including cell styles, without
+    /// reading new output (observations carry text-only snapshots unless
+    /// [`set_capture_attributes`](Self::set_capture_attributes) is on).
+    ///
+    /// # Errors
+    /// - `E_TERMINAL_PARSE`: The screen could not be captured
+    pub fn styled_snapshot(&self) -> Result<ScreenSnapshot, RunnerError> {
+        self.terminal.snapshot_with_cells(true)
     }
 
     /// Get the session identifier.
     pub fn session_id(&self) -> SessionId {
         self.session_id
     }
+
+    /// OS process id of the child (the `sandbox-exec` wrapper execs into the
+    /// target, so this is the target's pid under Seatbelt too).
+    pub fn process_id(&self) -> Option<u32> {
+        self.child.process_id()
+    }
+
+    /// Time from the last input sent by [`Session::send`] (key, text, raw,
+    /// resize, or held key) to the first PTY output that arrived after it.
+    ///
+    /// Measured on the reader thread, so it is independent of when the output
+    /// is observed. `None` until output follows the input.
+    ///
+    /// # Errors
+    /// - `E_INTERNAL` if the reader state lock is poisoned
+    pub fn response_latency(&self) -> Result<Option<Duration>, RunnerError> {
+        self.reader.response_latency()
+    }
+
+    /// Bells and raw PTY bytes received since the last input sent by
+    /// [`Session::send`] (key, text, raw, resize, or held key), for the
+    /// `bell` and `control_sequence` predicates.
+    pub fn raw_output(&self) -> RawOutput<'_> {
+        self.terminal.output_since_mark()
+    }
+
+    /// All stderr captured so far, or `None` when the session was spawned
+    /// without `separate_stderr`.
+    pub fn stderr(&self) -> Option<&str> {
+        #[cfg(unix)]
+        {
+            self.stderr.as_ref().map(stderr::StderrPipe::captured)
+        }
+        #[cfg(not(unix))]
+        {
+            None
+        }
+    }
+
+    /// Forward manual input from `source` to the PTY at each [`observe`](Self::observe).
+    pub fn set_manual_input(&mut self, source: Arc<dyn ManualInputSource>) {
+        self.manual_input = Some(source);
+    }
+
+    /// Show every observation to `tap` (see [`observe`](Self::observe)).
+    pub fn set_observation_tap(&mut self, tap: Arc<dyn ObservationTap>) {
+        self.observation_tap = Some(tap);
+    }
+
+    /// Compare the terminal with a second emulator backend from now on (see
+    /// [`crate::terminal::emulator_diff`]). Call before the first
+    /// [`observe`](Self::observe), so the backend sees all output.
+    #[cfg(feature = "emulator-diff")]
+    pub fn enable_emulator_diff(&mut self) {
+        self.terminal.enable_emulator_diff();
+    }
+
+    /// What the second emulator backend's comparisons found, when
+    /// [`enable_emulator_diff`](Self::enable_emulator_diff) was called.
+    #[cfg(feature = "emulator-diff")]
+    pub fn emulator_diff(&self) -> Option<crate::model::EmulatorDiffReport> {
+        self.terminal.emulator_diff()
+    }
+
+    /// Set the extra environment for hooks this session runs, such as
+    /// `process.pre_kill_hook`, replacing any previous overlay. The child's
+    /// environment is fixed at spawn and is not affected.
+    pub fn set_hook_env(&mut self, env: BTreeMap<String, String>) {
+        self.hook_env = env;
+    }
+
+    /// Include per-cell colors and attributes (`cells`) in the screens of
+    /// later observations (`run.capture_attributes`).
+    pub fn set_capture_attributes(&mut self, capture: bool) {
+        self.terminal.set_capture_attributes(capture);
+    }
+
+    /// Translate `key` and `hold_key` actions through `keymap` (see
+    /// [`Keymap`]); `None` restores the default translation.
+    pub fn set_keymap(&mut self, keymap: Option<Keymap>) {
+        self.keymap = keymap;
+    }
+
+    /// Deliver `signal` actions to `container` through its runtime rather
+    /// than to the runtime CLI running under the PTY.
+    pub fn set_container(&mut self, container: ContainerCleanup) {
+        self.container = Some(container);
+    }
+
+    fn forward_manual_input(&mut self) -> Result<(), RunnerError> {
+        let Some(source) = self.manual_input.clone() else {
+            return Ok(());
+        };
+        for action in source.take_pending() {
+            if !matches!(action.action_type, ActionType::Key | ActionType::Text) {
+                return Err(RunnerError::protocol(
+                    "E_PROTOCOL",
+                    "manual input must be a key or text action",
+                    serde_json::json!({ "received_type": action.action_type }),
+                ));
+            }
+            self.send(&action)?;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(unix)]
@@ -630,6 +872,59 @@ fn signal_process_group(pgid: Pid, signal: Signal) -> Result<(), RunnerError> {
     }
 }
 
+fn pty_size(size: &TerminalSize) -> PtySize {
+    PtySize {
+        rows: size.rows,
+        cols: size.cols,
+        pixel_width: size.pixel_width,
+        pixel_height: size.pixel_height,
+    }
+}
+
+/// Actions whose input starts a new [`Session::response_latency`] measurement.
+fn action_writes_input(action_type: &ActionType) -> bool {
+    matches!(
+        action_type,
+        ActionType::Key
+            | ActionType::Text
+            | ActionType::Resize
+            | ActionType::Raw
+            | ActionType::HoldKey
+    )
+}
+
+/// `history` is answered by the driver from its session log and never
+/// reaches the child.
+fn driver_query_not_sendable(action: &Action) -> RunnerError {
+    let name = if matches!(action.action_type, ActionType::Subscribe) {
+        "subscribe"
+    } else {
+        "history"
+    };
+    RunnerError::with_context(
+        ErrorCode::Protocol,
+        format!("{name} is a driver query, not a session action"),
+        serde_json::json!({ "action": name }),
+    )
+}
+
+/// Read an optional pixel dimension from a resize payload (absent = 0).
+fn pixel_field(action: &Action, key: &str) -> Result<u16, RunnerError> {
+    let Some(value) = action.payload.get(key) else {
+        return Ok(0);
+    };
+    value
+        .as_u64()
+        .and_then(|value| u16::try_from(value).ok())
+        .ok_or_else(|| {
+            RunnerError::protocol(
+                "E_PROTOCOL",
+                format!("{key} must be an integer between 0 and {}", u16::MAX),
+                serde_json::json!({ "received": value, "max": u16::MAX }),
+            )
+        })
+}
+
 const SUPPORTED_KEYS: &[&str] = &[
     "Enter",
     "F1",
@@ -658,9 +953,14 @@ const SUPPORTED_KEYS: &[&str] = &[
     "PageDown",
 ];
 
-fn key_to_bytes(key: &str) -> Result<Vec<u8>, RunnerError> {
-    if let Some(ctrl) = parse_ctrl_key(key) {
-        return Ok(vec![ctrl]);
+/// Bytes sent for `key`: its entry in `keymap` when it has one, otherwise
+/// the default translation. Modifier chords are encoded by [`keys`].
+pub(crate) fn key_to_bytes(key: &str, keymap: Option<&Keymap>) -> Result<Vec<u8>, RunnerError> {
+    if let Some(text) = keymap.and_then(|keymap| keymap.keys.get(key)) {
+        return Ok(text.as_bytes().to_vec());
+    }
+    if let Some((modifiers, base)) = keys::split_modifiers(key) {
+        return keys::chord_bytes(modifiers, base).ok_or_else(|| unsupported_key(key));
     }
 
     let bytes = match key {
@@ -693,33 +993,24 @@ fn key_to_bytes(key: &str) -> Result<Vec<u8>, RunnerError> {
             if key.len() == 1 {
                 return Ok(key.as_bytes().to_vec());
             }
-            return Err(RunnerError::protocol(
-                "E_PROTOCOL",
-                format!("unsupported key '{key}'"),
-                serde_json::json!({
-                    "received_key": key,
-                    "supported_keys": SUPPORTED_KEYS,
-                    "note": "Single characters and Ctrl+<char> are also supported (e.g., 'a', 'Ctrl+C')",
-                    "example": {"type": "key", "payload": {"key": "Enter"}}
-                }),
-            ));
+            return Err(unsupported_key(key));
         }
     };
     Ok(bytes)
 }
 
-fn parse_ctrl_key(key: &str) -> Option<u8> {
-    let suffix = key
-        .strip_prefix("Ctrl+")
-        .or_else(|| key.strip_prefix("ctrl+"))?;
-    let [byte] = suffix.as_bytes() else {
-        return None;
-    };
-    let ch = byte.to_ascii_uppercase();
-    if !ch.is_ascii_uppercase() {
-        return None;
-    }
-    Some(ch - b'@')
+fn unsupported_key(key: &str) -> RunnerError {
+    RunnerError::protocol(
+        "E_PROTOCOL",
+        format!("unsupported key '{key}'"),
+        serde_json::json!({
+            "received_key": key,
+            "supported_keys": SUPPORTED_KEYS,
+            "modifiers": ["Ctrl", "Alt", "Shift"],
+            "note": "Single characters and modifier chords are also supported (e.g., 'a', 'Ctrl+C', 'Alt+Enter', 'Shift+Tab', 'Ctrl+Up')",
+            "example": {"type": "key", "payload": {"key": "Enter"}}
+        }),
+    )
 }
 
 impl Session {
@@ -731,9 +1022,9 @@ impl Session {
     ///
     /// Performs the following steps:
     /// 1. Flushes any buffered output to the PTY
-    /// 2. Sends SIGTERM to the process group
-    /// 3. Waits up to the specified grace period for graceful exit
-    /// 4. Sends SIGKILL if still alive
+    /// 2. Sends `process.kill_signal` to the process group
+    /// 3. Waits up to `process.term_grace_ms` for graceful exit
+    /// 4. Runs `process.pre_kill_hook` and sends SIGKILL if still alive
     ///
     /// # Errors
     /// - `E_IO`: Failed to flush writer, signal process, or wait for exit
@@ -741,8 +1032,7 @@ impl Session {
     /// # Example
     /// ```no_run
     /// # use ptybox::session::{Session, SessionConfig};
-    /// # use ptybox::model::{RunId, TerminalSize};
-    /// # use std::time::Duration;
+    /// # use ptybox::model::{ProcessPolicy, RunId, TerminalSize};
     /// # fn example() -> Result<(), ptybox::runner::RunnerError> {
     /// # let config = SessionConfig {
     /// #     command: "/bin/cat".to_string(),
@@ -751,24 +1041,27 @@ impl Session {
     /// #     size: TerminalSize::default(),
     /// #     run_id: RunId::new(),
     /// #     env: Default::default(),
+    /// #     separate_stderr: false,
+    /// #     output_buffer: Default::default(),
+    /// #     resize: Default::default(),
+    /// #     remote: None,
+    /// #     encoding: Default::default(),
     /// # };
     /// let session = Session::spawn(config)?;
     /// // ... use session ...
-    /// session.close(Duration::from_millis(500))?;
+    /// let termination = session.close(&ProcessPolicy::default())?;
+    /// println!("escalated to SIGKILL: {}", termination.record.escalated);
     /// # Ok(())
     /// # }
     /// ```
-    pub fn close(
-        mut self,
-        grace: Duration,
-    ) -> Result<Option<portable_pty::ExitStatus>, RunnerError> {
+    pub fn close(mut self, process: &ProcessPolicy) -> Result<Termination, RunnerError> {
         // Flush any buffered output before terminating
         self.writer.flush().map_err(|err| {
             RunnerError::io("E_IO", "failed to flush pty writer during close", err)
         })?;
 
         // Terminate and wait for exit
-        self.terminate_process_group(grace)
+        self.terminate_with(process)
     }
 
     /// Best-effort cleanup of the child process. Used by Drop.
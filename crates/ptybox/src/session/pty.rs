@@ -0,0 +1,205 @@
+//! Opening the PTY, with retries for transient failures.
+//!
+//! In constrained containers `openpty` sometimes fails for reasons that clear
+//! up on their own: the devpts instance is at its `max` while other sessions
+//! close, or the process briefly runs out of descriptors. Such failures are
+//! retried with exponential backoff, up to [`PTY_OPEN_ATTEMPTS`] calls. A
+//! failure that is not transient, or the last transient one, becomes `E_IO`
+//! with [`PtyDiagnostics`] describing the host's PTY setup.
+
+use crate::model::{PtyDiagnostics, PtyProbe};
+use crate::runner::{ErrorCode, RunnerError};
+use nix::errno::Errno;
+use portable_pty::{native_pty_system, PtyPair, PtySize};
+use std::fmt::Display;
+use std::path::Path;
+use std::time::Duration;
+
+/// Most `openpty` calls made for one session.
+pub const PTY_OPEN_ATTEMPTS: u32 = 4;
+
+/// Delay before the first retry; doubled before each further one.
+const PTY_RETRY_BACKOFF: Duration = Duration::from_millis(20);
+
+/// Errors from `openpty` worth retrying.
+const TRANSIENT_ERRNOS: [Errno; 7] = [
+    Errno::EAGAIN,
+    Errno::EINTR,
+    Errno::EBUSY,
+    Errno::EIO,
+    Errno::ENOSPC,
+    Errno::EMFILE,
+    Errno::ENFILE,
+];
+
+/// A PTY pair and the errors of the attempts that failed before it opened.
+pub(crate) struct OpenedPty {
+    pub(crate) pair: PtyPair,
+    pub(crate) retried: Vec<String>,
+}
+
+/// Open a PTY of `size`, retrying transient failures.
+///
+/// # Errors
+/// `E_IO` with `source`, `attempts`, and `diagnostics` in its context when
+/// no attempt succeeded.
+pub(crate) fn open_pty(size: PtySize) -> Result<OpenedPty, RunnerError> {
+    let system = native_pty_system();
+    match open_with_retry(|| system.openpty(size)) {
+        Ok((pair, retried)) => Ok(OpenedPty { pair, retried }),
+        Err(errors) => {
+            let source = errors.last().cloned().unwrap_or_default();
+            Err(RunnerError::with_context(
+                ErrorCode::Io,
+                "failed to open pty",
+                serde_json::json!({
+                    "source": source,
+                    "attempts": errors.len(),
+                    "diagnostics": pty_diagnostics(parse_errno(&source)),
+                }),
+            ))
+        }
+    }
+}
+
+/// Open and close a PTY the way [`Session::spawn`](super::Session::spawn)
+/// would, and report the host's PTY setup.
+#[must_use]
+pub fn probe_pty() -> PtyProbe {
+    let system = native_pty_system();
+    let size = PtySize {
+        rows: 24,
+        cols: 80,
+        pixel_width: 0,
+        pixel_height: 0,
+    };
+    match open_with_retry(|| system.openpty(size)) {
+        Ok((_pair, retried)) => PtyProbe {
+            ok: true,
+            attempts: attempt_count(retried.len() + 1),
+            error: None,
+            diagnostics: pty_diagnostics(None),
+        },
+        Err(errors) => {
+            let error = errors.last().cloned();
+            PtyProbe {
+                ok: false,
+                attempts: attempt_count(errors.len()),
+                diagnostics: pty_diagnostics(error.as_deref().and_then(parse_errno)),
+                error,
+            }
+        }
+    }
+}
+
+/// Describe the host's PTY setup, with hints for an `openpty` failure with
+/// `errno` (`None` when nothing failed).
+#[must_use]
+pub fn pty_diagnostics(errno: Option<i32>) -> PtyDiagnostics {
+    let errno_value = errno.map(Errno::from_raw);
+    let ptmx_present = Path::new("/dev/ptmx").exists();
+    let devpts_mounted = cfg!(target_os = "linux").then(|| Path::new("/dev/pts/ptmx").exists());
+    let container = in_container();
+
+    let mut hints = Vec::new();
+    if !ptmx_present {
+        hints.push(
+            "/dev/ptmx is missing; give the container a /dev with ptmx (e.g. `--device /dev/ptmx`)"
+                .to_string(),
+        );
+    }
+    if devpts_mounted == Some(false) {
+        hints.push(
+            "/dev/pts is not a devpts mount; mount one with `mount -t devpts -o newinstance,ptmxmode=0666 devpts /dev/pts`"
+                .to_string(),
+        );
+    }
+    match errno_value {
+        Some(Errno::ENOSPC) => hints.push(
+            "the PTY limit is reached; close idle sessions or raise /proc/sys/kernel/pty/max (or the devpts `max` option)"
+                .to_string(),
+        ),
+        Some(Errno::EMFILE | Errno::ENFILE) => hints.push(
+            "out of file descriptors; raise the limit with `ulimit -n`".to_string(),
+        ),
+        Some(Errno::EACCES | Errno::EPERM) => hints.push(
+            "access to /dev/ptmx was denied; check its mode (usually 0666) and any seccomp or AppArmor profile"
+                .to_string(),
+        ),
+        _ => {}
+    }
+    if container && errno.is_some() && hints.is_empty() {
+        hints.push(
+            "running in a container; make sure it has its own devpts instance mounted at /dev/pts"
+                .to_string(),
+        );
+    }
+
+    PtyDiagnostics {
+        errno,
+        errno_name: errno_value.map(|value| format!("{value:?}")),
+        transient: errno_value.is_some_and(|value| TRANSIENT_ERRNOS.contains(&value)),
+        ptmx_present,
+        devpts_mounted,
+        container,
+        hints,
+    }
+}
+
+/// Call `open` until it succeeds, a non-transient error occurs, or
+/// [`PTY_OPEN_ATTEMPTS`] calls were made. Returns the value with the errors
+/// of earlier attempts, or all errors.
+fn open_with_retry<T, E: Display>(
+    mut open: impl FnMut() -> Result<T, E>,
+) -> Result<(T, Vec<String>), Vec<String>> {
+    let mut errors = Vec::new();
+    let mut backoff = PTY_RETRY_BACKOFF;
+    loop {
+        match open() {
+            Ok(value) => return Ok((value, errors)),
+            Err(err) => {
+                let message = err.to_string();
+                let transient = parse_errno(&message)
+                    .is_some_and(|errno| TRANSIENT_ERRNOS.contains(&Errno::from_raw(errno)));
+                errors.push(message);
+                if !transient || attempt_count(errors.len()) >= PTY_OPEN_ATTEMPTS {
+                    return Err(errors);
+                }
+                std::thread::sleep(backoff);
+                backoff = backoff.saturating_mul(2);
+            }
+        }
+    }
+}
+
+/// OS error number in an `openpty` error message, which carries it either
+/// as `os error N` or, from its `Debug` form, as `code: N`.
+fn parse_errno(message: &str) -> Option<i32> {
+    ["os error ", "code: "].iter().find_map(|marker| {
+        let start = message.find(marker)? + marker.len();
+        let digits: String = message
+            .get(start..)?
+            .chars()
+            .take_while(char::is_ascii_digit)
+            .collect();
+        digits.parse().ok()
+    })
+}
+
+fn attempt_count(count: usize) -> u32 {
+    u32::try_from(count).unwrap_or(u32::MAX)
+}
+
+fn in_container() -> bool {
+    if std::env::var_os("container").is_some()
+        || Path::new("/.dockerenv").exists()
+        || Path::new("/run/.containerenv").exists()
+    {
+        return true;
+    }
+    std::fs::read_to_string("/proc/1/cgroup").is_ok_and(|cgroup| {
+        ["docker", "kubepods", "containerd", "lxc", "podman"]
+            .iter()
+            .any(|runtime| cgroup.contains(runtime))
+    })
+}
@@ -0,0 +1,195 @@
+//! Write quotas: best-effort limits on how much allowlisted paths grow.
+//!
+//! The child's writes are not intercepted. Each quota path is walked like
+//! `du --apparent-size` when quotas are armed and then at most once per
+//! [`SAMPLE_INTERVAL`] while output is read; reads are cut into
+//! interval-sized slices so a long wait still samples on time. Writes made
+//! between two samples can overshoot the limit before it is noticed.
+
+use super::Session;
+use crate::model::{Observation, WriteQuotaUsage};
+use crate::runner::RunnerError;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Time between two samples of the quota paths.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Quota state for the running child.
+pub(super) struct QuotaMonitor {
+    quotas: Vec<TrackedQuota>,
+    /// Trees not counted toward any quota (the artifacts directory).
+    excluded: Vec<PathBuf>,
+    last_at: Instant,
+    /// The exceeded quota was already reported by an observation.
+    reported: bool,
+}
+
+struct TrackedQuota {
+    root: PathBuf,
+    usage: WriteQuotaUsage,
+}
+
+impl QuotaMonitor {
+    /// When the next sample is due.
+    pub(super) fn next_sample_at(&self) -> Instant {
+        self.last_at + SAMPLE_INTERVAL
+    }
+
+    fn sample(&mut self) {
+        self.last_at = Instant::now();
+        for quota in &mut self.quotas {
+            let growth =
+                tree_size(&quota.root, &self.excluded).saturating_sub(quota.usage.baseline_bytes);
+            let usage = &mut quota.usage;
+            usage.peak_bytes = usage.peak_bytes.max(growth);
+            usage.samples += 1;
+            usage.exceeded |= growth > usage.max_bytes;
+        }
+    }
+
+    fn exceeded(&self) -> Option<&WriteQuotaUsage> {
+        self.quotas
+            .iter()
+            .map(|quota| &quota.usage)
+            .find(|usage| usage.exceeded)
+    }
+}
+
+impl Session {
+    /// Sample how much each path in `quotas` grows, failing the observation
+    /// that first sees growth above its limit.
+    ///
+    /// The size of each tree now is its baseline. Trees under `excluded`
+    /// are not counted. Replaces any quotas armed earlier; an empty map
+    /// disarms them.
+    pub fn set_write_quotas(&mut self, quotas: &BTreeMap<String, u64>, excluded: &[PathBuf]) {
+        if quotas.is_empty() {
+            self.quotas = None;
+            return;
+        }
+        let excluded: Vec<PathBuf> = excluded.iter().map(|path| resolve(path)).collect();
+        let quotas = quotas
+            .iter()
+            .map(|(path, max_bytes)| {
+                let root = resolve(Path::new(path));
+                let baseline_bytes = tree_size(&root, &excluded);
+                TrackedQuota {
+                    root,
+                    usage: WriteQuotaUsage {
+                        path: path.clone(),
+                        max_bytes: *max_bytes,
+                        baseline_bytes,
+                        peak_bytes: 0,
+                        samples: 1,
+                        exceeded: false,
+                    },
+                }
+            })
+            .collect();
+        self.quotas = Some(QuotaMonitor {
+            quotas,
+            excluded,
+            last_at: Instant::now(),
+            reported: false,
+        });
+    }
+
+    /// Whether a sample has seen a write quota exceeded.
+    #[must_use]
+    pub fn write_quota_exceeded(&self) -> bool {
+        self.quotas
+            .as_ref()
+            .is_some_and(|monitor| monitor.exceeded().is_some())
+    }
+
+    /// The `E_TIMEOUT` error for the first exceeded write quota, if any.
+    #[must_use]
+    pub fn write_quota_error(&self) -> Option<RunnerError> {
+        let usage = self.quotas.as_ref()?.exceeded()?;
+        Some(RunnerError::timeout(
+            "E_TIMEOUT",
+            "write quota exceeded",
+            serde_json::json!({
+                "write_quota": {
+                    "path": usage.path,
+                    "max_bytes": usage.max_bytes,
+                    "used_bytes": usage.peak_bytes
+                }
+            }),
+        ))
+    }
+
+    /// Take a final sample and return the usage of every write quota, in
+    /// path order. Empty when no quotas are armed.
+    pub fn write_quota_usage(&mut self) -> Vec<WriteQuotaUsage> {
+        let Some(monitor) = self.quotas.as_mut() else {
+            return Vec::new();
+        };
+        monitor.sample();
+        monitor
+            .quotas
+            .iter()
+            .map(|quota| quota.usage.clone())
+            .collect()
+    }
+
+    /// Sample the quota paths when a sample is due; `true` once a quota is
+    /// exceeded and not yet reported.
+    pub(super) fn sample_write_quotas(&mut self) -> bool {
+        let Some(monitor) = self.quotas.as_mut() else {
+            return false;
+        };
+        if Instant::now() >= monitor.next_sample_at() {
+            monitor.sample();
+        }
+        !monitor.reported && monitor.exceeded().is_some()
+    }
+
+    /// Fail the first observation made after a quota was exceeded.
+    pub(super) fn check_write_quotas(
+        &mut self,
+        observation: Observation,
+    ) -> Result<Observation, RunnerError> {
+        let Some(monitor) = self.quotas.as_mut() else {
+            return Ok(observation);
+        };
+        if monitor.reported || monitor.exceeded().is_none() {
+            return Ok(observation);
+        }
+        monitor.reported = true;
+        match self.write_quota_error() {
+            Some(error) => Err(error),
+            None => Ok(observation),
+        }
+    }
+}
+
+/// `path` with symlinks resolved, or as given when it does not exist yet.
+fn resolve(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Total apparent size of the regular files under `root`, without following
+/// symlinks. Entries that vanish or cannot be read while walking are skipped.
+fn tree_size(root: &Path, excluded: &[PathBuf]) -> u64 {
+    let mut total: u64 = 0;
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(path) = pending.pop() {
+        if excluded.contains(&path) {
+            continue;
+        }
+        let Ok(metadata) = std::fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if metadata.is_dir() {
+            if let Ok(entries) = std::fs::read_dir(&path) {
+                pending.extend(entries.flatten().map(|entry| entry.path()));
+            }
+        } else if metadata.is_file() {
+            total = total.saturating_add(metadata.len());
+        }
+    }
+    total
+}
@@ -0,0 +1,85 @@
+//! Decoding of `raw` action payloads (hex or base64 encoded bytes).
+
+use crate::runner::RunnerError;
+use serde_json::Value;
+
+/// Decode the bytes carried by a `raw` action payload.
+///
+/// Exactly one of `hex` or `base64` must be present. Whitespace inside the
+/// encoded string is ignored so long sequences can be wrapped.
+///
+/// # Errors
+/// - `E_PROTOCOL`: Missing, ambiguous, or malformed encoding
+pub(crate) fn decode_raw_payload(payload: &Value) -> Result<Vec<u8>, RunnerError> {
+    let hex = payload.get("hex").and_then(Value::as_str);
+    let base64 = payload.get("base64").and_then(Value::as_str);
+    let decoded = match (hex, base64) {
+        (Some(encoded), None) => decode_hex(&strip_whitespace(encoded)),
+        (None, Some(encoded)) => decode_base64(&strip_whitespace(encoded)),
+        _ => {
+            return Err(RunnerError::protocol(
+                "E_PROTOCOL",
+                "raw action requires exactly one of 'hex' or 'base64'",
+                serde_json::json!({
+                    "received_payload": payload,
+                    "examples": [{"hex": "1b5b3f3130303468"}, {"base64": "G1s/MTAwNGg="}],
+                }),
+            ));
+        }
+    };
+    decoded.ok_or_else(|| {
+        RunnerError::protocol(
+            "E_PROTOCOL",
+            "raw action payload is not valid hex or base64",
+            serde_json::json!({ "received_payload": payload }),
+        )
+    })
+}
+
+fn strip_whitespace(encoded: &str) -> String {
+    encoded.chars().filter(|ch| !ch.is_whitespace()).collect()
+}
+
+fn decode_hex(encoded: &str) -> Option<Vec<u8>> {
+    if encoded.len() % 2 != 0 {
+        return None;
+    }
+    encoded
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            let text = std::str::from_utf8(pair).ok()?;
+            u8::from_str_radix(text, 16).ok()
+        })
+        .collect()
+}
+
+fn decode_base64(encoded: &str) -> Option<Vec<u8>> {
+    let trimmed = encoded.trim_end_matches('=');
+    if encoded.len() - trimmed.len() > 2 || trimmed.len() % 4 == 1 {
+        return None;
+    }
+    let mut out = Vec::with_capacity(trimmed.len() * 3 / 4);
+    let mut buffer: u32 = 0;
+    let mut bits = 0u32;
+    for byte in trimmed.bytes() {
+        buffer = (buffer << 6) | u32::from(base64_value(byte)?);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push(u8::try_from((buffer >> bits) & 0xff).ok()?);
+        }
+    }
+    Some(out)
+}
+
+fn base64_value(byte: u8) -> Option<u8> {
+    match byte {
+        b'A'..=b'Z' => Some(byte - b'A'),
+        b'a'..=b'z' => Some(byte - b'a' + 26),
+        b'0'..=b'9' => Some(byte - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
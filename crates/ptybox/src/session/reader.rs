@@ -0,0 +1,323 @@
+//! Background PTY reader with a bounded output buffer.
+//!
+//! A dedicated thread drains the PTY master into a fixed-capacity buffer so
+//! chatty programs keep flowing between observations. When the buffer is
+//! full the thread either stops reading, letting the kernel PTY buffer push
+//! back on the child, or keeps reading and discards what does not fit, as
+//! selected by [`OutputOverflow`].
+
+use crate::model::{OutputBufferConfig, OutputFlowStats, OutputOverflow};
+use crate::runner::RunnerError;
+use crate::util::pause_until;
+use std::collections::VecDeque;
+use std::io::Read;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// How long the reader thread idles when the PTY has nothing to read.
+const IDLE_POLL: Duration = Duration::from_millis(2);
+
+/// Size of a single PTY read.
+const CHUNK_BYTES: usize = 4096;
+
+/// Output that arrived this close to a read deadline means the program was
+/// still writing when the read stopped.
+const BURST_GAP: Duration = Duration::from_millis(20);
+
+/// Output collected by one [`PtyReader::read_until`] call.
+pub(crate) struct ReadBurst {
+    /// Bytes drained from the buffer.
+    pub(crate) bytes: Vec<u8>,
+    /// The PTY reached EOF.
+    pub(crate) eof: bool,
+    /// The deadline cut into an active burst of output.
+    pub(crate) truncated: bool,
+    /// Bytes discarded (drop mode) since the previous read.
+    pub(crate) dropped_bytes: u64,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    /// Signalled when `observe` drains the buffer.
+    drained: Condvar,
+    /// Signalled when the reader adds output or reaches EOF.
+    filled: Condvar,
+}
+
+struct State {
+    buffer: VecDeque<u8>,
+    capacity: usize,
+    on_full: OutputOverflow,
+    /// The buffer has overflowed since it was last drained.
+    full: bool,
+    eof: bool,
+    error: Option<std::io::Error>,
+    stop: bool,
+    stats: OutputFlowStats,
+    /// When input was last written to the child.
+    input_at: Option<Instant>,
+    /// When the first output after `input_at` arrived.
+    first_output_at: Option<Instant>,
+    /// When output last arrived from the PTY.
+    last_output_at: Option<Instant>,
+    /// Bytes discarded since the last `read_until` returned.
+    pending_dropped: u64,
+}
+
+impl ReadBurst {
+    /// Extend this burst with the one read right after it.
+    pub(crate) fn append(&mut self, next: ReadBurst) {
+        self.bytes.extend(next.bytes);
+        self.eof = next.eof;
+        self.truncated = next.truncated;
+        self.dropped_bytes += next.dropped_bytes;
+    }
+}
+
+impl State {
+    fn room(&self) -> usize {
+        self.capacity.saturating_sub(self.buffer.len())
+    }
+
+    fn record_overflow(&mut self) {
+        if !self.full {
+            self.full = true;
+            self.stats.overflows += 1;
+        }
+    }
+
+    /// Finish a read at `now`, handing over the collected bytes.
+    fn burst(&mut self, bytes: Vec<u8>, now: Instant, at_deadline: bool) -> ReadBurst {
+        let active = self
+            .last_output_at
+            .is_some_and(|at| now.saturating_duration_since(at) <= BURST_GAP);
+        ReadBurst {
+            bytes,
+            eof: self.eof,
+            truncated: at_deadline && !self.eof && active,
+            dropped_bytes: std::mem::take(&mut self.pending_dropped),
+        }
+    }
+}
+
+/// Handle to the reader thread; stops and joins it on drop.
+pub(crate) struct PtyReader {
+    shared: Arc<Shared>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl PtyReader {
+    /// Start reading `reader` on a background thread.
+    ///
+    /// # Errors
+    /// - `E_PROTOCOL` if `capacity_bytes` is zero
+    /// - `E_IO` if the thread cannot be spawned
+    pub(crate) fn spawn(
+        reader: Box<dyn Read + Send>,
+        config: OutputBufferConfig,
+    ) -> Result<Self, RunnerError> {
+        if config.capacity_bytes == 0 {
+            return Err(RunnerError::protocol(
+                "E_PROTOCOL",
+                "output_buffer.capacity_bytes must be positive",
+                Some(serde_json::json!({ "capacity_bytes": config.capacity_bytes })),
+            ));
+        }
+        let capacity = usize::try_from(config.capacity_bytes).unwrap_or(usize::MAX);
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                buffer: VecDeque::with_capacity(capacity.min(CHUNK_BYTES * 16)),
+                capacity,
+                on_full: config.on_full,
+                full: false,
+                eof: false,
+                error: None,
+                stop: false,
+                stats: OutputFlowStats::default(),
+                input_at: None,
+                first_output_at: None,
+                last_output_at: None,
+                pending_dropped: 0,
+            }),
+            drained: Condvar::new(),
+            filled: Condvar::new(),
+        });
+        let thread_shared = Arc::clone(&shared);
+        let handle = std::thread::Builder::new()
+            .name("ptybox-pty-reader".to_string())
+            .spawn(move || read_loop(reader, &thread_shared))
+            .map_err(|err| RunnerError::io("E_IO", "failed to spawn pty reader thread", err))?;
+        Ok(Self {
+            shared,
+            handle: Some(handle),
+        })
+    }
+
+    /// Collect buffered output until `deadline` or EOF.
+    ///
+    /// The returned burst is marked truncated when output was still arriving
+    /// as the deadline passed.
+    ///
+    /// # Errors
+    /// - `E_IO` if the reader thread hit a read error
+    pub(crate) fn read_until(&self, deadline: Instant) -> Result<ReadBurst, RunnerError> {
+        let mut total = Vec::new();
+        let mut state = self.lock()?;
+        loop {
+            if !state.buffer.is_empty() {
+                total.extend(state.buffer.drain(..));
+                state.full = false;
+                self.shared.drained.notify_all();
+            }
+            if let Some(err) = state.error.take() {
+                return Err(RunnerError::io("E_IO", "failed to read pty", err));
+            }
+            let now = Instant::now();
+            if state.eof {
+                return Ok(state.burst(total, now, false));
+            }
+            if now >= deadline {
+                return Ok(state.burst(total, now, true));
+            }
+            state = self
+                .shared
+                .filled
+                .wait_timeout(state, deadline - now)
+                .map(|(guard, _)| guard)
+                .map_err(|_| poisoned())?;
+        }
+    }
+
+    /// Counters since the session started, once the buffer has overflowed.
+    ///
+    /// # Errors
+    /// - `E_INTERNAL` if the reader state lock is poisoned
+    pub(crate) fn stats(&self) -> Result<Option<OutputFlowStats>, RunnerError> {
+        let stats = self.lock()?.stats;
+        Ok((stats.overflows > 0).then_some(stats))
+    }
+
+    /// Record that input was written at `at`; the next output that arrives
+    /// is timed against it.
+    ///
+    /// # Errors
+    /// - `E_INTERNAL` if the reader state lock is poisoned
+    pub(crate) fn mark_input(&self, at: Instant) -> Result<(), RunnerError> {
+        let mut state = self.lock()?;
+        state.input_at = Some(at);
+        state.first_output_at = None;
+        Ok(())
+    }
+
+    /// Time from the last [`mark_input`](Self::mark_input) to the first output
+    /// that arrived after it, or `None` if nothing has arrived yet.
+    ///
+    /// # Errors
+    /// - `E_INTERNAL` if the reader state lock is poisoned
+    pub(crate) fn response_latency(&self) -> Result<Option<Duration>, RunnerError> {
+        let state = self.lock()?;
+        Ok(state
+            .input_at
+            .zip(state.first_output_at)
+            .map(|(input, output)| output.saturating_duration_since(input)))
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, State>, RunnerError> {
+        self.shared.state.lock().map_err(|_| poisoned())
+    }
+}
+
+impl Drop for PtyReader {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.shared.state.lock() {
+            state.stop = true;
+        }
+        self.shared.drained.notify_all();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn poisoned() -> RunnerError {
+    RunnerError::internal("E_INTERNAL", "pty reader state lock poisoned")
+}
+
+fn read_loop(mut reader: Box<dyn Read + Send>, shared: &Shared) {
+    let mut chunk = vec![0u8; CHUNK_BYTES];
+    loop {
+        let Some(want) = wait_for_room(shared) else {
+            return;
+        };
+        let Some(slice) = chunk.get_mut(..want) else {
+            return;
+        };
+        match reader.read(slice) {
+            Ok(0) => {
+                finish(shared, |state| state.eof = true);
+                return;
+            }
+            Ok(count) => {
+                let Ok(mut state) = shared.state.lock() else {
+                    return;
+                };
+                let now = Instant::now();
+                if state.input_at.is_some() && state.first_output_at.is_none() {
+                    state.first_output_at = Some(now);
+                }
+                state.last_output_at = Some(now);
+                let fit = count.min(state.room());
+                if let Some(bytes) = chunk.get(..fit) {
+                    state.buffer.extend(bytes);
+                }
+                if fit < count {
+                    state.record_overflow();
+                    state.stats.dropped_bytes += (count - fit) as u64;
+                    state.pending_dropped += (count - fit) as u64;
+                }
+                drop(state);
+                shared.filled.notify_all();
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {
+                pause_until(Instant::now() + IDLE_POLL, IDLE_POLL);
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(err) => {
+                finish(shared, |state| state.error = Some(err));
+                return;
+            }
+        }
+    }
+}
+
+/// Block until the buffer has room (or, in `drop` mode, return at once) and
+/// return how many bytes to read. `None` means the reader should stop.
+fn wait_for_room(shared: &Shared) -> Option<usize> {
+    let mut state = shared.state.lock().ok()?;
+    loop {
+        if state.stop {
+            return None;
+        }
+        let room = state.room();
+        match state.on_full {
+            OutputOverflow::Drop => return Some(CHUNK_BYTES),
+            OutputOverflow::Backpressure if room > 0 => return Some(room.min(CHUNK_BYTES)),
+            OutputOverflow::Backpressure => {
+                state.record_overflow();
+                state = shared
+                    .drained
+                    .wait_timeout(state, IDLE_POLL * 25)
+                    .ok()
+                    .map(|(guard, _)| guard)?;
+            }
+        }
+    }
+}
+
+fn finish(shared: &Shared, update: impl FnOnce(&mut State)) {
+    if let Ok(mut state) = shared.state.lock() {
+        update(&mut state);
+    }
+    shared.filled.notify_all();
+}
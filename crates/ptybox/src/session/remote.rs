@@ -0,0 +1,155 @@
+//! Sessions that run their command on a remote host over SSH.
+//!
+//! With [`SessionConfig::remote`] set, [`Session::spawn`] connects to the
+//! target, verifies its host key against `known_hosts`, authenticates with
+//! the key, and starts the command on a remote PTY (`ssh` feature). The SSH
+//! channel stands in for the local PTY master and child, so actions,
+//! observations, and waits behave as in a local session. The remote command
+//! has no local process id: `signal` actions and termios changes are not
+//! available, and terminating the session closes the channel, which hangs up
+//! the remote process.
+
+use super::{Session, SessionConfig};
+use crate::model::policy::RemotePolicy;
+use crate::model::{RemoteHost, RemoteTarget, SessionId};
+use crate::policy::remote::{known_hosts_required, remote_env, validate_remote_target};
+use crate::runner::{ErrorCode, RunnerError};
+use std::time::Duration;
+
+/// Terminal type requested for the remote PTY when the env policy sets no
+/// `TERM`.
+const DEFAULT_REMOTE_TERM: &str = "xterm-256color";
+
+/// SSH connection settings for a remote session.
+#[derive(Clone, Debug)]
+pub struct RemoteConfig {
+    /// Host, port, user, and key.
+    pub target: RemoteTarget,
+    /// `OpenSSH` `known_hosts` file the host key must be listed in.
+    pub known_hosts: String,
+    /// Time allowed for connecting and the SSH handshake.
+    pub connect_timeout: Duration,
+}
+
+impl RemoteConfig {
+    /// Settings for `target` from `policy`, which must allow it.
+    ///
+    /// # Errors
+    /// `E_POLICY_DENIED` when `policy` does not allow the target (see
+    /// [`validate_remote_target`]).
+    pub fn from_policy(target: RemoteTarget, policy: &RemotePolicy) -> Result<Self, RunnerError> {
+        validate_remote_target(policy, &target)?;
+        Ok(Self {
+            known_hosts: policy
+                .known_hosts
+                .clone()
+                .ok_or_else(known_hosts_required)?,
+            connect_timeout: Duration::from_millis(policy.connect_timeout_ms),
+            target,
+        })
+    }
+}
+
+impl Session {
+    /// Remote host the command runs on; `None` for local sessions.
+    #[must_use]
+    pub fn remote_host(&self) -> Option<&RemoteHost> {
+        self.remote.as_ref()
+    }
+
+    pub(super) fn spawn_remote(
+        config: &SessionConfig,
+        remote: &RemoteConfig,
+    ) -> Result<Self, RunnerError> {
+        if config.separate_stderr {
+            return Err(RunnerError::with_context(
+                ErrorCode::Protocol,
+                "separate_stderr is not supported for remote sessions",
+                serde_json::json!({ "host": remote.target.host }),
+            ));
+        }
+        let env = remote_env(&config.env)?;
+        let term = env
+            .get("TERM")
+            .map_or(DEFAULT_REMOTE_TERM, String::as_str)
+            .to_string();
+        let assignments: Vec<String> = env
+            .iter()
+            .map(|(name, value)| shell_quote(&format!("{name}={value}")))
+            .collect();
+        let command_line = remote_command_line(config, &[]);
+        let exec_line = remote_command_line(config, &assignments);
+
+        let mut host = RemoteHost {
+            host: remote.target.host.clone(),
+            port: remote.target.port,
+            user: remote.target.user.clone(),
+            key_path: remote.target.key_path.clone(),
+            host_key_type: String::new(),
+            host_key_fingerprint: String::new(),
+            command_line,
+            env: env.into_keys().collect(),
+        };
+        let pty = connect(config, remote, &term, &exec_line, &mut host)?;
+        let mut session = Self::assemble(
+            config,
+            SessionId::new(),
+            pty,
+            Vec::new(),
+            #[cfg(unix)]
+            None,
+        )?;
+        session.remote = Some(host);
+        Ok(session)
+    }
+}
+
+#[cfg(feature = "ssh")]
+fn connect(
+    config: &SessionConfig,
+    remote: &RemoteConfig,
+    term: &str,
+    exec_line: &str,
+    host: &mut RemoteHost,
+) -> Result<super::SpawnedPty, RunnerError> {
+    let connected = super::ssh::open(remote, &config.size, term, exec_line)?;
+    host.host_key_type = connected.host_key_type;
+    host.host_key_fingerprint = connected.host_key_fingerprint;
+    Ok(connected.pty)
+}
+
+#[cfg(not(feature = "ssh"))]
+fn connect(
+    _config: &SessionConfig,
+    remote: &RemoteConfig,
+    _term: &str,
+    _exec_line: &str,
+    _host: &mut RemoteHost,
+) -> Result<super::SpawnedPty, RunnerError> {
+    Err(RunnerError::with_context(
+        ErrorCode::Protocol,
+        "remote sessions need a ptybox built with the `ssh` feature",
+        serde_json::json!({ "host": remote.target.host }),
+    ))
+}
+
+/// `[cd <cwd> && ]exec [env <assignments>] <command> <args>`, quoted for
+/// the remote user's shell.
+fn remote_command_line(config: &SessionConfig, assignments: &[String]) -> String {
+    let mut words = vec!["exec".to_string()];
+    if !assignments.is_empty() {
+        words.push("env".to_string());
+        words.extend(assignments.iter().cloned());
+    }
+    words.push(shell_quote(&config.command));
+    words.extend(config.args.iter().map(|arg| shell_quote(arg)));
+    let exec = words.join(" ");
+    match &config.cwd {
+        Some(cwd) => format!("cd {} && {exec}", shell_quote(cwd)),
+        None => exec,
+    }
+}
+
+fn shell_quote(word: &str) -> String {
+    format!("'{}'", word.replace('\'', r"'\''"))
+}
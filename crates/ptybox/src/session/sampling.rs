@@ -0,0 +1,134 @@
+//! Intermediate screen samples taken while output is being read.
+//!
+//! A single [`Session::observe`] can span a whole step timeout, so screens
+//! that appear and disappear within it (spinners, progress bars, fast
+//! redraws) never reach an observation. With sampling armed, reads are cut
+//! into interval-sized slices and the screen is captured between them.
+
+use super::reader::ReadBurst;
+use super::Session;
+use crate::model::{ScreenSample, StepId};
+use crate::runner::RunnerError;
+use std::time::{Duration, Instant};
+
+/// Sampling state for the step currently running.
+pub(super) struct Sampler {
+    step_id: StepId,
+    interval: Duration,
+    max_samples: u64,
+    last_at: Instant,
+    /// Hash of the last sampled screen; unchanged screens are not sampled.
+    last_hash: String,
+    samples: Vec<ScreenSample>,
+    taken: u64,
+    dropped: u64,
+}
+
+impl Session {
+    /// Capture the screen at most once per `interval` during reads until
+    /// [`stop_sampling`](Self::stop_sampling), tagging samples with `step_id`.
+    ///
+    /// A sample is only taken when the screen differs from the previous
+    /// sample (or from the screen when sampling started). After
+    /// `max_samples`, further changes are counted as dropped. Replaces any
+    /// sampling started earlier.
+    ///
+    /// # Errors
+    /// - `E_TERMINAL_PARSE`: The current screen could not be captured
+    pub fn start_sampling(
+        &mut self,
+        step_id: StepId,
+        interval: Duration,
+        max_samples: u64,
+    ) -> Result<(), RunnerError> {
+        let last_hash = self.terminal.snapshot()?.screen_hash();
+        self.sampler = Some(Sampler {
+            step_id,
+            interval,
+            max_samples,
+            last_at: Instant::now(),
+            last_hash,
+            samples: Vec::new(),
+            taken: 0,
+            dropped: 0,
+        });
+        Ok(())
+    }
+
+    /// Take the samples captured since the last call, oldest first.
+    pub fn take_samples(&mut self) -> Vec<ScreenSample> {
+        self.sampler
+            .as_mut()
+            .map(|sampler| std::mem::take(&mut sampler.samples))
+            .unwrap_or_default()
+    }
+
+    /// Stop sampling and return how many samples `max_samples` dropped.
+    /// Samples not yet taken are discarded.
+    pub fn stop_sampling(&mut self) -> u64 {
+        self.sampler.take().map_or(0, |sampler| sampler.dropped)
+    }
+
+    /// Read output until `deadline` (or EOF) into the terminal, sampling
+    /// the screen between slices when sampling is armed. Stops early when a
+    /// write quota sample finds a quota exceeded.
+    pub(super) fn read_output(&mut self, deadline: Instant) -> Result<ReadBurst, RunnerError> {
+        let mut combined: Option<ReadBurst> = None;
+        loop {
+            let mut slice_end = self
+                .sampler
+                .as_ref()
+                .map_or(deadline, |sampler| sampler.last_at + sampler.interval)
+                .min(deadline);
+            if let Some(quotas) = &self.quotas {
+                slice_end = slice_end.min(quotas.next_sample_at());
+            }
+            let burst = self.reader.read_until(slice_end)?;
+            self.feed_terminal(&burst.bytes)?;
+            self.capture_sample()?;
+            let quota_exceeded = self.sample_write_quotas();
+            let done = burst.eof || quota_exceeded || Instant::now() >= deadline;
+            let burst = match combined.take() {
+                Some(mut earlier) => {
+                    earlier.append(burst);
+                    earlier
+                }
+                None => burst,
+            };
+            if done {
+                return Ok(burst);
+            }
+            combined = Some(burst);
+        }
+    }
+
+    fn capture_sample(&mut self) -> Result<(), RunnerError> {
+        let now = Instant::now();
+        let Some(sampler) = self.sampler.as_mut() else {
+            return Ok(());
+        };
+        if now < sampler.last_at + sampler.interval {
+            return Ok(());
+        }
+        sampler.last_at = now;
+        let screen = self.terminal.snapshot()?;
+        let hash = screen.screen_hash();
+        if hash == sampler.last_hash {
+            return Ok(());
+        }
+        sampler.last_hash = hash;
+        if sampler.taken >= sampler.max_samples {
+            sampler.dropped += 1;
+            return Ok(());
+        }
+        sampler.taken += 1;
+        sampler.samples.push(ScreenSample {
+            step_id: sampler.step_id,
+            // Elapsed time is always well under u64::MAX
+            #[allow(clippy::cast_possible_truncation)]
+            timestamp_ms: { self.started_at.elapsed().as_millis() as u64 },
+            screen,
+        });
+        Ok(())
+    }
+}
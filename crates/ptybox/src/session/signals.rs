@@ -0,0 +1,156 @@
+//! `signal` actions: real signals delivered to the child's process group.
+//!
+//! Ctrl+C typed as text only becomes `SIGINT` when the PTY has `ISIG` set;
+//! a `signal` action sends the signal directly, whatever the line
+//! discipline. Each delivery is reported as a `signal_sent` event on the
+//! next observation.
+//!
+//! Only sessions with a local child process can be signaled; remote (SSH)
+//! sessions reject `signal` actions. Under the container sandbox the local
+//! child is the runtime CLI, so signals go to the container through the
+//! runtime instead ([`Session::set_container`]).
+
+#[cfg(unix)]
+use super::signal_process_group;
+use super::Session;
+use crate::model::policy::ChildSignal;
+use crate::model::Event;
+use crate::runner::RunnerError;
+#[cfg(unix)]
+use nix::sys::signal::Signal;
+#[cfg(unix)]
+use nix::unistd::Pid;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Payload of a `signal` action.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SignalPayload {
+    signal: ChildSignal,
+}
+
+/// Parse a `signal` action payload (`{signal: "SIGINT"}`).
+///
+/// # Errors
+/// - `E_PROTOCOL`: The payload is not an object with a supported `signal`
+pub(crate) fn decode_signal_payload(payload: &Value) -> Result<ChildSignal, RunnerError> {
+    SignalPayload::deserialize(payload)
+        .map(|parsed| parsed.signal)
+        .map_err(|err| {
+            RunnerError::protocol(
+                "E_PROTOCOL",
+                "invalid signal action payload",
+                serde_json::json!({
+                    "parse_error": err.to_string(),
+                    "received_payload": payload,
+                    "supported_signals": ChildSignal::ALL.map(ChildSignal::as_str),
+                }),
+            )
+        })
+}
+
+impl Session {
+    /// Send `signal` to the child's process group.
+    ///
+    /// No policy check is made here; the runner, driver, and serve gate the
+    /// `signal` action on `process.allowed_signals` first. The delivery is
+    /// reported as a `signal_sent` event on the next observation.
+    ///
+    /// # Errors
+    /// - `E_PROTOCOL`: The session has no local child process (remote sessions)
+    /// - `E_IO`: Failed to signal the process group or the container
+    pub fn send_signal(&mut self, signal: ChildSignal) -> Result<(), RunnerError> {
+        self.deliver_signal(signal)?;
+        self.pending_events.push(Event {
+            event_type: "signal_sent".to_string(),
+            message: Some(format!("sent {} to the process group", signal.as_str())),
+            details: Some(serde_json::json!({ "signal": signal })),
+        });
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    fn deliver_signal(&mut self, signal: ChildSignal) -> Result<(), RunnerError> {
+        if let Some(container) = &self.container {
+            return match container.signal(signal) {
+                // A container that already exited is fine, like ESRCH below
+                Err(_) if matches!(self.child.try_wait(), Ok(Some(_))) => Ok(()),
+                result => result,
+            };
+        }
+        let pgid = process_group(self.child.process_id(), signal)?;
+        signal_process_group(pgid, nix_signal(signal))
+    }
+
+    #[cfg(not(unix))]
+    fn deliver_signal(&mut self, signal: ChildSignal) -> Result<(), RunnerError> {
+        Err(RunnerError::protocol(
+            "E_PROTOCOL",
+            "signal actions are not supported on this platform",
+            serde_json::json!({ "signal": signal }),
+        ))
+    }
+
+    /// Run a `signal` action payload (`{signal}`).
+    pub(super) fn signal_action(&mut self, payload: &Value) -> Result<(), RunnerError> {
+        let signal = decode_signal_payload(payload)?;
+        self.send_signal(signal)
+    }
+}
+
+/// Process group led by the child `pid`.
+///
+/// # Errors
+/// - `E_PROTOCOL`: There is no local child process to signal
+#[cfg(unix)]
+fn process_group(pid: Option<u32>, signal: ChildSignal) -> Result<Pid, RunnerError> {
+    let Some(pid) = pid else {
+        return Err(RunnerError::protocol(
+            "E_PROTOCOL",
+            "signal actions need a local child process",
+            serde_json::json!({
+                "signal": signal,
+                "fix": "Send the signal's control key instead (e.g. Ctrl+C for SIGINT), or run the scenario locally"
+            }),
+        ));
+    };
+    // Process IDs are always positive and fit in i32
+    #[allow(clippy::cast_possible_wrap)]
+    Ok(Pid::from_raw(pid as i32))
+}
+
+#[cfg(unix)]
+fn nix_signal(signal: ChildSignal) -> Signal {
+    match signal {
+        ChildSignal::Int => Signal::SIGINT,
+        ChildSignal::Hup => Signal::SIGHUP,
+        ChildSignal::Term => Signal::SIGTERM,
+        ChildSignal::Quit => Signal::SIGQUIT,
+        ChildSignal::Tstp => Signal::SIGTSTP,
+        ChildSignal::Cont => Signal::SIGCONT,
+        ChildSignal::Usr1 => Signal::SIGUSR1,
+        ChildSignal::Usr2 => Signal::SIGUSR2,
+        ChildSignal::Winch => Signal::SIGWINCH,
+    }
+}
+
+#[cfg(all(test, unix))]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::runner::ErrorCode;
+
+    #[test]
+    fn sessions_without_a_local_pid_reject_signals() {
+        let err = process_group(None, ChildSignal::Int).unwrap_err();
+        assert_eq!(err.code, ErrorCode::Protocol);
+        let context = err.context.unwrap();
+        assert_eq!(context.get("signal").unwrap(), "SIGINT");
+        assert!(context.get("fix").is_some_and(serde_json::Value::is_string));
+        assert_eq!(
+            process_group(Some(42), ChildSignal::Int).unwrap(),
+            Pid::from_raw(42)
+        );
+    }
+}
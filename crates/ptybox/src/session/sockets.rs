@@ -0,0 +1,102 @@
+//! Unix sockets created for the child by `policy.sockets`.
+//!
+//! Each socket listens in a per-run directory under the temp dir that only
+//! the invoking user can enter. The child finds it through an environment
+//! variable; the listening ends go to the embedder, which serves them
+//! in-process. The directory and its sockets are removed when the
+//! [`SocketDir`] is dropped.
+
+use crate::model::policy::{EnvPolicy, SocketGrant};
+use crate::model::RunId;
+use crate::runner::{RunnerError, RunnerResult};
+use std::os::unix::fs::DirBuilderExt;
+use std::os::unix::net::UnixListener;
+use std::path::{Path, PathBuf};
+
+/// Longest socket path accepted, below the smallest `sun_path` (104 bytes
+/// on macOS) to leave room for the terminating NUL.
+const MAX_SOCKET_PATH_LEN: usize = 103;
+
+/// Directory that holds the sockets of run `run_id`.
+///
+/// The path is derived from the run id alone, so the sandbox profile can
+/// name it before the sockets exist. The temp dir is canonicalized because
+/// Seatbelt matches resolved paths (`/var` is a symlink on macOS).
+pub(crate) fn socket_dir(run_id: RunId) -> PathBuf {
+    let temp = std::env::temp_dir();
+    let temp = temp.canonicalize().unwrap_or(temp);
+    let id = run_id.to_string();
+    temp.join(format!("ptybox-{}", id.get(..13).unwrap_or(&id)))
+}
+
+/// Sockets bound for one run, listening until handed to the embedder.
+#[derive(Debug)]
+pub struct SocketDir {
+    dir: PathBuf,
+    sockets: Vec<(String, PathBuf)>,
+    listeners: Vec<(String, UnixListener)>,
+}
+
+impl SocketDir {
+    /// Create the run's socket directory (mode `0700`) and bind one
+    /// listening socket per grant.
+    ///
+    /// # Errors
+    /// Returns `E_IO` if the directory cannot be created, a socket path is
+    /// too long for the platform, or a socket cannot be bound.
+    pub fn bind(run_id: RunId, grants: &[SocketGrant]) -> RunnerResult<Self> {
+        let dir = socket_dir(run_id);
+        std::fs::DirBuilder::new()
+            .mode(0o700)
+            .create(&dir)
+            .map_err(|err| RunnerError::io("E_IO", "failed to create socket directory", err))?;
+        let mut bound = Self {
+            dir,
+            sockets: Vec::with_capacity(grants.len()),
+            listeners: Vec::with_capacity(grants.len()),
+        };
+        for grant in grants {
+            let path = bound.dir.join(format!("{}.sock", grant.name));
+            if path.as_os_str().len() > MAX_SOCKET_PATH_LEN {
+                return Err(RunnerError::io(
+                    "E_IO",
+                    format!("socket path for '{}' is too long", grant.name),
+                    std::io::Error::from(std::io::ErrorKind::InvalidInput),
+                ));
+            }
+            let listener = UnixListener::bind(&path)
+                .map_err(|err| RunnerError::io("E_IO", "failed to bind socket", err))?;
+            bound.sockets.push((grant.env_var(), path));
+            bound.listeners.push((grant.name.clone(), listener));
+        }
+        Ok(bound)
+    }
+
+    /// Directory holding the sockets.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Add each socket's environment variable to `env`, allowlisted so it
+    /// reaches the child.
+    pub fn apply_env(&self, env: &mut EnvPolicy) {
+        for (var, path) in &self.sockets {
+            if !env.allowlist.iter().any(|allowed| allowed == var) {
+                env.allowlist.push(var.clone());
+            }
+            env.set.insert(var.clone(), path.display().to_string());
+        }
+    }
+
+    /// Take the listening ends, paired with their grant names.
+    pub fn take_listeners(&mut self) -> Vec<(String, UnixListener)> {
+        std::mem::take(&mut self.listeners)
+    }
+}
+
+impl Drop for SocketDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
@@ -0,0 +1,387 @@
+//! SSH transport for remote sessions (`ssh` feature).
+//!
+//! One `session` channel carries the remote PTY. After the command starts,
+//! the SSH session is switched to non-blocking mode so the background reader
+//! and the writer can share the channel: each call takes the channel lock
+//! only for one libssh2 call and retries on `EAGAIN`.
+
+use super::remote::RemoteConfig;
+use super::SpawnedPty;
+use crate::model::TerminalSize;
+use crate::runner::{ErrorCode, RunnerError};
+use crate::util::encode_base64;
+use portable_pty::{Child, ChildKiller, ExitStatus, MasterPty, PtySize};
+use ssh2::{CheckResult, HashType, HostKeyType, KnownHostFileKind};
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Pause between retries of a libssh2 call that returned `EAGAIN`.
+const RETRY_PAUSE: Duration = Duration::from_millis(2);
+
+/// How long a write, resize, or close may keep retrying before it fails.
+const CALL_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// libssh2's "would block" error code.
+const LIBSSH2_ERROR_EAGAIN: i32 = -37;
+
+type SharedChannel = Arc<Mutex<ssh2::Channel>>;
+
+/// A started remote command and the host key it was verified with.
+pub(super) struct Connected {
+    pub(super) pty: SpawnedPty,
+    pub(super) host_key_type: String,
+    pub(super) host_key_fingerprint: String,
+}
+
+/// Connect to the target, verify its host key, authenticate, and start
+/// `command_line` on a remote PTY of `size` with terminal type `term`.
+///
+/// # Errors
+/// - `E_POLICY_DENIED`: the host key is missing from or does not match
+///   `known_hosts`
+/// - `E_IO`: connecting, the handshake, authentication, or starting the
+///   command failed
+pub(super) fn open(
+    remote: &RemoteConfig,
+    size: &TerminalSize,
+    term: &str,
+    command_line: &str,
+) -> Result<Connected, RunnerError> {
+    let target = &remote.target;
+    let tcp = connect(&target.host, target.port, remote.connect_timeout)?;
+    let mut ssh = ssh2::Session::new().map_err(|err| ssh_error("failed to start ssh", err))?;
+    ssh.set_tcp_stream(tcp);
+    ssh.set_timeout(u32::try_from(remote.connect_timeout.as_millis()).unwrap_or(u32::MAX));
+    ssh.handshake()
+        .map_err(|err| ssh_error("ssh handshake failed", err))?;
+    let (host_key_type, host_key_fingerprint) = verify_host_key(&ssh, remote)?;
+
+    ssh.userauth_pubkey_file(&target.user, None, Path::new(&target.key_path), None)
+        .map_err(|err| {
+            RunnerError::with_context(
+                ErrorCode::Io,
+                "ssh authentication failed",
+                serde_json::json!({
+                    "source": err.to_string(),
+                    "user": target.user,
+                    "key_path": target.key_path,
+                }),
+            )
+        })?;
+
+    let mut channel = ssh
+        .channel_session()
+        .map_err(|err| ssh_error("failed to open ssh channel", err))?;
+    channel
+        .request_pty(
+            term,
+            None,
+            Some((
+                u32::from(size.cols),
+                u32::from(size.rows),
+                u32::from(size.pixel_width),
+                u32::from(size.pixel_height),
+            )),
+        )
+        .map_err(|err| ssh_error("remote pty request failed", err))?;
+    channel
+        .exec(command_line)
+        .map_err(|err| ssh_error("failed to start remote command", err))?;
+    ssh.set_timeout(0);
+    ssh.set_blocking(false);
+
+    let channel = Arc::new(Mutex::new(channel));
+    let master = SshMaster {
+        channel: Arc::clone(&channel),
+        size: Mutex::new(super::pty_size(size)),
+    };
+    let pty = SpawnedPty {
+        reader: Box::new(ChannelReader {
+            channel: Arc::clone(&channel),
+        }),
+        writer: Box::new(ChannelWriter {
+            channel: Arc::clone(&channel),
+        }),
+        master: Box::new(master),
+        child: Box::new(SshChild { channel }),
+    };
+    Ok(Connected {
+        pty,
+        host_key_type,
+        host_key_fingerprint,
+    })
+}
+
+fn connect(host: &str, port: u16, timeout: Duration) -> Result<TcpStream, RunnerError> {
+    let context = |err: &io::Error| serde_json::json!({ "source": err.to_string(), "host": host, "port": port });
+    let addrs = (host, port).to_socket_addrs().map_err(|err| {
+        RunnerError::with_context(
+            ErrorCode::Io,
+            "failed to resolve remote host",
+            context(&err),
+        )
+    })?;
+    let mut last_error = io::Error::new(io::ErrorKind::NotFound, "no addresses");
+    for addr in addrs {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(stream) => return Ok(stream),
+            Err(err) => last_error = err,
+        }
+    }
+    Err(RunnerError::with_context(
+        ErrorCode::Io,
+        "failed to connect to remote host",
+        context(&last_error),
+    ))
+}
+
+/// Check the server's host key against `known_hosts`; returns its type and
+/// fingerprint.
+fn verify_host_key(
+    ssh: &ssh2::Session,
+    remote: &RemoteConfig,
+) -> Result<(String, String), RunnerError> {
+    let target = &remote.target;
+    let (key, key_type) = ssh
+        .host_key()
+        .ok_or_else(|| RunnerError::io("E_IO", "ssh server sent no host key", "missing"))?;
+    let key_type = host_key_type_name(key_type).to_string();
+    let fingerprint = ssh
+        .host_key_hash(HashType::Sha256)
+        .map(|hash| format!("SHA256:{}", encode_base64(hash).trim_end_matches('=')))
+        .unwrap_or_default();
+
+    let mut known_hosts = ssh
+        .known_hosts()
+        .map_err(|err| ssh_error("failed to read known_hosts", err))?;
+    known_hosts
+        .read_file(Path::new(&remote.known_hosts), KnownHostFileKind::OpenSSH)
+        .map_err(|err| {
+            RunnerError::with_context(
+                ErrorCode::Io,
+                "failed to read known_hosts",
+                serde_json::json!({
+                    "source": err.to_string(),
+                    "known_hosts": remote.known_hosts,
+                }),
+            )
+        })?;
+    let problem = match known_hosts.check_port(&target.host, target.port, key) {
+        CheckResult::Match => return Ok((key_type, fingerprint)),
+        CheckResult::Mismatch => "remote host key does not match known_hosts",
+        CheckResult::NotFound => "remote host key is not in known_hosts",
+        CheckResult::Failure => "remote host key could not be checked",
+    };
+    Err(RunnerError::policy_denied(
+        "E_POLICY_DENIED",
+        problem,
+        serde_json::json!({
+            "host": target.host,
+            "port": target.port,
+            "host_key_type": key_type,
+            "fingerprint": fingerprint,
+            "known_hosts": remote.known_hosts,
+            "fix": "Verify the fingerprint out of band, then add the key to the known_hosts file",
+        }),
+    ))
+}
+
+fn host_key_type_name(key_type: HostKeyType) -> &'static str {
+    match key_type {
+        HostKeyType::Rsa => "ssh-rsa",
+        HostKeyType::Dss => "ssh-dss",
+        HostKeyType::Ecdsa256 => "ecdsa-sha2-nistp256",
+        HostKeyType::Ecdsa384 => "ecdsa-sha2-nistp384",
+        HostKeyType::Ecdsa521 => "ecdsa-sha2-nistp521",
+        HostKeyType::Ed25519 => "ssh-ed25519",
+        HostKeyType::Unknown => "unknown",
+    }
+}
+
+fn ssh_error(message: &str, err: ssh2::Error) -> RunnerError {
+    RunnerError::io("E_IO", message, err)
+}
+
+fn lock(channel: &SharedChannel) -> io::Result<MutexGuard<'_, ssh2::Channel>> {
+    channel
+        .lock()
+        .map_err(|_| io::Error::other("ssh channel lock poisoned"))
+}
+
+/// Run `call` until it stops returning `EAGAIN`, for at most
+/// [`CALL_TIMEOUT`].
+fn retry<T>(
+    channel: &SharedChannel,
+    mut call: impl FnMut(&mut ssh2::Channel) -> io::Result<T>,
+) -> io::Result<T> {
+    let deadline = Instant::now() + CALL_TIMEOUT;
+    loop {
+        let result = call(&mut *lock(channel)?);
+        match result {
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                if Instant::now() >= deadline {
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        "ssh channel stayed busy",
+                    ));
+                }
+                std::thread::sleep(RETRY_PAUSE);
+            }
+            other => return other,
+        }
+    }
+}
+
+/// Remote output. Returns `WouldBlock` while nothing is pending, which the
+/// session reader polls on like a non-blocking local PTY.
+struct ChannelReader {
+    channel: SharedChannel,
+}
+
+impl Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut channel = lock(&self.channel)?;
+        match channel.read(buf) {
+            Ok(0) if !channel.eof() => Err(io::ErrorKind::WouldBlock.into()),
+            other => other,
+        }
+    }
+}
+
+/// Input for the remote PTY.
+struct ChannelWriter {
+    channel: SharedChannel,
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        retry(&self.channel, |channel| channel.write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        retry(&self.channel, |channel| channel.flush())
+    }
+}
+
+/// The remote PTY, standing in for a local PTY master.
+struct SshMaster {
+    channel: SharedChannel,
+    size: Mutex<PtySize>,
+}
+
+impl MasterPty for SshMaster {
+    fn resize(&self, size: PtySize) -> Result<(), anyhow::Error> {
+        retry(&self.channel, |channel| {
+            channel
+                .request_pty_size(
+                    u32::from(size.cols),
+                    u32::from(size.rows),
+                    Some(u32::from(size.pixel_width)),
+                    Some(u32::from(size.pixel_height)),
+                )
+                .map_err(io::Error::from)
+        })?;
+        if let Ok(mut current) = self.size.lock() {
+            *current = size;
+        }
+        Ok(())
+    }
+
+    fn get_size(&self) -> Result<PtySize, anyhow::Error> {
+        self.size
+            .lock()
+            .map(|size| *size)
+            .map_err(|_| anyhow::anyhow!("pty size lock poisoned"))
+    }
+
+    fn try_clone_reader(&self) -> Result<Box<dyn Read + Send>, anyhow::Error> {
+        Ok(Box::new(ChannelReader {
+            channel: Arc::clone(&self.channel),
+        }))
+    }
+
+    fn take_writer(&self) -> Result<Box<dyn Write + Send>, anyhow::Error> {
+        Ok(Box::new(ChannelWriter {
+            channel: Arc::clone(&self.channel),
+        }))
+    }
+
+    #[cfg(unix)]
+    fn process_group_leader(&self) -> Option<i32> {
+        None
+    }
+
+    #[cfg(unix)]
+    fn as_raw_fd(&self) -> Option<std::os::unix::io::RawFd> {
+        None
+    }
+}
+
+/// The remote command. It has no local process id; killing it closes the
+/// channel, and the server hangs up the remote process.
+#[derive(Clone)]
+struct SshChild {
+    channel: SharedChannel,
+}
+
+impl std::fmt::Debug for SshChild {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SshChild")
+    }
+}
+
+impl ChildKiller for SshChild {
+    fn kill(&mut self) -> io::Result<()> {
+        retry(&self.channel, |channel| {
+            channel.close().map_err(io::Error::from)
+        })
+    }
+
+    fn clone_killer(&self) -> Box<dyn ChildKiller + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+impl Child for SshChild {
+    fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+        let mut channel = lock(&self.channel)?;
+        if !channel.eof() {
+            return Ok(None);
+        }
+        match channel.close().and_then(|()| channel.wait_close()) {
+            Ok(()) => {}
+            Err(err) if err.code() == ssh2::ErrorCode::Session(LIBSSH2_ERROR_EAGAIN) => {
+                return Ok(None);
+            }
+            Err(err) => return Err(err.into()),
+        }
+        let signal = channel.exit_signal().ok().and_then(|exit| exit.exit_signal);
+        Ok(Some(match signal {
+            Some(name) => ExitStatus::with_signal(&format!("SIG{name}")),
+            None => ExitStatus::with_exit_code(
+                u32::try_from(channel.exit_status().map_err(io::Error::from)?).unwrap_or(1),
+            ),
+        }))
+    }
+
+    fn wait(&mut self) -> io::Result<ExitStatus> {
+        loop {
+            if let Some(status) = self.try_wait()? {
+                return Ok(status);
+            }
+            std::thread::sleep(RETRY_PAUSE);
+        }
+    }
+
+    fn process_id(&self) -> Option<u32> {
+        None
+    }
+
+    #[cfg(windows)]
+    fn as_raw_handle(&self) -> Option<std::os::windows::io::RawHandle> {
+        None
+    }
+}
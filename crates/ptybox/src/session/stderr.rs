@@ -0,0 +1,111 @@
+//! Separate stderr capture for sessions spawned with `separate_stderr`.
+//!
+//! The child's stderr is redirected into a named pipe while stdout stays on
+//! the PTY. A small `/bin/sh` trampoline performs the redirect and then
+//! `exec`s the real command, so the child keeps the PTY as its controlling
+//! terminal and its pid is unchanged.
+
+use crate::model::SessionId;
+use crate::runner::RunnerError;
+use nix::fcntl::OFlag;
+use nix::sys::stat::Mode;
+use std::fs::File;
+use std::io::Read;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
+
+/// Shell script that points fd 2 at the pipe (`$0`) and runs the command.
+const TRAMPOLINE: &str = r#"exec 2>"$0" && exec "$@""#;
+
+/// Read end of the stderr pipe plus everything captured so far.
+pub(crate) struct StderrPipe {
+    path: PathBuf,
+    file: File,
+    buffer: Vec<u8>,
+    pending_utf8_tail: Vec<u8>,
+    captured: String,
+}
+
+impl StderrPipe {
+    /// Create the pipe in the temp dir and open its read end without blocking.
+    pub(crate) fn create(session_id: SessionId) -> Result<Self, RunnerError> {
+        let path = std::env::temp_dir().join(format!("ptybox-{session_id}.stderr"));
+        nix::unistd::mkfifo(&path, Mode::S_IRUSR | Mode::S_IWUSR)
+            .map_err(|err| RunnerError::io("E_IO", "failed to create stderr pipe", err))?;
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .custom_flags(OFlag::O_NONBLOCK.bits())
+            .open(&path);
+        let file = match file {
+            Ok(file) => file,
+            Err(err) => {
+                let _ = std::fs::remove_file(&path);
+                return Err(RunnerError::io("E_IO", "failed to open stderr pipe", err));
+            }
+        };
+        Ok(Self {
+            path,
+            file,
+            buffer: vec![0u8; 4096],
+            pending_utf8_tail: Vec::new(),
+            captured: String::new(),
+        })
+    }
+
+    /// Command and arguments that run `command` with stderr sent to the pipe.
+    pub(crate) fn wrap(&self, command: &str, args: &[String]) -> (String, Vec<String>) {
+        let mut wrapped = vec![
+            "-c".to_string(),
+            TRAMPOLINE.to_string(),
+            self.path.display().to_string(),
+            command.to_string(),
+        ];
+        wrapped.extend(args.iter().cloned());
+        ("/bin/sh".to_string(), wrapped)
+    }
+
+    /// Drain whatever the child has written since the last call.
+    ///
+    /// Invalid UTF-8 is replaced rather than rejected: stderr is not parsed
+    /// as terminal output. An incomplete trailing sequence is held back until
+    /// the rest arrives.
+    pub(crate) fn read_delta(&mut self) -> Result<Option<String>, RunnerError> {
+        let mut bytes = std::mem::take(&mut self.pending_utf8_tail);
+        loop {
+            match self.file.read(&mut self.buffer) {
+                // No writer has connected yet, or every writer has closed.
+                Ok(0) => break,
+                Ok(count) => {
+                    if let Some(slice) = self.buffer.get(..count) {
+                        bytes.extend_from_slice(slice);
+                    }
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => break,
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(RunnerError::io("E_IO", "failed to read stderr pipe", err)),
+            }
+        }
+        if let Err(err) = std::str::from_utf8(&bytes) {
+            if err.error_len().is_none() {
+                self.pending_utf8_tail = bytes.split_off(err.valid_up_to());
+            }
+        }
+        if bytes.is_empty() {
+            return Ok(None);
+        }
+        let delta = String::from_utf8_lossy(&bytes).into_owned();
+        self.captured.push_str(&delta);
+        Ok(Some(delta))
+    }
+
+    /// All stderr captured since the session started.
+    pub(crate) fn captured(&self) -> &str {
+        &self.captured
+    }
+}
+
+impl Drop for StderrPipe {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
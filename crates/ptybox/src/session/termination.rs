@@ -0,0 +1,212 @@
+//! Policy-driven termination of the child process group.
+//!
+//! The runner, the driver, and [`Session::close`] stop the child the same
+//! way: `process.kill_signal`, a `process.term_grace_ms` grace period, the
+//! optional `process.pre_kill_hook`, then `SIGKILL`. What happened is
+//! returned as a [`TerminationRecord`] so it can be attached to the exit
+//! status. When the runtime budget ran out, [`Session::terminate_at_deadline`]
+//! sends `process.deadline_signal` first and gives the child
+//! `process.deadline_grace_ms` to exit on its own.
+
+#[cfg(unix)]
+use super::signal_process_group;
+use super::Session;
+use crate::model::policy::{KillSignal, PreKillHook, ProcessPolicy};
+use crate::model::{DeadlineWarning, ExitStatus, PreKillHookResult, TerminationRecord};
+use crate::runner::RunnerError;
+use crate::util::{convert_exit_status, pause_until};
+#[cfg(unix)]
+use nix::sys::signal::Signal;
+#[cfg(unix)]
+use nix::unistd::Pid;
+use std::collections::BTreeMap;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// How long to wait for the child to be reaped after `SIGKILL`.
+const KILL_WAIT_MS: u64 = 200;
+
+/// Result of [`Session::terminate_with`].
+#[derive(Debug)]
+pub struct Termination {
+    /// Exit status, if the child was reaped.
+    pub status: Option<portable_pty::ExitStatus>,
+    /// Signal, grace period, escalation, and hook outcome.
+    pub record: TerminationRecord,
+}
+
+impl Termination {
+    /// Exit status marked `terminated_by_harness`, carrying the record.
+    #[must_use]
+    pub fn exit_status(self) -> Option<ExitStatus> {
+        let record = self.record;
+        self.status.map(|status| ExitStatus {
+            termination: Some(record),
+            ..convert_exit_status(status, true)
+        })
+    }
+}
+
+impl Session {
+    /// Send `signal` to the process group without waiting.
+    ///
+    /// # Errors
+    /// - `E_IO`: Failed to signal process
+    pub fn signal(&mut self, signal: KillSignal) -> Result<(), RunnerError> {
+        #[cfg(unix)]
+        if let Some(pid) = self.child.process_id() {
+            // Process IDs are always positive and fit in i32
+            #[allow(clippy::cast_possible_wrap)]
+            let pgid = Pid::from_raw(pid as i32);
+            return signal_process_group(pgid, nix_signal(signal));
+        }
+
+        #[cfg(not(unix))]
+        let _ = signal;
+        self.child
+            .kill()
+            .map_err(|err| RunnerError::io("E_IO", "failed to terminate child", err))
+    }
+
+    /// Stop the process group as configured by `process`.
+    ///
+    /// Sends `process.kill_signal` and waits up to `process.term_grace_ms`
+    /// for exit. If the child is still running, runs `process.pre_kill_hook`
+    /// (when set, with the [`set_hook_env`](Self::set_hook_env) overlay) and
+    /// sends `SIGKILL`. With `kill_signal: SIGKILL` the grace period and
+    /// hook are skipped.
+    ///
+    /// # Errors
+    /// - `E_IO`: Failed to signal or wait for process
+    pub fn terminate_with(&mut self, process: &ProcessPolicy) -> Result<Termination, RunnerError> {
+        let mut record = TerminationRecord {
+            signal: process.kill_signal,
+            grace_ms: process.term_grace_ms,
+            escalated: false,
+            pre_kill_hook: None,
+            deadline_warning: None,
+        };
+        self.signal(process.kill_signal)?;
+        if process.kill_signal != KillSignal::Kill {
+            let grace = Duration::from_millis(process.term_grace_ms);
+            if let Some(status) = self.wait_for_exit(grace)? {
+                return Ok(Termination {
+                    status: Some(status),
+                    record,
+                });
+            }
+            if let Some(hook) = &process.pre_kill_hook {
+                let pid = self.child.process_id().unwrap_or_default();
+                record.pre_kill_hook = Some(run_pre_kill_hook(hook, pid, &self.hook_env));
+            }
+            record.escalated = true;
+            self.signal(KillSignal::Kill)?;
+        }
+        let status = self.wait_for_exit(Duration::from_millis(KILL_WAIT_MS))?;
+        Ok(Termination { status, record })
+    }
+
+    /// Stop the process group because the runtime budget ran out.
+    ///
+    /// With `process.deadline_signal` set, sends it and waits up to
+    /// `process.deadline_grace_ms` for the child to exit, recording the
+    /// warning in the result. A child still running then is stopped as by
+    /// [`terminate_with`](Self::terminate_with).
+    ///
+    /// # Errors
+    /// - `E_IO`: Failed to signal or wait for process
+    pub fn terminate_at_deadline(
+        &mut self,
+        process: &ProcessPolicy,
+    ) -> Result<Termination, RunnerError> {
+        let Some(signal) = process.deadline_signal else {
+            return self.terminate_with(process);
+        };
+        self.send_signal(signal)?;
+        let exited = self.wait_for_exit(Duration::from_millis(process.deadline_grace_ms))?;
+        let warning = DeadlineWarning {
+            signal,
+            grace_ms: process.deadline_grace_ms,
+            exited: exited.is_some(),
+        };
+        if exited.is_some() {
+            return Ok(Termination {
+                status: exited,
+                record: TerminationRecord {
+                    signal: process.kill_signal,
+                    grace_ms: process.term_grace_ms,
+                    escalated: false,
+                    pre_kill_hook: None,
+                    deadline_warning: Some(warning),
+                },
+            });
+        }
+        let mut termination = self.terminate_with(process)?;
+        termination.record.deadline_warning = Some(warning);
+        Ok(termination)
+    }
+}
+
+#[cfg(unix)]
+fn nix_signal(signal: KillSignal) -> Signal {
+    match signal {
+        KillSignal::Term => Signal::SIGTERM,
+        KillSignal::Int => Signal::SIGINT,
+        KillSignal::Hup => Signal::SIGHUP,
+        KillSignal::Quit => Signal::SIGQUIT,
+        KillSignal::Kill => Signal::SIGKILL,
+    }
+}
+
+/// Run `hook` with `env` and `PTYBOX_CHILD_PID` set, killing it after
+/// `timeout_ms`.
+fn run_pre_kill_hook(
+    hook: &PreKillHook,
+    pid: u32,
+    env: &BTreeMap<String, String>,
+) -> PreKillHookResult {
+    let mut result = PreKillHookResult {
+        command: hook.command.clone(),
+        exit_code: None,
+        timed_out: false,
+        error: None,
+    };
+    let spawned = Command::new(&hook.command)
+        .args(&hook.args)
+        .env_clear()
+        .envs(env)
+        .env("PTYBOX_CHILD_PID", pid.to_string())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    let mut child = match spawned {
+        Ok(child) => child,
+        Err(err) => {
+            result.error = Some(err.to_string());
+            return result;
+        }
+    };
+    let deadline = Instant::now() + Duration::from_millis(hook.timeout_ms);
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                result.exit_code = status.code();
+                return result;
+            }
+            Ok(None) if Instant::now() < deadline => {
+                pause_until(deadline, Duration::from_millis(10));
+            }
+            Ok(None) => {
+                result.timed_out = true;
+                let _ = child.kill();
+                let _ = child.wait();
+                return result;
+            }
+            Err(err) => {
+                result.error = Some(err.to_string());
+                return result;
+            }
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! PTY line-discipline control: echo, canonical mode, and signal keys.
+//!
+//! Both ends of a PTY share one set of termios flags, so changing them
+//! through the master is what the child sees on the slave side.
+
+use super::Session;
+use crate::model::TermiosSettings;
+use crate::runner::RunnerError;
+use serde_json::Value;
+
+/// Flags accepted by the `termios` action payload.
+const TERMIOS_FLAGS: [&str; 3] = ["echo", "icanon", "isig"];
+
+impl Session {
+    /// Set or clear `ECHO`, `ICANON`, and `ISIG` on the PTY.
+    ///
+    /// Flags passed as `None` are left as they are. Returns the flags read
+    /// back after the change, which are also kept for
+    /// [`take_termios`](Self::take_termios). The child may change them
+    /// again itself (for example when a TUI enters raw mode).
+    ///
+    /// # Errors
+    /// - `E_PROTOCOL`: No flag given, or termios is unsupported on this platform
+    /// - `E_IO`: Failed to read or write the PTY's termios
+    pub fn set_termios(
+        &mut self,
+        echo: Option<bool>,
+        icanon: Option<bool>,
+        isig: Option<bool>,
+    ) -> Result<TermiosSettings, RunnerError> {
+        if echo.is_none() && icanon.is_none() && isig.is_none() {
+            return Err(RunnerError::protocol(
+                "E_PROTOCOL",
+                "termios action requires at least one of echo, icanon, isig",
+                serde_json::json!({ "supported_flags": TERMIOS_FLAGS }),
+            ));
+        }
+        let settings = self.apply_termios(echo, icanon, isig)?;
+        self.last_termios = Some(settings);
+        Ok(settings)
+    }
+
+    /// The flags applied by the most recent `set_termios`, cleared on read.
+    pub fn take_termios(&mut self) -> Option<TermiosSettings> {
+        self.last_termios.take()
+    }
+
+    /// Run a `termios` action payload (`{echo?, icanon?, isig?}`).
+    pub(super) fn termios_action(&mut self, payload: &Value) -> Result<(), RunnerError> {
+        let Some(fields) = payload.as_object() else {
+            return Err(invalid_payload(
+                payload,
+                "termios payload must be an object",
+            ));
+        };
+        if let Some(unknown) = fields
+            .keys()
+            .find(|key| !TERMIOS_FLAGS.contains(&key.as_str()))
+        {
+            return Err(invalid_payload(
+                payload,
+                format!("unknown termios flag '{unknown}'"),
+            ));
+        }
+        let flag = |name: &str| -> Result<Option<bool>, RunnerError> {
+            match fields.get(name) {
+                None => Ok(None),
+                Some(Value::Bool(value)) => Ok(Some(*value)),
+                Some(_) => Err(invalid_payload(
+                    payload,
+                    format!("'{name}' must be a boolean in termios payload"),
+                )),
+            }
+        };
+        self.set_termios(flag("echo")?, flag("icanon")?, flag("isig")?)
+            .map(|_| ())
+    }
+
+    #[cfg(unix)]
+    fn apply_termios(
+        &self,
+        echo: Option<bool>,
+        icanon: Option<bool>,
+        isig: Option<bool>,
+    ) -> Result<TermiosSettings, RunnerError> {
+        use nix::sys::termios::{tcgetattr, tcsetattr, LocalFlags, SetArg};
+
+        let raw = self.master.as_raw_fd().ok_or_else(|| {
+            RunnerError::protocol(
+                "E_PROTOCOL",
+                "termios is not supported by this pty",
+                serde_json::json!({ "supported_flags": TERMIOS_FLAGS }),
+            )
+        })?;
+        let fd = filedescriptor::FileDescriptor::dup(&raw)
+            .map_err(|err| RunnerError::io("E_IO", "failed to duplicate pty descriptor", err))?;
+        let mut termios = tcgetattr(&fd)
+            .map_err(|err| RunnerError::io("E_IO", "failed to read pty termios", err))?;
+        for (flag, value) in [
+            (LocalFlags::ECHO, echo),
+            (LocalFlags::ICANON, icanon),
+            (LocalFlags::ISIG, isig),
+        ] {
+            if let Some(value) = value {
+                termios.local_flags.set(flag, value);
+            }
+        }
+        tcsetattr(&fd, SetArg::TCSANOW, &termios)
+            .map_err(|err| RunnerError::io("E_IO", "failed to write pty termios", err))?;
+        let applied = tcgetattr(&fd)
+            .map_err(|err| RunnerError::io("E_IO", "failed to read pty termios", err))?
+            .local_flags;
+        Ok(TermiosSettings {
+            echo: applied.contains(LocalFlags::ECHO),
+            icanon: applied.contains(LocalFlags::ICANON),
+            isig: applied.contains(LocalFlags::ISIG),
+        })
+    }
+
+    #[cfg(not(unix))]
+    #[allow(clippy::unused_self)]
+    fn apply_termios(
+        &self,
+        _echo: Option<bool>,
+        _icanon: Option<bool>,
+        _isig: Option<bool>,
+    ) -> Result<TermiosSettings, RunnerError> {
+        Err(RunnerError::protocol(
+            "E_PROTOCOL",
+            "termios is not supported on this platform",
+            serde_json::json!({ "platform": std::env::consts::OS }),
+        ))
+    }
+}
+
+fn invalid_payload(payload: &Value, message: impl Into<String>) -> RunnerError {
+    RunnerError::protocol(
+        "E_PROTOCOL",
+        message,
+        serde_json::json!({
+            "received_payload": payload,
+            "supported_flags": TERMIOS_FLAGS,
+        }),
+    )
+}
@@ -0,0 +1,223 @@
+//! Wait-condition polling shared by the runner, driver, and serve entry points.
+
+use super::Session;
+use crate::model::policy::Budgets;
+use crate::model::{Condition, Event, ExitStatus, Observation, WaitStats};
+use crate::predicate::{Predicate, PredicateContext};
+use crate::runner::RunnerError;
+use crate::util::convert_exit_status;
+use serde_json::Value;
+use std::time::{Duration, Instant};
+
+/// First poll interval; reset to this whenever new output arrives.
+const MIN_POLL_INTERVAL: Duration = Duration::from_millis(2);
+/// Upper bound for draining output once the child has exited (reads stop at EOF).
+const EXIT_DRAIN: Duration = Duration::from_millis(50);
+
+impl Session {
+    /// Poll the session until `condition` holds or the wait budget elapses.
+    ///
+    /// `condition` may be any [`Predicate`] type. Once the child has exited
+    /// the condition is checked one last time against the drained screen and
+    /// the exit status, so `process_exited` and `exit_code` hold then.
+    ///
+    /// The effective timeout is `timeout` capped at `budgets.max_wait_ms`.
+    /// Returns the first observation that satisfies the condition. This is
+    /// the supported way for embedders to wait on screen state; the runner,
+    /// driver, and serve daemon all go through it.
+    ///
+    /// Polling backs off exponentially from 2ms up to
+    /// `budgets.max_poll_interval_ms` while the screen is idle and drops back
+    /// to 2ms when output arrives. The returned observation carries the
+    /// transcript read across all polls. Polls and elapsed time are added to
+    /// [`Session::wait_stats`] whether or not the wait succeeds.
+    ///
+    /// # Errors
+    /// - `E_PROTOCOL`: Missing or invalid condition fields, or unknown condition type
+    /// - `E_TIMEOUT`: Condition not satisfied before the deadline
+    /// - `E_PROCESS_EXIT`: Process exited without the condition holding
+    /// - `E_IO`: Failed to read from the PTY
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use ptybox::session::{Session, SessionConfig};
+    /// # use ptybox::model::{Condition, RunId, TerminalSize};
+    /// # use ptybox::model::policy::Budgets;
+    /// # use std::time::Duration;
+    /// # fn example() -> Result<(), ptybox::runner::RunnerError> {
+    /// # let config = SessionConfig {
+    /// #     command: "/bin/cat".to_string(),
+    /// #     args: vec![],
+    /// #     cwd: None,
+    /// #     size: TerminalSize::default(),
+    /// #     run_id: RunId::new(),
+    /// #     env: Default::default(),
+    /// #     separate_stderr: false,
+    /// #     output_buffer: Default::default(),
+    /// #     resize: Default::default(),
+    /// #     remote: None,
+    /// #     encoding: Default::default(),
+    /// # };
+    /// let mut session = Session::spawn(config)?;
+    /// let observation = session.wait_until(
+    ///     &Condition::screen_contains("Ready"),
+    ///     Duration::from_secs(5),
+    ///     &Budgets::default(),
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn wait_until(
+        &mut self,
+        condition: &Condition,
+        timeout: Duration,
+        budgets: &Budgets,
+    ) -> Result<Observation, RunnerError> {
+        let started = Instant::now();
+        let deadline = started + timeout.min(Duration::from_millis(budgets.max_wait_ms));
+        let predicate = Predicate::parse(&condition.condition_type, &condition.payload)?;
+        let cap = Duration::from_millis(budgets.poll_interval_cap_ms());
+
+        let mut polls = 0;
+        let result = self.poll_condition(condition, &predicate, deadline, cap, &mut polls);
+        self.record_wait(polls, started.elapsed());
+        result
+    }
+
+    /// Cumulative polling counters for all waits on this session.
+    pub fn wait_stats(&self) -> WaitStats {
+        self.wait_stats
+    }
+
+    fn poll_condition(
+        &mut self,
+        condition: &Condition,
+        predicate: &Predicate,
+        deadline: Instant,
+        cap: Duration,
+        polls: &mut u64,
+    ) -> Result<Observation, RunnerError> {
+        let mut interval = MIN_POLL_INTERVAL.min(cap);
+        let mut output = PolledOutput::default();
+        loop {
+            if Instant::now() > deadline {
+                return Err(RunnerError::timeout(
+                    "E_TIMEOUT",
+                    "wait condition timed out",
+                    serde_json::json!({
+                        "condition": condition.condition_type,
+                        "polls": *polls,
+                    }),
+                ));
+            }
+
+            // Check for exit first so the observation below drains any output
+            // written before the child exited. observe() blocks for the full
+            // interval, so it doubles as the sleep.
+            let exit_status = self
+                .wait_for_exit(Duration::from_millis(0))?
+                .map(|status| convert_exit_status(status, false));
+            let drain = if exit_status.is_some() {
+                EXIT_DRAIN
+            } else {
+                interval
+            };
+            let observation = self.observe(drain)?;
+            *polls += 1;
+            let had_output = observation.transcript_delta.is_some();
+            let observation = output.absorb(observation);
+            if self.predicate_holds(predicate, &observation, exit_status.as_ref())? {
+                return Ok(output.finish(observation));
+            }
+            if exit_status.is_some() {
+                return Err(RunnerError::process_exit(
+                    "E_PROCESS_EXIT",
+                    "process exited during wait",
+                ));
+            }
+
+            interval = if had_output {
+                MIN_POLL_INTERVAL.min(cap)
+            } else {
+                (interval * 2).min(cap)
+            };
+        }
+    }
+
+    /// Evaluate `predicate` with this session's stderr and response latency.
+    pub(super) fn predicate_holds(
+        &self,
+        predicate: &Predicate,
+        observation: &Observation,
+        exit_status: Option<&ExitStatus>,
+    ) -> Result<bool, RunnerError> {
+        let latency_ms = self
+            .response_latency()?
+            .map(|latency| u64::try_from(latency.as_millis()).unwrap_or(u64::MAX));
+        let context = PredicateContext {
+            exit_status,
+            stderr: self.stderr(),
+            latency_ms,
+            raw_output: Some(self.raw_output()),
+        };
+        Ok(predicate.evaluate(observation, &context).0)
+    }
+
+    fn record_wait(&mut self, polls: u64, elapsed: Duration) {
+        self.wait_stats.polls = self.wait_stats.polls.saturating_add(polls);
+        self.wait_stats.wait_ms = self
+            .wait_stats
+            .wait_ms
+            .saturating_add(u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX));
+    }
+}
+
+/// Output read across all polls of one wait, so the returned observation
+/// carries the full transcript regardless of how many polls it took.
+#[derive(Default)]
+struct PolledOutput {
+    transcript: String,
+    stderr: String,
+    output_bytes: u64,
+    events: Vec<Event>,
+}
+
+impl PolledOutput {
+    /// Move the transcript, stderr, and events of `observation` into the running totals.
+    fn absorb(&mut self, mut observation: Observation) -> Observation {
+        if let Some(delta) = observation.transcript_delta.take() {
+            self.transcript.push_str(&delta);
+        }
+        if let Some(delta) = observation.stderr_delta.take() {
+            self.stderr.push_str(&delta);
+        }
+        for event in observation.events.drain(..) {
+            if event.event_type == "pty_output" {
+                self.output_bytes += event
+                    .details
+                    .as_ref()
+                    .and_then(|details| details.get("bytes"))
+                    .and_then(Value::as_u64)
+                    .unwrap_or(0);
+            } else {
+                self.events.push(event);
+            }
+        }
+        observation
+    }
+
+    /// Attach everything read during the wait to the final observation.
+    fn finish(self, mut observation: Observation) -> Observation {
+        if self.output_bytes > 0 {
+            observation.events.push(Event {
+                event_type: "pty_output".to_string(),
+                message: Some("terminal output read".to_string()),
+                details: Some(serde_json::json!({ "bytes": self.output_bytes })),
+            });
+        }
+        observation.events.extend(self.events);
+        observation.transcript_delta = (!self.transcript.is_empty()).then_some(self.transcript);
+        observation.stderr_delta = (!self.stderr.is_empty()).then_some(self.stderr);
+        observation
+    }
+}
@@ -0,0 +1,115 @@
+//! Scenario watchers: conditions checked against every observation.
+
+use super::Session;
+use crate::model::{Observation, Watcher};
+use crate::predicate::Predicate;
+use crate::runner::RunnerError;
+use crate::util::convert_exit_status;
+use std::time::Duration;
+
+/// A validated watcher with its condition parsed.
+pub(super) struct ArmedWatcher {
+    watcher: Watcher,
+    predicate: Predicate,
+}
+
+/// Check that every watcher has a unique, non-empty name and a well-formed
+/// predicate condition.
+///
+/// # Errors
+/// Returns `E_PROTOCOL` naming the offending watcher.
+pub fn validate_watchers(watchers: &[Watcher]) -> Result<(), RunnerError> {
+    arm(watchers).map(|_| ())
+}
+
+fn arm(watchers: &[Watcher]) -> Result<Vec<ArmedWatcher>, RunnerError> {
+    let mut armed: Vec<ArmedWatcher> = Vec::with_capacity(watchers.len());
+    for watcher in watchers {
+        if watcher.name.trim().is_empty() {
+            return Err(RunnerError::protocol(
+                "E_PROTOCOL",
+                "watcher name must not be empty",
+                serde_json::json!({ "condition": watcher.condition }),
+            ));
+        }
+        if armed.iter().any(|other| other.watcher.name == watcher.name) {
+            return Err(RunnerError::protocol(
+                "E_PROTOCOL",
+                format!("duplicate watcher name '{}'", watcher.name),
+                serde_json::json!({ "watcher": watcher.name }),
+            ));
+        }
+        let predicate = Predicate::parse(
+            &watcher.condition.condition_type,
+            &watcher.condition.payload,
+        )?;
+        armed.push(ArmedWatcher {
+            watcher: watcher.clone(),
+            predicate,
+        });
+    }
+    Ok(armed)
+}
+
+impl Session {
+    /// Check `watchers` against every subsequent observation.
+    ///
+    /// The first watcher whose condition holds makes that
+    /// [`observe`](Self::observe) fail with `E_ASSERTION_FAILED` (context:
+    /// watcher name, condition, and the triggering snapshot) and disarms all
+    /// watchers; the triggering observation is kept for
+    /// [`take_watcher_trip`](Self::take_watcher_trip). Replaces any watchers
+    /// armed earlier.
+    ///
+    /// # Errors
+    /// Returns `E_PROTOCOL` if a watcher is invalid (see [`validate_watchers`]).
+    pub fn set_watchers(&mut self, watchers: &[Watcher]) -> Result<(), RunnerError> {
+        self.watchers = arm(watchers)?;
+        self.watcher_trip = None;
+        Ok(())
+    }
+
+    /// Take the observation that triggered a watcher, if one has.
+    pub fn take_watcher_trip(&mut self) -> Option<Observation> {
+        self.watcher_trip.take()
+    }
+
+    pub(super) fn check_watchers(
+        &mut self,
+        observation: Observation,
+    ) -> Result<Observation, RunnerError> {
+        if self.watchers.is_empty() {
+            return Ok(observation);
+        }
+        let exit_status = if self
+            .watchers
+            .iter()
+            .any(|armed| armed.predicate.needs_exit_status())
+        {
+            self.wait_for_exit(Duration::ZERO)?
+                .map(|status| convert_exit_status(status, false))
+        } else {
+            None
+        };
+        let mut tripped = None;
+        for armed in &self.watchers {
+            if self.predicate_holds(&armed.predicate, &observation, exit_status.as_ref())? {
+                tripped = Some(armed.watcher.clone());
+                break;
+            }
+        }
+        let Some(watcher) = tripped else {
+            return Ok(observation);
+        };
+        self.watchers.clear();
+        let error = RunnerError::assertion_failed(
+            format!("watcher '{}' triggered", watcher.name),
+            serde_json::json!({
+                "watcher": watcher.name,
+                "condition": watcher.condition,
+            }),
+        );
+        self.watcher_trip = Some(observation);
+        Err(error)
+    }
+}
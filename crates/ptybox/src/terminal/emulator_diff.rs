@@ -0,0 +1,247 @@
+//! Second emulator backend for fidelity checks (feature `emulator-diff`).
+//!
+//! With [`Terminal::enable_emulator_diff`](super::Terminal::enable_emulator_diff)
+//! every chunk of PTY output the terminal processes is also fed to a second,
+//! independent backend (`alacritty_terminal`), and the visible screens (row
+//! text without trailing spaces, cursor, alternate screen) are compared after
+//! each chunk and each resize. Each time they go from agreeing to differing a
+//! divergence point is recorded with the byte range of the chunk, its escaped
+//! bytes, and the rows that differ; the first snapshot taken while they differ
+//! is recorded too. Only the primary backend feeds snapshots and assertions.
+//!
+//! Both backends reflow lines on resize, but not identically, so a
+//! divergence right after a resize may be reflow rather than parsing.
+
+use crate::model::{
+    Cursor, EmulatorDiffReport, EmulatorDiffSnapshot, EmulatorDiffTrigger, EmulatorDivergence,
+    EmulatorRowDiff, SnapshotId, EMULATOR_DIFF_VERSION,
+};
+use alacritty_terminal::event::VoidListener;
+use alacritty_terminal::grid::Dimensions;
+use alacritty_terminal::term::cell::Flags;
+use alacritty_terminal::term::{Config, Term, TermMode};
+use alacritty_terminal::vte::ansi::Processor;
+use std::cell::Cell;
+
+/// Backend behind snapshots and assertions.
+pub const PRIMARY_BACKEND: &str = "vt100 0.15";
+/// Backend the primary is compared with.
+pub const SECONDARY_BACKEND: &str = "alacritty_terminal 0.25";
+
+/// Divergence points kept in a report.
+const MAX_DIVERGENCE_POINTS: usize = 16;
+/// Differing rows kept per divergence point.
+const MAX_ROW_DIFFS: usize = 8;
+/// Chunk bytes kept (escaped) per divergence point.
+const MAX_CHUNK_EXCERPT: usize = 256;
+
+/// The secondary backend and what its comparisons found.
+pub(crate) struct EmulatorDiff {
+    secondary: Term<VoidListener>,
+    parser: Processor,
+    bytes_processed: u64,
+    comparisons: u64,
+    diverged: bool,
+    divergences: u64,
+    points: Vec<EmulatorDivergence>,
+    /// Set from [`EmulatorDiff::note_snapshot`], which only has `&self`.
+    first_differing_snapshot: Cell<Option<EmulatorDiffSnapshot>>,
+}
+
+/// What a backend shows: rows, cursor, and whether the alternate screen is
+/// active.
+struct View {
+    lines: Vec<String>,
+    cursor: Cursor,
+    alternate_screen: bool,
+}
+
+impl EmulatorDiff {
+    pub(crate) fn new(rows: u16, cols: u16) -> Self {
+        Self {
+            secondary: Term::new(
+                Config {
+                    scrolling_history: 0,
+                    ..Config::default()
+                },
+                &Size { rows, cols },
+                VoidListener,
+            ),
+            parser: Processor::new(),
+            bytes_processed: 0,
+            comparisons: 0,
+            diverged: false,
+            divergences: 0,
+            points: Vec::new(),
+            first_differing_snapshot: Cell::new(None),
+        }
+    }
+
+    /// Feed `chunk`, already processed by the primary backend, to the
+    /// secondary one and compare the screens.
+    pub(crate) fn process(&mut self, primary: &vt100::Screen, chunk: &[u8]) {
+        let chunk_start = self.bytes_processed;
+        self.parser.advance(&mut self.secondary, chunk);
+        self.bytes_processed = chunk_start.saturating_add(chunk.len() as u64);
+        self.compare(primary, EmulatorDiffTrigger::Output, chunk_start, chunk);
+    }
+
+    /// Resize the secondary backend after the primary one and compare.
+    pub(crate) fn resize(&mut self, primary: &vt100::Screen) {
+        let (rows, cols) = primary.size();
+        self.secondary.resize(Size { rows, cols });
+        self.compare(
+            primary,
+            EmulatorDiffTrigger::Resize,
+            self.bytes_processed,
+            &[],
+        );
+    }
+
+    /// Record `snapshot_id` if it is the first snapshot taken while the
+    /// screens differ.
+    pub(crate) fn note_snapshot(&self, snapshot_id: SnapshotId) {
+        if self.diverged && self.first_differing_snapshot.get().is_none() {
+            self.first_differing_snapshot
+                .set(Some(EmulatorDiffSnapshot {
+                    snapshot_id,
+                    byte_offset: self.bytes_processed,
+                }));
+        }
+    }
+
+    pub(crate) fn report(&self) -> EmulatorDiffReport {
+        EmulatorDiffReport {
+            emulator_diff_version: EMULATOR_DIFF_VERSION,
+            primary: PRIMARY_BACKEND.to_string(),
+            secondary: SECONDARY_BACKEND.to_string(),
+            bytes_processed: self.bytes_processed,
+            comparisons: self.comparisons,
+            divergences: self.divergences,
+            points: self.points.clone(),
+            first_differing_snapshot: self.first_differing_snapshot.get(),
+            diverged_at_end: self.diverged,
+        }
+    }
+
+    fn compare(
+        &mut self,
+        primary: &vt100::Screen,
+        trigger: EmulatorDiffTrigger,
+        chunk_start: u64,
+        chunk: &[u8],
+    ) {
+        self.comparisons += 1;
+        let (_, cols) = primary.size();
+        let primary = View {
+            lines: primary
+                .rows(0, cols)
+                .map(|line| line.trim_end().to_string())
+                .collect(),
+            cursor: cursor(primary.cursor_position(), !primary.hide_cursor()),
+            alternate_screen: primary.alternate_screen(),
+        };
+        let secondary = secondary_screen(&self.secondary);
+        let agree = primary.lines == secondary.lines
+            && primary.cursor == secondary.cursor
+            && primary.alternate_screen == secondary.alternate_screen;
+        let started = !agree && !self.diverged;
+        self.diverged = !agree;
+        if !started {
+            return;
+        }
+        self.divergences += 1;
+        if self.points.len() >= MAX_DIVERGENCE_POINTS {
+            return;
+        }
+        let rows = primary
+            .lines
+            .iter()
+            .zip(&secondary.lines)
+            .zip(0u16..)
+            .filter(|((primary, secondary), _)| primary != secondary)
+            .take(MAX_ROW_DIFFS)
+            .map(|((primary, secondary), row)| EmulatorRowDiff {
+                row,
+                primary: primary.clone(),
+                secondary: secondary.clone(),
+            })
+            .collect();
+        self.points.push(EmulatorDivergence {
+            trigger,
+            chunk_start,
+            byte_offset: self.bytes_processed,
+            chunk: chunk
+                .iter()
+                .take(MAX_CHUNK_EXCERPT)
+                .flat_map(|byte| std::ascii::escape_default(*byte))
+                .map(char::from)
+                .collect(),
+            rows,
+            primary_cursor: primary.cursor,
+            secondary_cursor: secondary.cursor,
+            primary_alternate_screen: primary.alternate_screen,
+            secondary_alternate_screen: secondary.alternate_screen,
+        });
+    }
+}
+
+const fn cursor((row, col): (u16, u16), visible: bool) -> Cursor {
+    Cursor { row, col, visible }
+}
+
+/// What the secondary backend shows, in the primary's terms: wide
+/// characters once, zero-width characters appended, trailing spaces
+/// trimmed.
+fn secondary_screen(term: &Term<VoidListener>) -> View {
+    let grid = term.grid();
+    let mut lines = vec![String::new(); grid.screen_lines()];
+    for indexed in grid.display_iter() {
+        let spacer = Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER;
+        if indexed.cell.flags.intersects(spacer) {
+            continue;
+        }
+        let line = usize::try_from(indexed.point.line.0)
+            .ok()
+            .and_then(|line| lines.get_mut(line));
+        if let Some(line) = line {
+            line.push(indexed.cell.c);
+            line.extend(indexed.cell.zerowidth().unwrap_or_default());
+        }
+    }
+    for line in &mut lines {
+        line.truncate(line.trim_end().len());
+    }
+    let point = grid.cursor.point;
+    View {
+        lines,
+        cursor: cursor(
+            (
+                u16::try_from(point.line.0).unwrap_or(0),
+                u16::try_from(point.column.0).unwrap_or(u16::MAX),
+            ),
+            term.mode().contains(TermMode::SHOW_CURSOR),
+        ),
+        alternate_screen: term.mode().contains(TermMode::ALT_SCREEN),
+    }
+}
+
+/// Screen size in the secondary backend's terms.
+struct Size {
+    rows: u16,
+    cols: u16,
+}
+
+impl Dimensions for Size {
+    fn total_lines(&self) -> usize {
+        self.screen_lines()
+    }
+
+    fn screen_lines(&self) -> usize {
+        usize::from(self.rows)
+    }
+
+    fn columns(&self) -> usize {
+        usize::from(self.cols)
+    }
+}
@@ -0,0 +1,207 @@
+//! PNG rendering of screen snapshots (`render` feature).
+//!
+//! Each cell is drawn as an 8x13 pixel glyph from a built-in bitmap font
+//! ([`CELL_WIDTH`] x [`CELL_HEIGHT`]). When the snapshot carries cells, their
+//! colors, bold, underline, and inverse attributes are applied; otherwise
+//! `lines` are drawn in the default colors. A visible cursor is drawn as an
+//! inverted cell. Colors follow the xterm palette.
+
+use super::font::{self, GLYPH_HEIGHT, GLYPH_WIDTH};
+use crate::model::{Color, ScreenSnapshot, Style};
+use crate::runner::{ErrorCode, RunnerError, RunnerResult};
+
+/// Width of one cell in pixels.
+pub const CELL_WIDTH: u32 = GLYPH_WIDTH;
+/// Height of one cell in pixels.
+pub const CELL_HEIGHT: u32 = GLYPH_HEIGHT;
+
+/// MIME type of [`RenderedImage::png`].
+pub const PNG_MIME_TYPE: &str = "image/png";
+
+const DEFAULT_FG: Rgb = [0xe5, 0xe5, 0xe5];
+const DEFAULT_BG: Rgb = [0x00, 0x00, 0x00];
+/// Glyph row drawn for underlined cells (just below the baseline).
+const UNDERLINE_ROW: u32 = 11;
+
+type Rgb = [u8; 3];
+
+/// Snapshot rendered as an encoded PNG.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RenderedImage {
+    /// Image width in pixels (`cols * CELL_WIDTH`).
+    pub width: u32,
+    /// Image height in pixels (`rows * CELL_HEIGHT`).
+    pub height: u32,
+    /// PNG-encoded RGB image.
+    pub png: Vec<u8>,
+}
+
+/// Render `snapshot` as a PNG.
+///
+/// # Errors
+/// `E_IO` if PNG encoding fails.
+pub fn render_png(snapshot: &ScreenSnapshot) -> RunnerResult<RenderedImage> {
+    let width = u32::from(snapshot.cols) * CELL_WIDTH;
+    let height = u32::from(snapshot.rows) * CELL_HEIGHT;
+    let mut canvas = Canvas::new(width, height);
+    for row in 0..snapshot.rows {
+        for col in 0..snapshot.cols {
+            let mut paint = cell_paint(snapshot, row, col);
+            let cursor = &snapshot.cursor;
+            if cursor.visible && cursor.row == row && cursor.col == col {
+                std::mem::swap(&mut paint.fg, &mut paint.bg);
+            }
+            canvas.draw_cell(u32::from(row), u32::from(col), &paint);
+        }
+    }
+    let png = encode_png(width, height, &canvas.pixels).map_err(|err| {
+        RunnerError::with_context(
+            ErrorCode::Io,
+            "failed to encode snapshot image",
+            serde_json::json!({ "error": err.to_string() }),
+        )
+    })?;
+    Ok(RenderedImage { width, height, png })
+}
+
+/// How one cell is drawn.
+struct Paint {
+    ch: char,
+    fg: Rgb,
+    bg: Rgb,
+    bold: bool,
+    underline: bool,
+}
+
+fn cell_paint(snapshot: &ScreenSnapshot, row: u16, col: u16) -> Paint {
+    let cell = snapshot
+        .cells
+        .as_ref()
+        .and_then(|rows| rows.get(usize::from(row)))
+        .and_then(|cells| cells.get(usize::from(col)));
+    let Some(cell) = cell else {
+        let ch = snapshot
+            .lines
+            .get(usize::from(row))
+            .and_then(|line| line.chars().nth(usize::from(col)))
+            .unwrap_or(' ');
+        return Paint {
+            ch,
+            fg: DEFAULT_FG,
+            bg: DEFAULT_BG,
+            bold: false,
+            underline: false,
+        };
+    };
+    let Style {
+        fg,
+        bg,
+        bold,
+        underline,
+        inverse,
+        ..
+    } = &cell.style;
+    let (mut fg, mut bg) = (rgb(fg, DEFAULT_FG), rgb(bg, DEFAULT_BG));
+    if *inverse {
+        std::mem::swap(&mut fg, &mut bg);
+    }
+    Paint {
+        ch: cell.ch.chars().next().unwrap_or(' '),
+        fg,
+        bg,
+        bold: *bold,
+        underline: *underline,
+    }
+}
+
+fn rgb(color: &Color, default: Rgb) -> Rgb {
+    match color {
+        Color::Default => default,
+        Color::Ansi16(index) | Color::Ansi256(index) => indexed(*index),
+        Color::Rgb { r, g, b } => [*r, *g, *b],
+    }
+}
+
+/// xterm 256-color palette entry.
+fn indexed(index: u8) -> Rgb {
+    const ANSI: [Rgb; 16] = [
+        [0x00, 0x00, 0x00],
+        [0xcd, 0x00, 0x00],
+        [0x00, 0xcd, 0x00],
+        [0xcd, 0xcd, 0x00],
+        [0x00, 0x00, 0xee],
+        [0xcd, 0x00, 0xcd],
+        [0x00, 0xcd, 0xcd],
+        [0xe5, 0xe5, 0xe5],
+        [0x7f, 0x7f, 0x7f],
+        [0xff, 0x00, 0x00],
+        [0x00, 0xff, 0x00],
+        [0xff, 0xff, 0x00],
+        [0x5c, 0x5c, 0xff],
+        [0xff, 0x00, 0xff],
+        [0x00, 0xff, 0xff],
+        [0xff, 0xff, 0xff],
+    ];
+    let level = |step: u8| if step == 0 { 0 } else { 55 + step * 40 };
+    match index {
+        0..=15 => ANSI.get(usize::from(index)).copied().unwrap_or(DEFAULT_FG),
+        16..=231 => {
+            let cube = index - 16;
+            [level(cube / 36), level(cube / 6 % 6), level(cube % 6)]
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            [gray, gray, gray]
+        }
+    }
+}
+
+/// RGB pixel buffer, row-major.
+struct Canvas {
+    width: u32,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32) -> Self {
+        let len = width as usize * height as usize;
+        Self {
+            width,
+            pixels: DEFAULT_BG.repeat(len),
+        }
+    }
+
+    fn draw_cell(&mut self, row: u32, col: u32, paint: &Paint) {
+        let glyph = font::glyph(paint.ch);
+        let (left, top) = (col * CELL_WIDTH, row * CELL_HEIGHT);
+        for (y, bits) in (0..CELL_HEIGHT).zip(glyph.iter().copied()) {
+            let mut bits = if paint.bold { bits | (bits >> 1) } else { bits };
+            if paint.underline && y == UNDERLINE_ROW {
+                bits = 0xff;
+            }
+            for x in 0..CELL_WIDTH {
+                let on = bits & (0x80 >> x) != 0;
+                self.set(left + x, top + y, if on { paint.fg } else { paint.bg });
+            }
+        }
+    }
+
+    fn set(&mut self, x: u32, y: u32, color: Rgb) {
+        let offset = (y as usize * self.width as usize + x as usize) * 3;
+        if let Some(pixel) = self.pixels.get_mut(offset..offset + 3) {
+            pixel.copy_from_slice(&color);
+        }
+    }
+}
+
+fn encode_png(width: u32, height: u32, pixels: &[u8]) -> Result<Vec<u8>, png::EncodingError> {
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_compression(png::Compression::Best);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(pixels)?;
+    writer.finish()?;
+    Ok(png)
+}
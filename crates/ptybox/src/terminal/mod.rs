@@ -13,10 +13,18 @@
 //! # Key Operations
 //!
 //! - [`Terminal::new`] - Create a new terminal with specified dimensions
-//! - [`Terminal::resize`] - Change terminal dimensions
+//! - [`Terminal::resize`] - Change terminal dimensions, truncating or rewrapping
+//!   lines as [`Terminal::with_resize`] configures (see `reflow`)
 //! - [`Terminal::process_bytes`] - Feed raw PTY output through the emulator
+//! - [`Terminal::take_replies`] - Drain answers to XTWINOPS size queries
+//! - [`Terminal::mark_output`] / [`Terminal::output_since_mark`] - Bells and raw
+//!   bytes processed since the last input (`bell`, `control_sequence` predicates)
 //! - [`Terminal::snapshot`] - Capture current screen state without cell styling
 //! - [`Terminal::snapshot_with_cells`] - Capture screen state with optional cell styling
+//! - [`Terminal::widgets`] - Detect boxes, selected rows, and y/n prompts (see [`widgets`])
+//! - `Terminal::enable_emulator_diff` / `Terminal::emulator_diff` - Compare with a
+//!   second emulator backend (`emulator-diff` feature, see `emulator_diff`)
+//! - `image::render_png` - Render a snapshot as a PNG (`render` feature)
 //!
 //! # Example
 //!
@@ -26,7 +34,7 @@
 //!
 //! # fn example() -> Result<(), ptybox::runner::RunnerError> {
 //! // Create a terminal with default size
-//! let mut terminal = Terminal::new(TerminalSize { rows: 24, cols: 80 });
+//! let mut terminal = Terminal::new(TerminalSize::new(24, 80));
 //!
 //! // Process some terminal output (including ANSI escape sequences)
 //! terminal.process_bytes(b"Hello, \x1b[1mBold\x1b[0m World!\r\n");
@@ -46,14 +54,52 @@
 //! - 16-color, 256-color, and true color (RGB) support
 //! - Alternate screen buffer detection
 //! - Wide character (CJK) handling
+//!
+//! Size queries (`CSI 14/16/18/19 t`) are answered from the configured
+//! [`TerminalSize`]; the session writes the replies back to the PTY.
 
-use crate::model::{Cell, Color, Cursor, ScreenSnapshot, SnapshotId, Style, TerminalSize};
+#[cfg(feature = "emulator-diff")]
+use crate::model::EmulatorDiffReport;
+use crate::model::{
+    Cell, Color, Cursor, ResizeConfig, ScreenSnapshot, SnapshotId, Style, TerminalSize, Widget,
+};
+use crate::predicate::RawOutput;
 use crate::runner::RunnerError;
 use vt100::Parser;
 
+#[cfg(feature = "emulator-diff")]
+pub mod emulator_diff;
+#[cfg(feature = "render")]
+mod font;
+#[cfg(feature = "render")]
+pub mod image;
+mod reflow;
+pub mod widgets;
+mod winops;
+
+use winops::WindowOpsScanner;
+
+/// Raw bytes kept for [`Terminal::output_since_mark`]; older bytes are dropped.
+pub const MAX_RAW_OUTPUT_WINDOW: usize = 64 * 1024;
+
 /// Terminal emulator wrapper using vt100.
 pub struct Terminal {
     parser: Parser,
+    size: TerminalSize,
+    winops: WindowOpsScanner,
+    replies: Vec<u8>,
+    /// Audible bell count at the last [`Terminal::mark_output`].
+    bells_at_mark: usize,
+    /// Raw bytes processed since the last mark, at most [`MAX_RAW_OUTPUT_WINDOW`].
+    raw_since_mark: Vec<u8>,
+    resize: ResizeConfig,
+    /// Audible bells counted by parsers replaced on resize.
+    bells_before_resize: usize,
+    /// Include cell styles in [`Terminal::snapshot`].
+    capture_attributes: bool,
+    /// Second backend fed the same output, when enabled.
+    #[cfg(feature = "emulator-diff")]
+    emulator_diff: Option<Box<emulator_diff::EmulatorDiff>>,
 }
 
 impl Terminal {
@@ -61,22 +107,114 @@ impl Terminal {
     pub fn new(size: TerminalSize) -> Self {
         Self {
             parser: Parser::new(size.rows, size.cols, 0),
+            size,
+            winops: WindowOpsScanner::default(),
+            replies: Vec::new(),
+            bells_at_mark: 0,
+            raw_since_mark: Vec::new(),
+            resize: ResizeConfig::default(),
+            bells_before_resize: 0,
+            capture_attributes: false,
+            #[cfg(feature = "emulator-diff")]
+            emulator_diff: None,
         }
     }
 
+    /// Use `resize` for later [`Terminal::resize`] calls. Call before
+    /// processing output: the scrollback is set up empty.
+    #[must_use]
+    pub fn with_resize(mut self, resize: ResizeConfig) -> Self {
+        let scrollback = usize::try_from(resize.scrollback_lines).unwrap_or(usize::MAX);
+        self.parser = Parser::new(self.size.rows, self.size.cols, scrollback);
+        self.resize = resize;
+        self
+    }
+
     /// Resize the terminal.
+    ///
+    /// Lines on the primary screen are truncated or rewrapped per
+    /// [`ResizeConfig::reflow`]. The alternate screen is resized in place,
+    /// as full-screen programs redraw it on `SIGWINCH`; while it is active the
+    /// primary screen underneath is resized in place too.
     pub fn resize(&mut self, size: TerminalSize) {
-        self.parser.set_size(size.rows, size.cols);
+        let current = self.parser.screen().size();
+        let reflow = current != (size.rows, size.cols)
+            && size.rows > 0
+            && size.cols > 0
+            && !self.parser.screen().alternate_screen();
+        if reflow {
+            self.bells_before_resize += self.parser.screen().audible_bell_count();
+            self.parser = reflow::rebuild(&mut self.parser, &size, self.resize);
+        } else {
+            self.parser.set_size(size.rows, size.cols);
+        }
+        self.size = size;
+        #[cfg(feature = "emulator-diff")]
+        if let Some(diff) = self.emulator_diff.as_mut() {
+            diff.resize(self.parser.screen());
+        }
+    }
+
+    /// Current size, including pixel dimensions.
+    pub fn size(&self) -> &TerminalSize {
+        &self.size
     }
 
     /// Process incoming bytes.
     pub fn process_bytes(&mut self, bytes: &[u8]) {
+        self.winops.scan(bytes, &self.size, &mut self.replies);
         self.parser.process(bytes);
+        #[cfg(feature = "emulator-diff")]
+        if let Some(diff) = self.emulator_diff.as_mut() {
+            diff.process(self.parser.screen(), bytes);
+        }
+        self.raw_since_mark.extend_from_slice(bytes);
+        let excess = self
+            .raw_since_mark
+            .len()
+            .saturating_sub(MAX_RAW_OUTPUT_WINDOW);
+        self.raw_since_mark.drain(..excess);
     }
 
-    /// Take a snapshot of the terminal screen without cell styling.
+    /// Start a new window for [`Terminal::output_since_mark`]; the session
+    /// calls this whenever it sends input.
+    pub fn mark_output(&mut self) {
+        self.bells_at_mark = self.audible_bells();
+        self.raw_since_mark.clear();
+    }
+
+    /// Audible bells (BEL outside OSC and DCS strings) and raw bytes processed
+    /// since the last [`Terminal::mark_output`]. Only the last
+    /// [`MAX_RAW_OUTPUT_WINDOW`] bytes are kept.
+    pub fn output_since_mark(&self) -> RawOutput<'_> {
+        let bells = self.audible_bells().saturating_sub(self.bells_at_mark);
+        RawOutput {
+            bells: u64::try_from(bells).unwrap_or(u64::MAX),
+            bytes: &self.raw_since_mark,
+        }
+    }
+
+    fn audible_bells(&self) -> usize {
+        self.bells_before_resize + self.parser.screen().audible_bell_count()
+    }
+
+    /// Drain replies to size queries seen by [`Terminal::process_bytes`].
+    ///
+    /// The bytes are meant to be written back to the application as input.
+    pub fn take_replies(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.replies)
+    }
+
+    /// Include per-cell colors and attributes in later [`Terminal::snapshot`]
+    /// calls (off by default).
+    pub fn set_capture_attributes(&mut self, capture: bool) {
+        self.capture_attributes = capture;
+    }
+
+    /// Take a snapshot of the terminal screen, with cell styling only when
+    /// [`Terminal::set_capture_attributes`] turned it on.
     pub fn snapshot(&self) -> Result<ScreenSnapshot, RunnerError> {
-        self.snapshot_with_cells(false)
+        self.snapshot_with_cells(self.capture_attributes)
     }
 
     /// Take a snapshot of the terminal screen, optionally including cell styling.
@@ -91,7 +229,8 @@ impl Terminal {
         );
         let (row, col) = screen.cursor_position();
         debug_assert!(row < rows, "cursor row must be within terminal bounds");
-        debug_assert!(col < cols, "cursor col must be within terminal bounds");
+        // A full last column leaves the cursor pending wrap at `col == cols`.
+        debug_assert!(col <= cols, "cursor col must be within terminal bounds");
         let cursor = Cursor {
             row,
             col,
@@ -104,9 +243,15 @@ impl Terminal {
             None
         };
 
+        let snapshot_id = SnapshotId::new();
+        #[cfg(feature = "emulator-diff")]
+        if let Some(diff) = &self.emulator_diff {
+            diff.note_snapshot(snapshot_id);
+        }
+
         Ok(ScreenSnapshot {
             snapshot_version: 1,
-            snapshot_id: SnapshotId::new(),
+            snapshot_id,
             rows,
             cols,
             cursor,
@@ -115,6 +260,30 @@ impl Terminal {
             cells,
         })
     }
+
+    /// Detect UI widgets on the current screen, including inverse-video
+    /// selections.
+    pub fn widgets(&self) -> Vec<Widget> {
+        widgets::detect_on_screen(self.parser.screen())
+    }
+
+    /// Feed later output and resizes to a second emulator backend as well
+    /// and compare the screens (see [`emulator_diff`]). Call before
+    /// processing output.
+    #[cfg(feature = "emulator-diff")]
+    pub fn enable_emulator_diff(&mut self) {
+        self.emulator_diff = Some(Box::new(emulator_diff::EmulatorDiff::new(
+            self.size.rows,
+            self.size.cols,
+        )));
+    }
+
+    /// What the second emulator backend's comparisons found so far, when
+    /// [`Terminal::enable_emulator_diff`] was called.
+    #[cfg(feature = "emulator-diff")]
+    pub fn emulator_diff(&self) -> Option<EmulatorDiffReport> {
+        self.emulator_diff.as_ref().map(|diff| diff.report())
+    }
 }
 
 /// Extract cell data from the screen using iterator chains.
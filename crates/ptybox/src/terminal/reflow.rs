@@ -0,0 +1,334 @@
+//! Laying out the primary screen for a new size ([`ResizeReflow`]).
+//!
+//! `vt100` resizes its grid in place: rows are cut at the bottom and the
+//! cursor is clamped, which no real terminal does. [`rebuild`] reads the
+//! primary screen and its scrollback as rows of cells, lays them out for the
+//! new size, and replays them into a fresh parser:
+//!
+//! - `truncate` (xterm): rows keep their cells, cut at the new width. When the
+//!   screen gets shorter, lines scroll off the top until the cursor row fits
+//!   and the rows below it are dropped; when it gets taller, lines come back
+//!   from scrollback.
+//! - `rewrap` (VTE, kitty): soft-wrapped rows are joined and wrapped again at
+//!   the new width, scrollback included, and the cursor moves with the cell
+//!   it was on. Blank rows below the cursor are dropped and the rest fills the
+//!   screen from the bottom, pulling lines back from scrollback.
+//!
+//! Input modes, the title, cursor visibility, and the current attributes carry
+//! over. The scroll region is reset, as xterm does on resize.
+
+use crate::model::{ResizeConfig, ResizeReflow, TerminalSize};
+use std::fmt::Write as _;
+use vt100::{Cell, Color, Parser, Screen};
+
+/// One screen row: its cells without wide-character continuations, and
+/// whether it soft-wraps into the next row.
+#[derive(Clone, Default)]
+struct Row {
+    cells: Vec<Cell>,
+    wrapped: bool,
+}
+
+/// Rows to replay, the first one to show, and the cursor as (row, column).
+struct Layout {
+    rows: Vec<Row>,
+    top: usize,
+    cursor: (usize, u16),
+}
+
+/// A parser of `size` holding the primary screen of `parser`, laid out as
+/// `config.reflow` says, with `config.scrollback_lines` of scrollback.
+pub(super) fn rebuild(parser: &mut Parser, size: &TerminalSize, config: ResizeConfig) -> Parser {
+    let (rows, history) = read_rows(parser);
+    let screen = parser.screen();
+    let (row, col) = screen.cursor_position();
+    let cursor = (history + usize::from(row), col);
+    let layout = match config.reflow {
+        ResizeReflow::Truncate => truncate(rows, history, cursor, screen.size(), size),
+        ResizeReflow::Rewrap => rewrap(rows, cursor, size),
+    };
+
+    let scrollback = usize::try_from(config.scrollback_lines).unwrap_or(usize::MAX);
+    let mut rebuilt = Parser::new(size.rows, size.cols, scrollback);
+    let (bytes, screen_top) = replay(&layout, size);
+    rebuilt.process(&bytes);
+    rebuilt.process(&restore_state(screen, &layout, screen_top));
+    rebuilt
+}
+
+/// Scrollback rows (oldest first) followed by the screen rows, and the number
+/// of scrollback rows.
+fn read_rows(parser: &mut Parser) -> (Vec<Row>, usize) {
+    parser.set_scrollback(usize::MAX);
+    let history = parser.screen().scrollback();
+    let mut rows = Vec::with_capacity(history);
+    for offset in (1..=history).rev() {
+        parser.set_scrollback(offset);
+        rows.push(read_row(parser.screen(), 0));
+    }
+    parser.set_scrollback(0);
+    let screen = parser.screen();
+    let (height, _) = screen.size();
+    rows.extend((0..height).map(|row| read_row(screen, row)));
+    (rows, history)
+}
+
+fn read_row(screen: &Screen, row: u16) -> Row {
+    let (_, cols) = screen.size();
+    let mut cells: Vec<Cell> = (0..cols)
+        .filter_map(|col| screen.cell(row, col))
+        .filter(|cell| !cell.is_wide_continuation())
+        .cloned()
+        .collect();
+    let wrapped = screen.row_wrapped(row);
+    if !wrapped {
+        trim(&mut cells);
+    }
+    Row { cells, wrapped }
+}
+
+fn truncate(
+    rows: Vec<Row>,
+    history: usize,
+    cursor: (usize, u16),
+    (old_rows, old_cols): (u16, u16),
+    size: &TerminalSize,
+) -> Layout {
+    let rows = if old_cols == size.cols {
+        rows
+    } else {
+        rows.into_iter()
+            .map(|mut row| {
+                cut(&mut row.cells, size.cols);
+                trim(&mut row.cells);
+                Row {
+                    cells: row.cells,
+                    wrapped: false,
+                }
+            })
+            .collect()
+    };
+    let top = if size.rows >= old_rows {
+        history.saturating_sub(usize::from(size.rows - old_rows))
+    } else {
+        let screen_row = cursor.0.saturating_sub(history);
+        history + (screen_row + 1).saturating_sub(usize::from(size.rows))
+    };
+    Layout {
+        rows,
+        top,
+        cursor: (cursor.0, cursor.1.min(size.cols.saturating_sub(1))),
+    }
+}
+
+fn rewrap(rows: Vec<Row>, cursor: (usize, u16), size: &TerminalSize) -> Layout {
+    let mut out: Vec<Row> = Vec::new();
+    let mut new_cursor = (0, 0);
+    let mut line: Vec<Cell> = Vec::new();
+    let mut line_cursor = None;
+    let count = rows.len();
+    for (index, row) in rows.into_iter().enumerate() {
+        if index == cursor.0 {
+            line_cursor = Some(columns(&line) + usize::from(cursor.1));
+        }
+        line.extend(row.cells);
+        if !row.wrapped || index + 1 == count {
+            trim(&mut line);
+            let (wrapped, at) = wrap(&line, size.cols, line_cursor.take());
+            if let Some((row, col)) = at {
+                new_cursor = (out.len() + row, col);
+            }
+            out.extend(wrapped);
+            line.clear();
+        }
+    }
+
+    let last_content = out
+        .iter()
+        .rposition(|row| !row.cells.is_empty())
+        .unwrap_or(0);
+    let end = last_content.max(new_cursor.0) + 1;
+    out.truncate(end);
+    Layout {
+        rows: out,
+        top: end.saturating_sub(usize::from(size.rows)).min(new_cursor.0),
+        cursor: new_cursor,
+    }
+}
+
+/// Split a line into rows of `width` columns the way `vt100` wraps output:
+/// a wide character that does not fit in the last column starts the next row,
+/// and only a row with its last column filled is marked wrapped. Returns the
+/// rows and where column `cursor` of the line lands.
+fn wrap(cells: &[Cell], width: u16, cursor: Option<usize>) -> (Vec<Row>, Option<(usize, u16)>) {
+    let mut rows = vec![Row::default()];
+    let mut col = 0u16;
+    let mut offset = 0usize;
+    let mut at = None;
+    for cell in cells {
+        let cell_width = cell_width(cell);
+        if col > 0 && col + cell_width > width {
+            if let Some(row) = rows.last_mut() {
+                row.wrapped = col == width;
+            }
+            rows.push(Row::default());
+            col = 0;
+        }
+        if let Some(target) = cursor.filter(|_| at.is_none()) {
+            if target < offset + usize::from(cell_width) {
+                let within = u16::try_from(target.saturating_sub(offset)).unwrap_or(0);
+                at = Some((rows.len() - 1, col + within));
+            }
+        }
+        if let Some(row) = rows.last_mut() {
+            row.cells.push(cell.clone());
+        }
+        col += cell_width;
+        offset += usize::from(cell_width);
+    }
+    if let Some(target) = cursor.filter(|_| at.is_none()) {
+        // Past the end of the line; a cursor past the last column starts the
+        // next row, where the next character would go.
+        let mut past = usize::from(col) + target.saturating_sub(offset);
+        if past >= usize::from(width) {
+            rows.push(Row::default());
+            past -= usize::from(width);
+        }
+        let col = u16::try_from(past)
+            .unwrap_or(u16::MAX)
+            .min(width.saturating_sub(1));
+        at = Some((rows.len() - 1, col));
+    }
+    (rows, at)
+}
+
+/// Bytes that draw the rows up to the bottom of the new screen, and the index
+/// of the row shown at the top of it.
+fn replay(layout: &Layout, size: &TerminalSize) -> (Vec<u8>, usize) {
+    let end = layout.rows.len().min(layout.top + usize::from(size.rows));
+    let mut out = String::new();
+    let mut pen = Pen::default();
+    let mut continues = true;
+    for row in layout.rows.iter().take(end) {
+        if !continues {
+            pen = Pen::default();
+            out.push_str("\x1b[m\r\n");
+        }
+        write_row(&mut out, row, &mut pen);
+        continues = row.wrapped;
+    }
+    (out.into_bytes(), end.saturating_sub(usize::from(size.rows)))
+}
+
+fn write_row(out: &mut String, row: &Row, pen: &mut Pen) {
+    for (index, cell) in row.cells.iter().enumerate() {
+        let cell_pen = Pen::of(cell);
+        if cell_pen != *pen {
+            cell_pen.write_sgr(out);
+            *pen = cell_pen;
+        }
+        if cell.has_contents() {
+            out.push_str(&cell.contents());
+        } else if row.wrapped && index + 1 == row.cells.len() {
+            // The row only wraps once its last column is written.
+            out.push(' ');
+        } else {
+            out.push_str("\x1b[X\x1b[C");
+        }
+    }
+}
+
+/// Cursor position, modes, title, and attributes of `screen` for the
+/// rebuilt parser.
+fn restore_state(screen: &Screen, layout: &Layout, screen_top: usize) -> Vec<u8> {
+    let row = u16::try_from(layout.cursor.0.saturating_sub(screen_top)).unwrap_or(u16::MAX);
+    let mut out = format!("\x1b[m\x1b[{};{}H", row + 1, layout.cursor.1 + 1).into_bytes();
+    out.extend(screen.input_mode_formatted());
+    out.extend(screen.title_formatted());
+    if screen.hide_cursor() {
+        out.extend_from_slice(b"\x1b[?25l");
+    }
+    out.extend(screen.attributes_formatted());
+    out
+}
+
+/// Drawing attributes of a cell.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+struct Pen {
+    fg: Color,
+    bg: Color,
+    bold: bool,
+    italic: bool,
+    underline: bool,
+    inverse: bool,
+}
+
+impl Pen {
+    fn of(cell: &Cell) -> Self {
+        Self {
+            fg: cell.fgcolor(),
+            bg: cell.bgcolor(),
+            bold: cell.bold(),
+            italic: cell.italic(),
+            underline: cell.underline(),
+            inverse: cell.inverse(),
+        }
+    }
+
+    fn write_sgr(&self, out: &mut String) {
+        out.push_str("\x1b[0");
+        for (on, code) in [
+            (self.bold, "1"),
+            (self.italic, "3"),
+            (self.underline, "4"),
+            (self.inverse, "7"),
+        ] {
+            if on {
+                out.push(';');
+                out.push_str(code);
+            }
+        }
+        for (color, base) in [(self.fg, 38), (self.bg, 48)] {
+            let _ = match color {
+                Color::Default => Ok(()),
+                Color::Idx(index) => write!(out, ";{base};5;{index}"),
+                Color::Rgb(r, g, b) => write!(out, ";{base};2;{r};{g};{b}"),
+            };
+        }
+        out.push('m');
+    }
+}
+
+fn cell_width(cell: &Cell) -> u16 {
+    if cell.is_wide() {
+        2
+    } else {
+        1
+    }
+}
+
+fn columns(cells: &[Cell]) -> usize {
+    cells.iter().map(|cell| usize::from(cell_width(cell))).sum()
+}
+
+/// Drop cells past `width` columns.
+fn cut(cells: &mut Vec<Cell>, width: u16) {
+    let mut used = 0u16;
+    let keep = cells
+        .iter()
+        .take_while(|cell| {
+            used += cell_width(cell);
+            used <= width
+        })
+        .count();
+    cells.truncate(keep);
+}
+
+/// Drop trailing cells that show nothing.
+fn trim(cells: &mut Vec<Cell>) {
+    while cells.last().is_some_and(|cell| {
+        !cell.has_contents() && cell.bgcolor() == Color::Default && !cell.inverse()
+    }) {
+        cells.pop();
+    }
+}
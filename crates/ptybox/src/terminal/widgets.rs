@@ -0,0 +1,256 @@
+//! Heuristic detection of common TUI widgets.
+//!
+//! Recognizes three patterns and reports each as a [`Widget`] region:
+//! - [`WidgetKind::Box`]: a rectangle of box-drawing (`┌─┐│└┘`, rounded,
+//!   double, heavy) or ASCII (`+-|`) borders, with the text set into the top
+//!   border (`┌─┤ Title ├─┐`) as its title
+//! - [`WidgetKind::Selection`]: a run of inverse-video cells, the usual way
+//!   list and menu rows are highlighted
+//! - [`WidgetKind::Prompt`]: a line containing `[y/n]`, `(y/n)`, `[yes/no]`,
+//!   or `(yes/no)` in any case
+//!
+//! Widgets are listed by kind (boxes, then selections, then prompts) and in
+//! reading order within a kind. Nested boxes are all reported. The detector
+//! only looks at one screen, so anything that merely looks like a widget
+//! (an ASCII table, a highlighted status bar) is reported too.
+
+use crate::model::{ScreenSnapshot, Widget, WidgetKind};
+
+const TOP_LEFT: [char; 5] = ['┌', '╭', '╔', '┏', '+'];
+const TOP_RIGHT: [char; 5] = ['┐', '╮', '╗', '┓', '+'];
+const BOTTOM_LEFT: [char; 5] = ['└', '╰', '╚', '┗', '+'];
+const BOTTOM_RIGHT: [char; 5] = ['┘', '╯', '╝', '┛', '+'];
+const HORIZONTAL: [char; 10] = ['─', '═', '━', '-', '=', '┬', '┴', '╤', '╧', '+'];
+const VERTICAL: [char; 12] = ['│', '║', '┃', '|', '├', '┤', '╟', '╢', '╠', '╣', '┠', '┨'];
+/// Characters that frame a title set into a top border.
+const TITLE_FRAME: [char; 8] = ['┤', '├', '╡', '╞', '┨', '┠', '[', ']'];
+const PROMPT_MARKERS: [&str; 4] = ["[y/n]", "(y/n)", "[yes/no]", "(yes/no)"];
+
+/// Placeholder for the second column of a wide character.
+const CONTINUATION: char = '\0';
+
+/// One screen column: its first character and whether it is highlighted.
+#[derive(Clone, Copy)]
+struct GridCell {
+    ch: char,
+    highlighted: bool,
+}
+
+/// Screen contents by row and column.
+struct Grid {
+    rows: Vec<Vec<GridCell>>,
+}
+
+/// Detect widgets on a captured screen.
+///
+/// Selections are only found when the snapshot carries `cells`.
+#[must_use]
+pub fn detect_widgets(snapshot: &ScreenSnapshot) -> Vec<Widget> {
+    let rows = match &snapshot.cells {
+        Some(cells) => cells
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .flat_map(|cell| {
+                        let head = GridCell {
+                            ch: cell.ch.chars().next().unwrap_or(' '),
+                            highlighted: cell.style.inverse,
+                        };
+                        let tail = (cell.width > 1).then_some(GridCell {
+                            ch: CONTINUATION,
+                            ..head
+                        });
+                        std::iter::once(head).chain(tail)
+                    })
+                    .collect()
+            })
+            .collect(),
+        None => snapshot
+            .lines
+            .iter()
+            .map(|line| {
+                line.chars()
+                    .map(|ch| GridCell {
+                        ch,
+                        highlighted: false,
+                    })
+                    .collect()
+            })
+            .collect(),
+    };
+    Grid { rows }.detect()
+}
+
+/// Detect widgets on the live `vt100` screen.
+pub(super) fn detect_on_screen(screen: &vt100::Screen) -> Vec<Widget> {
+    let (rows, cols) = screen.size();
+    let rows = (0..rows)
+        .map(|row| {
+            (0..cols)
+                .map(|col| match screen.cell(row, col) {
+                    Some(cell) if cell.is_wide_continuation() => GridCell {
+                        ch: CONTINUATION,
+                        highlighted: cell.inverse(),
+                    },
+                    Some(cell) => GridCell {
+                        ch: cell.contents().chars().next().unwrap_or(' '),
+                        highlighted: cell.inverse(),
+                    },
+                    None => GridCell {
+                        ch: ' ',
+                        highlighted: false,
+                    },
+                })
+                .collect()
+        })
+        .collect();
+    Grid { rows }.detect()
+}
+
+impl Grid {
+    fn detect(&self) -> Vec<Widget> {
+        let mut widgets = self.boxes();
+        widgets.extend(self.selections());
+        widgets.extend(self.prompts());
+        widgets
+    }
+
+    fn ch(&self, row: usize, col: usize) -> char {
+        self.rows
+            .get(row)
+            .and_then(|cells| cells.get(col))
+            .map_or(' ', |cell| cell.ch)
+    }
+
+    /// Text of `cols` on `row`, trimmed.
+    fn text(&self, row: usize, cols: std::ops::Range<usize>) -> String {
+        cols.map(|col| self.ch(row, col))
+            .filter(|ch| *ch != CONTINUATION)
+            .collect::<String>()
+            .trim()
+            .to_string()
+    }
+
+    fn boxes(&self) -> Vec<Widget> {
+        let mut found = Vec::new();
+        for (row, cells) in self.rows.iter().enumerate() {
+            for (col, cell) in cells.iter().enumerate() {
+                if TOP_LEFT.contains(&cell.ch) {
+                    found.extend(self.box_at(row, col));
+                }
+            }
+        }
+        found
+    }
+
+    /// The box whose top-left corner is at (`row`, `col`), if its borders close.
+    fn box_at(&self, row: usize, col: usize) -> Option<Widget> {
+        let (right, title) = self.top_border(row, col)?;
+        let bottom = self.bottom_row(row, col, right)?;
+        let text = (row + 1..bottom)
+            .map(|line| self.text(line, col + 1..right))
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        Some(Widget {
+            kind: WidgetKind::Box,
+            row: to_u16(row),
+            col: to_u16(col),
+            rows: to_u16(bottom - row + 1),
+            cols: to_u16(right - col + 1),
+            title,
+            text,
+        })
+    }
+
+    /// Column of the top-right corner and the title set into the border.
+    ///
+    /// The border must start and end with a horizontal line or title frame.
+    fn top_border(&self, row: usize, col: usize) -> Option<(usize, Option<String>)> {
+        let width = self.rows.get(row)?.len();
+        let right = (col + 2..width).find(|&c| TOP_RIGHT.contains(&self.ch(row, c)))?;
+        let is_edge = |ch: char| HORIZONTAL.contains(&ch) || TITLE_FRAME.contains(&ch);
+        if !is_edge(self.ch(row, col + 1)) || !is_edge(self.ch(row, right - 1)) {
+            return None;
+        }
+        let title = self
+            .text(row, col + 1..right)
+            .trim_matches(|ch: char| is_edge(ch) || ch.is_whitespace())
+            .to_string();
+        Some((right, (!title.is_empty()).then_some(title)))
+    }
+
+    /// Row of the bottom border, with vertical borders on every row between.
+    fn bottom_row(&self, top: usize, left: usize, right: usize) -> Option<usize> {
+        for row in top + 1..self.rows.len() {
+            let (l, r) = (self.ch(row, left), self.ch(row, right));
+            if BOTTOM_LEFT.contains(&l) && BOTTOM_RIGHT.contains(&r) {
+                return Some(row);
+            }
+            if !VERTICAL.contains(&l) || !VERTICAL.contains(&r) {
+                return None;
+            }
+        }
+        None
+    }
+
+    fn selections(&self) -> Vec<Widget> {
+        let mut found = Vec::new();
+        for (row, cells) in self.rows.iter().enumerate() {
+            let mut col = 0;
+            while col < cells.len() {
+                let highlighted = |c: &usize| cells.get(*c).is_some_and(|cell| cell.highlighted);
+                let Some(start) = (col..cells.len()).find(highlighted) else {
+                    break;
+                };
+                let end = (start..cells.len())
+                    .find(|c| !highlighted(c))
+                    .unwrap_or(cells.len());
+                let text = self.text(row, start..end);
+                if !text.is_empty() {
+                    found.push(Widget {
+                        kind: WidgetKind::Selection,
+                        row: to_u16(row),
+                        col: to_u16(start),
+                        rows: 1,
+                        cols: to_u16(end - start),
+                        title: None,
+                        text,
+                    });
+                }
+                col = end;
+            }
+        }
+        found
+    }
+
+    fn prompts(&self) -> Vec<Widget> {
+        (0..self.rows.len())
+            .filter_map(|row| {
+                let width = self.rows.get(row).map_or(0, Vec::len);
+                let text = self.text(row, 0..width);
+                let lower = text.to_lowercase();
+                if !PROMPT_MARKERS.iter().any(|marker| lower.contains(marker)) {
+                    return None;
+                }
+                let col = (0..width)
+                    .find(|&col| !self.ch(row, col).is_whitespace())
+                    .unwrap_or(0);
+                Some(Widget {
+                    kind: WidgetKind::Prompt,
+                    row: to_u16(row),
+                    col: to_u16(col),
+                    rows: 1,
+                    cols: to_u16(text.chars().count()),
+                    title: None,
+                    text,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Screen coordinates come from `u16` sizes, so they always fit.
+fn to_u16(value: usize) -> u16 {
+    u16::try_from(value).unwrap_or(u16::MAX)
+}
@@ -0,0 +1,81 @@
+//! XTWINOPS size queries (`CSI Ps t`) answered from the emulated terminal.
+//!
+//! Image-rendering TUIs ask the terminal for its size in pixels or cells.
+//! The replies are derived only from the configured [`TerminalSize`] so
+//! output is identical across hosts.
+//!
+//! | Query | Reply | When |
+//! |-------|-------|------|
+//! | `CSI 14 t` | `CSI 4 ; height ; width t` | pixel size known |
+//! | `CSI 16 t` | `CSI 6 ; cell_height ; cell_width t` | pixel size known |
+//! | `CSI 18 t` | `CSI 8 ; rows ; cols t` | always |
+//! | `CSI 19 t` | `CSI 9 ; rows ; cols t` | always |
+
+use crate::model::TerminalSize;
+
+/// Longest parameter string accepted before a query is abandoned.
+const MAX_PARAMS_LEN: usize = 16;
+
+const ESC: u8 = 0x1b;
+
+/// Incremental scanner for XTWINOPS queries split across PTY reads.
+#[derive(Debug, Default)]
+pub(crate) struct WindowOpsScanner {
+    partial: Vec<u8>,
+}
+
+impl WindowOpsScanner {
+    /// Scan `bytes` for size queries and append replies to `replies`.
+    pub(crate) fn scan(&mut self, bytes: &[u8], size: &TerminalSize, replies: &mut Vec<u8>) {
+        for &byte in bytes {
+            match self.partial.len() {
+                0 => {
+                    if byte == ESC {
+                        self.partial.push(byte);
+                    }
+                }
+                1 => self.restart_unless(byte == b'[', byte),
+                len => {
+                    if byte == b't' {
+                        let params = self.partial.get(2..).unwrap_or_default();
+                        reply(params, size, replies);
+                        self.partial.clear();
+                    } else {
+                        let param = byte.is_ascii_digit() || byte == b';';
+                        self.restart_unless(param && len - 2 < MAX_PARAMS_LEN, byte);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Keep `byte` as part of the current sequence, or drop the sequence and
+    /// start over if `byte` begins a new one.
+    fn restart_unless(&mut self, keep: bool, byte: u8) {
+        if keep {
+            self.partial.push(byte);
+        } else {
+            self.partial.clear();
+            if byte == ESC {
+                self.partial.push(byte);
+            }
+        }
+    }
+}
+
+fn reply(params: &[u8], size: &TerminalSize, replies: &mut Vec<u8>) {
+    let answer = match params {
+        b"14" | b"14;2" if size.has_pixels() => {
+            format!("\x1b[4;{};{}t", size.pixel_height, size.pixel_width)
+        }
+        b"16" if size.has_pixels() => format!(
+            "\x1b[6;{};{}t",
+            size.pixel_height / size.rows.max(1),
+            size.pixel_width / size.cols.max(1)
+        ),
+        b"18" => format!("\x1b[8;{};{}t", size.rows, size.cols),
+        b"19" => format!("\x1b[9;{};{}t", size.rows, size.cols),
+        _ => return,
+    };
+    replies.extend_from_slice(answer.as_bytes());
+}
@@ -2,7 +2,8 @@
 
 use crate::artifacts::ArtifactsWriterConfig;
 use crate::model::policy::{Policy, SandboxMode};
-use crate::model::{ExitStatus, RunId, ScreenSnapshot};
+use crate::model::{ContainerInvocation, ExitStatus, RunId, ScreenSnapshot};
+use crate::policy::container::{self, ContainerCleanup};
 use crate::policy::sandbox;
 use crate::runner::{RunnerError, RunnerResult};
 use std::path::{Path, PathBuf};
@@ -18,6 +19,41 @@ pub fn elapsed_ms(started_at: &Instant) -> u64 {
     ms
 }
 
+/// Lowercase hex encoding of `bytes`.
+pub fn encode_hex(bytes: &[u8]) -> String {
+    use std::fmt::Write as _;
+    bytes
+        .iter()
+        .fold(String::with_capacity(bytes.len() * 2), |mut out, byte| {
+            let _ = write!(out, "{byte:02x}");
+            out
+        })
+}
+
+/// Standard (RFC 4648, padded) base64 encoding of `bytes`.
+pub fn encode_base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let byte = |index: usize| u32::from(chunk.get(index).copied().unwrap_or(0));
+        let group = (byte(0) << 16) | (byte(1) << 8) | byte(2);
+        for position in 0..4 {
+            if position > chunk.len() {
+                out.push('=');
+                continue;
+            }
+            let sextet = (group >> (18 - 6 * position)) & 0x3f;
+            out.push(
+                ALPHABET
+                    .get(sextet as usize)
+                    .copied()
+                    .map_or('=', char::from),
+            );
+        }
+    }
+    out
+}
+
 /// Sleep until `deadline`, capped at `max_step` per sleep.
 ///
 /// Yields immediately if within 500µs of the deadline to avoid oversleeping.
@@ -59,6 +95,7 @@ pub fn convert_exit_status(
         exit_code: Some(code),
         signal,
         terminated_by_harness,
+        termination: None,
     }
 }
 
@@ -155,11 +192,289 @@ pub fn compute_checksum(path: &Path) -> RunnerResult<String> {
     Ok(format!("{:016x}", fnv1a_hash(&data)))
 }
 
+/// Whether `name` matches `pattern`, where `*` matches any run of
+/// characters (possibly empty) and `?` exactly one.
+#[must_use]
+pub fn glob_matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut pat, mut pos) = (0, 0);
+    // Pattern position after the last `*` and the name position it has
+    // consumed up to
+    let mut backtrack: Option<(usize, usize)> = None;
+    while let Some(&ch) = name.get(pos) {
+        match pattern.get(pat) {
+            Some('*') => {
+                backtrack = Some((pat + 1, pos));
+                pat += 1;
+            }
+            Some(&expected) if expected == '?' || expected == ch => {
+                pat += 1;
+                pos += 1;
+            }
+            _ => {
+                let Some((after_star, star_end)) = backtrack else {
+                    return false;
+                };
+                backtrack = Some((after_star, star_end + 1));
+                pat = after_star;
+                pos = star_end + 1;
+            }
+        }
+    }
+    pattern
+        .get(pat..)
+        .is_some_and(|rest| rest.iter().all(|ch| *ch == '*'))
+}
+
+/// Whether the `/`-separated `path` matches `pattern`, where `*` and `?`
+/// match within one path segment (as in [`glob_matches`]) and a `**`
+/// segment matches any number of whole segments, including none.
+///
+/// `src/*.rs` matches `src/main.rs` but not `src/cli/main.rs`;
+/// `src/**/*.rs` matches both.
+#[must_use]
+pub fn path_glob_matches(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').collect();
+    let path: Vec<&str> = path.split('/').collect();
+    let (mut pat, mut pos) = (0, 0);
+    // Pattern position after the last `**` and the path segment it has
+    // consumed up to
+    let mut backtrack: Option<(usize, usize)> = None;
+    while let Some(&segment) = path.get(pos) {
+        match pattern.get(pat) {
+            Some(&"**") => {
+                backtrack = Some((pat + 1, pos));
+                pat += 1;
+            }
+            Some(glob) if glob_matches(glob, segment) => {
+                pat += 1;
+                pos += 1;
+            }
+            _ => {
+                let Some((after_star, star_end)) = backtrack else {
+                    return false;
+                };
+                backtrack = Some((after_star, star_end + 1));
+                pat = after_star;
+                pos = star_end + 1;
+            }
+        }
+    }
+    pattern
+        .get(pat..)
+        .is_some_and(|rest| rest.iter().all(|segment| *segment == "**"))
+}
+
+/// SHA-256 round constants.
+const SHA256_K: [u32; 64] = [
+    0x428a_2f98,
+    0x7137_4491,
+    0xb5c0_fbcf,
+    0xe9b5_dba5,
+    0x3956_c25b,
+    0x59f1_11f1,
+    0x923f_82a4,
+    0xab1c_5ed5,
+    0xd807_aa98,
+    0x1283_5b01,
+    0x2431_85be,
+    0x550c_7dc3,
+    0x72be_5d74,
+    0x80de_b1fe,
+    0x9bdc_06a7,
+    0xc19b_f174,
+    0xe49b_69c1,
+    0xefbe_4786,
+    0x0fc1_9dc6,
+    0x240c_a1cc,
+    0x2de9_2c6f,
+    0x4a74_84aa,
+    0x5cb0_a9dc,
+    0x76f9_88da,
+    0x983e_5152,
+    0xa831_c66d,
+    0xb003_27c8,
+    0xbf59_7fc7,
+    0xc6e0_0bf3,
+    0xd5a7_9147,
+    0x06ca_6351,
+    0x1429_2967,
+    0x27b7_0a85,
+    0x2e1b_2138,
+    0x4d2c_6dfc,
+    0x5338_0d13,
+    0x650a_7354,
+    0x766a_0abb,
+    0x81c2_c92e,
+    0x9272_2c85,
+    0xa2bf_e8a1,
+    0xa81a_664b,
+    0xc24b_8b70,
+    0xc76c_51a3,
+    0xd192_e819,
+    0xd699_0624,
+    0xf40e_3585,
+    0x106a_a070,
+    0x19a4_c116,
+    0x1e37_6c08,
+    0x2748_774c,
+    0x34b0_bcb5,
+    0x391c_0cb3,
+    0x4ed8_aa4a,
+    0x5b9c_ca4f,
+    0x682e_6ff3,
+    0x748f_82ee,
+    0x78a5_636f,
+    0x84c8_7814,
+    0x8cc7_0208,
+    0x90be_fffa,
+    0xa450_6ceb,
+    0xbef9_a3f7,
+    0xc671_78f2,
+];
+
+/// SHA-256 initial hash values.
+const SHA256_H0: [u32; 8] = [
+    0x6a09_e667,
+    0xbb67_ae85,
+    0x3c6e_f372,
+    0xa54f_f53a,
+    0x510e_527f,
+    0x9b05_688c,
+    0x1f83_d9ab,
+    0x5be0_cd19,
+];
+
+/// SHA-256 digest of `data` as lowercase hex.
+///
+/// Used where a standard digest is expected (attestations, scenario hashes);
+/// artifact checksums use the cheaper [`fnv1a_hash`].
+pub fn sha256_hex(data: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    hasher.finish_hex()
+}
+
+/// Incremental SHA-256, for data that arrives in pieces.
+#[derive(Clone, Debug)]
+pub struct Sha256 {
+    state: [u32; 8],
+    /// Bytes of the current, incomplete block.
+    block: Vec<u8>,
+    len: u64,
+}
+
+impl Default for Sha256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sha256 {
+    /// Start a new digest.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            state: SHA256_H0,
+            block: Vec::with_capacity(64),
+            len: 0,
+        }
+    }
+
+    /// Add `data` to the digest.
+    pub fn update(&mut self, data: &[u8]) {
+        self.len = self.len.wrapping_add(data.len() as u64);
+        let mut rest = data;
+        if !self.block.is_empty() {
+            let take = (64 - self.block.len()).min(rest.len());
+            self.block
+                .extend_from_slice(rest.get(..take).unwrap_or_default());
+            rest = rest.get(take..).unwrap_or_default();
+            if self.block.len() < 64 {
+                return;
+            }
+            sha256_compress(&mut self.state, &self.block);
+            self.block.clear();
+        }
+        let mut blocks = rest.chunks_exact(64);
+        for block in &mut blocks {
+            sha256_compress(&mut self.state, block);
+        }
+        self.block.extend_from_slice(blocks.remainder());
+    }
+
+    /// The digest of everything added so far, as lowercase hex.
+    #[must_use]
+    pub fn finish_hex(&self) -> String {
+        let mut state = self.state;
+        let mut tail = self.block.clone();
+        tail.push(0x80);
+        while tail.len() % 64 != 56 {
+            tail.push(0);
+        }
+        tail.extend_from_slice(&self.len.wrapping_mul(8).to_be_bytes());
+        for block in tail.chunks_exact(64) {
+            sha256_compress(&mut state, block);
+        }
+        let digest: Vec<u8> = state.iter().flat_map(|word| word.to_be_bytes()).collect();
+        encode_hex(&digest)
+    }
+}
+
+#[allow(clippy::many_single_char_names)]
+fn sha256_compress(state: &mut [u32; 8], block: &[u8]) {
+    let mut schedule = [0u32; 64];
+    for (word, bytes) in schedule.iter_mut().zip(block.chunks_exact(4)) {
+        *word = bytes.try_into().map(u32::from_be_bytes).unwrap_or(0);
+    }
+    for index in 16..64 {
+        let back = |offset: usize| schedule.get(index - offset).copied().unwrap_or(0);
+        let w15 = back(15);
+        let w2 = back(2);
+        let s0 = w15.rotate_right(7) ^ w15.rotate_right(18) ^ (w15 >> 3);
+        let s1 = w2.rotate_right(17) ^ w2.rotate_right(19) ^ (w2 >> 10);
+        let value = back(16)
+            .wrapping_add(s0)
+            .wrapping_add(back(7))
+            .wrapping_add(s1);
+        if let Some(slot) = schedule.get_mut(index) {
+            *slot = value;
+        }
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for (k, w) in SHA256_K.iter().zip(schedule.iter()) {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let choice = (e & f) ^ (!e & g);
+        let temp1 = h
+            .wrapping_add(s1)
+            .wrapping_add(choice)
+            .wrapping_add(*k)
+            .wrapping_add(*w);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let majority = (a & b) ^ (a & c) ^ (b & c);
+        let temp2 = s0.wrapping_add(majority);
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(temp1);
+        d = c;
+        c = b;
+        b = a;
+        a = temp1.wrapping_add(temp2);
+    }
+    for (word, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *word = word.wrapping_add(value);
+    }
+}
+
 // =============================================================================
 // Spawn Infrastructure
 // =============================================================================
 
-/// Resolved command to spawn (possibly wrapped by sandbox-exec).
+/// Resolved command to spawn (possibly wrapped by sandbox-exec or a
+/// container runtime).
 pub struct SpawnCommand {
     /// The command to execute.
     pub command: String,
@@ -167,9 +482,19 @@ pub struct SpawnCommand {
     pub args: Vec<String>,
     /// Temporary sandbox profile path to clean up when done.
     pub cleanup_path: Option<PathBuf>,
+    /// Container to remove when done (container sandbox only).
+    pub container: Option<ContainerCleanup>,
+    /// Runtime invocation to record in `container.json` with
+    /// [`ArtifactsWriter::write_container_invocation`] (container sandbox only).
+    ///
+    /// [`ArtifactsWriter::write_container_invocation`]: crate::artifacts::ArtifactsWriter::write_container_invocation
+    pub invocation: Option<ContainerInvocation>,
 }
 
-/// Build the spawn command, wrapping in sandbox-exec if policy requires it.
+/// Build the spawn command, wrapping in sandbox-exec or a container runtime
+/// if policy requires it. `cwd` is the container's working directory.
+///
+/// With artifacts, the Seatbelt profile is written to `sandbox.sb`.
 ///
 /// # Errors
 /// Returns `E_IO` if the sandbox profile cannot be written.
@@ -177,6 +502,7 @@ pub fn build_spawn_command(
     policy: &Policy,
     command: &str,
     args: &[String],
+    cwd: Option<&str>,
     artifacts_dir: Option<&PathBuf>,
     run_id: RunId,
 ) -> RunnerResult<SpawnCommand> {
@@ -189,7 +515,9 @@ pub fn build_spawn_command(
             } else {
                 std::env::temp_dir().join(format!("ptybox-{run_id}.sb"))
             };
-            sandbox::write_profile(&profile_path, policy)?;
+            let socket_dir =
+                (!policy.sockets.is_empty()).then(|| crate::session::socket_dir(run_id));
+            sandbox::write_profile_with_sockets(&profile_path, policy, socket_dir.as_deref())?;
             let mut sandbox_args = vec!["-f".to_string(), profile_path.display().to_string()];
             sandbox_args.push(command.to_string());
             sandbox_args.extend(args.iter().cloned());
@@ -202,12 +530,51 @@ pub fn build_spawn_command(
                 command: "/usr/bin/sandbox-exec".to_string(),
                 args: sandbox_args,
                 cleanup_path: cleanup,
+                container: None,
+                invocation: None,
+            })
+        }
+        SandboxMode::Container {
+            runtime,
+            ref image,
+            ref program,
+            ..
+        } => {
+            let program = PathBuf::from(program);
+            let socket_dir =
+                (!policy.sockets.is_empty()).then(|| crate::session::socket_dir(run_id));
+            let invocation = ContainerInvocation {
+                runtime,
+                image: image.clone(),
+                program: program.display().to_string(),
+                args: container::container_args(
+                    policy,
+                    runtime,
+                    image,
+                    command,
+                    args,
+                    cwd,
+                    run_id,
+                    socket_dir.as_deref(),
+                ),
+            };
+            Ok(SpawnCommand {
+                command: invocation.program.clone(),
+                args: invocation.args.clone(),
+                cleanup_path: None,
+                container: Some(ContainerCleanup::new(
+                    program,
+                    container::container_name(run_id),
+                )),
+                invocation: Some(invocation),
             })
         }
         SandboxMode::Disabled { .. } => Ok(SpawnCommand {
             command: command.to_string(),
             args: args.to_vec(),
             cleanup_path: None,
+            container: None,
+            invocation: None,
         }),
     }
 }
@@ -215,17 +582,62 @@ pub fn build_spawn_command(
 /// RAII guard for sandbox profile cleanup.
 ///
 /// Ensures the sandbox profile file is deleted when the guard is dropped,
-/// even on panic. This prevents temporary files from accumulating.
+/// even on panic. This prevents temporary files from accumulating. The
+/// run's policy sockets, when bound, are removed along with it.
 pub struct SandboxCleanupGuard {
     /// The path to clean up, if any.
     pub path: Option<PathBuf>,
+    /// Socket directory created for `policy.sockets`, if any.
+    pub sockets: Option<crate::session::SocketDir>,
+    /// Container started for the run, if any.
+    pub container: Option<ContainerCleanup>,
 }
 
 impl SandboxCleanupGuard {
     /// Create a new cleanup guard for the given path.
     pub fn new(path: Option<PathBuf>) -> Self {
-        Self { path }
+        Self {
+            path,
+            sockets: None,
+            container: None,
+        }
+    }
+}
+
+/// Bind the sockets granted by `policy.sockets` and hand each listening end
+/// to `handler`, returning the child's environment with the socket paths.
+///
+/// # Errors
+/// - `E_POLICY_DENIED`: The policy grants sockets but there is no handler
+///   to serve them (the CLI, driver, and `serve` have none)
+/// - `E_IO`: A socket cannot be created
+pub fn bind_policy_sockets(
+    policy: &Policy,
+    run_id: RunId,
+    handler: Option<&std::sync::Arc<dyn crate::runner::SocketHandler>>,
+    guard: &mut SandboxCleanupGuard,
+) -> RunnerResult<crate::model::policy::EnvPolicy> {
+    let mut env = policy.effective_env();
+    if policy.sockets.is_empty() {
+        return Ok(env);
+    }
+    let Some(handler) = handler else {
+        return Err(RunnerError::policy_denied(
+            "E_POLICY_DENIED",
+            "policy sockets require a socket handler",
+            serde_json::json!({
+                "sockets": policy.sockets.iter().map(|grant| &grant.name).collect::<Vec<_>>(),
+                "fix": "Run through the library with RunnerOptions::socket_handler set, or remove policy.sockets",
+            }),
+        ));
+    };
+    let mut sockets = crate::session::SocketDir::bind(run_id, &policy.sockets)?;
+    sockets.apply_env(&mut env);
+    for (name, listener) in sockets.take_listeners() {
+        handler.serve(&name, listener);
     }
+    guard.sockets = Some(sockets);
+    Ok(env)
 }
 
 impl Drop for SandboxCleanupGuard {
@@ -233,6 +645,9 @@ impl Drop for SandboxCleanupGuard {
         if let Some(path) = self.path.take() {
             let _ = std::fs::remove_file(path);
         }
+        if let Some(container) = self.container.take() {
+            container.remove();
+        }
     }
 }
 
@@ -0,0 +1,152 @@
+// Test module - relaxed lint rules
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(missing_docs)]
+
+//! Global byte quota over the runs under an artifacts root.
+
+use std::path::{Path, PathBuf};
+
+use ptybox::artifacts::{
+    artifacts_status, set_artifacts_quota, ArtifactsWriterConfig, QUOTA_LEDGER_FILE,
+};
+use ptybox::model::policy::PolicyBuilder;
+use ptybox::model::{ArtifactsQuota, QuotaRetention, QuotaRunState, RunResult, RunStatus};
+use ptybox::run::run_exec_with_options;
+use ptybox::runner::{ErrorCode, RunnerOptions, RunnerResult};
+
+fn temp_dir(prefix: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ptybox-quota-{prefix}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.canonicalize().unwrap()
+}
+
+/// Run `/bin/echo` with artifacts in `root/name`, indexed at `root`.
+fn echo(root: &Path, name: &str) -> RunnerResult<RunResult> {
+    let policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_executables(vec!["/bin/echo".to_string()])
+        .allowed_write(vec![root.display().to_string()])
+        .build();
+    run_exec_with_options(
+        "/bin/echo".to_string(),
+        vec!["quota".to_string()],
+        None,
+        policy,
+        RunnerOptions {
+            artifacts: Some(ArtifactsWriterConfig {
+                dir: root.join(name),
+                overwrite: false,
+            }),
+            run_index: Some(root.to_path_buf()),
+            ..RunnerOptions::default()
+        },
+    )
+}
+
+fn quota(max_bytes: u64, retention: QuotaRetention) -> ArtifactsQuota {
+    ArtifactsQuota {
+        max_bytes,
+        retention,
+    }
+}
+
+#[test]
+fn roots_without_a_ledger_are_not_tracked() {
+    let root = temp_dir("untracked");
+    echo(&root, "first").unwrap();
+    assert!(!root.join(QUOTA_LEDGER_FILE).exists());
+    let err = artifacts_status(&root).unwrap_err();
+    assert_eq!(err.code, ErrorCode::Io);
+}
+
+#[test]
+fn completed_runs_are_evicted_oldest_first() {
+    let root = temp_dir("evict");
+    let status = set_artifacts_quota(&root, None).unwrap();
+    assert_eq!(status.used_bytes, 0);
+    let first = echo(&root, "first").unwrap();
+    let status = artifacts_status(&root).unwrap();
+    assert_eq!(status.completed_runs, 1);
+    let run = &status.runs[0];
+    assert_eq!((run.run_id, run.dir.as_str()), (first.run_id, "first"));
+    assert_eq!(run.state, QuotaRunState::Completed);
+    assert_eq!(run.status, Some(RunStatus::Passed));
+    assert!(run.bytes > 0);
+    assert_eq!(status.available_bytes, None);
+
+    // Room for two runs, not three.
+    let max_bytes = run.bytes * 5 / 2;
+    let status =
+        set_artifacts_quota(&root, Some(quota(max_bytes, QuotaRetention::default()))).unwrap();
+    assert_eq!(status.available_bytes, Some(max_bytes - run.bytes));
+    echo(&root, "second").unwrap();
+    echo(&root, "third").unwrap();
+
+    assert!(!root.join("first").exists());
+    let status = artifacts_status(&root).unwrap();
+    let dirs: Vec<&str> = status.runs.iter().map(|run| run.dir.as_str()).collect();
+    assert_eq!(dirs, ["second", "third"]);
+    assert_eq!(status.evicted_runs, 1);
+    assert_eq!(status.evicted_bytes, run.bytes);
+    assert!(status.used_bytes <= max_bytes);
+}
+
+#[test]
+fn runs_the_retention_keeps_can_exhaust_the_quota() {
+    let root = temp_dir("exhaust");
+    let retention = QuotaRetention {
+        keep_newest: 1,
+        ..QuotaRetention::default()
+    };
+    set_artifacts_quota(&root, Some(quota(1, retention))).unwrap();
+    echo(&root, "kept").unwrap();
+
+    let err = echo(&root, "refused").unwrap_err();
+    assert_eq!(err.code, ErrorCode::Io);
+    assert_eq!(err.message, "artifacts quota exhausted");
+    let context = err.context.unwrap();
+    assert_eq!(context["max_bytes"], 1);
+    assert_eq!(context["active_runs"], 0);
+    assert!(root.join("kept").exists());
+    assert!(!root.join("refused").exists());
+
+    let status = artifacts_status(&root).unwrap();
+    assert_eq!(status.runs.len(), 1);
+    assert_eq!(status.evictable_runs, 0);
+    assert_eq!(status.available_bytes, Some(0));
+
+    // Lifting the retention lets the next run evict the kept one.
+    set_artifacts_quota(&root, Some(quota(1, QuotaRetention::default()))).unwrap();
+    assert!(!root.join("kept").exists());
+    echo(&root, "admitted").unwrap();
+    assert!(root.join("admitted").exists());
+}
+
+#[test]
+fn ledger_dirs_outside_the_root_are_never_evicted() {
+    let base = temp_dir("escape");
+    let root = base.join("root");
+    let victim = base.join("victim");
+    std::fs::create_dir_all(&victim).unwrap();
+    set_artifacts_quota(&root, None).unwrap();
+    echo(&root, "first").unwrap();
+
+    let ledger_path = root.join(QUOTA_LEDGER_FILE);
+    let mut ledger: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(&ledger_path).unwrap()).unwrap();
+    ledger["runs"][0]["dir"] = serde_json::json!("../victim");
+    std::fs::write(&ledger_path, serde_json::to_vec(&ledger).unwrap()).unwrap();
+
+    let err = set_artifacts_quota(&root, Some(quota(1, QuotaRetention::default()))).unwrap_err();
+    assert_eq!(err.code, ErrorCode::Protocol);
+    assert_eq!(err.context.unwrap()["dir"], "../victim");
+    assert!(victim.exists());
+    assert!(root.join("first").exists());
+    assert_eq!(
+        artifacts_status(&root).unwrap_err().code,
+        ErrorCode::Protocol
+    );
+}
@@ -16,14 +16,22 @@
 //!
 //! Tests the artifact writing and checksum functionality.
 
-use ptybox::artifacts::{ArtifactsWriter, ArtifactsWriterConfig};
+use ptybox::artifacts::{
+    event_log_paths, read_artifact, reformat_artifacts, snapshot_paths, to_canonical_json,
+    ArtifactsDecryptor, ArtifactsWriter, ArtifactsWriterConfig,
+};
+use ptybox::model::policy::{
+    ArtifactsEncryption, ArtifactsLayout, ContainerRuntime, TranscriptPolicy, TranscriptTruncate,
+};
 use ptybox::model::{
-    Cursor, NormalizationRecord, NormalizationSource, Policy, RunId, ScreenSnapshot, SnapshotId,
+    ContainerInvocation, Cursor, EmulatorDiffReport, JournalEvent, NormalizationRecord,
+    NormalizationSource, Policy, RunId, RunResult, ScreenSnapshot, SnapshotId,
     NORMALIZATION_VERSION, SNAPSHOT_VERSION,
 };
 use ptybox::runner::ErrorCode;
+use serde_json::{json, Value};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 fn temp_artifacts_dir() -> PathBuf {
     // Include thread ID for test isolation when running tests in parallel
@@ -142,6 +150,7 @@ fn artifacts_write_policy() {
     let policy = Policy::default();
     let result = writer.write_policy(&policy);
     assert!(result.is_ok(), "Should write policy: {:?}", result.err());
+    writer.end_step().expect("Failed to flush at step boundary");
 
     let policy_path = dir.join("policy.json");
     assert!(policy_path.exists(), "policy.json should exist");
@@ -154,6 +163,90 @@ fn artifacts_write_policy() {
     cleanup_dir(&dir);
 }
 
+#[test]
+fn artifacts_json_is_canonical_by_default() {
+    let dir = temp_artifacts_dir();
+    let config = ArtifactsWriterConfig {
+        dir: dir.clone(),
+        overwrite: false,
+    };
+    let mut writer = ArtifactsWriter::new(RunId::new(), config).unwrap();
+    writer.write_policy(&Policy::default()).unwrap();
+    writer.flush_checksums().unwrap();
+
+    for name in ["policy.json", "checksums.json"] {
+        let content = fs::read(dir.join(name)).unwrap();
+        let parsed: serde_json::Value = serde_json::from_slice(&content).unwrap();
+        assert_eq!(content, to_canonical_json(&parsed).unwrap(), "{name}");
+        assert_eq!(content.last(), Some(&b'\n'), "{name}");
+    }
+    let policy = fs::read_to_string(dir.join("policy.json")).unwrap();
+    let artifacts = policy.find("\"artifacts\"").unwrap();
+    let sandbox = policy.find("\"sandbox\"").unwrap();
+    assert!(artifacts < sandbox, "keys should be sorted:\n{policy}");
+
+    cleanup_dir(&dir);
+}
+
+fn write_plain_policy(dir: &Path) {
+    let config = ArtifactsWriterConfig {
+        dir: dir.to_path_buf(),
+        overwrite: false,
+    };
+    let mut writer = ArtifactsWriter::new(RunId::new(), config).unwrap();
+    writer.set_canonical_json(false);
+    writer.write_policy(&Policy::default()).unwrap();
+    writer.flush_checksums().unwrap();
+}
+
+#[test]
+fn artifacts_reformat_rewrites_json_and_checksums() {
+    let dir = temp_artifacts_dir();
+    write_plain_policy(&dir);
+    let before = fs::read(dir.join("policy.json")).unwrap();
+
+    let report = reformat_artifacts(&dir).unwrap();
+    assert_eq!(report.reformatted, vec!["policy.json".to_string()]);
+    assert_eq!(report.unchanged, 0);
+
+    let after = fs::read(dir.join("policy.json")).unwrap();
+    assert_ne!(before, after);
+    let before: serde_json::Value = serde_json::from_slice(&before).unwrap();
+    let parsed: serde_json::Value = serde_json::from_slice(&after).unwrap();
+    assert_eq!(before, parsed);
+    assert_eq!(after, to_canonical_json(&parsed).unwrap());
+
+    let checksums: std::collections::BTreeMap<String, String> =
+        serde_json::from_slice(&fs::read(dir.join("checksums.json")).unwrap()).unwrap();
+    assert_eq!(
+        checksums["policy.json"],
+        ptybox::util::compute_checksum(&dir.join("policy.json")).unwrap()
+    );
+
+    let again = reformat_artifacts(&dir).unwrap();
+    assert!(again.reformatted.is_empty());
+    assert_eq!(again.unchanged, 1);
+
+    cleanup_dir(&dir);
+}
+
+#[test]
+fn artifacts_reformat_rejects_corrupted_artifact() {
+    let dir = temp_artifacts_dir();
+    write_plain_policy(&dir);
+    let policy = fs::read_to_string(dir.join("policy.json")).unwrap();
+    fs::write(dir.join("policy.json"), format!("{policy} ")).unwrap();
+
+    let err = reformat_artifacts(&dir).unwrap_err();
+    assert_eq!(err.code, ErrorCode::ReplayMismatch);
+    assert_eq!(
+        fs::read_to_string(dir.join("policy.json")).unwrap(),
+        format!("{policy} ")
+    );
+
+    cleanup_dir(&dir);
+}
+
 // =============================================================================
 // Write Snapshot Tests
 // =============================================================================
@@ -186,6 +279,7 @@ fn artifacts_write_snapshot() {
 
     let result = writer.write_snapshot(&snapshot);
     assert!(result.is_ok(), "Should write snapshot: {:?}", result.err());
+    writer.end_step().expect("Failed to flush at step boundary");
 
     let snapshot_path = dir.join("snapshots/000001.json");
     assert!(snapshot_path.exists(), "Snapshot file should exist");
@@ -230,6 +324,7 @@ fn artifacts_write_multiple_snapshots_numbered() {
             .write_snapshot(&snapshot)
             .expect("Failed to write snapshot");
     }
+    writer.end_step().expect("Failed to flush at step boundary");
 
     assert!(dir.join("snapshots/000001.json").exists());
     assert!(dir.join("snapshots/000002.json").exists());
@@ -262,6 +357,7 @@ fn artifacts_write_transcript() {
 
     let result = writer.write_transcript("Second line\n");
     assert!(result.is_ok(), "Should append transcript");
+    writer.end_step().expect("Failed to flush at step boundary");
 
     let transcript_path = dir.join("transcript.log");
     let content = fs::read_to_string(&transcript_path).expect("Failed to read transcript");
@@ -270,6 +366,186 @@ fn artifacts_write_transcript() {
     cleanup_dir(&dir);
 }
 
+#[test]
+fn artifacts_stderr_log_created_on_first_write() {
+    let dir = temp_artifacts_dir();
+    let config = ArtifactsWriterConfig {
+        dir: dir.clone(),
+        overwrite: false,
+    };
+    let mut writer = ArtifactsWriter::new(RunId::new(), config).expect("Failed to create writer");
+    writer.write_stderr("").expect("empty write");
+    writer.end_step().expect("flush");
+    assert!(!dir.join("stderr.log").exists());
+
+    writer.write_stderr("warning: one\n").expect("write");
+    writer.write_stderr("warning: two\n").expect("write");
+    writer.flush_checksums().expect("flush checksums");
+
+    let content = fs::read_to_string(dir.join("stderr.log")).expect("read stderr log");
+    assert_eq!(content, "warning: one\nwarning: two\n");
+    let checksums: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(dir.join("checksums.json")).expect("read checksums"),
+    )
+    .expect("parse checksums");
+    assert!(checksums.get("stderr.log").is_some());
+
+    cleanup_dir(&dir);
+}
+
+#[test]
+fn artifacts_transcript_buffered_until_step_boundary() {
+    let dir = temp_artifacts_dir();
+    let config = ArtifactsWriterConfig {
+        dir: dir.clone(),
+        overwrite: false,
+    };
+    let mut writer = ArtifactsWriter::new(RunId::new(), config).expect("Failed to create writer");
+
+    writer.begin_step(1, "first").expect("begin step");
+    writer.write_transcript("fast step\n").expect("write");
+    writer.begin_step(2, "second").expect("begin step");
+
+    let content = fs::read_to_string(dir.join("transcript.log")).expect("read transcript");
+    assert_eq!(content, "fast step\n");
+
+    cleanup_dir(&dir);
+}
+
+#[test]
+fn artifacts_head_tail_transcript_keeps_both_ends() {
+    let dir = temp_artifacts_dir();
+    let config = ArtifactsWriterConfig {
+        dir: dir.clone(),
+        overwrite: false,
+    };
+    let mut writer = ArtifactsWriter::new(RunId::new(), config).expect("Failed to create writer");
+    writer.set_transcript(&TranscriptPolicy {
+        truncate: TranscriptTruncate::HeadTail,
+        head_kib: 1,
+        tail_kib: 1,
+    });
+
+    // 4000 lines of 5 bytes: 20000 bytes, 2048 of which are kept.
+    for line in 0..4000 {
+        writer
+            .write_transcript(&format!("{line:04}\n"))
+            .expect("write");
+    }
+    // Multi-byte output straddling the tail cut stays valid UTF-8.
+    writer.write_transcript(&"é".repeat(700)).expect("write");
+    writer.write_transcript("x").expect("write");
+    writer.flush_checksums().expect("flush");
+
+    let content = fs::read_to_string(dir.join("transcript.log")).expect("read transcript");
+    assert!(content.starts_with("0000\n0001\n"));
+    assert!(content.ends_with(&format!("{}x", "é".repeat(511))));
+    let marker = "\n[ptybox: transcript truncated: 19354 of 21401 bytes elided]\n";
+    assert!(content.contains(marker), "missing marker in {content:?}");
+    assert_eq!(content.len(), 1024 + marker.len() + 1023);
+
+    cleanup_dir(&dir);
+}
+
+#[test]
+fn artifacts_head_tail_transcript_without_overflow_is_unchanged() {
+    let dir = temp_artifacts_dir();
+    let config = ArtifactsWriterConfig {
+        dir: dir.clone(),
+        overwrite: false,
+    };
+    let mut writer = ArtifactsWriter::new(RunId::new(), config).expect("Failed to create writer");
+    writer.set_transcript(&TranscriptPolicy {
+        truncate: TranscriptTruncate::HeadTail,
+        head_kib: 0,
+        tail_kib: 1,
+    });
+    writer.write_transcript("short output\n").expect("write");
+    drop(writer);
+
+    let content = fs::read_to_string(dir.join("transcript.log")).expect("read transcript");
+    assert_eq!(content, "short output\n");
+
+    cleanup_dir(&dir);
+}
+
+// =============================================================================
+// Layout Tests
+// =============================================================================
+
+fn test_snapshot() -> ScreenSnapshot {
+    ScreenSnapshot {
+        snapshot_version: SNAPSHOT_VERSION,
+        snapshot_id: SnapshotId::new(),
+        rows: 24,
+        cols: 80,
+        cursor: Cursor {
+            row: 0,
+            col: 0,
+            visible: true,
+        },
+        alternate_screen: false,
+        lines: vec!["test".to_string()],
+        cells: None,
+    }
+}
+
+#[test]
+fn artifacts_per_step_layout_writes_step_directories() {
+    let dir = temp_artifacts_dir();
+    let config = ArtifactsWriterConfig {
+        dir: dir.clone(),
+        overwrite: false,
+    };
+    let mut writer = ArtifactsWriter::new(RunId::new(), config).expect("Failed to create writer");
+    writer.set_layout(ArtifactsLayout::PerStep);
+
+    writer.begin_step(1, "open menu").expect("begin step");
+    writer.write_snapshot(&test_snapshot()).expect("snapshot");
+    writer.begin_step(2, "quit").expect("begin step");
+    writer.write_snapshot(&test_snapshot()).expect("snapshot");
+    writer.end_step().expect("end step");
+
+    assert!(dir
+        .join("steps/001-open-menu/snapshots/000001.json")
+        .exists());
+    assert!(dir.join("steps/001-open-menu/events.jsonl").exists());
+    assert!(dir.join("steps/002-quit/snapshots/000002.json").exists());
+    assert!(!dir.join("snapshots/000001.json").exists());
+
+    let paths = snapshot_paths(&dir).expect("list snapshots");
+    let names: Vec<_> = paths
+        .iter()
+        .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+        .collect();
+    assert_eq!(names, vec!["000001.json", "000002.json"]);
+    assert_eq!(event_log_paths(&dir).expect("list events").len(), 3);
+
+    cleanup_dir(&dir);
+}
+
+#[test]
+fn artifacts_step_directories_are_read_in_numeric_order() {
+    let dir = temp_artifacts_dir();
+    for step in ["1000-last", "101-middle", "999-late", "002-first"] {
+        let step_dir = dir.join("steps").join(step);
+        fs::create_dir_all(&step_dir).expect("create step dir");
+        fs::write(step_dir.join("events.jsonl"), "").expect("write events");
+    }
+
+    let steps: Vec<_> = event_log_paths(&dir)
+        .expect("list events")
+        .iter()
+        .map(|path| {
+            let step = path.parent().unwrap().file_name().unwrap();
+            step.to_string_lossy().to_string()
+        })
+        .collect();
+    assert_eq!(steps, ["002-first", "101-middle", "999-late", "1000-last"]);
+
+    cleanup_dir(&dir);
+}
+
 // =============================================================================
 // Checksum Tests
 // =============================================================================
@@ -348,6 +624,38 @@ fn artifacts_checksum_correctness() {
     cleanup_dir(&dir);
 }
 
+#[test]
+fn artifacts_container_invocation_is_checksummed() {
+    let dir = temp_artifacts_dir();
+    let config = ArtifactsWriterConfig {
+        dir: dir.clone(),
+        overwrite: false,
+    };
+    let mut writer = ArtifactsWriter::new(RunId::new(), config).expect("create writer");
+    let invocation = ContainerInvocation {
+        runtime: ContainerRuntime::Podman,
+        image: "docker.io/library/alpine:3.20".to_string(),
+        program: "/usr/bin/podman".to_string(),
+        args: vec!["run".to_string(), "--rm".to_string()],
+    };
+    writer
+        .write_container_invocation(&invocation)
+        .expect("container invocation");
+    writer.flush_checksums().expect("flush");
+
+    let written = fs::read(dir.join("container.json")).expect("read container.json");
+    let parsed: ContainerInvocation = serde_json::from_slice(&written).unwrap();
+    assert_eq!(parsed, invocation);
+    let checksums: serde_json::Value =
+        serde_json::from_slice(&fs::read(dir.join("checksums.json")).unwrap()).unwrap();
+    assert_eq!(
+        checksums["container.json"],
+        format!("{:016x}", compute_fnv1a_hash(&written))
+    );
+
+    cleanup_dir(&dir);
+}
+
 #[test]
 fn artifacts_checksum_updates_on_write() {
     let dir = temp_artifacts_dir();
@@ -435,3 +743,412 @@ fn artifacts_checksum_not_self_referential() {
 
     cleanup_dir(&dir);
 }
+
+// =============================================================================
+// Background I/O Tests
+// =============================================================================
+
+#[test]
+fn artifacts_background_io_keeps_write_order_and_checksums() {
+    let dir = temp_artifacts_dir();
+    let config = ArtifactsWriterConfig {
+        dir: dir.clone(),
+        overwrite: false,
+    };
+    let mut writer = ArtifactsWriter::new(RunId::new(), config).expect("Failed to create writer");
+
+    // More writes than the queue holds, so some calls wait for the thread.
+    for n in 0..2000 {
+        writer
+            .write_transcript(&format!("{n}\n"))
+            .expect("transcript");
+        writer
+            .write_json_line("lines.jsonl", &serde_json::json!({ "n": n }))
+            .expect("json line");
+    }
+    writer.flush_checksums().expect("flush");
+
+    let transcript = fs::read_to_string(dir.join("transcript.log")).expect("read transcript");
+    let lines = fs::read_to_string(dir.join("lines.jsonl")).expect("read lines");
+    for (n, (output, line)) in transcript.lines().zip(lines.lines()).enumerate() {
+        assert_eq!(output, n.to_string());
+        assert_eq!(line, format!("{{\"n\":{n}}}"));
+    }
+    assert_eq!(transcript.lines().count(), 2000);
+
+    let checksums: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.join("checksums.json")).unwrap()).unwrap();
+    for (name, content) in [("transcript.log", &transcript), ("lines.jsonl", &lines)] {
+        assert_eq!(
+            checksums[name],
+            format!("{:016x}", compute_fnv1a_hash(content.as_bytes())),
+            "{name}"
+        );
+    }
+
+    cleanup_dir(&dir);
+}
+
+#[test]
+fn artifacts_background_write_error_surfaces_at_step_boundary() {
+    let dir = temp_artifacts_dir();
+    let config = ArtifactsWriterConfig {
+        dir: dir.clone(),
+        overwrite: false,
+    };
+    let mut writer = ArtifactsWriter::new(RunId::new(), config).expect("Failed to create writer");
+    // A file where the snapshots directory belongs makes snapshot writes fail.
+    fs::write(dir.join("snapshots"), "").expect("block snapshots dir");
+
+    writer.begin_step(1, "first").expect("begin step");
+    writer
+        .write_snapshot(&test_snapshot())
+        .expect("snapshot is queued");
+    writer.write_transcript("after\n").expect("transcript");
+    let err = writer.end_step().expect_err("queued write failed");
+    assert_eq!(err.code, ErrorCode::Io);
+
+    // The error is reported once; later writes are unaffected.
+    writer.end_step().expect("no new errors");
+    let content = fs::read_to_string(dir.join("transcript.log")).expect("read transcript");
+    assert_eq!(content, "after\n");
+
+    writer.write_snapshot(&test_snapshot()).expect("queued");
+    let err = writer.flush_checksums().expect_err("flush reports failure");
+    assert_eq!(err.code, ErrorCode::Io);
+
+    cleanup_dir(&dir);
+}
+
+#[test]
+fn artifacts_inline_io_reports_write_errors_immediately() {
+    let dir = temp_artifacts_dir();
+    let config = ArtifactsWriterConfig {
+        dir: dir.clone(),
+        overwrite: false,
+    };
+    let mut writer = ArtifactsWriter::new(RunId::new(), config).expect("Failed to create writer");
+    writer.write_transcript("queued\n").expect("transcript");
+    writer.set_background_io(false).expect("switch to inline");
+    fs::write(dir.join("snapshots"), "").expect("block snapshots dir");
+
+    let err = writer
+        .write_snapshot(&test_snapshot())
+        .expect_err("inline write fails");
+    assert_eq!(err.code, ErrorCode::Io);
+
+    writer.write_transcript("inline\n").expect("transcript");
+    writer.set_background_io(true).expect("switch back");
+    writer.write_transcript("background\n").expect("transcript");
+    writer.end_step().expect("flush");
+    let content = fs::read_to_string(dir.join("transcript.log")).expect("read transcript");
+    assert_eq!(content, "queued\ninline\nbackground\n");
+
+    cleanup_dir(&dir);
+}
+
+// =============================================================================
+// Encryption Tests
+// =============================================================================
+
+/// Stand-in for the `age` binary: "encrypts" with rot13 behind a header line
+/// and refuses to decrypt without an existing identity file.
+fn write_fake_age(dir: &Path) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+    let path = dir.join("fake-age");
+    fs::write(
+        &path,
+        r#"#!/bin/sh
+mode=; out=; id=; in=
+while [ $# -gt 0 ]; do
+  case "$1" in
+    --encrypt) mode=enc ;;
+    --decrypt) mode=dec ;;
+    --recipient) shift ;;
+    --identity) shift; id=$1 ;;
+    --output) shift; out=$1 ;;
+    *) in=$1 ;;
+  esac
+  shift
+done
+if [ "$mode" = enc ]; then
+  { echo "age-encryption.org/v1"; tr 'A-Za-z' 'N-ZA-Mn-za-m'; } > "$out"
+else
+  [ -f "$id" ] || { echo "no identity" >&2; exit 1; }
+  tail -n +2 "$in" | tr 'A-Za-z' 'N-ZA-Mn-za-m'
+fi
+"#,
+    )
+    .expect("write fake age");
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).expect("chmod fake age");
+    path
+}
+
+#[test]
+fn artifacts_encryption_writes_age_files_and_decrypts() {
+    let dir = temp_artifacts_dir();
+    let tools = temp_artifacts_dir();
+    fs::create_dir_all(&tools).expect("tools dir");
+    let age = write_fake_age(&tools);
+    let identity = tools.join("identity.txt");
+    fs::write(&identity, "AGE-SECRET-KEY-1TEST\n").expect("identity");
+
+    let config = ArtifactsWriterConfig {
+        dir: dir.clone(),
+        overwrite: false,
+    };
+    let encryption = ArtifactsEncryption {
+        recipients: vec!["age1qqqqqqqqqqqqqqqqqqqq".to_string()],
+        age_binary: age.display().to_string(),
+    };
+    let mut policy = Policy::default();
+    policy.artifacts.encryption = Some(encryption.clone());
+    let mut writer = ArtifactsWriter::new(RunId::new(), config).expect("create writer");
+    writer
+        .set_encryption(&encryption)
+        .expect("enable encryption");
+    writer.write_policy(&policy).expect("policy");
+    writer.write_snapshot(&test_snapshot()).expect("snapshot");
+    writer
+        .write_transcript("secret output\n")
+        .expect("transcript");
+    writer.flush_checksums().expect("flush");
+
+    assert!(!dir.join("transcript.log").exists());
+    assert!(!dir.join("events.jsonl").exists());
+    assert!(dir.join("policy.json").exists());
+    let transcript = dir.join("transcript.log.age");
+    let ciphertext = fs::read_to_string(&transcript).expect("read ciphertext");
+    assert!(!ciphertext.contains("secret"));
+
+    let checksums: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.join("checksums.json")).unwrap()).unwrap();
+    assert_eq!(
+        checksums["transcript.log.age"],
+        format!("{:016x}", compute_fnv1a_hash(ciphertext.as_bytes()))
+    );
+    assert!(checksums.get("snapshots/000001.json.age").is_some());
+    assert!(checksums.get("transcript.log").is_none());
+
+    let snapshots = snapshot_paths(&dir).expect("list snapshots");
+    assert_eq!(snapshots, vec![dir.join("snapshots/000001.json.age")]);
+    assert_eq!(
+        event_log_paths(&dir).expect("list events"),
+        vec![dir.join("events.jsonl.age")]
+    );
+
+    let err = read_artifact(&transcript, None).expect_err("identity required");
+    assert_eq!(err.code, ErrorCode::PolicyDenied);
+    let decryptor = ArtifactsDecryptor::new(&identity, Some(age.clone()));
+    let plaintext = read_artifact(&transcript, Some(&decryptor)).expect("decrypt transcript");
+    assert_eq!(plaintext, b"secret output\n");
+    let snapshot: ScreenSnapshot =
+        serde_json::from_slice(&read_artifact(&snapshots[0], Some(&decryptor)).unwrap()).unwrap();
+    assert_eq!(snapshot.lines, vec!["test".to_string()]);
+    let missing_identity = ArtifactsDecryptor::new(tools.join("missing"), Some(age));
+    assert!(missing_identity.decrypt(&transcript).is_err());
+
+    cleanup_dir(&dir);
+    cleanup_dir(&tools);
+}
+
+#[test]
+fn artifacts_encryption_covers_emulator_diff() {
+    let dir = temp_artifacts_dir();
+    let tools = temp_artifacts_dir();
+    fs::create_dir_all(&tools).expect("tools dir");
+    let age = write_fake_age(&tools);
+    let identity = tools.join("identity.txt");
+    fs::write(&identity, "AGE-SECRET-KEY-1TEST\n").expect("identity");
+
+    let config = ArtifactsWriterConfig {
+        dir: dir.clone(),
+        overwrite: false,
+    };
+    let mut writer = ArtifactsWriter::new(RunId::new(), config).expect("create writer");
+    writer
+        .set_encryption(&ArtifactsEncryption {
+            recipients: vec!["age1qqqqqqqqqqqqqqqqqqqq".to_string()],
+            age_binary: age.display().to_string(),
+        })
+        .expect("enable encryption");
+    let report = EmulatorDiffReport {
+        primary: "secret primary".to_string(),
+        divergences: 1,
+        ..EmulatorDiffReport::default()
+    };
+    writer.write_emulator_diff(&report).expect("emulator diff");
+    writer.flush_checksums().expect("flush");
+
+    assert!(!dir.join("emulator-diff.json").exists());
+    let path = dir.join("emulator-diff.json.age");
+    assert!(!fs::read_to_string(&path).unwrap().contains("secret"));
+    let decryptor = ArtifactsDecryptor::new(&identity, Some(age));
+    let decrypted: EmulatorDiffReport =
+        serde_json::from_slice(&read_artifact(&path, Some(&decryptor)).unwrap()).unwrap();
+    assert_eq!(decrypted, report);
+
+    cleanup_dir(&dir);
+    cleanup_dir(&tools);
+}
+
+/// A failed run whose error contexts, assertion details, and step variables
+/// all quote `secret`.
+fn run_result_quoting(secret: &str) -> RunResult {
+    let failed = json!({
+        "type": "file_contains",
+        "passed": false,
+        "message": "file does not contain the expected text",
+        "details": { "content": secret },
+    });
+    let error = json!({
+        "code": "E_ASSERTION_FAILED",
+        "message": "assertion failed",
+        "context": { "snapshot": secret },
+    });
+    serde_json::from_value(json!({
+        "run_result_version": 1,
+        "protocol_version": 2,
+        "run_id": RunId::new(),
+        "status": "failed",
+        "started_at_ms": 0,
+        "ended_at_ms": 1,
+        "command": "/bin/true",
+        "args": [],
+        "cwd": "/",
+        "policy": Policy::default(),
+        "scenario": null,
+        "steps": [{
+            "step_id": ptybox::model::StepId::new(),
+            "name": "check",
+            "status": "failed",
+            "attempts": 1,
+            "started_at_ms": 0,
+            "ended_at_ms": 1,
+            "action": { "type": "observe", "payload": {} },
+            "assertions": [failed],
+            "error": error,
+            "vars": { "token": secret },
+            "resolved_vars": { "token": secret },
+        }],
+        "final_observation": null,
+        "exit_status": null,
+        "error": error,
+        "post_run": [failed],
+    }))
+    .expect("run result")
+}
+
+#[test]
+fn artifacts_encryption_scrubs_plaintext_run_result_and_journal() {
+    let dir = temp_artifacts_dir();
+    let tools = temp_artifacts_dir();
+    fs::create_dir_all(&tools).expect("tools dir");
+    let age = write_fake_age(&tools);
+
+    let config = ArtifactsWriterConfig {
+        dir: dir.clone(),
+        overwrite: false,
+    };
+    let mut writer = ArtifactsWriter::new(RunId::new(), config).expect("create writer");
+    writer
+        .set_encryption(&ArtifactsEncryption {
+            recipients: vec!["age1qqqqqqqqqqqqqqqqqqqq".to_string()],
+            age_binary: age.display().to_string(),
+        })
+        .expect("enable encryption");
+    let run_result = run_result_quoting("secret screen text");
+    let step = run_result.steps.as_ref().unwrap()[0].clone();
+    writer
+        .journal(
+            1,
+            JournalEvent::StepFinished {
+                step_index: 0,
+                result: Box::new(step),
+            },
+        )
+        .expect("journal");
+    writer.write_run_result(&run_result).expect("run result");
+    writer.flush_checksums().expect("flush");
+
+    let written: Value =
+        serde_json::from_str(&fs::read_to_string(dir.join("run.json")).unwrap()).unwrap();
+    assert_eq!(written["error"]["code"], "E_ASSERTION_FAILED");
+    assert!(written["error"]["context"].is_null());
+    assert!(written["steps"][0]["error"]["context"].is_null());
+    assert_eq!(written["post_run"][0]["type"], "file_contains");
+    assert!(written["post_run"][0]["details"].is_null());
+    assert!(written["steps"][0]["assertions"][0]["details"].is_null());
+    assert!(written["steps"][0].get("vars").is_none());
+    assert!(written["steps"][0].get("resolved_vars").is_none());
+    for name in ["run.json", "journal.jsonl"] {
+        let content = fs::read_to_string(dir.join(name)).unwrap();
+        assert!(!content.contains("secret"), "{name}: {content}");
+    }
+
+    cleanup_dir(&dir);
+    cleanup_dir(&tools);
+}
+
+#[test]
+fn sha256_hex_matches_known_vectors() {
+    assert_eq!(
+        ptybox::util::sha256_hex(b""),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+    assert_eq!(
+        ptybox::util::sha256_hex(b"abc"),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+    // 56 bytes: padding spills into a second block.
+    assert_eq!(
+        ptybox::util::sha256_hex(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+        "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+    );
+    assert_eq!(
+        ptybox::util::sha256_hex(&[b'a'; 1000]),
+        "41edece42d63e8d9bf515a9ba6932e1c20cbc9f5a5d134645adb5db1b9737ea3"
+    );
+}
+
+#[test]
+fn sha256_incremental_matches_one_shot_digest() {
+    let data: Vec<u8> = (0..1000u32).map(|n| (n % 251) as u8).collect();
+    for piece in [1, 7, 63, 64, 65, 200] {
+        let mut hasher = ptybox::util::Sha256::new();
+        for chunk in data.chunks(piece) {
+            hasher.update(chunk);
+        }
+        assert_eq!(
+            hasher.finish_hex(),
+            ptybox::util::sha256_hex(&data),
+            "{piece}"
+        );
+    }
+}
+
+#[test]
+fn path_glob_matches_keeps_single_stars_within_a_directory() {
+    let cases = [
+        ("src/*", "src/main.rs", true),
+        ("src/*", "src/cli/main.rs", false),
+        ("*.md", "README.md", true),
+        ("*.md", "docs/guide.md", false),
+        ("src/**", "src/cli/args/parse.rs", true),
+        ("src/**/*.rs", "src/main.rs", true),
+        ("src/**/*.rs", "src/cli/args/parse.rs", true),
+        ("src/**/*.rs", "src/cli/args/parse.txt", false),
+        ("**/mod.rs", "src/serve/mod.rs", true),
+        ("src/?/lib.rs", "src/a/lib.rs", true),
+        ("src/?/lib.rs", "src/a/b/lib.rs", false),
+        ("src/ui/login*", "src/ui/login_form.rs", true),
+        ("src/ui/login*", "src/ui/login/form.rs", false),
+    ];
+    for (pattern, path, expected) in cases {
+        assert_eq!(
+            ptybox::util::path_glob_matches(pattern, path),
+            expected,
+            "{pattern} {path}"
+        );
+    }
+}
@@ -12,7 +12,7 @@
 #![allow(clippy::cast_possible_truncation)]
 #![allow(missing_docs)]
 
-use ptybox::assertions::evaluate;
+use ptybox::assertions::{evaluate, evaluate_in_context, stability_window_ms, AssertionContext};
 use ptybox::model::scenario::Assertion;
 use ptybox::model::PROTOCOL_VERSION;
 use ptybox::model::{Cursor, Observation, RunId, ScreenSnapshot, SnapshotId};
@@ -39,6 +39,12 @@ fn observation_with_lines(lines: &[&str]) -> Observation {
         },
         transcript_delta: None,
         events: Vec::new(),
+        stderr_delta: None,
+        output_flow: None,
+        read_dropped_bytes: 0,
+        widgets: Vec::new(),
+        read_bytes: 0,
+        read_truncated: false,
     }
 }
 
@@ -48,6 +54,7 @@ fn screen_contains_passes_when_text_present() {
     let assertion = Assertion {
         assertion_type: "screen_contains".to_string(),
         payload: serde_json::json!({"text": "world"}),
+        inherited: false,
     };
 
     let (passed, message, _) = evaluate(&observation, &assertion);
@@ -64,6 +71,7 @@ fn cursor_at_fails_when_position_mismatch() {
     let assertion = Assertion {
         assertion_type: "cursor_at".to_string(),
         payload: serde_json::json!({"row": 0, "col": 0}),
+        inherited: false,
     };
 
     let (passed, message, _) = evaluate(&observation, &assertion);
@@ -86,6 +94,7 @@ fn regex_match_passes_when_pattern_matches() {
     let assertion = Assertion {
         assertion_type: "regex_match".to_string(),
         payload: serde_json::json!({"pattern": "hello\\s+world"}),
+        inherited: false,
     };
 
     let (passed, message, _) = evaluate(&observation, &assertion);
@@ -101,6 +110,7 @@ fn line_equals_passes_when_line_matches_exactly() {
     let assertion = Assertion {
         assertion_type: "line_equals".to_string(),
         payload: serde_json::json!({"line": 1, "text": "second line"}),
+        inherited: false,
     };
 
     let (passed, message, _) = evaluate(&observation, &assertion);
@@ -114,6 +124,7 @@ fn line_equals_fails_when_line_differs() {
     let assertion = Assertion {
         assertion_type: "line_equals".to_string(),
         payload: serde_json::json!({"line": 0, "text": "wrong text"}),
+        inherited: false,
     };
 
     let (passed, message, _) = evaluate(&observation, &assertion);
@@ -136,6 +147,7 @@ fn line_equals_fails_when_line_out_of_bounds() {
     let assertion = Assertion {
         assertion_type: "line_equals".to_string(),
         payload: serde_json::json!({"line": 99, "text": "anything"}),
+        inherited: false,
     };
 
     let (passed, message, _) = evaluate(&observation, &assertion);
@@ -152,6 +164,7 @@ fn line_contains_passes_when_substring_present() {
     let assertion = Assertion {
         assertion_type: "line_contains".to_string(),
         payload: serde_json::json!({"line": 0, "text": "hello"}),
+        inherited: false,
     };
 
     let (passed, message, _) = evaluate(&observation, &assertion);
@@ -165,6 +178,7 @@ fn line_contains_fails_when_substring_absent() {
     let assertion = Assertion {
         assertion_type: "line_contains".to_string(),
         payload: serde_json::json!({"line": 0, "text": "missing"}),
+        inherited: false,
     };
 
     let (passed, message, _) = evaluate(&observation, &assertion);
@@ -186,6 +200,7 @@ fn line_matches_passes_when_regex_matches() {
     let assertion = Assertion {
         assertion_type: "line_matches".to_string(),
         payload: serde_json::json!({"line": 0, "pattern": "user:\\s+\\w+"}),
+        inherited: false,
     };
 
     let (passed, message, _) = evaluate(&observation, &assertion);
@@ -199,6 +214,7 @@ fn line_matches_fails_with_invalid_regex() {
     let assertion = Assertion {
         assertion_type: "line_matches".to_string(),
         payload: serde_json::json!({"line": 0, "pattern": "[invalid"}),
+        inherited: false,
     };
 
     let (passed, message, _) = evaluate(&observation, &assertion);
@@ -215,6 +231,7 @@ fn not_contains_passes_when_text_absent() {
     let assertion = Assertion {
         assertion_type: "not_contains".to_string(),
         payload: serde_json::json!({"text": "goodbye"}),
+        inherited: false,
     };
 
     let (passed, message, _) = evaluate(&observation, &assertion);
@@ -228,6 +245,7 @@ fn not_contains_fails_when_text_present() {
     let assertion = Assertion {
         assertion_type: "not_contains".to_string(),
         payload: serde_json::json!({"text": "hello"}),
+        inherited: false,
     };
 
     let (passed, message, _) = evaluate(&observation, &assertion);
@@ -244,6 +262,35 @@ fn not_contains_fails_when_text_present() {
     );
 }
 
+// ============ screen_not_contains tests ============
+
+#[test]
+fn screen_not_contains_failure_records_offending_snapshot() {
+    let observation = observation_with_lines(&["fatal: disk full"]);
+    let assertion = Assertion::screen_not_contains("fatal", Some(500));
+
+    let (passed, message, details) = evaluate(&observation, &assertion);
+    assert!(!passed);
+    assert!(message.unwrap().contains("fatal"));
+    let details = details.expect("details should be recorded");
+    assert_eq!(details["text"], "fatal");
+    assert_eq!(details["snapshot"]["lines"][0], "fatal: disk full");
+}
+
+#[test]
+fn screen_not_contains_passes_and_reports_window() {
+    let observation = observation_with_lines(&["all good"]);
+    let windowed = Assertion::screen_not_contains("fatal", Some(250));
+    let (passed, _, _) = evaluate(&observation, &windowed);
+    assert!(passed);
+    assert_eq!(stability_window_ms(&windowed), Some(250));
+    assert_eq!(
+        stability_window_ms(&Assertion::screen_not_contains("fatal", None)),
+        None
+    );
+    assert_eq!(stability_window_ms(&Assertion::not_contains("fatal")), None);
+}
+
 // ============ screen_empty tests ============
 
 #[test]
@@ -252,6 +299,7 @@ fn screen_empty_passes_when_all_whitespace() {
     let assertion = Assertion {
         assertion_type: "screen_empty".to_string(),
         payload: serde_json::json!({}),
+        inherited: false,
     };
 
     let (passed, message, _) = evaluate(&observation, &assertion);
@@ -265,6 +313,7 @@ fn screen_empty_fails_when_content_present() {
     let assertion = Assertion {
         assertion_type: "screen_empty".to_string(),
         payload: serde_json::json!({}),
+        inherited: false,
     };
 
     let (passed, message, _) = evaluate(&observation, &assertion);
@@ -287,6 +336,7 @@ fn cursor_visible_passes_when_visible() {
     let assertion = Assertion {
         assertion_type: "cursor_visible".to_string(),
         payload: serde_json::json!({}),
+        inherited: false,
     };
 
     let (passed, message, _) = evaluate(&observation, &assertion);
@@ -301,6 +351,7 @@ fn cursor_visible_fails_when_hidden() {
     let assertion = Assertion {
         assertion_type: "cursor_visible".to_string(),
         payload: serde_json::json!({}),
+        inherited: false,
     };
 
     let (passed, message, _) = evaluate(&observation, &assertion);
@@ -323,6 +374,7 @@ fn cursor_hidden_passes_when_hidden() {
     let assertion = Assertion {
         assertion_type: "cursor_hidden".to_string(),
         payload: serde_json::json!({}),
+        inherited: false,
     };
 
     let (passed, message, _) = evaluate(&observation, &assertion);
@@ -337,6 +389,7 @@ fn cursor_hidden_fails_when_visible() {
     let assertion = Assertion {
         assertion_type: "cursor_hidden".to_string(),
         payload: serde_json::json!({}),
+        inherited: false,
     };
 
     let (passed, message, _) = evaluate(&observation, &assertion);
@@ -360,6 +413,7 @@ fn test_screen_contains_case_sensitive() {
     let assertion_exact = Assertion {
         assertion_type: "screen_contains".to_string(),
         payload: serde_json::json!({"text": "Hello"}),
+        inherited: false,
     };
     let (passed, message, _) = evaluate(&observation, &assertion_exact);
     assert!(passed, "Exact case should match: {:?}", message);
@@ -368,6 +422,7 @@ fn test_screen_contains_case_sensitive() {
     let assertion_wrong_case = Assertion {
         assertion_type: "screen_contains".to_string(),
         payload: serde_json::json!({"text": "hello"}),
+        inherited: false,
     };
     let (passed, message, _) = evaluate(&observation, &assertion_wrong_case);
     assert!(
@@ -387,6 +442,7 @@ fn test_screen_contains_partial_match() {
     let assertion_begin = Assertion {
         assertion_type: "screen_contains".to_string(),
         payload: serde_json::json!({"text": "The quick"}),
+        inherited: false,
     };
     let (passed, _, _) = evaluate(&observation, &assertion_begin);
     assert!(passed, "Should match partial at beginning");
@@ -395,6 +451,7 @@ fn test_screen_contains_partial_match() {
     let assertion_middle = Assertion {
         assertion_type: "screen_contains".to_string(),
         payload: serde_json::json!({"text": "brown fox"}),
+        inherited: false,
     };
     let (passed, _, _) = evaluate(&observation, &assertion_middle);
     assert!(passed, "Should match partial in middle");
@@ -403,6 +460,7 @@ fn test_screen_contains_partial_match() {
     let assertion_end = Assertion {
         assertion_type: "screen_contains".to_string(),
         payload: serde_json::json!({"text": "lazy dog"}),
+        inherited: false,
     };
     let (passed, _, _) = evaluate(&observation, &assertion_end);
     assert!(passed, "Should match partial at end");
@@ -411,6 +469,7 @@ fn test_screen_contains_partial_match() {
     let assertion_char = Assertion {
         assertion_type: "screen_contains".to_string(),
         payload: serde_json::json!({"text": "x"}),
+        inherited: false,
     };
     let (passed, _, _) = evaluate(&observation, &assertion_char);
     assert!(passed, "Should match single character");
@@ -426,6 +485,7 @@ fn test_screen_contains_unicode() {
     let assertion_accent = Assertion {
         assertion_type: "screen_contains".to_string(),
         payload: serde_json::json!({"text": "monde"}),
+        inherited: false,
     };
     let (passed, _, _) = evaluate(&observation, &assertion_accent);
     assert!(passed, "Should match unicode text");
@@ -434,6 +494,7 @@ fn test_screen_contains_unicode() {
     let assertion_welt = Assertion {
         assertion_type: "screen_contains".to_string(),
         payload: serde_json::json!({"text": "Welt"}),
+        inherited: false,
     };
     let (passed, _, _) = evaluate(&observation, &assertion_welt);
     assert!(passed, "Should find unicode on any line");
@@ -451,6 +512,7 @@ fn test_screen_contains_unicode_special_chars() {
     let assertion_symbols = Assertion {
         assertion_type: "screen_contains".to_string(),
         payload: serde_json::json!({"text": "Symbols"}),
+        inherited: false,
     };
     let (passed, _, _) = evaluate(&observation, &assertion_symbols);
     assert!(passed, "Should match text in unicode context");
@@ -458,6 +520,7 @@ fn test_screen_contains_unicode_special_chars() {
     let assertion_arrows = Assertion {
         assertion_type: "screen_contains".to_string(),
         payload: serde_json::json!({"text": "arrows and math"}),
+        inherited: false,
     };
     let (passed, _, _) = evaluate(&observation, &assertion_arrows);
     assert!(passed, "Should match multi-word substring");
@@ -473,6 +536,7 @@ fn test_line_equals_exact_vs_contains() {
     let assertion_exact = Assertion {
         assertion_type: "line_equals".to_string(),
         payload: serde_json::json!({"line": 0, "text": "hello world"}),
+        inherited: false,
     };
     let (passed, _, _) = evaluate(&observation, &assertion_exact);
     assert!(passed, "Exact match should pass");
@@ -481,6 +545,7 @@ fn test_line_equals_exact_vs_contains() {
     let assertion_partial = Assertion {
         assertion_type: "line_equals".to_string(),
         payload: serde_json::json!({"line": 0, "text": "hello"}),
+        inherited: false,
     };
     let (passed, message, _) = evaluate(&observation, &assertion_partial);
     assert!(
@@ -493,6 +558,7 @@ fn test_line_equals_exact_vs_contains() {
     let assertion_contains = Assertion {
         assertion_type: "line_contains".to_string(),
         payload: serde_json::json!({"line": 0, "text": "hello"}),
+        inherited: false,
     };
     let (passed, _, _) = evaluate(&observation, &assertion_contains);
     assert!(passed, "Partial match should pass for line_contains");
@@ -501,6 +567,7 @@ fn test_line_equals_exact_vs_contains() {
     let assertion_line1_exact = Assertion {
         assertion_type: "line_equals".to_string(),
         payload: serde_json::json!({"line": 1, "text": "hello"}),
+        inherited: false,
     };
     let (passed, _, _) = evaluate(&observation, &assertion_line1_exact);
     assert!(passed, "Line 1 exact match with 'hello' should pass");
@@ -521,6 +588,7 @@ fn test_regex_matches_complex_pattern() {
     let assertion_email = Assertion {
         assertion_type: "regex_match".to_string(),
         payload: serde_json::json!({"pattern": r"(\w+)@(\w+)\.(\w+)"}),
+        inherited: false,
     };
     let (passed, message, _) = evaluate(&observation, &assertion_email);
     assert!(
@@ -533,6 +601,7 @@ fn test_regex_matches_complex_pattern() {
     let assertion_phone = Assertion {
         assertion_type: "regex_match".to_string(),
         payload: serde_json::json!({"pattern": r"\d{3}-\d{3}-\d{4}"}),
+        inherited: false,
     };
     let (passed, _, _) = evaluate(&observation, &assertion_phone);
     assert!(passed, "Phone number pattern should match");
@@ -541,6 +610,7 @@ fn test_regex_matches_complex_pattern() {
     let assertion_date = Assertion {
         assertion_type: "regex_match".to_string(),
         payload: serde_json::json!({"pattern": r"(\d{4})-(\d{2})-(\d{2})"}),
+        inherited: false,
     };
     let (passed, _, _) = evaluate(&observation, &assertion_date);
     assert!(passed, "Date pattern with groups should match");
@@ -549,6 +619,7 @@ fn test_regex_matches_complex_pattern() {
     let assertion_version = Assertion {
         assertion_type: "regex_match".to_string(),
         payload: serde_json::json!({"pattern": r"v(\d+)\.(\d+)\.(\d+)(-\w+)?"}),
+        inherited: false,
     };
     let (passed, _, _) = evaluate(&observation, &assertion_version);
     assert!(passed, "Semantic version pattern should match");
@@ -557,6 +628,7 @@ fn test_regex_matches_complex_pattern() {
     let assertion_alt = Assertion {
         assertion_type: "regex_match".to_string(),
         payload: serde_json::json!({"pattern": r"(Email|Phone|Date|Version):"}),
+        inherited: false,
     };
     let (passed, _, _) = evaluate(&observation, &assertion_alt);
     assert!(passed, "Alternation pattern should match");
@@ -565,7 +637,65 @@ fn test_regex_matches_complex_pattern() {
     let assertion_no_match = Assertion {
         assertion_type: "regex_match".to_string(),
         payload: serde_json::json!({"pattern": r"^\d{5}$"}),
+        inherited: false,
     };
     let (passed, _, _) = evaluate(&observation, &assertion_no_match);
     assert!(!passed, "Non-matching pattern should fail");
 }
+
+#[test]
+fn stderr_contains_checks_captured_stderr() {
+    let observation = observation_with_lines(&["oops not here"]);
+    let assertion = Assertion::stderr_contains("oops");
+    let context = |stderr| AssertionContext {
+        exit_status: None,
+        stderr,
+        latency_ms: None,
+        raw_output: None,
+    };
+
+    let (passed, _, _) = evaluate_in_context(&observation, &assertion, &context(Some("oops\n")));
+    assert!(passed);
+
+    let (passed, message, _) = evaluate_in_context(&observation, &assertion, &context(Some("")));
+    assert!(!passed);
+    assert!(message.unwrap().contains("stderr did not contain"));
+
+    let (passed, message, _) = evaluate(&observation, &assertion);
+    assert!(!passed);
+    assert!(message.unwrap().contains("separate_stderr"));
+}
+
+#[test]
+fn responded_within_ms_compares_measured_latency() {
+    let observation = observation_with_lines(&["ready"]);
+    let assertion = Assertion::responded_within_ms(200);
+    let context = |latency_ms| AssertionContext {
+        latency_ms,
+        ..AssertionContext::default()
+    };
+
+    let (passed, _, details) = evaluate_in_context(&observation, &assertion, &context(Some(120)));
+    assert!(passed);
+    assert_eq!(details.unwrap()["latency_ms"], 120);
+
+    let (passed, message, details) =
+        evaluate_in_context(&observation, &assertion, &context(Some(350)));
+    assert!(!passed);
+    assert!(message.unwrap().contains("responded in 350ms"));
+    assert_eq!(details.unwrap()["budget_ms"], 200);
+
+    let (passed, message, _) = evaluate(&observation, &assertion);
+    assert!(!passed);
+    assert!(message.unwrap().contains("no response measured"));
+
+    let missing_budget = Assertion {
+        assertion_type: "responded_within_ms".to_string(),
+        payload: serde_json::json!({}),
+        inherited: false,
+    };
+    let (passed, message, _) =
+        evaluate_in_context(&observation, &missing_budget, &context(Some(1)));
+    assert!(!passed);
+    assert!(message.unwrap().contains("'ms'"));
+}
@@ -0,0 +1,305 @@
+// Test module - relaxed lint rules
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(missing_docs)]
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use ptybox::model::policy::{
+    ChildSignal, ContainerRuntime, DeterminismPolicy, EnvPolicy, Policy, PolicyBuilder,
+    SandboxMode, TerminalEncoding, DEFAULT_CONTAINER_MEMORY_BYTES, DEFAULT_CONTAINER_PIDS_LIMIT,
+};
+use ptybox::model::{OutputBufferConfig, ResizeConfig, RunId, TerminalSize};
+use ptybox::policy::container::{
+    container_args, container_name, validate_container_policy, ContainerCleanup,
+};
+use ptybox::runner::ErrorCode;
+use ptybox::session::{Session, SessionConfig};
+
+fn container_policy() -> Policy {
+    let mut policy = PolicyBuilder::new()
+        .sandbox_container(
+            ContainerRuntime::Podman,
+            "/usr/bin/podman",
+            "docker.io/library/alpine:3.20",
+        )
+        .allowed_read(vec!["/opt/app".to_string(), "/tmp/work".to_string()])
+        .allowed_write(vec!["/tmp/work".to_string()])
+        .allowed_executables(vec![
+            "/usr/bin/podman".to_string(),
+            "/usr/bin/top".to_string(),
+        ])
+        .build();
+    policy.env = EnvPolicy {
+        allowlist: vec!["TERM".to_string()],
+        set: BTreeMap::from([("TERM".to_string(), "xterm".to_string())]),
+        inherit: false,
+    };
+    policy.determinism = DeterminismPolicy {
+        timezone: Some("UTC".to_string()),
+        ..DeterminismPolicy::default()
+    };
+    policy
+}
+
+/// Values of every occurrence of `flag`.
+fn flag_values<'a>(args: &'a [String], flag: &str) -> Vec<&'a str> {
+    args.windows(2)
+        .filter(|pair| pair[0] == flag)
+        .map(|pair| pair[1].as_str())
+        .collect()
+}
+
+#[test]
+fn container_policy_round_trips_through_policy_json() {
+    let json = serde_json::json!({
+        "policy_version": 4,
+        "sandbox": "container",
+        "container": { "runtime": "docker", "image": "alpine:3.20" },
+        "network": "disabled",
+        "fs": {},
+        "exec": { "allowed_executables": [] },
+        "env": { "allowlist": [], "set": {}, "inherit": false },
+        "budgets": {
+            "max_runtime_ms": 1000,
+            "max_steps": 1,
+            "max_output_bytes": 1024,
+            "max_snapshot_bytes": 1024,
+            "max_wait_ms": 1000
+        },
+        "artifacts": { "enabled": false, "overwrite": false }
+    });
+    let policy: Policy = serde_json::from_value(json).unwrap();
+    assert_eq!(
+        policy.sandbox,
+        SandboxMode::Container {
+            runtime: ContainerRuntime::Docker,
+            image: "alpine:3.20".to_string(),
+            program: "/usr/bin/docker".to_string(),
+            pids_limit: DEFAULT_CONTAINER_PIDS_LIMIT,
+            memory_bytes: DEFAULT_CONTAINER_MEMORY_BYTES,
+        }
+    );
+    let value = serde_json::to_value(&policy).unwrap();
+    assert_eq!(value["sandbox"], "container");
+    assert_eq!(value["container"]["runtime"], "docker");
+    assert_eq!(value["container"]["image"], "alpine:3.20");
+    assert_eq!(value["container"]["program"], "/usr/bin/docker");
+    assert_eq!(value["container"]["pids_limit"], 256);
+
+    let seatbelt = serde_json::to_value(Policy::default()).unwrap();
+    assert!(seatbelt.get("container").is_none());
+}
+
+#[test]
+fn container_args_translate_fs_network_and_env_policy() {
+    let policy = container_policy();
+    let run_id = RunId::new();
+    let args = container_args(
+        &policy,
+        ContainerRuntime::Podman,
+        "docker.io/library/alpine:3.20",
+        "/usr/bin/top",
+        &["-b".to_string()],
+        Some("/tmp/work"),
+        run_id,
+        Some(Path::new("/tmp/ptybox-sockets")),
+    );
+
+    assert_eq!(args[..4], ["run", "--rm", "--interactive", "--tty"]);
+    assert_eq!(flag_values(&args, "--name"), [container_name(run_id)]);
+    assert_eq!(flag_values(&args, "--network"), ["none"]);
+    assert_eq!(flag_values(&args, "--userns"), ["keep-id"]);
+    assert_eq!(flag_values(&args, "--cap-drop"), ["ALL"]);
+    assert_eq!(flag_values(&args, "--pids-limit"), ["256"]);
+    assert_eq!(flag_values(&args, "--memory"), ["1073741824b"]);
+    // A path that is both readable and writable is mounted once, writable.
+    assert_eq!(
+        flag_values(&args, "--volume"),
+        [
+            "/opt/app:/opt/app:ro",
+            "/tmp/work:/tmp/work:rw",
+            "/tmp/ptybox-sockets:/tmp/ptybox-sockets:rw",
+        ]
+    );
+    // Names only: values come from the runtime's filtered environment.
+    assert_eq!(
+        flag_values(&args, "--env"),
+        ["PTYBOX_DEADLINE_MS", "PTYBOX_GRACE_MS", "TERM", "TZ"]
+    );
+    assert!(!args.iter().any(|arg| arg.contains("xterm")), "{args:?}");
+    assert_eq!(flag_values(&args, "--workdir"), ["/tmp/work"]);
+    assert_eq!(
+        args[args.len() - 4..],
+        [
+            "--entrypoint",
+            "/usr/bin/top",
+            "docker.io/library/alpine:3.20",
+            "-b"
+        ]
+    );
+}
+
+#[test]
+fn container_args_keep_network_and_map_user_for_docker() {
+    let mut policy = container_policy();
+    policy.network = ptybox::model::policy::NetworkPolicy::Enabled { ack: true };
+    let args = container_args(
+        &policy,
+        ContainerRuntime::Docker,
+        "alpine",
+        "/bin/sh",
+        &[],
+        None,
+        RunId::new(),
+        None,
+    );
+    assert!(flag_values(&args, "--network").is_empty(), "{args:?}");
+    assert!(flag_values(&args, "--userns").is_empty(), "{args:?}");
+    assert!(flag_values(&args, "--workdir").is_empty(), "{args:?}");
+    let user = flag_values(&args, "--user");
+    assert_eq!(user.len(), 1);
+    assert!(
+        user[0]
+            .split(':')
+            .all(|id| !id.is_empty() && id.parse::<u32>().is_ok()),
+        "{user:?}"
+    );
+}
+
+#[test]
+fn container_policy_rejects_bad_images_and_mount_paths() {
+    let cases = [
+        ("", "/tmp/work"),
+        ("-v", "/tmp/work"),
+        ("alpine latest", "/tmp/work"),
+        ("alpine", "/tmp/a:b"),
+        ("alpine", "/tmp/a,b"),
+    ];
+    for (image, path) in cases {
+        let mut policy = container_policy();
+        if let SandboxMode::Container { image: current, .. } = &mut policy.sandbox {
+            *current = image.to_string();
+        }
+        policy.fs.allowed_write = vec![path.to_string()];
+        let err = validate_container_policy(&policy).unwrap_err();
+        assert_eq!(err.code, ErrorCode::PolicyDenied, "{image:?} {path:?}");
+    }
+    validate_container_policy(&container_policy()).unwrap();
+}
+
+#[test]
+fn container_args_apply_the_policy_limits() {
+    let mut policy = container_policy();
+    if let SandboxMode::Container {
+        pids_limit,
+        memory_bytes,
+        ..
+    } = &mut policy.sandbox
+    {
+        *pids_limit = 64;
+        *memory_bytes = 512 * 1024 * 1024;
+    }
+    let args = container_args(
+        &policy,
+        ContainerRuntime::Podman,
+        "alpine",
+        "/bin/sh",
+        &[],
+        None,
+        RunId::new(),
+        None,
+    );
+    assert_eq!(flag_values(&args, "--pids-limit"), ["64"]);
+    assert_eq!(flag_values(&args, "--memory"), ["536870912b"]);
+    assert_eq!(flag_values(&args, "--network"), ["none"]);
+}
+
+/// Apply `edit` to the container section of a valid policy and validate it.
+fn validate_with(edit: impl FnOnce(&mut String, &mut u32, &mut u64)) -> Option<ErrorCode> {
+    let mut policy = container_policy();
+    if let SandboxMode::Container {
+        program,
+        pids_limit,
+        memory_bytes,
+        ..
+    } = &mut policy.sandbox
+    {
+        edit(program, pids_limit, memory_bytes);
+    }
+    validate_container_policy(&policy).err().map(|err| err.code)
+}
+
+#[test]
+fn container_policy_requires_an_allowlisted_runtime_path_and_limits() {
+    let denied = Some(ErrorCode::PolicyDenied);
+    assert_eq!(
+        validate_with(|program, _, _| *program = "podman".into()),
+        denied
+    );
+    assert_eq!(
+        validate_with(|program, _, _| *program = "/usr/local/bin/podman".into()),
+        denied
+    );
+    assert_eq!(validate_with(|_, pids, _| *pids = 0), denied);
+    assert_eq!(validate_with(|_, _, memory| *memory = 0), denied);
+    assert_eq!(validate_with(|_, _, _| {}), None);
+}
+
+/// Stand-in runtime that records its arguments and exits with `status`.
+fn fake_runtime(dir: &Path, status: u8) -> std::path::PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+    let path = dir.join("runtime");
+    std::fs::write(
+        &path,
+        format!(
+            "#!/bin/sh\necho \"$@\" >> {}/calls\necho no such container >&2\nexit {status}\n",
+            dir.display()
+        ),
+    )
+    .unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+#[test]
+fn container_sessions_signal_through_the_runtime() {
+    let dir = std::env::temp_dir().join(format!("ptybox-container-signal-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let mut session = Session::spawn(SessionConfig {
+        command: "/bin/cat".to_string(),
+        args: Vec::new(),
+        cwd: None,
+        size: TerminalSize::default(),
+        run_id: RunId::new(),
+        env: EnvPolicy::default(),
+        separate_stderr: false,
+        output_buffer: OutputBufferConfig::default(),
+        resize: ResizeConfig::default(),
+        remote: None,
+        encoding: TerminalEncoding::default(),
+    })
+    .unwrap();
+
+    session.set_container(ContainerCleanup::new(
+        fake_runtime(&dir, 0),
+        "ptybox-test".to_string(),
+    ));
+    session.send_signal(ChildSignal::Int).unwrap();
+    let calls = std::fs::read_to_string(dir.join("calls")).unwrap();
+    assert_eq!(calls, "kill --signal SIGINT ptybox-test\n");
+
+    // The local child is still running, so a failed kill is an error.
+    session.set_container(ContainerCleanup::new(
+        fake_runtime(&dir, 1),
+        "ptybox-test".to_string(),
+    ));
+    let err = session.send_signal(ChildSignal::Term).unwrap_err();
+    assert_eq!(err.code, ErrorCode::Io);
+    assert_eq!(err.context.unwrap()["stderr"], "no such container");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,205 @@
+// Test module - relaxed lint rules
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(clippy::panic)]
+#![allow(missing_docs)]
+
+//! Embedded driver tests
+//!
+//! Tests for the in-process protocol v2 driver used by language bindings.
+
+use ptybox::driver::{DriverConfig, EmbeddedDriver};
+use ptybox::model::policy::PolicyBuilder;
+use ptybox::model::{DriverEvent, DriverEventKind};
+use ptybox::runner::ErrorCode;
+use serde_json::Value;
+use std::time::Duration;
+
+fn cat_config(builder: PolicyBuilder) -> DriverConfig {
+    DriverConfig {
+        command: "/bin/cat".to_string(),
+        args: Vec::new(),
+        cwd: None,
+        policy: builder.build(),
+        artifacts: None,
+        separate_stderr: false,
+        ack_overrides: Vec::new(),
+    }
+}
+
+fn cat_policy() -> PolicyBuilder {
+    PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_executables(vec!["/bin/cat".to_string()])
+}
+
+#[test]
+fn embedded_driver_round_trips_requests() {
+    let mut driver = EmbeddedDriver::start(cat_config(cat_policy())).expect("start driver");
+    let handshake: Value = serde_json::from_str(driver.handshake()).unwrap();
+    assert_eq!(handshake["type"], "handshake");
+
+    let response = driver
+        .send(r#"{"protocol_version":2,"request_id":"r1","action":{"type":"text","payload":{"text":"hello"}}}"#)
+        .expect("send text");
+    let response: Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(response["request_id"], "r1");
+    assert_eq!(response["status"], "ok");
+
+    let response = driver
+        .send(r#"{"protocol_version":2,"request_id":"r2","action":{"type":"wait","payload":{"condition":{"type":"screen_contains","payload":{"text":"hello"}}}},"timeout_ms":2000}"#)
+        .expect("send wait");
+    let response: Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(response["status"], "ok");
+
+    driver.close().expect("close driver");
+}
+
+#[test]
+fn embedded_driver_waits_for_bell() {
+    let mut driver = EmbeddedDriver::start(cat_config(cat_policy())).expect("start driver");
+    // The tty echoes the BEL as `^G`; cat writes the real one once the line ends.
+    let response = driver
+        .send(r#"{"protocol_version":2,"request_id":"r1","action":{"type":"text","payload":{"text":"ding\u0007\n"}}}"#)
+        .expect("send text");
+    let response: Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(response["status"], "ok");
+
+    let response = driver
+        .send(r#"{"protocol_version":2,"request_id":"r2","action":{"type":"wait","payload":{"condition":{"type":"bell","payload":{}}}},"timeout_ms":2000}"#)
+        .expect("send wait");
+    let response: Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(response["status"], "ok", "{response}");
+
+    driver.close().expect("close driver");
+}
+
+/// A driver running `script` under `/bin/sh -c`.
+fn shell_driver(script: &str) -> EmbeddedDriver {
+    let mut config = cat_config(
+        PolicyBuilder::new()
+            .sandbox_disabled()
+            .allow_shell()
+            .allowed_executables(vec!["/bin/sh".to_string()]),
+    );
+    config.command = "/bin/sh".to_string();
+    config.args = vec!["-c".to_string(), script.to_string()];
+    EmbeddedDriver::start(config).expect("start driver")
+}
+
+fn next_event(driver: &mut EmbeddedDriver, timeout_ms: u64) -> Option<DriverEvent> {
+    driver
+        .next_event(Duration::from_millis(timeout_ms))
+        .map(|line| serde_json::from_str(&line).unwrap())
+}
+
+#[test]
+fn embedded_driver_pushes_subscribed_events() {
+    let mut driver = shell_driver("sleep 0.5; printf 'late\\007'; sleep 0.3; exit 3");
+    let response = driver
+        .send(r#"{"protocol_version":2,"request_id":"r1","action":{"type":"subscribe","payload":{}}}"#)
+        .expect("send subscribe");
+    let response: Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(response["status"], "ok", "{response}");
+    assert_eq!(
+        response["subscription"]["events"],
+        serde_json::json!(["screen_changed", "bell", "process_exited"])
+    );
+    assert_eq!(response["subscription"]["last_event_seq"], 0);
+
+    let mut events = Vec::new();
+    while let Some(event) = next_event(&mut driver, 3000) {
+        driver.ack(event.event_seq).expect("ack");
+        let exited = event.kind == DriverEventKind::ProcessExited;
+        events.push(event);
+        if exited {
+            break;
+        }
+    }
+    let kinds: Vec<DriverEventKind> = events.iter().map(|event| event.kind).collect();
+    assert!(kinds.contains(&DriverEventKind::ScreenChanged), "{kinds:?}");
+    assert!(kinds.contains(&DriverEventKind::Bell), "{kinds:?}");
+    assert_eq!(kinds.last(), Some(&DriverEventKind::ProcessExited));
+    let seqs: Vec<u64> = events.iter().map(|event| event.event_seq).collect();
+    assert_eq!(seqs, (1..=events.len() as u64).collect::<Vec<_>>());
+    let exit = events.last().unwrap().exit_status.as_ref().unwrap();
+    assert_eq!(exit.exit_code, Some(3));
+
+    // Requests still get their own responses while subscribed.
+    let response = driver
+        .send(r#"{"protocol_version":2,"request_id":"r2","action":{"type":"subscribe","payload":{"events":[]}}}"#)
+        .expect("send unsubscribe");
+    let response: Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(response["request_id"], "r2");
+    assert_eq!(response["subscription"]["events"], serde_json::json!([]));
+    assert_eq!(response["subscription"]["last_event_seq"], events.len());
+    drop(driver);
+}
+
+#[test]
+fn embedded_driver_drops_events_past_the_ack_window() {
+    let mut driver =
+        shell_driver("for word in one two three; do sleep 0.2; echo $word; done; sleep 5");
+    let response = driver
+        .send(r#"{"protocol_version":2,"request_id":"r1","action":{"type":"subscribe","payload":{"events":["screen_changed"],"max_unacked":1}}}"#)
+        .expect("send subscribe");
+    let response: Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(response["subscription"]["max_unacked"], 1);
+
+    let first = next_event(&mut driver, 2000).expect("first event");
+    assert_eq!(first.kind, DriverEventKind::ScreenChanged);
+    // Nothing more is pushed until the client acknowledges.
+    std::thread::sleep(Duration::from_millis(900));
+    assert!(next_event(&mut driver, 0).is_none());
+
+    driver.ack(first.event_seq).expect("ack");
+    let overflow = next_event(&mut driver, 2000).expect("overflow event");
+    assert_eq!(overflow.kind, DriverEventKind::Overflow);
+    assert_eq!(overflow.event_seq, 2);
+    assert!(overflow.dropped.unwrap() >= 1, "{overflow:?}");
+    assert!(overflow.screen_hash.is_some());
+
+    let response = driver
+        .send(r#"{"protocol_version":2,"request_id":"r2","action":{"type":"subscribe","payload":{"max_unacked":0}}}"#)
+        .expect("send subscribe");
+    let response: Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(response["error"]["code"], "E_PROTOCOL");
+    drop(driver);
+}
+
+#[test]
+fn embedded_driver_start_reports_policy_denial() {
+    let config = cat_config(PolicyBuilder::new().sandbox_disabled());
+    let err = EmbeddedDriver::start(config)
+        .err()
+        .expect("command outside allowlist should be denied");
+    assert_eq!(err.code, ErrorCode::PolicyDenied);
+}
+
+#[test]
+fn embedded_driver_rejects_multi_line_request() {
+    let mut driver = EmbeddedDriver::start(cat_config(cat_policy())).expect("start driver");
+    let err = driver.send("{}\n{}").expect_err("multi-line request");
+    assert_eq!(err.code, ErrorCode::Protocol);
+    driver.close().expect("close driver");
+}
+
+#[cfg(not(feature = "render"))]
+#[test]
+fn embedded_driver_rejects_include_image_without_render_feature() {
+    let mut driver = EmbeddedDriver::start(cat_config(cat_policy())).expect("start driver");
+    let response = driver
+        .send(r#"{"protocol_version":2,"request_id":"r1","action":{"type":"text","payload":{"text":"hi"}},"include_image":true}"#)
+        .expect("send text");
+    let response: Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(response["status"], "error");
+    assert_eq!(response["error"]["code"], "E_PROTOCOL");
+
+    let response = driver
+        .send(r#"{"protocol_version":2,"request_id":"r2","action":{"type":"text","payload":{"text":"hi"}}}"#)
+        .expect("send text");
+    let response: Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(response["status"], "ok");
+    driver.close().expect("close driver");
+}
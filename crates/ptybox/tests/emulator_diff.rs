@@ -0,0 +1,131 @@
+// Test module - relaxed lint rules
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(missing_docs)]
+
+//! Comparison with a second emulator backend (`emulator-diff` feature).
+
+use std::path::PathBuf;
+
+use ptybox::artifacts::ArtifactsWriterConfig;
+use ptybox::model::policy::PolicyBuilder;
+use ptybox::model::{EmulatorDiffTrigger, TerminalSize};
+use ptybox::run::run_exec_with_options;
+use ptybox::runner::RunnerOptions;
+use ptybox::terminal::emulator_diff::{PRIMARY_BACKEND, SECONDARY_BACKEND};
+use ptybox::terminal::Terminal;
+
+fn temp_dir(prefix: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "ptybox-emulator-diff-{prefix}-{}",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.canonicalize().unwrap()
+}
+
+#[test]
+fn backends_that_agree_report_no_divergence() {
+    let mut terminal = Terminal::new(TerminalSize::new(4, 20));
+    assert!(terminal.emulator_diff().is_none());
+    terminal.enable_emulator_diff();
+    terminal.process_bytes(b"hello\r\n\x1b[1mbold\x1b[0m\x1b[2;1H");
+    terminal.resize(TerminalSize::new(5, 30));
+    terminal.snapshot().unwrap();
+
+    let report = terminal.emulator_diff().unwrap();
+    assert_eq!(report.primary, PRIMARY_BACKEND);
+    assert_eq!(report.secondary, SECONDARY_BACKEND);
+    assert_eq!(report.bytes_processed, 25);
+    assert_eq!(report.comparisons, 2);
+    assert_eq!(report.divergences, 0);
+    assert!(report.points.is_empty());
+    assert!(report.first_differing_snapshot.is_none());
+    assert!(!report.diverged_at_end);
+}
+
+#[test]
+fn divergence_points_name_the_chunk_and_rows() {
+    let mut terminal = Terminal::new(TerminalSize::new(4, 20));
+    terminal.enable_emulator_diff();
+    terminal.process_bytes(b"ready\r\n");
+    let agreeing = terminal.snapshot().unwrap();
+    // CNL (`CSI n E`) is only understood by the secondary backend.
+    terminal.process_bytes(b"abc\x1b[1Exyz");
+    let differing = terminal.snapshot().unwrap();
+    terminal.snapshot().unwrap();
+    // Clearing the screen brings the backends back together.
+    terminal.process_bytes(b"\x1b[2J\x1b[H");
+
+    let report = terminal.emulator_diff().unwrap();
+    assert_eq!(report.bytes_processed, 24);
+    assert_eq!(report.comparisons, 3);
+    assert_eq!(report.divergences, 1);
+    assert!(!report.diverged_at_end);
+    let point = &report.points[0];
+    assert_eq!(point.trigger, EmulatorDiffTrigger::Output);
+    assert_eq!((point.chunk_start, point.byte_offset), (7, 17));
+    assert_eq!(point.chunk, "abc\\x1b[1Exyz");
+    let rows: Vec<(u16, &str, &str)> = point
+        .rows
+        .iter()
+        .map(|row| (row.row, row.primary.as_str(), row.secondary.as_str()))
+        .collect();
+    assert_eq!(rows, [(1, "abcxyz", "abc"), (2, "", "xyz")]);
+    assert_eq!((point.primary_cursor.row, point.primary_cursor.col), (1, 6));
+    assert_eq!(
+        (point.secondary_cursor.row, point.secondary_cursor.col),
+        (2, 3)
+    );
+    let snapshot = report.first_differing_snapshot.unwrap();
+    assert_ne!(snapshot.snapshot_id, agreeing.snapshot_id);
+    assert_eq!(snapshot.snapshot_id, differing.snapshot_id);
+    assert_eq!(snapshot.byte_offset, 17);
+}
+
+#[test]
+fn exec_runs_write_emulator_diff_json() {
+    let root = temp_dir("exec");
+    let policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_executables(vec!["/bin/printf".to_string()])
+        .allowed_write(vec![root.display().to_string()])
+        .build();
+    let options = |dir: &str, emulator_diff| RunnerOptions {
+        artifacts: Some(ArtifactsWriterConfig {
+            dir: root.join(dir),
+            overwrite: false,
+        }),
+        emulator_diff,
+        ..RunnerOptions::default()
+    };
+    let run = |dir: &str, emulator_diff| {
+        run_exec_with_options(
+            "/bin/printf".to_string(),
+            vec!["abc\\033[1Exyz".to_string()],
+            None,
+            policy.clone(),
+            options(dir, emulator_diff),
+        )
+        .unwrap()
+    };
+
+    let result = run("compared", true);
+    let report: serde_json::Value = serde_json::from_slice(
+        &std::fs::read(root.join("compared").join("emulator-diff.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(report["emulator_diff_version"], 1);
+    assert_eq!(report["divergences"], 1);
+    assert_eq!(report["diverged_at_end"], true);
+    assert_eq!(report["points"][0]["rows"][0]["primary"], "abcxyz");
+    assert_eq!(report["points"][0]["rows"][0]["secondary"], "abc");
+    // Snapshots still come from the primary backend.
+    let lines = result.final_observation.unwrap().screen.lines;
+    assert_eq!(lines[0], "abcxyz");
+
+    run("plain", false);
+    assert!(!root.join("plain").join("emulator-diff.json").exists());
+}
@@ -0,0 +1,97 @@
+// Test module - relaxed lint rules
+#![allow(unsafe_code)]
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(clippy::panic)]
+#![allow(missing_docs)]
+
+//! C ABI tests
+//!
+//! Exercises the `ffi` feature surface the way a language binding would.
+
+use ptybox::ffi::{
+    ptybox_driver_close, ptybox_driver_handshake, ptybox_driver_new, ptybox_driver_send,
+    ptybox_string_free,
+};
+use ptybox::model::policy::PolicyBuilder;
+use serde_json::Value;
+use std::ffi::{c_char, CStr, CString};
+
+fn take_json(value: *mut c_char) -> Value {
+    assert!(!value.is_null());
+    // SAFETY: non-null string returned by the ffi API.
+    let text = unsafe { CStr::from_ptr(value) }
+        .to_str()
+        .unwrap()
+        .to_string();
+    // SAFETY: returned by the ffi API and freed once.
+    unsafe { ptybox_string_free(value) };
+    serde_json::from_str(&text).unwrap()
+}
+
+fn config_json(allowed: &[&str]) -> CString {
+    let policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_executables(allowed.iter().map(|s| (*s).to_string()).collect())
+        .build();
+    let config = serde_json::json!({ "command": "/bin/cat", "policy": policy });
+    CString::new(config.to_string()).unwrap()
+}
+
+#[test]
+fn ffi_driver_session_lifecycle() {
+    let config = config_json(&["/bin/cat"]);
+    let mut error = std::ptr::null_mut();
+    // SAFETY: valid C strings and out-pointer.
+    let driver = unsafe { ptybox_driver_new(config.as_ptr(), &mut error) };
+    assert!(!driver.is_null());
+    assert!(error.is_null());
+
+    // SAFETY: live handle.
+    let handshake = take_json(unsafe { ptybox_driver_handshake(driver) });
+    assert_eq!(handshake["protocol_version"], 2);
+
+    let request = CString::new(
+        r#"{"protocol_version":2,"request_id":"r1","action":{"type":"text","payload":{"text":"hi"}}}"#,
+    )
+    .unwrap();
+    // SAFETY: live handle and valid C string.
+    let response = take_json(unsafe { ptybox_driver_send(driver, request.as_ptr()) });
+    assert_eq!(response["request_id"], "r1");
+    assert_eq!(response["status"], "ok");
+    assert!(response["observation"]["screen"].is_object());
+
+    // SAFETY: live handle, not used afterwards.
+    let close_error = unsafe { ptybox_driver_close(driver) };
+    assert!(close_error.is_null());
+}
+
+#[test]
+fn ffi_driver_new_reports_error_json() {
+    let config = config_json(&[]);
+    let mut error = std::ptr::null_mut();
+    // SAFETY: valid C strings and out-pointer.
+    let driver = unsafe { ptybox_driver_new(config.as_ptr(), &mut error) };
+    assert!(driver.is_null());
+    let error = take_json(error);
+    assert_eq!(error["code"], "E_POLICY_DENIED");
+}
+
+#[test]
+fn ffi_null_arguments_do_not_crash() {
+    let mut error = std::ptr::null_mut();
+    // SAFETY: null inputs are part of the documented contract.
+    let driver = unsafe { ptybox_driver_new(std::ptr::null(), &mut error) };
+    assert!(driver.is_null());
+    assert_eq!(take_json(error)["code"], "E_PROTOCOL");
+
+    // SAFETY: null handle and request are accepted.
+    let response = take_json(unsafe { ptybox_driver_send(std::ptr::null_mut(), std::ptr::null()) });
+    assert_eq!(response["status"], "error");
+    // SAFETY: null is ignored.
+    unsafe {
+        assert!(ptybox_driver_close(std::ptr::null_mut()).is_null());
+        ptybox_string_free(std::ptr::null_mut());
+    }
+}
@@ -0,0 +1,78 @@
+// Test module - relaxed lint rules
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(missing_docs)]
+
+use std::time::Duration;
+
+use ptybox::fuzz::{fuzz, generate_actions, FuzzConfig, CRASH_WATCHER};
+use ptybox::model::policy::PolicyBuilder;
+use ptybox::model::{ActionType, FuzzFailureKind, FuzzReport};
+use ptybox::runner::ErrorCode;
+
+/// Reads raw keystrokes and runs `on_digit` when one contains a digit.
+fn fuzz_digits(on_digit: &str) -> FuzzReport {
+    let script = format!(
+        "/bin/stty raw -echo; while c=$(/bin/dd bs=1 count=1 2>/dev/null); do \
+         case \"$c\" in [0-9]) {on_digit};; esac; done"
+    );
+    let policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allow_shell()
+        .allowed_executables(vec!["/bin/sh".to_string()])
+        .max_runtime_ms(10_000)
+        .build();
+    let mut config = FuzzConfig::new("/bin/sh", vec!["-c".to_string(), script], policy, 42);
+    config.iterations = 3;
+    config.steps = 30;
+    config.step_timeout = Duration::from_millis(20);
+    fuzz(&config).unwrap()
+}
+
+#[test]
+fn generate_actions_is_stable_per_seed() {
+    let first = serde_json::to_value(generate_actions(7, 40)).unwrap();
+    let again = serde_json::to_value(generate_actions(7, 40)).unwrap();
+    let other = serde_json::to_value(generate_actions(8, 40)).unwrap();
+
+    assert_eq!(first, again);
+    assert_ne!(first, other);
+    assert_eq!(first.as_array().unwrap().len(), 40);
+    for action in generate_actions(7, 200) {
+        let text = action.payload["text"].as_str().unwrap_or_default();
+        let key = action.payload["key"].as_str().unwrap_or_default();
+        assert!(!text.chars().any(char::is_control), "{text:?}");
+        assert!(!["Ctrl+C", "Ctrl+Z", "Ctrl+D"].contains(&key), "{key}");
+    }
+}
+
+#[test]
+fn fuzz_minimizes_a_watcher_failure() {
+    let report = fuzz_digits("echo \"thread 'main' panicked at fuzz\"; /bin/sleep 5");
+
+    let failure = report.failure.expect("panic text should be found");
+    assert_eq!(failure.kind, FuzzFailureKind::Watcher);
+    assert_eq!(failure.watcher.as_deref(), Some(CRASH_WATCHER));
+    assert_eq!(failure.error.code, "E_ASSERTION_FAILED");
+    assert_eq!(failure.minimized_steps, 1);
+    assert!(failure.minimized_steps <= failure.original_steps);
+    let steps = &failure.scenario.steps;
+    assert_eq!(steps.len(), 2);
+    assert!(matches!(steps[1].action.action_type, ActionType::Terminate));
+    assert_eq!(failure.scenario.watchers[0].name, CRASH_WATCHER);
+}
+
+#[test]
+fn fuzz_reports_an_unsuccessful_exit() {
+    let report = fuzz_digits("exit 3");
+
+    let failure = report.failure.expect("exit should be found");
+    assert_eq!(failure.kind, FuzzFailureKind::Exit);
+    assert_eq!(failure.error.code, ErrorCode::ProcessExit.as_str());
+    assert_eq!(failure.exit_status.unwrap().exit_code, Some(3));
+    assert_eq!(failure.minimized_steps, 1);
+    let last = failure.scenario.steps.last().unwrap();
+    assert!(matches!(last.action.action_type, ActionType::Wait));
+    assert_eq!(last.assert[0].assertion_type, "exit_code");
+}
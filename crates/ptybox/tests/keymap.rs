@@ -0,0 +1,146 @@
+// Test module - relaxed lint rules
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(missing_docs)]
+
+//! Keyboard layouts applied to `key` actions through `input.keymap`.
+
+use std::path::{Path, PathBuf};
+
+use ptybox::artifacts::ArtifactsWriterConfig;
+use ptybox::model::policy::{Keymap, PolicyBuilder};
+use ptybox::model::{RunStatus, Scenario};
+use ptybox::policy::keymap::{builtin_keymap, load_keymap, BUILTIN_KEYMAPS};
+use ptybox::run::run_scenario_with_options;
+use ptybox::runner::{ErrorCode, RunnerOptions};
+use serde_json::json;
+
+fn temp_dir(prefix: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ptybox-keymap-{prefix}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.canonicalize().unwrap()
+}
+
+/// `/bin/cat` typing `y`, `Y`, `;` and Enter, then EOF, under `keymap`.
+fn cat_scenario(root: &Path, keymap: &str) -> Scenario {
+    let mut policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_executables(vec!["/bin/cat".to_string()])
+        .allowed_write(vec![root.display().to_string()])
+        .build();
+    policy.input.keymap = Some(keymap.to_string());
+    let key = |id: u8, key: &str| {
+        json!({
+            "id": format!("00000000-0000-0000-0000-{id:012}"),
+            "name": key,
+            "action": { "type": "key", "payload": { "key": key } },
+            "assert": [],
+            "timeout_ms": 2000,
+            "retries": 0,
+        })
+    };
+    let mut enter = key(4, "Enter");
+    enter["assert"] = json!([{ "type": "screen_contains", "payload": { "text": "zZö" } }]);
+    serde_json::from_value(json!({
+        "scenario_version": 1,
+        "metadata": { "name": "keymap" },
+        "run": {
+            "command": "/bin/cat",
+            "args": [],
+            "initial_size": { "rows": 24, "cols": 80 },
+            "policy": policy,
+        },
+        "steps": [key(1, "y"), key(2, "Y"), key(3, ";"), enter, key(5, "Ctrl+D")],
+    }))
+    .unwrap()
+}
+
+#[test]
+fn builtin_keymaps_translate_us_positions() {
+    for name in BUILTIN_KEYMAPS {
+        assert_eq!(load_keymap(name).unwrap().name, name);
+    }
+    assert!(builtin_keymap("us").unwrap().keys.is_empty());
+    let de = builtin_keymap("de").unwrap();
+    assert_eq!(de.keys["y"], "z");
+    assert_eq!(de.keys["["], "ü");
+    assert!(!de.keys.contains_key("Enter"));
+    let fr = builtin_keymap("fr").unwrap();
+    assert_eq!(fr.keys["q"], "a");
+    assert_eq!(fr.keys["2"], "é");
+
+    let err = load_keymap("dvorak").unwrap_err();
+    assert_eq!(err.code, ErrorCode::PolicyDenied);
+    assert_eq!(err.context.unwrap()["builtin"], json!(["us", "de", "fr"]));
+    let err = load_keymap("layouts/de.json").unwrap_err();
+    assert_eq!(err.code, ErrorCode::PolicyDenied);
+}
+
+#[test]
+fn keymap_files_load_from_json_and_yaml() {
+    let root = temp_dir("files");
+    let json_path = root.join("mine.json");
+    std::fs::write(
+        &json_path,
+        r#"{"keymap_version": 1, "name": "mine", "keys": {"F13": "\u001b[25~"}}"#,
+    )
+    .unwrap();
+    let keymap = load_keymap(json_path.to_str().unwrap()).unwrap();
+    assert_eq!(keymap.name, "mine");
+    assert_eq!(keymap.keys["F13"], "\x1b[25~");
+
+    let yaml_path = root.join("mine.yaml");
+    std::fs::write(&yaml_path, "keymap_version: 1\nname: yaml\nkeys:\n  a: b\n").unwrap();
+    assert_eq!(
+        load_keymap(yaml_path.to_str().unwrap()).unwrap().keys["a"],
+        "b"
+    );
+
+    std::fs::write(&json_path, r#"{"keymap_version": 2, "name": "future"}"#).unwrap();
+    let err = load_keymap(json_path.to_str().unwrap()).unwrap_err();
+    assert_eq!(err.code, ErrorCode::Protocol);
+    assert_eq!(err.message, "unsupported keymap version");
+}
+
+#[test]
+fn scenario_keys_use_the_keymap_and_run_json_records_it() {
+    let root = temp_dir("run");
+    let options = RunnerOptions {
+        artifacts: Some(ArtifactsWriterConfig {
+            dir: root.join("artifacts"),
+            overwrite: false,
+        }),
+        ..RunnerOptions::default()
+    };
+
+    let run = run_scenario_with_options(cat_scenario(&root, "de"), options).unwrap();
+
+    assert_eq!(run.status, RunStatus::Passed);
+    assert_eq!(run.keymap.as_ref().unwrap().name, "de");
+    let run_json: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(root.join("artifacts").join("run.json")).unwrap())
+            .unwrap();
+    assert_eq!(run_json["keymap"]["name"], "de");
+    assert_eq!(run_json["keymap"]["keys"]["y"], "z");
+}
+
+#[test]
+fn a_runner_keymap_overrides_the_policy() {
+    let root = temp_dir("override");
+    let mut keymap: Keymap = builtin_keymap("us").unwrap();
+    keymap.name = "recorded".to_string();
+    keymap.keys.insert("y".to_string(), "z".to_string());
+    keymap.keys.insert("Y".to_string(), "Z".to_string());
+    keymap.keys.insert(";".to_string(), "ö".to_string());
+    let options = RunnerOptions {
+        keymap: Some(keymap),
+        ..RunnerOptions::default()
+    };
+
+    let run = run_scenario_with_options(cat_scenario(&root, "us"), options).unwrap();
+
+    assert_eq!(run.status, RunStatus::Passed);
+    assert_eq!(run.keymap.unwrap().name, "recorded");
+}
@@ -0,0 +1,256 @@
+// Test module - relaxed lint rules
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(missing_docs)]
+
+//! Log forwarding to syslog, journald, and UDP (`log-forward` feature).
+
+use std::net::UdpSocket;
+use std::os::unix::net::UnixDatagram;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use ptybox::artifacts::ArtifactsWriterConfig;
+use ptybox::log_forward::{LogForwardConfig, LogTarget};
+use ptybox::model::policy::{ArtifactsEncryption, Policy, PolicyBuilder};
+use ptybox::model::Scenario;
+use ptybox::policy::validate_log_forward_policy;
+use ptybox::run::{run_exec_with_options, run_scenario_with_options};
+use ptybox::runner::{ErrorCode, RunnerOptions};
+use serde_json::json;
+
+fn temp_dir(prefix: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "ptybox-log-forward-{prefix}-{}",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.canonicalize().unwrap()
+}
+
+fn cat_scenario(root: &Path, allowed_udp: &[String]) -> Scenario {
+    let policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_executables(vec!["/bin/cat".to_string()])
+        .allowed_write(vec![root.display().to_string()])
+        .log_forward_udp(allowed_udp.to_vec())
+        .build();
+    serde_json::from_value(json!({
+        "scenario_version": 1,
+        "metadata": { "name": "log-forward" },
+        "run": {
+            "command": "/bin/cat",
+            "args": [],
+            "initial_size": { "rows": 24, "cols": 80 },
+            "policy": policy,
+        },
+        "steps": [{
+            "id": "00000000-0000-0000-0000-000000000001",
+            "name": "type",
+            "action": { "type": "text", "payload": { "text": "hello\n" } },
+            "assert": [{ "type": "screen_contains", "payload": { "text": "hello" } }],
+            "timeout_ms": 2000,
+            "retries": 0,
+        }, {
+            "id": "00000000-0000-0000-0000-000000000002",
+            "name": "quit",
+            "action": { "type": "terminate", "payload": {} },
+            "timeout_ms": 2000,
+            "retries": 0,
+        }],
+    }))
+    .unwrap()
+}
+
+/// Datagrams already waiting on `recv` (the run drains its queue before
+/// returning).
+fn received(mut recv: impl FnMut(&mut [u8]) -> std::io::Result<usize>) -> Vec<String> {
+    let mut records = Vec::new();
+    let mut buf = vec![0; 65_536];
+    while let Ok(len) = recv(&mut buf) {
+        records.push(String::from_utf8_lossy(&buf[..len]).to_string());
+    }
+    records
+}
+
+fn file_names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn udp_forwarding_sends_run_step_and_transcript_records() {
+    let root = temp_dir("udp");
+    let collector = UdpSocket::bind("127.0.0.1:0").unwrap();
+    collector
+        .set_read_timeout(Some(Duration::from_millis(200)))
+        .unwrap();
+    let endpoint = collector.local_addr().unwrap().to_string();
+    let config = LogForwardConfig::udp(&format!("udp://{endpoint}")).unwrap();
+    let allowed = [endpoint];
+
+    let options = |dir: &str, log_forward| RunnerOptions {
+        artifacts: Some(ArtifactsWriterConfig {
+            dir: root.join(dir),
+            overwrite: false,
+        }),
+        log_forward,
+        ..RunnerOptions::default()
+    };
+    let run = run_scenario_with_options(
+        cat_scenario(&root, &allowed),
+        options("forwarded", Some(config)),
+    )
+    .unwrap();
+    let records = received(|buf| collector.recv(buf));
+
+    let run_id = format!("run_id=\"{}\"", run.run_id);
+    assert!(records.iter().all(|record| record.starts_with("<1")
+        && record.contains("ptybox@32473")
+        && record.contains(&run_id)));
+    assert!(
+        records[0].ends_with("] run started: /bin/cat"),
+        "{records:?}"
+    );
+    let hello = records
+        .iter()
+        .find(|record| record.contains("kind=\"transcript\""))
+        .expect("transcript record");
+    assert!(hello.ends_with("] hello"), "{hello}");
+    assert!(hello.contains("step=\"type\""), "{hello}");
+    assert!(records
+        .iter()
+        .any(|record| record.contains("] step passed in ") && record.ends_with("ms: type")));
+    let last = records.last().unwrap();
+    assert!(last.starts_with("<14>1 "), "{last}");
+    assert!(last.ends_with("] run passed: /bin/cat"), "{last}");
+
+    // The same run without forwarding writes the same artifact files.
+    run_scenario_with_options(cat_scenario(&root, &allowed), options("plain", None)).unwrap();
+    assert_eq!(
+        file_names(&root.join("forwarded")),
+        file_names(&root.join("plain"))
+    );
+}
+
+#[test]
+fn syslog_and_journald_records_carry_run_and_step_fields() {
+    let root = temp_dir("unix");
+    let policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_executables(vec!["/bin/echo".to_string()])
+        .build();
+    for name in ["syslog", "journald"] {
+        let path = root.join(format!("{name}.sock"));
+        let target = if name == "syslog" {
+            LogTarget::Syslog(path.clone())
+        } else {
+            LogTarget::Journald(path.clone())
+        };
+        let socket = UnixDatagram::bind(&path).unwrap();
+        socket
+            .set_read_timeout(Some(Duration::from_millis(200)))
+            .unwrap();
+        let mut config = LogForwardConfig::new(target);
+        config.ident = "agents".to_string();
+        let options = RunnerOptions {
+            log_forward: Some(config),
+            ..RunnerOptions::default()
+        };
+        let run = run_exec_with_options(
+            "/bin/echo".to_string(),
+            vec!["hi there".to_string()],
+            None,
+            policy.clone(),
+            options,
+        )
+        .unwrap();
+        let records = received(|buf| socket.recv(buf));
+        let hello = records
+            .iter()
+            .find(|record| record.contains("hi there"))
+            .expect("transcript record");
+        if name == "syslog" {
+            assert_eq!(
+                *hello,
+                format!(
+                    "<14>agents[{}]: [run_id={} kind=transcript] hi there",
+                    std::process::id(),
+                    run.run_id
+                )
+            );
+        } else {
+            let fields: Vec<&str> = hello.lines().collect();
+            assert!(fields.contains(&"MESSAGE=hi there"), "{fields:?}");
+            assert!(fields.contains(&"PRIORITY=6"), "{fields:?}");
+            assert!(fields.contains(&"SYSLOG_IDENTIFIER=agents"), "{fields:?}");
+            assert!(fields.contains(&"PTYBOX_KIND=transcript"), "{fields:?}");
+            let run_id = format!("PTYBOX_RUN_ID={}", run.run_id);
+            assert!(fields.contains(&run_id.as_str()), "{fields:?}");
+        }
+    }
+}
+
+#[test]
+fn udp_endpoints_must_be_allowlisted_by_the_policy() {
+    let config = LogForwardConfig::udp("udp://127.0.0.1:514").unwrap();
+    assert_eq!(config.target, LogTarget::Udp("127.0.0.1:514".to_string()));
+
+    let err = config.check_policy(&Policy::default()).unwrap_err();
+    assert_eq!(err.code, ErrorCode::PolicyDenied);
+    let fix = err.context.unwrap()["fix"].as_str().unwrap().to_string();
+    assert!(fix.contains("policy.log_forward.allowed_udp"), "{fix}");
+    let other = PolicyBuilder::new()
+        .log_forward_udp(vec!["logs.example.internal:514".to_string()])
+        .build();
+    let err = config.check_policy(&other).unwrap_err();
+    assert_eq!(err.code, ErrorCode::PolicyDenied);
+    let allowed = PolicyBuilder::new()
+        .log_forward_udp(vec!["127.0.0.1:514".to_string()])
+        .build();
+    config.check_policy(&allowed).unwrap();
+
+    let err = LogForwardConfig::udp("udp://127.0.0.1").unwrap_err();
+    assert_eq!(err.code, ErrorCode::Protocol);
+    let invalid = PolicyBuilder::new()
+        .log_forward_udp(vec!["127.0.0.1".to_string()])
+        .build();
+    let err = validate_log_forward_policy(&invalid.log_forward).unwrap_err();
+    assert_eq!(err.code, ErrorCode::PolicyDenied);
+}
+
+#[test]
+fn runs_refuse_to_forward_to_unlisted_collectors() {
+    let root = temp_dir("denied");
+    let collector = UdpSocket::bind("127.0.0.1:0").unwrap();
+    collector
+        .set_read_timeout(Some(Duration::from_millis(200)))
+        .unwrap();
+    let endpoint = collector.local_addr().unwrap().to_string();
+    let options = RunnerOptions {
+        log_forward: Some(LogForwardConfig::udp(&endpoint).unwrap()),
+        ..RunnerOptions::default()
+    };
+    let err = run_scenario_with_options(cat_scenario(&root, &[]), options).unwrap_err();
+    assert_eq!(err.code, ErrorCode::PolicyDenied);
+    assert!(received(|buf| collector.recv(buf)).is_empty());
+}
+
+#[test]
+fn forwarding_is_refused_when_artifacts_are_encrypted() {
+    let mut policy = PolicyBuilder::new().build();
+    policy.artifacts.encryption = Some(ArtifactsEncryption {
+        recipients: vec!["age1example".to_string()],
+        age_binary: "/usr/bin/age".to_string(),
+    });
+    let err = LogForwardConfig::syslog()
+        .check_policy(&policy)
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::PolicyDenied);
+}
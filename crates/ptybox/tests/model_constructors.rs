@@ -92,6 +92,16 @@ fn action_terminate_creates_correct_type() {
     assert!(matches!(action.action_type, ActionType::Terminate));
 }
 
+#[test]
+fn action_raw_encodes_bytes_as_hex() {
+    let action = Action::raw(b"\x1b[?1004h");
+    assert!(matches!(action.action_type, ActionType::Raw));
+    assert_eq!(
+        action.payload.get("hex").unwrap().as_str().unwrap(),
+        "1b5b3f3130303468"
+    );
+}
+
 // =============================================================================
 // Assertion Constructor Tests
 // =============================================================================
@@ -0,0 +1,190 @@
+// Test module - relaxed lint rules
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(missing_docs)]
+
+//! OpenTelemetry trace export (`otel` feature).
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::thread;
+
+use ptybox::model::policy::PolicyBuilder;
+use ptybox::model::Scenario;
+use ptybox::otel::OtelConfig;
+use ptybox::run::run_scenario_with_options;
+use ptybox::runner::RunnerOptions;
+use serde_json::{json, Value};
+
+/// Accept one OTLP request, answer 200, and return its path and JSON body.
+fn collect_one(listener: TcpListener) -> thread::JoinHandle<(String, Value)> {
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let mut content_length = 0;
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header).unwrap();
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        reader
+            .get_mut()
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+            .unwrap();
+        let path = request_line.split(' ').nth(1).unwrap().to_string();
+        (path, serde_json::from_slice(&body).unwrap())
+    })
+}
+
+fn attribute<'a>(span: &'a Value, key: &str) -> &'a Value {
+    let attributes = span["attributes"].as_array().unwrap();
+    let found = attributes.iter().find(|attr| attr["key"] == key);
+    &found.expect("attribute should be present")["value"]
+}
+
+#[test]
+fn run_exports_run_step_and_action_spans() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = format!("http://{}/v1/traces", listener.local_addr().unwrap());
+    let collector = collect_one(listener);
+
+    let policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_executables(vec!["/bin/cat".to_string()])
+        .build();
+    let scenario: Scenario = serde_json::from_value(json!({
+        "scenario_version": 1,
+        "metadata": { "name": "otel" },
+        "run": {
+            "command": "/bin/cat",
+            "args": [],
+            "initial_size": { "rows": 24, "cols": 80 },
+            "policy": policy,
+        },
+        "steps": [{
+            "id": "00000000-0000-0000-0000-000000000001",
+            "name": "type",
+            "action": { "type": "text", "payload": { "text": "hello\n" } },
+            "assert": [{ "type": "screen_contains", "payload": { "text": "hello" } }],
+            "timeout_ms": 2000,
+            "retries": 0,
+        }, {
+            "id": "00000000-0000-0000-0000-000000000002",
+            "name": "quit",
+            "action": { "type": "terminate", "payload": {} },
+            "timeout_ms": 2000,
+            "retries": 0,
+        }],
+    }))
+    .unwrap();
+    let mut config = OtelConfig::new(&endpoint).unwrap();
+    config.service_name = "ptybox-test".to_string();
+    let options = RunnerOptions {
+        otel: Some(config),
+        ..RunnerOptions::default()
+    };
+
+    let run = run_scenario_with_options(scenario, options).unwrap();
+    let (path, body) = collector.join().unwrap();
+
+    assert_eq!(path, "/v1/traces");
+    let resource = &body["resourceSpans"][0];
+    assert_eq!(
+        resource["resource"]["attributes"][0]["value"]["stringValue"],
+        "ptybox-test"
+    );
+    let spans = resource["scopeSpans"][0]["spans"].as_array().unwrap();
+    let names: Vec<&str> = spans.iter().map(|s| s["name"].as_str().unwrap()).collect();
+    assert_eq!(
+        names,
+        [
+            "ptybox.run",
+            "ptybox.step",
+            "ptybox.action",
+            "ptybox.step",
+            "ptybox.action"
+        ]
+    );
+    let (run_span, step_span, action_span) = (&spans[0], &spans[1], &spans[2]);
+
+    let trace_id = run.run_id.to_string().replace('-', "");
+    assert!(spans
+        .iter()
+        .all(|span| span["traceId"] == trace_id.as_str()));
+    assert_eq!(step_span["parentSpanId"], run_span["spanId"]);
+    assert_eq!(action_span["parentSpanId"], step_span["spanId"]);
+    assert_eq!(run_span["status"]["code"], 1);
+
+    assert_eq!(
+        attribute(run_span, "ptybox.run.status")["stringValue"],
+        "passed"
+    );
+    assert_eq!(attribute(run_span, "ptybox.steps")["intValue"], "2");
+    assert_eq!(
+        attribute(step_span, "ptybox.step.name")["stringValue"],
+        "type"
+    );
+    let steps = run.steps.as_ref().unwrap();
+    assert_eq!(
+        attribute(step_span, "ptybox.step.key")["stringValue"],
+        steps[0].step_key.as_deref().unwrap()
+    );
+    let output: u64 = attribute(step_span, "ptybox.output_bytes")["intValue"]
+        .as_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(
+        output >= 5,
+        "step should read the echoed text, got {output}"
+    );
+    assert_eq!(
+        attribute(action_span, "ptybox.action.type")["stringValue"],
+        "text"
+    );
+    assert_eq!(
+        attribute(action_span, "ptybox.input_bytes")["intValue"],
+        "6"
+    );
+}
+
+#[test]
+fn otel_config_rejects_unsupported_endpoints() {
+    let err = OtelConfig::new("https://collector:4318").unwrap_err();
+    assert_eq!(err.code.as_str(), "E_PROTOCOL");
+    assert!(OtelConfig::new("http://collector").is_ok());
+    assert!(OtelConfig::new("http://collector:notaport/v1/traces").is_err());
+}
+
+#[test]
+fn otel_config_sends_headers_only_to_loopback_collectors() {
+    let headers = vec![("authorization".to_string(), "Bearer secret".to_string())];
+    let remote = OtelConfig::new("http://collector:4318").unwrap();
+    let err = remote.with_headers(headers.clone()).unwrap_err();
+    assert_eq!(err.code.as_str(), "E_PROTOCOL");
+    assert!(!err.context.unwrap().to_string().contains("secret"));
+
+    let remote = OtelConfig::new("http://collector:4318").unwrap();
+    assert!(remote.with_headers(Vec::new()).is_ok());
+    for endpoint in [
+        "http://localhost:4318",
+        "http://127.0.0.1:4318",
+        "http://[::1]:4318",
+    ] {
+        let local = OtelConfig::new(endpoint).unwrap();
+        assert!(local.with_headers(headers.clone()).is_ok(), "{endpoint}");
+    }
+}
@@ -0,0 +1,154 @@
+// Test module - relaxed lint rules
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(clippy::panic)]
+#![allow(missing_docs)]
+
+//! Panics inside the runner end the step or run with `E_INTERNAL`.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use ptybox::artifacts::ArtifactsWriterConfig;
+use ptybox::model::policy::PolicyBuilder;
+use ptybox::model::{Observation, RunStatus, Scenario, StepStatus};
+use ptybox::run::run_scenario_with_options;
+use ptybox::runner::{ErrorCode, ObservationTap, ProgressCallback, ProgressEvent, RunnerOptions};
+use serde_json::json;
+
+fn temp_dir(prefix: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ptybox-panic-{prefix}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.canonicalize().unwrap()
+}
+
+fn text_step(id: u8, text: &str) -> serde_json::Value {
+    json!({
+        "id": format!("00000000-0000-0000-0000-{id:012}"),
+        "name": text,
+        "action": { "type": "text", "payload": { "text": format!("{text}\n") } },
+        "assert": [{ "type": "screen_contains", "payload": { "text": text } }],
+        "timeout_ms": 2000,
+        "retries": 0,
+    })
+}
+
+fn cat_scenario(root: &Path) -> Scenario {
+    let policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_executables(vec!["/bin/cat".to_string()])
+        .allowed_write(vec![root.display().to_string()])
+        .build();
+    serde_json::from_value(json!({
+        "scenario_version": 1,
+        "metadata": { "name": "panic" },
+        "run": {
+            "command": "/bin/cat",
+            "args": [],
+            "initial_size": { "rows": 24, "cols": 80 },
+            "policy": policy,
+        },
+        "steps": [text_step(1, "hello"), text_step(2, "boom"), text_step(3, "after")],
+    }))
+    .unwrap()
+}
+
+fn options(root: &Path) -> RunnerOptions {
+    RunnerOptions {
+        artifacts: Some(ArtifactsWriterConfig {
+            dir: root.join("artifacts"),
+            overwrite: false,
+        }),
+        ..RunnerOptions::default()
+    }
+}
+
+fn run_json(root: &Path) -> serde_json::Value {
+    serde_json::from_slice(&std::fs::read(root.join("artifacts").join("run.json")).unwrap())
+        .unwrap()
+}
+
+/// Panics the first time the screen shows `boom`.
+#[derive(Default)]
+struct PanickingTap {
+    fired: AtomicBool,
+}
+
+impl ObservationTap for PanickingTap {
+    fn on_observation(&self, observation: &Observation) {
+        let boom = observation.screen.lines.iter().any(|line| line == "boom");
+        assert!(
+            !boom || self.fired.swap(true, Ordering::SeqCst),
+            "tap exploded"
+        );
+    }
+}
+
+/// Panics when the second step starts.
+struct PanickingProgress;
+
+impl ProgressCallback for PanickingProgress {
+    fn on_progress(&self, event: &ProgressEvent) {
+        if let ProgressEvent::StepStarted { step_index: 2, .. } = event {
+            panic!("progress exploded");
+        }
+    }
+}
+
+#[test]
+fn a_panicking_step_errors_and_the_run_keeps_its_artifacts() {
+    let root = temp_dir("step");
+    let options = RunnerOptions {
+        observation_tap: Some(Arc::new(PanickingTap::default())),
+        ..options(&root)
+    };
+
+    let run = run_scenario_with_options(cat_scenario(&root), options).unwrap();
+
+    assert_eq!(run.status, RunStatus::Failed);
+    let error = run.error.unwrap();
+    assert_eq!(error.code, ErrorCode::Internal.as_str());
+    assert_eq!(error.message, "internal panic during step: tap exploded");
+    let context = error.context.unwrap();
+    assert_eq!(context["boundary"], "step");
+    assert_eq!(context["panic"], "tap exploded");
+    assert!(context["location"]
+        .as_str()
+        .unwrap()
+        .contains("panic_boundaries.rs"));
+    assert!(!context["backtrace"].as_array().unwrap().is_empty());
+    let statuses: Vec<StepStatus> = run.steps.unwrap().into_iter().map(|s| s.status).collect();
+    assert_eq!(
+        statuses,
+        [StepStatus::Passed, StepStatus::Errored, StepStatus::Skipped]
+    );
+
+    let run_json = run_json(&root);
+    assert_eq!(run_json["status"], "failed");
+    assert_eq!(run_json["steps"][1]["error"]["code"], "E_INTERNAL");
+    assert!(root.join("artifacts").join("transcript.log").exists());
+}
+
+#[test]
+fn a_panic_outside_a_step_errors_the_run() {
+    let root = temp_dir("run");
+    let options = RunnerOptions {
+        progress: Some(Arc::new(PanickingProgress)),
+        ..options(&root)
+    };
+
+    let err = run_scenario_with_options(cat_scenario(&root), options).unwrap_err();
+
+    assert_eq!(err.code, ErrorCode::Internal);
+    let context = err.context.unwrap();
+    assert_eq!(context["boundary"], "run");
+    assert_eq!(context["panic"], "progress exploded");
+
+    let run_json = run_json(&root);
+    assert_eq!(run_json["status"], "errored");
+    assert_eq!(run_json["error"]["code"], "E_INTERNAL");
+    assert_eq!(run_json["error"]["context"]["boundary"], "run");
+}
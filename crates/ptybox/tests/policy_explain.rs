@@ -13,7 +13,7 @@
 #![allow(missing_docs)]
 
 use ptybox::model::policy::{FsPolicy, NetworkEnforcementAck, NetworkPolicy, Policy, SandboxMode};
-use ptybox::model::{RunConfig, TerminalSize};
+use ptybox::model::{OutputBufferConfig, ResizeConfig, RunConfig, TerminalSize};
 use ptybox::policy::explain_policy_for_run_config;
 
 #[test]
@@ -30,6 +30,7 @@ fn explain_policy_reports_denials() {
             working_dir: None,
             write_ack: false,
             strict_write: false,
+            write_quotas: Default::default(),
         },
         ..Policy::default()
     };
@@ -39,6 +40,12 @@ fn explain_policy_reports_denials() {
         cwd: Some("/tmp".to_string()),
         initial_size: TerminalSize::default(),
         policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy.clone())),
+        separate_stderr: false,
+        output_buffer: OutputBufferConfig::default(),
+        resize: ResizeConfig::default(),
+        remote: None,
+        validate_args: false,
+        capture_attributes: false,
     };
 
     let explanation = explain_policy_for_run_config(&policy, &run);
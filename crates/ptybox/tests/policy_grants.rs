@@ -0,0 +1,175 @@
+// Test module - relaxed lint rules
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(missing_docs)]
+
+//! Scenario-scoped policy grants.
+
+use std::path::PathBuf;
+
+use ptybox::artifacts::ArtifactsWriterConfig;
+use ptybox::model::policy::{BudgetOverrides, Policy, PolicyBuilder, PolicyGrant};
+use ptybox::model::{RunStatus, Scenario};
+use ptybox::policy::grants::{apply_policy_grants, glob_matches};
+use ptybox::run::run_scenario_with_options;
+use ptybox::runner::{ErrorCode, RunnerOptions};
+use serde_json::json;
+
+fn grant(scenario: &str) -> PolicyGrant {
+    PolicyGrant {
+        scenario: scenario.to_string(),
+        name: None,
+        allowed_read: Vec::new(),
+        allowed_write: Vec::new(),
+        allowed_executables: Vec::new(),
+        budgets: BudgetOverrides::default(),
+    }
+}
+
+fn temp_dir(prefix: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ptybox-grants-{prefix}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.canonicalize().unwrap()
+}
+
+fn echo_scenario(name: &str, policy: &Policy) -> Scenario {
+    serde_json::from_value(json!({
+        "scenario_version": 1,
+        "metadata": { "name": name },
+        "run": {
+            "command": "/bin/echo",
+            "args": ["granted"],
+            "initial_size": { "rows": 24, "cols": 80 },
+            "policy": policy,
+        },
+        "steps": [{
+            "id": "00000000-0000-0000-0000-000000000001",
+            "name": "wait",
+            "action": { "type": "wait", "payload": {
+                "condition": { "type": "screen_contains", "payload": { "text": "granted" } }
+            } },
+            "timeout_ms": 2000,
+            "retries": 0,
+        }],
+    }))
+    .unwrap()
+}
+
+#[test]
+fn glob_matches_wildcards_against_the_whole_name() {
+    let cases = [
+        ("payments-*", "payments-refund", true),
+        ("payments-*", "payments-", true),
+        ("payments-*", "legacy-payments-refund", false),
+        ("*-smoke", "checkout-smoke", true),
+        ("*-smoke", "checkout-smoke-2", false),
+        ("login-?", "login-a", true),
+        ("login-?", "login-ab", false),
+        ("a*b*c", "axxbyyc", true),
+        ("a*b*c", "axxbyy", false),
+        ("exact", "exact", true),
+        ("exact", "exactly", false),
+        ("*", "", true),
+    ];
+    for (pattern, name, expected) in cases {
+        assert_eq!(glob_matches(pattern, name), expected, "{pattern} {name}");
+    }
+}
+
+#[test]
+fn matching_grants_extend_paths_and_replace_budgets() {
+    let mut policy = PolicyBuilder::new()
+        .allowed_read(vec!["/opt/shared".to_string()])
+        .allowed_executables(vec!["/bin/echo".to_string()])
+        .build();
+    let mut wide = grant("payments-*");
+    wide.name = Some("SEC-101".to_string());
+    wide.allowed_read = vec!["/opt/shared".to_string(), "/opt/payments".to_string()];
+    wide.allowed_executables = vec!["/bin/cat".to_string()];
+    wide.budgets.max_runtime_ms = Some(300_000);
+    let mut slow = grant("payments-refund");
+    slow.budgets.max_wait_ms = Some(45_000);
+    let mut other = grant("search-*");
+    other.allowed_write = vec!["/tmp/search".to_string()];
+    policy.grants = vec![wide.clone(), other, slow.clone()];
+    let default_steps = policy.budgets.max_steps;
+
+    let applied = apply_policy_grants(&mut policy, "payments-refund").unwrap();
+
+    assert_eq!(applied, vec![wide, slow]);
+    assert!(policy.grants.is_empty());
+    assert_eq!(policy.fs.allowed_read, ["/opt/shared", "/opt/payments"]);
+    assert!(policy.fs.allowed_write.is_empty());
+    assert_eq!(policy.exec.allowed_executables, ["/bin/echo", "/bin/cat"]);
+    assert_eq!(policy.budgets.max_runtime_ms, 300_000);
+    assert_eq!(policy.budgets.max_wait_ms, 45_000);
+    assert_eq!(policy.budgets.max_steps, default_steps);
+}
+
+#[test]
+fn grants_are_dropped_without_a_match_and_need_a_pattern() {
+    let mut policy = PolicyBuilder::new().grant(grant("payments-*")).build();
+    let before = policy.fs.clone();
+    assert!(apply_policy_grants(&mut policy, "search-basic")
+        .unwrap()
+        .is_empty());
+    assert!(policy.grants.is_empty());
+    assert_eq!(policy.fs, before);
+
+    let mut policy = PolicyBuilder::new()
+        .grant(grant("payments-*"))
+        .grant(grant(""))
+        .build();
+    let err = apply_policy_grants(&mut policy, "payments-refund").unwrap_err();
+    assert_eq!(err.code, ErrorCode::PolicyDenied);
+    assert_eq!(err.context.unwrap()["grant"], 1);
+}
+
+#[test]
+fn scenario_runs_record_the_grants_they_used() {
+    let root = temp_dir("run");
+    let mut echo = grant("echo-*");
+    echo.name = Some("SEC-7 allow echo".to_string());
+    echo.allowed_executables = vec!["/bin/echo".to_string()];
+    let policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_write(vec![root.display().to_string()])
+        .grant(echo.clone())
+        .build();
+
+    // Without a matching grant the executable is not allowlisted.
+    let err = run_scenario_with_options(
+        echo_scenario("search-basic", &policy),
+        RunnerOptions::default(),
+    )
+    .unwrap_err();
+    assert_eq!(err.code, ErrorCode::PolicyDenied);
+
+    let options = RunnerOptions {
+        artifacts: Some(ArtifactsWriterConfig {
+            dir: root.join("artifacts"),
+            overwrite: false,
+        }),
+        ..RunnerOptions::default()
+    };
+    let run = run_scenario_with_options(echo_scenario("echo-granted", &policy), options).unwrap();
+    assert_eq!(run.status, RunStatus::Passed);
+    assert_eq!(run.policy_grants, vec![echo]);
+    assert!(run.policy.grants.is_empty());
+    assert_eq!(run.policy.exec.allowed_executables, ["/bin/echo"]);
+
+    let read = |name: &str| -> serde_json::Value {
+        serde_json::from_slice(&std::fs::read(root.join("artifacts").join(name)).unwrap()).unwrap()
+    };
+    let run_json = read("run.json");
+    assert_eq!(run_json["policy_grants"][0]["name"], "SEC-7 allow echo");
+    assert_eq!(run_json["policy_grants"][0]["scenario"], "echo-*");
+    let policy_json = read("policy.json");
+    assert!(policy_json.get("grants").is_none(), "{policy_json}");
+    assert_eq!(
+        policy_json["exec"]["allowed_executables"],
+        json!(["/bin/echo"])
+    );
+}
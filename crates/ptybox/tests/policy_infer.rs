@@ -0,0 +1,69 @@
+// Test module - relaxed lint rules
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(missing_docs)]
+
+use std::path::Path;
+
+use ptybox::policy::infer::{infer_policy, parse_strace_log, InferConfig};
+use ptybox::runner::ErrorCode;
+
+#[test]
+fn infer_policy_requires_acknowledgement() {
+    let config = InferConfig::new("/bin/echo", vec!["hi".to_string()]);
+    let err = infer_policy(&config).unwrap_err();
+    assert_eq!(err.code, ErrorCode::PolicyDenied);
+    assert!(err.message.contains("without a sandbox"));
+}
+
+#[test]
+fn infer_policy_rejects_unknown_command() {
+    let mut config = InferConfig::new("ptybox-no-such-command", Vec::new());
+    config.ack = true;
+    let err = infer_policy(&config).unwrap_err();
+    assert_eq!(err.code, ErrorCode::CliInvalidArg);
+}
+
+#[test]
+fn parse_strace_log_records_successful_accesses() {
+    let log = r#"100 execve("/usr/bin/app", ["app"], 0x7ffd /* 20 vars */) = 0
+100 openat(AT_FDCWD, "/etc/app.conf", O_RDONLY|O_CLOEXEC) = 3
+100 openat(AT_FDCWD, "data/in.txt", O_RDONLY) = 4
+100 openat(AT_FDCWD, "/srv/missing", O_RDONLY) = -1 ENOENT (No such file or directory)
+100 openat(AT_FDCWD, "/srv/out/log.txt", O_WRONLY|O_CREAT|O_APPEND, 0644) = 5
+100 openat(7, "relative-to-fd", O_RDONLY) = 6
+100 clone3({flags=CLONE_VM, exit_signal=SIGCHLD}, 88) = 101
+101 execve("/usr/bin/helper", ["helper", "say \"hi\""], 0x55 /* 20 vars */ <unfinished ...>
+100 mkdir("/srv/cache", 0755 <unfinished ...>
+101 <... execve resumed>) = 0
+100 <... mkdir resumed>) = 0
+101 renameat2(AT_FDCWD, "/srv/out/a", AT_FDCWD, "/srv/out/b", RENAME_NOREPLACE) = 0
+101 openat(AT_FDCWD, "/srv/db", O_RDWR) = 3
+101 connect(4, {sa_family=AF_INET, sin_port=htons(443), sin_addr=inet_addr("10.0.0.1")}, 16) = 0
+101 +++ exited with 0 +++
+100 --- SIGCHLD {si_signo=SIGCHLD, si_code=CLD_EXITED} ---
+"#;
+    let observed = parse_strace_log(log, Path::new("/work"));
+
+    assert_eq!(
+        observed.executables,
+        vec!["/usr/bin/app", "/usr/bin/helper"]
+    );
+    assert_eq!(
+        observed.read,
+        vec!["/etc/app.conf", "/srv/db", "/work/data/in.txt"]
+    );
+    assert_eq!(
+        observed.written,
+        vec![
+            "/srv/cache",
+            "/srv/db",
+            "/srv/out/a",
+            "/srv/out/b",
+            "/srv/out/log.txt"
+        ]
+    );
+    assert!(observed.network);
+    assert!(observed.env.is_empty());
+}
@@ -12,12 +12,19 @@
 #![allow(clippy::cast_possible_truncation)]
 #![allow(missing_docs)]
 
-use ptybox::model::policy::{FsPolicy, NetworkEnforcementAck, NetworkPolicy, Policy, SandboxMode};
-use ptybox::model::{RunConfig, TerminalSize};
+use ptybox::model::policy::{
+    ArtifactsEncryption, DeterminismPolicy, FsPolicy, KillSignal, NetworkEnforcementAck,
+    NetworkPolicy, Policy, PreKillHook, RemotePolicy, SandboxFallback, SandboxMode, SocketGrant,
+    TranscriptPolicy, TranscriptTruncate,
+};
+use ptybox::model::{OutputBufferConfig, RemoteTarget, ResizeConfig, RunConfig, TerminalSize};
+use ptybox::policy::remote::{validate_remote_policy, validate_remote_target};
 use ptybox::policy::EffectivePolicy;
 use ptybox::policy::{
-    validate_artifacts_dir, validate_env_policy, validate_fs_policy, validate_network_policy,
-    validate_policy_version, validate_sandbox_mode, validate_write_access,
+    resolve_sandbox_enforcement, validate_artifacts_dir, validate_artifacts_encryption,
+    validate_artifacts_policy, validate_determinism_policy, validate_env_policy,
+    validate_fs_policy, validate_network_policy, validate_policy_version, validate_process_policy,
+    validate_sandbox_mode, validate_socket_policy, validate_write_access,
 };
 use ptybox::runner::ErrorCode;
 
@@ -41,6 +48,52 @@ fn sandbox_seatbelt_requires_availability() {
     }
 }
 
+#[test]
+fn sandbox_fallback_warn_requires_acknowledgement() {
+    let policy = Policy {
+        sandbox_fallback: SandboxFallback::Warn { ack: false },
+        ..Policy::default()
+    };
+    let err = resolve_sandbox_enforcement(&policy).unwrap_err();
+    assert_eq!(err.code, ErrorCode::PolicyDenied);
+    assert!(err.message.contains("explicit acknowledgement"));
+}
+
+#[test]
+fn sandbox_fallback_warn_reports_unenforced_aspects_when_unavailable() {
+    let policy = Policy {
+        sandbox_fallback: SandboxFallback::Warn { ack: true },
+        ..Policy::default()
+    };
+    let deny = Policy::default();
+    match resolve_sandbox_enforcement(&policy).unwrap() {
+        // Seatbelt works here: the policy is enforced as written.
+        None => assert!(resolve_sandbox_enforcement(&deny).unwrap().is_none()),
+        Some(report) => {
+            let err = resolve_sandbox_enforcement(&deny).unwrap_err();
+            assert_eq!(err.code, ErrorCode::SandboxUnavailable);
+            assert_eq!(report.reason.code, "E_SANDBOX_UNAVAILABLE");
+            assert!(report
+                .unenforced
+                .iter()
+                .any(|aspect| aspect.aspect == "network"));
+        }
+    }
+}
+
+#[test]
+fn sandbox_fallback_round_trips_through_policy_json() {
+    let mut value = serde_json::to_value(Policy::default()).unwrap();
+    assert!(value.get("sandbox_fallback").is_none());
+    value["sandbox_fallback"] = serde_json::json!("warn");
+    value["sandbox_fallback_ack"] = serde_json::json!(true);
+    let policy: Policy = serde_json::from_value(value).unwrap();
+    assert_eq!(policy.sandbox_fallback, SandboxFallback::Warn { ack: true });
+    let round_trip = serde_json::to_value(&policy).unwrap();
+    assert_eq!(round_trip["sandbox_fallback"], "warn");
+    assert_eq!(round_trip["sandbox_fallback_ack"], true);
+}
+
 #[test]
 fn fs_policy_rejects_root_allowlist() {
     let fs = FsPolicy {
@@ -49,6 +102,7 @@ fn fs_policy_rejects_root_allowlist() {
         working_dir: None,
         write_ack: false,
         strict_write: false,
+        write_quotas: Default::default(),
     };
     let err = validate_fs_policy(&fs).unwrap_err();
     assert_eq!(err.code, ErrorCode::PolicyDenied);
@@ -63,6 +117,7 @@ fn fs_policy_rejects_relative_allowlist_paths() {
         working_dir: None,
         write_ack: false,
         strict_write: false,
+        write_quotas: Default::default(),
     };
     let err = validate_fs_policy(&fs).unwrap_err();
     assert_eq!(err.code, ErrorCode::PolicyDenied);
@@ -77,6 +132,7 @@ fn fs_policy_rejects_relative_write_allowlist_paths() {
         working_dir: None,
         write_ack: true,
         strict_write: false,
+        write_quotas: Default::default(),
     };
     let err = validate_fs_policy(&fs).unwrap_err();
     assert_eq!(err.code, ErrorCode::PolicyDenied);
@@ -91,6 +147,7 @@ fn fs_policy_rejects_relative_working_dir() {
         working_dir: Some("relative".to_string()),
         write_ack: false,
         strict_write: false,
+        write_quotas: Default::default(),
     };
     let err = validate_fs_policy(&fs).unwrap_err();
     assert_eq!(err.code, ErrorCode::PolicyDenied);
@@ -106,6 +163,7 @@ fn run_config_rejects_relative_cwd() {
             working_dir: None,
             write_ack: false,
             strict_write: false,
+            write_quotas: Default::default(),
         },
         exec: ptybox::model::policy::ExecPolicy {
             allowed_executables: vec!["/bin/echo".to_string()],
@@ -119,6 +177,12 @@ fn run_config_rejects_relative_cwd() {
         cwd: Some("relative".to_string()),
         initial_size: TerminalSize::default(),
         policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy.clone())),
+        separate_stderr: false,
+        output_buffer: OutputBufferConfig::default(),
+        resize: ResizeConfig::default(),
+        remote: None,
+        validate_args: false,
+        capture_attributes: false,
     };
     let err = EffectivePolicy::new(policy)
         .validate_run_config(&run)
@@ -136,6 +200,7 @@ fn run_config_rejects_cwd_outside_allowlist() {
             working_dir: None,
             write_ack: false,
             strict_write: false,
+            write_quotas: Default::default(),
         },
         exec: ptybox::model::policy::ExecPolicy {
             allowed_executables: vec!["/bin/echo".to_string()],
@@ -149,6 +214,12 @@ fn run_config_rejects_cwd_outside_allowlist() {
         cwd: Some("/tmp/blocked".to_string()),
         initial_size: TerminalSize::default(),
         policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy.clone())),
+        separate_stderr: false,
+        output_buffer: OutputBufferConfig::default(),
+        resize: ResizeConfig::default(),
+        remote: None,
+        validate_args: false,
+        capture_attributes: false,
     };
     let err = EffectivePolicy::new(policy)
         .validate_run_config(&run)
@@ -170,6 +241,7 @@ fn exec_policy_rejects_relative_allowed_executable() {
             working_dir: None,
             write_ack: false,
             strict_write: false,
+            write_quotas: Default::default(),
         },
         ..Policy::default()
     };
@@ -179,6 +251,12 @@ fn exec_policy_rejects_relative_allowed_executable() {
         cwd: Some("/tmp".to_string()),
         initial_size: TerminalSize::default(),
         policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy.clone())),
+        separate_stderr: false,
+        output_buffer: OutputBufferConfig::default(),
+        resize: ResizeConfig::default(),
+        remote: None,
+        validate_args: false,
+        capture_attributes: false,
     };
     let err = EffectivePolicy::new(policy)
         .validate_run_config(&run)
@@ -196,6 +274,7 @@ fn fs_policy_rejects_home_allowlist() {
         working_dir: None,
         write_ack: false,
         strict_write: false,
+        write_quotas: Default::default(),
     };
     let err = validate_fs_policy(&fs).unwrap_err();
     assert_eq!(err.code, ErrorCode::PolicyDenied);
@@ -218,6 +297,7 @@ fn fs_policy_rejects_system_allowlists() {
             working_dir: None,
             write_ack: false,
             strict_write: false,
+            write_quotas: Default::default(),
         };
         let err = validate_fs_policy(&fs).unwrap_err();
         assert_eq!(err.code, ErrorCode::PolicyDenied);
@@ -233,12 +313,41 @@ fn fs_policy_rejects_working_dir_with_traversal() {
         working_dir: Some("/tmp/allowed/../blocked".to_string()),
         write_ack: false,
         strict_write: false,
+        write_quotas: Default::default(),
     };
     let err = validate_fs_policy(&fs).unwrap_err();
     assert_eq!(err.code, ErrorCode::PolicyDenied);
     assert!(err.message.contains("working_dir"));
 }
 
+#[test]
+fn fs_policy_write_quotas_must_be_positive_and_inside_write_allowlist() {
+    let quota_fs = |path: &str, max_bytes: u64| FsPolicy {
+        allowed_read: Vec::new(),
+        allowed_write: vec!["/tmp/allowed".to_string()],
+        working_dir: None,
+        write_ack: true,
+        strict_write: false,
+        write_quotas: [(path.to_string(), max_bytes)].into_iter().collect(),
+    };
+    validate_fs_policy(&quota_fs("/tmp/allowed/cache", 1024)).unwrap();
+
+    for (path, max_bytes, message) in [
+        ("/tmp/other", 1024, "not within allowlisted write paths"),
+        (
+            "/tmp/allowed/../other",
+            1024,
+            "not within allowlisted write paths",
+        ),
+        ("allowed", 1024, "not within allowlisted write paths"),
+        ("/tmp/allowed", 0, "greater than zero"),
+    ] {
+        let err = validate_fs_policy(&quota_fs(path, max_bytes)).unwrap_err();
+        assert_eq!(err.code, ErrorCode::PolicyDenied);
+        assert!(err.message.contains(message), "{path}: {}", err.message);
+    }
+}
+
 #[test]
 fn artifacts_dir_requires_write_allowlist() {
     let fs = FsPolicy {
@@ -247,6 +356,7 @@ fn artifacts_dir_requires_write_allowlist() {
         working_dir: None,
         write_ack: false,
         strict_write: false,
+        write_quotas: Default::default(),
     };
     let err = validate_artifacts_dir(std::path::Path::new("/tmp/output"), &fs).unwrap_err();
     assert_eq!(err.code, ErrorCode::PolicyDenied);
@@ -261,6 +371,7 @@ fn artifacts_dir_denies_traversal_outside_allowlist() {
         working_dir: None,
         write_ack: true,
         strict_write: false,
+        write_quotas: Default::default(),
     };
     let err =
         validate_artifacts_dir(std::path::Path::new("/tmp/allowed/../blocked"), &fs).unwrap_err();
@@ -276,6 +387,7 @@ fn artifacts_dir_requires_absolute_path() {
         working_dir: None,
         write_ack: true,
         strict_write: false,
+        write_quotas: Default::default(),
     };
     let err = validate_artifacts_dir(std::path::Path::new("relative/output"), &fs).unwrap_err();
     assert_eq!(err.code, ErrorCode::PolicyDenied);
@@ -314,6 +426,7 @@ fn strict_write_mode_requires_ack_for_artifacts() {
             enabled: true,
             dir: Some("/tmp/artifacts".to_string()),
             overwrite: false,
+            ..Default::default()
         },
         ..Policy::default()
     };
@@ -322,6 +435,55 @@ fn strict_write_mode_requires_ack_for_artifacts() {
     assert!(err.message.contains("write access"));
 }
 
+#[test]
+fn artifacts_encryption_requires_age_recipients_and_absolute_binary() {
+    let valid = ArtifactsEncryption {
+        recipients: vec![
+            "age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8p".to_string(),
+        ],
+        age_binary: "/usr/bin/age".to_string(),
+    };
+    validate_artifacts_encryption(&valid).unwrap();
+
+    let invalid = [
+        ArtifactsEncryption {
+            recipients: Vec::new(),
+            ..valid.clone()
+        },
+        ArtifactsEncryption {
+            recipients: vec!["ssh-ed25519 AAAA".to_string()],
+            ..valid.clone()
+        },
+        ArtifactsEncryption {
+            recipients: vec!["age1NOTBECH32".to_string()],
+            ..valid.clone()
+        },
+        ArtifactsEncryption {
+            age_binary: "age".to_string(),
+            ..valid.clone()
+        },
+    ];
+    for encryption in &invalid {
+        let err = validate_artifacts_encryption(encryption).unwrap_err();
+        assert_eq!(err.code, ErrorCode::PolicyDenied, "{encryption:?}");
+    }
+}
+
+#[test]
+fn head_tail_transcript_requires_a_nonzero_budget() {
+    let mut policy = Policy::default();
+    policy.artifacts.transcript = TranscriptPolicy {
+        truncate: TranscriptTruncate::HeadTail,
+        head_kib: 0,
+        tail_kib: 0,
+    };
+    let err = validate_artifacts_policy(&policy).unwrap_err();
+    assert_eq!(err.code, ErrorCode::PolicyDenied);
+
+    policy.artifacts.transcript.tail_kib = 4;
+    validate_artifacts_policy(&policy).unwrap();
+}
+
 #[test]
 fn strict_write_mode_allows_ack() {
     let policy = Policy {
@@ -350,6 +512,175 @@ fn env_policy_requires_allowlist_for_set() {
     assert!(err.message.contains("allowlist"));
 }
 
+#[test]
+fn determinism_pins_env_past_allowlist_and_overrides_set() {
+    let mut policy = Policy::default();
+    policy
+        .env
+        .set
+        .insert("TZ".to_string(), "America/Chicago".to_string());
+    policy.env.allowlist.push("TZ".to_string());
+    policy.determinism = DeterminismPolicy {
+        locale: Some("C.UTF-8".to_string()),
+        timezone: Some("UTC".to_string()),
+        paced_input: false,
+    };
+    validate_env_policy(&policy.effective_env()).unwrap();
+    let env = policy.effective_env();
+    assert_eq!(env.set["LANG"], "C.UTF-8");
+    assert_eq!(env.set["LC_ALL"], "C.UTF-8");
+    assert_eq!(env.set["TZ"], "UTC");
+    assert_eq!(env.allowlist.iter().filter(|key| *key == "TZ").count(), 1);
+    assert!(env.allowlist.iter().any(|key| key == "LANG"));
+}
+
+#[test]
+fn determinism_rejects_malformed_values() {
+    for (locale, timezone) in [(Some(""), None), (None, Some("UTC\nLD_PRELOAD=x"))] {
+        let determinism = DeterminismPolicy {
+            locale: locale.map(str::to_string),
+            timezone: timezone.map(str::to_string),
+            paced_input: false,
+        };
+        let err = validate_determinism_policy(&determinism).unwrap_err();
+        assert_eq!(err.code, ErrorCode::PolicyDenied);
+    }
+    validate_determinism_policy(&DeterminismPolicy {
+        locale: Some("de_DE.UTF-8@euro".to_string()),
+        timezone: Some("America/Argentina/Buenos_Aires".to_string()),
+        paced_input: false,
+    })
+    .unwrap();
+}
+
+#[test]
+fn determinism_round_trips_through_policy_json() {
+    let mut value = serde_json::to_value(Policy::default()).unwrap();
+    assert!(value.get("determinism").is_none());
+    value["determinism"] = serde_json::json!({"timezone": "Asia/Tokyo"});
+    let policy: Policy = serde_json::from_value(value).unwrap();
+    assert_eq!(policy.determinism.timezone.as_deref(), Some("Asia/Tokyo"));
+    assert!(policy.determinism.locale.is_none());
+    let round_trip = serde_json::to_value(&policy).unwrap();
+    assert_eq!(
+        round_trip["determinism"],
+        serde_json::json!({"timezone": "Asia/Tokyo"})
+    );
+}
+
+#[test]
+fn process_policy_requires_allowlisted_pre_kill_hook() {
+    let mut policy = Policy::default();
+    policy.process.pre_kill_hook = Some(PreKillHook {
+        command: "/usr/bin/gcore".to_string(),
+        args: Vec::new(),
+        timeout_ms: 1000,
+    });
+    let err = validate_process_policy(&policy).unwrap_err();
+    assert_eq!(err.code, ErrorCode::PolicyDenied);
+    assert!(err.message.contains("not allowlisted"));
+
+    policy.exec.allowed_executables = vec!["/usr/bin/gcore".to_string()];
+    validate_process_policy(&policy).unwrap();
+
+    if let Some(hook) = policy.process.pre_kill_hook.as_mut() {
+        hook.command = "gcore".to_string();
+    }
+    let err = validate_process_policy(&policy).unwrap_err();
+    assert!(err.message.contains("absolute path"));
+}
+
+fn socket(name: &str, env: Option<&str>) -> SocketGrant {
+    SocketGrant {
+        name: name.to_string(),
+        env: env.map(str::to_string),
+    }
+}
+
+#[test]
+fn socket_policy_requires_plain_unique_names_and_env_vars() {
+    let mut policy = Policy {
+        sockets: vec![socket("mock-api", None), socket("db", Some("DB_SOCKET"))],
+        ..Policy::default()
+    };
+    validate_socket_policy(&policy).unwrap();
+    assert_eq!(policy.sockets[0].env_var(), "PTYBOX_SOCKET_MOCK_API");
+
+    for (sockets, message) in [
+        (vec![socket("../up", None)], "invalid socket name"),
+        (vec![socket("", None)], "invalid socket name"),
+        (vec![socket(&"x".repeat(33), None)], "invalid socket name"),
+        (
+            vec![socket("db", None), socket("db", Some("OTHER"))],
+            "duplicate socket name",
+        ),
+        (
+            vec![socket("db", Some("LD_PRELOAD"))],
+            "invalid socket environment variable",
+        ),
+        (
+            vec![socket("db", Some("1BAD"))],
+            "invalid socket environment variable",
+        ),
+        (
+            vec![socket("a", Some("SAME")), socket("b", Some("SAME"))],
+            "duplicate socket environment variable",
+        ),
+    ] {
+        policy.sockets = sockets;
+        let err = validate_socket_policy(&policy).unwrap_err();
+        assert_eq!(err.code, ErrorCode::PolicyDenied);
+        assert_eq!(err.message, message);
+    }
+}
+
+#[test]
+fn socket_policy_round_trips_through_policy_json() {
+    let mut value = serde_json::to_value(Policy::default()).unwrap();
+    assert!(value.get("sockets").is_none());
+    value["sockets"] = serde_json::json!([{"name": "mock"}, {"name": "db", "env": "DB_SOCKET"}]);
+    let policy: Policy = serde_json::from_value(value).unwrap();
+    assert_eq!(
+        policy.sockets,
+        vec![socket("mock", None), socket("db", Some("DB_SOCKET"))]
+    );
+    let round_trip = serde_json::to_value(&policy).unwrap();
+    assert_eq!(
+        round_trip["sockets"],
+        serde_json::json!([{"name": "mock"}, {"name": "db", "env": "DB_SOCKET"}])
+    );
+}
+
+#[test]
+fn process_policy_round_trips_through_policy_json() {
+    let mut value = serde_json::to_value(Policy::default()).unwrap();
+    assert!(value.get("process").is_none());
+    value["process"] = serde_json::json!({"kill_signal": "SIGINT", "term_grace_ms": 1500});
+    let policy: Policy = serde_json::from_value(value).unwrap();
+    assert_eq!(policy.process.kill_signal, KillSignal::Int);
+    assert_eq!(policy.process.term_grace_ms, 1500);
+    let round_trip = serde_json::to_value(&policy).unwrap();
+    assert_eq!(
+        round_trip["process"],
+        serde_json::json!({"kill_signal": "SIGINT", "term_grace_ms": 1500})
+    );
+}
+
+#[test]
+fn determinism_matrix_is_cartesian_product() {
+    let cells = DeterminismPolicy::matrix(
+        &["C.UTF-8".to_string(), "ja_JP.UTF-8".to_string()],
+        &["UTC".to_string()],
+    );
+    assert_eq!(cells.len(), 2);
+    assert_eq!(cells[1].locale.as_deref(), Some("ja_JP.UTF-8"));
+    assert_eq!(cells[1].timezone.as_deref(), Some("UTC"));
+    assert!(DeterminismPolicy::matrix(&[], &[]).is_empty());
+    let tz_only = DeterminismPolicy::matrix(&[], &["UTC".to_string()]);
+    assert_eq!(tz_only.len(), 1);
+    assert!(tz_only[0].locale.is_none());
+}
+
 #[test]
 fn network_enabled_requires_ack_when_unsandboxed() {
     let policy = Policy {
@@ -414,6 +745,7 @@ fn write_allowlist_requires_explicit_ack() {
         working_dir: None,
         write_ack: false,
         strict_write: false,
+        write_quotas: Default::default(),
     };
     let err = validate_fs_policy(&fs).unwrap_err();
     assert_eq!(err.code, ErrorCode::PolicyDenied);
@@ -428,6 +760,7 @@ fn write_allowlist_with_ack_is_allowed() {
         working_dir: None,
         write_ack: true,
         strict_write: false,
+        write_quotas: Default::default(),
     };
     validate_fs_policy(&fs).unwrap();
 }
@@ -469,6 +802,7 @@ fn shell_detection_blocks_direct_shell_invocation() {
                 working_dir: None,
                 write_ack: false,
                 strict_write: false,
+                write_quotas: Default::default(),
             },
             ..Policy::default()
         };
@@ -478,6 +812,12 @@ fn shell_detection_blocks_direct_shell_invocation() {
             cwd: Some("/tmp".to_string()),
             initial_size: TerminalSize::default(),
             policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy.clone())),
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         };
         let err = EffectivePolicy::new(policy)
             .validate_run_config(&run)
@@ -506,6 +846,7 @@ fn shell_detection_blocks_sh_extension() {
             working_dir: None,
             write_ack: false,
             strict_write: false,
+            write_quotas: Default::default(),
         },
         ..Policy::default()
     };
@@ -515,6 +856,12 @@ fn shell_detection_blocks_sh_extension() {
         cwd: Some("/tmp".to_string()),
         initial_size: TerminalSize::default(),
         policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy.clone())),
+        separate_stderr: false,
+        output_buffer: OutputBufferConfig::default(),
+        resize: ResizeConfig::default(),
+        remote: None,
+        validate_args: false,
+        capture_attributes: false,
     };
     let err = EffectivePolicy::new(policy)
         .validate_run_config(&run)
@@ -540,6 +887,7 @@ fn shell_detection_allows_interpreter_dash_c_flag() {
             working_dir: None,
             write_ack: false,
             strict_write: false,
+            write_quotas: Default::default(),
         },
         ..Policy::default()
     };
@@ -549,6 +897,12 @@ fn shell_detection_allows_interpreter_dash_c_flag() {
         cwd: Some("/tmp".to_string()),
         initial_size: TerminalSize::default(),
         policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy.clone())),
+        separate_stderr: false,
+        output_buffer: OutputBufferConfig::default(),
+        resize: ResizeConfig::default(),
+        remote: None,
+        validate_args: false,
+        capture_attributes: false,
     };
     // This should succeed - Python -c is not shell execution
     let result = EffectivePolicy::new(policy).validate_run_config(&run);
@@ -573,6 +927,7 @@ fn shell_detection_blocks_shell_with_dash_c_flag() {
             working_dir: None,
             write_ack: false,
             strict_write: false,
+            write_quotas: Default::default(),
         },
         ..Policy::default()
     };
@@ -582,6 +937,12 @@ fn shell_detection_blocks_shell_with_dash_c_flag() {
         cwd: Some("/tmp".to_string()),
         initial_size: TerminalSize::default(),
         policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy.clone())),
+        separate_stderr: false,
+        output_buffer: OutputBufferConfig::default(),
+        resize: ResizeConfig::default(),
+        remote: None,
+        validate_args: false,
+        capture_attributes: false,
     };
     let err = EffectivePolicy::new(policy)
         .validate_run_config(&run)
@@ -634,6 +995,7 @@ fn shell_detection_blocks_symlinked_shell() {
             working_dir: None,
             write_ack: false,
             strict_write: false,
+            write_quotas: Default::default(),
         },
         ..Policy::default()
     };
@@ -643,6 +1005,12 @@ fn shell_detection_blocks_symlinked_shell() {
         cwd: Some("/tmp".to_string()),
         initial_size: TerminalSize::default(),
         policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy.clone())),
+        separate_stderr: false,
+        output_buffer: OutputBufferConfig::default(),
+        resize: ResizeConfig::default(),
+        remote: None,
+        validate_args: false,
+        capture_attributes: false,
     };
     let err = EffectivePolicy::new(policy)
         .validate_run_config(&run)
@@ -669,6 +1037,7 @@ fn shell_detection_allows_non_shell_commands() {
             working_dir: None,
             write_ack: false,
             strict_write: false,
+            write_quotas: Default::default(),
         },
         ..Policy::default()
     };
@@ -678,6 +1047,12 @@ fn shell_detection_allows_non_shell_commands() {
         cwd: Some("/tmp".to_string()),
         initial_size: TerminalSize::default(),
         policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy.clone())),
+        separate_stderr: false,
+        output_buffer: OutputBufferConfig::default(),
+        resize: ResizeConfig::default(),
+        remote: None,
+        validate_args: false,
+        capture_attributes: false,
     };
     // Should succeed - echo is not a shell
     EffectivePolicy::new(policy)
@@ -699,6 +1074,7 @@ fn shell_detection_allows_shells_when_enabled() {
             working_dir: None,
             write_ack: false,
             strict_write: false,
+            write_quotas: Default::default(),
         },
         ..Policy::default()
     };
@@ -708,6 +1084,12 @@ fn shell_detection_allows_shells_when_enabled() {
         cwd: Some("/tmp".to_string()),
         initial_size: TerminalSize::default(),
         policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy.clone())),
+        separate_stderr: false,
+        output_buffer: OutputBufferConfig::default(),
+        resize: ResizeConfig::default(),
+        remote: None,
+        validate_args: false,
+        capture_attributes: false,
     };
     // Should succeed when allow_shell is true
     EffectivePolicy::new(policy)
@@ -729,6 +1111,7 @@ fn policy_handles_unicode_paths() {
             working_dir: Some("/tmp/日本語ディレクトリ".to_string()),
             write_ack: true,
             strict_write: false,
+            write_quotas: Default::default(),
         },
         exec: ptybox::model::policy::ExecPolicy {
             allowed_executables: vec!["/bin/echo".to_string()],
@@ -743,6 +1126,12 @@ fn policy_handles_unicode_paths() {
         cwd: Some("/tmp/日本語ディレクトリ".to_string()),
         initial_size: TerminalSize::default(),
         policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy.clone())),
+        separate_stderr: false,
+        output_buffer: OutputBufferConfig::default(),
+        resize: ResizeConfig::default(),
+        remote: None,
+        validate_args: false,
+        capture_attributes: false,
     };
 
     // Should not panic - unicode paths are valid
@@ -771,6 +1160,7 @@ fn policy_handles_very_long_paths() {
             working_dir: None,
             write_ack: false,
             strict_write: false,
+            write_quotas: Default::default(),
         },
         exec: ptybox::model::policy::ExecPolicy {
             allowed_executables: vec!["/bin/echo".to_string()],
@@ -785,6 +1175,12 @@ fn policy_handles_very_long_paths() {
         cwd: Some(long_path.clone()),
         initial_size: TerminalSize::default(),
         policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy.clone())),
+        separate_stderr: false,
+        output_buffer: OutputBufferConfig::default(),
+        resize: ResizeConfig::default(),
+        remote: None,
+        validate_args: false,
+        capture_attributes: false,
     };
 
     // Should not panic - long paths should be processed
@@ -806,6 +1202,7 @@ fn policy_handles_paths_with_special_characters() {
             working_dir: Some(special_path.to_string()),
             write_ack: false,
             strict_write: false,
+            write_quotas: Default::default(),
         },
         exec: ptybox::model::policy::ExecPolicy {
             allowed_executables: vec!["/bin/echo".to_string()],
@@ -820,6 +1217,12 @@ fn policy_handles_paths_with_special_characters() {
         cwd: Some(special_path.to_string()),
         initial_size: TerminalSize::default(),
         policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy.clone())),
+        separate_stderr: false,
+        output_buffer: OutputBufferConfig::default(),
+        resize: ResizeConfig::default(),
+        remote: None,
+        validate_args: false,
+        capture_attributes: false,
     };
 
     // Should not panic - special characters in paths are valid
@@ -838,6 +1241,7 @@ fn policy_handles_empty_path_lists() {
             working_dir: None,
             write_ack: false,
             strict_write: false,
+            write_quotas: Default::default(),
         },
         exec: ptybox::model::policy::ExecPolicy {
             allowed_executables: vec!["/bin/echo".to_string()],
@@ -852,6 +1256,12 @@ fn policy_handles_empty_path_lists() {
         cwd: None,
         initial_size: TerminalSize::default(),
         policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy.clone())),
+        separate_stderr: false,
+        output_buffer: OutputBufferConfig::default(),
+        resize: ResizeConfig::default(),
+        remote: None,
+        validate_args: false,
+        capture_attributes: false,
     };
 
     // Should not panic - empty lists are valid (deny-by-default)
@@ -864,3 +1274,190 @@ fn policy_handles_empty_path_lists() {
         result.err()
     );
 }
+
+#[test]
+fn acknowledgement_report_is_empty_for_safe_policy() {
+    use ptybox::policy::acknowledgements::acknowledgement_report;
+
+    assert!(acknowledgement_report(&Policy::default(), &[]).is_none());
+}
+
+#[test]
+fn acknowledgement_report_prefers_overrides_for_active_options() {
+    use ptybox::model::{AckSource, Acknowledgement, UnsafeOption};
+    use ptybox::policy::acknowledgements::acknowledgement_report;
+
+    let policy = Policy {
+        sandbox: SandboxMode::Disabled { ack: true },
+        ..Policy::default()
+    };
+    let overrides = [
+        Acknowledgement {
+            option: UnsafeOption::SandboxDisabled,
+            source: AckSource::Cli,
+            origin: "--no-sandbox --ack-unsafe-sandbox".to_string(),
+            recorded_at_ms: 7,
+        },
+        Acknowledgement {
+            option: UnsafeOption::FsWrite,
+            source: AckSource::Env,
+            origin: "PTYBOX_ACK_UNSAFE_WRITE=1".to_string(),
+            recorded_at_ms: 7,
+        },
+    ];
+
+    let report = acknowledgement_report(&policy, &overrides).unwrap();
+    assert_eq!(report.acknowledgements, overrides[..1]);
+    assert_eq!(
+        report.summary,
+        "unsafe: sandbox_disabled (cli: --no-sandbox --ack-unsafe-sandbox)"
+    );
+}
+
+fn remote_target(host: &str, port: u16, user: &str) -> RemoteTarget {
+    RemoteTarget {
+        host: host.to_string(),
+        port,
+        user: user.to_string(),
+        key_path: "/etc/ptybox/appliance_ed25519".to_string(),
+    }
+}
+
+#[test]
+fn remote_policy_allows_only_listed_hosts_users_ports_and_keys() {
+    let remote = RemotePolicy {
+        allowed_hosts: vec![
+            "appliance.lan".to_string(),
+            "qa@10.0.0.5:2222".to_string(),
+            "[fd00::5]:2200".to_string(),
+        ],
+        allowed_keys: vec!["/etc/ptybox/appliance_ed25519".to_string()],
+        known_hosts: Some("/etc/ptybox/known_hosts".to_string()),
+        ..RemotePolicy::default()
+    };
+    validate_remote_policy(&remote).unwrap();
+    for target in [
+        remote_target("appliance.lan", 22, "anyone"),
+        remote_target("APPLIANCE.lan", 22, "root"),
+        remote_target("10.0.0.5", 2222, "qa"),
+        remote_target("fd00::5", 2200, "qa"),
+    ] {
+        validate_remote_target(&remote, &target).unwrap();
+    }
+
+    for (target, message) in [
+        (
+            remote_target("appliance.lan", 2222, "qa"),
+            "remote host is not allowlisted",
+        ),
+        (
+            remote_target("10.0.0.5", 2222, "root"),
+            "remote host is not allowlisted",
+        ),
+        (
+            remote_target("other.lan", 22, "qa"),
+            "remote host is not allowlisted",
+        ),
+        (
+            RemoteTarget {
+                key_path: "/home/qa/.ssh/id_ed25519".to_string(),
+                ..remote_target("appliance.lan", 22, "qa")
+            },
+            "ssh key is not allowlisted",
+        ),
+    ] {
+        let err = validate_remote_target(&remote, &target).unwrap_err();
+        assert_eq!(err.code, ErrorCode::PolicyDenied);
+        assert_eq!(err.message, message);
+    }
+
+    let without_known_hosts = RemotePolicy {
+        known_hosts: None,
+        ..remote.clone()
+    };
+    let err = validate_remote_target(
+        &without_known_hosts,
+        &remote_target("appliance.lan", 22, "qa"),
+    )
+    .unwrap_err();
+    assert!(err.message.contains("known_hosts"), "{}", err.message);
+
+    let err = validate_remote_target(
+        &RemotePolicy::default(),
+        &remote_target("appliance.lan", 22, "qa"),
+    )
+    .unwrap_err();
+    assert_eq!(err.message, "remote host is not allowlisted");
+}
+
+#[test]
+fn remote_policy_rejects_malformed_entries_and_relative_paths() {
+    for remote in [
+        RemotePolicy {
+            allowed_hosts: vec!["@appliance.lan".to_string()],
+            ..RemotePolicy::default()
+        },
+        RemotePolicy {
+            allowed_hosts: vec!["appliance.lan:ssh".to_string()],
+            ..RemotePolicy::default()
+        },
+        RemotePolicy {
+            allowed_hosts: vec!["appliance lan".to_string()],
+            ..RemotePolicy::default()
+        },
+        RemotePolicy {
+            allowed_keys: vec!["keys/appliance".to_string()],
+            ..RemotePolicy::default()
+        },
+        RemotePolicy {
+            known_hosts: Some("known_hosts".to_string()),
+            ..RemotePolicy::default()
+        },
+    ] {
+        let err = validate_remote_policy(&remote).unwrap_err();
+        assert_eq!(err.code, ErrorCode::PolicyDenied, "{remote:?}");
+    }
+}
+
+#[test]
+fn run_config_checks_remote_target_but_not_remote_cwd_against_fs() {
+    let policy = Policy {
+        exec: ptybox::model::policy::ExecPolicy {
+            allowed_executables: vec!["/opt/appliance/bin/console".to_string()],
+            allow_shell: false,
+        },
+        remote: RemotePolicy {
+            allowed_hosts: vec!["qa@appliance.lan".to_string()],
+            allowed_keys: vec!["/etc/ptybox/appliance_ed25519".to_string()],
+            known_hosts: Some("/etc/ptybox/known_hosts".to_string()),
+            ..RemotePolicy::default()
+        },
+        ..Policy::default()
+    };
+    let mut run = RunConfig {
+        command: "/opt/appliance/bin/console".to_string(),
+        args: Vec::new(),
+        cwd: Some("/var/lib/appliance".to_string()),
+        initial_size: TerminalSize::default(),
+        policy: ptybox::model::scenario::PolicyRef::Inline(Box::new(policy.clone())),
+        separate_stderr: false,
+        output_buffer: OutputBufferConfig::default(),
+        resize: ResizeConfig::default(),
+        remote: Some(remote_target("appliance.lan", 22, "qa")),
+        validate_args: false,
+        capture_attributes: false,
+    };
+    let effective = EffectivePolicy::new(policy);
+    effective.validate_run_config(&run).unwrap();
+
+    run.remote = Some(remote_target("appliance.lan", 22, "root"));
+    let err = effective.validate_run_config(&run).unwrap_err();
+    assert_eq!(err.message, "remote host is not allowlisted");
+
+    run.remote = None;
+    let err = effective.validate_run_config(&run).unwrap_err();
+    assert_eq!(
+        err.message,
+        "working directory is not within allowlisted paths"
+    );
+}
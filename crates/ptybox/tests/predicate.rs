@@ -0,0 +1,246 @@
+// Test module - relaxed lint rules
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(clippy::panic)]
+#![allow(missing_docs)]
+
+//! Shared predicate grammar for waits, assertions, and watchers.
+
+use ptybox::assertions::evaluate_with_exit_status;
+use ptybox::model::scenario::Assertion;
+use ptybox::model::{
+    Cursor, ExitStatus, Observation, RunId, ScreenSnapshot, SessionId, SnapshotId,
+};
+use ptybox::predicate::{Predicate, PredicateContext, PREDICATES};
+use ptybox::runner::ErrorCode;
+use serde_json::Value;
+
+fn observation(lines: &[&str]) -> Observation {
+    Observation {
+        protocol_version: 1,
+        run_id: RunId::new(),
+        session_id: SessionId::new(),
+        timestamp_ms: 0,
+        screen: ScreenSnapshot {
+            snapshot_version: 1,
+            snapshot_id: SnapshotId::new(),
+            rows: 24,
+            cols: 80,
+            cursor: Cursor {
+                row: 0,
+                col: 0,
+                visible: true,
+            },
+            alternate_screen: false,
+            lines: lines.iter().map(ToString::to_string).collect(),
+            cells: None,
+        },
+        transcript_delta: None,
+        events: Vec::new(),
+        stderr_delta: None,
+        output_flow: None,
+        read_truncated: false,
+        read_bytes: 0,
+        read_dropped_bytes: 0,
+        widgets: Vec::new(),
+    }
+}
+
+/// A payload carrying every field any predicate needs.
+fn full_payload() -> Value {
+    serde_json::json!({
+        "text": "ready",
+        "pattern": "re+ady",
+        "row": 0,
+        "col": 0,
+        "line": 0,
+        "code": 0,
+        "ms": 100,
+        "lines": ["ready"],
+    })
+}
+
+#[test]
+fn every_documented_predicate_parses() {
+    for spec in PREDICATES {
+        for name in std::iter::once(spec.name).chain(spec.aliases.iter().copied()) {
+            Predicate::parse(name, &full_payload())
+                .unwrap_or_else(|err| panic!("{name} did not parse: {err}"));
+        }
+        for (field, _) in spec.payload {
+            if matches!(*field, "for_ms" | "code" | "start_line" | "title") {
+                continue;
+            }
+            let mut payload = full_payload();
+            payload.as_object_mut().unwrap().remove(*field);
+            let err = Predicate::parse(spec.name, &payload).unwrap_err();
+            assert_eq!(err.code, ErrorCode::Protocol);
+            assert!(err.message.contains(field), "{}", err.message);
+        }
+    }
+}
+
+#[test]
+fn unknown_predicate_lists_supported_types() {
+    let err = Predicate::parse("screen_glows", &Value::Null).unwrap_err();
+    assert_eq!(err.code, ErrorCode::Protocol);
+    let supported = err.context.unwrap()["supported_types"].clone();
+    assert_eq!(supported.as_array().unwrap().len(), PREDICATES.len());
+    assert!(supported
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|name| name == "process_exited"));
+}
+
+#[test]
+fn predicates_read_exit_status_from_context() {
+    let exited = ExitStatus {
+        success: false,
+        exit_code: Some(2),
+        signal: None,
+        terminated_by_harness: false,
+        termination: None,
+    };
+    let screen = observation(&["done"]);
+    let context = PredicateContext {
+        exit_status: Some(&exited),
+        ..PredicateContext::default()
+    };
+
+    let process_exited = Predicate::parse("process_exited", &Value::Null).unwrap();
+    assert!(process_exited.evaluate(&screen, &context).0);
+    assert!(
+        !process_exited
+            .evaluate(&screen, &PredicateContext::default())
+            .0
+    );
+
+    let exit_code = Predicate::parse("exit_code", &serde_json::json!({"code": 2})).unwrap();
+    assert!(exit_code.evaluate(&screen, &context).0);
+}
+
+#[test]
+fn wait_conditions_work_as_assertions() {
+    let screen = observation(&["ready"]);
+    let matches = Assertion {
+        assertion_type: "screen_matches".to_string(),
+        payload: serde_json::json!({"pattern": "^re+ady$"}),
+        inherited: false,
+    };
+    assert!(evaluate_with_exit_status(&screen, &matches, None).0);
+
+    let process_exited = Assertion {
+        assertion_type: "process_exited".to_string(),
+        payload: serde_json::json!({}),
+        inherited: false,
+    };
+    let (passed, message, _) = evaluate_with_exit_status(&screen, &process_exited, None);
+    assert!(!passed);
+    assert_eq!(message.as_deref(), Some("process has not exited"));
+}
+
+#[test]
+fn expected_screen_matches_cells_with_wildcards() {
+    let screen = observation(&["Welcome, alice", "> item 1   ", "status: OK (3 files)"]);
+    let holds = |lines: &[&str], start_line: u64| {
+        let lines: Vec<String> = lines.iter().map(ToString::to_string).collect();
+        let assertion = Assertion::expected_screen(&lines, start_line);
+        evaluate_with_exit_status(&screen, &assertion, None).0
+    };
+
+    assert!(holds(&["Welcome, *", "> item ?", "status: * (? files)"], 0));
+    assert!(holds(&["> item 1"], 1), "trailing whitespace is ignored");
+    assert!(holds(&["*", "*"], 0), "a lone * matches any line");
+    assert!(!holds(&["Welcome, ?"], 0), "? matches exactly one cell");
+    assert!(!holds(&["> item 2"], 1));
+    assert!(!holds(&["status: OK \\(3 files\\)", "extra"], 2));
+    assert!(holds(&["status: OK (3 files\\)"], 2), "escaped literal");
+}
+
+#[test]
+fn expected_screen_reports_each_mismatched_line() {
+    let screen = observation(&["one", "two"]);
+    let assertion = Assertion::expected_screen(
+        &["one".to_string(), "2".to_string(), "three".to_string()],
+        0,
+    );
+    let (passed, message, details) = evaluate_with_exit_status(&screen, &assertion, None);
+    assert!(!passed);
+    assert_eq!(
+        message.as_deref(),
+        Some("2 of 3 expected lines differ (first at line 1)")
+    );
+    let mismatches = details.unwrap()["mismatches"].clone();
+    assert_eq!(
+        mismatches,
+        serde_json::json!([
+            {"line": 1, "expected": "2", "actual": "two"},
+            {"line": 2, "expected": "three", "actual": null},
+        ])
+    );
+}
+
+#[test]
+fn dialog_present_matches_detected_box_title() {
+    let mut observation = observation(&[
+        "┌─┤ Save changes? ├─┐",
+        "│ Yes   No          │",
+        "└───────────────────┘",
+    ]);
+    observation.widgets = ptybox::terminal::widgets::detect_widgets(&observation.screen);
+    let context = PredicateContext::default();
+
+    let any = Predicate::parse("dialog_present", &Value::Null).unwrap();
+    assert!(any.evaluate(&observation, &context).0);
+    let titled = Predicate::parse("dialog_present", &serde_json::json!({"title": "Save"})).unwrap();
+    assert!(titled.evaluate(&observation, &context).0);
+
+    let other = Predicate::parse("dialog_present", &serde_json::json!({"title": "Quit"})).unwrap();
+    let (holds, message, details) = other.evaluate(&observation, &context);
+    assert!(!holds);
+    assert_eq!(message.unwrap(), "no dialog titled 'Quit' on screen");
+    assert_eq!(
+        details.unwrap()["titles"],
+        serde_json::json!(["Save changes?"])
+    );
+}
+
+#[test]
+fn bell_and_control_sequence_read_raw_output() {
+    use ptybox::predicate::RawOutput;
+
+    let screen = observation(&["ready"]);
+    let context = |bells, bytes| PredicateContext {
+        raw_output: Some(RawOutput { bells, bytes }),
+        ..PredicateContext::default()
+    };
+    let bell = Predicate::parse("bell", &Value::Null).unwrap();
+    assert!(bell.evaluate(&screen, &context(1, b"\x07")).0);
+    let (holds, message, _) = bell.evaluate(&screen, &context(0, b"ready"));
+    assert!(!holds);
+    assert_eq!(message.unwrap(), "no bell since the last input");
+
+    let sync_end = Predicate::parse(
+        "control_sequence",
+        &serde_json::json!({ "pattern": r"\x1b\[\?2026l" }),
+    )
+    .unwrap();
+    assert!(
+        sync_end
+            .evaluate(&screen, &context(0, b"\x1b[?2026hdraw\x1b[?2026l"))
+            .0
+    );
+    let (holds, _, details) = sync_end.evaluate(&screen, &context(0, b"\x1b[?2026hdraw"));
+    assert!(!holds);
+    assert_eq!(details.unwrap()["bytes"], 12);
+
+    // Outside a session there is no raw output to match.
+    let (holds, message, _) = bell.evaluate(&screen, &PredicateContext::default());
+    assert!(!holds);
+    assert!(message.unwrap().contains("raw output"));
+    let err =
+        Predicate::parse("control_sequence", &serde_json::json!({ "pattern": "(" })).unwrap_err();
+    assert_eq!(err.code, ErrorCode::Protocol);
+}
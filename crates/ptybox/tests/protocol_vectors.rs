@@ -0,0 +1,105 @@
+// Test module - relaxed lint rules
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::panic)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(missing_docs)]
+
+//! Protocol conformance vector tests.
+
+use ptybox::driver::{protocol_vectors, write_protocol_vectors, PROTOCOL_SCHEMAS};
+use ptybox::model::driver::{DriverRequestV2, DriverResponseV2};
+use ptybox::runner::ErrorCode;
+use std::fs;
+use std::path::PathBuf;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "ptybox-vectors-{name}-{}-{}",
+        std::process::id(),
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos()
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn vectors_round_trip_through_model_types() {
+    let vectors = protocol_vectors();
+    for vector in vectors.exchanges.iter().chain(&vectors.errors) {
+        let request = serde_json::to_value(&vector.request).unwrap();
+        let parsed: DriverRequestV2 = serde_json::from_value(request.clone()).unwrap();
+        assert_eq!(
+            serde_json::to_value(parsed).unwrap(),
+            request,
+            "{}",
+            vector.name
+        );
+
+        let response = serde_json::to_value(&vector.response).unwrap();
+        let parsed: DriverResponseV2 = serde_json::from_value(response.clone()).unwrap();
+        assert_eq!(
+            serde_json::to_value(parsed).unwrap(),
+            response,
+            "{}",
+            vector.name
+        );
+        assert_eq!(vector.request.request_id, vector.response.request_id);
+    }
+}
+
+#[test]
+fn vectors_cover_every_error_code() {
+    let vectors = protocol_vectors();
+    let codes: Vec<&str> = vectors
+        .errors
+        .iter()
+        .map(|vector| vector.response.error.as_ref().unwrap().code.as_str())
+        .collect();
+    let expected: Vec<&str> = ErrorCode::ALL.iter().map(ErrorCode::as_str).collect();
+    assert_eq!(codes, expected);
+    for code in ErrorCode::ALL {
+        assert_eq!(ErrorCode::parse(code.as_str()), Some(code));
+    }
+}
+
+#[test]
+fn write_protocol_vectors_is_deterministic() {
+    let first = temp_dir("first");
+    let second = temp_dir("second");
+    let manifest = write_protocol_vectors(&first).unwrap();
+    write_protocol_vectors(&second).unwrap();
+
+    assert_eq!(manifest.errors.len(), ErrorCode::ALL.len());
+    assert_eq!(manifest.schemas.len(), PROTOCOL_SCHEMAS.len());
+    let files = manifest
+        .exchanges
+        .iter()
+        .chain(manifest.errors.iter().map(|error| &error.file))
+        .chain(&manifest.schemas)
+        .chain(std::iter::once(&"manifest.json".to_string()))
+        .cloned()
+        .collect::<Vec<_>>();
+    for file in &files {
+        let data = fs::read(first.join(file)).unwrap();
+        let _: serde_json::Value = serde_json::from_slice(&data).unwrap();
+        assert_eq!(data, fs::read(second.join(file)).unwrap(), "{file}");
+    }
+
+    let schema: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(first.join("schemas/driver-request-v2.schema.json")).unwrap(),
+    )
+    .unwrap();
+    let text: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(first.join("exchanges/text.json")).unwrap())
+            .unwrap();
+    for field in schema["required"].as_array().unwrap() {
+        assert!(text["request"].get(field.as_str().unwrap()).is_some());
+    }
+
+    let _ = fs::remove_dir_all(&first);
+    let _ = fs::remove_dir_all(&second);
+}
@@ -0,0 +1,87 @@
+// Test module - relaxed lint rules
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(missing_docs)]
+
+//! Remote sessions over SSH (`ssh` feature). No SSH server is available in
+//! the test environment, so these cover connection and handshake failures.
+
+use ptybox::model::policy::{EnvPolicy, RemotePolicy, TerminalEncoding};
+use ptybox::model::{OutputBufferConfig, RemoteTarget, ResizeConfig, RunId, TerminalSize};
+use ptybox::runner::ErrorCode;
+use ptybox::session::{RemoteConfig, Session, SessionConfig};
+use std::io::Write;
+use std::net::TcpListener;
+
+fn remote_config(port: u16) -> RemoteConfig {
+    let policy = RemotePolicy {
+        allowed_hosts: vec![format!("127.0.0.1:{port}")],
+        allowed_keys: vec!["/nonexistent/id_ed25519".to_string()],
+        known_hosts: Some("/nonexistent/known_hosts".to_string()),
+        connect_timeout_ms: 2000,
+    };
+    let target = RemoteTarget {
+        host: "127.0.0.1".to_string(),
+        port,
+        user: "qa".to_string(),
+        key_path: "/nonexistent/id_ed25519".to_string(),
+    };
+    RemoteConfig::from_policy(target, &policy).unwrap()
+}
+
+fn session_config(remote: RemoteConfig) -> SessionConfig {
+    SessionConfig {
+        command: "/bin/cat".to_string(),
+        args: Vec::new(),
+        cwd: None,
+        size: TerminalSize::default(),
+        run_id: RunId::new(),
+        env: EnvPolicy::default(),
+        separate_stderr: false,
+        output_buffer: OutputBufferConfig::default(),
+        resize: ResizeConfig::default(),
+        remote: Some(remote),
+        encoding: TerminalEncoding::default(),
+    }
+}
+
+#[test]
+fn remote_session_reports_refused_connections() {
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let err = Session::spawn(session_config(remote_config(port)))
+        .err()
+        .unwrap();
+    assert_eq!(err.code, ErrorCode::Io);
+    assert_eq!(err.message, "failed to connect to remote host");
+    assert_eq!(err.context.as_ref().unwrap()["port"], port);
+}
+
+#[test]
+fn remote_session_reports_a_failed_handshake() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let _ = stream.write_all(b"HTTP/1.1 400 Bad Request\r\n\r\n");
+    });
+    let err = Session::spawn(session_config(remote_config(port)))
+        .err()
+        .unwrap();
+    server.join().unwrap();
+    assert_eq!(err.code, ErrorCode::Io);
+    assert_eq!(err.message, "ssh handshake failed");
+}
+
+#[test]
+fn remote_session_rejects_separate_stderr() {
+    let config = SessionConfig {
+        separate_stderr: true,
+        ..session_config(remote_config(22))
+    };
+    let err = Session::spawn(config).err().unwrap();
+    assert_eq!(err.code, ErrorCode::Protocol);
+}
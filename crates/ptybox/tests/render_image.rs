@@ -0,0 +1,112 @@
+// Test module - relaxed lint rules
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(missing_docs)]
+
+//! Snapshot rendering and inline driver screenshots (`render` feature).
+
+use ptybox::driver::{DriverConfig, EmbeddedDriver};
+use ptybox::model::policy::PolicyBuilder;
+use ptybox::model::TerminalSize;
+use ptybox::terminal::image::{render_png, CELL_HEIGHT, CELL_WIDTH};
+use ptybox::terminal::Terminal;
+use ptybox::util::encode_base64;
+use serde_json::Value;
+
+/// PNG signature (`\x89PNG\r\n\x1a\n`) as it starts a base64 string.
+const PNG_BASE64_PREFIX: &str = "iVBORw0KGgo";
+
+fn decode(png: &[u8]) -> (png::OutputInfo, Vec<u8>) {
+    let mut reader = png::Decoder::new(png).read_info().expect("png header");
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).expect("png frame");
+    (info, pixels)
+}
+
+fn pixel(pixels: &[u8], width: u32, x: u32, y: u32) -> [u8; 3] {
+    let offset = ((y * width + x) * 3) as usize;
+    [pixels[offset], pixels[offset + 1], pixels[offset + 2]]
+}
+
+#[test]
+fn encode_base64_matches_rfc_4648_vectors() {
+    assert_eq!(encode_base64(b""), "");
+    assert_eq!(encode_base64(b"f"), "Zg==");
+    assert_eq!(encode_base64(b"fo"), "Zm8=");
+    assert_eq!(encode_base64(b"foo"), "Zm9v");
+    assert_eq!(encode_base64(b"foobar"), "Zm9vYmFy");
+    assert_eq!(encode_base64(&[0xfb, 0xff]), "+/8=");
+}
+
+#[test]
+fn render_png_draws_cells_with_colors_and_cursor() {
+    let mut terminal = Terminal::new(TerminalSize::new(2, 4));
+    terminal.process_bytes(b"\x1b[31m\xe2\x96\x88\x1b[0m\x1b[44m \x1b[0m");
+    let snapshot = terminal.snapshot_with_cells(true).unwrap();
+
+    let image = render_png(&snapshot).unwrap();
+    assert_eq!(
+        (image.width, image.height),
+        (4 * CELL_WIDTH, 2 * CELL_HEIGHT)
+    );
+    let (info, pixels) = decode(&image.png);
+    assert_eq!((info.width, info.height), (image.width, image.height));
+    assert_eq!(info.color_type, png::ColorType::Rgb);
+
+    // Full block in red, then a blue background cell.
+    assert_eq!(pixel(&pixels, info.width, 3, 6), [0xcd, 0x00, 0x00]);
+    assert_eq!(
+        pixel(&pixels, info.width, CELL_WIDTH + 3, 6),
+        [0x00, 0x00, 0xee]
+    );
+    // Cursor sits on the third cell, drawn inverted (default fg as background).
+    assert_eq!(
+        pixel(&pixels, info.width, 2 * CELL_WIDTH + 3, 6),
+        [0xe5, 0xe5, 0xe5]
+    );
+    // Second row is blank.
+    assert_eq!(pixel(&pixels, info.width, 3, CELL_HEIGHT + 6), [0, 0, 0]);
+}
+
+#[test]
+fn driver_attaches_inline_png_when_requested() {
+    let mut driver = EmbeddedDriver::start(DriverConfig {
+        command: "/bin/cat".to_string(),
+        args: Vec::new(),
+        cwd: None,
+        policy: PolicyBuilder::new()
+            .sandbox_disabled()
+            .allowed_executables(vec!["/bin/cat".to_string()])
+            .build(),
+        artifacts: None,
+        separate_stderr: false,
+        ack_overrides: Vec::new(),
+    })
+    .expect("start driver");
+
+    let response = driver
+        .send(r#"{"protocol_version":2,"request_id":"r1","action":{"type":"text","payload":{"text":"hi"}},"include_image":true}"#)
+        .expect("send text");
+    let response: Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(response["status"], "ok");
+    let image = &response["image"];
+    assert_eq!(image["mime_type"], "image/png");
+    assert_eq!(image["width"], 80 * CELL_WIDTH);
+    assert_eq!(image["height"], 24 * CELL_HEIGHT);
+    assert!(image["data_base64"]
+        .as_str()
+        .unwrap()
+        .starts_with(PNG_BASE64_PREFIX));
+    assert!(image.get("omitted").is_none());
+
+    let response = driver
+        .send(
+            r#"{"protocol_version":2,"request_id":"r2","action":{"type":"observe","payload":{}}}"#,
+        )
+        .expect("send observe");
+    let response: Value = serde_json::from_str(&response).unwrap();
+    assert!(response.get("image").is_none());
+
+    driver.close().expect("close driver");
+}
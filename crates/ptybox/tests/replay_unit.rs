@@ -17,7 +17,7 @@
 //! Tests the replay comparison and normalization functionality.
 
 use ptybox::model::{NormalizationFilter, NormalizationRuleTarget, Policy};
-use ptybox::replay::{explain_replay, ReplayOptions};
+use ptybox::replay::{explain_replay, ReplayCompare, ReplayOptions};
 use ptybox::runner::ErrorCode;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -157,6 +157,43 @@ fn explain_replay_cli_filters_override_policy() {
     cleanup_dir(&dir);
 }
 
+#[test]
+fn explain_replay_reports_compare_selection() {
+    let dir = temp_test_dir("explain-compare");
+    write_test_policy(&dir, None);
+
+    let explanation = explain_replay(&dir, ReplayOptions::default()).expect("Should succeed");
+    assert_eq!(explanation.compare, ReplayCompare::default());
+
+    let compare = ReplayCompare {
+        snapshots: true,
+        ..ReplayCompare::none()
+    };
+    let options = ReplayOptions {
+        compare,
+        ..Default::default()
+    };
+    let explanation = explain_replay(&dir, options).expect("Should succeed");
+    assert_eq!(explanation.compare, compare);
+
+    cleanup_dir(&dir);
+}
+
+#[test]
+fn explain_replay_rejects_empty_compare_selection() {
+    let dir = temp_test_dir("explain-compare-empty");
+    write_test_policy(&dir, None);
+
+    let options = ReplayOptions {
+        compare: ReplayCompare::none(),
+        ..Default::default()
+    };
+    let err = explain_replay(&dir, options).unwrap_err();
+    assert_eq!(err.code, ErrorCode::Protocol);
+
+    cleanup_dir(&dir);
+}
+
 // =============================================================================
 // Filter Precedence Tests
 // =============================================================================
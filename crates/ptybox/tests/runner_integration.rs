@@ -16,13 +16,29 @@
 //!
 //! Tests the high-level `run_scenario` and `run_exec` functions.
 
-use ptybox::model::policy::PolicyBuilder;
+use ptybox::model::policy::{
+    ChildSignal, DeterminismPolicy, KillSignal, Policy, PolicyBuilder, PreKillHook, ProcessPolicy,
+    SocketGrant, TranscriptPolicy, TranscriptTruncate,
+};
 use ptybox::model::scenario::PolicyRef;
 use ptybox::model::{
-    Action, ActionType, RunConfig, RunStatus, Scenario, ScenarioMetadata, Step, StepId, StepStatus,
-    TerminalSize,
+    Action, ActionType, Assertion, Condition, ErrorInfo, ExitStatus, FailureCategory,
+    FailureConfidence, KeyHoldRecord, OutputBufferConfig, ResizeConfig, RunConfig, RunStatus,
+    Scenario, ScenarioDefaults, ScenarioMetadata, Step, StepId, StepStatus, TerminalSize,
+    TermiosRecord, TermiosSettings, Watcher, MAX_STEP_METADATA_BYTES,
+};
+use ptybox::run::{run_exec, run_scenario, run_scenario_with_options};
+use ptybox::runner::{
+    classify_failure, ErrorCode, ManualInputSource, ProgressCallback, ProgressEvent, Reporter,
+    RunnerOptions, SocketHandler,
 };
-use ptybox::run::{run_exec, run_scenario};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 
 // =============================================================================
 // Helper Functions
@@ -46,6 +62,10 @@ fn create_scenario(steps: Vec<Step>, command: &str, args: Vec<String>) -> Scenar
         metadata: ScenarioMetadata {
             name: "test_scenario".to_string(),
             description: Some("Integration test scenario".to_string()),
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: RunConfig {
             command: command.to_string(),
@@ -53,8 +73,19 @@ fn create_scenario(steps: Vec<Step>, command: &str, args: Vec<String>) -> Scenar
             cwd: None,
             initial_size: TerminalSize::default(),
             policy: PolicyRef::Inline(Box::new(minimal_policy())),
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps,
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     }
 }
 
@@ -113,6 +144,10 @@ fn run_scenario_assertion_retry_success() {
         metadata: ScenarioMetadata {
             name: "retry_test".to_string(),
             description: Some("Test assertion with cat and terminate".to_string()),
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: RunConfig {
             command: "/bin/cat".to_string(),
@@ -120,6 +155,12 @@ fn run_scenario_assertion_retry_success() {
             cwd: None,
             initial_size: TerminalSize::default(),
             policy: PolicyRef::Inline(Box::new(policy)),
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![
             // Step 1: Send some text to cat
@@ -133,6 +174,13 @@ fn run_scenario_assertion_retry_success() {
                 assert: vec![], // No assertions on this step
                 timeout_ms: 1000,
                 retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
             // Step 2: Terminate cat
             Step {
@@ -145,8 +193,20 @@ fn run_scenario_assertion_retry_success() {
                 assert: vec![], // No assertions
                 timeout_ms: 1000,
                 retries: 0,
+                skip_default_assertions: false,
+                sample_interval_ms: None,
+                expected_screen: None,
+                capture: None,
+                env_overlay: BTreeMap::new(),
+                metadata: BTreeMap::new(),
+                vars: Vec::new(),
             },
         ],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
 
     let result = run_scenario(scenario);
@@ -190,6 +250,10 @@ fn run_scenario_timeout_boundary() {
         metadata: ScenarioMetadata {
             name: "timeout_test".to_string(),
             description: Some("Test timeout boundary".to_string()),
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: RunConfig {
             command: "/bin/sleep".to_string(),
@@ -197,8 +261,19 @@ fn run_scenario_timeout_boundary() {
             cwd: None,
             initial_size: TerminalSize::default(),
             policy: PolicyRef::Inline(Box::new(policy)),
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![], // No steps - just let it run until timeout
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
 
     let start = std::time::Instant::now();
@@ -223,6 +298,640 @@ fn run_scenario_timeout_boundary() {
     );
 }
 
+fn shell_scenario(steps: Vec<Step>, script: &str) -> Scenario {
+    let mut scenario =
+        create_scenario(steps, "/bin/sh", vec!["-c".to_string(), script.to_string()]);
+    scenario.run.policy = PolicyRef::Inline(Box::new(
+        PolicyBuilder::new()
+            .sandbox_disabled()
+            .allow_shell()
+            .allowed_executables(vec!["/bin/sh".to_string()])
+            .max_runtime_ms(10_000)
+            .build(),
+    ));
+    scenario
+}
+
+fn wait_then_absent_step(forbidden: &str, for_ms: u64) -> Step {
+    Step {
+        id: StepId::new(),
+        name: "stays_clean".to_string(),
+        action: Action {
+            action_type: ActionType::Wait,
+            payload: serde_json::json!({
+                "condition": {"type": "screen_contains", "payload": {"text": "ready"}}
+            }),
+        },
+        assert: vec![Assertion::screen_not_contains(forbidden, Some(for_ms))],
+        timeout_ms: 3000,
+        retries: 0,
+        skip_default_assertions: false,
+        sample_interval_ms: None,
+        expected_screen: None,
+        capture: None,
+        env_overlay: BTreeMap::new(),
+        metadata: BTreeMap::new(),
+        vars: Vec::new(),
+    }
+}
+
+#[test]
+fn run_scenario_screen_not_contains_window_catches_late_text() {
+    let scenario = shell_scenario(
+        vec![wait_then_absent_step("FATAL", 2000)],
+        "echo ready; sleep 0.3; echo FATAL; sleep 3",
+    );
+
+    let run_result = run_scenario(scenario).expect("run should complete");
+    assert_eq!(run_result.status, RunStatus::Failed);
+
+    let steps = run_result.steps.as_ref().unwrap();
+    let assertion = &steps[0].assertions[0];
+    assert_eq!(assertion.assertion_type, "screen_not_contains");
+    assert!(!assertion.passed);
+    let details = assertion.details.as_ref().expect("offending snapshot");
+    let lines = details["snapshot"]["lines"].as_array().unwrap();
+    assert!(lines
+        .iter()
+        .any(|line| line.as_str().unwrap().contains("FATAL")));
+    assert!(details["window_elapsed_ms"].as_u64().unwrap() < 2000);
+}
+
+#[test]
+fn run_scenario_screen_not_contains_window_enforces_output_budget() {
+    let mut scenario = shell_scenario(
+        vec![wait_then_absent_step("FATAL", 3000)],
+        "echo ready; sleep 0.2; while true; do echo spam; sleep 0.01; done",
+    );
+    if let PolicyRef::Inline(policy) = &mut scenario.run.policy {
+        policy.budgets.max_output_bytes = 256;
+    }
+
+    let run_result = run_scenario(scenario).expect("run should complete");
+    assert_eq!(run_result.status, RunStatus::Failed);
+    let step = &run_result.steps.as_ref().unwrap()[0];
+    assert_eq!(step.status, StepStatus::Errored);
+    let error = step.error.as_ref().expect("budget error");
+    assert_eq!(error.code, "E_TIMEOUT");
+    assert_eq!(error.message, "output budget exceeded");
+}
+
+#[test]
+fn run_scenario_screen_not_contains_window_snapshots_the_offending_sample() {
+    let root = std::env::temp_dir().join(format!("ptybox-window-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    let root = root.canonicalize().unwrap();
+    let artifacts_dir = root.join("artifacts");
+
+    let mut scenario = shell_scenario(
+        vec![wait_then_absent_step("FATAL", 2000)],
+        "echo ready; sleep 0.3; echo FATAL; sleep 3",
+    );
+    scenario.run.policy = PolicyRef::Inline(Box::new(
+        PolicyBuilder::new()
+            .sandbox_disabled()
+            .allow_shell()
+            .allowed_executables(vec!["/bin/sh".to_string()])
+            .allowed_write(vec![root.display().to_string()])
+            .max_runtime_ms(10_000)
+            .build(),
+    ));
+    let run_result = run_with_artifacts(scenario, &artifacts_dir);
+    assert_eq!(run_result.status, RunStatus::Failed);
+
+    let mut snapshots: Vec<_> = std::fs::read_dir(artifacts_dir.join("snapshots"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    snapshots.sort();
+    let last = std::fs::read_to_string(snapshots.last().unwrap()).unwrap();
+    assert!(last.contains("FATAL"), "{last}");
+    let samples = std::fs::read_dir(artifacts_dir.join("samples")).unwrap();
+    assert!(samples.count() >= 2);
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+/// Stand-in for the `age` binary: "encrypts" with rot13 behind a header line.
+fn write_fake_age(dir: &std::path::Path) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+    let path = dir.join("fake-age");
+    std::fs::write(
+        &path,
+        r#"#!/bin/sh
+out=
+while [ $# -gt 0 ]; do
+  [ "$1" = --output ] && { shift; out=$1; }
+  shift
+done
+{ echo "age-encryption.org/v1"; tr 'A-Za-z' 'N-ZA-Mn-za-m'; } > "$out"
+"#,
+    )
+    .unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+/// A shell policy writing under `root` with artifacts encrypted by the fake
+/// `age` from [`write_fake_age`].
+fn encrypted_policy(root: &std::path::Path) -> Policy {
+    let mut policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allow_shell()
+        .allowed_executables(vec!["/bin/sh".to_string()])
+        .allowed_write(vec![root.display().to_string()])
+        .max_runtime_ms(10_000)
+        .build();
+    policy.artifacts.encryption = Some(ptybox::model::policy::ArtifactsEncryption {
+        recipients: vec!["age1qqqqqqqqqqqqqqqqqqqq".to_string()],
+        age_binary: write_fake_age(root).display().to_string(),
+    });
+    policy
+}
+
+#[test]
+fn run_scenario_encrypted_failure_keeps_screen_out_of_plaintext_artifacts() {
+    let root = std::env::temp_dir().join(format!("ptybox-encrypted-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    let root = root.canonicalize().unwrap();
+    let artifacts_dir = root.join("artifacts");
+
+    // The screen shows the product, which appears nowhere in the scenario.
+    let mut step = wait_then_absent_step("fatal", 0);
+    step.assert = vec![Assertion::screen_not_contains("fatal", None)];
+    let mut scenario = shell_scenario(
+        vec![step],
+        "echo $((1234 * 5678)) FATAL | tr A-Z a-z; echo ready; sleep 3",
+    );
+    scenario.run.policy = PolicyRef::Inline(Box::new(encrypted_policy(&root)));
+
+    let run_result = run_with_artifacts(scenario, &artifacts_dir);
+    assert_eq!(run_result.status, RunStatus::Failed);
+    let assertion = &run_result.steps.as_ref().unwrap()[0].assertions[0];
+    assert!(!assertion.passed);
+    assert!(assertion.details.is_some());
+
+    let mut pending = vec![artifacts_dir.clone()];
+    let mut plaintext = Vec::new();
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if path.extension() != Some(std::ffi::OsStr::new("age")) {
+                plaintext.push(path);
+            }
+        }
+    }
+    assert!(plaintext.contains(&artifacts_dir.join("run.json")));
+    assert!(plaintext.contains(&artifacts_dir.join("journal.jsonl")));
+    for path in &plaintext {
+        let content = std::fs::read_to_string(path).unwrap();
+        assert!(!content.contains("7006652"), "{}", path.display());
+    }
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn run_scenario_screen_not_contains_window_passes_when_text_never_appears() {
+    let scenario = shell_scenario(
+        vec![wait_then_absent_step("FATAL", 300)],
+        "echo ready; sleep 1",
+    );
+
+    let run_result = run_scenario(scenario).expect("run should complete");
+    assert_eq!(run_result.status, RunStatus::Passed);
+    let steps = run_result.steps.as_ref().unwrap();
+    assert!(steps[0].assertions[0].passed);
+}
+
+fn panic_watcher() -> Watcher {
+    Watcher {
+        name: "no_panic".to_string(),
+        condition: Condition::screen_contains("panicked at"),
+    }
+}
+
+#[test]
+fn run_scenario_watcher_catches_transient_screen() {
+    let mut scenario = shell_scenario(
+        vec![wait_then_absent_step("FATAL", 0)],
+        r"echo 'panicked at src/main.rs'; sleep 0.3; printf '\033[2J\033[H'; echo ready; sleep 1",
+    );
+    scenario.watchers = vec![panic_watcher()];
+
+    let run_result = run_scenario(scenario).expect("run should complete");
+    assert_eq!(run_result.status, RunStatus::Failed);
+    let steps = run_result.steps.as_ref().unwrap();
+    assert_eq!(steps[0].status, StepStatus::Failed);
+
+    let classification = run_result.classification.as_ref().expect("classified");
+    assert_eq!(
+        classification.category,
+        FailureCategory::AssertionRegression
+    );
+    assert_eq!(classification.confidence, FailureConfidence::High);
+
+    let error = run_result.error.expect("watcher error");
+    assert_eq!(error.code, "E_ASSERTION_FAILED");
+    let context = error.context.expect("watcher context");
+    assert_eq!(context["watcher"], "no_panic");
+    assert_eq!(context["condition"]["type"], "screen_contains");
+    assert!(context.get("snapshot").is_none());
+}
+
+#[test]
+fn run_scenario_encrypted_watcher_trip_keeps_screen_out_of_plaintext_artifacts() {
+    let root =
+        std::env::temp_dir().join(format!("ptybox-encrypted-watcher-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    let root = root.canonicalize().unwrap();
+    let artifacts_dir = root.join("artifacts");
+
+    let mut scenario = shell_scenario(
+        vec![wait_then_absent_step("FATAL", 0)],
+        "echo $((1234 * 5678)) panicked at; sleep 0.3; echo ready; sleep 1",
+    );
+    scenario.watchers = vec![panic_watcher()];
+    scenario.run.policy = PolicyRef::Inline(Box::new(encrypted_policy(&root)));
+
+    let run_result = run_with_artifacts(scenario, &artifacts_dir);
+    assert_eq!(run_result.status, RunStatus::Failed);
+    assert_eq!(run_result.error.unwrap().code, "E_ASSERTION_FAILED");
+
+    for name in ["run.json", "journal.jsonl"] {
+        let content = std::fs::read_to_string(artifacts_dir.join(name)).unwrap();
+        assert!(content.contains("no_panic"), "{name}");
+        assert!(!content.contains("7006652"), "{name}");
+    }
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn run_scenario_capture_attributes_adds_cell_styles_to_observations() {
+    let script = r"printf '\033[1;31mERR\033[0m ok\n'; echo ready; sleep 1";
+    let run_result = run_scenario(shell_scenario(
+        vec![wait_then_absent_step("FATAL", 0)],
+        script,
+    ))
+    .expect("run should complete");
+    assert!(run_result.final_observation.unwrap().screen.cells.is_none());
+
+    let mut scenario = shell_scenario(vec![wait_then_absent_step("FATAL", 0)], script);
+    scenario.run.capture_attributes = true;
+    let run_result = run_scenario(scenario).expect("run should complete");
+    assert_eq!(run_result.status, RunStatus::Passed);
+    let cells = run_result.final_observation.unwrap().screen.cells.unwrap();
+    assert_eq!(cells[0][0].ch, "E");
+    assert!(cells[0][0].style.bold);
+    assert_eq!(cells[0][0].style.fg, ptybox::model::Color::Ansi16(1));
+    assert_eq!(cells[0][4].ch, "o");
+    assert!(!cells[0][4].style.bold);
+}
+
+#[test]
+fn run_scenario_watcher_stays_quiet_when_condition_never_holds() {
+    let mut scenario = shell_scenario(
+        vec![wait_then_absent_step("FATAL", 0)],
+        "echo ready; sleep 1",
+    );
+    scenario.watchers = vec![panic_watcher()];
+
+    let run_result = run_scenario(scenario).expect("run should complete");
+    assert_eq!(run_result.status, RunStatus::Passed);
+}
+
+#[test]
+fn run_scenario_watcher_accepts_assertion_predicates() {
+    let mut scenario = shell_scenario(
+        vec![wait_then_absent_step("FATAL", 0)],
+        "echo ready; echo 'error: disk full'; sleep 1",
+    );
+    scenario.watchers = vec![Watcher {
+        name: "no_errors".to_string(),
+        condition: Condition {
+            condition_type: "line_matches".to_string(),
+            payload: serde_json::json!({"line": 1, "pattern": "^error:"}),
+        },
+    }];
+
+    let run_result = run_scenario(scenario).expect("run should complete");
+    assert_eq!(run_result.status, RunStatus::Failed);
+    let context = run_result.error.unwrap().context.unwrap();
+    assert_eq!(context["watcher"], "no_errors");
+}
+
+#[test]
+fn run_scenario_rejects_invalid_watchers() {
+    let invalid = [
+        Watcher {
+            name: "unknown".to_string(),
+            condition: Condition {
+                condition_type: "screen_glows".to_string(),
+                payload: serde_json::json!({}),
+            },
+        },
+        Watcher {
+            name: String::new(),
+            condition: Condition::screen_contains("x"),
+        },
+        Watcher {
+            name: "regex".to_string(),
+            condition: Condition::screen_matches("("),
+        },
+    ];
+    for watcher in invalid {
+        let mut scenario = create_scenario(Vec::new(), "/bin/echo", Vec::new());
+        scenario.watchers = vec![watcher.clone()];
+        let err = run_scenario(scenario).expect_err("watcher rejected");
+        assert_eq!(err.code, ErrorCode::Protocol, "{watcher:?}");
+    }
+
+    let mut scenario = create_scenario(Vec::new(), "/bin/echo", Vec::new());
+    scenario.watchers = vec![panic_watcher(), panic_watcher()];
+    let err = run_scenario(scenario).expect_err("duplicate rejected");
+    assert!(err.message.contains("duplicate"), "{}", err.message);
+}
+
+#[test]
+fn run_scenario_marks_inherited_default_assertions() {
+    let mut scenario = shell_scenario(
+        vec![
+            wait_then_absent_step("FATAL", 0),
+            Step {
+                skip_default_assertions: true,
+                ..wait_then_absent_step("FATAL", 0)
+            },
+        ],
+        "echo ready; sleep 1",
+    );
+    scenario.defaults.assert_each_step = vec![Assertion::screen_contains("ready")];
+
+    let run_result = run_scenario(scenario).expect("run should complete");
+    assert_eq!(run_result.status, RunStatus::Passed);
+    let steps = run_result.steps.as_ref().unwrap();
+    assert_eq!(steps[0].assertions.len(), 2);
+    assert!(!steps[0].assertions[0].inherited);
+    assert!(steps[0].assertions[1].inherited);
+    assert_eq!(steps[0].assertions[1].assertion_type, "screen_contains");
+    assert_eq!(steps[1].assertions.len(), 1);
+    assert!(!steps[1].assertions[0].inherited);
+}
+
+#[test]
+fn run_scenario_reports_wait_stats_only_for_waiting_steps() {
+    let observe_step = Step {
+        name: "observe".to_string(),
+        action: Action {
+            action_type: ActionType::Observe,
+            payload: serde_json::json!({}),
+        },
+        assert: Vec::new(),
+        ..wait_then_absent_step("FATAL", 0)
+    };
+    let scenario = shell_scenario(
+        vec![wait_then_absent_step("FATAL", 0), observe_step],
+        "echo ready; sleep 1",
+    );
+
+    let run_result = run_scenario(scenario).expect("run should complete");
+    assert_eq!(run_result.status, RunStatus::Passed);
+    let steps = run_result.steps.as_ref().unwrap();
+    let wait = steps[0].wait.expect("wait step records poll counts");
+    assert!(wait.polls >= 1);
+    assert!(steps[1].wait.is_none());
+}
+
+struct QueuedInput(Mutex<Vec<Action>>);
+
+impl ManualInputSource for QueuedInput {
+    fn take_pending(&self) -> Vec<Action> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+#[test]
+fn run_scenario_forwards_manual_input_during_wait() {
+    let step = Step {
+        name: "wait_for_reply".to_string(),
+        action: Action {
+            action_type: ActionType::Wait,
+            payload: serde_json::json!({
+                "condition": {"type": "screen_contains", "payload": {"text": "got ping"}}
+            }),
+        },
+        assert: Vec::new(),
+        ..wait_then_absent_step("FATAL", 0)
+    };
+    let scenario = shell_scenario(vec![step], "read line; echo \"got $line\"; sleep 1");
+    let input = QueuedInput(Mutex::new(vec![
+        Action {
+            action_type: ActionType::Text,
+            payload: serde_json::json!({"text": "ping"}),
+        },
+        Action {
+            action_type: ActionType::Key,
+            payload: serde_json::json!({"key": "Enter"}),
+        },
+    ]));
+    let options = RunnerOptions {
+        manual_input: Some(Arc::new(input)),
+        ..RunnerOptions::default()
+    };
+
+    let run_result = run_scenario_with_options(scenario, options).expect("run should complete");
+    assert_eq!(run_result.status, RunStatus::Passed);
+}
+
+#[test]
+fn run_scenario_separate_stderr_keeps_stderr_off_screen() {
+    let step = Step {
+        name: "wait_for_ok".to_string(),
+        action: Action {
+            action_type: ActionType::Wait,
+            payload: serde_json::json!({
+                "condition": {"type": "screen_contains", "payload": {"text": "ok"}}
+            }),
+        },
+        assert: vec![
+            Assertion::stderr_contains("oops"),
+            Assertion::not_contains("oops"),
+        ],
+        ..wait_then_absent_step("FATAL", 0)
+    };
+    let mut scenario = shell_scenario(vec![step], "echo oops >&2; echo ok; sleep 1");
+    scenario.run.separate_stderr = true;
+
+    let run_result = run_scenario(scenario).expect("run should complete");
+    assert_eq!(run_result.status, RunStatus::Passed, "{run_result:?}");
+}
+
+#[test]
+fn run_scenario_stderr_contains_fails_without_separate_stderr() {
+    let step = Step {
+        assert: vec![Assertion::stderr_contains("oops")],
+        ..wait_then_absent_step("FATAL", 0)
+    };
+    let scenario = shell_scenario(vec![step], "echo ready >&2; sleep 1");
+
+    let run_result = run_scenario(scenario).expect("run should complete");
+    assert_eq!(run_result.status, RunStatus::Failed);
+    let assertion = &run_result.steps.as_ref().unwrap()[0].assertions[0];
+    assert!(assertion
+        .message
+        .as_deref()
+        .unwrap()
+        .contains("separate_stderr"));
+}
+
+#[test]
+fn run_scenario_records_provenance() {
+    let scenario = create_scenario(Vec::new(), "/bin/echo", vec!["hi".to_string()]);
+    let options = RunnerOptions {
+        git_sha: Some("0123abcd".to_string()),
+        ..RunnerOptions::default()
+    };
+
+    let first = run_scenario_with_options(scenario.clone(), options).expect("run should complete");
+    let provenance = first.provenance.expect("provenance recorded");
+    assert_eq!(provenance.ptybox_version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(provenance.git_sha.as_deref(), Some("0123abcd"));
+    let hash = provenance.scenario_hash.expect("scenario hash");
+    assert!(
+        hash.starts_with("sha256:") && hash.len() == 7 + 64,
+        "{hash}"
+    );
+
+    let second = run_scenario(scenario).expect("run should complete");
+    let provenance = second.provenance.expect("provenance recorded");
+    assert_eq!(provenance.scenario_hash.as_deref(), Some(hash.as_str()));
+    assert_eq!(provenance.git_sha, None);
+}
+
+#[test]
+fn run_scenario_records_pinned_determinism_in_run_json() {
+    let root = std::env::temp_dir().join(format!("ptybox-determinism-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    let root = root.canonicalize().unwrap();
+    let scenario = |determinism: DeterminismPolicy| {
+        let mut policy = PolicyBuilder::new()
+            .sandbox_disabled()
+            .allowed_executables(vec!["/bin/echo".to_string()])
+            .allowed_write(vec![root.display().to_string()])
+            .max_runtime_ms(10_000)
+            .build();
+        policy.determinism = determinism;
+        let mut scenario = create_scenario(Vec::new(), "/bin/echo", vec!["hi".to_string()]);
+        scenario.run.policy = PolicyRef::Inline(Box::new(policy));
+        scenario
+    };
+    let read_run_json = |dir: &std::path::Path| -> Value {
+        serde_json::from_slice(&std::fs::read(dir.join("run.json")).unwrap()).unwrap()
+    };
+
+    let pinned = scenario(DeterminismPolicy {
+        locale: Some("C.UTF-8".to_string()),
+        timezone: Some("UTC".to_string()),
+        paced_input: true,
+    });
+    let run_result = run_with_artifacts(pinned, &root.join("pinned"));
+    assert_eq!(
+        run_result.determinism,
+        Some(DeterminismPolicy {
+            locale: Some("C.UTF-8".to_string()),
+            timezone: Some("UTC".to_string()),
+            paced_input: false,
+        })
+    );
+    let run_json = read_run_json(&root.join("pinned"));
+    assert_eq!(
+        run_json["determinism"],
+        json!({ "locale": "C.UTF-8", "timezone": "UTC" })
+    );
+
+    let unpinned = scenario(DeterminismPolicy::default());
+    let run_result = run_with_artifacts(unpinned, &root.join("unpinned"));
+    assert_eq!(run_result.determinism, None);
+    assert!(read_run_json(&root.join("unpinned"))
+        .get("determinism")
+        .is_none());
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+fn classify_as(
+    run: &mut ptybox::model::RunResult,
+    code: &str,
+    context: Value,
+) -> Option<(FailureCategory, FailureConfidence)> {
+    run.status = RunStatus::Failed;
+    run.error = Some(ErrorInfo {
+        code: code.to_string(),
+        message: "failed".to_string(),
+        context: Some(context),
+    });
+    classify_failure(run).map(|found| (found.category, found.confidence))
+}
+
+#[test]
+fn classify_failure_maps_error_codes_and_exit_signals() {
+    let scenario = create_scenario(Vec::new(), "/bin/echo", vec!["hi".to_string()]);
+    let mut run = run_scenario(scenario).expect("run should complete");
+    assert_eq!(run.classification, None);
+
+    let budget = json!({ "step_name": "wait", "details": { "max_output_bytes": 10 } });
+    assert_eq!(
+        classify_as(&mut run, "E_TIMEOUT", budget),
+        Some((FailureCategory::Budget, FailureConfidence::High))
+    );
+    let wait = json!({ "details": { "condition": "screen_contains" } });
+    assert_eq!(
+        classify_as(&mut run, "E_TIMEOUT", wait),
+        Some((
+            FailureCategory::AssertionRegression,
+            FailureConfidence::Medium
+        ))
+    );
+    assert_eq!(
+        classify_as(&mut run, "E_POLICY_DENIED", json!({})),
+        Some((FailureCategory::PolicyMisconfig, FailureConfidence::High))
+    );
+    assert_eq!(
+        classify_as(&mut run, "E_IO", json!({})),
+        Some((FailureCategory::InfraFlake, FailureConfidence::Medium))
+    );
+
+    run.exit_status = Some(ExitStatus {
+        success: false,
+        exit_code: None,
+        signal: Some(11),
+        terminated_by_harness: false,
+        termination: None,
+    });
+    assert_eq!(
+        classify_as(&mut run, "E_TIMEOUT", json!({})),
+        Some((FailureCategory::AppCrash, FailureConfidence::High))
+    );
+    run.status = RunStatus::Canceled;
+    assert_eq!(classify_failure(&run), None);
+}
+
+#[test]
+fn run_rejects_malformed_git_sha() {
+    for sha in ["abc", "not-a-sha!", &"a".repeat(65)] {
+        let options = RunnerOptions {
+            git_sha: Some(sha.to_string()),
+            ..RunnerOptions::default()
+        };
+        let scenario = create_scenario(Vec::new(), "/bin/echo", Vec::new());
+        let err = run_scenario_with_options(scenario, options).expect_err("sha rejected");
+        assert_eq!(err.code, ErrorCode::Protocol, "{sha}");
+    }
+}
+
 // =============================================================================
 // run_exec Tests
 // =============================================================================
@@ -351,6 +1060,90 @@ fn run_exec_nonzero_exit_code() {
     assert_eq!(exit_status.exit_code, Some(42), "Exit code should be 42");
 }
 
+#[test]
+fn reporters_write_junit_for_exec_runs_and_run_errors() {
+    let root = std::env::temp_dir().join(format!("ptybox-junit-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    let options = |name: &str| RunnerOptions {
+        reporters: vec![Reporter::Junit(root.join(name))],
+        ..RunnerOptions::default()
+    };
+    let policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_executables(vec!["/bin/sh".to_string()])
+        .allow_shell()
+        .max_runtime_ms(5000)
+        .build();
+
+    let run = ptybox::run::run_exec_with_options(
+        "/bin/sh".to_string(),
+        vec!["-c".to_string(), "exit 42".to_string()],
+        None,
+        policy,
+        options("exec.xml"),
+    )
+    .unwrap();
+    assert_eq!(run.status, RunStatus::Failed);
+    let xml = std::fs::read_to_string(root.join("exec.xml")).unwrap();
+    assert!(
+        xml.contains("<testsuite name=\"/bin/sh\" tests=\"1\" failures=\"1\""),
+        "{xml}"
+    );
+    assert!(
+        xml.contains("<testcase name=\"run\" classname=\"/bin/sh\""),
+        "{xml}"
+    );
+    assert!(xml.contains("<property name=\"status\" value=\"failed\"/>"));
+
+    let scenario = create_scenario(Vec::new(), "/bin/not-allowed", Vec::new());
+    let err = run_scenario_with_options(scenario, options("denied.xml")).unwrap_err();
+    assert_eq!(err.code, ErrorCode::PolicyDenied);
+    let xml = std::fs::read_to_string(root.join("denied.xml")).unwrap();
+    assert!(
+        xml.contains("tests=\"1\" failures=\"0\" errors=\"1\""),
+        "{xml}"
+    );
+    assert!(xml.contains("type=\"E_POLICY_DENIED\""), "{xml}");
+    assert!(!xml.contains("<properties>"), "{xml}");
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn reporters_write_step_metadata_as_junit_properties() {
+    let root = std::env::temp_dir().join(format!("ptybox-junit-meta-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    let step = Step {
+        metadata: BTreeMap::from([
+            ("ticket".to_string(), json!("PTY-42")),
+            ("owner".to_string(), json!({ "team": "tui <core>" })),
+        ]),
+        ..capture_step("greet", wait_for("hi"), Vec::new(), Value::Null)
+    };
+    let scenario = create_scenario(vec![step], "/bin/echo", vec!["hi".to_string()]);
+    let options = RunnerOptions {
+        reporters: vec![Reporter::Junit(root.join("scenario.xml"))],
+        ..RunnerOptions::default()
+    };
+    let run = run_scenario_with_options(scenario, options).unwrap();
+    assert_eq!(run.status, RunStatus::Passed);
+
+    let xml = std::fs::read_to_string(root.join("scenario.xml")).unwrap();
+    let case = &xml[xml.find("<testcase name=\"greet\"").expect("greet case")..];
+    let case = &case[..case.find("</testcase>").expect("testcase with properties")];
+    assert!(
+        case.contains("<property name=\"ticket\" value=\"&quot;PTY-42&quot;\"/>"),
+        "{xml}"
+    );
+    assert!(
+        case.contains(
+            "<property name=\"owner\" value=\"{&quot;team&quot;:&quot;tui &lt;core&gt;&quot;}\"/>"
+        ),
+        "{xml}"
+    );
+    assert!(!case.contains("<failure"), "{xml}");
+    let _ = std::fs::remove_dir_all(&root);
+}
+
 #[test]
 fn run_exec_captures_output() {
     let policy = minimal_policy();
@@ -382,3 +1175,1619 @@ fn run_exec_captures_output() {
 
     assert!(found, "Output should contain test string");
 }
+
+// =============================================================================
+// Sandbox Fallback Tests
+// =============================================================================
+
+#[cfg(not(target_os = "macos"))]
+#[test]
+fn run_exec_seatbelt_fallback_records_enforcement_report() {
+    let root = std::env::temp_dir().join(format!("ptybox-fallback-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    let root = root.canonicalize().unwrap();
+    let artifacts_dir = root.join("artifacts");
+
+    let policy = PolicyBuilder::new()
+        .sandbox_seatbelt()
+        .sandbox_fallback_warn()
+        .allowed_executables(vec!["/bin/echo".to_string()])
+        .allowed_write(vec![root.display().to_string()])
+        .artifacts_dir(artifacts_dir.display().to_string())
+        .max_runtime_ms(10_000)
+        .build();
+
+    let run_result = run_exec(
+        "/bin/echo".to_string(),
+        vec!["unsandboxed".to_string()],
+        None,
+        policy,
+    )
+    .expect("fallback run should succeed");
+    assert_eq!(run_result.status, RunStatus::Passed);
+
+    let report = run_result.enforcement.expect("enforcement report");
+    assert_eq!(report.requested_sandbox, "seatbelt");
+    assert_eq!(report.applied_sandbox, "none");
+    assert_eq!(report.reason.code, "E_SANDBOX_UNAVAILABLE");
+    let aspects: Vec<&str> = report
+        .unenforced
+        .iter()
+        .map(|aspect| aspect.aspect.as_str())
+        .collect();
+    assert_eq!(
+        aspects,
+        vec![
+            "fs.allowed_read",
+            "fs.allowed_write",
+            "network",
+            "exec.allowed_executables"
+        ]
+    );
+
+    let written: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(artifacts_dir.join("enforcement.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(written["applied_sandbox"], "none");
+    assert!(!artifacts_dir.join("sandbox.sb").exists());
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+// =============================================================================
+// Transcript Truncation Tests
+// =============================================================================
+
+#[test]
+fn run_exec_budget_kill_keeps_head_and_tail_of_transcript() {
+    let root = std::env::temp_dir().join(format!("ptybox-head-tail-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    let root = root.canonicalize().unwrap();
+    let artifacts_dir = root.join("artifacts");
+
+    let mut policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_executables(vec!["/usr/bin/seq".to_string()])
+        .allowed_write(vec![root.display().to_string()])
+        .artifacts_dir(artifacts_dir.display().to_string())
+        .max_runtime_ms(10_000)
+        .max_output_bytes(64 * 1024)
+        .build();
+    policy.artifacts.transcript = TranscriptPolicy {
+        truncate: TranscriptTruncate::HeadTail,
+        head_kib: 1,
+        tail_kib: 1,
+    };
+
+    let err = run_exec(
+        "/usr/bin/seq".to_string(),
+        vec!["1".to_string(), "1000000".to_string()],
+        None,
+        policy,
+    )
+    .expect_err("output budget should be exceeded");
+    assert_eq!(err.code, ptybox::runner::ErrorCode::Timeout);
+
+    let transcript = std::fs::read_to_string(artifacts_dir.join("transcript.log")).unwrap();
+    assert!(transcript.starts_with("1\r\n2\r\n3\r\n"), "{transcript:?}");
+    assert!(transcript.contains("[ptybox: transcript truncated: "));
+    assert!(transcript.len() < 2 * 1024 + 128);
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+// =============================================================================
+// Held Key Tests
+// =============================================================================
+
+#[test]
+fn run_scenario_hold_key_is_one_step_and_recorded() {
+    let root = std::env::temp_dir().join(format!("ptybox-hold-key-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    let root = root.canonicalize().unwrap();
+    let artifacts_dir = root.join("artifacts");
+
+    let policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_executables(vec!["/bin/cat".to_string()])
+        .allowed_write(vec![root.display().to_string()])
+        .max_runtime_ms(10_000)
+        .build();
+    let hold = Step {
+        id: StepId::new(),
+        name: "hold_j".to_string(),
+        action: Action::hold_key("j", 150, 20),
+        assert: vec![Assertion::screen_contains("jjj")],
+        timeout_ms: 1000,
+        retries: 0,
+        skip_default_assertions: false,
+        sample_interval_ms: None,
+        expected_screen: None,
+        capture: None,
+        env_overlay: BTreeMap::new(),
+        metadata: BTreeMap::new(),
+        vars: Vec::new(),
+    };
+    let terminate = Step {
+        id: StepId::new(),
+        name: "terminate".to_string(),
+        action: Action::terminate(),
+        assert: Vec::new(),
+        timeout_ms: 1000,
+        retries: 0,
+        skip_default_assertions: false,
+        sample_interval_ms: None,
+        expected_screen: None,
+        capture: None,
+        env_overlay: BTreeMap::new(),
+        metadata: BTreeMap::new(),
+        vars: Vec::new(),
+    };
+    let mut scenario = create_scenario(vec![hold, terminate], "/bin/cat", Vec::new());
+    scenario.run.policy = PolicyRef::Inline(Box::new(policy));
+    let options = RunnerOptions {
+        artifacts: Some(ptybox::artifacts::ArtifactsWriterConfig {
+            dir: artifacts_dir.clone(),
+            overwrite: false,
+        }),
+        ..RunnerOptions::default()
+    };
+
+    let run_result = run_scenario_with_options(scenario, options).expect("run should complete");
+    assert_eq!(run_result.status, RunStatus::Passed, "{run_result:?}");
+    assert_eq!(run_result.steps.as_ref().unwrap().len(), 2);
+
+    let log = std::fs::read_to_string(artifacts_dir.join("key-holds.jsonl")).unwrap();
+    let records: Vec<KeyHoldRecord> = log
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].step, "hold_j");
+    assert_eq!(records[0].hold.key, "j");
+    assert_eq!(records[0].hold.presses, 3);
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn run_scenario_termios_records_applied_flags() {
+    let root = std::env::temp_dir().join(format!("ptybox-termios-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    let root = root.canonicalize().unwrap();
+    let artifacts_dir = root.join("artifacts");
+
+    let policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_executables(vec!["/bin/cat".to_string()])
+        .allowed_write(vec![root.display().to_string()])
+        .max_runtime_ms(10_000)
+        .build();
+    let step = |name: &str, action: Action, assert: Vec<Assertion>| Step {
+        id: StepId::new(),
+        name: name.to_string(),
+        action,
+        assert,
+        timeout_ms: 1000,
+        retries: 0,
+        skip_default_assertions: false,
+        sample_interval_ms: None,
+        expected_screen: None,
+        capture: None,
+        env_overlay: BTreeMap::new(),
+        metadata: BTreeMap::new(),
+        vars: Vec::new(),
+    };
+    let steps = vec![
+        step(
+            "echo_off",
+            Action::termios(Some(false), None, Some(false)),
+            Vec::new(),
+        ),
+        step(
+            "type",
+            Action::text("secret\n"),
+            vec![Assertion::screen_contains("secret")],
+        ),
+        step("terminate", Action::terminate(), Vec::new()),
+    ];
+    let mut scenario = create_scenario(steps, "/bin/cat", Vec::new());
+    scenario.run.policy = PolicyRef::Inline(Box::new(policy));
+    let options = RunnerOptions {
+        artifacts: Some(ptybox::artifacts::ArtifactsWriterConfig {
+            dir: artifacts_dir.clone(),
+            overwrite: false,
+        }),
+        ..RunnerOptions::default()
+    };
+
+    let run_result = run_scenario_with_options(scenario, options).expect("run should complete");
+    assert_eq!(run_result.status, RunStatus::Passed, "{run_result:?}");
+
+    let log = std::fs::read_to_string(artifacts_dir.join("termios.jsonl")).unwrap();
+    let records: Vec<TermiosRecord> = log
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].step, "echo_off");
+    assert_eq!(
+        records[0].settings,
+        TermiosSettings {
+            echo: false,
+            icanon: true,
+            isig: false,
+        }
+    );
+    let transcript = std::fs::read_to_string(artifacts_dir.join("transcript.log")).unwrap();
+    assert_eq!(transcript.matches("secret").count(), 1, "{transcript:?}");
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+fn capture_step(name: &str, action: Action, assert: Vec<Assertion>, capture: Value) -> Step {
+    Step {
+        id: StepId::new(),
+        name: name.to_string(),
+        action,
+        assert,
+        timeout_ms: 1000,
+        retries: 0,
+        skip_default_assertions: false,
+        sample_interval_ms: None,
+        expected_screen: None,
+        capture: serde_json::from_value(capture).unwrap(),
+        env_overlay: BTreeMap::new(),
+        metadata: BTreeMap::new(),
+        vars: Vec::new(),
+    }
+}
+
+#[test]
+fn run_scenario_step_capture_writes_named_artifacts() {
+    let root = std::env::temp_dir().join(format!("ptybox-capture-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    let root = root.canonicalize().unwrap();
+    let artifacts_dir = root.join("artifacts");
+
+    let policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_executables(vec!["/bin/cat".to_string()])
+        .allowed_write(vec![root.display().to_string()])
+        .max_runtime_ms(10_000)
+        .build();
+    let steps = vec![
+        capture_step(
+            "first",
+            Action::text("alpha\n"),
+            vec![Assertion::screen_contains("alpha")],
+            json!({ "name": "first-output" }),
+        ),
+        capture_step(
+            "second",
+            Action::text("beta\n"),
+            vec![Assertion::screen_contains("beta")],
+            json!({ "name": "screen", "source": "screen" }),
+        ),
+        capture_step("terminate", Action::terminate(), Vec::new(), Value::Null),
+    ];
+    let mut scenario = create_scenario(steps, "/bin/cat", Vec::new());
+    scenario.run.policy = PolicyRef::Inline(Box::new(policy));
+    let options = RunnerOptions {
+        artifacts: Some(ptybox::artifacts::ArtifactsWriterConfig {
+            dir: artifacts_dir.clone(),
+            overwrite: false,
+        }),
+        ..RunnerOptions::default()
+    };
+
+    let run_result = run_scenario_with_options(scenario, options).expect("run should complete");
+    assert_eq!(run_result.status, RunStatus::Passed, "{run_result:?}");
+    let steps = run_result.steps.unwrap();
+    assert_eq!(
+        steps[0].capture.as_deref(),
+        Some("captures/first-output.txt")
+    );
+    assert_eq!(steps[1].capture.as_deref(), Some("captures/screen.txt"));
+    assert_eq!(steps[2].capture, None);
+
+    let first = std::fs::read_to_string(artifacts_dir.join("captures/first-output.txt")).unwrap();
+    assert!(first.contains("alpha"), "{first:?}");
+    assert!(!first.contains("beta"), "{first:?}");
+    let screen = std::fs::read_to_string(artifacts_dir.join("captures/screen.txt")).unwrap();
+    assert!(screen.starts_with("alpha\nalpha\nbeta"), "{screen:?}");
+    assert_eq!(screen, screen.trim_end(), "screen capture is trimmed");
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn run_scenario_rejects_invalid_capture_names() {
+    for (first, second) in [("../out", "ok"), ("", "ok"), ("same", "same")] {
+        let steps = vec![
+            capture_step(
+                "one",
+                Action::text("x"),
+                Vec::new(),
+                json!({ "name": first }),
+            ),
+            capture_step(
+                "two",
+                Action::text("y"),
+                Vec::new(),
+                json!({ "name": second }),
+            ),
+        ];
+        let scenario = create_scenario(steps, "/bin/echo", Vec::new());
+        let err = run_scenario(scenario).expect_err("capture name should be rejected");
+        assert_eq!(err.code, ErrorCode::Protocol, "{first:?}/{second:?}");
+        assert!(err.message.contains("capture name"), "{}", err.message);
+    }
+}
+
+fn checksum_scenario(artifacts_dir: &std::path::Path, post_run: Vec<Assertion>) -> Scenario {
+    let steps = vec![
+        capture_step(
+            "show",
+            Action::text("report\n"),
+            vec![Assertion::screen_contains("report")],
+            json!({ "name": "report", "source": "screen" }),
+        ),
+        capture_step("terminate", Action::terminate(), Vec::new(), Value::Null),
+    ];
+    let mut scenario = create_scenario(steps, "/bin/cat", Vec::new());
+    scenario.run.policy = PolicyRef::Inline(Box::new(
+        PolicyBuilder::new()
+            .sandbox_disabled()
+            .allowed_executables(vec!["/bin/cat".to_string()])
+            .allowed_write(vec![artifacts_dir.parent().unwrap().display().to_string()])
+            .artifacts_dir(artifacts_dir.display().to_string())
+            .max_runtime_ms(10_000)
+            .build(),
+    ));
+    scenario.post_run = post_run;
+    scenario
+}
+
+#[test]
+fn run_scenario_capture_checksum_compares_capture_and_transcript_digests() {
+    let root = std::env::temp_dir().join(format!("ptybox-checksum-{}", StepId::new()));
+    std::fs::create_dir_all(&root).unwrap();
+    let root = root.canonicalize().unwrap();
+    let wrong = "0".repeat(64);
+
+    let first = root.join("first");
+    let result = run_scenario(checksum_scenario(
+        &first,
+        vec![
+            Assertion::capture_checksum_equals(Some("report"), &wrong),
+            Assertion::capture_checksum_equals(None, &wrong),
+        ],
+    ))
+    .unwrap();
+    assert_eq!(result.status, RunStatus::Failed);
+    assert_eq!(
+        result.error.as_ref().unwrap().message,
+        "post_run assertion failed"
+    );
+    let actual: Vec<String> = result
+        .post_run
+        .iter()
+        .map(|r| {
+            r.details.as_ref().unwrap()["actual"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        })
+        .collect();
+    let capture = std::fs::read(first.join("captures/report.txt")).unwrap();
+    let transcript = std::fs::read(first.join("transcript.log")).unwrap();
+    assert_eq!(actual[0], ptybox::util::sha256_hex(&capture));
+    assert_eq!(actual[1], ptybox::util::sha256_hex(&transcript));
+    assert!(
+        result.post_run.iter().all(|r| !r.passed),
+        "{:?}",
+        result.post_run
+    );
+
+    // Digests compare case-insensitively, and a second run reproduces them.
+    let result = run_scenario(checksum_scenario(
+        &root.join("second"),
+        vec![
+            Assertion::capture_checksum_equals(Some("report"), &actual[0].to_uppercase()),
+            Assertion::capture_checksum_equals(None, &actual[1]),
+        ],
+    ))
+    .unwrap();
+    assert_eq!(result.status, RunStatus::Passed, "{:?}", result.post_run);
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn run_scenario_rejects_invalid_capture_checksums() {
+    let root = std::env::temp_dir().join(format!("ptybox-checksum-{}", StepId::new()));
+    let digest = "a".repeat(64);
+    for (dir, assertion) in [
+        (
+            "unknown",
+            Assertion::capture_checksum_equals(Some("missing"), &digest),
+        ),
+        (
+            "short",
+            Assertion::capture_checksum_equals(Some("report"), "abc"),
+        ),
+    ] {
+        let scenario = checksum_scenario(&root.join(dir), vec![assertion]);
+        let err = run_scenario(scenario).expect_err("checksum should be rejected");
+        assert_eq!(err.code, ErrorCode::Protocol, "{}", err.message);
+    }
+
+    let mut scenario = create_scenario(Vec::new(), "/bin/echo", Vec::new());
+    scenario.post_run = vec![Assertion::capture_checksum_equals(None, &digest)];
+    let err = run_scenario(scenario).expect_err("checksum without artifacts");
+    assert_eq!(err.code, ErrorCode::Protocol);
+    assert!(
+        err.message.contains("artifacts directory"),
+        "{}",
+        err.message
+    );
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn run_scenario_applies_step_env_overlay_to_pre_kill_hook() {
+    let marker = std::env::temp_dir().join(format!("ptybox-overlay-{}", StepId::new()));
+    let policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allow_shell()
+        .allowed_executables(vec!["/bin/sh".to_string()])
+        .env_allowlist(vec!["PTYBOX_MARKER".to_string()])
+        .process(ProcessPolicy {
+            kill_signal: KillSignal::Term,
+            term_grace_ms: 100,
+            pre_kill_hook: Some(PreKillHook {
+                command: "/bin/sh".to_string(),
+                args: vec![
+                    "-c".to_string(),
+                    "echo hooked > \"$PTYBOX_MARKER\"".to_string(),
+                ],
+                timeout_ms: 2000,
+            }),
+            allowed_signals: Vec::new(),
+            ..ProcessPolicy::default()
+        })
+        .max_runtime_ms(10_000)
+        .build();
+    let ready = capture_step("ready", wait_for("ready"), Vec::new(), Value::Null);
+    let stuck = Step {
+        env_overlay: BTreeMap::from([("PTYBOX_MARKER".to_string(), marker.display().to_string())]),
+        ..capture_step("stuck", wait_for("never printed"), Vec::new(), Value::Null)
+    };
+    let mut scenario = create_scenario(
+        vec![ready, stuck],
+        "/bin/sh",
+        vec![
+            "-c".to_string(),
+            "trap '' TERM; echo ready; while :; do sleep 1; done".to_string(),
+        ],
+    );
+    scenario.run.policy = PolicyRef::Inline(Box::new(policy));
+
+    let result = run_scenario(scenario).unwrap();
+
+    let steps = result.steps.as_ref().unwrap();
+    assert!(steps[0].env_overlay.is_empty());
+    assert_eq!(
+        steps[1].env_overlay.get("PTYBOX_MARKER"),
+        Some(&marker.display().to_string())
+    );
+    let hook = result
+        .exit_status
+        .and_then(|status| status.termination)
+        .and_then(|termination| termination.pre_kill_hook)
+        .expect("pre-kill hook should run");
+    assert_eq!(hook.exit_code, Some(0));
+    assert_eq!(std::fs::read_to_string(&marker).unwrap().trim(), "hooked");
+    let _ = std::fs::remove_file(marker);
+}
+
+#[test]
+fn run_exec_warns_the_child_at_its_deadline() {
+    let root = std::env::temp_dir().join(format!("ptybox-deadline-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    let root = root.canonicalize().unwrap();
+    let policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allow_shell()
+        .allowed_executables(vec!["/bin/sh".to_string()])
+        .allowed_write(vec![root.display().to_string()])
+        .process(ProcessPolicy {
+            deadline_signal: Some(ChildSignal::Usr2),
+            deadline_grace_ms: 5000,
+            ..ProcessPolicy::default()
+        })
+        .max_runtime_ms(1500)
+        .build();
+    let script = "trap 'echo saved; exit 0' USR2; \
+        echo \"budget=$PTYBOX_DEADLINE_MS grace=$PTYBOX_GRACE_MS\"; \
+        while :; do sleep 1; done";
+
+    let err = ptybox::run::run_exec_with_options(
+        "/bin/sh".to_string(),
+        vec!["-c".to_string(), script.to_string()],
+        None,
+        policy,
+        RunnerOptions {
+            artifacts: Some(ptybox::artifacts::ArtifactsWriterConfig {
+                dir: root.join("artifacts"),
+                overwrite: false,
+            }),
+            ..RunnerOptions::default()
+        },
+    )
+    .expect_err("the runtime budget should run out");
+
+    assert_eq!(err.code, ErrorCode::Timeout);
+    let termination = &err.context.unwrap()["termination"];
+    assert_eq!(termination["deadline_warning"]["signal"], "SIGUSR2");
+    assert_eq!(termination["deadline_warning"]["exited"], true);
+    assert_eq!(termination["escalated"], false);
+    let transcript =
+        std::fs::read_to_string(root.join("artifacts").join("transcript.log")).unwrap();
+    assert!(
+        transcript.contains("budget=1500 grace=5200"),
+        "{transcript}"
+    );
+    assert!(transcript.contains("saved"), "{transcript}");
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn run_scenario_rejects_env_overlay_outside_allowlist() {
+    for var in ["NOT_ALLOWLISTED", "LD_PRELOAD"] {
+        let step = Step {
+            env_overlay: BTreeMap::from([(var.to_string(), "x".to_string())]),
+            ..capture_step("overlay", Action::text("x"), Vec::new(), Value::Null)
+        };
+        let scenario = create_scenario(vec![step], "/bin/echo", Vec::new());
+        let err = run_scenario(scenario).expect_err("overlay should be rejected");
+        assert_eq!(err.code, ErrorCode::PolicyDenied, "{var}");
+        let context = err.context.unwrap();
+        assert_eq!(context["step_name"], "overlay");
+        assert_eq!(context["details"]["var"], var);
+    }
+}
+
+#[test]
+fn run_scenario_copies_step_metadata_into_results() {
+    let metadata = BTreeMap::from([
+        ("ticket".to_string(), serde_json::json!("PTY-42")),
+        ("owners".to_string(), serde_json::json!(["tui", "infra"])),
+        ("flaky".to_string(), serde_json::json!({ "since": 3 })),
+    ]);
+    let step = Step {
+        metadata: metadata.clone(),
+        ..capture_step("greet", wait_for("hi"), Vec::new(), Value::Null)
+    };
+    let scenario = create_scenario(vec![step], "/bin/echo", vec!["hi".to_string()]);
+    let result = run_scenario(scenario).unwrap();
+
+    let steps = result.steps.as_ref().unwrap();
+    assert_eq!(steps[0].metadata, metadata);
+    let json = serde_json::to_value(&steps[0]).unwrap();
+    assert_eq!(json["metadata"]["owners"][1], "infra");
+}
+
+#[test]
+fn run_scenario_rejects_oversized_step_metadata() {
+    let step = Step {
+        metadata: BTreeMap::from([(
+            "notes".to_string(),
+            Value::String("x".repeat(usize::try_from(MAX_STEP_METADATA_BYTES).unwrap())),
+        )]),
+        ..capture_step("noisy", Action::text("x"), Vec::new(), Value::Null)
+    };
+    let scenario = create_scenario(vec![step], "/bin/echo", Vec::new());
+    let err = run_scenario(scenario).expect_err("metadata should be rejected");
+    assert_eq!(err.code, ErrorCode::Protocol);
+    let context = err.context.unwrap();
+    assert_eq!(context["step_name"], "noisy");
+    assert_eq!(
+        context["details"]["max_metadata_bytes"],
+        MAX_STEP_METADATA_BYTES
+    );
+}
+
+fn signal_scenario(allowed_signals: Vec<ChildSignal>) -> Scenario {
+    let policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allow_shell()
+        .allowed_executables(vec!["/bin/sh".to_string()])
+        .process(ProcessPolicy {
+            allowed_signals,
+            ..ProcessPolicy::default()
+        })
+        .max_runtime_ms(10_000)
+        .build();
+    let steps = vec![
+        capture_step("ready", wait_for("ready"), Vec::new(), Value::Null),
+        capture_step(
+            "interrupt",
+            Action::signal(ChildSignal::Int),
+            vec![Assertion::screen_contains("got-int")],
+            Value::Null,
+        ),
+    ];
+    let mut scenario = create_scenario(
+        steps,
+        "/bin/sh",
+        vec![
+            "-c".to_string(),
+            "trap 'echo got-int; exit 0' INT; echo ready; while :; do sleep 0.1; done".to_string(),
+        ],
+    );
+    scenario.steps[1].retries = 3;
+    scenario.run.policy = PolicyRef::Inline(Box::new(policy));
+    scenario
+}
+
+#[test]
+fn run_scenario_signal_action_delivers_allowed_signal() {
+    let result = run_scenario(signal_scenario(vec![ChildSignal::Int])).unwrap();
+
+    assert_eq!(result.status, RunStatus::Passed, "{:?}", result.error);
+    let steps = result.steps.unwrap();
+    assert!(matches!(steps[1].action.action_type, ActionType::Signal));
+    assert_eq!(steps[1].latency_ms, None);
+}
+
+#[test]
+fn run_scenario_signal_action_requires_allowlisted_signal() {
+    let err = run_scenario(signal_scenario(vec![ChildSignal::Hup])).unwrap_err();
+
+    assert_eq!(err.code, ErrorCode::PolicyDenied);
+    assert!(err.message.contains("SIGINT"), "{}", err.message);
+    let context = err.context.unwrap();
+    assert_eq!(context["allowed_signals"], json!(["SIGHUP"]));
+}
+
+/// Answers every connection on its sockets with `pong` and records the
+/// socket paths it was handed.
+#[derive(Default)]
+struct PongHandler {
+    served: Mutex<Vec<(String, PathBuf)>>,
+}
+
+impl SocketHandler for PongHandler {
+    fn serve(&self, name: &str, listener: UnixListener) {
+        let addr = listener.local_addr().unwrap();
+        let path = addr.as_pathname().unwrap().to_path_buf();
+        self.served.lock().unwrap().push((name.to_string(), path));
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { break };
+                let _ = stream.write_all(b"pong\n");
+            }
+        });
+    }
+}
+
+fn socket_scenario() -> Scenario {
+    let step = Step {
+        id: StepId::new(),
+        name: "wait_done".to_string(),
+        action: Action {
+            action_type: ActionType::Wait,
+            payload: json!({
+                "condition": {"type": "screen_contains", "payload": {"text": "done"}}
+            }),
+        },
+        assert: vec![Assertion::screen_contains("is-socket")],
+        timeout_ms: 5000,
+        retries: 0,
+        skip_default_assertions: false,
+        sample_interval_ms: None,
+        expected_screen: None,
+        capture: None,
+        env_overlay: BTreeMap::new(),
+        metadata: BTreeMap::new(),
+        vars: Vec::new(),
+    };
+    let mut scenario = shell_scenario(
+        vec![step],
+        r#"echo "at $PTYBOX_SOCKET_MOCK"; [ -S "$PTYBOX_SOCKET_MOCK" ] && echo is-socket; sleep 1; echo done"#,
+    );
+    scenario.run.policy = PolicyRef::Inline(Box::new(
+        PolicyBuilder::new()
+            .sandbox_disabled()
+            .allow_shell()
+            .allowed_executables(vec!["/bin/sh".to_string()])
+            .max_runtime_ms(10_000)
+            .sockets(vec![SocketGrant {
+                name: "mock".to_string(),
+                env: None,
+            }])
+            .build(),
+    ));
+    scenario
+}
+
+#[test]
+fn run_scenario_serves_policy_sockets_through_handler() {
+    let handler = Arc::new(PongHandler::default());
+    let served = Arc::clone(&handler);
+    let client = std::thread::spawn(move || {
+        let path = loop {
+            if let Some((_, path)) = served.served.lock().unwrap().first() {
+                break path.clone();
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        };
+        let mut reply = String::new();
+        UnixStream::connect(&path)
+            .unwrap()
+            .read_to_string(&mut reply)
+            .unwrap();
+        (path, reply)
+    });
+    let options = RunnerOptions {
+        socket_handler: Some(handler.clone()),
+        ..RunnerOptions::default()
+    };
+
+    let run_result =
+        run_scenario_with_options(socket_scenario(), options).expect("run should complete");
+    assert_eq!(run_result.status, RunStatus::Passed, "{run_result:?}");
+    let (path, reply) = client.join().unwrap();
+    assert_eq!(reply, "pong\n");
+    assert_eq!(handler.served.lock().unwrap()[0].0, "mock");
+    assert!(path.ends_with("mock.sock"), "{path:?}");
+    let screen = run_result
+        .final_observation
+        .unwrap()
+        .screen
+        .lines
+        .join("\n");
+    assert!(
+        screen.contains(&format!("at {}", path.display())),
+        "{screen}"
+    );
+    assert!(
+        !path.parent().unwrap().exists(),
+        "socket directory is removed"
+    );
+}
+
+#[test]
+fn run_scenario_rejects_policy_sockets_without_handler() {
+    let err = run_scenario(socket_scenario()).expect_err("sockets need a handler");
+    assert_eq!(err.code, ErrorCode::PolicyDenied);
+    assert!(err.message.contains("socket handler"), "{}", err.message);
+}
+
+// =============================================================================
+// Step Latency Tests
+// =============================================================================
+
+fn wait_for(text: &str) -> Action {
+    Action {
+        action_type: ActionType::Wait,
+        payload: serde_json::json!({ "condition": Condition::screen_contains(text) }),
+    }
+}
+
+fn latency_step(name: &str, action: Action, assert: Vec<Assertion>) -> Step {
+    Step {
+        id: StepId::new(),
+        name: name.to_string(),
+        action,
+        assert,
+        timeout_ms: 3000,
+        retries: 0,
+        skip_default_assertions: false,
+        sample_interval_ms: None,
+        expected_screen: None,
+        capture: None,
+        env_overlay: BTreeMap::new(),
+        metadata: BTreeMap::new(),
+        vars: Vec::new(),
+    }
+}
+
+#[test]
+fn run_scenario_records_step_latency() {
+    let steps = vec![
+        latency_step(
+            "type",
+            Action::text("x\n"),
+            vec![Assertion::responded_within_ms(2000)],
+        ),
+        latency_step(
+            "wait_reply",
+            wait_for("reply"),
+            vec![Assertion::responded_within_ms(2000)],
+        ),
+        latency_step("terminate", Action::terminate(), Vec::new()),
+    ];
+    let scenario = shell_scenario(steps, "read l; echo reply; sleep 5");
+
+    let run_result = run_scenario(scenario).expect("run should complete");
+    assert_eq!(run_result.status, RunStatus::Passed, "{run_result:?}");
+    let steps = run_result.steps.unwrap();
+    assert!(steps[0].latency_ms.is_some_and(|ms| ms <= 2000));
+    assert!(steps[1].latency_ms.is_some());
+    assert_eq!(steps[2].latency_ms, None);
+    let details = steps[0].assertions[0].details.as_ref().unwrap();
+    assert_eq!(details["budget_ms"], 2000);
+}
+
+#[test]
+fn run_scenario_fails_step_that_responds_too_slowly() {
+    let steps = vec![latency_step(
+        "wait_done",
+        wait_for("done"),
+        vec![Assertion::responded_within_ms(100)],
+    )];
+    let scenario = shell_scenario(steps, "sleep 0.4; echo done; sleep 5");
+
+    let run_result = run_scenario(scenario).expect("run should complete");
+    assert_eq!(run_result.status, RunStatus::Failed);
+    let step = &run_result.steps.unwrap()[0];
+    assert_eq!(step.status, StepStatus::Failed);
+    let latency = step.latency_ms.expect("latency should be measured");
+    assert!(latency >= 300, "latency {latency}ms");
+    let result = &step.assertions[0];
+    assert!(!result.passed);
+    assert_eq!(result.details.as_ref().unwrap()["latency_ms"], latency);
+}
+
+// =============================================================================
+// Step Sampling Tests
+// =============================================================================
+
+fn sampling_scenario(root: &std::path::Path, script: &str, max_step_samples: u64) -> Scenario {
+    let mut step = latency_step("wait_done", wait_for("done"), Vec::new());
+    step.sample_interval_ms = Some(20);
+    let mut scenario = create_scenario(
+        vec![step],
+        "/bin/sh",
+        vec!["-c".to_string(), script.to_string()],
+    );
+    let mut policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allow_shell()
+        .allowed_executables(vec!["/bin/sh".to_string()])
+        .allowed_write(vec![root.display().to_string()])
+        .max_runtime_ms(10_000)
+        .build();
+    policy.budgets.max_step_samples = Some(max_step_samples);
+    scenario.run.policy = PolicyRef::Inline(Box::new(policy));
+    scenario
+}
+
+fn run_with_artifacts(
+    scenario: Scenario,
+    artifacts_dir: &std::path::Path,
+) -> ptybox::model::RunResult {
+    let options = RunnerOptions {
+        artifacts: Some(ptybox::artifacts::ArtifactsWriterConfig {
+            dir: artifacts_dir.to_path_buf(),
+            overwrite: false,
+        }),
+        ..RunnerOptions::default()
+    };
+    run_scenario_with_options(scenario, options).expect("run should complete")
+}
+
+#[test]
+fn run_scenario_samples_intermediate_screens() {
+    let root = std::env::temp_dir().join(format!("ptybox-samples-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    let root = root.canonicalize().unwrap();
+    let artifacts_dir = root.join("artifacts");
+
+    let script = "for f in one two three; do printf '\\rframe-%s' $f; sleep 0.1; done; echo; echo done; sleep 5";
+    let run_result = run_with_artifacts(sampling_scenario(&root, script, 100), &artifacts_dir);
+    assert_eq!(run_result.status, RunStatus::Passed, "{run_result:?}");
+
+    let stats = run_result.steps.unwrap()[0]
+        .samples
+        .expect("sampling step should report samples");
+    assert!(stats.captured >= 2, "{stats:?}");
+    assert_eq!(stats.dropped, 0);
+
+    let paths = ptybox::artifacts::sample_paths(&artifacts_dir).unwrap();
+    assert_eq!(paths.len() as u64, stats.captured);
+    let screens: Vec<String> = paths
+        .iter()
+        .map(|path| {
+            let sample: ptybox::model::ScreenSample =
+                serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+            sample.screen.lines.join("\n")
+        })
+        .collect();
+    assert!(
+        screens.iter().any(|screen| screen.contains("frame-two")),
+        "{screens:?}"
+    );
+    // Samples never land among the snapshots replay compares
+    assert_eq!(
+        ptybox::artifacts::snapshot_paths(&artifacts_dir)
+            .unwrap()
+            .len(),
+        1
+    );
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn run_scenario_drops_samples_over_budget() {
+    let root = std::env::temp_dir().join(format!("ptybox-samples-cap-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    let root = root.canonicalize().unwrap();
+    let artifacts_dir = root.join("artifacts");
+
+    let script =
+        "for f in 1 2 3 4 5; do printf '\\rframe-%s' $f; sleep 0.1; done; echo; echo done; sleep 5";
+    let run_result = run_with_artifacts(sampling_scenario(&root, script, 1), &artifacts_dir);
+    assert_eq!(run_result.status, RunStatus::Passed, "{run_result:?}");
+
+    let stats = run_result.steps.unwrap()[0].samples.unwrap();
+    assert!(stats.captured <= 1, "{stats:?}");
+    assert!(stats.dropped >= 1, "{stats:?}");
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn run_scenario_rejects_too_small_sample_interval() {
+    let mut step = latency_step("wait_done", wait_for("done"), Vec::new());
+    step.sample_interval_ms = Some(1);
+    let scenario = shell_scenario(vec![step], "echo done");
+
+    let err = run_scenario(scenario).expect_err("interval below minimum should be rejected");
+    assert_eq!(err.code, ErrorCode::Protocol);
+}
+
+/// Sets the run's cancel flag once the first step completes.
+struct CancelAfterFirstStep(Arc<AtomicBool>);
+
+impl ProgressCallback for CancelAfterFirstStep {
+    fn on_progress(&self, event: &ProgressEvent) {
+        if matches!(event, ProgressEvent::StepCompleted { .. }) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+#[test]
+fn run_scenario_cancel_flag_skips_remaining_steps() {
+    let steps = vec![
+        latency_step("first", wait_for("one"), Vec::new()),
+        latency_step("second", wait_for("two"), Vec::new()),
+    ];
+    let scenario = create_scenario(
+        steps,
+        "/bin/echo",
+        vec!["one".to_string(), "two".to_string()],
+    );
+    let cancel = Arc::new(AtomicBool::new(false));
+    let options = RunnerOptions {
+        progress: Some(Arc::new(CancelAfterFirstStep(Arc::clone(&cancel)))),
+        cancel: Some(cancel),
+        ..RunnerOptions::default()
+    };
+
+    let result = run_scenario_with_options(scenario, options).unwrap();
+    assert_eq!(result.status, RunStatus::Canceled);
+    assert!(result.error.is_none());
+    let steps = result.steps.unwrap();
+    assert_eq!(steps[0].status, StepStatus::Passed);
+    assert_eq!(steps[1].status, StepStatus::Skipped);
+    assert!(
+        result.ended_at_ms < 5000,
+        "child should be stopped on cancel"
+    );
+}
+
+// =============================================================================
+// Write Quota Tests
+// =============================================================================
+
+fn quota_root(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("ptybox-quota-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    root.canonicalize().unwrap()
+}
+
+fn quota_policy(root: &std::path::Path, max_bytes: u64) -> ptybox::model::Policy {
+    PolicyBuilder::new()
+        .sandbox_disabled()
+        .allow_shell()
+        .allowed_executables(vec!["/bin/sh".to_string()])
+        .allowed_write(vec![root.display().to_string()])
+        .artifacts_dir(root.join("artifacts").display().to_string())
+        .write_quota(root.display().to_string(), max_bytes)
+        .max_runtime_ms(10_000)
+        .build()
+}
+
+fn read_resources(root: &std::path::Path) -> Value {
+    let data = std::fs::read(root.join("artifacts").join("resources.json")).unwrap();
+    serde_json::from_slice(&data).unwrap()
+}
+
+#[test]
+fn run_scenario_write_quota_stops_a_runaway_writer() {
+    let root = quota_root("runaway");
+    let script = format!(
+        "while :; do head -c 65536 /dev/zero >> '{}/blob'; sleep 0.05; done",
+        root.display()
+    );
+    let mut scenario = shell_scenario(
+        vec![latency_step("fill", wait_for("never printed"), Vec::new())],
+        &script,
+    );
+    scenario.steps[0].timeout_ms = 8000;
+    scenario.run.policy = PolicyRef::Inline(Box::new(quota_policy(&root, 256 * 1024)));
+
+    let started = std::time::Instant::now();
+    let result = run_scenario(scenario).unwrap();
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+
+    assert_eq!(result.status, RunStatus::Failed);
+    let error = result.error.as_ref().expect("quota error");
+    assert_eq!(error.code, "E_TIMEOUT");
+    assert_eq!(error.message, "write quota exceeded");
+    let quota = &error.context.as_ref().unwrap()["details"]["write_quota"];
+    assert_eq!(quota["path"], json!(root.display().to_string()));
+    assert!(quota["used_bytes"].as_u64().unwrap() > 256 * 1024);
+    let classification = result.classification.as_ref().unwrap();
+    assert_eq!(classification.category, FailureCategory::Budget);
+
+    let usage = &read_resources(&root)["write_quotas"][0];
+    assert_eq!(usage["max_bytes"], json!(256 * 1024));
+    assert_eq!(usage["exceeded"], json!(true));
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn run_exec_write_quota_reports_usage_without_counting_artifacts() {
+    let root = quota_root("usage");
+    let script = format!(
+        "head -c 1000 /dev/zero > '{}/out'; echo done",
+        root.display()
+    );
+
+    let result = run_exec(
+        "/bin/sh".to_string(),
+        vec!["-c".to_string(), script],
+        None,
+        quota_policy(&root, 1024 * 1024),
+    )
+    .unwrap();
+    assert_eq!(result.status, RunStatus::Passed);
+
+    let usage = &read_resources(&root)["write_quotas"][0];
+    assert_eq!(usage["path"], json!(root.display().to_string()));
+    assert_eq!(usage["baseline_bytes"], json!(0));
+    assert_eq!(usage["peak_bytes"], json!(1000));
+    assert_eq!(usage["exceeded"], json!(false));
+    assert!(usage["samples"].as_u64().unwrap() >= 2);
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn run_scenario_post_run_checks_files_and_writes_fs_manifest() {
+    let root = quota_root("post-run");
+    let out = root.join("out.txt");
+    let script = format!("printf hello > '{}'", out.display());
+    let mut scenario = shell_scenario(Vec::new(), &script);
+    scenario.run.policy = PolicyRef::Inline(Box::new(
+        PolicyBuilder::new()
+            .sandbox_disabled()
+            .allow_shell()
+            .allowed_executables(vec!["/bin/sh".to_string()])
+            .allowed_write(vec![root.display().to_string()])
+            .artifacts_dir(root.join("artifacts").display().to_string())
+            .artifacts_fs_manifest()
+            .max_runtime_ms(10_000)
+            .build(),
+    ));
+    let path = out.display().to_string();
+    scenario.post_run = vec![
+        Assertion::file_exists(&path),
+        Assertion::file_contains(&path, "hello"),
+        Assertion::file_contains(&path, "goodbye"),
+    ];
+
+    let result = run_scenario(scenario).unwrap();
+    assert_eq!(result.status, RunStatus::Failed, "{result:?}");
+    let passed: Vec<bool> = result.post_run.iter().map(|r| r.passed).collect();
+    assert_eq!(passed, vec![true, true, false]);
+    let error = result.error.as_ref().unwrap();
+    assert_eq!(error.code, "E_ASSERTION_FAILED");
+    assert_eq!(error.message, "post_run assertion failed");
+
+    let data = std::fs::read(root.join("artifacts").join("fs-manifest.json")).unwrap();
+    let manifest: Value = serde_json::from_slice(&data).unwrap();
+    let files = manifest["files"].as_array().unwrap();
+    assert_eq!(files.len(), 1, "artifacts are left out: {manifest}");
+    assert_eq!(files[0]["path"], json!(path));
+    assert_eq!(files[0]["size"], json!(5));
+    assert_eq!(
+        files[0]["sha256"],
+        json!("2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824")
+    );
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn run_scenario_invalid_wait_payload_lists_every_condition_type() {
+    let step = Step {
+        action: Action {
+            action_type: ActionType::Wait,
+            payload: json!({"until": "ready"}),
+        },
+        assert: Vec::new(),
+        ..wait_then_absent_step("ignored", 0)
+    };
+    let scenario = create_scenario(vec![step], "/bin/sleep", vec!["1".to_string()]);
+
+    let result = run_scenario(scenario).unwrap();
+    assert_eq!(result.status, RunStatus::Failed);
+    let step = &result.steps.as_ref().unwrap()[0];
+    let error = step.error.as_ref().expect("step error");
+    assert_eq!(error.code, "E_PROTOCOL", "{}", error.message);
+    let types = error.context.as_ref().unwrap()["expected"]["condition"]["type"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let listed: Vec<&str> = types.split(" | ").collect();
+    let expected: Vec<&str> = ptybox::predicate::PREDICATES
+        .iter()
+        .map(|spec| spec.name)
+        .collect();
+    assert_eq!(listed, expected);
+}
+
+#[test]
+fn run_scenario_rejects_invalid_post_run_assertions() {
+    let cases = [
+        (
+            Assertion::file_exists("/etc/passwd"),
+            ErrorCode::PolicyDenied,
+        ),
+        (
+            Assertion::file_exists("relative.txt"),
+            ErrorCode::PolicyDenied,
+        ),
+        (
+            Assertion {
+                assertion_type: "not_an_assertion".to_string(),
+                payload: json!({}),
+                inherited: false,
+            },
+            ErrorCode::Protocol,
+        ),
+        (
+            Assertion {
+                assertion_type: "screen_contains".to_string(),
+                payload: json!({}),
+                inherited: false,
+            },
+            ErrorCode::Protocol,
+        ),
+    ];
+    for (assertion, code) in cases {
+        let mut scenario = create_scenario(Vec::new(), "/bin/echo", Vec::new());
+        scenario.post_run = vec![assertion];
+        let err = run_scenario(scenario).expect_err("post_run should be rejected");
+        assert_eq!(err.code, code, "{}", err.message);
+    }
+}
+
+#[test]
+fn run_scenario_post_run_checks_after_a_failed_step() {
+    let root = quota_root("post");
+    let out = root.join("out.txt");
+    let script = format!(
+        "printf hello > '{}'; echo ready; echo oops >&2; exit 3",
+        out.display()
+    );
+    let failing = Step {
+        action: Action {
+            action_type: ActionType::Wait,
+            payload: json!({"condition": {"type": "process_exited"}}),
+        },
+        assert: vec![Assertion::screen_contains("never printed")],
+        timeout_ms: 500,
+        ..wait_then_absent_step("ignored", 0)
+    };
+    let mut scenario = shell_scenario(vec![failing], &script);
+    scenario.run.separate_stderr = true;
+    scenario.run.policy = PolicyRef::Inline(Box::new(
+        PolicyBuilder::new()
+            .sandbox_disabled()
+            .allow_shell()
+            .allowed_executables(vec!["/bin/sh".to_string()])
+            .allowed_write(vec![root.display().to_string()])
+            .max_runtime_ms(10_000)
+            .build(),
+    ));
+    scenario.post_run = vec![
+        Assertion::file_contains(&out.display().to_string(), "hello"),
+        Assertion::exit_code(3),
+        Assertion::screen_contains("ready"),
+        Assertion::stderr_empty(),
+    ];
+
+    let result = run_scenario(scenario).unwrap();
+    assert_eq!(result.status, RunStatus::Failed);
+    // The step failure stays the run error; post_run still ran.
+    assert_eq!(result.steps.as_ref().unwrap()[0].status, StepStatus::Failed);
+    assert_ne!(
+        result.error.as_ref().unwrap().message,
+        "post_run assertion failed"
+    );
+    let passed: Vec<bool> = result.post_run.iter().map(|r| r.passed).collect();
+    assert_eq!(
+        passed,
+        vec![true, true, true, false],
+        "{:?}",
+        result.post_run
+    );
+    assert_eq!(
+        result.post_run[3].details.as_ref().unwrap()["head"],
+        "oops\n"
+    );
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn run_scenario_post_run_failure_fails_a_passing_run() {
+    let mut scenario = create_scenario(Vec::new(), "/bin/echo", vec!["done".to_string()]);
+    let process_exited = Assertion {
+        assertion_type: "process_exited".to_string(),
+        payload: json!({}),
+        inherited: false,
+    };
+    // The deprecated `post` list is folded into `post_run`.
+    scenario.post_run = vec![process_exited];
+    scenario.post = vec![Assertion::exit_code(1)];
+
+    let result = run_scenario(scenario).unwrap();
+    assert_eq!(result.status, RunStatus::Failed);
+    let types: Vec<&str> = result
+        .post_run
+        .iter()
+        .map(|r| r.assertion_type.as_str())
+        .collect();
+    assert_eq!(types, vec!["process_exited", "exit_code"]);
+    assert!(result.post_run[1].details.is_some());
+    let error = result.error.as_ref().unwrap();
+    assert_eq!(error.code, "E_ASSERTION_FAILED");
+    assert_eq!(error.message, "post_run assertion failed");
+    // The error points at the failure; message and details stay in `post_run`.
+    assert_eq!(
+        error.context.as_ref().unwrap(),
+        &json!({ "post_run": [{ "index": 1, "type": "exit_code" }] })
+    );
+
+    let mut scenario = create_scenario(Vec::new(), "/bin/echo", Vec::new());
+    scenario.post = vec![Assertion::file_exists("/etc/passwd")];
+    let err = run_scenario(scenario).expect_err("path outside the allowlist");
+    assert_eq!(err.code, ErrorCode::PolicyDenied);
+}
+
+// =============================================================================
+// why-differs Tests
+// =============================================================================
+
+#[test]
+fn why_differs_ranks_the_budget_of_the_timed_out_run_high() {
+    let root = std::env::temp_dir().join(format!("ptybox-why-differs-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    let root = root.canonicalize().unwrap();
+    let run = |name: &str, max_wait_ms: u64, rows: u16| {
+        let wait = Step {
+            id: StepId::new(),
+            name: "ready".to_string(),
+            action: Action {
+                action_type: ActionType::Wait,
+                payload: json!({ "condition": Condition::screen_contains("ready") }),
+            },
+            assert: Vec::new(),
+            timeout_ms: 2000,
+            retries: 0,
+            skip_default_assertions: false,
+            sample_interval_ms: None,
+            expected_screen: None,
+            capture: None,
+            env_overlay: BTreeMap::new(),
+            metadata: BTreeMap::new(),
+            vars: Vec::new(),
+        };
+        let mut scenario = shell_scenario(vec![wait], "sleep 0.4; echo ready; sleep 1");
+        scenario.run.initial_size = TerminalSize::new(rows, 80);
+        scenario.run.policy = PolicyRef::Inline(Box::new(
+            PolicyBuilder::new()
+                .sandbox_disabled()
+                .allow_shell()
+                .allowed_executables(vec!["/bin/sh".to_string()])
+                .allowed_write(vec![root.display().to_string()])
+                .max_runtime_ms(10_000)
+                .max_wait_ms(max_wait_ms)
+                .build(),
+        ));
+        let dir = root.join(name);
+        let options = RunnerOptions {
+            artifacts: Some(ptybox::artifacts::ArtifactsWriterConfig {
+                dir: dir.clone(),
+                overwrite: false,
+            }),
+            ..RunnerOptions::default()
+        };
+        run_scenario_with_options(scenario, options).unwrap();
+        dir
+    };
+    let passing = run("local", 5000, 24);
+    let failing = run("ci", 100, 30);
+    assert!(passing.join("host.json").is_file());
+    assert!(passing.join("env.json").is_file());
+
+    let report = ptybox::artifacts::why_differs(&passing, &failing).unwrap();
+    assert_eq!(report.a.status, RunStatus::Passed);
+    assert_eq!(report.b.error_code.as_deref(), Some("E_TIMEOUT"));
+    assert_eq!(report.first_divergent_step.as_deref(), Some("ready"));
+    assert!(report.not_compared.is_empty(), "{:?}", report.not_compared);
+    let cause = |field: &str| {
+        report
+            .causes
+            .iter()
+            .find(|cause| cause.field == field)
+            .unwrap_or_else(|| panic!("no cause for {field}: {:?}", report.causes))
+    };
+    assert_eq!(
+        cause("policy.budgets.max_wait_ms").likelihood,
+        FailureConfidence::High
+    );
+    assert_eq!(cause("policy.budgets.max_wait_ms").b, json!(100));
+    assert_eq!(
+        cause("run.initial_size").likelihood,
+        FailureConfidence::High
+    );
+    let ranks: Vec<u32> = report.causes.iter().map(|cause| cause.rank).collect();
+    assert_eq!(ranks, (1..=report.causes.len() as u32).collect::<Vec<_>>());
+    assert!(report
+        .causes
+        .windows(2)
+        .all(|pair| pair[0].likelihood >= pair[1].likelihood));
+    assert!(report
+        .causes
+        .iter()
+        .all(|cause| !cause.field.starts_with("policy.artifacts")));
+
+    let same = ptybox::artifacts::why_differs(&passing, &passing).unwrap();
+    assert!(same.causes.is_empty(), "{:?}", same.causes);
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+fn fixture_scenario(root: &std::path::Path, script: &str, seed: u64) -> Scenario {
+    let mut scenario = shell_scenario(Vec::new(), script);
+    scenario.run.cwd = Some(root.display().to_string());
+    scenario.run.policy = PolicyRef::Inline(Box::new(
+        PolicyBuilder::new()
+            .sandbox_disabled()
+            .allow_shell()
+            .allowed_executables(vec!["/bin/sh".to_string()])
+            .allowed_write(vec![root.display().to_string()])
+            .artifacts_dir(root.join("artifacts").display().to_string())
+            .artifacts_overwrite()
+            .max_runtime_ms(10_000)
+            .build(),
+    ));
+    scenario.fixtures = serde_json::from_value(json!([
+        {
+            "path": root.join("big.bin").display().to_string(),
+            "file": {"size": "1500B", "fill": format!("random({seed})")}
+        },
+        {
+            "path": root.join("tree").display().to_string(),
+            "tree": {"depth": 1, "fanout": 2, "file_size": 10}
+        }
+    ]))
+    .unwrap();
+    scenario
+}
+
+#[test]
+fn run_scenario_generates_fixtures_before_spawn() {
+    let root = quota_root("fixtures");
+    let mut scenario = fixture_scenario(
+        &root,
+        "printf 'size=%s entries=%s\\n' $(wc -c < big.bin) $(ls tree/dir-0 | wc -l)",
+        7,
+    );
+    scenario.post_run = vec![Assertion::screen_contains("size=1500 entries=2")];
+
+    let result = run_scenario(scenario).unwrap();
+    assert_eq!(result.status, RunStatus::Passed, "{result:?}");
+    let counts: Vec<(u64, u64, u64)> = result
+        .fixtures
+        .iter()
+        .map(|record| (record.files, record.dirs, record.bytes))
+        .collect();
+    assert_eq!(counts, vec![(1, 0, 1500), (6, 3, 60)]);
+    let content = std::fs::read(root.join("big.bin")).unwrap();
+    assert!(content.iter().any(|byte| *byte != 0));
+    assert_eq!(
+        std::fs::read(root.join("tree/dir-1/file-1")).unwrap(),
+        vec![0; 10]
+    );
+
+    let data = std::fs::read(root.join("artifacts").join("run.json")).unwrap();
+    let run: Value = serde_json::from_slice(&data).unwrap();
+    assert_eq!(run["fixtures"][0]["file"]["fill"], json!("random(7)"));
+    assert_eq!(run["fixtures"][0]["file"]["size"], json!(1500));
+    assert_eq!(
+        run["fixtures"][1]["checksum"],
+        json!(result.fixtures[1].checksum)
+    );
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn run_scenario_fixtures_are_deterministic_per_seed() {
+    let root = quota_root("fixtures-seed");
+    let run = |seed| {
+        let result = run_scenario(fixture_scenario(&root, "true", seed)).unwrap();
+        assert_eq!(result.status, RunStatus::Passed, "{result:?}");
+        let content = std::fs::read(root.join("big.bin")).unwrap();
+        (result.fixtures[0].checksum.clone(), content)
+    };
+    let first = run(1);
+    assert_eq!(run(1), first);
+    let other = run(2);
+    assert_ne!(other.0, first.0);
+    assert_ne!(other.1, first.1);
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn run_scenario_rejects_fixtures_outside_policy() {
+    let root = quota_root("fixtures-denied");
+    let cases = [
+        (
+            json!({"path": "/etc/ptybox-fixture", "file": {"size": 1}}),
+            ErrorCode::PolicyDenied,
+        ),
+        (
+            json!({"path": "relative.bin", "file": {"size": 1}}),
+            ErrorCode::PolicyDenied,
+        ),
+        (
+            json!({"path": root.join("huge.bin").display().to_string(), "file": {"size": "2GiB"}}),
+            ErrorCode::Protocol,
+        ),
+        (
+            json!({
+                "path": root.join("wide").display().to_string(),
+                "tree": {"depth": 8, "fanout": 10}
+            }),
+            ErrorCode::Protocol,
+        ),
+    ];
+    for (fixture, code) in cases {
+        let mut scenario = fixture_scenario(&root, "true", 0);
+        scenario.fixtures = serde_json::from_value(json!([fixture])).unwrap();
+        let err = run_scenario(scenario).expect_err("fixture should be rejected");
+        assert_eq!(err.code, code, "{fixture}: {}", err.message);
+    }
+    assert!(!root.join("huge.bin").exists());
+    assert!(!root.join("wide").exists());
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+// =============================================================================
+// Argument Probe Tests
+// =============================================================================
+
+#[test]
+fn run_scenario_validate_args_records_help_and_missing_flags() {
+    let root = std::env::temp_dir().join(format!("ptybox-args-probe-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    let root = root.canonicalize().unwrap();
+    let artifacts_dir = root.join("artifacts");
+
+    let mut scenario = create_scenario(
+        Vec::new(),
+        "/bin/echo",
+        vec!["-n".to_string(), "--frobnicate=yes".to_string()],
+    );
+    scenario.run.validate_args = true;
+    scenario.run.policy = PolicyRef::Inline(Box::new(
+        PolicyBuilder::new()
+            .sandbox_disabled()
+            .allowed_executables(vec!["/bin/echo".to_string()])
+            .allowed_write(vec![root.display().to_string()])
+            .build(),
+    ));
+    let options = RunnerOptions {
+        artifacts: Some(ptybox::artifacts::ArtifactsWriterConfig {
+            dir: artifacts_dir.clone(),
+            overwrite: false,
+        }),
+        ..RunnerOptions::default()
+    };
+
+    let run_result = run_scenario_with_options(scenario, options).expect("run should complete");
+    assert_eq!(run_result.status, RunStatus::Passed, "{run_result:?}");
+    let record: ptybox::model::ArgsProbeRecord = serde_json::from_str(
+        &std::fs::read_to_string(artifacts_dir.join("args-probe.json")).unwrap(),
+    )
+    .unwrap();
+    let flags: Vec<&str> = record.probes.iter().map(|p| p.flag.as_str()).collect();
+    assert_eq!(flags, ["--help", "--version"]);
+    assert_eq!(record.probes[0].exit_code, Some(0));
+    assert!(record.probes[0].output.contains("-n"), "{record:?}");
+    assert_eq!(record.missing_flags, ["--frobnicate"]);
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn run_scenario_validate_args_fails_fast_when_the_probe_cannot_execute() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let root = std::env::temp_dir().join(format!("ptybox-args-probe-exec-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    let root = root.canonicalize().unwrap();
+    let command = root.join("not-executable");
+    std::fs::write(&command, "#!/bin/sh\n").unwrap();
+    std::fs::set_permissions(&command, std::fs::Permissions::from_mode(0o644)).unwrap();
+    let command = command.display().to_string();
+
+    let mut scenario = create_scenario(Vec::new(), &command, Vec::new());
+    scenario.run.validate_args = true;
+    scenario.run.policy = PolicyRef::Inline(Box::new(
+        PolicyBuilder::new()
+            .sandbox_disabled()
+            .allowed_executables(vec![command.clone()])
+            .build(),
+    ));
+
+    let err = run_scenario(scenario).expect_err("probe should fail");
+    assert_eq!(err.code, ErrorCode::ProcessExit);
+    assert_eq!(
+        err.message,
+        format!("argument probe could not execute '{command} --help'")
+    );
+    assert_eq!(err.context.unwrap()["flag"], "--help");
+
+    let _ = std::fs::remove_dir_all(&root);
+}
@@ -0,0 +1,133 @@
+// Test module - relaxed lint rules
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(clippy::panic)]
+#![allow(missing_docs)]
+
+//! Sandbox violation auditing (`sandbox-audit` feature).
+
+use ptybox::artifacts::ArtifactsWriterConfig;
+use ptybox::model::policy::PolicyBuilder;
+use ptybox::model::SandboxViolationReport;
+use ptybox::policy::audit::{parse_violation, AUDIT_SOURCE};
+use ptybox::runner::{run_exec_with_options, RunnerOptions};
+
+#[test]
+fn parse_violation_reads_ndjson_log_entry() {
+    let line = serde_json::json!({
+        "timestamp": "2026-01-02 03:04:05.678901-0800",
+        "eventMessage": "Sandbox: cat(4242) deny(1) file-read-data /private/etc/hosts",
+        "senderImagePath": "/System/Library/Extensions/Sandbox.kext/Contents/MacOS/Sandbox",
+    })
+    .to_string();
+
+    let violation = parse_violation(&line).unwrap();
+    assert_eq!(violation.process, "cat");
+    assert_eq!(violation.pid, 4242);
+    assert_eq!(violation.operation, "file-read-data");
+    assert_eq!(violation.target.as_deref(), Some("/private/etc/hosts"));
+    assert_eq!(
+        violation.timestamp.as_deref(),
+        Some("2026-01-02 03:04:05.678901-0800")
+    );
+}
+
+#[test]
+fn parse_violation_handles_bare_messages_and_operations_without_target() {
+    let violation = parse_violation("Sandbox: my tool(7) deny(2) network-outbound").unwrap();
+    assert_eq!(violation.process, "my tool");
+    assert_eq!(violation.pid, 7);
+    assert_eq!(violation.operation, "network-outbound");
+    assert_eq!(violation.target, None);
+
+    let with_spaces =
+        parse_violation("Sandbox: vim(9) deny(1) file-write-create /tmp/a dir/file").unwrap();
+    assert_eq!(with_spaces.target.as_deref(), Some("/tmp/a dir/file"));
+}
+
+#[test]
+fn parse_violation_ignores_other_log_lines() {
+    assert!(
+        parse_violation("Filtering the log data using \"sender == \\\"Sandbox\\\"\"").is_none()
+    );
+    assert!(
+        parse_violation(r#"{"eventMessage":"Sandbox: cat(1) allow file-read-data /"}"#).is_none()
+    );
+    assert!(parse_violation(r#"{"timestamp":"x"}"#).is_none());
+}
+
+#[test]
+fn unsandboxed_run_records_why_nothing_was_collected() {
+    let dir = tempfile_dir("audit-unsandboxed");
+    let artifacts = dir.join("artifacts");
+    let policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_read(vec![dir.display().to_string()])
+        .allowed_write(vec![artifacts.display().to_string()])
+        .allowed_executables(vec!["/bin/echo".to_string()])
+        .build();
+    let options = RunnerOptions {
+        artifacts: Some(ArtifactsWriterConfig {
+            dir: artifacts.clone(),
+            overwrite: false,
+        }),
+        sandbox_audit: true,
+        ..RunnerOptions::default()
+    };
+
+    run_exec_with_options(
+        "/bin/echo".to_string(),
+        vec!["hi".to_string()],
+        Some(dir.display().to_string()),
+        policy,
+        options,
+    )
+    .unwrap();
+
+    let report: SandboxViolationReport =
+        serde_json::from_slice(&std::fs::read(artifacts.join("violations.json")).unwrap()).unwrap();
+    assert_eq!(report.source, AUDIT_SOURCE);
+    assert!(!report.collected);
+    assert!(report.reason.unwrap().contains("not sandboxed"));
+    assert!(report.violations.is_empty());
+}
+
+#[test]
+fn audit_is_skipped_without_the_option() {
+    let dir = tempfile_dir("audit-off");
+    let artifacts = dir.join("artifacts");
+    let policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_read(vec![dir.display().to_string()])
+        .allowed_write(vec![artifacts.display().to_string()])
+        .allowed_executables(vec!["/bin/echo".to_string()])
+        .build();
+    let options = RunnerOptions {
+        artifacts: Some(ArtifactsWriterConfig {
+            dir: artifacts.clone(),
+            overwrite: false,
+        }),
+        ..RunnerOptions::default()
+    };
+
+    run_exec_with_options(
+        "/bin/echo".to_string(),
+        vec!["hi".to_string()],
+        Some(dir.display().to_string()),
+        policy,
+        options,
+    )
+    .unwrap();
+    assert!(!artifacts.join("violations.json").exists());
+}
+
+fn tempfile_dir(prefix: &str) -> std::path::PathBuf {
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let dir = std::env::temp_dir().join(format!("ptybox-test-{prefix}-{stamp}"));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
@@ -14,8 +14,10 @@
 
 use std::fs;
 
-use ptybox::model::policy::{FsPolicy, NetworkEnforcementAck, NetworkPolicy, Policy, SandboxMode};
-use ptybox::policy::sandbox::write_profile;
+use ptybox::model::policy::{
+    FsPolicy, NetworkEnforcementAck, NetworkPolicy, Policy, SandboxFallback, SandboxMode,
+};
+use ptybox::policy::sandbox::{write_profile, write_profile_with_sockets};
 use ptybox::runner::ErrorCode;
 
 fn temp_profile(name: &str) -> std::path::PathBuf {
@@ -28,6 +30,7 @@ fn base_policy() -> Policy {
     Policy {
         policy_version: ptybox::model::policy::POLICY_VERSION,
         sandbox: SandboxMode::Seatbelt,
+        sandbox_fallback: SandboxFallback::Deny,
         network: NetworkPolicy::Disabled,
         network_enforcement: NetworkEnforcementAck::default(),
         fs: FsPolicy {
@@ -36,12 +39,22 @@ fn base_policy() -> Policy {
             working_dir: None,
             write_ack: true,
             strict_write: false,
+            write_quotas: Default::default(),
         },
         exec: Default::default(),
         env: Default::default(),
         budgets: Default::default(),
         artifacts: Default::default(),
         replay: Default::default(),
+        input: Default::default(),
+        determinism: Default::default(),
+        process: Default::default(),
+        sockets: Vec::new(),
+        remote: Default::default(),
+        terminal: Default::default(),
+        log_forward: Default::default(),
+        substitution_allowlist: Vec::new(),
+        grants: Vec::new(),
     }
 }
 
@@ -88,6 +101,20 @@ fn sandbox_profile_omits_read_rules_when_empty() {
     assert!(!contents.contains("file-read* (subpath"));
 }
 
+#[test]
+fn sandbox_profile_allows_only_the_socket_directory() {
+    let policy = base_policy();
+    let path = temp_profile("sockets");
+    let dir = std::path::Path::new("/tmp/ptybox-sockets");
+    write_profile_with_sockets(&path, &policy, Some(dir)).unwrap();
+    let contents = fs::read_to_string(&path).unwrap();
+    let _ = fs::remove_file(&path);
+    assert!(contents.contains(
+        "(allow network-outbound (remote unix-socket (subpath \"/tmp/ptybox-sockets\")))"
+    ));
+    assert!(!contents.contains("(remote ip)"));
+}
+
 // =============================================================================
 // Sandbox Injection Tests
 // =============================================================================
@@ -12,17 +12,23 @@
 #![allow(clippy::cast_possible_truncation)]
 #![allow(missing_docs)]
 
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
 
 use ptybox::model::policy::{
-    ArtifactsPolicy, Budgets, EnvPolicy, ExecPolicy, FsPolicy, NetworkEnforcementAck,
-    NetworkPolicy, Policy, ReplayPolicy, SandboxMode, POLICY_VERSION,
+    ArtifactsPolicy, Budgets, DeterminismPolicy, EnvPolicy, ExecPolicy, FsPolicy, InputPolicy,
+    NetworkEnforcementAck, NetworkPolicy, Policy, ProcessPolicy, ReplayPolicy, SandboxFallback,
+    SandboxMode, POLICY_VERSION,
 };
 use ptybox::model::scenario::{
-    Action, ActionType, PolicyRef, RunConfig, Scenario, ScenarioMetadata, Step,
+    Action, ActionType, Assertion, OutputBufferConfig, PolicyRef, ResizeConfig, RunConfig,
+    Scenario, ScenarioDefaults, ScenarioMetadata, Step,
+};
+use ptybox::model::{
+    parse_byte_size, FileFixture, FixtureFill, FixtureKind, OutputOverflow, StepId, TerminalSize,
+    TreeFixture, DEFAULT_OUTPUT_BUFFER_BYTES,
 };
-use ptybox::model::{StepId, TerminalSize};
 use ptybox::runner::ErrorCode;
 
 fn temp_path(name: &str) -> PathBuf {
@@ -36,6 +42,7 @@ fn build_scenario() -> Scenario {
     let policy = Policy {
         policy_version: POLICY_VERSION,
         sandbox: SandboxMode::Disabled { ack: true },
+        sandbox_fallback: SandboxFallback::Deny,
         network: NetworkPolicy::Disabled,
         network_enforcement: NetworkEnforcementAck {
             unenforced_ack: true,
@@ -46,6 +53,7 @@ fn build_scenario() -> Scenario {
             working_dir: None,
             write_ack: false,
             strict_write: false,
+            write_quotas: Default::default(),
         },
         exec: ExecPolicy {
             allowed_executables: vec!["/bin/echo".to_string()],
@@ -59,6 +67,15 @@ fn build_scenario() -> Scenario {
         budgets: Budgets::default(),
         artifacts: ArtifactsPolicy::default(),
         replay: ReplayPolicy::default(),
+        input: InputPolicy::default(),
+        determinism: DeterminismPolicy::default(),
+        process: ProcessPolicy::default(),
+        sockets: Vec::new(),
+        remote: Default::default(),
+        terminal: Default::default(),
+        log_forward: Default::default(),
+        substitution_allowlist: Vec::new(),
+        grants: Vec::new(),
     };
 
     Scenario {
@@ -66,6 +83,10 @@ fn build_scenario() -> Scenario {
         metadata: ScenarioMetadata {
             name: "test-scenario".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: RunConfig {
             command: "/bin/echo".to_string(),
@@ -73,6 +94,12 @@ fn build_scenario() -> Scenario {
             cwd: None,
             initial_size: TerminalSize::default(),
             policy: PolicyRef::Inline(Box::new(policy)),
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![Step {
             id: StepId::new(),
@@ -84,7 +111,19 @@ fn build_scenario() -> Scenario {
             assert: vec![],
             timeout_ms: 100,
             retries: 0,
+            skip_default_assertions: false,
+            sample_interval_ms: None,
+            expected_screen: None,
+            capture: None,
+            env_overlay: BTreeMap::new(),
+            metadata: BTreeMap::new(),
+            vars: Vec::new(),
         }],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     }
 }
 
@@ -117,6 +156,198 @@ fn load_scenario_from_json_and_yaml() {
     let _ = fs::remove_file(yaml_path);
 }
 
+#[test]
+fn load_scenario_applies_default_assertions() {
+    let mut scenario = build_scenario();
+    let mut opted_out = scenario.steps[0].clone();
+    opted_out.id = StepId::new();
+    opted_out.skip_default_assertions = true;
+    scenario.steps.push(opted_out);
+    scenario.defaults.assert_each_step = vec![Assertion::screen_not_contains("panicked", None)];
+
+    let yaml_path = std::env::temp_dir().join("ptybox-test-scenario-defaults.yaml");
+    fs::write(&yaml_path, serde_yml::to_string(&scenario).unwrap()).unwrap();
+    let mut loaded = ptybox::scenario::load_scenario_file(yaml_path.to_str().unwrap()).unwrap();
+    let _ = fs::remove_file(yaml_path);
+
+    assert!(loaded.defaults.assert_each_step.is_empty());
+    assert_eq!(loaded.steps[0].assert.len(), 1);
+    assert!(loaded.steps[0].assert[0].inherited);
+    assert!(loaded.steps[1].assert.is_empty());
+
+    loaded.apply_defaults();
+    assert_eq!(loaded.steps[0].assert.len(), 1, "expansion is idempotent");
+}
+
+#[test]
+fn load_scenario_compiles_inline_expected_screen() {
+    let scenario = build_scenario();
+    let mut value = serde_json::to_value(&scenario).unwrap();
+    value["steps"][0]["expected_screen"] = serde_json::json!({
+        "lines": ["Welcome, *", "> ?"],
+        "start_line": 2,
+    });
+    let path = temp_path("scenario-expected-screen");
+    fs::write(&path, serde_json::to_string(&value).unwrap()).unwrap();
+    let mut loaded = ptybox::scenario::load_scenario_file(path.to_str().unwrap()).unwrap();
+    let _ = fs::remove_file(path);
+
+    let step = &loaded.steps[0];
+    assert!(step.expected_screen.is_none());
+    assert_eq!(step.assert.len(), 1);
+    assert_eq!(step.assert[0].assertion_type, "expected_screen");
+    assert_eq!(
+        step.assert[0].payload,
+        serde_json::json!({"lines": ["Welcome, *", "> ?"], "start_line": 2})
+    );
+
+    loaded.apply_defaults();
+    assert_eq!(loaded.steps[0].assert.len(), 1, "compilation is idempotent");
+}
+
+#[test]
+fn load_scenario_folds_deprecated_post_into_post_run() {
+    let scenario = build_scenario();
+    let mut value = serde_json::to_value(&scenario).unwrap();
+    value["post_run"] = serde_json::json!([{"type": "exit_code", "payload": {"code": 0}}]);
+    value["post"] = serde_json::json!([{"type": "process_exited", "payload": {}}]);
+    let path = temp_path("scenario-deprecated-post");
+    fs::write(&path, serde_json::to_string(&value).unwrap()).unwrap();
+    let mut loaded = ptybox::scenario::load_scenario_file(path.to_str().unwrap()).unwrap();
+    let _ = fs::remove_file(path);
+
+    assert!(loaded.post.is_empty());
+    let types: Vec<&str> = loaded
+        .post_run
+        .iter()
+        .map(|assertion| assertion.assertion_type.as_str())
+        .collect();
+    assert_eq!(types, vec!["exit_code", "process_exited"]);
+
+    loaded.apply_defaults();
+    assert_eq!(loaded.post_run.len(), 2, "folding is idempotent");
+}
+
+#[test]
+fn read_and_write_scenario_file_keep_defaults_unexpanded() {
+    let mut scenario = build_scenario();
+    scenario.defaults.assert_each_step = vec![Assertion::screen_not_contains("panicked", None)];
+    let source = temp_path("scenario-rewrite-source");
+    let dest = std::env::temp_dir().join("ptybox-test-scenario-rewrite.yaml");
+    fs::write(&source, serde_json::to_string(&scenario).unwrap()).unwrap();
+
+    let read = ptybox::scenario::read_scenario_file(source.to_str().unwrap()).unwrap();
+    assert_eq!(read.defaults.assert_each_step.len(), 1);
+    assert!(read.steps[0].assert.is_empty());
+
+    ptybox::scenario::write_scenario_file(dest.to_str().unwrap(), &read).unwrap();
+    let written = fs::read_to_string(&dest).unwrap();
+    let reread = ptybox::scenario::read_scenario_file(dest.to_str().unwrap()).unwrap();
+    let _ = fs::remove_file(source);
+    let _ = fs::remove_file(dest);
+
+    assert!(
+        written.contains("assert_each_step"),
+        "written as YAML: {written}"
+    );
+    assert_eq!(reread.defaults.assert_each_step.len(), 1);
+    assert_eq!(reread.steps.len(), 1);
+}
+
+#[test]
+fn run_output_buffer_defaults_and_round_trips() {
+    let scenario = build_scenario();
+    let mut value = serde_json::to_value(&scenario).unwrap();
+    assert!(value["run"].get("output_buffer").is_none());
+
+    value["run"]["output_buffer"] = serde_json::json!({ "on_full": "drop" });
+    let parsed: Scenario = serde_json::from_value(value).unwrap();
+    assert_eq!(
+        parsed.run.output_buffer,
+        OutputBufferConfig {
+            capacity_bytes: DEFAULT_OUTPUT_BUFFER_BYTES,
+            on_full: OutputOverflow::Drop,
+        }
+    );
+    let round_trip = serde_json::to_value(&parsed).unwrap();
+    assert_eq!(round_trip["run"]["output_buffer"]["on_full"], "drop");
+}
+
+#[test]
+fn metadata_provenance_fields_are_optional_and_round_trip() {
+    let scenario = build_scenario();
+    let mut value = serde_json::to_value(&scenario).unwrap();
+    assert!(value["metadata"].get("author").is_none());
+
+    value["metadata"]["author"] = serde_json::json!("qa@example.com");
+    value["metadata"]["source"] = serde_json::json!("https://example.com/suite.git");
+    value["metadata"]["revision"] = serde_json::json!("v1.2.0");
+    let parsed: Scenario = serde_json::from_value(value).unwrap();
+    assert_eq!(parsed.metadata.author.as_deref(), Some("qa@example.com"));
+    assert_eq!(
+        parsed.metadata.source.as_deref(),
+        Some("https://example.com/suite.git")
+    );
+    assert_eq!(parsed.metadata.revision.as_deref(), Some("v1.2.0"));
+    let round_trip = serde_json::to_value(&parsed).unwrap();
+    assert_eq!(round_trip["metadata"]["revision"], "v1.2.0");
+}
+
+#[test]
+fn insert_steps_splices_before_original_indices() {
+    let named = |name: &str| {
+        let mut step = build_scenario().steps[0].clone();
+        step.id = StepId::new();
+        step.name = name.to_string();
+        step
+    };
+    let mut scenario = build_scenario();
+    scenario.steps = vec![named("a"), named("b")];
+
+    scenario.insert_steps(vec![
+        (1, named("before-b-1")),
+        (9, named("end")),
+        (0, named("first")),
+        (1, named("before-b-2")),
+    ]);
+
+    let names: Vec<&str> = scenario.steps.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(
+        names,
+        ["first", "a", "before-b-1", "before-b-2", "b", "end"]
+    );
+}
+
+#[test]
+fn step_content_key_ignores_id_and_tracks_content() {
+    let step = build_scenario().steps[0].clone();
+    let key = step.content_key();
+    assert_eq!(key.len(), 16);
+    assert!(key.bytes().all(|byte| byte.is_ascii_hexdigit()));
+
+    // Same content under a fresh id, timeout, and metadata: same key.
+    let mut copy = step.clone();
+    copy.id = StepId::new();
+    copy.timeout_ms = 5000;
+    copy.metadata
+        .insert("owner".to_string(), serde_json::json!("qa"));
+    assert_eq!(copy.content_key(), key);
+
+    let mut renamed = step.clone();
+    renamed.name = "type again".to_string();
+    let mut retyped = step.clone();
+    retyped.action.payload = serde_json::json!({"text": "bye"});
+    let mut asserted = step;
+    asserted.assert.push(Assertion {
+        assertion_type: "screen_contains".to_string(),
+        payload: serde_json::json!({"text": "hello"}),
+        inherited: false,
+    });
+    for changed in [renamed, retyped, asserted] {
+        assert_ne!(changed.content_key(), key, "{}", changed.name);
+    }
+}
+
 #[test]
 fn load_scenario_file_not_found() {
     let result = ptybox::scenario::load_scenario_file("/nonexistent/path/scenario.json");
@@ -174,6 +405,7 @@ fn load_policy_ref_inline() {
     let policy = Policy {
         policy_version: POLICY_VERSION,
         sandbox: SandboxMode::Disabled { ack: true },
+        sandbox_fallback: SandboxFallback::Deny,
         network: NetworkPolicy::Disabled,
         network_enforcement: NetworkEnforcementAck {
             unenforced_ack: true,
@@ -184,6 +416,7 @@ fn load_policy_ref_inline() {
             working_dir: None,
             write_ack: false,
             strict_write: false,
+            write_quotas: Default::default(),
         },
         exec: ExecPolicy {
             allowed_executables: vec!["/bin/echo".to_string()],
@@ -193,6 +426,15 @@ fn load_policy_ref_inline() {
         budgets: Budgets::default(),
         artifacts: ArtifactsPolicy::default(),
         replay: ReplayPolicy::default(),
+        input: InputPolicy::default(),
+        determinism: DeterminismPolicy::default(),
+        process: ProcessPolicy::default(),
+        sockets: Vec::new(),
+        remote: Default::default(),
+        terminal: Default::default(),
+        log_forward: Default::default(),
+        substitution_allowlist: Vec::new(),
+        grants: Vec::new(),
     };
 
     let policy_ref = PolicyRef::Inline(Box::new(policy.clone()));
@@ -211,6 +453,7 @@ fn load_policy_ref_file() {
     let policy = Policy {
         policy_version: POLICY_VERSION,
         sandbox: SandboxMode::Disabled { ack: true },
+        sandbox_fallback: SandboxFallback::Deny,
         network: NetworkPolicy::Disabled,
         network_enforcement: NetworkEnforcementAck {
             unenforced_ack: true,
@@ -221,6 +464,7 @@ fn load_policy_ref_file() {
             working_dir: None,
             write_ack: false,
             strict_write: false,
+            write_quotas: Default::default(),
         },
         exec: ExecPolicy {
             allowed_executables: vec!["/usr/bin/cat".to_string()],
@@ -230,6 +474,15 @@ fn load_policy_ref_file() {
         budgets: Budgets::default(),
         artifacts: ArtifactsPolicy::default(),
         replay: ReplayPolicy::default(),
+        input: InputPolicy::default(),
+        determinism: DeterminismPolicy::default(),
+        process: ProcessPolicy::default(),
+        sockets: Vec::new(),
+        remote: Default::default(),
+        terminal: Default::default(),
+        log_forward: Default::default(),
+        substitution_allowlist: Vec::new(),
+        grants: Vec::new(),
     };
 
     let path = temp_path("policy-ref-file");
@@ -285,6 +538,7 @@ fn load_policy_file_success() {
     let policy = Policy {
         policy_version: POLICY_VERSION,
         sandbox: SandboxMode::Disabled { ack: true },
+        sandbox_fallback: SandboxFallback::Deny,
         network: NetworkPolicy::Disabled,
         network_enforcement: NetworkEnforcementAck {
             unenforced_ack: true,
@@ -295,6 +549,7 @@ fn load_policy_file_success() {
             working_dir: None,
             write_ack: false,
             strict_write: false,
+            write_quotas: Default::default(),
         },
         exec: ExecPolicy {
             allowed_executables: vec!["/bin/ls".to_string()],
@@ -304,6 +559,15 @@ fn load_policy_file_success() {
         budgets: Budgets::default(),
         artifacts: ArtifactsPolicy::default(),
         replay: ReplayPolicy::default(),
+        input: InputPolicy::default(),
+        determinism: DeterminismPolicy::default(),
+        process: ProcessPolicy::default(),
+        sockets: Vec::new(),
+        remote: Default::default(),
+        terminal: Default::default(),
+        log_forward: Default::default(),
+        substitution_allowlist: Vec::new(),
+        grants: Vec::new(),
     };
 
     let path = temp_path("policy-file-test");
@@ -326,6 +590,120 @@ fn load_policy_file_not_found() {
     assert_eq!(err.code, ErrorCode::Io);
 }
 
+fn write_policy_value(name: &str, value: &serde_json::Value) -> PathBuf {
+    let path = temp_path(name);
+    fs::write(&path, serde_json::to_vec(value).unwrap()).unwrap();
+    path
+}
+
+fn policy_with_paths(allowlist: &[&str], read_path: &str) -> serde_json::Value {
+    let mut value = serde_json::to_value(Policy::default()).unwrap();
+    value["substitution_allowlist"] = serde_json::json!(allowlist);
+    value["fs"]["allowed_read"] = serde_json::json!([read_path]);
+    value
+}
+
+#[test]
+fn load_policy_file_substitutes_allowlisted_placeholders() {
+    let mut value = policy_with_paths(
+        &["cwd", "env:CARGO_MANIFEST_DIR"],
+        "${env:CARGO_MANIFEST_DIR}/tests",
+    );
+    value["fs"]["working_dir"] = serde_json::json!("${cwd}");
+    value["exec"]["allowed_executables"] = serde_json::json!(["${cwd}/bin/tool"]);
+    let path = write_policy_value("policy-substitution", &value);
+
+    let loaded = ptybox::scenario::load_policy_file(&path).unwrap();
+    let cwd = std::env::current_dir().unwrap().display().to_string();
+    assert_eq!(
+        loaded.fs.allowed_read,
+        vec![format!("{}/tests", env!("CARGO_MANIFEST_DIR"))]
+    );
+    assert_eq!(loaded.fs.working_dir.as_deref(), Some(cwd.as_str()));
+    assert_eq!(
+        loaded.exec.allowed_executables,
+        vec![format!("{cwd}/bin/tool")]
+    );
+
+    let _ = fs::remove_file(path);
+}
+
+#[test]
+fn load_policy_file_substitutes_placeholders_in_grants() {
+    let mut value = policy_with_paths(&["cwd"], "/tmp");
+    value["grants"] = serde_json::json!([{
+        "scenario": "nightly-*",
+        "allowed_read": ["${cwd}/fixtures"],
+        "allowed_executables": ["${cwd}/bin/tool"],
+        "budgets": { "max_runtime_ms": 120000 }
+    }]);
+    let path = write_policy_value("policy-substitution-grants", &value);
+
+    let loaded = ptybox::scenario::load_policy_file(&path).unwrap();
+    let cwd = std::env::current_dir().unwrap().display().to_string();
+    let grant = &loaded.grants[0];
+    assert_eq!(grant.scenario, "nightly-*");
+    assert_eq!(grant.allowed_read, vec![format!("{cwd}/fixtures")]);
+    assert_eq!(grant.allowed_executables, vec![format!("{cwd}/bin/tool")]);
+    assert_eq!(grant.budgets.max_runtime_ms, Some(120_000));
+
+    value["grants"][0]["allowed_write"] = serde_json::json!(["${env:HOME}"]);
+    let path = write_policy_value("policy-substitution-grants", &value);
+    let err = ptybox::scenario::load_policy_file(&path).unwrap_err();
+    assert_eq!(err.context.unwrap()["field"], "grants[0].allowed_write");
+
+    let _ = fs::remove_file(path);
+}
+
+#[test]
+fn load_policy_file_rejects_unlisted_placeholder() {
+    let value = policy_with_paths(&["cwd"], "${env:CARGO_MANIFEST_DIR}");
+    let path = write_policy_value("policy-substitution-unlisted", &value);
+
+    let err = ptybox::scenario::load_policy_file(&path).unwrap_err();
+    assert_eq!(err.code, ErrorCode::PolicyDenied);
+    let context = err.context.unwrap();
+    assert_eq!(context["field"], "fs.allowed_read");
+    assert_eq!(context["placeholder"], "${env:CARGO_MANIFEST_DIR}");
+
+    let _ = fs::remove_file(path);
+}
+
+#[test]
+fn load_policy_file_rejects_unset_or_malformed_substitutions() {
+    let cases = [
+        policy_with_paths(
+            &["env:PTYBOX_TEST_UNSET_POLICY_VAR"],
+            "${env:PTYBOX_TEST_UNSET_POLICY_VAR}",
+        ),
+        policy_with_paths(&["cwd"], "${cwd"),
+        policy_with_paths(&["env:1BAD"], "/tmp"),
+        policy_with_paths(&["home"], "/tmp"),
+    ];
+    for (index, value) in cases.iter().enumerate() {
+        let path = write_policy_value(&format!("policy-substitution-bad-{index}"), value);
+        let err = ptybox::scenario::load_policy_file(&path).unwrap_err();
+        assert_eq!(err.code, ErrorCode::PolicyDenied, "case {index}: {err}");
+        let _ = fs::remove_file(path);
+    }
+}
+
+#[test]
+fn load_policy_file_leaves_non_path_fields_literal() {
+    let mut value = policy_with_paths(&["cwd"], "/tmp");
+    value["env"]["set"] = serde_json::json!({"GREETING": "${cwd}"});
+    let path = write_policy_value("policy-substitution-literal", &value);
+
+    let loaded = ptybox::scenario::load_policy_file(&path).unwrap();
+    assert_eq!(
+        loaded.env.set.get("GREETING").map(String::as_str),
+        Some("${cwd}")
+    );
+    assert_eq!(loaded.fs.allowed_read, vec!["/tmp".to_string()]);
+
+    let _ = fs::remove_file(path);
+}
+
 #[test]
 fn to_json_value_serialization() {
     #[derive(serde::Serialize)]
@@ -403,6 +781,7 @@ fn scenario_with_file_policy_ref() {
     let policy = Policy {
         policy_version: POLICY_VERSION,
         sandbox: SandboxMode::Disabled { ack: true },
+        sandbox_fallback: SandboxFallback::Deny,
         network: NetworkPolicy::Disabled,
         network_enforcement: NetworkEnforcementAck {
             unenforced_ack: true,
@@ -413,6 +792,7 @@ fn scenario_with_file_policy_ref() {
             working_dir: None,
             write_ack: false,
             strict_write: false,
+            write_quotas: Default::default(),
         },
         exec: ExecPolicy {
             allowed_executables: vec!["/bin/cat".to_string()],
@@ -422,6 +802,15 @@ fn scenario_with_file_policy_ref() {
         budgets: Budgets::default(),
         artifacts: ArtifactsPolicy::default(),
         replay: ReplayPolicy::default(),
+        input: InputPolicy::default(),
+        determinism: DeterminismPolicy::default(),
+        process: ProcessPolicy::default(),
+        sockets: Vec::new(),
+        remote: Default::default(),
+        terminal: Default::default(),
+        log_forward: Default::default(),
+        substitution_allowlist: Vec::new(),
+        grants: Vec::new(),
     };
 
     let policy_path = temp_path("external-policy");
@@ -434,6 +823,10 @@ fn scenario_with_file_policy_ref() {
         metadata: ScenarioMetadata {
             name: "file-ref-test".to_string(),
             description: None,
+            tags: Vec::new(),
+            author: None,
+            source: None,
+            revision: None,
         },
         run: RunConfig {
             command: "/bin/cat".to_string(),
@@ -443,8 +836,19 @@ fn scenario_with_file_policy_ref() {
             policy: PolicyRef::File {
                 path: policy_path.to_str().unwrap().to_string(),
             },
+            separate_stderr: false,
+            output_buffer: OutputBufferConfig::default(),
+            resize: ResizeConfig::default(),
+            remote: None,
+            validate_args: false,
+            capture_attributes: false,
         },
         steps: vec![],
+        defaults: ScenarioDefaults::default(),
+        watchers: Vec::new(),
+        post_run: Vec::new(),
+        post: Vec::new(),
+        fixtures: Vec::new(),
     };
 
     let scenario_path = temp_path("scenario-with-file-ref");
@@ -465,3 +869,150 @@ fn scenario_with_file_policy_ref() {
     let _ = fs::remove_file(policy_path);
     let _ = fs::remove_file(scenario_path);
 }
+
+#[test]
+fn load_scenario_file_with_source_locates_json_steps() {
+    let mut scenario = build_scenario();
+    scenario.metadata.description = Some("not \"steps\": [ {".to_string());
+    let mut second = scenario.steps[0].clone();
+    second.id = StepId::new();
+    second.name = "second".to_string();
+    scenario.steps.push(second);
+
+    let path = temp_path("source-lines");
+    let json = serde_json::to_string_pretty(&scenario).unwrap();
+    fs::write(&path, &json).unwrap();
+
+    let (loaded, source) =
+        ptybox::scenario::load_scenario_file_with_source(path.to_str().unwrap()).unwrap();
+    assert_eq!(loaded.steps.len(), 2);
+    assert_eq!(source.path, path.to_str().unwrap());
+    assert_eq!(source.step_lines.len(), 2);
+    let lines: Vec<&str> = json.lines().collect();
+    for line in &source.step_lines {
+        assert_eq!(lines[*line as usize - 1].trim(), "{");
+        assert!(lines[*line as usize].contains("\"id\""));
+    }
+    assert!(source.step_lines[0] < source.step_lines[1]);
+
+    fs::write(&path, serde_json::to_string(&scenario).unwrap()).unwrap();
+    let (_, source) =
+        ptybox::scenario::load_scenario_file_with_source(path.to_str().unwrap()).unwrap();
+    assert_eq!(source.step_lines, vec![1, 1]);
+
+    let _ = fs::remove_file(path);
+}
+
+#[test]
+fn load_scenario_file_with_source_locates_yaml_steps() {
+    let scenario = build_scenario();
+    let mut yaml = serde_yml::to_string(&ScenarioWithoutSteps::from(&scenario)).unwrap();
+    let step_id = scenario.steps[0].id;
+    let steps = format!(
+        "steps:   # two steps\n\
+         \n  - id: {step_id}\n    name: first\n    action:\n      type: text\n      payload:\n        text: hi\n    assert:\n      - type: screen_contains\n        payload:\n          text: hi\n    timeout_ms: 100\n    retries: 0\n\
+         # between steps\n\
+         \n  - id: {}\n    name: second\n    action: {{type: text, payload: {{text: bye}}}}\n    timeout_ms: 100\n    retries: 0\n",
+        StepId::new()
+    );
+    yaml.push_str(&steps);
+    let steps_at = yaml
+        .lines()
+        .position(|line| line.starts_with("steps:"))
+        .unwrap() as u32
+        + 1;
+
+    let path = std::env::temp_dir().join("ptybox-test-source-lines.yaml");
+    fs::write(&path, &yaml).unwrap();
+    let (loaded, source) =
+        ptybox::scenario::load_scenario_file_with_source(path.to_str().unwrap()).unwrap();
+    assert_eq!(loaded.steps.len(), 2);
+    assert_eq!(source.step_lines, vec![steps_at + 2, steps_at + 16]);
+    assert_eq!(source.step_line(1), Some(steps_at + 16));
+    assert_eq!(source.step_line(2), None);
+
+    let _ = fs::remove_file(path);
+}
+
+/// A scenario serialized without its `steps`, so a test can append them by hand.
+#[derive(serde::Serialize)]
+struct ScenarioWithoutSteps<'a> {
+    scenario_version: u32,
+    metadata: &'a ScenarioMetadata,
+    run: &'a RunConfig,
+}
+
+impl<'a> From<&'a Scenario> for ScenarioWithoutSteps<'a> {
+    fn from(scenario: &'a Scenario) -> Self {
+        Self {
+            scenario_version: scenario.scenario_version,
+            metadata: &scenario.metadata,
+            run: &scenario.run,
+        }
+    }
+}
+
+#[test]
+fn scenario_fixtures_accept_sizes_with_units_and_fills() {
+    let mut value = serde_json::to_value(build_scenario()).unwrap();
+    value["fixtures"] = serde_json::json!([
+        {"path": "/tmp/big.bin", "file": {"size": "10MB", "fill": "random(42)"}},
+        {"path": "/tmp/small.bin", "file": {"size": 512}},
+        {"path": "/tmp/tree", "tree": {"depth": 2, "fanout": 3, "file_size": "4KiB"}}
+    ]);
+    let path = temp_path("fixtures");
+    fs::write(&path, serde_json::to_string(&value).unwrap()).unwrap();
+    let scenario = ptybox::scenario::load_scenario_file(path.to_str().unwrap()).unwrap();
+
+    let kinds: Vec<&FixtureKind> = scenario.fixtures.iter().map(|f| &f.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            &FixtureKind::File(FileFixture {
+                size: 10_000_000,
+                fill: FixtureFill::Random(42),
+            }),
+            &FixtureKind::File(FileFixture {
+                size: 512,
+                fill: FixtureFill::Zeros,
+            }),
+            &FixtureKind::Tree(TreeFixture {
+                depth: 2,
+                fanout: 3,
+                file_size: 4096,
+                fill: FixtureFill::Zeros,
+            }),
+        ]
+    );
+    let round_trip = serde_json::to_value(&scenario).unwrap();
+    assert_eq!(
+        round_trip["fixtures"][0],
+        serde_json::json!({"path": "/tmp/big.bin", "file": {"size": 10_000_000, "fill": "random(42)"}})
+    );
+
+    let _ = fs::remove_file(path);
+}
+
+#[test]
+fn scenario_fixtures_reject_unknown_units_and_fills() {
+    assert_eq!(parse_byte_size("1GiB"), Ok(1 << 30));
+    assert_eq!(parse_byte_size(" 3 KB "), Ok(3000));
+    assert!(parse_byte_size("10TB").is_err());
+    assert!(parse_byte_size("MB").is_err());
+    assert!(parse_byte_size("99999999999GB").is_err());
+
+    for fixture in [
+        serde_json::json!({"path": "/tmp/a", "file": {"size": "10 parsecs"}}),
+        serde_json::json!({"path": "/tmp/a", "file": {"size": 1, "fill": "ones"}}),
+        serde_json::json!({"path": "/tmp/a", "file": {"size": 1, "fill": "random(-1)"}}),
+    ] {
+        let mut value = serde_json::to_value(build_scenario()).unwrap();
+        value["fixtures"] = serde_json::json!([fixture]);
+        let path = temp_path("bad-fixtures");
+        fs::write(&path, serde_json::to_string(&value).unwrap()).unwrap();
+        let err = ptybox::scenario::load_scenario_file(path.to_str().unwrap())
+            .expect_err("fixture should be rejected");
+        assert_eq!(err.message, "failed to parse json", "{fixture}");
+        let _ = fs::remove_file(path);
+    }
+}
@@ -0,0 +1,210 @@
+// Test module - relaxed lint rules
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(missing_docs)]
+
+//! Variables captured from step output with `vars` and used as `${var:NAME}`.
+
+use ptybox::artifacts::ArtifactsWriterConfig;
+use ptybox::model::policy::{ArtifactsEncryption, PolicyBuilder};
+use ptybox::model::scenario::PolicyRef;
+use ptybox::model::{RunStatus, Scenario, StepStatus};
+use ptybox::run::{run_scenario, run_scenario_with_options};
+use ptybox::runner::{ErrorCode, RunnerOptions};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+fn step(id: u8, action: Value) -> Value {
+    json!({
+        "id": format!("00000000-0000-0000-0000-{id:012}"),
+        "name": format!("step {id}"),
+        "action": action,
+        "assert": [],
+        "timeout_ms": 2000,
+        "retries": 0,
+    })
+}
+
+/// `/bin/cat` echoing `text`, which step 1 captures as `rows` (`as`),
+/// followed by `steps`.
+fn cat_scenario(text: &str, var_type: &str, steps: Vec<Value>) -> Scenario {
+    let policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_executables(vec!["/bin/cat".to_string()])
+        .build();
+    let mut typed = step(
+        1,
+        json!({ "type": "text", "payload": { "text": format!("{text}\n") } }),
+    );
+    typed["assert"] = json!([{ "type": "screen_contains", "payload": { "text": text } }]);
+    typed["vars"] =
+        json!([{ "name": "rows", "transcript_matches": "rows=(\\w+)", "as": var_type }]);
+    let mut all = vec![typed];
+    all.extend(steps);
+    all.push(step(
+        9,
+        json!({ "type": "key", "payload": { "key": "Ctrl+D" } }),
+    ));
+    serde_json::from_value(json!({
+        "scenario_version": 1,
+        "metadata": { "name": "vars" },
+        "run": {
+            "command": "/bin/cat",
+            "args": [],
+            "initial_size": { "rows": 24, "cols": 80 },
+            "policy": policy,
+        },
+        "steps": all,
+    }))
+    .unwrap()
+}
+
+fn resize_step() -> Value {
+    step(
+        2,
+        json!({ "type": "resize", "payload": { "rows": "${var:rows}", "cols": 80 } }),
+    )
+}
+
+#[test]
+fn captured_int_vars_substitute_as_numbers() {
+    let mut echo = step(
+        3,
+        json!({ "type": "text", "payload": { "text": "size ${var:rows}x80\n" } }),
+    );
+    echo["assert"] = json!([{ "type": "screen_contains", "payload": { "text": "size 30x80" } }]);
+    let run = run_scenario(cat_scenario("rows=30", "int", vec![resize_step(), echo])).unwrap();
+
+    assert_eq!(run.status, RunStatus::Passed, "{:?}", run.error);
+    let steps = run.steps.unwrap();
+    assert_eq!(steps[0].vars["rows"], "30");
+    assert_eq!(steps[1].resolved_vars["rows"], json!(30));
+    assert_eq!(steps[1].action.payload["rows"], json!(30));
+    assert_eq!(run.final_observation.unwrap().screen.rows, 30);
+    assert_eq!(steps[2].action.payload["text"], "size 30x80\n");
+    // The step key follows the scenario as written, not the captured value.
+    let scenario = run.scenario.unwrap();
+    assert_eq!(steps[1].step_key, Some(scenario.steps[1].content_key()));
+}
+
+#[test]
+fn a_value_of_the_wrong_type_errors_the_step_using_it() {
+    let run = run_scenario(cat_scenario("rows=abc", "int", vec![resize_step()])).unwrap();
+
+    assert_eq!(run.status, RunStatus::Failed);
+    let steps = run.steps.unwrap();
+    assert_eq!(steps[0].status, StepStatus::Passed);
+    assert_eq!(steps[1].status, StepStatus::Errored);
+    let error = steps[1].error.as_ref().unwrap();
+    assert_eq!(error.code, "E_PROTOCOL");
+    assert_eq!(
+        error.message,
+        "step 'step 2' uses variable 'rows', whose value 'abc' is not a valid int"
+    );
+    let context = error.context.as_ref().unwrap();
+    assert_eq!(context["step_name"], "step 2");
+    assert_eq!(context["details"]["variable"], "rows");
+    assert_eq!(context["details"]["as"], "int");
+    assert_eq!(steps[2].status, StepStatus::Skipped);
+}
+
+#[test]
+fn unknown_and_unmatched_vars_fail() {
+    let mut unknown = resize_step();
+    unknown["action"]["payload"]["cols"] = json!("${var:cols}");
+    let run = run_scenario(cat_scenario("rows=30", "int", vec![unknown])).unwrap();
+    let steps = run.steps.unwrap();
+    let error = steps[1].error.as_ref().unwrap();
+    assert_eq!(error.code, "E_PROTOCOL");
+    assert!(error
+        .message
+        .contains("'cols', which has not been captured"));
+    assert_eq!(
+        error.context.as_ref().unwrap()["details"]["captured"],
+        json!(["rows"])
+    );
+
+    let run = run_scenario(cat_scenario("cols=30", "string", Vec::new())).unwrap();
+    assert_eq!(run.status, RunStatus::Failed);
+    let steps = run.steps.unwrap();
+    assert_eq!(steps[0].status, StepStatus::Failed);
+    assert_eq!(steps[0].error.as_ref().unwrap().code, "E_ASSERTION_FAILED");
+    assert!(steps[0].vars.is_empty());
+}
+
+#[test]
+fn var_names_and_patterns_are_validated() {
+    let mut scenario = cat_scenario("rows=30", "bool", Vec::new());
+    scenario.steps[0].vars[0].name = "row-count".to_string();
+    let err = run_scenario(scenario).unwrap_err();
+    assert_eq!(err.code, ErrorCode::Protocol);
+    assert_eq!(err.context.unwrap()["details"]["variable"], "row-count");
+
+    let mut scenario = cat_scenario("rows=30", "bool", Vec::new());
+    scenario.steps[0].vars[0].transcript_matches = "rows=(".to_string();
+    let err = run_scenario(scenario).unwrap_err();
+    assert_eq!(err.code, ErrorCode::Protocol);
+}
+
+/// A fake `age` that writes its input rot13'd behind the age header.
+fn write_fake_age(dir: &Path) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+    let path = dir.join("fake-age");
+    std::fs::write(
+        &path,
+        r#"#!/bin/sh
+out=
+while [ $# -gt 0 ]; do
+  [ "$1" = --output ] && { shift; out=$1; }
+  shift
+done
+{ echo "age-encryption.org/v1"; tr 'A-Za-z' 'N-ZA-Mn-za-m'; } > "$out"
+"#,
+    )
+    .unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+#[test]
+fn encrypted_runs_keep_vars_out_of_plaintext_run_json() {
+    let root = std::env::temp_dir().join(format!("ptybox-vars-encrypted-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    let root = root.canonicalize().unwrap();
+    let artifacts_dir = root.join("artifacts");
+
+    let mut scenario = cat_scenario("rows=30", "int", vec![resize_step()]);
+    let mut policy = PolicyBuilder::new()
+        .sandbox_disabled()
+        .allowed_executables(vec!["/bin/cat".to_string()])
+        .allowed_write(vec![root.display().to_string()])
+        .build();
+    policy.artifacts.encryption = Some(ArtifactsEncryption {
+        recipients: vec!["age1qqqqqqqqqqqqqqqqqqqq".to_string()],
+        age_binary: write_fake_age(&root).display().to_string(),
+    });
+    scenario.run.policy = PolicyRef::Inline(Box::new(policy));
+    let options = RunnerOptions {
+        artifacts: Some(ArtifactsWriterConfig {
+            dir: artifacts_dir.clone(),
+            overwrite: false,
+        }),
+        ..RunnerOptions::default()
+    };
+    let run = run_scenario_with_options(scenario, options).unwrap();
+    assert_eq!(run.status, RunStatus::Passed, "{:?}", run.error);
+    assert_eq!(run.steps.unwrap()[1].resolved_vars["rows"], json!(30));
+
+    let written: Value =
+        serde_json::from_slice(&std::fs::read(artifacts_dir.join("run.json")).unwrap()).unwrap();
+    let steps = written["steps"].as_array().unwrap();
+    assert!(steps.iter().all(|step| step.get("vars").is_none()));
+    assert!(steps.iter().all(|step| step.get("resolved_vars").is_none()));
+    // The action is the one written in the scenario, not the substituted one.
+    assert_eq!(steps[1]["action"]["payload"]["rows"], "${var:rows}");
+    let journal = std::fs::read_to_string(artifacts_dir.join("journal.jsonl")).unwrap();
+    assert!(!journal.contains("resolved_vars"), "{journal}");
+    let _ = std::fs::remove_dir_all(&root);
+}
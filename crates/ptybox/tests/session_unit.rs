@@ -16,7 +16,11 @@
 //!
 //! Tests the core PTY session management functionality.
 
-use ptybox::model::{Action, ActionType, RunId, TerminalSize};
+use ptybox::model::policy::TerminalEncoding;
+use ptybox::model::{
+    Action, ActionType, ChildSignal, KillSignal, Observation, OutputBufferConfig, OutputOverflow,
+    PreKillHook, ProcessPolicy, RunId, TerminalSize,
+};
 use ptybox::runner::ErrorCode;
 use ptybox::session::{Session, SessionConfig};
 use std::time::Duration;
@@ -29,6 +33,11 @@ fn default_config(command: &str) -> SessionConfig {
         size: TerminalSize::default(),
         run_id: RunId::new(),
         env: Default::default(),
+        separate_stderr: false,
+        output_buffer: Default::default(),
+        resize: Default::default(),
+        remote: None,
+        encoding: Default::default(),
     }
 }
 
@@ -56,6 +65,11 @@ fn session_spawn_with_args() {
         size: TerminalSize::default(),
         run_id: RunId::new(),
         env: Default::default(),
+        separate_stderr: false,
+        output_buffer: Default::default(),
+        resize: Default::default(),
+        remote: None,
+        encoding: Default::default(),
     };
     let session = Session::spawn(config);
     assert!(
@@ -84,6 +98,11 @@ fn session_spawn_with_cwd() {
         size: TerminalSize::default(),
         run_id: RunId::new(),
         env: Default::default(),
+        separate_stderr: false,
+        output_buffer: Default::default(),
+        resize: Default::default(),
+        remote: None,
+        encoding: Default::default(),
     };
     let mut session = Session::spawn(config).expect("Failed to spawn");
     let observation = session.observe(Duration::from_millis(500)).unwrap();
@@ -134,6 +153,34 @@ fn session_send_key_single_char() {
     );
 }
 
+#[test]
+fn session_send_key_modifier_chords() {
+    let config = default_config("/bin/cat");
+    let mut session = Session::spawn(config).expect("Failed to spawn");
+
+    for key in [
+        "Alt+Enter",
+        "Shift+Tab",
+        "Ctrl+Alt+x",
+        "Ctrl+Up",
+        "Shift+F5",
+    ] {
+        let action = Action {
+            action_type: ActionType::Key,
+            payload: serde_json::json!({ "key": key }),
+        };
+        assert!(session.send(&action).is_ok(), "Failed to send {key}");
+    }
+
+    let action = Action {
+        action_type: ActionType::Key,
+        payload: serde_json::json!({"key": "Shift+Enter"}),
+    };
+    let err = session.send(&action).unwrap_err();
+    assert_eq!(err.code, ErrorCode::Protocol);
+    assert_eq!(err.message, "unsupported key 'Shift+Enter'");
+}
+
 #[test]
 fn session_send_key_missing_payload() {
     let config = default_config("/bin/cat");
@@ -248,6 +295,159 @@ fn session_send_resize_missing_rows() {
     assert_eq!(err.code, ErrorCode::Protocol);
 }
 
+#[test]
+fn session_send_resize_with_pixels_answers_queries() {
+    let mut config = default_config("/bin/sh");
+    config.args = vec![
+        "-c".to_string(),
+        "sleep 0.3; printf '\\033[14t'; cat".to_string(),
+    ];
+    let mut session = Session::spawn(config).expect("Failed to spawn");
+
+    let action = Action {
+        action_type: ActionType::Resize,
+        payload: serde_json::json!({"rows": 24, "cols": 80, "pixel_width": 640, "pixel_height": 480}),
+    };
+    session.send(&action).expect("resize with pixels");
+
+    // The reply is written to the PTY and echoed back by the line discipline.
+    let start = std::time::Instant::now();
+    let mut found = false;
+    while start.elapsed() < Duration::from_secs(5) {
+        let observation = session.observe(Duration::from_millis(100)).unwrap();
+        if observation.screen.lines.join("\n").contains("[4;480;640t") {
+            found = true;
+            break;
+        }
+    }
+    assert!(found, "pixel size query should be answered");
+}
+
+#[test]
+fn session_send_resize_rejects_invalid_pixels() {
+    let config = default_config("/bin/cat");
+    let mut session = Session::spawn(config).expect("Failed to spawn");
+
+    let action = Action {
+        action_type: ActionType::Resize,
+        payload: serde_json::json!({"rows": 24, "cols": 80, "pixel_width": 70000}),
+    };
+    let err = session.send(&action).unwrap_err();
+    assert_eq!(err.code, ErrorCode::Protocol);
+}
+
+#[test]
+fn session_hold_key_repeats_on_schedule() {
+    let mut session = Session::spawn(default_config("/bin/cat")).expect("Failed to spawn");
+
+    let started = std::time::Instant::now();
+    let hold = session.hold_key("x", 200, 25).expect("hold key");
+    assert!(started.elapsed() >= Duration::from_millis(200));
+    assert_eq!(hold.presses, 5);
+    assert_eq!(hold.hex, "78");
+    assert_eq!(hold.offsets_ms.len(), 5);
+    for (index, offset) in hold.offsets_ms.iter().enumerate() {
+        assert!(*offset >= index as u64 * 40, "{:?}", hold.offsets_ms);
+    }
+    assert_eq!(session.take_key_hold(), Some(hold));
+    assert_eq!(session.take_key_hold(), None);
+
+    let observation = session.observe(Duration::from_millis(200)).unwrap();
+    assert!(
+        observation.screen.lines[0].contains("xxxxx"),
+        "{:?}",
+        observation.screen.lines
+    );
+}
+
+#[test]
+fn session_send_hold_key_defaults_repeat_rate() {
+    let mut session = Session::spawn(default_config("/bin/cat")).expect("Failed to spawn");
+    let action = Action {
+        action_type: ActionType::HoldKey,
+        payload: serde_json::json!({"key": "Down", "duration_ms": 100}),
+    };
+    session.send(&action).expect("hold key");
+    let hold = session.take_key_hold().expect("hold recorded");
+    assert_eq!(hold.repeat_hz, 30);
+    assert_eq!(hold.presses, 3);
+    assert_eq!(hold.hex, "1b5b42");
+}
+
+#[test]
+fn session_hold_key_rejects_out_of_range() {
+    let mut session = Session::spawn(default_config("/bin/cat")).expect("Failed to spawn");
+    for (duration_ms, repeat_hz) in [(0, 30), (60_001, 30), (100, 0), (100, 101)] {
+        let err = session
+            .hold_key("a", duration_ms, repeat_hz)
+            .expect_err("out of range");
+        assert_eq!(err.code, ErrorCode::Protocol, "{duration_ms} {repeat_hz}");
+    }
+    let err = session.hold_key("NoSuchKey", 100, 30).expect_err("bad key");
+    assert_eq!(err.code, ErrorCode::Protocol);
+    assert_eq!(session.take_key_hold(), None);
+}
+
+#[test]
+fn session_termios_echo_off_shows_only_program_output() {
+    let mut session = Session::spawn(default_config("/bin/cat")).expect("Failed to spawn");
+    let settings = session
+        .set_termios(Some(false), None, None)
+        .expect("set termios");
+    assert!(!settings.echo);
+    assert!(settings.icanon);
+    assert_eq!(session.take_termios(), Some(settings));
+
+    session.send(&Action::text("hello\n")).expect("send text");
+    let observation = session.observe(Duration::from_millis(300)).unwrap();
+    let transcript = observation.transcript_delta.unwrap_or_default();
+    assert_eq!(transcript.matches("hello").count(), 1, "{transcript:?}");
+}
+
+#[test]
+fn session_termios_non_canonical_delivers_without_newline() {
+    let mut session = Session::spawn(default_config("/bin/cat")).expect("Failed to spawn");
+    session
+        .send(&Action::termios(Some(false), Some(true), None))
+        .expect("canonical termios");
+    session.send(&Action::text("abc")).expect("send text");
+    let observation = session.observe(Duration::from_millis(200)).unwrap();
+    assert!(!observation.screen.lines[0].contains("abc"));
+
+    session
+        .send(&Action::termios(None, Some(false), None))
+        .expect("raw termios");
+    let settings = session.take_termios().expect("termios recorded");
+    assert!(!settings.icanon);
+    assert!(!settings.echo);
+    session.send(&Action::text("xyz")).expect("send text");
+    let observation = session.observe(Duration::from_millis(300)).unwrap();
+    assert!(
+        observation.screen.lines[0].contains("xyz"),
+        "{:?}",
+        observation.screen.lines
+    );
+}
+
+#[test]
+fn session_termios_rejects_invalid_payloads() {
+    let mut session = Session::spawn(default_config("/bin/cat")).expect("Failed to spawn");
+    for payload in [
+        serde_json::json!({}),
+        serde_json::json!({"echo": "off"}),
+        serde_json::json!({"opost": false}),
+        serde_json::json!([true]),
+    ] {
+        let action = Action {
+            action_type: ActionType::Termios,
+            payload: payload.clone(),
+        };
+        let err = session.send(&action).expect_err("invalid payload");
+        assert_eq!(err.code, ErrorCode::Protocol, "{payload}");
+    }
+    assert_eq!(session.take_termios(), None);
+}
+
 #[test]
 fn session_send_wait_is_noop() {
     let config = default_config("/bin/cat");
@@ -274,6 +474,11 @@ fn session_observe_captures_output() {
         size: TerminalSize::default(),
         run_id: RunId::new(),
         env: Default::default(),
+        separate_stderr: false,
+        output_buffer: Default::default(),
+        resize: Default::default(),
+        remote: None,
+        encoding: Default::default(),
     };
     let mut session = Session::spawn(config).expect("Failed to spawn");
 
@@ -371,6 +576,11 @@ fn session_observe_handles_split_utf8_across_observations() {
         size: TerminalSize::default(),
         run_id: RunId::new(),
         env: Default::default(),
+        separate_stderr: false,
+        output_buffer: Default::default(),
+        resize: Default::default(),
+        remote: None,
+        encoding: Default::default(),
     };
     let mut session = Session::spawn(config).expect("Failed to spawn");
 
@@ -425,6 +635,11 @@ fn session_observe_reports_terminal_parse_for_truncated_utf8_at_eof() {
         size: TerminalSize::default(),
         run_id: RunId::new(),
         env: Default::default(),
+        separate_stderr: false,
+        output_buffer: Default::default(),
+        resize: Default::default(),
+        remote: None,
+        encoding: Default::default(),
     };
     let mut session = Session::spawn(config).expect("Failed to spawn");
 
@@ -455,6 +670,233 @@ fn session_observe_reports_terminal_parse_for_truncated_utf8_at_eof() {
     assert_eq!(err.code, ErrorCode::TerminalParse);
 }
 
+fn printf_config(format: &str, encoding: TerminalEncoding) -> SessionConfig {
+    SessionConfig {
+        args: vec!["-c".to_string(), format!("printf '{format}'")],
+        encoding,
+        ..default_config("/bin/sh")
+    }
+}
+
+/// Observe until the child exits and its output is drained.
+fn observe_to_exit(session: &mut Session) -> Result<Vec<Observation>, ptybox::runner::RunnerError> {
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    let mut observations = Vec::new();
+    while std::time::Instant::now() < deadline {
+        let observation = session.observe(Duration::from_millis(100))?;
+        let eof = observation
+            .events
+            .iter()
+            .any(|event| event.event_type == "pty_eof");
+        observations.push(observation);
+        if eof {
+            break;
+        }
+    }
+    Ok(observations)
+}
+
+fn transcript(observations: &[Observation]) -> String {
+    observations
+        .iter()
+        .filter_map(|observation| observation.transcript_delta.as_deref())
+        .collect()
+}
+
+#[test]
+fn session_observe_strict_encoding_rejects_invalid_utf8() {
+    let mut session = Session::spawn(printf_config("ok \\377\\n", TerminalEncoding::Utf8Strict))
+        .expect("Failed to spawn");
+    let err = observe_to_exit(&mut session).expect_err("invalid UTF-8 should fail");
+    assert_eq!(err.code, ErrorCode::TerminalParse);
+}
+
+#[test]
+fn session_observe_lossy_encoding_reports_decode_errors() {
+    let mut session = Session::spawn(printf_config(
+        "ok \\377 done\\n\\360",
+        TerminalEncoding::Utf8Lossy,
+    ))
+    .expect("Failed to spawn");
+    let observations = observe_to_exit(&mut session).expect("lossy decoding does not fail");
+
+    assert_eq!(
+        transcript(&observations).replace('\r', ""),
+        "ok \u{FFFD} done\n\u{FFFD}"
+    );
+    let errors: Vec<_> = observations
+        .iter()
+        .flat_map(|observation| &observation.events)
+        .filter(|event| event.event_type == "decode_error")
+        .collect();
+    let bytes: u64 = errors
+        .iter()
+        .map(|event| event.details.as_ref().unwrap()["bytes"].as_u64().unwrap())
+        .sum();
+    assert_eq!(bytes, 2, "{errors:?}");
+}
+
+#[test]
+fn session_observe_latin1_encoding_decodes_bytes_as_code_points() {
+    let mut session = Session::spawn(printf_config(
+        "caf\\351 \\374ber\\n",
+        TerminalEncoding::Latin1,
+    ))
+    .expect("Failed to spawn");
+    let observations = observe_to_exit(&mut session).expect("latin1 never fails");
+
+    assert!(transcript(&observations).contains("café über"));
+    let screen = &observations.last().unwrap().screen;
+    assert_eq!(screen.lines[0].trim_end(), "café über");
+}
+
+fn chatty_config(bytes: usize, on_full: OutputOverflow) -> SessionConfig {
+    SessionConfig {
+        args: vec![
+            "-c".to_string(),
+            format!("head -c {bytes} /dev/zero | tr '\\0' x"),
+        ],
+        output_buffer: OutputBufferConfig {
+            capacity_bytes: 1024,
+            on_full,
+        },
+        ..default_config("/bin/sh")
+    }
+}
+
+/// Observe until EOF, returning the transcript and the last observation's
+/// output counters.
+fn drain_session(session: &mut Session) -> (String, Option<ptybox::model::OutputFlowStats>) {
+    let mut transcript = String::new();
+    let deadline = std::time::Instant::now() + Duration::from_secs(10);
+    loop {
+        let observation = session
+            .observe(Duration::from_millis(20))
+            .expect("observe failed");
+        transcript.push_str(observation.transcript_delta.as_deref().unwrap_or(""));
+        let eof = observation
+            .events
+            .iter()
+            .any(|event| event.event_type == "pty_eof");
+        if eof || std::time::Instant::now() >= deadline {
+            return (transcript, observation.output_flow);
+        }
+    }
+}
+
+#[test]
+fn session_output_backpressure_keeps_all_output() {
+    let mut session = Session::spawn(chatty_config(64 * 1024, OutputOverflow::Backpressure))
+        .expect("Failed to spawn");
+    std::thread::sleep(Duration::from_millis(200));
+
+    let (transcript, flow) = drain_session(&mut session);
+    assert_eq!(transcript.matches('x').count(), 64 * 1024);
+    let flow = flow.expect("buffer should have filled");
+    assert!(flow.overflows >= 1, "{flow:?}");
+    assert_eq!(flow.dropped_bytes, 0);
+}
+
+#[test]
+fn session_output_drop_mode_counts_lost_bytes() {
+    let mut session =
+        Session::spawn(chatty_config(64 * 1024, OutputOverflow::Drop)).expect("Failed to spawn");
+    std::thread::sleep(Duration::from_millis(200));
+
+    let (transcript, flow) = drain_session(&mut session);
+    let flow = flow.expect("buffer should have overflowed");
+    assert!(flow.overflows >= 1, "{flow:?}");
+    assert!(flow.dropped_bytes > 0, "{flow:?}");
+    assert_eq!(
+        transcript.matches('x').count() as u64 + flow.dropped_bytes,
+        64 * 1024
+    );
+}
+
+#[test]
+fn session_output_flow_absent_without_overflow() {
+    let mut session = Session::spawn(default_config("/bin/echo")).expect("Failed to spawn");
+    let (_, flow) = drain_session(&mut session);
+    assert!(flow.is_none());
+}
+
+#[test]
+fn session_observe_flags_read_cut_by_timeout() {
+    let config = SessionConfig {
+        args: vec!["-c".to_string(), "while :; do echo tick; done".to_string()],
+        ..default_config("/bin/sh")
+    };
+    let mut session = Session::spawn(config).expect("Failed to spawn");
+    std::thread::sleep(Duration::from_millis(100));
+
+    let observation = session
+        .observe(Duration::from_millis(100))
+        .expect("observe failed");
+    assert!(observation.read_truncated);
+    assert!(observation.read_bytes > 0);
+}
+
+#[test]
+fn session_observe_not_truncated_after_output_settles() {
+    let config = SessionConfig {
+        args: vec!["-c".to_string(), "echo ready; sleep 5".to_string()],
+        ..default_config("/bin/sh")
+    };
+    let mut session = Session::spawn(config).expect("Failed to spawn");
+    std::thread::sleep(Duration::from_millis(200));
+
+    let observation = session
+        .observe(Duration::from_millis(200))
+        .expect("observe failed");
+    assert!(!observation.read_truncated);
+    assert!(observation.read_bytes > 0);
+    let value = serde_json::to_value(&observation).expect("serialize");
+    assert!(value.get("read_truncated").is_none());
+    assert!(value.get("read_dropped_bytes").is_none());
+}
+
+#[test]
+fn session_observe_reports_dropped_bytes_per_read() {
+    let mut session =
+        Session::spawn(chatty_config(64 * 1024, OutputOverflow::Drop)).expect("Failed to spawn");
+    std::thread::sleep(Duration::from_millis(200));
+
+    let mut dropped = 0;
+    let deadline = std::time::Instant::now() + Duration::from_secs(10);
+    let flow = loop {
+        let observation = session
+            .observe(Duration::from_millis(20))
+            .expect("observe failed");
+        dropped += observation.read_dropped_bytes;
+        let eof = observation
+            .events
+            .iter()
+            .any(|event| event.event_type == "pty_eof");
+        if eof || std::time::Instant::now() >= deadline {
+            break observation
+                .output_flow
+                .expect("buffer should have overflowed");
+        }
+    };
+    assert!(dropped > 0);
+    assert_eq!(dropped, flow.dropped_bytes);
+}
+
+#[test]
+fn session_spawn_rejects_zero_output_buffer() {
+    let config = SessionConfig {
+        output_buffer: OutputBufferConfig {
+            capacity_bytes: 0,
+            on_full: OutputOverflow::Backpressure,
+        },
+        ..default_config("/bin/echo")
+    };
+    let err = Session::spawn(config)
+        .err()
+        .expect("zero capacity should fail");
+    assert_eq!(err.code, ErrorCode::Protocol);
+}
+
 // =============================================================================
 // Terminate Tests
 // =============================================================================
@@ -468,6 +910,11 @@ fn session_terminate_graceful() {
         size: TerminalSize::default(),
         run_id: RunId::new(),
         env: Default::default(),
+        separate_stderr: false,
+        output_buffer: Default::default(),
+        resize: Default::default(),
+        remote: None,
+        encoding: Default::default(),
     };
     let mut session = Session::spawn(config).expect("Failed to spawn");
 
@@ -498,6 +945,11 @@ fn session_terminate_process_group_returns_status() {
         size: TerminalSize::default(),
         run_id: RunId::new(),
         env: Default::default(),
+        separate_stderr: false,
+        output_buffer: Default::default(),
+        resize: Default::default(),
+        remote: None,
+        encoding: Default::default(),
     };
     let mut session = Session::spawn(config).expect("Failed to spawn");
 
@@ -521,11 +973,16 @@ fn session_wait_for_exit_immediate() {
         size: TerminalSize::default(),
         run_id: RunId::new(),
         env: Default::default(),
+        separate_stderr: false,
+        output_buffer: Default::default(),
+        resize: Default::default(),
+        remote: None,
+        encoding: Default::default(),
     };
     let mut session = Session::spawn(config).expect("Failed to spawn");
 
     // Wait for process to complete
-    let status = session.wait_for_exit(Duration::from_millis(1000)).unwrap();
+    let status = session.wait_for_exit(Duration::from_secs(1)).unwrap();
     assert!(status.is_some(), "Echo should have exited");
     assert!(status.unwrap().success(), "Echo should exit successfully");
 }
@@ -539,6 +996,11 @@ fn session_wait_for_exit_timeout() {
         size: TerminalSize::default(),
         run_id: RunId::new(),
         env: Default::default(),
+        separate_stderr: false,
+        output_buffer: Default::default(),
+        resize: Default::default(),
+        remote: None,
+        encoding: Default::default(),
     };
     let mut session = Session::spawn(config).expect("Failed to spawn");
 
@@ -579,6 +1041,11 @@ fn session_terminate_process_group_already_dead() {
         size: TerminalSize::default(),
         run_id: RunId::new(),
         env: Default::default(),
+        separate_stderr: false,
+        output_buffer: Default::default(),
+        resize: Default::default(),
+        remote: None,
+        encoding: Default::default(),
     };
     let mut session = Session::spawn(config).expect("Failed to spawn");
 
@@ -614,6 +1081,11 @@ fn session_terminate_long_running_with_grace_period() {
         size: TerminalSize::default(),
         run_id: RunId::new(),
         env: Default::default(),
+        separate_stderr: false,
+        output_buffer: Default::default(),
+        resize: Default::default(),
+        remote: None,
+        encoding: Default::default(),
     };
     let mut session = Session::spawn(config).expect("Failed to spawn");
 
@@ -633,6 +1105,194 @@ fn session_terminate_long_running_with_grace_period() {
     );
 }
 
+/// Spawn a shell that ignores SIGTERM and wait until the trap is installed.
+fn spawn_sigterm_ignoring_shell() -> Session {
+    let mut config = default_config("/bin/sh");
+    config.args = vec![
+        "-c".to_string(),
+        "trap '' TERM; echo ready; while :; do sleep 1; done".to_string(),
+    ];
+    let mut session = Session::spawn(config).expect("Failed to spawn");
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    while std::time::Instant::now() < deadline {
+        let observation = session.observe(Duration::from_millis(100)).unwrap();
+        if observation
+            .screen
+            .lines
+            .iter()
+            .any(|line| line.contains("ready"))
+        {
+            return session;
+        }
+    }
+    panic!("shell never reported ready");
+}
+
+#[test]
+fn session_terminate_with_uses_configured_signal() {
+    let mut config = default_config("/bin/sleep");
+    config.args = vec!["60".to_string()];
+    let mut session = Session::spawn(config).expect("Failed to spawn");
+
+    let process = ProcessPolicy {
+        kill_signal: KillSignal::Int,
+        term_grace_ms: 2000,
+        pre_kill_hook: None,
+        allowed_signals: Vec::new(),
+        ..ProcessPolicy::default()
+    };
+    let termination = session.terminate_with(&process).unwrap();
+
+    assert!(termination.status.is_some(), "sleep should exit on SIGINT");
+    assert_eq!(termination.record.signal, KillSignal::Int);
+    assert_eq!(termination.record.grace_ms, 2000);
+    assert!(!termination.record.escalated);
+    let exit_status = termination.exit_status().unwrap();
+    assert!(exit_status.terminated_by_harness);
+    assert!(exit_status.termination.is_some());
+}
+
+#[test]
+fn session_terminate_with_escalates_after_grace_and_runs_hook() {
+    let session = spawn_sigterm_ignoring_shell();
+    let marker = std::env::temp_dir().join(format!("ptybox-pre-kill-{}", RunId::new()));
+    let process = ProcessPolicy {
+        kill_signal: KillSignal::Term,
+        term_grace_ms: 100,
+        pre_kill_hook: Some(PreKillHook {
+            command: "/bin/sh".to_string(),
+            args: vec![
+                "-c".to_string(),
+                format!("echo \"$PTYBOX_CHILD_PID\" > {}", marker.display()),
+            ],
+            timeout_ms: 2000,
+        }),
+        allowed_signals: Vec::new(),
+        ..ProcessPolicy::default()
+    };
+    let pid = session.process_id().unwrap();
+
+    let termination = session.close(&process).unwrap();
+
+    assert!(
+        termination.status.is_some(),
+        "SIGKILL should reap the shell"
+    );
+    assert!(termination.record.escalated);
+    let hook = termination.record.pre_kill_hook.unwrap();
+    assert_eq!(hook.exit_code, Some(0));
+    assert!(!hook.timed_out);
+    let recorded = std::fs::read_to_string(&marker).unwrap();
+    assert_eq!(recorded.trim(), pid.to_string());
+    let _ = std::fs::remove_file(marker);
+}
+
+#[test]
+fn session_terminate_with_kills_timed_out_hook() {
+    let mut session = spawn_sigterm_ignoring_shell();
+    let process = ProcessPolicy {
+        kill_signal: KillSignal::Term,
+        term_grace_ms: 50,
+        pre_kill_hook: Some(PreKillHook {
+            command: "/bin/sleep".to_string(),
+            args: vec!["10".to_string()],
+            timeout_ms: 100,
+        }),
+        allowed_signals: Vec::new(),
+        ..ProcessPolicy::default()
+    };
+
+    let start = std::time::Instant::now();
+    let termination = session.terminate_with(&process).unwrap();
+
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert!(termination.record.escalated);
+    let hook = termination.record.pre_kill_hook.unwrap();
+    assert!(hook.timed_out);
+    assert_eq!(hook.exit_code, None);
+}
+
+#[test]
+fn session_terminate_at_deadline_sends_the_warning_first() {
+    let mut session = spawn_sigterm_ignoring_shell();
+    let mut process = ProcessPolicy {
+        term_grace_ms: 50,
+        deadline_grace_ms: 100,
+        ..ProcessPolicy::default()
+    };
+    assert_eq!(process.deadline_grace_total_ms(), 50);
+
+    // SIGWINCH is ignored by default and the shell traps SIGTERM, so it
+    // outlives the warning and the usual sequence escalates.
+    process.deadline_signal = Some(ChildSignal::Winch);
+    assert_eq!(process.deadline_grace_total_ms(), 150);
+    let termination = session.terminate_at_deadline(&process).unwrap();
+
+    let warning = termination.record.deadline_warning.clone().unwrap();
+    assert_eq!(warning.signal, ChildSignal::Winch);
+    assert_eq!(warning.grace_ms, 100);
+    assert!(!warning.exited);
+    assert!(termination.record.escalated);
+    assert!(termination.status.is_some());
+}
+
+/// Observe until a screen line contains `text`, collecting event types.
+fn observe_until(session: &mut Session, text: &str) -> Vec<String> {
+    let mut events = Vec::new();
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    while std::time::Instant::now() < deadline {
+        let observation = session.observe(Duration::from_millis(100)).unwrap();
+        events.extend(observation.events.into_iter().map(|event| event.event_type));
+        if observation
+            .screen
+            .lines
+            .iter()
+            .any(|line| line.contains(text))
+        {
+            return events;
+        }
+    }
+    panic!("screen never showed {text:?}");
+}
+
+#[test]
+fn session_send_signal_reaches_child_and_records_event() {
+    let mut config = default_config("/bin/sh");
+    config.args = vec![
+        "-c".to_string(),
+        "trap 'echo got-usr1' USR1; echo ready; while :; do sleep 0.1; done".to_string(),
+    ];
+    let mut session = Session::spawn(config).expect("Failed to spawn");
+    observe_until(&mut session, "ready");
+
+    session.send(&Action::signal(ChildSignal::Usr1)).unwrap();
+
+    let events = observe_until(&mut session, "got-usr1");
+    assert_eq!(events.first().map(String::as_str), Some("signal_sent"));
+    let _ = session.terminate_process_group(Duration::from_millis(200));
+}
+
+#[test]
+fn session_signal_action_rejects_unknown_signals() {
+    let mut config = default_config("/bin/sleep");
+    config.args = vec!["5".to_string()];
+    let mut session = Session::spawn(config).expect("Failed to spawn");
+
+    for payload in [
+        serde_json::json!({ "signal": "SIGSEGV" }),
+        serde_json::json!({}),
+        serde_json::json!({ "signal": "SIGINT", "extra": true }),
+    ] {
+        let action = Action {
+            action_type: ActionType::Signal,
+            payload,
+        };
+        let err = session.send(&action).unwrap_err();
+        assert_eq!(err.code, ErrorCode::Protocol);
+    }
+    let _ = session.terminate_process_group(Duration::from_millis(200));
+}
+
 #[test]
 fn session_wait_for_exit_zero_timeout_returns_immediately() {
     let config = SessionConfig {
@@ -642,6 +1302,11 @@ fn session_wait_for_exit_zero_timeout_returns_immediately() {
         size: TerminalSize::default(),
         run_id: RunId::new(),
         env: Default::default(),
+        separate_stderr: false,
+        output_buffer: Default::default(),
+        resize: Default::default(),
+        remote: None,
+        encoding: Default::default(),
     };
     let mut session = Session::spawn(config).expect("Failed to spawn");
 
@@ -665,6 +1330,11 @@ fn session_multiple_terminate_calls_safe() {
         size: TerminalSize::default(),
         run_id: RunId::new(),
         env: Default::default(),
+        separate_stderr: false,
+        output_buffer: Default::default(),
+        resize: Default::default(),
+        remote: None,
+        encoding: Default::default(),
     };
     let mut session = Session::spawn(config).expect("Failed to spawn");
 
@@ -705,6 +1375,11 @@ fn session_observe_after_terminate() {
         size: TerminalSize::default(),
         run_id: RunId::new(),
         env: Default::default(),
+        separate_stderr: false,
+        output_buffer: Default::default(),
+        resize: Default::default(),
+        remote: None,
+        encoding: Default::default(),
     };
     let mut session = Session::spawn(config).expect("Failed to spawn");
 
@@ -743,6 +1418,11 @@ fn session_terminate_returns_exit_status_for_quick_exit() {
         size: TerminalSize::default(),
         run_id: RunId::new(),
         env: Default::default(),
+        separate_stderr: false,
+        output_buffer: Default::default(),
+        resize: Default::default(),
+        remote: None,
+        encoding: Default::default(),
     };
     let mut session = Session::spawn(config).expect("Failed to spawn");
 
@@ -926,3 +1606,52 @@ fn session_unknown_key_name_fails() {
         err.message
     );
 }
+
+#[test]
+fn probe_pty_opens_a_pty() {
+    let probe = ptybox::session::probe_pty();
+    assert!(probe.ok, "probe failed: {probe:?}");
+    assert_eq!(probe.attempts, 1);
+    assert!(probe.error.is_none());
+    assert!(probe.diagnostics.errno.is_none());
+}
+
+#[test]
+fn pty_diagnostics_classify_errno_and_give_hints() {
+    // ENOSPC: the devpts instance is full; retried and explained
+    let full = ptybox::session::pty_diagnostics(Some(28));
+    assert_eq!(full.errno_name.as_deref(), Some("ENOSPC"));
+    assert!(full.transient);
+    assert!(full.hints.iter().any(|hint| hint.contains("pty/max")));
+
+    // EACCES is not retried
+    let denied = ptybox::session::pty_diagnostics(Some(13));
+    assert_eq!(denied.errno_name.as_deref(), Some("EACCES"));
+    assert!(!denied.transient);
+    assert!(denied.hints.iter().any(|hint| hint.contains("/dev/ptmx")));
+}
+
+#[cfg(not(feature = "ssh"))]
+#[test]
+fn remote_session_needs_the_ssh_feature() {
+    let policy = ptybox::model::policy::RemotePolicy {
+        allowed_hosts: vec!["appliance.lan".to_string()],
+        allowed_keys: vec!["/etc/ptybox/appliance_ed25519".to_string()],
+        known_hosts: Some("/etc/ptybox/known_hosts".to_string()),
+        ..Default::default()
+    };
+    let target = ptybox::model::RemoteTarget {
+        host: "appliance.lan".to_string(),
+        port: 22,
+        user: "qa".to_string(),
+        key_path: "/etc/ptybox/appliance_ed25519".to_string(),
+    };
+    let config = SessionConfig {
+        remote: Some(ptybox::session::RemoteConfig::from_policy(target, &policy).unwrap()),
+        encoding: Default::default(),
+        ..default_config("/bin/cat")
+    };
+    let err = Session::spawn(config).err().unwrap();
+    assert_eq!(err.code, ErrorCode::Protocol);
+    assert!(err.message.contains("`ssh` feature"), "{}", err.message);
+}
@@ -0,0 +1,157 @@
+// Test module - relaxed lint rules
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::panic)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::expect_used)]
+#![allow(missing_docs)]
+
+//! Resize compatibility suite.
+//!
+//! Each file in `tests/fixtures/reflow` feeds output to a terminal, resizes
+//! it, and lists the screen xterm shows (`truncate`) and the screen a
+//! reflowing terminal such as VTE shows (`rewrap`). `check` steps compare the
+//! screen midway through.
+
+use ptybox::model::{ResizeConfig, ResizeReflow, TerminalSize};
+use ptybox::terminal::Terminal;
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+
+fn fixtures() -> Vec<(String, Value)> {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/reflow");
+    let mut fixtures: Vec<(String, Value)> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .map(|path| {
+            let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+            let value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+            (name, value)
+        })
+        .collect();
+    fixtures.sort_by(|a, b| a.0.cmp(&b.0));
+    assert!(!fixtures.is_empty(), "no fixtures in {}", dir.display());
+    fixtures
+}
+
+fn size(value: &Value) -> TerminalSize {
+    let rows = u16::try_from(value[0].as_u64().unwrap()).unwrap();
+    let cols = u16::try_from(value[1].as_u64().unwrap()).unwrap();
+    TerminalSize::new(rows, cols)
+}
+
+fn assert_screen(terminal: &Terminal, expected: &Value, context: &str) {
+    let snapshot = terminal.snapshot().unwrap();
+    let lines: Vec<&str> = expected["lines"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|line| line.as_str().unwrap())
+        .collect();
+    assert_eq!(snapshot.lines, lines, "{context}: lines");
+    let cursor = (
+        expected["cursor"][0].as_u64().unwrap(),
+        expected["cursor"][1].as_u64().unwrap(),
+    );
+    assert_eq!(
+        (
+            u64::from(snapshot.cursor.row),
+            u64::from(snapshot.cursor.col)
+        ),
+        cursor,
+        "{context}: cursor"
+    );
+}
+
+fn run_fixtures(reflow: ResizeReflow, mode: &str) {
+    for (name, fixture) in fixtures() {
+        let resize = ResizeConfig {
+            reflow,
+            scrollback_lines: fixture["scrollback_lines"]
+                .as_u64()
+                .map_or(0, |lines| u32::try_from(lines).unwrap()),
+        };
+        let mut terminal = Terminal::new(size(&fixture["size"])).with_resize(resize);
+        for (index, step) in fixture["steps"].as_array().unwrap().iter().enumerate() {
+            if let Some(output) = step["output"].as_str() {
+                terminal.process_bytes(output.as_bytes());
+            } else if let Some(to) = step.get("resize") {
+                terminal.resize(size(to));
+            } else if let Some(check) = step.get("check") {
+                assert_screen(
+                    &terminal,
+                    &check[mode],
+                    &format!("{name} step {index} ({mode})"),
+                );
+            } else {
+                panic!("{name}: unknown step {step}");
+            }
+        }
+        assert_screen(
+            &terminal,
+            &fixture["expect"][mode],
+            &format!("{name} ({mode})"),
+        );
+    }
+}
+
+#[test]
+fn truncate_matches_fixtures() {
+    run_fixtures(ResizeReflow::Truncate, "truncate");
+}
+
+#[test]
+fn rewrap_matches_fixtures() {
+    run_fixtures(ResizeReflow::Rewrap, "rewrap");
+}
+
+#[test]
+fn rewrap_keeps_cell_styles_and_modes() {
+    let resize = ResizeConfig {
+        reflow: ResizeReflow::Rewrap,
+        scrollback_lines: 0,
+    };
+    let mut terminal = Terminal::new(TerminalSize::new(3, 6)).with_resize(resize);
+    terminal.process_bytes(b"\x1b[?25lab\x1b[1;31mcdef\x1b[0mgh\x1b[7m");
+    terminal.resize(TerminalSize::new(3, 4));
+
+    let snapshot = terminal.snapshot_with_cells(true).unwrap();
+    assert_eq!(snapshot.lines, vec!["abcd", "efgh", ""]);
+    assert!(!snapshot.cursor.visible);
+    let cells = snapshot.cells.unwrap();
+    assert!(!cells[0][1].style.bold);
+    assert!(cells[0][2].style.bold);
+    assert!(cells[1][1].style.bold);
+    assert!(!cells[1][2].style.bold);
+
+    // Attributes set before the resize apply to later output.
+    terminal.process_bytes(b"x");
+    let cells = terminal.snapshot_with_cells(true).unwrap().cells.unwrap();
+    assert!(cells[2][0].style.inverse);
+}
+
+#[test]
+fn resize_keeps_bells_since_mark() {
+    let mut terminal = Terminal::new(TerminalSize::new(3, 10));
+    terminal.mark_output();
+    terminal.process_bytes(b"\x07ding");
+    terminal.resize(TerminalSize::new(2, 8));
+    terminal.process_bytes(b"\x07");
+    assert_eq!(terminal.output_since_mark().bells, 2);
+}
+
+#[test]
+fn alternate_screen_is_resized_in_place() {
+    let resize = ResizeConfig {
+        reflow: ResizeReflow::Rewrap,
+        scrollback_lines: 0,
+    };
+    let mut terminal = Terminal::new(TerminalSize::new(3, 6)).with_resize(resize);
+    terminal.process_bytes(b"\x1b[?1049hmenu123\x1b[3;1Hstatus");
+    terminal.resize(TerminalSize::new(2, 4));
+
+    let snapshot = terminal.snapshot().unwrap();
+    assert!(snapshot.alternate_screen);
+    assert_eq!(snapshot.lines, vec!["menu", "3"]);
+}
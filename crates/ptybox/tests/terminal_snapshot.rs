@@ -12,12 +12,13 @@
 #![allow(clippy::cast_possible_truncation)]
 #![allow(missing_docs)]
 
-use ptybox::model::{Color, TerminalSize};
+use ptybox::model::{Color, TerminalSize, WidgetKind};
+use ptybox::terminal::widgets::detect_widgets;
 use ptybox::terminal::Terminal;
 
 #[test]
 fn terminal_snapshot_includes_cursor_and_alt_screen() {
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 10 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 10));
     terminal.process_bytes(b"\x1b[?1049h");
     terminal.process_bytes(b"hi");
     terminal.process_bytes(b"\x1b[2;3H");
@@ -27,9 +28,18 @@ fn terminal_snapshot_includes_cursor_and_alt_screen() {
     assert!(snapshot.cursor.col > 0);
 }
 
+#[test]
+fn terminal_snapshot_allows_cursor_pending_wrap() {
+    let mut terminal = Terminal::new(TerminalSize::new(5, 10));
+    terminal.process_bytes(b"0123456789");
+    let snapshot = terminal.snapshot().expect("snapshot should succeed");
+    assert_eq!(snapshot.lines[0], "0123456789");
+    assert_eq!((snapshot.cursor.row, snapshot.cursor.col), (0, 10));
+}
+
 #[test]
 fn terminal_snapshot_preserves_unicode_and_wide_chars() {
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     terminal.process_bytes("🙂漢字".as_bytes());
     let snapshot = terminal.snapshot().expect("snapshot should succeed");
     let joined = snapshot.lines.join("\n");
@@ -43,7 +53,7 @@ fn terminal_snapshot_preserves_unicode_and_wide_chars() {
 
 #[test]
 fn snapshot_captures_cell_content() {
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     terminal.process_bytes(b"Hello");
     let snapshot = terminal
         .snapshot_with_cells(true)
@@ -58,9 +68,22 @@ fn snapshot_captures_cell_content() {
     assert_eq!(content, "Hello");
 }
 
+#[test]
+fn capture_attributes_adds_cells_to_plain_snapshots() {
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
+    terminal.process_bytes(b"\x1b[4;7mpick\x1b[0m");
+    assert!(terminal.snapshot().unwrap().cells.is_none());
+
+    terminal.set_capture_attributes(true);
+    let cells = terminal.snapshot().unwrap().cells.expect("cells");
+    assert!(cells[0][0].style.underline);
+    assert!(cells[0][0].style.inverse);
+    assert!(!cells[0][4].style.inverse);
+}
+
 #[test]
 fn snapshot_without_cells_has_none() {
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     terminal.process_bytes(b"test");
     let snapshot = terminal
         .snapshot_with_cells(false)
@@ -74,7 +97,7 @@ fn snapshot_without_cells_has_none() {
 
 #[test]
 fn snapshot_captures_foreground_color() {
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     // SGR 31 = red foreground (ANSI color index 1)
     terminal.process_bytes(b"\x1b[31mR");
     let snapshot = terminal
@@ -93,7 +116,7 @@ fn snapshot_captures_foreground_color() {
 
 #[test]
 fn snapshot_captures_background_color() {
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     // SGR 44 = blue background (ANSI color index 4)
     terminal.process_bytes(b"\x1b[44mB");
     let snapshot = terminal
@@ -112,7 +135,7 @@ fn snapshot_captures_background_color() {
 
 #[test]
 fn snapshot_captures_bold_attribute() {
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     // SGR 1 = bold
     terminal.process_bytes(b"\x1b[1mB");
     let snapshot = terminal
@@ -126,7 +149,7 @@ fn snapshot_captures_bold_attribute() {
 
 #[test]
 fn snapshot_captures_italic_attribute() {
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     // SGR 3 = italic
     terminal.process_bytes(b"\x1b[3mI");
     let snapshot = terminal
@@ -140,7 +163,7 @@ fn snapshot_captures_italic_attribute() {
 
 #[test]
 fn snapshot_captures_underline_attribute() {
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     // SGR 4 = underline
     terminal.process_bytes(b"\x1b[4mU");
     let snapshot = terminal
@@ -154,7 +177,7 @@ fn snapshot_captures_underline_attribute() {
 
 #[test]
 fn snapshot_captures_inverse_attribute() {
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     // SGR 7 = inverse
     terminal.process_bytes(b"\x1b[7mV");
     let snapshot = terminal
@@ -168,7 +191,7 @@ fn snapshot_captures_inverse_attribute() {
 
 #[test]
 fn snapshot_handles_ansi_16_colors() {
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     // SGR 30-37 are colors 0-7, SGR 90-97 are colors 8-15
     // 32 = green (2), 91 = bright red (9)
     terminal.process_bytes(b"\x1b[32mG\x1b[91mR");
@@ -192,7 +215,7 @@ fn snapshot_handles_ansi_16_colors() {
 
 #[test]
 fn snapshot_handles_ansi_256_colors() {
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     // SGR 38;5;N = 256-color foreground
     // SGR 48;5;N = 256-color background
     terminal.process_bytes(b"\x1b[38;5;123mF\x1b[48;5;200mB");
@@ -216,7 +239,7 @@ fn snapshot_handles_ansi_256_colors() {
 
 #[test]
 fn snapshot_handles_rgb_colors() {
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     // SGR 38;2;R;G;B = RGB foreground
     // SGR 48;2;R;G;B = RGB background
     terminal.process_bytes(b"\x1b[38;2;255;128;64mF\x1b[48;2;10;20;30mB");
@@ -247,7 +270,7 @@ fn snapshot_handles_rgb_colors() {
 
 #[test]
 fn snapshot_handles_wide_characters() {
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     // Wide characters like emoji and CJK take 2 cells
     terminal.process_bytes("🙂AB".as_bytes());
     let snapshot = terminal
@@ -271,7 +294,7 @@ fn snapshot_handles_wide_characters() {
 
 #[test]
 fn snapshot_handles_cjk_wide_characters() {
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     terminal.process_bytes("漢字".as_bytes());
     let snapshot = terminal
         .snapshot_with_cells(true)
@@ -300,7 +323,7 @@ fn snapshot_handles_cjk_wide_characters() {
 
 #[test]
 fn snapshot_default_style_is_correct() {
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     terminal.process_bytes(b"X");
     let snapshot = terminal
         .snapshot_with_cells(true)
@@ -325,7 +348,7 @@ fn snapshot_default_style_is_correct() {
 
 #[test]
 fn snapshot_combined_attributes() {
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     // Bold + underline + red fg + blue bg
     terminal.process_bytes(b"\x1b[1;4;31;44mX");
     let snapshot = terminal
@@ -354,7 +377,7 @@ fn snapshot_combined_attributes() {
 #[test]
 fn terminal_handles_incomplete_escape_sequence() {
     // Incomplete escape sequence should be handled gracefully
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     terminal.process_bytes(b"Hello\x1b["); // Incomplete CSI sequence
     terminal.process_bytes(b"World"); // Continue with more text
 
@@ -366,7 +389,7 @@ fn terminal_handles_incomplete_escape_sequence() {
 #[test]
 fn terminal_handles_unknown_escape_sequence() {
     // Unknown CSI sequence should be handled gracefully
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     terminal.process_bytes(b"\x1b[999zHello"); // Unknown CSI sequence followed by text
 
     let snapshot = terminal.snapshot().expect("snapshot should succeed");
@@ -381,7 +404,7 @@ fn terminal_handles_unknown_escape_sequence() {
 #[test]
 fn terminal_handles_null_bytes() {
     // Null bytes in output should be handled gracefully
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     terminal.process_bytes(b"Hello\x00World");
 
     let snapshot = terminal.snapshot().expect("snapshot should succeed");
@@ -392,7 +415,7 @@ fn terminal_handles_null_bytes() {
 #[test]
 fn terminal_handles_very_long_escape_sequence() {
     // Very long escape sequence parameters should be handled
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     // Create a CSI sequence with many parameters
     let mut seq = b"\x1b[".to_vec();
     for i in 0..100 {
@@ -413,7 +436,7 @@ fn terminal_handles_very_long_escape_sequence() {
 #[test]
 fn terminal_handles_bell_character() {
     // Bell character (BEL, 0x07) should be handled gracefully
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     terminal.process_bytes(b"Hello\x07World");
 
     let snapshot = terminal.snapshot().expect("snapshot should succeed");
@@ -425,7 +448,7 @@ fn terminal_handles_bell_character() {
 #[test]
 fn terminal_handles_carriage_return_only() {
     // CR without LF should return to start of line
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     terminal.process_bytes(b"AAAAA\rBB");
 
     let snapshot = terminal.snapshot().expect("snapshot should succeed");
@@ -440,7 +463,7 @@ fn terminal_handles_carriage_return_only() {
 #[test]
 fn terminal_handles_multiple_incomplete_sequences() {
     // Multiple incomplete escape sequences in succession
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     terminal.process_bytes(b"\x1b[\x1b[\x1b[mHello");
 
     let snapshot = terminal.snapshot().expect("snapshot should succeed");
@@ -451,7 +474,7 @@ fn terminal_handles_multiple_incomplete_sequences() {
 #[test]
 fn terminal_handles_interleaved_valid_invalid_sequences() {
     // Mix of valid and invalid sequences
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     terminal.process_bytes(b"\x1b[31mRed\x1b[999zInvalid\x1b[0mNormal");
 
     let snapshot = terminal.snapshot().expect("snapshot should succeed");
@@ -466,7 +489,7 @@ fn terminal_handles_interleaved_valid_invalid_sequences() {
 #[test]
 fn terminal_handles_escape_at_end_of_input() {
     // Escape character as the very last byte
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     terminal.process_bytes(b"Hello\x1b");
 
     let snapshot = terminal.snapshot().expect("snapshot should succeed");
@@ -480,7 +503,7 @@ fn terminal_handles_escape_at_end_of_input() {
 #[test]
 fn terminal_handles_csi_at_end_of_input() {
     // CSI sequence start as the very last bytes
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     terminal.process_bytes(b"Hello\x1b[");
 
     let snapshot = terminal.snapshot().expect("snapshot should succeed");
@@ -494,7 +517,7 @@ fn terminal_handles_csi_at_end_of_input() {
 #[test]
 fn terminal_handles_backspace_character() {
     // Backspace should move cursor back
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     terminal.process_bytes(b"ABC\x08X");
 
     let snapshot = terminal.snapshot().expect("snapshot should succeed");
@@ -510,7 +533,7 @@ fn terminal_handles_backspace_character() {
 #[test]
 fn terminal_handles_tab_character() {
     // Tab should advance cursor
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     terminal.process_bytes(b"A\tB");
 
     let snapshot = terminal.snapshot().expect("snapshot should succeed");
@@ -523,7 +546,7 @@ fn terminal_handles_tab_character() {
 #[test]
 fn terminal_handles_form_feed() {
     // Form feed (0x0C) should be handled gracefully
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     terminal.process_bytes(b"Hello\x0cWorld");
 
     let snapshot = terminal.snapshot().expect("snapshot should succeed");
@@ -534,10 +557,127 @@ fn terminal_handles_form_feed() {
 #[test]
 fn terminal_handles_vertical_tab() {
     // Vertical tab (0x0B) should be handled gracefully
-    let mut terminal = Terminal::new(TerminalSize { rows: 5, cols: 20 });
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
     terminal.process_bytes(b"Hello\x0bWorld");
 
     let snapshot = terminal.snapshot().expect("snapshot should succeed");
     // Should not crash
     assert!(!snapshot.lines.is_empty());
 }
+
+#[test]
+fn terminal_answers_xtwinops_size_queries() {
+    let mut terminal = Terminal::new(TerminalSize::new(24, 80).with_pixels(800, 480));
+    terminal.process_bytes(b"\x1b[18t\x1b[14t\x1b[16t");
+    assert_eq!(
+        terminal.take_replies(),
+        b"\x1b[8;24;80t\x1b[4;480;800t\x1b[6;20;10t".to_vec()
+    );
+    assert!(terminal.take_replies().is_empty());
+}
+
+#[test]
+fn terminal_skips_pixel_queries_without_pixel_size() {
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
+    terminal.process_bytes(b"\x1b[14t\x1b[16t\x1b[22;0t");
+    assert!(terminal.take_replies().is_empty());
+    terminal.process_bytes(b"\x1b[1");
+    terminal.process_bytes(b"9t");
+    assert_eq!(terminal.take_replies(), b"\x1b[9;5;20t".to_vec());
+}
+
+#[test]
+fn terminal_resize_updates_query_replies() {
+    let mut terminal = Terminal::new(TerminalSize::new(5, 20));
+    terminal.resize(TerminalSize::new(10, 40).with_pixels(400, 200));
+    terminal.process_bytes(b"\x1b[14t");
+    assert_eq!(terminal.take_replies(), b"\x1b[4;200;400t".to_vec());
+}
+
+// ============================================================================
+// Widget detection
+// ============================================================================
+
+#[test]
+fn terminal_detects_titled_dialog_and_selected_button() {
+    let mut terminal = Terminal::new(TerminalSize::new(8, 30));
+    terminal.process_bytes("┌──┤ Delete file ├──┐\r\n".as_bytes());
+    terminal.process_bytes("│ Delete notes.txt? │\r\n".as_bytes());
+    terminal.process_bytes("│ \x1b[7m< Yes >\x1b[0m  < No >   │\r\n".as_bytes());
+    terminal.process_bytes("└───────────────────┘\r\n".as_bytes());
+    terminal.process_bytes(b"Overwrite? [y/N] ");
+
+    let widgets = terminal.widgets();
+    let kinds: Vec<WidgetKind> = widgets.iter().map(|widget| widget.kind).collect();
+    assert_eq!(
+        kinds,
+        [WidgetKind::Box, WidgetKind::Selection, WidgetKind::Prompt]
+    );
+
+    let dialog = &widgets[0];
+    assert_eq!(
+        (dialog.row, dialog.col, dialog.rows, dialog.cols),
+        (0, 0, 4, 21)
+    );
+    assert_eq!(dialog.title.as_deref(), Some("Delete file"));
+    assert_eq!(dialog.text, "Delete notes.txt?\n< Yes >  < No >");
+
+    let selection = &widgets[1];
+    assert_eq!((selection.row, selection.col, selection.cols), (2, 2, 7));
+    assert_eq!(selection.text, "< Yes >");
+
+    assert_eq!(widgets[2].row, 4);
+    assert_eq!(widgets[2].text, "Overwrite? [y/N]");
+}
+
+#[test]
+fn widgets_detect_nested_ascii_boxes_from_snapshot_lines() {
+    let mut terminal = Terminal::new(TerminalSize::new(8, 30));
+    terminal.process_bytes(b"+-[ Menu ]--------+\r\n");
+    terminal.process_bytes(b"| +----+          |\r\n");
+    terminal.process_bytes(b"| | ok |          |\r\n");
+    terminal.process_bytes(b"| +----+          |\r\n");
+    terminal.process_bytes(b"+-----------------+\r\n");
+    terminal.process_bytes(b"a+b-c and +- are not boxes");
+    let snapshot = terminal.snapshot().expect("snapshot should succeed");
+
+    let widgets = detect_widgets(&snapshot);
+    let boxes: Vec<_> = widgets
+        .iter()
+        .map(|widget| (widget.row, widget.col, widget.title.as_deref()))
+        .collect();
+    assert_eq!(boxes, [(0, 0, Some("Menu")), (1, 2, None)]);
+    assert_eq!(widgets[1].text, "ok");
+}
+
+#[test]
+fn output_since_mark_counts_bells_and_keeps_raw_bytes() {
+    let mut terminal = Terminal::new(TerminalSize::new(24, 80));
+    terminal.process_bytes(b"early\x07");
+    assert_eq!(terminal.output_since_mark().bells, 1);
+
+    terminal.mark_output();
+    assert_eq!(terminal.output_since_mark().bells, 0);
+    assert!(terminal.output_since_mark().bytes.is_empty());
+
+    // A BEL that terminates an OSC title is not a bell.
+    terminal.process_bytes(b"\x1b]0;title\x07\x1b[?2026l");
+    let output = terminal.output_since_mark();
+    assert_eq!(output.bells, 0);
+    assert_eq!(output.bytes, b"\x1b]0;title\x07\x1b[?2026l");
+
+    terminal.process_bytes(b"\x07");
+    assert_eq!(terminal.output_since_mark().bells, 1);
+}
+
+#[test]
+fn output_since_mark_keeps_only_the_latest_window() {
+    use ptybox::terminal::MAX_RAW_OUTPUT_WINDOW;
+
+    let mut terminal = Terminal::new(TerminalSize::new(24, 80));
+    terminal.process_bytes(&vec![b'x'; MAX_RAW_OUTPUT_WINDOW]);
+    terminal.process_bytes(b"\x1b[?2026l");
+    let bytes = terminal.output_since_mark().bytes;
+    assert_eq!(bytes.len(), MAX_RAW_OUTPUT_WINDOW);
+    assert!(bytes.ends_with(b"\x1b[?2026l"));
+}
@@ -16,7 +16,9 @@
 //!
 //! Tests for the wait condition evaluation in the runner module.
 
-use ptybox::model::{Action, ActionType, RunId, TerminalSize};
+use ptybox::model::policy::Budgets;
+use ptybox::model::{Action, ActionType, Condition, RunId, TerminalSize};
+use ptybox::runner::ErrorCode;
 use ptybox::session::{Session, SessionConfig};
 use std::time::{Duration, Instant};
 
@@ -28,6 +30,11 @@ fn default_config(command: &str, args: Vec<String>) -> SessionConfig {
         size: TerminalSize::default(),
         run_id: RunId::new(),
         env: Default::default(),
+        separate_stderr: false,
+        output_buffer: Default::default(),
+        resize: Default::default(),
+        remote: None,
+        encoding: Default::default(),
     }
 }
 
@@ -166,3 +173,204 @@ fn wait_condition_resize_during_wait() {
     let observation = session.observe(Duration::from_millis(100)).unwrap();
     assert!(!observation.screen.lines.is_empty());
 }
+
+// =============================================================================
+// Session::wait_until
+// =============================================================================
+
+#[test]
+fn wait_until_returns_observation_matching_screen_contains() {
+    let config = default_config("/bin/cat", vec![]);
+    let mut session = Session::spawn(config).expect("Failed to spawn");
+    session
+        .send(&Action {
+            action_type: ActionType::Text,
+            payload: serde_json::json!({"text": "embedded"}),
+        })
+        .expect("Failed to send text");
+
+    let observation = session
+        .wait_until(
+            &Condition::screen_contains("embedded"),
+            Duration::from_secs(5),
+            &Budgets::default(),
+        )
+        .unwrap();
+    assert!(observation.screen.lines.join("\n").contains("embedded"));
+}
+
+#[test]
+fn wait_until_process_exited_succeeds_after_exit() {
+    let config = default_config("/bin/echo", vec!["bye".to_string()]);
+    let mut session = Session::spawn(config).expect("Failed to spawn");
+
+    session
+        .wait_until(
+            &Condition::process_exited(),
+            Duration::from_secs(5),
+            &Budgets::default(),
+        )
+        .unwrap();
+}
+
+#[test]
+fn wait_until_caps_timeout_at_max_wait_budget() {
+    let config = default_config("/bin/cat", vec![]);
+    let mut session = Session::spawn(config).expect("Failed to spawn");
+    let budgets = Budgets {
+        max_wait_ms: 100,
+        ..Budgets::default()
+    };
+
+    let start = Instant::now();
+    let err = session
+        .wait_until(
+            &Condition::screen_matches("never-appears"),
+            Duration::from_secs(30),
+            &budgets,
+        )
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::Timeout);
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn wait_until_rejects_unknown_condition_before_polling() {
+    let config = default_config("/bin/cat", vec![]);
+    let mut session = Session::spawn(config).expect("Failed to spawn");
+    let condition = Condition {
+        condition_type: "screen_glows".to_string(),
+        payload: serde_json::json!({}),
+    };
+
+    let err = session
+        .wait_until(&condition, Duration::from_secs(1), &Budgets::default())
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::Protocol);
+}
+
+fn idle_wait_stats(budgets: &Budgets) -> ptybox::model::WaitStats {
+    let config = default_config("/bin/cat", vec![]);
+    let mut session = Session::spawn(config).expect("Failed to spawn");
+    let err = session
+        .wait_until(
+            &Condition::screen_contains("never-appears"),
+            Duration::from_millis(300),
+            budgets,
+        )
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::Timeout);
+    assert_eq!(
+        err.context.as_ref().unwrap()["polls"].as_u64(),
+        Some(session.wait_stats().polls)
+    );
+    session.wait_stats()
+}
+
+#[test]
+fn wait_until_backs_off_and_records_wait_stats() {
+    let default_stats = idle_wait_stats(&Budgets::default());
+    assert!(default_stats.wait_ms >= 300);
+    // 2, 4, 8, 16, 32, then 50ms polls: far fewer than fixed 2ms polling.
+    assert!(
+        default_stats.polls <= 20,
+        "idle wait polled {} times",
+        default_stats.polls
+    );
+
+    let capped = Budgets {
+        max_poll_interval_ms: Some(2),
+        ..Budgets::default()
+    };
+    let capped_stats = idle_wait_stats(&capped);
+    assert!(capped_stats.polls > default_stats.polls);
+}
+
+#[test]
+fn wait_until_accepts_assertion_predicates() {
+    let config = default_config("/bin/cat", vec![]);
+    let mut session = Session::spawn(config).expect("Failed to spawn");
+    session
+        .send(&Action {
+            action_type: ActionType::Text,
+            payload: serde_json::json!({"text": "alpha"}),
+        })
+        .expect("Failed to send text");
+
+    for condition in [
+        Condition {
+            condition_type: "line_contains".to_string(),
+            payload: serde_json::json!({"line": 0, "text": "alpha"}),
+        },
+        Condition {
+            condition_type: "regex_match".to_string(),
+            payload: serde_json::json!({"pattern": "al+pha"}),
+        },
+        Condition {
+            condition_type: "not_contains".to_string(),
+            payload: serde_json::json!({"text": "omega"}),
+        },
+    ] {
+        session
+            .wait_until(&condition, Duration::from_secs(5), &Budgets::default())
+            .unwrap_or_else(|err| panic!("{}: {err}", condition.condition_type));
+    }
+}
+
+#[test]
+fn wait_until_exit_code_checks_the_exit_status() {
+    let exit_code = |code: i32| Condition {
+        condition_type: "exit_code".to_string(),
+        payload: serde_json::json!({ "code": code }),
+    };
+    let script = vec!["-c".to_string(), "exit 3".to_string()];
+
+    let mut session = Session::spawn(default_config("/bin/sh", script.clone())).unwrap();
+    session
+        .wait_until(&exit_code(3), Duration::from_secs(5), &Budgets::default())
+        .unwrap();
+
+    let mut session = Session::spawn(default_config("/bin/sh", script)).unwrap();
+    let err = session
+        .wait_until(&exit_code(0), Duration::from_secs(5), &Budgets::default())
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::ProcessExit);
+}
+
+#[test]
+fn wait_until_bell_and_control_sequence_match_raw_output() {
+    let script = vec![
+        "-c".to_string(),
+        r"read line; printf 'busy\a'; read line; printf '\033[?2026hdone\033[?2026l'; sleep 5"
+            .to_string(),
+    ];
+    let mut session = Session::spawn(default_config("/bin/sh", script)).unwrap();
+    let enter = Action {
+        action_type: ActionType::Key,
+        payload: serde_json::json!({"key": "Enter"}),
+    };
+    let bell = Condition {
+        condition_type: "bell".to_string(),
+        payload: serde_json::json!({}),
+    };
+    let sync_end = Condition {
+        condition_type: "control_sequence".to_string(),
+        payload: serde_json::json!({"pattern": r"\x1b\[\?2026l"}),
+    };
+
+    session.send(&enter).unwrap();
+    let observation = session
+        .wait_until(&bell, Duration::from_secs(5), &Budgets::default())
+        .unwrap();
+    assert!(observation.screen.lines.join("\n").contains("busy"));
+
+    // New input starts a new window: the earlier bell no longer counts.
+    session.send(&enter).unwrap();
+    session
+        .wait_until(&sync_end, Duration::from_secs(5), &Budgets::default())
+        .unwrap();
+    let err = session
+        .wait_until(&bell, Duration::from_millis(200), &Budgets::default())
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::Timeout);
+}